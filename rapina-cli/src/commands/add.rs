@@ -1,19 +1,13 @@
 use colored::Colorize;
 
-use super::codegen::{self, FieldInfo};
-
-fn parse_field(input: &str) -> Result<FieldInfo, String> {
-    let parts: Vec<&str> = input.splitn(2, ':').collect();
-    if parts.len() != 2 {
-        return Err(format!(
-            "Invalid field format '{}'. Expected 'name:type' (e.g., 'title:string')",
-            input
-        ));
-    }
-
-    let name = parts[0].trim();
-    let type_str = parts[1].trim();
+use super::codegen::{
+    self, ExtractorSource, FieldInfo, IndexInfo, ModuleOptions, ParentInfo, Prelude, UpdateStyle,
+};
+use crate::output;
 
+/// Checks a field name given on the command line: lowercase snake_case and
+/// usable as a Rust identifier, raw if need be.
+pub(crate) fn validate_field_name(name: &str) -> Result<(), String> {
     if name.is_empty() {
         return Err("Field name cannot be empty".to_string());
     }
@@ -27,24 +21,51 @@ fn parse_field(input: &str) -> Result<FieldInfo, String> {
         }
     }
 
+    if !codegen::can_be_raw_ident(name) {
+        return Err(format!(
+            "Field name '{}' is a reserved Rust keyword and cannot be used, even as r#{}",
+            name, name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses a `name:type` field argument. The type takes modifiers: a trailing
+/// `?` makes the column nullable and `!unique` or `:unique` adds a unique
+/// constraint, e.g. `bio:text?` or `email:string!unique`. A default follows
+/// the modifiers: `status:string?=draft`.
+fn parse_field(input: &str) -> Result<FieldInfo, String> {
+    let parts: Vec<&str> = input.splitn(2, ':').collect();
+    if parts.len() != 2 {
+        return Err(format!(
+            "Invalid field format '{}'. Expected 'name:type' (e.g., 'title:string')",
+            input
+        ));
+    }
+
+    let name = parts[0].trim();
+    let (type_spec, default) = match parts[1].split_once('=') {
+        Some((type_spec, default)) => (type_spec.trim(), Some(default)),
+        None => (parts[1].trim(), None),
+    };
+    validate_field_name(name)?;
+    let (type_str, nullable, unique) = parse_modifiers(name, type_spec)?;
+
     let (rust_type, schema_type, column_method) = match type_str.to_lowercase().as_str() {
-        "string" => ("String", "String", ".string().not_null()"),
-        "text" => ("String", "Text", ".text().not_null()"),
-        "i32" | "integer" => ("i32", "i32", ".integer().not_null()"),
-        "i64" | "bigint" => ("i64", "i64", ".big_integer().not_null()"),
-        "f32" | "float" => ("f32", "f32", ".float().not_null()"),
-        "f64" | "double" => ("f64", "f64", ".double().not_null()"),
-        "bool" | "boolean" => ("bool", "bool", ".boolean().not_null()"),
-        "uuid" => ("Uuid", "Uuid", ".uuid().not_null()"),
-        "datetime" | "timestamptz" => (
-            "DateTimeUtc",
-            "DateTime",
-            ".timestamp_with_time_zone().not_null()",
-        ),
-        "naivedatetime" | "timestamp" => ("DateTime", "NaiveDateTime", ".date_time().not_null()"),
-        "date" => ("Date", "Date", ".date().not_null()"),
-        "decimal" => ("Decimal", "Decimal", ".decimal().not_null()"),
-        "json" => ("Json", "Json", ".json().not_null()"),
+        "string" => ("String", "String", ".string()"),
+        "text" => ("String", "Text", ".text()"),
+        "i32" | "integer" => ("i32", "i32", ".integer()"),
+        "i64" | "bigint" => ("i64", "i64", ".big_integer()"),
+        "f32" | "float" => ("f32", "f32", ".float()"),
+        "f64" | "double" => ("f64", "f64", ".double()"),
+        "bool" | "boolean" => ("bool", "bool", ".boolean()"),
+        "uuid" => ("Uuid", "Uuid", ".uuid()"),
+        "datetime" | "timestamptz" => ("DateTimeUtc", "DateTime", ".timestamp_with_time_zone()"),
+        "naivedatetime" | "timestamp" => ("DateTime", "NaiveDateTime", ".date_time()"),
+        "date" => ("Date", "Date", ".date()"),
+        "decimal" => ("Decimal", "Decimal", ".decimal()"),
+        "json" => ("Json", "Json", ".json()"),
         _ => {
             return Err(format!(
                 "Unknown field type '{}'. Supported types: string, text, i32/integer, i64/bigint, \
@@ -55,106 +76,333 @@ fn parse_field(input: &str) -> Result<FieldInfo, String> {
         }
     };
 
+    if unique && schema_type == "Json" {
+        return Err(format!(
+            "Field '{}' cannot be unique: json columns have no equality to compare on",
+            name
+        ));
+    }
+
+    let default = default
+        .map(|value| parse_default(name, schema_type, value))
+        .transpose()?;
+
+    let mut column_method = column_method.to_string();
+    column_method.push_str(if nullable { ".null()" } else { ".not_null()" });
+    if unique {
+        column_method.push_str(".unique_key()");
+    }
+
     Ok(FieldInfo {
         name: name.to_string(),
         rust_type: rust_type.to_string(),
         schema_type: schema_type.to_string(),
-        column_method: column_method.to_string(),
+        column_method,
+        comment: None,
+        column: None,
+        default,
+        nullable,
+        unique,
     })
 }
 
+/// Splits `text?` or `string!unique` into the type and its nullable and
+/// unique flags.
+fn parse_modifiers<'a>(name: &str, type_spec: &'a str) -> Result<(&'a str, bool, bool), String> {
+    let mut nullable = false;
+    let mut unique = false;
+    let mut type_str = "";
+
+    if type_spec.matches('?').count() > 1 {
+        return Err(format!("Field '{}' is marked nullable twice", name));
+    }
+
+    for (i, token) in type_spec.split(['!', ':']).enumerate() {
+        let token = match token.strip_suffix('?') {
+            Some(token) => {
+                nullable = true;
+                token
+            }
+            None => token,
+        };
+        if i == 0 {
+            type_str = token;
+        } else if token == "unique" {
+            if unique {
+                return Err(format!("Field '{}' is marked unique twice", name));
+            }
+            unique = true;
+        } else {
+            return Err(format!(
+                "Unknown modifier '{}' on field '{}'. Supported modifiers: ? (nullable), \
+                 !unique or :unique",
+                token, name
+            ));
+        }
+    }
+
+    if nullable && name == "id" {
+        return Err(
+            "Field 'id' cannot be nullable: it names the primary key, which is always set"
+                .to_string(),
+        );
+    }
+
+    Ok((type_str, nullable, unique))
+}
+
+/// Checks the default in `name:type=default` against the field type and
+/// returns it as a `schema!` literal.
+fn parse_default(name: &str, schema_type: &str, value: &str) -> Result<String, String> {
+    let invalid = |expected: &str| {
+        format!(
+            "Invalid default '{}' for {} field '{}': expected {}",
+            value, schema_type, name, expected
+        )
+    };
+
+    match schema_type {
+        "String" | "Text" => Ok(format!("{:?}", value)),
+        "i32" => value
+            .parse::<i32>()
+            .map(|n| n.to_string())
+            .map_err(|_| invalid("an integer")),
+        "i64" => value
+            .parse::<i64>()
+            .map(|n| n.to_string())
+            .map_err(|_| invalid("an integer")),
+        "f32" | "f64" => match value.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(format!("{:?}", n)),
+            _ => Err(invalid("a number")),
+        },
+        "bool" => match value {
+            "true" | "false" => Ok(value.to_string()),
+            _ => Err(invalid("true or false")),
+        },
+        "DateTime" | "NaiveDateTime" => match value {
+            "now()" => Ok(value.to_string()),
+            _ => Err(invalid("now()")),
+        },
+        _ => Err(format!(
+            "Defaults are not supported for {} fields ('{}')",
+            schema_type, name
+        )),
+    }
+}
+
 fn validate_resource_name(name: &str) -> Result<(), String> {
+    validate_identifier("Resource", name)
+}
+
+fn validate_parent(
+    name: &str,
+    parent: &str,
+    fields: &[FieldInfo],
+    shallow: bool,
+) -> Result<ParentInfo, String> {
+    validate_identifier("Parent", parent)?;
+    if parent == name {
+        return Err(format!("Resource '{}' cannot be nested under itself", name));
+    }
+
+    let parent = ParentInfo::new(parent, shallow);
+    let fk = parent.fk_column();
+    if let Some(field) = fields
+        .iter()
+        .find(|f| f.name == parent.singular || f.name == fk)
+    {
+        return Err(format!(
+            "Field '{}' clashes with the '{}' reference to the parent; remove it, the foreign key is added for you",
+            field.name, fk
+        ));
+    }
+
+    Ok(parent)
+}
+
+fn validate_extractor_name(name: &str) -> Result<(), String> {
+    validate_identifier("Extractor", name)
+}
+
+fn validate_job_name(name: &str) -> Result<(), String> {
+    validate_identifier("Job", name)
+}
+
+fn validate_identifier(kind: &str, name: &str) -> Result<(), String> {
     if name.is_empty() {
-        return Err("Resource name cannot be empty".to_string());
+        return Err(format!("{} name cannot be empty", kind));
     }
 
     for c in name.chars() {
         if !c.is_ascii_lowercase() && !c.is_ascii_digit() && c != '_' {
             return Err(format!(
-                "Resource name must be lowercase alphanumeric with underscores, got '{}'",
-                c
+                "{} name must be lowercase alphanumeric with underscores, got '{}'",
+                kind, c
             ));
         }
     }
 
     if name.starts_with('_') || name.ends_with('_') {
-        return Err("Resource name cannot start or end with underscore".to_string());
+        return Err(format!("{} name cannot start or end with underscore", kind));
     }
 
-    let reserved = [
-        "self", "super", "crate", "mod", "type", "fn", "struct", "enum", "impl",
-    ];
-    if reserved.contains(&name) {
+    if codegen::is_rust_keyword(name) {
         return Err(format!("'{}' is a reserved Rust keyword", name));
     }
 
     Ok(())
 }
 
-fn print_next_steps(singular: &str, plural: &str, pascal: &str) {
-    println!();
-    println!("  {}:", "Next steps".bright_yellow());
-    println!();
-    println!(
+fn print_next_steps(
+    singular: &str,
+    plural: &str,
+    pascal: &str,
+    options: &ModuleOptions,
+    registered: bool,
+    split_entity: bool,
+) {
+    let with_export = options.with_export;
+    let (collection_path, item_path) =
+        codegen::resource_paths(plural, options.route_prefix, options.parent);
+    output::blank();
+    output::info(format!("  {}:", "Next steps".bright_yellow()));
+    output::blank();
+    output::info(format!(
         "  1. Add the module declaration to {}:",
         "src/main.rs".cyan()
-    );
-    println!();
-    println!("     mod {};", plural);
-    println!("     mod entity;");
-    println!("     mod migrations;");
-    println!();
-    println!("  2. Register the routes in your {}:", "Router".cyan());
-    println!();
-    println!(
-        "     use {plural}::handlers::{{list_{plural}, get_{singular}, create_{singular}, update_{singular}, delete_{singular}}};",
-        plural = plural,
-        singular = singular,
-    );
-    println!();
-    println!("     let router = Router::new()");
-    println!(
-        "         .get(\"/{plural}\", list_{plural})",
-        plural = plural
-    );
-    println!(
-        "         .get(\"/{plural}/:id\", get_{singular})",
-        plural = plural,
-        singular = singular,
-    );
-    println!(
-        "         .post(\"/{plural}\", create_{singular})",
+    ));
+    output::blank();
+    output::info(format!("     mod {};", plural));
+    output::info("     mod entity;");
+    if split_entity {
+        output::info("     mod entities;");
+    }
+    output::info("     mod migrations;");
+    output::blank();
+    if registered {
+        output::info(format!(
+            "  2. The routes were added to {} and are served at:",
+            "src/routes.rs".cyan()
+        ));
+        output::blank();
+        output::info(format!("     {}", collection_path));
+        output::info(format!("     {}", item_path));
+        if with_export {
+            output::info(format!("     {}/export", collection_path));
+        }
+    } else {
+        print_router_wiring(
+            singular,
+            plural,
+            &collection_path,
+            &item_path,
+            with_export,
+            options.update_style,
+        );
+    }
+    output::blank();
+    output::info(format!(
+        "  3. Enable the database feature in {}:",
+        "Cargo.toml".cyan()
+    ));
+    output::blank();
+    output::info("     rapina = { version = \"...\", features = [\"postgres\"] }");
+    output::blank();
+    if options.with_idempotency {
+        output::info(format!(
+            "  4. Register the idempotency middleware in {}:",
+            "src/main.rs".cyan()
+        ));
+        output::blank();
+        output::info("     .middleware(rapina::middleware::IdempotencyMiddleware::new())");
+        output::blank();
+    }
+    output::info(format!(
+        "  Resource {} created successfully!",
+        pascal.bright_green().bold()
+    ));
+    output::blank();
+}
+
+fn print_router_wiring(
+    singular: &str,
+    plural: &str,
+    collection_path: &str,
+    item_path: &str,
+    with_export: bool,
+    update_style: UpdateStyle,
+) {
+    let export_import = if with_export {
+        format!(", export_{}", plural)
+    } else {
+        String::new()
+    };
+    output::info(format!(
+        "  2. Register the routes in your {}:",
+        "Router".cyan()
+    ));
+    output::blank();
+    output::info(format!(
+        "     use {plural}::handlers::{{list_{plural}, get_{singular}, create_{singular}, update_{singular}, delete_{singular}{export_import}}};",
         plural = plural,
         singular = singular,
-    );
-    println!(
-        "         .put(\"/{plural}/:id\", update_{singular})",
+    ));
+    output::blank();
+    output::info("     let router = Router::new()");
+    output::info(format!(
+        "         .at(\"{path}\", |r| r.get(list_{plural}).post(create_{singular}))",
+        path = collection_path,
         plural = plural,
         singular = singular,
-    );
-    println!(
-        "         .delete(\"/{plural}/:id\", delete_{singular});",
-        plural = plural,
+    ));
+    let item_route = format!(
+        "         .at(\"{path}\", |r| r.get(get_{singular}).{method}(update_{singular}).delete(delete_{singular}))",
+        path = item_path,
         singular = singular,
+        method = update_style.method(),
     );
-    println!();
-    println!(
-        "  3. Enable the database feature in {}:",
-        "Cargo.toml".cyan()
-    );
-    println!();
-    println!("     rapina = {{ version = \"...\", features = [\"postgres\"] }}");
-    println!();
-    println!(
-        "  Resource {} created successfully!",
-        pascal.bright_green().bold()
-    );
-    println!();
+    if with_export {
+        output::info(item_route);
+        output::info(format!(
+            "         .get(\"{}/export\", export_{});",
+            collection_path, plural
+        ));
+    } else {
+        output::info(format!("{};", item_route));
+    }
+}
+
+/// Options for `rapina add resource`.
+pub struct ResourceConfig {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub prelude: String,
+    pub route_prefix: Option<String>,
+    pub parent: Option<String>,
+    pub shallow: bool,
+    pub with_export: bool,
+    pub with_field_selection: bool,
+    pub with_links: bool,
+    pub with_idempotency: bool,
+    pub json_schema: bool,
+    /// `put` or `merge-patch`
+    pub update_style: String,
+    /// Write the entity to `src/entities/<name>.rs` instead of `src/entity.rs`
+    pub split_entity: bool,
 }
 
-pub fn resource(name: &str, field_args: &[String]) -> Result<(), String> {
+pub fn resource(config: ResourceConfig) -> Result<(), String> {
+    let name = config.name.as_str();
+    let field_args = &config.fields;
+    let route_prefix = config.route_prefix.as_deref();
+    let json_schema = config.json_schema;
     validate_resource_name(name)?;
+    let prelude = Prelude::parse(&config.prelude)?;
+    let update_style = UpdateStyle::parse(&config.update_style)?;
     codegen::verify_rapina_project()?;
+    if let Some(prefix) = route_prefix {
+        codegen::normalize_route_prefix(prefix)?;
+    }
 
     if field_args.is_empty() {
         return Err(
@@ -168,20 +416,260 @@ pub fn resource(name: &str, field_args: &[String]) -> Result<(), String> {
         .map(|arg| parse_field(arg))
         .collect::<Result<Vec<_>, _>>()?;
 
+    let parent = match config.parent.as_deref() {
+        Some(parent) => {
+            let parent = validate_parent(name, parent, &fields, config.shallow)?;
+            codegen::verify_parent_entity(&parent, config.split_entity)?;
+            Some(parent)
+        }
+        None => None,
+    };
+
     let singular = name;
     let plural = &codegen::pluralize(name);
     let pascal = &codegen::to_pascal_case(name);
     let pascal_plural = &codegen::to_pascal_case(plural);
 
-    println!();
-    println!("  {} {}", "Adding resource:".bright_cyan(), pascal.bold());
-    println!();
+    output::blank();
+    output::info(format!(
+        "  {} {}",
+        "Adding resource:".bright_cyan(),
+        pascal.bold()
+    ));
+    output::blank();
+
+    let route_prefix = codegen::resolve_route_prefix(route_prefix)?;
+    let options = ModuleOptions {
+        prelude,
+        route_prefix: &route_prefix,
+        parent: parent.as_ref(),
+        with_export: config.with_export,
+        with_field_selection: config.with_field_selection,
+        with_links: config.with_links,
+        with_idempotency: config.with_idempotency,
+        json_schema,
+        update_style,
+        id: codegen::IdColumn::Integer,
+    };
+    codegen::create_feature_module(singular, plural, pascal, &fields, &options)?;
+
+    if config.split_entity {
+        codegen::create_entity_module(singular, pascal, &fields, parent.as_ref(), prelude)?;
+    } else if let Some(parent) = &parent {
+        codegen::add_child_entity(pascal, &fields, parent)?;
+    } else {
+        codegen::update_entity_file(pascal, &fields, None, None, &[], None, prelude)?;
+    }
+    // Nested resources also index the foreign key, since every list
+    // query filters on it
+    let indexes: Vec<IndexInfo> = match &parent {
+        Some(parent) => vec![IndexInfo {
+            name: None,
+            columns: vec![parent.fk_column()],
+            unique: false,
+        }],
+        None => Vec::new(),
+    };
+    if !json_schema {
+        codegen::disable_entity_json_schema(pascal)?;
+    }
+    codegen::create_migration_file(
+        plural,
+        pascal_plural,
+        &fields,
+        &indexes,
+        parent.as_ref(),
+        codegen::IdColumn::Integer,
+    )?;
+
+    let registration = codegen::with_update_route(
+        codegen::generate_route_registration(
+            singular,
+            plural,
+            &route_prefix,
+            parent.as_ref(),
+            config.with_export,
+        ),
+        update_style,
+    );
+    let registered = codegen::update_routes_module(plural, &registration)?;
+
+    print_next_steps(
+        singular,
+        plural,
+        pascal,
+        &options,
+        registered,
+        config.split_entity,
+    );
+
+    Ok(())
+}
+
+fn print_extractor_next_steps(name: &str, pascal: &str, needs_declaration: bool) {
+    output::blank();
+    output::info(format!("  {}:", "Next steps".bright_yellow()));
+    output::blank();
+    let mut step = 1;
+    if needs_declaration {
+        output::info(format!(
+            "  {}. Add the module declaration to {}:",
+            step,
+            "src/main.rs".cyan()
+        ));
+        output::blank();
+        output::info("     mod extractors;");
+        output::blank();
+        step += 1;
+    }
+    output::info(format!("  {}. Use the extractor in a handler:", step));
+    output::blank();
+    output::info(format!("     use crate::extractors::{};", pascal));
+    output::blank();
+    output::info("     #[get(\"/example\")]");
+    output::info(format!(
+        "     async fn example({}: {}) -> String {{",
+        name, pascal
+    ));
+    output::info(format!("         {}.0", name));
+    output::info("     }");
+    output::blank();
+    output::info(format!(
+        "  Extractor {} created successfully!",
+        pascal.bright_green().bold()
+    ));
+    output::blank();
+}
+
+/// Scaffold `src/policies.rs` with example authorization policies.
+pub fn policies(prelude: &str) -> Result<(), String> {
+    let prelude = Prelude::parse(prelude)?;
+    codegen::verify_rapina_project()?;
+
+    output::blank();
+    output::info(format!(
+        "  {} {}",
+        "Adding policies:".bright_cyan(),
+        "AdminOnly, Owner".bold()
+    ));
+    output::blank();
+
+    codegen::create_policies_file(prelude)?;
+
+    output::blank();
+    output::info(format!("  {}:", "Next steps".bright_yellow()));
+    output::blank();
+    output::info(format!(
+        "  1. Add the module declaration to {}:",
+        "src/main.rs".cyan()
+    ));
+    output::blank();
+    output::info("     mod policies;");
+    output::blank();
+    output::info("  2. Register the admin ids and enable auth:");
+    output::blank();
+    output::info("     .state(policies::AdminIds(admin_ids))");
+    output::info("     .with_auth(AuthConfig::from_env()?)");
+    output::blank();
+    output::info("  3. Guard a handler:");
+    output::blank();
+    output::info("     #[delete(\"/users/:id\")]");
+    output::info("     #[authorize(any(policies::AdminOnly, policies::Owner))]");
+    output::blank();
+
+    Ok(())
+}
+
+pub fn extractor(name: &str, source: Option<&str>, prelude: &str) -> Result<(), String> {
+    validate_extractor_name(name)?;
+    let prelude = Prelude::parse(prelude)?;
+    codegen::verify_rapina_project()?;
+
+    let source = match source {
+        Some(s) => ExtractorSource::parse(s)?,
+        None => ExtractorSource::Header(format!("x-{}", name.replace('_', "-"))),
+    };
+
+    let pascal = &codegen::to_pascal_case(name);
+
+    output::blank();
+    output::info(format!(
+        "  {} {}",
+        "Adding extractor:".bright_cyan(),
+        pascal.bold()
+    ));
+    output::blank();
+
+    let needs_declaration = codegen::create_extractor_file(name, pascal, &source, prelude)?;
+
+    print_extractor_next_steps(name, pascal, needs_declaration);
+
+    Ok(())
+}
 
-    codegen::create_feature_module(singular, plural, pascal, &fields)?;
-    codegen::update_entity_file(pascal, &fields, None, None)?;
-    codegen::create_migration_file(plural, pascal_plural, &fields)?;
+/// Scaffold a background job in `src/jobs/<name>.rs`.
+pub fn job(name: &str) -> Result<(), String> {
+    validate_job_name(name)?;
+    codegen::verify_rapina_project()?;
+
+    let pascal = &codegen::to_pascal_case(name);
+
+    output::blank();
+    output::info(format!(
+        "  {} {}",
+        "Adding job:".bright_cyan(),
+        pascal.bold()
+    ));
+    output::blank();
+
+    let mod_created = codegen::create_job_file(name, pascal)?;
+    let migration_created = codegen::create_jobs_migration_file()?;
 
-    print_next_steps(singular, plural, pascal);
+    output::blank();
+    output::info(format!("  {}:", "Next steps".bright_yellow()));
+    output::blank();
+    let mut step = 1;
+    if mod_created {
+        output::info(format!(
+            "  {}. Add the module declaration to {}:",
+            step,
+            "src/main.rs".cyan()
+        ));
+        output::blank();
+        output::info("     mod jobs;");
+        output::blank();
+        step += 1;
+    }
+    output::info(format!("  {}. Run the job in the app's workers:", step));
+    output::blank();
+    output::info(format!(
+        "     .with_jobs(JobQueue::new().register::<jobs::{}>())",
+        pascal
+    ));
+    output::blank();
+    step += 1;
+    output::info(format!("  {}. Enqueue it from a handler:", step));
+    output::blank();
+    output::info("     async fn example(jobs: Jobs) -> Result<StatusCode> {");
+    output::info(format!(
+        "         jobs.enqueue(crate::jobs::{} {{}}).await?;",
+        pascal
+    ));
+    output::info("         Ok(StatusCode::ACCEPTED)");
+    output::info("     }");
+    output::blank();
+    if migration_created {
+        output::info(format!(
+            "  The {} table is created the next time the app runs its migrations.",
+            "rapina_jobs".cyan()
+        ));
+        output::blank();
+    }
+    output::info(format!(
+        "  Job {} created successfully!",
+        pascal.bright_green().bold()
+    ));
+    output::blank();
 
     Ok(())
 }
@@ -255,6 +743,180 @@ mod tests {
         assert!(parse_field(":string").is_err());
         assert!(parse_field("name:unknown").is_err());
         assert!(parse_field("Name:string").is_err());
+        assert!(parse_field("self:string").is_err());
+        assert!(parse_field("super:string").is_err());
+    }
+
+    #[test]
+    fn test_parse_field_default() {
+        let cases = [
+            ("status:string=draft", "\"draft\""),
+            ("note:text=say \"hi\"", "\"say \\\"hi\\\"\""),
+            ("empty:string=", "\"\""),
+            ("views:i32=0", "0"),
+            ("balance:bigint=-5", "-5"),
+            ("score:f64=2", "2.0"),
+            ("ratio:float=-0.5", "-0.5"),
+            ("active:bool=true", "true"),
+            ("published_at:datetime=now()", "now()"),
+            ("seen_at:timestamp=now()", "now()"),
+        ];
+        for (input, expected) in cases {
+            let f = parse_field(input).unwrap();
+            assert_eq!(f.default.as_deref(), Some(expected), "failed for {}", input);
+        }
+
+        assert_eq!(parse_field("views:i32").unwrap().default, None);
+    }
+
+    #[test]
+    fn test_parse_field_invalid_default() {
+        let err = parse_field("views:i32=abc").err().unwrap();
+        assert_eq!(
+            err,
+            "Invalid default 'abc' for i32 field 'views': expected an integer"
+        );
+        assert!(parse_field("views:i32=3000000000").is_err());
+        assert!(parse_field("score:f64=NaN").is_err());
+        assert!(parse_field("active:bool=yes").is_err());
+        assert!(parse_field("published_at:datetime=2024-01-01").is_err());
+        let err = parse_field("token:uuid=abc").err().unwrap();
+        assert!(err.contains("not supported for Uuid fields"));
+    }
+
+    #[test]
+    fn test_parse_field_modifiers() {
+        let types = [
+            "string",
+            "text",
+            "i32",
+            "integer",
+            "i64",
+            "bigint",
+            "f32",
+            "float",
+            "f64",
+            "double",
+            "bool",
+            "boolean",
+            "uuid",
+            "datetime",
+            "timestamptz",
+            "naivedatetime",
+            "timestamp",
+            "date",
+            "decimal",
+        ];
+        for ty in types {
+            let plain = parse_field(&format!("x:{}", ty)).unwrap();
+            assert!(!plain.nullable && !plain.unique, "failed for {}", ty);
+            assert!(
+                plain.column_method.ends_with(".not_null()"),
+                "failed for {}",
+                ty
+            );
+
+            let f = parse_field(&format!("x:{}?", ty)).unwrap();
+            assert!(f.nullable && !f.unique, "failed for {}?", ty);
+            assert_eq!(f.rust_type, plain.rust_type, "failed for {}?", ty);
+            assert_eq!(f.value_type(), format!("Option<{}>", plain.rust_type));
+            assert_eq!(
+                f.schema_field_type(),
+                format!("Option<{}>", plain.schema_type)
+            );
+            assert!(f.column_method.ends_with(".null()"), "failed for {}?", ty);
+
+            for input in [format!("x:{}!unique", ty), format!("x:{}:unique", ty)] {
+                let f = parse_field(&input).unwrap();
+                assert!(!f.nullable && f.unique, "failed for {}", input);
+                assert_eq!(f.value_type(), plain.rust_type, "failed for {}", input);
+                assert!(f.column_method.ends_with(".not_null().unique_key()"));
+            }
+
+            for input in [format!("x:{}?!unique", ty), format!("x:{}!unique?", ty)] {
+                let f = parse_field(&input).unwrap();
+                assert!(f.nullable && f.unique, "failed for {}", input);
+                assert!(f.column_method.ends_with(".null().unique_key()"));
+            }
+        }
+
+        let f = parse_field("meta:json?").unwrap();
+        assert_eq!(f.schema_field_type(), "Option<Json>");
+
+        let f = parse_field("status:string?=draft").unwrap();
+        assert!(f.nullable);
+        assert_eq!(f.default.as_deref(), Some("\"draft\""));
+    }
+
+    #[test]
+    fn test_parse_field_invalid_modifiers() {
+        assert_eq!(
+            parse_field("id:i32?").err().unwrap(),
+            "Field 'id' cannot be nullable: it names the primary key, which is always set"
+        );
+        assert_eq!(
+            parse_field("bio:text??").err().unwrap(),
+            "Field 'bio' is marked nullable twice"
+        );
+        assert_eq!(
+            parse_field("email:string!unique:unique").err().unwrap(),
+            "Field 'email' is marked unique twice"
+        );
+        let err = parse_field("email:string!primary").err().unwrap();
+        assert!(err.starts_with("Unknown modifier 'primary' on field 'email'"));
+        assert!(parse_field("email:string!").is_err());
+        assert!(parse_field("bio:?").is_err());
+        assert!(parse_field("meta:json!unique").is_err());
+    }
+
+    #[test]
+    fn test_modifiers_flow_into_generated_code() {
+        let fields = vec![
+            parse_field("email:string!unique").unwrap(),
+            parse_field("bio:text?").unwrap(),
+        ];
+
+        let block = codegen::generate_schema_block("User", &fields, None, None, &[], None);
+        assert!(block.contains("        #[unique]\n        email: String,"));
+        assert!(block.contains("        bio: Option<Text>,"));
+
+        let migration = codegen::generate_migration(
+            "users",
+            "Users",
+            &fields,
+            &[],
+            None,
+            codegen::IdColumn::Integer,
+        );
+        assert!(migration.contains(".string().not_null().unique_key()"));
+        assert!(migration.contains(".text().null()"));
+
+        let dto = codegen::generate_dto("User", &fields, false);
+        assert!(dto.contains(
+            "pub struct CreateUser {\n    pub email: String,\n    pub bio: Option<String>,\n}"
+        ));
+        assert!(dto.contains("pub struct UpdateUser {\n    pub email: Option<String>,\n    pub bio: Option<String>,\n}"));
+
+        let handlers = codegen::generate_handlers(
+            "user",
+            "users",
+            "User",
+            &fields,
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
+        assert!(handlers.contains("        bio: Set(input.bio),"));
+        assert!(handlers.contains("active.email = Set(val);"));
+        assert!(handlers.contains("active.bio = Set(Some(val));"));
+    }
+
+    #[test]
+    fn test_parse_field_keyword() {
+        let f = parse_field("type:string").unwrap();
+        assert_eq!(f.name, "type");
+        assert_eq!(f.ident(), "r#type");
     }
 
     #[test]
@@ -271,6 +933,10 @@ mod tests {
         assert!(validate_resource_name("_user").is_err());
         assert!(validate_resource_name("user_").is_err());
         assert!(validate_resource_name("self").is_err());
+        assert!(validate_resource_name("type").is_err());
+        assert!(validate_resource_name("async").is_err());
+        assert!(validate_resource_name("match").is_err());
+        assert!(validate_resource_name("gen").is_err());
         assert!(validate_resource_name("user-name").is_err());
     }
 
@@ -304,19 +970,43 @@ mod tests {
                 rust_type: "String".to_string(),
                 schema_type: "String".to_string(),
                 column_method: ".string().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
             FieldInfo {
                 name: "active".to_string(),
                 rust_type: "bool".to_string(),
                 schema_type: "bool".to_string(),
                 column_method: ".boolean().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
         ];
-        let content = codegen::generate_handlers("post", "posts", "Post", &fields);
+        let content = codegen::generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &fields,
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
 
         assert!(content.contains("use crate::entity::Post;"));
         assert!(content.contains("use crate::entity::post::{ActiveModel, Model};"));
-        assert!(content.contains("pub async fn list_posts"));
+        assert!(content.contains("use rapina::pagination::{Paginate, Paginated};"));
+        assert!(content.contains(
+            "pub async fn list_posts(db: Db, page: Paginate) -> Result<Paginated<Model>> {"
+        ));
+        assert!(content.contains("    let items = page.exec(Post::find(), db.read()).await?;"));
+        assert!(!content.contains(".all("));
         assert!(content.contains("pub async fn get_post"));
         assert!(content.contains("pub async fn create_post"));
         assert!(content.contains("pub async fn update_post"));
@@ -331,6 +1021,52 @@ mod tests {
         assert!(content.contains("if let Some(val) = update.active"));
     }
 
+    #[test]
+    fn test_generate_handlers_publish_events() {
+        let fields = vec![FieldInfo {
+            name: "title".to_string(),
+            rust_type: "String".to_string(),
+            schema_type: "String".to_string(),
+            column_method: ".string().not_null()".to_string(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: false,
+            unique: false,
+        }];
+        let content = codegen::generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &fields,
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
+
+        assert!(content.contains("use rapina::events::{Event, Events};"));
+        assert!(content.contains("pub async fn create_post(db: Db, events: Events,"));
+        assert!(content.contains("pub async fn update_post(db: Db, events: Events,"));
+        assert!(content.contains("pub async fn delete_post(db: Db, events: Events,"));
+        assert!(content.contains("Event::created(\"post\", result.id)"));
+        assert!(content.contains("Event::updated(\"post\", id)"));
+        assert!(content.contains("Event::deleted(\"post\", id)"));
+        assert_eq!(
+            content
+                .matches("events.before_commit(&txn, &event)")
+                .count(),
+            3
+        );
+        assert_eq!(content.matches("txn.commit()").count(), 3);
+        assert_eq!(content.matches("events.after_commit(event)").count(), 3);
+
+        // Events are published only after the commit succeeds
+        let commit = content.find("txn.commit()").unwrap();
+        let after_commit = content.find("events.after_commit(event)").unwrap();
+        assert!(commit < after_commit);
+    }
+
     #[test]
     fn test_generate_dto() {
         let fields = vec![
@@ -339,15 +1075,25 @@ mod tests {
                 rust_type: "String".to_string(),
                 schema_type: "String".to_string(),
                 column_method: String::new(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
             FieldInfo {
                 name: "age".to_string(),
                 rust_type: "i32".to_string(),
                 schema_type: "i32".to_string(),
                 column_method: String::new(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
         ];
-        let content = codegen::generate_dto("User", &fields);
+        let content = codegen::generate_dto("User", &fields, true);
 
         assert!(content.contains("pub struct CreateUser"));
         assert!(content.contains("pub struct UpdateUser"));
@@ -357,9 +1103,104 @@ mod tests {
         assert!(content.contains("pub age: Option<i32>,"));
     }
 
+    #[test]
+    fn test_generated_resource_has_no_glob_imports() {
+        let fields: Vec<FieldInfo> = ["title:string", "id_card:uuid", "born:date", "meta:json"]
+            .iter()
+            .map(|f| parse_field(f).unwrap())
+            .collect();
+        let parent = ParentInfo::new("user", false);
+
+        let mut files = vec![codegen::generate_dto("Post", &fields, true)];
+        for prelude in [Prelude::Full, Prelude::Minimal] {
+            for parent in [None, Some(&parent)] {
+                files.push(codegen::generate_handlers(
+                    "post", "posts", "Post", &fields, "i32", prelude, "", parent,
+                ));
+            }
+            files.push(codegen::generate_error("Post", prelude));
+        }
+
+        for file in &files {
+            for line in file.lines().filter(|l| l.starts_with("use ")) {
+                assert!(
+                    !line.contains('*'),
+                    "glob import in generated code: {}",
+                    line
+                );
+            }
+        }
+        assert!(files[0].contains("use rapina::sea_orm::prelude::{Date, Json, Uuid};\n"));
+        assert!(files[1].contains("use rapina::error::{Error, Result};\n"));
+        assert!(files[1].contains("use rapina::extract::{Json, Path};\n"));
+        assert!(files[1].contains("use rapina::{delete, get, post, put};\n"));
+    }
+
+    #[test]
+    fn test_shadowed_error_fixture_is_current() {
+        let dir = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/shadowed_error/src/posts"
+        );
+        let fields: Vec<FieldInfo> = ["title:string", "published:bool", "metadata:json"]
+            .iter()
+            .map(|f| parse_field(f).unwrap())
+            .collect();
+        let expected = [
+            ("dto.rs", codegen::generate_dto("Post", &fields, true)),
+            ("error.rs", codegen::generate_error("Post", Prelude::Full)),
+            (
+                "handlers.rs",
+                codegen::generate_handlers(
+                    "post",
+                    "posts",
+                    "Post",
+                    &fields,
+                    "i32",
+                    Prelude::Full,
+                    "",
+                    None,
+                ),
+            ),
+        ];
+        // rapina/tests/generated_code.rs compiles these files, so they must
+        // match what `rapina add resource` writes today
+        for (file, content) in expected {
+            let fixture = std::fs::read_to_string(format!("{}/{}", dir, file)).unwrap();
+            assert_eq!(fixture, content, "{} is out of date", file);
+        }
+    }
+
+    #[test]
+    fn test_linked_handlers_fixture_is_current() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/shadowed_error/src/posts/linked_handlers.rs"
+        );
+        let fields: Vec<FieldInfo> = ["title:string", "published:bool", "metadata:json"]
+            .iter()
+            .map(|f| parse_field(f).unwrap())
+            .collect();
+        let handlers = codegen::generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &fields,
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
+        let content = codegen::with_links_handlers(handlers, "post", "posts", None);
+        // rapina/tests/generated_code.rs compiles this file, so it must match
+        // what `rapina add resource --with-links` writes today
+        let fixture = std::fs::read_to_string(path).unwrap();
+        assert_eq!(fixture, content, "linked_handlers.rs is out of date");
+    }
+
     #[test]
     fn test_generate_error() {
-        let content = codegen::generate_error("User");
+        let content = codegen::generate_error("User", Prelude::Full);
 
         assert!(content.contains("pub enum UserError"));
         assert!(content.contains("impl IntoApiError for UserError"));
@@ -376,15 +1217,25 @@ mod tests {
                 rust_type: "String".to_string(),
                 schema_type: "String".to_string(),
                 column_method: String::new(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
             FieldInfo {
                 name: "done".to_string(),
                 rust_type: "bool".to_string(),
                 schema_type: "bool".to_string(),
                 column_method: String::new(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
         ];
-        let content = codegen::generate_schema_block("Todo", &fields, None, None);
+        let content = codegen::generate_schema_block("Todo", &fields, None, None, &[], None);
 
         assert!(content.contains("schema! {"));
         assert!(content.contains("Todo {"));
@@ -400,15 +1251,32 @@ mod tests {
                 rust_type: "String".to_string(),
                 schema_type: "String".to_string(),
                 column_method: ".string().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
             FieldInfo {
                 name: "published".to_string(),
                 rust_type: "bool".to_string(),
                 schema_type: "bool".to_string(),
                 column_method: ".boolean().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
             },
         ];
-        let content = codegen::generate_migration("posts", "Posts", &fields);
+        let content = codegen::generate_migration(
+            "posts",
+            "Posts",
+            &fields,
+            &[],
+            None,
+            codegen::IdColumn::Integer,
+        );
 
         assert!(content.contains("MigrationTrait for Migration"));
         assert!(content.contains("Posts::Table"));
@@ -420,4 +1288,188 @@ mod tests {
         assert!(content.contains("enum Posts {"));
         assert!(content.contains("drop_table"));
     }
+
+    #[test]
+    fn test_keyword_field_codegen() {
+        let fields = vec![parse_field("type:string").unwrap()];
+
+        let schema = codegen::generate_schema_block("Order", &fields, None, None, &[], None);
+        assert!(schema.contains("        r#type: String,"));
+
+        let dto = codegen::generate_dto("Order", &fields, true);
+        assert!(dto.contains("pub r#type: String,"));
+        assert!(dto.contains("pub r#type: Option<String>,"));
+
+        let handlers = codegen::generate_handlers(
+            "order",
+            "orders",
+            "Order",
+            &fields,
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
+        assert!(handlers.contains("r#type: Set(input.r#type),"));
+        assert!(handlers.contains("if let Some(val) = update.r#type {"));
+        assert!(handlers.contains("active.r#type = Set(val);"));
+
+        let migration = codegen::generate_migration(
+            "orders",
+            "Orders",
+            &fields,
+            &[],
+            None,
+            codegen::IdColumn::Integer,
+        );
+        assert!(migration.contains("ColumnDef::new(Orders::Type)"));
+        assert!(migration.contains("    Type,"));
+        assert!(!migration.contains("r#"));
+    }
+
+    #[test]
+    fn test_validate_parent() {
+        let fields = vec![parse_field("body:text").unwrap()];
+        let parent = validate_parent("comment", "post", &fields, true).unwrap();
+        assert_eq!(parent.pascal_plural, "Posts");
+        assert_eq!(parent.fk_column(), "post_id");
+        assert!(parent.shallow);
+
+        assert!(validate_parent("comment", "comment", &fields, false).is_err());
+        assert!(validate_parent("comment", "Post", &fields, false).is_err());
+
+        let clashing = vec![parse_field("post_id:i32").unwrap()];
+        let err = validate_parent("comment", "post", &clashing, false).unwrap_err();
+        assert!(err.contains("clashes"));
+    }
+
+    #[test]
+    fn test_generate_policies() {
+        let content = codegen::generate_policies(Prelude::Full);
+        assert!(content.contains("impl Policy for AdminOnly {"));
+        assert!(content.contains("impl Policy for Owner {"));
+        assert!(content.contains("-> Result<()> {"));
+        assert!(content.contains("Err(Error::forbidden(\"only admins can do this\"))"));
+
+        let content = codegen::generate_policies(Prelude::Minimal);
+        assert!(content.contains("use rapina::prelude::minimal::*;"));
+        assert!(content.contains("-> rapina::error::Result<()> {"));
+        assert!(content.contains("rapina::error::Error::forbidden("));
+    }
+
+    #[test]
+    fn test_validate_extractor_name() {
+        assert!(validate_extractor_name("api_key").is_ok());
+        assert!(validate_extractor_name("ApiKey").is_err());
+        assert!(validate_extractor_name("fn").is_err());
+    }
+
+    #[test]
+    fn test_extractor_source_parse() {
+        assert!(matches!(
+            ExtractorSource::parse("header:X-Api-Key").unwrap(),
+            ExtractorSource::Header(h) if h == "x-api-key"
+        ));
+        assert!(matches!(
+            ExtractorSource::parse("query:token").unwrap(),
+            ExtractorSource::Query(q) if q == "token"
+        ));
+        assert!(ExtractorSource::parse("header").is_err());
+        assert!(ExtractorSource::parse("header:").is_err());
+        assert!(ExtractorSource::parse("cookie:session").is_err());
+    }
+
+    #[test]
+    fn test_generate_extractor_header() {
+        let source = ExtractorSource::Header("x-api-key".to_string());
+        let content = codegen::generate_extractor("ApiKey", &source, Prelude::Full);
+
+        assert!(content.contains("pub struct ApiKey(pub String);"));
+        assert!(content.contains("impl FromRequestParts for ApiKey"));
+        assert!(content.contains(".get(\"x-api-key\")"));
+        assert!(content.contains("Error::unauthorized(\"missing x-api-key header\")"));
+        assert!(content.contains("use rapina::testing::TestClient;"));
+        assert!(content.contains(".header(\"x-api-key\", \"secret\")"));
+        assert!(content.contains("StatusCode::UNAUTHORIZED"));
+        assert!(!content.contains("HashMap"));
+    }
+
+    #[test]
+    fn test_generate_extractor_query() {
+        let source = ExtractorSource::Query("token".to_string());
+        let content = codegen::generate_extractor("Token", &source, Prelude::Full);
+
+        assert!(content.contains("pub struct Token(pub String);"));
+        assert!(content.contains("use std::collections::HashMap;"));
+        assert!(content.contains("Query::<HashMap<String, String>>::from_request_parts"));
+        assert!(content.contains(".get(\"token\")"));
+        assert!(content.contains("client.get(\"/?token=secret\")"));
+        assert!(content.contains("StatusCode::BAD_REQUEST"));
+    }
+
+    #[test]
+    fn test_validate_job_name() {
+        assert!(validate_job_name("send_email").is_ok());
+        assert!(validate_job_name("SendEmail").is_err());
+        assert!(validate_job_name("_send").is_err());
+    }
+
+    #[test]
+    fn test_generate_job() {
+        let content = codegen::generate_job("send_email", "SendEmail");
+
+        assert!(content.contains("use rapina::jobs::{Job, JobContext, JobResult};"));
+        assert!(content.contains("pub struct SendEmail {}"));
+        assert!(content.contains("impl Job for SendEmail {"));
+        assert!(content.contains("const NAME: &'static str = \"send_email\";"));
+        assert!(content.contains("async fn run(self, ctx: JobContext) -> JobResult {"));
+    }
+
+    #[test]
+    fn test_generate_jobs_migration() {
+        let content = codegen::generate_jobs_migration();
+        assert!(content.contains("pub use rapina::jobs::migration::Migration;"));
+    }
+
+    #[test]
+    fn test_prelude_parse() {
+        assert_eq!(Prelude::parse("full").unwrap(), Prelude::Full);
+        assert_eq!(Prelude::parse("minimal").unwrap(), Prelude::Minimal);
+        assert!(Prelude::parse("slim").is_err());
+    }
+
+    #[test]
+    fn test_generate_with_minimal_prelude() {
+        let fields = vec![parse_field("title:string").unwrap()];
+
+        let handlers = codegen::generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &fields,
+            "i32",
+            Prelude::Minimal,
+            "",
+            None,
+        );
+        assert!(!handlers.contains("prelude"));
+        assert!(!handlers.contains("use rapina::error"));
+        assert!(handlers.contains("-> rapina::error::Result<Paginated<Model>>"));
+        assert!(handlers.contains("rapina::error::Error::not_found("));
+        assert!(!handlers.contains(" Result<"));
+        assert!(!handlers.contains("(|| Error::"));
+
+        let error = codegen::generate_error("Post", Prelude::Minimal);
+        assert!(
+            error.contains("use rapina::error::{DocumentedError, ErrorVariant, IntoApiError};\n")
+        );
+        assert!(error.contains("fn into_api_error(self) -> rapina::error::Error {"));
+        assert!(!error.contains("prelude"));
+
+        let source = ExtractorSource::Header("x-api-key".to_string());
+        let extractor = codegen::generate_extractor("ApiKey", &source, Prelude::Minimal);
+        assert!(extractor.contains("use rapina::prelude::minimal::*;"));
+        assert!(extractor.contains(") -> rapina::error::Result<Self> {"));
+        assert!(extractor.contains("rapina::error::Error::unauthorized("));
+    }
 }
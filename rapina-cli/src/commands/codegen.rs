@@ -1,12 +1,284 @@
 use colored::Colorize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::output;
 
 pub(crate) struct FieldInfo {
     pub name: String,
     pub rust_type: String,
     pub schema_type: String,
     pub column_method: String,
+    /// Column comment, emitted as a doc comment on the generated field
+    pub comment: Option<String>,
+    /// Database column name, when it differs from the field name
+    pub column: Option<String>,
+    /// Column default as a `schema!` literal, e.g. `0`, `"draft"` or `now()`
+    pub default: Option<String>,
+    /// Whether the column accepts NULL, making the field an `Option`
+    pub nullable: bool,
+    /// Whether the column has a unique constraint of its own
+    pub unique: bool,
+}
+
+impl FieldInfo {
+    /// The field name as it must be written in Rust source, e.g. `r#type`.
+    pub(crate) fn ident(&self) -> String {
+        rust_ident(&self.name)
+    }
+
+    /// The name of the field's column in the database.
+    pub(crate) fn column_name(&self) -> &str {
+        self.column.as_deref().unwrap_or(&self.name)
+    }
+
+    /// The field's type in DTOs and handlers, `Option<T>` when nullable.
+    pub(crate) fn value_type(&self) -> String {
+        optional(&self.rust_type, self.nullable)
+    }
+
+    /// The field's type in `schema!`, `Option<T>` when nullable.
+    pub(crate) fn schema_field_type(&self) -> String {
+        optional(&self.schema_type, self.nullable)
+    }
+
+    /// The `ColumnDef` builder calls for the column, with its default.
+    pub(crate) fn column_def(&self) -> String {
+        match self.default.as_deref() {
+            Some("now()") => format!("{}.default(Expr::current_timestamp())", self.column_method),
+            Some(value) => format!("{}.default({})", self.column_method, value),
+            None => self.column_method.clone(),
+        }
+    }
+}
+
+fn optional(ty: &str, nullable: bool) -> String {
+    if nullable {
+        format!("Option<{}>", ty)
+    } else {
+        ty.to_string()
+    }
+}
+
+/// Field types generated code imports from `rapina::sea_orm::prelude`.
+const SEA_ORM_PRELUDE_TYPES: [&str; 6] =
+    ["Date", "DateTime", "DateTimeUtc", "Decimal", "Json", "Uuid"];
+
+/// Which prelude generated code imports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Prelude {
+    /// `use rapina::prelude::*;`
+    #[default]
+    Full,
+    /// `use rapina::prelude::minimal::*;`, with `Error` and `Result` written
+    /// as fully qualified paths so they don't clash with the app's own types.
+    Minimal,
+}
+
+/// How the generated update handler reads its body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum UpdateStyle {
+    /// `PUT` with a JSON `Update{Pascal}` DTO of optional fields
+    #[default]
+    Put,
+    /// `PATCH` with an `application/merge-patch+json` body applied to the
+    /// stored row
+    MergePatch,
+}
+
+impl UpdateStyle {
+    pub(crate) fn parse(input: &str) -> Result<Self, String> {
+        match input {
+            "put" => Ok(UpdateStyle::Put),
+            "merge-patch" => Ok(UpdateStyle::MergePatch),
+            _ => Err(format!(
+                "Unknown update style '{}'. Expected 'put' or 'merge-patch'",
+                input
+            )),
+        }
+    }
+
+    /// The router method the update handler is registered with.
+    pub(crate) fn method(self) -> &'static str {
+        match self {
+            UpdateStyle::Put => "put",
+            UpdateStyle::MergePatch => "patch",
+        }
+    }
+}
+
+/// Primary key of a generated table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum IdColumn<'a> {
+    /// Auto-increment integer `id`
+    #[default]
+    Integer,
+    /// UUID `id`, generated by the entity on insert (`#[id(Uuid)]`)
+    #[cfg_attr(not(feature = "import"), allow(dead_code))]
+    Uuid,
+    /// One of the table's fields, not auto-incremented (`#[primary_key(col)]`)
+    #[cfg_attr(not(feature = "import"), allow(dead_code))]
+    Field(&'a str),
+}
+
+impl IdColumn<'_> {
+    /// The `ColumnDef` builder calls for the `id` column, `None` when the
+    /// primary key is one of the fields.
+    fn column_method(self) -> Option<&'static str> {
+        match self {
+            IdColumn::Integer => Some(
+                ".integer()\n                            .not_null()\n                            .auto_increment()",
+            ),
+            IdColumn::Uuid => Some(".uuid()\n                            .not_null()"),
+            IdColumn::Field(_) => None,
+        }
+    }
+
+    /// The field holding the key, when it is one of the table's fields.
+    fn field(self, fields: &[FieldInfo]) -> Option<&FieldInfo> {
+        match self {
+            IdColumn::Field(column) => fields.iter().find(|f| f.column_name() == column),
+            _ => None,
+        }
+    }
+
+    /// The key's Rust type, as the item handlers take it from the path.
+    pub(crate) fn rust_type(self, fields: &[FieldInfo]) -> &str {
+        match self {
+            IdColumn::Integer => "i32",
+            IdColumn::Uuid => "Uuid",
+            IdColumn::Field(_) => self.field(fields).map_or("i32", |f| f.rust_type.as_str()),
+        }
+    }
+}
+
+impl Prelude {
+    pub(crate) fn parse(input: &str) -> Result<Self, String> {
+        match input {
+            "full" => Ok(Prelude::Full),
+            "minimal" => Ok(Prelude::Minimal),
+            _ => Err(format!(
+                "Unknown prelude '{}'. Expected 'full' or 'minimal'",
+                input
+            )),
+        }
+    }
+
+    fn import(self) -> &'static str {
+        match self {
+            Prelude::Full => "use rapina::prelude::*;",
+            Prelude::Minimal => "use rapina::prelude::minimal::*;",
+        }
+    }
+
+    fn error(self) -> &'static str {
+        match self {
+            Prelude::Full => "Error",
+            Prelude::Minimal => "rapina::error::Error",
+        }
+    }
+
+    fn result(self) -> &'static str {
+        match self {
+            Prelude::Full => "Result",
+            Prelude::Minimal => "rapina::error::Result",
+        }
+    }
+
+    /// Imports `items` from `rapina::error`, leaving out `Error` and
+    /// `Result` when they are written in full.
+    fn error_import(self, items: &[&str]) -> String {
+        let items: Vec<&str> = items
+            .iter()
+            .copied()
+            .filter(|item| self == Prelude::Full || !matches!(*item, "Error" | "Result"))
+            .collect();
+        match items.as_slice() {
+            [] => String::new(),
+            [item] => format!("use rapina::error::{};\n", item),
+            items => format!("use rapina::error::{{{}}};\n", items.join(", ")),
+        }
+    }
+}
+
+/// An index or unique constraint created alongside a table.
+pub(crate) struct IndexInfo {
+    /// Explicit name, e.g. preserved from an imported database. When `None`,
+    /// a deterministic `idx_{table}_{columns}` / `uq_{table}_{columns}` name
+    /// is used.
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+impl IndexInfo {
+    pub(crate) fn resolved_name(&self, table: &str) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => {
+                let prefix = if self.unique { "uq" } else { "idx" };
+                format!("{}_{}_{}", prefix, table, self.columns.join("_"))
+            }
+        }
+    }
+}
+
+/// The parent of a nested resource, e.g. `post` for `/posts/:post_id/comments`.
+#[derive(Debug)]
+pub(crate) struct ParentInfo {
+    pub singular: String,
+    pub plural: String,
+    pub pascal: String,
+    pub pascal_plural: String,
+    /// Keep show/update/delete at the top level (`/comments/:id`)
+    pub shallow: bool,
+}
+
+impl ParentInfo {
+    pub(crate) fn new(singular: &str, shallow: bool) -> Self {
+        let plural = pluralize(singular);
+        Self {
+            singular: singular.to_string(),
+            pascal: to_pascal_case(singular),
+            pascal_plural: to_pascal_case(&plural),
+            plural,
+            shallow,
+        }
+    }
+
+    /// The foreign key column on the child, e.g. `post_id`.
+    pub(crate) fn fk_column(&self) -> String {
+        format!("{}_id", self.singular)
+    }
+}
+
+/// Strict, reserved, and edition-specific Rust keywords.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be written as raw identifiers.
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate"];
+
+pub(crate) fn is_rust_keyword(name: &str) -> bool {
+    RUST_KEYWORDS.contains(&name)
+}
+
+pub(crate) fn can_be_raw_ident(name: &str) -> bool {
+    !NON_RAW_KEYWORDS.contains(&name)
+}
+
+/// Escapes keywords as raw identifiers (`type` -> `r#type`).
+pub(crate) fn rust_ident(name: &str) -> String {
+    if is_rust_keyword(name) && can_be_raw_ident(name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
 }
 
 pub(crate) fn to_pascal_case(s: &str) -> String {
@@ -79,265 +351,1170 @@ pub(crate) fn verify_rapina_project() -> Result<(), String> {
     Ok(())
 }
 
+/// Normalizes a `--route-prefix` value to `/segment/...` with no trailing
+/// slash. An empty prefix (or `/`) means routes live at the root.
+pub(crate) fn normalize_route_prefix(input: &str) -> Result<String, String> {
+    let trimmed = input.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+
+    for segment in trimmed.split('/') {
+        if segment.is_empty() {
+            return Err(format!(
+                "Invalid route prefix '{}': empty path segment",
+                input
+            ));
+        }
+        if segment.starts_with(':') {
+            return Err(format!(
+                "Invalid route prefix '{}': path parameters are not allowed",
+                input
+            ));
+        }
+        if let Some(c) = segment
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~')))
+        {
+            return Err(format!(
+                "Invalid route prefix '{}': unexpected character '{}'",
+                input, c
+            ));
+        }
+    }
+
+    Ok(format!("/{}", trimmed))
+}
+
+/// Joins a route prefix and a path, leaving paths that already start with
+/// the prefix untouched so it is never applied twice.
+pub(crate) fn prefixed_path(prefix: &str, path: &str) -> String {
+    if prefix.is_empty()
+        || path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+    {
+        path.to_string()
+    } else {
+        format!("{}{}", prefix, path)
+    }
+}
+
+/// Reads `route_prefix` from `[package.metadata.rapina]` in Cargo.toml.
+pub(crate) fn stored_route_prefix(cargo_toml: &str) -> Option<String> {
+    let parsed: toml::Value = toml::from_str(cargo_toml).ok()?;
+    parsed
+        .get("package")?
+        .get("metadata")?
+        .get("rapina")?
+        .get("route_prefix")?
+        .as_str()
+        .map(String::from)
+}
+
+/// Sets `route_prefix` in `[package.metadata.rapina]`, adding the table if
+/// needed and keeping the rest of the file as written.
+pub(crate) fn set_route_prefix(cargo_toml: &str, prefix: &str) -> String {
+    let entry = format!("route_prefix = {:?}", prefix);
+    let mut lines: Vec<String> = cargo_toml.lines().map(String::from).collect();
+
+    if let Some(header) = lines
+        .iter()
+        .position(|line| line.trim() == "[package.metadata.rapina]")
+    {
+        let end = lines[header + 1..]
+            .iter()
+            .position(|line| line.trim_start().starts_with('['))
+            .map_or(lines.len(), |i| header + 1 + i);
+        match lines[header + 1..end]
+            .iter()
+            .position(|line| line.trim_start().starts_with("route_prefix"))
+        {
+            Some(i) => lines[header + 1 + i] = entry,
+            None => lines.insert(header + 1, entry),
+        }
+    } else {
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        lines.push(String::new());
+        lines.push("[package.metadata.rapina]".to_string());
+        lines.push(entry);
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Resolves the route prefix for a scaffold: the `--route-prefix` flag if
+/// given (saved to Cargo.toml for later scaffolds), otherwise the saved one.
+pub(crate) fn resolve_route_prefix(flag: Option<&str>) -> Result<String, String> {
+    let mut changes = Changes::default();
+    let prefix = stage_route_prefix(&mut changes, flag)?;
+    if !changes.is_empty() {
+        changes.apply()?;
+        output::success(format!(
+            "Saved route prefix {} to {}",
+            display_prefix(&prefix).cyan(),
+            "Cargo.toml".cyan()
+        ));
+    }
+    Ok(prefix)
+}
+
+/// Like [`resolve_route_prefix`], staging the Cargo.toml update in `changes`.
+pub(crate) fn stage_route_prefix(
+    changes: &mut Changes,
+    flag: Option<&str>,
+) -> Result<String, String> {
+    let content = changes
+        .read(CARGO_PATH)
+        .ok_or_else(|| "Failed to read Cargo.toml".to_string())?;
+    let stored = stored_route_prefix(&content);
+
+    let Some(flag) = flag else {
+        return match stored {
+            Some(prefix) => normalize_route_prefix(&prefix),
+            None => Ok(String::new()),
+        };
+    };
+
+    let prefix = normalize_route_prefix(flag)?;
+    if stored.as_deref() != Some(prefix.as_str()) {
+        if let Some(previous) = &stored {
+            output::warn(format!(
+                "Replacing route prefix {} with {}",
+                previous.yellow(),
+                display_prefix(&prefix).cyan()
+            ));
+        }
+        changes.write(CARGO_PATH, set_route_prefix(&content, &prefix));
+    }
+
+    Ok(prefix)
+}
+
+fn display_prefix(prefix: &str) -> &str {
+    if prefix.is_empty() { "/" } else { prefix }
+}
+
+/// Collection and item paths of a resource, e.g. `/posts/:post_id/comments`
+/// and `/posts/:post_id/comments/:id` when nested under `post`.
+pub(crate) fn resource_paths(
+    plural: &str,
+    route_prefix: &str,
+    parent: Option<&ParentInfo>,
+) -> (String, String) {
+    let top_level = prefixed_path(route_prefix, &format!("/{}", plural));
+    let Some(parent) = parent else {
+        let item = format!("{}/:id", top_level);
+        return (top_level, item);
+    };
+
+    let collection = prefixed_path(
+        route_prefix,
+        &format!("/{}/:{}/{}", parent.plural, parent.fk_column(), plural),
+    );
+    let item = if parent.shallow {
+        format!("{}/:id", top_level)
+    } else {
+        format!("{}/:id", collection)
+    };
+    (collection, item)
+}
+
 pub(crate) fn generate_mod_rs() -> String {
     "pub mod dto;\npub mod error;\npub mod handlers;\n".to_string()
 }
 
+/// Renders the CRUD handlers of a resource whose item routes take an
+/// `id_type` key: `i32`, `i64`, `Uuid` or `String`.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn generate_handlers(
     singular: &str,
     plural: &str,
     pascal: &str,
     fields: &[FieldInfo],
+    id_type: &str,
+    prelude: Prelude,
+    route_prefix: &str,
+    parent: Option<&ParentInfo>,
 ) -> String {
+    let (collection_path, item_path) = resource_paths(plural, route_prefix, parent);
+    let nested = NestedHandlerParts::new(pascal, parent, prelude);
+
+    // A `String` key is still needed for the not-found message and the
+    // event after the lookup takes it, so it is cloned or borrowed there
+    let (key, event_key, created_key) = if id_type == "String" {
+        ("id.clone()", "&id", "&result.id")
+    } else {
+        ("id", "id", "result.id")
+    };
+    let id_import = if SEA_ORM_PRELUDE_TYPES.contains(&id_type) {
+        format!("use rapina::sea_orm::prelude::{};\n", id_type)
+    } else {
+        String::new()
+    };
+
     let create_fields: Vec<String> = fields
         .iter()
-        .map(|f| format!("        {}: Set(input.{}),", f.name, f.name))
+        .map(|f| format!("        {name}: Set(input.{name}),", name = f.ident()))
         .collect();
     let create_body = create_fields.join("\n");
 
     let update_checks: Vec<String> = fields
         .iter()
         .map(|f| {
+            let value = if f.nullable { "Some(val)" } else { "val" };
             format!(
-                "    if let Some(val) = update.{name} {{\n        active.{name} = Set(val);\n    }}",
-                name = f.name
+                "    if let Some(val) = update.{name} {{\n        active.{name} = Set({value});\n    }}",
+                name = f.ident()
             )
         })
         .collect();
     let update_body = update_checks.join("\n");
 
+    // Generated files import each item by name rather than through a glob,
+    // so they keep compiling next to an app's own `Error` or `Json`
     format!(
-        r#"use rapina::prelude::*;
-use rapina::database::{{Db, DbError}};
-use rapina::sea_orm::{{ActiveModelTrait, EntityTrait, IntoActiveModel, Set}};
+        r#"use rapina::database::{{Db, DbError}};
+{error_import}use rapina::events::{{Event, Events}};
+use rapina::extract::{{Json, Path}};
+use rapina::pagination::{{Paginate, Paginated}};
+use rapina::sea_orm::{{{sea_orm_imports}}};
+{id_import}use rapina::{{delete, get, post, put}};
 
-use crate::entity::{pascal};
-use crate::entity::{singular}::{{ActiveModel, Model}};
+use crate::entity::{entity_imports};
+use crate::entity::{singular}::{{{model_imports}}};
 
 use super::dto::{{Create{pascal}, Update{pascal}}};
 use super::error::{pascal}Error;
 
-#[get("/{plural}")]
+#[get("{collection_path}")]
 #[errors({pascal}Error)]
-pub async fn list_{plural}(db: Db) -> Result<Json<Vec<Model>>> {{
-    let items = {pascal}::find().all(db.conn()).await.map_err(DbError)?;
-    Ok(Json(items))
+pub async fn list_{plural}(db: Db{list_args}, page: Paginate) -> {result}<Paginated<Model>> {{
+{list_body}
+    Ok(items)
 }}
 
-#[get("/{plural}/:id")]
+#[get("{item_path}")]
 #[errors({pascal}Error)]
-pub async fn get_{singular}(db: Db, id: Path<i32>) -> Result<Json<Model>> {{
-    let id = id.into_inner();
-    let item = {pascal}::find_by_id(id)
-        .one(db.conn())
+pub async fn get_{singular}(db: Db, {item_args}id: Path<{id_type}>) -> {result}<Json<Model>> {{
+{item_ids}{item_read_check}
+    let item = {pascal}::find_by_id({key}){item_filter}
+        .one(db.read())
         .await
         .map_err(DbError)?
-        .ok_or_else(|| Error::not_found(format!("{pascal} {{}} not found", id)))?;
+        .ok_or_else(|| {error}::not_found(format!("{pascal} {{}} not found", id)))?;
     Ok(Json(item))
 }}
 
-#[post("/{plural}")]
+#[post("{collection_path}")]
 #[errors({pascal}Error)]
-pub async fn create_{singular}(db: Db, body: Json<Create{pascal}>) -> Result<Json<Model>> {{
-    let input = body.into_inner();
+pub async fn create_{singular}(db: Db, events: Events, {create_args}body: Json<Create{pascal}>) -> {result}<Json<Model>> {{
+{create_ids}    let input = body.into_inner();
     let item = ActiveModel {{
-{create_body}
+{create_fk}{create_body}
         ..Default::default()
     }};
-    let result = item.insert(db.conn()).await.map_err(DbError)?;
+    let txn = db.write().begin().await.map_err(DbError)?;{create_check}
+    let result = item.insert(&txn).await.map_err(DbError)?;
+    let event = Event::created("{singular}", {created_key});
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
     Ok(Json(result))
 }}
 
-#[put("/{plural}/:id")]
+#[put("{item_path}")]
 #[errors({pascal}Error)]
-pub async fn update_{singular}(db: Db, id: Path<i32>, body: Json<Update{pascal}>) -> Result<Json<Model>> {{
-    let id = id.into_inner();
-    let item = {pascal}::find_by_id(id)
-        .one(db.conn())
+pub async fn update_{singular}(db: Db, events: Events, {item_args}id: Path<{id_type}>, body: Json<Update{pascal}>) -> {result}<Json<Model>> {{
+{item_ids}
+    let txn = db.write().begin().await.map_err(DbError)?;{item_txn_check}
+    let item = {pascal}::find_by_id({key}){item_filter}
+        .one(&txn)
         .await
         .map_err(DbError)?
-        .ok_or_else(|| Error::not_found(format!("{pascal} {{}} not found", id)))?;
+        .ok_or_else(|| {error}::not_found(format!("{pascal} {{}} not found", id)))?;
 
     let update = body.into_inner();
     let mut active: ActiveModel = item.into_active_model();
 {update_body}
 
-    let result = active.update(db.conn()).await.map_err(DbError)?;
+    let result = active.update(&txn).await.map_err(DbError)?;
+    let event = Event::updated("{singular}", {event_key});
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
     Ok(Json(result))
 }}
 
-#[delete("/{plural}/:id")]
+#[delete("{item_path}")]
 #[errors({pascal}Error)]
-pub async fn delete_{singular}(db: Db, id: Path<i32>) -> Result<Json<serde_json::Value>> {{
-    let id = id.into_inner();
-    let result = {pascal}::delete_by_id(id)
-        .exec(db.conn())
+pub async fn delete_{singular}(db: Db, events: Events, {item_args}id: Path<{id_type}>) -> {result}<Json<serde_json::Value>> {{
+{item_ids}
+    let txn = db.write().begin().await.map_err(DbError)?;{item_txn_check}
+    let result = {pascal}::delete_by_id({key}){item_filter}
+        .exec(&txn)
         .await
         .map_err(DbError)?;
     if result.rows_affected == 0 {{
-        return Err(Error::not_found(format!("{pascal} {{}} not found", id)));
+        return Err({error}::not_found(format!("{pascal} {{}} not found", id)));
     }}
+    let event = Event::deleted("{singular}", {event_key});
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
     Ok(Json(serde_json::json!({{ "deleted": id }})))
 }}
-"#,
+{parent_check_fn}"#,
+        error_import = prelude.error_import(&["Error", "Result"]),
+        result = prelude.result(),
+        error = prelude.error(),
         pascal = pascal,
         singular = singular,
         plural = plural,
         create_body = create_body,
         update_body = update_body,
+        collection_path = collection_path,
+        item_path = item_path,
+        sea_orm_imports = nested.sea_orm_imports,
+        entity_imports = nested.entity_imports,
+        model_imports = nested.model_imports,
+        list_args = nested.list_args,
+        list_body = nested.list_body,
+        create_args = nested.collection_args,
+        create_ids = nested.create_ids,
+        create_fk = nested.create_fk,
+        create_check = nested.create_check,
+        item_args = nested.item_args,
+        item_ids = nested.item_ids,
+        item_read_check = nested.item_read_check,
+        item_txn_check = nested.item_txn_check,
+        item_filter = nested.item_filter,
+        parent_check_fn = nested.parent_check_fn,
     )
 }
 
-pub(crate) fn generate_dto(pascal: &str, fields: &[FieldInfo]) -> String {
-    let create_fields: Vec<String> = fields
-        .iter()
-        .map(|f| format!("    pub {}: {},", f.name, f.rust_type))
-        .collect();
-
-    let update_fields: Vec<String> = fields
-        .iter()
-        .map(|f| format!("    pub {}: Option<{}>,", f.name, f.rust_type))
-        .collect();
-
-    // Detect non-primitive types that need imports from sea_orm prelude
-    let needs_sea_orm_import = fields.iter().any(|f| {
-        matches!(
-            f.rust_type.as_str(),
-            "Uuid" | "DateTimeUtc" | "Date" | "Decimal" | "Json"
-        )
-    });
-
-    let extra_import = if needs_sea_orm_import {
-        "use rapina::sea_orm::prelude::*;\n"
-    } else {
-        ""
+/// An `export_{plural}` handler streaming every row as a JSON array, added
+/// to the handlers file by `--with-export`.
+pub(crate) fn generate_export_handler(
+    plural: &str,
+    pascal: &str,
+    prelude: Prelude,
+    route_prefix: &str,
+    parent: Option<&ParentInfo>,
+) -> String {
+    let (collection_path, _) = resource_paths(plural, route_prefix, parent);
+    let (args, query) = match parent {
+        Some(parent) => {
+            let fk = parent.fk_column();
+            (
+                format!(", {}: Path<i32>", fk),
+                format!(
+                    "    let {fk} = {fk}.into_inner();\n    ensure_{parent}_exists(db.read(), {fk}).await?;\n    stream_json({pascal}::find().filter(Column::{column}.eq({fk})), db.read()).await",
+                    parent = parent.singular,
+                    column = to_pascal_case(&fk),
+                ),
+            )
+        }
+        None => (
+            String::new(),
+            format!("    stream_json({}::find(), db.read()).await", pascal),
+        ),
     };
 
     format!(
-        r#"use rapina::schemars::{{self, JsonSchema}};
-use serde::Deserialize;
-{extra_import}
-#[derive(Deserialize, JsonSchema)]
-pub struct Create{pascal} {{
-{create_fields}
-}}
-
-#[derive(Deserialize, JsonSchema)]
-pub struct Update{pascal} {{
-{update_fields}
+        r#"
+#[get("{collection_path}/export")]
+#[errors({pascal}Error)]
+pub async fn export_{plural}(db: Db{args}) -> {result}<rapina::http::Response<BoxBody>> {{
+{query}
 }}
 "#,
-        pascal = pascal,
-        extra_import = extra_import,
-        create_fields = create_fields.join("\n"),
-        update_fields = update_fields.join("\n"),
+        result = prelude.result(),
     )
 }
 
-pub(crate) fn generate_error(pascal: &str) -> String {
-    format!(
-        r#"use rapina::database::DbError;
-use rapina::prelude::*;
-
-pub enum {pascal}Error {{
-    DbError(DbError),
-}}
-
-impl IntoApiError for {pascal}Error {{
-    fn into_api_error(self) -> Error {{
-        match self {{
-            {pascal}Error::DbError(e) => e.into_api_error(),
-        }}
-    }}
-}}
+/// Adds the imports the export handler needs to a generated handlers file.
+fn with_export_handler(handlers: String, export_handler: &str) -> String {
+    let events_import = "use rapina::events::{Event, Events};\n";
+    let handlers = handlers.replacen(
+        events_import,
+        &format!(
+            "{}use rapina::export::stream_json;\nuse rapina::response::BoxBody;\n",
+            events_import
+        ),
+        1,
+    );
+    format!("{}{}", handlers, export_handler)
+}
 
-impl DocumentedError for {pascal}Error {{
-    fn error_variants() -> Vec<ErrorVariant> {{
-        vec![
-            ErrorVariant {{
-                status: 404,
-                code: "NOT_FOUND",
-                description: "{pascal} not found",
-            }},
-            ErrorVariant {{
-                status: 500,
-                code: "DATABASE_ERROR",
-                description: "Database operation failed",
-            }},
-        ]
-    }}
-}}
+/// Lets the generated list and get handlers return only the fields asked for
+/// with `?fields=`, added by `--with-field-selection`.
+fn with_field_selection_handlers(handlers: String, singular: &str, plural: &str) -> String {
+    let events_import = "use rapina::events::{Event, Events};\n";
+    let handlers = handlers.replacen(
+        events_import,
+        &format!(
+            "{}use rapina::fields::{{FieldSelect, PartialJson}};\n",
+            events_import
+        ),
+        1,
+    );
 
-impl From<DbError> for {pascal}Error {{
-    fn from(e: DbError) -> Self {{
-        {pascal}Error::DbError(e)
-    }}
-}}
-"#,
-        pascal = pascal,
-    )
+    let list_fn = format!("pub async fn list_{}(", plural);
+    let get_fn = format!("pub async fn get_{}(", singular);
+    let mut out = String::with_capacity(handlers.len() + 256);
+    for line in handlers.split_inclusive('\n') {
+        if line.starts_with(&list_fn) {
+            out.push_str(
+                &line
+                    .replacen(") -> ", ", fields: FieldSelect<Model>) -> ", 1)
+                    .replacen("<Paginated<Model>>", "<Paginated<PartialJson<Model>>>", 1),
+            );
+        } else if line.starts_with(&get_fn) {
+            out.push_str(
+                &line
+                    .replacen(") -> ", ", fields: FieldSelect<Model>) -> ", 1)
+                    .replacen("<Json<Model>>", "<PartialJson<Model>>", 1),
+            );
+        } else if line == "    Ok(items)\n" {
+            out.push_str("    Ok(items.map(|item| fields.apply(item)))\n");
+        } else if line == "    Ok(Json(item))\n" {
+            out.push_str("    Ok(fields.apply(item))\n");
+        } else {
+            out.push_str(line);
+        }
+    }
+    out
 }
 
-pub(crate) fn generate_schema_block(
-    pascal: &str,
-    fields: &[FieldInfo],
-    timestamps: Option<&str>,
-    primary_key: Option<&[String]>,
+/// Wraps the generated list and get responses in `Linked`, with links built
+/// from route names, added by `--with-links`.
+pub(crate) fn with_links_handlers(
+    handlers: String,
+    singular: &str,
+    plural: &str,
+    parent: Option<&ParentInfo>,
 ) -> String {
-    let schema_fields: Vec<String> = fields
-        .iter()
-        .map(|f| format!("        {}: {},", f.name, f.schema_type))
-        .collect();
+    let events_import = "use rapina::events::{Event, Events};\n";
+    let handlers = handlers.replacen(
+        events_import,
+        &format!("{}use rapina::links::{{Linked, UrlFor}};\n", events_import),
+        1,
+    );
 
-    let mut attrs = String::new();
+    // The collection link of a shallow item takes the parent id from the row
+    let (collection_params, item_params) = match parent {
+        None => (String::new(), "(\"id\", &id)".to_string()),
+        Some(parent) if parent.shallow => {
+            let fk = parent.fk_column();
+            (
+                format!("(\"{fk}\", &item.{fk})"),
+                "(\"id\", &id)".to_string(),
+            )
+        }
+        Some(parent) => {
+            let fk = parent.fk_column();
+            (
+                format!("(\"{fk}\", &{fk})"),
+                format!("(\"{fk}\", &{fk}), (\"id\", &id)"),
+            )
+        }
+    };
+    let list_params = match parent {
+        Some(parent) => format!("(\"{fk}\", &{fk})", fk = parent.fk_column()),
+        None => String::new(),
+    };
 
-    if let Some(pk_cols) = primary_key {
-        attrs.push_str(&format!("\n    #[primary_key({})]\n", pk_cols.join(", ")));
+    let list_fn = format!("pub async fn list_{}(", plural);
+    let get_fn = format!("pub async fn get_{}(", singular);
+    let mut out = String::with_capacity(handlers.len() + 512);
+    for line in handlers.split_inclusive('\n') {
+        if line.starts_with(&list_fn) {
+            out.push_str(&line.replacen(") -> ", ", urls: UrlFor) -> ", 1).replacen(
+                "<Paginated<Model>>",
+                "<Linked<Paginated<Model>>>",
+                1,
+            ));
+        } else if line.starts_with(&get_fn) {
+            out.push_str(&line.replacen(") -> ", ", urls: UrlFor) -> ", 1).replacen(
+                "<Json<Model>>",
+                "<Linked<Model>>",
+                1,
+            ));
+        } else if line == "    Ok(items)\n" {
+            out.push_str(&format!(
+                "    let links = urls.links().route(\"self\", \"list_{plural}\", &[{list_params}])?;\n    Ok(Linked::new(items, links))\n"
+            ));
+        } else if line == "    Ok(Json(item))\n" {
+            out.push_str(&format!(
+                "    let links = urls\n        .links()\n        .route(\"self\", \"get_{singular}\", &[{item_params}])?\n        .route(\"collection\", \"list_{plural}\", &[{collection_params}])?;\n    Ok(Linked::new(item, links))\n"
+            ));
+        } else {
+            out.push_str(line);
+        }
     }
+    out
+}
 
-    if let Some(ts) = timestamps {
-        attrs.push_str(&format!("\n    #[timestamps({})]\n", ts));
-    }
+/// Rewrites the handlers for a primary key stored in `pk` instead of an
+/// `id` column: events carry its value and updates leave it alone.
+fn with_primary_key_handlers(handlers: String, pk: &FieldInfo) -> String {
+    let update_check = format!(
+        "    if let Some(val) = update.{name} {{\n        active.{name} = Set(val);\n    }}\n",
+        name = pk.ident()
+    );
+    handlers
+        .replace("result.id)", &format!("result.{})", pk.ident()))
+        .replacen(&update_check, "", 1)
+}
 
-    format!(
-        r#"
-schema! {{
-    {pascal} {{{attrs}
-{fields}
-    }}
-}}
-"#,
-        pascal = pascal,
-        attrs = attrs,
-        fields = schema_fields.join("\n"),
-    )
+/// Leaves the primary key out of the update DTO, since changing it would
+/// make the update miss the row.
+fn without_updatable_primary_key(dto: String, pk: &FieldInfo) -> String {
+    let field = format!(
+        "{}    pub {}: Option<{}>,\n",
+        field_doc(pk, "    "),
+        pk.ident(),
+        pk.rust_type
+    );
+    dto.replacen(&field, "", 1)
 }
 
-pub(crate) fn generate_migration(
-    plural: &str,
-    pascal_plural: &str,
+/// Turns the generated update handler into a `PATCH` taking a JSON Merge
+/// Patch, added by `--update-style=merge-patch`. The patch is applied to the
+/// stored row and only the fields it changed are written; the primary key is
+/// never one of them.
+fn with_merge_patch_update(
+    handlers: String,
+    pascal: &str,
     fields: &[FieldInfo],
+    pk: Option<&FieldInfo>,
 ) -> String {
-    let column_defs: Vec<String> = fields
+    let handlers = handlers
+        .replacen(
+            "use rapina::extract::{Json, Path};\n",
+            "use rapina::extract::{Json, Path};\nuse rapina::patch::MergePatch;\n",
+            1,
+        )
+        .replacen(
+            "use rapina::{delete, get, post, put};",
+            "use rapina::{delete, get, patch, post};",
+            1,
+        )
+        .replacen(
+            &format!("use super::dto::{{Create{pascal}, Update{pascal}}};"),
+            &format!("use super::dto::Create{pascal};"),
+            1,
+        );
+
+    let update_check = |f: &FieldInfo| {
+        format!(
+            "    if let Some(val) = update.{name} {{\n",
+            name = f.ident()
+        )
+    };
+    let patched_fields: String = fields
         .iter()
+        .filter(|f| pk.is_none_or(|pk| pk.name != f.name))
         .map(|f| {
-            let iden = to_pascal_case(&f.name);
             format!(
-                "                    .col(ColumnDef::new({pascal_plural}::{iden}){col})",
-                pascal_plural = pascal_plural,
-                iden = iden,
-                col = f.column_method,
+                "    active.{name}.set_if_not_equals(patched.{name});\n",
+                name = f.ident()
             )
         })
         .collect();
 
-    let iden_variants: Vec<String> = fields
-        .iter()
-        .map(|f| format!("    {},", to_pascal_case(&f.name)))
-        .collect();
-
+    let mut out = String::with_capacity(handlers.len());
+    let mut lines = handlers.split_inclusive('\n').peekable();
+    while let Some(line) = lines.next() {
+        if let Some(path) = line.strip_prefix("#[put(") {
+            out.push_str(&format!(
+                "#[patch({}, consumes = \"application/merge-patch+json\")]\n",
+                path.trim_end().trim_end_matches(")]")
+            ));
+        } else if line.starts_with("pub async fn update_") {
+            out.push_str(&line.replacen(
+                &format!("body: Json<Update{pascal}>)"),
+                "patch: MergePatch<Model>)",
+                1,
+            ));
+        } else if line == "    let update = body.into_inner();\n" {
+            out.push_str("    let patched = patch.apply(&item)?;\n");
+        } else if fields.iter().any(|f| line == update_check(f)) {
+            // Skip the `if let` block down to its closing brace
+            for inner in lines.by_ref() {
+                if inner == "    }\n" {
+                    break;
+                }
+            }
+            if lines
+                .peek()
+                .is_none_or(|next| !next.starts_with("    if let Some(val) = update."))
+            {
+                out.push_str(&patched_fields);
+            }
+        } else {
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+/// Drops the `Update{Pascal}` DTO, which a merge-patch update handler has no
+/// use for.
+fn without_update_dto(dto: String, pascal: &str) -> String {
+    let Some(update) = dto.find(&format!("pub struct Update{} {{", pascal)) else {
+        return dto;
+    };
+    match dto[..update].rfind("\n\n#[derive(") {
+        Some(start) => dto[..=start].to_string(),
+        None => dto,
+    }
+}
+
+/// Documents the `Idempotency-Key` header on the generated create handler,
+/// added by `--with-idempotency`.
+fn with_idempotency_docs(handlers: String) -> String {
+    let doc = "/// Send an `Idempotency-Key` header to make retries safe: a retry with the\n\
+/// same key and body replays the first response instead of creating a\n\
+/// duplicate, and the same key with a different body returns 409 Conflict.\n\
+/// Requires `IdempotencyMiddleware` to be registered.\n";
+    handlers.replacen("#[post(", &format!("{}#[post(", doc), 1)
+}
+
+/// The parts of the handler template that change when a resource is nested
+/// under a parent: the parent id argument, the check that the parent exists,
+/// and the foreign key filter. All empty for top-level resources.
+struct NestedHandlerParts {
+    sea_orm_imports: &'static str,
+    entity_imports: String,
+    model_imports: &'static str,
+    list_args: String,
+    list_body: String,
+    collection_args: String,
+    create_ids: String,
+    create_fk: String,
+    create_check: String,
+    item_args: String,
+    item_ids: String,
+    item_read_check: String,
+    item_txn_check: String,
+    item_filter: String,
+    parent_check_fn: String,
+}
+
+impl NestedHandlerParts {
+    fn new(pascal: &str, parent: Option<&ParentInfo>, prelude: Prelude) -> Self {
+        let Some(parent) = parent else {
+            return Self {
+                sea_orm_imports: "ActiveModelTrait, EntityTrait, IntoActiveModel, Set, TransactionTrait",
+                entity_imports: pascal.to_string(),
+                model_imports: "ActiveModel, Model",
+                list_args: String::new(),
+                list_body: format!(
+                    "    let items = page.exec({}::find(), db.read()).await?;",
+                    pascal
+                ),
+                collection_args: String::new(),
+                create_ids: String::new(),
+                create_fk: String::new(),
+                create_check: String::new(),
+                item_args: String::new(),
+                item_ids: "    let id = id.into_inner();".to_string(),
+                item_read_check: String::new(),
+                item_txn_check: String::new(),
+                item_filter: String::new(),
+                parent_check_fn: String::new(),
+            };
+        };
+
+        let fk = parent.fk_column();
+        let check_fn = format!("ensure_{}_exists", parent.singular);
+        let filter = format!(
+            "\n        .filter(Column::{}.eq({}))",
+            to_pascal_case(&fk),
+            fk
+        );
+        let mut entities = [pascal, parent.pascal.as_str()];
+        entities.sort();
+
+        let mut parts = Self {
+            sea_orm_imports: "ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter, Set, TransactionTrait",
+            entity_imports: format!("{{{}, {}}}", entities[0], entities[1]),
+            model_imports: "ActiveModel, Column, Model",
+            list_args: format!(", {}: Path<i32>", fk),
+            list_body: format!(
+                "    let {fk} = {fk}.into_inner();\n    {check_fn}(db.read(), {fk}).await?;\n    let select = {pascal}::find(){filter};\n    let items = page.exec(select, db.read()).await?;",
+            ),
+            collection_args: format!("{}: Path<i32>, ", fk),
+            create_ids: format!("    let {fk} = {fk}.into_inner();\n"),
+            create_fk: format!("        {fk}: Set({fk}),\n"),
+            create_check: format!("\n    {check_fn}(&txn, {fk}).await?;"),
+            parent_check_fn: format!(
+                r#"
+/// Fails with 404 unless the parent {parent_pascal} exists.
+async fn {check_fn}(conn: &impl ConnectionTrait, {fk}: i32) -> {result}<()> {{
+    {parent_pascal}::find_by_id({fk})
+        .one(conn)
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| {error}::not_found(format!("{parent_pascal} {{}} not found", {fk})))?;
+    Ok(())
+}}
+"#,
+                parent_pascal = parent.pascal,
+                result = prelude.result(),
+                error = prelude.error(),
+            ),
+            item_args: String::new(),
+            item_ids: "    let id = id.into_inner();".to_string(),
+            item_read_check: String::new(),
+            item_txn_check: String::new(),
+            item_filter: String::new(),
+        };
+
+        // Shallow resources keep the item routes as they are at the top level
+        if !parent.shallow {
+            parts.item_args = format!("{}: Path<i32>, ", fk);
+            parts.item_ids = format!("    let ({fk}, id) = ({fk}.into_inner(), id.into_inner());");
+            parts.item_read_check = format!("\n    {check_fn}(db.read(), {fk}).await?;");
+            parts.item_txn_check = format!("\n    {check_fn}(&txn, {fk}).await?;");
+            parts.item_filter = filter;
+        }
+
+        parts
+    }
+}
+
+/// Renders a database comment as `///` doc comment lines.
+///
+/// Line endings are normalized and control characters dropped, since a bare
+/// carriage return is not allowed in a doc comment. Every line gets its own
+/// `/// ` prefix, so a line starting with `/` can't turn into a `////` comment.
+pub(crate) fn doc_comment(comment: &str, indent: &str) -> String {
+    let normalized = comment.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<String> = normalized
+        .lines()
+        .map(|line| {
+            line.chars()
+                .filter(|c| !c.is_control() || *c == '\t')
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+
+    let Some(first) = lines.iter().position(|l| !l.trim().is_empty()) else {
+        return String::new();
+    };
+    let last = lines
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .unwrap_or(first);
+
+    lines[first..=last]
+        .iter()
+        .map(|line| {
+            if line.is_empty() {
+                format!("{}///\n", indent)
+            } else {
+                format!("{}/// {}\n", indent, line)
+            }
+        })
+        .collect()
+}
+
+fn field_doc(field: &FieldInfo, indent: &str) -> String {
+    field
+        .comment
+        .as_deref()
+        .map(|c| doc_comment(c, indent))
+        .unwrap_or_default()
+}
+
+/// Renders `dto.rs`. Without `json_schema` the request types only derive
+/// `Deserialize`, for field types that don't implement `JsonSchema`.
+pub(crate) fn generate_dto(pascal: &str, fields: &[FieldInfo], json_schema: bool) -> String {
+    let create_fields: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{}    pub {}: {},",
+                field_doc(f, "    "),
+                f.ident(),
+                f.value_type()
+            )
+        })
+        .collect();
+
+    // A nullable field is updated the same way, with `null` read as "leave
+    // it alone", so its update type stays a single `Option`
+    let update_fields: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{}    pub {}: Option<{}>,",
+                field_doc(f, "    "),
+                f.ident(),
+                f.rust_type
+            )
+        })
+        .collect();
+
+    // Non-primitive types come from the sea_orm prelude, imported by name
+    let sea_orm_types: Vec<&str> = SEA_ORM_PRELUDE_TYPES
+        .into_iter()
+        .filter(|ty| {
+            fields.iter().any(|f| {
+                f.rust_type
+                    .split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .any(|word| word == *ty)
+            })
+        })
+        .collect();
+
+    let extra_import = match sea_orm_types.as_slice() {
+        [] => String::new(),
+        [ty] => format!("use rapina::sea_orm::prelude::{};\n", ty),
+        types => format!("use rapina::sea_orm::prelude::{{{}}};\n", types.join(", ")),
+    };
+
+    let (schema_import, derives) = if json_schema {
+        (
+            "use rapina::schemars::{self, JsonSchema};\n",
+            "Deserialize, JsonSchema",
+        )
+    } else {
+        ("", "Deserialize")
+    };
+
+    format!(
+        r#"{schema_import}use serde::Deserialize;
+{extra_import}
+#[derive({derives})]
+pub struct Create{pascal} {{
+{create_fields}
+}}
+
+#[derive({derives})]
+pub struct Update{pascal} {{
+{update_fields}
+}}
+"#,
+        schema_import = schema_import,
+        derives = derives,
+        pascal = pascal,
+        extra_import = extra_import,
+        create_fields = create_fields.join("\n"),
+        update_fields = update_fields.join("\n"),
+    )
+}
+
+pub(crate) fn generate_error(pascal: &str, prelude: Prelude) -> String {
+    let error_import =
+        prelude.error_import(&["DocumentedError", "Error", "ErrorVariant", "IntoApiError"]);
+
+    format!(
+        r#"use rapina::database::DbError;
+{error_import}
+pub enum {pascal}Error {{
+    DbError(DbError),
+}}
+
+impl IntoApiError for {pascal}Error {{
+    fn into_api_error(self) -> {error} {{
+        match self {{
+            {pascal}Error::DbError(e) => e.into_api_error(),
+        }}
+    }}
+}}
+
+impl DocumentedError for {pascal}Error {{
+    fn error_variants() -> Vec<ErrorVariant> {{
+        vec![
+            ErrorVariant {{
+                status: 404,
+                code: "NOT_FOUND",
+                description: "{pascal} not found",
+            }},
+            ErrorVariant {{
+                status: 500,
+                code: "DATABASE_ERROR",
+                description: "Database operation failed",
+            }},
+        ]
+    }}
+}}
+
+impl From<DbError> for {pascal}Error {{
+    fn from(e: DbError) -> Self {{
+        {pascal}Error::DbError(e)
+    }}
+}}
+"#,
+        error_import = error_import,
+        error = prelude.error(),
+        pascal = pascal,
+    )
+}
+
+pub(crate) fn generate_schema_block(
+    pascal: &str,
+    fields: &[FieldInfo],
+    timestamps: Option<&str>,
+    primary_key: Option<&[String]>,
+    indexes: &[IndexInfo],
+    doc: Option<&str>,
+) -> String {
+    schema_block(&[generate_schema_entity(
+        pascal,
+        fields,
+        timestamps,
+        primary_key,
+        indexes,
+        doc,
+        &SchemaRelations::default(),
+    )])
+}
+
+/// Wraps entities rendered by [`generate_schema_entity`] in one `schema!`
+/// block. Entities that refer to each other must share a block.
+pub(crate) fn schema_block(entities: &[String]) -> String {
+    format!("\nschema! {{\n{}}}\n", entities.join("\n"))
+}
+
+/// Relation fields of a `schema!` entity.
+#[derive(Debug, Default)]
+pub(crate) struct SchemaRelations {
+    /// belongs_to fields such as `author: User`, rendered before the columns.
+    pub belongs_to: Vec<String>,
+    /// has_many fields such as `posts: Vec<Post>`, rendered after them.
+    pub has_many: Vec<String>,
+}
+
+impl SchemaRelations {
+    /// A `post: Post` belongs_to field for the parent of a nested resource.
+    pub(crate) fn parent(parent: Option<&ParentInfo>) -> Self {
+        Self {
+            belongs_to: parent
+                .map(|p| format!("{}: {}", rust_ident(&p.singular), p.pascal))
+                .into_iter()
+                .collect(),
+            has_many: Vec::new(),
+        }
+    }
+}
+
+/// Renders one entity of a `schema!` block.
+pub(crate) fn generate_schema_entity(
+    pascal: &str,
+    fields: &[FieldInfo],
+    timestamps: Option<&str>,
+    primary_key: Option<&[String]>,
+    indexes: &[IndexInfo],
+    doc: Option<&str>,
+    relations: &SchemaRelations,
+) -> String {
+    let mut schema_fields: Vec<String> = relations
+        .belongs_to
+        .iter()
+        .map(|field| format!("        {},", field))
+        .collect();
+    schema_fields.extend(fields.iter().map(|f| {
+        // schema! can only express single-column indexes as field
+        // attributes; composite ones live in the migration only
+        let mut lines = field_doc(f, "        ");
+        let single_column =
+            |idx: &&IndexInfo| idx.columns.len() == 1 && idx.columns[0] == f.column_name();
+        if f.unique && !indexes.iter().filter(single_column).any(|idx| idx.unique) {
+            lines.push_str("        #[unique]\n");
+        }
+        for idx in indexes.iter().filter(single_column) {
+            let attr = if idx.unique { "unique" } else { "index" };
+            match &idx.name {
+                Some(name) => {
+                    lines.push_str(&format!("        #[{}(name = \"{}\")]\n", attr, name))
+                }
+                None => lines.push_str(&format!("        #[{}]\n", attr)),
+            }
+        }
+        if let Some(column) = &f.column {
+            lines.push_str(&format!("        #[column = \"{}\"]\n", column));
+        }
+        if let Some(default) = &f.default {
+            lines.push_str(&format!("        #[default = {}]\n", default));
+        }
+        lines.push_str(&format!(
+            "        {}: {},",
+            f.ident(),
+            f.schema_field_type()
+        ));
+        lines
+    }));
+    schema_fields.extend(
+        relations
+            .has_many
+            .iter()
+            .map(|field| format!("        {},", field)),
+    );
+
+    // Doc comments and entity attributes go before the entity name
+    let mut attrs = doc.map(|d| doc_comment(d, "    ")).unwrap_or_default();
+
+    if let Some(pk_cols) = primary_key {
+        attrs.push_str(&format!("    #[primary_key({})]\n", pk_cols.join(", ")));
+    }
+
+    if let Some(ts) = timestamps {
+        attrs.push_str(&format!("    #[timestamps({})]\n", ts));
+    }
+
+    format!(
+        r#"{attrs}    {pascal} {{
+{fields}
+    }}
+"#,
+        pascal = pascal,
+        attrs = attrs,
+        fields = schema_fields.join("\n"),
+    )
+}
+
+pub(crate) fn generate_migration(
+    plural: &str,
+    pascal_plural: &str,
+    fields: &[FieldInfo],
+    indexes: &[IndexInfo],
+    parent: Option<&ParentInfo>,
+    id: IdColumn,
+) -> String {
+    let fk_iden = parent.map(|p| to_pascal_case(&p.fk_column()));
+
+    let mut column_defs: Vec<String> = fk_iden
+        .iter()
+        .map(|iden| {
+            format!(
+                "                    .col(ColumnDef::new({}::{}).integer().not_null())",
+                pascal_plural, iden
+            )
+        })
+        .collect();
+    column_defs.extend(fields.iter().map(|f| {
+        let iden = to_pascal_case(f.column_name());
+        let primary_key = if id == IdColumn::Field(f.column_name()) {
+            ".primary_key()"
+        } else {
+            ""
+        };
+        format!(
+            "                    .col(ColumnDef::new({pascal_plural}::{iden}){col}{primary_key})",
+            pascal_plural = pascal_plural,
+            iden = iden,
+            col = f.column_def(),
+        )
+    }));
+
+    if let (Some(parent), Some(iden)) = (parent, &fk_iden) {
+        column_defs.push(format!(
+            r#"                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_{plural}_{fk}")
+                            .from({pascal_plural}::Table, {pascal_plural}::{iden})
+                            .to({parent_plural}::Table, {parent_plural}::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )"#,
+            plural = plural,
+            fk = parent.fk_column(),
+            pascal_plural = pascal_plural,
+            iden = iden,
+            parent_plural = parent.pascal_plural,
+        ));
+    }
+
+    let iden_variants: Vec<String> = fk_iden
+        .iter()
+        .map(|iden| format!("    {},", iden))
+        .chain(
+            fields
+                .iter()
+                .map(|f| format!("    {},", to_pascal_case(f.column_name()))),
+        )
+        .collect();
+
+    // The parent table is referenced by the foreign key
+    let parent_iden = parent
+        .map(|p| {
+            format!(
+                "\n#[derive(DeriveIden)]\nenum {} {{\n    Table,\n    Id,\n}}\n",
+                p.pascal_plural
+            )
+        })
+        .unwrap_or_default();
+
+    let index_stmts: Vec<String> = indexes
+        .iter()
+        .map(|idx| {
+            let cols: String = idx
+                .columns
+                .iter()
+                .map(|c| {
+                    format!(
+                        "\n                    .col({}::{})",
+                        pascal_plural,
+                        to_pascal_case(c)
+                    )
+                })
+                .collect();
+            let unique = if idx.unique {
+                "\n                    .unique()"
+            } else {
+                ""
+            };
+            format!(
+                r#"        manager
+            .create_index(
+                Index::create()
+                    .name("{name}")
+                    .table({pascal_plural}::Table){cols}{unique}
+                    .to_owned(),
+            )
+            .await?;
+"#,
+                name = idx.resolved_name(plural),
+                pascal_plural = pascal_plural,
+                cols = cols,
+                unique = unique,
+            )
+        })
+        .collect();
+
+    // Without indexes the create_table future is returned directly
+    let create_end = if index_stmts.is_empty() {
+        "            .await".to_string()
+    } else {
+        format!(
+            "            .await?;\n\n{}\n        Ok(())",
+            index_stmts.join("\n")
+        )
+    };
+
     let readable_name = format!("create {}", plural);
 
+    let id_column = id
+        .column_method()
+        .map(|method| {
+            format!(
+                "                    .col(\n                        ColumnDef::new({}::Id)\n                            {}\n                            .primary_key(),\n                    )\n",
+                pascal_plural, method
+            )
+        })
+        .unwrap_or_default();
+
     format!(
         r#"//! Migration: {readable_name}
 
@@ -354,17 +1531,10 @@ impl MigrationTrait for Migration {{
             .create_table(
                 Table::create()
                     .table({pascal_plural}::Table)
-                    .col(
-                        ColumnDef::new({pascal_plural}::Id)
-                            .integer()
-                            .not_null()
-                            .auto_increment()
-                            .primary_key(),
-                    )
-{column_defs}
+{id_column}{column_defs}
                     .to_owned(),
             )
-            .await
+{create_end}
     }}
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {{
@@ -377,204 +1547,2452 @@ impl MigrationTrait for Migration {{
 #[derive(DeriveIden)]
 enum {pascal_plural} {{
     Table,
-    Id,
-{iden_variants}
+{id_variant}{iden_variants}
 }}
-"#,
+{parent_iden}"#,
         readable_name = readable_name,
         pascal_plural = pascal_plural,
+        id_column = id_column,
+        id_variant = if id_column.is_empty() {
+            ""
+        } else {
+            "    Id,\n"
+        },
         column_defs = column_defs.join("\n"),
+        create_end = create_end,
         iden_variants = iden_variants.join("\n"),
+        parent_iden = parent_iden,
     )
 }
 
-pub(crate) fn update_entity_file(
-    pascal: &str,
-    fields: &[FieldInfo],
-    timestamps: Option<&str>,
-    primary_key: Option<&[String]>,
-) -> Result<(), String> {
-    let entity_path = Path::new("src/entity.rs");
-    let schema_block = generate_schema_block(pascal, fields, timestamps, primary_key);
-
-    if entity_path.exists() {
-        let content = fs::read_to_string(entity_path)
-            .map_err(|e| format!("Failed to read entity.rs: {}", e))?;
-
-        // Ensure schema! macro is importable
-        let needs_import =
-            !content.contains("use rapina::prelude::*") && !content.contains("use rapina::schema");
-        let prefix = if needs_import {
-            "use rapina::schema;\n"
-        } else {
-            ""
-        };
+const CARGO_PATH: &str = "Cargo.toml";
+const ENTITY_PATH: &str = "src/entity.rs";
+const MIGRATIONS_DIR: &str = "src/migrations";
 
-        let updated = format!("{}{}{}", prefix, content.trim_end(), schema_block);
-        fs::write(entity_path, updated).map_err(|e| format!("Failed to write entity.rs: {}", e))?;
-    } else {
-        let content = format!("use rapina::prelude::*;\n{}", schema_block);
-        fs::write(entity_path, content)
-            .map_err(|e| format!("Failed to create entity.rs: {}", e))?;
-    }
+/// A file staged by [`Changes`].
+pub(crate) struct StagedFile {
+    pub path: PathBuf,
+    /// The content on disk; `None` for a new file.
+    pub before: Option<String>,
+    pub after: String,
+}
 
-    println!("  {} Updated {}", "✓".green(), "src/entity.rs".cyan());
-    Ok(())
+/// Files a scaffold writes, held in memory until [`Changes::apply`], so a
+/// dry run can show them and a failed step leaves the project untouched.
+///
+/// Paths are relative to the project root. Reads see earlier staged writes.
+pub(crate) struct Changes {
+    root: PathBuf,
+    files: Vec<StagedFile>,
 }
 
-pub(crate) fn create_migration_file(
-    plural: &str,
-    pascal_plural: &str,
-    fields: &[FieldInfo],
-) -> Result<(), String> {
-    let migrations_dir = Path::new("src/migrations");
+impl Default for Changes {
+    fn default() -> Self {
+        Self::new(".")
+    }
+}
 
-    if !migrations_dir.exists() {
-        fs::create_dir_all(migrations_dir)
-            .map_err(|e| format!("Failed to create migrations directory: {}", e))?;
-        println!("  {} Created {}", "✓".green(), "src/migrations/".cyan());
+impl Changes {
+    pub(crate) fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            files: Vec::new(),
+        }
     }
 
-    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
-    let migration_name = format!("create_{}", plural);
-    let module_name = format!("m{}_{}", timestamp, migration_name);
-    let filename = format!("{}.rs", module_name);
-    let filepath = migrations_dir.join(&filename);
-
-    let template = generate_migration(plural, pascal_plural, fields);
-    fs::write(&filepath, template).map_err(|e| format!("Failed to write migration file: {}", e))?;
-    println!(
-        "  {} Created {}",
-        "✓".green(),
-        format!("src/migrations/{}", filename).cyan()
-    );
+    /// The content of `path`: as staged, or else as on disk.
+    pub(crate) fn read(&self, path: impl AsRef<Path>) -> Option<String> {
+        let path = path.as_ref();
+        match self.files.iter().find(|file| file.path == path) {
+            Some(file) => Some(file.after.clone()),
+            None => fs::read_to_string(self.root.join(path)).ok(),
+        }
+    }
 
-    super::migrate::update_mod_rs(migrations_dir, &module_name)?;
+    /// Whether a file or directory exists at `path`, on disk or staged.
+    pub(crate) fn exists(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        self.root.join(path).exists() || self.files.iter().any(|file| file.path.starts_with(path))
+    }
+
+    pub(crate) fn write(&mut self, path: impl Into<PathBuf>, content: impl Into<String>) {
+        let path = path.into();
+        let content = content.into();
+        match self.files.iter_mut().find(|file| file.path == path) {
+            Some(file) => file.after = content,
+            None => {
+                let before = fs::read_to_string(self.root.join(&path)).ok();
+                self.files.push(StagedFile {
+                    path,
+                    before,
+                    after: content,
+                });
+            }
+        }
+    }
+
+    #[cfg(feature = "import")]
+    pub(crate) fn files(&self) -> &[StagedFile] {
+        &self.files
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    #[cfg(any(feature = "import", test))]
+    /// A unified diff of every staged file against the disk.
+    pub(crate) fn preview(&self) -> String {
+        self.files
+            .iter()
+            .map(|file| {
+                let path = file.path.display();
+                let old = match file.before {
+                    Some(_) => format!("a/{}", path),
+                    None => "/dev/null".to_string(),
+                };
+                similar::TextDiff::from_lines(file.before.as_deref().unwrap_or(""), &file.after)
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&old, &format!("b/{}", path))
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Writes every staged file, creating directories as needed.
+    pub(crate) fn apply(&self) -> Result<(), String> {
+        for file in &self.files {
+            let path = self.root.join(&file.path);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            }
+            fs::write(&path, &file.after)
+                .map_err(|e| format!("Failed to write {}: {}", file.path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Reports each staged file as created or updated.
+    pub(crate) fn report(&self) {
+        for file in &self.files {
+            let path = file.path.display().to_string();
+            match file.before {
+                Some(_) => output::updated(&path),
+                None => output::created(&path),
+            }
+        }
+    }
+
+    /// Writes the staged files and reports them.
+    fn commit(self) -> Result<(), String> {
+        self.apply()?;
+        self.report();
+        Ok(())
+    }
+}
+
+pub(crate) fn update_entity_file(
+    pascal: &str,
+    fields: &[FieldInfo],
+    timestamps: Option<&str>,
+    primary_key: Option<&[String]>,
+    indexes: &[IndexInfo],
+    doc: Option<&str>,
+    prelude: Prelude,
+) -> Result<(), String> {
+    append_schema_block(
+        &generate_schema_block(pascal, fields, timestamps, primary_key, indexes, doc),
+        prelude,
+    )
+}
+
+/// Appends a `schema!` block, as rendered by [`schema_block`], to
+/// `src/entity.rs`.
+pub(crate) fn append_schema_block(schema_block: &str, prelude: Prelude) -> Result<(), String> {
+    let mut changes = Changes::default();
+    stage_schema_block(&mut changes, schema_block, prelude);
+    changes.commit()
+}
+
+/// Like [`append_schema_block`], staging the write in `changes`.
+pub(crate) fn stage_schema_block(changes: &mut Changes, schema_block: &str, prelude: Prelude) {
+    let updated = match changes.read(ENTITY_PATH) {
+        Some(content) => {
+            // Ensure schema! macro is importable
+            let needs_import = !content.contains("use rapina::prelude::*")
+                && !content.contains("use rapina::schema");
+            let prefix = if needs_import {
+                "use rapina::schema;\n"
+            } else {
+                ""
+            };
+            format!("{}{}{}", prefix, content.trim_end(), schema_block)
+        }
+        None => {
+            let import = match prelude {
+                Prelude::Full => "use rapina::prelude::*;",
+                Prelude::Minimal => "use rapina::schema;",
+            };
+            format!("{}\n{}", import, schema_block)
+        }
+    };
+    changes.write(ENTITY_PATH, updated);
+}
+
+/// Marks the `pascal` entity in `content` with `#[no_json_schema]`, so its
+/// `Model` doesn't derive `JsonSchema`.
+pub(crate) fn mark_no_json_schema(content: &str, pascal: &str) -> Option<String> {
+    add_entity_attr(content, pascal, "#[no_json_schema]")
+}
+
+/// Marks the `pascal` entity in `content` with `#[id(Uuid)]`, so its `id`
+/// is a UUID generated on insert.
+#[cfg(any(feature = "import", test))]
+pub(crate) fn mark_uuid_id(content: &str, pascal: &str) -> Option<String> {
+    add_entity_attr(content, pascal, "#[id(Uuid)]")
+}
+
+/// Inserts `attr` right above the `pascal` entity of a `schema!` block.
+fn add_entity_attr(content: &str, pascal: &str, attr: &str) -> Option<String> {
+    let entity_line = format!("{} {{", pascal);
+    let mut in_block = false;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed == "schema! {" {
+            in_block = true;
+        } else if in_block && trimmed == entity_line {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            return Some(format!(
+                "{}{}{}\n{}",
+                &content[..offset],
+                indent,
+                attr,
+                &content[offset..]
+            ));
+        } else if in_block && line.trim_end() == "}" {
+            in_block = false;
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Marks the `pascal` entity in `content` with `#[table_name = "..."]`, for
+/// resources named differently from their table.
+#[cfg(any(feature = "import", test))]
+pub(crate) fn mark_table_name(content: &str, pascal: &str, table: &str) -> Option<String> {
+    add_entity_attr(content, pascal, &format!("#[table_name = \"{}\"]", table))
+}
+
+/// Adds `#[no_json_schema]` to the `pascal` entity in `src/entity.rs`.
+pub(crate) fn disable_entity_json_schema(pascal: &str) -> Result<(), String> {
+    edit_entity_file(pascal, |content| mark_no_json_schema(content, pascal))
+}
+
+fn edit_entity_file(pascal: &str, edit: impl FnOnce(&str) -> Option<String>) -> Result<(), String> {
+    let mut changes = Changes::default();
+    stage_entity_edit(&mut changes, pascal, edit)?;
+    changes.apply()
+}
+
+/// Applies `edit` to the file whose `schema!` block defines `pascal`,
+/// staging the result in `changes`.
+pub(crate) fn stage_entity_edit(
+    changes: &mut Changes,
+    pascal: &str,
+    edit: impl FnOnce(&str) -> Option<String>,
+) -> Result<(), String> {
+    let entity_path =
+        find_entity_file(changes, pascal).unwrap_or_else(|| PathBuf::from(ENTITY_PATH));
+    let content = changes
+        .read(&entity_path)
+        .ok_or_else(|| format!("Failed to read {}", entity_path.display()))?;
+    let updated = edit(&content)
+        .ok_or_else(|| format!("Entity '{}' not found in {}", pascal, entity_path.display()))?;
+    changes.write(entity_path, updated);
+    Ok(())
+}
+
+/// Directory of the per-resource entity files written by
+/// `rapina add resource --split-entity`.
+const ENTITIES_DIR: &str = "src/entities";
+
+/// The file whose `schema!` block defines `pascal`: `src/entity.rs` or one
+/// of the files in `src/entities/`.
+fn find_entity_file(changes: &Changes, pascal: &str) -> Option<PathBuf> {
+    let mut files = vec![PathBuf::from(ENTITY_PATH)];
+    if let Ok(entries) = fs::read_dir(changes.root.join(ENTITIES_DIR)) {
+        let mut split: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| Path::new(ENTITIES_DIR).join(entry.file_name()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .collect();
+        split.sort();
+        files.extend(split);
+    }
+
+    files.into_iter().find(|path| {
+        changes
+            .read(path)
+            .is_some_and(|content| schema_block_end(&content, pascal).is_some())
+    })
+}
+
+/// Byte offset of the closing brace of the `schema!` block that defines
+/// `pascal`, as written by [`generate_schema_block`].
+fn schema_block_end(content: &str, pascal: &str) -> Option<usize> {
+    let entity_line = format!("{} {{", pascal);
+    let mut offset = 0;
+    let mut in_block = false;
+    let mut found = false;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed == "schema! {" {
+            in_block = true;
+            found = false;
+        } else if in_block && trimmed == entity_line {
+            found = true;
+        } else if in_block && line.trim_end() == "}" {
+            if found {
+                return Some(offset);
+            }
+            in_block = false;
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Adds a child entity with a belongs_to field for `parent` to the
+/// `schema!` block that defines the parent, which resolves the reference
+/// without an `extern entity` declaration.
+pub(crate) fn insert_child_entity(
+    content: &str,
+    pascal: &str,
+    fields: &[FieldInfo],
+    parent: &ParentInfo,
+) -> Option<String> {
+    let end = schema_block_end(content, &parent.pascal)?;
+    let entity = generate_schema_entity(
+        pascal,
+        fields,
+        None,
+        None,
+        &[],
+        None,
+        &SchemaRelations::parent(Some(parent)),
+    );
+    Some(format!(
+        "{}\n{}{}",
+        &content[..end],
+        entity,
+        &content[end..]
+    ))
+}
+
+/// Fails unless a `schema!` block defines the parent of a nested resource.
+/// A child with its own entity file declares the parent `extern`, which
+/// only resolves to another file of `src/entities/`.
+pub(crate) fn verify_parent_entity(parent: &ParentInfo, split: bool) -> Result<(), String> {
+    match find_entity_file(&Changes::default(), &parent.pascal) {
+        Some(path) if split && path == Path::new("src/entity.rs") => Err(format!(
+            "Parent resource '{}' is defined in src/entity.rs, but --split-entity needs it in {}/{}.rs. Drop --split-entity to add the child to the parent's schema! block",
+            parent.singular, ENTITIES_DIR, parent.singular
+        )),
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "Parent resource '{}' not found in src/entity.rs or {}/. Create it first with: rapina add resource {} <field:type> ...",
+            parent.singular, ENTITIES_DIR, parent.singular
+        )),
+    }
+}
+
+pub(crate) fn add_child_entity(
+    pascal: &str,
+    fields: &[FieldInfo],
+    parent: &ParentInfo,
+) -> Result<(), String> {
+    let entity_path = find_entity_file(&Changes::default(), &parent.pascal).ok_or_else(|| {
+        format!(
+            "Parent resource '{}' not found in src/entity.rs or {}/",
+            parent.singular, ENTITIES_DIR
+        )
+    })?;
+    let content = fs::read_to_string(&entity_path)
+        .map_err(|e| format!("Failed to read {}: {}", entity_path.display(), e))?;
+    let updated = insert_child_entity(&content, pascal, fields, parent).ok_or_else(|| {
+        format!(
+            "Parent resource '{}' not found in {}",
+            parent.singular,
+            entity_path.display()
+        )
+    })?;
+    fs::write(&entity_path, updated)
+        .map_err(|e| format!("Failed to write {}: {}", entity_path.display(), e))?;
+
+    output::updated(&entity_path.display().to_string());
+    Ok(())
+}
+
+/// Renders `src/entities/{singular}.rs`: a `schema!` block of its own for
+/// the `pascal` entity, declaring its parent `extern`.
+pub(crate) fn generate_entity_module(
+    pascal: &str,
+    fields: &[FieldInfo],
+    parent: Option<&ParentInfo>,
+    prelude: Prelude,
+) -> String {
+    let import = match prelude {
+        Prelude::Full => "use rapina::prelude::*;",
+        Prelude::Minimal => "use rapina::schema;",
+    };
+    let externs = match parent {
+        Some(parent) => format!("    extern entity {};\n\n", parent.pascal),
+        None => String::new(),
+    };
+    format!(
+        "{}\n\nschema! {{\n{}{}}}\n",
+        import,
+        externs,
+        generate_schema_entity(
+            pascal,
+            fields,
+            None,
+            None,
+            &[],
+            None,
+            &SchemaRelations::parent(parent)
+        )
+    )
+}
 
+/// Writes the `pascal` entity to `src/entities/{singular}.rs`, declares the
+/// file in `src/entities/mod.rs` and re-exports it from `src/entity.rs`, so
+/// handlers keep importing `crate::entity::{singular}`.
+pub(crate) fn create_entity_module(
+    singular: &str,
+    pascal: &str,
+    fields: &[FieldInfo],
+    parent: Option<&ParentInfo>,
+    prelude: Prelude,
+) -> Result<(), String> {
+    if let Some(path) = find_entity_file(&Changes::default(), pascal) {
+        return Err(format!(
+            "Entity '{}' is already defined in {}",
+            pascal,
+            path.display()
+        ));
+    }
+    let entity_path = format!("{}/{}.rs", ENTITIES_DIR, singular);
+    if Path::new(&entity_path).exists() {
+        return Err(format!("{} already exists", entity_path));
+    }
+
+    fs::create_dir_all(ENTITIES_DIR)
+        .map_err(|e| format!("Failed to create {}: {}", ENTITIES_DIR, e))?;
+    fs::write(
+        &entity_path,
+        generate_entity_module(pascal, fields, parent, prelude),
+    )
+    .map_err(|e| format!("Failed to create {}: {}", entity_path, e))?;
+    output::created(&entity_path);
+
+    append_line(
+        &format!("{}/mod.rs", ENTITIES_DIR),
+        &format!("pub mod {};", singular),
+    )?;
+    append_line(
+        "src/entity.rs",
+        &format!("pub use crate::entities::{}::*;", singular),
+    )
+}
+
+/// Appends `line` to the file at `path`, creating it if need be.
+fn append_line(path: &str, line: &str) -> Result<(), String> {
+    let existing = Path::new(path).exists();
+    let content = fs::read_to_string(path).unwrap_or_default();
+    if content.lines().any(|l| l.trim() == line) {
+        return Ok(());
+    }
+
+    let mut updated = content.trim_end().to_string();
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    updated.push_str(line);
+    updated.push('\n');
+    fs::write(path, updated).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    if existing {
+        output::updated(path);
+    } else {
+        output::created(path);
+    }
     Ok(())
 }
 
+pub(crate) fn create_migration_file(
+    plural: &str,
+    pascal_plural: &str,
+    fields: &[FieldInfo],
+    indexes: &[IndexInfo],
+    parent: Option<&ParentInfo>,
+    id: IdColumn,
+) -> Result<(), String> {
+    let mut changes = Changes::default();
+    stage_migration_file(
+        &mut changes,
+        plural,
+        pascal_plural,
+        fields,
+        indexes,
+        parent,
+        id,
+    );
+    changes.commit()
+}
+
+/// Like [`create_migration_file`], staging the migration and the
+/// `src/migrations/mod.rs` update in `changes`.
+pub(crate) fn stage_migration_file(
+    changes: &mut Changes,
+    plural: &str,
+    pascal_plural: &str,
+    fields: &[FieldInfo],
+    indexes: &[IndexInfo],
+    parent: Option<&ParentInfo>,
+    id: IdColumn,
+) {
+    let migrations_dir = Path::new(MIGRATIONS_DIR);
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let module_name = format!("m{}_create_{}", timestamp, plural);
+
+    changes.write(
+        migrations_dir.join(format!("{}.rs", module_name)),
+        generate_migration(plural, pascal_plural, fields, indexes, parent, id),
+    );
+
+    let mod_path = migrations_dir.join("mod.rs");
+    let mod_rs =
+        super::migrate::migrations_mod_rs(changes.read(&mod_path).as_deref(), &module_name);
+    changes.write(mod_path, mod_rs);
+}
+
+/// How a feature module's handlers are generated.
+pub(crate) struct ModuleOptions<'a> {
+    pub prelude: Prelude,
+    pub route_prefix: &'a str,
+    pub parent: Option<&'a ParentInfo>,
+    pub with_export: bool,
+    pub with_field_selection: bool,
+    pub with_links: bool,
+    pub with_idempotency: bool,
+    pub json_schema: bool,
+    pub update_style: UpdateStyle,
+    /// The table's primary key; imported tables may key on a UUID `id` or
+    /// one of their fields.
+    pub id: IdColumn<'a>,
+}
+
 pub(crate) fn create_feature_module(
     singular: &str,
     plural: &str,
     pascal: &str,
     fields: &[FieldInfo],
+    options: &ModuleOptions,
+) -> Result<(), String> {
+    let mut changes = Changes::default();
+    stage_feature_module(&mut changes, singular, plural, pascal, fields, options)?;
+    changes.commit()
+}
+
+/// Like [`create_feature_module`], staging the module's files in `changes`.
+pub(crate) fn stage_feature_module(
+    changes: &mut Changes,
+    singular: &str,
+    plural: &str,
+    pascal: &str,
+    fields: &[FieldInfo],
+    options: &ModuleOptions,
 ) -> Result<(), String> {
+    let ModuleOptions {
+        prelude,
+        route_prefix,
+        parent,
+        ..
+    } = *options;
     let module_dir = Path::new("src").join(plural);
 
-    if module_dir.exists() {
+    if changes.exists(&module_dir) {
         return Err(format!(
             "Directory 'src/{}/' already exists. Remove it first or choose a different resource name.",
             plural
         ));
     }
 
-    fs::create_dir_all(&module_dir)
-        .map_err(|e| format!("Failed to create module directory: {}", e))?;
-    println!(
-        "  {} Created {}",
-        "✓".green(),
-        format!("src/{}/", plural).cyan()
+    changes.write(module_dir.join("mod.rs"), generate_mod_rs());
+
+    let mut handlers = generate_handlers(
+        singular,
+        plural,
+        pascal,
+        fields,
+        options.id.rust_type(fields),
+        prelude,
+        route_prefix,
+        parent,
     );
+    if options.with_export {
+        let export_handler = generate_export_handler(plural, pascal, prelude, route_prefix, parent);
+        handlers = with_export_handler(handlers, &export_handler);
+    }
+    if options.with_field_selection {
+        handlers = with_field_selection_handlers(handlers, singular, plural);
+    }
+    if options.with_links {
+        handlers = with_links_handlers(handlers, singular, plural, parent);
+    }
+    if options.with_idempotency {
+        handlers = with_idempotency_docs(handlers);
+    }
+    if options.update_style == UpdateStyle::MergePatch {
+        handlers = with_merge_patch_update(handlers, pascal, fields, options.id.field(fields));
+    }
+    if let Some(pk) = options.id.field(fields) {
+        handlers = with_primary_key_handlers(handlers, pk);
+    }
+    changes.write(module_dir.join("handlers.rs"), handlers);
 
-    fs::write(module_dir.join("mod.rs"), generate_mod_rs())
-        .map_err(|e| format!("Failed to write mod.rs: {}", e))?;
-    println!(
-        "  {} Created {}",
-        "✓".green(),
-        format!("src/{}/mod.rs", plural).cyan()
-    );
+    let mut dto = generate_dto(pascal, fields, options.json_schema);
+    if let Some(pk) = options.id.field(fields) {
+        dto = without_updatable_primary_key(dto, pk);
+    }
+    if options.update_style == UpdateStyle::MergePatch {
+        dto = without_update_dto(dto, pascal);
+    }
+    changes.write(module_dir.join("dto.rs"), dto);
+    changes.write(module_dir.join("error.rs"), generate_error(pascal, prelude));
 
-    fs::write(
-        module_dir.join("handlers.rs"),
-        generate_handlers(singular, plural, pascal, fields),
+    Ok(())
+}
+
+const ROUTES_PATH: &str = "src/routes.rs";
+
+/// The `.at(...)` calls registering a resource's five handlers, preceded by
+/// a `// {plural}` marker line, plus the export handler when generated.
+pub(crate) fn generate_route_registration(
+    singular: &str,
+    plural: &str,
+    route_prefix: &str,
+    parent: Option<&ParentInfo>,
+    with_export: bool,
+) -> String {
+    let (collection_path, item_path) = resource_paths(plural, route_prefix, parent);
+    let export = if with_export {
+        format!(
+            "        .get(\"{collection_path}/export\", crate::{plural}::handlers::export_{plural})\n"
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        r#"        // {plural}
+        .at("{collection_path}", |r| {{
+            r.get(crate::{plural}::handlers::list_{plural})
+                .post(crate::{plural}::handlers::create_{singular})
+        }})
+        .at("{item_path}", |r| {{
+            r.get(crate::{plural}::handlers::get_{singular})
+                .put(crate::{plural}::handlers::update_{singular})
+                .delete(crate::{plural}::handlers::delete_{singular})
+        }})
+{export}"#
     )
-    .map_err(|e| format!("Failed to write handlers.rs: {}", e))?;
-    println!(
-        "  {} Created {}",
-        "✓".green(),
-        format!("src/{}/handlers.rs", plural).cyan()
-    );
+}
 
-    fs::write(module_dir.join("dto.rs"), generate_dto(pascal, fields))
-        .map_err(|e| format!("Failed to write dto.rs: {}", e))?;
-    println!(
-        "  {} Created {}",
-        "✓".green(),
-        format!("src/{}/dto.rs", plural).cyan()
-    );
+/// Registers the update handler of a [`generate_route_registration`] block
+/// under the method `update_style` calls for.
+pub(crate) fn with_update_route(registration: String, update_style: UpdateStyle) -> String {
+    match update_style {
+        UpdateStyle::Put => registration,
+        UpdateStyle::MergePatch => registration.replacen(".put(crate::", ".patch(crate::", 1),
+    }
+}
 
-    fs::write(module_dir.join("error.rs"), generate_error(pascal))
-        .map_err(|e| format!("Failed to write error.rs: {}", e))?;
-    println!(
-        "  {} Created {}",
-        "✓".green(),
-        format!("src/{}/error.rs", plural).cyan()
-    );
+/// `src/routes.rs` with a `register` function chaining the given
+/// registrations, in order.
+#[cfg(any(feature = "import", test))]
+pub(crate) fn generate_routes_module(registrations: &[String], prelude: Prelude) -> String {
+    format!(
+        r#"//! Route registration for scaffolded resources.
+//!
+//! Generated by `rapina import database`, which rewrites this file on every
+//! run. `rapina add resource` appends new resources to it.
 
-    Ok(())
+{prelude_import}
+
+pub fn register(router: Router) -> Router {{
+    router
+{registrations}}}
+"#,
+        prelude_import = prelude.import(),
+        registrations = registrations.concat(),
+    )
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Appends a registration to the end of the `register` chain.
+///
+/// Returns the content unchanged when the resource is already registered,
+/// and `None` when the file doesn't end with the `register` function.
+pub(crate) fn append_route_registration(
+    content: &str,
+    plural: &str,
+    registration: &str,
+) -> Option<String> {
+    if content.contains(&format!("        // {}\n", plural)) {
+        return Some(content.to_string());
+    }
 
-    #[test]
-    fn test_singularize() {
-        assert_eq!(singularize("users"), "user");
-        assert_eq!(singularize("posts"), "post");
-        assert_eq!(singularize("categories"), "category");
-        assert_eq!(singularize("addresses"), "address");
-        assert_eq!(singularize("boxes"), "box");
-        assert_eq!(singularize("buzzes"), "buzz");
-        assert_eq!(singularize("boss"), "boss");
-        assert_eq!(singularize("status"), "statu"); // naive, acceptable
+    if !content.contains("pub fn register(router: Router) -> Router") {
+        return None;
     }
+    let chain = content.trim_end().strip_suffix('}')?.trim_end();
+    Some(format!("{}\n{}}}\n", chain, registration))
+}
 
-    #[test]
-    fn test_generate_schema_block_with_timestamps() {
-        let fields = vec![FieldInfo {
-            name: "title".to_string(),
-            rust_type: "String".to_string(),
-            schema_type: "String".to_string(),
-            column_method: String::new(),
-        }];
+/// Stages `src/routes.rs`, written from scratch, in `changes`.
+#[cfg(feature = "import")]
+pub(crate) fn stage_routes_module(
+    changes: &mut Changes,
+    registrations: &[String],
+    prelude: Prelude,
+) {
+    changes.write(ROUTES_PATH, generate_routes_module(registrations, prelude));
+}
 
-        let block = generate_schema_block("Post", &fields, None, None);
-        assert!(block.contains("schema! {"));
-        assert!(block.contains("Post {"));
-        assert!(block.contains("title: String,"));
-        assert!(!block.contains("#[timestamps"));
+/// Appends a resource to `src/routes.rs` when the file exists.
+///
+/// Returns `true` when the routes were registered there.
+pub(crate) fn update_routes_module(plural: &str, registration: &str) -> Result<bool, String> {
+    let path = Path::new(ROUTES_PATH);
+    if !path.exists() {
+        return Ok(false);
+    }
 
-        let block = generate_schema_block("Post", &fields, Some("none"), None);
-        assert!(block.contains("#[timestamps(none)]"));
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read routes.rs: {}", e))?;
+    let Some(updated) = append_route_registration(&content, plural, registration) else {
+        output::warn(format!(
+            "{} has no trailing register function -- add the routes by hand",
+            ROUTES_PATH
+        ));
+        return Ok(false);
+    };
+    fs::write(path, updated).map_err(|e| format!("Failed to update routes.rs: {}", e))?;
+    output::updated(ROUTES_PATH);
+    Ok(true)
+}
 
-        let block = generate_schema_block("Post", &fields, Some("created_at"), None);
-        assert!(block.contains("#[timestamps(created_at)]"));
+/// Where a scaffolded extractor reads its value from.
+pub(crate) enum ExtractorSource {
+    Header(String),
+    Query(String),
+}
+
+impl ExtractorSource {
+    pub(crate) fn parse(input: &str) -> Result<Self, String> {
+        let (kind, key) = input.split_once(':').ok_or_else(|| {
+            format!(
+                "Invalid source '{}'. Expected 'header:<name>' or 'query:<name>'",
+                input
+            )
+        })?;
+
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("Source '{}' is missing a name", input));
+        }
+
+        match kind.trim() {
+            "header" => Ok(ExtractorSource::Header(key.to_lowercase())),
+            "query" => Ok(ExtractorSource::Query(key.to_string())),
+            other => Err(format!(
+                "Unknown source kind '{}'. Supported kinds: header, query",
+                other
+            )),
+        }
     }
+}
 
-    #[test]
-    fn test_generate_schema_block_with_primary_key() {
-        let fields = vec![
-            FieldInfo {
-                name: "user_id".to_string(),
-                rust_type: "i32".to_string(),
-                schema_type: "i32".to_string(),
-                column_method: ".integer().not_null()".to_string(),
-            },
-            FieldInfo {
-                name: "role_id".to_string(),
-                rust_type: "i32".to_string(),
-                schema_type: "i32".to_string(),
-                column_method: ".integer().not_null()".to_string(),
-            },
-        ];
+pub(crate) fn generate_extractor(
+    pascal: &str,
+    source: &ExtractorSource,
+    prelude: Prelude,
+) -> String {
+    let error = prelude.error();
+    let result = prelude.result();
+    let prelude_import = prelude.import();
 
-        let pk = vec!["user_id".to_string(), "role_id".to_string()];
-        let block = generate_schema_block("UsersRole", &fields, Some("none"), Some(&pk));
-        assert!(block.contains("#[primary_key(user_id, role_id)]"));
-        assert!(block.contains("#[timestamps(none)]"));
-        assert!(block.contains("user_id: i32,"));
-        assert!(block.contains("role_id: i32,"));
+    let (doc, extract_body, test_present, test_missing, missing_status) = match source {
+        ExtractorSource::Header(header) => (
+            format!(
+                "/// Extracts the `{header}` header from the request.\n///\n/// Rejects the request with 401 Unauthorized when the header is missing."
+            ),
+            format!(
+                r#"        let value = parts
+            .headers
+            .get("{header}")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| {error}::unauthorized("missing {header} header"))?;
+        Ok({pascal}(value.to_string()))"#
+            ),
+            format!(r#"client.get("/").header("{header}", "secret").send().await"#),
+            r#"client.get("/").send().await"#.to_string(),
+            "UNAUTHORIZED",
+        ),
+        ExtractorSource::Query(param) => (
+            format!(
+                "/// Extracts the `{param}` query parameter from the request.\n///\n/// Rejects the request with 400 Bad Request when the parameter is missing."
+            ),
+            format!(
+                r#"        let Query(query) =
+            Query::<HashMap<String, String>>::from_request_parts(parts, params, state).await?;
+        let value = query
+            .get("{param}")
+            .ok_or_else(|| {error}::bad_request("missing {param} query parameter"))?;
+        Ok({pascal}(value.clone()))"#
+            ),
+            format!(r#"client.get("/?{param}=secret").send().await"#),
+            r#"client.get("/").send().await"#.to_string(),
+            "BAD_REQUEST",
+        ),
+    };
+
+    let (extra_import, params_arg, state_arg) = match source {
+        ExtractorSource::Header(_) => ("", "_params", "_state"),
+        ExtractorSource::Query(_) => ("use std::collections::HashMap;\n", "params", "state"),
+    };
+
+    format!(
+        r#"use rapina::extract::{{FromRequestParts, PathParams}};
+{prelude_import}
+use rapina::state::AppState;
+{extra_import}use std::sync::Arc;
+
+{doc}
+#[derive(Debug, Clone)]
+pub struct {pascal}(pub String);
+
+impl FromRequestParts for {pascal} {{
+    async fn from_request_parts(
+        parts: &rapina::http::request::Parts,
+        {params_arg}: &PathParams,
+        {state_arg}: &Arc<AppState>,
+    ) -> {result}<Self> {{
+{extract_body}
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+    use rapina::testing::TestClient;
+
+    fn app() -> Rapina {{
+        Rapina::new()
+            .with_introspection(false)
+            .router(Router::new().route(Method::GET, "/", |req, params, state| async move {{
+                let (parts, _) = req.into_parts();
+                {pascal}::from_request_parts(&parts, &params, &state)
+                    .await
+                    .map(|{pascal}(value)| value)
+            }}))
+    }}
+
+    #[tokio::test]
+    async fn test_extracts_value() {{
+        let client = TestClient::new(app()).await;
+        let response = {test_present};
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text(), "secret");
+    }}
+
+    #[tokio::test]
+    async fn test_rejects_missing_value() {{
+        let client = TestClient::new(app()).await;
+        let response = {test_missing};
+
+        assert_eq!(response.status(), StatusCode::{missing_status});
+    }}
+}}
+"#
+    )
+}
+
+pub(crate) fn generate_policies(prelude: Prelude) -> String {
+    let error = prelude.error();
+    let result = prelude.result();
+    let prelude_import = prelude.import();
+
+    format!(
+        r#"//! Authorization policies, used with `#[authorize(...)]` on handlers.
+//!
+//! ```ignore
+//! #[delete("/users/:id")]
+//! #[authorize(any(policies::AdminOnly, policies::Owner))]
+//! async fn delete_user(id: Path<i32>) -> StatusCode {{ ... }}
+//! ```
+
+use std::collections::HashSet;
+
+use rapina::auth::{{Claims, Policy}};
+use rapina::http::request::Parts;
+{prelude_import}
+use rapina::state::AppState;
+
+/// Ids of the users with admin rights, registered with `.state(...)`.
+#[derive(Debug, Clone, Default)]
+pub struct AdminIds(pub HashSet<String>);
+
+/// Allows users listed in [`AdminIds`].
+///
+/// Denials answer 403 with the `ADMIN_ONLY` error code.
+#[derive(Debug, Default)]
+pub struct AdminOnly;
+
+impl Policy for AdminOnly {{
+    fn authorize(&self, claims: &Claims, _parts: &Parts, state: &AppState) -> {result}<()> {{
+        let admins = state
+            .get::<AdminIds>()
+            .ok_or_else(|| {error}::internal("AdminIds is not registered"))?;
+        if admins.0.contains(&claims.sub) {{
+            Ok(())
+        }} else {{
+            Err({error}::forbidden("only admins can do this"))
+        }}
+    }}
+}}
+
+/// Allows users acting on their own resource, the last segment of the path
+/// being their id, as in `/users/:id`.
+///
+/// Denials answer 403 with the `OWNER` error code.
+#[derive(Debug, Default)]
+pub struct Owner;
+
+impl Policy for Owner {{
+    fn authorize(&self, claims: &Claims, parts: &Parts, _state: &AppState) -> {result}<()> {{
+        let id = parts.uri.path().rsplit('/').next().unwrap_or_default();
+        if id == claims.sub {{
+            Ok(())
+        }} else {{
+            Err({error}::forbidden("only the owner can do this"))
+        }}
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+    use rapina::auth::CurrentUser;
+    use rapina::auth::policy::check;
+
+    fn parts(path: &str, user: &str) -> Parts {{
+        let (mut parts, _) = rapina::http::Request::builder()
+            .uri(path)
+            .body(())
+            .unwrap()
+            .into_parts();
+        parts.extensions.insert(CurrentUser {{
+            id: user.to_string(),
+            claims: Claims::new(user, 3600),
+        }});
+        parts
+    }}
+
+    #[test]
+    fn test_admin_only() {{
+        let state = AppState::new().with(AdminIds(HashSet::from(["1".to_string()])));
+
+        assert!(check(&AdminOnly, &parts("/reports", "1"), &state).is_ok());
+        let denied = check(&AdminOnly, &parts("/reports", "2"), &state).unwrap_err();
+        assert_eq!(denied.code, "ADMIN_ONLY");
+    }}
+
+    #[test]
+    fn test_owner() {{
+        let state = AppState::new();
+
+        assert!(check(&Owner, &parts("/users/1", "1"), &state).is_ok());
+        let denied = check(&Owner, &parts("/users/1", "2"), &state).unwrap_err();
+        assert_eq!(denied.code, "OWNER");
+    }}
+}}
+"#
+    )
+}
+
+/// Writes `src/policies.rs` with example policies.
+pub(crate) fn create_policies_file(prelude: Prelude) -> Result<(), String> {
+    let filepath = Path::new("src/policies.rs");
+    if filepath.exists() {
+        return Err(
+            "File 'src/policies.rs' already exists. Remove it first to regenerate it.".to_string(),
+        );
+    }
+
+    fs::write(filepath, generate_policies(prelude))
+        .map_err(|e| format!("Failed to write policies file: {}", e))?;
+    output::created("src/policies.rs");
+    Ok(())
+}
+
+/// Writes `src/extractors/{name}.rs`, re-exports it from the module's
+/// `mod.rs` and declares the module in `main.rs`.
+///
+/// Returns `true` when `main.rs` couldn't be updated, meaning the caller
+/// still has to declare the module by hand.
+pub(crate) fn create_extractor_file(
+    name: &str,
+    pascal: &str,
+    source: &ExtractorSource,
+    prelude: Prelude,
+) -> Result<bool, String> {
+    let extractors_dir = Path::new("src/extractors");
+    let filepath = extractors_dir.join(format!("{}.rs", name));
+
+    if filepath.exists() {
+        return Err(format!(
+            "File 'src/extractors/{}.rs' already exists. Remove it first or choose a different name.",
+            name
+        ));
+    }
+
+    if !extractors_dir.exists() {
+        fs::create_dir_all(extractors_dir)
+            .map_err(|e| format!("Failed to create extractors directory: {}", e))?;
+        output::created("src/extractors/");
+    }
+
+    fs::write(&filepath, generate_extractor(pascal, source, prelude))
+        .map_err(|e| format!("Failed to write extractor file: {}", e))?;
+    output::created(&format!("src/extractors/{}.rs", name));
+
+    update_reexport_mod_rs(extractors_dir, name, pascal)?;
+    Ok(!update_main_module("extractors")?)
+}
+
+const MAIN_PATH: &str = "src/main.rs";
+
+/// Adds a `mod {module};` declaration to `main.rs` content.
+///
+/// The declaration goes after the last top-level `mod` line, or above the
+/// first item when there is none. Returns the content unchanged when the
+/// module is already declared.
+pub(crate) fn declare_main_module(content: &str, module: &str) -> String {
+    let declaration = format!("mod {};", module);
+    let declared = content.lines().any(|line| {
+        let line = line.trim_end();
+        line == declaration || line.strip_prefix("pub ") == Some(&declaration)
+    });
+    if declared {
+        return content.to_string();
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let is_mod = |line: &&str| {
+        let line = line.strip_prefix("pub ").unwrap_or(line);
+        line.starts_with("mod ") && line.trim_end().ends_with(';')
+    };
+    let (at, insert) = match lines.iter().rposition(is_mod) {
+        Some(last) => (last + 1, vec![declaration.as_str()]),
+        None => {
+            let first_item = lines
+                .iter()
+                .position(|line| {
+                    !(line.trim().is_empty() || line.starts_with("//!") || line.starts_with("#!["))
+                })
+                .unwrap_or(lines.len());
+            (first_item, vec![declaration.as_str(), ""])
+        }
+    };
+
+    let mut updated: Vec<&str> = lines[..at].to_vec();
+    updated.extend(insert);
+    updated.extend(&lines[at..]);
+    format!("{}\n", updated.join("\n"))
+}
+
+/// Declares a module in `src/main.rs` when the file exists.
+///
+/// Returns `true` when the module is declared there.
+pub(crate) fn update_main_module(module: &str) -> Result<bool, String> {
+    let path = Path::new(MAIN_PATH);
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read main.rs: {}", e))?;
+    let updated = declare_main_module(&content, module);
+    if updated != content {
+        fs::write(path, updated).map_err(|e| format!("Failed to update main.rs: {}", e))?;
+        output::updated(MAIN_PATH);
+    }
+    Ok(true)
+}
+
+/// Adds the module declaration and re-export to `mod.rs` in `dir`, such as
+/// `src/extractors/mod.rs`.
+///
+/// Returns `true` when the file was newly created, meaning the caller still
+/// has to declare the module in `main.rs`.
+pub(crate) fn update_reexport_mod_rs(dir: &Path, name: &str, pascal: &str) -> Result<bool, String> {
+    let mod_path = dir.join("mod.rs");
+    let display = mod_path.display().to_string();
+    let entry = format!("pub mod {name};\npub use {name}::{pascal};\n");
+
+    let created = if mod_path.exists() {
+        let content =
+            fs::read_to_string(&mod_path).map_err(|e| format!("Failed to read mod.rs: {}", e))?;
+        let updated = if content.is_empty() || content.ends_with('\n') {
+            format!("{}{}", content, entry)
+        } else {
+            format!("{}\n{}", content, entry)
+        };
+        fs::write(&mod_path, updated).map_err(|e| format!("Failed to update mod.rs: {}", e))?;
+        output::updated(&display);
+        false
+    } else {
+        fs::write(&mod_path, entry).map_err(|e| format!("Failed to create mod.rs: {}", e))?;
+        output::created(&display);
+        true
+    };
+
+    Ok(created)
+}
+
+pub(crate) fn generate_job(name: &str, pascal: &str) -> String {
+    format!(
+        r#"use rapina::jobs::{{Job, JobContext, JobResult}};
+use serde::{{Deserialize, Serialize}};
+
+/// Background job, enqueued with `jobs.enqueue({pascal} {{ .. }})`.
+///
+/// The fields are stored as JSON until a worker runs the job, so keep them
+/// small: ids rather than whole records.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct {pascal} {{}}
+
+impl Job for {pascal} {{
+    const NAME: &'static str = "{name}";
+
+    async fn run(self, ctx: JobContext) -> JobResult {{
+        rapina::tracing::info!(job = Self::NAME, attempt = ctx.attempt(), "Running job");
+        Ok(())
+    }}
+}}
+"#
+    )
+}
+
+pub(crate) fn generate_jobs_migration() -> String {
+    "//! Creates the `rapina_jobs` table used by the background job queue.\n\npub use rapina::jobs::migration::Migration;\n".to_string()
+}
+
+/// Writes `src/jobs/<name>.rs` and re-exports it from `src/jobs/mod.rs`.
+///
+/// Returns `true` when `src/jobs/mod.rs` was newly created.
+pub(crate) fn create_job_file(name: &str, pascal: &str) -> Result<bool, String> {
+    let jobs_dir = Path::new("src/jobs");
+    let filepath = jobs_dir.join(format!("{}.rs", name));
+
+    if filepath.exists() {
+        return Err(format!(
+            "File 'src/jobs/{}.rs' already exists. Remove it first or choose a different name.",
+            name
+        ));
+    }
+
+    if !jobs_dir.exists() {
+        fs::create_dir_all(jobs_dir)
+            .map_err(|e| format!("Failed to create jobs directory: {}", e))?;
+        output::created("src/jobs/");
+    }
+
+    fs::write(&filepath, generate_job(name, pascal))
+        .map_err(|e| format!("Failed to write job file: {}", e))?;
+    output::created(&format!("src/jobs/{}.rs", name));
+
+    update_reexport_mod_rs(jobs_dir, name, pascal)
+}
+
+/// Adds the migration creating the `rapina_jobs` table, unless a migration
+/// already re-exports it.
+///
+/// Returns `true` when the migration was added.
+pub(crate) fn create_jobs_migration_file() -> Result<bool, String> {
+    let migrations_dir = Path::new("src/migrations");
+
+    if migrations_dir.exists() {
+        let entries = fs::read_dir(migrations_dir)
+            .map_err(|e| format!("Failed to read migrations directory: {}", e))?;
+        for entry in entries.flatten() {
+            let content = fs::read_to_string(entry.path()).unwrap_or_default();
+            if content.contains("rapina::jobs::migration") {
+                return Ok(false);
+            }
+        }
+    } else {
+        fs::create_dir_all(migrations_dir)
+            .map_err(|e| format!("Failed to create migrations directory: {}", e))?;
+        output::created("src/migrations/");
+    }
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let module_name = format!("m{}_create_rapina_jobs", timestamp);
+    let filename = format!("{}.rs", module_name);
+    fs::write(migrations_dir.join(&filename), generate_jobs_migration())
+        .map_err(|e| format!("Failed to write migration file: {}", e))?;
+    output::created(&format!("src/migrations/{}", filename));
+
+    super::migrate::update_mod_rs(migrations_dir, &module_name)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_singularize() {
+        assert_eq!(singularize("users"), "user");
+        assert_eq!(singularize("posts"), "post");
+        assert_eq!(singularize("categories"), "category");
+        assert_eq!(singularize("addresses"), "address");
+        assert_eq!(singularize("boxes"), "box");
+        assert_eq!(singularize("buzzes"), "buzz");
+        assert_eq!(singularize("boss"), "boss");
+        assert_eq!(singularize("status"), "statu"); // naive, acceptable
+    }
+
+    #[test]
+    fn test_generate_schema_block_with_timestamps() {
+        let fields = vec![FieldInfo {
+            name: "title".to_string(),
+            rust_type: "String".to_string(),
+            schema_type: "String".to_string(),
+            column_method: String::new(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: false,
+            unique: false,
+        }];
+
+        let block = generate_schema_block("Post", &fields, None, None, &[], None);
+        assert!(block.contains("schema! {"));
+        assert!(block.contains("Post {"));
+        assert!(block.contains("title: String,"));
+        assert!(!block.contains("#[timestamps"));
+
+        let block = generate_schema_block("Post", &fields, Some("none"), None, &[], None);
+        assert!(block.contains("#[timestamps(none)]"));
+
+        let block = generate_schema_block("Post", &fields, Some("created_at"), None, &[], None);
+        assert!(block.contains("#[timestamps(created_at)]"));
+        assert!(block.find("#[timestamps").unwrap() < block.find("Post {").unwrap());
+    }
+
+    #[test]
+    fn test_doc_comment_escaping() {
+        assert_eq!(doc_comment("Plain text", ""), "/// Plain text\n");
+        assert_eq!(
+            doc_comment("Says \"hi\" and 'bye' \\o/", "    "),
+            "    /// Says \"hi\" and 'bye' \\o/\n"
+        );
+        assert_eq!(
+            doc_comment("\r\nFirst line\r\n\r\nSecond line\rThird  \n\n", ""),
+            "/// First line\n///\n/// Second line\n/// Third\n"
+        );
+        assert_eq!(
+            doc_comment("/path/to */ thing", ""),
+            "/// /path/to */ thing\n"
+        );
+        assert_eq!(
+            doc_comment("tab\there\u{0}bell\u{7}", ""),
+            "/// tab\therebell\n"
+        );
+        assert_eq!(doc_comment("  \n\t\n", ""), "");
+    }
+
+    #[test]
+    fn test_comments_flow_into_schema_and_dto() {
+        let fields = vec![
+            FieldInfo {
+                name: "email".to_string(),
+                rust_type: "String".to_string(),
+                schema_type: "String".to_string(),
+                column_method: ".string().not_null()".to_string(),
+                comment: Some("Login address.\nMust be \"unique\".".to_string()),
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+            FieldInfo {
+                name: "name".to_string(),
+                rust_type: "String".to_string(),
+                schema_type: "String".to_string(),
+                column_method: ".string().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+        ];
+
+        let block = generate_schema_block(
+            "User",
+            &fields,
+            Some("none"),
+            None,
+            &[],
+            Some("People who can log in."),
+        );
+        assert!(
+            block.contains("    /// People who can log in.\n    #[timestamps(none)]\n    User {\n")
+        );
+        assert!(block.contains(
+            "        /// Login address.\n        /// Must be \"unique\".\n        email: String,"
+        ));
+        assert!(block.contains("\n        name: String,"));
+
+        let dto = generate_dto("User", &fields, true);
+        assert!(dto.contains(
+            "    /// Login address.\n    /// Must be \"unique\".\n    pub email: String,"
+        ));
+        assert!(dto.contains(
+            "    /// Login address.\n    /// Must be \"unique\".\n    pub email: Option<String>,"
+        ));
+    }
+
+    #[test]
+    fn test_generate_schema_block_with_primary_key() {
+        let fields = vec![
+            FieldInfo {
+                name: "user_id".to_string(),
+                rust_type: "i32".to_string(),
+                schema_type: "i32".to_string(),
+                column_method: ".integer().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+            FieldInfo {
+                name: "role_id".to_string(),
+                rust_type: "i32".to_string(),
+                schema_type: "i32".to_string(),
+                column_method: ".integer().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+        ];
+
+        let pk = vec!["user_id".to_string(), "role_id".to_string()];
+        let block = generate_schema_block("UsersRole", &fields, Some("none"), Some(&pk), &[], None);
+        assert!(block.contains("#[primary_key(user_id, role_id)]"));
+        assert!(block.contains("#[timestamps(none)]"));
+        assert!(block.contains("user_id: i32,"));
+        assert!(block.contains("role_id: i32,"));
+    }
+
+    #[test]
+    fn test_index_info_resolved_name() {
+        let idx = IndexInfo {
+            name: None,
+            columns: vec!["email".to_string()],
+            unique: true,
+        };
+        assert_eq!(idx.resolved_name("users"), "uq_users_email");
+
+        let idx = IndexInfo {
+            name: None,
+            columns: vec!["first_name".to_string(), "last_name".to_string()],
+            unique: false,
+        };
+        assert_eq!(idx.resolved_name("users"), "idx_users_first_name_last_name");
+
+        let idx = IndexInfo {
+            name: Some("users_email_key".to_string()),
+            columns: vec!["email".to_string()],
+            unique: true,
+        };
+        assert_eq!(idx.resolved_name("users"), "users_email_key");
+    }
+
+    #[test]
+    fn test_generate_schema_block_with_indexes() {
+        let fields = vec![
+            FieldInfo {
+                name: "email".to_string(),
+                rust_type: "String".to_string(),
+                schema_type: "String".to_string(),
+                column_method: ".string().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+            FieldInfo {
+                name: "last_name".to_string(),
+                rust_type: "String".to_string(),
+                schema_type: "String".to_string(),
+                column_method: ".string().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+        ];
+        let indexes = vec![
+            IndexInfo {
+                name: Some("users_email_key".to_string()),
+                columns: vec!["email".to_string()],
+                unique: true,
+            },
+            IndexInfo {
+                name: None,
+                columns: vec!["last_name".to_string()],
+                unique: false,
+            },
+            IndexInfo {
+                name: None,
+                columns: vec!["email".to_string(), "last_name".to_string()],
+                unique: false,
+            },
+        ];
+
+        let block = generate_schema_block("User", &fields, None, None, &indexes, None);
+        assert!(
+            block.contains("        #[unique(name = \"users_email_key\")]\n        email: String,")
+        );
+        assert!(block.contains("        #[index]\n        last_name: String,"));
+        assert_eq!(block.matches("#[index").count(), 1);
+    }
+
+    #[test]
+    fn test_generate_migration_with_indexes() {
+        let fields = vec![FieldInfo {
+            name: "email".to_string(),
+            rust_type: "String".to_string(),
+            schema_type: "String".to_string(),
+            column_method: ".string().not_null()".to_string(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: false,
+            unique: false,
+        }];
+        let indexes = vec![
+            IndexInfo {
+                name: None,
+                columns: vec!["email".to_string()],
+                unique: true,
+            },
+            IndexInfo {
+                name: Some("users_lookup".to_string()),
+                columns: vec!["id".to_string(), "email".to_string()],
+                unique: false,
+            },
+        ];
+
+        let content =
+            generate_migration("users", "Users", &fields, &indexes, None, IdColumn::Integer);
+        assert!(content.contains(".name(\"uq_users_email\")"));
+        assert!(content.contains(".name(\"users_lookup\")"));
+        assert!(content.contains(".col(Users::Id)"));
+        assert_eq!(content.matches(".unique()").count(), 1);
+        assert!(content.contains(".await?;"));
+        assert!(content.contains("Ok(())"));
+
+        let content = generate_migration("users", "Users", &fields, &[], None, IdColumn::Integer);
+        assert!(!content.contains("create_index"));
+        assert!(!content.contains("Ok(())"));
+    }
+
+    #[test]
+    fn test_normalize_route_prefix() {
+        assert_eq!(normalize_route_prefix("/api/v1").unwrap(), "/api/v1");
+        assert_eq!(normalize_route_prefix("api/v1/").unwrap(), "/api/v1");
+        assert_eq!(normalize_route_prefix(" /api ").unwrap(), "/api");
+        assert_eq!(normalize_route_prefix("/").unwrap(), "");
+        assert_eq!(normalize_route_prefix("").unwrap(), "");
+        assert!(normalize_route_prefix("/api//v1").is_err());
+        assert!(normalize_route_prefix("/tenants/:id").is_err());
+        assert!(normalize_route_prefix("/api v1").is_err());
+    }
+
+    #[test]
+    fn test_prefixed_path() {
+        assert_eq!(prefixed_path("", "/posts"), "/posts");
+        assert_eq!(prefixed_path("/api/v1", "/posts"), "/api/v1/posts");
+        assert_eq!(prefixed_path("/api/v1", "/api/v1/posts"), "/api/v1/posts");
+        assert_eq!(prefixed_path("/api", "/apiary"), "/api/apiary");
+    }
+
+    #[test]
+    fn test_route_prefix_metadata_roundtrip() {
+        let cargo = "[package]\nname = \"blog\"\n\n[dependencies]\nrapina = \"0.7\"\n";
+        assert_eq!(stored_route_prefix(cargo), None);
+
+        let updated = set_route_prefix(cargo, "/api/v1");
+        assert!(updated.ends_with("[package.metadata.rapina]\nroute_prefix = \"/api/v1\"\n"));
+        assert!(updated.starts_with(cargo));
+        assert_eq!(stored_route_prefix(&updated).as_deref(), Some("/api/v1"));
+
+        let replaced = set_route_prefix(&updated, "/api/v2");
+        assert_eq!(replaced.matches("route_prefix").count(), 1);
+        assert_eq!(stored_route_prefix(&replaced).as_deref(), Some("/api/v2"));
+    }
+
+    #[test]
+    fn test_set_route_prefix_keeps_existing_metadata() {
+        let cargo = "[package]\nname = \"blog\"\n\n[package.metadata.rapina]\nprelude = \"full\"\n\n[dependencies]\nrapina = \"0.7\"\n";
+        let updated = set_route_prefix(cargo, "/api");
+
+        assert!(
+            updated.contains(
+                "[package.metadata.rapina]\nroute_prefix = \"/api\"\nprelude = \"full\"\n"
+            )
+        );
+        assert!(updated.contains("[dependencies]\nrapina = \"0.7\""));
+        assert_eq!(stored_route_prefix(&updated).as_deref(), Some("/api"));
+    }
+
+    #[test]
+    fn test_generate_handlers_with_route_prefix() {
+        let fields = vec![FieldInfo {
+            name: "title".to_string(),
+            rust_type: "String".to_string(),
+            schema_type: "String".to_string(),
+            column_method: ".string()".to_string(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: false,
+            unique: false,
+        }];
+        let content = generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &fields,
+            "i32",
+            Prelude::Full,
+            "/api/v1",
+            None,
+        );
+
+        assert!(content.contains("#[get(\"/api/v1/posts\")]"));
+        assert!(content.contains("#[get(\"/api/v1/posts/:id\")]"));
+        assert!(content.contains("#[post(\"/api/v1/posts\")]"));
+        assert!(content.contains("#[put(\"/api/v1/posts/:id\")]"));
+        assert!(content.contains("#[delete(\"/api/v1/posts/:id\")]"));
+    }
+
+    fn body_field() -> Vec<FieldInfo> {
+        vec![FieldInfo {
+            name: "body".to_string(),
+            rust_type: "String".to_string(),
+            schema_type: "Text".to_string(),
+            column_method: ".text().not_null()".to_string(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: false,
+            unique: false,
+        }]
+    }
+
+    #[test]
+    fn test_resource_paths() {
+        assert_eq!(
+            resource_paths("comments", "", None),
+            ("/comments".to_string(), "/comments/:id".to_string())
+        );
+
+        let parent = ParentInfo::new("post", false);
+        assert_eq!(
+            resource_paths("comments", "/api", Some(&parent)),
+            (
+                "/api/posts/:post_id/comments".to_string(),
+                "/api/posts/:post_id/comments/:id".to_string()
+            )
+        );
+
+        let parent = ParentInfo::new("post", true);
+        assert_eq!(
+            resource_paths("comments", "/api", Some(&parent)),
+            (
+                "/api/posts/:post_id/comments".to_string(),
+                "/api/comments/:id".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_nested_handlers() {
+        let parent = ParentInfo::new("post", false);
+        let content = generate_handlers(
+            "comment",
+            "comments",
+            "Comment",
+            &body_field(),
+            "i32",
+            Prelude::Full,
+            "",
+            Some(&parent),
+        );
+
+        assert!(content.contains("use crate::entity::{Comment, Post};"));
+        assert!(content.contains("use crate::entity::comment::{ActiveModel, Column, Model};"));
+        assert!(content.contains("QueryFilter"));
+        assert!(content.contains("#[get(\"/posts/:post_id/comments\")]"));
+        assert!(content.contains("#[post(\"/posts/:post_id/comments\")]"));
+        assert!(content.contains("#[get(\"/posts/:post_id/comments/:id\")]"));
+        assert!(content.contains("#[put(\"/posts/:post_id/comments/:id\")]"));
+        assert!(content.contains("#[delete(\"/posts/:post_id/comments/:id\")]"));
+        assert!(content.contains(
+            "pub async fn list_comments(db: Db, post_id: Path<i32>, page: Paginate) -> Result<Paginated<Model>>"
+        ));
+        assert!(content.contains(
+            "    let select = Comment::find()\n        .filter(Column::PostId.eq(post_id));\n    let items = page.exec(select, db.read()).await?;\n"
+        ));
+        assert!(
+            content.contains("pub async fn get_comment(db: Db, post_id: Path<i32>, id: Path<i32>)")
+        );
+        assert!(content.contains("let (post_id, id) = (post_id.into_inner(), id.into_inner());"));
+        assert!(content.contains(".filter(Column::PostId.eq(post_id))"));
+        assert!(content.contains("post_id: Set(post_id),"));
+        assert!(content.contains("body: Set(input.body),"));
+        assert!(content.contains("ensure_post_exists(db.read(), post_id).await?;"));
+        assert!(content.contains("ensure_post_exists(&txn, post_id).await?;"));
+        assert!(content.contains(
+            "async fn ensure_post_exists(conn: &impl ConnectionTrait, post_id: i32) -> Result<()>"
+        ));
+        assert!(content.contains("Error::not_found(format!(\"Post {} not found\", post_id))"));
+        // list and get check the parent on reads, create/update/delete in the transaction
+        assert_eq!(content.matches("ensure_post_exists(db.read()").count(), 2);
+        assert_eq!(content.matches("ensure_post_exists(&txn").count(), 3);
+    }
+
+    #[test]
+    fn test_generate_export_handler() {
+        let handlers = generate_handlers(
+            "todo",
+            "todos",
+            "Todo",
+            &body_field(),
+            "i32",
+            Prelude::Full,
+            "/api",
+            None,
+        );
+        let export = generate_export_handler("todos", "Todo", Prelude::Full, "/api", None);
+        let content = with_export_handler(handlers, &export);
+
+        assert!(content.contains(
+            "use rapina::events::{Event, Events};\nuse rapina::export::stream_json;\nuse rapina::response::BoxBody;\n"
+        ));
+        assert!(content.contains("#[get(\"/api/todos/export\")]"));
+        assert!(content.contains(
+            "pub async fn export_todos(db: Db) -> Result<rapina::http::Response<BoxBody>> {\n    stream_json(Todo::find(), db.read()).await\n}"
+        ));
+        assert!(content.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_generate_handlers_with_field_selection() {
+        let handlers = generate_handlers(
+            "todo",
+            "todos",
+            "Todo",
+            &body_field(),
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
+        let content = with_field_selection_handlers(handlers, "todo", "todos");
+
+        assert!(content.contains(
+            "use rapina::events::{Event, Events};\nuse rapina::fields::{FieldSelect, PartialJson};\n"
+        ));
+        assert!(content.contains(
+            "pub async fn list_todos(db: Db, page: Paginate, fields: FieldSelect<Model>) -> Result<Paginated<PartialJson<Model>>> {"
+        ));
+        assert!(content.contains(
+            "pub async fn get_todo(db: Db, id: Path<i32>, fields: FieldSelect<Model>) -> Result<PartialJson<Model>> {"
+        ));
+        assert!(content.contains("    Ok(items.map(|item| fields.apply(item)))\n"));
+        assert!(content.contains("    Ok(fields.apply(item))\n"));
+        // create, update and delete are untouched
+        assert_eq!(content.matches("FieldSelect<Model>").count(), 2);
+        assert_eq!(content.matches("Ok(Json(result))").count(), 2);
+    }
+
+    #[test]
+    fn test_generate_nested_handlers_with_field_selection() {
+        let parent = ParentInfo::new("post", false);
+        let handlers = generate_handlers(
+            "comment",
+            "comments",
+            "Comment",
+            &body_field(),
+            "i32",
+            Prelude::Minimal,
+            "",
+            Some(&parent),
+        );
+        let content = with_field_selection_handlers(handlers, "comment", "comments");
+
+        assert!(content.contains(
+            "pub async fn list_comments(db: Db, post_id: Path<i32>, page: Paginate, fields: FieldSelect<Model>) -> rapina::error::Result<Paginated<PartialJson<Model>>> {"
+        ));
+        assert!(content.contains(
+            "fields: FieldSelect<Model>) -> rapina::error::Result<PartialJson<Model>> {"
+        ));
+    }
+
+    #[test]
+    fn test_generate_handlers_with_links() {
+        let handlers = generate_handlers(
+            "todo",
+            "todos",
+            "Todo",
+            &body_field(),
+            "i32",
+            Prelude::Full,
+            "/api",
+            None,
+        );
+        let content = with_links_handlers(handlers, "todo", "todos", None);
+
+        assert!(content.contains(
+            "use rapina::events::{Event, Events};\nuse rapina::links::{Linked, UrlFor};\n"
+        ));
+        assert!(content.contains(
+            "pub async fn list_todos(db: Db, page: Paginate, urls: UrlFor) -> Result<Linked<Paginated<Model>>> {"
+        ));
+        assert!(content.contains(
+            "    let links = urls.links().route(\"self\", \"list_todos\", &[])?;\n    Ok(Linked::new(items, links))\n"
+        ));
+        assert!(content.contains(
+            "pub async fn get_todo(db: Db, id: Path<i32>, urls: UrlFor) -> Result<Linked<Model>> {"
+        ));
+        assert!(content.contains(
+            "        .route(\"self\", \"get_todo\", &[(\"id\", &id)])?\n        .route(\"collection\", \"list_todos\", &[])?;\n    Ok(Linked::new(item, links))\n"
+        ));
+        // create, update and delete are untouched
+        assert_eq!(content.matches("urls: UrlFor").count(), 2);
+        assert_eq!(content.matches("Ok(Json(result))").count(), 2);
+    }
+
+    #[test]
+    fn test_generate_handlers_with_merge_patch_update() {
+        let mut fields = body_field();
+        fields.push(FieldInfo {
+            name: "due_at".to_string(),
+            rust_type: "DateTimeUtc".to_string(),
+            schema_type: "DateTime".to_string(),
+            column_method: ".timestamp_with_time_zone().null()".to_string(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: true,
+            unique: false,
+        });
+        let handlers = generate_handlers(
+            "todo",
+            "todos",
+            "Todo",
+            &fields,
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
+        let content = with_merge_patch_update(handlers, "Todo", &fields, None);
+
+        assert!(
+            content
+                .contains("use rapina::extract::{Json, Path};\nuse rapina::patch::MergePatch;\n")
+        );
+        assert!(content.contains("use rapina::{delete, get, patch, post};"));
+        assert!(content.contains("use super::dto::CreateTodo;\n"));
+        assert!(content.contains(
+            "#[patch(\"/todos/:id\", consumes = \"application/merge-patch+json\")]\n#[errors(TodoError)]\n"
+        ));
+        assert!(content.contains(
+            "pub async fn update_todo(db: Db, events: Events, id: Path<i32>, patch: MergePatch<Model>) -> Result<Json<Model>> {"
+        ));
+        assert!(content.contains(
+            "    let patched = patch.apply(&item)?;\n    let mut active: ActiveModel = item.into_active_model();\n    active.body.set_if_not_equals(patched.body);\n    active.due_at.set_if_not_equals(patched.due_at);\n\n    let result = active.update(&txn).await"
+        ));
+        assert!(!content.contains("UpdateTodo"));
+        assert!(!content.contains("#[put("));
+        assert!(!content.contains("update."));
+    }
+
+    #[test]
+    fn test_merge_patch_update_leaves_primary_key_alone() {
+        let fields = vec![
+            FieldInfo {
+                name: "code".to_string(),
+                rust_type: "String".to_string(),
+                schema_type: "String".to_string(),
+                column_method: ".string().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+            body_field().remove(0),
+        ];
+        let handlers = generate_handlers(
+            "country",
+            "countries",
+            "Country",
+            &fields,
+            "String",
+            Prelude::Full,
+            "",
+            None,
+        );
+        let content = with_merge_patch_update(handlers, "Country", &fields, Some(&fields[0]));
+        let content = with_primary_key_handlers(content, &fields[0]);
+
+        assert!(content.contains("    active.body.set_if_not_equals(patched.body);\n"));
+        assert!(!content.contains("active.code"));
+    }
+
+    #[test]
+    fn test_generate_dto_without_update_dto() {
+        let dto = without_update_dto(generate_dto("Todo", &body_field(), true), "Todo");
+        assert!(dto.contains("pub struct CreateTodo {"));
+        assert!(!dto.contains("UpdateTodo"));
+        assert!(dto.ends_with("    pub body: String,\n}\n"));
+    }
+
+    #[test]
+    fn test_generate_nested_handlers_with_links() {
+        let parent = ParentInfo::new("post", false);
+        let handlers = generate_handlers(
+            "comment",
+            "comments",
+            "Comment",
+            &body_field(),
+            "i32",
+            Prelude::Full,
+            "",
+            Some(&parent),
+        );
+        let content = with_links_handlers(handlers, "comment", "comments", Some(&parent));
+
+        assert!(
+            content.contains("route(\"self\", \"list_comments\", &[(\"post_id\", &post_id)])?;")
+        );
+        assert!(content.contains(
+            "route(\"self\", \"get_comment\", &[(\"post_id\", &post_id), (\"id\", &id)])?"
+        ));
+        assert!(
+            content
+                .contains("route(\"collection\", \"list_comments\", &[(\"post_id\", &post_id)])?;")
+        );
+
+        let shallow = ParentInfo::new("post", true);
+        let handlers = generate_handlers(
+            "comment",
+            "comments",
+            "Comment",
+            &body_field(),
+            "i32",
+            Prelude::Full,
+            "",
+            Some(&shallow),
+        );
+        let content = with_links_handlers(handlers, "comment", "comments", Some(&shallow));
+
+        assert!(content.contains("route(\"self\", \"get_comment\", &[(\"id\", &id)])?"));
+        assert!(content.contains(
+            "route(\"collection\", \"list_comments\", &[(\"post_id\", &item.post_id)])?;"
+        ));
+    }
+
+    #[test]
+    fn test_generate_handlers_with_idempotency() {
+        let handlers = generate_handlers(
+            "todo",
+            "todos",
+            "Todo",
+            &body_field(),
+            "i32",
+            Prelude::Full,
+            "",
+            None,
+        );
+        let content = with_idempotency_docs(handlers);
+
+        assert!(content.contains(
+            "/// Requires `IdempotencyMiddleware` to be registered.\n#[post(\"/todos\")]\n#[errors(TodoError)]\npub async fn create_todo("
+        ));
+        assert_eq!(content.matches("Idempotency-Key").count(), 1);
+    }
+
+    #[test]
+    fn test_generate_handlers_with_uuid_id() {
+        assert_eq!(IdColumn::Uuid.rust_type(&[]), "Uuid");
+        let content = generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &body_field(),
+            "Uuid",
+            Prelude::Full,
+            "",
+            None,
+        );
+
+        assert!(content.contains("use rapina::sea_orm::prelude::Uuid;\n"));
+        assert!(content.contains("pub async fn get_post(db: Db, id: Path<Uuid>)"));
+        assert!(content.contains("id: Path<Uuid>, body: Json<UpdatePost>"));
+        assert!(
+            content.contains("pub async fn delete_post(db: Db, events: Events, id: Path<Uuid>)")
+        );
+        assert!(content.contains("Post::find_by_id(id)"));
+        assert!(content.contains("Post::delete_by_id(id)"));
+        assert!(content.contains("Event::deleted(\"post\", id)"));
+        assert!(!content.contains("Path<i32>"));
+    }
+
+    #[test]
+    fn test_generate_handlers_with_string_id() {
+        let content = generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &body_field(),
+            "String",
+            Prelude::Full,
+            "",
+            None,
+        );
+
+        assert!(!content.contains("sea_orm::prelude"));
+        assert!(content.contains("pub async fn get_post(db: Db, id: Path<String>)"));
+        assert!(content.contains("Post::find_by_id(id.clone())"));
+        assert!(content.contains("Post::delete_by_id(id.clone())"));
+        assert!(content.contains("format!(\"Post {} not found\", id)"));
+        assert!(content.contains("Event::created(\"post\", &result.id)"));
+        assert!(content.contains("Event::updated(\"post\", &id)"));
+        assert!(content.contains("Event::deleted(\"post\", &id)"));
+    }
+
+    #[test]
+    fn test_generate_handlers_with_primary_key() {
+        let fields = vec![
+            FieldInfo {
+                name: "user_id".to_string(),
+                rust_type: "i64".to_string(),
+                schema_type: "i64".to_string(),
+                column_method: ".big_integer().not_null()".to_string(),
+                comment: None,
+                column: None,
+                default: None,
+                nullable: false,
+                unique: false,
+            },
+            body_field().remove(0),
+        ];
+        let id = IdColumn::Field("user_id");
+        assert_eq!(id.rust_type(&fields), "i64");
+        let handlers = generate_handlers(
+            "account",
+            "accounts",
+            "Account",
+            &fields,
+            id.rust_type(&fields),
+            Prelude::Full,
+            "",
+            None,
+        );
+        let content = with_primary_key_handlers(handlers, &fields[0]);
+
+        assert!(content.contains("pub async fn get_account(db: Db, id: Path<i64>)"));
+        assert!(content.contains("Account::find_by_id(id)"));
+        assert!(content.contains("Account::delete_by_id(id)"));
+        assert!(content.contains("Event::created(\"account\", result.user_id)"));
+        assert!(!content.contains("id: Path<i32>"));
+        assert!(!content.contains("update.user_id"));
+        assert!(content.contains("update.body"));
+
+        let dto =
+            without_updatable_primary_key(generate_dto("Account", &fields, false), &fields[0]);
+        assert_eq!(dto.matches("pub user_id: Option<i64>").count(), 0);
+        assert!(dto.contains("pub user_id: i64,"));
+        assert!(dto.contains("pub body: Option<String>,"));
+    }
+
+    #[test]
+    fn test_generate_nested_export_handler() {
+        let parent = ParentInfo::new("post", false);
+        let content =
+            generate_export_handler("comments", "Comment", Prelude::Minimal, "", Some(&parent));
+
+        assert!(content.contains("#[get(\"/posts/:post_id/comments/export\")]"));
+        assert!(content.contains(
+            "pub async fn export_comments(db: Db, post_id: Path<i32>) -> rapina::error::Result<rapina::http::Response<BoxBody>>"
+        ));
+        assert!(content.contains("ensure_post_exists(db.read(), post_id).await?;"));
+        assert!(content.contains(
+            "stream_json(Comment::find().filter(Column::PostId.eq(post_id)), db.read()).await"
+        ));
+    }
+
+    #[test]
+    fn test_generate_shallow_handlers() {
+        let parent = ParentInfo::new("post", true);
+        let content = generate_handlers(
+            "comment",
+            "comments",
+            "Comment",
+            &body_field(),
+            "i32",
+            Prelude::Minimal,
+            "",
+            Some(&parent),
+        );
+
+        assert!(content.contains("#[get(\"/posts/:post_id/comments\")]"));
+        assert!(content.contains("#[post(\"/posts/:post_id/comments\")]"));
+        assert!(content.contains("#[get(\"/comments/:id\")]"));
+        assert!(content.contains("#[put(\"/comments/:id\")]"));
+        assert!(content.contains("#[delete(\"/comments/:id\")]"));
+        assert!(content.contains("pub async fn get_comment(db: Db, id: Path<i32>)"));
+        assert!(content.contains("post_id: Set(post_id),"));
+        assert!(content.contains("-> rapina::error::Result<()>"));
+        assert_eq!(
+            content
+                .matches(".filter(Column::PostId.eq(post_id))")
+                .count(),
+            1
+        );
+        assert_eq!(content.matches("ensure_post_exists(").count(), 3);
+    }
+
+    #[test]
+    fn test_generate_migration_with_parent() {
+        let parent = ParentInfo::new("post", false);
+        let indexes = vec![IndexInfo {
+            name: None,
+            columns: vec!["post_id".to_string()],
+            unique: false,
+        }];
+        let content = generate_migration(
+            "comments",
+            "Comments",
+            &body_field(),
+            &indexes,
+            Some(&parent),
+            IdColumn::Integer,
+        );
+
+        assert!(content.contains(".col(ColumnDef::new(Comments::PostId).integer().not_null())"));
+        assert!(content.contains(".name(\"fk_comments_post_id\")"));
+        assert!(content.contains(".from(Comments::Table, Comments::PostId)"));
+        assert!(content.contains(".to(Posts::Table, Posts::Id)"));
+        assert!(content.contains(".on_delete(ForeignKeyAction::Cascade)"));
+        assert!(content.contains(".name(\"idx_comments_post_id\")"));
+        assert!(content.contains("    PostId,\n    Body,"));
+        assert!(content.contains("enum Posts {\n    Table,\n    Id,\n}"));
+    }
+
+    #[test]
+    fn test_generate_dto_without_json_schema() {
+        let dto = generate_dto("Invoice", &body_field(), false);
+        assert!(!dto.contains("JsonSchema"));
+        assert!(!dto.contains("schemars"));
+        assert!(dto.contains("#[derive(Deserialize)]\npub struct CreateInvoice {"));
+        assert!(dto.contains("#[derive(Deserialize)]\npub struct UpdateInvoice {"));
+    }
+
+    #[test]
+    fn test_mark_no_json_schema() {
+        let content = format!(
+            "use rapina::prelude::*;\n{}{}",
+            generate_schema_block("Post", &body_field(), None, None, &[], Some("Posts.")),
+            generate_schema_block("Tag", &body_field(), None, None, &[], None)
+        );
+
+        let updated = mark_no_json_schema(&content, "Tag").unwrap();
+
+        assert!(updated.contains("schema! {\n    #[no_json_schema]\n    Tag {\n"));
+        assert!(updated.contains("    /// Posts.\n    Post {\n"));
+        assert_eq!(updated.matches("#[no_json_schema]").count(), 1);
+        assert!(mark_no_json_schema(&content, "Comment").is_none());
+    }
+
+    #[test]
+    fn test_mark_uuid_id() {
+        let content = format!(
+            "use rapina::prelude::*;\n{}",
+            generate_schema_block("Post", &body_field(), Some("none"), None, &[], None)
+        );
+
+        let updated = mark_uuid_id(&content, "Post").unwrap();
+
+        assert!(updated.contains("    #[timestamps(none)]\n    #[id(Uuid)]\n    Post {\n"));
+    }
+
+    #[test]
+    fn test_mark_table_name() {
+        let content = format!(
+            "use rapina::prelude::*;\n{}",
+            generate_schema_block("Person", &body_field(), None, None, &[], None)
+        );
+
+        let updated = mark_table_name(&content, "Person", "users").unwrap();
+
+        assert!(updated.contains("    #[table_name = \"users\"]\n    Person {\n"));
+    }
+
+    #[test]
+    fn test_renamed_column() {
+        let mut fields = body_field();
+        fields[0].column = Some("txt".to_string());
+        let indexes = vec![IndexInfo {
+            name: Some("idx_posts_txt".to_string()),
+            columns: vec!["txt".to_string()],
+            unique: false,
+        }];
+
+        let block = generate_schema_block("Post", &fields, None, None, &indexes, None);
+        assert!(block.contains(
+            "        #[index(name = \"idx_posts_txt\")]\n        #[column = \"txt\"]\n        body: Text,"
+        ));
+
+        let migration =
+            generate_migration("posts", "Posts", &fields, &indexes, None, IdColumn::Integer);
+        assert!(migration.contains(".col(ColumnDef::new(Posts::Txt).text().not_null())"));
+        assert!(migration.contains("    Txt,"));
+        assert!(!migration.contains("Body"));
+    }
+
+    #[test]
+    fn test_column_defaults() {
+        let field = |name: &str, schema_type: &str, column_method: &str, default: &str| FieldInfo {
+            name: name.to_string(),
+            rust_type: schema_type.to_string(),
+            schema_type: schema_type.to_string(),
+            column_method: column_method.to_string(),
+            comment: None,
+            column: None,
+            default: Some(default.to_string()),
+            nullable: false,
+            unique: false,
+        };
+        let fields = vec![
+            field("status", "String", ".string().not_null()", "\"draft\""),
+            field("views", "i32", ".integer().not_null()", "0"),
+            field(
+                "published_at",
+                "DateTime",
+                ".timestamp_with_time_zone().not_null()",
+                "now()",
+            ),
+        ];
+
+        let block = generate_schema_block("Post", &fields, None, None, &[], None);
+        assert!(block.contains("        #[default = \"draft\"]\n        status: String,"));
+        assert!(block.contains("        #[default = 0]\n        views: i32,"));
+        assert!(block.contains("        #[default = now()]\n        published_at: DateTime,"));
+
+        let migration = generate_migration("posts", "Posts", &fields, &[], None, IdColumn::Integer);
+        assert!(migration.contains(
+            ".col(ColumnDef::new(Posts::Status).string().not_null().default(\"draft\"))"
+        ));
+        assert!(
+            migration
+                .contains(".col(ColumnDef::new(Posts::Views).integer().not_null().default(0))")
+        );
+        assert!(migration.contains(
+            ".col(ColumnDef::new(Posts::PublishedAt).timestamp_with_time_zone().not_null().default(Expr::current_timestamp()))"
+        ));
+    }
+
+    #[test]
+    fn test_generate_migration_with_uuid_id() {
+        let content =
+            generate_migration("posts", "Posts", &body_field(), &[], None, IdColumn::Uuid);
+
+        assert!(content.contains(
+            "ColumnDef::new(Posts::Id)\n                            .uuid()\n                            .not_null()\n                            .primary_key(),"
+        ));
+        assert!(!content.contains("auto_increment"));
+    }
+
+    #[test]
+    fn test_insert_child_entity() {
+        let title = vec![FieldInfo {
+            name: "title".to_string(),
+            rust_type: "String".to_string(),
+            schema_type: "String".to_string(),
+            column_method: String::new(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: false,
+            unique: false,
+        }];
+        let content = format!(
+            "use rapina::prelude::*;\n{}{}",
+            generate_schema_block("Post", &title, None, None, &[], None),
+            generate_schema_block("Tag", &title, None, None, &[], None)
+        );
+        let parent = ParentInfo::new("post", false);
+
+        let updated = insert_child_entity(&content, "Comment", &body_field(), &parent).unwrap();
+
+        assert!(updated.contains(
+            "schema! {\n    Post {\n        title: String,\n    }\n\n    Comment {\n        post: Post,\n        body: Text,\n    }\n}\n"
+        ));
+        assert!(updated.contains("schema! {\n    Tag {\n        title: String,\n    }\n}\n"));
+        assert!(
+            insert_child_entity(
+                &content,
+                "Comment",
+                &body_field(),
+                &ParentInfo::new("user", false)
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_generate_entity_module() {
+        let parent = ParentInfo::new("post", false);
+
+        let content =
+            generate_entity_module("Comment", &body_field(), Some(&parent), Prelude::Full);
+        assert_eq!(
+            content,
+            "use rapina::prelude::*;\n\nschema! {\n    extern entity Post;\n\n    Comment {\n        post: Post,\n        body: Text,\n    }\n}\n"
+        );
+        assert!(schema_block_end(&content, "Comment").is_some());
+
+        let content = generate_entity_module("Comment", &body_field(), None, Prelude::Minimal);
+        assert!(content.starts_with("use rapina::schema;\n\nschema! {\n    Comment {"));
+        assert!(!content.contains("extern entity"));
+    }
+
+    fn assert_registers_resource(content: &str, singular: &str, plural: &str) {
+        for handler in [
+            format!("list_{}", plural),
+            format!("create_{}", singular),
+            format!("get_{}", singular),
+            format!("update_{}", singular),
+            format!("delete_{}", singular),
+        ] {
+            assert!(
+                content.contains(&format!("crate::{}::handlers::{}", plural, handler)),
+                "missing {} in:\n{}",
+                handler,
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_routes_module() {
+        let registrations = vec![
+            generate_route_registration("post", "posts", "", None, false),
+            generate_route_registration("user", "users", "/api", None, false),
+        ];
+        let content = generate_routes_module(&registrations, Prelude::Full);
+
+        assert!(content.contains("use rapina::prelude::*;"));
+        assert!(content.contains("pub fn register(router: Router) -> Router {\n    router\n"));
+        assert_registers_resource(&content, "post", "posts");
+        assert_registers_resource(&content, "user", "users");
+        assert!(content.contains(".at(\"/posts\", |r| {"));
+        assert!(content.contains(".at(\"/posts/:id\", |r| {"));
+        assert!(content.contains(".at(\"/api/users\", |r| {"));
+        assert!(content.contains(".at(\"/api/users/:id\", |r| {"));
+        assert!(content.find("// posts").unwrap() < content.find("// users").unwrap());
+        assert!(content.ends_with("        })\n}\n"));
+        assert_eq!(
+            content,
+            generate_routes_module(&registrations, Prelude::Full)
+        );
+    }
+
+    #[test]
+    fn test_generate_route_registration_with_export() {
+        let registration = generate_route_registration("todo", "todos", "", None, true);
+        assert_registers_resource(&registration, "todo", "todos");
+        assert!(
+            registration.ends_with(
+                "        .get(\"/todos/export\", crate::todos::handlers::export_todos)\n"
+            )
+        );
+        assert!(!generate_route_registration("todo", "todos", "", None, false).contains("export"));
+    }
+
+    #[test]
+    fn test_with_update_route() {
+        let registration = generate_route_registration("todo", "todos", "", None, false);
+        assert_eq!(
+            with_update_route(registration.clone(), UpdateStyle::Put),
+            registration
+        );
+
+        let patched = with_update_route(registration, UpdateStyle::MergePatch);
+        assert!(patched.contains(".patch(crate::todos::handlers::update_todo)"));
+        assert!(!patched.contains(".put("));
+    }
+
+    #[test]
+    fn test_update_style_parse() {
+        assert_eq!(UpdateStyle::parse("put"), Ok(UpdateStyle::Put));
+        assert_eq!(
+            UpdateStyle::parse("merge-patch"),
+            Ok(UpdateStyle::MergePatch)
+        );
+        assert!(
+            UpdateStyle::parse("patch")
+                .unwrap_err()
+                .contains("'merge-patch'")
+        );
+    }
+
+    #[test]
+    fn test_generate_routes_module_minimal_prelude() {
+        let content = generate_routes_module(&[], Prelude::Minimal);
+        assert!(content.contains("use rapina::prelude::minimal::*;"));
+        assert!(content.contains("    router\n}\n"));
+    }
+
+    #[test]
+    fn test_append_route_registration() {
+        let content = generate_routes_module(
+            &[generate_route_registration(
+                "post", "posts", "", None, false,
+            )],
+            Prelude::Full,
+        );
+        let parent = ParentInfo::new("post", false);
+        let registration =
+            generate_route_registration("comment", "comments", "", Some(&parent), false);
+
+        let updated = append_route_registration(&content, "comments", &registration).unwrap();
+
+        assert_registers_resource(&updated, "post", "posts");
+        assert_registers_resource(&updated, "comment", "comments");
+        assert!(updated.contains(".at(\"/posts/:post_id/comments/:id\", |r| {"));
+        assert!(updated.find("// posts").unwrap() < updated.find("// comments").unwrap());
+        assert!(updated.ends_with("        })\n}\n"));
+
+        // Appending the same resource twice is a no-op
+        assert_eq!(
+            append_route_registration(&updated, "comments", &registration).unwrap(),
+            updated
+        );
+    }
+
+    #[test]
+    fn test_append_route_registration_without_register_fn() {
+        let registration = generate_route_registration("post", "posts", "", None, false);
+        assert!(append_route_registration("pub fn other() {}\n", "posts", &registration).is_none());
+    }
+
+    #[test]
+    fn test_declare_main_module() {
+        let content = "use rapina::prelude::*;\n\nfn main() {}\n";
+        let updated = declare_main_module(content, "extractors");
+        assert_eq!(
+            updated,
+            "mod extractors;\n\nuse rapina::prelude::*;\n\nfn main() {}\n"
+        );
+
+        // Already declared: no-op
+        assert_eq!(declare_main_module(&updated, "extractors"), updated);
+    }
+
+    #[test]
+    fn test_declare_main_module_after_existing_mods() {
+        let content = "//! App.\n\nmod entity;\nmod posts;\n\nuse rapina::prelude::*;\n";
+        let updated = declare_main_module(content, "extractors");
+        assert_eq!(
+            updated,
+            "//! App.\n\nmod entity;\nmod posts;\nmod extractors;\n\nuse rapina::prelude::*;\n"
+        );
+        assert_eq!(
+            declare_main_module("pub mod extractors;\n", "extractors"),
+            "pub mod extractors;\n"
+        );
+    }
+
+    #[test]
+    fn test_changes_stage_without_touching_disk() {
+        let root = std::env::temp_dir().join(format!("rapina-changes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/entity.rs"), "use rapina::prelude::*;\n").unwrap();
+
+        let mut changes = Changes::new(&root);
+        stage_schema_block(&mut changes, "\nschema! {}\n", Prelude::Full);
+        changes.write("src/users/mod.rs", "pub mod handlers;\n");
+
+        assert_eq!(
+            changes.read("src/entity.rs").unwrap(),
+            "use rapina::prelude::*;\nschema! {}\n"
+        );
+        assert!(changes.exists("src/users"));
+        assert!(!root.join("src/users").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("src/entity.rs")).unwrap(),
+            "use rapina::prelude::*;\n"
+        );
+
+        let preview = changes.preview();
+        assert!(preview.contains("--- a/src/entity.rs\n+++ b/src/entity.rs"));
+        assert!(preview.contains("+schema! {}"));
+        assert!(preview.contains("--- /dev/null\n+++ b/src/users/mod.rs"));
+        assert!(preview.contains("+pub mod handlers;"));
+
+        changes.apply().unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("src/users/mod.rs")).unwrap(),
+            "pub mod handlers;\n"
+        );
+        let _ = fs::remove_dir_all(&root);
     }
 }
@@ -0,0 +1,1054 @@
+//! Interactive database console.
+//!
+//! `rapina console` connects to the application's database and reads
+//! structured commands (`find User 1`, `count Post`, `delete Post 5`) or raw
+//! SQL from stdin. Entity names are resolved against the `schema!` blocks in
+//! `src/entity.rs`, so the console talks about the same `User` and `Post` the
+//! application does.
+
+use std::io::{self, BufRead, Write};
+
+use colored::Colorize;
+
+use super::codegen;
+use super::entities;
+use crate::output;
+
+/// Longest cell rendered before it is truncated with an ellipsis.
+const MAX_CELL_WIDTH: usize = 48;
+
+/// Rows returned by `list` when no limit is given.
+const DEFAULT_LIST_LIMIT: u64 = 20;
+
+pub struct ConsoleConfig {
+    pub url: String,
+    pub read_only: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Entities
+// ---------------------------------------------------------------------------
+
+/// An entity declared in a `schema!` block.
+#[derive(Debug, Clone, PartialEq)]
+struct EntityInfo {
+    name: String,
+    table: String,
+    primary_key: Vec<String>,
+}
+
+/// Collects the entities declared in the project's `schema!` blocks.
+fn load_entities() -> Vec<EntityInfo> {
+    entities::entity_sources()
+        .iter()
+        .flat_map(|source| parse_entities(source))
+        .collect()
+}
+
+/// Finds entity declarations in `schema!` blocks.
+///
+/// This is a line-based scan rather than a Rust parser: it relies on the
+/// layout `rapina add resource` and `rapina import database` generate, with
+/// entity attributes on their own lines and `Name {` opening each entity.
+fn parse_entities(source: &str) -> Vec<EntityInfo> {
+    let mut entities = Vec::new();
+    let mut in_schema = false;
+    let mut depth = 0usize;
+    let mut table_name: Option<String> = None;
+    let mut primary_key: Option<Vec<String>> = None;
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+
+        if !in_schema {
+            if trimmed.starts_with("schema!") && trimmed.contains('{') {
+                in_schema = true;
+                depth = 1;
+            }
+            continue;
+        }
+
+        if depth == 1 {
+            if let Some(rest) = trimmed.strip_prefix("#[table_name") {
+                table_name = rest.split('"').nth(1).map(str::to_string);
+            } else if let Some(rest) = trimmed.strip_prefix("#[primary_key(") {
+                let columns = rest.trim_end_matches([']', ')']);
+                primary_key = Some(
+                    columns
+                        .split(',')
+                        .map(|c| c.trim().to_string())
+                        .filter(|c| !c.is_empty())
+                        .collect(),
+                );
+            } else if !trimmed.starts_with("#[") && !trimmed.starts_with("//") {
+                if let Some((name, _)) = trimmed.split_once('{') {
+                    let name = name.trim();
+                    if is_identifier(name) {
+                        entities.push(EntityInfo {
+                            name: name.to_string(),
+                            table: table_name.take().unwrap_or_else(|| {
+                                codegen::pluralize(&entities::to_snake_case(name))
+                            }),
+                            primary_key: primary_key.take().unwrap_or_else(|| vec!["id".into()]),
+                        });
+                    }
+                }
+            }
+        }
+
+        for c in trimmed.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        if depth == 0 {
+            in_schema = false;
+            table_name = None;
+            primary_key = None;
+        }
+    }
+
+    entities
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn find_entity<'a>(entities: &'a [EntityInfo], name: &str) -> Result<&'a EntityInfo, String> {
+    entities
+        .iter()
+        .find(|e| e.name.eq_ignore_ascii_case(name) || e.table.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("Unknown entity '{}'. Run `entities` to list them.", name))
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Exit,
+    Entities,
+    Find { entity: String, id: String },
+    List { entity: String, limit: u64 },
+    Count { entity: String },
+    Delete { entity: String, id: String },
+    Sql(String),
+}
+
+impl Command {
+    fn is_mutating(&self) -> bool {
+        match self {
+            Command::Delete { .. } => true,
+            Command::Sql(sql) => !is_read_only_sql(sql),
+            _ => false,
+        }
+    }
+}
+
+fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let line = line.trim().trim_end_matches(';').trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let keyword = words[0].to_ascii_lowercase();
+    let args = &words[1..];
+
+    let command = match keyword.as_str() {
+        "help" | "?" => Command::Help,
+        "exit" | "quit" | "\\q" => Command::Exit,
+        "entities" => Command::Entities,
+        "find" => match args {
+            [entity, id] => Command::Find {
+                entity: entity.to_string(),
+                id: id.to_string(),
+            },
+            _ => return Err("Usage: find <Entity> <id>".to_string()),
+        },
+        "list" => match args {
+            [entity] => Command::List {
+                entity: entity.to_string(),
+                limit: DEFAULT_LIST_LIMIT,
+            },
+            [entity, limit] => Command::List {
+                entity: entity.to_string(),
+                limit: limit
+                    .parse()
+                    .map_err(|_| format!("Invalid limit '{}'", limit))?,
+            },
+            _ => return Err("Usage: list <Entity> [limit]".to_string()),
+        },
+        "count" => match args {
+            [entity] => Command::Count {
+                entity: entity.to_string(),
+            },
+            _ => return Err("Usage: count <Entity>".to_string()),
+        },
+        // `DELETE FROM ...` is SQL, `delete Post 5` is the structured command.
+        "delete" if !args.first().is_some_and(|w| w.eq_ignore_ascii_case("from")) => match args {
+            [entity, id] => Command::Delete {
+                entity: entity.to_string(),
+                id: id.to_string(),
+            },
+            _ => return Err("Usage: delete <Entity> <id>".to_string()),
+        },
+        "sql" => {
+            let sql = line[words[0].len()..].trim();
+            if sql.is_empty() {
+                return Err("Usage: sql <statement>".to_string());
+            }
+            Command::Sql(sql.to_string())
+        }
+        _ => Command::Sql(line.to_string()),
+    };
+
+    Ok(Some(command))
+}
+
+/// Whether a raw statement only reads.
+///
+/// Used to reject statements up front in `--read-only` mode. The connection
+/// itself is also opened read-only, so a write hidden inside a `WITH` still
+/// fails at the database.
+fn is_read_only_sql(sql: &str) -> bool {
+    const READ_KEYWORDS: &[&str] = &[
+        "select", "with", "show", "explain", "describe", "desc", "values", "table", "pragma",
+    ];
+    let first = sql
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '(')
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    READ_KEYWORDS.contains(&first.as_str())
+}
+
+/// Renders an id as a SQL literal: numbers as-is, anything else quoted.
+fn sql_literal(value: &str) -> String {
+    if value.parse::<i64>().is_ok() {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+fn print_help() {
+    output::blank();
+    output::info(format!("  {}", "Commands:".bold()));
+    output::info(format!(
+        "    {}    list entities and their tables",
+        "entities".cyan()
+    ));
+    output::info(format!(
+        "    {}  show one row by primary key",
+        "find <Entity> <id>".cyan()
+    ));
+    output::info(format!(
+        "    {}  show the first rows (default 20)",
+        "list <Entity> [n]".cyan()
+    ));
+    output::info(format!("    {}     count rows", "count <Entity>".cyan()));
+    output::info(format!(
+        "    {}  delete one row by primary key",
+        "delete <Entity> <id>".cyan()
+    ));
+    output::info(format!(
+        "    {}   run raw SQL (the `sql` prefix is optional)",
+        "sql <statement>".cyan()
+    ));
+    output::info(format!("    {}        leave the console", "exit".cyan()));
+    output::blank();
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+/// Rows returned by a query, already rendered as text.
+#[derive(Debug, Default)]
+pub(super) struct ResultSet {
+    pub(super) columns: Vec<String>,
+    pub(super) rows: Vec<Vec<String>>,
+}
+
+fn truncate_cell(value: &str) -> String {
+    let value = value.replace(['\n', '\r'], " ");
+    if value.chars().count() > MAX_CELL_WIDTH {
+        let mut truncated: String = value.chars().take(MAX_CELL_WIDTH - 1).collect();
+        truncated.push('…');
+        truncated
+    } else {
+        value
+    }
+}
+
+fn format_table(result: &ResultSet) -> String {
+    let rows: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|cell| truncate_cell(cell)).collect())
+        .collect();
+
+    let mut widths: Vec<usize> = result.columns.iter().map(|c| c.chars().count()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            if let Some(width) = widths.get_mut(i) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+    }
+
+    let pad = |value: &str, width: usize| {
+        let fill = width.saturating_sub(value.chars().count());
+        format!("{}{}", value, " ".repeat(fill))
+    };
+
+    let mut out = String::new();
+    let header: Vec<String> = result
+        .columns
+        .iter()
+        .zip(&widths)
+        .map(|(c, &w)| pad(c, w))
+        .collect();
+    out.push_str(&format!("  {}\n", header.join("  ").trim_end()));
+    let rule: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+    out.push_str(&format!("  {}\n", rule.join("  ")));
+    for row in &rows {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(c, &w)| pad(c, w)).collect();
+        out.push_str(&format!("  {}\n", cells.join("  ").trim_end()));
+    }
+    out
+}
+
+/// Prints the rows a command returned.
+///
+/// Results are what the user asked for, so they are printed even with
+/// `--quiet`; with `--log-format json` they are written as a `rows` event.
+fn print_result(result: &ResultSet) {
+    if output::is_json() {
+        output::record(
+            "rows",
+            serde_json::json!({ "columns": result.columns, "rows": result.rows }),
+        );
+        return;
+    }
+    println!();
+    if !result.columns.is_empty() {
+        print!("{}", format_table(result));
+        println!();
+    }
+    println!("  ({} row(s))", result.rows.len());
+    println!();
+}
+
+// ---------------------------------------------------------------------------
+// Database
+// ---------------------------------------------------------------------------
+
+/// Renders column `index` of a row as text by trying each type in turn,
+/// with `None` for NULL.
+///
+/// sqlx checks type compatibility before decoding, so a mismatch is an error
+/// rather than garbage and the next type gets a chance.
+macro_rules! render_cell {
+    ($row:expr, $index:expr, [$($ty:ty),* $(,)?]) => {{
+        use sqlx::{Column, Row, TypeInfo};
+        let row = $row;
+        let index = $index;
+        $(
+            if let Ok(value) = row.try_get::<Option<$ty>, _>(index) {
+                return value.map(|v| v.to_string());
+            }
+        )*
+        if let Ok(value) = row.try_get::<Option<Vec<u8>>, _>(index) {
+            return value.map(|v| format!("<{} bytes>", v.len()));
+        }
+        Some(format!("<{}>", row.columns()[index].type_info().name()))
+    }};
+}
+
+macro_rules! result_set {
+    ($rows:expr, $render:path) => {{
+        use sqlx::{Column, Row};
+        let rows = $rows;
+        ResultSet {
+            columns: rows
+                .first()
+                .map(|row| row.columns().iter().map(|c| c.name().to_string()).collect())
+                .unwrap_or_default(),
+            rows: rows
+                .iter()
+                .map(|row| {
+                    (0..row.len())
+                        .map(|i| $render(row, i).unwrap_or_else(|| "NULL".to_string()))
+                        .collect()
+                })
+                .collect(),
+        }
+    }};
+}
+
+macro_rules! cells {
+    ($rows:expr, $render:path) => {{
+        use sqlx::Row;
+        $rows
+            .iter()
+            .map(|row| (0..row.len()).map(|i| $render(row, i)).collect())
+            .collect()
+    }};
+}
+
+#[cfg(feature = "import-postgres")]
+fn render_pg_cell(row: &sqlx::postgres::PgRow, index: usize) -> Option<String> {
+    use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+    render_cell!(
+        row,
+        index,
+        [
+            String,
+            i16,
+            i32,
+            i64,
+            f32,
+            f64,
+            bool,
+            DateTime<Utc>,
+            NaiveDateTime,
+            NaiveDate,
+            NaiveTime,
+            sqlx::types::Uuid,
+            sqlx::types::JsonValue,
+        ]
+    )
+}
+
+#[cfg(feature = "import-mysql")]
+fn render_mysql_cell(row: &sqlx::mysql::MySqlRow, index: usize) -> Option<String> {
+    use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+    render_cell!(
+        row,
+        index,
+        [
+            String,
+            bool,
+            i8,
+            i16,
+            i32,
+            i64,
+            u64,
+            f32,
+            f64,
+            DateTime<Utc>,
+            NaiveDateTime,
+            NaiveDate,
+            NaiveTime,
+            sqlx::types::JsonValue,
+        ]
+    )
+}
+
+#[cfg(feature = "import-sqlite")]
+fn render_sqlite_cell(row: &sqlx::sqlite::SqliteRow, index: usize) -> Option<String> {
+    use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+    render_cell!(
+        row,
+        index,
+        [
+            String,
+            i64,
+            f64,
+            DateTime<Utc>,
+            NaiveDateTime,
+            NaiveDate,
+            bool,
+        ]
+    )
+}
+
+pub(super) enum Database {
+    #[cfg(feature = "import-postgres")]
+    Postgres(sqlx::PgPool),
+    #[cfg(feature = "import-mysql")]
+    MySql(sqlx::MySqlPool),
+    #[cfg(feature = "import-sqlite")]
+    Sqlite(sqlx::SqlitePool),
+}
+
+impl Database {
+    /// Opens a single connection, so session settings apply to every command.
+    pub(super) async fn connect(url: &str, read_only: bool) -> Result<Self, String> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            #[cfg(feature = "import-postgres")]
+            {
+                let mut options = sqlx::postgres::PgPoolOptions::new().max_connections(1);
+                if read_only {
+                    options = options.after_connect(|conn, _| {
+                        Box::pin(async move {
+                            sqlx::query("SET default_transaction_read_only = on")
+                                .execute(conn)
+                                .await
+                                .map(|_| ())
+                        })
+                    });
+                }
+                options
+                    .connect(url)
+                    .await
+                    .map(Database::Postgres)
+                    .map_err(|e| format!("Failed to connect to Postgres: {}", e))
+            }
+            #[cfg(not(feature = "import-postgres"))]
+            {
+                let _ = read_only;
+                Err("Postgres support requires the import-postgres feature. \
+                     Reinstall with: cargo install rapina-cli --features import-postgres"
+                    .to_string())
+            }
+        } else if url.starts_with("mysql://") || url.starts_with("mariadb://") {
+            #[cfg(feature = "import-mysql")]
+            {
+                let mut options = sqlx::mysql::MySqlPoolOptions::new().max_connections(1);
+                if read_only {
+                    options = options.after_connect(|conn, _| {
+                        Box::pin(async move {
+                            sqlx::query("SET SESSION TRANSACTION READ ONLY")
+                                .execute(conn)
+                                .await
+                                .map(|_| ())
+                        })
+                    });
+                }
+                options
+                    .connect(url)
+                    .await
+                    .map(Database::MySql)
+                    .map_err(|e| format!("Failed to connect to MySQL: {}", e))
+            }
+            #[cfg(not(feature = "import-mysql"))]
+            {
+                let _ = read_only;
+                Err("MySQL support requires the import-mysql feature. \
+                     Reinstall with: cargo install rapina-cli --features import-mysql"
+                    .to_string())
+            }
+        } else if url.starts_with("sqlite://") || url.starts_with("sqlite:") {
+            #[cfg(feature = "import-sqlite")]
+            {
+                use std::str::FromStr;
+                let options = sqlx::sqlite::SqliteConnectOptions::from_str(url)
+                    .map_err(|e| format!("Invalid SQLite URL: {}", e))?
+                    .read_only(read_only);
+                sqlx::sqlite::SqlitePoolOptions::new()
+                    .max_connections(1)
+                    .connect_with(options)
+                    .await
+                    .map(Database::Sqlite)
+                    .map_err(|e| format!("Failed to connect to SQLite: {}", e))
+            }
+            #[cfg(not(feature = "import-sqlite"))]
+            {
+                let _ = read_only;
+                Err("SQLite support requires the import-sqlite feature. \
+                     Reinstall with: cargo install rapina-cli --features import-sqlite"
+                    .to_string())
+            }
+        } else {
+            Err(format!(
+                "Unsupported database URL scheme. Expected postgres://, mysql://, or sqlite:// -- got {:?}",
+                url.split("://").next().unwrap_or("unknown")
+            ))
+        }
+    }
+
+    pub(super) fn backend(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "import-postgres")]
+            Database::Postgres(_) => "postgres",
+            #[cfg(feature = "import-mysql")]
+            Database::MySql(_) => "mysql",
+            #[cfg(feature = "import-sqlite")]
+            Database::Sqlite(_) => "sqlite",
+        }
+    }
+
+    pub(super) fn quote_ident(&self, ident: &str) -> String {
+        if self.backend() == "mysql" {
+            format!("`{}`", ident.replace('`', "``"))
+        } else {
+            format!("\"{}\"", ident.replace('"', "\"\""))
+        }
+    }
+
+    pub(super) async fn fetch(&self, sql: &str) -> Result<ResultSet, String> {
+        match *self {
+            #[cfg(feature = "import-postgres")]
+            Database::Postgres(ref pool) => sqlx::query(sql)
+                .fetch_all(pool)
+                .await
+                .map(|rows| result_set!(rows, render_pg_cell))
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-mysql")]
+            Database::MySql(ref pool) => sqlx::query(sql)
+                .fetch_all(pool)
+                .await
+                .map(|rows| result_set!(rows, render_mysql_cell))
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-sqlite")]
+            Database::Sqlite(ref pool) => sqlx::query(sql)
+                .fetch_all(pool)
+                .await
+                .map(|rows| result_set!(rows, render_sqlite_cell))
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Like [`fetch`](Self::fetch), but keeps NULL apart from the text
+    /// `"NULL"`, for callers that write the values out again.
+    pub(super) async fn fetch_cells(&self, sql: &str) -> Result<Vec<Vec<Option<String>>>, String> {
+        match *self {
+            #[cfg(feature = "import-postgres")]
+            Database::Postgres(ref pool) => sqlx::query(sql)
+                .fetch_all(pool)
+                .await
+                .map(|rows| cells!(rows, render_pg_cell))
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-mysql")]
+            Database::MySql(ref pool) => sqlx::query(sql)
+                .fetch_all(pool)
+                .await
+                .map(|rows| cells!(rows, render_mysql_cell))
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-sqlite")]
+            Database::Sqlite(ref pool) => sqlx::query(sql)
+                .fetch_all(pool)
+                .await
+                .map(|rows| cells!(rows, render_sqlite_cell))
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    pub(super) async fn execute(&self, sql: &str) -> Result<u64, String> {
+        match *self {
+            #[cfg(feature = "import-postgres")]
+            Database::Postgres(ref pool) => sqlx::query(sql)
+                .execute(pool)
+                .await
+                .map(|r| r.rows_affected())
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-mysql")]
+            Database::MySql(ref pool) => sqlx::query(sql)
+                .execute(pool)
+                .await
+                .map(|r| r.rows_affected())
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-sqlite")]
+            Database::Sqlite(ref pool) => sqlx::query(sql)
+                .execute(pool)
+                .await
+                .map(|r| r.rows_affected())
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Runs a statement without preparing it, for transaction control that
+    /// MySQL refuses to prepare. Since the pool holds a single connection,
+    /// a `BEGIN` spans every statement until the matching `COMMIT`.
+    pub(super) async fn execute_unprepared(&self, sql: &str) -> Result<(), String> {
+        match *self {
+            #[cfg(feature = "import-postgres")]
+            Database::Postgres(ref pool) => sqlx::raw_sql(sql)
+                .execute(pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-mysql")]
+            Database::MySql(ref pool) => sqlx::raw_sql(sql)
+                .execute(pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "import-sqlite")]
+            Database::Sqlite(ref pool) => sqlx::raw_sql(sql)
+                .execute(pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// REPL
+// ---------------------------------------------------------------------------
+
+fn single_primary_key(entity: &EntityInfo) -> Result<&str, String> {
+    match entity.primary_key.as_slice() {
+        [column] => Ok(column),
+        _ => Err(format!(
+            "{} has a composite primary key ({}); use SQL instead",
+            entity.name,
+            entity.primary_key.join(", ")
+        )),
+    }
+}
+
+async fn run_command(
+    db: &Database,
+    entities: &[EntityInfo],
+    command: Command,
+) -> Result<(), String> {
+    match command {
+        Command::Help | Command::Exit => {}
+        Command::Entities => {
+            if entities.is_empty() {
+                output::warn("No entities found in src/entity.rs");
+                return Ok(());
+            }
+            let result = ResultSet {
+                columns: vec!["ENTITY".into(), "TABLE".into(), "PRIMARY KEY".into()],
+                rows: entities
+                    .iter()
+                    .map(|e| vec![e.name.clone(), e.table.clone(), e.primary_key.join(", ")])
+                    .collect(),
+            };
+            if output::is_json() {
+                print_result(&result);
+            } else {
+                println!();
+                print!("{}", format_table(&result));
+                println!();
+            }
+        }
+        Command::Find { entity, id } => {
+            let entity = find_entity(entities, &entity)?;
+            let sql = format!(
+                "SELECT * FROM {} WHERE {} = {}",
+                db.quote_ident(&entity.table),
+                db.quote_ident(single_primary_key(entity)?),
+                sql_literal(&id)
+            );
+            let result = db.fetch(&sql).await?;
+            if result.rows.is_empty() {
+                output::warn(format!("{} {} not found", entity.name, id));
+            } else {
+                print_result(&result);
+            }
+        }
+        Command::List { entity, limit } => {
+            let entity = find_entity(entities, &entity)?;
+            let sql = format!(
+                "SELECT * FROM {} LIMIT {}",
+                db.quote_ident(&entity.table),
+                limit
+            );
+            print_result(&db.fetch(&sql).await?);
+        }
+        Command::Count { entity } => {
+            let entity = find_entity(entities, &entity)?;
+            let sql = format!(
+                "SELECT COUNT(*) AS count FROM {}",
+                db.quote_ident(&entity.table)
+            );
+            let result = db.fetch(&sql).await?;
+            let count = result
+                .rows
+                .first()
+                .and_then(|row| row.first())
+                .cloned()
+                .unwrap_or_else(|| "0".to_string());
+            if output::is_json() {
+                output::record(
+                    "count",
+                    serde_json::json!({ "table": entity.table, "count": count }),
+                );
+            } else {
+                println!("  {} {}", count.bold(), entity.table);
+            }
+        }
+        Command::Delete { entity, id } => {
+            let entity = find_entity(entities, &entity)?;
+            let sql = format!(
+                "DELETE FROM {} WHERE {} = {}",
+                db.quote_ident(&entity.table),
+                db.quote_ident(single_primary_key(entity)?),
+                sql_literal(&id)
+            );
+            match db.execute(&sql).await? {
+                0 => output::warn(format!("{} {} not found", entity.name, id)),
+                _ => output::success(format!("Deleted {} {}", entity.name, id)),
+            }
+        }
+        Command::Sql(sql) => {
+            if is_read_only_sql(&sql) {
+                print_result(&db.fetch(&sql).await?);
+            } else {
+                let affected = db.execute(&sql).await?;
+                output::success(format!("{} row(s) affected", affected));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Start the interactive console.
+pub fn execute(config: ConsoleConfig) -> Result<(), String> {
+    let entities = load_entities();
+
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to create async runtime: {}", e))?;
+    let db = rt.block_on(Database::connect(&config.url, config.read_only))?;
+
+    output::blank();
+    output::success(format!(
+        "Connected to {}{}, {} entit{} loaded",
+        db.backend(),
+        if config.read_only { " (read-only)" } else { "" },
+        entities.len(),
+        if entities.len() == 1 { "y" } else { "ies" }
+    ));
+    output::info(format!(
+        "  Type {} for commands, {} to quit.",
+        "help".cyan(),
+        "exit".cyan()
+    ));
+    output::blank();
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        // Prompt on stderr so stdout stays clean for --log-format json
+        eprint!("{} ", "rapina>".bright_magenta());
+        io::stderr().flush().ok();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => return Err(format!("Failed to read input: {}", e)),
+            None => break,
+        };
+
+        let command = match parse_command(&line) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(e) => {
+                output::error(e);
+                continue;
+            }
+        };
+
+        match command {
+            Command::Exit => break,
+            Command::Help => print_help(),
+            command if config.read_only && command.is_mutating() => {
+                output::error(
+                    "The console is read-only; restart without --read-only to modify data",
+                );
+            }
+            command => {
+                if let Err(e) = rt.block_on(run_command(&db, &entities, command)) {
+                    output::error(e);
+                }
+            }
+        }
+    }
+
+    output::blank();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTITIES: &str = r#"
+use rapina::prelude::*;
+
+schema! {
+    /// A registered user.
+    User {
+        email: String,
+        #[unique]
+        name: String,
+    }
+
+    #[table_name = "people"]
+    Person {
+        name: String,
+    }
+
+    #[primary_key(user_id, role_id)]
+    #[timestamps(none)]
+    UserRole {
+        user_id: i32,
+        role_id: i32,
+    }
+
+    BlogPost {
+        author: belongs_to User,
+    }
+}
+"#;
+
+    fn entities() -> Vec<EntityInfo> {
+        parse_entities(ENTITIES)
+    }
+
+    #[test]
+    fn test_parse_entities_default_table_and_pk() {
+        let entities = entities();
+        assert_eq!(
+            entities[0],
+            EntityInfo {
+                name: "User".into(),
+                table: "users".into(),
+                primary_key: vec!["id".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_entities_attributes() {
+        let entities = entities();
+        assert_eq!(entities.len(), 4);
+        assert_eq!(entities[1].table, "people");
+        assert_eq!(entities[2].table, "user_roles");
+        assert_eq!(entities[2].primary_key, vec!["user_id", "role_id"]);
+        assert_eq!(entities[3].table, "blog_posts");
+        assert_eq!(entities[3].primary_key, vec!["id"]);
+    }
+
+    #[test]
+    fn test_parse_entities_ignores_code_outside_schema() {
+        let source = "struct Foo {\n    x: i32,\n}\n";
+        assert!(parse_entities(source).is_empty());
+    }
+
+    #[test]
+    fn test_find_entity_by_name_or_table() {
+        let entities = entities();
+        assert_eq!(find_entity(&entities, "user").unwrap().name, "User");
+        assert_eq!(find_entity(&entities, "people").unwrap().name, "Person");
+        assert!(find_entity(&entities, "Comment").is_err());
+    }
+
+    #[test]
+    fn test_parse_structured_commands() {
+        assert_eq!(
+            parse_command("find User 1").unwrap(),
+            Some(Command::Find {
+                entity: "User".into(),
+                id: "1".into()
+            })
+        );
+        assert_eq!(
+            parse_command("count Post;").unwrap(),
+            Some(Command::Count {
+                entity: "Post".into()
+            })
+        );
+        assert_eq!(
+            parse_command("delete Post 5").unwrap(),
+            Some(Command::Delete {
+                entity: "Post".into(),
+                id: "5".into()
+            })
+        );
+        assert_eq!(
+            parse_command("list Post").unwrap(),
+            Some(Command::List {
+                entity: "Post".into(),
+                limit: DEFAULT_LIST_LIMIT
+            })
+        );
+        assert_eq!(parse_command("   ").unwrap(), None);
+        assert_eq!(parse_command("exit").unwrap(), Some(Command::Exit));
+    }
+
+    #[test]
+    fn test_parse_command_usage_errors() {
+        assert!(parse_command("find User").is_err());
+        assert!(parse_command("count").is_err());
+        assert!(parse_command("list Post many").is_err());
+        assert!(parse_command("sql").is_err());
+    }
+
+    #[test]
+    fn test_parse_raw_sql() {
+        assert_eq!(
+            parse_command("SELECT * FROM users").unwrap(),
+            Some(Command::Sql("SELECT * FROM users".into()))
+        );
+        assert_eq!(
+            parse_command("sql select 1;").unwrap(),
+            Some(Command::Sql("select 1".into()))
+        );
+        assert_eq!(
+            parse_command("DELETE FROM posts WHERE id = 5").unwrap(),
+            Some(Command::Sql("DELETE FROM posts WHERE id = 5".into()))
+        );
+    }
+
+    #[test]
+    fn test_mutating_commands() {
+        assert!(
+            parse_command("delete Post 5")
+                .unwrap()
+                .unwrap()
+                .is_mutating()
+        );
+        assert!(
+            parse_command("UPDATE users SET name = 'x'")
+                .unwrap()
+                .unwrap()
+                .is_mutating()
+        );
+        assert!(!parse_command("find User 1").unwrap().unwrap().is_mutating());
+        assert!(
+            !parse_command("  (select 1)")
+                .unwrap()
+                .unwrap()
+                .is_mutating()
+        );
+    }
+
+    #[test]
+    fn test_sql_literal() {
+        assert_eq!(sql_literal("42"), "42");
+        assert_eq!(sql_literal("abc"), "'abc'");
+        assert_eq!(sql_literal("o'brien"), "'o''brien'");
+        assert_eq!(sql_literal("1 OR 1=1"), "'1 OR 1=1'");
+    }
+
+    #[test]
+    fn test_format_table() {
+        let result = ResultSet {
+            columns: vec!["id".into(), "name".into()],
+            rows: vec![
+                vec!["1".into(), "Ada".into()],
+                vec!["10".into(), "NULL".into()],
+            ],
+        };
+        let table = format_table(&result);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "  id  name");
+        assert_eq!(lines[1], "  ──  ────");
+        assert_eq!(lines[2], "  1   Ada");
+        assert_eq!(lines[3], "  10  NULL");
+    }
+
+    #[test]
+    fn test_truncate_cell() {
+        let long = "x".repeat(MAX_CELL_WIDTH + 10);
+        let truncated = truncate_cell(&long);
+        assert_eq!(truncated.chars().count(), MAX_CELL_WIDTH);
+        assert!(truncated.ends_with('…'));
+        assert_eq!(truncate_cell("a\nb"), "a b");
+    }
+}
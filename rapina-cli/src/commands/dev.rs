@@ -1,6 +1,7 @@
 //! Implementation of the `rapina dev` command.
 use crate::colors;
 use crate::commands::verify_rapina_project;
+use crate::output;
 use colored::Colorize;
 use notify_debouncer_mini::{DebounceEventResult, new_debouncer, notify::RecursiveMode};
 use std::path::Path;
@@ -44,10 +45,10 @@ pub fn execute(config: DevConfig) -> Result<(), String> {
     .map_err(|e| format!("Failed to set Ctrl+C handler: {}", e))?;
 
     // Initial build and run
-    println!(
+    output::info(format!(
         "{} Building project...",
         "INFO".custom_color(colors::blue()).bold()
-    );
+    ));
 
     let mut server_process = build_and_run(&config, &binary_name)?;
 
@@ -75,22 +76,22 @@ pub fn execute(config: DevConfig) -> Result<(), String> {
             .watch(Path::new("src"), RecursiveMode::Recursive)
             .map_err(|e| format!("Failed to watch src directory: {}", e))?;
 
-        println!(
+        output::info(format!(
             "{} Watching for changes in: {}",
             "INFO".custom_color(colors::blue()).bold(),
             "./src".custom_color(colors::sky())
-        );
+        ));
 
         // Main loop
         let mut server_crashed = false;
         while running.load(Ordering::SeqCst) {
             // Check for file changes (non-blocking with timeout)
             if rx.recv_timeout(Duration::from_millis(100)).is_ok() {
-                println!();
-                println!(
+                output::blank();
+                output::info(format!(
                     "{} Change detected, rebuilding...",
                     "INFO".custom_color(colors::yellow()).bold()
-                );
+                ));
 
                 // Kill current server
                 let _ = server_process.kill();
@@ -103,7 +104,7 @@ pub fn execute(config: DevConfig) -> Result<(), String> {
                         server_crashed = false;
                     }
                     Err(e) => {
-                        eprintln!("{} {}", "ERROR".custom_color(colors::red()).bold(), e);
+                        output::error(e);
                         // Keep waiting for more changes
                     }
                 }
@@ -115,20 +116,16 @@ pub fn execute(config: DevConfig) -> Result<(), String> {
                 && !server_crashed
             {
                 server_crashed = true;
-                eprintln!(
-                    "{} Server exited with status: {}",
-                    "ERROR".custom_color(colors::red()).bold(),
-                    status
-                );
+                output::error(format!("Server exited with status: {}", status));
                 // Wait for file change before trying to restart
             }
         }
     } else {
         // No reload, just wait for the server
-        println!(
+        output::info(format!(
             "{} Hot reload disabled, press Ctrl+C to stop",
             "INFO".custom_color(colors::blue()).bold()
-        );
+        ));
 
         while running.load(Ordering::SeqCst) {
             std::thread::sleep(Duration::from_millis(100));
@@ -144,11 +141,11 @@ pub fn execute(config: DevConfig) -> Result<(), String> {
     }
 
     // Cleanup
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "{} Shutting down...",
         "INFO".custom_color(colors::blue()).bold()
-    );
+    ));
     let _ = server_process.kill();
     let _ = server_process.wait();
 
@@ -165,14 +162,15 @@ fn build_and_run(config: &DevConfig, binary_name: &str) -> Result<Child, String>
 
     if !build_output.status.success() {
         let stderr = String::from_utf8_lossy(&build_output.stderr);
+        // Compiler errors go straight to stderr, even with --quiet
         eprintln!("{}", stderr);
         return Err("Build failed".to_string());
     }
 
-    println!(
+    output::info(format!(
         "{} Build successful",
         "INFO".custom_color(colors::green()).bold()
-    );
+    ));
 
     // Run the server
     let child = Command::new(format!("./target/debug/{}", binary_name))
@@ -183,11 +181,11 @@ fn build_and_run(config: &DevConfig, binary_name: &str) -> Result<Child, String>
         .spawn()
         .map_err(|e| format!("Failed to start server: {}", e))?;
 
-    println!(
+    output::info(format!(
         "{} Server started on {} (Press CTRL+C to quit)",
         "INFO".custom_color(colors::green()).bold(),
         format!("http://{}:{}", config.host, config.port).custom_color(colors::sky())
-    );
+    ));
 
     Ok(child)
 }
@@ -219,68 +217,68 @@ fn print_banner(config: &DevConfig) {
     // Box is 61 chars wide total, 59 chars inner content
     let b = "│".custom_color(colors::mauve());
 
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         " {}",
         "╭───────────────────────────────────────────────────────────╮"
             .custom_color(colors::mauve())
-    );
+    ));
 
     // Title - centered in 59 chars
-    println!(
+    output::info(format!(
         " {}{}{}",
         b,
         format!("{:^59}", "Rapina CLI - Development Mode")
             .custom_color(colors::lavender())
             .bold(),
         b
-    );
+    ));
 
-    println!(
+    output::info(format!(
         " {}",
         "├───────────────────────────────────────────────────────────┤"
             .custom_color(colors::mauve())
-    );
+    ));
 
     // Empty line
-    println!(" {}{:59}{}", b, "", b);
+    output::info(format!(" {}{:59}{}", b, "", b));
 
     // Serving at: align label and value
     let serving_line = format!(" Serving at: {:<44}", url);
-    println!(
+    output::info(format!(
         " {}{}{}",
         b,
         format!("{:59}", serving_line).custom_color(colors::sky()),
         b
-    );
+    ));
 
     // Routes: align with Serving at
     let routes_line = format!(" Routes:     {:<44}", routes_url);
-    println!(
+    output::info(format!(
         " {}{}{}",
         b,
         format!("{:59}", routes_line).custom_color(colors::sky()),
         b
-    );
+    ));
 
     // Empty line
-    println!(" {}{:59}{}", b, "", b);
+    output::info(format!(" {}{:59}{}", b, "", b));
 
     // Production hint
-    println!(
+    output::info(format!(
         " {}{}{}",
         b,
         format!(" {:<58}", "For production: cargo build --release").custom_color(colors::subtext()),
         b
-    );
+    ));
 
     // Empty line
-    println!(" {}{:59}{}", b, "", b);
+    output::info(format!(" {}{:59}{}", b, "", b));
 
-    println!(
+    output::info(format!(
         " {}",
         "╰───────────────────────────────────────────────────────────╯"
             .custom_color(colors::mauve())
-    );
-    println!();
+    ));
+    output::blank();
 }
@@ -1,9 +1,9 @@
 //! Health checks for your Rapina API.
 
-use crate::common::urls;
+use crate::common::{introspection, urls};
+use crate::output;
 use colored::Colorize;
 use serde_json::Value;
-use std::process::Command;
 
 struct DiagnosticResult {
     warnings: Vec<String>,
@@ -14,21 +14,24 @@ struct DiagnosticResult {
 pub struct DoctorConfig {
     pub host: String,
     pub port: u16,
+    /// Introspection token the application requires, if any.
+    pub token: Option<String>,
 }
 
 /// Run health checks on the API.
 pub fn execute(config: DoctorConfig) -> Result<(), String> {
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "  {} Running API health checks on http://{}:{}...",
         "→".cyan(),
         config.host,
         config.port
-    );
-    println!();
+    ));
+    output::blank();
 
-    let routes = fetch_json(&urls::build_routes_url(&config.host, config.port))?;
-    let openapi = fetch_json(&urls::build_openapi_url(&config.host, config.port));
+    let token = config.token.as_deref();
+    let routes = fetch_json(&urls::build_routes_url(&config.host, config.port), token)?;
+    let openapi = fetch_json(&urls::build_openapi_url(&config.host, config.port), token);
 
     let mut result = DiagnosticResult {
         warnings: Vec::new(),
@@ -141,7 +144,7 @@ fn check_openapi_metadata(openapi: &Result<Value, String>, result: &mut Diagnost
             continue;
         }
 
-        for method in ["get", "post", "put", "delete"] {
+        for method in ["get", "post", "put", "patch", "delete"] {
             if let Some(operation) = item.get(method) {
                 let has_summary = operation.get("summary").is_some();
                 let has_description = operation.get("description").is_some();
@@ -170,49 +173,46 @@ fn check_openapi_metadata(openapi: &Result<Value, String>, result: &mut Diagnost
 fn print_results(result: &DiagnosticResult) {
     // Print passed checks
     for msg in &result.passed {
-        println!("  {} {}", "✓".green(), msg);
+        output::success(msg);
     }
 
     // Print warnings
     for msg in &result.warnings {
-        println!("  {} {}", "⚠".yellow(), msg);
+        output::warn(msg);
     }
 
     // Print errors
     for msg in &result.errors {
-        println!("  {} {}", "✗".red(), msg);
+        output::error(msg);
     }
 
-    println!();
+    output::blank();
 
     // Summary
-    println!(
+    output::info(format!(
         "  {} {} passed, {} warnings, {} errors",
         "Summary:".bold(),
         result.passed.len().to_string().green(),
         result.warnings.len().to_string().yellow(),
         result.errors.len().to_string().red()
-    );
-    println!();
+    ));
+    output::blank();
 
     if result.warnings.is_empty() && result.errors.is_empty() {
-        println!("  Your API is healthy.");
+        output::info("  Your API is healthy.");
     } else if result.errors.is_empty() {
-        println!("  Consider addressing the warnings above.");
+        output::info("  Consider addressing the warnings above.");
     }
-    println!();
+    output::blank();
 }
 
 /// Fetch JSON from URL.
-fn fetch_json(url: &str) -> Result<Value, String> {
-    let output = Command::new("curl")
-        .args(["-s", "-f", url])
-        .output()
-        .map_err(|e| format!("Failed to run curl: {}", e))?;
+fn fetch_json(url: &str, token: Option<&str>) -> Result<Value, String> {
+    let output = introspection::fetch(url, token)?;
 
     if !output.status.success() {
         return Err(format!(
-            "Failed to fetch data. Is the server running? ({})",
+            "Failed to fetch data. Is the server running? ({}) If it requires an introspection token, pass --token.",
             url
         ));
     }
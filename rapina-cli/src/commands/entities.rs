@@ -0,0 +1,357 @@
+//! Finding and parsing the `schema!` entities of a project.
+//!
+//! Shared by the commands that read entity declarations: the database
+//! console, `db fake`, `db export`/`db import-data`, `rename field` and
+//! `schema diagram`.
+
+use std::path::{Path, PathBuf};
+
+use syn::ext::IdentExt;
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Ident, Token};
+
+use super::codegen;
+
+/// Reads `src/entity.rs` and any `src/entity/*.rs` or `src/entities/*.rs`,
+/// in a stable order.
+#[cfg(feature = "import")]
+pub(super) fn entity_sources() -> Vec<String> {
+    entity_files(Path::new(""))
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .collect()
+}
+
+/// `src/entity.rs` followed by `src/entity/*.rs` and `src/entities/*.rs`
+/// under `root`, sorted. The files may not exist.
+pub(super) fn entity_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = vec![root.join("src/entity.rs")];
+    for dir in ["src/entity", "src/entities"] {
+        if let Ok(entries) = std::fs::read_dir(root.join(dir)) {
+            let mut nested: Vec<_> = entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+                .collect();
+            nested.sort();
+            files.extend(nested);
+        }
+    }
+    files
+}
+
+/// Same conversion `schema!` uses for table names (`BlogPost` -> `blog_post`).
+pub(super) fn to_snake_case(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev_lower =
+                i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            let prev_upper = i > 0 && chars[i - 1].is_uppercase();
+            if prev_lower || (prev_upper && next_lower) {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// schema! blocks
+// ---------------------------------------------------------------------------
+
+/// The column types `schema!` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Scalar {
+    String,
+    Text,
+    I32,
+    I64,
+    F32,
+    F64,
+    Bool,
+    Uuid,
+    DateTime,
+    NaiveDateTime,
+    Date,
+    Decimal,
+    Json,
+}
+
+impl Scalar {
+    pub(super) fn from_ident(ident: &str) -> Option<Self> {
+        Some(match ident {
+            "String" => Scalar::String,
+            "Text" => Scalar::Text,
+            "i32" => Scalar::I32,
+            "i64" => Scalar::I64,
+            "f32" => Scalar::F32,
+            "f64" => Scalar::F64,
+            "bool" => Scalar::Bool,
+            "Uuid" => Scalar::Uuid,
+            "DateTime" => Scalar::DateTime,
+            "NaiveDateTime" => Scalar::NaiveDateTime,
+            "Date" => Scalar::Date,
+            "Decimal" => Scalar::Decimal,
+            "Json" => Scalar::Json,
+            _ => return None,
+        })
+    }
+
+    /// The type as written in `schema!`.
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Scalar::String => "String",
+            Scalar::Text => "Text",
+            Scalar::I32 => "i32",
+            Scalar::I64 => "i64",
+            Scalar::F32 => "f32",
+            Scalar::F64 => "f64",
+            Scalar::Bool => "bool",
+            Scalar::Uuid => "Uuid",
+            Scalar::DateTime => "DateTime",
+            Scalar::NaiveDateTime => "NaiveDateTime",
+            Scalar::Date => "Date",
+            Scalar::Decimal => "Decimal",
+            Scalar::Json => "Json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum FieldKind {
+    Scalar(Scalar),
+    /// A `belongs_to` field, naming the parent entity.
+    BelongsTo(String),
+    /// A `Vec` of a scalar, stored as a Postgres array.
+    Array(Scalar),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Field {
+    pub(super) name: String,
+    pub(super) column: String,
+    pub(super) kind: FieldKind,
+    pub(super) optional: bool,
+    pub(super) unique: bool,
+}
+
+/// An entity declared in a `schema!` block.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Entity {
+    pub(super) name: String,
+    pub(super) table: String,
+    /// `#[primary_key(..)]` columns; `None` for the default `id`.
+    pub(super) primary_key: Option<Vec<String>>,
+    /// Type of the default `id`, from `#[id(..)]`.
+    pub(super) id: Scalar,
+    pub(super) fields: Vec<Field>,
+    /// `Vec<T>` fields of another entity, as (field, entity).
+    pub(super) has_many: Vec<(String, String)>,
+    pub(super) created_at: bool,
+    pub(super) updated_at: bool,
+}
+
+impl Entity {
+    pub(super) fn is_primary_key(&self, field: &Field) -> bool {
+        self.primary_key
+            .as_ref()
+            .is_some_and(|pk| pk.contains(&field.name))
+    }
+}
+
+/// The `schema!` invocations among a file's items.
+pub(super) fn schema_blocks(file: &syn::File) -> impl Iterator<Item = &syn::Macro> {
+    file.items.iter().filter_map(|item| match item {
+        syn::Item::Macro(item)
+            if item
+                .mac
+                .path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "schema") =>
+        {
+            Some(&item.mac)
+        }
+        _ => None,
+    })
+}
+
+/// Parses the entities in the tokens of a `schema!` invocation.
+pub(super) fn parse_schema(input: ParseStream) -> syn::Result<Vec<Entity>> {
+    let mut entities = Vec::new();
+
+    while !input.is_empty() {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name: Ident = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+
+        let mut fields = Vec::new();
+        let mut has_many = Vec::new();
+        while !content.is_empty() {
+            let field_attrs = content.call(Attribute::parse_outer)?;
+            let field_name = content.call(Ident::parse_any)?.unraw().to_string();
+            content.parse::<Token![:]>()?;
+            let ty: syn::Type = content.parse()?;
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+            match vec_inner(&ty) {
+                Some(target) if Scalar::from_ident(&target).is_none() => {
+                    has_many.push((field_name, target));
+                }
+                _ => fields.extend(parse_field(&field_name, &field_attrs, &ty)),
+            }
+        }
+
+        entities.push(parse_entity(name.to_string(), &attrs, fields, has_many)?);
+    }
+
+    Ok(entities)
+}
+
+fn parse_entity(
+    name: String,
+    attrs: &[Attribute],
+    fields: Vec<Field>,
+    has_many: Vec<(String, String)>,
+) -> syn::Result<Entity> {
+    let mut entity = Entity {
+        table: codegen::pluralize(&to_snake_case(&name)),
+        name,
+        primary_key: None,
+        id: Scalar::I32,
+        fields,
+        has_many,
+        created_at: true,
+        updated_at: true,
+    };
+
+    for attr in attrs {
+        if attr.path().is_ident("table_name") {
+            if let Some(table) = string_value(attr) {
+                entity.table = table;
+            }
+        } else if attr.path().is_ident("primary_key") {
+            let columns = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+            entity.primary_key = Some(columns.iter().map(|c| c.to_string()).collect());
+        } else if attr.path().is_ident("id") {
+            let id: Ident = attr.parse_args()?;
+            entity.id = Scalar::from_ident(&id.to_string()).unwrap_or(Scalar::I32);
+        } else if attr.path().is_ident("timestamps") {
+            let option: Ident = attr.parse_args()?;
+            (entity.created_at, entity.updated_at) = match option.to_string().as_str() {
+                "created_at" => (true, false),
+                "updated_at" => (false, true),
+                _ => (false, false),
+            };
+        }
+    }
+
+    Ok(entity)
+}
+
+/// Builds a column field from its declaration. Types that aren't a scalar,
+/// an entity or a `Vec` of scalars return `None`.
+fn parse_field(name: &str, attrs: &[Attribute], ty: &syn::Type) -> Option<Field> {
+    let (type_name, optional) = type_name(ty)?;
+    let unique = attrs.iter().any(|attr| attr.path().is_ident("unique"));
+    let column = || {
+        attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("column"))
+            .and_then(string_value)
+            .unwrap_or_else(|| name.to_string())
+    };
+    let (kind, column) = if type_name == "Vec" {
+        let scalar = Scalar::from_ident(&vec_inner(ty)?)?;
+        (FieldKind::Array(scalar), column())
+    } else {
+        match Scalar::from_ident(&type_name) {
+            Some(scalar) => (FieldKind::Scalar(scalar), column()),
+            None => (
+                FieldKind::BelongsTo(type_name),
+                format!("{}_id", to_snake_case(name)),
+            ),
+        }
+    };
+
+    Some(Field {
+        name: name.to_string(),
+        column,
+        kind,
+        optional,
+        unique,
+    })
+}
+
+/// The last path segment of a type, unwrapping `Option<T>`.
+fn type_name(ty: &syn::Type) -> Option<(String, bool)> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident == "Option" {
+        let (name, _) = type_name(first_type_argument(segment)?)?;
+        return Some((name, true));
+    }
+    Some((segment.ident.to_string(), false))
+}
+
+/// `T` in `Vec<T>` or `Option<Vec<T>>`.
+fn vec_inner(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident == "Option" {
+        return vec_inner(first_type_argument(segment)?);
+    }
+    if segment.ident != "Vec" {
+        return None;
+    }
+    let (name, _) = type_name(first_type_argument(segment)?)?;
+    Some(name)
+}
+
+fn first_type_argument(segment: &syn::PathSegment) -> Option<&syn::Type> {
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// The string in `#[name = "value"]`.
+fn string_value(attr: &Attribute) -> Option<String> {
+    let syn::Meta::NameValue(meta) = &attr.meta else {
+        return None;
+    };
+    match &meta.value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(value),
+            ..
+        }) => Some(value.value()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("User"), "user");
+        assert_eq!(to_snake_case("BlogPost"), "blog_post");
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+    }
+}
@@ -0,0 +1,1224 @@
+//! Fake data for `schema!` entities.
+//!
+//! `rapina db fake <resource> --count 1000` reads the entity's fields from
+//! the `schema!` blocks in `src/entity.rs` and inserts rows of plausible
+//! values, in batches, inside a single transaction. Values follow the column
+//! type and are refined by the column name, so `email` gets an address,
+//! `name` a person's name and `created_at` a timestamp from the past year.
+//!
+//! `belongs_to` fields (and integer `<entity>_id` columns) point at existing
+//! parent rows. When a parent table is empty, some parent rows are faked
+//! first, recursively.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{Datelike, Duration, NaiveDateTime, Utc};
+use syn::parse::Parser;
+
+use super::codegen;
+use super::console::Database;
+use super::entities::{self, Entity, Field, FieldKind, Scalar, parse_schema, schema_blocks};
+use crate::output;
+
+/// Rows per `INSERT` statement. SQLite caps a `VALUES` list at 500 rows.
+pub(super) const BATCH_SIZE: usize = 100;
+
+/// Upper bound on the parent rows created for an empty parent table.
+const MAX_PARENT_ROWS: u64 = 100;
+
+/// Fresh values tried for a unique column before falling back to a suffix.
+const UNIQUE_ATTEMPTS: usize = 20;
+
+/// Fresh key combinations tried for a composite primary key.
+const COMPOSITE_KEY_ATTEMPTS: usize = 100;
+
+/// Share of optional columns left NULL.
+const NULL_RATE: f64 = 0.1;
+
+/// Seeded runs date their rows back from this instant rather than from now,
+/// so a seed produces the same rows whenever it runs.
+const SEEDED_NOW: &str = "2026-01-01 00:00:00";
+
+pub(super) const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub struct FakeConfig {
+    pub url: String,
+    pub resource: String,
+    pub count: u64,
+    pub seed: Option<u64>,
+}
+
+// ---------------------------------------------------------------------------
+// Entities
+// ---------------------------------------------------------------------------
+
+impl Entity {
+    /// The column other tables reference this entity by.
+    fn key_column(&self) -> Result<&str, String> {
+        match self.primary_key.as_deref() {
+            None => Ok("id"),
+            Some([column]) => Ok(column),
+            Some(columns) => Err(format!(
+                "{} has a composite primary key ({}) and can't be referenced",
+                self.name,
+                columns.join(", ")
+            )),
+        }
+    }
+}
+
+pub(super) fn load_entities() -> Vec<Entity> {
+    entities::entity_sources()
+        .iter()
+        .flat_map(|source| parse_entities(source))
+        .collect()
+}
+
+/// Parses every `schema!` block in a source file. Blocks that don't parse
+/// are skipped; the application wouldn't compile with them either.
+pub(super) fn parse_entities(source: &str) -> Vec<Entity> {
+    let Ok(file) = syn::parse_file(source) else {
+        return Vec::new();
+    };
+
+    schema_blocks(&file)
+        .filter_map(|mac| parse_schema.parse2(mac.tokens.clone()).ok())
+        .flatten()
+        .collect()
+}
+
+/// Accepts the entity name, its table, or a singular or plural resource name
+/// (`User`, `users`, `user`, `blog_posts`).
+pub(super) fn find_entity<'a>(
+    entities: &'a [Entity],
+    resource: &str,
+) -> Result<&'a Entity, String> {
+    let pascal = codegen::to_pascal_case(&codegen::singularize(resource));
+    entities
+        .iter()
+        .find(|e| {
+            e.name.eq_ignore_ascii_case(resource)
+                || e.table.eq_ignore_ascii_case(resource)
+                || e.name == pascal
+        })
+        .ok_or_else(|| {
+            if entities.is_empty() {
+                "No schema! entities found in src/entity.rs".to_string()
+            } else {
+                let names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
+                format!(
+                    "Unknown resource '{}'. Entities: {}",
+                    resource,
+                    names.join(", ")
+                )
+            }
+        })
+}
+
+/// The entity a field points at: the target of a `belongs_to`, or the
+/// entity named by an integer `<entity>_id` column.
+fn referenced_entity<'a>(entities: &'a [Entity], field: &Field) -> Option<&'a Entity> {
+    match &field.kind {
+        FieldKind::BelongsTo(name) => entities.iter().find(|e| &e.name == name),
+        FieldKind::Scalar(Scalar::I32 | Scalar::I64) => {
+            let stem = field.column.strip_suffix("_id")?;
+            entities
+                .iter()
+                .find(|e| entities::to_snake_case(&e.name) == stem)
+        }
+        FieldKind::Scalar(_) | FieldKind::Array(_) => None,
+    }
+}
+
+/// `target` and the entities it references, parents first.
+///
+/// References that lead back to an entity already on the path (including
+/// self-references) are not followed; they can only point at rows that
+/// already exist.
+fn insertion_order<'a>(entities: &'a [Entity], target: &'a Entity) -> Vec<&'a Entity> {
+    fn visit<'a>(
+        entities: &'a [Entity],
+        entity: &'a Entity,
+        path: &mut Vec<&'a str>,
+        order: &mut Vec<&'a Entity>,
+    ) {
+        if order.iter().any(|e| e.name == entity.name) {
+            return;
+        }
+        path.push(&entity.name);
+        for field in &entity.fields {
+            if let Some(parent) = referenced_entity(entities, field)
+                && !path.contains(&parent.name.as_str())
+            {
+                visit(entities, parent, path, order);
+            }
+        }
+        path.pop();
+        order.push(entity);
+    }
+
+    let mut order = Vec::new();
+    visit(entities, target, &mut Vec::new(), &mut order);
+    order
+}
+
+// ---------------------------------------------------------------------------
+// Values
+// ---------------------------------------------------------------------------
+
+/// SplitMix64. Small, and stable across releases, so a seed keeps producing
+/// the same rows.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `low..=high`.
+    fn range(&mut self, low: i64, high: i64) -> i64 {
+        let span = high.abs_diff(low) + 1;
+        low.wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    /// Uniform in `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        self.unit() < probability
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> T {
+        items[(self.next_u64() % items.len() as u64) as usize].clone()
+    }
+}
+
+fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos ^ u64::from(std::process::id()).rotate_left(32)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Value {
+    Null,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl Value {
+    /// A key read back from the database, as the console renders it.
+    fn from_rendered(value: &str) -> Self {
+        match value.parse() {
+            Ok(n) => Value::Int(n),
+            Err(_) => Value::Text(value.to_string()),
+        }
+    }
+
+    fn to_sql(&self) -> String {
+        match self {
+            Value::Null => "NULL".to_string(),
+            Value::Int(n) => n.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::Bool(true) => "TRUE".to_string(),
+            Value::Bool(false) => "FALSE".to_string(),
+            Value::Text(s) => format!("'{}'", s.replace('\'', "''")),
+        }
+    }
+
+    /// What uniqueness is checked on. NULLs never collide.
+    fn key(&self) -> Option<String> {
+        match self {
+            Value::Null => None,
+            Value::Int(n) => Some(n.to_string()),
+            Value::Float(f) => Some(f.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Text(s) => Some(s.clone()),
+        }
+    }
+
+    /// The `n`th variation of a value, for unique columns that ran out of
+    /// fresh values. `None` for values that can't vary.
+    fn variant(&self, n: u64) -> Option<Value> {
+        match self {
+            Value::Int(i) => Some(Value::Int(i.wrapping_add(n as i64))),
+            Value::Float(f) => Some(Value::Float(f + n as f64)),
+            Value::Text(s) => Some(Value::Text(match s.split_once('@') {
+                Some((local, domain)) => format!("{}{}@{}", local, n, domain),
+                None => format!("{}-{}", s, n),
+            })),
+            Value::Null | Value::Bool(_) => None,
+        }
+    }
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada",
+    "Alan",
+    "Grace",
+    "Linus",
+    "Margaret",
+    "Dennis",
+    "Barbara",
+    "Ken",
+    "Frances",
+    "Edsger",
+    "Radia",
+    "Tim",
+    "Hedy",
+    "Donald",
+    "Katherine",
+    "John",
+    "Sophie",
+    "Niklaus",
+    "Joan",
+    "Guido",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Lovelace", "Turing", "Hopper", "Torvalds", "Hamilton", "Ritchie", "Liskov", "Thompson",
+    "Allen", "Dijkstra", "Perlman", "Berners", "Lamarr", "Knuth", "Johnson", "McCarthy", "Wilson",
+    "Wirth", "Clarke", "Rossum",
+];
+
+const WORDS: &[&str] = &[
+    "amber", "river", "quiet", "stone", "harbor", "maple", "signal", "copper", "meadow", "orbit",
+    "lantern", "summit", "velvet", "canyon", "ember", "glacier", "hollow", "island", "juniper",
+    "kettle", "lumen", "marble", "nectar", "oasis", "pepper", "quartz", "ridge", "saffron",
+    "timber", "umber", "vista", "willow", "zephyr", "beacon", "cobalt", "delta",
+];
+
+const CITIES: &[&str] = &[
+    "Lisbon",
+    "São Paulo",
+    "Toronto",
+    "Nairobi",
+    "Osaka",
+    "Berlin",
+    "Austin",
+    "Melbourne",
+    "Bogotá",
+    "Oslo",
+    "Seoul",
+    "Dublin",
+];
+
+const COUNTRIES: &[&str] = &[
+    "Portugal",
+    "Brazil",
+    "Canada",
+    "Kenya",
+    "Japan",
+    "Germany",
+    "United States",
+    "Australia",
+    "Colombia",
+    "Norway",
+    "South Korea",
+    "Ireland",
+];
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+const COLORS: &[&str] = &["red", "green", "blue", "yellow", "purple", "orange", "teal"];
+
+const STATUSES: &[&str] = &["active", "pending", "archived"];
+
+fn name_has(name: &str, words: &[&str]) -> bool {
+    words.iter().any(|w| name.contains(w))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Produces column values from a type and a column name.
+struct Generator {
+    rng: Rng,
+    now: NaiveDateTime,
+}
+
+impl Generator {
+    fn value(&mut self, column: &str, scalar: Scalar) -> Value {
+        let name = column.to_ascii_lowercase();
+        match scalar {
+            Scalar::String => Value::Text(self.text(&name, false)),
+            Scalar::Text => Value::Text(self.text(&name, true)),
+            Scalar::I32 | Scalar::I64 => Value::Int(self.integer(&name)),
+            Scalar::F32 | Scalar::F64 | Scalar::Decimal => Value::Float(self.float(&name)),
+            Scalar::Bool => Value::Bool(self.rng.chance(0.5)),
+            Scalar::Uuid => Value::Text(self.uuid()),
+            Scalar::DateTime | Scalar::NaiveDateTime => {
+                Value::Text(self.timestamp(&name).format(TIMESTAMP_FORMAT).to_string())
+            }
+            Scalar::Date => Value::Text(self.date(&name)),
+            Scalar::Json => Value::Text(format!(
+                "{{\"tags\": [\"{}\", \"{}\"]}}",
+                self.rng.pick(WORDS),
+                self.rng.pick(WORDS)
+            )),
+        }
+    }
+
+    fn text(&mut self, name: &str, long: bool) -> String {
+        if name_has(name, &["email"]) {
+            format!(
+                "{}.{}{}@{}",
+                self.rng.pick(FIRST_NAMES).to_lowercase(),
+                self.rng.pick(LAST_NAMES).to_lowercase(),
+                self.rng.range(1, 9999),
+                self.rng.pick(DOMAINS)
+            )
+        } else if name_has(name, &["first_name", "given_name"]) {
+            self.rng.pick(FIRST_NAMES).to_string()
+        } else if name_has(name, &["last_name", "surname", "family_name"]) {
+            self.rng.pick(LAST_NAMES).to_string()
+        } else if name_has(name, &["username", "login", "handle", "nickname"]) {
+            format!(
+                "{}{}",
+                self.rng.pick(FIRST_NAMES).to_lowercase(),
+                self.rng.range(1, 9999)
+            )
+        } else if name == "name" || name.ends_with("_name") || name == "author" {
+            format!(
+                "{} {}",
+                self.rng.pick(FIRST_NAMES),
+                self.rng.pick(LAST_NAMES)
+            )
+        } else if name_has(name, &["phone", "mobile"]) {
+            format!(
+                "+1 555-{:03}-{:04}",
+                self.rng.range(0, 999),
+                self.rng.range(0, 9999)
+            )
+        } else if name_has(name, &["avatar", "image", "photo", "picture"]) {
+            format!(
+                "https://images.example.com/{}.png",
+                self.rng.range(1, 100_000)
+            )
+        } else if name_has(name, &["url", "website", "homepage", "link"]) {
+            format!(
+                "https://{}.example.com/{}",
+                self.rng.pick(WORDS),
+                self.rng.pick(WORDS)
+            )
+        } else if name_has(name, &["slug"]) {
+            let words = self.words(2, 4);
+            words.join("-")
+        } else if name_has(name, &["city"]) {
+            self.rng.pick(CITIES).to_string()
+        } else if name_has(name, &["country"]) {
+            self.rng.pick(COUNTRIES).to_string()
+        } else if name_has(name, &["address", "street"]) {
+            format!(
+                "{} {} Street",
+                self.rng.range(1, 9999),
+                capitalize(self.rng.pick(WORDS))
+            )
+        } else if name_has(name, &["zip", "postal"]) {
+            format!("{:05}", self.rng.range(0, 99_999))
+        } else if name_has(name, &["color", "colour"]) {
+            self.rng.pick(COLORS).to_string()
+        } else if name_has(name, &["status"]) {
+            self.rng.pick(STATUSES).to_string()
+        } else if name_has(name, &["password", "hash", "digest"]) {
+            self.hex(64)
+        } else if name_has(name, &["token", "secret", "api_key"]) {
+            self.hex(32)
+        } else if name_has(name, &["sku", "code", "reference"]) {
+            self.hex(8).to_uppercase()
+        } else if name_has(name, &["title", "subject", "headline", "label"]) {
+            let words = self.words(2, 5);
+            capitalize(&words.join(" "))
+        } else if long
+            || name_has(
+                name,
+                &[
+                    "description",
+                    "body",
+                    "content",
+                    "bio",
+                    "summary",
+                    "comment",
+                    "note",
+                    "message",
+                ],
+            )
+        {
+            let sentences = self.rng.range(1, 3);
+            (0..sentences)
+                .map(|_| format!("{}.", capitalize(&self.words(5, 12).join(" "))))
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else {
+            self.words(1, 3).join(" ")
+        }
+    }
+
+    fn integer(&mut self, name: &str) -> i64 {
+        let (low, high) = if name.ends_with("_id") {
+            (1, 10_000)
+        } else if name == "age" || name.ends_with("_age") {
+            (18, 90)
+        } else if name_has(name, &["year"]) {
+            (1970, i64::from(self.now.year()))
+        } else if name_has(name, &["rating", "stars", "score"]) {
+            (1, 5)
+        } else if name_has(name, &["quantity", "qty", "count", "stock", "inventory"]) {
+            (0, 500)
+        } else if name_has(
+            name,
+            &["price", "amount", "cents", "total", "cost", "balance"],
+        ) {
+            (100, 100_000)
+        } else if name_has(name, &["position", "sort", "rank", "priority", "order"]) {
+            (1, 100)
+        } else if name_has(name, &["duration", "seconds"]) {
+            (1, 3600)
+        } else {
+            (1, 10_000)
+        };
+        self.rng.range(low, high)
+    }
+
+    fn float(&mut self, name: &str) -> f64 {
+        let (low, high) = if name.starts_with("lat") {
+            (-90.0, 90.0)
+        } else if name.starts_with("lng") || name.starts_with("lon") {
+            (-180.0, 180.0)
+        } else if name_has(
+            name,
+            &["price", "amount", "total", "cost", "balance", "salary"],
+        ) {
+            (1.0, 1000.0)
+        } else if name_has(name, &["rating", "score"]) {
+            (1.0, 5.0)
+        } else if name_has(name, &["percent", "rate", "ratio"]) {
+            (0.0, 100.0)
+        } else {
+            (0.0, 1000.0)
+        };
+        let value = low + self.rng.unit() * (high - low);
+        (value * 100.0).round() / 100.0
+    }
+
+    /// A random version 4 UUID.
+    fn uuid(&mut self) -> String {
+        let mut hex: Vec<char> = self.hex(32).chars().collect();
+        hex[12] = '4';
+        hex[16] = self.rng.pick(&['8', '9', 'a', 'b']);
+        let hex: String = hex.into_iter().collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+
+    /// Within the past year, or the next year for deadlines and expiries.
+    fn timestamp(&mut self, name: &str) -> NaiveDateTime {
+        let offset = Duration::seconds(self.rng.range(0, 365 * 24 * 3600));
+        if name_has(name, &["expire", "due", "deadline", "scheduled", "until"]) {
+            self.now + offset
+        } else {
+            self.now - offset
+        }
+    }
+
+    fn date(&mut self, name: &str) -> String {
+        let date = if name_has(name, &["birth", "dob"]) {
+            self.now.date() - Duration::days(self.rng.range(18 * 365, 80 * 365))
+        } else {
+            self.timestamp(name).date()
+        };
+        date.format("%Y-%m-%d").to_string()
+    }
+
+    /// `created_at` in the past year and `updated_at` between it and now.
+    fn timestamps(&mut self) -> (NaiveDateTime, NaiveDateTime) {
+        let created = self.timestamp("created_at");
+        let since = (self.now - created).num_seconds();
+        let updated = created + Duration::seconds(self.rng.range(0, since));
+        (created, updated)
+    }
+
+    fn words(&mut self, min: i64, max: i64) -> Vec<&'static str> {
+        let count = self.rng.range(min, max);
+        (0..count).map(|_| self.rng.pick(WORDS)).collect()
+    }
+
+    fn hex(&mut self, len: usize) -> String {
+        let mut out = String::with_capacity(len + 16);
+        while out.len() < len {
+            out.push_str(&format!("{:016x}", self.rng.next_u64()));
+        }
+        out.truncate(len);
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Inserting
+// ---------------------------------------------------------------------------
+
+enum Source<'a> {
+    Scalar(Scalar),
+    Reference(&'a Entity),
+    CreatedAt,
+    UpdatedAt,
+}
+
+struct Column<'a> {
+    name: String,
+    source: Source<'a>,
+    optional: bool,
+    unique: bool,
+}
+
+/// The columns an insert fills. The default `id` is left to the database,
+/// as are array columns and `belongs_to` fields whose entity isn't declared.
+fn insert_columns<'a>(entities: &'a [Entity], entity: &Entity) -> Vec<Column<'a>> {
+    let single_key = match entity.primary_key.as_deref() {
+        Some([column]) => Some(column.as_str()),
+        _ => None,
+    };
+
+    let mut columns: Vec<Column> = entity
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let source = match referenced_entity(entities, field) {
+                Some(parent) => Source::Reference(parent),
+                None => match field.kind {
+                    FieldKind::Scalar(scalar) => Source::Scalar(scalar),
+                    FieldKind::BelongsTo(_) | FieldKind::Array(_) => return None,
+                },
+            };
+            Some(Column {
+                name: field.column.clone(),
+                source,
+                optional: field.optional,
+                unique: field.unique || single_key == Some(field.name.as_str()),
+            })
+        })
+        .collect();
+
+    for (present, source, name) in [
+        (entity.created_at, Source::CreatedAt, "created_at"),
+        (entity.updated_at, Source::UpdatedAt, "updated_at"),
+    ] {
+        if present {
+            columns.push(Column {
+                name: name.to_string(),
+                source,
+                optional: false,
+                unique: false,
+            });
+        }
+    }
+
+    columns
+}
+
+struct Faker<'a> {
+    db: &'a Database,
+    entities: &'a [Entity],
+    generator: Generator,
+    /// Keys of the rows each entity can be referenced by.
+    keys: HashMap<String, Vec<Value>>,
+    /// Rows inserted per table, reported once the transaction commits.
+    inserted: Vec<(String, u64)>,
+}
+
+impl<'a> Faker<'a> {
+    async fn run(&mut self, target: &'a Entity, count: u64) -> Result<(), String> {
+        let order = insertion_order(self.entities, target);
+        let parent_rows = (count / 10).clamp(1, MAX_PARENT_ROWS);
+
+        for &entity in &order[..order.len() - 1] {
+            if self.load_keys(entity).await? == 0 {
+                self.insert(entity, parent_rows).await?;
+                self.keys.remove(&entity.name);
+                self.load_keys(entity).await?;
+            }
+        }
+
+        self.insert(target, count).await
+    }
+
+    /// Caches the keys of `entity`'s existing rows and returns how many.
+    async fn load_keys(&mut self, entity: &Entity) -> Result<usize, String> {
+        if let Some(keys) = self.keys.get(&entity.name) {
+            return Ok(keys.len());
+        }
+        let column = self.db.quote_ident(entity.key_column()?);
+        let sql = format!(
+            "SELECT {} FROM {} ORDER BY {}",
+            column,
+            self.db.quote_ident(&entity.table),
+            column
+        );
+        let keys: Vec<Value> = self
+            .db
+            .fetch(&sql)
+            .await?
+            .rows
+            .iter()
+            .filter_map(|row| row.first())
+            .map(|key| Value::from_rendered(key))
+            .collect();
+        let count = keys.len();
+        self.keys.insert(entity.name.clone(), keys);
+        Ok(count)
+    }
+
+    /// Rows of `columns` already in `table`, rendered as text, so unique
+    /// columns can avoid them.
+    async fn existing_values(
+        &self,
+        table: &str,
+        columns: &[&str],
+    ) -> Result<Vec<Vec<String>>, String> {
+        let quoted: Vec<String> = columns.iter().map(|c| self.db.quote_ident(c)).collect();
+        let sql = format!(
+            "SELECT {} FROM {}",
+            quoted.join(", "),
+            self.db.quote_ident(table)
+        );
+        Ok(self.db.fetch(&sql).await?.rows)
+    }
+
+    async fn insert(&mut self, entity: &'a Entity, count: u64) -> Result<(), String> {
+        let columns = insert_columns(self.entities, entity);
+        for column in &columns {
+            if let Source::Reference(parent) = column.source {
+                self.load_keys(parent).await?;
+            }
+        }
+
+        let mut seen: Vec<Option<HashSet<String>>> = Vec::with_capacity(columns.len());
+        for column in &columns {
+            seen.push(if column.unique {
+                let rows = self.existing_values(&entity.table, &[&column.name]).await?;
+                Some(
+                    rows.into_iter()
+                        .filter_map(|row| row.into_iter().next())
+                        .filter(|value| value != "NULL")
+                        .collect(),
+                )
+            } else {
+                None
+            });
+        }
+
+        // A composite primary key is unique as a whole, not per column
+        let composite: Vec<usize> = match entity.primary_key.as_deref() {
+            Some(pk) if pk.len() > 1 => entity
+                .fields
+                .iter()
+                .filter(|f| entity.is_primary_key(f))
+                .filter_map(|f| columns.iter().position(|c| c.name == f.column))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut seen_keys: HashSet<Vec<String>> = HashSet::new();
+        if !composite.is_empty() {
+            let names: Vec<&str> = composite
+                .iter()
+                .map(|&i| columns[i].name.as_str())
+                .collect();
+            seen_keys.extend(self.existing_values(&entity.table, &names).await?);
+        }
+
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for _ in 0..count {
+            let mut row = self.row(entity, &columns, &mut seen)?;
+            if !composite.is_empty() {
+                self.unique_key(entity, &columns, &composite, &mut row, &mut seen_keys)?;
+            }
+            batch.push(row);
+            if batch.len() == BATCH_SIZE {
+                self.flush(entity, &columns, &mut batch).await?;
+            }
+        }
+        self.flush(entity, &columns, &mut batch).await?;
+
+        self.inserted.push((entity.table.clone(), count));
+        Ok(())
+    }
+
+    fn row(
+        &mut self,
+        entity: &Entity,
+        columns: &[Column],
+        seen: &mut [Option<HashSet<String>>],
+    ) -> Result<Vec<Value>, String> {
+        let (created, updated) = self.generator.timestamps();
+        let mut row = Vec::with_capacity(columns.len());
+
+        for (column, seen) in columns.iter().zip(seen.iter_mut()) {
+            let value = match column.source {
+                Source::CreatedAt => Value::Text(created.format(TIMESTAMP_FORMAT).to_string()),
+                Source::UpdatedAt => Value::Text(updated.format(TIMESTAMP_FORMAT).to_string()),
+                _ => match seen {
+                    Some(seen) => self.unique_value(entity, column, seen)?,
+                    None => self.value(entity, column)?,
+                },
+            };
+            row.push(value);
+        }
+
+        Ok(row)
+    }
+
+    fn value(&mut self, entity: &Entity, column: &Column) -> Result<Value, String> {
+        if column.optional && self.generator.rng.chance(NULL_RATE) {
+            return Ok(Value::Null);
+        }
+        match column.source {
+            Source::Scalar(scalar) => Ok(self.generator.value(&column.name, scalar)),
+            Source::Reference(parent) => {
+                let keys = self
+                    .keys
+                    .get(&parent.name)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                if keys.is_empty() {
+                    if column.optional {
+                        return Ok(Value::Null);
+                    }
+                    return Err(format!(
+                        "{}.{} references {}, which has no rows to point at",
+                        entity.table, column.name, parent.table
+                    ));
+                }
+                Ok(self.generator.rng.pick(keys))
+            }
+            Source::CreatedAt | Source::UpdatedAt => unreachable!("timestamps are set per row"),
+        }
+    }
+
+    fn unique_value(
+        &mut self,
+        entity: &Entity,
+        column: &Column,
+        seen: &mut HashSet<String>,
+    ) -> Result<Value, String> {
+        let mut value = Value::Null;
+        for _ in 0..UNIQUE_ATTEMPTS {
+            value = self.value(entity, column)?;
+            match value.key() {
+                None => return Ok(value),
+                Some(key) => {
+                    if seen.insert(key) {
+                        return Ok(value);
+                    }
+                }
+            }
+        }
+
+        // Fresh values keep colliding; vary the last one until it's unused
+        if !matches!(column.source, Source::Reference(_)) {
+            for n in 1..=seen.len() as u64 + 1 {
+                let Some(variant) = value.variant(n) else {
+                    break;
+                };
+                if let Some(key) = variant.key()
+                    && seen.insert(key)
+                {
+                    return Ok(variant);
+                }
+            }
+        }
+
+        Err(format!(
+            "Ran out of unique values for {}.{} after {} value(s)",
+            entity.table,
+            column.name,
+            seen.len()
+        ))
+    }
+
+    /// Regenerates the primary key columns of `row` until the combination
+    /// is unused.
+    fn unique_key(
+        &mut self,
+        entity: &Entity,
+        columns: &[Column],
+        key_columns: &[usize],
+        row: &mut [Value],
+        seen: &mut HashSet<Vec<String>>,
+    ) -> Result<(), String> {
+        for _ in 0..COMPOSITE_KEY_ATTEMPTS {
+            let key: Vec<String> = key_columns
+                .iter()
+                .map(|&i| row[i].key().unwrap_or_default())
+                .collect();
+            if seen.insert(key) {
+                return Ok(());
+            }
+            for &i in key_columns {
+                row[i] = self.value(entity, &columns[i])?;
+            }
+        }
+
+        let names: Vec<&str> = key_columns
+            .iter()
+            .map(|&i| columns[i].name.as_str())
+            .collect();
+        Err(format!(
+            "Ran out of unused ({}) combinations for {} after {} row(s); \
+             add more referenced rows or lower --count",
+            names.join(", "),
+            entity.table,
+            seen.len()
+        ))
+    }
+
+    async fn flush(
+        &self,
+        entity: &Entity,
+        columns: &[Column<'_>],
+        batch: &mut Vec<Vec<Value>>,
+    ) -> Result<(), String> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let table = self.db.quote_ident(&entity.table);
+        if columns.is_empty() {
+            // Nothing to fill but the key: one default row per statement
+            let sql = match self.db.backend() {
+                "mysql" => format!("INSERT INTO {} () VALUES ()", table),
+                _ => format!("INSERT INTO {} DEFAULT VALUES", table),
+            };
+            for _ in batch.iter() {
+                self.db.execute(&sql).await?;
+            }
+        } else {
+            let names: Vec<String> = columns
+                .iter()
+                .map(|c| self.db.quote_ident(&c.name))
+                .collect();
+            self.db.execute(&insert_sql(&table, &names, batch)).await?;
+        }
+
+        batch.clear();
+        Ok(())
+    }
+}
+
+pub(super) fn insert_sql(table: &str, columns: &[String], rows: &[Vec<Value>]) -> String {
+    let values: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let values: Vec<String> = row.iter().map(Value::to_sql).collect();
+            format!("({})", values.join(", "))
+        })
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        table,
+        columns.join(", "),
+        values.join(", ")
+    )
+}
+
+/// Insert fake rows for a resource.
+pub fn execute(config: FakeConfig) -> Result<(), String> {
+    let entities = load_entities();
+    let target = find_entity(&entities, &config.resource)?;
+
+    let now = match config.seed {
+        Some(_) => NaiveDateTime::parse_from_str(SEEDED_NOW, TIMESTAMP_FORMAT)
+            .map_err(|e| e.to_string())?,
+        None => Utc::now().naive_utc(),
+    };
+    let generator = Generator {
+        rng: Rng(config.seed.unwrap_or_else(random_seed)),
+        now,
+    };
+
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to create async runtime: {}", e))?;
+    let db = rt.block_on(Database::connect(&config.url, false))?;
+
+    let mut faker = Faker {
+        db: &db,
+        entities: &entities,
+        generator,
+        keys: HashMap::new(),
+        inserted: Vec::new(),
+    };
+
+    rt.block_on(async {
+        db.execute_unprepared("BEGIN").await?;
+        match faker.run(target, config.count).await {
+            Ok(()) => db.execute_unprepared("COMMIT").await,
+            Err(e) => {
+                let _ = db.execute_unprepared("ROLLBACK").await;
+                Err(e)
+            }
+        }
+    })?;
+
+    for (table, count) in &faker.inserted {
+        output::success(format!("Inserted {} row(s) into {}", count, table));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTITIES: &str = r#"
+use rapina::prelude::*;
+
+schema! {
+    User {
+        #[unique]
+        email: String,
+        name: String,
+        bio: Option<Text>,
+        posts: Vec<Post>,
+    }
+
+    #[timestamps(created_at)]
+    Post {
+        title: String,
+        #[column = "post_body"]
+        body: Text,
+        author: User,
+        editor: Option<User>,
+    }
+
+    #[table_name = "people"]
+    #[timestamps(none)]
+    #[id(Uuid)]
+    Person {
+        r#type: String,
+        parent: Option<Person>,
+    }
+
+    #[primary_key(user_id, role_id)]
+    #[timestamps(none)]
+    UserRole {
+        user_id: i32,
+        role_id: i32,
+    }
+}
+"#;
+
+    fn entities() -> Vec<Entity> {
+        parse_entities(ENTITIES)
+    }
+
+    fn generator(seed: u64) -> Generator {
+        Generator {
+            rng: Rng(seed),
+            now: NaiveDateTime::parse_from_str(SEEDED_NOW, TIMESTAMP_FORMAT).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse_entity_fields() {
+        let entities = entities();
+        let user = &entities[0];
+        assert_eq!(user.table, "users");
+        assert!(user.created_at && user.updated_at);
+        assert_eq!(user.fields.len(), 3, "has_many fields have no column");
+        assert_eq!(user.has_many, [("posts".to_string(), "Post".to_string())]);
+        assert_eq!(
+            user.fields[0],
+            Field {
+                name: "email".into(),
+                column: "email".into(),
+                kind: FieldKind::Scalar(Scalar::String),
+                optional: false,
+                unique: true,
+            }
+        );
+        assert!(user.fields[2].optional);
+        assert_eq!(user.fields[2].kind, FieldKind::Scalar(Scalar::Text));
+    }
+
+    #[test]
+    fn test_parse_relations_and_attributes() {
+        let entities = entities();
+        let post = &entities[1];
+        assert!(post.created_at && !post.updated_at);
+        assert_eq!(post.fields[1].column, "post_body");
+        assert_eq!(post.fields[2].column, "author_id");
+        assert_eq!(post.fields[2].kind, FieldKind::BelongsTo("User".into()));
+        assert!(post.fields[3].optional);
+
+        let person = &entities[2];
+        assert_eq!(person.table, "people");
+        assert!(!person.created_at && !person.updated_at);
+        assert_eq!(person.fields[0].column, "type");
+        assert_eq!(person.id, Scalar::Uuid);
+        assert_eq!(entities[0].id, Scalar::I32);
+
+        let user_role = &entities[3];
+        assert_eq!(
+            user_role.primary_key,
+            Some(vec!["user_id".into(), "role_id".into()])
+        );
+        assert!(user_role.key_column().is_err());
+    }
+
+    #[test]
+    fn test_find_entity() {
+        let entities = entities();
+        assert_eq!(find_entity(&entities, "User").unwrap().name, "User");
+        assert_eq!(find_entity(&entities, "posts").unwrap().name, "Post");
+        assert_eq!(
+            find_entity(&entities, "user_role").unwrap().name,
+            "UserRole"
+        );
+        assert_eq!(find_entity(&entities, "people").unwrap().name, "Person");
+        assert!(find_entity(&entities, "comments").is_err());
+    }
+
+    #[test]
+    fn test_insertion_order_puts_parents_first() {
+        let entities = entities();
+        let names = |target: &str| -> Vec<String> {
+            insertion_order(&entities, find_entity(&entities, target).unwrap())
+                .iter()
+                .map(|e| e.name.clone())
+                .collect()
+        };
+        assert_eq!(names("Post"), vec!["User", "Post"]);
+        assert_eq!(names("Person"), vec!["Person"]);
+        assert_eq!(names("User"), vec!["User"]);
+    }
+
+    #[test]
+    fn test_integer_id_columns_reference_entities() {
+        let entities = entities();
+        let user_role = &entities[3];
+        assert_eq!(
+            referenced_entity(&entities, &user_role.fields[0]).map(|e| e.name.as_str()),
+            Some("User")
+        );
+        // There is no Role entity
+        assert!(referenced_entity(&entities, &user_role.fields[1]).is_none());
+    }
+
+    #[test]
+    fn test_seed_is_deterministic() {
+        let sample = |seed| {
+            let mut generator = generator(seed);
+            (0..20)
+                .map(|_| generator.value("email", Scalar::String))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sample(7), sample(7));
+        assert_ne!(sample(7), sample(8));
+    }
+
+    #[test]
+    fn test_values_follow_column_names() {
+        let mut generator = generator(1);
+        let text = |generator: &mut Generator, column| match generator.value(column, Scalar::String)
+        {
+            Value::Text(s) => s,
+            other => panic!("expected text, got {:?}", other),
+        };
+
+        let email = text(&mut generator, "email");
+        assert!(email.contains('@') && DOMAINS.iter().any(|d| email.ends_with(d)));
+        assert!(text(&mut generator, "name").contains(' '));
+        assert!(FIRST_NAMES.contains(&text(&mut generator, "first_name").as_str()));
+        assert!(text(&mut generator, "website").starts_with("https://"));
+
+        for _ in 0..50 {
+            let Value::Int(age) = generator.value("age", Scalar::I32) else {
+                panic!("expected an integer");
+            };
+            assert!((18..=90).contains(&age));
+        }
+    }
+
+    #[test]
+    fn test_timestamps_are_recent() {
+        let mut generator = generator(3);
+        let now = generator.now;
+        for _ in 0..50 {
+            let (created, updated) = generator.timestamps();
+            assert!(created <= updated && updated <= now);
+            assert!(now - created <= Duration::days(365));
+        }
+        assert!(generator.timestamp("expires_at") >= now);
+    }
+
+    #[test]
+    fn test_uuid_format() {
+        let uuid = generator(5).uuid();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert!("89ab".contains(&uuid[19..20]));
+    }
+
+    #[test]
+    fn test_value_variants_stay_valid() {
+        assert_eq!(
+            Value::Text("ada@example.com".into()).variant(2),
+            Some(Value::Text("ada2@example.com".into()))
+        );
+        assert_eq!(
+            Value::Text("amber".into()).variant(3),
+            Some(Value::Text("amber-3".into()))
+        );
+        assert_eq!(Value::Int(10).variant(1), Some(Value::Int(11)));
+        assert_eq!(Value::Bool(true).variant(1), None);
+    }
+
+    #[test]
+    fn test_insert_sql() {
+        let sql = insert_sql(
+            "\"users\"",
+            &["\"name\"".into(), "\"age\"".into(), "\"bio\"".into()],
+            &[
+                vec![Value::Text("O'Brien".into()), Value::Int(30), Value::Null],
+                vec![Value::Text("Ada".into()), Value::Int(36), Value::Bool(true)],
+            ],
+        );
+        assert_eq!(
+            sql,
+            "INSERT INTO \"users\" (\"name\", \"age\", \"bio\") VALUES ('O''Brien', 30, NULL), ('Ada', 36, TRUE)"
+        );
+    }
+
+    #[test]
+    fn test_insert_columns_skip_default_key() {
+        let entities = entities();
+        let columns = insert_columns(&entities, &entities[1]);
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["title", "post_body", "author_id", "editor_id", "created_at"]
+        );
+        assert!(matches!(columns[2].source, Source::Reference(e) if e.name == "User"));
+    }
+}
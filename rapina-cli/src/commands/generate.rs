@@ -0,0 +1,604 @@
+//! Code generated from the committed OpenAPI document.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path};
+
+use serde_json::{Map, Value};
+
+use super::codegen;
+use crate::output;
+
+/// Methods in the order tests are written for each path.
+const METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+/// How deep sample values follow nested schemas before giving up.
+const MAX_DEPTH: usize = 8;
+
+pub struct ContractTestsConfig {
+    /// OpenAPI document to generate from, as written by `rapina openapi export`.
+    pub spec: String,
+    /// Test file to write.
+    pub output: String,
+    /// Environment variable holding the bearer token sent with every request.
+    pub token_env: String,
+}
+
+/// Write contract tests for every documented operation.
+pub fn contract_tests(config: ContractTestsConfig) -> Result<(), String> {
+    codegen::verify_rapina_project()?;
+
+    let content = fs::read_to_string(&config.spec).map_err(|e| {
+        format!(
+            "Failed to read {}: {}. Export it first with `rapina openapi export -o {}`",
+            config.spec, e, config.spec
+        )
+    })?;
+    let spec: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", config.spec, e))?;
+
+    let output_path = Path::new(&config.output);
+    let include = include_path(output_path, Path::new(&config.spec));
+    let generated = generate_contract_tests(&spec, &include, &config.token_env)?;
+
+    let existing = fs::read_to_string(output_path).ok();
+    if existing.as_deref() == Some(generated.source.as_str()) {
+        output::skipped("file", &config.output, "already up to date");
+    } else {
+        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(output_path, &generated.source)
+            .map_err(|e| format!("Failed to write {}: {}", config.output, e))?;
+        if existing.is_some() {
+            output::updated(&config.output);
+        } else {
+            output::created(&config.output);
+        }
+    }
+
+    output::blank();
+    output::success(format!(
+        "Generated {} contract test(s) from {}",
+        generated.tests, config.spec
+    ));
+    for operation in &generated.skipped {
+        output::detail(format!("Skipped {} (untestable)", operation));
+    }
+    output::info("  Start the server with `rapina dev`, then run `cargo test --test contract`");
+    output::blank();
+
+    Ok(())
+}
+
+/// A generated test file.
+struct ContractTests {
+    source: String,
+    tests: usize,
+    /// `METHOD /path` of operations marked `x-rapina-untestable`.
+    skipped: Vec<String>,
+}
+
+/// The spec path as seen from the directory of the test file, for `include_str!`.
+fn include_path(output: &Path, spec: &Path) -> String {
+    if spec.is_absolute() {
+        return spec.display().to_string().replace('\\', "/");
+    }
+    let depth = output
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .filter(|c| matches!(c, Component::Normal(_)))
+                .count()
+        })
+        .unwrap_or(0);
+    let spec = spec
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("{}{}", "../".repeat(depth), spec)
+}
+
+fn generate_contract_tests(
+    spec: &Value,
+    include: &str,
+    token_env: &str,
+) -> Result<ContractTests, String> {
+    let paths = spec
+        .get("paths")
+        .and_then(Value::as_object)
+        .ok_or("The OpenAPI document has no paths")?;
+
+    let mut sorted: Vec<(&String, &Value)> = paths.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut names = HashSet::new();
+    let mut tests = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (path, item) in sorted {
+        for method in METHODS {
+            let Some(operation) = item.get(*method) else {
+                continue;
+            };
+            let label = format!("{} {}", method.to_uppercase(), path);
+            if operation.get("x-rapina-untestable") == Some(&Value::Bool(true)) {
+                skipped.push(label);
+                continue;
+            }
+            let name = unique_name(&mut names, &test_name(method, path, operation));
+            tests.push(generate_test(spec, &name, method, path, operation));
+        }
+    }
+
+    let mut source = format!(
+        r#"//! Contract tests generated by `rapina generate contract-tests` from the
+//! OpenAPI document. Regenerate this file after changing the API instead of
+//! editing it.
+//!
+//! Each test sends a minimal valid request to one documented operation and
+//! checks the status and body against the document. The tests talk to a
+//! running server: start it with `rapina dev`, then run
+//! `cargo test --test contract`. Set RAPINA_CONTRACT_ADDR to test a server
+//! that isn't on 127.0.0.1:3000, and {token_env} to send a bearer token.
+"#
+    );
+    if !skipped.is_empty() {
+        source.push_str("//!\n//! Skipped (untestable):\n");
+        for operation in &skipped {
+            source.push_str(&format!("//! - {}\n", operation));
+        }
+    }
+    source.push_str(&format!(
+        r#"
+use rapina::testing::{{Contract, TestClient, TestRequestBuilder}};
+
+const SPEC: &str = include_str!("{include}");
+
+fn contract() -> Contract {{
+    Contract::from_json(SPEC)
+}}
+
+fn client() -> TestClient {{
+    let addr =
+        std::env::var("RAPINA_CONTRACT_ADDR").unwrap_or_else(|_| "127.0.0.1:3000".to_string());
+    TestClient::connect(
+        addr.parse()
+            .expect("RAPINA_CONTRACT_ADDR must be an ip:port address"),
+    )
+}}
+
+/// Sends the token in {token_env} as a bearer token, if it's set.
+fn authorize(request: TestRequestBuilder<'_>) -> TestRequestBuilder<'_> {{
+    match std::env::var("{token_env}") {{
+        Ok(token) => request.header("authorization", &format!("Bearer {{}}", token)),
+        Err(_) => request,
+    }}
+}}
+"#
+    ));
+    for test in &tests {
+        source.push('\n');
+        source.push_str(test);
+    }
+
+    Ok(ContractTests {
+        source,
+        tests: tests.len(),
+        skipped,
+    })
+}
+
+fn generate_test(spec: &Value, name: &str, method: &str, path: &str, operation: &Value) -> String {
+    let parameters: Vec<&Value> = operation
+        .get("parameters")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let mut uri = path.to_string();
+    let mut query = Vec::new();
+    for parameter in parameters {
+        let Some(param) = parameter.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let value = match parameter.get("schema") {
+            Some(schema) => match sample(spec, schema, schema, 0) {
+                Value::String(s) => s,
+                Value::Null => "1".to_string(),
+                other => other.to_string(),
+            },
+            None => "1".to_string(),
+        };
+        match parameter.get("in").and_then(Value::as_str) {
+            Some("path") => uri = uri.replace(&format!("{{{}}}", param), &value),
+            Some("query") if parameter.get("required") == Some(&Value::Bool(true)) => {
+                query.push(format!("{}={}", param, value));
+            }
+            _ => {}
+        }
+    }
+    if !query.is_empty() {
+        uri = format!("{}?{}", uri, query.join("&"));
+    }
+
+    let body = operation
+        .pointer("/requestBody/content/application~1json/schema")
+        .map(|schema| sample(spec, schema, schema, 0));
+
+    let mut request = format!("authorize(client.{}({:?}))", method, uri);
+    if let Some(body) = body {
+        request = format!(
+            "{}\n        .json(&serde_json::json!({}))",
+            request,
+            serde_json::to_string(&body).unwrap_or_default()
+        );
+    }
+
+    format!(
+        r#"#[tokio::test]
+async fn {name}() {{
+    let client = client();
+    let response = {request}
+        .send()
+        .await;
+    contract().assert_response({method:?}, {path:?}, &response);
+}}
+"#,
+        method = method.to_uppercase(),
+    )
+}
+
+/// The operationId as a snake_case function name, or one built from the
+/// method and path.
+fn test_name(method: &str, path: &str, operation: &Value) -> String {
+    let base = match operation.get("operationId").and_then(Value::as_str) {
+        Some(id) => id.to_string(),
+        None => format!("{}_{}", method, path),
+    };
+
+    let mut name = String::new();
+    for (i, c) in base.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !name.ends_with('_') {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name = name.trim_end_matches('_').to_string();
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{}_{}", method, name)
+            .trim_end_matches('_')
+            .to_string()
+    } else if !codegen::can_be_raw_ident(&name) {
+        format!("{}_", name)
+    } else {
+        codegen::rust_ident(&name)
+    }
+}
+
+fn unique_name(names: &mut HashSet<String>, name: &str) -> String {
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while !names.insert(candidate.clone()) {
+        candidate = format!("{}_{}", name, n);
+        n += 1;
+    }
+    candidate
+}
+
+/// A minimal value matching `schema`: examples and defaults when given,
+/// otherwise only the required fields, filled with placeholder values.
+///
+/// `$ref`s are looked up in `root` (schemars keeps `definitions` next to the
+/// schema) and then in the whole document.
+fn sample(spec: &Value, root: &Value, schema: &Value, depth: usize) -> Value {
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+    let Some(schema) = schema.as_object() else {
+        return Value::Null;
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer).or_else(|| spec.pointer(pointer)));
+        return target.map_or(Value::Null, |target| sample(spec, root, target, depth + 1));
+    }
+    for key in ["example", "default", "const"] {
+        if let Some(value) = schema.get(key) {
+            return value.clone();
+        }
+    }
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|values| values.first())
+    {
+        return first.clone();
+    }
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        let mut merged = Map::new();
+        for sub in all {
+            if let Value::Object(fields) = sample(spec, root, sub, depth + 1) {
+                merged.extend(fields);
+            }
+        }
+        return Value::Object(merged);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(first) = schema
+            .get(key)
+            .and_then(Value::as_array)
+            .and_then(|options| options.first())
+        {
+            return sample(spec, root, first, depth + 1);
+        }
+    }
+
+    let ty = match schema.get("type") {
+        Some(Value::String(ty)) => ty.as_str(),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|ty| *ty != "null")
+            .unwrap_or("null"),
+        _ if schema.contains_key("properties") => "object",
+        _ => return Value::Null,
+    };
+
+    match ty {
+        "string" => {
+            let value = match schema.get("format").and_then(Value::as_str) {
+                Some("email") => "user@example.com",
+                Some("uuid") => "00000000-0000-0000-0000-000000000001",
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("date") => "2024-01-01",
+                Some("uri" | "url") => "https://example.com",
+                _ => "string",
+            };
+            match schema.get("minLength").and_then(Value::as_u64) {
+                Some(min) if min as usize > value.len() => Value::from("x".repeat(min as usize)),
+                _ => Value::from(value),
+            }
+        }
+        "integer" => Value::from(schema.get("minimum").and_then(Value::as_i64).unwrap_or(1)),
+        "number" => Value::from(schema.get("minimum").and_then(Value::as_f64).unwrap_or(1.0)),
+        "boolean" => Value::Bool(true),
+        "array" => {
+            let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            let item = schema
+                .get("items")
+                .map_or(Value::Null, |items| sample(spec, root, items, depth + 1));
+            Value::Array(vec![item; min as usize])
+        }
+        "object" => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let mut object = Map::new();
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                let value = properties
+                    .and_then(|p| p.get(name))
+                    .map_or(Value::Null, |property| {
+                        sample(spec, root, property, depth + 1)
+                    });
+                object.insert(name.to_string(), value);
+            }
+            Value::Object(object)
+        }
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/users": {
+                    "post": {
+                        "operationId": "create_user",
+                        "requestBody": {
+                            "required": true,
+                            "content": {"application/json": {"schema": {
+                                "$ref": "#/components/schemas/CreateUser"
+                            }}}
+                        },
+                        "responses": {"200": {"description": "Success"}}
+                    },
+                    "get": {
+                        "operationId": "list_users",
+                        "parameters": [
+                            {"name": "page", "in": "query", "required": true, "schema": {"type": "integer"}},
+                            {"name": "q", "in": "query", "required": false, "schema": {"type": "string"}}
+                        ],
+                        "responses": {"200": {"description": "Success"}}
+                    }
+                },
+                "/users/{id}": {
+                    "get": {
+                        "operationId": "get_user",
+                        "parameters": [{"name": "id", "in": "path", "required": true}],
+                        "responses": {"200": {"description": "Success"}}
+                    },
+                    "delete": {
+                        "operationId": "delete_user",
+                        "x-rapina-untestable": true,
+                        "responses": {"200": {"description": "Success"}}
+                    }
+                }
+            },
+            "components": {"schemas": {"CreateUser": {
+                "type": "object",
+                "required": ["age", "email", "role"],
+                "properties": {
+                    "email": {"type": "string", "format": "email"},
+                    "age": {"type": "integer", "minimum": 18},
+                    "role": {"type": "string", "enum": ["member", "admin"]},
+                    "bio": {"type": "string"}
+                }
+            }}}
+        })
+    }
+
+    #[test]
+    fn test_generate_contract_tests() {
+        let generated = generate_contract_tests(&spec(), "../openapi.json", "API_TOKEN").unwrap();
+        let source = &generated.source;
+
+        assert_eq!(generated.tests, 3);
+        assert_eq!(generated.skipped, vec!["DELETE /users/{id}"]);
+        assert!(source.contains("//! - DELETE /users/{id}\n"));
+        assert!(source.contains("const SPEC: &str = include_str!(\"../openapi.json\");"));
+        assert!(source.contains("std::env::var(\"API_TOKEN\")"));
+        assert!(source.contains("let response = authorize(client.get(\"/users?page=1\"))"));
+        assert!(source.contains(
+            ".json(&serde_json::json!({\"age\":18,\"email\":\"user@example.com\",\"role\":\"member\"}))"
+        ));
+        assert!(source.contains("authorize(client.get(\"/users/1\"))"));
+        assert!(
+            source.contains("contract().assert_response(\"GET\", \"/users/{id}\", &response);")
+        );
+        assert!(!source.contains("delete_user"));
+
+        // Paths sorted, then methods in a fixed order
+        let list = source.find("async fn list_users()").unwrap();
+        let create = source.find("async fn create_user()").unwrap();
+        let get = source.find("async fn get_user()").unwrap();
+        assert!(list < create && create < get);
+    }
+
+    #[test]
+    fn test_generate_contract_tests_is_deterministic() {
+        let first = generate_contract_tests(&spec(), "../openapi.json", "T").unwrap();
+        let second = generate_contract_tests(&spec(), "../openapi.json", "T").unwrap();
+        assert_eq!(first.source, second.source);
+    }
+
+    #[test]
+    fn test_generate_contract_tests_without_paths() {
+        assert!(generate_contract_tests(&json!({}), "../openapi.json", "T").is_err());
+    }
+
+    #[test]
+    fn test_test_name() {
+        let no_id = json!({});
+        assert_eq!(
+            test_name("get", "/users/{id}", &json!({"operationId": "getUser"})),
+            "get_user"
+        );
+        assert_eq!(test_name("get", "/users/{id}", &no_id), "get_users_id");
+        assert_eq!(test_name("post", "/", &no_id), "post");
+        assert_eq!(
+            test_name("get", "/", &json!({"operationId": "2fa"})),
+            "get_2fa"
+        );
+        assert_eq!(
+            test_name("get", "/", &json!({"operationId": "type"})),
+            "r#type"
+        );
+        assert_eq!(
+            test_name("get", "/", &json!({"operationId": "self"})),
+            "self_"
+        );
+    }
+
+    #[test]
+    fn test_unique_name() {
+        let mut names = HashSet::new();
+        assert_eq!(unique_name(&mut names, "health"), "health");
+        assert_eq!(unique_name(&mut names, "health"), "health_2");
+        assert_eq!(unique_name(&mut names, "health"), "health_3");
+    }
+
+    #[test]
+    fn test_include_path() {
+        let spec = Path::new("openapi.json");
+        assert_eq!(
+            include_path(Path::new("tests/contract.rs"), spec),
+            "../openapi.json"
+        );
+        assert_eq!(
+            include_path(Path::new("./tests/api/contract.rs"), spec),
+            "../../openapi.json"
+        );
+        assert_eq!(
+            include_path(
+                Path::new("tests/contract.rs"),
+                Path::new("./docs/openapi.json")
+            ),
+            "../docs/openapi.json"
+        );
+    }
+
+    #[test]
+    fn test_sample_values() {
+        let spec = json!({});
+        let sample = |schema: Value| super::sample(&spec, &schema, &schema, 0);
+
+        assert_eq!(
+            sample(json!({"type": "string", "format": "uuid"})),
+            "00000000-0000-0000-0000-000000000001"
+        );
+        assert_eq!(
+            sample(json!({"type": "string", "minLength": 8})),
+            "xxxxxxxx"
+        );
+        assert_eq!(sample(json!({"type": ["string", "null"]})), "string");
+        assert_eq!(sample(json!({"type": "number"})), 1.0);
+        assert_eq!(sample(json!({"type": "integer", "example": 42})), 42);
+        assert_eq!(sample(json!({"type": "boolean"})), true);
+        assert_eq!(
+            sample(json!({"type": "array", "minItems": 2, "items": {"type": "integer"}})),
+            json!([1, 1])
+        );
+        assert_eq!(
+            sample(json!({"type": "array", "items": {"type": "integer"}})),
+            json!([])
+        );
+        assert_eq!(
+            sample(json!({"oneOf": [{"type": "boolean"}, {"type": "string"}]})),
+            true
+        );
+        assert_eq!(
+            sample(json!({
+                "$ref": "#/definitions/Tag",
+                "definitions": {"Tag": {"required": ["name"], "properties": {"name": {"type": "string"}}}}
+            })),
+            json!({"name": "string"})
+        );
+    }
+
+    #[test]
+    fn test_sample_stops_on_recursive_schemas() {
+        let spec = json!({"components": {"schemas": {"Node": {
+            "type": "object",
+            "required": ["next"],
+            "properties": {"next": {"$ref": "#/components/schemas/Node"}}
+        }}}});
+        let schema = json!({"$ref": "#/components/schemas/Node"});
+        let value = sample(&spec, &schema, &schema, 0);
+        assert!(value.pointer("/next/next/next").is_some());
+    }
+}
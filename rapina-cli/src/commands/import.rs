@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 
 use colored::Colorize;
+use serde::Deserialize;
 
-use super::codegen::{self, FieldInfo};
+use super::codegen::{self, Changes, FieldInfo, IndexInfo, Prelude};
+use crate::output;
 
 // ---------------------------------------------------------------------------
 // Intermediate representation
@@ -14,6 +17,8 @@ struct IntrospectedTable {
     columns: Vec<IntrospectedColumn>,
     primary_key_columns: Vec<String>,
     foreign_keys: Vec<IntrospectedForeignKey>,
+    indexes: Vec<IntrospectedIndex>,
+    comment: Option<String>,
 }
 
 #[derive(Debug)]
@@ -21,16 +26,23 @@ struct IntrospectedColumn {
     name: String,
     col_type: NormalizedType,
     is_nullable: bool,
+    comment: Option<String>,
 }
 
 #[derive(Debug)]
 struct IntrospectedForeignKey {
     columns: Vec<String>,
     referenced_table: String,
-    #[allow(dead_code)]
     referenced_columns: Vec<String>,
 }
 
+#[derive(Debug)]
+struct IntrospectedIndex {
+    name: String,
+    columns: Vec<String>,
+    unique: bool,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum NormalizedType {
     Str,
@@ -162,6 +174,11 @@ fn normalized_to_field_info(
         rust_type: rust_type.to_string(),
         schema_type: schema_type.to_string(),
         column_method: format!("{}{}", column_base, null_suffix),
+        comment: None,
+        column: None,
+        default: None,
+        nullable: is_nullable,
+        unique: false,
     })
 }
 
@@ -178,6 +195,8 @@ async fn introspect_postgres(
         .await
         .map_err(|e| format!("Failed to connect to Postgres: {}", e))?;
 
+    let mut comments = fetch_pg_comments(&pool, schema_name).await?;
+
     let discovery = sea_schema::postgres::discovery::SchemaDiscovery::new(pool, schema_name);
     let schema = discovery
         .discover()
@@ -209,20 +228,74 @@ async fn introspect_postgres(
                 name: col.name.clone(),
                 col_type: map_pg_type(&col.col_type),
                 is_nullable: col.not_null.is_none(),
+                comment: comments.remove(&(table_def.info.name.clone(), Some(col.name.clone()))),
+            })
+            .collect();
+
+        let indexes: Vec<IntrospectedIndex> = table_def
+            .unique_constraints
+            .iter()
+            .map(|uq| IntrospectedIndex {
+                name: uq.name.clone(),
+                columns: uq.columns.clone(),
+                unique: true,
             })
             .collect();
 
+        let comment = comments.remove(&(table_def.info.name.clone(), None));
+
         tables.push(IntrospectedTable {
             name: table_def.info.name.clone(),
             columns,
             primary_key_columns: pk_columns,
             foreign_keys,
+            indexes,
+            comment,
         });
     }
 
     Ok(tables)
 }
 
+/// Table and column comments keyed by `(table, column)`; table comments have
+/// no column. sea_schema doesn't expose `COMMENT ON` for Postgres, so they
+/// are read from the catalog directly.
+#[cfg(feature = "import-postgres")]
+async fn fetch_pg_comments(
+    pool: &sqlx::PgPool,
+    schema_name: &str,
+) -> Result<HashMap<(String, Option<String>), String>, String> {
+    const QUERY: &str = r#"
+        SELECT c.relname::text, NULL::text, obj_description(c.oid, 'pg_class')
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1
+          AND c.relkind IN ('r', 'p')
+          AND obj_description(c.oid, 'pg_class') IS NOT NULL
+        UNION ALL
+        SELECT c.relname::text, a.attname::text, col_description(c.oid, a.attnum)
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_attribute a ON a.attrelid = c.oid
+        WHERE n.nspname = $1
+          AND c.relkind IN ('r', 'p')
+          AND a.attnum > 0
+          AND NOT a.attisdropped
+          AND col_description(c.oid, a.attnum) IS NOT NULL
+    "#;
+
+    let rows: Vec<(String, Option<String>, String)> = sqlx::query_as(QUERY)
+        .bind(schema_name)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to read comments: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(table, column, comment)| ((table, column), comment))
+        .collect())
+}
+
 #[cfg(feature = "import-mysql")]
 async fn introspect_mysql(url: &str, schema_name: &str) -> Result<Vec<IntrospectedTable>, String> {
     let pool = sqlx::MySqlPool::connect(url)
@@ -261,6 +334,18 @@ async fn introspect_mysql(url: &str, schema_name: &str) -> Result<Vec<Introspect
                 name: col.name.clone(),
                 col_type: map_mysql_type(&col.col_type),
                 is_nullable: col.null,
+                comment: non_empty(&col.comment),
+            })
+            .collect();
+
+        let indexes: Vec<IntrospectedIndex> = table_def
+            .indexes
+            .iter()
+            .filter(|idx| idx.name != "PRIMARY")
+            .map(|idx| IntrospectedIndex {
+                name: idx.name.clone(),
+                columns: idx.parts.iter().map(|p| p.column.clone()).collect(),
+                unique: idx.unique,
             })
             .collect();
 
@@ -269,19 +354,31 @@ async fn introspect_mysql(url: &str, schema_name: &str) -> Result<Vec<Introspect
             columns,
             primary_key_columns: pk_columns,
             foreign_keys,
+            indexes,
+            comment: non_empty(&table_def.info.comment),
         });
     }
 
     Ok(tables)
 }
 
+/// MySQL reports a missing comment as an empty string.
+#[cfg(feature = "import-mysql")]
+fn non_empty(comment: &str) -> Option<String> {
+    if comment.trim().is_empty() {
+        None
+    } else {
+        Some(comment.to_string())
+    }
+}
+
 #[cfg(feature = "import-sqlite")]
 async fn introspect_sqlite(url: &str) -> Result<Vec<IntrospectedTable>, String> {
     let pool = sqlx::SqlitePool::connect(url)
         .await
         .map_err(|e| format!("Failed to connect to SQLite: {}", e))?;
 
-    let discovery = sea_schema::sqlite::discovery::SchemaDiscovery::new(pool);
+    let discovery = sea_schema::sqlite::discovery::SchemaDiscovery::new(pool.clone());
     let schema: sea_schema::sqlite::def::Schema = discovery
         .discover()
         .await
@@ -296,9 +393,9 @@ async fn introspect_sqlite(url: &str) -> Result<Vec<IntrospectedTable>, String>
             .map(|col| col.name.clone())
             .collect();
 
-        // SQLite ForeignKeysInfo fields are pub(crate), so we can't
-        // extract FK details from outside the crate. FK resolution
-        // is skipped for SQLite imports.
+        // SQLite IndexInfo fields are pub(crate), so index preservation is
+        // skipped for SQLite imports, and foreign keys are read with a
+        // PRAGMA instead. SQLite has no column comments either.
         let columns: Vec<IntrospectedColumn> = table_def
             .columns
             .iter()
@@ -306,20 +403,348 @@ async fn introspect_sqlite(url: &str) -> Result<Vec<IntrospectedTable>, String>
                 name: col.name.clone(),
                 col_type: map_sqlite_type(&col.r#type),
                 is_nullable: !col.not_null,
+                comment: None,
             })
             .collect();
 
         tables.push(IntrospectedTable {
             name: table_def.name.clone(),
             columns,
+            foreign_keys: sqlite_foreign_keys(&pool, &table_def.name).await?,
             primary_key_columns: pk_columns,
-            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            comment: None,
         });
     }
 
     Ok(tables)
 }
 
+/// Reads a table's foreign keys from `PRAGMA foreign_key_list`, one row per
+/// column, grouped by constraint.
+#[cfg(feature = "import-sqlite")]
+async fn sqlite_foreign_keys(
+    pool: &sqlx::SqlitePool,
+    table: &str,
+) -> Result<Vec<IntrospectedForeignKey>, String> {
+    let rows: Vec<(i64, String, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, \"table\", \"from\", \"to\" FROM pragma_foreign_key_list(?) ORDER BY id, seq",
+    )
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to read foreign keys of {}: {}", table, e))?;
+
+    let mut foreign_keys: Vec<(i64, IntrospectedForeignKey)> = Vec::new();
+    for (id, referenced_table, column, referenced_column) in rows {
+        if foreign_keys.last().is_none_or(|(last, _)| *last != id) {
+            foreign_keys.push((
+                id,
+                IntrospectedForeignKey {
+                    columns: Vec::new(),
+                    referenced_table,
+                    referenced_columns: Vec::new(),
+                },
+            ));
+        }
+        let (_, fk) = foreign_keys.last_mut().expect("pushed above");
+        fk.columns.push(column);
+        // `REFERENCES users` without a column points at the primary key
+        fk.referenced_columns
+            .push(referenced_column.unwrap_or_else(|| "id".to_string()));
+    }
+
+    Ok(foreign_keys.into_iter().map(|(_, fk)| fk).collect())
+}
+
+// ---------------------------------------------------------------------------
+// Override file
+// ---------------------------------------------------------------------------
+
+/// Adjusts the import for columns that don't map well on their own, read
+/// from the project root when present.
+const OVERRIDES_FILE: &str = "rapina-import.toml";
+
+/// Reserved key of a `[rename.<table>]` section that renames the resource
+/// instead of a field.
+const TABLE_RENAME_KEY: &str = "table";
+
+/// Contents of `rapina-import.toml`:
+///
+/// ```toml
+/// [overrides.users]
+/// external_ref = "uuid"
+/// is_admin = { type = "bool", nullable = false }
+/// legacy_blob = "skip"
+///
+/// [rename.users]
+/// table = "people"
+/// tbl = "label"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportOverrides {
+    /// Column overrides, by table then column
+    #[serde(default)]
+    overrides: BTreeMap<String, BTreeMap<String, ColumnOverride>>,
+    /// Field renames by table then column, plus the resource name under
+    /// the `table` key
+    #[serde(default)]
+    rename: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum ColumnOverride {
+    /// A type name, or `"skip"` to leave the column out
+    Type(String),
+    Detailed(ColumnSpec),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColumnSpec {
+    #[serde(rename = "type")]
+    ty: Option<String>,
+    nullable: Option<bool>,
+    #[serde(default)]
+    skip: bool,
+}
+
+/// An override or rename from the override file that changed the import,
+/// reported in the summary.
+#[derive(Debug, Clone, PartialEq)]
+struct AppliedOverride {
+    table: String,
+    column: Option<String>,
+    change: String,
+}
+
+impl AppliedOverride {
+    fn new(table: &str, column: Option<&str>, change: String) -> Self {
+        let applied = Self {
+            table: table.to_string(),
+            column: column.map(str::to_string),
+            change,
+        };
+        output::record(
+            "override",
+            serde_json::json!({
+                "table": applied.table,
+                "column": applied.column,
+                "change": applied.change,
+            }),
+        );
+        applied
+    }
+}
+
+impl std::fmt::Display for AppliedOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "{}.{}: {}", self.table, column, self.change),
+            None => write!(f, "{}: {}", self.table, self.change),
+        }
+    }
+}
+
+/// Type names accepted in the override file.
+const OVERRIDE_TYPES: &[(&str, NormalizedType)] = &[
+    ("string", NormalizedType::Str),
+    ("text", NormalizedType::Text),
+    ("i32", NormalizedType::I32),
+    ("i64", NormalizedType::I64),
+    ("f32", NormalizedType::F32),
+    ("f64", NormalizedType::F64),
+    ("bool", NormalizedType::Bool),
+    ("uuid", NormalizedType::Uuid),
+    ("datetime", NormalizedType::DateTimeUtc),
+    ("naive_datetime", NormalizedType::NaiveDateTime),
+    ("date", NormalizedType::Date),
+    ("decimal", NormalizedType::Decimal),
+    ("json", NormalizedType::Json),
+];
+
+fn override_type(table: &str, column: &str, name: &str) -> Result<NormalizedType, String> {
+    OVERRIDE_TYPES
+        .iter()
+        .find(|(type_name, _)| *type_name == name)
+        .map(|(_, ty)| ty.clone())
+        .ok_or_else(|| {
+            let names: Vec<&str> = OVERRIDE_TYPES.iter().map(|(name, _)| *name).collect();
+            format!(
+                "{}: unknown type {:?} for {}.{}. Expected skip or one of: {}",
+                OVERRIDES_FILE,
+                name,
+                table,
+                column,
+                names.join(", ")
+            )
+        })
+}
+
+impl ImportOverrides {
+    /// Reads the override file from the current directory, if there is one.
+    fn load() -> Result<Self, String> {
+        let path = std::path::Path::new(OVERRIDES_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", OVERRIDES_FILE, e))?;
+        Self::parse(&content)
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|e| format!("Invalid {}: {}", OVERRIDES_FILE, e))
+    }
+
+    /// Checks that every table and column named in the file was discovered,
+    /// so a typo fails the import instead of being ignored.
+    fn validate(&self, tables: &[IntrospectedTable]) -> Result<(), String> {
+        let sections = self
+            .overrides
+            .iter()
+            .map(|(table, columns)| ("overrides", table, columns.keys().collect::<Vec<_>>()))
+            .chain(self.rename.iter().map(|(table, columns)| {
+                let columns = columns
+                    .keys()
+                    .filter(|column| column.as_str() != TABLE_RENAME_KEY)
+                    .collect();
+                ("rename", table, columns)
+            }));
+
+        for (section, table_name, columns) in sections {
+            let Some(table) = tables.iter().find(|t| &t.name == table_name) else {
+                let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+                return Err(format!(
+                    "{}: unknown table {:?} in [{}.{}]. Discovered tables: {}",
+                    OVERRIDES_FILE,
+                    table_name,
+                    section,
+                    table_name,
+                    names.join(", ")
+                ));
+            };
+            for column in columns {
+                if !table.columns.iter().any(|c| &c.name == column) {
+                    let names: Vec<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+                    return Err(format!(
+                        "{}: unknown column {:?} in [{}.{}]. Columns of {}: {}",
+                        OVERRIDES_FILE,
+                        column,
+                        section,
+                        table_name,
+                        table_name,
+                        names.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the generated resource for `table`.
+    fn resource_name<'a>(&'a self, table: &'a str) -> &'a str {
+        self.rename
+            .get(table)
+            .and_then(|renames| renames.get(TABLE_RENAME_KEY))
+            .map(String::as_str)
+            .unwrap_or(table)
+    }
+
+    /// Field name for `column` of `table`, when it is renamed.
+    fn field_name(&self, table: &str, column: &str) -> Option<&str> {
+        if column == TABLE_RENAME_KEY {
+            return None;
+        }
+        self.rename.get(table)?.get(column).map(String::as_str)
+    }
+
+    /// Applies type, nullability and skip overrides to the discovered
+    /// tables, before they are validated and mapped to fields, and reports
+    /// every override and rename that takes effect.
+    fn apply(
+        &self,
+        mut tables: Vec<IntrospectedTable>,
+    ) -> Result<(Vec<IntrospectedTable>, Vec<AppliedOverride>), String> {
+        self.validate(&tables)?;
+        let mut applied = Vec::new();
+
+        for table in &mut tables {
+            if let Some(columns) = self.overrides.get(&table.name) {
+                for (column, over) in columns {
+                    let spec = match over {
+                        ColumnOverride::Type(name) if name == "skip" => ColumnSpec {
+                            ty: None,
+                            nullable: None,
+                            skip: true,
+                        },
+                        ColumnOverride::Type(name) => ColumnSpec {
+                            ty: Some(name.clone()),
+                            nullable: None,
+                            skip: false,
+                        },
+                        ColumnOverride::Detailed(spec) => spec.clone(),
+                    };
+
+                    if spec.skip {
+                        if table.primary_key_columns.contains(column) {
+                            return Err(format!(
+                                "{}: {}.{} is part of the primary key and can't be skipped",
+                                OVERRIDES_FILE, table.name, column
+                            ));
+                        }
+                        table.columns.retain(|c| &c.name != column);
+                        applied.push(AppliedOverride::new(
+                            &table.name,
+                            Some(column),
+                            "skipped".to_string(),
+                        ));
+                        continue;
+                    }
+
+                    let col = table
+                        .columns
+                        .iter_mut()
+                        .find(|c| &c.name == column)
+                        .expect("validated above");
+                    if let Some(name) = &spec.ty {
+                        col.col_type = override_type(&table.name, column, name)?;
+                        applied.push(AppliedOverride::new(
+                            &table.name,
+                            Some(column),
+                            format!("type {}", name),
+                        ));
+                    }
+                    if let Some(nullable) = spec.nullable {
+                        col.is_nullable = nullable;
+                        let change = if nullable { "nullable" } else { "not null" };
+                        applied.push(AppliedOverride::new(
+                            &table.name,
+                            Some(column),
+                            change.to_string(),
+                        ));
+                    }
+                }
+            }
+
+            if let Some(renames) = self.rename.get(&table.name) {
+                for (column, name) in renames {
+                    let (column, change) = if column == TABLE_RENAME_KEY {
+                        (None, format!("resource renamed to {}", name))
+                    } else {
+                        (Some(column.as_str()), format!("field renamed to {}", name))
+                    };
+                    applied.push(AppliedOverride::new(&table.name, column, change));
+                }
+            }
+        }
+
+        Ok((tables, applied))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Filtering and validation
 // ---------------------------------------------------------------------------
@@ -330,11 +755,39 @@ const INTERNAL_TABLES: &[&str] = &[
     "__diesel_schema_migrations",
 ];
 
+/// A table or column left out of the import, reported as it is found and
+/// again in the import summary.
+#[derive(Debug, Clone, PartialEq)]
+struct SkippedItem {
+    kind: &'static str,
+    name: String,
+    reason: String,
+}
+
+impl SkippedItem {
+    fn new(kind: &'static str, name: impl Into<String>, reason: impl Into<String>) -> Self {
+        let item = Self {
+            kind,
+            name: name.into(),
+            reason: reason.into(),
+        };
+        output::skipped(item.kind, &item.name, &item.reason);
+        item
+    }
+}
+
+impl std::fmt::Display for SkippedItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.kind, self.name, self.reason)
+    }
+}
+
 fn filter_and_validate_tables(
     tables: Vec<IntrospectedTable>,
     table_filter: Option<&[String]>,
-) -> Vec<IntrospectedTable> {
+) -> (Vec<IntrospectedTable>, Vec<SkippedItem>) {
     let mut result = Vec::new();
+    let mut skipped = Vec::new();
 
     for table in tables {
         // Skip internal / system tables
@@ -351,47 +804,52 @@ fn filter_and_validate_tables(
 
         // Must have a primary key
         if table.primary_key_columns.is_empty() {
-            eprintln!(
-                "  {} table {:?} skipped -- no primary key found",
-                "warn:".yellow(),
-                table.name
-            );
+            skipped.push(SkippedItem::new(
+                "table",
+                &table.name,
+                "no primary key found",
+            ));
             continue;
         }
 
-        // For single PK: must be named "id" and be i32
-        // For composite PK: all columns must be i32
+        // For single PK: an `id` must be i32 or a UUID, any other column
+        // becomes a `#[primary_key(col)]` field and must be an integer
         if table.primary_key_columns.len() == 1 {
-            if table.primary_key_columns[0] != "id" {
-                eprintln!(
-                    "  {} table {:?} skipped -- PK column is {:?} (schema! requires column named \"id\" for single PK)",
-                    "warn:".yellow(),
-                    table.name,
-                    table.primary_key_columns[0]
-                );
-                continue;
-            }
-
-            if let Some(pk_col) = table.columns.iter().find(|c| c.name == "id") {
-                match &pk_col.col_type {
-                    NormalizedType::I32 => {}
-                    other => {
-                        eprintln!(
-                            "  {} table {:?} skipped -- PK is {:?} (schema! requires i32)",
-                            "warn:".yellow(),
-                            table.name,
-                            other
-                        );
-                        continue;
+            let pk = &table.primary_key_columns[0];
+            if let Some(pk_col) = table.columns.iter().find(|c| &c.name == pk) {
+                let reason = match (pk.as_str(), &pk_col.col_type) {
+                    ("id", NormalizedType::I32 | NormalizedType::Uuid) => None,
+                    ("id", other) => {
+                        Some(format!("PK is {:?} (schema! requires i32 or Uuid)", other))
                     }
+                    (_, NormalizedType::I32 | NormalizedType::I64) => None,
+                    (_, other) => Some(format!(
+                        "PK column {:?} is {:?} (only integer keys can be imported under another name than \"id\")",
+                        pk, other
+                    )),
+                };
+                if let Some(reason) = reason {
+                    skipped.push(SkippedItem::new("table", &table.name, reason));
+                    continue;
                 }
             }
+        } else if let Some(missing) = table
+            .primary_key_columns
+            .iter()
+            .find(|pk| !table.columns.iter().any(|c| &c.name == *pk))
+        {
+            skipped.push(SkippedItem::new(
+                "table",
+                &table.name,
+                format!("PK column {:?} not found", missing),
+            ));
+            continue;
         }
 
         result.push(table);
     }
 
-    result
+    (result, skipped)
 }
 
 // ---------------------------------------------------------------------------
@@ -399,66 +857,261 @@ fn filter_and_validate_tables(
 // ---------------------------------------------------------------------------
 
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 struct RelationshipInfo {
     field_name: String,
+    /// Table of the related entity.
+    related_table: String,
     related_pascal: String,
     kind: RelationKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 enum RelationKind {
-    BelongsTo,
+    /// Stands in for the `{field_name}_id` column in the `schema!` block.
+    BelongsTo {
+        optional: bool,
+    },
     HasMany,
 }
 
-fn resolve_relationships(tables: &[IntrospectedTable]) -> HashMap<String, Vec<RelationshipInfo>> {
-    let table_names: std::collections::HashSet<&str> =
-        tables.iter().map(|t| t.name.as_str()).collect();
+/// A foreign key that couldn't be turned into a relation, reported in the
+/// import summary.
+#[derive(Debug, Clone, PartialEq)]
+struct SkippedRelationship {
+    table: String,
+    columns: Vec<String>,
+    referenced_table: String,
+    referenced_columns: Vec<String>,
+    /// The relation field the foreign key would become.
+    field_name: String,
+    reason: &'static str,
+}
+
+impl std::fmt::Display for SkippedRelationship {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{} ({}) -> {} ({}): {}",
+            self.table,
+            self.field_name,
+            self.columns.join(", "),
+            self.referenced_table,
+            self.referenced_columns.join(", "),
+            self.reason
+        )
+    }
+}
+
+/// Relation field name for a multi-column foreign key.
+///
+/// Composite keys usually pair a scoping column, such as `tenant_id`, with
+/// the column pointing at the referenced row's `id`; that column names the
+/// relation (`author_id` -> `author`). Otherwise the referenced table does.
+fn composite_field_name(fk: &IntrospectedForeignKey) -> String {
+    fk.columns
+        .iter()
+        .zip(&fk.referenced_columns)
+        .find(|(_, referenced)| referenced.as_str() == "id")
+        .and_then(|(column, _)| column.strip_suffix("_id"))
+        .map(str::to_string)
+        .unwrap_or_else(|| codegen::singularize(&fk.referenced_table))
+}
+
+fn resolve_relationships(
+    tables: &[IntrospectedTable],
+    overrides: &ImportOverrides,
+) -> (
+    HashMap<String, Vec<RelationshipInfo>>,
+    Vec<SkippedRelationship>,
+) {
+    let by_name: HashMap<&str, &IntrospectedTable> =
+        tables.iter().map(|t| (t.name.as_str(), t)).collect();
     let mut relationships: HashMap<String, Vec<RelationshipInfo>> = HashMap::new();
+    let mut skipped = Vec::new();
 
     for table in tables {
+        let mut targets = std::collections::HashSet::new();
+
         for fk in &table.foreign_keys {
             // Only resolve if the referenced table is also being imported
-            if !table_names.contains(fk.referenced_table.as_str()) {
+            let Some(referenced) = by_name.get(fk.referenced_table.as_str()) else {
                 continue;
-            }
-
-            // Only handle single-column FKs (e.g., author_id -> users.id)
-            if fk.columns.len() != 1 {
+            };
+
+            let field_name = match fk.columns.as_slice() {
+                [column] => column.strip_suffix("_id").unwrap_or(column).to_string(),
+                _ => composite_field_name(fk),
+            };
+            let reason = belongs_to_unsupported(table, referenced, fk, overrides)
+                .or_else(|| {
+                    // Each relation implements Related for its target, so a
+                    // second one to the same entity wouldn't compile
+                    (!targets.insert(fk.referenced_table.as_str()))
+                        .then_some("schema! supports one relation between two entities")
+                })
+                .or_else(|| {
+                    table
+                        .columns
+                        .iter()
+                        .any(|c| c.name == field_name)
+                        .then_some("the relation field name is already used by a column")
+                });
+            if let Some(reason) = reason {
+                skipped.push(SkippedRelationship {
+                    table: table.name.clone(),
+                    columns: fk.columns.clone(),
+                    referenced_table: fk.referenced_table.clone(),
+                    referenced_columns: fk.referenced_columns.clone(),
+                    field_name,
+                    reason,
+                });
                 continue;
             }
 
-            let fk_column = &fk.columns[0];
-            let field_name = fk_column.strip_suffix("_id").unwrap_or(fk_column);
-            let ref_singular = codegen::singularize(&fk.referenced_table);
+            let ref_singular = codegen::singularize(overrides.resource_name(&fk.referenced_table));
             let ref_pascal = codegen::to_pascal_case(&ref_singular);
+            let optional = table
+                .columns
+                .iter()
+                .any(|c| c.name == fk.columns[0] && c.is_nullable);
 
             // BelongsTo on the FK side
             relationships
                 .entry(table.name.clone())
                 .or_default()
                 .push(RelationshipInfo {
-                    field_name: field_name.to_string(),
+                    field_name,
+                    related_table: fk.referenced_table.clone(),
                     related_pascal: ref_pascal.clone(),
-                    kind: RelationKind::BelongsTo,
+                    kind: RelationKind::BelongsTo { optional },
                 });
 
             // HasMany on the referenced side
-            let owner_singular = codegen::singularize(&table.name);
-            let owner_pascal = codegen::to_pascal_case(&owner_singular);
+            let owner_plural = overrides.resource_name(&table.name);
+            let owner_pascal = codegen::to_pascal_case(&codegen::singularize(owner_plural));
             relationships
                 .entry(fk.referenced_table.clone())
                 .or_default()
                 .push(RelationshipInfo {
-                    field_name: table.name.clone(),
+                    field_name: owner_plural.to_string(),
+                    related_table: table.name.clone(),
                     related_pascal: owner_pascal,
                     kind: RelationKind::HasMany,
                 });
         }
     }
 
-    relationships
+    (relationships, skipped)
+}
+
+/// Why a foreign key can't become a belongs_to field, which is always a
+/// `{field}_id` column pointing at the `id` of another entity.
+fn belongs_to_unsupported(
+    table: &IntrospectedTable,
+    referenced: &IntrospectedTable,
+    fk: &IntrospectedForeignKey,
+    overrides: &ImportOverrides,
+) -> Option<&'static str> {
+    let [column] = fk.columns.as_slice() else {
+        return Some("composite foreign keys can't be expressed in schema! yet");
+    };
+    if fk.referenced_columns != ["id"] || has_custom_primary_key(referenced) {
+        Some("belongs_to relations can only reference an id primary key")
+    } else if table.name == referenced.name {
+        Some("self-referencing foreign keys can't be expressed in schema! yet")
+    } else if !column.ends_with("_id")
+        || overrides
+            .field_name(&table.name, column)
+            .is_some_and(|name| name != column)
+    {
+        Some("only a `{field}_id` column can become a belongs_to field")
+    } else if table.primary_key_columns.contains(column) {
+        Some("foreign keys in the primary key can't be expressed in schema! yet")
+    } else {
+        None
+    }
+}
+
+/// Tables ordered so that the ones a table belongs to come before it.
+/// `schema!` resolves references anywhere in its block; the order only
+/// keeps the generated file readable top to bottom.
+fn dependency_order<'a>(
+    tables: &'a [IntrospectedTable],
+    relationships: &HashMap<String, Vec<RelationshipInfo>>,
+) -> Vec<&'a IntrospectedTable> {
+    let depends_on = |table: &IntrospectedTable| -> Vec<&str> {
+        relationships
+            .get(&table.name)
+            .into_iter()
+            .flatten()
+            .filter(|r| matches!(r.kind, RelationKind::BelongsTo { .. }))
+            .map(|r| r.related_table.as_str())
+            .collect()
+    };
+
+    let mut ordered: Vec<&IntrospectedTable> = Vec::with_capacity(tables.len());
+    let mut remaining: Vec<&IntrospectedTable> = tables.iter().collect();
+    while !remaining.is_empty() {
+        let ready = remaining.iter().position(|table| {
+            depends_on(table)
+                .iter()
+                .all(|dep| ordered.iter().any(|t| t.name == *dep))
+        });
+        // A cycle keeps the original order for what's left of it
+        ordered.push(remaining.remove(ready.unwrap_or(0)));
+    }
+    ordered
+}
+
+/// Renders the table's `schema!` entity, with its resolved foreign keys as
+/// relation fields.
+fn schema_entity(
+    table: &IntrospectedTable,
+    relationships: &HashMap<String, Vec<RelationshipInfo>>,
+    overrides: &ImportOverrides,
+) -> String {
+    let pascal =
+        codegen::to_pascal_case(&codegen::singularize(overrides.resource_name(&table.name)));
+    let (fields, _) = table_fields(table, overrides);
+    let indexes = preserved_indexes(table, &fields);
+    let (primary_key, _) = primary_key_parts(table, &fields, overrides);
+
+    let mut relations = codegen::SchemaRelations::default();
+    let mut fk_columns = Vec::new();
+    for relationship in relationships.get(&table.name).into_iter().flatten() {
+        let field = codegen::rust_ident(&relationship.field_name);
+        let target = &relationship.related_pascal;
+        match relationship.kind {
+            RelationKind::BelongsTo { optional } => {
+                fk_columns.push(format!("{}_id", relationship.field_name));
+                relations.belongs_to.push(if optional {
+                    format!("{}: Option<{}>", field, target)
+                } else {
+                    format!("{}: {}", field, target)
+                });
+            }
+            RelationKind::HasMany => relations
+                .has_many
+                .push(format!("{}: Vec<{}>", field, target)),
+        }
+    }
+
+    // The belongs_to field brings its own foreign key column; the
+    // migration keeps creating it from the full field list
+    let columns: Vec<FieldInfo> = fields
+        .into_iter()
+        .filter(|f| !fk_columns.iter().any(|c| c == f.column_name()))
+        .collect();
+
+    codegen::generate_schema_entity(
+        &pascal,
+        &columns,
+        detect_timestamps(table),
+        primary_key.as_deref(),
+        &indexes,
+        table.comment.as_deref(),
+        &relations,
+    )
 }
 
 // ---------------------------------------------------------------------------
@@ -478,75 +1131,304 @@ fn detect_timestamps(table: &IntrospectedTable) -> Option<&'static str> {
 }
 
 // ---------------------------------------------------------------------------
-// Per-table generation
+// Index preservation
 // ---------------------------------------------------------------------------
 
-fn generate_for_table(
-    table: &IntrospectedTable,
-    _relationships: &HashMap<String, Vec<RelationshipInfo>>,
-) -> Result<(), String> {
-    let singular = codegen::singularize(&table.name);
-    let plural = &table.name;
-    let pascal = codegen::to_pascal_case(&singular);
-    let pascal_plural = codegen::to_pascal_case(plural);
+/// Keeps the table's existing indexes, under their original names, as long
+/// as every column they cover is part of the generated migration.
+fn preserved_indexes(table: &IntrospectedTable, fields: &[FieldInfo]) -> Vec<IndexInfo> {
+    let has_column = |col: &String| col == "id" || fields.iter().any(|f| f.column_name() == col);
+
+    table
+        .indexes
+        .iter()
+        .filter(|idx| !idx.columns.is_empty() && idx.columns.iter().all(has_column))
+        .filter(|idx| idx.columns != table.primary_key_columns)
+        .map(|idx| IndexInfo {
+            name: Some(idx.name.clone()),
+            columns: idx.columns.clone(),
+            unique: idx.unique,
+        })
+        .collect()
+}
 
-    let is_composite_pk = table.primary_key_columns.len() > 1;
+// ---------------------------------------------------------------------------
+// Per-table generation
+// ---------------------------------------------------------------------------
 
-    // For composite PK, skip only timestamps. PK columns become regular fields.
-    // For single PK, skip id and timestamps as before.
-    let skip_columns: Vec<&str> = if is_composite_pk {
+/// Whether the table's primary key is something other than a single `id`
+/// column, and so needs `#[primary_key(...)]`.
+fn has_custom_primary_key(table: &IntrospectedTable) -> bool {
+    table.primary_key_columns != ["id"]
+}
+
+/// Maps the table's columns to fields, leaving out the ones `schema!`
+/// manages itself and those without an equivalent type.
+fn table_fields(
+    table: &IntrospectedTable,
+    overrides: &ImportOverrides,
+) -> (Vec<FieldInfo>, Vec<SkippedItem>) {
+    // Only a single `id` PK is managed by schema!. Any other PK columns
+    // become regular fields, named in `#[primary_key(...)]`.
+    let skip_columns: Vec<&str> = if has_custom_primary_key(table) {
         vec!["created_at", "updated_at"]
     } else {
         vec!["id", "created_at", "updated_at"]
     };
 
     let mut fields = Vec::new();
-    let mut skipped = 0;
+    let mut skipped_columns = Vec::new();
 
     for col in &table.columns {
         if skip_columns.contains(&col.name.as_str()) {
             continue;
         }
 
-        match normalized_to_field_info(&col.name, &col.col_type, col.is_nullable) {
-            Some(fi) => fields.push(fi),
+        let name = overrides
+            .field_name(&table.name, &col.name)
+            .unwrap_or(&col.name);
+        match normalized_to_field_info(name, &col.col_type, col.is_nullable) {
+            Some(mut fi) => {
+                fi.comment = col.comment.clone();
+                if name != col.name {
+                    fi.column = Some(col.name.clone());
+                }
+                fields.push(fi);
+            }
             None => {
                 if let NormalizedType::Unmappable(ref type_name) = col.col_type {
-                    eprintln!(
-                        "    {} column {:?}.{:?} ({}) has no schema! equivalent -- skipped",
-                        "warn:".yellow(),
-                        table.name,
-                        col.name,
-                        type_name
-                    );
+                    skipped_columns.push(SkippedItem::new(
+                        "column",
+                        format!("{}.{}", table.name, col.name),
+                        format!("{} has no schema! equivalent", type_name),
+                    ));
                 }
-                skipped += 1;
             }
         }
     }
 
-    let timestamps = detect_timestamps(table);
+    (fields, skipped_columns)
+}
 
-    let primary_key = if is_composite_pk {
-        Some(table.primary_key_columns.clone())
-    } else {
-        None
+/// The table's `#[primary_key(...)]` fields, if any, and its key column,
+/// which handlers look rows up by.
+fn primary_key_parts<'a>(
+    table: &IntrospectedTable,
+    fields: &'a [FieldInfo],
+    overrides: &ImportOverrides,
+) -> (Option<Vec<String>>, codegen::IdColumn<'a>) {
+    if !has_custom_primary_key(table) {
+        let uuid_id = table
+            .columns
+            .iter()
+            .any(|c| c.name == "id" && c.col_type == NormalizedType::Uuid);
+        let id = if uuid_id {
+            codegen::IdColumn::Uuid
+        } else {
+            codegen::IdColumn::Integer
+        };
+        return (None, id);
+    }
+
+    let columns = table.primary_key_columns.iter().map(|column| {
+        overrides
+            .field_name(&table.name, column)
+            .unwrap_or(column)
+            .to_string()
+    });
+    let pk_field = match table.primary_key_columns.as_slice() {
+        [column] => fields.iter().find(|f| f.column_name() == column),
+        _ => None,
+    };
+    let id = match pk_field {
+        Some(field) => codegen::IdColumn::Field(field.column_name()),
+        None => codegen::IdColumn::Integer,
     };
+    (Some(columns.collect()), id)
+}
+
+/// Stages the migration and feature module for a table whose entity is
+/// already in `src/entity.rs`.
+fn generate_for_table(
+    changes: &mut Changes,
+    table: &IntrospectedTable,
+    overrides: &ImportOverrides,
+    prelude: Prelude,
+    route_prefix: &str,
+    json_schema: bool,
+) -> Result<Vec<SkippedItem>, String> {
+    let plural = overrides.resource_name(&table.name);
+    let singular = codegen::singularize(plural);
+    let pascal = codegen::to_pascal_case(&singular);
+    let pascal_plural = codegen::to_pascal_case(&table.name);
+
+    let (fields, skipped_columns) = table_fields(table, overrides);
+
+    let indexes = preserved_indexes(table, &fields);
+    let (_, id) = primary_key_parts(table, &fields, overrides);
 
-    codegen::update_entity_file(&pascal, &fields, timestamps, primary_key.as_deref())?;
-    codegen::create_migration_file(plural, &pascal_plural, &fields)?;
-    codegen::create_feature_module(&singular, plural, &pascal, &fields)?;
-
-    println!(
-        "  {} Imported table {:?} as {} ({} columns, {} skipped)",
-        "✓".green(),
-        table.name,
-        pascal.bright_cyan(),
-        fields.len(),
-        skipped
+    if id == codegen::IdColumn::Uuid {
+        codegen::stage_entity_edit(changes, &pascal, |content| {
+            codegen::mark_uuid_id(content, &pascal)
+        })?;
+    }
+    if plural != table.name {
+        codegen::stage_entity_edit(changes, &pascal, |content| {
+            codegen::mark_table_name(content, &pascal, &table.name)
+        })?;
+    }
+    if !json_schema {
+        codegen::stage_entity_edit(changes, &pascal, |content| {
+            codegen::mark_no_json_schema(content, &pascal)
+        })?;
+    }
+    codegen::stage_migration_file(
+        changes,
+        &table.name,
+        &pascal_plural,
+        &fields,
+        &indexes,
+        None,
+        id,
     );
+    codegen::stage_feature_module(
+        changes,
+        &singular,
+        plural,
+        &pascal,
+        &fields,
+        &codegen::ModuleOptions {
+            prelude,
+            route_prefix,
+            parent: None,
+            with_export: false,
+            with_field_selection: false,
+            with_links: false,
+            with_idempotency: false,
+            json_schema,
+            update_style: codegen::UpdateStyle::Put,
+            id,
+        },
+    )?;
+
+    Ok(skipped_columns)
+}
 
-    Ok(())
+/// Everything an import writes, staged but not yet on disk.
+struct ImportPlan {
+    changes: Changes,
+    /// `(resource, entity)` for each imported table.
+    imported: Vec<(String, String)>,
+    skipped: Vec<SkippedItem>,
+    skipped_relationships: Vec<SkippedRelationship>,
+    route_prefix: String,
+    /// `src/<plural>/` directories that already exist; their tables are
+    /// left out of the plan.
+    collisions: Vec<String>,
+}
+
+/// Stages the entities, migrations, feature modules and routes for
+/// `tables` without touching the disk.
+fn plan_import(
+    mut changes: Changes,
+    tables: Vec<IntrospectedTable>,
+    overrides: &ImportOverrides,
+    prelude: Prelude,
+    route_prefix: Option<&str>,
+    json_schema: bool,
+) -> Result<ImportPlan, String> {
+    let route_prefix = codegen::stage_route_prefix(&mut changes, route_prefix)?;
+
+    let (tables, collisions): (Vec<IntrospectedTable>, Vec<IntrospectedTable>) =
+        tables.into_iter().partition(|table| {
+            !changes.exists(Path::new("src").join(overrides.resource_name(&table.name)))
+        });
+    let collisions = collisions
+        .into_iter()
+        .map(|table| format!("src/{}/", overrides.resource_name(&table.name)))
+        .collect();
+    let (relationships, skipped_relationships) = resolve_relationships(&tables, overrides);
+
+    // One block, so the entities can refer to each other
+    let entities: Vec<String> = dependency_order(&tables, &relationships)
+        .into_iter()
+        .map(|table| schema_entity(table, &relationships, overrides))
+        .collect();
+    codegen::stage_schema_block(&mut changes, &codegen::schema_block(&entities), prelude);
+
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    let mut registrations = Vec::new();
+
+    for table in &tables {
+        let plural = overrides.resource_name(&table.name);
+        let singular = codegen::singularize(plural);
+        let pascal = codegen::to_pascal_case(&singular);
+        skipped.extend(generate_for_table(
+            &mut changes,
+            table,
+            overrides,
+            prelude,
+            &route_prefix,
+            json_schema,
+        )?);
+        registrations.push((
+            plural.to_string(),
+            codegen::generate_route_registration(&singular, plural, &route_prefix, None, false),
+        ));
+        imported.push((plural.to_string(), pascal));
+    }
+
+    // Sorted so re-importing the same tables yields the same file
+    registrations.sort();
+    let registrations: Vec<String> = registrations.into_iter().map(|(_, r)| r).collect();
+    codegen::stage_routes_module(&mut changes, &registrations, prelude);
+
+    Ok(ImportPlan {
+        changes,
+        imported,
+        skipped,
+        skipped_relationships,
+        route_prefix,
+        collisions,
+    })
+}
+
+fn collision_error(collisions: &[String]) -> String {
+    format!(
+        "Refusing to import: {} already exist(s). Remove them, leave their tables out with --tables, \
+         or rename the resources in {}.",
+        collisions.join(", "),
+        OVERRIDES_FILE
+    )
+}
+
+fn print_applied_overrides(applied: &[AppliedOverride]) {
+    if applied.is_empty() {
+        return;
+    }
+    output::blank();
+    output::info(format!(
+        "  {} Applied {} override(s) from {}:",
+        "Overrides:".bright_yellow(),
+        applied.len(),
+        OVERRIDES_FILE
+    ));
+    for item in applied {
+        output::info(format!("    - {}", item));
+    }
+}
+
+/// The `mod` lines `main.rs` needs for the imported resources, sorted to
+/// match `src/routes.rs`.
+fn module_declarations(imported: &[(String, String)]) -> Vec<String> {
+    let mut modules: Vec<&str> = imported.iter().map(|(table, _)| table.as_str()).collect();
+    modules.sort();
+    modules
+        .into_iter()
+        .chain(["entity", "migrations", "routes"])
+        .map(|module| format!("mod {};", module))
+        .collect()
 }
 
 // ---------------------------------------------------------------------------
@@ -557,11 +1439,23 @@ pub fn database(
     url: &str,
     table_filter: Option<&[String]>,
     schema_name: Option<&str>,
+    prelude: &str,
+    route_prefix: Option<&str>,
+    json_schema: bool,
+    dry_run: bool,
 ) -> Result<(), String> {
+    let prelude = Prelude::parse(prelude)?;
     codegen::verify_rapina_project()?;
+    let overrides = ImportOverrides::load()?;
+    if let Some(prefix) = route_prefix {
+        codegen::normalize_route_prefix(prefix)?;
+    }
 
-    println!();
-    println!("  {} Connecting to database...", "->".bright_cyan());
+    output::blank();
+    output::info(format!(
+        "  {} Connecting to database...",
+        "->".bright_cyan()
+    ));
 
     let rt = tokio::runtime::Runtime::new()
         .map_err(|e| format!("Failed to create async runtime: {}", e))?;
@@ -617,52 +1511,159 @@ pub fn database(
     })?;
 
     let total_discovered = tables.len();
-    println!("  {} Discovered {} table(s)", "✓".green(), total_discovered);
+    output::success(format!("Discovered {} table(s)", total_discovered));
+    for table in &tables {
+        output::detail(format!(
+            "{} ({} column(s), {} foreign key(s))",
+            table.name,
+            table.columns.len(),
+            table.foreign_keys.len()
+        ));
+    }
 
-    let tables = filter_and_validate_tables(tables, table_filter);
+    let (tables, applied) = overrides.apply(tables)?;
+    let (tables, mut skipped) = filter_and_validate_tables(tables, table_filter);
 
-    println!(
-        "  {} {} table(s) passed validation",
-        "✓".green(),
-        tables.len()
-    );
-    println!();
+    output::success(format!("{} table(s) passed validation", tables.len()));
+    output::blank();
 
     if tables.is_empty() {
-        println!("  No tables to import.");
+        output::info("  No tables to import.");
         return Ok(());
     }
 
-    let relationships = resolve_relationships(&tables);
-    let mut imported = Vec::new();
+    let ImportPlan {
+        changes,
+        imported,
+        skipped: skipped_columns,
+        skipped_relationships,
+        route_prefix,
+        collisions,
+    } = plan_import(
+        Changes::default(),
+        tables,
+        &overrides,
+        prelude,
+        route_prefix,
+        json_schema,
+    )?;
+    skipped.extend(skipped_columns);
+
+    if dry_run {
+        output::info(format!(
+            "  {} Would import {} table(s):",
+            "Dry run:".bright_yellow(),
+            imported.len()
+        ));
+        for (table_name, pascal) in &imported {
+            output::info(format!("    - {} -> {}", table_name, pascal.bright_cyan()));
+        }
+        output::blank();
+        output::info(format!("  Would write {} file(s):", changes.files().len()));
+        for file in changes.files() {
+            let action = if file.before.is_some() {
+                "update"
+            } else {
+                "create"
+            };
+            output::record(
+                "planned",
+                serde_json::json!({ "path": file.path, "action": action }),
+            );
+            output::info(format!("    {} {}", action, file.path.display()));
+        }
+        output::blank();
+        output::info(changes.preview().trim_end());
+        print_applied_overrides(&applied);
+        output::blank();
+        if !collisions.is_empty() {
+            return Err(collision_error(&collisions));
+        }
+        output::info(format!(
+            "  {} no files were changed. Run without --dry-run to apply.",
+            "Dry run:".bright_yellow()
+        ));
+        return Ok(());
+    }
 
-    for table in &tables {
-        let singular = codegen::singularize(&table.name);
-        let pascal = codegen::to_pascal_case(&singular);
-        generate_for_table(table, &relationships)?;
-        imported.push((table.name.clone(), pascal));
+    if !collisions.is_empty() {
+        return Err(collision_error(&collisions));
     }
+    changes.apply()?;
 
     // Summary
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "  {} Imported {} table(s):",
         "Summary:".bright_yellow(),
         imported.len()
-    );
+    ));
     for (table_name, pascal) in &imported {
-        println!("    - {} -> {}", table_name, pascal.bright_cyan());
+        output::info(format!("    - {} -> {}", table_name, pascal.bright_cyan()));
+    }
+    if !skipped.is_empty() {
+        output::blank();
+        output::info(format!(
+            "  {} Skipped {} table(s) and column(s):",
+            "warn:".yellow(),
+            skipped.len()
+        ));
+        for item in &skipped {
+            output::info(format!("    - {}", item));
+        }
+    }
+    if !skipped_relationships.is_empty() {
+        output::blank();
+        output::info(format!(
+            "  {} Skipped {} relationship(s):",
+            "warn:".yellow(),
+            skipped_relationships.len()
+        ));
+        for relationship in &skipped_relationships {
+            output::info(format!("    - {}", relationship));
+        }
     }
+    print_applied_overrides(&applied);
+
+    output::blank();
+    output::info(format!(
+        "  {} Wrote {} file(s):",
+        "Files:".bright_yellow(),
+        changes.files().len()
+    ));
+    changes.report();
 
     // Next steps
-    println!();
-    println!("  {}:", "Next steps".bright_yellow());
-    println!();
-    println!("  1. Review generated files in {}", "src/".cyan());
-    println!("  2. Add module declarations to {}", "src/main.rs".cyan());
-    println!("  3. Register routes in your Router");
-    println!("  4. Run {} to verify", "cargo build".cyan());
-    println!();
+    output::blank();
+    output::info(format!("  {}:", "Next steps".bright_yellow()));
+    output::blank();
+    output::info(format!("  1. Review generated files in {}", "src/".cyan()));
+    output::info(format!(
+        "  2. Add module declarations to {}:",
+        "src/main.rs".cyan()
+    ));
+    output::blank();
+    for line in module_declarations(&imported) {
+        output::info(format!("     {}", line));
+    }
+    output::blank();
+    if route_prefix.is_empty() {
+        output::info(format!(
+            "  3. Register the routes from {}:",
+            "src/routes.rs".cyan()
+        ));
+    } else {
+        output::info(format!(
+            "  3. Register the routes from {} (under {}):",
+            "src/routes.rs".cyan(),
+            route_prefix.cyan()
+        ));
+    }
+    output::blank();
+    output::info("     .router(routes::register(Router::new()))");
+    output::blank();
+    output::info(format!("  4. Run {} to verify", "cargo build".cyan()));
+    output::blank();
 
     Ok(())
 }
@@ -754,20 +1755,25 @@ mod tests {
                     name: "id".into(),
                     col_type: NormalizedType::I32,
                     is_nullable: false,
+                    comment: None,
                 },
                 IntrospectedColumn {
                     name: "created_at".into(),
                     col_type: NormalizedType::DateTimeUtc,
                     is_nullable: false,
+                    comment: None,
                 },
                 IntrospectedColumn {
                     name: "updated_at".into(),
                     col_type: NormalizedType::DateTimeUtc,
                     is_nullable: false,
+                    comment: None,
                 },
             ],
             primary_key_columns: vec!["id".into()],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         };
         assert_eq!(detect_timestamps(&table), None);
     }
@@ -780,9 +1786,12 @@ mod tests {
                 name: "id".into(),
                 col_type: NormalizedType::I32,
                 is_nullable: false,
+                comment: None,
             }],
             primary_key_columns: vec!["id".into()],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         };
         assert_eq!(detect_timestamps(&table), Some("none"));
     }
@@ -796,15 +1805,19 @@ mod tests {
                     name: "id".into(),
                     col_type: NormalizedType::I32,
                     is_nullable: false,
+                    comment: None,
                 },
                 IntrospectedColumn {
                     name: "created_at".into(),
                     col_type: NormalizedType::DateTimeUtc,
                     is_nullable: false,
+                    comment: None,
                 },
             ],
             primary_key_columns: vec!["id".into()],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         };
         assert_eq!(detect_timestamps(&table), Some("created_at"));
     }
@@ -817,16 +1830,21 @@ mod tests {
                 columns: vec![],
                 primary_key_columns: vec!["id".into()],
                 foreign_keys: vec![],
+                indexes: vec![],
+                comment: None,
             },
             IntrospectedTable {
                 name: "_prisma_migrations".into(),
                 columns: vec![],
                 primary_key_columns: vec!["id".into()],
                 foreign_keys: vec![],
+                indexes: vec![],
+                comment: None,
             },
         ];
-        let result = filter_and_validate_tables(tables, None);
+        let (result, skipped) = filter_and_validate_tables(tables, None);
         assert!(result.is_empty());
+        assert!(skipped.is_empty());
     }
 
     #[test]
@@ -836,9 +1854,12 @@ mod tests {
             columns: vec![],
             primary_key_columns: vec![],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         }];
-        let result = filter_and_validate_tables(tables, None);
+        let (result, skipped) = filter_and_validate_tables(tables, None);
         assert!(result.is_empty());
+        assert_eq!(skipped.len(), 1);
     }
 
     #[test]
@@ -848,41 +1869,92 @@ mod tests {
             columns: vec![],
             primary_key_columns: vec!["user_id".into(), "role_id".into()],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         }];
-        let result = filter_and_validate_tables(tables, None);
+        let (result, _) = filter_and_validate_tables(tables, None);
         assert!(result.is_empty());
     }
 
     #[test]
-    fn test_filter_skips_non_id_pk() {
+    fn test_filter_accepts_non_id_integer_pk() {
         let tables = vec![IntrospectedTable {
             name: "events".into(),
             columns: vec![IntrospectedColumn {
                 name: "event_id".into(),
                 col_type: NormalizedType::I32,
                 is_nullable: false,
+                comment: None,
             }],
             primary_key_columns: vec!["event_id".into()],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         }];
-        let result = filter_and_validate_tables(tables, None);
+        let (result, skipped) = filter_and_validate_tables(tables, None);
+        assert_eq!(result.len(), 1);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_filter_skips_non_id_string_pk() {
+        let tables = vec![IntrospectedTable {
+            name: "orders".into(),
+            columns: vec![IntrospectedColumn {
+                name: "order_no".into(),
+                col_type: NormalizedType::Str,
+                is_nullable: false,
+                comment: None,
+            }],
+            primary_key_columns: vec!["order_no".into()],
+            foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
+        }];
+        let (result, skipped) = filter_and_validate_tables(tables, None);
         assert!(result.is_empty());
+        assert!(skipped[0].reason.contains("only integer keys"));
     }
 
     #[test]
-    fn test_filter_skips_uuid_pk() {
+    fn test_filter_skips_i64_pk() {
         let tables = vec![IntrospectedTable {
             name: "events".into(),
             columns: vec![IntrospectedColumn {
                 name: "id".into(),
-                col_type: NormalizedType::Uuid,
+                col_type: NormalizedType::I64,
                 is_nullable: false,
+                comment: None,
             }],
             primary_key_columns: vec!["id".into()],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         }];
-        let result = filter_and_validate_tables(tables, None);
+        let (result, skipped) = filter_and_validate_tables(tables, None);
         assert!(result.is_empty());
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].reason.contains("requires i32 or Uuid"));
+    }
+
+    #[test]
+    fn test_filter_accepts_uuid_pk() {
+        let tables = vec![IntrospectedTable {
+            name: "events".into(),
+            columns: vec![IntrospectedColumn {
+                name: "id".into(),
+                col_type: NormalizedType::Uuid,
+                is_nullable: false,
+                comment: None,
+            }],
+            primary_key_columns: vec!["id".into()],
+            foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
+        }];
+        let (result, skipped) = filter_and_validate_tables(tables, None);
+        assert!(skipped.is_empty());
+        assert_eq!(result.len(), 1);
     }
 
     #[test]
@@ -893,11 +1965,15 @@ mod tests {
                 name: "id".into(),
                 col_type: NormalizedType::I32,
                 is_nullable: false,
+                comment: None,
             }],
             primary_key_columns: vec!["id".into()],
             foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
         }];
-        let result = filter_and_validate_tables(tables, None);
+        let (result, skipped) = filter_and_validate_tables(tables, None);
+        assert!(skipped.is_empty());
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].name, "users");
     }
@@ -911,9 +1987,12 @@ mod tests {
                     name: "id".into(),
                     col_type: NormalizedType::I32,
                     is_nullable: false,
+                    comment: None,
                 }],
                 primary_key_columns: vec!["id".into()],
                 foreign_keys: vec![],
+                indexes: vec![],
+                comment: None,
             },
             IntrospectedTable {
                 name: "posts".into(),
@@ -921,68 +2000,611 @@ mod tests {
                     name: "id".into(),
                     col_type: NormalizedType::I32,
                     is_nullable: false,
+                    comment: None,
                 }],
                 primary_key_columns: vec!["id".into()],
                 foreign_keys: vec![],
+                indexes: vec![],
+                comment: None,
             },
         ];
         let filter = vec!["users".to_string()];
-        let result = filter_and_validate_tables(tables, Some(&filter));
+        let (result, skipped) = filter_and_validate_tables(tables, Some(&filter));
+        assert!(skipped.is_empty());
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].name, "users");
     }
 
+    fn table(name: &str, columns: Vec<IntrospectedColumn>) -> IntrospectedTable {
+        IntrospectedTable {
+            name: name.into(),
+            columns,
+            primary_key_columns: vec!["id".into()],
+            foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
+        }
+    }
+
+    fn foreign_key(column: &str, referenced_table: &str) -> IntrospectedForeignKey {
+        IntrospectedForeignKey {
+            columns: vec![column.into()],
+            referenced_table: referenced_table.into(),
+            referenced_columns: vec!["id".into()],
+        }
+    }
+
+    fn blog_tables() -> Vec<IntrospectedTable> {
+        let mut posts = table(
+            "posts",
+            vec![
+                int_column("id"),
+                int_column("user_id"),
+                IntrospectedColumn {
+                    name: "category_id".into(),
+                    col_type: NormalizedType::I32,
+                    is_nullable: true,
+                    comment: None,
+                },
+                IntrospectedColumn {
+                    name: "title".into(),
+                    col_type: NormalizedType::Str,
+                    is_nullable: false,
+                    comment: None,
+                },
+            ],
+        );
+        posts.foreign_keys = vec![
+            foreign_key("user_id", "users"),
+            foreign_key("category_id", "categories"),
+        ];
+        vec![
+            posts,
+            table("users", vec![int_column("id")]),
+            table("categories", vec![int_column("id")]),
+        ]
+    }
+
+    fn schema_text(tables: &[IntrospectedTable], overrides: &ImportOverrides) -> String {
+        let (rels, _) = resolve_relationships(tables, overrides);
+        let entities: Vec<String> = dependency_order(tables, &rels)
+            .into_iter()
+            .map(|table| schema_entity(table, &rels, overrides))
+            .collect();
+        codegen::schema_block(&entities)
+    }
+
     #[test]
     fn test_resolve_relationships() {
+        let tables = blog_tables();
+        let overrides = ImportOverrides::default();
+
+        let (_, skipped) = resolve_relationships(&tables, &overrides);
+        assert!(skipped.is_empty());
+
+        assert_eq!(
+            schema_text(&tables, &overrides),
+            "\nschema! {
+    #[timestamps(none)]
+    User {
+        posts: Vec<Post>,
+    }
+
+    #[timestamps(none)]
+    Category {
+        posts: Vec<Post>,
+    }
+
+    #[timestamps(none)]
+    Post {
+        user: User,
+        category: Option<Category>,
+        title: String,
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_relationships_follow_resource_overrides() {
+        let tables = blog_tables();
+        let overrides = ImportOverrides::parse(
+            r#"
+[rename.users]
+table = "authors"
+
+[rename.posts]
+table = "articles"
+category_id = "section_id"
+"#,
+        )
+        .unwrap();
+
+        let schema = schema_text(&tables, &overrides);
+
+        assert!(schema.contains("    Author {\n        articles: Vec<Article>,\n"));
+        assert!(schema.contains("        user: Author,\n"));
+        // A renamed foreign key no longer matches the column belongs_to adds
+        assert!(
+            schema.contains(
+                "        #[column = \"category_id\"]\n        section_id: Option<i32>,\n"
+            )
+        );
+        assert!(!schema.contains("category: Option<Category>"));
+    }
+
+    #[test]
+    fn test_belongs_to_keeps_fk_in_migration() {
+        let tables = blog_tables();
+        let overrides = ImportOverrides::default();
+        let (fields, _) = table_fields(&tables[0], &overrides);
+
+        let migration = codegen::generate_migration(
+            "posts",
+            "Posts",
+            &fields,
+            &[],
+            None,
+            codegen::IdColumn::Integer,
+        );
+
+        assert!(migration.contains("Posts::UserId"));
+        assert!(migration.contains("Posts::CategoryId"));
+    }
+
+    #[test]
+    fn test_unsupported_foreign_keys_stay_columns() {
+        let mut comments = table(
+            "comments",
+            vec![
+                int_column("id"),
+                int_column("parent_id"),
+                int_column("author_id"),
+                int_column("editor_id"),
+                int_column("post_ref"),
+            ],
+        );
+        comments.foreign_keys = vec![
+            foreign_key("parent_id", "comments"),
+            foreign_key("author_id", "users"),
+            foreign_key("editor_id", "users"),
+            foreign_key("post_ref", "posts"),
+        ];
+        let tables = vec![
+            comments,
+            table("users", vec![int_column("id")]),
+            table("posts", vec![int_column("id")]),
+        ];
+        let overrides = ImportOverrides::default();
+
+        let (rels, skipped) = resolve_relationships(&tables, &overrides);
+
+        let reasons: Vec<(&str, &str)> = skipped
+            .iter()
+            .map(|s| (s.columns[0].as_str(), s.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    "parent_id",
+                    "self-referencing foreign keys can't be expressed in schema! yet"
+                ),
+                (
+                    "editor_id",
+                    "schema! supports one relation between two entities"
+                ),
+                (
+                    "post_ref",
+                    "only a `{field}_id` column can become a belongs_to field"
+                ),
+            ]
+        );
+        assert_eq!(rels["comments"].len(), 1);
+
+        let schema = schema_text(&tables, &overrides);
+        assert!(schema.contains("        author: User,\n"));
+        assert!(schema.contains("        parent_id: i32,\n"));
+        assert!(schema.contains("        editor_id: i32,\n"));
+        assert!(!schema.contains("        author_id: i32,\n"));
+    }
+
+    #[cfg(feature = "import-sqlite")]
+    #[test]
+    fn test_introspect_sqlite_foreign_keys() {
+        let path = std::env::temp_dir().join(format!("rapina-import-fk-{}.db", std::process::id()));
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let tables = rt.block_on(async {
+            let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+            sqlx::raw_sql(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+                 CREATE TABLE posts (
+                     id INTEGER PRIMARY KEY,
+                     user_id INTEGER NOT NULL REFERENCES users (id),
+                     editor_id INTEGER REFERENCES users
+                 );",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool.close().await;
+            introspect_sqlite(&url).await.unwrap()
+        });
+        let _ = std::fs::remove_file(&path);
+
+        let posts = tables.iter().find(|t| t.name == "posts").unwrap();
+        let mut foreign_keys: Vec<(Vec<String>, &str, Vec<String>)> = posts
+            .foreign_keys
+            .iter()
+            .map(|fk| {
+                (
+                    fk.columns.clone(),
+                    fk.referenced_table.as_str(),
+                    fk.referenced_columns.clone(),
+                )
+            })
+            .collect();
+        foreign_keys.sort();
+        assert_eq!(
+            foreign_keys,
+            vec![
+                (
+                    vec!["editor_id".to_string()],
+                    "users",
+                    vec!["id".to_string()]
+                ),
+                (vec!["user_id".to_string()], "users", vec!["id".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_order_breaks_cycles() {
+        let mut a = table("as", vec![int_column("id"), int_column("b_id")]);
+        a.foreign_keys = vec![foreign_key("b_id", "bs")];
+        let mut b = table("bs", vec![int_column("id"), int_column("a_id")]);
+        b.foreign_keys = vec![foreign_key("a_id", "as")];
+        let tables = vec![a, b];
+        let (rels, _) = resolve_relationships(&tables, &ImportOverrides::default());
+
+        let order: Vec<&str> = dependency_order(&tables, &rels)
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(order, vec!["as", "bs"]);
+    }
+
+    fn int_column(name: &str) -> IntrospectedColumn {
+        IntrospectedColumn {
+            name: name.into(),
+            col_type: NormalizedType::I32,
+            is_nullable: false,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_relationships_reports_composite_fk() {
         let tables = vec![
             IntrospectedTable {
                 name: "users".into(),
-                columns: vec![IntrospectedColumn {
-                    name: "id".into(),
-                    col_type: NormalizedType::I32,
-                    is_nullable: false,
-                }],
-                primary_key_columns: vec!["id".into()],
+                columns: vec![int_column("tenant_id"), int_column("id")],
+                primary_key_columns: vec!["tenant_id".into(), "id".into()],
                 foreign_keys: vec![],
+                indexes: vec![],
+                comment: None,
             },
             IntrospectedTable {
                 name: "posts".into(),
                 columns: vec![
-                    IntrospectedColumn {
-                        name: "id".into(),
-                        col_type: NormalizedType::I32,
-                        is_nullable: false,
-                    },
-                    IntrospectedColumn {
-                        name: "user_id".into(),
-                        col_type: NormalizedType::I32,
-                        is_nullable: false,
-                    },
+                    int_column("id"),
+                    int_column("tenant_id"),
+                    int_column("author_id"),
                 ],
                 primary_key_columns: vec!["id".into()],
                 foreign_keys: vec![IntrospectedForeignKey {
-                    columns: vec!["user_id".into()],
+                    columns: vec!["tenant_id".into(), "author_id".into()],
                     referenced_table: "users".into(),
-                    referenced_columns: vec!["id".into()],
+                    referenced_columns: vec!["tenant_id".into(), "id".into()],
                 }],
+                indexes: vec![],
+                comment: None,
             },
         ];
 
-        let rels = resolve_relationships(&tables);
+        let (rels, skipped) = resolve_relationships(&tables, &ImportOverrides::default());
+
+        assert!(rels.is_empty());
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].table, "posts");
+        assert_eq!(skipped[0].field_name, "author");
+        assert_eq!(skipped[0].columns, vec!["tenant_id", "author_id"]);
+        assert_eq!(
+            skipped[0].to_string(),
+            "posts.author (tenant_id, author_id) -> users (tenant_id, id): \
+             composite foreign keys can't be expressed in schema! yet"
+        );
+    }
+
+    #[test]
+    fn test_composite_field_name_falls_back_to_referenced_table() {
+        let fk = IntrospectedForeignKey {
+            columns: vec!["region".into(), "code".into()],
+            referenced_table: "depots".into(),
+            referenced_columns: vec!["region".into(), "code".into()],
+        };
+        assert_eq!(composite_field_name(&fk), "depot");
+    }
+
+    #[test]
+    fn test_module_declarations_sorted() {
+        let imported = vec![
+            ("users".to_string(), "User".to_string()),
+            ("posts".to_string(), "Post".to_string()),
+        ];
+        assert_eq!(
+            module_declarations(&imported),
+            vec![
+                "mod posts;",
+                "mod users;",
+                "mod entity;",
+                "mod migrations;",
+                "mod routes;"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preserved_indexes_keep_original_names() {
+        let table = IntrospectedTable {
+            name: "users".into(),
+            columns: vec![],
+            primary_key_columns: vec!["id".into()],
+            foreign_keys: vec![],
+            indexes: vec![
+                IntrospectedIndex {
+                    name: "users_email_key".into(),
+                    columns: vec!["email".into()],
+                    unique: true,
+                },
+                IntrospectedIndex {
+                    name: "users_geom_idx".into(),
+                    columns: vec!["geom".into()],
+                    unique: false,
+                },
+                IntrospectedIndex {
+                    name: "users_pkey".into(),
+                    columns: vec!["id".into()],
+                    unique: true,
+                },
+            ],
+            comment: None,
+        };
+        let fields = vec![normalized_to_field_info("email", &NormalizedType::Str, false).unwrap()];
+
+        let indexes = preserved_indexes(&table, &fields);
+        assert_eq!(indexes.len(), 1);
+        assert_eq!(indexes[0].name.as_deref(), Some("users_email_key"));
+        assert_eq!(indexes[0].columns, vec!["email".to_string()]);
+        assert!(indexes[0].unique);
+    }
+
+    fn users_table() -> IntrospectedTable {
+        IntrospectedTable {
+            name: "users".into(),
+            columns: vec![
+                int_column("id"),
+                IntrospectedColumn {
+                    name: "external_ref".into(),
+                    col_type: NormalizedType::Unmappable("Custom(\"citext\")".into()),
+                    is_nullable: true,
+                    comment: None,
+                },
+                int_column("is_admin"),
+                IntrospectedColumn {
+                    name: "legacy_blob".into(),
+                    col_type: NormalizedType::Unmappable("bytea".into()),
+                    is_nullable: true,
+                    comment: None,
+                },
+                IntrospectedColumn {
+                    name: "tbl".into(),
+                    col_type: NormalizedType::Str,
+                    is_nullable: false,
+                    comment: None,
+                },
+            ],
+            primary_key_columns: vec!["id".into()],
+            foreign_keys: vec![],
+            indexes: vec![IntrospectedIndex {
+                name: "users_tbl_idx".into(),
+                columns: vec!["tbl".into()],
+                unique: false,
+            }],
+            comment: None,
+        }
+    }
+
+    fn accounts_table() -> IntrospectedTable {
+        IntrospectedTable {
+            name: "accounts".into(),
+            columns: vec![
+                IntrospectedColumn {
+                    name: "user_id".into(),
+                    col_type: NormalizedType::I64,
+                    is_nullable: false,
+                    comment: None,
+                },
+                IntrospectedColumn {
+                    name: "email".into(),
+                    col_type: NormalizedType::Str,
+                    is_nullable: false,
+                    comment: None,
+                },
+            ],
+            primary_key_columns: vec!["user_id".into()],
+            foreign_keys: vec![],
+            indexes: vec![],
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_non_id_pk_schema_block() {
+        let table = accounts_table();
+        let overrides = ImportOverrides::default();
+        let (fields, _) = table_fields(&table, &overrides);
+        let (primary_key, id) = primary_key_parts(&table, &fields, &overrides);
+
+        assert_eq!(fields[0].name, "user_id");
+        assert_eq!(fields[0].rust_type, "i64");
+        assert_eq!(id, codegen::IdColumn::Field("user_id"));
+        assert_eq!(id.rust_type(&fields), "i64");
+
+        let block = codegen::generate_schema_block(
+            "Account",
+            &fields,
+            None,
+            primary_key.as_deref(),
+            &[],
+            None,
+        );
+        assert!(block.contains("    #[primary_key(user_id)]\n    Account {\n"));
+        assert!(block.contains("        user_id: i64,\n"));
+    }
 
-        // posts should have a BelongsTo User
-        let post_rels = rels.get("posts").unwrap();
-        assert_eq!(post_rels.len(), 1);
-        assert_eq!(post_rels[0].field_name, "user");
-        assert_eq!(post_rels[0].related_pascal, "User");
-        assert!(matches!(post_rels[0].kind, RelationKind::BelongsTo));
+    #[test]
+    fn test_non_id_pk_migration() {
+        let table = accounts_table();
+        let overrides = ImportOverrides::default();
+        let (fields, _) = table_fields(&table, &overrides);
+        let (_, id) = primary_key_parts(&table, &fields, &overrides);
+
+        let migration = codegen::generate_migration("accounts", "Accounts", &fields, &[], None, id);
+        assert!(migration.contains(
+            ".col(ColumnDef::new(Accounts::UserId).big_integer().not_null().primary_key())"
+        ));
+        assert!(!migration.contains("Accounts::Id"));
+        assert!(!migration.contains("auto_increment"));
+        assert!(migration.contains("enum Accounts {\n    Table,\n    UserId,\n    Email,\n}"));
+    }
 
-        // users should have a HasMany Post
-        let user_rels = rels.get("users").unwrap();
-        assert_eq!(user_rels.len(), 1);
-        assert_eq!(user_rels[0].field_name, "posts");
-        assert_eq!(user_rels[0].related_pascal, "Post");
-        assert!(matches!(user_rels[0].kind, RelationKind::HasMany));
+    #[test]
+    fn test_id_pk_keeps_generated_id() {
+        let table = users_table();
+        let overrides = ImportOverrides::default();
+        let (fields, _) = table_fields(&table, &overrides);
+        let (primary_key, id) = primary_key_parts(&table, &fields, &overrides);
+
+        assert!(primary_key.is_none());
+        assert_eq!(id, codegen::IdColumn::Integer);
+        assert_eq!(id.rust_type(&fields), "i32");
+        assert!(fields.iter().all(|f| f.name != "id"));
+    }
+
+    #[test]
+    fn test_overrides_map_fixture_table() {
+        let overrides = ImportOverrides::parse(
+            r#"
+[overrides.users]
+external_ref = "uuid"
+is_admin = { type = "bool", nullable = true }
+legacy_blob = "skip"
+
+[rename.users]
+table = "people"
+tbl = "label"
+"#,
+        )
+        .unwrap();
+
+        let (tables, applied) = overrides.apply(vec![users_table()]).unwrap();
+        let (tables, skipped) = filter_and_validate_tables(tables, None);
+        assert!(skipped.is_empty());
+        let table = &tables[0];
+        assert_eq!(overrides.resource_name(&table.name), "people");
+
+        let (fields, skipped) = table_fields(table, &overrides);
+        assert!(skipped.is_empty());
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["external_ref", "is_admin", "label"]);
+        assert_eq!(fields[0].schema_type, "Uuid");
+        assert_eq!(fields[0].column_method, ".uuid().null()");
+        assert_eq!(fields[1].column_method, ".boolean().null()");
+        assert_eq!(fields[2].column.as_deref(), Some("tbl"));
+        assert_eq!(fields[2].column_name(), "tbl");
+
+        let indexes = preserved_indexes(table, &fields);
+        assert_eq!(indexes[0].columns, vec!["tbl".to_string()]);
+
+        let changes: Vec<String> = applied.iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "users.external_ref: type uuid",
+                "users.is_admin: type bool",
+                "users.is_admin: nullable",
+                "users.legacy_blob: skipped",
+                "users: resource renamed to people",
+                "users.tbl: field renamed to label",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overrides_without_file_change_nothing() {
+        let overrides = ImportOverrides::default();
+        let (tables, applied) = overrides.apply(vec![users_table()]).unwrap();
+        assert!(applied.is_empty());
+        assert_eq!(overrides.resource_name("users"), "users");
+
+        let (fields, skipped) = table_fields(&tables[0], &overrides);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(skipped.len(), 2);
+    }
+
+    #[test]
+    fn test_overrides_unknown_table_lists_discovered() {
+        let overrides = ImportOverrides::parse("[overrides.usres]\nid = \"i64\"\n").unwrap();
+        let err = overrides.apply(vec![users_table()]).unwrap_err();
+        assert!(err.contains("unknown table \"usres\""));
+        assert!(err.contains("Discovered tables: users"));
+    }
+
+    #[test]
+    fn test_overrides_unknown_column_lists_columns() {
+        let overrides = ImportOverrides::parse("[rename.users]\nlabel = \"name\"\n").unwrap();
+        let err = overrides.apply(vec![users_table()]).unwrap_err();
+        assert!(err.contains("unknown column \"label\" in [rename.users]"));
+        assert!(err.contains("id, external_ref, is_admin, legacy_blob, tbl"));
+    }
+
+    #[test]
+    fn test_overrides_reject_unknown_type() {
+        let overrides =
+            ImportOverrides::parse("[overrides.users]\nexternal_ref = \"citext\"\n").unwrap();
+        let err = overrides.apply(vec![users_table()]).unwrap_err();
+        assert!(err.contains("unknown type \"citext\""));
+        assert!(err.contains("uuid"));
+    }
+
+    #[test]
+    fn test_overrides_cannot_skip_primary_key() {
+        let overrides = ImportOverrides::parse("[overrides.users]\nid = \"skip\"\n").unwrap();
+        let err = overrides.apply(vec![users_table()]).unwrap_err();
+        assert!(err.contains("primary key"));
+    }
+
+    #[test]
+    fn test_overrides_reject_unknown_keys() {
+        assert!(ImportOverrides::parse("[override.users]\nid = \"i64\"\n").is_err());
+        assert!(
+            ImportOverrides::parse("[overrides.users]\nid = { type = \"i64\", null = true }\n")
+                .is_err()
+        );
     }
 
     #[cfg(feature = "import-postgres")]
@@ -1035,4 +2657,48 @@ mod tests {
             NormalizedType::Unmappable(_)
         ));
     }
+
+    #[test]
+    fn test_plan_import_stages_files_and_reports_collisions() {
+        let root = std::env::temp_dir().join(format!("rapina-import-plan-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src/users")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"blog\"\n").unwrap();
+
+        let plan = plan_import(
+            Changes::new(&root),
+            blog_tables(),
+            &ImportOverrides::default(),
+            Prelude::Full,
+            None,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(plan.collisions, vec!["src/users/"]);
+        let mut resources: Vec<&str> = plan.imported.iter().map(|(r, _)| r.as_str()).collect();
+        resources.sort();
+        assert_eq!(resources, vec!["categories", "posts"]);
+
+        let paths: Vec<String> = plan
+            .changes
+            .files()
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect();
+        for path in [
+            "src/entity.rs",
+            "src/migrations/mod.rs",
+            "src/posts/handlers.rs",
+            "src/categories/mod.rs",
+            "src/routes.rs",
+        ] {
+            assert!(paths.iter().any(|p| p == path), "{} in {:?}", path, paths);
+        }
+        assert!(!paths.iter().any(|p| p.starts_with("src/users")));
+        assert!(!root.join("src/entity.rs").exists());
+        assert!(!root.join("src/posts").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
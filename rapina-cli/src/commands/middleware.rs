@@ -0,0 +1,103 @@
+//! Show the resolved middleware order of a running application.
+
+use crate::common::{introspection, urls};
+use crate::output;
+use colored::Colorize;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct MiddlewareInfo {
+    name: String,
+    phase: String,
+}
+
+pub struct MiddlewareConfig {
+    pub host: String,
+    pub port: u16,
+    /// Introspection token the application requires, if any.
+    pub token: Option<String>,
+}
+
+/// List the global middleware of the running application, outermost first.
+pub fn execute(config: MiddlewareConfig) -> Result<(), String> {
+    let url = urls::build_middleware_url(&config.host, config.port);
+    output::detail(format!("GET {}", url));
+    let middleware = fetch_middleware(&url, config.token.as_deref())?;
+
+    if middleware.is_empty() {
+        output::warn("No global middleware registered");
+        return Ok(());
+    }
+
+    for (position, info) in middleware.iter().enumerate() {
+        output::record(
+            "middleware",
+            serde_json::json!({
+                "position": position + 1,
+                "name": info.name,
+                "phase": info.phase,
+            }),
+        );
+    }
+
+    output::blank();
+    output::info(format!(
+        "  {:<3}  {:<16}  {}",
+        "#".bold(),
+        "PHASE".bold(),
+        "MIDDLEWARE".bold()
+    ));
+    output::info("  ───  ────────────────  ───────────────");
+    for (position, info) in middleware.iter().enumerate() {
+        output::info(format!(
+            "  {:<3}  {:<16}  {}",
+            position + 1,
+            info.phase.cyan(),
+            info.name
+        ));
+    }
+    output::blank();
+    output::info("  Requests pass through from top to bottom, responses from bottom to top.");
+    output::blank();
+
+    Ok(())
+}
+
+/// Fetch the middleware order from the running application.
+fn fetch_middleware(url: &str, token: Option<&str>) -> Result<Vec<MiddlewareInfo>, String> {
+    let output = introspection::fetch(url, token)?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to fetch middleware. Is the server running with introspection enabled on {}? If it requires an introspection token, pass --token.",
+            url
+        ));
+    }
+
+    let body =
+        String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 response: {}", e))?;
+
+    parse_middleware(&body)
+}
+
+fn parse_middleware(body: &str) -> Result<Vec<MiddlewareInfo>, String> {
+    serde_json::from_str(body).map_err(|e| format!("Invalid JSON response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_middleware() {
+        let middleware = parse_middleware(
+            r#"[{"name":"CorsMiddleware","phase":"pre_routing"},{"name":"AuthMiddleware","phase":"auth"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(middleware.len(), 2);
+        assert_eq!(middleware[0].name, "CorsMiddleware");
+        assert_eq!(middleware[0].phase, "pre_routing");
+        assert_eq!(middleware[1].name, "AuthMiddleware");
+    }
+}
@@ -2,15 +2,28 @@
 
 pub mod add;
 pub(crate) mod codegen;
+#[cfg(feature = "import")]
+pub mod console;
 pub mod dev;
 pub mod doctor;
+pub(crate) mod entities;
+#[cfg(feature = "import")]
+pub mod fake;
+pub mod generate;
 #[cfg(feature = "import")]
 pub mod import;
+pub mod middleware;
 pub mod migrate;
 pub mod new;
 pub mod openapi;
+pub mod rename;
 pub mod routes;
+pub mod schema;
 pub mod test;
+#[cfg(feature = "import")]
+pub mod transfer;
+pub mod upgrade;
+pub mod version;
 
 /// Verify that we're in a valid Rapina project directory.
 pub fn verify_rapina_project() -> Result<toml::Value, String> {
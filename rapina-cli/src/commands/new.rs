@@ -4,6 +4,8 @@ use colored::Colorize;
 use std::fs;
 use std::path::Path;
 
+use crate::output;
+
 /// Execute the `new` command to create a new Rapina project.
 pub fn execute(name: &str, no_ai: bool) -> Result<(), String> {
     // Validate project name
@@ -15,13 +17,13 @@ pub fn execute(name: &str, no_ai: bool) -> Result<(), String> {
         return Err(format!("Directory '{}' already exists", name));
     }
 
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "  {} {}",
         "Creating new Rapina project:".bright_cyan(),
         name.bold()
-    );
-    println!();
+    ));
+    output::blank();
 
     // Create project directory structure
     let src_path = project_path.join("src");
@@ -31,33 +33,33 @@ pub fn execute(name: &str, no_ai: bool) -> Result<(), String> {
     let cargo_toml = generate_cargo_toml(name);
     let cargo_path = project_path.join("Cargo.toml");
     fs::write(&cargo_path, cargo_toml).map_err(|e| format!("Failed to write Cargo.toml: {}", e))?;
-    println!("  {} Created {}", "✓".green(), "Cargo.toml".cyan());
+    output::created("Cargo.toml");
 
     // Create src/main.rs
     let main_rs = generate_main_rs();
     let main_path = src_path.join("main.rs");
     fs::write(&main_path, main_rs).map_err(|e| format!("Failed to write main.rs: {}", e))?;
-    println!("  {} Created {}", "✓".green(), "src/main.rs".cyan());
+    output::created("src/main.rs");
 
     // Create .gitignore
     let gitignore = generate_gitignore();
     let gitignore_path = project_path.join(".gitignore");
     fs::write(&gitignore_path, gitignore)
         .map_err(|e| format!("Failed to write .gitignore: {}", e))?;
-    println!("  {} Created {}", "✓".green(), ".gitignore".cyan());
+    output::created(".gitignore");
 
     // Create README.md
     let readme = generate_readme(name);
     let readme_path = project_path.join("README.md");
     fs::write(&readme_path, readme).map_err(|e| format!("Failed to write README.md: {}", e))?;
-    println!("  {} Created {}", "✓".green(), "README.md".cyan());
+    output::created("README.md");
 
     // Create AI assistant config files
     if !no_ai {
         let agent_md = generate_agent_md();
         let agent_path = project_path.join("AGENT.md");
         fs::write(&agent_path, agent_md).map_err(|e| format!("Failed to write AGENT.md: {}", e))?;
-        println!("  {} Created {}", "✓".green(), "AGENT.md".cyan());
+        output::created("AGENT.md");
 
         let claude_dir = project_path.join(".claude");
         fs::create_dir_all(&claude_dir).map_err(|e| format!("Failed to create .claude/: {}", e))?;
@@ -65,7 +67,7 @@ pub fn execute(name: &str, no_ai: bool) -> Result<(), String> {
         let claude_path = claude_dir.join("CLAUDE.md");
         fs::write(&claude_path, claude_md)
             .map_err(|e| format!("Failed to write .claude/CLAUDE.md: {}", e))?;
-        println!("  {} Created {}", "✓".green(), ".claude/CLAUDE.md".cyan());
+        output::created(".claude/CLAUDE.md");
 
         let cursor_dir = project_path.join(".cursor");
         fs::create_dir_all(&cursor_dir).map_err(|e| format!("Failed to create .cursor/: {}", e))?;
@@ -73,16 +75,16 @@ pub fn execute(name: &str, no_ai: bool) -> Result<(), String> {
         let cursor_path = cursor_dir.join("rules");
         fs::write(&cursor_path, cursor_rules)
             .map_err(|e| format!("Failed to write .cursor/rules: {}", e))?;
-        println!("  {} Created {}", "✓".green(), ".cursor/rules".cyan());
+        output::created(".cursor/rules");
     }
 
-    println!();
-    println!("  {} Project created successfully!", "🎉".bold());
-    println!();
-    println!("  {}:", "Next steps".bright_yellow());
-    println!("    cd {}", name.cyan());
-    println!("    rapina dev");
-    println!();
+    output::blank();
+    output::info(format!("  {} Project created successfully!", "🎉".bold()));
+    output::blank();
+    output::info(format!("  {}:", "Next steps".bright_yellow()));
+    output::info(format!("    cd {}", name.cyan()));
+    output::info("    rapina dev");
+    output::blank();
 
     Ok(())
 }
@@ -1,23 +1,33 @@
 //! OpenAPI specification tools.
 
+use crate::common::introspection;
+use crate::output;
 use colored::Colorize;
-use serde_json::Value;
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 
 const DEFAULT_URL: &str = "http://127.0.0.1:3000/__rapina/openapi.json";
 
 /// Export OpenAPI spec to stdout or file.
-pub fn export(output: Option<String>) -> Result<(), String> {
-    let spec = fetch_openapi_spec()?;
+///
+/// With `strict`, fails unless the spec's `info` block has a title and version.
+pub fn export(output: Option<String>, strict: bool, token: Option<&str>) -> Result<(), String> {
+    let spec = fetch_openapi_spec(token)?;
+    if strict {
+        check_info(&spec)?;
+    }
     let canonical = canonicalize_json(&spec)?;
 
     match output {
         Some(path) => {
             fs::write(&path, &canonical).map_err(|e| format!("Failed to write file: {}", e))?;
-            println!("  {} OpenAPI spec exported to {}", "✓".green(), path.cyan());
+            output::success(format!("OpenAPI spec exported to {}", path.cyan()));
         }
         None => {
+            // The spec is the command's output, so it's printed in every mode
             println!("{}", canonical);
         }
     }
@@ -26,9 +36,9 @@ pub fn export(output: Option<String>) -> Result<(), String> {
 }
 
 /// Check if the committed openapi.json matches the current code.
-pub fn check(file: &str) -> Result<(), String> {
-    println!();
-    println!("  {} Checking OpenAPI spec...", "→".cyan());
+pub fn check(file: &str, token: Option<&str>) -> Result<(), String> {
+    output::blank();
+    output::info(format!("  {} Checking OpenAPI spec...", "→".cyan()));
 
     // Read committed file
     let committed =
@@ -37,65 +47,72 @@ pub fn check(file: &str) -> Result<(), String> {
         serde_json::from_str(&committed).map_err(|e| format!("Failed to parse {}: {}", file, e))?;
 
     // Fetch current spec
-    let current = fetch_openapi_spec()?;
+    let current = fetch_openapi_spec(token)?;
 
     // Compare canonical versions
     let committed_canonical = canonicalize_json(&committed_json)?;
     let current_canonical = canonicalize_json(&current)?;
 
     if committed_canonical == current_canonical {
-        println!("  {} OpenAPI spec is up to date", "✓".green());
+        output::success("OpenAPI spec is up to date");
         Ok(())
     } else {
-        println!("  {} OpenAPI spec is outdated", "✗".red());
-        println!();
-        println!(
+        output::warn("OpenAPI spec is outdated");
+        output::blank();
+        output::info(format!(
             "  Run {} to update it.",
             "rapina openapi export -o openapi.json".cyan()
-        );
+        ));
         Err("OpenAPI spec doesn't match the current code".to_string())
     }
 }
 
 /// Compare spec with another branch and detect breaking changes.
-pub fn diff(base: &str, file: &str) -> Result<(), String> {
-    println!();
-    println!(
+pub fn diff(base: &str, file: &str, token: Option<&str>) -> Result<(), String> {
+    output::blank();
+    output::info(format!(
         "  {} Comparing OpenAPI spec with {} branch...",
         "→".cyan(),
         base.yellow()
-    );
+    ));
 
     // Get spec from base branch using git
     let base_spec = get_spec_from_branch(base, file)?;
 
     // Fetch current spec
-    let current_spec = fetch_openapi_spec()?;
+    let current_spec = fetch_openapi_spec(token)?;
 
     // Detect breaking changes
     let changes = detect_breaking_changes(&base_spec, &current_spec);
 
     if changes.breaking.is_empty() && changes.non_breaking.is_empty() {
-        println!("  {} No API changes detected", "✓".green());
+        output::success("No API changes detected");
         return Ok(());
     }
 
-    println!();
+    for change in &changes.breaking {
+        output::record("breaking_change", json!({ "change": change }));
+    }
+    for change in &changes.non_breaking {
+        output::record("non_breaking_change", json!({ "change": change }));
+    }
+
+    output::blank();
 
     if !changes.breaking.is_empty() {
-        println!("  {} Breaking changes:", "✗".red().bold());
+        output::info(format!("  {} Breaking changes:", "✗".red().bold()));
         for change in &changes.breaking {
-            println!("    {} {}", "•".red(), change);
+            output::info(format!("    {} {}", "•".red(), change));
         }
-        println!();
+        output::blank();
     }
 
     if !changes.non_breaking.is_empty() {
-        println!("  {} Non-breaking changes:", "⚠".yellow());
+        output::info(format!("  {} Non-breaking changes:", "⚠".yellow()));
         for change in &changes.non_breaking {
-            println!("    {} {}", "•".yellow(), change);
+            output::info(format!("    {} {}", "•".yellow(), change));
         }
-        println!();
+        output::blank();
     }
 
     if !changes.breaking.is_empty() {
@@ -108,16 +125,223 @@ pub fn diff(base: &str, file: &str) -> Result<(), String> {
     }
 }
 
+/// Merge examples recorded by `TestClient::record_examples` into a spec file.
+///
+/// Writes to `output`, or back to `file` when it's not given.
+pub fn enrich(file: &str, examples_dir: &str, output: Option<&str>) -> Result<(), String> {
+    output::blank();
+    output::info(format!(
+        "  {} Adding examples from {}...",
+        "→".cyan(),
+        examples_dir.cyan()
+    ));
+
+    let content =
+        fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+    let mut spec: Value =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", file, e))?;
+    let examples = load_examples(Path::new(examples_dir))?;
+
+    let report = merge_examples(&mut spec, &examples);
+    for operation in &report.missing {
+        output::skipped("example", operation, "no operation in the spec");
+    }
+
+    let output = output.unwrap_or(file);
+    fs::write(output, canonicalize_json(&spec)?)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    output::success(format!(
+        "Added {} example(s) to {} operation(s) in {}",
+        report.added,
+        report.operations.len(),
+        output.cyan()
+    ));
+    Ok(())
+}
+
+/// Reads the recorded example files in `dir`, sorted by file name.
+fn load_examples(dir: &Path) -> Result<Vec<Value>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "json")
+                && !path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+/// What merging examples into a spec changed.
+#[derive(Debug, Default)]
+struct EnrichReport {
+    added: usize,
+    /// `METHOD /path` of every operation that got an example.
+    operations: BTreeSet<String>,
+    /// `METHOD /path` of recorded routes the spec has no operation for.
+    missing: BTreeSet<String>,
+}
+
+/// Adds each recorded exchange to its operation as a named example, on the
+/// request body and on the response for its status.
+///
+/// Examples are named after the status, like `status_200`, then
+/// `status_200_2` and so on, in the order they are given. Merging the same
+/// examples again overwrites them in place, so the result is deterministic.
+fn merge_examples(spec: &mut Value, examples: &[Value]) -> EnrichReport {
+    let mut report = EnrichReport::default();
+    let mut counts: BTreeMap<(String, String, u64), usize> = BTreeMap::new();
+
+    for example in examples {
+        let (Some(method), Some(route)) = (example["method"].as_str(), example["route"].as_str())
+        else {
+            continue;
+        };
+        let method = method.to_ascii_lowercase();
+        let path = openapi_path(route);
+        let label = format!("{} {}", method.to_ascii_uppercase(), path);
+
+        let Some(operation) = spec
+            .get_mut("paths")
+            .and_then(|paths| paths.get_mut(&path))
+            .and_then(|item| item.get_mut(&method))
+            .and_then(Value::as_object_mut)
+        else {
+            report.missing.insert(label);
+            continue;
+        };
+
+        let request = &example["request"];
+        let response = &example["response"];
+        let status = response["status"].as_u64().unwrap_or(200);
+        let count = counts
+            .entry((method.clone(), path.clone(), status))
+            .or_default();
+        *count += 1;
+        let name = match *count {
+            1 => format!("status_{}", status),
+            n => format!("status_{}_{}", status, n),
+        };
+        let summary = format!(
+            "{} {}",
+            method.to_ascii_uppercase(),
+            request["path"].as_str().unwrap_or(route)
+        );
+
+        if let Some(body) = request.get("body") {
+            let content = object_entry(operation, "requestBody")
+                .entry("content")
+                .or_insert_with(|| json!({}));
+            add_example(content, &request["headers"], body, &name, &summary);
+        }
+        if let Some(body) = response.get("body") {
+            let responses = object_entry(operation, "responses");
+            let response_object = responses
+                .entry(status.to_string())
+                .or_insert_with(|| json!({ "description": "Recorded example" }));
+            let content = response_object
+                .as_object_mut()
+                .map(|r| r.entry("content").or_insert_with(|| json!({})));
+            if let Some(content) = content {
+                add_example(content, &response["headers"], body, &name, &summary);
+            }
+        }
+
+        report.added += 1;
+        report.operations.insert(label);
+    }
+
+    report
+}
+
+/// The object under `key`, created when missing.
+fn object_entry<'a>(object: &'a mut Map<String, Value>, key: &str) -> &'a mut Map<String, Value> {
+    let value = object.entry(key).or_insert_with(|| json!({}));
+    if !value.is_object() {
+        *value = json!({});
+    }
+    value.as_object_mut().unwrap()
+}
+
+/// Adds a named example under the media type from the recorded headers.
+fn add_example(content: &mut Value, headers: &Value, body: &Value, name: &str, summary: &str) {
+    let media_type = headers["content-type"]
+        .as_str()
+        .and_then(|ct| ct.split(';').next())
+        .map(|ct| ct.trim().to_ascii_lowercase())
+        .unwrap_or_else(|| {
+            if body.is_string() {
+                "text/plain".to_string()
+            } else {
+                "application/json".to_string()
+            }
+        });
+    let Some(content) = content.as_object_mut() else {
+        return;
+    };
+    let media = content.entry(media_type).or_insert_with(|| json!({}));
+    if let Some(media) = media.as_object_mut() {
+        object_entry(media, "examples").insert(
+            name.to_string(),
+            json!({ "summary": summary, "value": body }),
+        );
+    }
+}
+
+/// Converts a route pattern like `/users/:id` to `/users/{id}`.
+pub(crate) fn openapi_path(route: &str) -> String {
+    route
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Ensure the spec's `info` block carries a non-empty title and version.
+fn check_info(spec: &Value) -> Result<(), String> {
+    let info = spec.get("info");
+    let missing: Vec<&str> = ["title", "version"]
+        .into_iter()
+        .filter(|field| {
+            info.and_then(|i| i.get(*field))
+                .and_then(|v| v.as_str())
+                .is_none_or(|v| v.trim().is_empty())
+        })
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "OpenAPI spec is missing info.{}. Set it with Rapina::openapi_info()",
+            missing.join(" and info.")
+        ))
+    }
+}
+
 /// Fetch OpenAPI spec from running application.
-fn fetch_openapi_spec() -> Result<Value, String> {
-    let output = Command::new("curl")
-        .args(["-s", "-f", DEFAULT_URL])
-        .output()
-        .map_err(|e| format!("Failed to run curl: {}", e))?;
+fn fetch_openapi_spec(token: Option<&str>) -> Result<Value, String> {
+    let output = introspection::fetch(DEFAULT_URL, token)?;
 
     if !output.status.success() {
         return Err(format!(
-            "Failed to fetch OpenAPI spec. Is the server running on {}?",
+            "Failed to fetch OpenAPI spec. Is the server running on {}? If it requires an introspection token, pass --token.",
             DEFAULT_URL
         ));
     }
@@ -310,7 +534,97 @@ fn check_schema_changes(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
+
+    const EXAMPLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/examples");
+
+    fn users_spec() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/users": {
+                    "post": {
+                        "operationId": "create_user",
+                        "responses": { "200": { "description": "Success" } }
+                    }
+                },
+                "/users/{id}": {
+                    "get": {
+                        "operationId": "get_user",
+                        "responses": { "200": { "description": "Success" } }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_merge_recorded_examples() {
+        let mut spec = users_spec();
+        let examples = load_examples(Path::new(EXAMPLES)).unwrap();
+        let report = merge_examples(&mut spec, &examples);
+
+        assert_eq!(report.added, 3);
+        assert_eq!(
+            report.operations.iter().collect::<Vec<_>>(),
+            ["GET /users/{id}", "POST /users"]
+        );
+        assert_eq!(report.missing.iter().collect::<Vec<_>>(), ["GET /health"]);
+
+        let create = &spec["paths"]["/users"]["post"];
+        assert_eq!(
+            create["requestBody"]["content"]["application/json"]["examples"]["status_201"],
+            json!({ "summary": "POST /users", "value": { "name": "Ada" } })
+        );
+        assert_eq!(
+            create["responses"]["201"]["description"],
+            "Recorded example"
+        );
+        assert_eq!(
+            create["responses"]["201"]["content"]["application/json"]["examples"]["status_201"]["value"],
+            json!({ "id": 1, "name": "Ada" })
+        );
+
+        let examples = &spec["paths"]["/users/{id}"]["get"]["responses"]["200"]["content"]["application/json"]
+            ["examples"];
+        assert_eq!(examples["status_200"]["summary"], "GET /users/1");
+        assert_eq!(examples["status_200_2"]["summary"], "GET /users/2");
+    }
+
+    #[test]
+    fn test_merge_is_deterministic() {
+        let examples = load_examples(Path::new(EXAMPLES)).unwrap();
+
+        let mut once = users_spec();
+        merge_examples(&mut once, &examples);
+        let mut twice = once.clone();
+        merge_examples(&mut twice, &examples);
+        let mut reversed = users_spec();
+        let mut shuffled = examples.clone();
+        shuffled.reverse();
+        merge_examples(&mut reversed, &shuffled);
+
+        assert_eq!(once, twice);
+        assert_eq!(
+            canonicalize_json(&once).unwrap(),
+            canonicalize_json(&twice).unwrap()
+        );
+        // Order decides numbering only among examples of the same status
+        assert_eq!(
+            reversed["paths"]["/users/{id}"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["examples"]["status_200"]["summary"],
+            "GET /users/2"
+        );
+    }
+
+    #[test]
+    fn test_openapi_path() {
+        assert_eq!(openapi_path("/users/:id"), "/users/{id}");
+        assert_eq!(
+            openapi_path("/users/:user_id/posts/:id"),
+            "/users/{user_id}/posts/{id}"
+        );
+        assert_eq!(openapi_path("/health"), "/health");
+    }
 
     #[test]
     fn test_detect_removed_endpoint() {
@@ -366,6 +680,22 @@ mod tests {
         assert!(report.breaking.iter().any(|c| c.contains("POST")));
     }
 
+    #[test]
+    fn test_check_info_accepts_title_and_version() {
+        let spec = json!({"info": {"title": "Todo API", "version": "1.0.0"}});
+        assert!(check_info(&spec).is_ok());
+    }
+
+    #[test]
+    fn test_check_info_reports_missing_fields() {
+        let spec = json!({"info": {"title": "  "}});
+        let err = check_info(&spec).unwrap_err();
+        assert!(err.contains("info.title and info.version"));
+
+        let err = check_info(&json!({})).unwrap_err();
+        assert!(err.contains("info.title"));
+    }
+
     #[test]
     fn test_no_changes() {
         let spec = json!({
@@ -0,0 +1,888 @@
+//! Rename fields of generated resources.
+//!
+//! `rapina rename field post title headline` renames the field in its
+//! `schema!` block, in the resource's DTOs and handlers, and writes a
+//! migration renaming the column. Every file is checked and rewritten in
+//! memory first, so a missing field or a name clash fails before anything
+//! on disk changes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use syn::ext::IdentExt;
+use syn::parse::{ParseStream, Parser};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+use syn::{Attribute, Ident, Token};
+
+use super::add;
+use super::codegen;
+use super::entities;
+use super::upgrade::{Edit, FileChange, LineIndex, apply_edits, display_path, unified_diff};
+use crate::output;
+
+/// Types `schema!` maps to a column; anything else is a relationship.
+const SCALAR_TYPES: &[&str] = &[
+    "String",
+    "Text",
+    "i32",
+    "i64",
+    "f32",
+    "f64",
+    "bool",
+    "Uuid",
+    "DateTime",
+    "NaiveDateTime",
+    "Date",
+    "Decimal",
+    "Json",
+];
+
+pub struct RenameFieldConfig {
+    pub resource: String,
+    pub old: String,
+    pub new: String,
+    /// Keep accepting the old name in request bodies via `#[alias]`.
+    pub alias: bool,
+    /// Leave the database column as it is and map it with `#[column]`.
+    pub keep_column: bool,
+    pub dry_run: bool,
+}
+
+/// A migration the rename will create.
+struct NewMigration {
+    module: String,
+    source: String,
+}
+
+/// Everything a rename will do, computed without touching the project.
+struct Plan {
+    changes: Vec<FileChange>,
+    migration: Option<NewMigration>,
+    /// Follow-up work that couldn't be done automatically.
+    notes: Vec<String>,
+}
+
+/// Rename a field of a resource.
+pub fn field(config: RenameFieldConfig) -> Result<(), String> {
+    codegen::verify_rapina_project()?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let plan = plan(Path::new("."), &config, &timestamp)?;
+
+    output::blank();
+    for change in &plan.changes {
+        output::info(unified_diff(change).trim_end());
+    }
+    if let Some(migration) = &plan.migration {
+        output::info(format!(
+            "+++ b/src/migrations/{}.rs (new migration)",
+            migration.module
+        ));
+    }
+    output::blank();
+
+    if config.dry_run {
+        output::info("  Dry run: no files were changed. Run without --dry-run to apply.");
+        output::blank();
+        return Ok(());
+    }
+
+    for change in &plan.changes {
+        fs::write(&change.path, &change.after)
+            .map_err(|e| format!("Failed to write {}: {}", change.path.display(), e))?;
+        output::updated(&display_path(&change.path));
+    }
+
+    if let Some(migration) = &plan.migration {
+        let migrations_dir = Path::new("src/migrations");
+        if !migrations_dir.exists() {
+            fs::create_dir_all(migrations_dir)
+                .map_err(|e| format!("Failed to create migrations directory: {}", e))?;
+            output::created("src/migrations/");
+        }
+        let filename = format!("{}.rs", migration.module);
+        fs::write(migrations_dir.join(&filename), &migration.source)
+            .map_err(|e| format!("Failed to write migration file: {}", e))?;
+        output::created(&format!("src/migrations/{}", filename));
+        super::migrate::update_mod_rs(migrations_dir, &migration.module)?;
+    }
+
+    for note in &plan.notes {
+        output::warn(note);
+    }
+
+    output::blank();
+    output::success(format!("Renamed {} to {}", config.old, config.new));
+    if config.alias {
+        output::info(format!(
+            "  Requests sending \"{}\" are still accepted. Remove the #[alias] once clients have moved to \"{}\".",
+            config.old, config.new
+        ));
+    }
+    output::blank();
+
+    Ok(())
+}
+
+fn plan(root: &Path, config: &RenameFieldConfig, timestamp: &str) -> Result<Plan, String> {
+    add::validate_field_name(&config.new)?;
+    if config.old == config.new {
+        return Err(format!("'{}' already has that name", config.old));
+    }
+
+    let mut renamed = None;
+    for path in entities::entity_files(root) {
+        let Ok(source) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(rename) = rename_in_schema(&source, config)? {
+            renamed = Some((path, source, rename));
+            break;
+        }
+    }
+    let Some((entity_path, before, rename)) = renamed else {
+        return Err(format!(
+            "Resource '{}' not found in the schema! blocks of src/entity.rs",
+            config.resource
+        ));
+    };
+
+    let mut changes = vec![FileChange {
+        path: entity_path,
+        before,
+        after: rename.source,
+    }];
+    let mut notes = Vec::new();
+
+    let module = [
+        rename.table.clone(),
+        codegen::pluralize(&entities::to_snake_case(&rename.entity)),
+    ]
+    .into_iter()
+    .map(|dir| root.join("src").join(dir))
+    .find(|dir| dir.is_dir());
+
+    match module {
+        Some(module) => {
+            let (old, new) = (config.old.as_str(), config.new.as_str());
+            changes.extend(rewrite_file(module.join("dto.rs"), |source| {
+                rename_in_dto(source, old, new, config.alias)
+            })?);
+            changes.extend(rewrite_file(module.join("handlers.rs"), |source| {
+                rename_in_handlers(source, old, new)
+            })?);
+        }
+        None => notes.push(format!(
+            "No src/{}/ module found; rename the field in its DTOs and handlers by hand",
+            rename.table
+        )),
+    }
+
+    let migration = (rename.old_column != rename.new_column).then(|| NewMigration {
+        module: format!(
+            "m{}_rename_{}_{}_to_{}",
+            timestamp, rename.table, rename.old_column, rename.new_column
+        ),
+        source: generate_rename_migration(&rename.table, &rename.old_column, &rename.new_column),
+    });
+
+    Ok(Plan {
+        changes,
+        migration,
+        notes,
+    })
+}
+
+/// Runs `rewrite` over a file, returning the change if it edited anything.
+/// Missing files are skipped.
+fn rewrite_file(
+    path: PathBuf,
+    rewrite: impl FnOnce(&str) -> Result<String, String>,
+) -> Result<Option<FileChange>, String> {
+    let Ok(before) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let after = rewrite(&before).map_err(|e| format!("{}: {}", display_path(&path), e))?;
+    Ok((after != before).then_some(FileChange {
+        path,
+        before,
+        after,
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// schema! blocks
+// ---------------------------------------------------------------------------
+
+struct SchemaField {
+    attrs: Vec<Attribute>,
+    name: Ident,
+    ty: syn::Type,
+}
+
+struct SchemaEntity {
+    attrs: Vec<Attribute>,
+    name: Ident,
+    fields: Vec<SchemaField>,
+}
+
+impl SchemaEntity {
+    fn table(&self) -> String {
+        self.attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("table_name"))
+            .and_then(string_value)
+            .map(|(value, _)| value)
+            .unwrap_or_else(|| codegen::pluralize(&entities::to_snake_case(&self.name.to_string())))
+    }
+
+    fn primary_key(&self) -> Vec<String> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("primary_key"))
+            .and_then(|attr| {
+                attr.parse_args_with(
+                    syn::punctuated::Punctuated::<Ident, Token![,]>::parse_terminated,
+                )
+                .ok()
+            })
+            .map(|columns| columns.iter().map(|c| c.to_string()).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl SchemaField {
+    fn name(&self) -> String {
+        self.name.unraw().to_string()
+    }
+
+    /// The type name, looking through `Option`.
+    fn type_name(&self) -> String {
+        let mut ty = &self.ty;
+        loop {
+            let syn::Type::Path(path) = ty else {
+                return String::new();
+            };
+            let Some(segment) = path.path.segments.last() else {
+                return String::new();
+            };
+            if segment.ident == "Option"
+                && let syn::PathArguments::AngleBracketed(args) = &segment.arguments
+                && let Some(syn::GenericArgument::Type(inner)) = args.args.first()
+            {
+                ty = inner;
+                continue;
+            }
+            return segment.ident.to_string();
+        }
+    }
+
+    fn is_scalar(&self) -> bool {
+        SCALAR_TYPES.contains(&self.type_name().as_str())
+    }
+
+    fn column_attr(&self) -> Option<(String, proc_macro2::Span)> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("column"))
+            .and_then(string_value)
+    }
+
+    /// The database column backing the field, if it has one.
+    fn column(&self) -> Option<String> {
+        if self.type_name() == "Vec" {
+            None
+        } else if self.is_scalar() {
+            Some(self.column_attr().map_or_else(|| self.name(), |(c, _)| c))
+        } else {
+            Some(format!("{}_id", entities::to_snake_case(&self.name())))
+        }
+    }
+}
+
+fn parse_schema(input: ParseStream) -> syn::Result<Vec<SchemaEntity>> {
+    let mut entities = Vec::new();
+
+    while !input.is_empty() {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name: Ident = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+
+        let mut fields = Vec::new();
+        while !content.is_empty() {
+            let attrs = content.call(Attribute::parse_outer)?;
+            let name = content.call(Ident::parse_any)?;
+            content.parse::<Token![:]>()?;
+            let ty: syn::Type = content.parse()?;
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+            fields.push(SchemaField { attrs, name, ty });
+        }
+
+        entities.push(SchemaEntity {
+            attrs,
+            name,
+            fields,
+        });
+    }
+
+    Ok(entities)
+}
+
+/// The string and its span in `#[name = "value"]`.
+fn string_value(attr: &Attribute) -> Option<(String, proc_macro2::Span)> {
+    let syn::Meta::NameValue(meta) = &attr.meta else {
+        return None;
+    };
+    match &meta.value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(value),
+            ..
+        }) => Some((value.value(), value.span())),
+        _ => None,
+    }
+}
+
+/// A field renamed in a `schema!` source.
+struct SchemaRename {
+    source: String,
+    entity: String,
+    table: String,
+    old_column: String,
+    new_column: String,
+}
+
+fn matches_resource(entity: &SchemaEntity, resource: &str) -> bool {
+    let name = entity.name.to_string();
+    name.eq_ignore_ascii_case(resource)
+        || entity.table().eq_ignore_ascii_case(resource)
+        || name == codegen::to_pascal_case(&codegen::singularize(resource))
+}
+
+/// Renames `config.old` in the entity matching `config.resource`. Returns
+/// `None` when the entity isn't declared in this source.
+fn rename_in_schema(
+    source: &str,
+    config: &RenameFieldConfig,
+) -> Result<Option<SchemaRename>, String> {
+    let file = syn::parse_file(source).map_err(|e| format!("failed to parse: {}", e))?;
+    let entity = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Macro(item)
+                if item
+                    .mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "schema") =>
+            {
+                parse_schema.parse2(item.mac.tokens.clone()).ok()
+            }
+            _ => None,
+        })
+        .flatten()
+        .find(|entity| matches_resource(entity, &config.resource));
+    let Some(entity) = entity else {
+        return Ok(None);
+    };
+
+    let entity_name = entity.name.to_string();
+    let (old, new) = (config.old.as_str(), config.new.as_str());
+
+    let Some(field) = entity.fields.iter().find(|f| f.name() == old) else {
+        return Err(format!("{} has no field '{}'", entity_name, old));
+    };
+    if entity.fields.iter().any(|f| f.name() == new) {
+        return Err(format!("{} already has a field '{}'", entity_name, new));
+    }
+    if !field.is_scalar() {
+        return Err(format!(
+            "'{}' is a relationship; only scalar fields can be renamed",
+            old
+        ));
+    }
+    if entity.primary_key().iter().any(|pk| pk == old) {
+        return Err(format!(
+            "'{}' is part of the primary key of {}; rename it by hand",
+            old, entity_name
+        ));
+    }
+
+    let explicit_column = field.column_attr();
+    let old_column = field.column().unwrap_or_else(|| old.to_string());
+    let new_column = if config.keep_column {
+        old_column.clone()
+    } else {
+        new.to_string()
+    };
+    if let Some(other) = entity
+        .fields
+        .iter()
+        .filter(|f| f.name() != old)
+        .find(|f| f.column().as_deref() == Some(new_column.as_str()))
+    {
+        return Err(format!(
+            "Column '{}' is already used by {}.{}",
+            new_column,
+            entity_name,
+            other.name()
+        ));
+    }
+
+    let index = LineIndex::new(source);
+    let name_start = index.start(field.name.span());
+    let indent = index.indent(name_start);
+    let mut edits = Vec::new();
+
+    match explicit_column {
+        Some((_, span)) if !config.keep_column => edits.push(Edit::replace(
+            index.start(span),
+            index.end(span),
+            format!("\"{}\"", new_column),
+        )),
+        None if config.keep_column => edits.push(Edit::insert(
+            name_start,
+            format!("#[column = \"{}\"]\n{}", old_column, indent),
+        )),
+        _ => {}
+    }
+    if config.alias {
+        edits.push(Edit::insert(
+            name_start,
+            format!("#[alias = \"{}\"]\n{}", old, indent),
+        ));
+    }
+    // Pushed after the insertions at the same offset so it doesn't overwrite them
+    edits.push(Edit::replace(
+        name_start,
+        index.end(field.name.span()),
+        codegen::rust_ident(new),
+    ));
+
+    Ok(Some(SchemaRename {
+        source: apply_edits(source, edits),
+        table: entity.table(),
+        entity: entity_name,
+        old_column,
+        new_column,
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Resource modules
+// ---------------------------------------------------------------------------
+
+/// Renames the field in every struct of a `dto.rs`, adding
+/// `#[serde(alias = "old")]` when asked.
+fn rename_in_dto(source: &str, old: &str, new: &str, alias: bool) -> Result<String, String> {
+    let file = syn::parse_file(source).map_err(|e| format!("failed to parse: {}", e))?;
+    let index = LineIndex::new(source);
+    let mut edits = Vec::new();
+
+    for item in &file.items {
+        let syn::Item::Struct(item) = item else {
+            continue;
+        };
+        for field in &item.fields {
+            let Some(ident) = field.ident.as_ref().filter(|i| i.unraw() == old) else {
+                continue;
+            };
+            if alias {
+                let start = match &field.vis {
+                    syn::Visibility::Inherited => index.start(ident.span()),
+                    vis => index.start(vis.span()),
+                };
+                edits.push(Edit::insert(
+                    start,
+                    format!("#[serde(alias = \"{}\")]\n{}", old, index.indent(start)),
+                ));
+            }
+            edits.push(Edit::replace(
+                index.start(ident.span()),
+                index.end(ident.span()),
+                codegen::rust_ident(new),
+            ));
+        }
+    }
+
+    Ok(apply_edits(source, edits))
+}
+
+/// Renames uses of the field in a `handlers.rs`: `input.old`, `old: Set(..)`
+/// in struct literals and `Column::Old`.
+fn rename_in_handlers(source: &str, old: &str, new: &str) -> Result<String, String> {
+    struct Renamer<'a> {
+        index: &'a LineIndex<'a>,
+        old: &'a str,
+        old_variant: String,
+        new: String,
+        new_variant: String,
+        edits: Vec<Edit>,
+    }
+
+    impl Renamer<'_> {
+        fn replace(&mut self, span: proc_macro2::Span, text: String) {
+            self.edits.push(Edit::replace(
+                self.index.start(span),
+                self.index.end(span),
+                text,
+            ));
+        }
+    }
+
+    impl<'ast> Visit<'ast> for Renamer<'_> {
+        fn visit_expr_field(&mut self, node: &'ast syn::ExprField) {
+            if let syn::Member::Named(ident) = &node.member
+                && ident.unraw() == self.old
+            {
+                self.replace(ident.span(), self.new.clone());
+            }
+            syn::visit::visit_expr_field(self, node);
+        }
+
+        fn visit_field_value(&mut self, node: &'ast syn::FieldValue) {
+            if let syn::Member::Named(ident) = &node.member
+                && ident.unraw() == self.old
+            {
+                let text = if node.colon_token.is_some() {
+                    self.new.clone()
+                } else {
+                    // Shorthand `old` becomes `new: old`
+                    format!("{}: {}", self.new, ident)
+                };
+                self.replace(ident.span(), text);
+                if node.colon_token.is_none() {
+                    return;
+                }
+            }
+            syn::visit::visit_field_value(self, node);
+        }
+
+        fn visit_path(&mut self, node: &'ast syn::Path) {
+            let segments: Vec<&syn::PathSegment> = node.segments.iter().collect();
+            for pair in segments.windows(2) {
+                if pair[0].ident == "Column" && pair[1].ident == self.old_variant {
+                    let span = pair[1].ident.span();
+                    self.replace(span, self.new_variant.clone());
+                }
+            }
+            syn::visit::visit_path(self, node);
+        }
+    }
+
+    let file = syn::parse_file(source).map_err(|e| format!("failed to parse: {}", e))?;
+    let index = LineIndex::new(source);
+    let mut renamer = Renamer {
+        index: &index,
+        old,
+        old_variant: codegen::to_pascal_case(old),
+        new: codegen::rust_ident(new),
+        new_variant: codegen::to_pascal_case(new),
+        edits: Vec::new(),
+    };
+    renamer.visit_file(&file);
+
+    Ok(apply_edits(source, renamer.edits))
+}
+
+fn generate_rename_migration(table: &str, old_column: &str, new_column: &str) -> String {
+    let table_iden = codegen::to_pascal_case(table);
+    let old_iden = codegen::to_pascal_case(old_column);
+    let new_iden = codegen::to_pascal_case(new_column);
+
+    format!(
+        r#"//! Migration: rename {table}.{old_column} to {new_column}
+
+use rapina::sea_orm_migration;
+use rapina::migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {{
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {{
+        manager
+            .alter_table(
+                Table::alter()
+                    .table({table_iden}::Table)
+                    .rename_column({table_iden}::{old_iden}, {table_iden}::{new_iden})
+                    .to_owned(),
+            )
+            .await
+    }}
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {{
+        manager
+            .alter_table(
+                Table::alter()
+                    .table({table_iden}::Table)
+                    .rename_column({table_iden}::{new_iden}, {table_iden}::{old_iden})
+                    .to_owned(),
+            )
+            .await
+    }}
+}}
+
+#[derive(DeriveIden)]
+enum {table_iden} {{
+    Table,
+    {old_iden},
+    {new_iden},
+}}
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rename");
+
+    const ENTITY: &str = r#"use rapina::prelude::*;
+
+schema! {
+    Post {
+        title: String,
+        #[column = "post_body"]
+        body: Text,
+        author: User,
+    }
+
+    #[primary_key(code)]
+    Tag {
+        code: String,
+        label: String,
+    }
+
+    User {
+        name: String,
+    }
+}
+"#;
+
+    fn config(resource: &str, old: &str, new: &str) -> RenameFieldConfig {
+        RenameFieldConfig {
+            resource: resource.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+            alias: false,
+            keep_column: false,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_rename_in_schema() {
+        let rename = rename_in_schema(ENTITY, &config("posts", "title", "headline"))
+            .unwrap()
+            .unwrap();
+        assert!(rename.source.contains("        headline: String,\n"));
+        assert!(!rename.source.contains("title"));
+        assert_eq!(rename.entity, "Post");
+        assert_eq!(rename.table, "posts");
+        assert_eq!(rename.old_column, "title");
+        assert_eq!(rename.new_column, "headline");
+    }
+
+    #[test]
+    fn test_rename_in_schema_with_alias() {
+        let mut config = config("Post", "title", "headline");
+        config.alias = true;
+        let rename = rename_in_schema(ENTITY, &config).unwrap().unwrap();
+        assert!(
+            rename
+                .source
+                .contains("        #[alias = \"title\"]\n        headline: String,\n")
+        );
+    }
+
+    #[test]
+    fn test_rename_in_schema_keep_column() {
+        let mut config = config("Post", "title", "headline");
+        config.keep_column = true;
+        let rename = rename_in_schema(ENTITY, &config).unwrap().unwrap();
+        assert!(
+            rename
+                .source
+                .contains("        #[column = \"title\"]\n        headline: String,\n")
+        );
+        assert_eq!(rename.old_column, rename.new_column);
+    }
+
+    #[test]
+    fn test_rename_in_schema_rewrites_explicit_column() {
+        let rename = rename_in_schema(ENTITY, &config("post", "body", "content"))
+            .unwrap()
+            .unwrap();
+        assert!(
+            rename
+                .source
+                .contains("        #[column = \"content\"]\n        content: Text,\n")
+        );
+        assert_eq!(rename.old_column, "post_body");
+        assert_eq!(rename.new_column, "content");
+    }
+
+    #[test]
+    fn test_rename_in_schema_errors() {
+        let error = |resource, old, new| {
+            rename_in_schema(ENTITY, &config(resource, old, new))
+                .err()
+                .unwrap()
+        };
+        assert_eq!(
+            error("Post", "subtitle", "x"),
+            "Post has no field 'subtitle'"
+        );
+        assert_eq!(
+            error("Post", "title", "body"),
+            "Post already has a field 'body'"
+        );
+        assert_eq!(
+            error("Post", "title", "author_id"),
+            "Column 'author_id' is already used by Post.author"
+        );
+        assert!(error("Post", "author", "writer").contains("relationship"));
+        assert!(error("Tag", "code", "slug").contains("primary key"));
+    }
+
+    #[test]
+    fn test_rename_in_schema_other_resource() {
+        assert!(
+            rename_in_schema(ENTITY, &config("comments", "title", "headline"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_rename_in_dto() {
+        let source = "#[derive(Deserialize)]\npub struct CreatePost {\n    pub title: String,\n}\n\n#[derive(Deserialize)]\npub struct UpdatePost {\n    pub title: Option<String>,\n}\n";
+        let renamed = rename_in_dto(source, "title", "headline", true).unwrap();
+        assert_eq!(
+            renamed,
+            "#[derive(Deserialize)]\npub struct CreatePost {\n    #[serde(alias = \"title\")]\n    pub headline: String,\n}\n\n#[derive(Deserialize)]\npub struct UpdatePost {\n    #[serde(alias = \"title\")]\n    pub headline: Option<String>,\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_in_scaffolded_handlers() {
+        let fields = vec![codegen::FieldInfo {
+            name: "title".to_string(),
+            rust_type: "String".to_string(),
+            schema_type: "String".to_string(),
+            column_method: ".string().not_null()".to_string(),
+            comment: None,
+            column: None,
+            default: None,
+            nullable: false,
+            unique: false,
+        }];
+        let handlers = codegen::generate_handlers(
+            "post",
+            "posts",
+            "Post",
+            &fields,
+            "i32",
+            codegen::Prelude::Full,
+            "",
+            None,
+        );
+        assert!(handlers.contains("title: Set(input.title)"));
+
+        let renamed = rename_in_handlers(&handlers, "title", "headline").unwrap();
+        assert!(renamed.contains("headline: Set(input.headline)"));
+        assert!(renamed.contains("if let Some(val) = update.headline"));
+        assert!(renamed.contains("active.headline = Set(val);"));
+        assert!(!renamed.contains("title"));
+    }
+
+    #[test]
+    fn test_rename_in_handlers_shorthand_and_columns() {
+        let source = "fn f() {\n    let m = ActiveModel { title, ..Default::default() };\n    Entity::find().filter(Column::Title.eq(1));\n}\n";
+        let renamed = rename_in_handlers(source, "title", "headline").unwrap();
+        assert_eq!(
+            renamed,
+            "fn f() {\n    let m = ActiveModel { headline: title, ..Default::default() };\n    Entity::find().filter(Column::Headline.eq(1));\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_rename_migration() {
+        let migration = generate_rename_migration("blog_posts", "title", "headline");
+        assert!(migration.contains(".rename_column(BlogPosts::Title, BlogPosts::Headline)"));
+        assert!(migration.contains(".rename_column(BlogPosts::Headline, BlogPosts::Title)"));
+        assert!(migration.contains("enum BlogPosts {\n    Table,\n    Title,\n    Headline,\n}"));
+    }
+
+    #[test]
+    fn test_plan_fixture_project() {
+        let root = Path::new(FIXTURES);
+        let mut config = config("posts", "title", "headline");
+        config.alias = true;
+
+        let plan = plan(root, &config, "20260101_000000").unwrap();
+
+        let changed: Vec<String> = plan
+            .changes
+            .iter()
+            .map(|c| {
+                c.path
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(
+            changed,
+            vec!["src/entity.rs", "src/posts/dto.rs", "src/posts/handlers.rs"]
+        );
+        assert!(plan.notes.is_empty());
+
+        let migration = plan.migration.unwrap();
+        assert_eq!(
+            migration.module,
+            "m20260101_000000_rename_posts_title_to_headline"
+        );
+    }
+
+    #[test]
+    fn test_plan_acronym_entity_in_entities_dir() {
+        let root = Path::new(FIXTURES);
+
+        let plan = plan(
+            root,
+            &config("http_logs", "path", "route"),
+            "20260101_000000",
+        )
+        .unwrap();
+
+        assert!(plan.changes[0].path.ends_with("src/entities/http_log.rs"));
+        assert_eq!(
+            plan.migration.unwrap().module,
+            "m20260101_000000_rename_http_logs_path_to_route"
+        );
+        assert_eq!(
+            plan.notes,
+            vec![
+                "No src/http_logs/ module found; rename the field in its DTOs and handlers by hand"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_rejects_bad_names_before_reading_files() {
+        let root = Path::new(FIXTURES);
+        assert!(plan(root, &config("posts", "title", "Headline"), "0").is_err());
+        assert!(plan(root, &config("posts", "title", "title"), "0").is_err());
+        assert!(plan(root, &config("comments", "title", "headline"), "0").is_err());
+    }
+}
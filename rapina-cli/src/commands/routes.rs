@@ -1,80 +1,363 @@
 //! List all registered routes.
 
-use crate::common::urls;
+use crate::commands::openapi::openapi_path;
+use crate::common::{introspection, urls};
+use crate::output;
 use colored::Colorize;
 use serde::Deserialize;
-use std::process::Command;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
 
 #[derive(Deserialize)]
 struct RouteInfo {
     method: String,
     path: String,
     handler_name: String,
+    #[serde(default)]
+    feature_flag: Option<String>,
+    #[serde(default)]
+    consumes: Option<String>,
+    #[serde(default)]
+    produces: Option<String>,
+    #[serde(default)]
+    accept_version: Option<u32>,
+    /// Middleware that runs for this route only, after the global stack.
+    #[serde(default)]
+    middleware: Vec<String>,
 }
 
 pub struct RoutesConfig {
     pub host: String,
     pub port: u16,
+    /// Introspection token the application requires, if any.
+    pub token: Option<String>,
+    /// Cross-reference the routes with an OpenAPI spec instead of listing them.
+    pub verify_spec: Option<SpecSource>,
+}
+
+/// Where `--verify-spec` reads the OpenAPI spec from.
+pub enum SpecSource {
+    /// A spec file, such as a committed `openapi.json`.
+    File(String),
+    /// The spec served by the running application.
+    Live,
 }
 
 /// List all registered routes from the running application.
 pub fn execute(config: RoutesConfig) -> Result<(), String> {
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "  {} Fetching routes on http://{}:{}...",
         "→".cyan(),
         config.host,
         config.port
-    );
-    let routes = fetch_routes(&urls::build_routes_url(&config.host, config.port))?;
+    ));
+    let url = urls::build_routes_url(&config.host, config.port);
+    output::detail(format!("GET {}", url));
+    let routes = fetch_routes(&url, config.token.as_deref())?;
+
+    if let Some(source) = &config.verify_spec {
+        return verify_spec(&config, source, &routes);
+    }
 
     if routes.is_empty() {
-        println!("  {} No routes registered", "⚠".yellow());
+        output::warn("No routes registered");
         return Ok(());
     }
 
-    println!();
-    println!(
+    for route in &routes {
+        output::record(
+            "route",
+            serde_json::json!({
+                "method": route.method,
+                "path": route.path,
+                "handler": route.handler_name,
+                "feature_flag": route.feature_flag,
+                "consumes": route.consumes,
+                "produces": route.produces,
+                "accept_version": route.accept_version,
+                "middleware": route.middleware,
+            }),
+        );
+    }
+
+    output::blank();
+    output::info(format!(
         "  {:<6}  {:<20}  {}",
         "METHOD".bold(),
         "PATH".bold(),
         "HANDLER".bold()
-    );
-    println!("  ──────  ────────────────────  ───────────────");
+    ));
+    output::info("  ──────  ────────────────────  ───────────────");
 
     for route in &routes {
         let method_colored = match route.method.as_str() {
             "GET" => route.method.green(),
             "POST" => route.method.blue(),
             "PUT" => route.method.yellow(),
+            "PATCH" => route.method.magenta(),
             "DELETE" => route.method.red(),
             _ => route.method.normal(),
         };
-        println!(
-            "  {:<6}  {:<20}  {}",
+        let tag = |label: &str, value: &Option<String>| match value {
+            Some(value) => format!("  {}", format!("[{}: {}]", label, value).dimmed()),
+            None => String::new(),
+        };
+        let middleware = (!route.middleware.is_empty()).then(|| route.middleware.join(" → "));
+        let version = route.accept_version.map(|version| version.to_string());
+        output::info(format!(
+            "  {:<6}  {:<20}  {}{}{}{}{}{}",
             method_colored,
             route.path.cyan(),
-            route.handler_name
-        );
+            route.handler_name,
+            tag("flag", &route.feature_flag),
+            tag("consumes", &route.consumes),
+            tag("produces", &route.produces),
+            tag("version", &version),
+            tag("middleware", &middleware)
+        ));
     }
 
-    println!();
-    println!("  {} {} route(s) registered", "✓".green(), routes.len());
-    println!();
+    output::blank();
+    output::success(format!("{} route(s) registered", routes.len()));
+    output::blank();
 
     Ok(())
 }
 
+/// Compare the live routes with an OpenAPI spec, failing on any discrepancy.
+fn verify_spec(
+    config: &RoutesConfig,
+    source: &SpecSource,
+    routes: &[RouteInfo],
+) -> Result<(), String> {
+    let spec = match source {
+        SpecSource::File(path) => {
+            let contents =
+                fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse {}: {}", path, e))?
+        }
+        SpecSource::Live => {
+            let url = urls::build_openapi_url(&config.host, config.port);
+            output::detail(format!("GET {}", url));
+            fetch_spec(&url, config.token.as_deref())?
+        }
+    };
+
+    let report = compare_with_spec(routes, &spec);
+    for (method, path) in &report.undocumented {
+        output::record(
+            "undocumented_route",
+            serde_json::json!({ "method": method, "path": path }),
+        );
+    }
+    for (method, path) in &report.unrouted {
+        output::record(
+            "unrouted_operation",
+            serde_json::json!({ "method": method, "path": path }),
+        );
+    }
+    for mismatch in &report.method_mismatches {
+        output::record(
+            "method_mismatch",
+            serde_json::json!({
+                "path": mismatch.path,
+                "routes": mismatch.routes,
+                "spec": mismatch.spec,
+            }),
+        );
+    }
+
+    if report.is_empty() {
+        output::success("Routes and OpenAPI spec match");
+        output::blank();
+        return Ok(());
+    }
+
+    let section = |title: &str, lines: Vec<String>| {
+        if lines.is_empty() {
+            return;
+        }
+        output::blank();
+        output::info(format!("  {}", title.bold()));
+        for line in lines {
+            output::info(format!("    {} {}", "•".red(), line));
+        }
+    };
+    section(
+        "Routes missing from the spec:",
+        report
+            .undocumented
+            .iter()
+            .map(|(method, path)| format!("{:<6}  {}", method, path))
+            .collect(),
+    );
+    section(
+        "Spec operations with no route:",
+        report
+            .unrouted
+            .iter()
+            .map(|(method, path)| format!("{:<6}  {}", method, path))
+            .collect(),
+    );
+    section(
+        "Method mismatches:",
+        report
+            .method_mismatches
+            .iter()
+            .map(|m| {
+                format!(
+                    "{}  routes: {}  spec: {}",
+                    m.path,
+                    m.routes.join(", "),
+                    m.spec.join(", ")
+                )
+            })
+            .collect(),
+    );
+    output::blank();
+
+    Err(format!(
+        "Found {} discrepancy(ies) between the routes and the OpenAPI spec",
+        report.len()
+    ))
+}
+
+/// Differences between the registered routes and an OpenAPI spec.
+#[derive(Debug, Default, PartialEq)]
+struct SpecReport {
+    /// Routes whose path isn't in the spec, as (method, path).
+    undocumented: Vec<(String, String)>,
+    /// Spec operations whose path has no route, as (method, path).
+    unrouted: Vec<(String, String)>,
+    /// Paths present on both sides with different methods.
+    method_mismatches: Vec<MethodMismatch>,
+}
+
+#[derive(Debug, PartialEq)]
+struct MethodMismatch {
+    path: String,
+    routes: Vec<String>,
+    spec: Vec<String>,
+}
+
+impl SpecReport {
+    fn len(&self) -> usize {
+        self.undocumented.len() + self.unrouted.len() + self.method_mismatches.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Cross-reference routes with the operations of `spec`.
+///
+/// Paths are matched by shape, so `/users/:id` matches `/users/{user_id}`.
+/// Internal `/__rapina` routes are ignored on both sides.
+fn compare_with_spec(routes: &[RouteInfo], spec: &Value) -> SpecReport {
+    // shape -> (displayed path, methods)
+    let mut live: BTreeMap<String, (String, BTreeSet<String>)> = BTreeMap::new();
+    for route in routes {
+        if route.path.starts_with("/__rapina") {
+            continue;
+        }
+        live.entry(path_shape(&route.path))
+            .or_insert_with(|| (openapi_path(&route.path), BTreeSet::new()))
+            .1
+            .insert(route.method.to_uppercase());
+    }
+
+    let mut documented: BTreeMap<String, (String, BTreeSet<String>)> = BTreeMap::new();
+    if let Some(paths) = spec.get("paths").and_then(Value::as_object) {
+        for (path, item) in paths {
+            if path.starts_with("/__rapina") {
+                continue;
+            }
+            let methods = item
+                .as_object()
+                .into_iter()
+                .flat_map(|item| item.keys())
+                .filter(|key| HTTP_METHODS.contains(&key.to_lowercase().as_str()))
+                .map(|key| key.to_uppercase());
+            documented
+                .entry(path_shape(path))
+                .or_insert_with(|| (path.clone(), BTreeSet::new()))
+                .1
+                .extend(methods);
+        }
+    }
+
+    let mut report = SpecReport::default();
+    for (shape, (path, methods)) in &live {
+        match documented.get(shape) {
+            None => report
+                .undocumented
+                .extend(methods.iter().map(|method| (method.clone(), path.clone()))),
+            Some((_, spec_methods)) if spec_methods != methods => {
+                report.method_mismatches.push(MethodMismatch {
+                    path: path.clone(),
+                    routes: methods.iter().cloned().collect(),
+                    spec: spec_methods.iter().cloned().collect(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (shape, (path, methods)) in &documented {
+        if !live.contains_key(shape) {
+            report
+                .unrouted
+                .extend(methods.iter().map(|method| (method.clone(), path.clone())));
+        }
+    }
+    report
+}
+
+/// The path with its parameters blanked out, e.g. `/users/{}` for both
+/// `/users/:id` and `/users/{user_id}`.
+fn path_shape(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            let is_param = segment.starts_with(':')
+                || segment.starts_with('*')
+                || (segment.starts_with('{') && segment.ends_with('}'));
+            if is_param { "{}" } else { segment }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Fetch the OpenAPI spec from running application.
+fn fetch_spec(url: &str, token: Option<&str>) -> Result<Value, String> {
+    let output = introspection::fetch(url, token)?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to fetch the OpenAPI spec from {}. Is the server running with OpenAPI enabled? If it requires an introspection token, pass --token.",
+            url
+        ));
+    }
+
+    let body =
+        String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 response: {}", e))?;
+
+    serde_json::from_str(&body).map_err(|e| format!("Invalid JSON response: {}", e))
+}
+
 /// Fetch routes from running application.
-fn fetch_routes(url: &str) -> Result<Vec<RouteInfo>, String> {
-    let output = Command::new("curl")
-        .args(["-s", "-f", url])
-        .output()
-        .map_err(|e| format!("Failed to run curl: {}", e))?;
+fn fetch_routes(url: &str, token: Option<&str>) -> Result<Vec<RouteInfo>, String> {
+    let output = introspection::fetch(url, token)?;
 
     if !output.status.success() {
         return Err(format!(
-            "Failed to fetch routes. Is the server running on {}?",
+            "Failed to fetch routes. Is the server running on {}? If it requires an introspection token, pass --token.",
             url
         ));
     }
@@ -84,3 +367,113 @@ fn fetch_routes(url: &str) -> Result<Vec<RouteInfo>, String> {
 
     serde_json::from_str(&body).map_err(|e| format!("Invalid JSON response: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn route(method: &str, path: &str) -> RouteInfo {
+        RouteInfo {
+            method: method.to_string(),
+            path: path.to_string(),
+            handler_name: "handler".to_string(),
+            feature_flag: None,
+            consumes: None,
+            produces: None,
+            accept_version: None,
+            middleware: Vec::new(),
+        }
+    }
+
+    fn owned(method: &str, path: &str) -> (String, String) {
+        (method.to_string(), path.to_string())
+    }
+
+    #[test]
+    fn test_matching_routes_and_spec() {
+        let routes = [
+            route("GET", "/users"),
+            route("POST", "/users"),
+            route("GET", "/users/:id"),
+            route("GET", "/__rapina/routes"),
+        ];
+        let spec = json!({
+            "paths": {
+                "/users": { "get": {}, "post": {} },
+                "/users/{user_id}": { "get": {}, "parameters": [] }
+            }
+        });
+
+        assert!(compare_with_spec(&routes, &spec).is_empty());
+    }
+
+    #[test]
+    fn test_route_missing_from_spec() {
+        let routes = [route("GET", "/users"), route("DELETE", "/posts/:id")];
+        let spec = json!({ "paths": { "/users": { "get": {} } } });
+
+        let report = compare_with_spec(&routes, &spec);
+
+        assert_eq!(report.undocumented, vec![owned("DELETE", "/posts/{id}")]);
+        assert!(report.unrouted.is_empty());
+        assert!(report.method_mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_spec_operation_without_route() {
+        let routes = [route("GET", "/users")];
+        let spec = json!({
+            "paths": {
+                "/users": { "get": {} },
+                "/orders/{id}": { "get": {}, "put": {} },
+                "/__rapina/health": { "get": {} }
+            }
+        });
+
+        let report = compare_with_spec(&routes, &spec);
+
+        assert!(report.undocumented.is_empty());
+        assert_eq!(
+            report.unrouted,
+            vec![owned("GET", "/orders/{id}"), owned("PUT", "/orders/{id}")]
+        );
+    }
+
+    #[test]
+    fn test_method_mismatch() {
+        let routes = [
+            route("GET", "/users/:id"),
+            route("PUT", "/users/:id"),
+            route("GET", "/users/:id"),
+        ];
+        let spec = json!({ "paths": { "/users/{id}": { "get": {}, "patch": {} } } });
+
+        let report = compare_with_spec(&routes, &spec);
+
+        assert_eq!(
+            report.method_mismatches,
+            vec![MethodMismatch {
+                path: "/users/{id}".to_string(),
+                routes: vec!["GET".to_string(), "PUT".to_string()],
+                spec: vec!["GET".to_string(), "PATCH".to_string()],
+            }]
+        );
+        assert_eq!(report.len(), 1);
+    }
+
+    #[test]
+    fn test_spec_without_paths() {
+        let report = compare_with_spec(&[route("GET", "/")], &json!({}));
+
+        assert_eq!(report.undocumented, vec![owned("GET", "/")]);
+    }
+
+    #[test]
+    fn test_path_shape() {
+        assert_eq!(path_shape("/users/:id/posts"), "/users/{}/posts");
+        assert_eq!(path_shape("/users/{user_id}/posts"), "/users/{}/posts");
+        assert_eq!(path_shape("/files/*rest"), "/files/{}");
+        assert_eq!(path_shape("/health"), "/health");
+    }
+}
@@ -0,0 +1,552 @@
+//! Entity-relationship diagrams for `schema!` entities.
+//!
+//! `rapina schema diagram` reads the `schema!` blocks in `src/entity.rs`,
+//! `src/entity/*.rs` and `src/entities/*.rs` and renders every entity with
+//! its columns, plus the relationships between them, as a Mermaid
+//! `erDiagram` or a Graphviz graph.
+//!
+//! Relationships come from the field types: a field typed as another entity
+//! is a `belongs_to`, a `Vec<T>` field is a `has_many`, and two entities
+//! holding a `Vec` of each other are a many-to-many.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use syn::parse::Parser;
+use syn::spanned::Spanned;
+
+use super::entities::{self, FieldKind};
+use crate::output;
+
+pub struct DiagramConfig {
+    /// `mermaid` or `dot`.
+    pub format: String,
+    /// File to write; stdout when `None`.
+    pub out: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Mermaid,
+    Dot,
+}
+
+impl Format {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "mermaid" => Ok(Format::Mermaid),
+            "dot" => Ok(Format::Dot),
+            other => Err(format!(
+                "Unknown diagram format '{}'. Expected 'mermaid' or 'dot'",
+                other
+            )),
+        }
+    }
+}
+
+pub fn diagram(config: DiagramConfig) -> Result<(), String> {
+    let format = Format::parse(&config.format)?;
+
+    let mut entities = Vec::new();
+    for path in entities::entity_files(Path::new("")) {
+        let Ok(source) = fs::read_to_string(&path) else {
+            continue;
+        };
+        entities.extend(parse_entities(&source).map_err(|e| format!("{}:{}", path.display(), e))?);
+    }
+    if entities.is_empty() {
+        return Err("No schema! entities found in src/entity.rs".to_string());
+    }
+
+    let rendered = match format {
+        Format::Mermaid => render_mermaid(&entities),
+        Format::Dot => render_dot(&entities),
+    };
+
+    match config.out {
+        Some(path) => {
+            fs::write(&path, rendered).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            output::created(&path);
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// schema! blocks
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    name: String,
+    /// The `schema!` type, or `i32` for a `belongs_to` key.
+    ty: String,
+    optional: bool,
+    primary_key: bool,
+    unique: bool,
+    /// The entity a `belongs_to` column points at.
+    references: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entity {
+    name: String,
+    table: String,
+    columns: Vec<Column>,
+    /// `Vec<T>` fields, as (field, entity).
+    has_many: Vec<(String, String)>,
+}
+
+impl Entity {
+    fn belongs_to(&self, parent: &str) -> bool {
+        self.columns
+            .iter()
+            .any(|column| column.references.as_deref() == Some(parent))
+    }
+
+    fn has_many_of(&self, child: &str) -> bool {
+        self.has_many.iter().any(|(_, target)| target == child)
+    }
+}
+
+/// Parses every `schema!` block in a source file. Unlike the other commands,
+/// a block that doesn't parse is an error, reported as `line: message`.
+fn parse_entities(source: &str) -> Result<Vec<Entity>, String> {
+    let file = syn::parse_file(source).map_err(located)?;
+
+    let mut parsed = Vec::new();
+    for mac in entities::schema_blocks(&file) {
+        let entities = entities::parse_schema
+            .parse2(mac.tokens.clone())
+            .map_err(|e| located(fallback_span(e, mac.span())))?;
+        parsed.extend(entities);
+    }
+
+    // Foreign keys take the type of the id they reference
+    let id_types: HashMap<&str, &str> = parsed
+        .iter()
+        .filter(|e| e.primary_key.is_none())
+        .map(|e| (e.name.as_str(), e.id.as_str()))
+        .collect();
+    Ok(parsed
+        .iter()
+        .map(|entity| diagram_entity(entity, &id_types))
+        .collect())
+}
+
+/// Errors at the end of a block point at the call site, which has no real
+/// line. Point them at the `schema!` invocation instead.
+fn fallback_span(error: syn::Error, invocation: proc_macro2::Span) -> syn::Error {
+    if error.span().start().line <= 1 {
+        syn::Error::new(invocation, error)
+    } else {
+        error
+    }
+}
+
+fn located(error: syn::Error) -> String {
+    format!(
+        "{}: invalid schema! block: {}",
+        error.span().start().line,
+        error
+    )
+}
+
+/// The columns of a parsed entity, in table order.
+fn diagram_entity(entity: &entities::Entity, id_types: &HashMap<&str, &str>) -> Entity {
+    let mut columns = Vec::new();
+    if entity.primary_key.is_none() {
+        columns.push(Column {
+            name: "id".to_string(),
+            ty: entity.id.as_str().to_string(),
+            optional: false,
+            primary_key: true,
+            unique: false,
+            references: None,
+        });
+    }
+
+    for field in &entity.fields {
+        let (ty, references) = match &field.kind {
+            FieldKind::Scalar(scalar) => (scalar.as_str().to_string(), None),
+            FieldKind::Array(scalar) => (format!("{}[]", scalar.as_str()), None),
+            FieldKind::BelongsTo(parent) => (
+                id_types.get(parent.as_str()).unwrap_or(&"i32").to_string(),
+                Some(parent.clone()),
+            ),
+        };
+        columns.push(Column {
+            name: field.column.clone(),
+            ty,
+            optional: field.optional,
+            primary_key: entity.is_primary_key(field),
+            unique: field.unique,
+            references,
+        });
+    }
+
+    for (column, enabled) in [
+        ("created_at", entity.created_at),
+        ("updated_at", entity.updated_at),
+    ] {
+        if enabled {
+            columns.push(Column {
+                name: column.to_string(),
+                ty: "DateTime".to_string(),
+                optional: false,
+                primary_key: false,
+                unique: false,
+                references: None,
+            });
+        }
+    }
+
+    Entity {
+        name: entity.name.clone(),
+        table: entity.table.clone(),
+        columns,
+        has_many: entity.has_many.clone(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Relationships
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cardinality {
+    /// One parent (exactly one, or zero or one when optional) to many children.
+    OneToMany {
+        optional: bool,
+    },
+    ManyToMany,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Relationship {
+    from: String,
+    to: String,
+    label: String,
+    cardinality: Cardinality,
+}
+
+/// Every relationship between the entities, each listed once.
+///
+/// A `belongs_to` and the matching `has_many` on the parent describe the same
+/// relationship, so the `has_many` is only drawn when the child has no
+/// `belongs_to` back. Two `Vec` fields pointing at each other are one
+/// many-to-many.
+fn relationships(entities: &[Entity]) -> Vec<Relationship> {
+    let find = |name: &str| entities.iter().find(|entity| entity.name == name);
+    let mut relationships = Vec::new();
+
+    for entity in entities {
+        for column in &entity.columns {
+            let Some(parent) = &column.references else {
+                continue;
+            };
+            relationships.push(Relationship {
+                from: parent.clone(),
+                to: entity.name.clone(),
+                label: column
+                    .name
+                    .strip_suffix("_id")
+                    .unwrap_or(&column.name)
+                    .to_string(),
+                cardinality: Cardinality::OneToMany {
+                    optional: column.optional,
+                },
+            });
+        }
+
+        for (field, target) in &entity.has_many {
+            let other = find(target);
+            if other.is_some_and(|other| other.belongs_to(&entity.name)) {
+                continue;
+            }
+            if other.is_some_and(|other| other.has_many_of(&entity.name)) {
+                let seen = relationships.iter().any(|r: &Relationship| {
+                    r.cardinality == Cardinality::ManyToMany
+                        && r.from == *target
+                        && r.to == entity.name
+                });
+                if !seen {
+                    relationships.push(Relationship {
+                        from: entity.name.clone(),
+                        to: target.clone(),
+                        label: field.clone(),
+                        cardinality: Cardinality::ManyToMany,
+                    });
+                }
+                continue;
+            }
+            relationships.push(Relationship {
+                from: entity.name.clone(),
+                to: target.clone(),
+                label: field.clone(),
+                cardinality: Cardinality::OneToMany { optional: false },
+            });
+        }
+    }
+
+    relationships
+}
+
+// ---------------------------------------------------------------------------
+// Rendering
+// ---------------------------------------------------------------------------
+
+fn column_keys(column: &Column) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+    if column.primary_key {
+        keys.push("PK");
+    }
+    if column.references.is_some() {
+        keys.push("FK");
+    }
+    if column.unique {
+        keys.push("UK");
+    }
+    keys
+}
+
+fn render_mermaid(entities: &[Entity]) -> String {
+    let mut out = String::from("erDiagram\n");
+
+    for entity in entities {
+        out.push_str(&format!("    {} {{\n", entity.name));
+        for column in &entity.columns {
+            out.push_str(&format!("        {} {}", column.ty, column.name));
+            let keys = column_keys(column);
+            if !keys.is_empty() {
+                out.push_str(&format!(" {}", keys.join(", ")));
+            }
+            if column.optional {
+                out.push_str(" \"nullable\"");
+            }
+            out.push('\n');
+        }
+        out.push_str("    }\n");
+    }
+
+    for relationship in relationships(entities) {
+        let connector = match relationship.cardinality {
+            Cardinality::OneToMany { optional: false } => "||--o{",
+            Cardinality::OneToMany { optional: true } => "|o--o{",
+            Cardinality::ManyToMany => "}o--o{",
+        };
+        out.push_str(&format!(
+            "    {} {} {} : \"{}\"\n",
+            relationship.from, connector, relationship.to, relationship.label
+        ));
+    }
+
+    out
+}
+
+fn render_dot(entities: &[Entity]) -> String {
+    let mut out = String::from("digraph schema {\n");
+    out.push_str("    rankdir=LR;\n");
+    out.push_str("    node [shape=plaintext];\n");
+
+    for entity in entities {
+        out.push_str(&format!("\n    {} [label=<\n", entity.name));
+        out.push_str("        <table border=\"0\" cellborder=\"1\" cellspacing=\"0\">\n");
+        out.push_str(&format!(
+            "            <tr><td colspan=\"3\"><b>{}</b> ({})</td></tr>\n",
+            entity.name, entity.table
+        ));
+        for column in &entity.columns {
+            let ty = if column.optional {
+                format!("Option&lt;{}&gt;", column.ty)
+            } else {
+                column.ty.clone()
+            };
+            out.push_str(&format!(
+                "            <tr><td align=\"left\">{}</td><td align=\"left\">{}</td><td>{}</td></tr>\n",
+                column.name,
+                ty,
+                column_keys(column).join(", ")
+            ));
+        }
+        out.push_str("        </table>\n    >];\n");
+    }
+
+    let relationships = relationships(entities);
+    if !relationships.is_empty() {
+        out.push('\n');
+    }
+    for relationship in relationships {
+        let cardinality = match relationship.cardinality {
+            Cardinality::OneToMany { optional: false } => "1:N",
+            Cardinality::OneToMany { optional: true } => "0..1:N",
+            Cardinality::ManyToMany => "N:M",
+        };
+        out.push_str(&format!(
+            "    {} -> {} [label=\"{} ({})\"];\n",
+            relationship.from, relationship.to, relationship.label, cardinality
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/schema/src/entity.rs"
+    ));
+
+    #[test]
+    fn test_mermaid_snapshot() {
+        let entities = parse_entities(FIXTURE).unwrap();
+        assert_eq!(
+            render_mermaid(&entities),
+            r#"erDiagram
+    User {
+        i32 id PK
+        String email UK
+        String name
+        DateTime created_at
+        DateTime updated_at
+    }
+    Post {
+        i32 id PK
+        String title
+        Text body
+        i32 author_id FK
+        i32 editor_id FK "nullable"
+        DateTime created_at
+        DateTime updated_at
+    }
+    Tag {
+        String code PK
+        String label UK
+    }
+    Comment {
+        i32 id PK
+        Text body
+        i32 post_id FK
+        DateTime created_at
+    }
+    User ||--o{ Post : "author"
+    User |o--o{ Post : "editor"
+    Post }o--o{ Tag : "tags"
+    Post ||--o{ Comment : "post"
+"#
+        );
+    }
+
+    #[test]
+    fn test_dot_snapshot() {
+        let entities = parse_entities(FIXTURE).unwrap();
+        let dot = render_dot(&entities);
+        assert!(dot.starts_with("digraph schema {\n    rankdir=LR;\n"));
+        assert!(dot.contains(
+            "            <tr><td colspan=\"3\"><b>Tag</b> (tags)</td></tr>\n            \
+             <tr><td align=\"left\">code</td><td align=\"left\">String</td><td>PK</td></tr>\n            \
+             <tr><td align=\"left\">label</td><td align=\"left\">String</td><td>UK</td></tr>\n"
+        ));
+        assert!(dot.contains(
+            "<tr><td align=\"left\">editor_id</td><td align=\"left\">Option&lt;i32&gt;</td><td>FK</td></tr>"
+        ));
+        assert!(dot.ends_with(
+            "\n    User -> Post [label=\"author (1:N)\"];\n    \
+             User -> Post [label=\"editor (0..1:N)\"];\n    \
+             Post -> Tag [label=\"tags (N:M)\"];\n    \
+             Post -> Comment [label=\"post (1:N)\"];\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_has_many_without_belongs_to_is_drawn() {
+        let entities = parse_entities(
+            "schema! {\n    Team {\n        members: Vec<Member>,\n    }\n    Member {\n        name: String,\n    }\n}\n",
+        )
+        .unwrap();
+        let relationships = relationships(&entities);
+        assert_eq!(relationships.len(), 1);
+        assert_eq!(relationships[0].from, "Team");
+        assert_eq!(relationships[0].to, "Member");
+        assert_eq!(relationships[0].label, "members");
+    }
+
+    #[test]
+    fn test_table_name_and_column_attributes() {
+        let entities = parse_entities(
+            "schema! {\n    #[table_name = \"people\"]\n    #[timestamps(none)]\n    Person {\n        #[column = \"full_name\"]\n        name: String,\n    }\n}\n",
+        )
+        .unwrap();
+        assert_eq!(entities[0].table, "people");
+        let columns: Vec<&str> = entities[0]
+            .columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(columns, ["id", "full_name"]);
+    }
+
+    #[test]
+    fn test_scalar_vec_is_array_column() {
+        let entities = parse_entities(
+            "schema! {\n    #[timestamps(none)]\n    Post {\n        tags: Vec<String>,\n        scores: Option<Vec<i32>>,\n    }\n}\n",
+        )
+        .unwrap();
+        assert!(entities[0].has_many.is_empty());
+        let columns: Vec<(&str, &str, bool)> = entities[0]
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.ty.as_str(), c.optional))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("id", "i32", false),
+                ("tags", "String[]", false),
+                ("scores", "i32[]", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uuid_id_and_foreign_key() {
+        let entities = parse_entities(
+            "schema! {\n    #[id(Uuid)]\n    #[timestamps(none)]\n    Account {\n        email: String,\n    }\n\n    #[timestamps(none)]\n    Note {\n        owner: Option<Account>,\n    }\n}\n",
+        )
+        .unwrap();
+        let columns: Vec<(&str, &str, bool)> = entities
+            .iter()
+            .flat_map(|e| &e.columns)
+            .map(|c| (c.name.as_str(), c.ty.as_str(), c.optional))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("id", "Uuid", false),
+                ("email", "String", false),
+                ("id", "i32", false),
+                ("owner_id", "Uuid", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_block_reports_line() {
+        let source =
+            "use rapina::prelude::*;\n\nschema! {\n    User {\n        name String,\n    }\n}\n";
+        let err = parse_entities(source).unwrap_err();
+        assert!(err.starts_with("5: invalid schema! block:"), "{}", err);
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        assert_eq!(Format::parse("dot"), Ok(Format::Dot));
+        assert!(Format::parse("svg").unwrap_err().contains("'svg'"));
+    }
+}
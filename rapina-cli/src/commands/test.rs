@@ -11,6 +11,7 @@ use std::sync::{Arc, mpsc};
 use std::time::Duration;
 
 use crate::commands::verify_rapina_project;
+use crate::output;
 
 /// Configuration for the test command.
 #[derive(Default)]
@@ -59,12 +60,12 @@ fn check_coverage_tool() -> Result<(), String> {
 
 /// Run tests once.
 fn run_tests(config: &TestConfig) -> Result<(), String> {
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "{} Running tests...",
         "INFO".custom_color(colors::blue()).bold()
-    );
-    println!();
+    ));
+    output::blank();
 
     let (cmd, args) = build_test_command(config);
 
@@ -86,7 +87,8 @@ fn run_tests(config: &TestConfig) -> Result<(), String> {
         process_test_line(&line, &mut summary);
     }
 
-    // Process stderr (compilation errors, etc.)
+    // Process stderr (compilation errors, etc.). Passed through as is, since
+    // it only goes to stderr and carries the errors even with --quiet.
     let stderr_reader = BufReader::new(stderr);
     for line in stderr_reader.lines().map_while(Result::ok) {
         eprintln!("{}", line);
@@ -96,7 +98,7 @@ fn run_tests(config: &TestConfig) -> Result<(), String> {
         .wait()
         .map_err(|e| format!("Failed to wait for tests: {}", e))?;
 
-    println!();
+    output::blank();
     print_summary(&summary, status.success());
 
     if status.success() {
@@ -115,11 +117,11 @@ fn run_watch_mode(config: &TestConfig) -> Result<(), String> {
     })
     .map_err(|e| format!("Failed to set Ctrl+C handler: {}", e))?;
 
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "{} Watch mode enabled. Press Ctrl+C to stop.",
         "INFO".custom_color(colors::blue()).bold()
-    );
+    ));
 
     // Initial run
     let _ = run_tests(config);
@@ -157,35 +159,35 @@ fn run_watch_mode(config: &TestConfig) -> Result<(), String> {
             .map_err(|e| format!("Failed to watch tests directory: {}", e))?;
     }
 
-    println!(
+    output::info(format!(
         "{} Watching for changes...",
         "INFO".custom_color(colors::blue()).bold()
-    );
-    println!();
+    ));
+    output::blank();
 
     while running.load(Ordering::SeqCst) {
         if rx.recv_timeout(Duration::from_millis(100)).is_ok() {
-            println!();
-            println!(
+            output::blank();
+            output::info(format!(
                 "{} Change detected, re-running tests...",
                 "INFO".custom_color(colors::yellow()).bold()
-            );
+            ));
 
             let _ = run_tests(config);
 
-            println!(
+            output::info(format!(
                 "{} Watching for changes...",
                 "INFO".custom_color(colors::blue()).bold()
-            );
-            println!();
+            ));
+            output::blank();
         }
     }
 
-    println!();
-    println!(
+    output::blank();
+    output::info(format!(
         "{} Stopped watching.",
         "INFO".custom_color(colors::blue()).bold()
-    );
+    ));
 
     Ok(())
 }
@@ -205,8 +207,12 @@ fn build_test_command(config: &TestConfig) -> (String, Vec<String>) {
         args.push(filter.clone());
     }
 
-    // Add color output
-    args.push("--color=always".to_string());
+    // Add color output, unless --no-color or --log-format json turned it off
+    if colored::control::SHOULD_COLORIZE.should_colorize() {
+        args.push("--color=always".to_string());
+    } else {
+        args.push("--color=never".to_string());
+    }
 
     ("cargo".to_string(), args)
 }
@@ -218,36 +224,47 @@ fn process_test_line(line: &str, summary: &mut TestSummary) {
         // Already captured in summary parsing
     } else if line.contains(" ... ok") {
         summary.passed += 1;
-        println!(
+        record_test(line, "passed");
+        output::info(format!(
             "  {} {}",
             "✓".custom_color(colors::green()),
             extract_test_name(line).custom_color(colors::subtext())
-        );
+        ));
     } else if line.contains(" ... FAILED") {
         summary.failed += 1;
-        println!(
+        record_test(line, "failed");
+        output::info(format!(
             "  {} {}",
             "✗".custom_color(colors::red()),
             extract_test_name(line).custom_color(colors::red())
-        );
+        ));
     } else if line.contains(" ... ignored") {
         summary.ignored += 1;
-        println!(
+        record_test(line, "ignored");
+        output::info(format!(
             "  {} {}",
             "○".custom_color(colors::yellow()),
             extract_test_name(line).custom_color(colors::subtext())
-        );
+        ));
     } else if line.starts_with("running ")
         || line.contains("Compiling")
         || line.contains("Finished")
     {
-        println!("{}", line.custom_color(colors::subtext()));
+        output::detail(line);
     } else if !line.trim().is_empty() && !line.starts_with("test ") {
         // Print other relevant output (doc tests header, etc.)
-        println!("{}", line);
+        output::info(line);
     }
 }
 
+/// Writes a test result as a JSON event.
+fn record_test(line: &str, result: &str) {
+    output::record(
+        "test",
+        serde_json::json!({ "name": extract_test_name(line), "result": result }),
+    );
+}
+
 /// Extract test name from a test output line.
 fn extract_test_name(line: &str) -> &str {
     line.strip_prefix("test ")
@@ -260,24 +277,34 @@ fn extract_test_name(line: &str) -> &str {
 fn print_summary(summary: &TestSummary, success: bool) {
     let total = summary.passed + summary.failed + summary.ignored;
 
-    println!("{}", "─".repeat(50).custom_color(colors::subtext()));
+    output::record(
+        "summary",
+        serde_json::json!({
+            "success": success,
+            "passed": summary.passed,
+            "failed": summary.failed,
+            "ignored": summary.ignored,
+        }),
+    );
+
+    output::info("─".repeat(50).custom_color(colors::subtext()));
 
     if success {
-        println!(
+        output::info(format!(
             "{} {} passed, {} failed, {} ignored",
             "PASS".custom_color(colors::green()).bold(),
             summary.passed.to_string().custom_color(colors::green()),
             summary.failed.to_string().custom_color(colors::subtext()),
             summary.ignored.to_string().custom_color(colors::yellow()),
-        );
+        ));
     } else {
-        println!(
+        output::info(format!(
             "{} {} passed, {} failed, {} ignored",
             "FAIL".custom_color(colors::red()).bold(),
             summary.passed.to_string().custom_color(colors::green()),
             summary.failed.to_string().custom_color(colors::red()),
             summary.ignored.to_string().custom_color(colors::yellow()),
-        );
+        ));
     }
 
     if total > 0 {
@@ -292,8 +319,8 @@ fn print_summary(summary: &TestSummary, success: bool) {
             "█".repeat(failed_width).custom_color(colors::red()),
             "░".repeat(ignored_width).custom_color(colors::subtext()),
         );
-        println!("{}", bar);
+        output::info(bar);
     }
 
-    println!();
+    output::blank();
 }
@@ -0,0 +1,1324 @@
+//! Moving a resource's rows between the database and a file.
+//!
+//! `rapina db export posts --out posts.csv` writes the rows of a `schema!`
+//! entity as CSV or NDJSON, a page at a time, and `rapina db import-data
+//! posts posts.csv` reads such a file back. Columns and their types come from
+//! the entity's `schema!` block, as for `db fake`, so neither command needs
+//! the application binary. Imported values are checked against those types
+//! before anything is written, and rows go in batches inside a single
+//! transaction.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde_json::{Value as JsonValue, json};
+
+use super::console::Database;
+use super::entities::{Entity, FieldKind, Scalar};
+use super::fake::{self, BATCH_SIZE, TIMESTAMP_FORMAT, Value};
+use crate::output;
+
+/// Rows fetched per query while exporting, and rows between progress lines
+/// while importing.
+const PAGE_SIZE: u64 = 1000;
+
+pub struct ExportConfig {
+    pub url: String,
+    pub resource: String,
+    pub out: String,
+    pub format: Option<String>,
+    /// SQL condition rows must meet, from `--where`.
+    pub filter: Option<String>,
+    pub limit: Option<u64>,
+}
+
+pub struct ImportConfig {
+    pub url: String,
+    pub resource: String,
+    pub file: String,
+    pub format: Option<String>,
+    pub on_conflict: Option<String>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Ndjson,
+}
+
+impl Format {
+    /// `--format` when given, otherwise the file's extension. Anything that
+    /// isn't `.ndjson` or `.jsonl` is read as CSV.
+    fn resolve(explicit: Option<&str>, path: &str) -> Result<Self, String> {
+        match explicit {
+            Some("csv") => Ok(Format::Csv),
+            Some("ndjson" | "jsonl") => Ok(Format::Ndjson),
+            Some(other) => Err(format!(
+                "Unknown format '{}'. Expected csv or ndjson",
+                other
+            )),
+            None => match Path::new(path).extension().and_then(|e| e.to_str()) {
+                Some("ndjson" | "jsonl") => Ok(Format::Ndjson),
+                _ => Ok(Format::Csv),
+            },
+        }
+    }
+}
+
+/// What an import does with a row whose key is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnConflict {
+    /// No `--on-conflict`: the insert fails and the import rolls back.
+    Fail,
+    Skip,
+    Update,
+}
+
+impl OnConflict {
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value {
+            None => Ok(OnConflict::Fail),
+            Some("skip") => Ok(OnConflict::Skip),
+            Some("update") => Ok(OnConflict::Update),
+            Some(other) => Err(format!(
+                "Unknown conflict policy '{}'. Expected skip or update",
+                other
+            )),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Columns
+// ---------------------------------------------------------------------------
+
+/// What an import does when a row leaves a column out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Missing {
+    Required,
+    Null,
+    /// The database assigns it, like the default integer `id`.
+    Database,
+    /// Set to the import's start time, like the app does for timestamps.
+    Now,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    name: String,
+    scalar: Scalar,
+    /// Part of the primary key.
+    key: bool,
+    missing: Missing,
+}
+
+/// The entity's columns in table order: the default `id`, the fields, then
+/// the timestamps. Array columns aren't transferred.
+fn columns(entities: &[Entity], entity: &Entity) -> Vec<Column> {
+    let mut columns = Vec::new();
+
+    if entity.primary_key.is_none() {
+        columns.push(Column {
+            name: "id".to_string(),
+            scalar: entity.id,
+            key: true,
+            missing: match entity.id {
+                Scalar::I32 | Scalar::I64 => Missing::Database,
+                _ => Missing::Required,
+            },
+        });
+    }
+
+    for field in &entity.fields {
+        let scalar = match &field.kind {
+            FieldKind::Scalar(scalar) => *scalar,
+            FieldKind::BelongsTo(parent) => key_scalar(entities, parent),
+            FieldKind::Array(_) => continue,
+        };
+        columns.push(Column {
+            name: field.column.clone(),
+            scalar,
+            key: entity.is_primary_key(field),
+            missing: if field.optional {
+                Missing::Null
+            } else {
+                Missing::Required
+            },
+        });
+    }
+
+    for (present, name) in [
+        (entity.created_at, "created_at"),
+        (entity.updated_at, "updated_at"),
+    ] {
+        if present {
+            columns.push(Column {
+                name: name.to_string(),
+                scalar: Scalar::DateTime,
+                key: false,
+                missing: Missing::Now,
+            });
+        }
+    }
+
+    columns
+}
+
+/// The type of the key a `belongs_to` column stores. Undeclared parents are
+/// assumed to have the default integer `id`.
+fn key_scalar(entities: &[Entity], parent: &str) -> Scalar {
+    let Some(parent) = entities.iter().find(|e| e.name == parent) else {
+        return Scalar::I32;
+    };
+    match parent.primary_key.as_deref() {
+        None => parent.id,
+        Some([column]) => parent
+            .fields
+            .iter()
+            .find(|f| &f.name == column)
+            .and_then(|f| match f.kind {
+                FieldKind::Scalar(scalar) => Some(scalar),
+                FieldKind::BelongsTo(_) | FieldKind::Array(_) => None,
+            })
+            .unwrap_or(Scalar::I32),
+        Some(_) => Scalar::I32,
+    }
+}
+
+fn column_names(columns: &[Column]) -> String {
+    let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+    names.join(", ")
+}
+
+// ---------------------------------------------------------------------------
+// Formats
+// ---------------------------------------------------------------------------
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "t" | "1" => Some(true),
+        "false" | "f" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Quotes a CSV field when it needs it. The empty string is quoted too, so
+/// it reads back differently from NULL, which is written as nothing at all.
+fn csv_field(value: &str) -> String {
+    if value.is_empty() || value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(columns: &[Column], row: &[Option<String>]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(row)
+        .map(|(column, cell)| match cell {
+            None => String::new(),
+            Some(value) if column.scalar == Scalar::Bool => match parse_bool(value) {
+                Some(b) => b.to_string(),
+                None => csv_field(value),
+            },
+            Some(value) => csv_field(value),
+        })
+        .collect();
+    fields.join(",")
+}
+
+/// A rendered cell as JSON, typed by its column: numbers and booleans as
+/// such, `Json` columns as the document itself, everything else as a string.
+fn json_cell(scalar: Scalar, cell: Option<&str>) -> JsonValue {
+    let Some(value) = cell else {
+        return JsonValue::Null;
+    };
+    let typed = match scalar {
+        Scalar::I32 | Scalar::I64 => value.parse::<i64>().ok().map(JsonValue::from),
+        Scalar::F32 | Scalar::F64 => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(JsonValue::Number),
+        Scalar::Bool => parse_bool(value).map(JsonValue::Bool),
+        Scalar::Json => serde_json::from_str(value).ok(),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| JsonValue::String(value.to_string()))
+}
+
+/// One NDJSON line. Keys follow the column order rather than going through
+/// a map, which would sort them.
+fn ndjson_row(columns: &[Column], row: &[Option<String>]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(row)
+        .map(|(column, cell)| {
+            format!(
+                "{}:{}",
+                JsonValue::String(column.name.clone()),
+                json_cell(column.scalar, cell.as_deref())
+            )
+        })
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// A CSV record's fields and the line it starts on.
+type Record = (usize, Vec<Option<String>>);
+
+/// Reads CSV records as RFC 4180 describes them, including quoted fields
+/// that span lines. An empty unquoted field is NULL; `""` is an empty string.
+struct CsvReader<R> {
+    input: R,
+    line: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    fn new(input: R) -> Self {
+        Self { input, line: 0 }
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, String> {
+        let read = self
+            .input
+            .read_line(buf)
+            .map_err(|e| format!("Failed to read input: {}", e))?;
+        if read > 0 {
+            self.line += 1;
+        }
+        Ok(read)
+    }
+
+    /// The next record. Blank lines are skipped.
+    fn record(&mut self) -> Result<Option<Record>, String> {
+        let mut buf = String::new();
+        loop {
+            buf.clear();
+            if self.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            if !buf.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+        let start = self.line;
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut pos = 0;
+
+        while let Some(c) = buf[pos..].chars().next() {
+            pos += c.len_utf8();
+            if in_quotes {
+                if c == '"' {
+                    if buf[pos..].starts_with('"') {
+                        field.push('"');
+                        pos += 1;
+                    } else {
+                        in_quotes = false;
+                    }
+                } else {
+                    field.push(c);
+                }
+                // The quoted field goes on past the end of the line
+                if in_quotes && pos == buf.len() && self.read_line(&mut buf)? == 0 {
+                    return Err(format!("line {}: unterminated quoted field", start));
+                }
+            } else {
+                match c {
+                    '"' if field.is_empty() && !quoted => {
+                        quoted = true;
+                        in_quotes = true;
+                    }
+                    ',' => {
+                        fields.push(csv_cell(std::mem::take(&mut field), quoted));
+                        quoted = false;
+                    }
+                    '\r' | '\n' => break,
+                    _ => field.push(c),
+                }
+            }
+        }
+        fields.push(csv_cell(field, quoted));
+
+        Ok(Some((start, fields)))
+    }
+}
+
+fn csv_cell(field: String, quoted: bool) -> Option<String> {
+    if field.is_empty() && !quoted {
+        None
+    } else {
+        Some(field)
+    }
+}
+
+/// A row read from a file, one slot per entity column: `None` where the
+/// file doesn't mention the column, `Some(None)` where it holds NULL.
+type Cells = Vec<Option<Option<String>>>;
+
+/// Reads rows from a CSV or NDJSON file, mapping them onto the columns.
+enum RowReader<'a, R> {
+    Csv {
+        reader: CsvReader<R>,
+        /// Entity column of each CSV field, from the header.
+        positions: Vec<usize>,
+        columns: &'a [Column],
+    },
+    Ndjson {
+        input: R,
+        line: usize,
+        columns: &'a [Column],
+    },
+}
+
+impl<'a, R: BufRead> RowReader<'a, R> {
+    fn new(format: Format, input: R, table: &str, columns: &'a [Column]) -> Result<Self, String> {
+        match format {
+            Format::Ndjson => Ok(RowReader::Ndjson {
+                input,
+                line: 0,
+                columns,
+            }),
+            Format::Csv => {
+                let mut reader = CsvReader::new(input);
+                let Some((line, header)) = reader.record()? else {
+                    return Err("The file is empty; expected a header row".to_string());
+                };
+                let mut positions = Vec::with_capacity(header.len());
+                for name in header {
+                    let name = name.unwrap_or_default();
+                    let index = column_index(columns, table, &name, line)?;
+                    if positions.contains(&index) {
+                        return Err(format!("line {}: column '{}' appears twice", line, name));
+                    }
+                    positions.push(index);
+                }
+                Ok(RowReader::Csv {
+                    reader,
+                    positions,
+                    columns,
+                })
+            }
+        }
+    }
+
+    fn next(&mut self, table: &str) -> Result<Option<(usize, Cells)>, String> {
+        match self {
+            RowReader::Csv {
+                reader,
+                positions,
+                columns,
+            } => {
+                let Some((line, fields)) = reader.record()? else {
+                    return Ok(None);
+                };
+                if fields.len() != positions.len() {
+                    return Err(format!(
+                        "line {}: expected {} field(s), found {}",
+                        line,
+                        positions.len(),
+                        fields.len()
+                    ));
+                }
+                let mut cells = vec![None; columns.len()];
+                for (&index, field) in positions.iter().zip(fields) {
+                    cells[index] = Some(field);
+                }
+                Ok(Some((line, cells)))
+            }
+            RowReader::Ndjson {
+                input,
+                line,
+                columns,
+            } => {
+                let mut buf = String::new();
+                loop {
+                    buf.clear();
+                    if input
+                        .read_line(&mut buf)
+                        .map_err(|e| format!("Failed to read input: {}", e))?
+                        == 0
+                    {
+                        return Ok(None);
+                    }
+                    *line += 1;
+                    if !buf.trim().is_empty() {
+                        break;
+                    }
+                }
+                ndjson_cells(columns, table, &buf, *line).map(|cells| Some((*line, cells)))
+            }
+        }
+    }
+}
+
+fn ndjson_cells(columns: &[Column], table: &str, text: &str, line: usize) -> Result<Cells, String> {
+    let value: JsonValue =
+        serde_json::from_str(text).map_err(|e| format!("line {}: invalid JSON: {}", line, e))?;
+    let JsonValue::Object(object) = value else {
+        return Err(format!("line {}: expected a JSON object", line));
+    };
+
+    let mut cells = vec![None; columns.len()];
+    for (key, value) in object {
+        let index = column_index(columns, table, &key, line)?;
+        cells[index] = Some(match value {
+            JsonValue::Null => None,
+            JsonValue::String(s) if columns[index].scalar != Scalar::Json => Some(s),
+            other => Some(other.to_string()),
+        });
+    }
+    Ok(cells)
+}
+
+fn column_index(columns: &[Column], table: &str, name: &str, line: usize) -> Result<usize, String> {
+    columns.iter().position(|c| c.name == name).ok_or_else(|| {
+        format!(
+            "line {}: unknown column '{}'. {} has: {}",
+            line,
+            name,
+            table,
+            column_names(columns)
+        )
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+fn is_uuid(value: &str) -> bool {
+    value.len() == 36
+        && value.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Accepts the shapes databases print timestamps in: RFC 3339, and
+/// `YYYY-MM-DD HH:MM:SS` with optional fractions and offset or `UTC`.
+fn is_timestamp(value: &str) -> bool {
+    let value = value.strip_suffix(" UTC").unwrap_or(value);
+    DateTime::parse_from_rfc3339(value).is_ok()
+        || DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f %:z").is_ok()
+        || DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z").is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+}
+
+/// Checks a value from the file against its column type.
+fn parse_value(scalar: Scalar, text: &str) -> Result<Value, &'static str> {
+    let trimmed = text.trim();
+    match scalar {
+        Scalar::String | Scalar::Text => Ok(Value::Text(text.to_string())),
+        Scalar::I32 => trimmed
+            .parse::<i32>()
+            .map(|n| Value::Int(n.into()))
+            .map_err(|_| "expected a 32-bit integer"),
+        Scalar::I64 => trimmed
+            .parse::<i64>()
+            .map(Value::Int)
+            .map_err(|_| "expected an integer"),
+        Scalar::F32 | Scalar::F64 => trimmed
+            .parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(Value::Float)
+            .ok_or("expected a number"),
+        Scalar::Bool => parse_bool(trimmed)
+            .map(Value::Bool)
+            .ok_or("expected true or false"),
+        Scalar::Uuid if is_uuid(trimmed) => Ok(Value::Text(trimmed.to_string())),
+        Scalar::Uuid => Err("expected a UUID"),
+        Scalar::DateTime | Scalar::NaiveDateTime if is_timestamp(trimmed) => {
+            Ok(Value::Text(trimmed.to_string()))
+        }
+        Scalar::DateTime | Scalar::NaiveDateTime => Err("expected a timestamp"),
+        Scalar::Date => NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+            .map(|_| Value::Text(trimmed.to_string()))
+            .map_err(|_| "expected a date (YYYY-MM-DD)"),
+        Scalar::Decimal if trimmed.parse::<f64>().is_ok_and(f64::is_finite) => {
+            Ok(Value::Text(trimmed.to_string()))
+        }
+        Scalar::Decimal => Err("expected a decimal number"),
+        Scalar::Json => serde_json::from_str::<JsonValue>(text)
+            .map(|_| Value::Text(text.to_string()))
+            .map_err(|_| "expected a JSON document"),
+    }
+}
+
+/// Turns a row read from the file into values for the columns it fills.
+/// `None` marks a column left to the database.
+fn validate_row(
+    columns: &[Column],
+    cells: Cells,
+    line: usize,
+    now: &str,
+) -> Result<Vec<Option<Value>>, String> {
+    columns
+        .iter()
+        .zip(cells)
+        .map(|(column, cell)| match (cell, column.missing) {
+            (Some(Some(text)), _) => parse_value(column.scalar, &text)
+                .map(Some)
+                .map_err(|e| format!("line {}: {}: {}, got {:?}", line, column.name, e, text)),
+            (Some(None) | None, Missing::Null) => Ok(Some(Value::Null)),
+            (Some(None) | None, Missing::Database) => Ok(None),
+            (None, Missing::Now) => Ok(Some(Value::Text(now.to_string()))),
+            (Some(None), _) => Err(format!("line {}: {} can't be null", line, column.name)),
+            (None, _) => Err(format!("line {}: {} is missing", line, column.name)),
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Statements
+// ---------------------------------------------------------------------------
+
+/// An `INSERT` of `rows`, with the clause `policy` needs on `backend`.
+/// `columns` are quoted names, each flagged when it's part of the key.
+fn insert_statement(
+    backend: &str,
+    table: &str,
+    columns: &[(String, bool)],
+    rows: &[Vec<Value>],
+    policy: OnConflict,
+) -> String {
+    let names: Vec<String> = columns.iter().map(|(name, _)| name.clone()).collect();
+    let insert = fake::insert_sql(table, &names, rows);
+    let keys: Vec<&str> = columns
+        .iter()
+        .filter(|(_, key)| *key)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let others = columns.iter().filter(|(_, key)| !key).map(|(name, _)| name);
+
+    match (policy, backend) {
+        (OnConflict::Fail, _) => insert,
+        (OnConflict::Skip, "mysql") => insert.replacen("INSERT", "INSERT IGNORE", 1),
+        (OnConflict::Skip, _) => format!("{} ON CONFLICT DO NOTHING", insert),
+        (OnConflict::Update, "mysql") => {
+            let updates: Vec<String> = others.map(|c| format!("{} = VALUES({})", c, c)).collect();
+            if updates.is_empty() {
+                insert.replacen("INSERT", "INSERT IGNORE", 1)
+            } else {
+                format!("{} ON DUPLICATE KEY UPDATE {}", insert, updates.join(", "))
+            }
+        }
+        (OnConflict::Update, _) => {
+            let updates: Vec<String> = others.map(|c| format!("{} = excluded.{}", c, c)).collect();
+            if updates.is_empty() {
+                format!("{} ON CONFLICT ({}) DO NOTHING", insert, keys.join(", "))
+            } else {
+                format!(
+                    "{} ON CONFLICT ({}) DO UPDATE SET {}",
+                    insert,
+                    keys.join(", "),
+                    updates.join(", ")
+                )
+            }
+        }
+    }
+}
+
+fn progress(done: u64, total: Option<u64>) {
+    output::record("progress", json!({ "rows": done, "total": total }));
+    match total {
+        Some(total) => output::info(format!("  {} / {} rows", done, total)),
+        None => output::info(format!("  {} rows", done)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+async fn export_rows(
+    db: &Database,
+    entity: &Entity,
+    columns: &[Column],
+    config: &ExportConfig,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<u64, String> {
+    let table = db.quote_ident(&entity.table);
+    let filter = config
+        .filter
+        .as_ref()
+        .map(|condition| format!(" WHERE {}", condition))
+        .unwrap_or_default();
+
+    let count = db
+        .fetch(&format!("SELECT COUNT(*) FROM {}{}", table, filter))
+        .await?;
+    let total = count
+        .rows
+        .first()
+        .and_then(|row| row.first())
+        .and_then(|n| n.parse::<u64>().ok())
+        .unwrap_or_default();
+    let total = config.limit.map_or(total, |limit| total.min(limit));
+
+    let names: Vec<String> = columns.iter().map(|c| db.quote_ident(&c.name)).collect();
+    let order: Vec<String> = columns
+        .iter()
+        .filter(|c| c.key)
+        .map(|c| db.quote_ident(&c.name))
+        .collect();
+    let order = if order.is_empty() {
+        String::new()
+    } else {
+        format!(" ORDER BY {}", order.join(", "))
+    };
+
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {}", config.out, e);
+    if format == Format::Csv {
+        let header: Vec<String> = columns.iter().map(|c| csv_field(&c.name)).collect();
+        writeln!(out, "{}", header.join(",")).map_err(write_error)?;
+    }
+
+    let mut written = 0;
+    while written < total {
+        let sql = format!(
+            "SELECT {} FROM {}{}{} LIMIT {} OFFSET {}",
+            names.join(", "),
+            table,
+            filter,
+            order,
+            PAGE_SIZE.min(total - written),
+            written
+        );
+        let rows = db.fetch_cells(&sql).await?;
+        if rows.is_empty() {
+            // Rows were deleted since they were counted
+            break;
+        }
+        for row in &rows {
+            let line = match format {
+                Format::Csv => csv_row(columns, row),
+                Format::Ndjson => ndjson_row(columns, row),
+            };
+            writeln!(out, "{}", line).map_err(write_error)?;
+        }
+        written += rows.len() as u64;
+        if total > PAGE_SIZE {
+            progress(written, Some(total));
+        }
+    }
+
+    out.flush().map_err(write_error)?;
+    Ok(written)
+}
+
+/// Write a resource's rows to a CSV or NDJSON file.
+pub fn export(config: ExportConfig) -> Result<(), String> {
+    let format = Format::resolve(config.format.as_deref(), &config.out)?;
+    let entities = fake::load_entities();
+    let entity = fake::find_entity(&entities, &config.resource)?;
+    let columns = columns(&entities, entity);
+
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to create async runtime: {}", e))?;
+    let db = rt.block_on(Database::connect(&config.url, true))?;
+
+    let file =
+        File::create(&config.out).map_err(|e| format!("Failed to create {}: {}", config.out, e))?;
+    let mut out = BufWriter::new(file);
+    let written = rt.block_on(export_rows(
+        &db, entity, &columns, &config, format, &mut out,
+    ))?;
+
+    output::success(format!(
+        "Exported {} row(s) from {} to {}",
+        written, entity.table, config.out
+    ));
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Import
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default, PartialEq)]
+struct Imported {
+    /// Rows read from the file.
+    rows: u64,
+    /// Rows the database reports as inserted or updated.
+    written: u64,
+}
+
+struct Importer<'a> {
+    db: &'a Database,
+    table: String,
+    columns: &'a [Column],
+    policy: OnConflict,
+    /// Which columns the current batch fills; rows that leave a different
+    /// set to the database go in the next one.
+    filled: Vec<bool>,
+    batch: Vec<Vec<Value>>,
+    imported: Imported,
+}
+
+impl Importer<'_> {
+    async fn push(&mut self, row: Vec<Option<Value>>) -> Result<(), String> {
+        let filled: Vec<bool> = row.iter().map(Option::is_some).collect();
+        if filled != self.filled || self.batch.len() == BATCH_SIZE {
+            self.flush().await?;
+            self.filled = filled;
+        }
+        self.batch.push(row.into_iter().flatten().collect());
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), String> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let columns: Vec<(String, bool)> = self
+            .columns
+            .iter()
+            .zip(&self.filled)
+            .filter(|(_, filled)| **filled)
+            .map(|(c, _)| (self.db.quote_ident(&c.name), c.key))
+            .collect();
+
+        let backend = self.db.backend();
+        if backend == "mysql" {
+            // MySQL treats backslashes in string literals as escapes
+            for value in self.batch.iter_mut().flatten() {
+                if let Value::Text(text) = value {
+                    *text = text.replace('\\', "\\\\");
+                }
+            }
+        }
+
+        let sql = insert_statement(
+            backend,
+            &self.db.quote_ident(&self.table),
+            &columns,
+            &self.batch,
+            self.policy,
+        );
+        self.imported.written += self.db.execute(&sql).await?;
+
+        let before = self.imported.rows / PAGE_SIZE;
+        self.imported.rows += self.batch.len() as u64;
+        if self.imported.rows / PAGE_SIZE > before {
+            progress(self.imported.rows, None);
+        }
+
+        self.batch.clear();
+        Ok(())
+    }
+}
+
+async fn import_rows(
+    db: &Database,
+    entity: &Entity,
+    columns: &[Column],
+    input: impl BufRead,
+    format: Format,
+    policy: OnConflict,
+    limit: Option<u64>,
+) -> Result<Imported, String> {
+    let now = Utc::now().naive_utc().format(TIMESTAMP_FORMAT).to_string();
+    let mut reader = RowReader::new(format, input, &entity.table, columns)?;
+    let mut importer = Importer {
+        db,
+        table: entity.table.clone(),
+        columns,
+        policy,
+        filled: Vec::new(),
+        batch: Vec::with_capacity(BATCH_SIZE),
+        imported: Imported::default(),
+    };
+
+    let mut read = 0;
+    while limit.is_none_or(|limit| read < limit) {
+        let Some((line, cells)) = reader.next(&entity.table)? else {
+            break;
+        };
+        let row = validate_row(columns, cells, line, &now)?;
+        if policy == OnConflict::Update
+            && let Some(column) = columns
+                .iter()
+                .zip(&row)
+                .find(|(c, value)| c.key && value.is_none())
+                .map(|(c, _)| c)
+        {
+            return Err(format!(
+                "line {}: --on-conflict update needs the key column {}",
+                line, column.name
+            ));
+        }
+        importer.push(row).await?;
+        read += 1;
+    }
+
+    importer.flush().await?;
+    Ok(importer.imported)
+}
+
+/// Insert the rows of a CSV or NDJSON file into a resource's table.
+pub fn import(config: ImportConfig) -> Result<(), String> {
+    let format = Format::resolve(config.format.as_deref(), &config.file)?;
+    let policy = OnConflict::parse(config.on_conflict.as_deref())?;
+    let entities = fake::load_entities();
+    let entity = fake::find_entity(&entities, &config.resource)?;
+    let columns = columns(&entities, entity);
+
+    let file =
+        File::open(&config.file).map_err(|e| format!("Failed to open {}: {}", config.file, e))?;
+
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to create async runtime: {}", e))?;
+    let db = rt.block_on(Database::connect(&config.url, false))?;
+
+    let imported = rt.block_on(async {
+        db.execute_unprepared("BEGIN").await?;
+        let result = import_rows(
+            &db,
+            entity,
+            &columns,
+            BufReader::new(file),
+            format,
+            policy,
+            config.limit,
+        )
+        .await;
+        match result {
+            Ok(imported) => db.execute_unprepared("COMMIT").await.map(|_| imported),
+            Err(e) => {
+                let _ = db.execute_unprepared("ROLLBACK").await;
+                Err(e)
+            }
+        }
+    })?;
+
+    if policy == OnConflict::Skip {
+        output::success(format!(
+            "Imported {} row(s) into {}, skipped {} already present",
+            imported.written,
+            entity.table,
+            imported.rows.saturating_sub(imported.written)
+        ));
+    } else {
+        output::success(format!(
+            "Imported {} row(s) into {}",
+            imported.rows, entity.table
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTITIES: &str = r#"
+use rapina::prelude::*;
+
+schema! {
+    Author {
+        name: String,
+    }
+
+    #[timestamps(created_at)]
+    Post {
+        title: String,
+        body: Option<Text>,
+        views: i64,
+        published: bool,
+        metadata: Option<Json>,
+        author: Author,
+    }
+}
+"#;
+
+    fn entities() -> Vec<Entity> {
+        fake::parse_entities(ENTITIES)
+    }
+
+    fn post_columns() -> Vec<Column> {
+        let entities = entities();
+        columns(&entities, &entities[1])
+    }
+
+    fn some(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values.iter().map(|v| v.map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn test_columns_follow_the_schema() {
+        let columns = post_columns();
+        assert_eq!(
+            column_names(&columns),
+            "id, title, body, views, published, metadata, author_id, created_at"
+        );
+        assert_eq!(columns[0].missing, Missing::Database);
+        assert!(columns[0].key);
+        assert_eq!(columns[2].missing, Missing::Null);
+        assert_eq!(columns[6].scalar, Scalar::I32);
+        assert_eq!(columns[7].missing, Missing::Now);
+    }
+
+    #[test]
+    fn test_format_resolution() {
+        assert_eq!(Format::resolve(None, "posts.csv"), Ok(Format::Csv));
+        assert_eq!(Format::resolve(None, "posts.ndjson"), Ok(Format::Ndjson));
+        assert_eq!(Format::resolve(None, "posts.jsonl"), Ok(Format::Ndjson));
+        assert_eq!(
+            Format::resolve(Some("ndjson"), "posts.txt"),
+            Ok(Format::Ndjson)
+        );
+        assert!(Format::resolve(Some("xml"), "posts.xml").is_err());
+        assert!(OnConflict::parse(Some("replace")).is_err());
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        let columns = post_columns();
+        let row = some(&[
+            Some("1"),
+            Some("Hello, \"world\""),
+            Some("line one\nline two"),
+            Some("10"),
+            Some("1"),
+            None,
+            Some("3"),
+            Some(""),
+        ]);
+        assert_eq!(
+            csv_row(&columns, &row),
+            "1,\"Hello, \"\"world\"\"\",\"line one\nline two\",10,true,,3,\"\""
+        );
+    }
+
+    #[test]
+    fn test_csv_reader_round_trips_escaped_fields() {
+        let input = "id,title\n1,\"Hello, \"\"world\"\"\"\r\n\n2,\"multi\nline\"\n3,\n4,\"\"\n";
+        let mut reader = CsvReader::new(input.as_bytes());
+        let mut records = Vec::new();
+        while let Some(record) = reader.record().unwrap() {
+            records.push(record);
+        }
+        assert_eq!(
+            records,
+            vec![
+                (1, some(&[Some("id"), Some("title")])),
+                (2, some(&[Some("1"), Some("Hello, \"world\"")])),
+                (4, some(&[Some("2"), Some("multi\nline")])),
+                (6, some(&[Some("3"), None])),
+                (7, some(&[Some("4"), Some("")])),
+            ]
+        );
+
+        let mut reader = CsvReader::new("1,\"open\n".as_bytes());
+        assert_eq!(
+            reader.record(),
+            Err("line 1: unterminated quoted field".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ndjson_rows_are_typed() {
+        let columns = post_columns();
+        let row = some(&[
+            Some("1"),
+            Some("Hi"),
+            None,
+            Some("10"),
+            Some("0"),
+            Some("{\"tags\":[\"a\"]}"),
+            Some("3"),
+            Some("2026-01-01 00:00:00"),
+        ]);
+        assert_eq!(
+            ndjson_row(&columns, &row),
+            r#"{"id":1,"title":"Hi","body":null,"views":10,"published":false,"metadata":{"tags":["a"]},"author_id":3,"created_at":"2026-01-01 00:00:00"}"#
+        );
+    }
+
+    #[test]
+    fn test_ndjson_cells_reject_unknown_columns() {
+        let columns = post_columns();
+        let cells = ndjson_cells(
+            &columns,
+            "posts",
+            r#"{"title":"Hi","metadata":{"a":1},"body":null}"#,
+            1,
+        )
+        .unwrap();
+        assert_eq!(cells[1], Some(Some("Hi".to_string())));
+        assert_eq!(cells[5], Some(Some("{\"a\":1}".to_string())));
+        assert_eq!(cells[2], Some(None));
+        assert_eq!(cells[0], None);
+
+        let err = ndjson_cells(&columns, "posts", r#"{"titel":"Hi"}"#, 4).unwrap_err();
+        assert!(err.starts_with("line 4: unknown column 'titel'"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_row() {
+        let columns = post_columns();
+        let now = "2026-01-01 00:00:00";
+        let cells = |values: &[Option<Option<&str>>]| -> Cells {
+            values
+                .iter()
+                .map(|v| v.map(|v| v.map(str::to_string)))
+                .collect()
+        };
+
+        let row = validate_row(
+            &columns,
+            cells(&[
+                None,
+                Some(Some("Hi")),
+                None,
+                Some(Some("10")),
+                Some(Some("true")),
+                Some(None),
+                Some(Some("3")),
+                None,
+            ]),
+            2,
+            now,
+        )
+        .unwrap();
+        assert_eq!(
+            row,
+            vec![
+                None,
+                Some(Value::Text("Hi".into())),
+                Some(Value::Null),
+                Some(Value::Int(10)),
+                Some(Value::Bool(true)),
+                Some(Value::Null),
+                Some(Value::Int(3)),
+                Some(Value::Text(now.into())),
+            ]
+        );
+
+        let mut bad = cells(&[
+            None,
+            Some(Some("Hi")),
+            None,
+            Some(Some("ten")),
+            Some(Some("true")),
+            None,
+            Some(Some("3")),
+            None,
+        ]);
+        assert_eq!(
+            validate_row(&columns, bad.clone(), 5, now).unwrap_err(),
+            "line 5: views: expected an integer, got \"ten\""
+        );
+        bad[3] = Some(Some("10".to_string()));
+        bad[1] = Some(None);
+        assert_eq!(
+            validate_row(&columns, bad.clone(), 5, now).unwrap_err(),
+            "line 5: title can't be null"
+        );
+        bad[1] = None;
+        assert_eq!(
+            validate_row(&columns, bad, 5, now).unwrap_err(),
+            "line 5: title is missing"
+        );
+    }
+
+    #[test]
+    fn test_parse_value_checks_types() {
+        assert!(parse_value(Scalar::Uuid, "550e8400-e29b-41d4-a716-446655440000").is_ok());
+        assert!(parse_value(Scalar::Uuid, "550e8400").is_err());
+        assert!(parse_value(Scalar::DateTime, "2026-01-01T10:00:00Z").is_ok());
+        assert!(parse_value(Scalar::DateTime, "2026-01-01 10:00:00 UTC").is_ok());
+        assert!(parse_value(Scalar::DateTime, "2026-01-01 10:00:00 +00:00").is_ok());
+        assert!(parse_value(Scalar::DateTime, "yesterday").is_err());
+        assert!(parse_value(Scalar::Date, "2026-02-30").is_err());
+        assert!(parse_value(Scalar::I32, "3000000000").is_err());
+        assert!(parse_value(Scalar::F64, "NaN").is_err());
+        assert!(parse_value(Scalar::Json, "{\"a\":").is_err());
+        assert_eq!(
+            parse_value(Scalar::Decimal, "12.50"),
+            Ok(Value::Text("12.50".into()))
+        );
+    }
+
+    #[test]
+    fn test_insert_statement_conflict_clauses() {
+        let columns = vec![
+            ("\"id\"".to_string(), true),
+            ("\"name\"".to_string(), false),
+        ];
+        let rows = vec![vec![Value::Int(1), Value::Text("Ada".into())]];
+        let insert = "INSERT INTO \"users\" (\"id\", \"name\") VALUES (1, 'Ada')";
+
+        assert_eq!(
+            insert_statement("sqlite", "\"users\"", &columns, &rows, OnConflict::Fail),
+            insert
+        );
+        assert_eq!(
+            insert_statement("postgres", "\"users\"", &columns, &rows, OnConflict::Skip),
+            format!("{} ON CONFLICT DO NOTHING", insert)
+        );
+        assert_eq!(
+            insert_statement("sqlite", "\"users\"", &columns, &rows, OnConflict::Update),
+            format!(
+                "{} ON CONFLICT (\"id\") DO UPDATE SET \"name\" = excluded.\"name\"",
+                insert
+            )
+        );
+
+        let columns = vec![("`id`".to_string(), true), ("`name`".to_string(), false)];
+        assert_eq!(
+            insert_statement("mysql", "`users`", &columns, &rows, OnConflict::Skip),
+            "INSERT IGNORE INTO `users` (`id`, `name`) VALUES (1, 'Ada')"
+        );
+        assert_eq!(
+            insert_statement("mysql", "`users`", &columns, &rows, OnConflict::Update),
+            "INSERT INTO `users` (`id`, `name`) VALUES (1, 'Ada') \
+             ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"
+        );
+    }
+
+    #[cfg(feature = "import-sqlite")]
+    #[test]
+    fn test_export_and_import_sqlite() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("rapina-transfer-{}.db", std::process::id()));
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+        let entities = entities();
+        let post = &entities[1];
+        let columns = columns(&entities, post);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let export = |format: Format, filter: Option<&str>, limit: Option<u64>| {
+            let config = ExportConfig {
+                url: url.clone(),
+                resource: "posts".into(),
+                out: "posts".into(),
+                format: None,
+                filter: filter.map(str::to_string),
+                limit,
+            };
+            let mut out = Vec::new();
+            rt.block_on(async {
+                let db = Database::connect(&url, true).await.unwrap();
+                export_rows(&db, post, &columns, &config, format, &mut out)
+                    .await
+                    .unwrap()
+            });
+            String::from_utf8(out).unwrap()
+        };
+        let import = |input: &str, format: Format, policy: OnConflict| {
+            rt.block_on(async {
+                let db = Database::connect(&url, false).await.unwrap();
+                import_rows(&db, post, &columns, input.as_bytes(), format, policy, None).await
+            })
+        };
+        let titles = || {
+            rt.block_on(async {
+                let db = Database::connect(&url, true).await.unwrap();
+                db.fetch("SELECT title FROM posts ORDER BY id")
+                    .await
+                    .unwrap()
+                    .rows
+                    .into_iter()
+                    .map(|row| row[0].clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        rt.block_on(async {
+            let db = Database::connect(&url, false).await.unwrap();
+            db.execute_unprepared(
+                "CREATE TABLE posts (
+                     id INTEGER PRIMARY KEY,
+                     title TEXT NOT NULL,
+                     body TEXT,
+                     views INTEGER NOT NULL,
+                     published BOOLEAN NOT NULL,
+                     metadata TEXT,
+                     author_id INTEGER NOT NULL,
+                     created_at TEXT NOT NULL
+                 );
+                 INSERT INTO posts VALUES
+                     (1, 'Hello, \"world\"', 'line one
+line two', 10, 1, '{\"tags\":[\"a\"]}', 7, '2026-01-01 00:00:00'),
+                     (2, 'Empty body', '', 0, 0, NULL, 7, '2026-01-02 00:00:00'),
+                     (3, 'No body', NULL, 5, 1, NULL, 8, '2026-01-03 00:00:00');",
+            )
+            .await
+            .unwrap();
+        });
+
+        let csv = export(Format::Csv, None, None);
+        assert_eq!(
+            csv,
+            "id,title,body,views,published,metadata,author_id,created_at\n\
+             1,\"Hello, \"\"world\"\"\",\"line one\nline two\",10,true,\"{\"\"tags\"\":[\"\"a\"\"]}\",7,2026-01-01 00:00:00\n\
+             2,Empty body,\"\",0,false,,7,2026-01-02 00:00:00\n\
+             3,No body,,5,true,,8,2026-01-03 00:00:00\n"
+        );
+        let ndjson = export(Format::Ndjson, Some("author_id = 7"), Some(1));
+        assert_eq!(
+            ndjson,
+            "{\"id\":1,\"title\":\"Hello, \\\"world\\\"\",\"body\":\"line one\\nline two\",\"views\":10,\"published\":true,\"metadata\":{\"tags\":[\"a\"]},\"author_id\":7,\"created_at\":\"2026-01-01 00:00:00\"}\n"
+        );
+
+        // Back in, into an emptied table, and out again unchanged
+        rt.block_on(async {
+            let db = Database::connect(&url, false).await.unwrap();
+            db.execute("DELETE FROM posts").await.unwrap();
+        });
+        let imported = import(&csv, Format::Csv, OnConflict::Fail).unwrap();
+        assert_eq!(
+            imported,
+            Imported {
+                rows: 3,
+                written: 3
+            }
+        );
+        assert_eq!(export(Format::Csv, None, None), csv);
+
+        // Conflicts fail, are skipped, or update the existing row
+        assert!(import(&ndjson, Format::Ndjson, OnConflict::Fail).is_err());
+        let renamed = ndjson.replace("Hello, \\\"world\\\"", "Renamed");
+        let skipped = import(&renamed, Format::Ndjson, OnConflict::Skip).unwrap();
+        assert_eq!(
+            skipped,
+            Imported {
+                rows: 1,
+                written: 0
+            }
+        );
+        assert_eq!(titles()[0], "Hello, \"world\"");
+        import(&renamed, Format::Ndjson, OnConflict::Update).unwrap();
+        assert_eq!(titles()[0], "Renamed");
+
+        // Rows without an id get one from the database and a created_at
+        let new_rows = "{\"title\":\"Fresh\",\"views\":1,\"published\":false,\"author_id\":9}\n";
+        import(new_rows, Format::Ndjson, OnConflict::Fail).unwrap();
+        assert_eq!(titles().len(), 4);
+        assert_eq!(
+            import(new_rows, Format::Ndjson, OnConflict::Update).unwrap_err(),
+            "line 1: --on-conflict update needs the key column id"
+        );
+        assert!(
+            import("title,views\nBad,many\n", Format::Csv, OnConflict::Fail)
+                .unwrap_err()
+                .starts_with("line 2: views")
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,1079 @@
+//! Upgrade generated code between rapina versions.
+//!
+//! Each release that changes what `rapina add resource` or `rapina import`
+//! generate gets an entry in [`MIGRATIONS`]. An entry lists codemods, which
+//! rewrite files in every `src/<resource>/` module, and manual steps that
+//! can't be automated. `rapina upgrade` runs every entry between the
+//! project's rapina version and the target version, oldest first.
+
+use colored::Colorize;
+use proc_macro2::{LineColumn, Span};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use super::verify_rapina_project;
+use crate::output;
+
+/// Configuration for the upgrade command.
+pub struct UpgradeConfig {
+    /// Version the project is on, when it can't be read from Cargo.toml.
+    pub from: Option<String>,
+    /// Version to upgrade to. Defaults to the CLI's own version.
+    pub to: Option<String>,
+    /// Print a unified diff instead of writing files.
+    pub dry_run: bool,
+}
+
+/// A release that changed the conventions of generated code.
+struct Migration {
+    version: &'static str,
+    codemods: &'static [Codemod],
+    /// Changes that can't be made automatically, shown after upgrading.
+    manual_steps: &'static [&'static str],
+}
+
+/// An automated rewrite of one generated file in every resource module.
+struct Codemod {
+    name: &'static str,
+    /// File name inside each `src/<resource>/` directory, e.g. `handlers.rs`.
+    file: &'static str,
+    apply: fn(&str) -> Result<Rewrite, String>,
+}
+
+/// The result of running a codemod over one file.
+struct Rewrite {
+    source: String,
+    /// Code the codemod recognized but couldn't safely rewrite.
+    skipped: Vec<String>,
+}
+
+/// Known migrations, oldest first.
+///
+/// Add an entry when a release changes generated code. Codemods must leave
+/// files that are already up to date untouched, since a project's version
+/// doesn't say which CLI version generated each resource.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: "0.7.3",
+        codemods: &[Codemod {
+            name: "publish-entity-events",
+            file: "handlers.rs",
+            apply: publish_entity_events,
+        }],
+        manual_steps: &[
+            "Events from scaffolded handlers are only logged until you register a sink with `.state(Events::new(...))`",
+        ],
+    },
+    Migration {
+        version: "0.8.0",
+        codemods: &[Codemod {
+            name: "split-reads-and-writes",
+            file: "handlers.rs",
+            apply: split_reads_and_writes,
+        }],
+        manual_steps: &[
+            "New resources list with `Paginate` and return `Paginated<Model>`; existing `list_*` handlers still return every row, and switching them changes the response to a `{\"data\": [...], \"page\": ...}` envelope",
+        ],
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl Version {
+    /// Parses a version or a simple requirement such as `0.7`, `^0.6.1`, or `=0.7.3`.
+    fn parse(input: &str) -> Result<Self, String> {
+        let core = input
+            .trim()
+            .trim_start_matches(['^', '~', '=', ' '])
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default();
+
+        let numbers = core
+            .split('.')
+            .map(|part| part.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("Invalid version '{}'", input))?;
+
+        match numbers.as_slice() {
+            [major] => Ok(Self::new(*major, 0, 0)),
+            [major, minor] => Ok(Self::new(*major, *minor, 0)),
+            [major, minor, patch] => Ok(Self::new(*major, *minor, *patch)),
+            _ => Err(format!("Invalid version '{}'", input)),
+        }
+    }
+
+    fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A file the upgrade will rewrite.
+pub(super) struct FileChange {
+    pub(super) path: PathBuf,
+    pub(super) before: String,
+    pub(super) after: String,
+}
+
+/// Everything an upgrade will do, computed without touching the project.
+struct Plan {
+    from: Version,
+    to: Version,
+    applied: Vec<&'static str>,
+    changes: Vec<FileChange>,
+    manual_steps: Vec<String>,
+}
+
+/// Upgrade the project in the current directory.
+pub fn execute(config: UpgradeConfig) -> Result<(), String> {
+    verify_rapina_project()?;
+
+    let plan = plan(Path::new("."), config.from.as_deref(), config.to.as_deref())?;
+
+    output::blank();
+    output::info(format!(
+        "  {} rapina {} → {}",
+        "Upgrading".bold(),
+        plan.from.to_string().cyan(),
+        plan.to.to_string().cyan()
+    ));
+    output::blank();
+
+    if plan.changes.is_empty() && plan.manual_steps.is_empty() {
+        output::success("Nothing to upgrade");
+        output::blank();
+        return Ok(());
+    }
+
+    for name in &plan.applied {
+        output::info(format!("  {} Codemod {}", "→".cyan(), name.bold()));
+        output::record("codemod", serde_json::json!({ "name": name }));
+    }
+
+    if config.dry_run {
+        output::blank();
+        for change in &plan.changes {
+            let diff = unified_diff(change);
+            output::record(
+                "diff",
+                serde_json::json!({ "path": display_path(&change.path), "diff": diff }),
+            );
+            output::info(diff.trim_end());
+        }
+    } else {
+        for change in &plan.changes {
+            fs::write(&change.path, &change.after)
+                .map_err(|e| format!("Failed to write {}: {}", change.path.display(), e))?;
+            output::updated(&display_path(&change.path));
+        }
+    }
+
+    if !plan.manual_steps.is_empty() {
+        output::blank();
+        output::info(format!("  {}", "Manual steps:".bold()));
+        for step in &plan.manual_steps {
+            output::info(format!("    {} {}", "•".yellow(), step));
+            output::record("manual_step", serde_json::json!({ "step": step }));
+        }
+    }
+
+    output::blank();
+    if config.dry_run {
+        output::info(format!(
+            "  Dry run: no files were changed. Run without {} to apply.",
+            "--dry-run".cyan()
+        ));
+    } else {
+        output::info(format!(
+            "  Run {} to check the result.",
+            "cargo build && rapina test".cyan()
+        ));
+    }
+    output::blank();
+
+    Ok(())
+}
+
+fn plan(root: &Path, from: Option<&str>, to: Option<&str>) -> Result<Plan, String> {
+    let cargo_path = root.join("Cargo.toml");
+    let cargo =
+        fs::read_to_string(&cargo_path).map_err(|e| format!("Failed to read Cargo.toml: {}", e))?;
+    let spec = rapina_version_spec(&cargo)?;
+
+    let from = match (from, &spec) {
+        (Some(from), _) => Version::parse(from)?,
+        (None, Some(spec)) => Version::parse(spec)?,
+        (None, None) => {
+            return Err(
+                "Can't tell which rapina version this project uses (no version in Cargo.toml). \
+                 Pass it with --from"
+                    .to_string(),
+            );
+        }
+    };
+    let to = Version::parse(to.unwrap_or(env!("CARGO_PKG_VERSION")))?;
+
+    if to < from {
+        return Err(format!("Refusing to downgrade from {} to {}", from, to));
+    }
+
+    let mut files: BTreeMap<PathBuf, (String, String)> = BTreeMap::new();
+    let mut applied = Vec::new();
+    let mut manual_steps = Vec::new();
+    let modules = resource_modules(root)?;
+
+    for migration in MIGRATIONS {
+        let version = Version::parse(migration.version)?;
+        if version <= from || version > to {
+            continue;
+        }
+
+        for codemod in migration.codemods {
+            applied.push(codemod.name);
+
+            for module in &modules {
+                let path = module.join(codemod.file);
+                if !files.contains_key(&path) {
+                    if !path.is_file() {
+                        continue;
+                    }
+                    let source = fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                    files.insert(path.clone(), (source.clone(), source));
+                }
+
+                let current = &files[&path].1;
+                let rewrite = (codemod.apply)(current)
+                    .map_err(|e| format!("{}: {}", display_path(&path), e))?;
+                manual_steps.extend(
+                    rewrite
+                        .skipped
+                        .into_iter()
+                        .map(|reason| format!("{}: {}", display_path(&path), reason)),
+                );
+                if let Some(entry) = files.get_mut(&path) {
+                    entry.1 = rewrite.source;
+                }
+            }
+        }
+
+        manual_steps.extend(migration.manual_steps.iter().map(|s| s.to_string()));
+    }
+
+    let mut changes: Vec<FileChange> = files
+        .into_iter()
+        .filter(|(_, (before, after))| before != after)
+        .map(|(path, (before, after))| FileChange {
+            path,
+            before,
+            after,
+        })
+        .collect();
+
+    if from < to
+        && let Some(spec) = &spec
+        && let Some(bumped) = bump_rapina_version(&cargo, spec, &to.to_string())
+    {
+        changes.insert(
+            0,
+            FileChange {
+                path: cargo_path,
+                before: cargo,
+                after: bumped,
+            },
+        );
+    }
+
+    Ok(Plan {
+        from,
+        to,
+        applied,
+        changes,
+        manual_steps,
+    })
+}
+
+/// Reads the version requirement of the `rapina` dependency, if it has one.
+fn rapina_version_spec(cargo: &str) -> Result<Option<String>, String> {
+    let parsed: toml::Value =
+        toml::from_str(cargo).map_err(|e| format!("Failed to parse Cargo.toml: {}", e))?;
+
+    let dependency = parsed
+        .get("dependencies")
+        .and_then(|deps| deps.get("rapina"))
+        .ok_or("No rapina dependency found in Cargo.toml")?;
+
+    Ok(match dependency {
+        toml::Value::String(version) => Some(version.clone()),
+        toml::Value::Table(table) => table
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        _ => None,
+    })
+}
+
+/// Rewrites the rapina version in `[dependencies]`, keeping the rest of the
+/// file as it was.
+fn bump_rapina_version(cargo: &str, spec: &str, version: &str) -> Option<String> {
+    let mut section = String::new();
+    let mut bumped = false;
+
+    let lines: Vec<String> = cargo
+        .split_inclusive('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                section = trimmed.to_string();
+            } else if !bumped
+                && section == "[dependencies]"
+                && trimmed
+                    .strip_prefix("rapina")
+                    .is_some_and(|rest| rest.trim_start().starts_with('='))
+            {
+                let quoted = format!("\"{}\"", spec);
+                if line.contains(&quoted) {
+                    bumped = true;
+                    return line.replacen(&quoted, &format!("\"{}\"", version), 1);
+                }
+            }
+            line.to_string()
+        })
+        .collect();
+
+    bumped.then(|| lines.concat())
+}
+
+/// Directories under `src/` that hold generated resources, sorted by name.
+fn resource_modules(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let src = root.join("src");
+    let entries = match fs::read_dir(&src) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut modules: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    modules.sort();
+    Ok(modules)
+}
+
+pub(super) fn display_path(path: &Path) -> String {
+    path.strip_prefix(".").unwrap_or(path).display().to_string()
+}
+
+pub(super) fn unified_diff(change: &FileChange) -> String {
+    let path = display_path(&change.path);
+    similar::TextDiff::from_lines(&change.before, &change.after)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Source editing
+// ---------------------------------------------------------------------------
+
+/// A text edit at byte offsets of the original source.
+pub(super) struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+impl Edit {
+    pub(super) fn insert(at: usize, text: impl Into<String>) -> Self {
+        Self {
+            start: at,
+            end: at,
+            text: text.into(),
+        }
+    }
+
+    pub(super) fn replace(start: usize, end: usize, text: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            text: text.into(),
+        }
+    }
+}
+
+/// Applies edits back to front so earlier offsets stay valid. Insertions at
+/// the same offset keep the order they were pushed in.
+pub(super) fn apply_edits(source: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| edit.start);
+    let mut output = source.to_string();
+    for edit in edits.iter().rev() {
+        output.replace_range(edit.start..edit.end, &edit.text);
+    }
+    output
+}
+
+/// Maps syn span positions (line, char column) to byte offsets.
+pub(super) struct LineIndex<'a> {
+    source: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(super) fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { source, starts }
+    }
+
+    fn offset(&self, position: LineColumn) -> usize {
+        let line_start = self.starts[position.line - 1];
+        self.source[line_start..]
+            .char_indices()
+            .nth(position.column)
+            .map(|(i, _)| line_start + i)
+            .unwrap_or(self.source.len())
+    }
+
+    pub(super) fn start(&self, span: Span) -> usize {
+        self.offset(span.start())
+    }
+
+    pub(super) fn end(&self, span: Span) -> usize {
+        self.offset(span.end())
+    }
+
+    /// The leading whitespace of the line containing `offset`.
+    pub(super) fn indent(&self, offset: usize) -> &'a str {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let rest = &self.source[self.starts[line]..];
+        let len = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        &rest[..len]
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Codemod: publish-entity-events (0.7.3)
+// ---------------------------------------------------------------------------
+
+/// Rewrites scaffolded create/update/delete handlers to run in a transaction
+/// and publish `Event::created`/`updated`/`deleted`, as `rapina add resource`
+/// generates them since 0.7.3.
+///
+/// Handlers that already take `Events` are left alone. Handlers that look
+/// scaffolded but have been changed too much to rewrite safely are reported.
+fn publish_entity_events(source: &str) -> Result<Rewrite, String> {
+    let file = syn::parse_file(source).map_err(|e| format!("failed to parse: {}", e))?;
+    let index = LineIndex::new(source);
+    let mut edits = Vec::new();
+    let mut skipped = Vec::new();
+
+    for item in &file.items {
+        let syn::Item::Fn(func) = item else {
+            continue;
+        };
+        let Some((event, entity)) = mutation_handler(func) else {
+            continue;
+        };
+        if has_arg_of_type(func, "Events") {
+            continue;
+        }
+
+        match publish_events_in(func, event, &entity, &index) {
+            Ok(fn_edits) => edits.extend(fn_edits),
+            Err(reason) => skipped.push(format!(
+                "`{}` {}; publish `Event::{}` manually",
+                func.sig.ident, reason, event
+            )),
+        }
+    }
+
+    if edits.is_empty() {
+        return Ok(Rewrite {
+            source: source.to_string(),
+            skipped,
+        });
+    }
+
+    edits.extend(import_edits(&file, &index));
+    Ok(Rewrite {
+        source: apply_edits(source, edits),
+        skipped,
+    })
+}
+
+/// The route attribute of a handler, e.g. `get` for `#[get("/todos")]`.
+fn route_method(func: &syn::ItemFn) -> Option<String> {
+    func.attrs.iter().find_map(|attr| {
+        let name = attr.path().get_ident()?.to_string();
+        matches!(name.as_str(), "get" | "post" | "put" | "patch" | "delete").then_some(name)
+    })
+}
+
+/// Recognizes scaffolded mutation handlers by their route attribute and name,
+/// returning the `Event` constructor and the entity name.
+fn mutation_handler(func: &syn::ItemFn) -> Option<(&'static str, String)> {
+    let (event, prefix) = match route_method(func)?.as_str() {
+        "post" => ("created", "create_"),
+        "put" | "patch" => ("updated", "update_"),
+        "delete" => ("deleted", "delete_"),
+        _ => return None,
+    };
+
+    let entity = func.sig.ident.to_string().strip_prefix(prefix)?.to_string();
+    Some((event, entity))
+}
+
+fn typed_args(func: &syn::ItemFn) -> impl Iterator<Item = &syn::PatType> {
+    func.sig.inputs.iter().filter_map(|arg| match arg {
+        syn::FnArg::Typed(pat_type) => Some(pat_type),
+        syn::FnArg::Receiver(_) => None,
+    })
+}
+
+fn arg_named<'f>(func: &'f syn::ItemFn, name: &str) -> Option<&'f syn::PatType> {
+    typed_args(func).find(|arg| matches!(&*arg.pat, syn::Pat::Ident(ident) if ident.ident == name))
+}
+
+fn has_arg_of_type(func: &syn::ItemFn, ty: &str) -> bool {
+    typed_args(func).any(|arg| {
+        matches!(&*arg.ty, syn::Type::Path(path)
+            if path.path.segments.last().is_some_and(|segment| segment.ident == ty))
+    })
+}
+
+fn binds(stmts: &[syn::Stmt], name: &str) -> bool {
+    stmts.iter().any(|stmt| {
+        matches!(stmt, syn::Stmt::Local(local)
+            if matches!(&local.pat, syn::Pat::Ident(ident) if ident.ident == name))
+    })
+}
+
+fn is_ok_call(expr: &syn::Expr) -> bool {
+    matches!(expr, syn::Expr::Call(call)
+        if matches!(&*call.func, syn::Expr::Path(path) if path.path.is_ident("Ok")))
+}
+
+/// Collects every `db.conn()` call in a handler body.
+#[derive(Default)]
+struct ConnCalls {
+    /// Spans of the whole `db.conn()` calls.
+    spans: Vec<Span>,
+    /// Spans of just the `conn` method names.
+    methods: Vec<Span>,
+}
+
+impl<'ast> Visit<'ast> for ConnCalls {
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let on_db = matches!(&*call.receiver, syn::Expr::Path(path) if path.path.is_ident("db"));
+        if on_db && call.method == "conn" && call.args.is_empty() {
+            self.spans.push(call.span());
+            self.methods.push(call.method.span());
+        } else {
+            syn::visit::visit_expr_method_call(self, call);
+        }
+    }
+}
+
+fn publish_events_in(
+    func: &syn::ItemFn,
+    event: &str,
+    entity: &str,
+    index: &LineIndex,
+) -> Result<Vec<Edit>, String> {
+    let db_arg = arg_named(func, "db").ok_or("has no `db: Db` argument")?;
+
+    let mut calls = ConnCalls::default();
+    calls.visit_block(&func.block);
+    let first_call = calls
+        .spans
+        .iter()
+        .map(|span| index.start(*span))
+        .min()
+        .ok_or("doesn't use `db.conn()`")?;
+
+    let stmts = &func.block.stmts;
+    let Some(syn::Stmt::Expr(tail, None)) = stmts.last() else {
+        return Err("doesn't end in an `Ok(...)` expression".to_string());
+    };
+    if !is_ok_call(tail) {
+        return Err("doesn't end in an `Ok(...)` expression".to_string());
+    }
+
+    let anchor = stmts
+        .iter()
+        .map(|stmt| stmt.span())
+        .find(|span| index.start(*span) <= first_call && first_call < index.end(*span))
+        .ok_or("uses `db.conn()` outside a statement")?;
+
+    let id = if event == "created" {
+        if !binds(stmts, "result") {
+            return Err("doesn't bind the inserted model to `result`".to_string());
+        }
+        "result.id"
+    } else {
+        if arg_named(func, "id").is_none() {
+            return Err("has no `id` argument".to_string());
+        }
+        "id"
+    };
+
+    let mut edits = vec![Edit::insert(index.end(db_arg.span()), ", events: Events")];
+
+    let anchor_start = index.start(anchor);
+    edits.push(Edit::insert(
+        anchor_start,
+        format!(
+            "let txn = db.conn().begin().await.map_err(DbError)?;\n{}",
+            index.indent(anchor_start)
+        ),
+    ));
+
+    for span in &calls.spans {
+        edits.push(Edit::replace(index.start(*span), index.end(*span), "&txn"));
+    }
+
+    let tail_start = index.start(tail.span());
+    let indent = index.indent(tail_start);
+    edits.push(Edit::insert(
+        tail_start,
+        format!(
+            "let event = Event::{event}(\"{entity}\", {id});\n\
+             {indent}events.before_commit(&txn, &event).await?;\n\
+             {indent}txn.commit().await.map_err(DbError)?;\n\
+             {indent}events.after_commit(event).await;\n\
+             {indent}"
+        ),
+    ));
+
+    Ok(edits)
+}
+
+/// Names imported by the file's `use` items, plus the parent path of globs
+/// as `path::*`.
+fn imported_names(file: &syn::File) -> Vec<String> {
+    fn walk(tree: &syn::UseTree, prefix: &str, names: &mut Vec<String>) {
+        match tree {
+            syn::UseTree::Path(path) => {
+                walk(&path.tree, &format!("{}{}::", prefix, path.ident), names)
+            }
+            syn::UseTree::Name(name) => names.push(name.ident.to_string()),
+            syn::UseTree::Rename(rename) => names.push(rename.ident.to_string()),
+            syn::UseTree::Glob(_) => names.push(format!("{}*", prefix)),
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    walk(tree, prefix, names);
+                }
+            }
+        }
+    }
+
+    let mut names = Vec::new();
+    for item in &file.items {
+        if let syn::Item::Use(item) = item {
+            walk(&item.tree, "", &mut names);
+        }
+    }
+    names
+}
+
+/// Finds the `use rapina::sea_orm::{...}` item and its group, if any.
+fn sea_orm_use(file: &syn::File) -> Option<(&syn::ItemUse, &syn::UseGroup)> {
+    file.items.iter().find_map(|item| {
+        let syn::Item::Use(item) = item else {
+            return None;
+        };
+        let syn::UseTree::Path(rapina) = &item.tree else {
+            return None;
+        };
+        let syn::UseTree::Path(sea_orm) = &*rapina.tree else {
+            return None;
+        };
+        if rapina.ident != "rapina" || sea_orm.ident != "sea_orm" {
+            return None;
+        }
+        match &*sea_orm.tree {
+            syn::UseTree::Group(group) => Some((item, group)),
+            _ => None,
+        }
+    })
+}
+
+/// Adds the `Event`, `Events`, and `TransactionTrait` imports the rewritten
+/// handlers need, unless the file already has them.
+fn import_edits(file: &syn::File, index: &LineIndex) -> Vec<Edit> {
+    let names = imported_names(file);
+    let has = |name: &str, glob: &str| names.iter().any(|n| n == name || n.ends_with(glob));
+
+    let missing_events: Vec<&str> = ["Event", "Events"]
+        .into_iter()
+        .filter(|name| !has(name, "events::*"))
+        .collect();
+    let needs_transaction = !has("TransactionTrait", "sea_orm::*");
+
+    let last_use_end = file
+        .items
+        .iter()
+        .rev()
+        .find(|item| matches!(item, syn::Item::Use(_)))
+        .map(|item| index.end(item.span()))
+        .unwrap_or(0);
+
+    // New imports go on their own line above the sea_orm import, or after the
+    // last import when there is none
+    let sea_orm = sea_orm_use(file);
+    let (line_at, before, after) = match sea_orm {
+        Some((item, _)) => (index.start(item.span()), "", "\n"),
+        None => (last_use_end, "\n", ""),
+    };
+
+    let mut edits = Vec::new();
+    match missing_events.as_slice() {
+        [] => {}
+        [name] => edits.push(Edit::insert(
+            line_at,
+            format!("{}use rapina::events::{};{}", before, name, after),
+        )),
+        names => edits.push(Edit::insert(
+            line_at,
+            format!(
+                "{}use rapina::events::{{{}}};{}",
+                before,
+                names.join(", "),
+                after
+            ),
+        )),
+    }
+
+    if needs_transaction {
+        match sea_orm {
+            Some((_, group)) => {
+                let close = index.start(group.brace_token.span.close());
+                let separator = if group.items.trailing_punct() {
+                    " "
+                } else {
+                    ", "
+                };
+                edits.push(Edit::insert(
+                    close,
+                    format!("{}TransactionTrait", separator),
+                ));
+            }
+            None => edits.push(Edit::insert(
+                line_at,
+                format!("{}use rapina::sea_orm::TransactionTrait;{}", before, after),
+            )),
+        }
+    }
+
+    edits
+}
+
+// ---------------------------------------------------------------------------
+// Codemod: split-reads-and-writes (0.8.0)
+// ---------------------------------------------------------------------------
+
+/// Replaces `db.conn()` in route handlers with `db.read()` in GET handlers
+/// and `db.write()` in the rest, so reads can go to replicas.
+///
+/// Any handler that writes gets `write()` throughout, which keeps reads
+/// after its writes on the primary.
+fn split_reads_and_writes(source: &str) -> Result<Rewrite, String> {
+    let file = syn::parse_file(source).map_err(|e| format!("failed to parse: {}", e))?;
+    let index = LineIndex::new(source);
+    let mut edits = Vec::new();
+
+    for item in &file.items {
+        let syn::Item::Fn(func) = item else {
+            continue;
+        };
+        let Some(method) = route_method(func) else {
+            continue;
+        };
+        let accessor = if method == "get" { "read" } else { "write" };
+
+        let mut calls = ConnCalls::default();
+        calls.visit_block(&func.block);
+        for span in &calls.methods {
+            edits.push(Edit::replace(
+                index.start(*span),
+                index.end(*span),
+                accessor,
+            ));
+        }
+    }
+
+    Ok(Rewrite {
+        source: apply_edits(source, edits),
+        skipped: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/upgrade");
+
+    fn fixture(path: &str) -> String {
+        fs::read_to_string(Path::new(FIXTURES).join(path)).unwrap()
+    }
+
+    #[test]
+    fn test_version_parse() {
+        assert_eq!(Version::parse("0.7.3").unwrap(), Version::new(0, 7, 3));
+        assert_eq!(Version::parse("0.7").unwrap(), Version::new(0, 7, 0));
+        assert_eq!(Version::parse("^0.6.1").unwrap(), Version::new(0, 6, 1));
+        assert_eq!(Version::parse("=1").unwrap(), Version::new(1, 0, 0));
+        assert_eq!(
+            Version::parse("0.8.0-beta.1").unwrap(),
+            Version::new(0, 8, 0)
+        );
+        assert!(Version::parse("latest").is_err());
+        assert!(Version::parse("1.2.3.4").is_err());
+    }
+
+    #[test]
+    fn test_migrations_are_ordered_and_valid() {
+        let versions: Vec<Version> = MIGRATIONS
+            .iter()
+            .map(|m| Version::parse(m.version).unwrap())
+            .collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_rapina_version_spec() {
+        assert_eq!(
+            rapina_version_spec("[dependencies]\nrapina = \"0.6.0\"\n").unwrap(),
+            Some("0.6.0".to_string())
+        );
+        assert_eq!(
+            rapina_version_spec(
+                "[dependencies]\nrapina = { version = \"0.7\", features = [\"postgres\"] }\n"
+            )
+            .unwrap(),
+            Some("0.7".to_string())
+        );
+        assert_eq!(
+            rapina_version_spec("[dependencies]\nrapina = { path = \"../rapina\" }\n").unwrap(),
+            None
+        );
+        assert!(rapina_version_spec("[dependencies]\nserde = \"1\"\n").is_err());
+    }
+
+    #[test]
+    fn test_bump_rapina_version() {
+        let cargo = "[package]\nname = \"app\"\nversion = \"0.6.0\"\n\n[dependencies]\nrapina = { version = \"0.6.0\", features = [\"sqlite\"] }\n";
+        let bumped = bump_rapina_version(cargo, "0.6.0", "0.7.3").unwrap();
+
+        assert!(bumped.contains("version = \"0.6.0\"\n\n[dependencies]"));
+        assert!(bumped.contains("rapina = { version = \"0.7.3\", features = [\"sqlite\"] }"));
+    }
+
+    #[test]
+    fn test_bump_ignores_dev_dependencies() {
+        let cargo = "[dev-dependencies]\nrapina = \"0.6.0\"\n";
+        assert!(bump_rapina_version(cargo, "0.6.0", "0.7.3").is_none());
+    }
+
+    #[test]
+    fn test_line_index_offsets() {
+        let source = "fn a() {}\n    let é = 1;\n";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.offset(LineColumn { line: 1, column: 3 }), 3);
+        assert_eq!(index.offset(LineColumn { line: 2, column: 9 }), 20);
+        assert_eq!(index.indent(16), "    ");
+    }
+
+    #[test]
+    fn test_apply_edits_keeps_insertion_order() {
+        let edits = vec![
+            Edit::insert(0, "a"),
+            Edit::insert(0, "b"),
+            Edit::replace(1, 2, "X"),
+        ];
+        assert_eq!(apply_edits("123", edits), "ab1X3");
+    }
+
+    #[test]
+    fn test_publish_entity_events_rewrites_scaffolded_handlers() {
+        let before = fixture("publish_entity_events/before/src/todos/handlers.rs");
+        let after = fixture("publish_entity_events/after/src/todos/handlers.rs");
+
+        let rewrite = publish_entity_events(&before).unwrap();
+
+        assert_eq!(rewrite.source, after);
+        assert!(rewrite.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_publish_entity_events_is_idempotent() {
+        let after = fixture("publish_entity_events/after/src/todos/handlers.rs");
+
+        let rewrite = publish_entity_events(&after).unwrap();
+
+        assert_eq!(rewrite.source, after);
+    }
+
+    #[test]
+    fn test_publish_entity_events_reports_customized_handlers() {
+        let before = fixture("publish_entity_events/before/src/posts/handlers.rs");
+
+        let rewrite = publish_entity_events(&before).unwrap();
+
+        assert_eq!(rewrite.source, before);
+        assert_eq!(rewrite.skipped.len(), 1);
+        assert!(rewrite.skipped[0].contains("`delete_post`"));
+        assert!(rewrite.skipped[0].contains("Event::deleted"));
+    }
+
+    #[test]
+    fn test_plan_fixture_project() {
+        let root = Path::new(FIXTURES).join("publish_entity_events/before");
+
+        let plan = plan(&root, None, Some("0.7.3")).unwrap();
+
+        assert_eq!(plan.from, Version::new(0, 6, 0));
+        assert_eq!(plan.applied, vec!["publish-entity-events"]);
+
+        let changed: Vec<String> = plan
+            .changes
+            .iter()
+            .map(|c| {
+                c.path
+                    .strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(changed, vec!["Cargo.toml", "src/todos/handlers.rs"]);
+
+        for change in &plan.changes {
+            let relative = change.path.strip_prefix(&root).unwrap();
+            let expected = fixture(&format!(
+                "publish_entity_events/after/{}",
+                relative.to_string_lossy().replace('\\', "/")
+            ));
+            assert_eq!(change.after, expected, "{}", relative.display());
+        }
+
+        assert!(plan.manual_steps.iter().any(|s| s.contains("delete_post")));
+    }
+
+    #[test]
+    fn test_split_reads_and_writes_rewrites_handlers() {
+        for module in ["todos", "posts"] {
+            let path = format!("src/{}/handlers.rs", module);
+            let before = fixture(&format!("split_reads_and_writes/before/{}", path));
+            let after = fixture(&format!("split_reads_and_writes/after/{}", path));
+
+            let rewrite = split_reads_and_writes(&before).unwrap();
+
+            assert_eq!(rewrite.source, after, "{}", path);
+            assert!(rewrite.skipped.is_empty());
+            assert_eq!(split_reads_and_writes(&after).unwrap().source, after);
+        }
+    }
+
+    #[test]
+    fn test_split_reads_and_writes_ignores_non_handlers() {
+        let source = "fn helper(db: &Db) {\n    db.conn();\n}\n";
+
+        assert_eq!(split_reads_and_writes(source).unwrap().source, source);
+    }
+
+    #[test]
+    fn test_plan_split_reads_and_writes_fixture_project() {
+        let root = Path::new(FIXTURES).join("split_reads_and_writes/before");
+
+        let plan = plan(&root, None, Some("0.8.0")).unwrap();
+
+        assert_eq!(plan.from, Version::new(0, 7, 3));
+        assert_eq!(plan.applied, vec!["split-reads-and-writes"]);
+        assert!(plan.manual_steps.iter().any(|s| s.contains("Paginate")));
+        assert_eq!(plan.changes.len(), 3);
+        for change in &plan.changes {
+            let relative = change.path.strip_prefix(&root).unwrap();
+            let expected = fixture(&format!(
+                "split_reads_and_writes/after/{}",
+                relative.to_string_lossy().replace('\\', "/")
+            ));
+            assert_eq!(change.after, expected, "{}", relative.display());
+        }
+    }
+
+    #[test]
+    fn test_plan_runs_migrations_in_order() {
+        let root = Path::new(FIXTURES).join("publish_entity_events/before");
+
+        let plan = plan(&root, None, Some("0.8.0")).unwrap();
+
+        assert_eq!(
+            plan.applied,
+            vec!["publish-entity-events", "split-reads-and-writes"]
+        );
+        let handlers = plan
+            .changes
+            .iter()
+            .find(|c| c.path.ends_with("src/todos/handlers.rs"))
+            .unwrap();
+        assert_eq!(
+            handlers.after,
+            fixture("split_reads_and_writes/after/src/todos/handlers.rs")
+        );
+    }
+
+    #[test]
+    fn test_plan_up_to_date_project() {
+        let root = Path::new(FIXTURES).join("publish_entity_events/after");
+
+        let plan = plan(&root, None, Some("0.7.3")).unwrap();
+
+        assert!(plan.applied.is_empty());
+        assert!(plan.changes.is_empty());
+    }
+
+    #[test]
+    fn test_plan_refuses_downgrade() {
+        let root = Path::new(FIXTURES).join("publish_entity_events/after");
+
+        assert!(plan(&root, None, Some("0.6.0")).is_err());
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let change = FileChange {
+            path: PathBuf::from("src/a.rs"),
+            before: "one\ntwo\n".to_string(),
+            after: "one\nthree\n".to_string(),
+        };
+        let diff = unified_diff(&change);
+
+        assert!(diff.starts_with("--- a/src/a.rs\n+++ b/src/a.rs\n"));
+        assert!(diff.contains("-two\n"));
+        assert!(diff.contains("+three\n"));
+    }
+}
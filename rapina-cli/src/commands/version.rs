@@ -0,0 +1,115 @@
+//! Show the build info of a running application.
+
+use crate::common::urls;
+use crate::output;
+use colored::Colorize;
+use serde::Deserialize;
+use std::process::Command;
+
+#[derive(Deserialize)]
+struct BuildInfo {
+    name: String,
+    version: String,
+    #[serde(default)]
+    git_sha: Option<String>,
+    #[serde(default)]
+    build_timestamp: Option<String>,
+    #[serde(default)]
+    rustc_version: Option<String>,
+}
+
+pub struct RemoteVersionConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+/// Print what the running application was built from.
+pub fn execute_remote(config: RemoteVersionConfig) -> Result<(), String> {
+    let url = urls::build_version_url(&config.host, config.port);
+    output::detail(format!("GET {}", url));
+    let info = fetch_build_info(&url)?;
+
+    output::record(
+        "version",
+        serde_json::json!({
+            "name": info.name,
+            "version": info.version,
+            "git_sha": info.git_sha,
+            "build_timestamp": info.build_timestamp,
+            "rustc_version": info.rustc_version,
+        }),
+    );
+
+    let unknown = || "unknown".dimmed().to_string();
+    output::blank();
+    output::info(format!("  {} {}", info.name.bold(), info.version.cyan()));
+    output::info(format!(
+        "  {:<8} {}",
+        "commit",
+        info.git_sha.clone().unwrap_or_else(unknown)
+    ));
+    output::info(format!(
+        "  {:<8} {}",
+        "built",
+        info.build_timestamp.clone().unwrap_or_else(unknown)
+    ));
+    output::info(format!(
+        "  {:<8} {}",
+        "rustc",
+        info.rustc_version.clone().unwrap_or_else(unknown)
+    ));
+    output::blank();
+
+    Ok(())
+}
+
+/// Fetch build info from the running application.
+fn fetch_build_info(url: &str) -> Result<BuildInfo, String> {
+    let output = Command::new("curl")
+        .args(["-s", "-f", url])
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to fetch build info from {}. Is the server running with build_info() and introspection enabled?",
+            url
+        ));
+    }
+
+    let body =
+        String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 response: {}", e))?;
+
+    parse_build_info(&body)
+}
+
+fn parse_build_info(body: &str) -> Result<BuildInfo, String> {
+    serde_json::from_str(body).map_err(|e| format!("Invalid JSON response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_build_info() {
+        let info = parse_build_info(
+            r#"{"name":"billing","version":"1.4.0","git_sha":"3f2a9c1","build_timestamp":"2026-01-02T03:04:05Z","rustc_version":null}"#,
+        )
+        .unwrap();
+
+        assert_eq!(info.name, "billing");
+        assert_eq!(info.version, "1.4.0");
+        assert_eq!(info.git_sha.as_deref(), Some("3f2a9c1"));
+        assert_eq!(
+            info.build_timestamp.as_deref(),
+            Some("2026-01-02T03:04:05Z")
+        );
+        assert_eq!(info.rustc_version, None);
+    }
+
+    #[test]
+    fn test_parse_build_info_rejects_other_json() {
+        assert!(parse_build_info(r#"[{"method":"GET"}]"#).is_err());
+    }
+}
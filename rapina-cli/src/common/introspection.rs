@@ -0,0 +1,33 @@
+//! Requests to the running application's `/__rapina` endpoints.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs `curl` against `url`, sending `token` as a bearer token when given.
+///
+/// The header is passed on stdin, so the token doesn't show up in the
+/// process list.
+pub fn fetch(url: &str, token: Option<&str>) -> Result<Output, String> {
+    let mut command = Command::new("curl");
+    command.args(["-s", "-f", url]);
+    let Some(token) = token else {
+        return command
+            .output()
+            .map_err(|e| format!("Failed to run curl: {}", e));
+    };
+
+    let mut child = command
+        .args(["-H", "@-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "Authorization: Bearer {}", token)
+            .map_err(|e| format!("Failed to pass the token to curl: {}", e))?;
+    }
+    child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run curl: {}", e))
+}
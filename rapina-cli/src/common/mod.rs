@@ -1 +1,2 @@
+pub mod introspection;
 pub mod urls;
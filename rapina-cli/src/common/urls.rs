@@ -7,3 +7,11 @@ pub fn build_routes_url(host: &str, port: u16) -> String {
 pub fn build_openapi_url(host: &str, port: u16) -> String {
     format!("http://{}:{}/{}/openapi.json", host, port, PATH_BASE)
 }
+
+pub fn build_middleware_url(host: &str, port: u16) -> String {
+    format!("http://{}:{}/{}/middleware", host, port, PATH_BASE)
+}
+
+pub fn build_version_url(host: &str, port: u16) -> String {
+    format!("http://{}:{}/{}/version", host, port, PATH_BASE)
+}
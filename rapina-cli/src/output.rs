@@ -0,0 +1,227 @@
+//! Terminal and machine-readable output for CLI commands.
+//!
+//! Commands report what they do through these functions instead of printing
+//! directly, so the global `--quiet`, `--verbose`, `--no-color` and
+//! `--log-format` flags apply to all of them.
+//!
+//! With `--log-format json`, file changes, warnings, skipped items and errors
+//! are written to stdout as one JSON object per line, e.g.
+//! `{"event":"created","path":"src/users/mod.rs"}`. Free-form text such as
+//! summaries and next steps is only printed in text mode.
+//!
+//! A few things are printed as is: data a command exists to produce
+//! (`openapi export` without `--output`, `schema diagram` without `--out`,
+//! console query results in text mode), compiler output passed through to
+//! stderr, and interactive prompts, which also go to stderr.
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde_json::{Value, json};
+
+/// How output is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Colored text for people
+    #[default]
+    Text,
+    /// One JSON object per event, for tooling
+    Json,
+}
+
+/// How much is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Only warnings and errors
+    Quiet,
+    #[default]
+    Normal,
+    /// Everything, including detail lines
+    Verbose,
+}
+
+/// Output settings taken from the global flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputConfig {
+    pub format: LogFormat,
+    pub verbosity: Verbosity,
+    pub color: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            verbosity: Verbosity::Normal,
+            color: true,
+        }
+    }
+}
+
+static CONFIG: OnceLock<OutputConfig> = OnceLock::new();
+
+/// Applies the output settings for the rest of the process.
+///
+/// Colors are turned off when `config.color` is false and always in JSON
+/// mode, so messages embedded in events are plain text.
+pub fn init(config: OutputConfig) {
+    if !config.color || config.format == LogFormat::Json {
+        colored::control::set_override(false);
+    }
+    let _ = CONFIG.set(config);
+}
+
+fn config() -> OutputConfig {
+    CONFIG.get().copied().unwrap_or_default()
+}
+
+/// Whether colors should be used, given `--no-color` and the `NO_COLOR`
+/// environment variable (any non-empty value disables them).
+pub fn color_enabled(no_color_flag: bool, no_color_env: Option<&str>) -> bool {
+    !no_color_flag && no_color_env.is_none_or(str::is_empty)
+}
+
+fn shows(verbosity: Verbosity) -> bool {
+    config().verbosity >= verbosity
+}
+
+/// Whether events are written as JSON.
+pub fn is_json() -> bool {
+    config().format == LogFormat::Json
+}
+
+fn emit(event: Value) {
+    println!("{}", event);
+}
+
+/// Writes a structured record, such as a listed route, in JSON mode. Does
+/// nothing in text mode, where the caller prints its own rendering.
+pub fn record(event: &str, fields: Value) {
+    if !is_json() {
+        return;
+    }
+    let mut record = json!({ "event": event });
+    if let (Some(record), Value::Object(fields)) = (record.as_object_mut(), fields) {
+        record.extend(fields);
+    }
+    emit(record);
+}
+
+/// Reports a file or directory that was created.
+pub fn created(path: &str) {
+    file_event("created", "Created", path);
+}
+
+/// Reports a file that was changed.
+pub fn updated(path: &str) {
+    file_event("updated", "Updated", path);
+}
+
+fn file_event(event: &str, verb: &str, path: &str) {
+    if !shows(Verbosity::Normal) {
+        return;
+    }
+    if is_json() {
+        emit(json!({ "event": event, "path": path }));
+    } else {
+        println!("  {} {} {}", "✓".green(), verb, path.cyan());
+    }
+}
+
+/// Reports a step that completed, such as "Discovered 4 table(s)".
+pub fn success(message: impl Display) {
+    if !shows(Verbosity::Normal) {
+        return;
+    }
+    if is_json() {
+        emit(json!({ "event": "success", "message": message.to_string() }));
+    } else {
+        println!("  {} {}", "✓".green(), message);
+    }
+}
+
+/// Reports something that needs the user's attention. Printed to stderr in
+/// text mode, even with `--quiet`.
+pub fn warn(message: impl Display) {
+    if is_json() {
+        emit(json!({ "event": "warning", "message": message.to_string() }));
+    } else {
+        eprintln!("  {} {}", "warn:".yellow(), message);
+    }
+}
+
+/// Reports an item that was left out, e.g. a table without a primary key.
+pub fn skipped(kind: &str, name: &str, reason: &str) {
+    if is_json() {
+        emit(json!({ "event": "skipped", "kind": kind, "name": name, "reason": reason }));
+    } else {
+        eprintln!(
+            "  {} {} {:?} skipped -- {}",
+            "warn:".yellow(),
+            kind,
+            name,
+            reason
+        );
+    }
+}
+
+/// Reports the error a command failed with. Printed to stderr in text mode.
+pub fn error(message: impl Display) {
+    if is_json() {
+        emit(json!({ "event": "error", "message": message.to_string() }));
+    } else {
+        eprintln!("{} {}", "Error:".red().bold(), message);
+    }
+}
+
+/// Prints a line of free-form text. Not printed with `--quiet` or in JSON mode.
+pub fn info(line: impl Display) {
+    if shows(Verbosity::Normal) && !is_json() {
+        println!("{}", line);
+    }
+}
+
+/// Prints an empty line. Not printed with `--quiet` or in JSON mode.
+pub fn blank() {
+    info("");
+}
+
+/// Prints a detail line, only with `--verbose`.
+pub fn detail(message: impl Display) {
+    if !shows(Verbosity::Verbose) {
+        return;
+    }
+    if is_json() {
+        emit(json!({ "event": "detail", "message": message.to_string() }));
+    } else {
+        println!("    {}", message.to_string().dimmed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_enabled() {
+        assert!(color_enabled(false, None));
+        assert!(color_enabled(false, Some("")));
+        assert!(!color_enabled(false, Some("1")));
+        assert!(!color_enabled(true, None));
+    }
+
+    #[test]
+    fn test_verbosity_order() {
+        assert!(Verbosity::Verbose > Verbosity::Normal);
+        assert!(Verbosity::Normal > Verbosity::Quiet);
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = OutputConfig::default();
+        assert_eq!(config.format, LogFormat::Text);
+        assert_eq!(config.verbosity, Verbosity::Normal);
+        assert!(config.color);
+    }
+}
@@ -0,0 +1,8 @@
+use rapina::prelude::*;
+
+schema! {
+    HTTPLog {
+        path: String,
+        status: i32,
+    }
+}
@@ -0,0 +1,8 @@
+use rapina::prelude::*;
+
+schema! {
+    Post {
+        title: String,
+        body: Text,
+    }
+}
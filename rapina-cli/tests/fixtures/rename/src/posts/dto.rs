@@ -0,0 +1,13 @@
+use rapina::prelude::*;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreatePost {
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct UpdatePost {
+    pub title: Option<String>,
+    pub body: Option<String>,
+}
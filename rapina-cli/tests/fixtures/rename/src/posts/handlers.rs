@@ -0,0 +1,45 @@
+use rapina::prelude::*;
+use rapina::database::{Db, DbError};
+use rapina::sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, Set};
+
+use crate::entity::Post;
+use crate::entity::post::{ActiveModel, Model};
+
+use super::dto::{CreatePost, UpdatePost};
+use super::error::PostError;
+
+#[post("/posts")]
+#[errors(PostError)]
+pub async fn create_post(db: Db, body: Json<CreatePost>) -> Result<Json<Model>> {
+    let input = body.into_inner();
+    let item = ActiveModel {
+        title: Set(input.title),
+        body: Set(input.body),
+        ..Default::default()
+    };
+    let result = item.insert(db.conn()).await.map_err(DbError)?;
+    Ok(Json(result))
+}
+
+#[put("/posts/:id")]
+#[errors(PostError)]
+pub async fn update_post(db: Db, id: Path<i32>, body: Json<UpdatePost>) -> Result<Json<Model>> {
+    let id = id.into_inner();
+    let item = Post::find_by_id(id)
+        .one(db.conn())
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Post {} not found", id)))?;
+
+    let update = body.into_inner();
+    let mut active: ActiveModel = item.into_active_model();
+    if let Some(val) = update.title {
+        active.title = Set(val);
+    }
+    if let Some(val) = update.body {
+        active.body = Set(val);
+    }
+
+    let result = active.update(db.conn()).await.map_err(DbError)?;
+    Ok(Json(result))
+}
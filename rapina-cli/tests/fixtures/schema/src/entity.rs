@@ -0,0 +1,34 @@
+use rapina::prelude::*;
+
+schema! {
+    User {
+        #[unique]
+        email: String,
+        name: String,
+        posts: Vec<Post>,
+    }
+
+    Post {
+        title: String,
+        body: Text,
+        author: User,
+        editor: Option<User>,
+        tags: Vec<Tag>,
+        comments: Vec<Comment>,
+    }
+
+    #[primary_key(code)]
+    #[timestamps(none)]
+    Tag {
+        code: String,
+        #[unique]
+        label: String,
+        posts: Vec<Post>,
+    }
+
+    #[timestamps(created_at)]
+    Comment {
+        body: Text,
+        post: Post,
+    }
+}
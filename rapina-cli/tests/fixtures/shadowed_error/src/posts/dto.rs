@@ -0,0 +1,17 @@
+use rapina::schemars::{self, JsonSchema};
+use serde::Deserialize;
+use rapina::sea_orm::prelude::Json;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreatePost {
+    pub title: String,
+    pub published: bool,
+    pub metadata: Json,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct UpdatePost {
+    pub title: Option<String>,
+    pub published: Option<bool>,
+    pub metadata: Option<Json>,
+}
@@ -0,0 +1,37 @@
+use rapina::database::DbError;
+use rapina::error::{DocumentedError, Error, ErrorVariant, IntoApiError};
+
+pub enum PostError {
+    DbError(DbError),
+}
+
+impl IntoApiError for PostError {
+    fn into_api_error(self) -> Error {
+        match self {
+            PostError::DbError(e) => e.into_api_error(),
+        }
+    }
+}
+
+impl DocumentedError for PostError {
+    fn error_variants() -> Vec<ErrorVariant> {
+        vec![
+            ErrorVariant {
+                status: 404,
+                code: "NOT_FOUND",
+                description: "Post not found",
+            },
+            ErrorVariant {
+                status: 500,
+                code: "DATABASE_ERROR",
+                description: "Database operation failed",
+            },
+        ]
+    }
+}
+
+impl From<DbError> for PostError {
+    fn from(e: DbError) -> Self {
+        PostError::DbError(e)
+    }
+}
@@ -0,0 +1,101 @@
+use rapina::database::{Db, DbError};
+use rapina::error::{Error, Result};
+use rapina::events::{Event, Events};
+use rapina::extract::{Json, Path};
+use rapina::pagination::{Paginate, Paginated};
+use rapina::sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, Set, TransactionTrait};
+use rapina::{delete, get, post, put};
+
+use crate::entity::Post;
+use crate::entity::post::{ActiveModel, Model};
+
+use super::dto::{CreatePost, UpdatePost};
+use super::error::PostError;
+
+#[get("/posts")]
+#[errors(PostError)]
+pub async fn list_posts(db: Db, page: Paginate) -> Result<Paginated<Model>> {
+    let items = page.exec(Post::find(), db.read()).await?;
+    Ok(items)
+}
+
+#[get("/posts/:id")]
+#[errors(PostError)]
+pub async fn get_post(db: Db, id: Path<i32>) -> Result<Json<Model>> {
+    let id = id.into_inner();
+    let item = Post::find_by_id(id)
+        .one(db.read())
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Post {} not found", id)))?;
+    Ok(Json(item))
+}
+
+#[post("/posts")]
+#[errors(PostError)]
+pub async fn create_post(db: Db, events: Events, body: Json<CreatePost>) -> Result<Json<Model>> {
+    let input = body.into_inner();
+    let item = ActiveModel {
+        title: Set(input.title),
+        published: Set(input.published),
+        metadata: Set(input.metadata),
+        ..Default::default()
+    };
+    let txn = db.write().begin().await.map_err(DbError)?;
+    let result = item.insert(&txn).await.map_err(DbError)?;
+    let event = Event::created("post", result.id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(result))
+}
+
+#[put("/posts/:id")]
+#[errors(PostError)]
+pub async fn update_post(db: Db, events: Events, id: Path<i32>, body: Json<UpdatePost>) -> Result<Json<Model>> {
+    let id = id.into_inner();
+    let txn = db.write().begin().await.map_err(DbError)?;
+    let item = Post::find_by_id(id)
+        .one(&txn)
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Post {} not found", id)))?;
+
+    let update = body.into_inner();
+    let mut active: ActiveModel = item.into_active_model();
+    if let Some(val) = update.title {
+        active.title = Set(val);
+    }
+    if let Some(val) = update.published {
+        active.published = Set(val);
+    }
+    if let Some(val) = update.metadata {
+        active.metadata = Set(val);
+    }
+
+    let result = active.update(&txn).await.map_err(DbError)?;
+    let event = Event::updated("post", id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(result))
+}
+
+#[delete("/posts/:id")]
+#[errors(PostError)]
+pub async fn delete_post(db: Db, events: Events, id: Path<i32>) -> Result<Json<serde_json::Value>> {
+    let id = id.into_inner();
+    let txn = db.write().begin().await.map_err(DbError)?;
+    let result = Post::delete_by_id(id)
+        .exec(&txn)
+        .await
+        .map_err(DbError)?;
+    if result.rows_affected == 0 {
+        return Err(Error::not_found(format!("Post {} not found", id)));
+    }
+    let event = Event::deleted("post", id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
@@ -0,0 +1,3 @@
+pub mod dto;
+pub mod error;
+pub mod handlers;
@@ -0,0 +1,11 @@
+//! An app prelude exporting its own `Error`, `Result` and `Json`, glob
+//! imported into every module next to the generated code.
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+pub struct Json(pub String);
@@ -0,0 +1,24 @@
+use rapina::prelude::*;
+use rapina::database::{Db, DbError};
+use rapina::sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, Set};
+
+use crate::entity::Post;
+
+use super::error::PostError;
+
+/// Posts are archived rather than deleted.
+#[delete("/posts/:post_id")]
+#[errors(PostError)]
+pub async fn delete_post(db: Db, post_id: Path<i32>) -> Result<StatusCode> {
+    let post_id = post_id.into_inner();
+    let post = Post::find_by_id(post_id)
+        .one(db.conn())
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Post {} not found", post_id)))?;
+
+    let mut active = post.into_active_model();
+    active.archived = Set(true);
+    active.update(db.conn()).await.map_err(DbError)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,94 @@
+use rapina::prelude::*;
+use rapina::database::{Db, DbError};
+use rapina::events::{Event, Events};
+use rapina::sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, Set, TransactionTrait};
+
+use crate::entity::Todo;
+use crate::entity::todo::{ActiveModel, Model};
+
+use super::dto::{CreateTodo, UpdateTodo};
+use super::error::TodoError;
+
+#[get("/todos")]
+#[errors(TodoError)]
+pub async fn list_todos(db: Db) -> Result<Json<Vec<Model>>> {
+    let items = Todo::find().all(db.read()).await.map_err(DbError)?;
+    Ok(Json(items))
+}
+
+#[get("/todos/:id")]
+#[errors(TodoError)]
+pub async fn get_todo(db: Db, id: Path<i32>) -> Result<Json<Model>> {
+    let id = id.into_inner();
+    let item = Todo::find_by_id(id)
+        .one(db.read())
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Todo {} not found", id)))?;
+    Ok(Json(item))
+}
+
+#[post("/todos")]
+#[errors(TodoError)]
+pub async fn create_todo(db: Db, events: Events, body: Json<CreateTodo>) -> Result<Json<Model>> {
+    let input = body.into_inner();
+    let item = ActiveModel {
+        title: Set(input.title),
+        done: Set(input.done),
+        ..Default::default()
+    };
+    let txn = db.write().begin().await.map_err(DbError)?;
+    let result = item.insert(&txn).await.map_err(DbError)?;
+    let event = Event::created("todo", result.id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(result))
+}
+
+#[put("/todos/:id")]
+#[errors(TodoError)]
+pub async fn update_todo(db: Db, events: Events, id: Path<i32>, body: Json<UpdateTodo>) -> Result<Json<Model>> {
+    let id = id.into_inner();
+    let txn = db.write().begin().await.map_err(DbError)?;
+    let item = Todo::find_by_id(id)
+        .one(&txn)
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Todo {} not found", id)))?;
+
+    let update = body.into_inner();
+    let mut active: ActiveModel = item.into_active_model();
+    if let Some(val) = update.title {
+        active.title = Set(val);
+    }
+    if let Some(val) = update.done {
+        active.done = Set(val);
+    }
+
+    let result = active.update(&txn).await.map_err(DbError)?;
+    let event = Event::updated("todo", id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(result))
+}
+
+#[delete("/todos/:id")]
+#[errors(TodoError)]
+pub async fn delete_todo(db: Db, events: Events, id: Path<i32>) -> Result<Json<serde_json::Value>> {
+    let id = id.into_inner();
+    let txn = db.write().begin().await.map_err(DbError)?;
+    let result = Todo::delete_by_id(id)
+        .exec(&txn)
+        .await
+        .map_err(DbError)?;
+    if result.rows_affected == 0 {
+        return Err(Error::not_found(format!("Todo {} not found", id)));
+    }
+    let event = Event::deleted("todo", id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
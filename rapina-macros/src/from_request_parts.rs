@@ -0,0 +1,407 @@
+//! `#[derive(FromRequestParts)]` for structs assembled from headers, query
+//! parameters, path parameters and application state.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitInt, LitStr, Type};
+
+/// Statuses a field may reject with, mirroring the `Error` helpers.
+const REJECTION_STATUSES: &[u16] = &[400, 401, 403, 404, 422];
+
+enum Source {
+    Header(String),
+    Query(String),
+    Path(String),
+    State,
+}
+
+impl Source {
+    fn describe(&self) -> &'static str {
+        match self {
+            Source::Header(_) => "header",
+            Source::Query(_) => "query parameter",
+            Source::Path(_) => "path parameter",
+            Source::State => "state",
+        }
+    }
+}
+
+struct FieldAttr {
+    source: Source,
+    rejection: u16,
+}
+
+pub fn derive_from_request_parts_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = match syn::parse2(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error(),
+    };
+    match expand(&input) {
+        Ok(tokens) => tokens,
+        Err(err) => err.to_compile_error(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "FromRequestParts can only be derived for structs",
+            ));
+        }
+    };
+
+    let body = match fields {
+        Fields::Named(named) => {
+            let mut inits = Vec::new();
+            for field in &named.named {
+                let ident = field.ident.as_ref().unwrap();
+                let value = field_value(ident, &field.ty, &field.attrs)?;
+                inits.push(quote! { #ident: #value });
+            }
+            quote! { Self { #(#inits),* } }
+        }
+        Fields::Unit => quote! { Self },
+        Fields::Unnamed(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "FromRequestParts can only be derived for structs with named fields",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics rapina::extract::FromRequestParts for #name #ty_generics #where_clause {
+            async fn from_request_parts(
+                parts: &rapina::http::request::Parts,
+                params: &rapina::extract::PathParams,
+                state: &std::sync::Arc<rapina::state::AppState>,
+            ) -> std::result::Result<Self, rapina::error::Error> {
+                let _ = (parts, params, state);
+                Ok(#body)
+            }
+        }
+    })
+}
+
+fn field_value(
+    ident: &syn::Ident,
+    ty: &Type,
+    attrs: &[syn::Attribute],
+) -> syn::Result<TokenStream> {
+    let Some(attr) = parse_field_attr(ident, attrs)? else {
+        return Ok(quote! {
+            <#ty as rapina::extract::FromRequestParts>::from_request_parts(parts, params, state).await?
+        });
+    };
+
+    let (inner, optional) = match option_inner(ty) {
+        Some(inner) => (inner, true),
+        None => (ty, false),
+    };
+
+    let value = match &attr.source {
+        Source::Header(key) => quote! { rapina::extract::derive::header(parts, #key) },
+        Source::Query(key) => {
+            quote! { rapina::extract::derive::query(parts, #key).as_deref() }
+        }
+        Source::Path(key) => quote! { rapina::extract::derive::path(params, #key) },
+        Source::State => {
+            return Ok(if optional {
+                quote! { rapina::extract::derive::state::<#inner>(state).ok() }
+            } else {
+                quote! { rapina::extract::derive::state::<#inner>(state)? }
+            });
+        }
+    };
+
+    let key = match &attr.source {
+        Source::Header(key) | Source::Query(key) | Source::Path(key) => key,
+        Source::State => unreachable!(),
+    };
+    let source = attr.source.describe();
+    let status = attr.rejection;
+
+    Ok(if optional {
+        quote! {
+            rapina::extract::derive::optional::<#inner>(#source, #key, #value, #status)?
+        }
+    } else {
+        quote! {
+            rapina::extract::derive::required::<#inner>(#source, #key, #value, #status)?
+        }
+    })
+}
+
+fn parse_field_attr(
+    ident: &syn::Ident,
+    attrs: &[syn::Attribute],
+) -> syn::Result<Option<FieldAttr>> {
+    let mut found: Option<FieldAttr> = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("from")) {
+        if found.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "duplicate #[from] attribute; a field can only be read from one source",
+            ));
+        }
+
+        let mut source: Option<Source> = None;
+        let mut rejection: Option<(u16, Span)> = None;
+
+        attr.parse_nested_meta(|meta| {
+            let key = meta
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+
+            if key == "rejection" {
+                let lit: LitInt = meta.value()?.parse()?;
+                let status: u16 = lit.base10_parse()?;
+                if !REJECTION_STATUSES.contains(&status) {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        format!(
+                            "unsupported rejection status {}; expected 400, 401, 403, 404 or 422",
+                            status
+                        ),
+                    ));
+                }
+                rejection = Some((status, lit.span()));
+                return Ok(());
+            }
+
+            let parsed = match key.as_str() {
+                "header" => Source::Header(
+                    source_name(&meta)?.unwrap_or_else(|| ident.to_string().replace('_', "-")),
+                ),
+                "query" => Source::Query(source_name(&meta)?.unwrap_or_else(|| ident.to_string())),
+                "path" => Source::Path(source_name(&meta)?.unwrap_or_else(|| ident.to_string())),
+                "state" => Source::State,
+                _ => {
+                    return Err(meta.error(
+                        "unknown #[from] option; expected `header`, `query`, `path`, `state` or `rejection`",
+                    ));
+                }
+            };
+
+            if source.is_some() {
+                return Err(meta.error("a field can only be read from one source"));
+            }
+            source = Some(parsed);
+            Ok(())
+        })?;
+
+        let Some(source) = source else {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[from] needs a source: `header`, `query`, `path` or `state`",
+            ));
+        };
+
+        if let (Source::State, Some((_, span))) = (&source, rejection) {
+            return Err(syn::Error::new(
+                span,
+                "`rejection` has no effect on state fields; missing state is a server error",
+            ));
+        }
+
+        found = Some(FieldAttr {
+            source,
+            rejection: rejection.map(|(status, _)| status).unwrap_or(400),
+        });
+    }
+
+    Ok(found)
+}
+
+/// Reads the `= "name"` part of `header = "name"`, if present.
+fn source_name(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(syn::Token![=]) {
+        let lit: LitStr = meta.value()?.parse()?;
+        Ok(Some(lit.value()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns `T` when `ty` is `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::derive_from_request_parts_impl;
+    use quote::quote;
+
+    fn expand(input: proc_macro2::TokenStream) -> String {
+        derive_from_request_parts_impl(input).to_string()
+    }
+
+    #[test]
+    fn test_header_field() {
+        let output = expand(quote! {
+            struct Caller {
+                #[from(header = "x-user-id")]
+                user_id: u64,
+            }
+        });
+
+        assert!(output.contains("impl rapina :: extract :: FromRequestParts for Caller"));
+        assert!(output.contains("rapina :: extract :: derive :: required :: < u64 >"));
+        assert!(output.contains("\"header\" , \"x-user-id\""));
+        assert!(output.contains("rapina :: extract :: derive :: header (parts , \"x-user-id\")"));
+        assert!(output.contains("400u16"));
+    }
+
+    #[test]
+    fn test_header_name_defaults_to_field_name() {
+        let output = expand(quote! {
+            struct Caller {
+                #[from(header)]
+                x_request_id: String,
+            }
+        });
+
+        assert!(output.contains("\"x-request-id\""));
+    }
+
+    #[test]
+    fn test_optional_query_field() {
+        let output = expand(quote! {
+            struct Scope {
+                #[from(query = "tenant")]
+                tenant: Option<String>,
+            }
+        });
+
+        assert!(output.contains("rapina :: extract :: derive :: optional :: < String >"));
+        assert!(output.contains("\"query parameter\" , \"tenant\""));
+        assert!(output.contains("derive :: query (parts , \"tenant\") . as_deref ()"));
+    }
+
+    #[test]
+    fn test_path_field_with_rejection() {
+        let output = expand(quote! {
+            struct Target {
+                #[from(path = "id", rejection = 404)]
+                id: i32,
+            }
+        });
+
+        assert!(output.contains("\"path parameter\" , \"id\""));
+        assert!(output.contains("derive :: path (params , \"id\")"));
+        assert!(output.contains("404u16"));
+    }
+
+    #[test]
+    fn test_state_field() {
+        let output = expand(quote! {
+            struct Deps {
+                #[from(state)]
+                config: AppConfig,
+                #[from(state)]
+                cache: Option<Cache>,
+            }
+        });
+
+        assert!(output.contains("derive :: state :: < AppConfig > (state) ?"));
+        assert!(output.contains("derive :: state :: < Cache > (state) . ok ()"));
+    }
+
+    #[test]
+    fn test_unattributed_field_delegates() {
+        let output = expand(quote! {
+            struct Request {
+                ctx: Context,
+            }
+        });
+
+        assert!(output.contains(
+            "< Context as rapina :: extract :: FromRequestParts > :: from_request_parts (parts , params , state) . await ?"
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_rejection_status() {
+        let output = expand(quote! {
+            struct Caller {
+                #[from(header = "x-user-id", rejection = 500)]
+                user_id: u64,
+            }
+        });
+
+        assert!(output.contains("compile_error"));
+        assert!(output.contains("unsupported rejection status 500"));
+    }
+
+    #[test]
+    fn test_rejection_on_state_field() {
+        let output = expand(quote! {
+            struct Deps {
+                #[from(state, rejection = 401)]
+                config: AppConfig,
+            }
+        });
+
+        assert!(output.contains("compile_error"));
+        assert!(output.contains("no effect on state fields"));
+    }
+
+    #[test]
+    fn test_multiple_sources() {
+        let output = expand(quote! {
+            struct Caller {
+                #[from(header = "x-user-id", query = "user_id")]
+                user_id: u64,
+            }
+        });
+
+        assert!(output.contains("compile_error"));
+        assert!(output.contains("only be read from one source"));
+    }
+
+    #[test]
+    fn test_unknown_option() {
+        let output = expand(quote! {
+            struct Caller {
+                #[from(cookie = "session")]
+                session: String,
+            }
+        });
+
+        assert!(output.contains("compile_error"));
+        assert!(output.contains("unknown #[from] option"));
+    }
+
+    #[test]
+    fn test_tuple_struct_rejected() {
+        let output = expand(quote! {
+            struct Caller(u64);
+        });
+
+        assert!(output.contains("compile_error"));
+        assert!(output.contains("structs with named fields"));
+    }
+}
@@ -2,7 +2,9 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{FnArg, ItemFn, LitStr, Pat};
 
+mod from_request_parts;
 mod schema;
+mod sql;
 
 #[proc_macro_attribute]
 pub fn get(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -19,6 +21,11 @@ pub fn put(attr: TokenStream, item: TokenStream) -> TokenStream {
     route_macro("PUT", attr, item)
 }
 
+#[proc_macro_attribute]
+pub fn patch(attr: TokenStream, item: TokenStream) -> TokenStream {
+    route_macro("PATCH", attr, item)
+}
+
 #[proc_macro_attribute]
 pub fn delete(attr: TokenStream, item: TokenStream) -> TokenStream {
     route_macro("DELETE", attr, item)
@@ -65,12 +72,63 @@ pub fn public(_attr: TokenStream, item: TokenStream) -> TokenStream {
     .into()
 }
 
+/// Restricts a route to callers an authorization policy allows.
+///
+/// Takes a value implementing `rapina::auth::Policy`, or a combination
+/// built with `all(...)`, `any(...)` and `not(...)`. The policy runs after
+/// authentication and before any extractor: requests without an
+/// authenticated user get 401, and denials get 403 with the policy's name as
+/// the error code.
+///
+/// # Example
+///
+/// ```ignore
+/// use rapina::prelude::*;
+///
+/// #[delete("/posts/:id")]
+/// #[authorize(any(AdminOnly, Owner))]
+/// async fn delete_post(id: Path<u64>) -> StatusCode {
+///     StatusCode::NO_CONTENT
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn authorize(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut func: ItemFn = match syn::parse(item) {
+        Ok(func) => func,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let has_route = func.attrs.iter().any(|attr| {
+        attr.path().segments.last().is_some_and(|s| {
+            matches!(
+                s.ident.to_string().as_str(),
+                "get" | "post" | "put" | "patch" | "delete"
+            )
+        })
+    });
+    let attr: proc_macro2::TokenStream = attr.into();
+    if !has_route {
+        return syn::Error::new_spanned(
+            &func.sig.ident,
+            "#[authorize] must be used on a handler with a route macro like #[get(\"/path\")]",
+        )
+        .to_compile_error()
+        .into();
+    }
+    // Move below the route macro, which reads and removes it
+    func.attrs.push(syn::parse_quote!(#[authorize(#attr)]));
+    quote!(#func).into()
+}
+
 fn route_macro_core(
     method: &str,
     attr: proc_macro2::TokenStream,
     item: proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
-    let path: LitStr = syn::parse2(attr).expect("expected path as string literal");
+    let route_args: RouteArgs = match syn::parse2(attr) {
+        Ok(route_args) => route_args,
+        Err(err) => return err.to_compile_error(),
+    };
+    let path = &route_args.path;
     let path_str = path.value();
     let mut func: ItemFn = syn::parse2(item).expect("expected function");
 
@@ -84,6 +142,12 @@ fn route_macro_core(
     // Extract #[errors(ErrorType)] attribute if present
     let error_type = extract_errors_attr(&mut func.attrs);
 
+    // Extract #[authorize(...)] attribute if present
+    let policy = match extract_authorize_attr(&mut func.attrs) {
+        Ok(policy) => policy,
+        Err(err) => return err.to_compile_error(),
+    };
+
     let error_responses_impl = if let Some(err_type) = &error_type {
         quote! {
             fn error_responses() -> Vec<rapina::error::ErrorVariant> {
@@ -99,7 +163,8 @@ fn route_macro_core(
         if let Some(inner_type) = extract_json_inner_type(return_type) {
             quote! {
                 fn response_schema() -> Option<serde_json::Value> {
-                    Some(serde_json::to_value(rapina::schemars::schema_for!(#inner_type)).unwrap())
+                    use rapina::openapi::{JsonSchemaProbe, OpaqueSchemaProbe};
+                    Some((&rapina::openapi::SchemaProbe::<#inner_type>::new()).response_schema())
                 }
             }
         } else {
@@ -111,6 +176,49 @@ fn route_macro_core(
 
     let args: Vec<_> = func.sig.inputs.iter().collect();
 
+    // Document `?fields=` when the handler takes a FieldSelect<T>
+    let selectable_fields_impl = args
+        .iter()
+        .find_map(|arg| match arg {
+            FnArg::Typed(pat_type) => field_select_type(&pat_type.ty),
+            FnArg::Receiver(_) => None,
+        })
+        .map(|selected| {
+            quote! {
+                fn selectable_fields() -> Option<Vec<String>> {
+                    Some(rapina::fields::FieldSelect::<#selected>::valid_fields())
+                }
+            }
+        })
+        .unwrap_or_default();
+
+    // Work out which route parameter each Path argument binds
+    let path_bindings = match bind_path_params(path, &func_name_str, &args) {
+        Ok(bindings) => bindings,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    let path_schemas_impl = path_schemas_impl(path, &args, &path_bindings);
+
+    // Let startup warn about `State` types that were never registered
+    let state_types: Vec<&syn::Type> = args
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(pat_type) => Some(required_state_types(&pat_type.ty)),
+            FnArg::Receiver(_) => None,
+        })
+        .flatten()
+        .collect();
+    let required_state_impl = if state_types.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn required_state() -> Vec<(std::any::TypeId, &'static str)> {
+                vec![#((std::any::TypeId::of::<#state_types>(), std::any::type_name::<#state_types>())),*]
+            }
+        }
+    };
+
     // Extract return type for type annotation (helps with type inference in async blocks)
     let return_type_annotation = match &func.sig.output {
         syn::ReturnType::Type(_, ty) => quote! { : #ty },
@@ -129,15 +237,25 @@ fn route_macro_core(
         let mut parts_extractions = Vec::new();
         let mut body_extractors: Vec<(syn::Ident, Box<syn::Type>)> = Vec::new();
 
-        for arg in &args {
-            if let FnArg::Typed(pat_type) = arg
-                && let Pat::Ident(pat_ident) = &*pat_type.pat
-            {
-                let arg_name = &pat_ident.ident;
+        for (index, arg) in args.iter().enumerate() {
+            if let FnArg::Typed(pat_type) = arg {
+                // `_` arguments still run their extractor, e.g. `_: Authorize<AdminOnly>`
+                let arg_name = &match &*pat_type.pat {
+                    Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+                    Pat::Wild(_) => quote::format_ident!("__rapina_unused_{}", index),
+                    _ => continue,
+                };
                 let arg_type = &pat_type.ty;
 
                 let type_str = quote!(#arg_type).to_string();
-                if is_parts_only_extractor(&type_str) {
+                if let Some((_, binding)) = path_bindings.iter().find(|(name, _)| name == arg_name)
+                {
+                    parts_extractions.push(binding.extraction(arg_name, arg_type));
+                } else if is_optional_state(arg_type) {
+                    parts_extractions.push(quote! {
+                        let #arg_name = <#arg_type>::optional(&__rapina_state);
+                    });
+                } else if is_parts_only_extractor(&type_str) {
                     parts_extractions.push(quote! {
                         let #arg_name = match <#arg_type as rapina::extract::FromRequestParts>::from_request_parts(&__rapina_parts, &__rapina_params, &__rapina_state).await {
                             Ok(v) => v,
@@ -161,6 +279,17 @@ fn route_macro_core(
                     Err(e) => return rapina::response::IntoResponse::into_response(e),
                 };
             }
+        } else if let Some((raw, _)) = body_extractors.iter().find(|(_, ty)| is_raw_request(ty)) {
+            let others: Vec<_> = body_extractors
+                .iter()
+                .filter(|(n, _)| n != raw)
+                .map(|(n, _)| n.to_string())
+                .collect();
+            panic!(
+                "`{}: RawRequest` takes the whole request and can't be combined with extractors that consume it: {}. Read the body from the RawRequest instead.",
+                raw,
+                others.join(", ")
+            );
         } else {
             let names: Vec<_> = body_extractors.iter().map(|(n, _)| n.to_string()).collect();
             panic!(
@@ -180,6 +309,101 @@ fn route_macro_core(
         }
     };
 
+    // Wrap the handler in the response cache when `cache = "..."` is set
+    let call_body = match &route_args.cache {
+        Some(cache) if method != "GET" => {
+            return syn::Error::new(cache.span, "`cache` is only supported on #[get] routes")
+                .to_compile_error();
+        }
+        Some(cache) => {
+            let ttl_ms = cache.ttl.as_millis() as u64;
+            let tags = &route_args.invalidate_on;
+            quote! {
+                let __rapina_cache = rapina::cache::ResponseCache::from_state(&__rapina_state);
+                let __rapina_cache_key = rapina::cache::ResponseCache::key(&__rapina_req);
+                if let Some(__rapina_hit) = __rapina_cache.get(&__rapina_cache_key) {
+                    return __rapina_hit;
+                }
+                let __rapina_response = async move { #handler_body }.await;
+                __rapina_cache
+                    .store(
+                        __rapina_cache_key,
+                        __rapina_response,
+                        std::time::Duration::from_millis(#ttl_ms),
+                        &[#(#tags),*],
+                    )
+                    .await
+            }
+        }
+        None => handler_body,
+    };
+
+    // Check the policy before extractors run or the cache answers
+    let call_body = match &policy {
+        Some(policy) => quote! {
+            let (__rapina_parts, __rapina_body) = __rapina_req.into_parts();
+            if let Err(e) = rapina::auth::policy::check(&#policy, &__rapina_parts, &__rapina_state) {
+                return rapina::response::IntoResponse::into_response(e);
+            }
+            let __rapina_req = rapina::http::Request::from_parts(__rapina_parts, __rapina_body);
+            #call_body
+        },
+        None => call_body,
+    };
+
+    // Answer 404 before anything else runs while the route's flag is off
+    let (call_body, feature_flag_impl) = match &route_args.flag {
+        Some(flag) => (
+            quote! {
+                if !rapina::flags::Flags::from_state(&__rapina_state).is_enabled(#flag).await {
+                    return rapina::response::IntoResponse::into_response(
+                        rapina::error::Error::not_found("not found"),
+                    );
+                }
+                #call_body
+            },
+            quote! {
+                fn feature_flag() -> Option<&'static str> {
+                    Some(#flag)
+                }
+            },
+        ),
+        None => (call_body, quote! {}),
+    };
+
+    let untestable_impl = if route_args.untestable {
+        quote! {
+            fn untestable() -> bool {
+                true
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Selectors that let several handlers share a method and path
+    let media_type_impl = |name: &str, value: &Option<LitStr>| match value {
+        Some(value) => {
+            let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+            quote! {
+                fn #name() -> Option<&'static str> {
+                    Some(#value)
+                }
+            }
+        }
+        None => quote! {},
+    };
+    let consumes_impl = media_type_impl("consumes", &route_args.consumes);
+    let produces_impl = media_type_impl("produces", &route_args.produces);
+    let accept_version_impl = match &route_args.accept_version {
+        Some(version) => quote! {
+            fn accept_version() -> Option<u32> {
+                Some(#version)
+            }
+        },
+        None => quote! {},
+    };
+
     // Build the router method call for the register function
     let router_method = syn::Ident::new(&method.to_lowercase(), proc_macro2::Span::call_site());
     let register_fn_name = syn::Ident::new(
@@ -198,6 +422,14 @@ fn route_macro_core(
 
             #response_schema_impl
             #error_responses_impl
+            #feature_flag_impl
+            #untestable_impl
+            #path_schemas_impl
+            #selectable_fields_impl
+            #consumes_impl
+            #produces_impl
+            #accept_version_impl
+            #required_state_impl
 
             fn call(
                 &self,
@@ -206,7 +438,7 @@ fn route_macro_core(
                 __rapina_state: std::sync::Arc<rapina::state::AppState>,
             ) -> std::pin::Pin<Box<dyn std::future::Future<Output = rapina::hyper::Response<rapina::response::BoxBody>> + Send>> {
                 Box::pin(async move {
-                    #handler_body
+                    #call_body
                 })
             }
         }
@@ -230,15 +462,568 @@ fn route_macro_core(
     }
 }
 
+/// Arguments of a route macro: the path, then optional `key = value` options.
+///
+/// ```ignore
+/// #[get("/stats", cache = "60s", invalidate_on = ["post"])]
+/// #[get("/dashboard", flag = "new_dashboard")]
+/// #[post("/login", consumes = "application/json")]
+/// #[get("/users", accept_version = 2)]
+/// ```
+struct RouteArgs {
+    path: LitStr,
+    cache: Option<CacheOption>,
+    invalidate_on: Vec<LitStr>,
+    flag: Option<LitStr>,
+    untestable: bool,
+    consumes: Option<LitStr>,
+    produces: Option<LitStr>,
+    accept_version: Option<syn::LitInt>,
+}
+
+struct CacheOption {
+    ttl: std::time::Duration,
+    span: proc_macro2::Span,
+}
+
+impl syn::parse::Parse for RouteArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let path: LitStr = input.parse()?;
+        let mut cache = None;
+        let mut invalidate_on: Option<(proc_macro2::Span, Vec<LitStr>)> = None;
+        let mut flag: Option<LitStr> = None;
+        let mut untestable: Option<syn::LitBool> = None;
+        let mut consumes: Option<LitStr> = None;
+        let mut produces: Option<LitStr> = None;
+        let mut accept_version: Option<syn::LitInt> = None;
+
+        while !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: syn::Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+
+            match key.to_string().as_str() {
+                "cache" => {
+                    if cache.is_some() {
+                        return Err(syn::Error::new(key.span(), "duplicate `cache` option"));
+                    }
+                    let value: LitStr = input.parse()?;
+                    let ttl = parse_duration(&value.value()).ok_or_else(|| {
+                        syn::Error::new(
+                            value.span(),
+                            format!(
+                                "invalid cache duration \"{}\": expected a positive number followed by ms, s, m, h or d, like \"60s\"",
+                                value.value()
+                            ),
+                        )
+                    })?;
+                    cache = Some(CacheOption {
+                        ttl,
+                        span: value.span(),
+                    });
+                }
+                "invalidate_on" => {
+                    if invalidate_on.is_some() {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "duplicate `invalidate_on` option",
+                        ));
+                    }
+                    let content;
+                    syn::bracketed!(content in input);
+                    let entities = content
+                        .parse_terminated(<LitStr as syn::parse::Parse>::parse, syn::Token![,])?;
+                    invalidate_on = Some((key.span(), entities.into_iter().collect()));
+                }
+                "flag" => {
+                    if flag.is_some() {
+                        return Err(syn::Error::new(key.span(), "duplicate `flag` option"));
+                    }
+                    let value: LitStr = input.parse()?;
+                    if value.value().trim().is_empty() {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "`flag` needs a feature flag name",
+                        ));
+                    }
+                    flag = Some(value);
+                }
+                "untestable" => {
+                    if untestable.is_some() {
+                        return Err(syn::Error::new(key.span(), "duplicate `untestable` option"));
+                    }
+                    untestable = Some(input.parse()?);
+                }
+                "consumes" | "produces" => {
+                    let slot = if key == "consumes" {
+                        &mut consumes
+                    } else {
+                        &mut produces
+                    };
+                    if slot.is_some() {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            format!("duplicate `{}` option", key),
+                        ));
+                    }
+                    let value: LitStr = input.parse()?;
+                    if !is_media_type(&value.value()) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!(
+                                "invalid media type \"{}\": expected `type/subtype`, like \"application/json\"",
+                                value.value()
+                            ),
+                        ));
+                    }
+                    *slot = Some(value);
+                }
+                "accept_version" => {
+                    if accept_version.is_some() {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "duplicate `accept_version` option",
+                        ));
+                    }
+                    let value: syn::LitInt = input.parse()?;
+                    if !matches!(value.base10_parse::<u32>(), Ok(v) if v > 0) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "`accept_version` needs a positive version number, like 2",
+                        ));
+                    }
+                    accept_version = Some(syn::LitInt::new(value.base10_digits(), value.span()));
+                }
+                other => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown route option `{}`; expected `cache`, `invalidate_on`, `flag`, `untestable`, `consumes`, `produces` or `accept_version`",
+                            other
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some((span, _)) = &invalidate_on
+            && cache.is_none()
+        {
+            return Err(syn::Error::new(
+                *span,
+                "`invalidate_on` requires a `cache` duration",
+            ));
+        }
+
+        Ok(RouteArgs {
+            path,
+            cache,
+            invalidate_on: invalidate_on.map(|(_, e)| e).unwrap_or_default(),
+            flag,
+            untestable: untestable.is_some_and(|value| value.value),
+            consumes,
+            produces,
+            accept_version,
+        })
+    }
+}
+
+/// Whether `value` looks like `type/subtype`, without parameters.
+fn is_media_type(value: &str) -> bool {
+    value.split_once('/').is_some_and(|(ty, sub)| {
+        let token = |s: &str| {
+            !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$&^_.+-*".contains(c))
+        };
+        token(ty) && token(sub)
+    })
+}
+
+/// Parses durations like `500ms`, `60s`, `5m`, `1h` or `1d`. Zero is rejected.
+fn parse_duration(value: &str) -> Option<std::time::Duration> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let millis = match unit {
+        "ms" => Some(amount),
+        "s" => amount.checked_mul(1_000),
+        "m" => amount.checked_mul(60_000),
+        "h" => amount.checked_mul(3_600_000),
+        "d" => amount.checked_mul(86_400_000),
+        _ => None,
+    }?;
+    (millis > 0).then(|| std::time::Duration::from_millis(millis))
+}
+
 fn is_parts_only_extractor(type_str: &str) -> bool {
     type_str.contains("Path")
         || type_str.contains("Query")
         || type_str.contains("Headers")
         || type_str.contains("State")
         || type_str.contains("Context")
+        || type_str.contains("MatchedRoute")
         || type_str.contains("CurrentUser")
         || type_str.contains("Db")
         || type_str.contains("Cookie")
+        || type_str.contains("Events")
+        || type_str.contains("Jobs")
+        || type_str.contains("Flags")
+        || type_str.contains("VerifiedSignature")
+        || type_str.contains("FieldSelect")
+        || type_str.contains("Paginate")
+        || type_str.contains("UrlFor")
+        || type_str.contains("Parts")
+        || type_str.contains("Authorize")
+}
+
+/// Whether the argument is a `RawRequest`, which takes the whole request.
+fn is_raw_request(ty: &syn::Type) -> bool {
+    matches!(
+        ty,
+        syn::Type::Path(type_path)
+            if type_path.path.segments.last().is_some_and(|s| s.ident == "RawRequest")
+    )
+}
+
+/// The `T` of a `FieldSelect<T>` argument.
+fn field_select_type(ty: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(type_path) = ty
+        && let Some(last_segment) = type_path.path.segments.last()
+        && last_segment.ident == "FieldSelect"
+        && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
+        && let Some(syn::GenericArgument::Type(inner)) = args.args.first()
+    {
+        Some(inner)
+    } else {
+        None
+    }
+}
+
+/// How a `Path` argument gets its value from the route parameters.
+enum PathBinding {
+    /// The route's only parameter, through `FromRequestParts`
+    Single,
+    /// The parameter with this name
+    Named(String),
+    /// One parameter per tuple element, in route order
+    Tuple(Vec<(String, syn::Type)>),
+}
+
+impl PathBinding {
+    fn extraction(&self, arg_name: &syn::Ident, arg_type: &syn::Type) -> proc_macro2::TokenStream {
+        match self {
+            PathBinding::Single => quote! {
+                let #arg_name = match <#arg_type as rapina::extract::FromRequestParts>::from_request_parts(&__rapina_parts, &__rapina_params, &__rapina_state).await {
+                    Ok(v) => v,
+                    Err(e) => return rapina::response::IntoResponse::into_response(e),
+                };
+            },
+            PathBinding::Named(name) => quote! {
+                let #arg_name = match <#arg_type>::from_param(&__rapina_params, #name) {
+                    Ok(v) => v,
+                    Err(e) => return rapina::response::IntoResponse::into_response(e),
+                };
+            },
+            PathBinding::Tuple(elems) => {
+                let values = elems.iter().map(|(name, ty)| {
+                    quote! {
+                        match rapina::extract::Path::<#ty>::from_param(&__rapina_params, #name) {
+                            Ok(v) => v.into_inner(),
+                            Err(e) => return rapina::response::IntoResponse::into_response(e),
+                        }
+                    }
+                });
+                quote! {
+                    let #arg_name = rapina::extract::Path((#(#values,)*));
+                }
+            }
+        }
+    }
+}
+
+/// Parameter names in a route pattern, in order: `/users/:id` gives `["id"]`.
+fn route_params(path: &LitStr) -> syn::Result<Vec<String>> {
+    let mut params: Vec<String> = Vec::new();
+    for segment in path.value().split('/') {
+        let Some(name) = segment.strip_prefix(':') else {
+            continue;
+        };
+        if name.is_empty() {
+            return Err(syn::Error::new(
+                path.span(),
+                "path parameters need a name, e.g. `/:id`",
+            ));
+        }
+        if params.iter().any(|p| p == name) {
+            return Err(syn::Error::new(
+                path.span(),
+                format!("path parameter `:{}` appears more than once", name),
+            ));
+        }
+        params.push(name.to_string());
+    }
+    Ok(params)
+}
+
+/// Returns `T` if `ty` is `Path<T>`.
+fn path_extractor_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(type_path) = ty
+        && let Some(last_segment) = type_path.path.segments.last()
+        && last_segment.ident == "Path"
+        && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
+        && let Some(syn::GenericArgument::Type(inner_type)) = args.args.first()
+    {
+        Some(inner_type)
+    } else {
+        None
+    }
+}
+
+/// The types a `State<T>` or `States<(A, B, ..)>` argument reads from
+/// application state. `State<Option<T>>` needs nothing.
+fn required_state_types(ty: &syn::Type) -> Vec<&syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return Vec::new();
+    };
+    let Some(last_segment) = type_path.path.segments.last() else {
+        return Vec::new();
+    };
+    let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+        return Vec::new();
+    };
+    let Some(syn::GenericArgument::Type(inner)) = args.args.first() else {
+        return Vec::new();
+    };
+    match last_segment.ident.to_string().as_str() {
+        "State" if !is_optional_state(ty) => vec![inner],
+        "States" => match inner {
+            syn::Type::Tuple(tuple) => tuple.elems.iter().collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `ty` is `State<Option<T>>`, which resolves to `None` instead of
+/// failing when `T` isn't registered.
+fn is_optional_state(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(type_path) = ty
+        && let Some(last_segment) = type_path.path.segments.last()
+        && last_segment.ident == "State"
+        && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
+        && let Some(syn::GenericArgument::Type(syn::Type::Path(inner))) = args.args.first()
+        && let Some(inner_segment) = inner.path.segments.last()
+    {
+        inner_segment.ident == "Option"
+    } else {
+        false
+    }
+}
+
+/// Documents the type each route parameter is extracted as, so the OpenAPI
+/// spec can give path parameters a schema.
+fn path_schemas_impl(
+    path: &LitStr,
+    args: &[&FnArg],
+    bindings: &[(syn::Ident, PathBinding)],
+) -> proc_macro2::TokenStream {
+    let params = route_params(path).unwrap_or_default();
+    let mut schemas = Vec::new();
+    for (arg_name, binding) in bindings {
+        let Some(inner) = args.iter().find_map(|arg| match arg {
+            FnArg::Typed(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat_ident) if &pat_ident.ident == arg_name => {
+                    path_extractor_inner(&pat_type.ty)
+                }
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        }) else {
+            continue;
+        };
+        match binding {
+            PathBinding::Single => schemas.push((params[0].clone(), inner.clone())),
+            PathBinding::Named(name) => schemas.push((name.clone(), inner.clone())),
+            PathBinding::Tuple(elems) => schemas.extend(elems.iter().cloned()),
+        }
+    }
+    if schemas.is_empty() {
+        return quote! {};
+    }
+
+    let entries = schemas.iter().map(|(name, ty)| {
+        quote! {
+            if let Some(schema) = (&rapina::openapi::SchemaProbe::<#ty>::new()).param_schema() {
+                schemas.push((#name, schema));
+            }
+        }
+    });
+    quote! {
+        fn path_schemas() -> Vec<(&'static str, serde_json::Value)> {
+            use rapina::openapi::{JsonSchemaProbe, OpaqueSchemaProbe};
+            let mut schemas = Vec::new();
+            #(#entries)*
+            schemas
+        }
+    }
+}
+
+/// Reads the parameter name from a `#[path_param("name")]` argument attribute.
+fn path_param_attr(attrs: &[syn::Attribute]) -> syn::Result<Option<LitStr>> {
+    match attrs.iter().find(|attr| attr.path().is_ident("path_param")) {
+        Some(attr) => attr.parse_args::<LitStr>().map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Matches every `Path` argument to the route parameters it binds.
+///
+/// Binding rules:
+/// - `#[path_param("name")]` binds the parameter called `name`
+/// - a tuple `Path<(A, B)>` binds all parameters in route order
+/// - otherwise a route with one parameter binds it whatever the argument is
+///   called, and a route with several binds the one named like the argument
+///
+/// Fails with a spanned error when an argument names a parameter the route
+/// doesn't have, a tuple has the wrong arity, a parameter is bound twice, or
+/// a parameter isn't bound at all. Handlers without `Path` arguments are not
+/// checked.
+fn bind_path_params(
+    path: &LitStr,
+    handler_name: &str,
+    args: &[&FnArg],
+) -> syn::Result<Vec<(syn::Ident, PathBinding)>> {
+    let params = route_params(path)?;
+    let route = path.value();
+    let mut bindings = Vec::new();
+    let mut bound: Vec<String> = Vec::new();
+    let mut errors: Vec<syn::Error> = Vec::new();
+
+    for arg in args {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let Pat::Ident(pat_ident) = &*pat_type.pat else {
+            continue;
+        };
+        let Some(inner) = path_extractor_inner(&pat_type.ty) else {
+            continue;
+        };
+        let arg_name = &pat_ident.ident;
+        let explicit = path_param_attr(&pat_type.attrs)?;
+
+        if params.is_empty() {
+            errors.push(syn::Error::new_spanned(
+                &pat_type.ty,
+                format!("route \"{}\" has no path parameters to extract", route),
+            ));
+            continue;
+        }
+
+        let binding = match (explicit, inner) {
+            (Some(name), syn::Type::Tuple(_)) => {
+                errors.push(syn::Error::new(
+                    name.span(),
+                    "#[path_param] names a single parameter and can't be used with a tuple",
+                ));
+                continue;
+            }
+            (None, syn::Type::Tuple(tuple)) => {
+                if tuple.elems.len() != params.len() {
+                    errors.push(syn::Error::new_spanned(
+                        &pat_type.ty,
+                        format!(
+                            "expected a tuple of {} for route \"{}\", found {} element(s)",
+                            params.len(),
+                            route,
+                            tuple.elems.len()
+                        ),
+                    ));
+                    continue;
+                }
+                PathBinding::Tuple(
+                    params
+                        .iter()
+                        .cloned()
+                        .zip(tuple.elems.iter().cloned())
+                        .collect(),
+                )
+            }
+            (Some(name), _) => {
+                if !params.contains(&name.value()) {
+                    errors.push(syn::Error::new(
+                        name.span(),
+                        format!("route \"{}\" has no parameter `:{}`", route, name.value()),
+                    ));
+                    continue;
+                }
+                PathBinding::Named(name.value())
+            }
+            (None, _) if params.len() == 1 => PathBinding::Single,
+            (None, _) => {
+                let name = arg_name.to_string();
+                let name = name.trim_start_matches('_');
+                if !params.iter().any(|p| p == name) {
+                    errors.push(syn::Error::new_spanned(
+                        arg_name,
+                        format!(
+                            "`{}` doesn't match any parameter of route \"{}\"; rename the argument or add #[path_param(\"...\")]",
+                            name, route
+                        ),
+                    ));
+                    continue;
+                }
+                PathBinding::Named(name.to_string())
+            }
+        };
+
+        let names: Vec<String> = match &binding {
+            PathBinding::Single => vec![params[0].clone()],
+            PathBinding::Named(name) => vec![name.clone()],
+            PathBinding::Tuple(elems) => elems.iter().map(|(name, _)| name.clone()).collect(),
+        };
+        for name in names {
+            if bound.contains(&name) {
+                errors.push(syn::Error::new_spanned(
+                    &pat_type.ty,
+                    format!(
+                        "path parameter `:{}` is already extracted by another argument",
+                        name
+                    ),
+                ));
+            } else {
+                bound.push(name);
+            }
+        }
+
+        bindings.push((arg_name.clone(), binding));
+    }
+
+    if errors.is_empty() && !bindings.is_empty() {
+        for param in params.iter().filter(|p| !bound.contains(p)) {
+            errors.push(syn::Error::new(
+                path.span(),
+                format!(
+                    "path parameter `:{}` is not extracted by any argument of `{}`",
+                    param, handler_name
+                ),
+            ));
+        }
+    }
+
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut first) => {
+            first.extend(errors);
+            Err(first)
+        }
+        None => Ok(bindings),
+    }
 }
 
 /// Extracts the inner type from Json<T> wrapper for schema generation
@@ -246,14 +1031,21 @@ fn extract_json_inner_type(return_type: &syn::Type) -> Option<proc_macro2::Token
     if let syn::Type::Path(type_path) = return_type
         && let Some(last_segment) = type_path.path.segments.last()
     {
-        // Direct Json<T>
-        if last_segment.ident == "Json"
+        // Direct Json<T>, JsonWith<T> or PartialJson<T>
+        if (last_segment.ident == "Json"
+            || last_segment.ident == "JsonWith"
+            || last_segment.ident == "PartialJson")
             && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
             && let Some(syn::GenericArgument::Type(inner_type)) = args.args.first()
         {
             return Some(quote!(#inner_type));
         }
 
+        // Linked<T> and Paginated<T> are documented with their envelope
+        if last_segment.ident == "Linked" || last_segment.ident == "Paginated" {
+            return Some(quote!(#return_type));
+        }
+
         // Result<Json<T>> or Result<Json<T>, E>
         if last_segment.ident == "Result"
             && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
@@ -275,6 +1067,75 @@ fn extract_errors_attr(attrs: &mut Vec<syn::Attribute>) -> Option<syn::Type> {
     Some(err_type)
 }
 
+/// Extract #[authorize(...)] attribute from function attributes, removing it
+/// if found, and build the policy expression it describes.
+fn extract_authorize_attr(
+    attrs: &mut Vec<syn::Attribute>,
+) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    let Some(idx) = attrs
+        .iter()
+        .position(|attr| attr.path().is_ident("authorize"))
+    else {
+        return Ok(None);
+    };
+    let attr = attrs.remove(idx);
+    if let Some(other) = attrs.iter().find(|attr| attr.path().is_ident("authorize")) {
+        return Err(syn::Error::new_spanned(
+            other,
+            "only one #[authorize] is allowed per handler; combine policies with all(...)",
+        ));
+    }
+    let expr: syn::Expr = attr.parse_args().map_err(|err| {
+        syn::Error::new(
+            err.span(),
+            "expected a policy, like #[authorize(AdminOnly)] or #[authorize(any(AdminOnly, Owner))]",
+        )
+    })?;
+    policy_expr(&expr).map(Some)
+}
+
+/// Turns `any(..)`, `all(..)` and `not(..)` into the policy combinators and
+/// leaves any other expression as the policy value.
+fn policy_expr(expr: &syn::Expr) -> syn::Result<proc_macro2::TokenStream> {
+    if let syn::Expr::Call(call) = expr
+        && let syn::Expr::Path(func) = &*call.func
+        && let Some(ident) = func.path.get_ident()
+    {
+        match ident.to_string().as_str() {
+            "all" | "any" => {
+                if call.args.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        call,
+                        format!("`{}(...)` needs at least one policy", ident),
+                    ));
+                }
+                let policies = call
+                    .args
+                    .iter()
+                    .map(policy_expr)
+                    .collect::<syn::Result<Vec<_>>>()?;
+                return Ok(quote! {
+                    rapina::auth::policy::#ident(vec![
+                        #(Box::new(#policies) as Box<dyn rapina::auth::Policy>),*
+                    ])
+                });
+            }
+            "not" => {
+                if call.args.len() != 1 {
+                    return Err(syn::Error::new_spanned(
+                        call,
+                        "`not(...)` takes exactly one policy",
+                    ));
+                }
+                let policy = policy_expr(&call.args[0])?;
+                return Ok(quote! { rapina::auth::policy::not(#policy) });
+            }
+            _ => {}
+        }
+    }
+    Ok(quote! { #expr })
+}
+
 /// Extract #[public] attribute from function attributes, removing it if found.
 fn extract_public_attr(attrs: &mut Vec<syn::Attribute>) -> bool {
     if let Some(idx) = attrs.iter().position(|attr| attr.path().is_ident("public")) {
@@ -297,6 +1158,34 @@ pub fn derive_config(input: TokenStream) -> TokenStream {
     derive_config_impl(input.into()).into()
 }
 
+/// Derive macro for extractors built from request parts
+///
+/// Generates a `FromRequestParts` implementation that fills each field from
+/// the source named in its `#[from(...)]` attribute: `header = "name"`,
+/// `query = "name"`, `path = "name"` or `state`. Values are parsed with
+/// `FromStr`; `Option<T>` fields become `None` when the source is absent.
+/// Missing or invalid values reject with 400 unless the field sets
+/// `rejection = 401` (or 403, 404, 422). Fields without an attribute are
+/// extracted through their own `FromRequestParts` implementation.
+///
+/// # Example
+///
+/// ```ignore
+/// use rapina::prelude::*;
+///
+/// #[derive(FromRequestParts)]
+/// struct Caller {
+///     #[from(header = "x-user-id", rejection = 401)]
+///     user_id: u64,
+///     #[from(query = "tenant")]
+///     tenant: Option<String>,
+/// }
+/// ```
+#[proc_macro_derive(FromRequestParts, attributes(from))]
+pub fn derive_from_request_parts(input: TokenStream) -> TokenStream {
+    from_request_parts::derive_from_request_parts_impl(input.into()).into()
+}
+
 /// Define database entities with Prisma-like syntax.
 ///
 /// This macro generates SeaORM entity definitions from a declarative syntax
@@ -334,7 +1223,29 @@ pub fn derive_config(input: TokenStream) -> TokenStream {
 /// - `Model` struct with auto `id`, `created_at`, `updated_at`
 /// - `Relation` enum with proper SeaORM attributes
 /// - `Related<T>` trait implementations
-/// - `ActiveModelBehavior` implementation
+/// - `ActiveModelBehavior` implementation that fills in `created_at` and
+///   `updated_at` and calls the entity's lifecycle hooks
+///
+/// # Lifecycle Hooks
+///
+/// `#[before_save(path)]`, `#[after_save(path)]`, `#[before_delete(path)]`
+/// and `#[after_delete(path)]` on an entity call user functions, in
+/// declaration order. See `rapina::database::SaveHook` and
+/// `rapina::database::DeleteHook` for their signatures.
+///
+/// ```ignore
+/// rapina::schema! {
+///     #[before_save(normalize_email)]
+///     User {
+///         email: String,
+///     }
+/// }
+///
+/// async fn normalize_email(mut user: user::ActiveModel, insert: bool) -> Result<user::ActiveModel, DbErr> {
+///     // ...
+///     Ok(user)
+/// }
+/// ```
 ///
 /// # Supported Types
 ///
@@ -360,12 +1271,42 @@ pub fn schema(input: TokenStream) -> TokenStream {
     schema::schema_impl(input.into()).into()
 }
 
-fn derive_config_impl(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    let input: syn::DeriveInput = syn::parse2(input).expect("expected struct");
-    let name = &input.ident;
-
-    let fields = match &input.data {
-        syn::Data::Struct(data) => match &data.fields {
+/// Build a parameterized raw SQL query, checked at compile time.
+///
+/// Expands to a `rapina::database::Sql`. Placeholders are written as `?`
+/// (bound in order) or `:name` (bound by name, may repeat), and are turned
+/// into the backend's syntax (`$1` on Postgres, `?` elsewhere) when the
+/// query runs. Arguments are bound as values, never spliced into the SQL.
+///
+/// The macro rejects unterminated strings and comments, unbalanced
+/// parentheses, and placeholders that don't match the arguments. It does
+/// not check table or column names. Write `??` for a literal `?`.
+///
+/// ```ignore
+/// use rapina::database::{Db, sql};
+///
+/// let rows: Vec<AuthorStats> = sql!(
+///     "SELECT author_id, count(*) AS posts FROM posts WHERE created_at > :since GROUP BY author_id",
+///     since = since,
+/// )
+/// .all(db.read())
+/// .await?;
+///
+/// sql!("DELETE FROM sessions WHERE user_id = ?", user_id)
+///     .execute(db.write())
+///     .await?;
+/// ```
+#[proc_macro]
+pub fn sql(input: TokenStream) -> TokenStream {
+    sql::sql_impl(input.into()).into()
+}
+
+fn derive_config_impl(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let input: syn::DeriveInput = syn::parse2(input).expect("expected struct");
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        syn::Data::Struct(data) => match &data.fields {
             syn::Fields::Named(fields) => &fields.named,
             _ => panic!("Config derive only supports structs with named fields"),
         },
@@ -448,7 +1389,7 @@ fn derive_config_impl(input: proc_macro2::TokenStream) -> proc_macro2::TokenStre
 
 #[cfg(test)]
 mod tests {
-    use super::route_macro_core;
+    use super::{parse_duration, route_macro_core};
     use quote::quote;
 
     #[test]
@@ -492,7 +1433,7 @@ mod tests {
 
     #[test]
     fn test_function_with_multiple_extractors() {
-        let path = quote!("/users");
+        let path = quote!("/users/:id");
         let input = quote! {
             async fn create_user(
                 id: rapina::extract::Path<u64>,
@@ -512,6 +1453,183 @@ mod tests {
         assert!(output_str.contains("FromRequest"));
     }
 
+    #[test]
+    fn test_optional_state_resolves_without_failing() {
+        let path = quote!("/report");
+        let input = quote! {
+            async fn report(cache: State<Option<Cache>>, config: State<Config>) -> String {
+                "report".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(output_str.contains(
+            "let cache = < State < Option < Cache > > > :: optional (& __rapina_state) ;"
+        ));
+        assert!(output_str.contains(
+            "< State < Config > as rapina :: extract :: FromRequestParts > :: from_request_parts"
+        ));
+    }
+
+    #[test]
+    fn test_path_params_bound_by_argument_name() {
+        let path = quote!("/posts/:year/:month");
+        let input = quote! {
+            async fn archive(year: Path<u32>, month: Path<u32>) -> String {
+                format!("{}/{}", year.into_inner(), month.into_inner())
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(!output_str.contains("compile_error"));
+        assert!(output_str.contains("from_param (& __rapina_params , \"year\")"));
+        assert!(output_str.contains("from_param (& __rapina_params , \"month\")"));
+    }
+
+    #[test]
+    fn test_path_param_attr_binds_named_segment() {
+        let path = quote!("/users/:user_id/posts/:post_id");
+        let input = quote! {
+            async fn get_post(
+                #[path_param("user_id")] user: Path<u64>,
+                #[path_param("post_id")] post: Path<u64>,
+            ) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(!output_str.contains("compile_error"));
+        assert!(output_str.contains("from_param (& __rapina_params , \"user_id\")"));
+        assert!(output_str.contains("from_param (& __rapina_params , \"post_id\")"));
+    }
+
+    #[test]
+    fn test_tuple_path_binds_params_in_order() {
+        let path = quote!("/users/:user_id/posts/:post_id");
+        let input = quote! {
+            async fn get_post(ids: Path<(u64, String)>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(!output_str.contains("compile_error"));
+        assert!(output_str.contains(
+            "rapina :: extract :: Path :: < u64 > :: from_param (& __rapina_params , \"user_id\")"
+        ));
+        assert!(output_str.contains("rapina :: extract :: Path :: < String > :: from_param (& __rapina_params , \"post_id\")"));
+    }
+
+    #[test]
+    fn test_path_param_attr_missing_segment() {
+        let path = quote!("/users/:id");
+        let input = quote! {
+            async fn get_user(#[path_param("user_id")] id: Path<u64>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(output_str.contains("compile_error"));
+        assert!(output_str.contains("route \\\"/users/:id\\\" has no parameter `:user_id`"));
+    }
+
+    #[test]
+    fn test_unbound_path_param() {
+        let path = quote!("/users/:user_id/posts/:post_id");
+        let input = quote! {
+            async fn get_post(user_id: Path<u64>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(
+            output_str.contains(
+                "path parameter `:post_id` is not extracted by any argument of `get_post`"
+            )
+        );
+    }
+
+    #[test]
+    fn test_argument_name_not_in_multi_param_route() {
+        let path = quote!("/posts/:year/:month");
+        let input = quote! {
+            async fn archive(year: Path<u32>, day: Path<u32>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(output_str.contains("`day` doesn't match any parameter"));
+    }
+
+    #[test]
+    fn test_tuple_path_arity_mismatch() {
+        let path = quote!("/users/:user_id/posts/:post_id");
+        let input = quote! {
+            async fn get_post(ids: Path<(u64, u64, u64)>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(output_str.contains("expected a tuple of 2"));
+        assert!(output_str.contains("found 3 element(s)"));
+    }
+
+    #[test]
+    fn test_path_extractor_without_route_params() {
+        let path = quote!("/users");
+        let input = quote! {
+            async fn list_users(id: Path<u64>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(output_str.contains("has no path parameters to extract"));
+    }
+
+    #[test]
+    fn test_single_param_route_keeps_any_argument_name() {
+        let path = quote!("/users/:id");
+        let input = quote! {
+            async fn get_user(user_id: Path<u64>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(!output_str.contains("compile_error"));
+        assert!(output_str.contains("FromRequestParts"));
+    }
+
+    #[test]
+    fn test_paginate_and_url_for_are_parts_extractors() {
+        let path = quote!("/posts");
+        let input = quote! {
+            async fn list_posts(db: Db, page: Paginate, urls: UrlFor) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("GET", path, input).to_string();
+
+        assert!(!output_str.contains("FromRequest >"));
+    }
+
     #[test]
     #[should_panic(expected = "Multiple body-consuming extractors are not supported")]
     fn test_multiple_body_extractors_panics() {
@@ -528,6 +1646,34 @@ mod tests {
         route_macro_core("POST", path, input);
     }
 
+    #[test]
+    #[should_panic(expected = "`req: RawRequest` takes the whole request")]
+    fn test_raw_request_with_body_extractor_panics() {
+        let path = quote!("/users");
+        let input = quote! {
+            async fn handler(req: RawRequest, body: Json<String>) -> String {
+                "ok".to_string()
+            }
+        };
+
+        route_macro_core("POST", path, input);
+    }
+
+    #[test]
+    fn test_raw_request_with_parts_extractors() {
+        let path = quote!("/users");
+        let input = quote! {
+            async fn handler(parts: Parts, headers: Headers, req: RawRequest) -> String {
+                "ok".to_string()
+            }
+        };
+
+        let output_str = route_macro_core("POST", path, input).to_string();
+
+        assert!(output_str.contains("< Parts as rapina :: extract :: FromRequestParts >"));
+        assert!(output_str.contains("< RawRequest as rapina :: extract :: FromRequest >"));
+    }
+
     #[test]
     #[should_panic(expected = "expected function")]
     fn test_invalid_input_panics() {
@@ -549,12 +1695,57 @@ mod tests {
         let output = route_macro_core("GET", path, input);
         let output_str = output.to_string();
 
-        // Check response_schema method is generated with schema_for!
+        // Check response_schema method is generated through the schema probe
         assert!(output_str.contains("fn response_schema"));
-        assert!(output_str.contains("rapina :: schemars :: schema_for !"));
+        assert!(output_str.contains("rapina :: openapi :: SchemaProbe :: < UserResponse >"));
         assert!(output_str.contains("UserResponse"));
     }
 
+    #[test]
+    fn test_linked_return_type_documents_envelope() {
+        let path = quote!("/posts/:id");
+        let input = quote! {
+            async fn get_post(urls: UrlFor) -> Result<Linked<Post>> {
+                Ok(Linked::new(Post { id: 1 }, urls.links()))
+            }
+        };
+
+        let output = route_macro_core("GET", path, input);
+        let output_str = output.to_string();
+
+        assert!(output_str.contains("rapina :: openapi :: SchemaProbe :: < Linked < Post > >"));
+    }
+
+    #[test]
+    fn test_paginated_return_type_documents_envelope() {
+        let path = quote!("/posts");
+        let input = quote! {
+            async fn list_posts(db: Db, page: Paginate) -> Result<Paginated<Post>> {
+                page.exec(Post::find(), db.read()).await
+            }
+        };
+
+        let output = route_macro_core("GET", path, input);
+        let output_str = output.to_string();
+
+        assert!(output_str.contains("rapina :: openapi :: SchemaProbe :: < Paginated < Post > >"));
+    }
+
+    #[test]
+    fn test_json_with_return_type_generates_response_schema() {
+        let path = quote!("/users");
+        let input = quote! {
+            async fn get_user() -> JsonWith<UserResponse> {
+                Json(UserResponse { id: 1 }).with_options(JsonOptions::default())
+            }
+        };
+
+        let output = route_macro_core("GET", path, input);
+        let output_str = output.to_string();
+
+        assert!(output_str.contains("rapina :: openapi :: SchemaProbe :: < UserResponse >"));
+    }
+
     #[test]
     fn test_result_json_return_type_generates_response_schema() {
         let path = quote!("/users");
@@ -568,8 +1759,7 @@ mod tests {
         let output_str = output.to_string();
 
         assert!(output_str.contains("fn response_schema"));
-        assert!(output_str.contains("rapina :: schemars :: schema_for !"));
-        assert!(output_str.contains("UserResponse"));
+        assert!(output_str.contains("rapina :: openapi :: SchemaProbe :: < UserResponse >"));
     }
 
     #[test]
@@ -604,7 +1794,7 @@ mod tests {
 
         // Check response_schema method is NOT generated for non-Json types
         assert!(!output_str.contains("fn response_schema"));
-        assert!(!output_str.contains("schema_for"));
+        assert!(!output_str.contains("SchemaProbe"));
     }
 
     #[test]
@@ -685,6 +1875,438 @@ mod tests {
         assert!(output_str.contains("__rapina_router . post"));
     }
 
+    #[test]
+    fn test_cache_option_wraps_handler() {
+        let attr = quote!("/stats", cache = "60s", invalidate_on = ["post", "user"]);
+        let input = quote! {
+            async fn stats() -> &'static str {
+                "ok"
+            }
+        };
+
+        let output = route_macro_core("GET", attr, input);
+        let output_str = output.to_string();
+
+        assert!(output_str.contains("ResponseCache :: from_state"));
+        assert!(output_str.contains("from_millis (60000u64)"));
+        assert!(output_str.contains("& [\"post\" , \"user\"]"));
+        assert!(output_str.contains("path : \"/stats\""));
+    }
+
+    #[test]
+    fn test_route_without_cache_option_is_not_cached() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats"),
+            quote! {
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(!output.to_string().contains("ResponseCache"));
+    }
+
+    #[test]
+    fn test_invalid_cache_duration() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats", cache = "soon"),
+            quote! {
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        let output_str = output.to_string();
+        assert!(output_str.contains("compile_error"));
+        assert!(output_str.contains("invalid cache duration"));
+    }
+
+    #[test]
+    fn test_cache_option_on_post_is_rejected() {
+        let output = route_macro_core(
+            "POST",
+            quote!("/stats", cache = "60s"),
+            quote! {
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(
+            output
+                .to_string()
+                .contains("`cache` is only supported on #[get] routes")
+        );
+    }
+
+    #[test]
+    fn test_invalidate_on_requires_cache() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats", invalidate_on = ["post"]),
+            quote! {
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(output.to_string().contains("requires a `cache` duration"));
+    }
+
+    #[test]
+    fn test_flag_option_gates_handler() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/dashboard", flag = "new_dashboard"),
+            quote! {
+                async fn dashboard() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        let output_str = output.to_string();
+
+        assert!(output_str.contains(
+            "rapina :: flags :: Flags :: from_state (& __rapina_state) . is_enabled (\"new_dashboard\")"
+        ));
+        assert!(output_str.contains("Error :: not_found"));
+        assert!(output_str.contains("fn feature_flag ()"));
+        assert!(output_str.contains("Some (\"new_dashboard\")"));
+    }
+
+    #[test]
+    fn test_authorize_checks_policy_before_cache() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats", cache = "60s"),
+            quote! {
+                #[authorize(any(AdminOnly, not(Banned)))]
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        let output_str = output.to_string();
+
+        assert!(!output_str.contains("# [authorize"));
+        let check = output_str
+            .find("rapina :: auth :: policy :: check (& rapina :: auth :: policy :: any (vec ! [Box :: new (AdminOnly) as Box < dyn rapina :: auth :: Policy >")
+            .unwrap();
+        assert!(output_str.contains("Box :: new (rapina :: auth :: policy :: not (Banned))"));
+        let cache = output_str.find("ResponseCache :: from_state").unwrap();
+        assert!(check < cache);
+    }
+
+    #[test]
+    fn test_authorize_errors() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats"),
+            quote! {
+                #[authorize(not(AdminOnly, Banned))]
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(
+            output
+                .to_string()
+                .contains("`not(...)` takes exactly one policy")
+        );
+
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats"),
+            quote! {
+                #[authorize(any())]
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(
+            output
+                .to_string()
+                .contains("`any(...)` needs at least one policy")
+        );
+
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats"),
+            quote! {
+                #[authorize(AdminOnly)]
+                #[authorize(Owner)]
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(
+            output
+                .to_string()
+                .contains("only one #[authorize] is allowed")
+        );
+    }
+
+    #[test]
+    fn test_flag_checked_before_cache() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats", cache = "60s", flag = "stats"),
+            quote! {
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        let output_str = output.to_string();
+
+        let flag = output_str.find("is_enabled").unwrap();
+        let cache = output_str.find("ResponseCache :: from_state").unwrap();
+        assert!(flag < cache);
+    }
+
+    #[test]
+    fn test_route_without_flag_is_not_gated() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/dashboard"),
+            quote! {
+                async fn dashboard() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        let output_str = output.to_string();
+        assert!(!output_str.contains("rapina :: flags"));
+        assert!(!output_str.contains("fn feature_flag"));
+        assert!(!output_str.contains("fn untestable"));
+    }
+
+    #[test]
+    fn test_untestable_option() {
+        let output = route_macro_core(
+            "POST",
+            quote!("/payments", untestable = true),
+            quote! {
+                async fn charge() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(
+            output
+                .to_string()
+                .contains("fn untestable () -> bool { true }")
+        );
+    }
+
+    #[test]
+    fn test_consumes_and_produces_options() {
+        let output = route_macro_core(
+            "POST",
+            quote!(
+                "/login",
+                consumes = "application/json",
+                produces = "text/html"
+            ),
+            quote! {
+                async fn login_json() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        let output_str = output.to_string();
+        assert!(output_str.contains(
+            "fn consumes () -> Option < & 'static str > { Some (\"application/json\") }"
+        ));
+        assert!(
+            output_str
+                .contains("fn produces () -> Option < & 'static str > { Some (\"text/html\") }")
+        );
+    }
+
+    #[test]
+    fn test_accept_version_option() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/users", accept_version = 2),
+            quote! {
+                async fn list_users_v2() -> &'static str {
+                    "v2"
+                }
+            },
+        );
+        assert!(
+            output
+                .to_string()
+                .contains("fn accept_version () -> Option < u32 > { Some (2) }")
+        );
+
+        let output = route_macro_core(
+            "GET",
+            quote!("/users", accept_version = 0),
+            quote! {
+                async fn list_users_v0() -> &'static str {
+                    "v0"
+                }
+            },
+        );
+        assert!(output.to_string().contains("positive version number"));
+    }
+
+    #[test]
+    fn test_invalid_consumes_media_type() {
+        let output = route_macro_core(
+            "POST",
+            quote!("/login", consumes = "json"),
+            quote! {
+                async fn login_json() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(output.to_string().contains("invalid media type"));
+    }
+
+    #[test]
+    fn test_path_arguments_document_parameter_schemas() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/orgs/:org/users/:id"),
+            quote! {
+                async fn get_user(org: Path<String>, id: Path<Uuid>) -> String {
+                    format!("{}", id.into_inner())
+                }
+            },
+        );
+        let output_str = output.to_string();
+        assert!(output_str.contains("fn path_schemas ()"));
+        assert!(output_str.contains(
+            "rapina :: openapi :: SchemaProbe :: < String > :: new ()) . param_schema ()"
+        ));
+        assert!(output_str.contains("schemas . push ((\"org\" , schema))"));
+        assert!(output_str.contains("schemas . push ((\"id\" , schema))"));
+
+        let output = route_macro_core(
+            "GET",
+            quote!("/users"),
+            quote! {
+                async fn list_users() -> String {
+                    String::new()
+                }
+            },
+        );
+        assert!(!output.to_string().contains("fn path_schemas"));
+    }
+
+    #[test]
+    fn test_state_arguments_generate_required_state() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/report"),
+            quote! {
+                async fn report(
+                    config: State<Config>,
+                    cache: State<Option<Cache>>,
+                    deps: States<(Mailer, Payments)>,
+                ) -> String {
+                    String::new()
+                }
+            },
+        );
+        let output_str = output.to_string();
+        assert!(output_str.contains("fn required_state ()"));
+        assert!(output_str.contains("std :: any :: TypeId :: of :: < Config > ()"));
+        assert!(output_str.contains("std :: any :: TypeId :: of :: < Mailer > ()"));
+        assert!(output_str.contains("std :: any :: TypeId :: of :: < Payments > ()"));
+        assert!(!output_str.contains("TypeId :: of :: < Cache >"));
+
+        let output = route_macro_core(
+            "GET",
+            quote!("/users"),
+            quote! {
+                async fn list_users() -> String {
+                    String::new()
+                }
+            },
+        );
+        assert!(!output.to_string().contains("fn required_state"));
+    }
+
+    #[test]
+    fn test_field_select_documents_selectable_fields() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/users/:id"),
+            quote! {
+                async fn get_user(id: Path<i32>, fields: FieldSelect<User>) -> Result<PartialJson<User>> {
+                    Ok(fields.apply(find(id)))
+                }
+            },
+        );
+        let output_str = output.to_string();
+        assert!(output_str.contains("fn selectable_fields ()"));
+        assert!(
+            output_str.contains("rapina :: fields :: FieldSelect :: < User > :: valid_fields ()")
+        );
+        assert!(output_str.contains(
+            "< FieldSelect < User > as rapina :: extract :: FromRequestParts > :: from_request_parts"
+        ));
+        assert!(output_str.contains("rapina :: openapi :: SchemaProbe :: < User >"));
+    }
+
+    #[test]
+    fn test_empty_flag_name() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/dashboard", flag = ""),
+            quote! {
+                async fn dashboard() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(
+            output
+                .to_string()
+                .contains("`flag` needs a feature flag name")
+        );
+    }
+
+    #[test]
+    fn test_unknown_route_option() {
+        let output = route_macro_core(
+            "GET",
+            quote!("/stats", ttl = "60s"),
+            quote! {
+                async fn stats() -> &'static str {
+                    "ok"
+                }
+            },
+        );
+        assert!(output.to_string().contains("unknown route option `ttl`"));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        use std::time::Duration;
+
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("60s"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("1d"), Some(Duration::from_secs(86400)));
+        assert_eq!(parse_duration("0s"), None);
+        assert_eq!(parse_duration("60"), None);
+        assert_eq!(parse_duration("s"), None);
+        assert_eq!(parse_duration("1.5s"), None);
+        assert_eq!(parse_duration("10 minutes"), None);
+    }
+
     #[test]
     fn test_public_attr_below_route_sets_is_public() {
         let path = quote!("/health");
@@ -1,20 +1,27 @@
 //! Semantic analysis for the schema macro.
 //!
 //! Two-pass analysis:
-//! 1. Collect all entity names into a registry
+//! 1. Collect all entity and enum names into a registry
 //! 2. Resolve relationships and validate targets exist
 
+use heck::ToSnakeCase;
 use proc_macro2::Span;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use syn::ext::IdentExt;
 use syn::{Ident, Result};
 
-use super::parse::{EntityAttrs, EntityDef, FieldAttrs, FieldDef, RawFieldType, Schema};
-use super::types::FieldType;
+use super::parse::{
+    DefaultValue, EntityAttrs, EntityDef, EnumDef, ExternEntity, FieldAttrs, FieldDef,
+    RawFieldType, ReferentialActionKind, Schema,
+};
+use super::types::{FieldType, IdType, ScalarType};
 
 /// Analyzed schema with resolved relationships.
 #[derive(Debug)]
 pub struct AnalyzedSchema {
     pub entities: Vec<AnalyzedEntity>,
+    pub enums: Vec<EnumDef>,
+    pub externs: Vec<ExternEntity>,
 }
 
 /// An entity with resolved field types.
@@ -33,23 +40,88 @@ pub struct AnalyzedField {
     pub attrs: FieldAttrs,
     pub name: Ident,
     pub ty: FieldType,
+    /// Database default of the column, checked against its type
+    pub default: Option<DefaultValue>,
     #[allow(dead_code)]
     pub span: Span,
 }
 
-/// Entity registry for cross-reference validation.
+/// Entity registry for cross-reference validation, mapping each entity to
+/// the type of its id and to its belongs_to fields, and each enum to its
+/// variants.
 struct EntityRegistry {
-    names: HashSet<String>,
+    ids: HashMap<String, IdType>,
+    /// `(field, target entity)` for each field naming another entity
+    belongs_to: HashMap<String, Vec<(Ident, String)>>,
+    enums: HashMap<String, Vec<String>>,
 }
 
 impl EntityRegistry {
-    fn new(entities: &[EntityDef]) -> Self {
-        let names = entities.iter().map(|e| e.name.to_string()).collect();
-        EntityRegistry { names }
+    fn new(entities: &[EntityDef], enums: &[EnumDef], externs: &[ExternEntity]) -> Self {
+        // Extern entities resolve like local ones, but their fields aren't
+        // known here, so they have no belongs_to relations
+        let ids: HashMap<String, IdType> = entities
+            .iter()
+            .map(|e| (e.name.to_string(), e.attrs.id_type))
+            .chain(externs.iter().map(|e| (e.name.to_string(), e.id_type)))
+            .collect();
+        let belongs_to = entities
+            .iter()
+            .map(|e| {
+                let fields = e
+                    .fields
+                    .iter()
+                    .filter_map(|f| match &f.ty {
+                        RawFieldType::Unknown { name, .. }
+                            if ids.contains_key(&name.to_string()) =>
+                        {
+                            Some((f.name.clone(), name.to_string()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                (e.name.to_string(), fields)
+            })
+            .collect();
+        let enums = enums
+            .iter()
+            .map(|e| {
+                let variants = e.variants.iter().map(|v| v.name.to_string()).collect();
+                (e.name.to_string(), variants)
+            })
+            .collect();
+        EntityRegistry {
+            ids,
+            belongs_to,
+            enums,
+        }
     }
 
     fn contains(&self, name: &str) -> bool {
-        self.names.contains(name)
+        self.ids.contains_key(name)
+    }
+
+    /// Whether `name` is defined in this schema rather than declared extern.
+    fn is_local(&self, name: &str) -> bool {
+        self.belongs_to.contains_key(name)
+    }
+
+    fn id_type(&self, name: &str) -> Option<IdType> {
+        self.ids.get(name).copied()
+    }
+
+    fn enum_variants(&self, name: &str) -> Option<&[String]> {
+        self.enums.get(name).map(Vec::as_slice)
+    }
+
+    /// The first belongs_to field of `entity` pointing at `target`, other
+    /// than `skip`.
+    fn belongs_to_field(&self, entity: &str, target: &str, skip: Option<&Ident>) -> Option<&Ident> {
+        self.belongs_to
+            .get(entity)?
+            .iter()
+            .find(|(field, field_target)| field_target == target && Some(field) != skip)
+            .map(|(field, _)| field)
     }
 }
 
@@ -67,8 +139,78 @@ pub fn analyze_schema(schema: Schema) -> Result<AnalyzedSchema> {
         }
     }
 
+    // An extern entity is defined in another block, so it can't also be
+    // defined in this one
+    let mut seen_externs = HashSet::new();
+    for schema_extern in &schema.externs {
+        let extern_name = schema_extern.name.to_string();
+        if seen_entities.contains(&extern_name) {
+            return Err(syn::Error::new(
+                schema_extern.name.span(),
+                format!(
+                    "entity '{}' is declared extern but also defined in this schema",
+                    extern_name
+                ),
+            ));
+        }
+        if !seen_externs.insert(extern_name.clone()) {
+            return Err(syn::Error::new(
+                schema_extern.name.span(),
+                format!("duplicate extern entity '{}'", extern_name),
+            ));
+        }
+    }
+
+    // Enums are re-exported next to the entities, and each gets a module
+    // named after it like an entity does, so neither may clash. Extern
+    // entities bring their module in with a `use`
+    let mut seen_modules: HashMap<String, String> = schema
+        .entities
+        .iter()
+        .map(|e| &e.name)
+        .chain(schema.externs.iter().map(|e| &e.name))
+        .map(|name| (name.to_string().to_snake_case(), name.to_string()))
+        .collect();
+    for schema_enum in &schema.enums {
+        let enum_name = schema_enum.name.to_string();
+        let module = enum_name.to_snake_case();
+        if let Some(existing) = seen_modules.get(&module) {
+            let message = if *existing == enum_name
+                && (seen_entities.contains(existing) || seen_externs.contains(existing))
+            {
+                format!("enum '{}' has the same name as an entity", enum_name)
+            } else if *existing == enum_name {
+                format!("duplicate enum name '{}'", enum_name)
+            } else {
+                format!(
+                    "enum '{}' clashes with '{}': both would generate module '{}'",
+                    enum_name, existing, module
+                )
+            };
+            return Err(syn::Error::new(schema_enum.name.span(), message));
+        }
+        seen_modules.insert(module, enum_name);
+    }
+
     // Build entity registry for cross-reference
-    let registry = EntityRegistry::new(&schema.entities);
+    let registry = EntityRegistry::new(&schema.entities, &schema.enums, &schema.externs);
+
+    // Explicit index names must be unique across the whole schema, since
+    // all entities end up in the same database
+    let mut seen_index_names = HashSet::new();
+    for entity in &schema.entities {
+        for field in &entity.fields {
+            let names = [&field.attrs.unique_name, &field.attrs.index_name];
+            for name in names.into_iter().flatten() {
+                if !seen_index_names.insert(name.value.clone()) {
+                    return Err(syn::Error::new(
+                        name.span,
+                        format!("duplicate index name '{}'", name.value),
+                    ));
+                }
+            }
+        }
+    }
 
     // Analyze each entity
     let mut analyzed_entities = Vec::new();
@@ -78,13 +220,15 @@ pub fn analyze_schema(schema: Schema) -> Result<AnalyzedSchema> {
 
     Ok(AnalyzedSchema {
         entities: analyzed_entities,
+        enums: schema.enums,
+        externs: schema.externs,
     })
 }
 
 fn analyze_entity(entity: EntityDef, registry: &EntityRegistry) -> Result<AnalyzedEntity> {
     // Reject created_at/updated_at only when they'd collide with auto-generated timestamps
     for field in &entity.fields {
-        let name = field.name.to_string();
+        let name = field.name.unraw().to_string();
         if name == "created_at" && entity.attrs.has_created_at {
             return Err(syn::Error::new(
                 field.name.span(),
@@ -102,13 +246,61 @@ fn analyze_entity(entity: EntityDef, registry: &EntityRegistry) -> Result<Analyz
     let mut analyzed_fields = Vec::new();
 
     for field in entity.fields {
-        analyzed_fields.push(analyze_field(field, registry)?);
+        analyzed_fields.push(analyze_field(field, &entity.name, registry)?);
+    }
+
+    // #[schema(with)] replaces a column's type in the Model's JsonSchema,
+    // so it needs a column and the derive
+    for field in &analyzed_fields {
+        if let Some(ref with) = field.attrs.schema_with {
+            if !matches!(field.ty, FieldType::Scalar { .. } | FieldType::Enum { .. }) {
+                return Err(syn::Error::new(
+                    with.span,
+                    "#[schema(with)] only applies to scalar fields, not relationships",
+                ));
+            }
+            if !entity.attrs.json_schema {
+                return Err(syn::Error::new(
+                    with.span,
+                    format!(
+                        "#[schema(with)] has no effect: entity '{}' is marked #[no_json_schema]",
+                        entity.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    // #[alias] names must not shadow another field when deserializing
+    for field in &analyzed_fields {
+        for alias in &field.attrs.aliases {
+            if !matches!(field.ty, FieldType::Scalar { .. } | FieldType::Enum { .. }) {
+                return Err(syn::Error::new(
+                    alias.span,
+                    "#[alias] only applies to scalar fields, not relationships",
+                ));
+            }
+            if analyzed_fields
+                .iter()
+                .any(|f| f.name.unraw() == alias.value)
+            {
+                return Err(syn::Error::new(
+                    alias.span,
+                    format!(
+                        "alias '{}' is already a field of entity '{}'",
+                        alias.value, entity.name
+                    ),
+                ));
+            }
+        }
     }
 
     // Validate custom primary key columns exist in the entity
     if let Some(ref pk_cols) = entity.attrs.primary_key {
-        let field_names: HashSet<String> =
-            analyzed_fields.iter().map(|f| f.name.to_string()).collect();
+        let field_names: HashSet<String> = analyzed_fields
+            .iter()
+            .map(|f| f.name.unraw().to_string())
+            .collect();
 
         for col in pk_cols {
             if !field_names.contains(col) {
@@ -124,7 +316,20 @@ fn analyze_entity(entity: EntityDef, registry: &EntityRegistry) -> Result<Analyz
 
         // Validate PK columns are scalar types (not relationships)
         for field in &analyzed_fields {
-            let fname = field.name.to_string();
+            let fname = field.name.unraw().to_string();
+            if pk_cols.contains(&fname) && matches!(field.ty, FieldType::Scalar { array: true, .. })
+            {
+                return Err(syn::Error::new(
+                    field.name.span(),
+                    format!("primary_key column '{}' cannot be an array", fname),
+                ));
+            }
+            if pk_cols.contains(&fname) && matches!(field.ty, FieldType::Enum { .. }) {
+                return Err(syn::Error::new(
+                    field.name.span(),
+                    format!("primary_key column '{}' cannot be an enum", fname),
+                ));
+            }
             if pk_cols.contains(&fname) && !matches!(field.ty, FieldType::Scalar { .. }) {
                 return Err(syn::Error::new(
                     field.name.span(),
@@ -145,13 +350,53 @@ fn analyze_entity(entity: EntityDef, registry: &EntityRegistry) -> Result<Analyz
     })
 }
 
-fn analyze_field(field: FieldDef, registry: &EntityRegistry) -> Result<AnalyzedField> {
-    let ty = match field.ty {
-        RawFieldType::Scalar { scalar, optional } => FieldType::Scalar { scalar, optional },
+fn analyze_field(
+    field: FieldDef,
+    owner: &Ident,
+    registry: &EntityRegistry,
+) -> Result<AnalyzedField> {
+    if let Some(ref through) = field.attrs.through {
+        let ty = analyze_many_to_many(&field, through, owner, registry)?;
+        if field.attrs.default.is_some() {
+            return Err(syn::Error::new(
+                field.name.span(),
+                "#[default] only applies to scalar fields, not relationships",
+            ));
+        }
+        check_referential_actions(&field.attrs, &ty)?;
+        return Ok(AnalyzedField {
+            attrs: field.attrs,
+            name: field.name,
+            ty,
+            default: None,
+            span: field.span,
+        });
+    }
 
-        RawFieldType::Vec { inner } => {
+    let ty = match field.ty {
+        RawFieldType::Scalar {
+            scalar,
+            optional,
+            array,
+        } => FieldType::Scalar {
+            scalar,
+            optional,
+            array,
+        },
+
+        RawFieldType::Vec { inner, option } => {
             let inner_name = inner.to_string();
 
+            if registry.enum_variants(&inner_name).is_some() {
+                return Err(syn::Error::new(
+                    inner.span(),
+                    format!(
+                        "Vec<{}> is not supported: enum columns hold a single value",
+                        inner_name
+                    ),
+                ));
+            }
+
             // Vec<T> must reference an entity (has_many)
             if !registry.contains(&inner_name) {
                 return Err(syn::Error::new(
@@ -163,23 +408,41 @@ fn analyze_field(field: FieldDef, registry: &EntityRegistry) -> Result<AnalyzedF
                 ));
             }
 
+            // An entity without children already loads an empty Vec
+            if let Some(option) = option {
+                return Err(syn::Error::new(
+                    option.span(),
+                    "has_many relations cannot be Optional; omit Option<>",
+                ));
+            }
+
             FieldType::HasMany { target: inner }
         }
 
+        RawFieldType::VecOfOption { option, inner } => {
+            return Err(syn::Error::new(
+                option.span(),
+                format!("Vec of Option is not supported; use Vec<{}>", inner),
+            ));
+        }
+
         RawFieldType::Unknown { name, optional } => {
             let type_name = name.to_string();
 
             // If it's a known entity, it's a belongs_to relationship
-            if registry.contains(&type_name) {
+            if let Some(target_id) = registry.id_type(&type_name) {
                 FieldType::BelongsTo {
                     target: name,
                     optional,
+                    target_id,
                 }
+            } else if registry.enum_variants(&type_name).is_some() {
+                FieldType::Enum { name, optional }
             } else {
                 return Err(syn::Error::new(
                     name.span(),
                     format!(
-                        "unknown type '{}'. Use a scalar type (String, i32, etc.) or reference a defined entity.",
+                        "unknown type '{}'. Use a scalar type (String, i32, etc.), a schema enum, or reference a defined entity.",
                         type_name
                     ),
                 ));
@@ -187,14 +450,250 @@ fn analyze_field(field: FieldDef, registry: &EntityRegistry) -> Result<AnalyzedF
         }
     };
 
+    let default = match field.attrs.default {
+        Some(ref default) => Some(check_default(default, &ty, &field.name, registry)?),
+        None => None,
+    };
+    check_referential_actions(&field.attrs, &ty)?;
+
     Ok(AnalyzedField {
         attrs: field.attrs,
         name: field.name,
         ty,
+        default,
         span: field.span,
     })
 }
 
+/// Checks that `#[on_delete]` and `#[on_update]` sit on a belongs_to field,
+/// and that `set_null` has a nullable foreign key to clear.
+fn check_referential_actions(attrs: &FieldAttrs, ty: &FieldType) -> Result<()> {
+    let actions = [
+        ("on_delete", &attrs.on_delete),
+        ("on_update", &attrs.on_update),
+    ];
+    for (name, action) in actions {
+        let Some(action) = action else {
+            continue;
+        };
+        match ty {
+            FieldType::BelongsTo {
+                optional: false,
+                target,
+                ..
+            } if action.kind == ReferentialActionKind::SetNull => {
+                return Err(syn::Error::new(
+                    action.span,
+                    format!(
+                        "#[{}(set_null)] needs a nullable foreign key; make the relationship Option<{}>",
+                        name, target
+                    ),
+                ));
+            }
+            FieldType::BelongsTo { .. } => {}
+            _ => {
+                return Err(syn::Error::new(
+                    action.span,
+                    format!("#[{}] only applies to belongs_to relationships", name),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a `#[default]` value fits the column it is declared on.
+fn check_default(
+    value: &DefaultValue,
+    ty: &FieldType,
+    field: &Ident,
+    registry: &EntityRegistry,
+) -> Result<DefaultValue> {
+    let error = |message: String| syn::Error::new(field.span(), message);
+
+    let scalar = match ty {
+        FieldType::Enum { name, .. } => {
+            let variants = registry
+                .enum_variants(&name.to_string())
+                .unwrap_or_default();
+            return match value {
+                DefaultValue::Variant(variant) if variants.contains(&variant.to_string()) => {
+                    Ok(value.clone())
+                }
+                DefaultValue::Variant(variant) => Err(syn::Error::new(
+                    variant.span(),
+                    format!(
+                        "unknown variant '{}' of enum '{}'. Variants: {}",
+                        variant,
+                        name,
+                        variants.join(", ")
+                    ),
+                )),
+                _ => Err(error(format!(
+                    "invalid default for {} field '{}': expected one of its variants, e.g. #[default({})]",
+                    name,
+                    field.unraw(),
+                    variants[0]
+                ))),
+            };
+        }
+        FieldType::Scalar {
+            array: false,
+            scalar,
+            ..
+        } => scalar,
+        FieldType::Scalar { array: true, .. } => {
+            return Err(error(
+                "#[default] is not supported on array columns".to_string(),
+            ));
+        }
+        _ => {
+            return Err(error(
+                "#[default] only applies to scalar fields, not relationships".to_string(),
+            ));
+        }
+    };
+
+    let fits = match (scalar, value) {
+        (ScalarType::String | ScalarType::Text, DefaultValue::Str(_)) => true,
+        (ScalarType::I32, DefaultValue::Int(n)) => i32::try_from(*n).is_ok(),
+        (ScalarType::I64, DefaultValue::Int(n)) => i64::try_from(*n).is_ok(),
+        (ScalarType::F32 | ScalarType::F64, DefaultValue::Int(_) | DefaultValue::Float(_)) => true,
+        (ScalarType::Bool, DefaultValue::Bool(_)) => true,
+        (ScalarType::DateTime | ScalarType::NaiveDateTime, DefaultValue::Now) => true,
+        (ScalarType::Uuid | ScalarType::Date | ScalarType::Decimal | ScalarType::Json, _) => {
+            return Err(error(format!(
+                "#[default] is not supported on {} columns",
+                scalar_name(scalar)
+            )));
+        }
+        _ => false,
+    };
+    if !fits {
+        let expected = match scalar {
+            ScalarType::String | ScalarType::Text => "a string literal",
+            ScalarType::I32 => "an integer literal that fits in an i32",
+            ScalarType::I64 => "an integer literal that fits in an i64",
+            ScalarType::F32 | ScalarType::F64 => "a number literal",
+            ScalarType::Bool => "true or false",
+            _ => "now()",
+        };
+        return Err(error(format!(
+            "invalid default for {} field '{}': expected {}",
+            scalar_name(scalar),
+            field.unraw(),
+            expected
+        )));
+    }
+
+    Ok(value.clone())
+}
+
+/// The type name of a scalar as written in the schema.
+fn scalar_name(scalar: &ScalarType) -> &'static str {
+    match scalar {
+        ScalarType::String => "String",
+        ScalarType::Text => "Text",
+        ScalarType::I32 => "i32",
+        ScalarType::I64 => "i64",
+        ScalarType::F32 => "f32",
+        ScalarType::F64 => "f64",
+        ScalarType::Bool => "bool",
+        ScalarType::Uuid => "Uuid",
+        ScalarType::DateTime => "DateTime",
+        ScalarType::NaiveDateTime => "NaiveDateTime",
+        ScalarType::Date => "Date",
+        ScalarType::Decimal => "Decimal",
+        ScalarType::Json => "Json",
+    }
+}
+
+/// Resolves a `#[through(Join)]` field, which must be a `Vec<Target>`
+/// whose join entity belongs to both the owner and the target.
+fn analyze_many_to_many(
+    field: &FieldDef,
+    through: &Ident,
+    owner: &Ident,
+    registry: &EntityRegistry,
+) -> Result<FieldType> {
+    let target = match &field.ty {
+        RawFieldType::Vec {
+            inner,
+            option: None,
+        } => inner,
+        _ => {
+            return Err(syn::Error::new(
+                through.span(),
+                "#[through] only applies to Vec<Entity> fields",
+            ));
+        }
+    };
+
+    let target_name = target.to_string();
+    if !registry.contains(&target_name) {
+        return Err(syn::Error::new(
+            target.span(),
+            format!(
+                "unknown entity '{}' in Vec<{0}>. Did you define this entity?",
+                target_name
+            ),
+        ));
+    }
+
+    let through_name = through.to_string();
+    if !registry.contains(&through_name) {
+        return Err(syn::Error::new(
+            through.span(),
+            format!(
+                "unknown entity '{}' in #[through({0})]. Did you define this entity?",
+                through_name
+            ),
+        ));
+    }
+
+    // The join entity's fields pick the relations, so it has to be here
+    if !registry.is_local(&through_name) {
+        return Err(syn::Error::new(
+            through.span(),
+            format!(
+                "join entity '{}' is declared extern; define it in the same schema! block as '{}'",
+                through_name, owner
+            ),
+        ));
+    }
+
+    let missing = |side: &str| {
+        syn::Error::new(
+            through.span(),
+            format!(
+                "join entity '{}' needs a belongs_to field pointing at '{}'",
+                through_name, side
+            ),
+        )
+    };
+    let owner_name = owner.to_string();
+    let from_field = registry
+        .belongs_to_field(&through_name, &owner_name, None)
+        .ok_or_else(|| missing(&owner_name))?;
+    // A self-referencing relation needs a second field for the other side
+    let to_field = registry
+        .belongs_to_field(&through_name, &target_name, Some(from_field))
+        .ok_or_else(|| missing(&target_name))?;
+
+    let relation = |field: &Ident| {
+        Ident::new(
+            &super::generate::to_pascal_case(&field.unraw().to_string()),
+            field.span(),
+        )
+    };
+    Ok(FieldType::ManyToMany {
+        target: target.clone(),
+        through: through.clone(),
+        from_relation: relation(from_field),
+        to_relation: relation(to_field),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -261,6 +760,107 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_analyze_belongs_to_uuid_target() {
+        let input = quote! {
+            #[id(Uuid)]
+            User {
+                email: String,
+            }
+
+            Post {
+                author: User,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+
+        let post = &analyzed.entities[1];
+        assert!(matches!(
+            post.fields[0].ty,
+            FieldType::BelongsTo {
+                target_id: IdType::Uuid,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_analyze_extern_entity_references() {
+        let input = quote! {
+            #[id(Uuid)]
+            extern entity User;
+            extern entity Comment;
+
+            Post {
+                author: User,
+                comments: Vec<Comment>,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+
+        assert_eq!(analyzed.entities.len(), 1);
+        assert_eq!(analyzed.externs.len(), 2);
+        let post = &analyzed.entities[0];
+        assert!(matches!(
+            post.fields[0].ty,
+            FieldType::BelongsTo {
+                target_id: IdType::Uuid,
+                ..
+            }
+        ));
+        assert!(matches!(post.fields[1].ty, FieldType::HasMany { .. }));
+    }
+
+    #[test]
+    fn test_analyze_extern_entity_errors() {
+        let cases = [
+            (
+                quote! {
+                    extern entity User;
+                    User { name: String }
+                },
+                "entity 'User' is declared extern but also defined in this schema",
+            ),
+            (
+                quote! {
+                    extern entity User;
+                    extern entity User;
+                    Post { author: User }
+                },
+                "duplicate extern entity 'User'",
+            ),
+            (
+                quote! {
+                    extern entity Status;
+                    enum Status { Draft }
+                    Post { title: String }
+                },
+                "enum 'Status' has the same name as an entity",
+            ),
+            (
+                quote! {
+                    extern entity Membership;
+                    extern entity Group;
+                    Member {
+                        #[through(Membership)]
+                        groups: Vec<Group>,
+                    }
+                },
+                "join entity 'Membership' is declared extern",
+            ),
+        ];
+
+        for (input, message) in cases {
+            let parsed = parse_schema(input).unwrap();
+            let err = analyze_schema(parsed).unwrap_err().to_string();
+            assert!(err.contains(message), "{}", err);
+        }
+    }
+
     #[test]
     fn test_analyze_optional_belongs_to() {
         let input = quote! {
@@ -298,6 +898,179 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("unknown entity"));
     }
 
+    fn analyze_roles_field(join: proc_macro2::TokenStream) -> Result<AnalyzedSchema> {
+        let input = quote! {
+            User {
+                #[through(UsersRole)]
+                roles: Vec<Role>,
+            }
+
+            Role {
+                name: String,
+            }
+
+            #join
+        };
+        analyze_schema(parse_schema(input).unwrap())
+    }
+
+    #[test]
+    fn test_analyze_many_to_many() {
+        let analyzed = analyze_roles_field(quote! {
+            UsersRole {
+                granted_by: Option<User>,
+                member: User,
+                role: Role,
+            }
+        })
+        .unwrap();
+
+        let FieldType::ManyToMany {
+            target,
+            through,
+            from_relation,
+            to_relation,
+        } = &analyzed.entities[0].fields[0].ty
+        else {
+            panic!("expected a many-to-many field");
+        };
+        assert_eq!(target, "Role");
+        assert_eq!(through, "UsersRole");
+        assert_eq!(from_relation, "GrantedBy");
+        assert_eq!(to_relation, "Role");
+    }
+
+    #[test]
+    fn test_many_to_many_errors() {
+        let err = analyze_roles_field(quote! {}).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown entity 'UsersRole' in #[through(UsersRole)]. Did you define this entity?"
+        );
+
+        let err = analyze_roles_field(quote! {
+            UsersRole {
+                role: Role,
+            }
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "join entity 'UsersRole' needs a belongs_to field pointing at 'User'"
+        );
+
+        let err = analyze_roles_field(quote! {
+            UsersRole {
+                user: User,
+                role_id: i32,
+            }
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "join entity 'UsersRole' needs a belongs_to field pointing at 'Role'"
+        );
+
+        let input = quote! {
+            User {
+                #[through(UsersRole)]
+                role: Role,
+            }
+
+            Role {
+                name: String,
+            }
+
+            UsersRole {
+                user: User,
+                role: Role,
+            }
+        };
+        let err = analyze_schema(parse_schema(input).unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "#[through] only applies to Vec<Entity> fields"
+        );
+    }
+
+    fn analyze_tags_field(ty: proc_macro2::TokenStream) -> Result<AnalyzedSchema> {
+        let input = quote! {
+            Post {
+                tags: #ty,
+            }
+
+            Tag {
+                name: String,
+            }
+        };
+        analyze_schema(parse_schema(input).unwrap())
+    }
+
+    #[test]
+    fn test_analyze_array_columns() {
+        let analyzed = analyze_tags_field(quote!(Vec<String>)).unwrap();
+        assert!(matches!(
+            analyzed.entities[0].fields[0].ty,
+            FieldType::Scalar {
+                optional: false,
+                array: true,
+                ..
+            }
+        ));
+
+        let analyzed = analyze_tags_field(quote!(Option<Vec<String>>)).unwrap();
+        assert!(matches!(
+            analyzed.entities[0].fields[0].ty,
+            FieldType::Scalar {
+                optional: true,
+                array: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_optional_has_many_error() {
+        let err = analyze_tags_field(quote!(Option<Vec<Tag>>)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "has_many relations cannot be Optional; omit Option<>"
+        );
+    }
+
+    #[test]
+    fn test_optional_vec_of_unknown_entity_error() {
+        let err = analyze_tags_field(quote!(Option<Vec<Label>>)).unwrap_err();
+        assert!(err.to_string().contains("unknown entity 'Label'"));
+    }
+
+    #[test]
+    fn test_vec_of_option_error() {
+        let err = analyze_tags_field(quote!(Vec<Option<Tag>>)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Vec of Option is not supported; use Vec<Tag>"
+        );
+
+        let err = analyze_tags_field(quote!(Vec<Option<String>>)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Vec of Option is not supported; use Vec<String>"
+        );
+    }
+
+    #[test]
+    fn test_array_primary_key_error() {
+        let input = quote! {
+            #[primary_key(codes)]
+            Region {
+                codes: Vec<String>,
+            }
+        };
+        let err = analyze_schema(parse_schema(input).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("cannot be an array"));
+    }
+
     #[test]
     fn test_unknown_type_error() {
         let input = quote! {
@@ -398,6 +1171,350 @@ mod tests {
         assert_eq!(field.attrs.column_name, Some("user_email".to_string()));
     }
 
+    #[test]
+    fn test_analyze_schema_with_rejected_on_relationship() {
+        let input = quote! {
+            Post {
+                #[schema(with = "i32")]
+                author: User,
+            }
+
+            User {
+                name: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let result = analyze_schema(parsed);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("only applies to scalar fields")
+        );
+    }
+
+    #[test]
+    fn test_analyze_alias_rejected_on_relationship() {
+        let input = quote! {
+            Post {
+                #[alias = "writer"]
+                author: User,
+            }
+
+            User {
+                name: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let result = analyze_schema(parsed);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("only applies to scalar fields")
+        );
+    }
+
+    #[test]
+    fn test_analyze_alias_conflicting_with_field() {
+        let input = quote! {
+            Post {
+                #[alias = "body"]
+                title: String,
+                body: Text,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let result = analyze_schema(parsed);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("alias 'body' is already a field of entity 'Post'")
+        );
+    }
+
+    #[test]
+    fn test_analyze_default_matches_column_type() {
+        let input = quote! {
+            Post {
+                #[default = "draft"]
+                status: Text,
+                #[default = 3]
+                rating: Option<f32>,
+                #[default = now()]
+                published_at: NaiveDateTime,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let defaults: Vec<_> = analyzed.entities[0]
+            .fields
+            .iter()
+            .map(|f| f.default.clone().unwrap())
+            .collect();
+        assert_eq!(
+            defaults,
+            vec![
+                DefaultValue::Str("draft".to_string()),
+                DefaultValue::Int(3),
+                DefaultValue::Now,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_invalid_default_errors() {
+        let cases = [
+            (
+                quote! { #[default = "abc"] views: i32 },
+                "invalid default for i32 field 'views': expected an integer literal",
+            ),
+            (
+                quote! { #[default = 3000000000] views: i32 },
+                "fits in an i32",
+            ),
+            (
+                quote! { #[default = 1] active: bool },
+                "expected true or false",
+            ),
+            (
+                quote! { #[default = now()] title: String },
+                "expected a string literal",
+            ),
+            (
+                quote! { #[default = "2024-01-01"] published_at: DateTime },
+                "expected now()",
+            ),
+            (
+                quote! { #[default = "x"] id_ref: Uuid },
+                "#[default] is not supported on Uuid columns",
+            ),
+            (
+                quote! { #[default = "x"] tags: Vec<String> },
+                "not supported on array columns",
+            ),
+        ];
+
+        for (field, message) in cases {
+            let input = quote! {
+                Post {
+                    #field,
+                }
+            };
+            let parsed = parse_schema(input).unwrap();
+            let err = analyze_schema(parsed).unwrap_err().to_string();
+            assert!(err.contains(message), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_analyze_default_rejected_on_relationship() {
+        let input = quote! {
+            User {
+                name: String,
+            }
+
+            Post {
+                #[default = 1]
+                author: User,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let result = analyze_schema(parsed);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("only applies to scalar fields")
+        );
+    }
+
+    #[test]
+    fn test_analyze_referential_actions_rejected_off_belongs_to() {
+        for input in [
+            quote! {
+                User {
+                    #[on_delete(cascade)]
+                    name: String,
+                }
+            },
+            quote! {
+                User {
+                    #[on_update(cascade)]
+                    posts: Vec<Post>,
+                }
+
+                Post {
+                    author: User,
+                }
+            },
+        ] {
+            let parsed = parse_schema(input).unwrap();
+            let err = analyze_schema(parsed).unwrap_err().to_string();
+            assert!(
+                err.contains("only applies to belongs_to relationships"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyze_set_null_requires_optional_relationship() {
+        let input = quote! {
+            User {
+                name: String,
+            }
+
+            Post {
+                #[on_delete(set_null)]
+                author: User,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let err = analyze_schema(parsed).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "#[on_delete(set_null)] needs a nullable foreign key; make the relationship Option<User>"
+        );
+
+        let input = quote! {
+            User {
+                name: String,
+            }
+
+            Post {
+                #[on_delete(set_null)]
+                #[on_update(cascade)]
+                author: Option<User>,
+            }
+        };
+        let parsed = parse_schema(input).unwrap();
+        assert!(analyze_schema(parsed).is_ok());
+    }
+
+    #[test]
+    fn test_analyze_enum_field() {
+        let input = quote! {
+            enum PostStatus { Draft, Published }
+
+            Post {
+                #[default(Published)]
+                status: PostStatus,
+                previous: Option<PostStatus>,
+            }
+        };
+
+        let analyzed = analyze_schema(parse_schema(input).unwrap()).unwrap();
+        assert_eq!(analyzed.enums.len(), 1);
+
+        let fields = &analyzed.entities[0].fields;
+        assert!(matches!(
+            &fields[0].ty,
+            FieldType::Enum { name, optional: false } if name == "PostStatus"
+        ));
+        assert!(matches!(
+            &fields[0].default,
+            Some(DefaultValue::Variant(variant)) if variant == "Published"
+        ));
+        assert!(matches!(
+            fields[1].ty,
+            FieldType::Enum { optional: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_analyze_enum_errors() {
+        let cases = [
+            (
+                quote! {
+                    enum Status { Draft }
+                    Post { #[default(Gone)] status: Status }
+                },
+                "unknown variant 'Gone' of enum 'Status'. Variants: Draft",
+            ),
+            (
+                quote! {
+                    enum Status { Draft }
+                    Post { #[default = "draft"] status: Status }
+                },
+                "expected one of its variants, e.g. #[default(Draft)]",
+            ),
+            (
+                quote! {
+                    enum Status { Draft }
+                    Post { #[default(Draft)] title: String }
+                },
+                "expected a string literal",
+            ),
+            (
+                quote! {
+                    enum Status { Draft }
+                    Post { history: Vec<Status> }
+                },
+                "Vec<Status> is not supported",
+            ),
+            (
+                quote! {
+                    enum Post { Draft }
+                    Post { title: String }
+                },
+                "enum 'Post' has the same name as an entity",
+            ),
+            (
+                quote! {
+                    enum Status { Draft }
+                    enum Status { Published }
+                    Post { title: String }
+                },
+                "duplicate enum name 'Status'",
+            ),
+            (
+                quote! {
+                    enum HTTPStatus { Ok }
+                    HttpStatus { code: i32 }
+                },
+                "both would generate module 'http_status'",
+            ),
+            (
+                quote! {
+                    enum Status { Draft }
+                    #[primary_key(status)]
+                    Post { status: Status }
+                },
+                "primary_key column 'status' cannot be an enum",
+            ),
+        ];
+
+        for (input, message) in cases {
+            let parsed = parse_schema(input).unwrap();
+            let err = analyze_schema(parsed).unwrap_err().to_string();
+            assert!(err.contains(message), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_analyze_schema_with_rejected_without_json_schema() {
+        let input = quote! {
+            #[no_json_schema]
+            Invoice {
+                #[schema(with = "String")]
+                total: Decimal,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let result = analyze_schema(parsed);
+        assert!(result.unwrap_err().to_string().contains("has no effect"));
+    }
+
     #[test]
     fn test_analyze_composite_primary_key() {
         let input = quote! {
@@ -479,4 +1596,46 @@ mod tests {
             Some(vec!["user_id".to_string(), "role_id".to_string()])
         );
     }
+
+    #[test]
+    fn test_analyze_duplicate_index_name_across_entities() {
+        let input = quote! {
+            User {
+                #[index(name = "idx_email")]
+                email: String,
+            }
+
+            Invite {
+                #[unique(name = "idx_email")]
+                email: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let result = analyze_schema(parsed);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("duplicate index name 'idx_email'")
+        );
+    }
+
+    #[test]
+    fn test_analyze_distinct_index_names() {
+        let input = quote! {
+            User {
+                #[index(name = "idx_users_email")]
+                email: String,
+            }
+
+            Invite {
+                #[index(name = "idx_invites_email")]
+                email: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        assert!(analyze_schema(parsed).is_ok());
+    }
 }
@@ -1,14 +1,20 @@
 //! Code generation for SeaORM entity modules.
 
 use heck::ToSnakeCase;
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::Ident;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
 
 use super::analyze::{AnalyzedEntity, AnalyzedField, AnalyzedSchema};
-use super::types::{FieldType, ScalarType};
+use super::parse::{DefaultValue, EnumDef};
+use super::types::{FieldType, IdType, ScalarType};
 
 /// Generate the complete schema code from analyzed entities.
 pub fn generate_schema(schema: AnalyzedSchema) -> TokenStream {
+    let enum_modules: Vec<TokenStream> = schema.enums.iter().map(generate_enum_module).collect();
+
     let entity_modules: Vec<TokenStream> = schema
         .entities
         .iter()
@@ -28,9 +34,99 @@ pub fn generate_schema(schema: AnalyzedSchema) -> TokenStream {
         })
         .collect();
 
+    // Extern entities live in a sibling module named after them, so
+    // relations can keep pointing at super::user: use super::user::user;
+    let extern_imports: Vec<TokenStream> = schema
+        .externs
+        .iter()
+        .map(|schema_extern| {
+            let mod_name = format_ident!("{}", schema_extern.name.to_string().to_snake_case());
+            quote! {
+                #[allow(unused_imports)]
+                use super::#mod_name::#mod_name;
+            }
+        })
+        .collect();
+
+    // Enums are re-exported under their own name: pub use post_status::PostStatus;
+    let enum_reexports: Vec<TokenStream> = schema
+        .enums
+        .iter()
+        .map(|schema_enum| {
+            let mod_name = enum_mod_name(&schema_enum.name);
+            let enum_name = &schema_enum.name;
+            quote! {
+                pub use #mod_name::#enum_name;
+            }
+        })
+        .collect();
+
     quote! {
+        #(#extern_imports)*
+        #(#enum_modules)*
         #(#entity_modules)*
         #(#reexports)*
+        #(#enum_reexports)*
+    }
+}
+
+fn enum_mod_name(name: &Ident) -> Ident {
+    format_ident!("{}", name.to_string().to_snake_case())
+}
+
+/// The value a variant is stored as, and serialized to: its snake_case name.
+fn enum_variant_value(variant: &Ident) -> String {
+    variant.to_string().to_snake_case()
+}
+
+/// A module holding a schema enum as a SeaORM active enum stored in a
+/// string column. The first variant is the `Default`, which fixtures use.
+fn generate_enum_module(schema_enum: &EnumDef) -> TokenStream {
+    let mod_name = enum_mod_name(&schema_enum.name);
+    let enum_name = &schema_enum.name;
+    let docs = &schema_enum.docs;
+    let variants = schema_enum.variants.iter().enumerate().map(|(i, variant)| {
+        let name = &variant.name;
+        let docs = &variant.docs;
+        let value = enum_variant_value(name);
+        let default = if i == 0 {
+            quote! { #[default] }
+        } else {
+            quote! {}
+        };
+        quote! {
+            #(#[doc = #docs])*
+            #default
+            #[sea_orm(string_value = #value)]
+            #[serde(rename = #value)]
+            #name,
+        }
+    });
+
+    quote! {
+        pub mod #mod_name {
+            use rapina::sea_orm;
+            use sea_orm::entity::prelude::*;
+            use serde::{Deserialize, Serialize};
+            use rapina::schemars::{self, JsonSchema};
+
+            #(#[doc = #docs])*
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize, JsonSchema)]
+            #[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+            pub enum #enum_name {
+                #(#variants)*
+            }
+        }
+    }
+}
+
+/// The Rust type of an enum column, as seen from an entity module.
+fn enum_type(name: &Ident, optional: bool) -> TokenStream {
+    let mod_name = enum_mod_name(name);
+    if optional {
+        quote! { Option<super::#mod_name::#name> }
+    } else {
+        quote! { super::#mod_name::#name }
     }
 }
 
@@ -47,6 +143,7 @@ fn generate_entity_module(entity: &AnalyzedEntity, schema: &AnalyzedSchema) -> T
     let model_fields = generate_model_fields(entity);
     let relation_variants = generate_relation_variants(entity, schema);
     let related_impls = generate_related_impls(entity, schema);
+    let index_statements = generate_index_statements(entity, &table_name);
 
     // Generate timestamp fields based on entity attrs
     let created_at_field = if entity.attrs.has_created_at {
@@ -72,10 +169,26 @@ fn generate_entity_module(entity: &AnalyzedEntity, schema: &AnalyzedSchema) -> T
         )
     });
 
-    let derive_attr = if has_floats {
-        quote! { #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, JsonSchema)] }
+    let eq_derive = if has_floats {
+        quote! {}
+    } else {
+        quote! { Eq, }
+    };
+
+    // #[no_json_schema] drops the derive for field types that don't
+    // implement JsonSchema; handlers then document the Model as an opaque
+    // object
+    let (json_schema_derive, json_schema_import) = if entity.attrs.json_schema {
+        (
+            quote! { , JsonSchema },
+            quote! { use rapina::schemars::{self, JsonSchema}; },
+        )
     } else {
-        quote! { #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize, JsonSchema)] }
+        (quote! {}, quote! {})
+    };
+
+    let derive_attr = quote! {
+        #[derive(Clone, Debug, PartialEq, #eq_derive DeriveEntityModel, Serialize, Deserialize #json_schema_derive)]
     };
 
     // Generate primary key fields
@@ -83,20 +196,48 @@ fn generate_entity_module(entity: &AnalyzedEntity, schema: &AnalyzedSchema) -> T
         // Custom primary key: mark specified fields with #[sea_orm(primary_key, auto_increment = false)]
         generate_custom_pk_fields(entity, pk_cols)
     } else {
-        // Default: auto-increment id
-        quote! {
-            #[sea_orm(primary_key)]
-            pub id: i32,
+        match entity.attrs.id_type {
+            // Default: auto-increment id
+            IdType::I32 => quote! {
+                #[sea_orm(primary_key)]
+                pub id: i32,
+            },
+            // Generated in before_save
+            IdType::Uuid => {
+                let id_type = IdType::Uuid.rust_type();
+                quote! {
+                    #[sea_orm(primary_key, auto_increment = false)]
+                    pub id: #id_type,
+                }
+            }
         }
     };
 
+    let model_docs = &entity.attrs.docs;
+    let fixtures = if entity.attrs.fixtures {
+        generate_fixtures(entity)
+    } else {
+        quote! {}
+    };
+
+    // With hooks or timestamps the behavior is implemented next to the
+    // module, so hook paths resolve from where `schema!` is invoked
+    let (inner_behavior, outer_behavior) = match generate_active_model_behavior(entity, &mod_name) {
+        Some(behavior) => (quote! {}, behavior),
+        None => (
+            quote! { impl ActiveModelBehavior for ActiveModel {} },
+            quote! {},
+        ),
+    };
+
     quote! {
         pub mod #mod_name {
             use rapina::sea_orm;
             use sea_orm::entity::prelude::*;
             use serde::{Deserialize, Serialize};
-            use rapina::schemars::{self, JsonSchema};
+            #json_schema_import
 
+            #(#[doc = #model_docs])*
             #derive_attr
             #[sea_orm(table_name = #table_name)]
             pub struct Model {
@@ -113,7 +254,327 @@ fn generate_entity_module(entity: &AnalyzedEntity, schema: &AnalyzedSchema) -> T
 
             #related_impls
 
-            impl ActiveModelBehavior for ActiveModel {}
+            #inner_behavior
+
+            /// Indexes declared with `#[unique]` and `#[index]`, ready to be
+            /// created from a migration.
+            pub fn indexes() -> Vec<sea_orm::sea_query::IndexCreateStatement> {
+                vec![#index_statements]
+            }
+
+            #fixtures
+        }
+
+        #outer_behavior
+    }
+}
+
+/// The `ActiveModelBehavior` impl maintaining `created_at`/`updated_at`,
+/// generating UUID ids and calling the entity's lifecycle hooks, or `None`
+/// when there is nothing to do and the empty default impl suffices.
+///
+/// The id and timestamps are filled in before the `before_save` hooks run,
+/// so a hook sees (and may override) them. A value the caller set
+/// explicitly is left alone, except for a nil UUID, which counts as unset.
+fn generate_active_model_behavior(
+    entity: &AnalyzedEntity,
+    mod_name: &Ident,
+) -> Option<TokenStream> {
+    let hooks = &entity.attrs.hooks;
+    let has_timestamps = entity.attrs.has_created_at || entity.attrs.has_updated_at;
+    let generates_id = entity.attrs.primary_key.is_none() && entity.attrs.id_type == IdType::Uuid;
+    if hooks.is_empty() && !has_timestamps && !generates_id {
+        return None;
+    }
+
+    let active_model = quote! { #mod_name::ActiveModel };
+    let model = quote! { #mod_name::Model };
+
+    let before_save = if has_timestamps || generates_id || !hooks.before_save.is_empty() {
+        let id = if generates_id {
+            quote! {
+                if insert && model.id.try_as_ref().is_none_or(|id| id.is_nil()) {
+                    model.id = rapina::sea_orm::ActiveValue::Set(rapina::uuid::Uuid::new_v4());
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let created_at = if entity.attrs.has_created_at {
+            quote! {
+                if insert && !model.created_at.is_set() {
+                    model.created_at = rapina::sea_orm::ActiveValue::Set(now);
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let updated_at = if entity.attrs.has_updated_at {
+            quote! {
+                if !model.updated_at.is_set() {
+                    model.updated_at = rapina::sea_orm::ActiveValue::Set(now);
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let timestamps = if has_timestamps {
+            quote! {
+                let now = rapina::chrono::Utc::now();
+                #created_at
+                #updated_at
+            }
+        } else {
+            quote! {}
+        };
+        let defaults = if has_timestamps || generates_id {
+            quote! {
+                let mut model = model;
+                #id
+                #timestamps
+            }
+        } else {
+            quote! {}
+        };
+        let calls = hooks.before_save.iter().map(|hook| {
+            quote_spanned! {hook.span()=>
+                let model = rapina::database::SaveHook::<#active_model>::call(&#hook, model, insert).await?;
+            }
+        });
+        // Only-`updated_at` entities without hooks never read the flag
+        let insert = if entity.attrs.has_created_at || generates_id || !hooks.before_save.is_empty()
+        {
+            quote! { insert }
+        } else {
+            quote! { _insert }
+        };
+        quote! {
+            async fn before_save<C>(self, _db: &C, #insert: bool) -> ::std::result::Result<Self, rapina::sea_orm::DbErr>
+            where
+                C: rapina::sea_orm::ConnectionTrait,
+            {
+                let model = self;
+                #defaults
+                #(#calls)*
+                Ok(model)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let after_save = if hooks.after_save.is_empty() {
+        quote! {}
+    } else {
+        let calls = hooks.after_save.iter().map(|hook| {
+            quote_spanned! {hook.span()=>
+                let model = rapina::database::SaveHook::<#model>::call(&#hook, model, insert).await?;
+            }
+        });
+        quote! {
+            async fn after_save<C>(model: #model, _db: &C, insert: bool) -> ::std::result::Result<#model, rapina::sea_orm::DbErr>
+            where
+                C: rapina::sea_orm::ConnectionTrait,
+            {
+                #(#calls)*
+                Ok(model)
+            }
+        }
+    };
+
+    let delete_hook = |name: &str, paths: &[TokenStream]| {
+        if paths.is_empty() {
+            return quote! {};
+        }
+        let name = format_ident!("{}", name);
+        let calls = paths.iter().map(|hook| {
+            quote_spanned! {hook.span()=>
+                let model = rapina::database::DeleteHook::<#active_model>::call(&#hook, model).await?;
+            }
+        });
+        quote! {
+            async fn #name<C>(self, _db: &C) -> ::std::result::Result<Self, rapina::sea_orm::DbErr>
+            where
+                C: rapina::sea_orm::ConnectionTrait,
+            {
+                let model = self;
+                #(#calls)*
+                Ok(model)
+            }
+        }
+    };
+    let before_delete = delete_hook("before_delete", &hooks.before_delete);
+    let after_delete = delete_hook("after_delete", &hooks.after_delete);
+
+    Some(quote! {
+        #[rapina::async_trait::async_trait]
+        impl rapina::sea_orm::ActiveModelBehavior for #active_model {
+            #before_save
+            #after_save
+            #before_delete
+            #after_delete
+        }
+    })
+}
+
+/// The Rust type of a scalar column: `T`, `Vec<T>`, or either in an `Option`.
+fn scalar_type(scalar: &ScalarType, optional: bool, array: bool) -> TokenStream {
+    let rust_type = scalar.rust_type();
+    let rust_type = if array {
+        quote! { Vec<#rust_type> }
+    } else {
+        rust_type
+    };
+    if optional {
+        quote! { Option<#rust_type> }
+    } else {
+        rust_type
+    }
+}
+
+/// The columns of the generated `Model`, in declaration order.
+fn model_columns(entity: &AnalyzedEntity) -> Vec<(Ident, TokenStream)> {
+    let mut columns = Vec::new();
+
+    match entity.attrs.primary_key {
+        Some(ref pk_cols) => {
+            for col_name in pk_cols {
+                if let Some(field) = entity.fields.iter().find(|f| f.name.unraw() == col_name)
+                    && let FieldType::Scalar { scalar, .. } = &field.ty
+                {
+                    columns.push((field.name.clone(), scalar.rust_type()));
+                }
+            }
+        }
+        None => columns.push((format_ident!("id"), entity.attrs.id_type.rust_type())),
+    }
+
+    let pk_cols = entity.attrs.primary_key.as_deref().unwrap_or_default();
+    for field in &entity.fields {
+        if pk_cols
+            .iter()
+            .any(|pk| pk == &field.name.unraw().to_string())
+        {
+            continue;
+        }
+        match &field.ty {
+            FieldType::Scalar {
+                scalar,
+                optional,
+                array,
+            } => {
+                columns.push((field.name.clone(), scalar_type(scalar, *optional, *array)));
+            }
+            FieldType::Enum { name, optional } => {
+                columns.push((field.name.clone(), enum_type(name, *optional)));
+            }
+            FieldType::BelongsTo {
+                optional,
+                target_id,
+                ..
+            } => {
+                let fk_name =
+                    format_ident!("{}_id", field.name.unraw().to_string().to_snake_case());
+                let id_type = target_id.rust_type();
+                let ty = if *optional {
+                    quote! { Option<#id_type> }
+                } else {
+                    id_type
+                };
+                columns.push((fk_name, ty));
+            }
+            FieldType::HasMany { .. } | FieldType::ManyToMany { .. } => {}
+        }
+    }
+
+    if entity.attrs.has_created_at {
+        columns.push((format_ident!("created_at"), quote! { DateTimeUtc }));
+    }
+    if entity.attrs.has_updated_at {
+        columns.push((format_ident!("updated_at"), quote! { DateTimeUtc }));
+    }
+
+    columns
+}
+
+/// Test builders for `Model` and `ActiveModel`, enabled by `#[fixtures]`.
+///
+/// They compile under `cfg(test)`, or always with Rapina's `fixtures`
+/// feature, so integration tests can build models from the library.
+///
+/// Every column starts at its type's `Default` value: empty strings, zero,
+/// `false`, `None`, the nil UUID, the Unix epoch for timestamps, and the
+/// first variant for enums.
+fn generate_fixtures(entity: &AnalyzedEntity) -> TokenStream {
+    let columns = model_columns(entity);
+    let names: Vec<&Ident> = columns.iter().map(|(name, _)| name).collect();
+    let setters: Vec<TokenStream> = columns
+        .iter()
+        .map(|(name, ty)| {
+            let doc = format!(" Sets `{}`.", name.unraw());
+            quote! {
+                #[doc = #doc]
+                pub fn #name(mut self, value: impl Into<#ty>) -> Self {
+                    self.model.#name = value.into();
+                    self
+                }
+            }
+        })
+        .collect();
+
+    quote! {
+        rapina::__schema_fixtures! {
+            /// Builder for `Model` test fixtures, created by `Model::fixture()`.
+            #[derive(Clone, Debug)]
+            pub struct ModelFixture {
+                model: Model,
+            }
+
+            impl Model {
+                /// Starts a fixture with every column set to its default value.
+                pub fn fixture() -> ModelFixture {
+                    ModelFixture {
+                        model: Model {
+                            #(#names: Default::default(),)*
+                        },
+                    }
+                }
+            }
+
+            impl ModelFixture {
+                #(#setters)*
+
+                /// Returns the finished `Model`.
+                pub fn build(self) -> Model {
+                    self.model
+                }
+            }
+
+            /// Builder for `ActiveModel` test fixtures, created by `ActiveModel::fixture()`.
+            #[derive(Clone, Debug)]
+            pub struct ActiveModelFixture {
+                model: Model,
+            }
+
+            impl ActiveModel {
+                /// Starts a fixture with every column set to its default value.
+                pub fn fixture() -> ActiveModelFixture {
+                    ActiveModelFixture {
+                        model: Model::fixture().build(),
+                    }
+                }
+            }
+
+            impl ActiveModelFixture {
+                #(#setters)*
+
+                /// Returns an `ActiveModel` with every column `Set`.
+                pub fn build(self) -> ActiveModel {
+                    ActiveModel {
+                        #(#names: sea_orm::ActiveValue::Set(self.model.#names),)*
+                    }
+                }
+            }
         }
     }
 }
@@ -122,12 +583,18 @@ fn generate_custom_pk_fields(entity: &AnalyzedEntity, pk_cols: &[String]) -> Tok
     let fields: Vec<TokenStream> = pk_cols
         .iter()
         .filter_map(|col_name| {
-            let field = entity.fields.iter().find(|f| f.name == col_name)?;
+            let field = entity.fields.iter().find(|f| f.name.unraw() == col_name)?;
             if let FieldType::Scalar { scalar, .. } = &field.ty {
                 let field_name = &field.name;
                 let rust_type = scalar.rust_type();
+                let docs = &field.attrs.docs;
+                let schema_attr = schema_with_attr(field);
+                let alias_attrs = serde_alias_attrs(field);
                 Some(quote! {
+                    #(#[doc = #docs])*
                     #[sea_orm(primary_key, auto_increment = false)]
+                    #schema_attr
+                    #alias_attrs
                     pub #field_name: #rust_type,
                 })
             } else {
@@ -139,13 +606,31 @@ fn generate_custom_pk_fields(entity: &AnalyzedEntity, pk_cols: &[String]) -> Tok
     quote! { #(#fields)* }
 }
 
+/// `#[serde(alias = "...")]` for each `#[alias]` of a field, so request
+/// bodies using a former name still deserialize.
+fn serde_alias_attrs(field: &AnalyzedField) -> TokenStream {
+    let aliases = field.attrs.aliases.iter().map(|alias| &alias.value);
+    quote! { #(#[serde(alias = #aliases)])* }
+}
+
+/// `#[schemars(with = "...")]` for a field declared with `#[schema(with)]`.
+fn schema_with_attr(field: &AnalyzedField) -> TokenStream {
+    match &field.attrs.schema_with {
+        Some(with) => {
+            let value = &with.value;
+            quote! { #[schemars(with = #value)] }
+        }
+        None => quote! {},
+    }
+}
+
 fn generate_model_fields(entity: &AnalyzedEntity) -> TokenStream {
     let pk_cols = entity.attrs.primary_key.as_deref().unwrap_or_default();
 
     let fields: Vec<TokenStream> = entity
         .fields
         .iter()
-        .filter(|f| !pk_cols.iter().any(|pk| pk == &f.name.to_string()))
+        .filter(|f| !pk_cols.iter().any(|pk| pk == &f.name.unraw().to_string()))
         .filter_map(generate_model_field)
         .collect();
 
@@ -154,89 +639,212 @@ fn generate_model_fields(entity: &AnalyzedEntity) -> TokenStream {
     }
 }
 
-fn generate_model_field(field: &AnalyzedField) -> Option<TokenStream> {
+/// A scalar or enum column of the `Model`, with its `#[sea_orm]`, schema
+/// and alias attributes.
+fn generate_column_field(
+    field: &AnalyzedField,
+    column_type_attr: Option<TokenStream>,
+    final_type: TokenStream,
+) -> TokenStream {
     let field_name = &field.name;
+    let docs = &field.attrs.docs;
 
-    match &field.ty {
-        FieldType::Scalar { scalar, optional } => {
-            let rust_type = scalar.rust_type();
-            let column_type_attr = scalar.column_type_attr();
+    // Build sea_orm attribute parts
+    let mut sea_orm_parts: Vec<TokenStream> = Vec::new();
 
-            let final_type = if *optional {
-                quote! { Option<#rust_type> }
-            } else {
-                rust_type
-            };
+    // Add unique if specified
+    if field.attrs.unique {
+        sea_orm_parts.push(quote! { unique });
+    }
 
-            // Build sea_orm attribute parts
-            let mut sea_orm_parts: Vec<TokenStream> = Vec::new();
+    // Add indexed if specified
+    if field.attrs.indexed {
+        sea_orm_parts.push(quote! { indexed });
+    }
 
-            // Add unique if specified
-            if field.attrs.unique {
-                sea_orm_parts.push(quote! { unique });
-            }
+    // Add custom column name if specified
+    if let Some(ref col_name) = field.attrs.column_name {
+        sea_orm_parts.push(quote! { column_name = #col_name });
+    }
 
-            // Add indexed if specified
-            if field.attrs.indexed {
-                sea_orm_parts.push(quote! { indexed });
-            }
+    if let Some(ref default) = field.default {
+        sea_orm_parts.push(column_default_attr(default));
+    }
 
-            // Add custom column name if specified
-            if let Some(ref col_name) = field.attrs.column_name {
-                sea_orm_parts.push(quote! { column_name = #col_name });
-            }
+    // Combine column_type_attr with other attributes
+    let field_attr = if sea_orm_parts.is_empty() {
+        column_type_attr.unwrap_or_default()
+    } else if let Some(col_type) = column_type_attr {
+        // Extract the column_type value and combine
+        let col_type_str = col_type.to_string();
+        if col_type_str.contains("column_type") {
+            // Parse out the column_type value
+            let combined = quote! {
+                #[sea_orm(#(#sea_orm_parts),*)]
+                #col_type
+            };
+            combined
+        } else {
+            quote! { #[sea_orm(#(#sea_orm_parts),*)] }
+        }
+    } else {
+        quote! { #[sea_orm(#(#sea_orm_parts),*)] }
+    };
 
-            // Combine column_type_attr with other attributes
-            let field_attr = if sea_orm_parts.is_empty() {
-                column_type_attr.unwrap_or_default()
-            } else if let Some(col_type) = column_type_attr {
-                // Extract the column_type value and combine
-                let col_type_str = col_type.to_string();
-                if col_type_str.contains("column_type") {
-                    // Parse out the column_type value
-                    let combined = quote! {
-                        #[sea_orm(#(#sea_orm_parts),*)]
-                        #col_type
-                    };
-                    combined
-                } else {
-                    quote! { #[sea_orm(#(#sea_orm_parts),*)] }
-                }
+    let schema_attr = schema_with_attr(field);
+    let alias_attrs = serde_alias_attrs(field);
+
+    quote! {
+        #(#[doc = #docs])*
+        #field_attr
+        #schema_attr
+        #alias_attrs
+        pub #field_name: #final_type,
+    }
+}
+
+fn generate_model_field(field: &AnalyzedField) -> Option<TokenStream> {
+    let field_name = &field.name;
+    let docs = &field.attrs.docs;
+
+    match &field.ty {
+        FieldType::Scalar {
+            scalar,
+            optional,
+            array,
+        } => {
+            // SeaORM derives the array column type from the element type
+            let column_type_attr = if *array {
+                None
             } else {
-                quote! { #[sea_orm(#(#sea_orm_parts),*)] }
+                scalar.column_type_attr()
             };
-
-            Some(quote! {
-                #field_attr
-                pub #field_name: #final_type,
-            })
+            let final_type = scalar_type(scalar, *optional, *array);
+            Some(generate_column_field(field, column_type_attr, final_type))
         }
 
+        // The active enum carries its own column type
+        FieldType::Enum { name, optional } => Some(generate_column_field(
+            field,
+            None,
+            enum_type(name, *optional),
+        )),
+
         FieldType::BelongsTo {
-            target: _,
             optional,
+            target_id,
+            ..
         } => {
-            // Generate foreign key column: author -> author_id
-            let fk_name = format_ident!("{}_id", field_name.to_string().to_snake_case());
+            // Generate foreign key column: author -> author_id, typed like
+            // the target's id
+            let fk_name = format_ident!("{}_id", field_name.unraw().to_string().to_snake_case());
+            let id_type = target_id.rust_type();
 
             if *optional {
                 Some(quote! {
-                    pub #fk_name: Option<i32>,
+                    #(#[doc = #docs])*
+                    pub #fk_name: Option<#id_type>,
                 })
             } else {
                 Some(quote! {
-                    pub #fk_name: i32,
+                    #(#[doc = #docs])*
+                    pub #fk_name: #id_type,
                 })
             }
         }
 
-        FieldType::HasMany { .. } => {
+        FieldType::HasMany { .. } | FieldType::ManyToMany { .. } => {
             // has_many doesn't generate a column, just a relation
             None
         }
     }
 }
 
+/// The `default_value` / `default_expr` part of a column's `#[sea_orm]`
+/// attribute. SeaORM only takes literals as `default_value`, so negative
+/// numbers and `now()` go through `default_expr`. Enum variants default to
+/// the string they are stored as.
+fn column_default_attr(default: &DefaultValue) -> TokenStream {
+    match default {
+        DefaultValue::Str(value) => quote! { default_value = #value },
+        DefaultValue::Bool(value) => quote! { default_value = #value },
+        DefaultValue::Int(value) if *value >= 0 => {
+            let lit = proc_macro2::Literal::i128_unsuffixed(*value);
+            quote! { default_value = #lit }
+        }
+        DefaultValue::Float(value) if !value.starts_with('-') => {
+            let lit = syn::LitFloat::new(value, Span::call_site());
+            quote! { default_value = #lit }
+        }
+        DefaultValue::Int(value) => {
+            let expr = value.to_string();
+            quote! { default_expr = #expr }
+        }
+        DefaultValue::Float(value) => quote! { default_expr = #value },
+        DefaultValue::Now => {
+            quote! { default_expr = "sea_orm::sea_query::Expr::current_timestamp()" }
+        }
+        DefaultValue::Variant(variant) => {
+            let value = enum_variant_value(variant);
+            quote! { default_value = #value }
+        }
+    }
+}
+
+/// Default index name: `uq_{table}_{column}` or `idx_{table}_{column}`.
+fn default_index_name(prefix: &str, table_name: &str, column: &str) -> String {
+    format!("{}_{}_{}", prefix, table_name, column)
+}
+
+fn generate_index_statements(entity: &AnalyzedEntity, table_name: &str) -> TokenStream {
+    let mut statements = Vec::new();
+
+    for field in &entity.fields {
+        if !matches!(field.ty, FieldType::Scalar { .. } | FieldType::Enum { .. }) {
+            continue;
+        }
+
+        let field_name = field.name.unraw().to_string();
+        let column = field
+            .attrs
+            .column_name
+            .clone()
+            .unwrap_or_else(|| field_name.clone());
+        let variant_ident = format_ident!("{}", to_pascal_case(&field_name));
+
+        if field.attrs.unique {
+            let name = match &field.attrs.unique_name {
+                Some(name) => name.value.clone(),
+                None => default_index_name("uq", table_name, &column),
+            };
+            statements.push(quote! {
+                sea_orm::sea_query::Index::create()
+                    .name(#name)
+                    .table(Entity)
+                    .col(Column::#variant_ident)
+                    .unique()
+                    .to_owned()
+            });
+        }
+
+        if field.attrs.indexed {
+            let name = match &field.attrs.index_name {
+                Some(name) => name.value.clone(),
+                None => default_index_name("idx", table_name, &column),
+            };
+            statements.push(quote! {
+                sea_orm::sea_query::Index::create()
+                    .name(#name)
+                    .table(Entity)
+                    .col(Column::#variant_ident)
+                    .to_owned()
+            });
+        }
+    }
+
+    quote! { #(#statements),* }
+}
+
 fn generate_relation_variants(entity: &AnalyzedEntity, schema: &AnalyzedSchema) -> TokenStream {
     let variants: Vec<TokenStream> = entity
         .fields
@@ -256,7 +864,7 @@ fn generate_relation_variant(
 ) -> Option<TokenStream> {
     match &field.ty {
         FieldType::HasMany { target } => {
-            let variant_name = to_pascal_case(&field.name.to_string());
+            let variant_name = to_pascal_case(&field.name.unraw().to_string());
             let variant_ident = format_ident!("{}", variant_name);
             let target_mod_str = target.to_string().to_snake_case();
             let has_many_path = format!("super::{}::Entity", target_mod_str);
@@ -267,31 +875,42 @@ fn generate_relation_variant(
             })
         }
 
-        FieldType::BelongsTo {
-            target,
-            optional: _,
-        } => {
-            let variant_name = to_pascal_case(&field.name.to_string());
+        FieldType::BelongsTo { target, .. } => {
+            let variant_name = to_pascal_case(&field.name.unraw().to_string());
             let variant_ident = format_ident!("{}", variant_name);
             let target_mod_str = target.to_string().to_snake_case();
             let belongs_to_path = format!("super::{}::Entity", target_mod_str);
             let fk_column_str = format!(
                 "Column::{}",
-                to_pascal_case(&format!("{}_id", field.name.to_string().to_snake_case()))
+                to_pascal_case(&format!(
+                    "{}_id",
+                    field.name.unraw().to_string().to_snake_case()
+                ))
             );
             let to_column_str = format!("super::{}::Column::Id", target_mod_str);
+            let on_delete = field.attrs.on_delete.as_ref().map(|action| {
+                let action = action.kind.sea_orm_name();
+                quote! { , on_delete = #action }
+            });
+            let on_update = field.attrs.on_update.as_ref().map(|action| {
+                let action = action.kind.sea_orm_name();
+                quote! { , on_update = #action }
+            });
 
             Some(quote! {
                 #[sea_orm(
                     belongs_to = #belongs_to_path,
                     from = #fk_column_str,
                     to = #to_column_str
+                    #on_delete
+                    #on_update
                 )]
                 #variant_ident,
             })
         }
 
-        FieldType::Scalar { .. } => None,
+        // Reached through the join entity's relations, see generate_related_impl
+        FieldType::ManyToMany { .. } | FieldType::Scalar { .. } | FieldType::Enum { .. } => None,
     }
 }
 
@@ -308,7 +927,7 @@ fn generate_related_impls(entity: &AnalyzedEntity, _schema: &AnalyzedSchema) ->
 }
 
 fn generate_related_impl(field: &AnalyzedField) -> Option<TokenStream> {
-    let variant_name = to_pascal_case(&field.name.to_string());
+    let variant_name = to_pascal_case(&field.name.unraw().to_string());
     let variant_ident = format_ident!("{}", variant_name);
 
     match &field.ty {
@@ -323,12 +942,33 @@ fn generate_related_impl(field: &AnalyzedField) -> Option<TokenStream> {
                 }
             })
         }
-        FieldType::Scalar { .. } => None,
+        FieldType::ManyToMany {
+            target,
+            through,
+            from_relation,
+            to_relation,
+        } => {
+            let target_mod = format_ident!("{}", target.to_string().to_snake_case());
+            let through_mod = format_ident!("{}", through.to_string().to_snake_case());
+
+            Some(quote! {
+                impl Related<super::#target_mod::Entity> for Entity {
+                    fn to() -> RelationDef {
+                        super::#through_mod::Relation::#to_relation.def()
+                    }
+
+                    fn via() -> Option<RelationDef> {
+                        Some(super::#through_mod::Relation::#from_relation.def().rev())
+                    }
+                }
+            })
+        }
+        FieldType::Scalar { .. } | FieldType::Enum { .. } => None,
     }
 }
 
 /// Convert snake_case or camelCase to PascalCase.
-fn to_pascal_case(s: &str) -> String {
+pub(super) fn to_pascal_case(s: &str) -> String {
     let mut result = String::new();
     let mut capitalize_next = true;
 
@@ -377,10 +1017,80 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_text_column() {
+    fn test_generate_doc_comments() {
         let input = quote! {
-            Post {
-                content: Text,
+            /// Registered users.
+            User {
+                /// Login address.
+                email: String,
+                /// Team the user belongs to.
+                team: Option<Team>,
+            }
+
+            Team {
+                name: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("doc = \" Registered users.\""));
+        assert!(output.contains("doc = \" Login address.\""));
+        assert!(output.contains("doc = \" Team the user belongs to.\""));
+    }
+
+    #[test]
+    fn test_generate_fixtures() {
+        let input = quote! {
+            #[fixtures]
+            Post {
+                title: String,
+                score: Option<i64>,
+                author: User,
+                comments: Vec<Comment>,
+            }
+
+            User {
+                name: String,
+            }
+
+            Comment {
+                body: Text,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("pub struct ModelFixture"));
+        assert!(output.contains("pub struct ActiveModelFixture"));
+        assert!(output.contains("pub fn fixture () -> ModelFixture"));
+        assert!(output.contains("pub fn fixture () -> ActiveModelFixture"));
+        for setter in [
+            "pub fn id (mut self , value : impl Into < i32 >)",
+            "pub fn title (mut self , value : impl Into < String >)",
+            "pub fn score (mut self , value : impl Into < Option < i64 > >)",
+            "pub fn author_id (mut self , value : impl Into < i32 >)",
+            "pub fn created_at (mut self , value : impl Into < DateTimeUtc >)",
+            "pub fn updated_at (mut self , value : impl Into < DateTimeUtc >)",
+        ] {
+            assert_eq!(output.matches(setter).count(), 2, "missing {}", setter);
+        }
+        assert!(!output.contains("pub fn comments"));
+        assert!(output.contains("rapina :: __schema_fixtures !"));
+
+        // Entities without the attribute get no fixtures
+        assert_eq!(output.matches("pub struct ModelFixture").count(), 1);
+    }
+
+    #[test]
+    fn test_generate_text_column() {
+        let input = quote! {
+            Post {
+                content: Text,
             }
         };
 
@@ -415,6 +1125,100 @@ mod tests {
         assert!(output.contains("belongs_to = \"super::user::Entity\""));
         assert!(output.contains("from = \"Column::AuthorId\""));
         assert!(output.contains("to = \"super::user::Column::Id\""));
+        assert!(!output.contains("on_delete"));
+    }
+
+    #[test]
+    fn test_generate_belongs_to_referential_actions() {
+        let input = quote! {
+            User {
+                email: String,
+            }
+
+            Team {
+                name: String,
+            }
+
+            Post {
+                #[on_delete(cascade)]
+                #[on_update(restrict)]
+                author: User,
+                #[on_delete(set_null)]
+                team: Option<Team>,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let generated = generate_schema(analyzed);
+        let output = generated.to_string();
+
+        assert!(output.contains(
+            "to = \"super::user::Column::Id\" , on_delete = \"Cascade\" , on_update = \"Restrict\""
+        ));
+        assert!(output.contains("to = \"super::team::Column::Id\" , on_delete = \"SetNull\")"));
+    }
+
+    #[test]
+    fn test_generate_extern_belongs_to() {
+        let input = quote! {
+            #[id(Uuid)]
+            extern entity User;
+
+            Post {
+                title: String,
+                author: User,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let generated = generate_schema(analyzed);
+        let output = generated.to_string();
+
+        assert!(output.contains("use super :: user :: user ;"));
+        assert!(output.contains("pub author_id : rapina :: uuid :: Uuid"));
+        assert!(output.contains("belongs_to = \"super::user::Entity\""));
+        assert!(!output.contains("pub mod user"));
+        assert!(!output.contains("pub use user :: Entity as User"));
+    }
+
+    #[test]
+    fn test_generate_uuid_id() {
+        let input = quote! {
+            #[id(Uuid)]
+            #[timestamps(none)]
+            User {
+                email: String,
+            }
+
+            Post {
+                author: User,
+                editor: Option<User>,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains(
+            "# [sea_orm (primary_key , auto_increment = false)] pub id : rapina :: uuid :: Uuid"
+        ));
+        assert!(output.contains("pub author_id : rapina :: uuid :: Uuid"));
+        assert!(output.contains("pub editor_id : Option < rapina :: uuid :: Uuid >"));
+        // Post keeps the default integer id
+        assert!(output.contains("# [sea_orm (primary_key)] pub id : i32"));
+
+        // Timestamps are off, so the behavior exists only to fill in the id
+        assert!(
+            output
+                .contains("impl rapina :: sea_orm :: ActiveModelBehavior for user :: ActiveModel")
+        );
+        assert!(output.contains(
+            "if insert && model . id . try_as_ref () . is_none_or (| id | id . is_nil ())"
+        ));
+        assert!(output.contains("rapina :: uuid :: Uuid :: new_v4 ()"));
     }
 
     #[test]
@@ -439,6 +1243,60 @@ mod tests {
         assert!(output.contains("impl Related < super :: post :: Entity >"));
     }
 
+    #[test]
+    fn test_generate_many_to_many() {
+        let input = quote! {
+            User {
+                email: String,
+                #[through(UsersRole)]
+                roles: Vec<Role>,
+            }
+
+            Role {
+                name: String,
+            }
+
+            UsersRole {
+                user: User,
+                role: Role,
+            }
+        };
+
+        let analyzed = analyze_schema(parse_schema(input).unwrap()).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains(
+            "impl Related < super :: role :: Entity > for Entity { \
+             fn to () -> RelationDef { super :: users_role :: Relation :: Role . def () } \
+             fn via () -> Option < RelationDef > { \
+             Some (super :: users_role :: Relation :: User . def () . rev ()) } }"
+        ));
+        // No column or relation variant on the owner itself
+        assert!(output.contains("pub email : String , pub created_at"));
+        assert!(!output.contains("has_many = \"super::role::Entity\""));
+    }
+
+    #[test]
+    fn test_generate_array_columns() {
+        let input = quote! {
+            Post {
+                tags: Vec<String>,
+                scores: Option<Vec<Decimal>>,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("pub tags : Vec < String >"));
+        assert!(
+            output.contains("pub scores : Option < Vec < rapina :: rust_decimal :: Decimal > >")
+        );
+        // The element's column type would be wrong for the array
+        assert!(!output.contains("Decimal(Some((19, 4)))"));
+    }
+
     #[test]
     fn test_generate_optional_belongs_to() {
         let input = quote! {
@@ -511,6 +1369,110 @@ mod tests {
         assert!(output.contains("column_name = \"user_email\""));
     }
 
+    #[test]
+    fn test_generate_keyword_field() {
+        let input = quote! {
+            Order {
+                type: String,
+                #[index]
+                async: Option<bool>,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let generated = generate_schema(analyzed);
+        let output = generated.to_string();
+
+        assert!(output.contains("pub r#type : String"));
+        assert!(output.contains("pub r#async : Option < bool >"));
+    }
+
+    #[test]
+    fn test_generate_keyword_belongs_to_field() {
+        let input = quote! {
+            User {
+                name: String,
+            }
+
+            Post {
+                ref: User,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let generated = generate_schema(analyzed);
+        let output = generated.to_string();
+
+        assert!(output.contains("pub ref_id : i32"));
+        assert!(output.contains("Column::RefId"));
+        assert!(output.contains("Ref ,"));
+    }
+
+    #[test]
+    fn test_generate_default_index_names() {
+        let input = quote! {
+            User {
+                #[unique]
+                email: String,
+                #[index]
+                #[column = "family_name"]
+                last_name: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let generated = generate_schema(analyzed);
+        let output = generated.to_string();
+
+        assert!(output.contains("pub fn indexes ()"));
+        assert!(output.contains(". name (\"uq_users_email\")"));
+        assert!(output.contains("Column :: Email"));
+        assert!(output.contains(". name (\"idx_users_family_name\")"));
+        assert!(output.contains("Column :: LastName"));
+    }
+
+    #[test]
+    fn test_generate_explicit_index_names() {
+        let input = quote! {
+            #[table_name = "people"]
+            Person {
+                #[unique(name = "people_email_key")]
+                #[index(name = "people_email_lookup")]
+                email: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let generated = generate_schema(analyzed);
+        let output = generated.to_string();
+
+        assert!(output.contains(". name (\"people_email_key\")"));
+        assert!(output.contains(". name (\"people_email_lookup\")"));
+        assert!(!output.contains("uq_people_email"));
+        assert!(!output.contains("idx_people_email"));
+    }
+
+    #[test]
+    fn test_generate_no_indexes() {
+        let input = quote! {
+            User {
+                email: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let generated = generate_schema(analyzed);
+        let output = generated.to_string();
+
+        assert!(output.contains("vec ! []"));
+        assert!(!output.contains("Index :: create"));
+    }
+
     #[test]
     fn test_to_pascal_case() {
         assert_eq!(to_pascal_case("hello_world"), "HelloWorld");
@@ -572,6 +1534,82 @@ mod tests {
         assert!(output.contains("updated_at"));
     }
 
+    #[test]
+    fn test_generate_behavior_without_timestamps_or_hooks_is_empty() {
+        let input = quote! {
+            #[timestamps(none)]
+            User {
+                email: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("impl ActiveModelBehavior for ActiveModel { }"));
+        assert!(!output.contains("async_trait"));
+    }
+
+    #[test]
+    fn test_generate_behavior_maintains_timestamps() {
+        let input = quote! {
+            User {
+                email: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(
+            output
+                .contains("impl rapina :: sea_orm :: ActiveModelBehavior for user :: ActiveModel")
+        );
+        assert!(output.contains("if insert && ! model . created_at . is_set ()"));
+        assert!(output.contains("if ! model . updated_at . is_set ()"));
+        assert!(!output.contains("after_save"));
+        assert!(!output.contains("before_delete"));
+    }
+
+    #[test]
+    fn test_generate_behavior_calls_hooks_in_order() {
+        let input = quote! {
+            #[timestamps(none)]
+            #[before_save(hooks::normalize_email)]
+            #[before_save(hooks::trim_name)]
+            #[after_save(hooks::announce)]
+            #[before_delete(hooks::archive)]
+            #[after_delete(hooks::forget)]
+            User {
+                email: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        let normalize = output
+            .find("SaveHook :: < user :: ActiveModel > :: call (& hooks :: normalize_email , model , insert)")
+            .unwrap();
+        let trim = output
+            .find("SaveHook :: < user :: ActiveModel > :: call (& hooks :: trim_name , model , insert)")
+            .unwrap();
+        assert!(normalize < trim);
+        assert!(output.contains(
+            "SaveHook :: < user :: Model > :: call (& hooks :: announce , model , insert)"
+        ));
+        assert!(output.contains("async fn before_delete < C > (self , _db : & C)"));
+        assert!(
+            output.contains(
+                "DeleteHook :: < user :: ActiveModel > :: call (& hooks :: forget , model)"
+            )
+        );
+        assert!(!output.contains("Utc :: now"));
+    }
+
     #[test]
     fn test_generate_indexed_field() {
         let input = quote! {
@@ -607,6 +1645,178 @@ mod tests {
         assert!(!output.contains("PartialEq , Eq"));
     }
 
+    #[test]
+    fn test_generate_derives_json_schema_by_default() {
+        let input = quote! {
+            Invoice {
+                total: Decimal,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("Deserialize , JsonSchema"));
+        assert!(output.contains("use rapina :: schemars"));
+    }
+
+    #[test]
+    fn test_generate_no_json_schema() {
+        let input = quote! {
+            #[no_json_schema]
+            Invoice {
+                total: Decimal,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(!output.contains("JsonSchema"));
+        assert!(!output.contains("schemars"));
+        assert!(output.contains("DeriveEntityModel , Serialize , Deserialize"));
+    }
+
+    #[test]
+    fn test_generate_schema_with_override() {
+        let input = quote! {
+            #[primary_key(code)]
+            #[timestamps(none)]
+            Currency {
+                #[schema(with = "String")]
+                code: Uuid,
+                #[schema(with = "Option<String>")]
+                rate: Option<Decimal>,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("# [schemars (with = \"String\")] pub code"));
+        assert!(output.contains("# [schemars (with = \"Option<String>\")] pub rate"));
+    }
+
+    #[test]
+    fn test_generate_column_defaults() {
+        let input = quote! {
+            Post {
+                #[default = "draft"]
+                status: String,
+                #[default = 0]
+                views: i32,
+                #[default = -1]
+                rank: i64,
+                #[default = true]
+                visible: bool,
+                #[default = now()]
+                published_at: DateTime,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("# [sea_orm (default_value = \"draft\")] pub status"));
+        assert!(output.contains("# [sea_orm (default_value = 0)] pub views"));
+        assert!(output.contains("# [sea_orm (default_expr = \"-1\")] pub rank"));
+        assert!(output.contains("# [sea_orm (default_value = true)] pub visible"));
+        assert!(output.contains(
+            "# [sea_orm (default_expr = \"sea_orm::sea_query::Expr::current_timestamp()\")] pub published_at"
+        ));
+    }
+
+    #[test]
+    fn test_generate_enum_column() {
+        let input = quote! {
+            /// Where a post is in its lifecycle.
+            enum PostStatus {
+                Draft,
+                InReview,
+                Published,
+            }
+
+            Post {
+                #[default(Draft)]
+                #[index]
+                status: PostStatus,
+                previous: Option<PostStatus>,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("pub mod post_status"));
+        assert!(
+            output.contains("EnumIter , DeriveActiveEnum , Serialize , Deserialize , JsonSchema")
+        );
+        assert!(output.contains(
+            "# [sea_orm (rs_type = \"String\" , db_type = \"String(StringLen::None)\")] pub enum PostStatus"
+        ));
+        assert!(output.contains(
+            "# [default] # [sea_orm (string_value = \"draft\")] # [serde (rename = \"draft\")] Draft ,"
+        ));
+        assert!(output.contains(
+            "# [sea_orm (string_value = \"in_review\")] # [serde (rename = \"in_review\")] InReview ,"
+        ));
+        assert_eq!(output.matches("# [default]").count(), 1);
+
+        assert!(output.contains(
+            "# [sea_orm (indexed , default_value = \"draft\")] pub status : super :: post_status :: PostStatus ,"
+        ));
+        assert!(output.contains("pub previous : Option < super :: post_status :: PostStatus > ,"));
+        assert!(output.contains("\"idx_posts_status\""));
+    }
+
+    #[test]
+    fn test_generate_enum_reexports() {
+        let input = quote! {
+            enum Status { Active }
+
+            User {
+                status: Status,
+            }
+
+            UserStatus {
+                note: String,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("pub use user :: Entity as User ;"));
+        assert!(output.contains("pub use user_status :: Entity as UserStatus ;"));
+        assert!(output.contains("pub use status :: Status ;"));
+        assert_eq!(output.matches("pub mod status").count(), 1);
+        assert_eq!(output.matches("as Status ;").count(), 0);
+    }
+
+    #[test]
+    fn test_generate_serde_alias() {
+        let input = quote! {
+            Post {
+                #[alias = "title"]
+                headline: String,
+                body: Text,
+            }
+        };
+
+        let parsed = parse_schema(input).unwrap();
+        let analyzed = analyze_schema(parsed).unwrap();
+        let output = generate_schema(analyzed).to_string();
+
+        assert!(output.contains("# [serde (alias = \"title\")] pub headline"));
+        assert_eq!(output.matches("serde (alias").count(), 1);
+    }
+
     #[test]
     fn test_generate_no_float_field_includes_eq() {
         let input = quote! {
@@ -3,16 +3,32 @@
 //! Handles custom syn parsing for entity definitions.
 
 use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::{Ident, Result, Token, braced};
 
-use super::types::ScalarType;
+use super::types::{IdType, ScalarType};
 
-/// A complete schema definition containing multiple entities.
+/// A complete schema definition containing multiple entities, and the
+/// enums their fields may use.
 #[derive(Debug)]
 pub struct Schema {
     pub entities: Vec<EntityDef>,
+    pub enums: Vec<EnumDef>,
+    /// Entities of other `schema!` blocks referenced from this one
+    pub externs: Vec<ExternEntity>,
+}
+
+/// An entity declared in another `schema!` block, written
+/// `extern entity User;`. Fields may reference it like a local entity; it
+/// resolves to the `user` module of the sibling module `super::user`.
+#[derive(Debug)]
+pub struct ExternEntity {
+    pub name: Ident,
+    /// Type of the entity's `id`, e.g. `#[id(Uuid)] extern entity User;`
+    pub id_type: IdType,
 }
 
 /// Attributes that can be applied to an entity.
@@ -25,8 +41,39 @@ pub struct EntityAttrs {
     /// Include updated_at timestamp (default: true)
     pub has_updated_at: bool,
     /// Custom primary key columns, e.g., #[primary_key(user_id, role_id)]
-    /// When None, a single auto-increment `id: i32` is generated.
+    /// When None, a single `id` column of type `id_type` is generated.
     pub primary_key: Option<Vec<String>>,
+    /// Type of the generated `id`, e.g. #[id(Uuid)] (default: i32)
+    pub id_type: IdType,
+    /// Doc comment lines, emitted on the generated `Model`
+    pub docs: Vec<String>,
+    /// Generate test fixture builders, e.g. #[fixtures]
+    pub fixtures: bool,
+    /// Derive `JsonSchema` on the `Model` (default: true), turned off with
+    /// #[no_json_schema]
+    pub json_schema: bool,
+    /// Lifecycle hooks called from the generated `ActiveModelBehavior`
+    pub hooks: EntityHooks,
+}
+
+/// Paths to user functions declared with #[before_save(path)],
+/// #[after_save(path)], #[before_delete(path)] and #[after_delete(path)],
+/// in declaration order.
+#[derive(Debug, Clone, Default)]
+pub struct EntityHooks {
+    pub before_save: Vec<TokenStream>,
+    pub after_save: Vec<TokenStream>,
+    pub before_delete: Vec<TokenStream>,
+    pub after_delete: Vec<TokenStream>,
+}
+
+impl EntityHooks {
+    pub fn is_empty(&self) -> bool {
+        self.before_save.is_empty()
+            && self.after_save.is_empty()
+            && self.before_delete.is_empty()
+            && self.after_delete.is_empty()
+    }
 }
 
 impl Default for EntityAttrs {
@@ -36,6 +83,11 @@ impl Default for EntityAttrs {
             has_created_at: true,
             has_updated_at: true,
             primary_key: None,
+            id_type: IdType::default(),
+            docs: Vec::new(),
+            fixtures: false,
+            json_schema: true,
+            hooks: EntityHooks::default(),
         }
     }
 }
@@ -49,6 +101,91 @@ pub struct FieldAttrs {
     pub column_name: Option<String>,
     /// Mark field as indexed, e.g., #[index]
     pub indexed: bool,
+    /// Explicit unique constraint name, e.g., #[unique(name = "uq_users_login")]
+    pub unique_name: Option<IndexName>,
+    /// Explicit index name, e.g., #[index(name = "idx_users_login")]
+    pub index_name: Option<IndexName>,
+    /// Doc comment lines, emitted on the generated model field
+    pub docs: Vec<String>,
+    /// Type documented in place of the field's own in the JSON schema,
+    /// e.g., #[schema(with = "String")]
+    pub schema_with: Option<SchemaWith>,
+    /// Former names still accepted when deserializing, e.g., #[alias = "title"]
+    pub aliases: Vec<FieldAlias>,
+    /// Join entity of a many-to-many relation, e.g., #[through(UsersRole)]
+    pub through: Option<Ident>,
+    /// Database default of the column, e.g., #[default = 0],
+    /// #[default = now()] or #[default(Draft)]
+    pub default: Option<DefaultValue>,
+    /// What happens to the row when its belongs_to target is deleted,
+    /// e.g., #[on_delete(cascade)]
+    pub on_delete: Option<ReferentialAction>,
+    /// What happens to the row when its belongs_to target's key changes,
+    /// e.g., #[on_update(cascade)]
+    pub on_update: Option<ReferentialAction>,
+}
+
+/// A foreign key action given in an `#[on_delete(...)]` or `#[on_update(...)]`
+/// attribute.
+#[derive(Debug, Clone)]
+pub struct ReferentialAction {
+    pub kind: ReferentialActionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferentialActionKind {
+    Cascade,
+    SetNull,
+    Restrict,
+}
+
+impl ReferentialActionKind {
+    /// The `ForeignKeyAction` variant SeaORM expects in its relation attributes.
+    pub fn sea_orm_name(self) -> &'static str {
+        match self {
+            ReferentialActionKind::Cascade => "Cascade",
+            ReferentialActionKind::SetNull => "SetNull",
+            ReferentialActionKind::Restrict => "Restrict",
+        }
+    }
+}
+
+/// A column default as written in a `#[default = ...]` or `#[default(...)]`
+/// attribute, checked against the column type during analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultValue {
+    Str(String),
+    /// Integer literal, with its sign
+    Int(i128),
+    /// Float literal, with its sign, as written
+    Float(String),
+    Bool(bool),
+    /// `now()`, the current timestamp
+    Now,
+    /// A variant of the column's enum, written `#[default(Draft)]`
+    Variant(Ident),
+}
+
+/// The type named by a `#[schema(with = "...")]` attribute.
+#[derive(Debug, Clone)]
+pub struct SchemaWith {
+    pub value: String,
+    pub span: Span,
+}
+
+/// A former field name given in an `#[alias = "..."]` attribute.
+#[derive(Debug, Clone)]
+pub struct FieldAlias {
+    pub value: String,
+    pub span: Span,
+}
+
+/// An explicit index or constraint name given in an attribute.
+#[derive(Debug, Clone)]
+pub struct IndexName {
+    pub value: String,
+    pub span: Span,
 }
 
 /// A single entity definition.
@@ -60,6 +197,22 @@ pub struct EntityDef {
     pub span: Span,
 }
 
+/// An enum declared with `enum Name { A, B }`, stored in a string column.
+#[derive(Debug)]
+pub struct EnumDef {
+    /// Doc comment lines, emitted on the generated enum
+    pub docs: Vec<String>,
+    pub name: Ident,
+    pub variants: Vec<EnumVariant>,
+}
+
+/// A variant of a schema enum.
+#[derive(Debug)]
+pub struct EnumVariant {
+    pub docs: Vec<String>,
+    pub name: Ident,
+}
+
 /// A field within an entity.
 #[derive(Debug)]
 pub struct FieldDef {
@@ -73,10 +226,18 @@ pub struct FieldDef {
 /// At parse time, we don't know if a type like `User` is an entity or invalid.
 #[derive(Debug)]
 pub enum RawFieldType {
-    /// A known scalar type (String, i32, etc.)
-    Scalar { scalar: ScalarType, optional: bool },
-    /// Vec<T> - will become has_many if T is an entity
-    Vec { inner: Ident },
+    /// A known scalar type (String, i32, etc.), or an array of one when
+    /// written `Vec<T>` / `Option<Vec<T>>`
+    Scalar {
+        scalar: ScalarType,
+        optional: bool,
+        array: bool,
+    },
+    /// Vec<T> - will become has_many if T is an entity. `option` is the
+    /// `Option` of `Option<Vec<T>>`, kept for diagnostics
+    Vec { inner: Ident, option: Option<Ident> },
+    /// Vec<Option<T>> - never valid, kept for diagnostics
+    VecOfOption { option: Ident, inner: Ident },
     /// T or Option<T> where T is unknown - needs resolution
     Unknown { name: Ident, optional: bool },
 }
@@ -84,9 +245,17 @@ pub enum RawFieldType {
 impl Parse for Schema {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut entities = Vec::new();
+        let mut enums = Vec::new();
+        let mut externs = Vec::new();
 
         while !input.is_empty() {
-            entities.push(input.parse()?);
+            if peek_enum(input) {
+                enums.push(input.parse()?);
+            } else if peek_extern(input) {
+                externs.push(input.parse()?);
+            } else {
+                entities.push(input.parse()?);
+            }
         }
 
         if entities.is_empty() {
@@ -96,10 +265,145 @@ impl Parse for Schema {
             ));
         }
 
-        Ok(Schema { entities })
+        Ok(Schema {
+            entities,
+            enums,
+            externs,
+        })
+    }
+}
+
+/// Whether the next item is an `extern entity`, possibly behind attributes.
+fn peek_extern(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.call(syn::Attribute::parse_outer).is_ok() && fork.peek(Token![extern])
+}
+
+impl Parse for ExternEntity {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut id_type = IdType::default();
+        while input.peek(Token![#]) {
+            input.parse::<Token![#]>()?;
+            let content;
+            syn::bracketed!(content in input);
+
+            let attr_name: Ident = content.parse()?;
+            if attr_name != "id" {
+                return Err(syn::Error::new(
+                    attr_name.span(),
+                    format!(
+                        "unknown extern entity attribute '{}'. Supported: id",
+                        attr_name
+                    ),
+                ));
+            }
+            let inner;
+            syn::parenthesized!(inner in content);
+            let ty: Ident = inner.parse()?;
+            id_type = IdType::from_ident(&ty.to_string()).ok_or_else(|| {
+                syn::Error::new(
+                    ty.span(),
+                    format!("unsupported id type '{}'. Supported: i32, Uuid", ty),
+                )
+            })?;
+        }
+
+        input.parse::<Token![extern]>()?;
+        let keyword: Ident = input.parse()?;
+        if keyword != "entity" {
+            return Err(syn::Error::new(
+                keyword.span(),
+                "expected `extern entity Name;`",
+            ));
+        }
+        let name: Ident = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        Ok(ExternEntity { name, id_type })
+    }
+}
+
+/// Whether the next item is an `enum`, possibly behind doc comments.
+fn peek_enum(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.call(syn::Attribute::parse_outer).is_ok() && fork.peek(Token![enum])
+}
+
+impl Parse for EnumDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let docs = parse_enum_docs(input)?;
+        input.parse::<Token![enum]>()?;
+        let name: Ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let variants: Punctuated<EnumVariant, Token![,]> =
+            content.parse_terminated(EnumVariant::parse, Token![,])?;
+        let variants: Vec<EnumVariant> = variants.into_iter().collect();
+
+        if variants.is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("enum '{}' needs at least one variant", name),
+            ));
+        }
+
+        let mut seen_variants = std::collections::HashSet::new();
+        for variant in &variants {
+            if !seen_variants.insert(variant.name.to_string()) {
+                return Err(syn::Error::new(
+                    variant.name.span(),
+                    format!("duplicate variant '{}' in enum '{}'", variant.name, name),
+                ));
+            }
+        }
+
+        Ok(EnumDef {
+            docs,
+            name,
+            variants,
+        })
+    }
+}
+
+impl Parse for EnumVariant {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let docs = parse_enum_docs(input)?;
+        let name: Ident = input.parse()?;
+        if input.peek(syn::token::Paren) || input.peek(syn::token::Brace) || input.peek(Token![=]) {
+            return Err(syn::Error::new(
+                name.span(),
+                "schema enum variants cannot carry data or discriminants",
+            ));
+        }
+        Ok(EnumVariant { docs, name })
     }
 }
 
+/// Parse the doc comments of an enum or variant, the only attributes they
+/// accept.
+fn parse_enum_docs(input: ParseStream) -> Result<Vec<String>> {
+    let mut docs = Vec::new();
+    while input.peek(Token![#]) {
+        input.parse::<Token![#]>()?;
+        let content;
+        syn::bracketed!(content in input);
+
+        let attr_name: Ident = content.parse()?;
+        if attr_name != "doc" {
+            return Err(syn::Error::new(
+                attr_name.span(),
+                format!(
+                    "unknown enum attribute '{}'. Only doc comments are supported",
+                    attr_name
+                ),
+            ));
+        }
+        docs.push(parse_doc_value(&content)?);
+    }
+    Ok(docs)
+}
+
 impl Parse for EntityDef {
     fn parse(input: ParseStream) -> Result<Self> {
         // Parse entity attributes
@@ -120,7 +424,7 @@ impl Parse for EntityDef {
         // unless a custom primary key is defined
         if attrs.primary_key.is_none() {
             for field in &fields {
-                if field.name == "id" {
+                if field.name.unraw() == "id" {
                     return Err(syn::Error::new(
                         field.name.span(),
                         "field 'id' is reserved and automatically generated. Use #[primary_key(...)] to define a custom primary key",
@@ -132,7 +436,7 @@ impl Parse for EntityDef {
         // Check for duplicate field names
         let mut seen_fields = std::collections::HashSet::new();
         for field in &fields {
-            let field_name = field.name.to_string();
+            let field_name = field.name.unraw().to_string();
             if !seen_fields.insert(field_name.clone()) {
                 return Err(syn::Error::new(
                     field.name.span(),
@@ -153,6 +457,7 @@ impl Parse for EntityDef {
 /// Parse entity-level attributes like #[table_name = "people"] or #[timestamps(created_at)]
 fn parse_entity_attrs(input: ParseStream) -> Result<EntityAttrs> {
     let mut attrs = EntityAttrs::default();
+    let mut id_attr: Option<Ident> = None;
 
     while input.peek(Token![#]) {
         input.parse::<Token![#]>()?;
@@ -163,6 +468,9 @@ fn parse_entity_attrs(input: ParseStream) -> Result<EntityAttrs> {
         let attr_name_str = attr_name.to_string();
 
         match attr_name_str.as_str() {
+            "doc" => attrs.docs.push(parse_doc_value(&content)?),
+            "fixtures" => attrs.fixtures = true,
+            "no_json_schema" => attrs.json_schema = false,
             "table_name" => {
                 content.parse::<Token![=]>()?;
                 let value: syn::LitStr = content.parse()?;
@@ -199,14 +507,33 @@ fn parse_entity_attrs(input: ParseStream) -> Result<EntityAttrs> {
                     }
                 }
             }
+            "before_save" | "after_save" | "before_delete" | "after_delete" => {
+                // Parse before_save(path::to::function)
+                let inner;
+                syn::parenthesized!(inner in content);
+                let path: syn::Path = inner.parse()?;
+                if !inner.is_empty() {
+                    return Err(inner.error(format!(
+                        "expected a single function path, e.g. #[{}(hooks::normalize)]",
+                        attr_name_str
+                    )));
+                }
+                let hooks = match attr_name_str.as_str() {
+                    "before_save" => &mut attrs.hooks.before_save,
+                    "after_save" => &mut attrs.hooks.after_save,
+                    "before_delete" => &mut attrs.hooks.before_delete,
+                    _ => &mut attrs.hooks.after_delete,
+                };
+                hooks.push(path.into_token_stream());
+            }
             "primary_key" => {
                 // Parse primary_key(col1, col2, ...)
                 let inner;
                 syn::parenthesized!(inner in content);
                 let columns: Punctuated<Ident, Token![,]> =
-                    inner.parse_terminated(Ident::parse, Token![,])?;
+                    inner.parse_terminated(Ident::parse_any, Token![,])?;
 
-                let pk_cols: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+                let pk_cols: Vec<String> = columns.iter().map(|c| c.unraw().to_string()).collect();
                 if pk_cols.is_empty() {
                     return Err(syn::Error::new(
                         attr_name.span(),
@@ -216,11 +543,24 @@ fn parse_entity_attrs(input: ParseStream) -> Result<EntityAttrs> {
 
                 attrs.primary_key = Some(pk_cols);
             }
+            "id" => {
+                // Parse id(Uuid) or id(i32)
+                let inner;
+                syn::parenthesized!(inner in content);
+                let ty: Ident = inner.parse()?;
+                attrs.id_type = IdType::from_ident(&ty.to_string()).ok_or_else(|| {
+                    syn::Error::new(
+                        ty.span(),
+                        format!("unsupported id type '{}'. Supported: i32, Uuid", ty),
+                    )
+                })?;
+                id_attr = Some(attr_name);
+            }
             _ => {
                 return Err(syn::Error::new(
                     attr_name.span(),
                     format!(
-                        "unknown entity attribute '{}'. Supported: table_name, timestamps, primary_key",
+                        "unknown entity attribute '{}'. Supported: table_name, timestamps, primary_key, id, fixtures, no_json_schema, before_save, after_save, before_delete, after_delete",
                         attr_name_str
                     ),
                 ));
@@ -228,6 +568,15 @@ fn parse_entity_attrs(input: ParseStream) -> Result<EntityAttrs> {
         }
     }
 
+    if let Some(id_attr) = id_attr
+        && attrs.primary_key.is_some()
+    {
+        return Err(syn::Error::new(
+            id_attr.span(),
+            "#[id(...)] sets the type of the generated id and cannot be combined with #[primary_key(...)]",
+        ));
+    }
+
     Ok(attrs)
 }
 
@@ -236,7 +585,7 @@ impl Parse for FieldDef {
         // Parse field attributes
         let attrs = parse_field_attrs(input)?;
 
-        let name: Ident = input.parse()?;
+        let name = parse_field_name(input)?;
         let span = name.span();
         input.parse::<Token![:]>()?;
         let ty = parse_field_type(input)?;
@@ -250,6 +599,42 @@ impl Parse for FieldDef {
     }
 }
 
+/// Rust keywords that are valid field names only as raw identifiers.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be used as identifiers at all, not even raw ones.
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate", "_"];
+
+/// Parse a field name, accepting keywords like `type` as well as raw
+/// identifiers like `r#type`. Keywords are turned into raw identifiers so the
+/// generated struct fields compile; the column name stays `type`.
+fn parse_field_name(input: ParseStream) -> Result<Ident> {
+    let ident = Ident::parse_any(input)?;
+    let name = ident.unraw().to_string();
+
+    if NON_RAW_KEYWORDS.contains(&name.as_str()) {
+        return Err(syn::Error::new(
+            ident.span(),
+            format!(
+                "'{}' cannot be used as a field name. Pick a different name and use #[column = \"{}\"] if the column must keep it",
+                name, name
+            ),
+        ));
+    }
+
+    if RUST_KEYWORDS.contains(&name.as_str()) {
+        Ok(Ident::new_raw(&name, ident.span()))
+    } else {
+        Ok(ident)
+    }
+}
+
 /// Parse field-level attributes like #[unique] or #[column = "email_address"]
 fn parse_field_attrs(input: ParseStream) -> Result<FieldAttrs> {
     let mut attrs = FieldAttrs::default();
@@ -263,22 +648,53 @@ fn parse_field_attrs(input: ParseStream) -> Result<FieldAttrs> {
         let attr_name_str = attr_name.to_string();
 
         match attr_name_str.as_str() {
+            "doc" => attrs.docs.push(parse_doc_value(&content)?),
             "unique" => {
                 attrs.unique = true;
+                attrs.unique_name = parse_index_name_arg(&content)?;
             }
             "index" => {
                 attrs.indexed = true;
+                attrs.index_name = parse_index_name_arg(&content)?;
             }
             "column" => {
                 content.parse::<Token![=]>()?;
                 let value: syn::LitStr = content.parse()?;
                 attrs.column_name = Some(value.value());
             }
+            "schema" => attrs.schema_with = Some(parse_schema_with_arg(&content)?),
+            "alias" => {
+                content.parse::<Token![=]>()?;
+                let value: syn::LitStr = content.parse()?;
+                if value.value().is_empty() {
+                    return Err(syn::Error::new(value.span(), "alias cannot be empty"));
+                }
+                attrs.aliases.push(FieldAlias {
+                    value: value.value(),
+                    span: value.span(),
+                });
+            }
+            "through" => {
+                let inner;
+                syn::parenthesized!(inner in content);
+                attrs.through = Some(inner.parse()?);
+            }
+            "default" if content.peek(syn::token::Paren) => {
+                let inner;
+                syn::parenthesized!(inner in content);
+                attrs.default = Some(DefaultValue::Variant(inner.parse()?));
+            }
+            "default" => {
+                content.parse::<Token![=]>()?;
+                attrs.default = Some(parse_default_value(&content)?);
+            }
+            "on_delete" => attrs.on_delete = Some(parse_referential_action(&content)?),
+            "on_update" => attrs.on_update = Some(parse_referential_action(&content)?),
             _ => {
                 return Err(syn::Error::new(
                     attr_name.span(),
                     format!(
-                        "unknown field attribute '{}'. Supported: unique, index, column",
+                        "unknown field attribute '{}'. Supported: unique, index, column, schema, alias, through, default, on_delete, on_update",
                         attr_name_str
                     ),
                 ));
@@ -289,71 +705,230 @@ fn parse_field_attrs(input: ParseStream) -> Result<FieldAttrs> {
     Ok(attrs)
 }
 
-/// Parse a field type from the input stream.
-fn parse_field_type(input: ParseStream) -> Result<RawFieldType> {
-    // Check for Option<T>
-    if input.peek(Ident) {
-        let ident: Ident = input.parse()?;
-        let ident_str = ident.to_string();
-
-        if ident_str == "Option" {
-            // Parse Option<T>
-            input.parse::<Token![<]>()?;
-            let inner_type = parse_inner_type(input)?;
-            input.parse::<Token![>]>()?;
-
-            return match inner_type {
-                InnerType::Scalar(scalar) => Ok(RawFieldType::Scalar {
-                    scalar,
-                    optional: true,
-                }),
-                InnerType::Ident(name) => Ok(RawFieldType::Unknown {
-                    name,
-                    optional: true,
-                }),
-            };
+/// Parse the `= "..."` part of a `#[doc]` attribute, which is what `///`
+/// comments desugar to.
+fn parse_doc_value(input: ParseStream) -> Result<String> {
+    input.parse::<Token![=]>()?;
+    let value: syn::LitStr = input.parse()?;
+    Ok(value.value())
+}
+
+/// Parse the optional `(name = "...")` argument of #[unique] and #[index].
+fn parse_index_name_arg(input: ParseStream) -> Result<Option<IndexName>> {
+    if !input.peek(syn::token::Paren) {
+        return Ok(None);
+    }
+
+    let inner;
+    syn::parenthesized!(inner in input);
+
+    let key: Ident = inner.parse()?;
+    if key != "name" {
+        return Err(syn::Error::new(
+            key.span(),
+            format!("unknown argument '{}'. Supported: name", key),
+        ));
+    }
+    inner.parse::<Token![=]>()?;
+    let value: syn::LitStr = inner.parse()?;
+
+    if value.value().is_empty() {
+        return Err(syn::Error::new(value.span(), "index name cannot be empty"));
+    }
+
+    Ok(Some(IndexName {
+        value: value.value(),
+        span: value.span(),
+    }))
+}
+
+/// Parse the `(with = "...")` argument of #[schema].
+fn parse_schema_with_arg(input: ParseStream) -> Result<SchemaWith> {
+    let inner;
+    syn::parenthesized!(inner in input);
+
+    let key: Ident = inner.parse()?;
+    if key != "with" {
+        return Err(syn::Error::new(
+            key.span(),
+            format!("unknown argument '{}'. Supported: with", key),
+        ));
+    }
+    inner.parse::<Token![=]>()?;
+    let value: syn::LitStr = inner.parse()?;
+
+    if syn::parse_str::<syn::Type>(&value.value()).is_err() {
+        return Err(syn::Error::new(
+            value.span(),
+            format!("'{}' is not a type", value.value()),
+        ));
+    }
+
+    Ok(SchemaWith {
+        value: value.value(),
+        span: value.span(),
+    })
+}
+
+/// Parse the `(cascade)`, `(set_null)` or `(restrict)` argument of
+/// #[on_delete] and #[on_update].
+fn parse_referential_action(input: ParseStream) -> Result<ReferentialAction> {
+    let inner;
+    syn::parenthesized!(inner in input);
+
+    let action: Ident = inner.parse()?;
+    let kind = match action.to_string().as_str() {
+        "cascade" => ReferentialActionKind::Cascade,
+        "set_null" => ReferentialActionKind::SetNull,
+        "restrict" => ReferentialActionKind::Restrict,
+        other => {
+            return Err(syn::Error::new(
+                action.span(),
+                format!(
+                    "unknown referential action '{}'. Supported: cascade, set_null, restrict",
+                    other
+                ),
+            ));
         }
+    };
+
+    Ok(ReferentialAction {
+        kind,
+        span: action.span(),
+    })
+}
 
-        if ident_str == "Vec" {
-            // Parse Vec<T>
-            input.parse::<Token![<]>()?;
-            let inner: Ident = input.parse()?;
-            input.parse::<Token![>]>()?;
+/// Parse the value of #[default = ...]: a string, number or bool literal,
+/// or `now()`.
+fn parse_default_value(input: ParseStream) -> Result<DefaultValue> {
+    let span = input.span();
 
-            return Ok(RawFieldType::Vec { inner });
+    if input.peek(Ident) {
+        let ident: Ident = input.parse()?;
+        if ident == "now" && input.peek(syn::token::Paren) {
+            let inner;
+            syn::parenthesized!(inner in input);
+            if inner.is_empty() {
+                return Ok(DefaultValue::Now);
+            }
         }
+        return Err(syn::Error::new(
+            span,
+            "expected a literal or now() as the default, e.g. #[default = 0]",
+        ));
+    }
 
-        // Try to parse as scalar
-        if let Some(scalar) = ScalarType::from_ident(&ident_str) {
-            return Ok(RawFieldType::Scalar {
-                scalar,
-                optional: false,
-            });
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let value = match input.parse::<syn::Lit>()? {
+        syn::Lit::Str(lit) if !negative => DefaultValue::Str(lit.value()),
+        syn::Lit::Bool(lit) if !negative => DefaultValue::Bool(lit.value),
+        syn::Lit::Int(lit) if lit.suffix().is_empty() => {
+            let value: i128 = lit.base10_parse()?;
+            DefaultValue::Int(if negative { -value } else { value })
+        }
+        syn::Lit::Float(lit) if lit.suffix().is_empty() => {
+            let digits = lit.base10_digits();
+            DefaultValue::Float(if negative {
+                format!("-{}", digits)
+            } else {
+                digits.to_string()
+            })
+        }
+        _ => {
+            return Err(syn::Error::new(
+                span,
+                "expected a string, number or bool literal, or now(), as the default",
+            ));
         }
+    };
 
-        // Unknown type - might be an entity reference
-        Ok(RawFieldType::Unknown {
-            name: ident,
-            optional: false,
-        })
-    } else {
-        Err(syn::Error::new(input.span(), "expected type"))
+    Ok(value)
+}
+
+/// Parse a field type from the input stream.
+fn parse_field_type(input: ParseStream) -> Result<RawFieldType> {
+    let (wrapper, inner) = match parse_type_shape(input)? {
+        TypeShape::Name(name) => return Ok(named_type(name, false)),
+        TypeShape::Wrapped { wrapper, inner } => (wrapper, *inner),
+    };
+
+    let (nested, innermost) = match inner {
+        TypeShape::Name(name) if wrapper == "Option" => return Ok(named_type(name, true)),
+        TypeShape::Name(name) => return Ok(vec_type(name, None)),
+        TypeShape::Wrapped { wrapper, inner } => (wrapper, *inner),
+    };
+    let name = match innermost {
+        TypeShape::Name(name) => name,
+        TypeShape::Wrapped { wrapper, .. } => {
+            return Err(syn::Error::new(
+                wrapper.span(),
+                "Option and Vec can be nested at most once",
+            ));
+        }
+    };
+
+    match (wrapper.to_string().as_str(), nested.to_string().as_str()) {
+        ("Option", "Vec") => Ok(vec_type(name, Some(wrapper))),
+        ("Vec", "Option") => Ok(RawFieldType::VecOfOption {
+            option: nested,
+            inner: name,
+        }),
+        _ => Err(syn::Error::new(
+            nested.span(),
+            format!("{}<{}<{}>> is not supported", wrapper, nested, name),
+        )),
     }
 }
 
-enum InnerType {
-    Scalar(ScalarType),
-    Ident(Ident),
+/// A field type as written: a name, or `Option<..>` / `Vec<..>` around one.
+enum TypeShape {
+    Name(Ident),
+    Wrapped {
+        wrapper: Ident,
+        inner: Box<TypeShape>,
+    },
 }
 
-fn parse_inner_type(input: ParseStream) -> Result<InnerType> {
+fn parse_type_shape(input: ParseStream) -> Result<TypeShape> {
+    if !input.peek(Ident) {
+        return Err(syn::Error::new(input.span(), "expected type"));
+    }
     let ident: Ident = input.parse()?;
-    let ident_str = ident.to_string();
+    if ident != "Option" && ident != "Vec" {
+        return Ok(TypeShape::Name(ident));
+    }
 
-    if let Some(scalar) = ScalarType::from_ident(&ident_str) {
-        Ok(InnerType::Scalar(scalar))
-    } else {
-        Ok(InnerType::Ident(ident))
+    input.parse::<Token![<]>()?;
+    let inner = parse_type_shape(input)?;
+    input.parse::<Token![>]>()?;
+    Ok(TypeShape::Wrapped {
+        wrapper: ident,
+        inner: Box::new(inner),
+    })
+}
+
+/// `T` or `Option<T>`: a scalar column, or a reference to resolve later.
+fn named_type(name: Ident, optional: bool) -> RawFieldType {
+    match ScalarType::from_ident(&name.to_string()) {
+        Some(scalar) => RawFieldType::Scalar {
+            scalar,
+            optional,
+            array: false,
+        },
+        None => RawFieldType::Unknown { name, optional },
+    }
+}
+
+/// `Vec<T>` or `Option<Vec<T>>`: an array column for scalars, otherwise a
+/// has_many to resolve later.
+fn vec_type(inner: Ident, option: Option<Ident>) -> RawFieldType {
+    match ScalarType::from_ident(&inner.to_string()) {
+        Some(scalar) => RawFieldType::Scalar {
+            scalar,
+            optional: option.is_some(),
+            array: true,
+        },
+        None => RawFieldType::Vec { inner, option },
     }
 }
 
@@ -398,6 +973,393 @@ mod tests {
         assert_eq!(schema.entities.len(), 2);
     }
 
+    #[test]
+    fn test_parse_keyword_field_becomes_raw_ident() {
+        let input = quote! {
+            Order {
+                type: String,
+                r#ref: String,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let fields = &schema.entities[0].fields;
+        assert_eq!(fields[0].name.to_string(), "r#type");
+        assert_eq!(fields[1].name.to_string(), "r#ref");
+    }
+
+    #[test]
+    fn test_duplicate_raw_and_keyword_field_error() {
+        let input = quote! {
+            Order {
+                type: String,
+                r#type: String,
+            }
+        };
+
+        let result = parse_schema(input);
+        assert!(result.unwrap_err().to_string().contains("duplicate"));
+    }
+
+    #[test]
+    fn test_non_raw_keyword_field_error() {
+        let input = quote! {
+            Order {
+                self: String,
+            }
+        };
+
+        let result = parse_schema(input);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("cannot be used as a field name")
+        );
+    }
+
+    #[test]
+    fn test_parse_named_index_attrs() {
+        let input = quote! {
+            User {
+                #[unique(name = "uq_login")]
+                login: String,
+                #[index(name = "idx_last_name")]
+                last_name: String,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let fields = &schema.entities[0].fields;
+        assert!(fields[0].attrs.unique);
+        assert_eq!(
+            fields[0].attrs.unique_name.as_ref().unwrap().value,
+            "uq_login"
+        );
+        assert!(fields[1].attrs.indexed);
+        assert_eq!(
+            fields[1].attrs.index_name.as_ref().unwrap().value,
+            "idx_last_name"
+        );
+    }
+
+    #[test]
+    fn test_parse_json_schema_attrs() {
+        let input = quote! {
+            #[no_json_schema]
+            Invoice {
+                total: Decimal,
+            }
+
+            Payment {
+                #[schema(with = "String")]
+                amount: Decimal,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        assert!(!schema.entities[0].attrs.json_schema);
+        assert!(schema.entities[1].attrs.json_schema);
+        let with = schema.entities[1].fields[0].attrs.schema_with.as_ref();
+        assert_eq!(with.unwrap().value, "String");
+    }
+
+    #[test]
+    fn test_parse_schema_with_not_a_type_error() {
+        let input = quote! {
+            Payment {
+                #[schema(with = "not a type")]
+                amount: Decimal,
+            }
+        };
+
+        let result = parse_schema(input);
+        assert!(result.unwrap_err().to_string().contains("is not a type"));
+    }
+
+    #[test]
+    fn test_parse_alias() {
+        let input = quote! {
+            Post {
+                #[alias = "title"]
+                #[alias = "name"]
+                headline: String,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let aliases: Vec<&str> = schema.entities[0].fields[0]
+            .attrs
+            .aliases
+            .iter()
+            .map(|a| a.value.as_str())
+            .collect();
+        assert_eq!(aliases, vec!["title", "name"]);
+    }
+
+    #[test]
+    fn test_parse_through() {
+        let input = quote! {
+            User {
+                #[through(UsersRole)]
+                roles: Vec<Role>,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let through = schema.entities[0].fields[0].attrs.through.as_ref().unwrap();
+        assert_eq!(through.to_string(), "UsersRole");
+    }
+
+    #[test]
+    fn test_parse_default() {
+        let input = quote! {
+            Post {
+                #[default = "draft"]
+                status: String,
+                #[default = 0]
+                views: i32,
+                #[default = -1.5]
+                score: f64,
+                #[default = false]
+                pinned: bool,
+                #[default = now()]
+                published_at: DateTime,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let defaults: Vec<DefaultValue> = schema.entities[0]
+            .fields
+            .iter()
+            .map(|f| f.attrs.default.clone().unwrap())
+            .collect();
+        assert_eq!(
+            defaults,
+            vec![
+                DefaultValue::Str("draft".to_string()),
+                DefaultValue::Int(0),
+                DefaultValue::Float("-1.5".to_string()),
+                DefaultValue::Bool(false),
+                DefaultValue::Now,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_enum() {
+        let input = quote! {
+            /// Where a post is in its lifecycle.
+            enum PostStatus {
+                Draft,
+                /// Visible to everyone.
+                Published,
+                Archived,
+            }
+
+            Post {
+                #[default(Draft)]
+                status: PostStatus,
+                previous: Option<PostStatus>,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        assert_eq!(schema.entities.len(), 1);
+        assert_eq!(schema.enums.len(), 1);
+
+        let status = &schema.enums[0];
+        assert_eq!(status.name.to_string(), "PostStatus");
+        assert_eq!(status.docs, vec![" Where a post is in its lifecycle."]);
+        let variants: Vec<String> = status.variants.iter().map(|v| v.name.to_string()).collect();
+        assert_eq!(variants, vec!["Draft", "Published", "Archived"]);
+        assert_eq!(status.variants[1].docs, vec![" Visible to everyone."]);
+
+        let fields = &schema.entities[0].fields;
+        assert!(matches!(
+            &fields[0].attrs.default,
+            Some(DefaultValue::Variant(variant)) if variant == "Draft"
+        ));
+        assert!(matches!(
+            &fields[1].ty,
+            RawFieldType::Unknown { name, optional: true } if name == "PostStatus"
+        ));
+    }
+
+    #[test]
+    fn test_parse_enum_errors() {
+        let cases = [
+            (quote! { enum Empty {} }, "needs at least one variant"),
+            (
+                quote! { enum Status { Draft, Draft } },
+                "duplicate variant 'Draft'",
+            ),
+            (
+                quote! { enum Status { Draft(String) } },
+                "cannot carry data or discriminants",
+            ),
+            (
+                quote! { enum Status { Draft = 1 } },
+                "cannot carry data or discriminants",
+            ),
+            (
+                quote! { #[derive(Hash)] enum Status { Draft } },
+                "unknown enum attribute 'derive'",
+            ),
+        ];
+
+        for (item, message) in cases {
+            let input = quote! {
+                #item
+                Post { title: String }
+            };
+            let err = parse_schema(input).unwrap_err().to_string();
+            assert!(err.contains(message), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_extern_entity() {
+        let input = quote! {
+            extern entity User;
+            #[id(Uuid)]
+            extern entity Team;
+
+            Post {
+                author: User,
+                team: Team,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        assert_eq!(schema.externs.len(), 2);
+        assert_eq!(schema.externs[0].name, "User");
+        assert_eq!(schema.externs[0].id_type, IdType::I32);
+        assert_eq!(schema.externs[1].name, "Team");
+        assert_eq!(schema.externs[1].id_type, IdType::Uuid);
+        assert_eq!(schema.entities.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_extern_entity_errors() {
+        let cases = [
+            (quote! { extern entity User }, "expected `;`"),
+            (
+                quote! { extern model User; },
+                "expected `extern entity Name;`",
+            ),
+            (
+                quote! { #[table_name = "users"] extern entity User; },
+                "unknown extern entity attribute 'table_name'",
+            ),
+            (
+                quote! { #[id(i64)] extern entity User; },
+                "unsupported id type 'i64'",
+            ),
+        ];
+
+        for (item, message) in cases {
+            let input = quote! {
+                #item
+                Post { title: String }
+            };
+            let err = parse_schema(input).unwrap_err().to_string();
+            assert!(err.contains(message), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_referential_actions() {
+        let input = quote! {
+            Post {
+                #[on_delete(cascade)]
+                #[on_update(restrict)]
+                author: User,
+                #[on_delete(set_null)]
+                editor: Option<User>,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let fields = &schema.entities[0].fields;
+        let kind = |action: &Option<ReferentialAction>| action.as_ref().map(|a| a.kind);
+        assert_eq!(
+            kind(&fields[0].attrs.on_delete),
+            Some(ReferentialActionKind::Cascade)
+        );
+        assert_eq!(
+            kind(&fields[0].attrs.on_update),
+            Some(ReferentialActionKind::Restrict)
+        );
+        assert_eq!(
+            kind(&fields[1].attrs.on_delete),
+            Some(ReferentialActionKind::SetNull)
+        );
+        assert_eq!(kind(&fields[1].attrs.on_update), None);
+
+        let input = quote! {
+            Post {
+                #[on_delete(no_action)]
+                author: User,
+            }
+        };
+        let err = parse_schema(input).unwrap_err().to_string();
+        assert!(
+            err.contains("unknown referential action 'no_action'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_parse_default_errors() {
+        for attr in [
+            quote! { #[default = today()] },
+            quote! { #[default = now] },
+            quote! { #[default = -"x"] },
+            quote! { #[default = 5u8] },
+        ] {
+            let input = quote! {
+                Post {
+                    #attr
+                    views: i32,
+                }
+            };
+            assert!(parse_schema(input).is_err(), "{}", attr);
+        }
+    }
+
+    #[test]
+    fn test_parse_empty_alias_error() {
+        let input = quote! {
+            Post {
+                #[alias = ""]
+                headline: String,
+            }
+        };
+
+        let result = parse_schema(input);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("alias cannot be empty")
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_index_arg_error() {
+        let input = quote! {
+            User {
+                #[index(kind = "btree")]
+                login: String,
+            }
+        };
+
+        let result = parse_schema(input);
+        assert!(result.unwrap_err().to_string().contains("unknown argument"));
+    }
+
     #[test]
     fn test_parse_vec_field() {
         let input = quote! {
@@ -411,6 +1373,67 @@ mod tests {
         assert!(matches!(field.ty, RawFieldType::Vec { .. }));
     }
 
+    fn parse_field(ty: TokenStream) -> Result<RawFieldType> {
+        let input = quote! {
+            Post {
+                field: #ty,
+            }
+        };
+        parse_schema(input).map(|mut schema| schema.entities.remove(0).fields.remove(0).ty)
+    }
+
+    #[test]
+    fn test_parse_scalar_vec_is_array() {
+        assert!(matches!(
+            parse_field(quote!(Vec<String>)).unwrap(),
+            RawFieldType::Scalar {
+                scalar: ScalarType::String,
+                optional: false,
+                array: true,
+            }
+        ));
+        assert!(matches!(
+            parse_field(quote!(Option<Vec<i32>>)).unwrap(),
+            RawFieldType::Scalar {
+                scalar: ScalarType::I32,
+                optional: true,
+                array: true,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_optional_entity_vec() {
+        let RawFieldType::Vec { inner, option } = parse_field(quote!(Option<Vec<Tag>>)).unwrap()
+        else {
+            panic!("expected Vec");
+        };
+        assert_eq!(inner, "Tag");
+        assert_eq!(option.unwrap(), "Option");
+    }
+
+    #[test]
+    fn test_parse_vec_of_option() {
+        for ty in [quote!(Vec<Option<Tag>>), quote!(Vec<Option<String>>)] {
+            assert!(matches!(
+                parse_field(ty).unwrap(),
+                RawFieldType::VecOfOption { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_unsupported_nesting() {
+        let err = parse_field(quote!(Option<Option<String>>)).unwrap_err();
+        assert_eq!(err.to_string(), "Option<Option<String>> is not supported");
+
+        let err = parse_field(quote!(Vec<Vec<Tag>>)).unwrap_err();
+        assert_eq!(err.to_string(), "Vec<Vec<Tag>> is not supported");
+
+        let err = parse_field(quote!(Option<Vec<Option<Tag>>>)).unwrap_err();
+        assert!(err.to_string().contains("nested at most once"));
+    }
+
     #[test]
     fn test_parse_option_field() {
         let input = quote! {
@@ -515,6 +1538,129 @@ mod tests {
         assert_eq!(field.attrs.column_name, Some("user_email".to_string()));
     }
 
+    #[test]
+    fn test_parse_doc_comments() {
+        let input = quote! {
+            /// Registered users.
+            User {
+                /// Login address.
+                ///
+                /// Must be "unique".
+                #[unique]
+                email: String,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let entity = &schema.entities[0];
+        assert_eq!(entity.attrs.docs, vec![" Registered users."]);
+        assert_eq!(
+            entity.fields[0].attrs.docs,
+            vec![" Login address.", "", " Must be \"unique\"."]
+        );
+        assert!(entity.fields[0].attrs.unique);
+    }
+
+    #[test]
+    fn test_parse_fixtures_attr() {
+        let input = quote! {
+            #[fixtures]
+            #[timestamps(none)]
+            User {
+                email: String,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        assert!(schema.entities[0].attrs.fixtures);
+        assert!(!schema.entities[0].attrs.has_created_at);
+    }
+
+    #[test]
+    fn test_parse_hook_attrs() {
+        let input = quote! {
+            #[before_save(hooks::normalize_email)]
+            #[before_save(crate::audit::stamp)]
+            #[after_delete(hooks::forget)]
+            User {
+                email: String,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        let hooks = &schema.entities[0].attrs.hooks;
+        let before_save: Vec<String> = hooks.before_save.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            before_save,
+            vec!["hooks :: normalize_email", "crate :: audit :: stamp"]
+        );
+        assert!(hooks.after_save.is_empty());
+        assert!(hooks.before_delete.is_empty());
+        assert_eq!(hooks.after_delete[0].to_string(), "hooks :: forget");
+    }
+
+    #[test]
+    fn test_parse_hook_attr_requires_single_path() {
+        let input = quote! {
+            #[before_save(normalize_email, trim_name)]
+            User {
+                email: String,
+            }
+        };
+
+        let err = parse_schema(input).unwrap_err().to_string();
+        assert!(err.contains("expected a single function path"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_id_attr() {
+        let input = quote! {
+            #[id(Uuid)]
+            User {
+                email: String,
+            }
+        };
+
+        let schema = parse_schema(input).unwrap();
+        assert_eq!(schema.entities[0].attrs.id_type, IdType::Uuid);
+
+        let input = quote! {
+            User {
+                email: String,
+            }
+        };
+        let schema = parse_schema(input).unwrap();
+        assert_eq!(schema.entities[0].attrs.id_type, IdType::I32);
+    }
+
+    #[test]
+    fn test_parse_unsupported_id_type_error() {
+        let input = quote! {
+            #[id(String)]
+            User {
+                email: String,
+            }
+        };
+
+        let err = parse_schema(input).unwrap_err().to_string();
+        assert!(err.contains("unsupported id type 'String'"));
+    }
+
+    #[test]
+    fn test_id_attr_with_primary_key_error() {
+        let input = quote! {
+            #[id(Uuid)]
+            #[primary_key(user_id, role_id)]
+            UserRole {
+                user_id: i32,
+                role_id: i32,
+            }
+        };
+
+        let err = parse_schema(input).unwrap_err().to_string();
+        assert!(err.contains("cannot be combined with #[primary_key(...)]"));
+    }
+
     #[test]
     fn test_unknown_entity_attr_error() {
         let input = quote! {
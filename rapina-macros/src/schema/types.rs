@@ -74,13 +74,66 @@ impl ScalarType {
     }
 }
 
+/// Type of the `id` primary key generated for an entity, chosen with
+/// `#[id(...)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum IdType {
+    /// Auto-increment integer (default)
+    #[default]
+    I32,
+    /// UUID, generated in `before_save` when an insert leaves it unset
+    Uuid,
+}
+
+impl IdType {
+    /// Parse the argument of `#[id(...)]`.
+    pub fn from_ident(ident: &str) -> Option<Self> {
+        match ident {
+            "i32" => Some(IdType::I32),
+            "Uuid" => Some(IdType::Uuid),
+            _ => None,
+        }
+    }
+
+    /// Generate the Rust type of the id, and of foreign keys pointing at it.
+    pub fn rust_type(&self) -> TokenStream {
+        match self {
+            IdType::I32 => quote! { i32 },
+            IdType::Uuid => ScalarType::Uuid.rust_type(),
+        }
+    }
+}
+
 /// Field type classification.
 #[derive(Debug, Clone)]
 pub enum FieldType {
-    /// A scalar database column (String, i32, etc.)
-    Scalar { scalar: ScalarType, optional: bool },
+    /// A scalar database column (String, i32, etc.), or a Postgres array
+    /// of one
+    Scalar {
+        scalar: ScalarType,
+        optional: bool,
+        array: bool,
+    },
+    /// A column holding one of the schema's enums (Enum or Option<Enum>)
+    Enum { name: syn::Ident, optional: bool },
     /// A has_many relationship (Vec<Entity>)
     HasMany { target: syn::Ident },
-    /// A belongs_to relationship (Entity or Option<Entity>)
-    BelongsTo { target: syn::Ident, optional: bool },
+    /// A many-to-many relationship (`#[through(Join)]` on a Vec<Entity>),
+    /// going from the owner to the join entity and on to the target over
+    /// the join entity's belongs_to relations
+    ManyToMany {
+        target: syn::Ident,
+        through: syn::Ident,
+        /// Join entity relation pointing back at the owner
+        from_relation: syn::Ident,
+        /// Join entity relation pointing at the target
+        to_relation: syn::Ident,
+    },
+    /// A belongs_to relationship (Entity or Option<Entity>), whose foreign
+    /// key has the type of the target's id
+    BelongsTo {
+        target: syn::Ident,
+        optional: bool,
+        target_id: IdType,
+    },
 }
@@ -0,0 +1,438 @@
+//! The `sql!` macro: raw SQL with bind parameters checked at compile time.
+//!
+//! The SQL string is split into fragments around its placeholders, so the
+//! runtime only has to join them with the backend's placeholder syntax.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Expr, Ident, LitStr, Token};
+
+struct SqlInput {
+    sql: LitStr,
+    args: Vec<Arg>,
+}
+
+enum Arg {
+    Positional(Expr),
+    Named(Ident, Expr),
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) && input.peek2(Token![=]) && !input.peek2(Token![==]) {
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Ok(Arg::Named(name, input.parse()?))
+        } else {
+            Ok(Arg::Positional(input.parse()?))
+        }
+    }
+}
+
+impl Parse for SqlInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let sql: LitStr = input.parse()?;
+        let mut args = Vec::new();
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            args = Punctuated::<Arg, Token![,]>::parse_terminated(input)?
+                .into_iter()
+                .collect();
+        }
+        Ok(SqlInput { sql, args })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Placeholder {
+    Positional,
+    Named(String),
+}
+
+/// SQL split around its placeholders: `parts.len() == placeholders.len() + 1`.
+#[derive(Debug, PartialEq)]
+struct Lexed {
+    parts: Vec<String>,
+    placeholders: Vec<Placeholder>,
+}
+
+/// Splits `sql` around `?` and `:name` placeholders, skipping string
+/// literals, quoted identifiers and comments, and checks that quotes,
+/// comments and parentheses are closed.
+fn lex(sql: &str) -> Result<Lexed, String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut parts = Vec::new();
+    let mut placeholders = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' | '"' | '`' => {
+                let start = i;
+                current.push(c);
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(format!(
+                                "unterminated {} starting at offset {}",
+                                if c == '\'' {
+                                    "string literal"
+                                } else {
+                                    "quoted identifier"
+                                },
+                                start
+                            ));
+                        }
+                        // A doubled quote is an escaped quote
+                        Some(&q) if q == c && chars.get(i + 1) == Some(&c) => {
+                            current.push(c);
+                            current.push(c);
+                            i += 2;
+                        }
+                        Some(&q) => {
+                            current.push(q);
+                            i += 1;
+                            if q == c {
+                                break;
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    current.push(chars[i]);
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let start = i;
+                current.push_str("/*");
+                i += 2;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(format!(
+                                "unterminated comment starting at offset {}",
+                                start
+                            ));
+                        }
+                        Some('*') if chars.get(i + 1) == Some(&'/') => {
+                            current.push_str("*/");
+                            i += 2;
+                            break;
+                        }
+                        Some(&other) => {
+                            current.push(other);
+                            i += 1;
+                        }
+                    }
+                }
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => {
+                if depth == 0 {
+                    return Err(format!("unbalanced ')' at offset {}", i));
+                }
+                depth -= 1;
+            }
+            '?' if chars.get(i + 1) == Some(&'?') => {
+                current.push('?');
+                i += 2;
+                continue;
+            }
+            '?' => {
+                parts.push(std::mem::take(&mut current));
+                placeholders.push(Placeholder::Positional);
+                i += 1;
+                continue;
+            }
+            ':' if chars.get(i + 1) == Some(&':') => {
+                current.push_str("::");
+                i += 2;
+                continue;
+            }
+            ':' if chars
+                .get(i + 1)
+                .is_some_and(|n| n.is_ascii_alphabetic() || *n == '_') =>
+            {
+                let start = i + 1;
+                let mut end = start;
+                while chars
+                    .get(end)
+                    .is_some_and(|n| n.is_ascii_alphanumeric() || *n == '_')
+                {
+                    end += 1;
+                }
+                parts.push(std::mem::take(&mut current));
+                placeholders.push(Placeholder::Named(chars[start..end].iter().collect()));
+                i = end;
+                continue;
+            }
+            '$' if chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()) => {
+                return Err(
+                    "use `?` or `:name` placeholders instead of `$1`; they're rewritten for the database backend"
+                        .to_string(),
+                );
+            }
+            _ => {}
+        }
+        current.push(c);
+        i += 1;
+    }
+
+    if depth > 0 {
+        return Err(format!("{} unclosed '('", depth));
+    }
+    parts.push(current);
+    Ok(Lexed {
+        parts,
+        placeholders,
+    })
+}
+
+pub fn sql_impl(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(tokens) => tokens,
+        Err(err) => err.to_compile_error(),
+    }
+}
+
+fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let SqlInput { sql, args } = syn::parse2(input)?;
+    let lexed = lex(&sql.value())
+        .map_err(|msg| syn::Error::new(sql.span(), format!("invalid SQL: {}", msg)))?;
+
+    let named = lexed
+        .placeholders
+        .iter()
+        .any(|p| matches!(p, Placeholder::Named(_)));
+    if named && lexed.placeholders.contains(&Placeholder::Positional) {
+        return Err(syn::Error::new(
+            sql.span(),
+            "SQL mixes `?` and `:name` placeholders; use one style",
+        ));
+    }
+
+    let mut exprs = Vec::new();
+    let mut order = Vec::new();
+    if named {
+        let mut names: Vec<&Ident> = Vec::new();
+        for arg in &args {
+            match arg {
+                Arg::Named(name, expr) => {
+                    if names.contains(&name) {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            format!("argument `{}` is given more than once", name),
+                        ));
+                    }
+                    names.push(name);
+                    exprs.push(expr);
+                }
+                Arg::Positional(expr) => {
+                    return Err(syn::Error::new_spanned(
+                        expr,
+                        "SQL uses `:name` placeholders; pass arguments as `name = value`",
+                    ));
+                }
+            }
+        }
+        for placeholder in &lexed.placeholders {
+            let Placeholder::Named(placeholder) = placeholder else {
+                unreachable!()
+            };
+            let Some(index) = names.iter().position(|n| *n == placeholder) else {
+                return Err(syn::Error::new(
+                    sql.span(),
+                    format!("no argument for placeholder `:{}`", placeholder),
+                ));
+            };
+            order.push(index);
+        }
+        if let Some(unused) = names
+            .iter()
+            .enumerate()
+            .find(|(i, _)| !order.contains(i))
+            .map(|(_, n)| n)
+        {
+            return Err(syn::Error::new(
+                unused.span(),
+                format!("argument `{}` is not used in the SQL", unused),
+            ));
+        }
+    } else {
+        for arg in &args {
+            match arg {
+                Arg::Positional(expr) => exprs.push(expr),
+                Arg::Named(name, _) => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        format!(
+                            "named argument `{}` needs a `:{}` placeholder in the SQL",
+                            name, name
+                        ),
+                    ));
+                }
+            }
+        }
+        if exprs.len() != lexed.placeholders.len() {
+            return Err(syn::Error::new(
+                sql.span(),
+                format!(
+                    "SQL has {} placeholder{} but {} argument{} given",
+                    lexed.placeholders.len(),
+                    if lexed.placeholders.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    },
+                    exprs.len(),
+                    if exprs.len() == 1 { " was" } else { "s were" }
+                ),
+            ));
+        }
+        order = (0..exprs.len()).collect();
+    }
+
+    let parts = &lexed.parts;
+    let bindings: Vec<Ident> = (0..exprs.len())
+        .map(|i| format_ident!("__rapina_sql_arg_{}", i))
+        .collect();
+
+    Ok(quote! {
+        {
+            #(
+                let #bindings: rapina::sea_orm::Value = ::core::convert::Into::into(#exprs);
+            )*
+            rapina::database::Sql::from_parts(
+                &[#(#parts),*],
+                &[#(#order),*],
+                ::std::vec![#(#bindings),*],
+            )
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str) -> Placeholder {
+        Placeholder::Named(name.to_string())
+    }
+
+    #[test]
+    fn test_lex_positional() {
+        let lexed = lex("SELECT * FROM posts WHERE id = ? AND status = ?").unwrap();
+        assert_eq!(
+            lexed.parts,
+            ["SELECT * FROM posts WHERE id = ", " AND status = ", ""]
+        );
+        assert_eq!(
+            lexed.placeholders,
+            [Placeholder::Positional, Placeholder::Positional]
+        );
+    }
+
+    #[test]
+    fn test_lex_named_and_casts() {
+        let lexed = lex("SELECT created_at::date FROM posts WHERE author_id = :author").unwrap();
+        assert_eq!(
+            lexed.parts,
+            ["SELECT created_at::date FROM posts WHERE author_id = ", ""]
+        );
+        assert_eq!(lexed.placeholders, [named("author")]);
+    }
+
+    #[test]
+    fn test_lex_skips_strings_and_comments() {
+        let lexed =
+            lex("SELECT 'it''s ? :x', \"a?\" -- why?\n/* :y */ FROM t WHERE a = ?").unwrap();
+        assert_eq!(lexed.placeholders, [Placeholder::Positional]);
+        assert_eq!(
+            lexed.parts[0],
+            "SELECT 'it''s ? :x', \"a?\" -- why?\n/* :y */ FROM t WHERE a = "
+        );
+    }
+
+    #[test]
+    fn test_lex_escaped_question_mark() {
+        let lexed = lex("SELECT data ?? 'key' FROM t").unwrap();
+        assert!(lexed.placeholders.is_empty());
+        assert_eq!(lexed.parts, ["SELECT data ? 'key' FROM t"]);
+    }
+
+    #[test]
+    fn test_lex_unterminated_string() {
+        let err = lex("SELECT 'oops FROM t").unwrap_err();
+        assert!(err.contains("unterminated string literal"));
+    }
+
+    #[test]
+    fn test_lex_unterminated_comment() {
+        let err = lex("SELECT 1 /* oops").unwrap_err();
+        assert!(err.contains("unterminated comment"));
+    }
+
+    #[test]
+    fn test_lex_unbalanced_parens() {
+        assert!(
+            lex("SELECT count(*)) FROM t")
+                .unwrap_err()
+                .contains("unbalanced ')'")
+        );
+        assert!(
+            lex("SELECT count(* FROM t")
+                .unwrap_err()
+                .contains("unclosed '('")
+        );
+    }
+
+    #[test]
+    fn test_lex_rejects_dollar_placeholders() {
+        assert!(
+            lex("SELECT * FROM t WHERE id = $1")
+                .unwrap_err()
+                .contains("`$1`")
+        );
+    }
+
+    #[test]
+    fn test_expand_argument_count_mismatch() {
+        let err = expand(quote! { "SELECT * FROM t WHERE a = ? AND b = ?", a }).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "SQL has 2 placeholders but 1 argument was given"
+        );
+    }
+
+    #[test]
+    fn test_expand_named_argument_errors() {
+        let err = expand(quote! { "SELECT * FROM t WHERE a = :a", b = 1 }).unwrap_err();
+        assert_eq!(err.to_string(), "no argument for placeholder `:a`");
+
+        let err = expand(quote! { "SELECT * FROM t WHERE a = :a", a = 1, b = 2 }).unwrap_err();
+        assert_eq!(err.to_string(), "argument `b` is not used in the SQL");
+
+        let err = expand(quote! { "SELECT * FROM t WHERE a = :a AND b = ?", a = 1 }).unwrap_err();
+        assert!(err.to_string().contains("mixes"));
+    }
+
+    #[test]
+    fn test_expand_named_reuses_arguments() {
+        let output = expand(quote! { "SELECT * FROM t WHERE a = :id OR b = :id", id = 7 })
+            .unwrap()
+            .to_string();
+        assert!(output.contains("& [0usize , 0usize]"));
+        assert!(output.contains("rapina :: database :: Sql :: from_parts"));
+    }
+}
@@ -63,6 +63,15 @@ async fn health() -> StatusCode {
     StatusCode::OK
 }
 
+#[get("/users")]
+async fn list_users() -> Json<Vec<User>> {
+    Json(vec![User {
+        id: 1,
+        name: "Antonio".to_string(),
+        email: "antonio@tier3.dev".to_string(),
+    }])
+}
+
 #[get("/users/:id")]
 async fn get_user(id: Path<u64>) -> Result<Json<User>> {
     let id = id.into_inner();
@@ -113,7 +122,7 @@ async fn main() -> std::io::Result<()> {
         .get("/health", health)
         .get("/users/:id", get_user)
         .get("/me", get_me)
-        .post("/users", create_user);
+        .at("/users", |r| r.get(list_users).post(create_user));
 
     Rapina::new()
         .openapi("Rapina Test", "1.0.0")
@@ -0,0 +1,85 @@
+//! Example demonstrating a webhook endpoint that verifies an HMAC signature.
+//!
+//! Run with: `WEBHOOK_SECRET=your-secret cargo run --example webhook`
+//!
+//! Test it by signing the body the same way GitHub does:
+//!
+//! ```sh
+//! BODY='{"action":"opened","number":1}'
+//! SIG=$(printf '%s' "$BODY" | openssl dgst -sha256 -hmac your-secret | sed 's/^.* //')
+//! curl -X POST localhost:3000/webhooks/github \
+//!   -H "x-hub-signature-256: sha256=$SIG" -d "$BODY"
+//! ```
+
+use hmac::{Hmac, Mac};
+use rapina::prelude::*;
+use sha2::Sha256;
+
+#[derive(Deserialize)]
+struct PullRequestEvent {
+    action: String,
+    number: u64,
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => u8::from_str_radix(std::str::from_utf8(&[*hi, *lo]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks a `sha256=<hex>` signature against the exact bytes we received.
+fn verify_signature(secret: &[u8], header: Option<&str>, body: &[u8]) -> Result<()> {
+    let signature = header
+        .and_then(|h| h.strip_prefix("sha256="))
+        .and_then(decode_hex)
+        .ok_or_else(|| Error::unauthorized("missing or malformed signature"))?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|_| Error::internal("invalid webhook secret"))?;
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| Error::unauthorized("invalid signature"))
+}
+
+// RawJson keeps the raw body around, since re-serializing the parsed value
+// would not reproduce the bytes that were signed
+#[post("/webhooks/github")]
+async fn github_webhook(headers: Headers, body: RawJson<PullRequestEvent>) -> Result<StatusCode> {
+    let secret = get_env_or("WEBHOOK_SECRET", "your-secret");
+    let header = headers
+        .get("x-hub-signature-256")
+        .and_then(|v| v.to_str().ok());
+    verify_signature(secret.as_bytes(), header, body.bytes())?;
+
+    let event = body.into_inner();
+    tracing::info!(action = %event.action, number = event.number, "pull request event");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Bytes and String skip JSON parsing entirely
+#[post("/webhooks/raw")]
+async fn raw_webhook(body: Bytes) -> String {
+    format!("Received {} bytes", body.len())
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let router = Router::new()
+        .post("/webhooks/github", github_webhook)
+        .post("/webhooks/raw", raw_webhook);
+
+    println!("Endpoints:");
+    println!("  POST /webhooks/github");
+    println!("  POST /webhooks/raw");
+
+    Rapina::new()
+        .middleware(rapina::middleware::BodyLimitMiddleware::new(64 * 1024))
+        .router(router)
+        .listen("127.0.0.1:3000")
+        .await
+}
@@ -1,21 +1,37 @@
 //! The main application builder for Rapina.
 
+use std::any::Any;
 use std::future::Future;
 use std::net::SocketAddr;
 use std::time::Duration;
 
 use crate::auth::{AuthConfig, AuthMiddleware, PublicRoutes};
-use crate::introspection::{RouteRegistry, list_routes};
+use crate::cache::ResponseCache;
+use crate::drain::{Drain, DrainMiddleware, drain_status, readiness};
+use crate::i18n::{LocaleConfig, LocaleMiddleware};
+use crate::introspection::{
+    BuildInfo, IntrospectionGuard, MiddlewareRegistry, RouteRegistry, build_version,
+    list_middleware, list_routes,
+};
+use crate::json::{JsonOptions, ResponseLimits};
+use crate::links::UrlFor;
 #[cfg(feature = "metrics")]
 use crate::metrics::{MetricsMiddleware, MetricsRegistry, metrics_handler};
 use crate::middleware::{
-    CompressionConfig, CompressionMiddleware, CorsConfig, CorsMiddleware, Middleware,
-    MiddlewareStack, RateLimitConfig, RateLimitMiddleware,
+    BodyLimitMiddleware, CacheConfig, CacheMiddleware, CompressionConfig, CompressionMiddleware,
+    ContentTypeGuard, CorsConfig, CorsMiddleware, DefaultHeaderValue, DefaultHeadersMiddleware,
+    Middleware, MiddlewareStack, Phase, RateLimitConfig, RateLimitMiddleware, SlowLogConfig,
+    SlowRequestMiddleware, default_header_name,
 };
 use crate::observability::TracingConfig;
-use crate::openapi::{OpenApiRegistry, build_openapi_spec, openapi_spec};
+use crate::openapi::{
+    ExternalDocs, Info, OpenApiRegistry, Server, build_openapi_spec, interpolate_env, openapi_spec,
+};
+use crate::reload::ReloadHook;
 use crate::router::Router;
-use crate::server::{ShutdownHook, serve};
+use crate::schedule::{ScheduledJob, Scheduler, list_jobs};
+use crate::server::{Binding, Listener, ShutdownHook, serve_all};
+use crate::service::RapinaService;
 use crate::state::AppState;
 
 /// The main application type for building Rapina servers.
@@ -53,22 +69,65 @@ pub struct Rapina {
     pub(crate) middlewares: MiddlewareStack,
     /// Whether introspection is enabled.
     pub(crate) introspection: bool,
+    /// Token required on the `/__rapina` endpoints, if any
+    pub(crate) introspection_token: Option<String>,
     /// Whether metrics is enabled.
     pub(crate) metrics: bool,
     /// Whether OpenAPI is enabled
     pub(crate) openapi: bool,
-    pub(crate) openapi_title: String,
-    pub(crate) openapi_version: String,
+    pub(crate) openapi_info: Info,
+    pub(crate) openapi_servers: Vec<Server>,
+    pub(crate) openapi_external_docs: Option<ExternalDocs>,
+    /// Build metadata served on `/__rapina/version`
+    pub(crate) build_info: Option<BuildInfo>,
+    /// Whether the OpenAPI `info.version` comes from `build_info`
+    pub(crate) build_info_in_openapi: bool,
     /// Authentication configuration (if enabled)
     pub(crate) auth_config: Option<AuthConfig>,
     /// Public routes registry
     pub(crate) public_routes: PublicRoutes,
     /// Whether auto-discovery is enabled
     pub(crate) auto_discover: bool,
+    /// Whether a global CORS policy was configured
+    pub(crate) cors: bool,
+    /// Request body content types for routes without their own guard
+    pub(crate) content_types: Option<ContentTypeGuard>,
     /// Graceful shutdown timeout (default 30s)
     pub(crate) shutdown_timeout: Duration,
     /// Hooks to run during graceful shutdown
     pub(crate) shutdown_hooks: Vec<ShutdownHook>,
+    /// Callbacks to run on `SIGHUP`
+    pub(crate) reload_hooks: Vec<ReloadHook>,
+    /// Cron-scheduled background jobs
+    pub(crate) scheduler: Scheduler,
+    /// Workers for database-backed background jobs
+    #[cfg(feature = "database")]
+    pub(crate) job_queue: Option<crate::jobs::JobQueue>,
+    /// Slow request and slow query thresholds
+    pub(crate) slow_log: SlowLogConfig,
+    /// Headers added to every response that doesn't set them
+    pub(crate) default_headers: DefaultHeadersMiddleware,
+    /// Routes only served in tests, see [`test_routes`](Self::test_routes)
+    pub(crate) test_routes: Router,
+    /// Set by [`TestClient`](crate::testing::TestClient) to serve test routes
+    pub(crate) serve_test_routes: bool,
+    /// Extra addresses served alongside the main one
+    pub(crate) listeners: Vec<Listener>,
+    /// Draining state, when enabled with [`with_draining`](Self::with_draining)
+    pub(crate) drain: Option<Drain>,
+    /// App-wide request body limit, on by default
+    pub(crate) body_limit: Option<BodyLimitMiddleware>,
+    /// Whether `GET /__rapina/heap` was requested
+    #[cfg(feature = "profiling")]
+    pub(crate) heap_profiling: bool,
+}
+
+/// Environment variable that enables [`Rapina::test_routes`] outside tests.
+pub const TEST_ROUTES_ENV: &str = "RAPINA_TEST_ROUTES";
+
+/// Whether the `RAPINA_TEST_ROUTES` value turns test routes on.
+fn test_routes_env_enabled(value: Option<&str>) -> bool {
+    matches!(value.map(str::trim), Some("1" | "true"))
 }
 
 impl Rapina {
@@ -81,15 +140,34 @@ impl Rapina {
             state: AppState::new(),
             middlewares: MiddlewareStack::new(),
             introspection: cfg!(debug_assertions),
+            introspection_token: None,
             metrics: false,
             openapi: false,
-            openapi_title: "API".to_string(),
-            openapi_version: "1.0.0".to_string(),
+            openapi_info: Info::new("API", "1.0.0"),
+            openapi_servers: Vec::new(),
+            openapi_external_docs: None,
+            build_info: None,
+            build_info_in_openapi: false,
             auth_config: None,
             public_routes: PublicRoutes::new(),
             auto_discover: false,
+            cors: false,
+            content_types: None,
             shutdown_timeout: Duration::from_secs(30),
             shutdown_hooks: Vec::new(),
+            reload_hooks: Vec::new(),
+            scheduler: Scheduler::new(),
+            #[cfg(feature = "database")]
+            job_queue: None,
+            slow_log: SlowLogConfig::default(),
+            default_headers: DefaultHeadersMiddleware::new(),
+            test_routes: Router::new(),
+            serve_test_routes: false,
+            listeners: Vec::new(),
+            drain: None,
+            body_limit: Some(BodyLimitMiddleware::default()),
+            #[cfg(feature = "profiling")]
+            heap_profiling: false,
         }
     }
 
@@ -107,6 +185,9 @@ impl Rapina {
     /// routes registry (no manual `.public_route()` calls needed).
     ///
     /// Discovery is additive with manual `.router()` — both work together.
+    /// A discovered handler that was also registered manually is only added
+    /// once. If two different handlers claim the same method and path,
+    /// startup fails with an error naming both handlers.
     ///
     /// # Example
     ///
@@ -135,9 +216,36 @@ impl Rapina {
         self
     }
 
-    /// Adds a middleware to the application.
-    pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
-        self.middlewares.add(middleware);
+    /// Adds a middleware to the application, in the
+    /// [`Normal`](Phase::Normal) phase.
+    ///
+    /// Adding a [`BodyLimitMiddleware`] replaces the default body limit, see
+    /// [`with_body_limit`](Self::with_body_limit).
+    pub fn middleware<M: Middleware>(self, middleware: M) -> Self {
+        self.middleware_in(Phase::Normal, middleware)
+    }
+
+    /// Adds a middleware to the application in the given phase.
+    ///
+    /// Phases run in a fixed order whatever order they are registered in,
+    /// see [`Phase`]. A logging middleware added in
+    /// [`PreRouting`](Phase::PreRouting) sees requests that auth rejects;
+    /// one added with [`middleware`](Self::middleware) runs after auth.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rapina::middleware::{Phase, TraceIdMiddleware};
+    ///
+    /// Rapina::new()
+    ///     .with_auth(auth_config)
+    ///     .middleware_in(Phase::PreRouting, TraceIdMiddleware::new())
+    /// ```
+    pub fn middleware_in<M: Middleware>(mut self, phase: Phase, middleware: M) -> Self {
+        if (&middleware as &dyn Any).is::<BodyLimitMiddleware>() {
+            self.body_limit = None;
+        }
+        self.middlewares.add_in(phase, middleware);
         self
     }
 
@@ -145,6 +253,7 @@ impl Rapina {
     ///
     /// Use `CorsConfig::permisive()` for development (it allows all origins),
     /// or `CorsConfig::with_origins()` for production with specific origins.
+    /// Routes with their own policy from [`Router::cors`] use that instead.
     ///
     /// # Example
     ///
@@ -155,7 +264,37 @@ impl Rapina {
     ///  .listen("127.0.0.1:3000")
     ///  .await
     pub fn with_cors(mut self, config: CorsConfig) -> Self {
-        self.middlewares.add(CorsMiddleware::new(config));
+        self.cors = true;
+        self.middlewares
+            .add_in(Phase::PreRouting, CorsMiddleware::new(config));
+        self
+    }
+
+    /// Rejects request bodies that aren't `application/json` with 415.
+    ///
+    /// Shorthand for `require_content_types(ContentTypeGuard::json())`.
+    pub fn require_json(self) -> Self {
+        self.require_content_types(ContentTypeGuard::json())
+    }
+
+    /// Rejects request bodies whose `Content-Type` isn't allowed by `guard`
+    /// with 415, before any route middleware or handler reads them.
+    ///
+    /// Routes restricted with [`Router::content_types`] keep their own
+    /// list. The allowed types show up as the request body content types of
+    /// `POST` and `PUT` operations in the OpenAPI spec.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .require_content_types(ContentTypeGuard::new(["application/json", "application/x-ndjson"]))
+    ///     .router(router)
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    pub fn require_content_types(mut self, guard: ContentTypeGuard) -> Self {
+        self.content_types = Some(guard);
         self
     }
 
@@ -174,13 +313,61 @@ impl Rapina {
     ///     .await
     /// ```
     pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
-        self.middlewares.add(RateLimitMiddleware::new(config));
+        self.middlewares
+            .add_in(Phase::PreRouting, RateLimitMiddleware::new(config));
         self
     }
 
-    /// Enables response compression (gzip, deflate).
+    /// Enables response compression (brotli, gzip, deflate).
     pub fn with_compression(mut self, config: CompressionConfig) -> Self {
-        self.middlewares.add(CompressionMiddleware::new(config));
+        self.middlewares
+            .add_in(Phase::PostProcessing, CompressionMiddleware::new(config));
+        self
+    }
+
+    /// Enables response caching for every `GET` route.
+    ///
+    /// Handlers with their own `cache = "..."` option keep their TTL. See
+    /// [`cache`](crate::cache) for what gets cached and how it's invalidated.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .with_cache(CacheConfig::new(Duration::from_secs(30)))
+    ///     .discover()
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        let cache = ResponseCache::new().max_entries(config.max_entries);
+        self.state = self.state.with(cache.clone());
+        self.middlewares
+            .add(CacheMiddleware::with_cache(cache, config.ttl));
+        self
+    }
+
+    /// Sets the locales the app supports.
+    ///
+    /// The [`Locale`](crate::i18n::Locale) extractor negotiates against them.
+    /// When the config has a [`translate`](LocaleConfig::translate) hook,
+    /// error responses are translated into each request's locale.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .with_locales(LocaleConfig::new(["en", "pt-BR"]).translate(translate_error))
+    ///     .router(router)
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    pub fn with_locales(mut self, config: LocaleConfig) -> Self {
+        if config.has_translator() {
+            self.middlewares
+                .add_in(Phase::PostProcessing, LocaleMiddleware::new(config.clone()));
+        }
+        self.state = self.state.with(config);
         self
     }
 
@@ -232,6 +419,163 @@ impl Rapina {
         self
     }
 
+    /// Logs a warning for every request that takes at least `threshold`.
+    ///
+    /// The warning includes the method, path, status and duration. When the
+    /// handler used the [`Db`](crate::database::Db) extractor, it also lists
+    /// the request's slowest queries, with SQL truncated and parameters left
+    /// out.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use rapina::prelude::*;
+    ///
+    /// let app = Rapina::new()
+    ///     .slow_request_threshold(Duration::from_millis(500))
+    ///     .slow_query_threshold(Duration::from_millis(100));
+    /// ```
+    pub fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_log.request = Some(threshold);
+        self
+    }
+
+    /// Logs a warning for every database query that takes at least
+    /// `threshold`, and times queries for the slow request log.
+    ///
+    /// Applies to queries made through the [`Db`](crate::database::Db)
+    /// extractor.
+    pub fn slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_log.query = Some(threshold);
+        self
+    }
+
+    /// Sets the app-wide request body limit.
+    ///
+    /// Every app starts with [`BodyLimitMiddleware::default`], a 1 MB limit
+    /// answered with 413 Payload Too Large. It runs before any other
+    /// middleware, so oversized uploads are turned away before auth or
+    /// rate limiting look at them. Routes can raise or lower it with
+    /// [`Router::body_limit`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rapina::middleware::BodyLimitMiddleware;
+    ///
+    /// Rapina::new()
+    ///     .with_body_limit(BodyLimitMiddleware::new(2 * 1024 * 1024))
+    /// ```
+    pub fn with_body_limit(mut self, limit: BodyLimitMiddleware) -> Self {
+        self.body_limit = Some(limit);
+        self
+    }
+
+    /// Turns off the default request body limit.
+    ///
+    /// Limits set with [`Router::body_limit`] still apply to their routes.
+    pub fn without_body_limit(mut self) -> Self {
+        self.body_limit = None;
+        self
+    }
+
+    /// Adds a header to every response that doesn't already have it.
+    ///
+    /// Can be called repeatedly. Headers the handler sets, or that a
+    /// [`Router::default_header`] sets for its routes, win over app-wide
+    /// defaults. Values can be computed per request from state with
+    /// [`DefaultHeaderValue::lazy`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rapina::middleware::DefaultHeaderValue;
+    ///
+    /// Rapina::new()
+    ///     .state(ApiVersion("2024-06-01".to_string()))
+    ///     .default_header("x-content-type-options", "nosniff")
+    ///     .default_header(
+    ///         "x-api-version",
+    ///         DefaultHeaderValue::lazy(|state| state.get::<ApiVersion>().map(|v| v.0.clone())),
+    ///     )
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name, or if a fixed value is
+    /// not a valid header value.
+    pub fn default_header(mut self, name: &str, value: impl Into<DefaultHeaderValue>) -> Self {
+        self.default_headers
+            .push(default_header_name(name), value.into());
+        self
+    }
+
+    /// Sets how [`Json`](crate::extract::Json) responses and request bodies
+    /// are formatted across the app.
+    ///
+    /// With [`RenameRule::CamelCase`](crate::json::RenameRule::CamelCase),
+    /// struct fields are sent in camelCase, request bodies accept either
+    /// camelCase or the Rust field names, and the OpenAPI spec documents the
+    /// camelCase names. A single response can override this with
+    /// [`Json::with_options`](crate::extract::Json::with_options).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rapina::json::{JsonOptions, RenameRule};
+    ///
+    /// Rapina::new().json_options(JsonOptions {
+    ///     rename_all: RenameRule::CamelCase,
+    ///     pretty: false,
+    /// })
+    /// ```
+    pub fn json_options(mut self, options: JsonOptions) -> Self {
+        self.state = self.state.with(options);
+        self
+    }
+
+    /// Caps the size of response bodies at `bytes`.
+    ///
+    /// A [`Json`](crate::extract::Json) response that grows past the limit
+    /// stops serializing, is logged, and is replaced with a 500 carrying the
+    /// `RESPONSE_TOO_LARGE` code, so an unpaginated table can't turn into a
+    /// multi-gigabyte body. Other bodies built in memory are checked the same
+    /// way before they are sent; streams are not limited.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new().max_response_size(10 * 1024 * 1024)
+    /// ```
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        let mut limits = self.response_limits();
+        limits.max_size = Some(bytes);
+        self.state = self.state.with(limits);
+        self
+    }
+
+    /// Includes the serde error in `SERIALIZATION_ERROR` responses.
+    ///
+    /// A [`Json`](crate::extract::Json) response that fails to serialize is
+    /// always logged with its type name and the serde error, and answered
+    /// with a 500. By default the body only says that serialization failed;
+    /// with this enabled its `details` also name the type and the error.
+    /// Meant for development, as the error can reveal internals.
+    pub fn expose_serialization_errors(mut self, enabled: bool) -> Self {
+        let mut limits = self.response_limits();
+        limits.expose_errors = enabled;
+        self.state = self.state.with(limits);
+        self
+    }
+
+    fn response_limits(&self) -> ResponseLimits {
+        self.state
+            .get::<ResponseLimits>()
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Enables or disables the introspection endpoint.
     ///
     /// When enabled, a `GET /.__rapina/routes` endpoint is registered
@@ -243,6 +587,52 @@ impl Rapina {
         self
     }
 
+    /// Requires `token` on every `/__rapina` endpoint, the OpenAPI spec
+    /// included.
+    ///
+    /// Clients send it as `Authorization: Bearer <token>` or in an
+    /// `X-Rapina-Token` header. Requests without the right token get the
+    /// same 404 as an unknown path, so the endpoints aren't advertised.
+    /// `/__rapina/ready` stays open for load balancer probes.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .with_introspection(true)
+    ///     .introspection_token(std::env::var("RAPINA_INTROSPECTION_TOKEN")?)
+    ///     .router(router)
+    ///     .listen("0.0.0.0:3000")
+    ///     .await
+    /// ```
+    pub fn introspection_token(mut self, token: impl Into<String>) -> Self {
+        self.introspection_token = Some(token.into());
+        self
+    }
+
+    /// Adds routes that only exist in tests.
+    ///
+    /// Use them for hooks that poke internal state, like clearing a cache or
+    /// reading a counter, without exposing them in production. They are
+    /// served by [`TestClient`](crate::testing::TestClient), or when the
+    /// `RAPINA_TEST_ROUTES` environment variable is `1`, and otherwise
+    /// dropped. They never show up in introspection or the OpenAPI spec.
+    ///
+    /// The router's middleware and route settings apply to its routes as
+    /// they would under [`router`](Self::router).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// Rapina::new()
+    ///     .router(router)
+    ///     .test_routes(Router::new().post("/__test/cache/clear", clear_cache))
+    /// ```
+    pub fn test_routes(mut self, router: Router) -> Self {
+        self.test_routes = self.test_routes.nest("", router);
+        self
+    }
+
     /// Sets the graceful shutdown timeout.
     ///
     /// When the server receives a shutdown signal (SIGINT/SIGTERM), it stops
@@ -253,6 +643,55 @@ impl Rapina {
         self
     }
 
+    /// Lets the server drain before an orchestrated shutdown.
+    ///
+    /// Draining starts on `SIGUSR1` or through the
+    /// [`start_drain`](crate::drain::start_drain) handler. From then on,
+    /// `GET /__rapina/ready` answers 503 and responses carry
+    /// `Connection: close`; after `grace_period` the listeners close and the
+    /// server shuts down as on `SIGTERM`. With introspection enabled,
+    /// `GET /__rapina/drain` reports the draining state. See
+    /// [`drain`](crate::drain) for mounting the handler on an admin listener.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .with_draining(Duration::from_secs(20))
+    ///     .router(router)
+    ///     .listen("0.0.0.0:8080")
+    ///     .await
+    /// ```
+    pub fn with_draining(mut self, grace_period: Duration) -> Self {
+        self.drain = Some(Drain::new(grace_period));
+        self
+    }
+
+    /// Serves another router on a second address from the same process.
+    ///
+    /// The listener shares the app's state, so a change made through one
+    /// address is visible through the other. It serves only its own router
+    /// and middleware: the app's routes, global middleware, introspection
+    /// and OpenAPI endpoints stay on the address given to
+    /// [`listen`](Self::listen). One shutdown signal drains every listener
+    /// and runs the shutdown hooks once.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rapina::server::Listener;
+    ///
+    /// Rapina::new()
+    ///     .router(public_router)
+    ///     .listener(Listener::new("127.0.0.1:9090", admin_router).middleware(AdminOnly))
+    ///     .listen("0.0.0.0:8080")
+    ///     .await
+    /// ```
+    pub fn listener(mut self, listener: Listener) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
     /// Registers an async hook to run during graceful shutdown.
     ///
     /// Hooks run after in-flight connections have drained (or the timeout
@@ -278,6 +717,104 @@ impl Rapina {
         self
     }
 
+    /// Registers an async callback to run when the process receives `SIGHUP`.
+    ///
+    /// Callbacks run in the background in registration order, without
+    /// interrupting in-flight requests. They typically re-read configuration
+    /// into a [`Reloadable`](crate::reload::Reloadable) cell that handlers
+    /// read through [`ReloadableState`](crate::reload::ReloadableState). A
+    /// callback that returns an error is logged and should leave the previous
+    /// configuration in place. `SIGHUP` is only handled on Unix.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .state(Reloadable::new(Limits::load()?))
+    ///     .on_reload(|state| async move {
+    ///         let limits = Limits::load()?;
+    ///         state.get::<Reloadable<Limits>>().unwrap().set(limits);
+    ///         Ok::<_, std::io::Error>(())
+    ///     })
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    pub fn on_reload<F, Fut, E>(mut self, hook: F) -> Self
+    where
+        F: Fn(std::sync::Arc<AppState>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display + 'static,
+    {
+        self.reload_hooks.push(Box::new(move |state| {
+            let reload = hook(state);
+            Box::pin(async move { reload.await.map_err(|e| e.to_string()) })
+        }));
+        self
+    }
+
+    /// Schedules a background job using a cron expression.
+    ///
+    /// Accepts standard five-field cron syntax, or six fields with a leading
+    /// seconds field. The closure receives the application state each time
+    /// the job runs. Times are evaluated in UTC, missed runs are skipped and
+    /// overlapping runs are prevented; use [`schedule_job`](Self::schedule_job)
+    /// to change any of these.
+    ///
+    /// Jobs start when the server starts listening and stop on shutdown.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cron expression is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .schedule("0 3 * * *", "nightly-cleanup", |_state| async {
+    ///         tracing::info!("running nightly cleanup");
+    ///     })
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    pub fn schedule<F, Fut>(self, expression: &str, name: &str, task: F) -> Self
+    where
+        F: Fn(std::sync::Arc<AppState>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let job = ScheduledJob::new(expression, name, task)
+            .unwrap_or_else(|e| panic!("invalid schedule for job '{}': {}", name, e));
+        self.schedule_job(job)
+    }
+
+    /// Registers a fully configured [`ScheduledJob`].
+    pub fn schedule_job(mut self, job: ScheduledJob) -> Self {
+        self.scheduler.add(job);
+        self
+    }
+
+    /// Runs enqueued [background jobs](crate::jobs) in this process.
+    ///
+    /// The workers start when the server starts listening and use the
+    /// connection from [`with_database`](Self::with_database). On shutdown
+    /// they finish the jobs they are running, after in-flight requests
+    /// have drained.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .with_database(DatabaseConfig::from_env()?)
+    ///     .await?
+    ///     .with_jobs(JobQueue::new().register::<SendEmail>())
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    #[cfg(feature = "database")]
+    pub fn with_jobs(mut self, queue: crate::jobs::JobQueue) -> Self {
+        self.job_queue = Some(queue);
+        self
+    }
+
     /// Enables or disables the metrics endpoint.
     ///
     /// When enabled, a `GET /metrics` endpoint is registered
@@ -289,6 +826,21 @@ impl Rapina {
         self
     }
 
+    /// Enables or disables the heap profiling endpoint.
+    ///
+    /// When enabled, `GET /__rapina/heap` reports allocator counters, the
+    /// process's resident set size, live connections, in-flight requests,
+    /// cached responses and state entries. See [`profiling`](crate::profiling).
+    ///
+    /// Debug builds serve it once enabled. Release builds also need
+    /// `RAPINA_HEAP_PROFILING=1` in the environment, and skip it with a
+    /// warning otherwise.
+    #[cfg(feature = "profiling")]
+    pub fn with_heap_profiling(mut self, enabled: bool) -> Self {
+        self.heap_profiling = enabled;
+        self
+    }
+
     /// Enables or disables openapi endpoint
     ///
     /// When enabled, a get `/__rapina/openapi.json` endpoint is registered
@@ -296,8 +848,97 @@ impl Rapina {
     /// OpenAPI is disabled by default
     pub fn openapi(mut self, title: impl Into<String>, version: impl Into<String>) -> Self {
         self.openapi = true;
-        self.openapi_title = title.into();
-        self.openapi_version = version.into();
+        self.openapi_info.title = title.into();
+        self.openapi_info.version = version.into();
+        self
+    }
+
+    /// Enables the openapi endpoint with full API metadata.
+    ///
+    /// Like [`openapi`](Self::openapi), but also sets the description,
+    /// contact and license shown in the spec's `info` block.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rapina::openapi::{Info, License};
+    /// use rapina::prelude::*;
+    ///
+    /// let app = Rapina::new().openapi_info(
+    ///     Info::new("Todo API", "2.1.0")
+    ///         .description("Tracks todos across teams")
+    ///         .license(License::new("MIT")),
+    /// );
+    /// ```
+    pub fn openapi_info(mut self, info: Info) -> Self {
+        self.openapi = true;
+        self.openapi_info = info;
+        self
+    }
+
+    /// Adds a server to the OpenAPI spec's `servers` list.
+    ///
+    /// Call it once per environment (production, staging, ...). `${VAR}`
+    /// references in the URL are replaced with environment variables at
+    /// startup; startup fails if one is unset.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rapina::prelude::*;
+    ///
+    /// let app = Rapina::new()
+    ///     .openapi("Todo API", "2.1.0")
+    ///     .openapi_server("https://api.example.com", "Production")
+    ///     .openapi_server("https://${STAGING_HOST}", "Staging");
+    /// ```
+    pub fn openapi_server(
+        mut self,
+        url: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.openapi_servers.push(Server {
+            url: url.into(),
+            description: Some(description.into()),
+        });
+        self
+    }
+
+    /// Registers what was built, usually captured with
+    /// [`build_info!`](crate::build_info).
+    ///
+    /// With introspection enabled it is served on `GET /__rapina/version`,
+    /// and it is also stored in the state for handlers that want it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// Rapina::new()
+    ///     .build_info(rapina::build_info!())
+    ///     .router(router)
+    /// ```
+    pub fn build_info(mut self, info: BuildInfo) -> Self {
+        self.build_info = Some(info);
+        self
+    }
+
+    /// Uses the version from [`build_info`](Self::build_info), with the short
+    /// commit hash when known, as the OpenAPI `info.version`.
+    pub fn build_info_in_openapi(mut self) -> Self {
+        self.build_info_in_openapi = true;
+        self
+    }
+
+    /// Links the OpenAPI spec to documentation hosted elsewhere.
+    pub fn openapi_external_docs(
+        mut self,
+        url: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.openapi_external_docs = Some(ExternalDocs {
+            url: url.into(),
+            description: Some(description.into()),
+        });
         self
     }
 
@@ -305,7 +946,8 @@ impl Rapina {
     ///
     /// This method connects to the database and registers the connection
     /// in the application state. Use the [`Db`](crate::database::Db) extractor
-    /// in your handlers to access the connection.
+    /// in your handlers to access the connection. Configured read replicas
+    /// are connected too, and health-checked in the background.
     ///
     /// # Example
     ///
@@ -340,9 +982,50 @@ impl Rapina {
             .await
             .map_err(|e| std::io::Error::other(format!("Database connection failed: {}", e)))?;
         self.state = self.state.with(conn);
+
+        if !config.replica_urls.is_empty() {
+            let replicas = config.connect_replicas().await.map_err(|e| {
+                std::io::Error::other(format!("Read replica connection failed: {}", e))
+            })?;
+            replicas
+                .spawn_health_checks(Duration::from_secs(config.replica_health_interval.max(1)));
+            self.state = self.state.with(replicas);
+        }
         Ok(self)
     }
 
+    /// Registers a [`CachedQuery`](crate::database::CachedQuery) in the
+    /// application state.
+    ///
+    /// Handlers read it with `State<CachedQuery<T>>`. Events for the
+    /// entities it lists in `invalidate_on` drop its cached value.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let plans = CachedQuery::new(
+    ///     |db| async move { Plan::find().all(&db).await },
+    ///     Duration::from_secs(300),
+    /// )
+    /// .invalidate_on("plan");
+    ///
+    /// Rapina::new().cached_query(plans)
+    /// ```
+    #[cfg(feature = "database")]
+    pub fn cached_query<T: Clone + Send + Sync + 'static>(
+        mut self,
+        query: crate::database::CachedQuery<T>,
+    ) -> Self {
+        let queries = self
+            .state
+            .get::<crate::database::QueryCaches>()
+            .cloned()
+            .unwrap_or_default();
+        queries.register(&query);
+        self.state = self.state.with(queries).with(query);
+        self
+    }
+
     /// Runs all pending database migrations at startup.
     ///
     /// Call this after `with_database()` to apply migrations before serving requests.
@@ -373,6 +1056,17 @@ impl Rapina {
                 )
             })?;
 
+        // `rapina migrate down` and `fresh` run the app just for this
+        if let Some(command) =
+            crate::migration::MigrateCommand::from_env().map_err(std::io::Error::other)?
+        {
+            command
+                .run::<M>(conn)
+                .await
+                .map_err(|e| std::io::Error::other(format!("Migration failed: {}", e)))?;
+            std::process::exit(0);
+        }
+
         crate::migration::run_pending::<M>(conn)
             .await
             .map_err(|e| std::io::Error::other(format!("Migration failed: {}", e)))?;
@@ -384,7 +1078,10 @@ impl Rapina {
     ///
     /// Both [`listen`](Self::listen) and [`TestClient::new`](crate::testing::TestClient::new)
     /// call this so the app behaves identically in tests and production.
-    pub(crate) fn prepare(mut self) -> Self {
+    ///
+    /// Fails if auto-discovery finds handlers that conflict with each other
+    /// or with manually registered routes.
+    pub(crate) fn prepare(mut self) -> std::io::Result<Self> {
         // Auto-discover routes from inventory (must run before auth middleware)
         if self.auto_discover {
             let manual_count = self.router.routes.len();
@@ -397,7 +1094,14 @@ impl Rapina {
 
             let mut discovered_public = 0usize;
             for descriptor in inventory::iter::<crate::discovery::RouteDescriptor> {
-                self.router = (descriptor.register)(self.router);
+                let already_registered = self.router.routes.iter().any(|(method, route)| {
+                    method.as_str() == descriptor.method
+                        && route.pattern == descriptor.path
+                        && route.handler_name == descriptor.handler_name
+                });
+                if !already_registered {
+                    self.router = (descriptor.register)(self.router);
+                }
                 if descriptor.is_public || public_names.contains(descriptor.handler_name) {
                     self.public_routes.add(descriptor.method, descriptor.path);
                     discovered_public += 1;
@@ -410,13 +1114,91 @@ impl Rapina {
                 discovered_count,
                 discovered_public
             );
+
+            let conflicts = crate::discovery::find_conflicts(&self.router.routes());
+            if !conflicts.is_empty() {
+                let details: Vec<String> = conflicts.iter().map(|c| format!("  {}", c)).collect();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("conflicting routes:\n{}", details.join("\n")),
+                ));
+            }
+        }
+
+        // Before the built-in endpoints, which the app router's middleware
+        // and settings shouldn't cover, and before the app-wide settings
+        // below, which only fill in what routes don't have
+        self.router.apply_settings();
+
+        // Routes without their own guard get the app-wide one
+        if let Some(guard) = self.content_types.take() {
+            let guard = std::sync::Arc::new(guard);
+            for (_, route) in &mut self.router.routes {
+                route.content_types.get_or_insert_with(|| guard.clone());
+            }
+        }
+
+        // Route-level CORS policies need the middleware even without a global one
+        if !self.cors && self.router.has_route_cors() {
+            self.middlewares
+                .add_in(Phase::PreRouting, CorsMiddleware::route_scoped());
+        }
+
+        // Handlers with a `cache` option share one store per app
+        if self.state.get::<ResponseCache>().is_none() {
+            self.state = self.state.with(ResponseCache::new());
+        }
+
+        if self.slow_log.is_enabled() {
+            self.state = self.state.with(self.slow_log);
+        }
+        if let Some(threshold) = self.slow_log.request {
+            self.middlewares
+                .add_in(Phase::PreRouting, SlowRequestMiddleware::new(threshold));
+        }
+
+        // Ahead of the other middleware, so oversized bodies are rejected
+        // before any work is done for them
+        if let Some(body_limit) = self.body_limit.take() {
+            self.middlewares.push_front(std::sync::Arc::new(body_limit));
+        }
+
+        // Outermost, so responses other middleware answer early get them too
+        if !self.default_headers.is_empty() {
+            let default_headers = std::mem::take(&mut self.default_headers);
+            self.middlewares
+                .push_front(std::sync::Arc::new(default_headers));
+        }
+
+        if let Some(drain) = &self.drain {
+            self.state = self.state.with(drain.clone());
+            // Outermost, so every response closes its connection
+            self.middlewares
+                .push_front(std::sync::Arc::new(DrainMiddleware::new(drain.clone())));
         }
 
         // Add auth middleware if configured
         if let Some(auth_config) = self.auth_config.take() {
             let auth_middleware =
                 AuthMiddleware::with_public_routes(auth_config, self.public_routes.clone());
-            self.middlewares.add(auth_middleware);
+            self.middlewares.add_in(Phase::Auth, auth_middleware);
+        }
+
+        if let Some(token) = self.introspection_token.take() {
+            if token.trim().is_empty() {
+                return Err(std::io::Error::other(
+                    "introspection_token() needs a non-empty token",
+                ));
+            }
+            self.middlewares
+                .add_in(Phase::Auth, IntrospectionGuard::new(token.trim()));
+        }
+
+        // Before the built-in endpoints, so links only resolve to app routes
+        self.state = self.state.with(UrlFor::new(&self.router.routes()));
+
+        if let Some(info) = &self.build_info {
+            self.state = self.state.with(info.clone());
         }
 
         if self.introspection {
@@ -424,7 +1206,27 @@ impl Rapina {
             self.state = self.state.with(RouteRegistry::with_routes(routes));
             self.router = self
                 .router
-                .get_named("/__rapina/routes", "list_routes", list_routes);
+                .get_named("/__rapina/routes", "list_routes", list_routes)
+                .get_named("/__rapina/middleware", "list_middleware", list_middleware);
+
+            if self.build_info.is_some() {
+                self.router =
+                    self.router
+                        .get_named("/__rapina/version", "build_version", build_version);
+            }
+
+            if !self.scheduler.is_empty() {
+                self.state = self.state.with(self.scheduler.registry());
+                self.router = self
+                    .router
+                    .get_named("/__rapina/jobs", "list_jobs", list_jobs);
+            }
+
+            if self.drain.is_some() {
+                self.router =
+                    self.router
+                        .get_named("/__rapina/drain", "drain_status", drain_status);
+            }
         }
 
         #[cfg(feature = "metrics")]
@@ -437,37 +1239,200 @@ impl Rapina {
                 .get_named("/metrics", "metrics", metrics_handler);
         }
 
+        #[cfg(feature = "profiling")]
+        if self.heap_profiling {
+            use crate::profiling::{
+                HEAP_PROFILING_ENV, InFlightMiddleware, ServerStats, heap_profile,
+                heap_profiling_enabled,
+            };
+
+            let env = std::env::var(HEAP_PROFILING_ENV).ok();
+            if heap_profiling_enabled(true, env.as_deref()) {
+                let stats = ServerStats::new();
+                self.state = self.state.with(stats.clone());
+                self.middlewares
+                    .push_front(std::sync::Arc::new(InFlightMiddleware::new(stats)));
+                self.router = self
+                    .router
+                    .get_named("/__rapina/heap", "heap_profile", heap_profile);
+            } else {
+                tracing::warn!(
+                    "Skipping /__rapina/heap in a release build; set {}=1 to serve it",
+                    HEAP_PROFILING_ENV
+                );
+            }
+        }
+
+        // The global middleware are all in place from here on
+        for warning in self.middlewares.ordering_warnings() {
+            tracing::warn!("Middleware order: {}", warning);
+        }
+        if self.introspection {
+            self.state = self
+                .state
+                .with(MiddlewareRegistry::new(self.middlewares.describe()));
+        }
+
         if self.openapi {
             let routes = self.router.routes();
-            let spec = build_openapi_spec(&self.openapi_title, &self.openapi_version, &routes);
+            let mut spec = build_openapi_spec(
+                &self.openapi_info.title,
+                &self.openapi_info.version,
+                &routes,
+            );
+            if let Some(options) = self.state.get::<JsonOptions>() {
+                options.rename_spec(&mut spec);
+            }
+            spec.info = self.openapi_info.clone();
+            if self.build_info_in_openapi {
+                let info = self.build_info.as_ref().ok_or_else(|| {
+                    std::io::Error::other("build_info_in_openapi() needs build_info(...) to be set")
+                })?;
+                spec.info.version = info.full_version();
+            }
+            spec.external_docs = self.openapi_external_docs.clone();
+            for server in &self.openapi_servers {
+                let url = interpolate_env(&server.url).map_err(|var| {
+                    std::io::Error::other(format!(
+                        "OpenAPI server URL '{}' references unset environment variable '{}'",
+                        server.url, var
+                    ))
+                })?;
+                spec.servers.push(Server {
+                    url,
+                    description: server.description.clone(),
+                });
+            }
             self.state = self.state.with(OpenApiRegistry::new(spec));
             self.router =
                 self.router
                     .get_named("/__rapina/openapi.json", "openapi_spec", openapi_spec);
         }
 
+        if self.drain.is_some() {
+            self.router = self
+                .router
+                .get_named("/__rapina/ready", "readiness", readiness);
+        }
+
+        // Added last so introspection and the OpenAPI spec never list them
+        let test_routes = std::mem::take(&mut self.test_routes.routes);
+        if !test_routes.is_empty() {
+            let enabled = cfg!(test)
+                || self.serve_test_routes
+                || test_routes_env_enabled(std::env::var(TEST_ROUTES_ENV).ok().as_deref());
+            if enabled {
+                tracing::warn!(
+                    "Serving {} test-only route(s); these must not be enabled in production",
+                    test_routes.len()
+                );
+                self.router.routes.extend(test_routes);
+            } else {
+                tracing::debug!(
+                    "Skipping {} test-only route(s); set {}=1 to serve them",
+                    test_routes.len(),
+                    TEST_ROUTES_ENV
+                );
+            }
+        }
+
         // Sort routes so static segments take priority over parameterized ones.
         // This prevents `/users/:id` from shadowing `/users/current`.
         self.router.sort_routes();
 
-        self
+        Ok(self)
+    }
+
+    /// Describes handlers, on any listener, that extract state which was
+    /// never registered.
+    pub(crate) fn state_warnings(&self) -> Vec<String> {
+        std::iter::once(&self.router)
+            .chain(self.listeners.iter().map(|listener| &listener.router))
+            .flat_map(|router| router.state_warnings(&self.state))
+            .collect()
     }
 
-    /// Starts the HTTP server on the given address.
+    /// Prepares the application and returns it as a [`RapinaService`] instead
+    /// of binding a listener.
+    ///
+    /// Use this to embed the app in another server or runtime. Scheduled jobs
+    /// and shutdown hooks only run under [`listen`](Self::listen).
+    pub fn into_service(self) -> std::io::Result<RapinaService> {
+        let app = self.prepare()?;
+        Ok(RapinaService::new(app.router, app.state, app.middlewares))
+    }
+
+    /// Starts the HTTP server on the given address, along with any extra
+    /// [`listener`](Self::listener)s.
     ///
     /// # Panics
     ///
-    /// Panics if the address cannot be parsed.
+    /// Panics if an address cannot be parsed.
     pub async fn listen(self, addr: &str) -> std::io::Result<()> {
         let addr: SocketAddr = addr.parse().expect("invalid address");
-        let app = self.prepare();
-        serve(
-            app.router,
-            app.state,
-            app.middlewares,
+        // A migration command that reached `listen` was never run, and
+        // serving instead would leave `rapina migrate` waiting forever
+        #[cfg(feature = "database")]
+        if std::env::var_os(crate::migration::MIGRATE_COMMAND_ENV).is_some() {
+            return Err(std::io::Error::other(format!(
+                "{} is set, but the app never called .run_migrations::<Migrator>()",
+                crate::migration::MIGRATE_COMMAND_ENV
+            )));
+        }
+        let app = self.prepare()?;
+
+        // Otherwise a missing `.state()` call only shows up on the first request
+        if cfg!(debug_assertions) {
+            for warning in app.state_warnings() {
+                tracing::warn!("{}", warning);
+            }
+        }
+
+        let mut bindings = vec![Binding {
             addr,
+            router: app.router,
+            middlewares: app.middlewares,
+        }];
+        for listener in app.listeners {
+            let mut router = listener.router;
+            router.apply_settings();
+            router.sort_routes();
+            let mut middlewares = listener.middlewares;
+            if let Some(drain) = &app.drain {
+                middlewares.push_front(std::sync::Arc::new(DrainMiddleware::new(drain.clone())));
+            }
+            bindings.push(Binding {
+                addr: listener.addr.parse().expect("invalid address"),
+                router,
+                middlewares,
+            });
+        }
+
+        let mut shutdown_hooks = app.shutdown_hooks;
+        if !app.scheduler.is_empty() {
+            let scheduler = app.scheduler.start(std::sync::Arc::new(app.state.clone()));
+            shutdown_hooks.insert(
+                0,
+                Box::new(move || {
+                    Box::pin(async move {
+                        scheduler.shutdown();
+                    })
+                }),
+            );
+        }
+
+        #[cfg(feature = "database")]
+        if let Some(queue) = app.job_queue {
+            let jobs = queue.start(std::sync::Arc::new(app.state.clone()))?;
+            shutdown_hooks.insert(0, Box::new(move || Box::pin(jobs.shutdown())));
+        }
+
+        serve_all(
+            bindings,
+            app.state,
             app.shutdown_timeout,
-            app.shutdown_hooks,
+            shutdown_hooks,
+            app.reload_hooks,
         )
         .await
     }
@@ -498,6 +1463,15 @@ mod tests {
         assert!(app.middlewares.is_empty());
     }
 
+    #[test]
+    fn test_test_routes_env_enabled() {
+        assert!(test_routes_env_enabled(Some("1")));
+        assert!(test_routes_env_enabled(Some("true")));
+        assert!(!test_routes_env_enabled(Some("0")));
+        assert!(!test_routes_env_enabled(Some("")));
+        assert!(!test_routes_env_enabled(None));
+    }
+
     #[test]
     fn test_rapina_with_router() {
         let router = Router::new().route(
@@ -632,6 +1606,14 @@ mod tests {
         assert!(!app.metrics);
     }
 
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn test_rapina_with_heap_profiling() {
+        assert!(!Rapina::new().heap_profiling);
+        let app = Rapina::new().with_heap_profiling(true);
+        assert!(app.heap_profiling);
+    }
+
     #[test]
     fn test_rapina_shutdown_timeout_default() {
         let app = Rapina::new();
@@ -644,6 +1626,14 @@ mod tests {
         assert_eq!(app.shutdown_timeout, Duration::from_secs(10));
     }
 
+    #[test]
+    fn test_rapina_on_reload_adds_hook() {
+        let app = Rapina::new()
+            .on_reload(|_| async { Ok::<_, std::io::Error>(()) })
+            .on_reload(|_| async { Err("invalid config") });
+        assert_eq!(app.reload_hooks.len(), 2);
+    }
+
     #[test]
     fn test_rapina_on_shutdown_adds_hook() {
         let app = Rapina::new()
@@ -651,4 +1641,18 @@ mod tests {
             .on_shutdown(|| async { println!("hook 2") });
         assert_eq!(app.shutdown_hooks.len(), 2);
     }
+
+    #[test]
+    fn test_rapina_schedule_adds_job() {
+        let app = Rapina::new()
+            .schedule("0 3 * * *", "nightly", |_| async {})
+            .schedule("*/10 * * * * *", "frequent", |_| async {});
+        assert_eq!(app.scheduler.registry().jobs().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid schedule for job 'broken'")]
+    fn test_rapina_schedule_panics_on_invalid_expression() {
+        let _ = Rapina::new().schedule("not a cron", "broken", |_| async {});
+    }
 }
@@ -33,8 +33,10 @@
 //! ```
 
 mod middleware;
+pub mod policy;
 
 pub use middleware::AuthMiddleware;
+pub use policy::{Authorize, Policy};
 
 use crate::error::Error;
 use crate::extract::{FromRequestParts, PathParams};
@@ -0,0 +1,240 @@
+//! Authorization policies checked after authentication.
+//!
+//! Authentication tells who is calling; a [`Policy`] decides whether they
+//! may call a given route. Attach policies to handlers with
+//! `#[authorize(...)]` below the route macro, or take an [`Authorize<P>`]
+//! argument:
+//!
+//! ```ignore
+//! use rapina::prelude::*;
+//!
+//! struct AdminOnly;
+//!
+//! impl Policy for AdminOnly {
+//!     fn authorize(&self, claims: &Claims, _parts: &Parts, state: &AppState) -> Result<()> {
+//!         let admins = state.get::<Admins>().expect("Admins is registered");
+//!         if admins.contains(&claims.sub) {
+//!             Ok(())
+//!         } else {
+//!             Err(Error::forbidden("admins only"))
+//!         }
+//!     }
+//! }
+//!
+//! #[delete("/users/:id")]
+//! #[authorize(any(AdminOnly, Owner))]
+//! async fn delete_user(id: Path<u64>) -> StatusCode {
+//!     StatusCode::NO_CONTENT
+//! }
+//! ```
+//!
+//! Requests without an authenticated user get 401. A policy that denies
+//! with [`Error::forbidden`] has the error code replaced by its
+//! [`name`](Policy::name), so clients can tell which rule failed
+//! (`ADMIN_ONLY`), including inside [`any`] and [`all`]. Other errors, like
+//! a 404 for a missing resource, are returned as they are.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use http::request::Parts;
+
+use crate::auth::{Claims, CurrentUser};
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::state::AppState;
+
+/// A rule deciding whether the authenticated caller may use a route.
+pub trait Policy: Send + Sync + 'static {
+    /// Allows the request, or returns the error to answer with.
+    ///
+    /// Deny with [`Error::forbidden`]; its generic `FORBIDDEN` code is
+    /// replaced with the policy's [`name`](Self::name).
+    fn authorize(&self, claims: &Claims, parts: &Parts, state: &AppState) -> Result<(), Error>;
+
+    /// Error code used when the policy denies a request.
+    ///
+    /// Defaults to the type name in SCREAMING_SNAKE_CASE, `AdminOnly`
+    /// becoming `ADMIN_ONLY`.
+    fn name(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        screaming_snake(name.rsplit("::").next().unwrap_or(name))
+    }
+}
+
+impl<P: Policy + ?Sized> Policy for Box<P> {
+    fn authorize(&self, claims: &Claims, parts: &Parts, state: &AppState) -> Result<(), Error> {
+        (**self).authorize(claims, parts, state)
+    }
+
+    fn name(&self) -> String {
+        (**self).name()
+    }
+}
+
+/// Runs `policy` for the request, as `#[authorize(...)]` does.
+///
+/// Fails with 401 when the request has no authenticated user, and tags
+/// `FORBIDDEN` denials with the policy's name.
+pub fn check<P: Policy + ?Sized>(policy: &P, parts: &Parts, state: &AppState) -> Result<(), Error> {
+    let user = parts
+        .extensions
+        .get::<CurrentUser>()
+        .ok_or_else(|| Error::unauthorized("authentication required"))?;
+    evaluate(policy, &user.claims, parts, state)
+}
+
+/// Runs `policy`, naming it in 403 denials.
+fn evaluate<P: Policy + ?Sized>(
+    policy: &P,
+    claims: &Claims,
+    parts: &Parts,
+    state: &AppState,
+) -> Result<(), Error> {
+    policy.authorize(claims, parts, state).map_err(|mut err| {
+        if err.status == 403 && err.code == "FORBIDDEN" {
+            err.code = policy.name();
+        }
+        err
+    })
+}
+
+/// Allows the request when every policy allows it, see [`all`].
+pub struct All(Vec<Box<dyn Policy>>);
+
+/// Allows the request when any policy allows it, see [`any`].
+pub struct Any(Vec<Box<dyn Policy>>);
+
+/// Allows the request when the policy denies it, see [`not`].
+pub struct Not(Box<dyn Policy>);
+
+/// Allows the request when every policy allows it.
+///
+/// Policies run in order and the first denial is returned.
+pub fn all(policies: Vec<Box<dyn Policy>>) -> All {
+    All(policies)
+}
+
+/// Allows the request when any policy allows it.
+///
+/// Policies run in order until one allows. When all of them deny, the first
+/// denial is returned.
+pub fn any(policies: Vec<Box<dyn Policy>>) -> Any {
+    Any(policies)
+}
+
+/// Allows the request when `policy` denies it with a 403.
+pub fn not(policy: impl Policy) -> Not {
+    Not(Box::new(policy))
+}
+
+impl Policy for All {
+    fn authorize(&self, claims: &Claims, parts: &Parts, state: &AppState) -> Result<(), Error> {
+        self.0
+            .iter()
+            .try_for_each(|policy| evaluate(policy, claims, parts, state))
+    }
+
+    fn name(&self) -> String {
+        "ALL".to_string()
+    }
+}
+
+impl Policy for Any {
+    fn authorize(&self, claims: &Claims, parts: &Parts, state: &AppState) -> Result<(), Error> {
+        let mut first_denial = None;
+        for policy in &self.0 {
+            match evaluate(policy, claims, parts, state) {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    first_denial.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_denial.unwrap_or_else(|| Error::forbidden("no policy allowed the request")))
+    }
+
+    fn name(&self) -> String {
+        "ANY".to_string()
+    }
+}
+
+impl Policy for Not {
+    fn authorize(&self, claims: &Claims, parts: &Parts, state: &AppState) -> Result<(), Error> {
+        match evaluate(&self.0, claims, parts, state) {
+            Ok(()) => Err(Error::forbidden("request matches an excluded policy")),
+            Err(err) if err.status == 403 => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("NOT_{}", self.0.name())
+    }
+}
+
+/// Extractor that runs a policy before the handler.
+///
+/// The policy type must implement `Default`. Equivalent to
+/// `#[authorize(P)]`, for handlers that prefer to list it as an argument.
+///
+/// ```ignore
+/// #[get("/admin/stats")]
+/// async fn stats(_: Authorize<AdminOnly>) -> Json<Stats> { ... }
+/// ```
+pub struct Authorize<P>(PhantomData<P>);
+
+impl<P: Policy + Default> FromRequestParts for Authorize<P> {
+    async fn from_request_parts(
+        parts: &Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        check(&P::default(), parts, state)?;
+        Ok(Self(PhantomData))
+    }
+}
+
+/// `AdminOnly` to `ADMIN_ONLY`.
+fn screaming_snake(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_uppercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct AdminOnly;
+
+    impl Policy for AdminOnly {
+        fn authorize(&self, _: &Claims, _: &Parts, _: &AppState) -> Result<(), Error> {
+            Err(Error::forbidden("admins only"))
+        }
+    }
+
+    #[test]
+    fn test_default_policy_name() {
+        assert_eq!(AdminOnly.name(), "ADMIN_ONLY");
+        assert_eq!(not(AdminOnly).name(), "NOT_ADMIN_ONLY");
+    }
+
+    #[test]
+    fn test_screaming_snake() {
+        assert_eq!(screaming_snake("Owner"), "OWNER");
+        assert_eq!(screaming_snake("HTTPSOnly"), "HTTPS_ONLY");
+        assert_eq!(screaming_snake("Tier2Access"), "TIER2_ACCESS");
+    }
+}
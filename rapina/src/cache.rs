@@ -0,0 +1,412 @@
+//! In-memory response caching.
+//!
+//! Handlers opt in with the `cache` option of the route macros:
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//!
+//! #[get("/stats", cache = "60s", invalidate_on = ["post"])]
+//! async fn stats(db: Db) -> Result<Json<Stats>> {
+//!     // Runs at most once a minute per path and query string
+//! }
+//! ```
+//!
+//! Successful responses are stored in the app's [`ResponseCache`] under the
+//! request method, path and query string, and sent with
+//! `Cache-Control: max-age=<seconds>`. Entities listed in `invalidate_on` drop
+//! the handler's entries whenever an event for them is published through
+//! [`Events`](crate::events::Events), so `post.created` clears a cached
+//! `/stats` right away instead of after the TTL.
+//!
+//! For caching every `GET` route, see
+//! [`CacheMiddleware`](crate::middleware::CacheMiddleware). A handler's own
+//! `cache` option takes precedence over the middleware's TTL.
+//!
+//! The cache key ignores headers, so don't cache handlers whose response
+//! depends on the caller (e.g. ones taking `CurrentUser`).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, Method, Request, Response, StatusCode, header};
+use http_body_util::BodyExt;
+
+use crate::response::BoxBody;
+use crate::state::AppState;
+
+/// Default upper bound on stored responses.
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    path: String,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    expires_at: Instant,
+    tags: Vec<String>,
+}
+
+/// Shared store for cached responses.
+///
+/// One is registered in application state automatically; register your own
+/// with [`Rapina::state`](crate::app::Rapina::state) to change the size limit,
+/// or extract it with `State<ResponseCache>` to invalidate entries by hand.
+///
+/// # Example
+///
+/// ```
+/// use rapina::cache::ResponseCache;
+///
+/// let cache = ResponseCache::new().max_entries(1_000);
+/// cache.invalidate_prefix("/stats");
+/// assert!(cache.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<HashMap<String, CachedResponse>>>,
+    max_entries: usize,
+}
+
+impl ResponseCache {
+    /// Creates an empty cache holding up to [`DEFAULT_MAX_ENTRIES`] responses.
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Sets the maximum number of stored responses.
+    ///
+    /// When full, expired entries are dropped first, then the ones closest
+    /// to expiring.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Returns the cache registered in state, or an empty one if there is none.
+    pub fn from_state(state: &AppState) -> Self {
+        state.get::<ResponseCache>().cloned().unwrap_or_default()
+    }
+
+    /// Cache key for a request: method, path and query string.
+    pub fn key<B>(req: &Request<B>) -> String {
+        let path_and_query = req
+            .uri()
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/");
+        format!("{} {}", req.method(), path_and_query)
+    }
+
+    /// Returns the cached response for `key` if it hasn't expired.
+    ///
+    /// The `Cache-Control` max-age is lowered to the time the entry has left.
+    pub fn get(&self, key: &str) -> Option<Response<BoxBody>> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > now => {}
+            Some(_) => {
+                entries.remove(key);
+                return None;
+            }
+            None => return None,
+        }
+        let entry = &entries[key];
+
+        let mut response = Response::new(BoxBody::new(entry.body.clone()));
+        *response.status_mut() = entry.status;
+        *response.headers_mut() = entry.headers.clone();
+        let remaining = entry.expires_at.duration_since(now);
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, max_age(remaining));
+        Some(response)
+    }
+
+    /// Stores a successful response for `ttl` and returns it.
+    ///
+    /// Adds `Cache-Control: max-age=<ttl>` unless the response already has a
+    /// `Cache-Control` header. Responses that aren't `200 OK`, set cookies, or
+    /// are marked `no-store` or `private` are returned without being stored.
+    pub async fn store(
+        &self,
+        key: String,
+        response: Response<BoxBody>,
+        ttl: Duration,
+        tags: &[&str],
+    ) -> Response<BoxBody> {
+        if !is_cacheable(&response) {
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        let body = match body.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(_) => unreachable!("streamed bodies are not cached"),
+        };
+        parts
+            .headers
+            .entry(header::CACHE_CONTROL)
+            .or_insert_with(|| max_age(ttl));
+
+        let path = key
+            .split_once(' ')
+            .map(|(_, rest)| rest.split('?').next().unwrap_or(rest))
+            .unwrap_or("")
+            .to_string();
+        self.insert(
+            key,
+            CachedResponse {
+                path,
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+                expires_at: Instant::now() + ttl,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+            },
+        );
+
+        Response::from_parts(parts, BoxBody::new(body))
+    }
+
+    fn insert(&self, key: String, entry: CachedResponse) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let now = Instant::now();
+            entries.retain(|_, e| e.expires_at > now);
+            if entries.len() >= self.max_entries {
+                let soonest = entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.expires_at)
+                    .map(|(k, _)| k.clone());
+                if let Some(soonest) = soonest {
+                    entries.remove(&soonest);
+                }
+            }
+        }
+        entries.insert(key, entry);
+    }
+
+    /// Removes every entry whose path starts with `prefix`.
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, e| !e.path.starts_with(prefix));
+    }
+
+    /// Removes the entries of handlers that listed `entity` in `invalidate_on`.
+    pub fn invalidate_entity(&self, entity: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, e| !e.tags.iter().any(|t| t == entity));
+    }
+
+    /// Removes every entry.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Returns the number of stored entries, including expired ones not yet evicted.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns `true` if nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether responses to this method can be cached.
+pub(crate) fn is_cacheable_method(method: &Method) -> bool {
+    method == Method::GET || method == Method::HEAD
+}
+
+fn is_cacheable(response: &Response<BoxBody>) -> bool {
+    if response.status() != StatusCode::OK
+        || response.headers().contains_key(header::SET_COOKIE)
+        || response.body().is_stream()
+    {
+        return false;
+    }
+    match response
+        .headers()
+        .get(header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(value) => {
+            let value = value.to_ascii_lowercase();
+            !value.contains("no-store") && !value.contains("private")
+        }
+        None => true,
+    }
+}
+
+fn max_age(ttl: Duration) -> HeaderValue {
+    let secs = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+    HeaderValue::from_str(&format!("max-age={}", secs)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(body: &'static str) -> Response<BoxBody> {
+        Response::new(BoxBody::new(body))
+    }
+
+    async fn body_of(response: Response<BoxBody>) -> Bytes {
+        response.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[test]
+    fn test_key_includes_method_path_and_query() {
+        let req = Request::get("/stats?range=week").body(()).unwrap();
+        assert_eq!(ResponseCache::key(&req), "GET /stats?range=week");
+
+        let req = Request::get("/stats").body(()).unwrap();
+        assert_eq!(ResponseCache::key(&req), "GET /stats");
+    }
+
+    #[tokio::test]
+    async fn test_store_and_get() {
+        let cache = ResponseCache::new();
+        let response = cache
+            .store("GET /stats".into(), ok("42"), Duration::from_secs(60), &[])
+            .await;
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "max-age=60");
+        assert_eq!(body_of(response).await, "42");
+
+        let hit = cache.get("GET /stats").unwrap();
+        assert_eq!(hit.status(), StatusCode::OK);
+        assert!(
+            hit.headers()[header::CACHE_CONTROL]
+                .to_str()
+                .unwrap()
+                .starts_with("max-age=")
+        );
+        assert_eq!(body_of(hit).await, "42");
+        assert!(cache.get("GET /other").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_dropped() {
+        let cache = ResponseCache::new();
+        cache
+            .store("GET /stats".into(), ok("42"), Duration::ZERO, &[])
+            .await;
+        assert!(cache.get("GET /stats").is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_does_not_store_errors_or_private_responses() {
+        let cache = ResponseCache::new();
+        let ttl = Duration::from_secs(60);
+
+        let mut not_found = ok("missing");
+        *not_found.status_mut() = StatusCode::NOT_FOUND;
+        cache.store("GET /a".into(), not_found, ttl, &[]).await;
+
+        let mut private = ok("mine");
+        private
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("private"));
+        cache.store("GET /b".into(), private, ttl, &[]).await;
+
+        let mut cookie = ok("session");
+        cookie
+            .headers_mut()
+            .insert(header::SET_COOKIE, HeaderValue::from_static("id=1"));
+        cache.store("GET /c".into(), cookie, ttl, &[]).await;
+
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_keeps_existing_cache_control() {
+        let cache = ResponseCache::new();
+        let mut response = ok("42");
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=5"),
+        );
+        let response = cache
+            .store("GET /stats".into(), response, Duration::from_secs(60), &[])
+            .await;
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_prefix() {
+        let cache = ResponseCache::new();
+        let ttl = Duration::from_secs(60);
+        cache
+            .store("GET /posts?page=2".into(), ok("a"), ttl, &[])
+            .await;
+        cache.store("GET /posts/1".into(), ok("b"), ttl, &[]).await;
+        cache.store("GET /users".into(), ok("c"), ttl, &[]).await;
+
+        cache.invalidate_prefix("/posts");
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get("GET /users").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_entity() {
+        let cache = ResponseCache::new();
+        let ttl = Duration::from_secs(60);
+        cache
+            .store("GET /stats".into(), ok("a"), ttl, &["post", "user"])
+            .await;
+        cache
+            .store("GET /users".into(), ok("b"), ttl, &["user"])
+            .await;
+
+        cache.invalidate_entity("post");
+        assert!(cache.get("GET /stats").is_none());
+        assert!(cache.get("GET /users").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_evicts_when_full() {
+        let cache = ResponseCache::new().max_entries(2);
+        cache
+            .store("GET /a".into(), ok("a"), Duration::from_secs(10), &[])
+            .await;
+        cache
+            .store("GET /b".into(), ok("b"), Duration::from_secs(60), &[])
+            .await;
+        cache
+            .store("GET /c".into(), ok("c"), Duration::from_secs(60), &[])
+            .await;
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("GET /a").is_none());
+        assert!(cache.get("GET /b").is_some());
+        assert!(cache.get("GET /c").is_some());
+    }
+
+    #[test]
+    fn test_max_age_rounds_up() {
+        assert_eq!(max_age(Duration::from_secs(60)), "max-age=60");
+        assert_eq!(max_age(Duration::from_millis(1500)), "max-age=2");
+    }
+}
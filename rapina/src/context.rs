@@ -1,29 +1,135 @@
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How many of a request's slowest queries are kept for the slow request log.
+const KEPT_QUERIES: usize = 5;
+
+/// SQL longer than this is cut short when recorded.
+const SQL_PREVIEW_LEN: usize = 200;
 
 #[derive(Debug, Clone)]
 pub struct RequestContext {
     pub trace_id: String,
     pub start_time: Instant,
+    /// Shared by every clone, so a write anywhere in the request pins the rest.
+    primary_pin: Arc<AtomicBool>,
+    /// The request's slowest database queries, slowest first.
+    queries: Arc<Mutex<Vec<QueryTiming>>>,
+    /// The route the request resolved to, set once before middleware runs.
+    matched_route: Arc<OnceLock<MatchedRoute>>,
 }
 
-impl RequestContext {
-    pub fn new() -> Self {
+/// The route a request was dispatched to.
+///
+/// Holds the route template, such as `/users/:id`, rather than the concrete
+/// path, so it can be used as a low-cardinality label in logs and metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedRoute {
+    /// The pattern the route was registered with, e.g. `/users/:id`.
+    pub pattern: String,
+    /// The handler name shown in route introspection, e.g. `get_user`.
+    pub name: String,
+}
+
+/// A database query timed during a request.
+///
+/// The SQL is truncated and never includes bound parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryTiming {
+    pub sql: String,
+    pub duration: Duration,
+    pub failed: bool,
+}
+
+impl QueryTiming {
+    pub fn new(sql: &str, duration: Duration, failed: bool) -> Self {
+        let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+        let sql = match sql.char_indices().nth(SQL_PREVIEW_LEN) {
+            Some((end, _)) => format!("{}...", &sql[..end]),
+            None => sql,
+        };
         Self {
-            trace_id: uuid::Uuid::new_v4().to_string(),
-            start_time: Instant::now(),
+            sql,
+            duration,
+            failed,
         }
     }
+}
+
+impl RequestContext {
+    pub fn new() -> Self {
+        Self::with_trace_id(uuid::Uuid::new_v4().to_string())
+    }
 
     pub fn with_trace_id(trace_id: String) -> Self {
         Self {
             trace_id,
             start_time: Instant::now(),
+            primary_pin: Arc::new(AtomicBool::new(false)),
+            queries: Arc::new(Mutex::new(Vec::new())),
+            matched_route: Arc::new(OnceLock::new()),
         }
     }
 
     pub fn elapsed(&self) -> std::time::Duration {
         self.start_time.elapsed()
     }
+
+    /// Sends the rest of this request's database reads to the primary.
+    ///
+    /// Called when a handler writes, so reads that follow don't hit a
+    /// replica that hasn't caught up yet.
+    pub fn pin_to_primary(&self) {
+        self.primary_pin.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether reads for this request must go to the primary.
+    pub fn is_pinned_to_primary(&self) -> bool {
+        self.primary_pin.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "database")]
+    pub(crate) fn primary_pin(&self) -> Arc<AtomicBool> {
+        self.primary_pin.clone()
+    }
+
+    /// Records a query run for this request, keeping only the slowest few.
+    pub fn record_query(&self, timing: QueryTiming) {
+        let mut queries = self.queries.lock().unwrap();
+        let at = queries.partition_point(|q| q.duration >= timing.duration);
+        if at < KEPT_QUERIES {
+            queries.insert(at, timing);
+            queries.truncate(KEPT_QUERIES);
+        }
+    }
+
+    /// The slowest queries recorded for this request, slowest first.
+    pub fn slowest_queries(&self) -> Vec<QueryTiming> {
+        self.queries.lock().unwrap().clone()
+    }
+
+    /// The route this request was dispatched to.
+    ///
+    /// Routes are resolved before any middleware runs, so this is available
+    /// to middleware as well as handlers. `None` when no route matched.
+    pub fn matched_route(&self) -> Option<&MatchedRoute> {
+        self.matched_route.get()
+    }
+
+    /// Records the route the request resolved to. Only the first call has
+    /// an effect.
+    pub(crate) fn set_matched_route(&self, route: MatchedRoute) {
+        let _ = self.matched_route.set(route);
+    }
+
+    /// A context for the same request under a different trace ID.
+    pub(crate) fn with_replaced_trace_id(&self, trace_id: String) -> Self {
+        Self {
+            trace_id,
+            ..self.clone()
+        }
+    }
 }
 
 impl Default for RequestContext {
@@ -82,6 +188,82 @@ mod tests {
         assert_eq!(ctx1.trace_id, ctx2.trace_id);
     }
 
+    #[test]
+    fn test_primary_pin_is_shared_by_clones() {
+        let ctx1 = RequestContext::new();
+        let ctx2 = ctx1.clone();
+        assert!(!ctx2.is_pinned_to_primary());
+
+        ctx1.pin_to_primary();
+        assert!(ctx2.is_pinned_to_primary());
+        assert!(!RequestContext::new().is_pinned_to_primary());
+    }
+
+    #[test]
+    fn test_record_query_keeps_slowest() {
+        let ctx = RequestContext::new();
+        for ms in [3, 10, 1, 7, 5, 2, 9] {
+            ctx.clone().record_query(QueryTiming::new(
+                &format!("SELECT {}", ms),
+                Duration::from_millis(ms),
+                false,
+            ));
+        }
+
+        let kept: Vec<u128> = ctx
+            .slowest_queries()
+            .iter()
+            .map(|q| q.duration.as_millis())
+            .collect();
+        assert_eq!(kept, vec![10, 9, 7, 5, 3]);
+    }
+
+    #[test]
+    fn test_query_timing_truncates_sql() {
+        let long = format!("SELECT *\n  FROM items WHERE {}", "x = 1 AND ".repeat(50));
+        let timing = QueryTiming::new(&long, Duration::from_millis(1), false);
+
+        assert!(timing.sql.starts_with("SELECT * FROM items WHERE x = 1"));
+        assert!(timing.sql.ends_with("..."));
+        assert_eq!(timing.sql.chars().count(), SQL_PREVIEW_LEN + 3);
+    }
+
+    #[test]
+    fn test_matched_route_is_shared_by_clones() {
+        let ctx1 = RequestContext::new();
+        let ctx2 = ctx1.clone();
+        assert!(ctx2.matched_route().is_none());
+
+        ctx1.set_matched_route(MatchedRoute {
+            pattern: "/users/:id".to_string(),
+            name: "get_user".to_string(),
+        });
+        ctx1.set_matched_route(MatchedRoute {
+            pattern: "/other".to_string(),
+            name: "other".to_string(),
+        });
+
+        let matched = ctx2.matched_route().unwrap();
+        assert_eq!(matched.pattern, "/users/:id");
+        assert_eq!(matched.name, "get_user");
+    }
+
+    #[test]
+    fn test_replaced_trace_id_keeps_request_state() {
+        let ctx = RequestContext::new();
+        ctx.set_matched_route(MatchedRoute {
+            pattern: "/".to_string(),
+            name: "index".to_string(),
+        });
+
+        let replaced = ctx.with_replaced_trace_id("upstream-id".to_string());
+        replaced.pin_to_primary();
+
+        assert_eq!(replaced.trace_id, "upstream-id");
+        assert_eq!(replaced.matched_route().unwrap().pattern, "/");
+        assert!(ctx.is_pinned_to_primary());
+    }
+
     #[test]
     fn test_debug() {
         let ctx = RequestContext::with_trace_id("test-id".to_string());
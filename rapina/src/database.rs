@@ -45,11 +45,36 @@
 //! DATABASE_MIN_CONNECTIONS=5    # default: 1
 //! DATABASE_CONNECT_TIMEOUT=30   # seconds, default: 30
 //! DATABASE_IDLE_TIMEOUT=600     # seconds, default: 600
+//! DATABASE_REPLICA_URLS=postgres://replica1/myapp,postgres://replica2/myapp
+//! ```
+//!
+//! # Read Replicas
+//!
+//! With replica URLs configured, [`Db::read`] spreads queries round-robin
+//! across healthy replicas and [`Db::write`] always uses the primary. Once a
+//! request writes, its later reads go to the primary too, so it never reads
+//! stale data from a lagging replica. When no replica is healthy, reads fall
+//! back to the primary.
+//!
+//! ```rust,ignore
+//! #[get("/users")]
+//! async fn list_users(db: Db) -> Result<Json<Vec<User>>> {
+//!     let users = UserEntity::find().all(db.read()).await?;
+//!     Ok(Json(users))
+//! }
 //! ```
 
-use sea_orm::{ConnectOptions, Database, DatabaseConnection};
-use std::time::Duration;
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, ExecResult,
+    FromQueryResult, Statement,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use crate::context::{QueryTiming, RequestContext};
 use crate::error::{Error, IntoApiError};
 
 /// Database configuration with environment-aware defaults.
@@ -70,6 +95,10 @@ pub struct DatabaseConfig {
     pub idle_timeout: u64,
     /// Enable SQL query logging (default: true in debug, false in release)
     pub sqlx_logging: bool,
+    /// Read replica URLs; reads use the primary when empty (default: none)
+    pub replica_urls: Vec<String>,
+    /// Seconds between replica health checks (default: 5)
+    pub replica_health_interval: u64,
 }
 
 impl DatabaseConfig {
@@ -82,6 +111,8 @@ impl DatabaseConfig {
             connect_timeout: 30,
             idle_timeout: 600,
             sqlx_logging: cfg!(debug_assertions),
+            replica_urls: Vec::new(),
+            replica_health_interval: 5,
         }
     }
 
@@ -96,6 +127,8 @@ impl DatabaseConfig {
     /// - `DATABASE_CONNECT_TIMEOUT`: Connection timeout in seconds (default: 30)
     /// - `DATABASE_IDLE_TIMEOUT`: Idle timeout in seconds (default: 600)
     /// - `DATABASE_LOGGING`: Enable SQL logging (default: true in debug)
+    /// - `DATABASE_REPLICA_URLS`: Comma-separated read replica URLs (default: none)
+    /// - `DATABASE_REPLICA_HEALTH_INTERVAL`: Seconds between replica health checks (default: 5)
     pub fn from_env() -> Result<Self, std::io::Error> {
         let url = std::env::var("DATABASE_URL").map_err(|_| {
             std::io::Error::new(
@@ -129,6 +162,15 @@ impl DatabaseConfig {
             .and_then(|v| v.parse().ok())
             .unwrap_or(cfg!(debug_assertions));
 
+        let replica_urls = std::env::var("DATABASE_REPLICA_URLS")
+            .map(|v| parse_replica_urls(&v))
+            .unwrap_or_default();
+
+        let replica_health_interval = std::env::var("DATABASE_REPLICA_HEALTH_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+
         Ok(Self {
             url,
             max_connections,
@@ -136,6 +178,8 @@ impl DatabaseConfig {
             connect_timeout,
             idle_timeout,
             sqlx_logging,
+            replica_urls,
+            replica_health_interval,
         })
     }
 
@@ -169,9 +213,34 @@ impl DatabaseConfig {
         self
     }
 
-    /// Connects to the database and returns a connection pool.
+    /// Adds a read replica.
+    pub fn replica(mut self, url: impl Into<String>) -> Self {
+        self.replica_urls.push(url.into());
+        self
+    }
+
+    /// Sets the number of seconds between replica health checks.
+    pub fn replica_health_interval(mut self, secs: u64) -> Self {
+        self.replica_health_interval = secs;
+        self
+    }
+
+    /// Connects to the primary database and returns a connection pool.
     pub async fn connect(&self) -> Result<DatabaseConnection, DbError> {
-        let mut opts = ConnectOptions::new(&self.url);
+        self.connect_to(&self.url).await
+    }
+
+    /// Connects to every read replica, using the same pool settings as the primary.
+    pub async fn connect_replicas(&self) -> Result<Replicas, DbError> {
+        let mut conns = Vec::with_capacity(self.replica_urls.len());
+        for url in &self.replica_urls {
+            conns.push(self.connect_to(url).await?);
+        }
+        Ok(Replicas::new(conns))
+    }
+
+    async fn connect_to(&self, url: &str) -> Result<DatabaseConnection, DbError> {
+        let mut opts = ConnectOptions::new(url);
         opts.max_connections(self.max_connections)
             .min_connections(self.min_connections)
             .connect_timeout(Duration::from_secs(self.connect_timeout))
@@ -182,6 +251,129 @@ impl DatabaseConfig {
     }
 }
 
+fn parse_replica_urls(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The read replicas shared by every [`Db`] extractor.
+///
+/// Picks replicas round-robin, skipping ones that failed their last health
+/// check. Cloning is cheap; clones share the connections and health state.
+#[derive(Debug, Clone)]
+pub struct Replicas {
+    inner: Arc<ReplicaPool>,
+}
+
+#[derive(Debug)]
+struct ReplicaPool {
+    replicas: Vec<Replica>,
+    next: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Replica {
+    conn: DatabaseConnection,
+    healthy: AtomicBool,
+}
+
+impl Replicas {
+    /// Wraps replica connections, all initially considered healthy.
+    pub fn new(conns: Vec<DatabaseConnection>) -> Self {
+        let replicas = conns
+            .into_iter()
+            .map(|conn| Replica {
+                conn,
+                healthy: AtomicBool::new(true),
+            })
+            .collect();
+        Self {
+            inner: Arc::new(ReplicaPool {
+                replicas,
+                next: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// The number of replicas, healthy or not.
+    pub fn len(&self) -> usize {
+        self.inner.replicas.len()
+    }
+
+    /// Whether there are no replicas.
+    pub fn is_empty(&self) -> bool {
+        self.inner.replicas.is_empty()
+    }
+
+    /// The number of replicas that passed their last health check.
+    pub fn healthy(&self) -> usize {
+        self.inner
+            .replicas
+            .iter()
+            .filter(|r| r.healthy.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// The next healthy replica in round-robin order, if any.
+    pub fn pick(&self) -> Option<&DatabaseConnection> {
+        self.pick_index()
+            .map(|index| &self.inner.replicas[index].conn)
+    }
+
+    fn pick_index(&self) -> Option<usize> {
+        let replicas = &self.inner.replicas;
+        if replicas.is_empty() {
+            return None;
+        }
+
+        let start = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        (0..replicas.len())
+            .map(|offset| (start + offset) % replicas.len())
+            .find(|&index| replicas[index].healthy.load(Ordering::Relaxed))
+    }
+
+    /// Marks the replica at `index` healthy or unhealthy.
+    pub fn set_healthy(&self, index: usize, healthy: bool) {
+        if let Some(replica) = self.inner.replicas.get(index) {
+            replica.healthy.store(healthy, Ordering::Relaxed);
+        }
+    }
+
+    /// Pings every replica and records which ones answered.
+    pub async fn check_health(&self) {
+        for (index, replica) in self.inner.replicas.iter().enumerate() {
+            let healthy = replica.conn.ping().await.is_ok();
+            let was_healthy = replica.healthy.swap(healthy, Ordering::Relaxed);
+            if was_healthy && !healthy {
+                tracing::warn!(replica = index, "read replica failed health check");
+            } else if !was_healthy && healthy {
+                tracing::info!(replica = index, "read replica recovered");
+            }
+        }
+    }
+
+    /// Runs [`check_health`](Self::check_health) every `interval` until
+    /// every clone of this set is dropped.
+    pub(crate) fn spawn_health_checks(&self, interval: Duration) {
+        let pool = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(inner) = pool.upgrade() else {
+                    break;
+                };
+                Replicas { inner }.check_health().await;
+            }
+        });
+    }
+}
+
 /// Wrapper around SeaORM's `DbErr` for Rapina error integration.
 ///
 /// This type implements `IntoApiError`, allowing you to use `?` directly
@@ -239,6 +431,8 @@ impl From<sea_orm::DbErr> for DbError {
 /// Database connection extractor for handlers.
 ///
 /// Use this to access the database connection pool in your handlers.
+/// Use [`read`](Self::read) for queries that can be served by a replica and
+/// [`write`](Self::write) for anything that changes data.
 ///
 /// # Example
 ///
@@ -249,36 +443,133 @@ impl From<sea_orm::DbErr> for DbError {
 /// #[get("/users")]
 /// async fn list_users(db: Db) -> Result<Json<Vec<User>>> {
 ///     let users = UserEntity::find()
-///         .all(db.conn())
+///         .all(db.read())
 ///         .await?;
 ///     Ok(Json(users.into_iter().map(Into::into).collect()))
 /// }
 /// ```
 #[derive(Debug, Clone)]
-pub struct Db(DatabaseConnection);
+pub struct Db {
+    primary: DatabaseConnection,
+    replicas: Option<Replicas>,
+    /// Per-request copies of the replica connections that time queries,
+    /// in the same order as `replicas`.
+    timed_replicas: Option<Vec<DatabaseConnection>>,
+    primary_pin: Arc<AtomicBool>,
+}
 
 impl Db {
     /// Creates a new Db wrapper around a connection.
     pub fn new(conn: DatabaseConnection) -> Self {
-        Self(conn)
+        Self {
+            primary: conn,
+            replicas: None,
+            timed_replicas: None,
+            primary_pin: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Routes [`read`](Self::read) to these replicas.
+    pub fn with_replicas(mut self, replicas: Replicas) -> Self {
+        self.replicas = Some(replicas);
+        self.timed_replicas = None;
+        self
+    }
+
+    /// Shares the request's pin-to-primary flag, so writes through any `Db`
+    /// in the request affect the others.
+    pub(crate) fn with_primary_pin(mut self, pin: Arc<AtomicBool>) -> Self {
+        self.primary_pin = pin;
+        self
+    }
+
+    /// Times every query made through this `Db` and records it on `ctx`
+    /// for the slow request log. Queries taking at least `threshold` are
+    /// also logged on their own.
+    pub(crate) fn with_query_timing(
+        mut self,
+        ctx: RequestContext,
+        threshold: Option<Duration>,
+    ) -> Self {
+        let callback = Arc::new(move |info: &sea_orm::metric::Info<'_>| {
+            let timing = QueryTiming::new(&info.statement.sql, info.elapsed, info.failed);
+            if let Some(threshold) = threshold
+                && info.elapsed >= threshold
+            {
+                tracing::warn!(
+                    duration_ms = info.elapsed.as_millis() as u64,
+                    threshold_ms = threshold.as_millis() as u64,
+                    trace_id = %ctx.trace_id,
+                    failed = info.failed,
+                    sql = %timing.sql,
+                    "slow query"
+                );
+            }
+            ctx.record_query(timing);
+        });
+
+        let timed = |conn: &DatabaseConnection| {
+            let mut conn = conn.clone();
+            let callback = callback.clone();
+            conn.set_metric_callback(move |info| callback(info));
+            conn
+        };
+
+        self.primary = timed(&self.primary);
+        self.timed_replicas = self.replicas.as_ref().map(|replicas| {
+            replicas
+                .inner
+                .replicas
+                .iter()
+                .map(|r| timed(&r.conn))
+                .collect()
+        });
+        self
     }
 
-    /// Returns a reference to the underlying database connection.
+    /// Returns a reference to the primary database connection.
     ///
     /// Use this when calling SeaORM methods that take `&DatabaseConnection`.
+    /// Prefer [`read`](Self::read) or [`write`](Self::write) when the app
+    /// has read replicas.
     pub fn conn(&self) -> &DatabaseConnection {
-        &self.0
+        &self.primary
     }
 
-    /// Consumes the wrapper and returns the underlying connection.
+    /// Returns a connection for reads.
+    ///
+    /// This is the next healthy replica, or the primary when there are no
+    /// healthy replicas or this request has already written.
+    pub fn read(&self) -> &DatabaseConnection {
+        if self.primary_pin.load(Ordering::Relaxed) {
+            return &self.primary;
+        }
+        let Some(replicas) = &self.replicas else {
+            return &self.primary;
+        };
+        match (replicas.pick_index(), &self.timed_replicas) {
+            (Some(index), Some(timed)) => &timed[index],
+            (Some(index), None) => &replicas.inner.replicas[index].conn,
+            (None, _) => &self.primary,
+        }
+    }
+
+    /// Returns the primary connection for writes, and pins the rest of the
+    /// request's reads to the primary.
+    pub fn write(&self) -> &DatabaseConnection {
+        self.primary_pin.store(true, Ordering::Relaxed);
+        &self.primary
+    }
+
+    /// Consumes the wrapper and returns the primary connection.
     pub fn into_inner(self) -> DatabaseConnection {
-        self.0
+        self.primary
     }
 }
 
 impl AsRef<DatabaseConnection> for Db {
     fn as_ref(&self) -> &DatabaseConnection {
-        &self.0
+        &self.primary
     }
 }
 
@@ -286,10 +577,456 @@ impl std::ops::Deref for Db {
     type Target = DatabaseConnection;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.primary
+    }
+}
+
+pub use rapina_macros::sql;
+
+/// A raw SQL query with bound values, built by the [`sql!`] macro.
+///
+/// Placeholders are rendered for the connection's backend when the query
+/// runs: `$1`, `$2`, ... on Postgres and `?` on MySQL and SQLite. Rows are
+/// read into any type implementing SeaORM's `FromQueryResult`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use rapina::database::{Db, DbError, sql};
+/// use rapina::sea_orm::FromQueryResult;
+///
+/// #[derive(FromQueryResult, Serialize, JsonSchema)]
+/// struct AuthorStats {
+///     author_id: i32,
+///     posts: i64,
+/// }
+///
+/// #[get("/reports/authors")]
+/// async fn author_report(db: Db, query: Query<ReportParams>) -> Result<Json<Vec<AuthorStats>>> {
+///     let stats = sql!(
+///         "SELECT author_id, count(*) AS posts FROM posts
+///          WHERE status = :status GROUP BY author_id ORDER BY posts DESC",
+///         status = query.into_inner().status,
+///     )
+///     .all(db.read())
+///     .await?;
+///     Ok(Json(stats))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sql {
+    parts: &'static [&'static str],
+    /// For each placeholder, the index of its value.
+    order: &'static [usize],
+    values: Vec<sea_orm::Value>,
+}
+
+impl Sql {
+    #[doc(hidden)]
+    pub fn from_parts(
+        parts: &'static [&'static str],
+        order: &'static [usize],
+        values: Vec<sea_orm::Value>,
+    ) -> Self {
+        debug_assert_eq!(parts.len(), order.len() + 1);
+        Self {
+            parts,
+            order,
+            values,
+        }
+    }
+
+    /// The SQL text with placeholders for `backend`.
+    pub fn sql(&self, backend: DbBackend) -> String {
+        let mut sql = String::from(self.parts[0]);
+        for (index, part) in self.order.iter().zip(&self.parts[1..]) {
+            match backend {
+                DbBackend::Postgres => {
+                    sql.push('$');
+                    sql.push_str(&(index + 1).to_string());
+                }
+                _ => sql.push('?'),
+            }
+            sql.push_str(part);
+        }
+        sql
+    }
+
+    /// The bound values, in the order `backend` expects them.
+    ///
+    /// Postgres refers to each value by number, so a named parameter used
+    /// twice is bound once; the other backends bind one value per `?`.
+    pub fn values(&self, backend: DbBackend) -> Vec<sea_orm::Value> {
+        match backend {
+            DbBackend::Postgres => self.values.clone(),
+            _ => self.order.iter().map(|&i| self.values[i].clone()).collect(),
+        }
+    }
+
+    /// Builds the SeaORM statement for `backend`.
+    pub fn statement(&self, backend: DbBackend) -> Statement {
+        Statement::from_sql_and_values(backend, self.sql(backend), self.values(backend))
+    }
+
+    /// Runs the query and reads every row.
+    pub async fn all<T, C>(self, db: &C) -> Result<Vec<T>, DbError>
+    where
+        T: FromQueryResult,
+        C: ConnectionTrait,
+    {
+        let statement = self.statement(db.get_database_backend());
+        Ok(T::find_by_statement(statement).all(db).await?)
+    }
+
+    /// Runs the query and reads the first row, if any.
+    pub async fn one<T, C>(self, db: &C) -> Result<Option<T>, DbError>
+    where
+        T: FromQueryResult,
+        C: ConnectionTrait,
+    {
+        let statement = self.statement(db.get_database_backend());
+        Ok(T::find_by_statement(statement).one(db).await?)
+    }
+
+    /// Runs a statement that returns no rows, such as `INSERT`, `UPDATE`
+    /// or `DELETE`.
+    pub async fn execute<C: ConnectionTrait>(self, db: &C) -> Result<ExecResult, DbError> {
+        let statement = self.statement(db.get_database_backend());
+        Ok(db.execute(statement).await?)
+    }
+}
+
+/// Loads the value of a [`CachedQuery`].
+type QueryLoader<T> = Box<
+    dyn Fn(DatabaseConnection) -> Pin<Box<dyn Future<Output = Result<T, sea_orm::DbErr>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// The result of a query, cached for a fixed time.
+///
+/// Meant for small reference tables that are read constantly and rarely
+/// change, such as countries or plans. Results are shared by every request
+/// until the TTL runs out; concurrent requests that find the value expired
+/// wait for a single refresh instead of all querying the database.
+///
+/// Register it with [`Rapina::cached_query`](crate::app::Rapina::cached_query)
+/// and read it with `State<CachedQuery<T>>`. Entities listed in
+/// [`invalidate_on`](Self::invalidate_on) drop the cached value whenever an
+/// event for them is published through [`Events`](crate::events::Events), as
+/// scaffolded handlers do after every mutation.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use rapina::database::{CachedQuery, Db};
+/// use rapina::prelude::*;
+///
+/// #[get("/plans")]
+/// async fn list_plans(db: Db, plans: State<CachedQuery<Vec<plan::Model>>>) -> Result<Json<Vec<plan::Model>>> {
+///     Ok(Json(plans.get(db.read()).await?))
+/// }
+///
+/// let plans = CachedQuery::new(
+///     |db| async move { Plan::find().all(&db).await },
+///     Duration::from_secs(300),
+/// )
+/// .invalidate_on("plan");
+///
+/// Rapina::new()
+///     .with_database(config).await?
+///     .cached_query(plans)
+///     .discover()
+///     .listen("127.0.0.1:3000")
+///     .await
+/// ```
+pub struct CachedQuery<T> {
+    inner: Arc<CachedQueryInner<T>>,
+    entities: Vec<String>,
+}
+
+struct CachedQueryInner<T> {
+    load: QueryLoader<T>,
+    ttl: Duration,
+    /// Held while reading or refreshing, so only one refresh runs at a time.
+    value: tokio::sync::Mutex<Option<CachedValue<T>>>,
+    /// Bumped by every invalidation; values loaded before it are stale.
+    generation: AtomicU64,
+    /// When the current value was loaded and for which generation.
+    loaded: Mutex<Option<(Instant, u64)>>,
+    hits: AtomicU64,
+    loads: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+struct CachedValue<T> {
+    value: T,
+    loaded_at: Instant,
+    generation: u64,
+}
+
+/// Counters and staleness of a [`CachedQuery`], from [`CachedQuery::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachedQueryStats {
+    /// Reads answered from the cache.
+    pub hits: u64,
+    /// Reads that ran the query.
+    pub loads: u64,
+    /// Times the cached value was invalidated.
+    pub invalidations: u64,
+    /// How long ago the current value was loaded, if it ever was.
+    pub age: Option<Duration>,
+    /// Whether the next read will run the query.
+    pub stale: bool,
+}
+
+impl<T: Clone + Send + 'static> CachedQuery<T> {
+    /// Creates a cache that runs `load` at most once every `ttl`.
+    ///
+    /// `load` is given the connection passed to [`get`](Self::get).
+    pub fn new<F, Fut>(load: F, ttl: Duration) -> Self
+    where
+        F: Fn(DatabaseConnection) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, sea_orm::DbErr>> + Send + 'static,
+    {
+        Self {
+            inner: Arc::new(CachedQueryInner {
+                load: Box::new(move |conn| Box::pin(load(conn))),
+                ttl,
+                value: tokio::sync::Mutex::new(None),
+                generation: AtomicU64::new(0),
+                loaded: Mutex::new(None),
+                hits: AtomicU64::new(0),
+                loads: AtomicU64::new(0),
+                invalidations: AtomicU64::new(0),
+            }),
+            entities: Vec::new(),
+        }
+    }
+
+    /// Drops the cached value whenever an event for `entity` is published.
+    ///
+    /// `entity` is the snake_case name used in events, e.g. `plan`.
+    pub fn invalidate_on(mut self, entity: impl Into<String>) -> Self {
+        self.entities.push(entity.into());
+        self
+    }
+
+    /// Returns the cached value, running the query on `conn` if it expired.
+    ///
+    /// A failed query is returned as an error and nothing is cached.
+    pub async fn get(&self, conn: &DatabaseConnection) -> Result<T, Error> {
+        let inner = &self.inner;
+        let mut slot = inner.value.lock().await;
+        let generation = inner.generation.load(Ordering::Acquire);
+        if let Some(cached) = slot.as_ref()
+            && cached.generation == generation
+            && cached.loaded_at.elapsed() < inner.ttl
+        {
+            inner.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(cached.value.clone());
+        }
+
+        let value = (inner.load)(conn.clone()).await.map_err(DbError)?;
+        inner.loads.fetch_add(1, Ordering::Relaxed);
+        let loaded_at = Instant::now();
+        *inner.loaded.lock().unwrap() = Some((loaded_at, generation));
+        *slot = Some(CachedValue {
+            value: value.clone(),
+            loaded_at,
+            generation,
+        });
+        Ok(value)
+    }
+}
+
+impl<T> CachedQuery<T> {
+    /// Drops the cached value, so the next read runs the query.
+    pub fn invalidate(&self) {
+        self.inner.invalidate();
+    }
+
+    /// Returns hit and load counts and how stale the cached value is.
+    pub fn stats(&self) -> CachedQueryStats {
+        let inner = &self.inner;
+        let loaded = *inner.loaded.lock().unwrap();
+        let age = loaded.map(|(loaded_at, _)| loaded_at.elapsed());
+        let stale = match loaded {
+            Some((loaded_at, generation)) => {
+                generation != inner.generation.load(Ordering::Acquire)
+                    || loaded_at.elapsed() >= inner.ttl
+            }
+            None => true,
+        };
+        CachedQueryStats {
+            hits: inner.hits.load(Ordering::Relaxed),
+            loads: inner.loads.load(Ordering::Relaxed),
+            invalidations: inner.invalidations.load(Ordering::Relaxed),
+            age,
+            stale,
+        }
+    }
+}
+
+impl<T> CachedQueryInner<T> {
+    fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<T> Clone for CachedQuery<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            entities: self.entities.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for CachedQuery<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedQuery")
+            .field("ttl", &self.inner.ttl)
+            .field("entities", &self.entities)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A cache that can be invalidated without knowing its value type.
+trait Invalidate: Send + Sync {
+    fn invalidate(&self);
+}
+
+impl<T: Send> Invalidate for CachedQueryInner<T> {
+    fn invalidate(&self) {
+        CachedQueryInner::invalidate(self);
+    }
+}
+
+/// An entity name and a query its events invalidate.
+type QueryCacheEntry = (String, Arc<dyn Invalidate>);
+
+/// Every [`CachedQuery`] registered with the app, indexed by the entities
+/// that invalidate it.
+#[derive(Clone, Default)]
+pub(crate) struct QueryCaches {
+    entries: Arc<Mutex<Vec<QueryCacheEntry>>>,
+}
+
+impl QueryCaches {
+    pub(crate) fn register<T: Send + 'static>(&self, query: &CachedQuery<T>) {
+        let mut entries = self.entries.lock().unwrap();
+        for entity in &query.entities {
+            entries.push((entity.clone(), query.inner.clone()));
+        }
+    }
+
+    /// Invalidates the queries listed under `entity`.
+    pub(crate) fn invalidate_entity(&self, entity: &str) {
+        for (name, query) in self.entries.lock().unwrap().iter() {
+            if name == entity {
+                query.invalidate();
+            }
+        }
+    }
+}
+
+/// A function declared with `#[before_save(path)]` or `#[after_save(path)]`
+/// on a `schema!` entity.
+///
+/// Implemented for every `async fn(model, insert: bool) -> Result<model, DbErr>`.
+/// `before_save` hooks take and return the entity's `ActiveModel`,
+/// `after_save` hooks its `Model`; `insert` is `true` for inserts and
+/// `false` for updates.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use rapina::prelude::*;
+/// use rapina::sea_orm::{DbErr, Set};
+///
+/// schema! {
+///     #[before_save(normalize_email)]
+///     User {
+///         email: String,
+///     }
+/// }
+///
+/// async fn normalize_email(
+///     mut user: user::ActiveModel,
+///     _insert: bool,
+/// ) -> Result<user::ActiveModel, DbErr> {
+///     if let Some(email) = user.email.try_as_ref() {
+///         user.email = Set(email.trim().to_lowercase());
+///     }
+///     Ok(user)
+/// }
+/// ```
+pub trait SaveHook<M>: Send + Sync {
+    /// Runs the hook.
+    fn call(
+        &self,
+        model: M,
+        insert: bool,
+    ) -> impl Future<Output = Result<M, sea_orm::DbErr>> + Send;
+}
+
+impl<F, Fut, M> SaveHook<M> for F
+where
+    F: Fn(M, bool) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<M, sea_orm::DbErr>> + Send,
+{
+    fn call(
+        &self,
+        model: M,
+        insert: bool,
+    ) -> impl Future<Output = Result<M, sea_orm::DbErr>> + Send {
+        self(model, insert)
+    }
+}
+
+/// A function declared with `#[before_delete(path)]` or
+/// `#[after_delete(path)]` on a `schema!` entity.
+///
+/// Implemented for every
+/// `async fn(model: ActiveModel) -> Result<ActiveModel, DbErr>`.
+pub trait DeleteHook<M>: Send + Sync {
+    /// Runs the hook.
+    fn call(&self, model: M) -> impl Future<Output = Result<M, sea_orm::DbErr>> + Send;
+}
+
+impl<F, Fut, M> DeleteHook<M> for F
+where
+    F: Fn(M) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<M, sea_orm::DbErr>> + Send,
+{
+    fn call(&self, model: M) -> impl Future<Output = Result<M, sea_orm::DbErr>> + Send {
+        self(model)
     }
 }
 
+/// Emits the builders of a `#[fixtures]` entity in `schema!`: always with
+/// the `fixtures` feature, so a project's integration tests can use them,
+/// and only under `cfg(test)` otherwise.
+#[doc(hidden)]
+#[cfg(feature = "fixtures")]
+#[macro_export]
+macro_rules! __schema_fixtures {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "fixtures"))]
+#[macro_export]
+macro_rules! __schema_fixtures {
+    ($($item:item)*) => {
+        $(#[cfg(test)] $item)*
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,6 +1037,30 @@ mod tests {
         assert_eq!(config.url, "postgres://localhost/test");
         assert_eq!(config.max_connections, 10);
         assert_eq!(config.min_connections, 1);
+        assert!(config.replica_urls.is_empty());
+    }
+
+    #[test]
+    fn test_database_config_replicas() {
+        let config = DatabaseConfig::new("postgres://primary/test")
+            .replica("postgres://replica1/test")
+            .replica("postgres://replica2/test")
+            .replica_health_interval(10);
+
+        assert_eq!(
+            config.replica_urls,
+            vec!["postgres://replica1/test", "postgres://replica2/test"]
+        );
+        assert_eq!(config.replica_health_interval, 10);
+    }
+
+    #[test]
+    fn test_parse_replica_urls() {
+        assert_eq!(
+            parse_replica_urls(" postgres://a/db, ,postgres://b/db,"),
+            vec!["postgres://a/db", "postgres://b/db"]
+        );
+        assert!(parse_replica_urls("").is_empty());
     }
 
     #[test]
@@ -333,4 +1094,106 @@ mod tests {
         assert_eq!(api_err.status, 500);
         assert_eq!(api_err.message, "something went wrong");
     }
+
+    fn counting_query(ttl: Duration) -> (CachedQuery<usize>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let query = CachedQuery::new(
+            move |_| {
+                let counter = counter.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    Ok(counter.fetch_add(1, Ordering::SeqCst) + 1)
+                }
+            },
+            ttl,
+        );
+        (query, calls)
+    }
+
+    #[tokio::test]
+    async fn test_cached_query_deduplicates_concurrent_loads() {
+        let (query, calls) = counting_query(Duration::from_secs(60));
+
+        let reads: Vec<_> = (0..20)
+            .map(|_| {
+                let query = query.clone();
+                tokio::spawn(async move { query.get(&DatabaseConnection::Disconnected).await })
+            })
+            .collect();
+        for read in reads {
+            assert_eq!(read.await.unwrap().unwrap(), 1);
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let stats = query.stats();
+        assert_eq!(stats.loads, 1);
+        assert_eq!(stats.hits, 19);
+        assert!(!stats.stale);
+        assert!(stats.age.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cached_query_invalidate() {
+        let (query, calls) = counting_query(Duration::from_secs(60));
+        let conn = DatabaseConnection::Disconnected;
+
+        assert!(query.stats().stale);
+        assert_eq!(query.get(&conn).await.unwrap(), 1);
+        query.invalidate();
+        assert!(query.stats().stale);
+        assert_eq!(query.get(&conn).await.unwrap(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(query.stats().invalidations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cached_query_expires() {
+        let (query, calls) = counting_query(Duration::ZERO);
+        let conn = DatabaseConnection::Disconnected;
+
+        query.get(&conn).await.unwrap();
+        query.get(&conn).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_query_does_not_cache_errors() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let query = CachedQuery::new(
+            move |_| {
+                let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt == 0 {
+                        Err(sea_orm::DbErr::Custom("connection reset".to_string()))
+                    } else {
+                        Ok("loaded")
+                    }
+                }
+            },
+            Duration::from_secs(60),
+        );
+        let conn = DatabaseConnection::Disconnected;
+
+        let err = query.get(&conn).await.unwrap_err();
+        assert_eq!(err.status, 500);
+        assert_eq!(query.get(&conn).await.unwrap(), "loaded");
+        assert_eq!(query.stats().loads, 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_caches_invalidate_entity() {
+        let (plans, _) = counting_query(Duration::from_secs(60));
+        let plans = plans.invalidate_on("plan");
+        let (countries, _) = counting_query(Duration::from_secs(60));
+        let countries = countries.invalidate_on("country");
+        let caches = QueryCaches::default();
+        caches.register(&plans);
+        caches.register(&countries);
+
+        caches.invalidate_entity("plan");
+        assert_eq!(plans.stats().invalidations, 1);
+        assert_eq!(countries.stats().invalidations, 0);
+    }
 }
@@ -7,8 +7,16 @@
 //!
 //! The `#[public]` attribute emits a [`PublicMarker`] so the discovery loop
 //! can mark routes as public without manual `.public_route()` calls.
+//!
+//! Since handlers from every module are collected, two of them can end up
+//! annotated with the same method and path. Discovery reports these as
+//! [`RouteConflict`]s at startup instead of silently letting one shadow the other.
+
+use std::collections::HashMap;
+use std::fmt;
 
 use crate::error::ErrorVariant;
+use crate::introspection::RouteInfo;
 use crate::router::Router;
 
 /// Metadata about a route handler, collected at link time via `inventory`.
@@ -44,3 +52,171 @@ pub struct PublicMarker {
 }
 
 inventory::collect!(PublicMarker);
+
+/// A method and path claimed by more than one handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteConflict {
+    /// HTTP method shared by the handlers
+    pub method: String,
+    /// Path pattern of the first handler registered
+    pub path: String,
+    /// Names of the conflicting handlers, in registration order
+    pub handlers: Vec<String>,
+}
+
+impl fmt::Display for RouteConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let handlers: Vec<String> = self.handlers.iter().map(|h| format!("`{}`", h)).collect();
+        write!(
+            f,
+            "{} {} is handled by more than one handler: {}",
+            self.method,
+            self.path,
+            handlers.join(", ")
+        )
+    }
+}
+
+/// Method, normalized path, `consumes`, `produces` and header conditions
+/// of a route.
+type RouteKey<'a> = (
+    &'a str,
+    String,
+    Option<&'a str>,
+    Option<&'a str>,
+    &'a [String],
+);
+
+/// Finds routes registered by different handlers for the same method and path.
+///
+/// Paths that only differ in parameter names, such as `/users/:id` and
+/// `/users/:user_id`, conflict as well, since only the first can ever match.
+/// The same handler registered twice is not a conflict, and neither are
+/// overloads with different `consumes` or `produces` media types or header
+/// conditions.
+pub fn find_conflicts(routes: &[RouteInfo]) -> Vec<RouteConflict> {
+    let mut conflicts: Vec<RouteConflict> = Vec::new();
+    let mut seen: HashMap<RouteKey, usize> = HashMap::new();
+
+    for route in routes {
+        let key = (
+            route.method.as_str(),
+            normalize_path(&route.path),
+            route.consumes.as_deref(),
+            route.produces.as_deref(),
+            route.header_conditions.as_slice(),
+        );
+        match seen.get(&key) {
+            Some(&idx) => {
+                let handlers = &mut conflicts[idx].handlers;
+                if !handlers.contains(&route.handler_name) {
+                    handlers.push(route.handler_name.clone());
+                }
+            }
+            None => {
+                seen.insert(key, conflicts.len());
+                conflicts.push(RouteConflict {
+                    method: route.method.clone(),
+                    path: route.path.clone(),
+                    handlers: vec![route.handler_name.clone()],
+                });
+            }
+        }
+    }
+
+    conflicts.retain(|c| c.handlers.len() > 1);
+    conflicts
+}
+
+/// Replaces parameter names with a placeholder so `/users/:id` and
+/// `/users/:user_id` compare equal.
+pub(crate) fn normalize_path(path: &str) -> String {
+    path.split('/')
+        .map(|seg| if seg.starts_with(':') { ":" } else { seg })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(method: &str, path: &str, handler: &str) -> RouteInfo {
+        RouteInfo::new(method, path, handler, None, Vec::new())
+    }
+
+    #[test]
+    fn test_no_conflicts() {
+        let routes = vec![
+            route("GET", "/users", "list_users"),
+            route("POST", "/users", "create_user"),
+            route("GET", "/users/:id", "get_user"),
+            route("GET", "/users/current", "current_user"),
+        ];
+        assert!(find_conflicts(&routes).is_empty());
+    }
+
+    #[test]
+    fn test_same_path_different_handlers() {
+        let routes = vec![
+            route("GET", "/users", "list_users"),
+            route("GET", "/users", "all_users"),
+        ];
+        let conflicts = find_conflicts(&routes);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].handlers, vec!["list_users", "all_users"]);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "GET /users is handled by more than one handler: `list_users`, `all_users`"
+        );
+    }
+
+    #[test]
+    fn test_param_names_do_not_matter() {
+        let routes = vec![
+            route("GET", "/users/:id", "get_user"),
+            route("GET", "/users/:user_id", "show_user"),
+        ];
+        let conflicts = find_conflicts(&routes);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "/users/:id");
+    }
+
+    #[test]
+    fn test_overloads_by_media_type_do_not_conflict() {
+        let routes = vec![
+            route("POST", "/login", "login_json").with_consumes("application/json"),
+            route("POST", "/login", "login_form")
+                .with_consumes("application/x-www-form-urlencoded"),
+        ];
+        assert!(find_conflicts(&routes).is_empty());
+
+        let routes = vec![
+            route("POST", "/login", "login_json").with_consumes("application/json"),
+            route("POST", "/login", "sign_in").with_consumes("application/json"),
+        ];
+        let conflicts = find_conflicts(&routes);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].handlers, vec!["login_json", "sign_in"]);
+    }
+
+    #[test]
+    fn test_overloads_by_version_do_not_conflict() {
+        let routes = vec![
+            route("GET", "/users", "list_users_v1")
+                .with_header_conditions(vec!["accept: version 1".into()]),
+            route("GET", "/users", "list_users_v2")
+                .with_header_conditions(vec!["accept: version 2".into()]),
+        ];
+        assert!(find_conflicts(&routes).is_empty());
+    }
+
+    #[test]
+    fn test_same_handler_twice_is_not_a_conflict() {
+        let routes = vec![
+            route("GET", "/users", "list_users"),
+            route("GET", "/users", "list_users"),
+        ];
+        assert!(find_conflicts(&routes).is_empty());
+    }
+}
@@ -0,0 +1,319 @@
+//! Draining connections ahead of a shutdown.
+//!
+//! Orchestrators like Kubernetes run a `preStop` hook before sending
+//! `SIGTERM`. Enable draining with
+//! [`Rapina::with_draining`](crate::app::Rapina::with_draining) and the hook
+//! can tell the server to stop taking new work, either by sending `SIGUSR1`
+//! or by calling the [`start_drain`] endpoint:
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//! use rapina::drain::start_drain;
+//! use rapina::prelude::*;
+//! use rapina::server::Listener;
+//!
+//! let admin = Router::new().post_named("/drain", "start_drain", start_drain);
+//!
+//! Rapina::new()
+//!     .with_draining(Duration::from_secs(20))
+//!     .router(router)
+//!     .listener(Listener::new("127.0.0.1:9090", admin))
+//!     .listen("0.0.0.0:8080")
+//!     .await
+//! ```
+//!
+//! While draining, `GET /__rapina/ready` answers 503 so the pod is taken out
+//! of rotation, and every response carries `Connection: close` so clients
+//! reconnect elsewhere. New connections are still accepted. Once the grace
+//! period has passed, the listeners close and the server shuts down as if it
+//! had received `SIGTERM`. With introspection enabled, `GET /__rapina/drain`
+//! reports the current state.
+//!
+//! The drain endpoint is not registered on the app's router, since anyone
+//! who can reach it can take the pod out of service; mount it on an admin
+//! [`Listener`](crate::server::Listener) instead.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::header::{CONNECTION, HeaderValue};
+use http::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use serde::Serialize;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::context::RequestContext;
+use crate::extract::PathParams;
+use crate::middleware::{BoxFuture, Middleware, Next};
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+/// Whether the server is draining, shared through application state.
+///
+/// Registered by [`Rapina::with_draining`](crate::app::Rapina::with_draining).
+/// Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct Drain {
+    grace_period: Duration,
+    started: Arc<watch::Sender<Option<Instant>>>,
+}
+
+impl Drain {
+    /// Creates a drain that shuts the server down `grace_period` after it
+    /// starts.
+    pub fn new(grace_period: Duration) -> Self {
+        let (started, _) = watch::channel(None);
+        Self {
+            grace_period,
+            started: Arc::new(started),
+        }
+    }
+
+    /// Starts draining. Returns `false` if the server was already draining,
+    /// in which case the original deadline is kept.
+    pub fn start(&self) -> bool {
+        let started = self.started.send_if_modified(|started| {
+            if started.is_some() {
+                return false;
+            }
+            *started = Some(Instant::now());
+            true
+        });
+        if started {
+            tracing::info!(
+                "Draining: readiness checks fail and connections close after each response; shutting down in {:?}",
+                self.grace_period
+            );
+        }
+        started
+    }
+
+    /// Whether draining has started.
+    pub fn is_draining(&self) -> bool {
+        self.started.borrow().is_some()
+    }
+
+    /// How long after draining starts the server shuts down.
+    pub fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+
+    /// The current state, as reported by the introspection endpoint.
+    pub fn status(&self) -> DrainStatus {
+        let started = *self.started.borrow();
+        DrainStatus {
+            draining: started.is_some(),
+            grace_period_secs: self.grace_period.as_secs_f64(),
+            draining_for_secs: started.map(|started| started.elapsed().as_secs_f64()),
+        }
+    }
+
+    /// Resolves once the grace period has passed since draining started.
+    pub(crate) async fn elapsed(&self) {
+        let mut started = self.started.subscribe();
+        let Ok(started) = started.wait_for(Option::is_some).await.map(|s| *s) else {
+            return std::future::pending().await;
+        };
+        if let Some(started) = started {
+            tokio::time::sleep_until(started + self.grace_period).await;
+        }
+    }
+}
+
+/// Draining state reported by `GET /__rapina/drain`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DrainStatus {
+    /// Whether draining has started.
+    pub draining: bool,
+    /// Seconds between the start of draining and shutdown.
+    pub grace_period_secs: f64,
+    /// Seconds since draining started, while draining.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draining_for_secs: Option<f64>,
+}
+
+/// Disables keep-alive while the server is draining.
+///
+/// Added by [`Rapina::with_draining`](crate::app::Rapina::with_draining) to
+/// the app and every extra listener.
+#[derive(Debug, Clone)]
+pub(crate) struct DrainMiddleware {
+    drain: Drain,
+}
+
+impl DrainMiddleware {
+    pub(crate) fn new(drain: Drain) -> Self {
+        Self { drain }
+    }
+}
+
+impl Middleware for DrainMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            let mut response = next.run(req).await;
+            if self.drain.is_draining() {
+                response
+                    .headers_mut()
+                    .insert(CONNECTION, HeaderValue::from_static("close"));
+            }
+            response
+        })
+    }
+}
+
+fn json_response(status: StatusCode, body: &impl Serialize) -> Response<BoxBody> {
+    let json = serde_json::to_vec(body).unwrap_or_default();
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(BoxBody::new(json))
+        .unwrap()
+}
+
+/// Handler that starts draining.
+///
+/// Answers 202 with the draining state. Mount it on an admin listener, see
+/// the [module docs](self).
+pub async fn start_drain(
+    _req: Request<Incoming>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    match state.get::<Drain>() {
+        Some(drain) => {
+            drain.start();
+            json_response(StatusCode::ACCEPTED, &drain.status())
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Handler for the readiness endpoint.
+///
+/// Answers 200 until draining starts, then 503.
+pub async fn readiness(
+    _req: Request<Incoming>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    let draining = state.get::<Drain>().is_some_and(Drain::is_draining);
+    if draining {
+        json_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &serde_json::json!({ "status": "draining" }),
+        )
+    } else {
+        json_response(StatusCode::OK, &serde_json::json!({ "status": "ready" }))
+    }
+}
+
+/// Handler for the draining introspection endpoint.
+///
+/// Returns the [`DrainStatus`] as JSON.
+pub async fn drain_status(
+    _req: Request<Incoming>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    match state.get::<Drain>() {
+        Some(drain) => json_response(StatusCode::OK, &drain.status()),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Rapina;
+    use crate::router::Router;
+    use crate::testing::TestClient;
+
+    #[test]
+    fn test_start_is_idempotent() {
+        let drain = Drain::new(Duration::from_secs(5));
+        assert!(!drain.is_draining());
+        assert!(drain.status().draining_for_secs.is_none());
+
+        assert!(drain.start());
+        assert!(!drain.start());
+        assert!(drain.is_draining());
+        assert!(drain.status().draining_for_secs.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_elapsed_waits_for_grace_period() {
+        tokio::time::pause();
+        let drain = Drain::new(Duration::from_millis(200));
+        let waiter = drain.clone();
+        let elapsed = tokio::spawn(async move { waiter.elapsed().await });
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        tokio::task::yield_now().await;
+        assert!(!elapsed.is_finished(), "nothing happens before draining");
+
+        drain.start();
+        let started = Instant::now();
+        tokio::time::advance(Duration::from_millis(199)).await;
+        tokio::task::yield_now().await;
+        assert!(!elapsed.is_finished());
+
+        elapsed.await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_draining_flips_readiness_and_disables_keep_alive() {
+        let router = Router::new().route(http::Method::GET, "/", |_, _, _| async { "ok" });
+        let app = Rapina::new()
+            .with_introspection(true)
+            .with_draining(Duration::from_secs(30))
+            .router(router)
+            .test_routes(Router::new().post_named("/__test/drain", "start_drain", start_drain));
+        let client = TestClient::new(app).await;
+
+        let ready = client.get("/__rapina/ready").send().await;
+        assert_eq!(ready.status(), StatusCode::OK);
+        let response = client.get("/").send().await;
+        assert!(response.headers().get(CONNECTION).is_none());
+        let status = client.get("/__rapina/drain").send().await;
+        assert_eq!(status.json::<serde_json::Value>()["draining"], false);
+
+        let started = client.post("/__test/drain").send().await;
+        assert_eq!(started.status(), StatusCode::ACCEPTED);
+        assert_eq!(started.json::<serde_json::Value>()["draining"], true);
+
+        let ready = client.get("/__rapina/ready").send().await;
+        assert_eq!(ready.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(ready.json::<serde_json::Value>()["status"], "draining");
+        let response = client.get("/").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(CONNECTION).unwrap(), "close");
+        let status = client.get("/__rapina/drain").send().await;
+        let status = status.json::<serde_json::Value>();
+        assert_eq!(status["draining"], true);
+        assert_eq!(status["grace_period_secs"], 30.0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_status_requires_introspection() {
+        let app = Rapina::new()
+            .with_introspection(false)
+            .with_draining(Duration::from_secs(30));
+        let client = TestClient::new(app).await;
+
+        assert_eq!(
+            client.get("/__rapina/drain").send().await.status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            client.get("/__rapina/ready").send().await.status(),
+            StatusCode::OK
+        );
+    }
+}
@@ -25,24 +25,51 @@
 //! }
 //! ```
 
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::fmt;
 
 use crate::response::{BoxBody, IntoResponse};
-use bytes::Bytes;
-use http_body_util::Full;
 
-/// The JSON structure returned for error responses.
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
+/// The JSON body of every error response produced by Rapina.
+///
+/// Published in the OpenAPI spec as the `ApiErrorBody` component, which
+/// every documented error response references, so clients can generate a
+/// type for it.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ApiErrorBody {
     /// The error details.
     pub error: ErrorDetail,
-    /// Unique identifier for request tracing.
+    /// Unique identifier of the request, for correlating logs.
     pub trace_id: String,
 }
 
+/// The previous name of [`ApiErrorBody`].
+pub type ErrorResponse = ApiErrorBody;
+
+impl ApiErrorBody {
+    /// Name of the schema under `components/schemas` in the OpenAPI spec.
+    pub const SCHEMA_NAME: &'static str = "ApiErrorBody";
+
+    /// Returns the JSON Schema published for this body.
+    pub fn schema() -> serde_json::Value {
+        let mut schema =
+            serde_json::to_value(schemars::schema_for!(ApiErrorBody)).unwrap_or_default();
+        if let Some(schema) = schema.as_object_mut() {
+            schema.remove("$schema");
+        }
+        schema
+    }
+
+    /// Returns the OpenAPI `$ref` pointing at [`schema`](Self::schema).
+    pub fn schema_ref() -> String {
+        format!("#/components/schemas/{}", Self::SCHEMA_NAME)
+    }
+}
+
 /// Detailed error information in the response body.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(inline)]
 pub struct ErrorDetail {
     /// Machine-readable error code (e.g., "NOT_FOUND", "BAD_REQUEST").
     pub code: String,
@@ -128,11 +155,31 @@ impl Error {
         Self::new(404, "NOT_FOUND", message)
     }
 
+    /// Creates a 406 Not Acceptable error.
+    pub fn not_acceptable(message: impl Into<String>) -> Self {
+        Self::new(406, "NOT_ACCEPTABLE", message)
+    }
+
+    /// Creates a 408 Request Timeout error.
+    pub fn request_timeout(message: impl Into<String>) -> Self {
+        Self::new(408, "REQUEST_TIMEOUT", message)
+    }
+
     /// Creates a 409 Conflict error.
     pub fn conflict(message: impl Into<String>) -> Self {
         Self::new(409, "CONFLICT", message)
     }
 
+    /// Creates a 413 Payload Too Large error.
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self::new(413, "PAYLOAD_TOO_LARGE", message)
+    }
+
+    /// Creates a 415 Unsupported Media Type error.
+    pub fn unsupported_media_type(message: impl Into<String>) -> Self {
+        Self::new(415, "UNSUPPORTED_MEDIA_TYPE", message)
+    }
+
     /// Creates a 422 Validation Error.
     pub fn validation(message: impl Into<String>) -> Self {
         Self::new(422, "VALIDATION_ERROR", message)
@@ -148,9 +195,9 @@ impl Error {
         Self::new(500, "INTERNAL_ERROR", message)
     }
 
-    /// Converts this error to an ErrorResponse with the given trace ID.
-    pub fn to_response(&self, trace_id: String) -> ErrorResponse {
-        ErrorResponse {
+    /// Converts this error to an [`ApiErrorBody`] with the given trace ID.
+    pub fn to_response(&self, trace_id: String) -> ApiErrorBody {
+        ApiErrorBody {
             error: ErrorDetail {
                 code: self.code.clone(),
                 message: self.message.clone(),
@@ -218,6 +265,9 @@ impl<T: IntoApiError> From<T> for Error {
 }
 
 /// Metadata about an error variant for OpenAPI documentation.
+///
+/// Each variant is documented as a response whose body is an
+/// [`ApiErrorBody`].
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ErrorVariant {
     /// HTTP status code (e.g., 404, 409).
@@ -287,7 +337,7 @@ impl IntoResponse for Error {
         http::Response::builder()
             .status(self.status)
             .header("content-type", "application/json")
-            .body(Full::new(Bytes::from(body)))
+            .body(BoxBody::new(body))
             .unwrap()
     }
 }
@@ -423,6 +473,34 @@ mod tests {
         assert_eq!(err.code, "CONFLICT");
     }
 
+    #[test]
+    fn test_error_request_timeout() {
+        let err = Error::request_timeout("body read timed out");
+        assert_eq!(err.status, 408);
+        assert_eq!(err.code, "REQUEST_TIMEOUT");
+    }
+
+    #[test]
+    fn test_error_not_acceptable() {
+        let err = Error::not_acceptable("expected text/html");
+        assert_eq!(err.status, 406);
+        assert_eq!(err.code, "NOT_ACCEPTABLE");
+    }
+
+    #[test]
+    fn test_error_payload_too_large() {
+        let err = Error::payload_too_large("body too large");
+        assert_eq!(err.status, 413);
+        assert_eq!(err.code, "PAYLOAD_TOO_LARGE");
+    }
+
+    #[test]
+    fn test_error_unsupported_media_type() {
+        let err = Error::unsupported_media_type("expected application/json");
+        assert_eq!(err.status, 415);
+        assert_eq!(err.code, "UNSUPPORTED_MEDIA_TYPE");
+    }
+
     #[test]
     fn test_error_validation() {
         let err = Error::validation("invalid data");
@@ -505,6 +583,22 @@ mod tests {
         assert_eq!(trace_id.len(), 36); // UUID format
     }
 
+    #[test]
+    fn test_api_error_body_schema() {
+        let schema = ApiErrorBody::schema();
+
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(schema["title"], "ApiErrorBody");
+        assert_eq!(schema["required"], serde_json::json!(["error", "trace_id"]));
+        let error = &schema["properties"]["error"];
+        assert_eq!(error["required"], serde_json::json!(["code", "message"]));
+        assert!(error["properties"].get("details").is_some());
+        assert_eq!(
+            ApiErrorBody::schema_ref(),
+            "#/components/schemas/ApiErrorBody"
+        );
+    }
+
     #[test]
     fn test_error_response_skips_none_details() {
         let err = Error::bad_request("test");
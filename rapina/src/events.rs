@@ -0,0 +1,401 @@
+//! Domain events published after entity mutations.
+//!
+//! Handlers publish an [`Event`] (e.g. `user.created`) through the [`Events`]
+//! extractor whenever they create, update, or delete data. Where events go is
+//! decided by the [`EventSink`] registered in application state, so handlers
+//! stay the same whether events end up in the logs, a message broker, or a
+//! transactional outbox table.
+//!
+//! If no sink is registered, events are logged with [`TracingSink`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use rapina::events::{Event, Events, EventSink};
+//!
+//! #[post("/users")]
+//! async fn create_user(db: Db, events: Events, body: Json<CreateUser>) -> Result<Json<Model>> {
+//!     let txn = db.conn().begin().await.map_err(DbError)?;
+//!     let user = ActiveModel { /* ... */ }.insert(&txn).await.map_err(DbError)?;
+//!
+//!     let event = Event::created("user", user.id);
+//!     events.before_commit(&txn, &event).await?;
+//!     txn.commit().await.map_err(DbError)?;
+//!     events.after_commit(event).await;
+//!
+//!     Ok(Json(user))
+//! }
+//!
+//! Rapina::new()
+//!     .state(Events::new(KafkaSink::new(brokers)))
+//!     .discover()
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::cache::ResponseCache;
+#[cfg(feature = "database")]
+use crate::database::QueryCaches;
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::state::AppState;
+
+/// Boxed future returned by [`EventSink`] methods.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The kind of mutation an event describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl EventAction {
+    /// Returns the lowercase action name used in event names.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventAction::Created => "created",
+            EventAction::Updated => "updated",
+            EventAction::Deleted => "deleted",
+        }
+    }
+}
+
+/// An entity mutation event, such as `user.created`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Event {
+    /// Entity name in snake_case (e.g. `blog_post`)
+    pub entity: String,
+    /// Primary key of the affected row, rendered as a string
+    pub id: String,
+    /// What happened to the entity
+    pub action: EventAction,
+    /// When the mutation happened
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Event {
+    /// Creates a new event timestamped with the current time.
+    pub fn new(entity: impl Into<String>, id: impl ToString, action: EventAction) -> Self {
+        Self {
+            entity: entity.into(),
+            id: id.to_string(),
+            action,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Creates an `<entity>.created` event.
+    pub fn created(entity: impl Into<String>, id: impl ToString) -> Self {
+        Self::new(entity, id, EventAction::Created)
+    }
+
+    /// Creates an `<entity>.updated` event.
+    pub fn updated(entity: impl Into<String>, id: impl ToString) -> Self {
+        Self::new(entity, id, EventAction::Updated)
+    }
+
+    /// Creates an `<entity>.deleted` event.
+    pub fn deleted(entity: impl Into<String>, id: impl ToString) -> Self {
+        Self::new(entity, id, EventAction::Deleted)
+    }
+
+    /// Returns the event name, e.g. `user.created`.
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.entity, self.action.as_str())
+    }
+}
+
+/// Destination for published events.
+///
+/// Implement this trait to forward events to a broker such as Kafka or NATS,
+/// then register it with `Rapina::new().state(Events::new(sink))`.
+///
+/// Sinks that write to the database can opt into the transactional outbox
+/// pattern by returning `true` from [`is_transactional`](Self::is_transactional)
+/// and implementing `publish_in_transaction`. Their events are then written
+/// inside the same transaction as the mutation, so they are only visible if
+/// the mutation commits.
+pub trait EventSink: Send + Sync + 'static {
+    /// Publishes an event after the mutation has been committed.
+    fn publish(&self, event: Event) -> BoxFuture<'_, Result<(), Error>>;
+
+    /// Whether this sink publishes inside the mutation's transaction.
+    fn is_transactional(&self) -> bool {
+        false
+    }
+
+    /// Publishes an event inside the mutation's transaction.
+    ///
+    /// Only called when [`is_transactional`](Self::is_transactional) returns
+    /// `true`. The default implementation falls back to [`publish`](Self::publish).
+    #[cfg(feature = "database")]
+    fn publish_in_transaction<'a>(
+        &'a self,
+        txn: &'a sea_orm::DatabaseTransaction,
+        event: Event,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        let _ = txn;
+        self.publish(event)
+    }
+}
+
+/// Sink that logs every event with `tracing`. Used when no sink is registered.
+#[derive(Debug, Clone, Default)]
+pub struct TracingSink;
+
+impl EventSink for TracingSink {
+    fn publish(&self, event: Event) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            tracing::info!(
+                event = %event.name(),
+                entity = %event.entity,
+                id = %event.id,
+                timestamp = %event.timestamp.to_rfc3339(),
+                "event published"
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Sink that keeps published events in memory.
+///
+/// Useful in tests to assert which events a handler published.
+///
+/// # Example
+///
+/// ```
+/// use rapina::events::{Event, Events, InMemorySink};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let sink = InMemorySink::new();
+/// let events = Events::new(sink.clone());
+///
+/// events.after_commit(Event::created("user", 1)).await;
+///
+/// assert_eq!(sink.events()[0].name(), "user.created");
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySink {
+    events: Arc<Mutex<Vec<Event>>>,
+}
+
+impl InMemorySink {
+    /// Creates an empty in-memory sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all events published so far, in order.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Removes all recorded events.
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+}
+
+impl EventSink for InMemorySink {
+    fn publish(&self, event: Event) -> BoxFuture<'_, Result<(), Error>> {
+        self.events.lock().unwrap().push(event);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Handle for publishing events, available as an extractor and as state.
+///
+/// Extracts the `Events` registered in application state, or a
+/// [`TracingSink`]-backed instance if none was registered.
+///
+/// When extracted, the handle also drops cached responses of handlers that
+/// listed the event's entity in `invalidate_on` (see [`crate::cache`]), and
+/// cached queries that listed it in
+/// [`CachedQuery::invalidate_on`](crate::database::CachedQuery::invalidate_on).
+#[derive(Clone)]
+pub struct Events {
+    sink: Arc<dyn EventSink>,
+    cache: Option<ResponseCache>,
+    #[cfg(feature = "database")]
+    queries: Option<QueryCaches>,
+}
+
+impl Events {
+    /// Creates an `Events` handle backed by the given sink.
+    pub fn new<S: EventSink>(sink: S) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            cache: None,
+            #[cfg(feature = "database")]
+            queries: None,
+        }
+    }
+
+    /// Publishes an event inside the transaction if the sink is transactional.
+    ///
+    /// Call this before committing. Errors should abort the request so the
+    /// transaction is rolled back together with the outbox entry. For
+    /// non-transactional sinks this is a no-op.
+    #[cfg(feature = "database")]
+    pub async fn before_commit(
+        &self,
+        txn: &sea_orm::DatabaseTransaction,
+        event: &Event,
+    ) -> Result<(), Error> {
+        if self.sink.is_transactional() {
+            self.sink.publish_in_transaction(txn, event.clone()).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Publishes an event after the mutation was committed.
+    ///
+    /// Does nothing for transactional sinks, which already published in
+    /// [`before_commit`](Self::before_commit). Failures are logged rather
+    /// than returned, since the mutation itself already succeeded.
+    ///
+    /// Cached responses and queries that depend on the event's entity are
+    /// invalidated either way.
+    pub async fn after_commit(&self, event: Event) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_entity(&event.entity);
+        }
+        #[cfg(feature = "database")]
+        if let Some(queries) = &self.queries {
+            queries.invalidate_entity(&event.entity);
+        }
+        if self.sink.is_transactional() {
+            return;
+        }
+        let name = event.name();
+        if let Err(e) = self.sink.publish(event).await {
+            tracing::error!(event = %name, error = %e.message, "failed to publish event");
+        }
+    }
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new(TracingSink)
+    }
+}
+
+impl std::fmt::Debug for Events {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Events").finish_non_exhaustive()
+    }
+}
+
+impl FromRequestParts for Events {
+    async fn from_request_parts(
+        _parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let mut events = state.get::<Events>().cloned().unwrap_or_default();
+        events.cache = state.get::<ResponseCache>().cloned();
+        #[cfg(feature = "database")]
+        {
+            events.queries = state.get::<QueryCaches>().cloned();
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{TestRequest, empty_params, empty_state, state_with};
+
+    #[test]
+    fn test_event_name() {
+        assert_eq!(Event::created("user", 1).name(), "user.created");
+        assert_eq!(Event::updated("blog_post", 2).name(), "blog_post.updated");
+        assert_eq!(Event::deleted("user", 3).name(), "user.deleted");
+    }
+
+    #[test]
+    fn test_event_serializes() {
+        let event = Event::created("user", 42);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["entity"], "user");
+        assert_eq!(json["id"], "42");
+        assert_eq!(json["action"], "created");
+        assert!(json["timestamp"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_sink_records_events() {
+        let sink = InMemorySink::new();
+        let events = Events::new(sink.clone());
+
+        events.after_commit(Event::created("user", 1)).await;
+        events.after_commit(Event::deleted("user", 1)).await;
+
+        let recorded = sink.events();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].name(), "user.created");
+        assert_eq!(recorded[1].name(), "user.deleted");
+
+        sink.clear();
+        assert!(sink.events().is_empty());
+    }
+
+    struct TransactionalSink(InMemorySink);
+
+    impl EventSink for TransactionalSink {
+        fn publish(&self, event: Event) -> BoxFuture<'_, Result<(), Error>> {
+            self.0.publish(event)
+        }
+
+        fn is_transactional(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_after_commit_skips_transactional_sinks() {
+        let sink = InMemorySink::new();
+        let events = Events::new(TransactionalSink(sink.clone()));
+
+        events.after_commit(Event::created("user", 1)).await;
+
+        assert!(sink.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_extractor_uses_registered_sink() {
+        let sink = InMemorySink::new();
+        let state = state_with(Events::new(sink.clone()));
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let events = Events::from_request_parts(&parts, &empty_params(), &state)
+            .await
+            .unwrap();
+        events.after_commit(Event::updated("user", 7)).await;
+
+        assert_eq!(sink.events()[0].name(), "user.updated");
+    }
+
+    #[tokio::test]
+    async fn test_extractor_defaults_to_tracing_sink() {
+        let state = empty_state();
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let events = Events::from_request_parts(&parts, &empty_params(), &state).await;
+        assert!(events.is_ok());
+    }
+}
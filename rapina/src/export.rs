@@ -0,0 +1,258 @@
+//! Exporting database query results as JSON.
+//!
+//! [`stream_json`] and [`stream_ndjson`] read rows through SeaORM's
+//! `stream()` cursor instead of `all()`, so an export never holds every
+//! model in memory at once: each row is serialized as soon as the cursor
+//! yields it and dropped right after.
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::database::Db;
+//! use rapina::export::stream_ndjson;
+//! use rapina::response::BoxBody;
+//!
+//! #[get("/todos/export")]
+//! async fn export_todos(db: Db) -> Result<http::Response<BoxBody>> {
+//!     stream_ndjson(Todo::find(), db.read()).await
+//! }
+//! ```
+//!
+//! # Errors
+//!
+//! A query that fails to start returns the usual error response. When the
+//! cursor fails halfway, [`stream_json`] discards the partial array and
+//! returns the error, while [`stream_ndjson`] keeps the rows read so far and
+//! ends with an `{"error": {"code": ..., "message": ...}}` record, which
+//! NDJSON consumers can tell apart from a row.
+//!
+//! The body is assembled while rows arrive and sent once the cursor is
+//! exhausted, since responses are built on a complete [`BoxBody`].
+
+use futures_util::StreamExt;
+use sea_orm::{DatabaseConnection, EntityTrait, Select};
+use serde::Serialize;
+
+use crate::database::DbError;
+use crate::error::{Error, IntoApiError};
+use crate::response::BoxBody;
+
+/// Rows serialized between yields back to the runtime, so a large export
+/// doesn't monopolize its worker thread.
+const BATCH_ROWS: usize = 500;
+
+/// Streams the rows of `select` as a JSON array.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// #[get("/users/export")]
+/// async fn export_users(db: Db) -> Result<http::Response<BoxBody>> {
+///     stream_json(User::find().order_by_asc(user::Column::Id), db.read()).await
+/// }
+/// ```
+pub async fn stream_json<E>(
+    select: Select<E>,
+    conn: &DatabaseConnection,
+) -> Result<http::Response<BoxBody>, Error>
+where
+    E: EntityTrait,
+    E::Model: Serialize + Send,
+{
+    stream_rows(select, conn, Format::Json).await
+}
+
+/// Streams the rows of `select` as newline-delimited JSON, one row per line.
+pub async fn stream_ndjson<E>(
+    select: Select<E>,
+    conn: &DatabaseConnection,
+) -> Result<http::Response<BoxBody>, Error>
+where
+    E: EntityTrait,
+    E::Model: Serialize + Send,
+{
+    stream_rows(select, conn, Format::NdJson).await
+}
+
+async fn stream_rows<E>(
+    select: Select<E>,
+    conn: &DatabaseConnection,
+    format: Format,
+) -> Result<http::Response<BoxBody>, Error>
+where
+    E: EntityTrait,
+    E::Model: Serialize + Send,
+{
+    let rows = select.stream(conn).await.map_err(DbError)?;
+    let mut rows = std::pin::pin!(rows);
+    let mut writer = RowWriter::new(format);
+
+    while let Some(row) = rows.next().await {
+        let result = match row {
+            Ok(model) => writer.row(&model).map_err(|err| {
+                tracing::error!(error = %err, "failed to serialize exported row");
+                Error::internal("failed to serialize row")
+            }),
+            Err(err) => Err(DbError(err).into_api_error()),
+        };
+
+        if let Err(error) = result {
+            match format {
+                Format::Json => return Err(error),
+                Format::NdJson => {
+                    writer.error(&error);
+                    break;
+                }
+            }
+        }
+
+        if writer.rows % BATCH_ROWS == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    Ok(writer.finish())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    NdJson,
+}
+
+impl Format {
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::NdJson => "application/x-ndjson",
+        }
+    }
+}
+
+/// Serializes rows into a JSON array or NDJSON body.
+struct RowWriter {
+    format: Format,
+    buf: Vec<u8>,
+    rows: usize,
+}
+
+impl RowWriter {
+    fn new(format: Format) -> Self {
+        let buf = match format {
+            Format::Json => b"[".to_vec(),
+            Format::NdJson => Vec::new(),
+        };
+        Self {
+            format,
+            buf,
+            rows: 0,
+        }
+    }
+
+    fn row<T: Serialize>(&mut self, row: &T) -> serde_json::Result<()> {
+        let start = self.buf.len();
+        if self.format == Format::Json && self.rows > 0 {
+            self.buf.push(b',');
+        }
+        if let Err(err) = serde_json::to_writer(&mut self.buf, row) {
+            self.buf.truncate(start);
+            return Err(err);
+        }
+        if self.format == Format::NdJson {
+            self.buf.push(b'\n');
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Appends the trailing error record of an NDJSON body.
+    fn error(&mut self, error: &Error) {
+        let record = serde_json::json!({
+            "error": { "code": error.code, "message": error.message }
+        });
+        serde_json::to_writer(&mut self.buf, &record).expect("error record serializes");
+        self.buf.push(b'\n');
+    }
+
+    fn finish(mut self) -> http::Response<BoxBody> {
+        if self.format == Format::Json {
+            self.buf.push(b']');
+        }
+        http::Response::builder()
+            .status(http::StatusCode::OK)
+            .header("content-type", self.format.content_type())
+            .body(BoxBody::new(self.buf))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+    use serde::ser::Error as _;
+
+    #[derive(Serialize)]
+    struct Row {
+        id: i32,
+        name: &'static str,
+    }
+
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(S::Error::custom("nope"))
+        }
+    }
+
+    async fn body(response: http::Response<BoxBody>) -> String {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_array() {
+        let mut writer = RowWriter::new(Format::Json);
+        writer.row(&Row { id: 1, name: "a" }).unwrap();
+        writer.row(&Row { id: 2, name: "b" }).unwrap();
+
+        let response = writer.finish();
+        assert_eq!(response.headers()["content-type"], "application/json");
+        assert_eq!(
+            body(response).await,
+            r#"[{"id":1,"name":"a"},{"id":2,"name":"b"}]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_json_array() {
+        assert_eq!(body(RowWriter::new(Format::Json).finish()).await, "[]");
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_with_trailing_error() {
+        let mut writer = RowWriter::new(Format::NdJson);
+        writer.row(&Row { id: 1, name: "a" }).unwrap();
+        writer.error(&Error::internal("database query failed"));
+
+        let response = writer.finish();
+        assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+        assert_eq!(
+            body(response).await,
+            "{\"id\":1,\"name\":\"a\"}\n\
+             {\"error\":{\"code\":\"INTERNAL_ERROR\",\"message\":\"database query failed\"}}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_row_leaves_no_partial_output() {
+        let mut writer = RowWriter::new(Format::Json);
+        writer.row(&Row { id: 1, name: "a" }).unwrap();
+        assert!(writer.row(&Unserializable).is_err());
+        assert_eq!(writer.rows, 1);
+
+        assert_eq!(body(writer.finish()).await, r#"[{"id":1,"name":"a"}]"#);
+    }
+}
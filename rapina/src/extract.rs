@@ -3,22 +3,24 @@
 //! Extractors are types that implement [`FromRequest`] or [`FromRequestParts`]
 //! and can be used as handler parameters to automatically parse request data.
 
-use bytes::Bytes;
+pub use bytes::Bytes;
+use bytes::{Buf, BytesMut};
 use http::Request;
 use http_body_util::BodyExt;
 use hyper::body::Incoming;
+pub use rapina_macros::FromRequestParts;
 use serde::de::DeserializeOwned;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use validator::Validate;
 
-use crate::context::RequestContext;
+use crate::context::{MatchedRoute, RequestContext};
 use crate::error::Error;
+use crate::json::{JsonOptions, RenameRule, json_response};
 use crate::response::{BoxBody, IntoResponse};
 use crate::state::AppState;
 
-const JSON_CONTENT_TYPE: &str = "application/json";
 const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
 
 /// Extracts and deserializes JSON request bodies.
@@ -178,9 +180,42 @@ pub struct Cookie<T>(pub T);
 ///     state.into_inner().db_url
 /// }
 /// ```
+///
+/// In route handlers, `State<Option<T>>` resolves to `None` instead of
+/// failing when `T` was never registered:
+///
+/// ```ignore
+/// #[get("/report")]
+/// async fn report(cache: State<Option<ReportCache>>) -> String {
+///     match cache.into_inner() {
+///         Some(cache) => cache.render(),
+///         None => "no cache configured".to_string(),
+///     }
+/// }
+/// ```
 #[derive(Debug)]
 pub struct State<T>(pub T);
 
+/// Extracts several pieces of application state at once.
+///
+/// Takes a tuple of up to eight types, each registered with
+/// [`Rapina::state`](crate::app::Rapina::state). When any are missing, the
+/// error lists all of them rather than just the first.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::prelude::*;
+///
+/// #[get("/checkout")]
+/// async fn checkout(states: States<(Payments, Inventory, Mailer)>) -> Result<String> {
+///     let (payments, inventory, mailer) = states.into_inner();
+///     // ...
+/// }
+/// ```
+#[derive(Debug)]
+pub struct States<T>(pub T);
+
 /// Provides access to the request context.
 ///
 /// Contains the `trace_id` and request start time for logging and tracing.
@@ -226,6 +261,97 @@ pub struct Context(pub RequestContext);
 #[derive(Debug)]
 pub struct Validated<T>(pub T);
 
+/// Extracts the raw request body together with its parsed JSON value.
+///
+/// Useful for webhooks that sign the exact bytes they send: the signature
+/// is checked against [`bytes`](RawJson::bytes) while the handler works
+/// with the deserialized [`value`](RawJson::value). Like [`Json`], it
+/// returns 400 Bad Request if parsing fails.
+///
+/// The body can be read as [`Bytes`] or `String` as well. Those extractors
+/// don't check the Content-Type; `String` only requires valid UTF-8.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::prelude::*;
+///
+/// #[post("/webhooks/github")]
+/// async fn github_webhook(headers: Headers, body: RawJson<PushEvent>) -> Result<StatusCode> {
+///     verify_signature(headers.get("x-hub-signature-256"), body.bytes())?;
+///     let event = body.into_inner();
+///     // Handle the verified event...
+///     Ok(StatusCode::NO_CONTENT)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RawJson<T> {
+    bytes: Bytes,
+    value: T,
+}
+
+/// Extracts the request head: method, URI, version, headers and extensions.
+///
+/// For the rare handler that needs more than the other extractors expose,
+/// such as the original URI or a value a middleware put in the extensions.
+/// It's a copy of the head, so it can be combined with any other extractor,
+/// including one that reads the body. Extensions inserted by middleware that
+/// ran before the handler, like the [`RequestContext`], are included.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::prelude::*;
+/// use rapina::extract::Parts;
+///
+/// #[post("/proxy")]
+/// async fn proxy(parts: Parts, body: Bytes) -> Result<String> {
+///     let ctx = parts.extensions.get::<RequestContext>().unwrap();
+///     Ok(format!("{} {} ({})", parts.method, parts.uri, ctx.trace_id))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Parts(pub http::request::Parts);
+
+/// Hands the whole request, body included, to the handler untouched.
+///
+/// An escape hatch for adapters and handlers that process the request
+/// themselves. Since it takes the body, it can't be combined with another
+/// body extractor such as [`Json`] or [`Bytes`]; the route macros reject
+/// that at compile time. Extractors that only read the head still work
+/// alongside it, and extensions inserted by middleware, like the
+/// [`RequestContext`], are still on the request.
+///
+/// Body limits from [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware)
+/// are only enforced by the buffering extractors, so a handler reading the
+/// body itself has to bound it.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::prelude::*;
+/// use rapina::extract::RawRequest;
+///
+/// #[post("/upload")]
+/// async fn upload(req: RawRequest) -> Result<StatusCode> {
+///     let mut body = req.into_inner().into_body();
+///     // Stream the body frame by frame...
+///     Ok(StatusCode::ACCEPTED)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RawRequest(pub Request<Incoming>);
+
+/// Maximum body size for buffering extractors, set by
+/// [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BodyLimit(pub(crate) usize);
+
+/// Point in time by which buffering extractors must have read the whole body,
+/// set by [`BodyLimitMiddleware::read_timeout`](crate::middleware::BodyLimitMiddleware::read_timeout).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BodyDeadline(pub(crate) tokio::time::Instant);
+
 /// Type alias for path parameters extracted from the URL.
 pub type PathParams = HashMap<String, String>;
 
@@ -247,6 +373,10 @@ pub trait FromRequest: Sized {
 /// Implement this trait for extractors that don't need the request body,
 /// such as path parameters, query strings, or headers.
 /// Multiple parts-only extractors can be used in a single handler.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be extracted from the request parts",
+    note = "`State<T>` and `States<(A, B, ..)>` need every type to be `Clone + Send + Sync + 'static`, and `States` takes at most 8 types"
+)]
 pub trait FromRequestParts: Sized + Send {
     /// Extract the value from request parts.
     fn from_request_parts(
@@ -296,6 +426,28 @@ impl Headers {
     }
 }
 
+impl Parts {
+    /// Consumes the extractor and returns the inner request parts.
+    pub fn into_inner(self) -> http::request::Parts {
+        self.0
+    }
+}
+
+impl std::ops::Deref for Parts {
+    type Target = http::request::Parts;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl RawRequest {
+    /// Consumes the extractor and returns the request.
+    pub fn into_inner(self) -> Request<Incoming> {
+        self.0
+    }
+}
+
 impl<T> Cookie<T> {
     /// Consumes the extractor and returns the inner value.
     pub fn into_inner(self) -> T {
@@ -310,6 +462,23 @@ impl<T> State<T> {
     }
 }
 
+impl<T: Clone + Send + Sync + 'static> State<Option<T>> {
+    /// Resolves `T` from the application state, or `None` when it was never
+    /// registered.
+    ///
+    /// Route handlers call this for `State<Option<T>>` parameters.
+    pub fn optional(state: &AppState) -> Self {
+        State(state.get::<T>().cloned())
+    }
+}
+
+impl<T> States<T> {
+    /// Consumes the extractor and returns the tuple of values.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
 impl Context {
     /// Consumes the extractor and returns the inner RequestContext.
     pub fn into_inner(self) -> RequestContext {
@@ -334,34 +503,150 @@ impl<T> Validated<T> {
     }
 }
 
-impl<T: DeserializeOwned + Send> FromRequest for Json<T> {
+impl<T> RawJson<T> {
+    /// Returns the body exactly as it was received.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Returns the deserialized value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Consumes the extractor and returns the deserialized value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Consumes the extractor and returns both the raw body and the value.
+    pub fn into_parts(self) -> (Bytes, T) {
+        (self.bytes, self.value)
+    }
+}
+
+/// Buffers the request body chunk by chunk, enforcing the [`BodyLimit`] and
+/// [`BodyDeadline`] if the middleware set them.
+///
+/// The size check runs on every frame, so an oversized body is rejected with
+/// 413 as soon as it crosses the limit instead of after it has been buffered,
+/// whatever the Content-Length header claimed. A body that doesn't finish
+/// arriving before the deadline is rejected with 408.
+pub(crate) async fn read_body_limited<B>(req: Request<B>) -> Result<Bytes, Error>
+where
+    B: hyper::body::Body + Unpin,
+{
+    let limit = req.extensions().get::<BodyLimit>().map(|limit| limit.0);
+    let deadline = req.extensions().get::<BodyDeadline>().map(|d| d.0);
+    let mut body = req.into_body();
+
+    if let Some(max_size) = limit
+        && body.size_hint().lower() > max_size as u64
+    {
+        return Err(Error::payload_too_large("body too large"));
+    }
+
+    let mut buf = BytesMut::new();
+    loop {
+        // timeout_at polls the body first, so a ready frame would slip past
+        // an expired deadline
+        if deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline) {
+            return Err(Error::request_timeout("timed out reading request body"));
+        }
+        let frame = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, body.frame())
+                .await
+                .map_err(|_| Error::request_timeout("timed out reading request body"))?,
+            None => body.frame().await,
+        };
+        let Some(frame) = frame else {
+            break;
+        };
+        let frame = frame.map_err(|_| Error::bad_request("Failed to read request body"))?;
+
+        if let Ok(mut data) = frame.into_data() {
+            if limit.is_some_and(|max_size| buf.len() + data.remaining() > max_size) {
+                return Err(Error::payload_too_large("body too large"));
+            }
+            while data.has_remaining() {
+                let chunk = data.chunk();
+                let len = chunk.len();
+                buf.extend_from_slice(chunk);
+                data.advance(len);
+            }
+        }
+    }
+
+    Ok(buf.freeze())
+}
+
+impl FromRequest for Bytes {
     async fn from_request(
         req: Request<Incoming>,
         _params: &PathParams,
         _state: &Arc<AppState>,
     ) -> Result<Self, Error> {
-        let body = req.into_body();
-        let bytes = body
-            .collect()
-            .await
-            .map_err(|_| Error::bad_request("Failed to read request body"))?
-            .to_bytes();
+        read_body_limited(req).await
+    }
+}
+
+impl FromRequest for String {
+    async fn from_request(
+        req: Request<Incoming>,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let bytes = read_body_limited(req).await?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| Error::bad_request("Request body is not valid UTF-8"))
+    }
+}
 
+impl FromRequest for RawRequest {
+    async fn from_request(
+        req: Request<Incoming>,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        Ok(RawRequest(req))
+    }
+}
+
+impl<T: DeserializeOwned + Send> FromRequest for RawJson<T> {
+    async fn from_request(
+        req: Request<Incoming>,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let bytes = read_body_limited(req).await?;
         let value: T = serde_json::from_slice(&bytes)
             .map_err(|e| Error::bad_request(format!("Invalid JSON in request body: {}", e)))?;
 
+        Ok(RawJson { bytes, value })
+    }
+}
+
+impl<T: DeserializeOwned + Send> FromRequest for Json<T> {
+    async fn from_request(
+        req: Request<Incoming>,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let bytes = read_body_limited(req).await?;
+
+        let value: T = match state.get::<JsonOptions>() {
+            Some(options) if options.rename_all != RenameRule::None => options.from_slice(&bytes),
+            _ => serde_json::from_slice(&bytes),
+        }
+        .map_err(|e| Error::bad_request(format!("Invalid JSON in request body: {}", e)))?;
+
         Ok(Json(value))
     }
 }
 
 impl<T: serde::Serialize> IntoResponse for (http::StatusCode, Json<T>) {
     fn into_response(self) -> http::Response<BoxBody> {
-        let body = serde_json::to_vec(&(self.1).0).unwrap_or_default();
-        http::Response::builder()
-            .status(self.0)
-            .header("content-type", JSON_CONTENT_TYPE)
-            .body(http_body_util::Full::new(Bytes::from(body)))
-            .unwrap()
+        json_response(self.0, &(self.1).0, JsonOptions::current())
     }
 }
 
@@ -393,12 +678,7 @@ impl<T: DeserializeOwned + Send> FromRequest for Form<T> {
             )));
         }
 
-        let body = req.into_body();
-        let bytes = body
-            .collect()
-            .await
-            .map_err(|_| Error::bad_request("Failed to read form data from request body"))?
-            .to_bytes();
+        let bytes = read_body_limited(req).await?;
 
         let value: T = serde_urlencoded::from_bytes(&bytes)
             .map_err(|e| Error::bad_request(format!("Invalid URL-encoded form data: {}", e)))?;
@@ -443,16 +723,64 @@ impl<T: Clone + Send + Sync + 'static> FromRequestParts for State<T> {
         _params: &PathParams,
         state: &Arc<AppState>,
     ) -> Result<Self, Error> {
-        let value = state.get::<T>().ok_or_else(|| {
-            Error::internal(format!(
-                "State not registered for type '{}'. Did you forget to call .state()?",
-                std::any::type_name::<T>()
-            ))
-        })?;
+        let value = state
+            .get::<T>()
+            .ok_or_else(|| missing_state(&[std::any::type_name::<T>()]))?;
         Ok(State(value.clone()))
     }
 }
 
+/// The error for state types that were never registered.
+///
+/// Also logs it, since the 500 response alone hides a setup mistake.
+pub(crate) fn missing_state(types: &[&str]) -> Error {
+    let names: Vec<String> = types.iter().map(|name| format!("'{}'", name)).collect();
+    let noun = if types.len() == 1 { "type" } else { "types" };
+    tracing::error!(
+        types = %names.join(", "),
+        "State not registered. Did you forget to call .state()?"
+    );
+    Error::internal(format!(
+        "State not registered for {} {}. Did you forget to call .state()?",
+        noun,
+        names.join(", ")
+    ))
+}
+
+macro_rules! impl_states {
+    ($($ty:ident),+) => {
+        impl<$($ty: Clone + Send + Sync + 'static),+> FromRequestParts for States<($($ty,)+)> {
+            #[allow(non_snake_case)]
+            async fn from_request_parts(
+                _parts: &http::request::Parts,
+                _params: &PathParams,
+                state: &Arc<AppState>,
+            ) -> Result<Self, Error> {
+                let mut missing = Vec::new();
+                $(
+                    let $ty = state.get::<$ty>();
+                    if $ty.is_none() {
+                        missing.push(std::any::type_name::<$ty>());
+                    }
+                )+
+                if !missing.is_empty() {
+                    return Err(missing_state(&missing));
+                }
+                Ok(States(($($ty.unwrap().clone(),)+)))
+            }
+        }
+    };
+}
+
+impl_states!(A);
+impl_states!(A, B);
+impl_states!(A, B, C);
+impl_states!(A, B, C, D);
+impl_states!(A, B, C, D, E);
+impl_states!(A, B, C, D, E, F);
+impl_states!(A, B, C, D, E, F, G);
+impl_states!(A, B, C, D, E, F, G, H);
+
 impl FromRequestParts for Context {
     async fn from_request_parts(
         parts: &http::request::Parts,
@@ -473,6 +801,36 @@ impl FromRequestParts for Context {
     }
 }
 
+/// Extracts the route the request was dispatched to, e.g. `/users/:id`.
+///
+/// ```ignore
+/// use rapina::prelude::*;
+///
+/// #[get("/users/:id")]
+/// async fn get_user(route: MatchedRoute) -> String {
+///     route.pattern // "/users/:id"
+/// }
+/// ```
+impl FromRequestParts for MatchedRoute {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        parts
+            .extensions
+            .get::<RequestContext>()
+            .and_then(|ctx| ctx.matched_route())
+            .cloned()
+            .ok_or_else(|| {
+                Error::internal(
+                    "No matched route recorded for this request. \
+                     MatchedRoute is only available to requests dispatched by the router.",
+                )
+            })
+    }
+}
+
 impl<T: DeserializeOwned + Send> FromRequestParts for Query<T> {
     async fn from_request_parts(
         parts: &http::request::Parts,
@@ -496,6 +854,16 @@ impl FromRequestParts for Headers {
     }
 }
 
+impl FromRequestParts for Parts {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        Ok(Parts(parts.clone()))
+    }
+}
+
 impl<T: DeserializeOwned + Send> FromRequestParts for Cookie<T> {
     async fn from_request_parts(
         parts: &http::request::Parts,
@@ -534,25 +902,29 @@ impl<T: DeserializeOwned + Send> FromRequestParts for Cookie<T> {
     }
 }
 
-impl<T: FromStr + Send> FromRequestParts for Path<T>
+impl<T: FromStr> Path<T>
 where
     T::Err: std::fmt::Display,
 {
-    async fn from_request_parts(
-        _parts: &http::request::Parts,
-        params: &PathParams,
-        _state: &Arc<AppState>,
-    ) -> Result<Self, Error> {
-        let (param_name, value) = params.iter().next().ok_or_else(|| {
-            Error::bad_request(
-                "Missing path parameter. Ensure your route pattern includes a parameter like /:id",
-            )
+    /// Parses the path parameter called `name`.
+    ///
+    /// The route macros use this for handlers whose route has several
+    /// parameters, so each `Path` argument gets the one it asks for.
+    pub fn from_param(params: &PathParams, name: &str) -> Result<Self, Error> {
+        let value = params.get(name).ok_or_else(|| {
+            Error::internal(format!(
+                "Route has no path parameter '{}'. Check that the handler is registered with the pattern from its route macro",
+                name
+            ))
         })?;
+        Self::parse(name, value)
+    }
 
+    fn parse(name: &str, value: &str) -> Result<Self, Error> {
         let parsed = value.parse::<T>().map_err(|e| {
             Error::bad_request(format!(
                 "Path parameter '{}' must be a valid {}, got '{}': {}",
-                param_name,
+                name,
                 std::any::type_name::<T>(),
                 value,
                 e
@@ -563,6 +935,25 @@ where
     }
 }
 
+impl<T: FromStr + Send> FromRequestParts for Path<T>
+where
+    T::Err: std::fmt::Display,
+{
+    async fn from_request_parts(
+        _parts: &http::request::Parts,
+        params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let (param_name, value) = params.iter().next().ok_or_else(|| {
+            Error::bad_request(
+                "Missing path parameter. Ensure your route pattern includes a parameter like /:id",
+            )
+        })?;
+
+        Self::parse(param_name, value)
+    }
+}
+
 impl<T: FromRequestParts> FromRequest for T {
     async fn from_request(
         req: Request<Incoming>,
@@ -575,6 +966,13 @@ impl<T: FromRequestParts> FromRequest for T {
 }
 
 pub fn extract_path_params(pattern: &str, path: &str) -> Option<PathParams> {
+    match_path(pattern, path, false)
+}
+
+/// Matches `path` against `pattern`, comparing static segments without
+/// regard to ASCII case when `case_insensitive` is set. Parameter values are
+/// returned as they appear in the path.
+pub(crate) fn match_path(pattern: &str, path: &str, case_insensitive: bool) -> Option<PathParams> {
     let pattern_parts: Vec<&str> = pattern.split('/').collect();
     let path_parts: Vec<&str> = path.split('/').collect();
 
@@ -587,7 +985,9 @@ pub fn extract_path_params(pattern: &str, path: &str) -> Option<PathParams> {
     for (pattern_part, path_part) in pattern_parts.iter().zip(path_parts.iter()) {
         if let Some(param_name) = pattern_part.strip_prefix(':') {
             params.insert(param_name.to_string(), path_part.to_string());
-        } else if pattern_part != path_part {
+        } else if pattern_part != path_part
+            && !(case_insensitive && pattern_part.eq_ignore_ascii_case(path_part))
+        {
             return None;
         }
     }
@@ -599,10 +999,11 @@ pub fn extract_path_params(pattern: &str, path: &str) -> Option<PathParams> {
 #[cfg(feature = "database")]
 impl FromRequestParts for crate::database::Db {
     async fn from_request_parts(
-        _parts: &http::request::Parts,
+        parts: &http::request::Parts,
         _params: &PathParams,
         state: &Arc<AppState>,
     ) -> Result<Self, Error> {
+        use crate::database::{Db, Replicas};
         use sea_orm::DatabaseConnection;
 
         let conn = state.get::<DatabaseConnection>().ok_or_else(|| {
@@ -610,7 +1011,110 @@ impl FromRequestParts for crate::database::Db {
                 "Database connection not configured. Did you forget to call .with_database()?",
             )
         })?;
-        Ok(crate::database::Db::new(conn.clone()))
+
+        let mut db = Db::new(conn.clone());
+        if let Some(replicas) = state.get::<Replicas>() {
+            db = db.with_replicas(replicas.clone());
+        }
+        if let Some(ctx) = parts.extensions.get::<RequestContext>() {
+            db = db.with_primary_pin(ctx.primary_pin());
+            if let Some(slow_log) = state.get::<crate::middleware::SlowLogConfig>()
+                && slow_log.is_enabled()
+            {
+                db = db.with_query_timing(ctx.clone(), slow_log.query);
+            }
+        }
+        Ok(db)
+    }
+}
+
+/// Runtime support for `#[derive(FromRequestParts)]`.
+///
+/// The derive expands to calls into this module; it is not meant to be
+/// used directly.
+#[doc(hidden)]
+pub mod derive {
+    use super::*;
+
+    pub fn header<'a>(parts: &'a http::request::Parts, name: &str) -> Option<&'a str> {
+        parts.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn query(parts: &http::request::Parts, name: &str) -> Option<String> {
+        let pairs: Vec<(String, String)> = serde_urlencoded::from_str(parts.uri.query()?).ok()?;
+        pairs
+            .into_iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn path<'a>(params: &'a PathParams, name: &str) -> Option<&'a str> {
+        params.get(name).map(String::as_str)
+    }
+
+    pub fn required<T: FromStr>(
+        source: &str,
+        name: &str,
+        value: Option<&str>,
+        status: u16,
+    ) -> Result<T, Error>
+    where
+        T::Err: std::fmt::Display,
+    {
+        match value {
+            Some(value) => parse(source, name, value, status),
+            None => Err(rejection(status, format!("Missing {} '{}'", source, name))),
+        }
+    }
+
+    pub fn optional<T: FromStr>(
+        source: &str,
+        name: &str,
+        value: Option<&str>,
+        status: u16,
+    ) -> Result<Option<T>, Error>
+    where
+        T::Err: std::fmt::Display,
+    {
+        value
+            .map(|value| parse(source, name, value, status))
+            .transpose()
+    }
+
+    pub fn state<T: Clone + Send + Sync + 'static>(state: &AppState) -> Result<T, Error> {
+        state
+            .get::<T>()
+            .cloned()
+            .ok_or_else(|| missing_state(&[std::any::type_name::<T>()]))
+    }
+
+    fn parse<T: FromStr>(source: &str, name: &str, value: &str, status: u16) -> Result<T, Error>
+    where
+        T::Err: std::fmt::Display,
+    {
+        value.parse::<T>().map_err(|e| {
+            rejection(
+                status,
+                format!(
+                    "Invalid {} '{}': expected {}, got '{}': {}",
+                    source,
+                    name,
+                    std::any::type_name::<T>(),
+                    value,
+                    e
+                ),
+            )
+        })
+    }
+
+    fn rejection(status: u16, message: String) -> Error {
+        match status {
+            401 => Error::unauthorized(message),
+            403 => Error::forbidden(message),
+            404 => Error::not_found(message),
+            422 => Error::validation(message),
+            _ => Error::bad_request(message),
+        }
     }
 }
 
@@ -619,6 +1123,50 @@ mod tests {
     use super::*;
     use crate::test::{TestRequest, empty_params, empty_state, params};
 
+    #[test]
+    fn test_raw_json_accessors() {
+        let raw = RawJson {
+            bytes: Bytes::from_static(br#"{"n": 1}"#),
+            value: 1u32,
+        };
+
+        assert_eq!(raw.bytes().as_ref(), br#"{"n": 1}"#);
+        assert_eq!(*raw.value(), 1);
+
+        let (bytes, value) = raw.into_parts();
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(value, 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_body_limited_within_limit() {
+        let mut req = Request::new(http_body_util::Full::new(Bytes::from_static(b"hello")));
+        req.extensions_mut().insert(BodyLimit(5));
+
+        let body = read_body_limited(req).await.unwrap();
+        assert_eq!(body.as_ref(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_read_body_limited_over_limit() {
+        let mut req = Request::new(http_body_util::Full::new(Bytes::from_static(b"hello")));
+        req.extensions_mut().insert(BodyLimit(4));
+
+        let err = read_body_limited(req).await.unwrap_err();
+        assert_eq!(err.status, 413);
+    }
+
+    #[tokio::test]
+    async fn test_read_body_limited_past_deadline() {
+        let mut req = Request::new(http_body_util::Full::new(Bytes::from_static(b"hello")));
+        req.extensions_mut().insert(BodyDeadline(
+            tokio::time::Instant::now() - std::time::Duration::from_secs(1),
+        ));
+
+        let err = read_body_limited(req).await.unwrap_err();
+        assert_eq!(err.status, 408);
+    }
+
     // Path params extraction tests
     #[test]
     fn test_extract_path_params_exact_match() {
@@ -662,6 +1210,14 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn test_match_path_case_insensitive_static_segments() {
+        assert!(match_path("/users/:id", "/Users/5", false).is_none());
+
+        let params = match_path("/users/:id", "/Users/AbC", true).unwrap();
+        assert_eq!(params.get("id"), Some(&"AbC".to_string()));
+    }
+
     // Query extractor tests
     #[tokio::test]
     async fn test_query_extractor_success() {
@@ -801,6 +1357,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_path_from_named_param() {
+        let date = params(&[("year", "2024"), ("month", "07")]);
+
+        assert_eq!(Path::<u32>::from_param(&date, "year").unwrap().0, 2024);
+        assert_eq!(Path::<u32>::from_param(&date, "month").unwrap().0, 7);
+        assert_eq!(
+            Path::<u32>::from_param(&date, "day").unwrap_err().status,
+            500
+        );
+
+        let invalid = params(&[("year", "soon")]);
+        assert_eq!(
+            Path::<u32>::from_param(&invalid, "year")
+                .unwrap_err()
+                .status,
+            400
+        );
+    }
+
     // Context extractor tests
     #[tokio::test]
     async fn test_context_extractor() {
@@ -854,6 +1430,57 @@ mod tests {
         assert_eq!(result.unwrap_err().status, 500);
     }
 
+    #[tokio::test]
+    async fn test_states_extractor_success() {
+        let state = Arc::new(AppState::new().with(7u32).with("config".to_string()));
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let states = States::<(u32, String)>::from_request_parts(&parts, &empty_params(), &state)
+            .await
+            .unwrap();
+        assert_eq!(states.into_inner(), (7, "config".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_states_extractor_lists_every_missing_type() {
+        #[derive(Clone, Debug)]
+        struct Mailer;
+        #[derive(Clone, Debug)]
+        struct Payments;
+
+        let state = crate::test::state_with(7u32);
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let err =
+            States::<(Mailer, u32, Payments)>::from_request_parts(&parts, &empty_params(), &state)
+                .await
+                .unwrap_err();
+        assert_eq!(err.status, 500);
+        assert!(err.message.starts_with("State not registered for types '"));
+        assert!(err.message.contains("Mailer"));
+        assert!(err.message.contains("Payments"));
+        assert!(!err.message.contains("u32"));
+    }
+
+    #[test]
+    fn test_missing_state_message() {
+        assert_eq!(
+            missing_state(&["app::Config"]).message,
+            "State not registered for type 'app::Config'. Did you forget to call .state()?"
+        );
+        assert_eq!(
+            missing_state(&["app::A", "app::B"]).message,
+            "State not registered for types 'app::A', 'app::B'. Did you forget to call .state()?"
+        );
+    }
+
+    #[test]
+    fn test_optional_state() {
+        let state = AppState::new().with(7u32);
+        assert_eq!(State::<Option<u32>>::optional(&state).into_inner(), Some(7));
+        assert_eq!(State::<Option<String>>::optional(&state).into_inner(), None);
+    }
+
     // into_inner tests
     #[test]
     fn test_json_into_inner() {
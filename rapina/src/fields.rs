@@ -0,0 +1,639 @@
+//! Partial responses with `?fields=`.
+//!
+//! [`FieldSelect<T>`] reads a comma-separated list of field names from the
+//! `fields` query parameter and checks it against the fields `T` serializes.
+//! [`FieldSelect::apply`] wraps a response in [`PartialJson`], which drops
+//! every other field while the value is serialized, so `GET /users/5?fields=id,name`
+//! returns `{"id":5,"name":"Ada"}`.
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::fields::{FieldSelect, PartialJson};
+//!
+//! #[get("/users/:id")]
+//! async fn get_user(id: Path<i32>, fields: FieldSelect<User>) -> Result<PartialJson<User>> {
+//!     let user = find_user(id.into_inner()).await?;
+//!     Ok(fields.apply(user))
+//! }
+//! ```
+//!
+//! # Valid Fields
+//!
+//! The fields a client may select come from `T`'s [`JsonSchema`], as seen
+//! when serializing. Fields marked `#[serde(skip_serializing)]` or
+//! `#[serde(skip)]` are never sent, so they can't be selected either:
+//! naming one, or any field `T` doesn't have, is a 400 error listing the
+//! valid fields. Without `fields`, or with an empty list, the whole value is
+//! returned.
+//!
+//! Selection applies to the top-level fields of a struct, or of each struct
+//! in a top-level sequence, so the same `FieldSelect<User>` works for
+//! `PartialJson<Vec<User>>`. Nested structs are serialized in full.
+
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::Deserialize;
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, Json, PathParams};
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+struct FieldsQuery {
+    fields: Option<String>,
+}
+
+/// Serializable fields per type, computed from its schema on first use.
+fn serialized_fields<T: JsonSchema + 'static>() -> Arc<[String]> {
+    static CACHE: OnceLock<RwLock<HashMap<TypeId, Arc<[String]>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+
+    if let Some(fields) = cache.read().unwrap().get(&TypeId::of::<T>()) {
+        return fields.clone();
+    }
+
+    let schema = schemars::generate::SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    let fields: Arc<[String]> = serde_json::to_value(schema)
+        .ok()
+        .and_then(|schema| {
+            schema
+                .get("properties")
+                .and_then(|properties| properties.as_object())
+                .map(|properties| properties.keys().cloned().collect())
+        })
+        .unwrap_or_default();
+
+    cache
+        .write()
+        .unwrap()
+        .insert(TypeId::of::<T>(), fields.clone());
+    fields
+}
+
+/// Field selection extractor. Reads `?fields=id,name` from the query string.
+///
+/// Returns 400 when a name isn't one of the fields `T` serializes.
+pub struct FieldSelect<T> {
+    fields: Option<Arc<[String]>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> std::fmt::Debug for FieldSelect<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldSelect")
+            .field("fields", &self.fields)
+            .finish()
+    }
+}
+
+impl<T> Clone for FieldSelect<T> {
+    fn clone(&self) -> Self {
+        Self {
+            fields: self.fields.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: JsonSchema + 'static> FieldSelect<T> {
+    /// The field names clients may select, as documented in OpenAPI.
+    pub fn valid_fields() -> Vec<String> {
+        serialized_fields::<T>().to_vec()
+    }
+}
+
+impl<T> FieldSelect<T> {
+    /// The selected fields, or `None` when the whole value is wanted.
+    pub fn fields(&self) -> Option<&[String]> {
+        self.fields.as_deref()
+    }
+
+    /// Whether `field` is part of the response.
+    pub fn is_selected(&self, field: &str) -> bool {
+        self.fields
+            .as_ref()
+            .is_none_or(|fields| fields.iter().any(|f| f == field))
+    }
+
+    /// Wraps a response so only the selected fields are serialized.
+    pub fn apply<V: Serialize>(&self, value: V) -> PartialJson<V> {
+        PartialJson {
+            value,
+            fields: self.fields.clone(),
+        }
+    }
+}
+
+impl<T: JsonSchema + Send + 'static> FromRequestParts for FieldSelect<T> {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let query_str = parts.uri.query().unwrap_or("");
+        let raw: FieldsQuery = serde_urlencoded::from_str(query_str)
+            .map_err(|e| Error::bad_request(format!("invalid fields param: {}", e)))?;
+
+        let requested: Vec<String> = raw
+            .fields
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        if requested.is_empty() {
+            return Ok(FieldSelect {
+                fields: None,
+                _marker: PhantomData,
+            });
+        }
+
+        let valid = serialized_fields::<T>();
+        let unknown: Vec<&str> = requested
+            .iter()
+            .filter(|name| !valid.contains(*name))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(Error::bad_request(format!(
+                "unknown field(s) {}; valid fields are: {}",
+                unknown.join(", "),
+                valid.join(", ")
+            ))
+            .with_details(serde_json::json!({
+                "unknown": unknown,
+                "valid": &*valid,
+            })));
+        }
+
+        Ok(FieldSelect {
+            fields: Some(requested.into()),
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// JSON response serializing only the fields picked by a [`FieldSelect`].
+///
+/// Build one with [`FieldSelect::apply`]. Without a selection it serializes
+/// exactly like [`Json<T>`].
+#[derive(Debug, Clone)]
+pub struct PartialJson<T> {
+    value: T,
+    fields: Option<Arc<[String]>>,
+}
+
+impl<T> PartialJson<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Serialize> Serialize for PartialJson<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.fields {
+            None => self.value.serialize(serializer),
+            Some(fields) => self.value.serialize(Filter {
+                inner: serializer,
+                fields,
+            }),
+        }
+    }
+}
+
+/// Documented as `T`, since any of its fields may be selected.
+impl<T: JsonSchema> JsonSchema for PartialJson<T> {
+    fn inline_schema() -> bool {
+        T::inline_schema()
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        T::schema_name()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        T::schema_id()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        T::json_schema(generator)
+    }
+}
+
+impl<T: Serialize> IntoResponse for PartialJson<T> {
+    fn into_response(self) -> http::Response<BoxBody> {
+        Json(self).into_response()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Filtering serializer
+// ---------------------------------------------------------------------------
+
+/// A serializer that writes structs as maps holding only the selected
+/// fields, and passes everything else through to `inner`.
+struct Filter<'a, S> {
+    inner: S,
+    fields: &'a [String],
+}
+
+/// A value serialized through [`Filter`].
+struct Filtered<'a, T: ?Sized> {
+    value: &'a T,
+    fields: &'a [String],
+}
+
+impl<T: Serialize + ?Sized> Serialize for Filtered<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(Filter {
+            inner: serializer,
+            fields: self.fields,
+        })
+    }
+}
+
+struct FilterSeq<'a, S> {
+    inner: S,
+    fields: &'a [String],
+}
+
+impl<S: SerializeSeq> SerializeSeq for FilterSeq<'_, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.inner.serialize_element(&Filtered {
+            value,
+            fields: self.fields,
+        })
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+struct FilterStruct<'a, M> {
+    map: M,
+    fields: &'a [String],
+}
+
+impl<M: SerializeMap> ser::SerializeStruct for FilterStruct<'_, M> {
+    type Ok = M::Ok;
+    type Error = M::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), M::Error> {
+        if self.fields.iter().any(|field| field == key) {
+            self.map.serialize_entry(key, value)?;
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<M::Ok, M::Error> {
+        self.map.end()
+    }
+}
+
+impl<'a, S: Serializer> Serializer for Filter<'a, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = FilterSeq<'a, S::SerializeSeq>;
+    type SerializeTuple = S::SerializeTuple;
+    type SerializeTupleStruct = S::SerializeTupleStruct;
+    type SerializeTupleVariant = S::SerializeTupleVariant;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = FilterStruct<'a, S::SerializeMap>;
+    type SerializeStructVariant = S::SerializeStructVariant;
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(FilterStruct {
+            map: self.inner.serialize_map(None)?,
+            fields: self.fields,
+        })
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(FilterSeq {
+            inner: self.inner.serialize_seq(len)?,
+            fields: self.fields,
+        })
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_some(&Filtered {
+            value,
+            fields: self.fields,
+        })
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_struct(
+            name,
+            &Filtered {
+                value,
+                fields: self.fields,
+            },
+        )
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u128(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f32(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f64(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_newtype_variant(name, variant_index, variant, value)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<S::SerializeTuple, S::Error> {
+        self.inner.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<S::SerializeTupleStruct, S::Error> {
+        self.inner.serialize_tuple_struct(name, len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<S::SerializeTupleVariant, S::Error> {
+        self.inner
+            .serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<S::SerializeMap, S::Error> {
+        self.inner.serialize_map(len)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<S::SerializeStructVariant, S::Error> {
+        self.inner
+            .serialize_struct_variant(name, variant_index, variant, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{TestRequest, empty_params, empty_state};
+    use serde::Serialize;
+
+    #[derive(Serialize, JsonSchema)]
+    struct Address {
+        city: String,
+        zip: String,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    struct User {
+        id: i32,
+        name: String,
+        email: Option<String>,
+        address: Address,
+        #[serde(skip_serializing)]
+        #[allow(dead_code)]
+        password_hash: String,
+    }
+
+    fn user(id: i32) -> User {
+        User {
+            id,
+            name: "Ada".to_string(),
+            email: None,
+            address: Address {
+                city: "London".to_string(),
+                zip: "N1".to_string(),
+            },
+            password_hash: "secret".to_string(),
+        }
+    }
+
+    async fn select(uri: &str) -> Result<FieldSelect<User>, Error> {
+        let (parts, _) = TestRequest::get(uri).into_parts();
+        FieldSelect::<User>::from_request_parts(&parts, &empty_params(), &empty_state()).await
+    }
+
+    #[test]
+    fn test_valid_fields_skip_hidden() {
+        let mut fields = FieldSelect::<User>::valid_fields();
+        fields.sort();
+        assert_eq!(fields, ["address", "email", "id", "name"]);
+    }
+
+    #[tokio::test]
+    async fn test_no_selection_serializes_everything() {
+        let fields = select("/users/1").await.unwrap();
+        assert!(fields.fields().is_none());
+        assert!(fields.is_selected("email"));
+
+        let json = serde_json::to_value(fields.apply(user(1))).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": 1,
+                "name": "Ada",
+                "email": null,
+                "address": { "city": "London", "zip": "N1" },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_selects_fields() {
+        let fields = select("/users/1?fields=id,%20address&page=2")
+            .await
+            .unwrap();
+        assert_eq!(fields.fields().unwrap(), ["id", "address"]);
+
+        let json = serde_json::to_value(fields.apply(user(1))).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "id": 1, "address": { "city": "London", "zip": "N1" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_selects_fields_of_each_item() {
+        let fields = select("/users?fields=name").await.unwrap();
+        let json = serde_json::to_value(fields.apply(vec![user(1), user(2)])).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{ "name": "Ada" }, { "name": "Ada" }])
+        );
+
+        let json = serde_json::to_value(fields.apply(Some(user(3)))).unwrap();
+        assert_eq!(json, serde_json::json!({ "name": "Ada" }));
+    }
+
+    #[tokio::test]
+    async fn test_empty_selection_means_all_fields() {
+        let fields = select("/users/1?fields=").await.unwrap();
+        assert!(fields.fields().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_field_rejected() {
+        let err = select("/users/1?fields=id,nickname").await.unwrap_err();
+        assert_eq!(err.status, 400);
+        assert!(err.message.contains("unknown field(s) nickname"));
+        let details = err.details.unwrap();
+        assert_eq!(details["unknown"], serde_json::json!(["nickname"]));
+        assert!(
+            details["valid"]
+                .as_array()
+                .unwrap()
+                .contains(&"name".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hidden_field_not_selectable() {
+        let err = select("/users/1?fields=password_hash").await.unwrap_err();
+        assert_eq!(err.status, 400);
+        assert!(err.message.contains("unknown field(s) password_hash"));
+        assert!(
+            !err.details.unwrap()["valid"]
+                .as_array()
+                .unwrap()
+                .contains(&"password_hash".into())
+        );
+    }
+
+    #[test]
+    fn test_partial_json_is_documented_as_the_inner_type() {
+        let partial = serde_json::to_value(schemars::schema_for!(PartialJson<User>)).unwrap();
+        let full = serde_json::to_value(schemars::schema_for!(User)).unwrap();
+        assert_eq!(partial["properties"], full["properties"]);
+    }
+}
@@ -0,0 +1,246 @@
+//! Feature flags for gating handlers.
+//!
+//! Flags are answered by the [`FeatureFlags`] provider held in the [`Flags`]
+//! handle registered in application state. Routes can be gated entirely with
+//! the `flag` option of the route macros, which answers `404 Not Found` while
+//! the flag is off, or branch inside the handler with the [`Flags`] extractor:
+//!
+//! ```rust,ignore
+//! use rapina::flags::{Flags, StaticFlags};
+//! use rapina::prelude::*;
+//!
+//! #[get("/dashboard", flag = "new_dashboard")]
+//! async fn dashboard() -> &'static str {
+//!     "the new dashboard"
+//! }
+//!
+//! #[get("/home")]
+//! async fn home(flags: Flags) -> Result<&'static str> {
+//!     flags.require("home_page").await?;
+//!     if flags.is_enabled("new_dashboard").await {
+//!         return Ok("home, with a link to the new dashboard");
+//!     }
+//!     Ok("home")
+//! }
+//!
+//! let flags = StaticFlags::new().with("new_dashboard", false);
+//!
+//! Rapina::new()
+//!     .state(Flags::new(flags.clone()))
+//!     .discover()
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+//!
+//! Providers are asked on every request, so flipping a flag takes effect
+//! immediately: call [`StaticFlags::enable`] on a clone kept elsewhere, or
+//! implement [`FeatureFlags`] on top of a remote flag service.
+//!
+//! If no provider is registered, every flag is off.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::state::AppState;
+
+/// Boxed future returned by [`FeatureFlags`] methods.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Source of feature flag state.
+///
+/// Implement this trait to read flags from a flag service, a config store or
+/// the database, then register it with `Rapina::new().state(Flags::new(provider))`.
+/// Flags the provider doesn't know about should be reported as off.
+pub trait FeatureFlags: Send + Sync + 'static {
+    /// Returns whether the flag called `name` is currently on.
+    fn is_enabled<'a>(&'a self, name: &'a str) -> BoxFuture<'a, bool>;
+}
+
+/// Provider that keeps flags in memory.
+///
+/// Clones share the same flags, so keep one around to flip flags at runtime.
+///
+/// # Example
+///
+/// ```
+/// use rapina::flags::{FeatureFlags, StaticFlags};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let flags = StaticFlags::new().with("new_dashboard", false);
+/// let handle = flags.clone();
+///
+/// assert!(!flags.is_enabled("new_dashboard").await);
+/// handle.enable("new_dashboard");
+/// assert!(flags.is_enabled("new_dashboard").await);
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StaticFlags {
+    flags: Arc<RwLock<HashMap<String, bool>>>,
+}
+
+impl StaticFlags {
+    /// Creates a provider with every flag off.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the initial state of a flag.
+    pub fn with(self, name: impl Into<String>, enabled: bool) -> Self {
+        self.set(name, enabled);
+        self
+    }
+
+    /// Turns a flag on.
+    pub fn enable(&self, name: impl Into<String>) {
+        self.set(name, true);
+    }
+
+    /// Turns a flag off.
+    pub fn disable(&self, name: impl Into<String>) {
+        self.set(name, false);
+    }
+
+    /// Sets a flag on or off.
+    pub fn set(&self, name: impl Into<String>, enabled: bool) {
+        self.flags.write().unwrap().insert(name.into(), enabled);
+    }
+
+    /// Returns every flag the provider knows about and whether it is on.
+    pub fn snapshot(&self) -> HashMap<String, bool> {
+        self.flags.read().unwrap().clone()
+    }
+}
+
+impl FeatureFlags for StaticFlags {
+    fn is_enabled<'a>(&'a self, name: &'a str) -> BoxFuture<'a, bool> {
+        let enabled = self
+            .flags
+            .read()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or(false);
+        Box::pin(async move { enabled })
+    }
+}
+
+/// Handle for checking feature flags, available as an extractor and as state.
+///
+/// Extracts the `Flags` registered in application state, or a handle with
+/// every flag off if none was registered.
+#[derive(Clone, Default)]
+pub struct Flags {
+    provider: Option<Arc<dyn FeatureFlags>>,
+}
+
+impl Flags {
+    /// Creates a `Flags` handle backed by the given provider.
+    pub fn new<F: FeatureFlags>(provider: F) -> Self {
+        Self {
+            provider: Some(Arc::new(provider)),
+        }
+    }
+
+    /// Returns the handle registered in state, or one with every flag off.
+    pub fn from_state(state: &AppState) -> Self {
+        state.get::<Flags>().cloned().unwrap_or_default()
+    }
+
+    /// Returns whether the flag called `name` is currently on.
+    pub async fn is_enabled(&self, name: &str) -> bool {
+        match &self.provider {
+            Some(provider) => provider.is_enabled(name).await,
+            None => false,
+        }
+    }
+
+    /// Fails with `404 Not Found` unless the flag called `name` is on.
+    ///
+    /// This is the same response a route gated with `flag = "..."` gives, so
+    /// gating part of a handler looks no different from gating all of it.
+    pub async fn require(&self, name: &str) -> Result<(), Error> {
+        if self.is_enabled(name).await {
+            Ok(())
+        } else {
+            Err(Error::not_found("not found"))
+        }
+    }
+}
+
+impl std::fmt::Debug for Flags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Flags").finish_non_exhaustive()
+    }
+}
+
+impl FromRequestParts for Flags {
+    async fn from_request_parts(
+        _parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        Ok(Flags::from_state(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{TestRequest, empty_params, empty_state, state_with};
+
+    #[tokio::test]
+    async fn test_static_flags_default_off() {
+        let flags = StaticFlags::new();
+        assert!(!flags.is_enabled("anything").await);
+    }
+
+    #[tokio::test]
+    async fn test_static_flags_clones_share_state() {
+        let flags = StaticFlags::new().with("beta", true);
+        let handle = flags.clone();
+        assert!(flags.is_enabled("beta").await);
+
+        handle.disable("beta");
+        assert!(!flags.is_enabled("beta").await);
+
+        handle.set("beta", true);
+        assert!(flags.is_enabled("beta").await);
+        assert_eq!(handle.snapshot().get("beta"), Some(&true));
+    }
+
+    #[tokio::test]
+    async fn test_require_rejects_with_not_found() {
+        let flags = Flags::new(StaticFlags::new().with("on", true));
+
+        assert!(flags.require("on").await.is_ok());
+        let err = flags.require("off").await.unwrap_err();
+        assert_eq!(err.status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_extractor_uses_registered_provider() {
+        let state = state_with(Flags::new(StaticFlags::new().with("beta", true)));
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let flags = Flags::from_request_parts(&parts, &empty_params(), &state)
+            .await
+            .unwrap();
+        assert!(flags.is_enabled("beta").await);
+    }
+
+    #[tokio::test]
+    async fn test_extractor_without_provider_has_every_flag_off() {
+        let state = empty_state();
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let flags = Flags::from_request_parts(&parts, &empty_params(), &state)
+            .await
+            .unwrap();
+        assert!(!flags.is_enabled("beta").await);
+    }
+}
@@ -1,5 +1,6 @@
 //! Handler trait for named route handlers.
 
+use std::any::TypeId;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -32,6 +33,52 @@ pub trait Handler: Clone + Send + Sync + 'static {
         Vec::new()
     }
 
+    /// Feature flag that must be on for the route to respond (if any).
+    fn feature_flag() -> Option<&'static str> {
+        None
+    }
+
+    /// JSON Schemas of the route parameters the handler's `Path` arguments
+    /// extract, by parameter name.
+    fn path_schemas() -> Vec<(&'static str, serde_json::Value)> {
+        Vec::new()
+    }
+
+    /// Fields a `FieldSelect` argument lets clients pick with `?fields=`.
+    fn selectable_fields() -> Option<Vec<String>> {
+        None
+    }
+
+    /// Request body media type the handler is picked for, when several
+    /// handlers share a method and path.
+    fn consumes() -> Option<&'static str> {
+        None
+    }
+
+    /// Response media type the handler is picked for by `Accept`, when
+    /// several handlers share a method and path.
+    fn produces() -> Option<&'static str> {
+        None
+    }
+
+    /// Version the handler is picked for when `Accept` asks for one, like
+    /// `application/vnd.myapp.v2+json`, when several handlers share a
+    /// method and path.
+    fn accept_version() -> Option<u32> {
+        None
+    }
+
+    /// State types the handler's `State` and `States` arguments need, with
+    /// their names, so a missing `.state()` call can be reported at startup.
+    fn required_state() -> Vec<(TypeId, &'static str)> {
+        Vec::new()
+    }
+
+    /// Whether generated contract tests should skip the route.
+    fn untestable() -> bool {
+        false
+    }
+
     /// Handle the request.
     fn call(&self, req: Request<Incoming>, params: PathParams, state: Arc<AppState>) -> BoxFuture;
 }
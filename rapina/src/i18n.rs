@@ -0,0 +1,518 @@
+//! Language negotiation for localized responses.
+//!
+//! [`AcceptLanguage`] parses the `Accept-Language` header into language
+//! ranges ordered by preference, and [`Locale`] resolves them against the
+//! locales the app supports. Register supported locales, and optionally a
+//! translation hook for error messages, with
+//! [`Rapina::with_locales`](crate::app::Rapina::with_locales).
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::i18n::{Locale, LocaleConfig};
+//!
+//! #[get("/greeting")]
+//! async fn greeting(locale: Locale) -> &'static str {
+//!     match locale.as_str() {
+//!         "pt-BR" => "Olá!",
+//!         _ => "Hello!",
+//!     }
+//! }
+//!
+//! Rapina::new()
+//!     .with_locales(
+//!         LocaleConfig::new(["en", "pt-BR"]).translate(|code, locale| match (code, locale) {
+//!             ("NOT_FOUND", "pt-BR") => Some("Recurso não encontrado".to_string()),
+//!             _ => None,
+//!         }),
+//!     )
+//!     // ...
+//! ```
+
+use std::sync::Arc;
+
+use http::{HeaderMap, HeaderValue, Request, Response, header};
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::middleware::{BoxFuture, Middleware, Next};
+use crate::response::BoxBody;
+use crate::state::AppState;
+
+const FALLBACK_LOCALE: &str = "en";
+
+type Translator = Arc<dyn Fn(&str, &str) -> Option<String> + Send + Sync>;
+
+/// One entry of an `Accept-Language` header, such as `pt-BR;q=0.8`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageRange {
+    /// The language tag, or `*` for any language.
+    pub tag: String,
+    /// The quality value, from `0.0` to `1.0`. Defaults to `1.0`.
+    pub quality: f32,
+}
+
+/// Extracts the `Accept-Language` header as language ranges, most preferred first.
+///
+/// Ranges with equal quality keep their order from the header. Entries with
+/// a malformed tag or quality value are skipped, as are ranges with `q=0`,
+/// which mark a language as unacceptable. A missing header gives an empty list.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::i18n::AcceptLanguage;
+///
+/// let accept = AcceptLanguage::parse("en;q=0.5, pt-BR, fr;q=oops");
+/// assert_eq!(accept.preferred(), Some("pt-BR"));
+/// assert_eq!(accept.0.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AcceptLanguage(pub Vec<LanguageRange>);
+
+impl AcceptLanguage {
+    /// Parses the value of an `Accept-Language` header.
+    pub fn parse(value: &str) -> Self {
+        let mut ranges: Vec<LanguageRange> = value.split(',').filter_map(parse_range).collect();
+        ranges.sort_by(|a, b| b.quality.total_cmp(&a.quality));
+        Self(ranges)
+    }
+
+    /// Reads every `Accept-Language` header in `headers`.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let values: Vec<&str> = headers
+            .get_all(header::ACCEPT_LANGUAGE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        Self::parse(&values.join(","))
+    }
+
+    /// The most preferred language tag, if any.
+    pub fn preferred(&self) -> Option<&str> {
+        self.0.first().map(|range| range.tag.as_str())
+    }
+
+    /// Iterates over the language ranges, most preferred first.
+    pub fn iter(&self) -> impl Iterator<Item = &LanguageRange> {
+        self.0.iter()
+    }
+}
+
+fn parse_range(entry: &str) -> Option<LanguageRange> {
+    let mut parts = entry.split(';');
+    let tag = parts.next()?.trim();
+    if !is_language_tag(tag) {
+        return None;
+    }
+
+    let mut quality = 1.0;
+    for param in parts {
+        let Some((name, value)) = param.split_once('=') else {
+            continue;
+        };
+        if name.trim().eq_ignore_ascii_case("q") {
+            quality = value.trim().parse::<f32>().ok()?;
+        }
+    }
+
+    if !(0.0..=1.0).contains(&quality) || quality == 0.0 {
+        return None;
+    }
+
+    Some(LanguageRange {
+        tag: tag.to_string(),
+        quality,
+    })
+}
+
+fn is_language_tag(tag: &str) -> bool {
+    tag == "*"
+        || (!tag.is_empty()
+            && tag.split('-').all(|sub| {
+                !sub.is_empty() && sub.len() <= 8 && sub.chars().all(|c| c.is_ascii_alphanumeric())
+            }))
+}
+
+impl FromRequestParts for AcceptLanguage {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+/// The locales an app supports, and how to translate error messages into them.
+///
+/// The first supported locale is the default, used when nothing the client
+/// accepts is supported. Register with
+/// [`Rapina::with_locales`](crate::app::Rapina::with_locales).
+#[derive(Clone)]
+pub struct LocaleConfig {
+    supported: Vec<String>,
+    default: String,
+    translator: Option<Translator>,
+}
+
+impl LocaleConfig {
+    /// Creates a config for the given locales, in order of preference.
+    pub fn new<I, S>(supported: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let supported: Vec<String> = supported.into_iter().map(Into::into).collect();
+        let default = supported
+            .first()
+            .cloned()
+            .unwrap_or_else(|| FALLBACK_LOCALE.to_string());
+        Self {
+            supported,
+            default,
+            translator: None,
+        }
+    }
+
+    /// Sets the locale used when negotiation finds no match.
+    pub fn default_locale(mut self, locale: impl Into<String>) -> Self {
+        self.default = locale.into();
+        self
+    }
+
+    /// Translates error messages by error code and resolved locale.
+    ///
+    /// The hook receives the error code (e.g. `"NOT_FOUND"`) and the locale
+    /// negotiated for the request. Returning `None` keeps the original message.
+    pub fn translate<F>(mut self, translator: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.translator = Some(Arc::new(translator));
+        self
+    }
+
+    /// The supported locales, in order of preference.
+    pub fn supported(&self) -> &[String] {
+        &self.supported
+    }
+
+    /// Resolves the best supported locale for `accept`.
+    ///
+    /// Ranges are tried most preferred first. A range matches a supported
+    /// locale exactly (ignoring case), then as a prefix (`pt` matches
+    /// `pt-BR`), then with its subtags truncated (`en-GB` matches `en`).
+    /// `*` and an empty header resolve to the default locale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::i18n::{AcceptLanguage, LocaleConfig};
+    ///
+    /// let config = LocaleConfig::new(["en", "pt-BR"]);
+    ///
+    /// assert_eq!(config.negotiate(&AcceptLanguage::parse("pt")), "pt-BR");
+    /// assert_eq!(config.negotiate(&AcceptLanguage::parse("en-GB, pt;q=0.5")), "en");
+    /// assert_eq!(config.negotiate(&AcceptLanguage::parse("de")), "en");
+    /// ```
+    pub fn negotiate(&self, accept: &AcceptLanguage) -> String {
+        accept
+            .iter()
+            .find_map(|range| self.lookup(&range.tag))
+            .unwrap_or(&self.default)
+            .clone()
+    }
+
+    fn lookup(&self, tag: &str) -> Option<&String> {
+        if tag == "*" {
+            return Some(&self.default);
+        }
+
+        let exact = |candidate: &str| {
+            self.supported
+                .iter()
+                .find(|locale| locale.eq_ignore_ascii_case(candidate))
+        };
+
+        exact(tag)
+            .or_else(|| {
+                self.supported.iter().find(|locale| {
+                    locale.as_bytes().get(tag.len()) == Some(&b'-')
+                        && locale
+                            .get(..tag.len())
+                            .is_some_and(|head| head.eq_ignore_ascii_case(tag))
+                })
+            })
+            .or_else(|| {
+                let mut truncated = tag;
+                while let Some((head, _)) = truncated.rsplit_once('-') {
+                    truncated = head;
+                    if let Some(locale) = exact(truncated) {
+                        return Some(locale);
+                    }
+                }
+                None
+            })
+    }
+
+    pub(crate) fn has_translator(&self) -> bool {
+        self.translator.is_some()
+    }
+
+    fn translate_message(&self, code: &str, locale: &str) -> Option<String> {
+        self.translator.as_ref().and_then(|t| t(code, locale))
+    }
+}
+
+impl std::fmt::Debug for LocaleConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocaleConfig")
+            .field("supported", &self.supported)
+            .field("default", &self.default)
+            .field("translator", &self.translator.is_some())
+            .finish()
+    }
+}
+
+/// Extracts the locale negotiated for the request.
+///
+/// Resolves `Accept-Language` against the app's [`LocaleConfig`]. Without
+/// one, it is the client's most preferred language, or `en` when the
+/// header is missing. Never rejects a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale(pub String);
+
+impl Locale {
+    /// The locale tag, e.g. `pt-BR`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consumes the extractor and returns the locale tag.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+
+    fn resolve(headers: &HeaderMap, config: Option<&LocaleConfig>) -> Self {
+        let accept = AcceptLanguage::from_headers(headers);
+        let locale = match config {
+            Some(config) => config.negotiate(&accept),
+            None => accept
+                .iter()
+                .map(|range| range.tag.as_str())
+                .find(|tag| *tag != "*")
+                .unwrap_or(FALLBACK_LOCALE)
+                .to_string(),
+        };
+        Self(locale)
+    }
+}
+
+impl FromRequestParts for Locale {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        Ok(Self::resolve(&parts.headers, state.get::<LocaleConfig>()))
+    }
+}
+
+/// Rewrites error messages with the [`LocaleConfig`] translation hook.
+///
+/// Added by [`Rapina::with_locales`](crate::app::Rapina::with_locales) when
+/// the config has a hook. Only JSON error responses in the standard error
+/// shape are touched; translated ones get a `Content-Language` header.
+pub(crate) struct LocaleMiddleware {
+    config: Arc<LocaleConfig>,
+}
+
+impl LocaleMiddleware {
+    pub(crate) fn new(config: LocaleConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl Middleware for LocaleMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            let locale = Locale::resolve(req.headers(), Some(&self.config));
+            let response = next.run(req).await;
+
+            let is_json = response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("application/json"));
+            if !(response.status().is_client_error() || response.status().is_server_error())
+                || !is_json
+                || response.body().is_stream()
+            {
+                return response;
+            }
+
+            let (mut parts, body) = response.into_parts();
+            let bytes = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => return Response::from_parts(parts, BoxBody::empty()),
+            };
+
+            let Some(translated) = self.translate(&bytes, locale.as_str()) else {
+                return Response::from_parts(parts, BoxBody::new(bytes));
+            };
+
+            parts.headers.remove(header::CONTENT_LENGTH);
+            if let Ok(value) = HeaderValue::from_str(locale.as_str()) {
+                parts.headers.insert(header::CONTENT_LANGUAGE, value);
+            }
+            Response::from_parts(parts, BoxBody::new(translated))
+        })
+    }
+}
+
+impl LocaleMiddleware {
+    /// Returns the error body with its message translated, if the hook has one.
+    fn translate(&self, body: &[u8], locale: &str) -> Option<Vec<u8>> {
+        let mut value: serde_json::Value = serde_json::from_slice(body).ok()?;
+        let error = value.get_mut("error")?.as_object_mut()?;
+        let code = error.get("code")?.as_str()?;
+        let message = self.config.translate_message(code, locale)?;
+        error.insert("message".to_string(), serde_json::Value::String(message));
+        serde_json::to_vec(&value).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(accept: &AcceptLanguage) -> Vec<&str> {
+        accept.iter().map(|range| range.tag.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_orders_by_quality() {
+        let accept = AcceptLanguage::parse("fr;q=0.3, en-US, de;q=0.7, en;q=0.7");
+        assert_eq!(tags(&accept), vec!["en-US", "de", "en", "fr"]);
+        assert_eq!(accept.0[0].quality, 1.0);
+        assert_eq!(accept.0[3].quality, 0.3);
+    }
+
+    #[test]
+    fn test_parse_skips_malformed_entries() {
+        let accept = AcceptLanguage::parse("en;q=abc, pt;q=1.5, es;q=-1, , de;q=0.5, fr;q");
+        assert_eq!(tags(&accept), vec!["fr", "de"]);
+    }
+
+    #[test]
+    fn test_parse_skips_invalid_tags() {
+        let accept = AcceptLanguage::parse("en us, pt_BR, toolongsubtag, ja");
+        assert_eq!(tags(&accept), vec!["ja"]);
+    }
+
+    #[test]
+    fn test_parse_drops_unacceptable() {
+        let accept = AcceptLanguage::parse("en;q=0, pt;q=0.000");
+        assert!(accept.0.is_empty());
+        assert_eq!(accept.preferred(), None);
+    }
+
+    #[test]
+    fn test_parse_ignores_unknown_params_and_whitespace() {
+        let accept = AcceptLanguage::parse(" pt-BR ; foo=bar ; Q=0.9 ,en");
+        assert_eq!(tags(&accept), vec!["en", "pt-BR"]);
+        assert_eq!(accept.0[1].quality, 0.9);
+    }
+
+    #[test]
+    fn test_from_headers_joins_multiple_values() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            header::ACCEPT_LANGUAGE,
+            HeaderValue::from_static("en;q=0.5"),
+        );
+        headers.append(header::ACCEPT_LANGUAGE, HeaderValue::from_static("pt"));
+        assert_eq!(
+            tags(&AcceptLanguage::from_headers(&headers)),
+            vec!["pt", "en"]
+        );
+    }
+
+    #[test]
+    fn test_negotiate_precedence() {
+        let config = LocaleConfig::new(["en", "pt-BR", "es"]);
+
+        let negotiate = |header: &str| config.negotiate(&AcceptLanguage::parse(header));
+
+        assert_eq!(negotiate("es, pt-BR;q=0.9"), "es");
+        assert_eq!(negotiate("es;q=0.5, pt-BR;q=0.9"), "pt-BR");
+        assert_eq!(negotiate("PT-br"), "pt-BR");
+        assert_eq!(negotiate("pt"), "pt-BR");
+        assert_eq!(negotiate("es-MX"), "es");
+        assert_eq!(negotiate("de, es;q=0.1"), "es");
+    }
+
+    #[test]
+    fn test_negotiate_fallback() {
+        let config = LocaleConfig::new(["en", "pt-BR"]);
+        assert_eq!(config.negotiate(&AcceptLanguage::parse("de, fr")), "en");
+        assert_eq!(config.negotiate(&AcceptLanguage::default()), "en");
+        assert_eq!(config.negotiate(&AcceptLanguage::parse("*")), "en");
+
+        let config = config.default_locale("pt-BR");
+        assert_eq!(config.negotiate(&AcceptLanguage::parse("de")), "pt-BR");
+
+        let empty = LocaleConfig::new(Vec::<String>::new());
+        assert_eq!(empty.negotiate(&AcceptLanguage::parse("de")), "en");
+    }
+
+    #[test]
+    fn test_prefix_match_requires_subtag_boundary() {
+        let config = LocaleConfig::new(["pt-BR", "english"]);
+        assert_eq!(config.negotiate(&AcceptLanguage::parse("eng")), "pt-BR");
+    }
+
+    #[test]
+    fn test_locale_without_config() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Locale::resolve(&headers, None).as_str(), "en");
+
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            HeaderValue::from_static("*, fr-CA;q=0.8"),
+        );
+        assert_eq!(Locale::resolve(&headers, None).as_str(), "fr-CA");
+    }
+
+    #[test]
+    fn test_translate_error_body() {
+        let middleware = LocaleMiddleware::new(LocaleConfig::new(["en", "pt-BR"]).translate(
+            |code, locale| match (code, locale) {
+                ("NOT_FOUND", "pt-BR") => Some("não encontrado".to_string()),
+                _ => None,
+            },
+        ));
+        let body = br#"{"error":{"code":"NOT_FOUND","message":"not found"},"trace_id":"t"}"#;
+
+        let translated = middleware.translate(body, "pt-BR").unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&translated).unwrap();
+        assert_eq!(value["error"]["message"], "não encontrado");
+        assert_eq!(value["error"]["code"], "NOT_FOUND");
+        assert_eq!(value["trace_id"], "t");
+
+        assert!(middleware.translate(body, "en").is_none());
+        assert!(middleware.translate(b"not json", "pt-BR").is_none());
+    }
+}
@@ -0,0 +1,298 @@
+//! Build metadata served on `/__rapina/version`.
+
+use std::process::Command;
+use std::sync::Arc;
+
+use http::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use serde::{Deserialize, Serialize};
+
+use crate::extract::PathParams;
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+/// Environment variable [`BuildInfo::emit`] passes the git commit in.
+pub const GIT_SHA_ENV: &str = "RAPINA_GIT_SHA";
+/// Environment variable [`BuildInfo::emit`] passes the build time in.
+pub const BUILD_TIMESTAMP_ENV: &str = "RAPINA_BUILD_TIMESTAMP";
+/// Environment variable [`BuildInfo::emit`] passes the compiler version in.
+pub const RUSTC_VERSION_ENV: &str = "RAPINA_RUSTC_VERSION";
+
+/// What was built and how, so deploy tooling can check what's live.
+///
+/// Capture it with [`build_info!`](crate::build_info) and register it with
+/// [`Rapina::build_info`](crate::app::Rapina::build_info). The crate name
+/// and version always come from Cargo; the commit, build time and compiler
+/// version are only known when the app's `build.rs` calls
+/// [`BuildInfo::emit`].
+///
+/// # Examples
+///
+/// In `build.rs`:
+///
+/// ```rust,ignore
+/// fn main() {
+///     rapina::introspection::BuildInfo::emit();
+/// }
+/// ```
+///
+/// And in the app:
+///
+/// ```rust,ignore
+/// Rapina::new()
+///     .build_info(rapina::build_info!())
+///     .router(router)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// Package name, from `CARGO_PKG_NAME`
+    pub name: String,
+    /// Package version, from `CARGO_PKG_VERSION`
+    pub version: String,
+    /// Full commit hash the binary was built from
+    pub git_sha: Option<String>,
+    /// RFC 3339 time of the build
+    pub build_timestamp: Option<String>,
+    /// Output of `rustc --version`
+    pub rustc_version: Option<String>,
+}
+
+impl BuildInfo {
+    /// Build info with only a name and version.
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            git_sha: None,
+            build_timestamp: None,
+            rustc_version: None,
+        }
+    }
+
+    /// Sets the commit hash.
+    pub fn git_sha(mut self, sha: impl Into<String>) -> Self {
+        self.git_sha = Some(sha.into());
+        self
+    }
+
+    /// Sets the build time.
+    pub fn build_timestamp(mut self, timestamp: impl Into<String>) -> Self {
+        self.build_timestamp = Some(timestamp.into());
+        self
+    }
+
+    /// Sets the compiler version.
+    pub fn rustc_version(mut self, version: impl Into<String>) -> Self {
+        self.rustc_version = Some(version.into());
+        self
+    }
+
+    /// The version with the short commit hash as semver build metadata,
+    /// e.g. `1.4.0+3f2a9c1`, used as the OpenAPI `info.version`.
+    pub fn full_version(&self) -> String {
+        match &self.git_sha {
+            Some(sha) => {
+                let short = sha.get(..7).unwrap_or(sha);
+                format!("{}+{}", self.version, short)
+            }
+            None => self.version.clone(),
+        }
+    }
+
+    /// Passes the commit, build time and compiler version to the crate
+    /// being built. Call it from `build.rs`.
+    ///
+    /// The commit comes from `RAPINA_GIT_SHA` when it is set, which helps
+    /// in Docker builds without a `.git` directory, and from
+    /// `git rev-parse HEAD` otherwise. The build time honors
+    /// `SOURCE_DATE_EPOCH` for reproducible builds.
+    pub fn emit() {
+        let sha = std::env::var(GIT_SHA_ENV)
+            .ok()
+            .filter(|sha| !sha.is_empty())
+            .or_else(|| command_output("git", &["rev-parse", "HEAD"]));
+        if let Some(sha) = sha {
+            println!("cargo:rustc-env={}={}", GIT_SHA_ENV, sha);
+        }
+        println!("cargo:rerun-if-env-changed={}", GIT_SHA_ENV);
+
+        // Rebuild when HEAD moves, not on every source change
+        if let Some(git_dir) = command_output("git", &["rev-parse", "--git-dir"]) {
+            println!("cargo:rerun-if-changed={}/HEAD", git_dir);
+            println!("cargo:rerun-if-changed={}/refs/heads", git_dir);
+        }
+
+        let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|secs| secs.parse::<i64>().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .unwrap_or_else(chrono::Utc::now);
+        println!(
+            "cargo:rustc-env={}={}",
+            BUILD_TIMESTAMP_ENV,
+            timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        );
+        println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        if let Some(version) = command_output(&rustc, &["--version"]) {
+            println!("cargo:rustc-env={}={}", RUSTC_VERSION_ENV, version);
+        }
+    }
+}
+
+/// Trimmed stdout of a command, or `None` if it fails.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (!stdout.is_empty()).then(|| stdout.to_string())
+}
+
+/// Captures the calling crate's [`BuildInfo`].
+///
+/// Expands in the app, so the name and version are the app's own, along
+/// with whatever its `build.rs` passed in through [`BuildInfo::emit`].
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::introspection::BuildInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha: option_env!("RAPINA_GIT_SHA").map(String::from),
+            build_timestamp: option_env!("RAPINA_BUILD_TIMESTAMP").map(String::from),
+            rustc_version: option_env!("RAPINA_RUSTC_VERSION").map(String::from),
+        }
+    };
+}
+
+/// Handler for the version endpoint.
+///
+/// Returns the registered [`BuildInfo`] as JSON.
+pub async fn build_version(
+    _req: Request<Incoming>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    match state.get::<BuildInfo>() {
+        Some(info) => {
+            let json = serde_json::to_vec(info).unwrap_or_default();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "application/json")
+                .body(BoxBody::new(json))
+                .unwrap()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::{app::Rapina, router::Router, testing::TestClient};
+
+    use super::*;
+
+    fn stub() -> BuildInfo {
+        BuildInfo::new("billing", "1.4.0")
+            .git_sha("3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39")
+            .build_timestamp("2026-01-02T03:04:05Z")
+            .rustc_version("rustc 1.90.0 (1159e78c4 2025-09-14)")
+    }
+
+    #[test]
+    fn test_build_info_macro_reads_cargo_env() {
+        let info = crate::build_info!();
+        assert_eq!(info.name, "rapina");
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_full_version() {
+        assert_eq!(stub().full_version(), "1.4.0+3f2a9c1");
+        assert_eq!(BuildInfo::new("billing", "1.4.0").full_version(), "1.4.0");
+        assert_eq!(
+            BuildInfo::new("billing", "1.4.0")
+                .git_sha("abc")
+                .full_version(),
+            "1.4.0+abc"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_version_endpoint_returns_build_info() {
+        let app = Rapina::new()
+            .with_introspection(true)
+            .build_info(stub())
+            .router(Router::new());
+        let client = TestClient::new(app).await;
+
+        let response = client.get("/__rapina/version").send().await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = response.json();
+        assert_eq!(
+            body,
+            json!({
+                "name": "billing",
+                "version": "1.4.0",
+                "git_sha": "3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39",
+                "build_timestamp": "2026-01-02T03:04:05Z",
+                "rustc_version": "rustc 1.90.0 (1159e78c4 2025-09-14)",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_version_endpoint_nulls_unknown_fields() {
+        let app = Rapina::new()
+            .with_introspection(true)
+            .build_info(BuildInfo::new("billing", "1.4.0"))
+            .router(Router::new());
+        let client = TestClient::new(app).await;
+
+        let body: Value = client.get("/__rapina/version").send().await.json();
+
+        assert_eq!(body["version"], "1.4.0");
+        assert_eq!(body["git_sha"], Value::Null);
+        assert_eq!(body["build_timestamp"], Value::Null);
+        assert_eq!(body["rustc_version"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_version_endpoint_requires_build_info_and_introspection() {
+        let app = Rapina::new().with_introspection(true).router(Router::new());
+        let client = TestClient::new(app).await;
+        let response = client.get("/__rapina/version").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let app = Rapina::new()
+            .with_introspection(false)
+            .build_info(stub())
+            .router(Router::new());
+        let client = TestClient::new(app).await;
+        let response = client.get("/__rapina/version").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_build_info_in_openapi_version() {
+        let app = Rapina::new()
+            .with_introspection(false)
+            .openapi("Billing", "0.0.0")
+            .build_info(stub())
+            .build_info_in_openapi()
+            .router(Router::new());
+        let client = TestClient::new(app).await;
+
+        let spec: Value = client.get("/__rapina/openapi.json").send().await.json();
+
+        assert_eq!(spec["info"]["title"], "Billing");
+        assert_eq!(spec["info"]["version"], "1.4.0+3f2a9c1");
+    }
+}
@@ -52,7 +52,7 @@ pub async fn list_routes(
             Response::builder()
                 .status(StatusCode::OK)
                 .header("content-type", "application/json")
-                .body(http_body_util::Full::new(bytes::Bytes::from(json)))
+                .body(BoxBody::new(json))
                 .unwrap()
         }
         None => StatusCode::NOT_FOUND.into_response(),
@@ -0,0 +1,119 @@
+//! Token check in front of the `/__rapina` endpoints.
+
+use http::header::AUTHORIZATION;
+use http::{HeaderMap, Request, Response, StatusCode};
+use hyper::body::Incoming;
+use sha2::{Digest, Sha256};
+
+use crate::context::RequestContext;
+use crate::middleware::{BoxFuture, Middleware, Next};
+use crate::response::{BoxBody, IntoResponse};
+
+/// Header that carries the token for clients that can't send a bearer token.
+pub const INTROSPECTION_TOKEN_HEADER: &str = "x-rapina-token";
+
+const INTROSPECTION_PREFIX: &str = "/__rapina/";
+
+/// The readiness probe is part of draining rather than introspection, and
+/// load balancers poll it without credentials.
+const READY_PATH: &str = "/__rapina/ready";
+
+/// Answers 404 on `/__rapina` paths unless the request carries the
+/// introspection token, so the endpoints look absent to everyone else.
+///
+/// Registered by
+/// [`Rapina::introspection_token`](crate::app::Rapina::introspection_token).
+pub(crate) struct IntrospectionGuard {
+    digest: [u8; 32],
+}
+
+impl IntrospectionGuard {
+    pub(crate) fn new(token: &str) -> Self {
+        Self {
+            digest: Sha256::digest(token.as_bytes()).into(),
+        }
+    }
+
+    /// Lowercased first, since the router may match paths case-insensitively.
+    fn guards(path: &str) -> bool {
+        let path = path.to_ascii_lowercase();
+        path.starts_with(INTROSPECTION_PREFIX) && path.trim_end_matches('/') != READY_PATH
+    }
+
+    /// Whether `headers` carry the token, as `Authorization: Bearer` or in
+    /// `X-Rapina-Token`.
+    fn allows(&self, headers: &HeaderMap) -> bool {
+        let bearer = headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        let header = headers
+            .get(INTROSPECTION_TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok());
+        [bearer, header]
+            .into_iter()
+            .flatten()
+            .any(|token| self.matches(token.trim()))
+    }
+
+    /// Compares digests rather than the tokens themselves, so the time taken
+    /// reveals neither the matching prefix nor the token's length.
+    fn matches(&self, token: &str) -> bool {
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        digest
+            .iter()
+            .zip(&self.digest)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+    }
+}
+
+impl Middleware for IntrospectionGuard {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            if Self::guards(req.uri().path()) && !self.allows(req.headers()) {
+                return StatusCode::NOT_FOUND.into_response();
+            }
+            next.run(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_allows_bearer_or_header_token() {
+        let guard = IntrospectionGuard::new("s3cret");
+
+        assert!(guard.allows(&headers("authorization", "Bearer s3cret")));
+        assert!(guard.allows(&headers("x-rapina-token", "s3cret")));
+        assert!(!guard.allows(&headers("authorization", "Bearer s3cre")));
+        assert!(!guard.allows(&headers("authorization", "s3cret")));
+        assert!(!guard.allows(&headers("x-rapina-token", "s3cret-and-more")));
+        assert!(!guard.allows(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_guards_introspection_paths_except_readiness() {
+        assert!(IntrospectionGuard::guards("/__rapina/routes"));
+        assert!(IntrospectionGuard::guards("/__rapina/openapi.json"));
+        assert!(IntrospectionGuard::guards("/__RAPINA/Routes"));
+        assert!(!IntrospectionGuard::guards("/__rapina/ready"));
+        assert!(!IntrospectionGuard::guards("/users"));
+        assert!(!IntrospectionGuard::guards("/__rapinas/routes"));
+    }
+}
@@ -0,0 +1,51 @@
+//! Introspection endpoint for the resolved middleware order.
+
+use std::sync::Arc;
+
+use http::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+
+use crate::extract::PathParams;
+use crate::middleware::MiddlewareInfo;
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+/// The app's global middleware in the order they run, stored in
+/// application state when introspection is enabled.
+#[derive(Debug, Clone, Default)]
+pub struct MiddlewareRegistry {
+    middleware: Vec<MiddlewareInfo>,
+}
+
+impl MiddlewareRegistry {
+    /// Creates a registry listing `middleware`, outermost first.
+    pub fn new(middleware: Vec<MiddlewareInfo>) -> Self {
+        Self { middleware }
+    }
+
+    /// Returns the middleware, outermost first.
+    pub fn middleware(&self) -> &[MiddlewareInfo] {
+        &self.middleware
+    }
+}
+
+/// Handler for the middleware introspection endpoint.
+///
+/// Returns the global middleware with their phases, outermost first.
+pub async fn list_middleware(
+    _req: Request<Incoming>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    match state.get::<MiddlewareRegistry>() {
+        Some(registry) => {
+            let json = serde_json::to_vec(registry.middleware()).unwrap_or_default();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "application/json")
+                .body(BoxBody::new(json))
+                .unwrap()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
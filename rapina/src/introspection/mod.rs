@@ -3,8 +3,17 @@
 //! This module provides tools for inspecting route metadata,
 //! enabling documentation generation and AI-native tooling.
 
+mod build_info;
 mod endpoint;
+mod guard;
+mod middleware;
 mod route_info;
 
+pub use build_info::{
+    BUILD_TIMESTAMP_ENV, BuildInfo, GIT_SHA_ENV, RUSTC_VERSION_ENV, build_version,
+};
 pub use endpoint::{RouteRegistry, list_routes};
+pub use guard::INTROSPECTION_TOKEN_HEADER;
+pub(crate) use guard::IntrospectionGuard;
+pub use middleware::{MiddlewareRegistry, list_middleware};
 pub use route_info::RouteInfo;
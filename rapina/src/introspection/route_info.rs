@@ -1,5 +1,7 @@
 //! Route metadata for introspection.
 
+use std::collections::BTreeMap;
+
 use serde::Serialize;
 
 use crate::error::ErrorVariant;
@@ -32,6 +34,43 @@ pub struct RouteInfo {
     /// Error variants for OpenAPI documentation.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub error_responses: Vec<ErrorVariant>,
+    /// Feature flag gating the route, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_flag: Option<String>,
+    /// Content types the request body may have, when the route restricts them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub request_content_types: Vec<String>,
+    /// Skipped by `rapina generate contract-tests`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub untestable: bool,
+    /// JSON Schemas of the path parameters, by name, when the handler's
+    /// `Path` arguments give their types.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub path_schemas: BTreeMap<String, serde_json::Value>,
+    /// Fields clients may pick with `?fields=`, when the handler supports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selectable_fields: Option<Vec<String>>,
+    /// Request body media type the handler is picked for among handlers
+    /// sharing the method and path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumes: Option<String>,
+    /// Response media type the handler is picked for by `Accept` among
+    /// handlers sharing the method and path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub produces: Option<String>,
+    /// Request header conditions the handler is picked for among handlers
+    /// sharing the method and path, like `accept: version 2`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub header_conditions: Vec<String>,
+    /// `Accept` version the handler is picked for, from `accept_version`
+    /// or a [`HeaderMatcher::version`](crate::router::HeaderMatcher::version)
+    /// condition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_version: Option<u32>,
+    /// Middleware that runs for this route only, in order, after the global
+    /// middleware listed at `/__rapina/middleware`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub middleware: Vec<String>,
 }
 
 impl RouteInfo {
@@ -49,8 +88,78 @@ impl RouteInfo {
             handler_name: handler_name.into(),
             response_schema,
             error_responses,
+            feature_flag: None,
+            request_content_types: Vec::new(),
+            untestable: false,
+            path_schemas: BTreeMap::new(),
+            selectable_fields: None,
+            consumes: None,
+            produces: None,
+            header_conditions: Vec::new(),
+            accept_version: None,
+            middleware: Vec::new(),
         }
     }
+
+    /// Marks the route as gated by a feature flag.
+    pub fn with_feature_flag(mut self, flag: impl Into<String>) -> Self {
+        self.feature_flag = Some(flag.into());
+        self
+    }
+
+    /// Records the content types the route accepts request bodies in.
+    pub fn with_request_content_types(mut self, types: Vec<String>) -> Self {
+        self.request_content_types = types;
+        self
+    }
+
+    /// Records the schemas of the route's path parameters.
+    pub fn with_path_schemas(mut self, schemas: BTreeMap<String, serde_json::Value>) -> Self {
+        self.path_schemas = schemas;
+        self
+    }
+
+    /// Records the fields the route lets clients select with `?fields=`.
+    pub fn with_selectable_fields(mut self, fields: Vec<String>) -> Self {
+        self.selectable_fields = Some(fields);
+        self
+    }
+
+    /// Records the request body media type the route is picked for.
+    pub fn with_consumes(mut self, media_type: impl Into<String>) -> Self {
+        self.consumes = Some(media_type.into());
+        self
+    }
+
+    /// Records the response media type the route is picked for.
+    pub fn with_produces(mut self, media_type: impl Into<String>) -> Self {
+        self.produces = Some(media_type.into());
+        self
+    }
+
+    /// Records the header conditions the route is picked for.
+    pub fn with_header_conditions(mut self, conditions: Vec<String>) -> Self {
+        self.header_conditions = conditions;
+        self
+    }
+
+    /// Records the `Accept` version the route is picked for.
+    pub fn with_accept_version(mut self, version: u32) -> Self {
+        self.accept_version = Some(version);
+        self
+    }
+
+    /// Records the names of the route's own middleware.
+    pub fn with_middleware(mut self, names: Vec<String>) -> Self {
+        self.middleware = names;
+        self
+    }
+
+    /// Marks the route as skipped by generated contract tests.
+    pub fn untestable(mut self) -> Self {
+        self.untestable = true;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -106,4 +215,33 @@ mod tests {
         assert_eq!(info.error_responses.len(), 1);
         assert_eq!(info.error_responses[0].status, 404);
     }
+
+    #[test]
+    fn test_route_info_untestable() {
+        let info = RouteInfo::new("POST", "/payments", "charge", None, Vec::new());
+        assert!(!serde_json::to_string(&info).unwrap().contains("untestable"));
+
+        let info = info.untestable();
+        assert!(info.untestable);
+        assert!(
+            serde_json::to_string(&info)
+                .unwrap()
+                .contains("\"untestable\":true")
+        );
+    }
+
+    #[test]
+    fn test_route_info_feature_flag() {
+        let info = RouteInfo::new("GET", "/dashboard", "dashboard", None, Vec::new());
+        assert!(
+            !serde_json::to_string(&info)
+                .unwrap()
+                .contains("feature_flag")
+        );
+
+        let info = info.with_feature_flag("new_dashboard");
+        assert_eq!(info.feature_flag.as_deref(), Some("new_dashboard"));
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(json.contains("\"feature_flag\":\"new_dashboard\""));
+    }
 }
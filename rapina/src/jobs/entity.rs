@@ -0,0 +1,45 @@
+//! The `rapina_jobs` table.
+//!
+//! Pending and running jobs live here until they succeed, when their row is
+//! deleted. Jobs that run out of attempts stay behind with status `failed`
+//! and the last error, so they can be inspected or re-queued by hand.
+
+use sea_orm::entity::prelude::*;
+
+/// Waiting for its `run_at` time.
+pub const PENDING: &str = "pending";
+/// Claimed by a worker.
+pub const RUNNING: &str = "running";
+/// Gave up after the last attempt.
+pub const FAILED: &str = "failed";
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "rapina_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// The job's [`Job::NAME`](super::Job::NAME).
+    pub name: String,
+    /// The job serialized as JSON.
+    #[sea_orm(column_type = "Text")]
+    pub payload: String,
+    /// [`PENDING`], [`RUNNING`] or [`FAILED`].
+    pub status: String,
+    /// Attempts started so far.
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// Earliest time the next attempt may start.
+    pub run_at: DateTimeUtc,
+    /// When a worker claimed the job, while it is running.
+    pub locked_at: Option<DateTimeUtc>,
+    /// Error of the most recent failed attempt.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
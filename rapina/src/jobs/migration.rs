@@ -0,0 +1,87 @@
+//! Migration creating the `rapina_jobs` table.
+//!
+//! Register it like any other migration. `rapina add job` adds a module
+//! that re-exports it:
+//!
+//! ```rust,ignore
+//! // src/migrations/m20261016_120000_create_rapina_jobs.rs
+//! pub use rapina::jobs::migration::Migration;
+//! ```
+
+use sea_orm_migration::prelude::*;
+
+use super::entity::Column;
+
+/// Creates the `rapina_jobs` table and its polling index.
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261016_000001_create_rapina_jobs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RapinaJobs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Column::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Column::Name).string().not_null())
+                    .col(ColumnDef::new(Column::Payload).text().not_null())
+                    .col(ColumnDef::new(Column::Status).string().not_null())
+                    .col(ColumnDef::new(Column::Attempts).integer().not_null())
+                    .col(ColumnDef::new(Column::MaxAttempts).integer().not_null())
+                    .col(
+                        ColumnDef::new(Column::RunAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Column::LockedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Column::LastError).text())
+                    .col(
+                        ColumnDef::new(Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Column::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_rapina_jobs_status_run_at")
+                    .table(RapinaJobs::Table)
+                    .col(Column::Status)
+                    .col(Column::RunAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RapinaJobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RapinaJobs {
+    Table,
+}
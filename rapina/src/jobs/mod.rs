@@ -0,0 +1,574 @@
+//! Background jobs persisted in the database.
+//!
+//! A [`Job`] is a serializable struct with a `run` method. Handlers enqueue
+//! jobs through the [`Jobs`] extractor, which stores them in the
+//! `rapina_jobs` table, and a [`JobQueue`] started with the application
+//! picks them up in the background. Failed runs are retried with
+//! exponential backoff; a job that runs out of attempts is kept with status
+//! `failed` and its last error.
+//!
+//! On PostgreSQL, workers claim jobs with `FOR UPDATE SKIP LOCKED`, so any
+//! number of processes can share the queue. Other databases claim jobs with
+//! a conditional update, which is safe but retries more under contention.
+//!
+//! The table is created by [`migration::Migration`]; `rapina add job`
+//! registers it the first time it runs.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rapina::jobs::{Job, JobContext, JobQueue, JobResult, Jobs};
+//! use rapina::prelude::*;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct SendEmail {
+//!     to: String,
+//! }
+//!
+//! impl Job for SendEmail {
+//!     const NAME: &'static str = "send_email";
+//!
+//!     async fn run(self, ctx: JobContext) -> JobResult {
+//!         let mailer = ctx.state().get::<Mailer>().unwrap();
+//!         mailer.send(&self.to, "Welcome!").await?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[post("/signup")]
+//! async fn signup(jobs: Jobs, body: Json<Signup>) -> Result<StatusCode> {
+//!     jobs.enqueue(SendEmail { to: body.into_inner().email }).await?;
+//!     Ok(StatusCode::ACCEPTED)
+//! }
+//!
+//! Rapina::new()
+//!     .with_database(DatabaseConfig::from_env()?)
+//!     .await?
+//!     .run_migrations::<migrations::Migrator>()
+//!     .await?
+//!     .with_jobs(JobQueue::new().register::<SendEmail>())
+//!     .discover()
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+
+pub mod entity;
+pub mod migration;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, LockBehavior, LockType};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, Condition, ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::database::DbError;
+use crate::error::Error;
+use crate::events::BoxFuture;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::state::AppState;
+
+use entity::{Column, Entity, FAILED, PENDING, RUNNING};
+
+/// Error returned by a failed job run.
+///
+/// Any error type converts into it with `?`.
+pub type JobError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Result of a job run.
+pub type JobResult = Result<(), JobError>;
+
+/// A unit of background work.
+///
+/// The struct itself is the payload: it is serialized to JSON when enqueued
+/// and deserialized again by the worker that runs it.
+pub trait Job: Serialize + DeserializeOwned + Send + 'static {
+    /// Name stored with each enqueued job, used to find the job's type when
+    /// it runs. Must be unique within the application and stable across
+    /// deploys.
+    const NAME: &'static str;
+
+    /// How many times the job runs before it is marked as failed.
+    const MAX_ATTEMPTS: u32 = 5;
+
+    /// Runs the job. Returning an error schedules a retry.
+    fn run(self, ctx: JobContext) -> impl Future<Output = JobResult> + Send;
+}
+
+/// What a running job can reach: application state and its own bookkeeping.
+pub struct JobContext {
+    state: Arc<AppState>,
+    conn: DatabaseConnection,
+    id: i64,
+    attempt: u32,
+}
+
+impl JobContext {
+    /// Returns the id of the job's row in `rapina_jobs`.
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Returns the current attempt, starting at 1.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Returns the application state.
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    /// Returns the database connection.
+    pub fn db(&self) -> &DatabaseConnection {
+        &self.conn
+    }
+
+    /// Returns a handle for enqueueing follow-up jobs.
+    pub fn jobs(&self) -> Jobs {
+        Jobs::new(self.conn.clone())
+    }
+}
+
+/// Handle for enqueueing jobs.
+///
+/// Works as an extractor in any app with a database; the jobs run wherever
+/// a [`JobQueue`] that registers them is started.
+#[derive(Clone)]
+pub struct Jobs {
+    conn: DatabaseConnection,
+}
+
+impl Jobs {
+    /// Creates a handle that enqueues jobs on `conn`.
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Enqueues a job to run as soon as a worker is free.
+    ///
+    /// Returns the id of the job's row.
+    pub async fn enqueue<J: Job>(&self, job: J) -> Result<i64, DbError> {
+        self.enqueue_at(job, Utc::now()).await
+    }
+
+    /// Enqueues a job to run after `delay`.
+    pub async fn enqueue_in<J: Job>(&self, job: J, delay: Duration) -> Result<i64, DbError> {
+        let delay = chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX);
+        self.enqueue_at(job, Utc::now() + delay).await
+    }
+
+    /// Enqueues a job to run at `run_at`, or as soon as possible if it is
+    /// in the past.
+    pub async fn enqueue_at<J: Job>(&self, job: J, run_at: DateTime<Utc>) -> Result<i64, DbError> {
+        let payload = serde_json::to_string(&job).map_err(|e| DbErr::Json(e.to_string()))?;
+        let now = Utc::now();
+        let row = entity::ActiveModel {
+            id: NotSet,
+            name: Set(J::NAME.to_string()),
+            payload: Set(payload),
+            status: Set(PENDING.to_string()),
+            attempts: Set(0),
+            max_attempts: Set(J::MAX_ATTEMPTS.min(i32::MAX as u32) as i32),
+            run_at: Set(run_at),
+            locked_at: Set(None),
+            last_error: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+        let result = Entity::insert(row).exec(&self.conn).await?;
+        Ok(result.last_insert_id)
+    }
+}
+
+impl FromRequestParts for Jobs {
+    async fn from_request_parts(
+        _parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let conn = state.get::<DatabaseConnection>().ok_or_else(|| {
+            Error::internal(
+                "Database connection not configured. Did you forget to call .with_database()?",
+            )
+        })?;
+        Ok(Jobs::new(conn.clone()))
+    }
+}
+
+type JobFn =
+    Arc<dyn Fn(&str, JobContext) -> Result<BoxFuture<'static, JobResult>, JobError> + Send + Sync>;
+
+/// Workers that run enqueued jobs.
+///
+/// Register every [`Job`] type the workers should run, then hand the queue
+/// to [`Rapina::with_jobs`](crate::app::Rapina::with_jobs). Workers start
+/// when the server starts listening; on shutdown they stop polling and
+/// finish the job they are running before the process exits.
+///
+/// # Example
+///
+/// ```ignore
+/// use std::time::Duration;
+/// use rapina::jobs::JobQueue;
+///
+/// let queue = JobQueue::new()
+///     .register::<SendEmail>()
+///     .register::<GenerateReport>()
+///     .workers(4)
+///     .poll_interval(Duration::from_millis(500));
+/// ```
+pub struct JobQueue {
+    handlers: HashMap<&'static str, JobFn>,
+    workers: usize,
+    poll_interval: Duration,
+    backoff: Duration,
+    max_backoff: Duration,
+    stale_after: Duration,
+}
+
+impl JobQueue {
+    /// Creates a queue with one worker that polls every second.
+    ///
+    /// Retries wait 1s, then 2s, 4s and so on, up to an hour.
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+            workers: 1,
+            poll_interval: Duration::from_secs(1),
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(3600),
+            stale_after: Duration::from_secs(15 * 60),
+        }
+    }
+
+    /// Registers a job type the workers can run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another job type is registered with the same name.
+    pub fn register<J: Job>(mut self) -> Self {
+        let handler: JobFn = Arc::new(|payload, ctx| {
+            let job: J = serde_json::from_str(payload)?;
+            Ok(Box::pin(job.run(ctx)))
+        });
+        if self.handlers.insert(J::NAME, handler).is_some() {
+            panic!("job '{}' is registered twice", J::NAME);
+        }
+        self
+    }
+
+    /// Sets how many jobs run at the same time. Defaults to 1.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Sets how long an idle worker waits before polling again.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets the delay before the first retry and the longest delay between
+    /// retries. The delay doubles after each failed attempt.
+    pub fn backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.backoff = base;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets how long a job may stay claimed before another worker assumes
+    /// its worker died and runs it again. Defaults to 15 minutes.
+    pub fn stale_after(mut self, timeout: Duration) -> Self {
+        self.stale_after = timeout;
+        self
+    }
+
+    /// Returns true if no job types have been registered.
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Spawns the workers.
+    ///
+    /// Fails if the state has no database connection.
+    pub fn start(self, state: Arc<AppState>) -> std::io::Result<JobQueueHandle> {
+        let conn = state.get::<DatabaseConnection>().cloned().ok_or_else(|| {
+            std::io::Error::other(
+                "The job queue needs a database. Call .with_database() before .listen()",
+            )
+        })?;
+
+        let (stop, stopped) = watch::channel(false);
+        let count = self.workers;
+        let worker = Arc::new(Worker {
+            queue: self,
+            conn,
+            state,
+        });
+        let mut names: Vec<_> = worker.queue.handlers.keys().copied().collect();
+        names.sort_unstable();
+        tracing::info!(jobs = ?names, workers = count, "Job queue started");
+
+        let workers = (0..count)
+            .map(|_| tokio::spawn(worker.clone().run(stopped.clone())))
+            .collect();
+        Ok(JobQueueHandle { stop, workers })
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle to the workers of a started [`JobQueue`].
+pub struct JobQueueHandle {
+    stop: watch::Sender<bool>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl JobQueueHandle {
+    /// Stops polling and waits for the jobs that are running to finish.
+    pub async fn shutdown(self) {
+        self.stop.send_replace(true);
+        for worker in self.workers {
+            let _ = worker.await;
+        }
+    }
+}
+
+/// How a claimed job ended.
+enum Outcome {
+    Done,
+    Retry(String),
+    Fail(String),
+}
+
+struct Worker {
+    queue: JobQueue,
+    conn: DatabaseConnection,
+    state: Arc<AppState>,
+}
+
+impl Worker {
+    async fn run(self: Arc<Self>, mut stop: watch::Receiver<bool>) {
+        while !*stop.borrow() {
+            match self.claim().await {
+                Ok(Some(job)) => {
+                    self.process(job).await;
+                    continue;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::error!(error = %e, "Failed to poll the job queue"),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(self.queue.poll_interval) => {}
+                _ = stop.changed() => {}
+            }
+        }
+    }
+
+    /// Marks the next due job as running and returns it.
+    ///
+    /// Also picks up jobs left running by a worker that stopped without
+    /// recording an outcome.
+    async fn claim(&self) -> Result<Option<entity::Model>, DbErr> {
+        let now = Utc::now();
+        let stale = now - chrono::Duration::from_std(self.queue.stale_after).unwrap_or_default();
+
+        let txn = self.conn.begin().await?;
+        let mut due = Entity::find()
+            .filter(
+                Condition::any()
+                    .add(Column::Status.eq(PENDING).and(Column::RunAt.lte(now)))
+                    .add(Column::Status.eq(RUNNING).and(Column::LockedAt.lt(stale))),
+            )
+            .order_by_asc(Column::RunAt)
+            .order_by_asc(Column::Id);
+        if txn.get_database_backend() == DatabaseBackend::Postgres {
+            due = due.lock_with_behavior(LockType::Update, LockBehavior::SkipLocked);
+        }
+        let Some(job) = due.one(&txn).await? else {
+            return Ok(None);
+        };
+
+        // The status and attempts guard stops two workers from claiming the
+        // same job where rows can't be locked
+        let claimed = Entity::update_many()
+            .col_expr(Column::Status, Expr::value(RUNNING))
+            .col_expr(Column::Attempts, Expr::col(Column::Attempts).add(1))
+            .col_expr(Column::LockedAt, Expr::value(now))
+            .col_expr(Column::UpdatedAt, Expr::value(now))
+            .filter(Column::Id.eq(job.id))
+            .filter(Column::Status.eq(job.status.as_str()))
+            .filter(Column::Attempts.eq(job.attempts))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+
+        if claimed.rows_affected == 0 {
+            return Ok(None);
+        }
+        Ok(Some(entity::Model {
+            status: RUNNING.to_string(),
+            attempts: job.attempts + 1,
+            locked_at: Some(now),
+            ..job
+        }))
+    }
+
+    async fn process(&self, job: entity::Model) {
+        let outcome = self.execute(&job).await;
+        let result = match &outcome {
+            Outcome::Done => {
+                tracing::debug!(job = %job.name, id = job.id, "Job finished");
+                Entity::delete_by_id(job.id)
+                    .exec(&self.conn)
+                    .await
+                    .map(|_| ())
+            }
+            Outcome::Retry(error) if job.attempts < job.max_attempts => {
+                let delay = self.backoff(job.attempts);
+                tracing::warn!(
+                    job = %job.name,
+                    id = job.id,
+                    attempt = job.attempts,
+                    retry_in = ?delay,
+                    error = %error,
+                    "Job failed, retrying"
+                );
+                let run_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+                self.release(&job, PENDING, run_at, error).await
+            }
+            Outcome::Retry(error) | Outcome::Fail(error) => {
+                tracing::error!(
+                    job = %job.name,
+                    id = job.id,
+                    attempts = job.attempts,
+                    error = %error,
+                    "Job failed"
+                );
+                self.release(&job, FAILED, job.run_at, error).await
+            }
+        };
+
+        if let Err(e) = result {
+            tracing::error!(job = %job.name, id = job.id, error = %e, "Failed to record job outcome");
+        }
+    }
+
+    async fn execute(&self, job: &entity::Model) -> Outcome {
+        if job.attempts > job.max_attempts {
+            return Outcome::Fail("worker stopped during the last attempt".to_string());
+        }
+        let Some(handler) = self.queue.handlers.get(job.name.as_str()) else {
+            return Outcome::Fail(format!("no job registered as '{}'", job.name));
+        };
+
+        let ctx = JobContext {
+            state: self.state.clone(),
+            conn: self.conn.clone(),
+            id: job.id,
+            attempt: job.attempts as u32,
+        };
+        let run = match handler(&job.payload, ctx) {
+            Ok(run) => run,
+            Err(e) => return Outcome::Fail(format!("invalid payload: {}", e)),
+        };
+
+        tracing::debug!(job = %job.name, id = job.id, attempt = job.attempts, "Running job");
+        match tokio::spawn(run).await {
+            Ok(Ok(())) => Outcome::Done,
+            Ok(Err(e)) => Outcome::Retry(e.to_string()),
+            Err(_) => Outcome::Retry("job panicked".to_string()),
+        }
+    }
+
+    async fn release(
+        &self,
+        job: &entity::Model,
+        status: &str,
+        run_at: DateTime<Utc>,
+        error: &str,
+    ) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(Column::Status, Expr::value(status))
+            .col_expr(Column::RunAt, Expr::value(run_at))
+            .col_expr(Column::LockedAt, Expr::value(Option::<DateTime<Utc>>::None))
+            .col_expr(Column::LastError, Expr::value(error))
+            .col_expr(Column::UpdatedAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(job.id))
+            .exec(&self.conn)
+            .await
+            .map(|_| ())
+    }
+
+    /// Delay before the attempt after `attempt`.
+    fn backoff(&self, attempt: i32) -> Duration {
+        let doublings = attempt.saturating_sub(1).clamp(0, 31) as u32;
+        self.queue
+            .backoff
+            .saturating_mul(1 << doublings)
+            .min(self.queue.max_backoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, serde::Deserialize)]
+    struct Noop;
+
+    impl Job for Noop {
+        const NAME: &'static str = "noop";
+
+        async fn run(self, _ctx: JobContext) -> JobResult {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let worker = Worker {
+            queue: JobQueue::new().backoff(Duration::from_secs(2), Duration::from_secs(10)),
+            conn: DatabaseConnection::Disconnected,
+            state: Arc::new(AppState::new()),
+        };
+
+        assert_eq!(worker.backoff(1), Duration::from_secs(2));
+        assert_eq!(worker.backoff(2), Duration::from_secs(4));
+        assert_eq!(worker.backoff(3), Duration::from_secs(8));
+        assert_eq!(worker.backoff(4), Duration::from_secs(10));
+        assert_eq!(worker.backoff(100), Duration::from_secs(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "job 'noop' is registered twice")]
+    fn test_register_rejects_duplicate_names() {
+        let _ = JobQueue::new().register::<Noop>().register::<Noop>();
+    }
+
+    #[test]
+    fn test_start_requires_database() {
+        let result = JobQueue::new()
+            .register::<Noop>()
+            .start(Arc::new(AppState::new()));
+
+        let error = result.err().unwrap();
+        assert!(error.to_string().contains(".with_database()"));
+    }
+}
@@ -0,0 +1,1413 @@
+//! App-wide JSON formatting: renamed keys and pretty output.
+//!
+//! [`Rapina::json_options`](crate::app::Rapina::json_options) sets how every
+//! [`Json`] response is written. With [`RenameRule::CamelCase`], struct
+//! fields are renamed while they are serialized, so a `created_at` field is
+//! sent as `createdAt` without adding `#[serde(rename_all)]` to each type.
+//! [`Json`] request bodies then accept either spelling, and the OpenAPI spec
+//! documents the renamed fields.
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::json::{JsonOptions, RenameRule};
+//!
+//! Rapina::new()
+//!     .json_options(JsonOptions {
+//!         rename_all: RenameRule::CamelCase,
+//!         pretty: cfg!(debug_assertions),
+//!     })
+//!     .router(router)
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+//!
+//! A single response can use other options with [`Json::with_options`].
+//!
+//! # What Gets Renamed
+//!
+//! Only struct fields are renamed, at any depth. Map keys, enum variants and
+//! fields that already carry a `#[serde(rename)]` spelling other than their
+//! Rust name pass through unchanged, and so do error responses. When reading
+//! request bodies, fields of structs inside `#[serde(flatten)]` and inside
+//! internally tagged or untagged enums must use their Rust names.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::{OnceLock, RwLock};
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess,
+    SeqAccess, VariantAccess, Visitor,
+};
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+use crate::error::Error;
+use crate::extract::Json;
+use crate::openapi::{OpenApiSpec, Schema};
+use crate::response::{BoxBody, IntoResponse};
+
+tokio::task_local! {
+    /// The app's options, set for the duration of each request.
+    static CURRENT: JsonOptions;
+
+    /// The app's response safeguards, set for the duration of each request.
+    static LIMITS: ResponseLimits;
+}
+
+/// How struct field names are written in JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RenameRule {
+    /// Keep the Rust field names.
+    #[default]
+    None,
+    /// `created_at` becomes `createdAt`.
+    CamelCase,
+}
+
+impl RenameRule {
+    /// Applies the rule to a snake_case field name.
+    pub fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            RenameRule::None => Cow::Borrowed(name),
+            RenameRule::CamelCase if !name.contains('_') => Cow::Borrowed(name),
+            RenameRule::CamelCase => {
+                let mut out = String::with_capacity(name.len());
+                let mut upper = false;
+                for c in name.chars() {
+                    if c == '_' {
+                        // Leading underscores have no word to capitalize
+                        upper = !out.is_empty();
+                    } else if upper {
+                        out.extend(c.to_uppercase());
+                        upper = false;
+                    } else {
+                        out.push(c);
+                    }
+                }
+                Cow::Owned(out)
+            }
+        }
+    }
+
+    /// The renamed field as a `&'static str`, which serde's struct
+    /// serializers require. Field names come from derives, so there is a
+    /// fixed set of them and each is allocated once.
+    fn apply_static(self, name: &'static str) -> &'static str {
+        static CACHE: OnceLock<RwLock<HashMap<(RenameRule, &'static str), &'static str>>> =
+            OnceLock::new();
+
+        // serde_json marks raw values with a `$serde_json::...` field
+        if name.starts_with('$') {
+            return name;
+        }
+        let Cow::Owned(renamed) = self.apply(name) else {
+            return name;
+        };
+        let cache = CACHE.get_or_init(Default::default);
+        if let Some(renamed) = cache.read().unwrap().get(&(self, name)) {
+            return renamed;
+        }
+        cache
+            .write()
+            .unwrap()
+            .entry((self, name))
+            .or_insert_with(|| Box::leak(renamed.into_boxed_str()))
+    }
+}
+
+/// JSON formatting for [`Json`] responses and request bodies.
+///
+/// Set app-wide with [`Rapina::json_options`](crate::app::Rapina::json_options),
+/// or for one response with [`Json::with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JsonOptions {
+    /// How struct field names are written.
+    pub rename_all: RenameRule,
+    /// Indent the output for reading.
+    pub pretty: bool,
+}
+
+impl JsonOptions {
+    /// The options of the app handling the current request, or the
+    /// defaults outside of one.
+    pub fn current() -> Self {
+        CURRENT.try_with(|options| *options).unwrap_or_default()
+    }
+
+    /// Runs `future` with these as the [`current`](Self::current) options.
+    pub(crate) async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
+    /// Serializes `value` with these options.
+    pub fn to_vec<T: Serialize + ?Sized>(&self, value: &T) -> serde_json::Result<Vec<u8>> {
+        self.serialize_limited(value, usize::MAX)
+            .map_err(|err| match err {
+                WriteError::Serialize(err) => err,
+                WriteError::TooLarge => serde_json::Error::io(io::Error::other("output too large")),
+            })
+    }
+
+    /// Serializes `value`, giving up as soon as the output grows past
+    /// `max_size` bytes.
+    fn serialize_limited<T: Serialize + ?Sized>(
+        &self,
+        value: &T,
+        max_size: usize,
+    ) -> Result<Vec<u8>, WriteError> {
+        let mut out = LimitedWriter {
+            buf: Vec::with_capacity(128.min(max_size)),
+            max_size,
+            exceeded: false,
+        };
+        let result = if self.pretty {
+            let mut serializer = serde_json::Serializer::pretty(&mut out);
+            value.serialize(Rename::new(&mut serializer, self.rename_all))
+        } else {
+            let mut serializer = serde_json::Serializer::new(&mut out);
+            value.serialize(Rename::new(&mut serializer, self.rename_all))
+        };
+        match result {
+            Ok(()) => Ok(out.buf),
+            Err(_) if out.exceeded => Err(WriteError::TooLarge),
+            Err(err) => Err(WriteError::Serialize(err)),
+        }
+    }
+
+    /// Deserializes `bytes`, accepting both the Rust and the renamed field
+    /// names.
+    pub fn from_slice<T: DeserializeOwned>(&self, bytes: &[u8]) -> serde_json::Result<T> {
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        let value = T::deserialize(Unrename {
+            inner: &mut deserializer,
+            rule: self.rename_all,
+        })?;
+        deserializer.end()?;
+        Ok(value)
+    }
+
+    /// Renames the properties of the JSON schemas in `spec` to match the
+    /// wire format.
+    pub(crate) fn rename_spec(&self, spec: &mut OpenApiSpec) {
+        if self.rename_all == RenameRule::None {
+            return;
+        }
+        let operations = spec.paths.values_mut().flat_map(|item| {
+            [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.patch,
+                &mut item.delete,
+            ]
+            .into_iter()
+            .flatten()
+        });
+        for operation in operations {
+            let requests = operation
+                .request_body
+                .iter_mut()
+                .map(|body| &mut body.content);
+            let responses = operation
+                .responses
+                .values_mut()
+                .filter_map(|response| response.content.as_mut());
+            for (media_type, body) in requests.chain(responses).flatten() {
+                if let (true, Schema::Inline(schema)) =
+                    (media_type.contains("json"), &mut body.schema)
+                {
+                    self.rename_schema(schema);
+                }
+            }
+        }
+    }
+
+    /// Renames the `properties` and `required` entries of a JSON schema and
+    /// the schemas nested in it.
+    fn rename_schema(&self, schema: &mut serde_json::Value) {
+        let Some(object) = schema.as_object_mut() else {
+            return;
+        };
+
+        if let Some(serde_json::Value::Object(properties)) = object.get_mut("properties") {
+            let renamed = std::mem::take(properties)
+                .into_iter()
+                .map(|(name, mut property)| {
+                    self.rename_schema(&mut property);
+                    (self.rename_all.apply(&name).into_owned(), property)
+                })
+                .collect();
+            *properties = renamed;
+        }
+        if let Some(serde_json::Value::Array(required)) = object.get_mut("required") {
+            for name in required.iter_mut() {
+                if let Some(renamed) = name.as_str().map(|n| self.rename_all.apply(n).into_owned())
+                {
+                    *name = renamed.into();
+                }
+            }
+        }
+
+        for key in ["items", "additionalProperties", "not"] {
+            if let Some(nested) = object.get_mut(key) {
+                self.rename_schema(nested);
+            }
+        }
+        for key in ["allOf", "anyOf", "oneOf", "prefixItems"] {
+            if let Some(serde_json::Value::Array(nested)) = object.get_mut(key) {
+                nested.iter_mut().for_each(|s| self.rename_schema(s));
+            }
+        }
+        for key in ["$defs", "definitions"] {
+            if let Some(serde_json::Value::Object(nested)) = object.get_mut(key) {
+                nested.values_mut().for_each(|s| self.rename_schema(s));
+            }
+        }
+    }
+}
+
+/// Safeguards for response bodies, set with
+/// [`Rapina::max_response_size`](crate::app::Rapina::max_response_size) and
+/// [`Rapina::expose_serialization_errors`](crate::app::Rapina::expose_serialization_errors).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct ResponseLimits {
+    /// Largest body, in bytes, that is sent.
+    pub(crate) max_size: Option<usize>,
+    /// Whether serialization failures put the serde error in the response.
+    pub(crate) expose_errors: bool,
+}
+
+impl ResponseLimits {
+    /// The limits of the app handling the current request, or the defaults
+    /// outside of one.
+    pub(crate) fn current() -> Self {
+        LIMITS.try_with(|limits| *limits).unwrap_or_default()
+    }
+
+    /// Runs `future` with these as the [`current`](Self::current) limits.
+    pub(crate) async fn scope<F: Future>(self, future: F) -> F::Output {
+        LIMITS.scope(self, future).await
+    }
+
+    /// Replaces a buffered response larger than the limit.
+    ///
+    /// JSON bodies are stopped while they are serialized; this catches
+    /// everything else a handler built in memory. Streams are left alone.
+    pub(crate) fn check(&self, response: http::Response<BoxBody>) -> http::Response<BoxBody> {
+        use hyper::body::Body;
+
+        let Some(max_size) = self.max_size else {
+            return response;
+        };
+        match response.body().size_hint().exact() {
+            Some(size) if !response.body().is_stream() && size > max_size as u64 => {
+                tracing::error!(
+                    size,
+                    max_size,
+                    "Response body exceeds the maximum response size"
+                );
+                response_too_large(max_size).into_response()
+            }
+            _ => response,
+        }
+    }
+}
+
+/// Why [`JsonOptions::serialize_limited`] gave up.
+enum WriteError {
+    Serialize(serde_json::Error),
+    TooLarge,
+}
+
+/// A buffer that refuses writes past `max_size` bytes.
+struct LimitedWriter {
+    buf: Vec<u8>,
+    max_size: usize,
+    exceeded: bool,
+}
+
+impl io::Write for LimitedWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if data.len() > self.max_size - self.buf.len() {
+            self.exceeded = true;
+            return Err(io::Error::other("response exceeds the maximum size"));
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn response_too_large(max_size: usize) -> Error {
+    Error::new(
+        500,
+        "RESPONSE_TOO_LARGE",
+        format!("Response exceeds the maximum size of {} bytes", max_size),
+    )
+}
+
+impl<T> Json<T> {
+    /// Serializes this response with `options` instead of the app's.
+    ///
+    /// The OpenAPI spec still documents the route with the app's options.
+    pub fn with_options(self, options: JsonOptions) -> JsonWith<T> {
+        JsonWith {
+            value: self.0,
+            options,
+        }
+    }
+}
+
+/// A JSON response with its own [`JsonOptions`], made by
+/// [`Json::with_options`].
+#[derive(Debug)]
+pub struct JsonWith<T> {
+    value: T,
+    options: JsonOptions,
+}
+
+impl<T> JsonWith<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Serialize> IntoResponse for JsonWith<T> {
+    fn into_response(self) -> http::Response<BoxBody> {
+        json_response(http::StatusCode::OK, &self.value, self.options)
+    }
+}
+
+/// A JSON response with `status`, serialized with `options`.
+pub(crate) fn json_response<T: Serialize + ?Sized>(
+    status: http::StatusCode,
+    value: &T,
+    options: JsonOptions,
+) -> http::Response<BoxBody> {
+    let limits = ResponseLimits::current();
+    let max_size = limits.max_size.unwrap_or(usize::MAX);
+    let type_name = std::any::type_name::<T>();
+
+    match options.serialize_limited(value, max_size) {
+        Ok(body) => http::Response::builder()
+            .status(status)
+            .header("content-type", "application/json")
+            .body(BoxBody::new(body))
+            .unwrap(),
+        Err(WriteError::TooLarge) => {
+            tracing::error!(
+                r#type = type_name,
+                max_size,
+                "JSON response exceeds the maximum response size"
+            );
+            response_too_large(max_size).into_response()
+        }
+        Err(WriteError::Serialize(err)) => {
+            tracing::error!(
+                r#type = type_name,
+                error = %err,
+                "Failed to serialize JSON response"
+            );
+            let mut error = Error::new(
+                500,
+                "SERIALIZATION_ERROR",
+                "Failed to serialize the response",
+            );
+            if limits.expose_errors {
+                error = error.with_details(serde_json::json!({
+                    "type": type_name,
+                    "error": err.to_string(),
+                }));
+            }
+            error.into_response()
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Renaming serializer
+// ---------------------------------------------------------------------------
+
+/// A serializer that renames struct fields and passes everything else
+/// through to `inner`, wrapping nested values so they are renamed too.
+struct Rename<S> {
+    inner: S,
+    rule: RenameRule,
+}
+
+impl<S> Rename<S> {
+    fn new(inner: S, rule: RenameRule) -> Self {
+        Self { inner, rule }
+    }
+}
+
+/// A value serialized through [`Rename`].
+struct Renamed<'a, T: ?Sized> {
+    value: &'a T,
+    rule: RenameRule,
+}
+
+impl<T: Serialize + ?Sized> Serialize for Renamed<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(Rename::new(serializer, self.rule))
+    }
+}
+
+/// Wraps `value` so it is serialized through [`Rename`].
+fn renamed<T: ?Sized>(value: &T, rule: RenameRule) -> Renamed<'_, T> {
+    Renamed { value, rule }
+}
+
+impl<S: SerializeSeq> SerializeSeq for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.inner.serialize_element(&renamed(value, self.rule))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeTuple> SerializeTuple for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.inner.serialize_element(&renamed(value, self.rule))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeTupleStruct> SerializeTupleStruct for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.inner.serialize_field(&renamed(value, self.rule))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeTupleVariant> SerializeTupleVariant for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.inner.serialize_field(&renamed(value, self.rule))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeMap> SerializeMap for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), S::Error> {
+        self.inner.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.inner.serialize_value(&renamed(value, self.rule))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeStruct> SerializeStruct for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        self.inner
+            .serialize_field(self.rule.apply_static(key), &renamed(value, self.rule))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        self.inner.skip_field(self.rule.apply_static(key))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeStructVariant> SerializeStructVariant for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        self.inner
+            .serialize_field(self.rule.apply_static(key), &renamed(value, self.rule))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        self.inner.skip_field(self.rule.apply_static(key))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: Serializer> Serializer for Rename<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Rename<S::SerializeSeq>;
+    type SerializeTuple = Rename<S::SerializeTuple>;
+    type SerializeTupleStruct = Rename<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Rename<S::SerializeTupleVariant>;
+    type SerializeMap = Rename<S::SerializeMap>;
+    type SerializeStruct = Rename<S::SerializeStruct>;
+    type SerializeStructVariant = Rename<S::SerializeStructVariant>;
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(Rename::new(
+            self.inner.serialize_struct(name, len)?,
+            self.rule,
+        ))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        Ok(Rename::new(
+            self.inner
+                .serialize_struct_variant(name, variant_index, variant, len)?,
+            self.rule,
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(Rename::new(self.inner.serialize_seq(len)?, self.rule))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(Rename::new(self.inner.serialize_tuple(len)?, self.rule))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        Ok(Rename::new(
+            self.inner.serialize_tuple_struct(name, len)?,
+            self.rule,
+        ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        Ok(Rename::new(
+            self.inner
+                .serialize_tuple_variant(name, variant_index, variant, len)?,
+            self.rule,
+        ))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        Ok(Rename::new(self.inner.serialize_map(len)?, self.rule))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_some(&renamed(value, self.rule))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_newtype_struct(name, &renamed(value, self.rule))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_variant(
+            name,
+            variant_index,
+            variant,
+            &renamed(value, self.rule),
+        )
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u128(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f32(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f64(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Deserializer accepting renamed fields
+// ---------------------------------------------------------------------------
+
+/// A deserializer that maps renamed struct field names back to the Rust
+/// names before the struct sees them.
+struct Unrename<D> {
+    inner: D,
+    rule: RenameRule,
+}
+
+/// Wraps a visitor so everything it is handed goes through [`Unrename`].
+/// `fields` is set when the visitor is reading a struct with those fields.
+struct UnrenameVisitor<V> {
+    inner: V,
+    rule: RenameRule,
+    fields: Option<&'static [&'static str]>,
+}
+
+/// Wraps a seed so the value it reads goes through [`Unrename`].
+struct UnrenameSeed<T> {
+    inner: T,
+    rule: RenameRule,
+}
+
+/// Reads a struct's field name, mapping a renamed one to its Rust name.
+struct FieldSeed<T> {
+    inner: T,
+    rule: RenameRule,
+    fields: &'static [&'static str],
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+                self.inner.$method(UnrenameVisitor {
+                    inner: visitor,
+                    rule: self.rule,
+                    fields: None,
+                })
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Unrename<D> {
+    type Error = D::Error;
+
+    forward_deserialize!(
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_seq,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    );
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_unit_struct(
+            name,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: None,
+            },
+        )
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_newtype_struct(
+            name,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: None,
+            },
+        )
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_tuple(
+            len,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: None,
+            },
+        )
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_tuple_struct(
+            name,
+            len,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: None,
+            },
+        )
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_struct(
+            name,
+            fields,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: Some(fields),
+            },
+        )
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_enum(
+            name,
+            variants,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: None,
+            },
+        )
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for UnrenameVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<V::Value, E> {
+        self.inner.visit_bool(v)
+    }
+
+    fn visit_i8<E: de::Error>(self, v: i8) -> Result<V::Value, E> {
+        self.inner.visit_i8(v)
+    }
+
+    fn visit_i16<E: de::Error>(self, v: i16) -> Result<V::Value, E> {
+        self.inner.visit_i16(v)
+    }
+
+    fn visit_i32<E: de::Error>(self, v: i32) -> Result<V::Value, E> {
+        self.inner.visit_i32(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<V::Value, E> {
+        self.inner.visit_i64(v)
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<V::Value, E> {
+        self.inner.visit_i128(v)
+    }
+
+    fn visit_u8<E: de::Error>(self, v: u8) -> Result<V::Value, E> {
+        self.inner.visit_u8(v)
+    }
+
+    fn visit_u16<E: de::Error>(self, v: u16) -> Result<V::Value, E> {
+        self.inner.visit_u16(v)
+    }
+
+    fn visit_u32<E: de::Error>(self, v: u32) -> Result<V::Value, E> {
+        self.inner.visit_u32(v)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<V::Value, E> {
+        self.inner.visit_u64(v)
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<V::Value, E> {
+        self.inner.visit_u128(v)
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<V::Value, E> {
+        self.inner.visit_f32(v)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<V::Value, E> {
+        self.inner.visit_f64(v)
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<V::Value, E> {
+        self.inner.visit_char(v)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<V::Value, E> {
+        self.inner.visit_str(v)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<V::Value, E> {
+        self.inner.visit_borrowed_str(v)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+        self.inner.visit_string(v)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<V::Value, E> {
+        self.inner.visit_bytes(v)
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<V::Value, E> {
+        self.inner.visit_borrowed_bytes(v)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<V::Value, E> {
+        self.inner.visit_byte_buf(v)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_some(Unrename {
+            inner: deserializer,
+            rule: self.rule,
+        })
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.visit_newtype_struct(Unrename {
+            inner: deserializer,
+            rule: self.rule,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(UnrenameVisitor {
+            inner: seq,
+            rule: self.rule,
+            fields: None,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_map(UnrenameVisitor {
+            inner: map,
+            rule: self.rule,
+            fields: self.fields,
+        })
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_enum(UnrenameVisitor {
+            inner: data,
+            rule: self.rule,
+            fields: None,
+        })
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for UnrenameVisitor<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        self.inner.next_element_seed(UnrenameSeed {
+            inner: seed,
+            rule: self.rule,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for UnrenameVisitor<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        match self.fields {
+            Some(fields) => self.inner.next_key_seed(FieldSeed {
+                inner: seed,
+                rule: self.rule,
+                fields,
+            }),
+            None => self.inner.next_key_seed(seed),
+        }
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.inner.next_value_seed(UnrenameSeed {
+            inner: seed,
+            rule: self.rule,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for UnrenameVisitor<A> {
+    type Error = A::Error;
+    type Variant = UnrenameVisitor<A::Variant>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Self::Variant), A::Error> {
+        let (value, variant) = self.inner.variant_seed(seed)?;
+        Ok((
+            value,
+            UnrenameVisitor {
+                inner: variant,
+                rule: self.rule,
+                fields: None,
+            },
+        ))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for UnrenameVisitor<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        self.inner.newtype_variant_seed(UnrenameSeed {
+            inner: seed,
+            rule: self.rule,
+        })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        self.inner.tuple_variant(
+            len,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: None,
+            },
+        )
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.inner.struct_variant(
+            fields,
+            UnrenameVisitor {
+                inner: visitor,
+                rule: self.rule,
+                fields: Some(fields),
+            },
+        )
+    }
+}
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for UnrenameSeed<T> {
+    type Value = T::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T::Value, D::Error> {
+        self.inner.deserialize(Unrename {
+            inner: deserializer,
+            rule: self.rule,
+        })
+    }
+}
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for FieldSeed<T> {
+    type Value = T::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T::Value, D::Error> {
+        let key = deserializer.deserialize_identifier(KeyVisitor)?;
+        let field = self
+            .fields
+            .iter()
+            .find(|field| **field != key && self.rule.apply(field) == key);
+        match (field, key) {
+            (Some(field), _) => self.inner.deserialize(field.into_deserializer()),
+            (None, Cow::Borrowed(key)) => self
+                .inner
+                .deserialize(de::value::BorrowedStrDeserializer::new(key)),
+            (None, Cow::Owned(key)) => self.inner.deserialize(key.into_deserializer()),
+        }
+    }
+}
+
+/// Reads a map key as a string.
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = Cow<'de, str>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a field name")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v.to_string()))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Address {
+        street_name: String,
+        zip_code: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Role {
+        Admin,
+        Custom { role_name: String },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        user_id: i32,
+        first_name: String,
+        #[serde(rename = "mail")]
+        email_address: String,
+        addresses: Vec<Address>,
+        roles: Vec<Role>,
+        extra_data: BTreeMap<String, i32>,
+    }
+
+    fn user() -> User {
+        User {
+            user_id: 1,
+            first_name: "Ada".to_string(),
+            email_address: "ada@example.com".to_string(),
+            addresses: vec![Address {
+                street_name: "Main St".to_string(),
+                zip_code: None,
+            }],
+            roles: vec![
+                Role::Admin,
+                Role::Custom {
+                    role_name: "ops".to_string(),
+                },
+            ],
+            extra_data: BTreeMap::from([("login_count".to_string(), 3)]),
+        }
+    }
+
+    fn camel_case() -> JsonOptions {
+        JsonOptions {
+            rename_all: RenameRule::CamelCase,
+            pretty: false,
+        }
+    }
+
+    #[test]
+    fn test_camel_case_rule() {
+        let rule = RenameRule::CamelCase;
+        assert_eq!(rule.apply("created_at"), "createdAt");
+        assert_eq!(rule.apply("id"), "id");
+        assert_eq!(rule.apply("a_b_c"), "aBC");
+        assert_eq!(rule.apply("_private_field"), "privateField");
+        assert_eq!(rule.apply("field_2"), "field2");
+        assert_eq!(RenameRule::None.apply("created_at"), "created_at");
+    }
+
+    #[test]
+    fn test_serialize_renames_struct_fields_only() {
+        let json = String::from_utf8(camel_case().to_vec(&user()).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"userId":1,"firstName":"Ada","mail":"ada@example.com","addresses":[{"streetName":"Main St","zipCode":null}],"roles":["Admin",{"Custom":{"roleName":"ops"}}],"extraData":{"login_count":3}}"#
+        );
+    }
+
+    #[test]
+    fn test_default_options_match_serde_json() {
+        let bytes = JsonOptions::default().to_vec(&user()).unwrap();
+        assert_eq!(bytes, serde_json::to_vec(&user()).unwrap());
+    }
+
+    #[test]
+    fn test_pretty() {
+        let options = JsonOptions {
+            pretty: true,
+            ..camel_case()
+        };
+        let json = String::from_utf8(options.to_vec(&user()).unwrap()).unwrap();
+        assert!(json.starts_with("{\n  \"userId\": 1,\n  \"firstName\": \"Ada\","));
+    }
+
+    #[test]
+    fn test_deserialize_accepts_both_cases() {
+        let camel = camel_case().to_vec(&user()).unwrap();
+        assert_eq!(camel_case().from_slice::<User>(&camel).unwrap(), user());
+
+        let snake = serde_json::to_vec(&user()).unwrap();
+        assert_eq!(camel_case().from_slice::<User>(&snake).unwrap(), user());
+    }
+
+    #[test]
+    fn test_deserialize_keeps_map_keys() {
+        let body = br#"{"userId":1,"firstName":"Ada","mail":"a","addresses":[],"roles":[],"extraData":{"loginCount":3}}"#;
+        let user: User = camel_case().from_slice(body).unwrap();
+        assert_eq!(
+            user.extra_data,
+            BTreeMap::from([("loginCount".to_string(), 3)])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        assert!(camel_case().from_slice::<User>(b"{\"userId\":1}").is_err());
+        assert!(
+            camel_case()
+                .from_slice::<Address>(b"{\"streetName\":\"x\"} x")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rename_schema() {
+        let mut schema = serde_json::json!({
+            "type": "object",
+            "required": ["user_id", "addresses"],
+            "properties": {
+                "user_id": { "type": "integer" },
+                "addresses": { "type": "array", "items": { "$ref": "#/$defs/Address" } },
+                "extra_data": { "type": "object", "additionalProperties": { "type": "integer" } },
+            },
+            "$defs": {
+                "Address": {
+                    "type": "object",
+                    "required": ["street_name"],
+                    "properties": { "street_name": { "type": "string" } },
+                },
+            },
+        });
+        camel_case().rename_schema(&mut schema);
+
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["userId", "addresses"])
+        );
+        assert!(schema["properties"]["userId"].is_object());
+        assert!(schema["properties"]["extraData"].is_object());
+        assert!(schema["properties"].get("user_id").is_none());
+        assert!(schema["$defs"]["Address"]["properties"]["streetName"].is_object());
+        assert_eq!(
+            schema["$defs"]["Address"]["required"],
+            serde_json::json!(["streetName"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_current_is_scoped() {
+        assert_eq!(JsonOptions::current(), JsonOptions::default());
+        let current = camel_case().scope(async { JsonOptions::current() }).await;
+        assert_eq!(current, camel_case());
+    }
+}
@@ -0,0 +1,416 @@
+//! Running Rapina applications on AWS Lambda.
+//!
+//! [`run`] takes the place of [`Rapina::listen`](crate::app::Rapina::listen)
+//! when the app is deployed behind API Gateway (HTTP API, payload format 2.0)
+//! or an Application Load Balancer. Each event is converted into an HTTP
+//! request, dispatched through the router and middleware stack, and the
+//! response converted back into the event format the trigger expects.
+//!
+//! The app is prepared once, before the first event is polled, so state and
+//! connection pools are built during the cold start rather than per request.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use rapina::prelude::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), lambda_runtime::Error> {
+//!     let app = Rapina::new().discover();
+//!     rapina::lambda::run(app).await
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Response, header};
+use http_body_util::{BodyExt, Full};
+use lambda_runtime::LambdaEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app::Rapina;
+use crate::response::BoxBody;
+use crate::service::RapinaService;
+
+/// Prepares the app and serves Lambda invocations until the runtime stops.
+pub async fn run(app: Rapina) -> Result<(), lambda_runtime::Error> {
+    let service = app.into_service()?;
+
+    lambda_runtime::run(lambda_runtime::service_fn(
+        move |event: LambdaEvent<Value>| {
+            let service = service.clone();
+            async move { handle_event(&service, event.payload).await }
+        },
+    ))
+    .await
+}
+
+/// Handles a single API Gateway v2 or ALB event.
+///
+/// Exposed so the conversion can be exercised without the Lambda runtime,
+/// e.g. by feeding recorded events in tests.
+pub async fn handle_event(
+    service: &RapinaService,
+    event: Value,
+) -> Result<Value, lambda_runtime::Error> {
+    let request_context = event.get("requestContext");
+
+    if request_context.is_some_and(|ctx| ctx.get("http").is_some()) {
+        let event: ApiGatewayV2Request = serde_json::from_value(event)?;
+        let response = service.handle(event.to_request()?).await;
+        Ok(serde_json::to_value(
+            ApiGatewayV2Response::from_response(response).await?,
+        )?)
+    } else if request_context.is_some_and(|ctx| ctx.get("elb").is_some()) {
+        let event: AlbRequest = serde_json::from_value(event)?;
+        let multi_value = event.multi_value_headers.is_some();
+        let response = service.handle(event.to_request()?).await;
+        Ok(serde_json::to_value(
+            AlbResponse::from_response(response, multi_value).await?,
+        )?)
+    } else {
+        Err("unsupported Lambda event: expected an API Gateway v2 or ALB request".into())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiGatewayV2Request {
+    raw_path: String,
+    #[serde(default)]
+    raw_query_string: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    cookies: Vec<String>,
+    body: Option<String>,
+    #[serde(default)]
+    is_base64_encoded: bool,
+    request_context: ApiGatewayV2Context,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiGatewayV2Context {
+    http: ApiGatewayV2Http,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiGatewayV2Http {
+    method: String,
+}
+
+impl ApiGatewayV2Request {
+    fn to_request(&self) -> Result<Request<Full<Bytes>>, lambda_runtime::Error> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            headers.append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+        }
+        // Payload format 2.0 moves cookies out of the headers
+        if !self.cookies.is_empty() {
+            headers.insert(
+                header::COOKIE,
+                HeaderValue::try_from(self.cookies.join("; "))?,
+            );
+        }
+
+        build_request(
+            &self.request_context.http.method,
+            &self.raw_path,
+            &self.raw_query_string,
+            headers,
+            decode_body(self.body.as_deref(), self.is_base64_encoded)?,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlbRequest {
+    http_method: String,
+    path: String,
+    query_string_parameters: Option<HashMap<String, String>>,
+    multi_value_query_string_parameters: Option<HashMap<String, Vec<String>>>,
+    headers: Option<HashMap<String, String>>,
+    multi_value_headers: Option<HashMap<String, Vec<String>>>,
+    body: Option<String>,
+    #[serde(default)]
+    is_base64_encoded: bool,
+}
+
+impl AlbRequest {
+    fn to_request(&self) -> Result<Request<Full<Bytes>>, lambda_runtime::Error> {
+        let mut headers = HeaderMap::new();
+        if let Some(multi) = &self.multi_value_headers {
+            for (name, values) in multi {
+                for value in values {
+                    headers.append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+                }
+            }
+        } else if let Some(single) = &self.headers {
+            for (name, value) in single {
+                headers.append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+            }
+        }
+
+        // ALB passes query parameters through as the client sent them, so
+        // they are joined back without re-encoding
+        let mut pairs = Vec::new();
+        if let Some(multi) = &self.multi_value_query_string_parameters {
+            for (key, values) in multi {
+                pairs.extend(values.iter().map(|value| format!("{}={}", key, value)));
+            }
+        } else if let Some(single) = &self.query_string_parameters {
+            pairs.extend(
+                single
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value)),
+            );
+        }
+
+        build_request(
+            &self.http_method,
+            &self.path,
+            &pairs.join("&"),
+            headers,
+            decode_body(self.body.as_deref(), self.is_base64_encoded)?,
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiGatewayV2Response {
+    status_code: u16,
+    headers: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cookies: Vec<String>,
+    body: String,
+    is_base64_encoded: bool,
+}
+
+impl ApiGatewayV2Response {
+    async fn from_response(response: Response<BoxBody>) -> Result<Self, lambda_runtime::Error> {
+        let (parts, body) = response.into_parts();
+        let (body, is_base64_encoded) = encode_body(&parts.headers, body).await?;
+
+        let mut headers: HashMap<String, String> = HashMap::new();
+        let mut cookies = Vec::new();
+        for (name, value) in &parts.headers {
+            let value = value.to_str()?.to_string();
+            if name == header::SET_COOKIE {
+                cookies.push(value);
+            } else if let Some(existing) = headers.get_mut(name.as_str()) {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            } else {
+                headers.insert(name.to_string(), value);
+            }
+        }
+
+        Ok(Self {
+            status_code: parts.status.as_u16(),
+            headers,
+            cookies,
+            body,
+            is_base64_encoded,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AlbResponse {
+    status_code: u16,
+    status_description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    multi_value_headers: Option<HashMap<String, Vec<String>>>,
+    body: String,
+    is_base64_encoded: bool,
+}
+
+impl AlbResponse {
+    /// ALB expects the response headers in the same shape as the request's:
+    /// `multiValueHeaders` when the target group has multi-value headers on.
+    async fn from_response(
+        response: Response<BoxBody>,
+        multi_value: bool,
+    ) -> Result<Self, lambda_runtime::Error> {
+        let (parts, body) = response.into_parts();
+        let (body, is_base64_encoded) = encode_body(&parts.headers, body).await?;
+
+        let mut grouped: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, value) in &parts.headers {
+            grouped
+                .entry(name.to_string())
+                .or_default()
+                .push(value.to_str()?.to_string());
+        }
+
+        let (headers, multi_value_headers) = if multi_value {
+            (None, Some(grouped))
+        } else {
+            let single = grouped
+                .into_iter()
+                .filter_map(|(name, mut values)| values.pop().map(|value| (name, value)))
+                .collect();
+            (Some(single), None)
+        };
+
+        Ok(Self {
+            status_code: parts.status.as_u16(),
+            status_description: parts.status.to_string(),
+            headers,
+            multi_value_headers,
+            body,
+            is_base64_encoded,
+        })
+    }
+}
+
+fn build_request(
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Request<Full<Bytes>>, lambda_runtime::Error> {
+    let uri = if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, query)
+    };
+
+    let mut request = Request::builder()
+        .method(Method::from_bytes(method.as_bytes())?)
+        .uri(uri)
+        .body(Full::new(body))?;
+    *request.headers_mut() = headers;
+    Ok(request)
+}
+
+fn decode_body(
+    body: Option<&str>,
+    is_base64_encoded: bool,
+) -> Result<Bytes, lambda_runtime::Error> {
+    match body {
+        None => Ok(Bytes::new()),
+        Some(body) if is_base64_encoded => Ok(Bytes::from(BASE64.decode(body)?)),
+        Some(body) => Ok(Bytes::copy_from_slice(body.as_bytes())),
+    }
+}
+
+/// Returns the body as a string, base64-encoding it unless it is text.
+async fn encode_body(
+    headers: &HeaderMap,
+    body: BoxBody,
+) -> Result<(String, bool), lambda_runtime::Error> {
+    let bytes = body.collect().await?.to_bytes();
+
+    if is_text(headers)
+        && let Ok(text) = std::str::from_utf8(&bytes)
+    {
+        return Ok((text.to_string(), false));
+    }
+
+    if bytes.is_empty() {
+        return Ok((String::new(), false));
+    }
+
+    Ok((BASE64.encode(&bytes), true))
+}
+
+fn is_text(headers: &HeaderMap) -> bool {
+    if headers.contains_key(header::CONTENT_ENCODING) {
+        return false;
+    }
+
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return true;
+    };
+
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime.as_str(),
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/x-www-form-urlencoded"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(content_type: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(content_type).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_is_text_content_types() {
+        assert!(is_text(&headers("application/json")));
+        assert!(is_text(&headers("text/plain; charset=utf-8")));
+        assert!(is_text(&headers("application/problem+json")));
+        assert!(!is_text(&headers("image/png")));
+        assert!(!is_text(&headers("application/octet-stream")));
+        assert!(is_text(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_compressed_body_is_not_text() {
+        let mut headers = headers("application/json");
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        assert!(!is_text(&headers));
+    }
+
+    #[test]
+    fn test_decode_body() {
+        assert_eq!(decode_body(None, false).unwrap(), Bytes::new());
+        assert_eq!(decode_body(Some("hi"), false).unwrap().as_ref(), b"hi");
+        assert_eq!(decode_body(Some("aGk="), true).unwrap().as_ref(), b"hi");
+        assert!(decode_body(Some("not base64!"), true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encode_binary_body() {
+        let (body, encoded) = encode_body(
+            &headers("image/png"),
+            BoxBody::new(Bytes::from_static(&[0x89, b'P', b'N', b'G'])),
+        )
+        .await
+        .unwrap();
+        assert!(encoded);
+        assert_eq!(body, "iVBORw==");
+    }
+
+    #[test]
+    fn test_build_request_with_query() {
+        let request =
+            build_request("GET", "/users", "page=2", HeaderMap::new(), Bytes::new()).unwrap();
+        assert_eq!(request.method(), Method::GET);
+        assert_eq!(request.uri(), "/users?page=2");
+    }
+}
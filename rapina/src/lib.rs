@@ -48,6 +48,8 @@
 //! Rapina provides several extractors for parsing request data:
 //!
 //! - [`Json`](extract::Json) - Parse JSON request bodies
+//! - [`RawJson`](extract::RawJson) - Parse JSON while keeping the raw body bytes
+//! - [`Bytes`](extract::Bytes) and `String` - Read the raw request body
 //! - [`Path`](extract::Path) - Extract path parameters
 //! - [`Query`](extract::Query) - Parse query string parameters
 //! - [`Form`](extract::Form) - Parse URL-encoded form data
@@ -56,6 +58,7 @@
 //! - [`State`](extract::State) - Access application state
 //! - [`Context`](extract::Context) - Access request context with trace_id
 //! - [`Validated`](extract::Validated) - Validate extracted data
+//! - [`MergePatch`](patch::MergePatch) and [`JsonPatch`](patch::JsonPatch) - Read `PATCH` bodies
 //!
 //! ## Middleware
 //!
@@ -81,27 +84,49 @@
 
 pub mod app;
 pub mod auth;
+pub mod cache;
 pub mod config;
 pub mod context;
 #[cfg(feature = "database")]
 pub mod database;
 pub mod discovery;
+pub mod drain;
 pub mod error;
+pub mod events;
+#[cfg(feature = "database")]
+pub mod export;
 pub mod extract;
+pub mod fields;
+pub mod flags;
 pub mod handler;
+pub mod i18n;
 pub mod introspection;
+#[cfg(feature = "database")]
+pub mod jobs;
+pub mod json;
+#[cfg(feature = "lambda")]
+pub mod lambda;
+pub mod links;
 #[cfg(feature = "metrics")]
 pub mod metrics;
 pub mod middleware;
 #[cfg(feature = "database")]
 pub mod migration;
+pub mod ndjson;
 pub mod observability;
 pub mod openapi;
 #[cfg(feature = "database")]
 pub mod pagination;
+pub mod patch;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+pub mod reload;
 pub mod response;
 pub mod router;
+pub mod schedule;
 pub mod server;
+pub mod service;
+pub mod signing;
 pub mod state;
 pub mod test;
 pub mod testing;
@@ -114,20 +139,48 @@ pub mod testing;
 /// ```
 /// use rapina::prelude::*;
 /// ```
+///
+/// The full prelude exports:
+///
+/// - The app and router: [`Rapina`](crate::app::Rapina), [`Router`](crate::router::Router)
+/// - Extractors from [`extract`](crate::extract): `Bytes`, `Context`, `Cookie`, `Form`,
+///   `Headers`, `Json`, `Path`, `Query`, `RawJson`, `State`, `States`, `Validated`
+/// - Errors from [`error`](crate::error): `Error`, `Result`, `IntoApiError`, `DocumentedError`,
+///   `ErrorVariant`
+/// - Auth, config, and observability helpers: `AuthConfig`, `CurrentUser`,
+///   `TokenResponse`, `Policy`, `Authorize`, `ConfigError`, the `get_env*` functions, `load_dotenv`,
+///   `RequestContext`, `MatchedRoute`, `RouteInfo`, `TracingConfig`
+/// - Middleware building blocks: `Middleware`, `Next`, `KeyExtractor`, `RateLimitConfig`
+/// - Streaming NDJSON uploads from [`ndjson`](crate::ndjson): `NdJson`, `IngestSummary`
+/// - Pagination (with the `database` feature): `Paginate`, `Paginated`, `PaginationConfig`
+/// - Partial updates from [`patch`](crate::patch): `JsonPatch`, `MergePatch`
+/// - `IntoResponse`, `Method`, `StatusCode`
+/// - Derives and helpers: `Serialize`, `Deserialize`, `JsonSchema`, `Validate`, `tracing`
+/// - Macros: `get`, `post`, `put`, `patch`, `delete`, `public`, `authorize`, `schema`,
+///   `Config`, `FromRequestParts`
+///
+/// Every item is also available outside the prelude, either from its module
+/// (e.g. `rapina::error::Error`) or from the crate root (e.g. `rapina::serde`),
+/// so applications can skip the prelude entirely. Apps that define their own
+/// `Error` or `Result` can use [`prelude::minimal`](crate::prelude::minimal) instead.
 pub mod prelude {
     pub use crate::app::Rapina;
-    pub use crate::auth::{AuthConfig, CurrentUser, TokenResponse};
+    pub use crate::auth::{AuthConfig, Authorize, CurrentUser, Policy, TokenResponse};
     pub use crate::config::{
         ConfigError, get_env, get_env_or, get_env_parsed, get_env_parsed_or, load_dotenv,
     };
-    pub use crate::context::RequestContext;
+    pub use crate::context::{MatchedRoute, RequestContext};
     pub use crate::error::{DocumentedError, Error, ErrorVariant, IntoApiError, Result};
-    pub use crate::extract::{Context, Cookie, Form, Headers, Json, Path, Query, State, Validated};
+    pub use crate::extract::{
+        Bytes, Context, Cookie, Form, Headers, Json, Path, Query, RawJson, State, States, Validated,
+    };
     pub use crate::introspection::RouteInfo;
     pub use crate::middleware::{KeyExtractor, Middleware, Next, RateLimitConfig};
+    pub use crate::ndjson::{IngestSummary, NdJson};
     pub use crate::observability::TracingConfig;
     #[cfg(feature = "database")]
     pub use crate::pagination::{Paginate, Paginated, PaginationConfig};
+    pub use crate::patch::{JsonPatch, MergePatch};
     pub use crate::response::IntoResponse;
     pub use crate::router::Router;
 
@@ -137,18 +190,56 @@ pub mod prelude {
     pub use tracing;
     pub use validator::Validate;
 
-    pub use rapina_macros::{Config, delete, get, post, public, put, schema};
+    pub use rapina_macros::{
+        Config, FromRequestParts, authorize, delete, get, patch, post, public, put, schema,
+    };
+
+    /// Slim prelude with only the routing and extractor essentials.
+    ///
+    /// It leaves out `Error`, `Result`, and the serde derives, so it can be
+    /// glob-imported next to an application's own types of the same name:
+    ///
+    /// ```
+    /// use rapina::prelude::minimal::*;
+    ///
+    /// #[derive(Debug)]
+    /// enum Error {
+    ///     NotFound,
+    /// }
+    ///
+    /// type Result<T> = std::result::Result<T, Error>;
+    /// ```
+    pub mod minimal {
+        pub use crate::app::Rapina;
+        pub use crate::extract::{
+            Context, Cookie, Form, Headers, Json, Path, Query, State, States, Validated,
+        };
+        pub use crate::response::IntoResponse;
+        pub use crate::router::Router;
+
+        pub use http::{Method, StatusCode};
+
+        pub use rapina_macros::{delete, get, patch, post, public, put};
+    }
 }
 
 // Re-export proc macros at crate root so they work as rapina::schema!, rapina::get!, etc.
-pub use rapina_macros::{Config, delete, get, post, public, put, schema};
+pub use rapina_macros::{
+    Config, FromRequestParts, authorize, delete, get, patch, post, public, put, schema,
+};
 
 // Re-export dependencies so users don't need to add them to their Cargo.toml
+pub use bytes;
+pub use chrono;
+pub use chrono_tz;
 pub use http;
 pub use hyper;
 pub use rust_decimal;
 pub use schemars;
+pub use serde;
+pub use tracing;
 pub use uuid;
+pub use validator;
 
 #[doc(hidden)]
 pub use inventory;
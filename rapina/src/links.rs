@@ -0,0 +1,353 @@
+//! Links between resources, built from route names.
+//!
+//! [`UrlFor`] maps handler names to the paths they're served at, so a
+//! handler can point at another route without hardcoding its path. Routes
+//! added under [`Router::group`](crate::router::Router::group) resolve to
+//! their prefixed path, and so do routes scaffolded with `--route-prefix`.
+//! [`Links`] collects named links, and [`Linked<T>`] returns them next to
+//! a resource as `{ "data": ..., "links": { ... } }`.
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::links::{Linked, UrlFor};
+//!
+//! #[get("/posts/:id")]
+//! async fn get_post(id: Path<i32>, urls: UrlFor) -> Result<Linked<Post>> {
+//!     let id = id.into_inner();
+//!     let post = find_post(id).await?;
+//!     let links = urls
+//!         .links()
+//!         .route("self", "get_post", &[("id", &id)])?
+//!         .route("comments", "list_comments", &[("post_id", &id)])?;
+//!     Ok(Linked::new(post, links))
+//! }
+//! ```
+//!
+//! A link to a route that doesn't exist, or one missing a path parameter,
+//! is an internal error rather than a broken `href`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::sync::Arc;
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::Serialize;
+use serde::ser::{SerializeMap, Serializer};
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, Json, PathParams};
+use crate::introspection::RouteInfo;
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+/// Path parameters for [`UrlFor::url_for`], as `(name, value)` pairs.
+pub type UrlParams<'a> = [(&'a str, &'a dyn Display)];
+
+/// Builds paths to named routes.
+///
+/// Registered in the application state for every app, and extracted like
+/// any other state. Route names are handler names, as listed by route
+/// introspection; when several routes share a name the first one wins.
+#[derive(Debug, Clone, Default)]
+pub struct UrlFor {
+    routes: Arc<HashMap<String, String>>,
+}
+
+impl UrlFor {
+    /// Creates a `UrlFor` resolving the given routes by handler name.
+    pub fn new(routes: &[RouteInfo]) -> Self {
+        let mut by_name = HashMap::new();
+        for route in routes {
+            by_name
+                .entry(route.handler_name.clone())
+                .or_insert_with(|| route.path.clone());
+        }
+        Self {
+            routes: Arc::new(by_name),
+        }
+    }
+
+    /// Returns the path of the route named `name`, with each `:param`
+    /// replaced by its percent-encoded value from `params`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::introspection::RouteInfo;
+    /// use rapina::links::UrlFor;
+    ///
+    /// let urls = UrlFor::new(&[RouteInfo::new("GET", "/api/posts/:id", "get_post", None, Vec::new())]);
+    /// assert_eq!(urls.url_for("get_post", &[("id", &5)]).unwrap(), "/api/posts/5");
+    /// ```
+    pub fn url_for(&self, name: &str, params: &UrlParams<'_>) -> Result<String, Error> {
+        let pattern = self
+            .routes
+            .get(name)
+            .ok_or_else(|| Error::internal(format!("no route named '{}'", name)))?;
+
+        let mut used = 0;
+        let mut url = String::with_capacity(pattern.len());
+        for segment in pattern.split('/').skip(1) {
+            url.push('/');
+            let Some(param) = segment.strip_prefix(':') else {
+                url.push_str(segment);
+                continue;
+            };
+            let (_, value) = params
+                .iter()
+                .find(|(key, _)| *key == param)
+                .ok_or_else(|| {
+                    Error::internal(format!(
+                        "route '{}' ({}) needs a value for ':{}'",
+                        name, pattern, param
+                    ))
+                })?;
+            encode_segment(&mut url, &value.to_string());
+            used += 1;
+        }
+
+        if used < params.len() {
+            let unknown: Vec<&str> = params
+                .iter()
+                .map(|(key, _)| *key)
+                .filter(|key| !pattern.split('/').any(|s| s.strip_prefix(':') == Some(key)))
+                .collect();
+            return Err(Error::internal(format!(
+                "route '{}' ({}) has no parameter(s) {}",
+                name,
+                pattern,
+                unknown.join(", ")
+            )));
+        }
+        if url.is_empty() {
+            url.push('/');
+        }
+        Ok(url)
+    }
+
+    /// Starts an empty set of links resolved against these routes.
+    pub fn links(&self) -> Links {
+        Links {
+            urls: self.clone(),
+            links: Vec::new(),
+        }
+    }
+}
+
+impl FromRequestParts for UrlFor {
+    async fn from_request_parts(
+        _parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        Ok(state.get::<UrlFor>().cloned().unwrap_or_default())
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, so a value always
+/// stays within one path segment.
+fn encode_segment(out: &mut String, value: &str) {
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{:02X}", byte);
+        }
+    }
+}
+
+/// Named links to related resources, serialized as `{ "rel": "href" }` in
+/// the order they were added.
+///
+/// Start one with [`UrlFor::links`]. Embed it as a field of a response type,
+/// or wrap the response in [`Linked<T>`].
+#[derive(Debug, Clone)]
+pub struct Links {
+    urls: UrlFor,
+    links: Vec<(String, String)>,
+}
+
+impl Links {
+    /// Adds a link named `rel` to the route named `route`.
+    ///
+    /// Adding a `rel` twice replaces the earlier link.
+    pub fn route(self, rel: &str, route: &str, params: &UrlParams<'_>) -> Result<Self, Error> {
+        let href = self.urls.url_for(route, params)?;
+        Ok(self.href(rel, href))
+    }
+
+    /// Adds a link named `rel` with a literal `href`, such as an external URL.
+    pub fn href(mut self, rel: &str, href: impl Into<String>) -> Self {
+        let href = href.into();
+        match self.links.iter_mut().find(|(name, _)| name == rel) {
+            Some((_, existing)) => *existing = href,
+            None => self.links.push((rel.to_string(), href)),
+        }
+        self
+    }
+
+    /// Returns the `href` of the link named `rel`.
+    pub fn get(&self, rel: &str) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|(name, _)| name == rel)
+            .map(|(_, href)| href.as_str())
+    }
+}
+
+impl Serialize for Links {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.links.len()))?;
+        for (rel, href) in &self.links {
+            map.serialize_entry(rel, href)?;
+        }
+        map.end()
+    }
+}
+
+impl JsonSchema for Links {
+    fn schema_name() -> Cow<'static, str> {
+        "Links".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "additionalProperties": { "type": "string" },
+        })
+    }
+}
+
+/// JSON response wrapping a resource and its links as
+/// `{ "data": ..., "links": { ... } }`.
+///
+/// Handlers returning `Linked<T>` or `Result<Linked<T>>` are documented in
+/// the OpenAPI spec with the envelope.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Linked<T> {
+    pub data: T,
+    pub links: Links,
+}
+
+impl<T> Linked<T> {
+    /// Wraps `data` with its links.
+    pub fn new(data: T, links: Links) -> Self {
+        Self { data, links }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Linked<T> {
+    fn into_response(self) -> http::Response<BoxBody> {
+        Json(self).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{TestRequest, empty_params, empty_state, state_with};
+
+    fn urls() -> UrlFor {
+        UrlFor::new(&[
+            RouteInfo::new("GET", "/", "index", None, Vec::new()),
+            RouteInfo::new("GET", "/posts", "list_posts", None, Vec::new()),
+            RouteInfo::new("GET", "/posts/:id", "get_post", None, Vec::new()),
+            RouteInfo::new("PUT", "/posts/:id", "get_post", None, Vec::new()),
+            RouteInfo::new(
+                "GET",
+                "/posts/:post_id/comments/:id",
+                "get_comment",
+                None,
+                Vec::new(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_url_for_fills_params() {
+        let urls = urls();
+        assert_eq!(urls.url_for("index", &[]).unwrap(), "/");
+        assert_eq!(urls.url_for("list_posts", &[]).unwrap(), "/posts");
+        assert_eq!(urls.url_for("get_post", &[("id", &5)]).unwrap(), "/posts/5");
+        assert_eq!(
+            urls.url_for("get_comment", &[("id", &"b"), ("post_id", &3)])
+                .unwrap(),
+            "/posts/3/comments/b"
+        );
+    }
+
+    #[test]
+    fn test_url_for_encodes_values() {
+        let url = urls().url_for("get_post", &[("id", &"a/b c?")]).unwrap();
+        assert_eq!(url, "/posts/a%2Fb%20c%3F");
+    }
+
+    #[test]
+    fn test_url_for_errors() {
+        let urls = urls();
+
+        let err = urls.url_for("missing", &[]).unwrap_err();
+        assert_eq!(err.status, 500);
+        assert!(err.message.contains("no route named 'missing'"));
+
+        let err = urls.url_for("get_comment", &[("id", &1)]).unwrap_err();
+        assert!(err.message.contains("needs a value for ':post_id'"));
+
+        let err = urls
+            .url_for("get_post", &[("id", &1), ("slug", &"x")])
+            .unwrap_err();
+        assert!(err.message.contains("has no parameter(s) slug"));
+    }
+
+    #[test]
+    fn test_links_serialize_in_order() {
+        let links = urls()
+            .links()
+            .route("self", "get_post", &[("id", &5)])
+            .unwrap()
+            .route("collection", "list_posts", &[])
+            .unwrap()
+            .href("docs", "https://example.com/docs")
+            .href("self", "/posts/6");
+
+        assert_eq!(links.get("self"), Some("/posts/6"));
+        assert_eq!(
+            serde_json::to_string(&links).unwrap(),
+            r#"{"self":"/posts/6","collection":"/posts","docs":"https://example.com/docs"}"#
+        );
+    }
+
+    #[test]
+    fn test_linked_schema_is_envelope() {
+        #[derive(Serialize, JsonSchema)]
+        struct Post {
+            id: i32,
+        }
+
+        let schema = serde_json::to_value(schemars::schema_for!(Linked<Post>)).unwrap();
+        assert_eq!(schema["required"], serde_json::json!(["data", "links"]));
+        assert_eq!(schema["properties"]["links"]["$ref"], "#/$defs/Links");
+        assert_eq!(
+            schema["$defs"]["Links"]["additionalProperties"]["type"],
+            "string"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_url_for() {
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let urls = UrlFor::from_request_parts(&parts, &empty_params(), &state_with(urls()))
+            .await
+            .unwrap();
+        assert_eq!(urls.url_for("list_posts", &[]).unwrap(), "/posts");
+
+        let urls = UrlFor::from_request_parts(&parts, &empty_params(), &empty_state())
+            .await
+            .unwrap();
+        assert!(urls.url_for("list_posts", &[]).is_err());
+    }
+}
@@ -1,11 +1,10 @@
 use std::sync::Arc;
 
-use bytes::Bytes;
 use http::{Request, Response, StatusCode};
-use http_body_util::Full;
 use hyper::body::Incoming;
 use prometheus::{
-    CounterVec, Encoder, HistogramOpts, HistogramVec, IntGauge, Opts, Registry, TextEncoder,
+    CounterVec, Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
 };
 
 use crate::extract::PathParams;
@@ -18,6 +17,11 @@ pub struct MetricsRegistry {
     pub(crate) http_requests_total: CounterVec,
     pub(crate) http_request_duration_seconds: HistogramVec,
     pub(crate) http_requests_in_flight: IntGauge,
+    /// Per-route concurrency limits, by route pattern.
+    pub(crate) route_concurrency_limit: IntGaugeVec,
+    pub(crate) route_concurrency_in_flight: IntGaugeVec,
+    pub(crate) route_concurrency_queued: IntGaugeVec,
+    pub(crate) route_concurrency_rejected_total: IntCounterVec,
 }
 
 impl MetricsRegistry {
@@ -57,11 +61,67 @@ impl MetricsRegistry {
             .register(Box::new(http_requests_in_flight.clone()))
             .expect("failed to register http_requests_in_flight");
 
+        let route_concurrency_limit = IntGaugeVec::new(
+            Opts::new(
+                "route_concurrency_limit",
+                "Maximum number of requests a route handles at once",
+            ),
+            &["path"],
+        )
+        .expect("failed to create route_concurrency_limit metric");
+
+        registry
+            .register(Box::new(route_concurrency_limit.clone()))
+            .expect("failed to register route_concurrency_limit");
+
+        let route_concurrency_in_flight = IntGaugeVec::new(
+            Opts::new(
+                "route_concurrency_in_flight",
+                "Number of requests a concurrency-limited route is handling",
+            ),
+            &["path"],
+        )
+        .expect("failed to create route_concurrency_in_flight metric");
+
+        registry
+            .register(Box::new(route_concurrency_in_flight.clone()))
+            .expect("failed to register route_concurrency_in_flight");
+
+        let route_concurrency_queued = IntGaugeVec::new(
+            Opts::new(
+                "route_concurrency_queued",
+                "Number of requests waiting for a concurrency-limited route",
+            ),
+            &["path"],
+        )
+        .expect("failed to create route_concurrency_queued metric");
+
+        registry
+            .register(Box::new(route_concurrency_queued.clone()))
+            .expect("failed to register route_concurrency_queued");
+
+        let route_concurrency_rejected_total = IntCounterVec::new(
+            Opts::new(
+                "route_concurrency_rejected_total",
+                "Requests rejected because a route was at its concurrency limit",
+            ),
+            &["path"],
+        )
+        .expect("failed to create route_concurrency_rejected_total metric");
+
+        registry
+            .register(Box::new(route_concurrency_rejected_total.clone()))
+            .expect("failed to register route_concurrency_rejected_total");
+
         Self {
             registry: Arc::new(registry),
             http_requests_total,
             http_request_duration_seconds,
             http_requests_in_flight,
+            route_concurrency_limit,
+            route_concurrency_in_flight,
+            route_concurrency_queued,
+            route_concurrency_rejected_total,
         }
     }
 
@@ -97,12 +157,12 @@ pub async fn metrics_handler(
             Response::builder()
                 .status(StatusCode::OK)
                 .header("content-type", "text/plain; version=0.0.4; charset=utf-8")
-                .body(Full::new(Bytes::from(body)))
+                .body(BoxBody::new(body))
                 .unwrap()
         }
         None => Response::builder()
             .status(StatusCode::SERVICE_UNAVAILABLE)
-            .body(Full::new(Bytes::new()))
+            .body(BoxBody::empty())
             .unwrap(),
     }
 }
@@ -1,22 +1,54 @@
+use std::time::Duration;
+
 use hyper::body::Incoming;
 use hyper::{Request, Response};
 
 use crate::context::RequestContext;
 use crate::error::Error;
+use crate::extract::{BodyDeadline, BodyLimit};
 use crate::response::{BoxBody, IntoResponse};
 
 use super::{BoxFuture, Middleware, Next};
 
 const DEFAULT_MAX_SIZE: usize = 1024 * 1024; // 1MB
 
+/// A route's own body limit, set with
+/// [`Router::body_limit`](crate::router::Router::body_limit). It replaces
+/// the middleware's limit, whether smaller or larger.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RouteBodyLimit(pub(crate) usize);
+
+/// Rejects request bodies over a size limit with 413 Payload Too Large.
+///
+/// A declared `Content-Length` over the limit is rejected before the
+/// handler runs; bodies without one, or lying about it, are cut off as soon
+/// as they cross the limit while an extractor reads them.
+///
+/// [`Rapina::new`](crate::app::Rapina::new) installs one with a 1 MB limit;
+/// change it with [`Rapina::with_body_limit`](crate::app::Rapina::with_body_limit)
+/// or turn it off with
+/// [`Rapina::without_body_limit`](crate::app::Rapina::without_body_limit).
 #[derive(Debug, Clone)]
 pub struct BodyLimitMiddleware {
     pub(crate) max_size: usize,
+    pub(crate) read_timeout: Option<Duration>,
 }
 
 impl BodyLimitMiddleware {
     pub fn new(max_size: usize) -> Self {
-        Self { max_size }
+        Self {
+            max_size,
+            read_timeout: None,
+        }
+    }
+
+    /// Rejects requests whose body hasn't fully arrived within `timeout` of
+    /// the request starting with 408 Request Timeout.
+    ///
+    /// Protects against clients that trickle a body in to hold connections open.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
     }
 }
 
@@ -29,21 +61,34 @@ impl Default for BodyLimitMiddleware {
 impl Middleware for BodyLimitMiddleware {
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
-        _ctx: &'a RequestContext,
+        mut req: Request<Incoming>,
+        ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
         Box::pin(async move {
+            let max_size = req
+                .extensions()
+                .get::<RouteBodyLimit>()
+                .map_or(self.max_size, |limit| limit.0);
             let content_length = req
                 .headers()
                 .get(hyper::header::CONTENT_LENGTH)
                 .and_then(|v| v.to_str().ok())
                 .and_then(|v| v.parse::<usize>().ok());
 
-            if content_length.is_some_and(|len| len > self.max_size) {
-                return Error::bad_request("body too large").into_response();
+            if content_length.is_some_and(|len| len > max_size) {
+                return Error::payload_too_large("body too large")
+                    .with_trace_id(&ctx.trace_id)
+                    .into_response();
             }
 
+            // Bodies without a Content-Length (or lying about it) are checked
+            // chunk by chunk while they are buffered
+            req.extensions_mut().insert(BodyLimit(max_size));
+            if let Some(timeout) = self.read_timeout {
+                req.extensions_mut()
+                    .insert(BodyDeadline(tokio::time::Instant::now() + timeout));
+            }
             next.run(req).await
         })
     }
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use http::header;
+use hyper::body::Incoming;
+use hyper::{Request, Response};
+
+use crate::cache::{self, DEFAULT_MAX_ENTRIES, ResponseCache};
+use crate::context::RequestContext;
+use crate::response::BoxBody;
+
+use super::{BoxFuture, Middleware, Next};
+
+/// Configuration for [`CacheMiddleware`].
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub ttl: Duration,
+    pub max_entries: usize,
+}
+
+impl CacheConfig {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+}
+
+/// Caches successful `GET` responses for every route.
+///
+/// Responses that already carry a `Cache-Control` header are left alone, so
+/// handlers with their own `cache = "..."` option keep their TTL, and
+/// handlers can opt out by sending `Cache-Control: no-store`.
+#[derive(Debug, Clone)]
+pub struct CacheMiddleware {
+    cache: ResponseCache,
+    ttl: Duration,
+}
+
+impl CacheMiddleware {
+    pub fn new(config: CacheConfig) -> Self {
+        Self::with_cache(
+            ResponseCache::new().max_entries(config.max_entries),
+            config.ttl,
+        )
+    }
+
+    pub(crate) fn with_cache(cache: ResponseCache, ttl: Duration) -> Self {
+        Self { cache, ttl }
+    }
+}
+
+impl Middleware for CacheMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            if !cache::is_cacheable_method(req.method()) {
+                return next.run(req).await;
+            }
+
+            let key = ResponseCache::key(&req);
+            if let Some(response) = self.cache.get(&key) {
+                return response;
+            }
+
+            let response = next.run(req).await;
+            if response.headers().contains_key(header::CACHE_CONTROL) {
+                return response;
+            }
+            self.cache.store(key, response, self.ttl, &[]).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_new() {
+        let config = CacheConfig::new(Duration::from_secs(30));
+        assert_eq!(config.ttl, Duration::from_secs(30));
+        assert_eq!(config.max_entries, DEFAULT_MAX_ENTRIES);
+    }
+
+    #[test]
+    fn test_config_max_entries() {
+        let config = CacheConfig::new(Duration::from_secs(30)).max_entries(100);
+        assert_eq!(config.max_entries, 100);
+    }
+}
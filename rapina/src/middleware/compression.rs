@@ -1,53 +1,65 @@
 use std::io::Write;
 
-use bytes::Bytes;
 use flate2::Compression;
 use flate2::write::{DeflateEncoder, GzEncoder};
 use http::{HeaderValue, Response, header};
-use http_body_util::{BodyExt, Full};
+use http_body_util::BodyExt;
 use hyper::Request;
 use hyper::body::Incoming;
 
 use crate::context::RequestContext;
-use crate::response::BoxBody;
+use crate::response::{BoxBody, add_vary};
 
+use super::content_type::{media_essence, media_matches};
 use super::{BoxFuture, Middleware, Next};
 
 const DEFAULT_MIN_SIZE: usize = 1024;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum Algorithm {
+/// Brotli window size, as a power of two. 22 is the encoder's own default.
+const BROTLI_WINDOW: u32 = 22;
+
+const DEFAULT_CONTENT_TYPES: &[&str] = &[
+    "text/*",
+    "application/json",
+    "application/xml",
+    "application/javascript",
+    "+json",
+    "+xml",
+];
+
+/// A `Content-Encoding` the compression middleware can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Brotli,
     Gzip,
     Deflate,
 }
 
-impl Algorithm {
-    fn from_accept_encoding(header: &str) -> Option<Self> {
-        if header.contains("gzip") {
-            Some(Algorithm::Gzip)
-        } else if header.contains("deflate") {
-            Some(Algorithm::Deflate)
-        } else {
-            None
-        }
-    }
-
+impl CompressionAlgorithm {
     fn content_encoding(&self) -> &'static str {
         match self {
-            Algorithm::Gzip => "gzip",
-            Algorithm::Deflate => "deflate",
+            CompressionAlgorithm::Brotli => "br",
+            CompressionAlgorithm::Gzip => "gzip",
+            CompressionAlgorithm::Deflate => "deflate",
         }
     }
 
-    fn compress(&self, data: &[u8], level: Compression) -> std::io::Result<Vec<u8>> {
+    fn compress(&self, data: &[u8], level: u32) -> std::io::Result<Vec<u8>> {
         match self {
-            Algorithm::Gzip => {
-                let mut encoder = GzEncoder::new(Vec::new(), level);
+            CompressionAlgorithm::Brotli => {
+                let mut encoder =
+                    brotli::CompressorWriter::new(Vec::new(), 4096, level, BROTLI_WINDOW);
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(encoder.into_inner())
+            }
+            CompressionAlgorithm::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
                 encoder.write_all(data)?;
                 encoder.finish()
             }
-            Algorithm::Deflate => {
-                let mut encoder = DeflateEncoder::new(Vec::new(), level);
+            CompressionAlgorithm::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::new(level));
                 encoder.write_all(data)?;
                 encoder.finish()
             }
@@ -55,10 +67,57 @@ impl Algorithm {
     }
 }
 
+/// Picks the encoding the client prefers most among `offered`.
+///
+/// Quality values are honoured, `*` covers anything not listed, and `q=0`
+/// rules an encoding out. Ties go to whichever comes first in `offered`.
+fn negotiate(
+    accept_encoding: &str,
+    offered: &[CompressionAlgorithm],
+) -> Option<CompressionAlgorithm> {
+    let preferences: Vec<(String, f32)> = accept_encoding
+        .split(',')
+        .filter_map(|item| {
+            let mut params = item.split(';');
+            let coding = params.next()?.trim().to_ascii_lowercase();
+            if coding.is_empty() {
+                return None;
+            }
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((coding, quality))
+        })
+        .collect();
+
+    let quality_of = |name: &str| {
+        preferences
+            .iter()
+            .find(|(coding, _)| coding == name)
+            .or_else(|| preferences.iter().find(|(coding, _)| coding == "*"))
+            .map_or(0.0, |(_, q)| *q)
+    };
+
+    let mut best: Option<(CompressionAlgorithm, f32)> = None;
+    for &algorithm in offered {
+        let quality = quality_of(algorithm.content_encoding());
+        if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+            best = Some((algorithm, quality));
+        }
+    }
+    best.map(|(algorithm, _)| algorithm)
+}
+
 #[derive(Debug, Clone)]
 pub struct CompressionConfig {
     pub min_size: usize,
     pub level: u32,
+    /// Encodings to offer, most preferred first.
+    pub algorithms: Vec<CompressionAlgorithm>,
+    /// Media types worth compressing: exact types like `application/json`,
+    /// ranges like `text/*`, or structured suffixes like `+json`.
+    pub content_types: Vec<String>,
 }
 
 impl CompressionConfig {
@@ -66,8 +125,31 @@ impl CompressionConfig {
         Self {
             min_size,
             level: level.min(9),
+            ..Self::default()
         }
     }
+
+    /// Restricts which encodings are offered, in order of preference.
+    pub fn algorithms(
+        mut self,
+        algorithms: impl IntoIterator<Item = CompressionAlgorithm>,
+    ) -> Self {
+        self.algorithms = algorithms.into_iter().collect();
+        self
+    }
+
+    /// Replaces the allowlist of compressible content types.
+    pub fn content_types<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.content_types = content_types
+            .into_iter()
+            .map(|ct| ct.into().to_ascii_lowercase())
+            .collect();
+        self
+    }
 }
 
 impl Default for CompressionConfig {
@@ -75,6 +157,15 @@ impl Default for CompressionConfig {
         Self {
             min_size: DEFAULT_MIN_SIZE,
             level: 6,
+            algorithms: vec![
+                CompressionAlgorithm::Brotli,
+                CompressionAlgorithm::Gzip,
+                CompressionAlgorithm::Deflate,
+            ],
+            content_types: DEFAULT_CONTENT_TYPES
+                .iter()
+                .map(|ct| ct.to_string())
+                .collect(),
         }
     }
 }
@@ -89,19 +180,22 @@ impl CompressionMiddleware {
         Self { config }
     }
 
-    fn is_compressible_content_type(content_type: Option<&HeaderValue>) -> bool {
+    fn is_compressible_content_type(&self, content_type: Option<&HeaderValue>) -> bool {
         let Some(ct) = content_type else {
             return true;
         };
 
-        let ct_str = ct.to_str().unwrap_or("");
-
-        ct_str.starts_with("text/")
-            || ct_str.starts_with("application/json")
-            || ct_str.starts_with("application/xml")
-            || ct_str.starts_with("application/javascript")
-            || ct_str.contains("+json")
-            || ct_str.contains("+xml")
+        let essence = media_essence(ct.to_str().unwrap_or(""));
+
+        self.config
+            .content_types
+            .iter()
+            .any(|allowed| match allowed.strip_prefix('+') {
+                Some(suffix) => essence
+                    .rsplit_once('+')
+                    .is_some_and(|(_, actual)| actual == suffix),
+                None => media_matches(allowed, &essence),
+            })
     }
 
     fn is_already_encoded(response: &Response<BoxBody>) -> bool {
@@ -129,14 +223,15 @@ impl Middleware for CompressionMiddleware {
                 .and_then(|v| v.to_str().ok())
                 .unwrap_or("");
 
-            let algorithm = Algorithm::from_accept_encoding(accept_encoding);
+            let algorithm = negotiate(accept_encoding, &self.config.algorithms);
 
             let response = next.run(req).await;
 
             let algorithm = match algorithm {
                 Some(alg)
                     if !Self::is_already_encoded(&response)
-                        && Self::is_compressible_content_type(
+                        && !response.body().is_stream()
+                        && self.is_compressible_content_type(
                             response.headers().get(header::CONTENT_TYPE),
                         ) =>
                 {
@@ -148,33 +243,30 @@ impl Middleware for CompressionMiddleware {
             let (parts, body) = response.into_parts();
             let body_bytes = match body.collect().await {
                 Ok(collected) => collected.to_bytes(),
-                Err(_) => return Response::from_parts(parts, Full::new(Bytes::new())),
+                Err(_) => return Response::from_parts(parts, BoxBody::empty()),
             };
 
             if body_bytes.len() < self.config.min_size {
-                return Response::from_parts(parts, Full::new(body_bytes));
+                return Response::from_parts(parts, BoxBody::new(body_bytes));
             }
 
-            let level = Compression::new(self.config.level);
-            let compressed = match algorithm.compress(&body_bytes, level) {
+            let compressed = match algorithm.compress(&body_bytes, self.config.level) {
                 Ok(data) => data,
-                Err(_) => return Response::from_parts(parts, Full::new(body_bytes)),
+                Err(_) => return Response::from_parts(parts, BoxBody::new(body_bytes)),
             };
 
             // not worth it
             if compressed.len() >= body_bytes.len() {
-                return Response::from_parts(parts, Full::new(body_bytes));
+                return Response::from_parts(parts, BoxBody::new(body_bytes));
             }
 
-            let mut response = Response::from_parts(parts, Full::new(Bytes::from(compressed)));
+            let mut response = Response::from_parts(parts, BoxBody::new(compressed));
             response.headers_mut().insert(
                 header::CONTENT_ENCODING,
                 HeaderValue::from_static(algorithm.content_encoding()),
             );
             response.headers_mut().remove(header::CONTENT_LENGTH);
-            response
-                .headers_mut()
-                .insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+            add_vary(response.headers_mut(), "Accept-Encoding");
 
             response
         })
@@ -199,23 +291,70 @@ mod tests {
     }
 
     #[test]
-    fn test_algorithm_from_accept_encoding() {
+    fn test_negotiate_prefers_server_order_on_ties() {
+        let offered = CompressionConfig::default().algorithms;
+        assert_eq!(
+            negotiate("gzip, deflate, br", &offered),
+            Some(CompressionAlgorithm::Brotli)
+        );
+        assert_eq!(
+            negotiate("gzip, deflate", &offered),
+            Some(CompressionAlgorithm::Gzip)
+        );
+        assert_eq!(
+            negotiate("deflate", &offered),
+            Some(CompressionAlgorithm::Deflate)
+        );
+        assert_eq!(negotiate("identity", &offered), None);
+        assert_eq!(negotiate("", &offered), None);
+    }
+
+    #[test]
+    fn test_negotiate_honours_quality_values() {
+        let offered = CompressionConfig::default().algorithms;
+        assert_eq!(
+            negotiate("br;q=0.5, gzip", &offered),
+            Some(CompressionAlgorithm::Gzip)
+        );
         assert_eq!(
-            Algorithm::from_accept_encoding("gzip, deflate"),
-            Some(Algorithm::Gzip)
+            negotiate("br;q=0, *", &offered),
+            Some(CompressionAlgorithm::Gzip)
         );
+        assert_eq!(negotiate("*;q=0", &offered), None);
+        assert_eq!(negotiate("gzip;q=0", &offered), None);
+    }
+
+    #[test]
+    fn test_negotiate_only_offers_configured_algorithms() {
         assert_eq!(
-            Algorithm::from_accept_encoding("deflate"),
-            Some(Algorithm::Deflate)
+            negotiate("br, gzip", &[CompressionAlgorithm::Gzip]),
+            Some(CompressionAlgorithm::Gzip)
         );
-        assert_eq!(Algorithm::from_accept_encoding("br"), None);
+        assert_eq!(negotiate("br", &[CompressionAlgorithm::Gzip]), None);
+    }
+
+    #[test]
+    fn test_brotli_compression_round_trips() {
+        use std::io::Read;
+
+        let data = "hello from rapina ".repeat(100);
+        let compressed = CompressionAlgorithm::Brotli
+            .compress(data.as_bytes(), 6)
+            .unwrap();
+        assert!(compressed.len() < data.len());
+
+        let mut decoded = String::new();
+        brotli::Decompressor::new(compressed.as_slice(), 4096)
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
     }
 
     #[test]
     fn test_gzip_compression() {
         let data = "hello from rapina ".repeat(100);
-        let compressed = Algorithm::Gzip
-            .compress(data.as_bytes(), Compression::default())
+        let compressed = CompressionAlgorithm::Gzip
+            .compress(data.as_bytes(), 6)
             .unwrap();
         assert!(compressed.len() < data.len());
     }
@@ -223,23 +362,39 @@ mod tests {
     #[test]
     fn test_deflate_compression() {
         let data = "hello from rapina ".repeat(100);
-        let compressed = Algorithm::Deflate
-            .compress(data.as_bytes(), Compression::default())
+        let compressed = CompressionAlgorithm::Deflate
+            .compress(data.as_bytes(), 6)
             .unwrap();
         assert!(compressed.len() < data.len());
     }
 
     #[test]
     fn test_is_compressible_content_type() {
-        assert!(CompressionMiddleware::is_compressible_content_type(Some(
-            &HeaderValue::from_static("text/html")
-        )));
-        assert!(CompressionMiddleware::is_compressible_content_type(Some(
-            &HeaderValue::from_static("application/json")
-        )));
-        assert!(!CompressionMiddleware::is_compressible_content_type(Some(
-            &HeaderValue::from_static("image/png")
-        )));
-        assert!(CompressionMiddleware::is_compressible_content_type(None));
+        let middleware = CompressionMiddleware::default();
+        let compressible = |ct: &'static str| {
+            middleware.is_compressible_content_type(Some(&HeaderValue::from_static(ct)))
+        };
+
+        assert!(compressible("text/html"));
+        assert!(compressible("application/json"));
+        assert!(compressible("application/json; charset=utf-8"));
+        assert!(compressible("application/problem+json"));
+        assert!(!compressible("image/png"));
+        assert!(!compressible("application/octet-stream"));
+        assert!(middleware.is_compressible_content_type(None));
+    }
+
+    #[test]
+    fn test_custom_content_type_allowlist() {
+        let middleware = CompressionMiddleware::new(
+            CompressionConfig::default().content_types(["application/json", "image/svg+xml"]),
+        );
+        let compressible = |ct: &'static str| {
+            middleware.is_compressible_content_type(Some(&HeaderValue::from_static(ct)))
+        };
+
+        assert!(compressible("application/json"));
+        assert!(compressible("image/svg+xml"));
+        assert!(!compressible("text/html"));
     }
 }
@@ -0,0 +1,277 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::StatusCode;
+use hyper::body::Incoming;
+use hyper::{Request, Response};
+use tokio::sync::Semaphore;
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+use super::{BoxFuture, Middleware, Next};
+
+/// How many requests a single route may handle at once.
+///
+/// Unlike server-wide load shedding, a limit protects whatever the route
+/// calls into, such as a PDF renderer or a slow upstream API, so one heavy
+/// endpoint can't starve the rest of the app. Requests over the limit wait
+/// in a queue of up to [`queue`](Self::queue) entries, and once that is
+/// full they are answered with `503 Service Unavailable` (or the status set
+/// with [`status`](Self::status)) and a `Retry-After` header.
+///
+/// Applied with [`Router::concurrency_limit`](crate::router::Router::concurrency_limit).
+/// A bare number converts into a limit with no queue.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::middleware::ConcurrencyLimit;
+/// use std::time::Duration;
+///
+/// let limit = ConcurrencyLimit::new(2)
+///     .queue(10)
+///     .retry_after(Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    pub(crate) max: usize,
+    pub(crate) queue: usize,
+    pub(crate) status: StatusCode,
+    pub(crate) retry_after: Duration,
+}
+
+impl ConcurrencyLimit {
+    /// Allows `max` requests at once, rejecting the rest right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    pub fn new(max: usize) -> Self {
+        assert!(max > 0, "concurrency limit must be at least 1");
+        Self {
+            max,
+            queue: 0,
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            retry_after: Duration::from_secs(1),
+        }
+    }
+
+    /// Lets up to `depth` requests wait for a free slot instead of being
+    /// rejected.
+    pub fn queue(mut self, depth: usize) -> Self {
+        self.queue = depth;
+        self
+    }
+
+    /// Answers rejected requests with `status` instead of 503.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` is not 429 or 503.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        assert!(
+            matches!(
+                status,
+                StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+            ),
+            "concurrency limit status must be 429 or 503, got {}",
+            status
+        );
+        self.status = status;
+        self
+    }
+
+    /// The `Retry-After` sent with rejections, rounded up to whole seconds.
+    /// Defaults to one second.
+    pub fn retry_after(mut self, after: Duration) -> Self {
+        self.retry_after = after;
+        self
+    }
+
+    fn error(&self) -> Error {
+        match self.status {
+            StatusCode::TOO_MANY_REQUESTS => Error::rate_limited("too many concurrent requests"),
+            _ => Error::new(503, "SERVICE_UNAVAILABLE", "too many concurrent requests"),
+        }
+    }
+
+    fn retry_after_secs(&self) -> u64 {
+        let secs = self.retry_after.as_secs();
+        if self.retry_after.subsec_nanos() > 0 {
+            secs + 1
+        } else {
+            secs
+        }
+    }
+}
+
+impl From<usize> for ConcurrencyLimit {
+    fn from(max: usize) -> Self {
+        Self::new(max)
+    }
+}
+
+/// Runs a route's requests through its own [`ConcurrencyLimit`].
+pub(crate) struct ConcurrencyLimitMiddleware {
+    limit: ConcurrencyLimit,
+    permits: Semaphore,
+    queued: AtomicUsize,
+}
+
+impl ConcurrencyLimitMiddleware {
+    pub(crate) fn new(limit: ConcurrencyLimit) -> Self {
+        Self {
+            permits: Semaphore::new(limit.max),
+            queued: AtomicUsize::new(0),
+            limit,
+        }
+    }
+
+    /// Takes a slot in the queue, unless it is full.
+    fn enqueue(&self) -> Option<QueueSlot<'_>> {
+        self.queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                (queued < self.limit.queue).then_some(queued + 1)
+            })
+            .ok()
+            .map(|_| QueueSlot(&self.queued))
+    }
+
+    fn reject(&self, ctx: &RequestContext) -> Response<BoxBody> {
+        let mut response = self
+            .limit
+            .error()
+            .with_trace_id(&ctx.trace_id)
+            .into_response();
+        response.headers_mut().insert(
+            "retry-after",
+            self.limit.retry_after_secs().to_string().parse().unwrap(),
+        );
+        response
+    }
+
+    /// Publishes the route's in-flight and queued counts, and the rejection
+    /// if there was one, when metrics are enabled.
+    #[cfg(feature = "metrics")]
+    fn record(&self, ctx: &RequestContext, state: &AppState, rejected: bool) {
+        let Some(registry) = state.get::<crate::metrics::MetricsRegistry>() else {
+            return;
+        };
+        let path = ctx
+            .matched_route()
+            .map(|route| route.pattern.as_str())
+            .unwrap_or("unknown");
+        let in_flight = self.limit.max - self.permits.available_permits();
+        registry
+            .route_concurrency_limit
+            .with_label_values(&[path])
+            .set(self.limit.max as i64);
+        registry
+            .route_concurrency_in_flight
+            .with_label_values(&[path])
+            .set(in_flight as i64);
+        registry
+            .route_concurrency_queued
+            .with_label_values(&[path])
+            .set(self.queued.load(Ordering::Acquire) as i64);
+        if rejected {
+            registry
+                .route_concurrency_rejected_total
+                .with_label_values(&[path])
+                .inc();
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    fn record(&self, _ctx: &RequestContext, _state: &AppState, _rejected: bool) {}
+}
+
+/// A place in the queue, given back when the request gets a slot or is
+/// dropped while waiting.
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Middleware for ConcurrencyLimitMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            let state = next.state();
+            let permit = match self.permits.try_acquire() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    let Some(slot) = self.enqueue() else {
+                        self.record(ctx, state, true);
+                        return self.reject(ctx);
+                    };
+                    self.record(ctx, state, false);
+                    let permit = self
+                        .permits
+                        .acquire()
+                        .await
+                        .expect("route semaphore is never closed");
+                    drop(slot);
+                    permit
+                }
+            };
+            self.record(ctx, state, false);
+
+            let response = next.run(req).await;
+            drop(permit);
+            self.record(ctx, state, false);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrency_limit_defaults() {
+        let limit = ConcurrencyLimit::from(2);
+        assert_eq!(limit.max, 2);
+        assert_eq!(limit.queue, 0);
+        assert_eq!(limit.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(limit.retry_after_secs(), 1);
+    }
+
+    #[test]
+    fn test_concurrency_limit_retry_after_rounds_up() {
+        let limit = ConcurrencyLimit::new(1).retry_after(Duration::from_millis(1500));
+        assert_eq!(limit.retry_after_secs(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "concurrency limit must be at least 1")]
+    fn test_concurrency_limit_rejects_zero() {
+        let _ = ConcurrencyLimit::new(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "concurrency limit status must be 429 or 503")]
+    fn test_concurrency_limit_rejects_other_status() {
+        let _ = ConcurrencyLimit::new(1).status(StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[test]
+    fn test_queue_slots_are_bounded_and_released() {
+        let mw = ConcurrencyLimitMiddleware::new(ConcurrencyLimit::new(1).queue(1));
+        let slot = mw.enqueue().unwrap();
+        assert!(mw.enqueue().is_none());
+        drop(slot);
+        assert!(mw.enqueue().is_some());
+    }
+}
@@ -0,0 +1,248 @@
+use hyper::body::Incoming;
+use hyper::{Request, Response, header};
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::response::{BoxBody, IntoResponse};
+
+use super::{BoxFuture, Middleware, Next};
+
+/// Rejects requests whose body has a `Content-Type` outside an allow-list
+/// with 415 Unsupported Media Type, before anything reads the body.
+///
+/// Only requests that carry a body are checked: a non-zero `Content-Length`
+/// or a `Transfer-Encoding`. Bodiless `GET`, `HEAD` and `DELETE` requests
+/// pass through. Parameters are ignored, so `application/json;
+/// charset=utf-8` matches `application/json`, and an allowed `type/*`
+/// matches any subtype.
+///
+/// Apply it app-wide with [`Rapina::require_json`] or
+/// [`Rapina::require_content_types`], or to some routes with
+/// [`Router::content_types`]. A route's own guard replaces the app's, and
+/// the allowed types are published as the operation's request body content
+/// types in the OpenAPI spec.
+///
+/// [`Rapina::require_json`]: crate::app::Rapina::require_json
+/// [`Rapina::require_content_types`]: crate::app::Rapina::require_content_types
+/// [`Router::content_types`]: crate::router::Router::content_types
+///
+/// # Examples
+///
+/// ```
+/// use rapina::prelude::*;
+/// use rapina::middleware::ContentTypeGuard;
+///
+/// let uploads = Router::new()
+///     .post_named("/avatars", "upload_avatar", |_, _, _| async { StatusCode::CREATED })
+///     .content_types(ContentTypeGuard::new(["image/png", "image/jpeg"]));
+///
+/// let app = Rapina::new()
+///     .require_json()
+///     .router(Router::new().group("/uploads", uploads));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentTypeGuard {
+    allowed: Vec<String>,
+}
+
+impl ContentTypeGuard {
+    /// Allows the given media types, e.g. `["application/json", "text/*"]`.
+    pub fn new<I, S>(types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            allowed: types
+                .into_iter()
+                .map(|t| t.into().trim().to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Allows `application/json` only.
+    pub fn json() -> Self {
+        Self::new(["application/json"])
+    }
+
+    /// The allowed media types.
+    pub fn allowed(&self) -> &[String] {
+        &self.allowed
+    }
+
+    /// Whether a `Content-Type` header value is allowed.
+    pub fn allows(&self, content_type: &str) -> bool {
+        let essence = media_essence(content_type);
+        self.allowed
+            .iter()
+            .any(|allowed| media_matches(allowed, &essence))
+    }
+
+    /// Checks a request's headers, answering with the 415 to send if the
+    /// body's type isn't allowed.
+    pub(crate) fn check<B>(&self, req: &Request<B>) -> Result<(), Error> {
+        if !has_body(req) {
+            return Ok(());
+        }
+
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap_or_default());
+        match content_type {
+            Some(content_type) if self.allows(content_type) => Ok(()),
+            Some(content_type) => Err(self.unsupported(format!(
+                "unsupported content type '{}'; expected {}",
+                content_type,
+                self.allowed.join(" or ")
+            ))),
+            None => Err(self.unsupported(format!(
+                "missing Content-Type; expected {}",
+                self.allowed.join(" or ")
+            ))),
+        }
+    }
+
+    fn unsupported(&self, message: String) -> Error {
+        Error::unsupported_media_type(message)
+            .with_details(serde_json::json!({ "allowed": self.allowed }))
+    }
+}
+
+/// The lowercased `type/subtype` of a media type, without parameters.
+pub(crate) fn media_essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Whether a media `range` like `text/*` covers the media type `essence`.
+pub(crate) fn media_matches(range: &str, essence: &str) -> bool {
+    match range.strip_suffix("/*") {
+        Some(top) => essence
+            .split_once('/')
+            .is_some_and(|(ty, sub)| ty == top && !sub.is_empty()),
+        None => range == essence || range == "*/*",
+    }
+}
+
+pub(crate) fn has_body<B>(req: &Request<B>) -> bool {
+    let headers = req.headers();
+    headers.contains_key(header::TRANSFER_ENCODING)
+        || headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .is_some_and(|len| len > 0)
+}
+
+impl Middleware for ContentTypeGuard {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            if let Err(error) = self.check(&req) {
+                return error.into_response();
+            }
+            next.run(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, headers: &[(&str, &str)]) -> Request<()> {
+        let mut builder = Request::builder().method(method).uri("/");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn test_allows_json_with_charset() {
+        let guard = ContentTypeGuard::json();
+        assert!(guard.allows("application/json"));
+        assert!(guard.allows("application/json; charset=utf-8"));
+        assert!(guard.allows("Application/JSON;charset=UTF-8"));
+        assert!(!guard.allows("application/jsonp"));
+        assert!(!guard.allows("text/plain"));
+
+        let req = request(
+            "POST",
+            &[
+                ("content-type", "application/json; charset=utf-8"),
+                ("content-length", "2"),
+            ],
+        );
+        assert!(guard.check(&req).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_wrong_type() {
+        let guard = ContentTypeGuard::json();
+        let req = request(
+            "POST",
+            &[("content-type", "text/plain"), ("content-length", "5")],
+        );
+
+        let error = guard.check(&req).unwrap_err();
+        assert_eq!(error.status, 415);
+        assert_eq!(error.code, "UNSUPPORTED_MEDIA_TYPE");
+        assert_eq!(
+            error.message,
+            "unsupported content type 'text/plain'; expected application/json"
+        );
+        assert_eq!(
+            error.details,
+            Some(serde_json::json!({ "allowed": ["application/json"] }))
+        );
+    }
+
+    #[test]
+    fn test_rejects_body_without_content_type() {
+        let guard = ContentTypeGuard::json();
+
+        let sized = request("PUT", &[("content-length", "10")]);
+        let error = guard.check(&sized).unwrap_err();
+        assert_eq!(error.status, 415);
+        assert_eq!(
+            error.message,
+            "missing Content-Type; expected application/json"
+        );
+
+        let chunked = request("POST", &[("transfer-encoding", "chunked")]);
+        assert_eq!(guard.check(&chunked).unwrap_err().status, 415);
+    }
+
+    #[test]
+    fn test_bodiless_requests_pass() {
+        let guard = ContentTypeGuard::json();
+        for method in ["GET", "HEAD", "DELETE", "POST"] {
+            assert!(guard.check(&request(method, &[])).is_ok());
+        }
+        let empty = request(
+            "POST",
+            &[("content-type", "text/plain"), ("content-length", "0")],
+        );
+        assert!(guard.check(&empty).is_ok());
+    }
+
+    #[test]
+    fn test_wildcard_subtypes() {
+        let guard = ContentTypeGuard::new(["image/*", " Text/CSV "]);
+        assert_eq!(guard.allowed(), ["image/*", "text/csv"]);
+        assert!(guard.allows("image/png"));
+        assert!(guard.allows("text/csv; header=present"));
+        assert!(!guard.allows("image/"));
+        assert!(!guard.allows("application/json"));
+    }
+}
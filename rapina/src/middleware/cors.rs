@@ -3,11 +3,14 @@
 //! Provides configurable CORS support for Rapina applications,
 //! handling preflight OPTIONS requests and adding appropriate headers.
 
+use std::time::Duration;
+
 use http::{HeaderValue, Method, Request, Response, StatusCode, header};
 use hyper::body::Incoming;
 
 use crate::context::RequestContext;
-use crate::response::BoxBody;
+use crate::response::{BoxBody, add_vary};
+use crate::router::Router;
 
 use super::{BoxFuture, Middleware, Next};
 
@@ -22,6 +25,12 @@ pub struct CorsConfig {
     pub allowed_methods: AllowedMethods,
     /// Allowed request headers.
     pub allowed_headers: AllowedHeaders,
+    /// Whether browsers may send cookies and credentials with requests.
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight response.
+    pub max_age: Option<Duration>,
+    /// Response headers scripts may read, beyond the CORS-safelisted ones.
+    pub exposed_headers: Vec<header::HeaderName>,
 }
 
 impl CorsConfig {
@@ -33,6 +42,9 @@ impl CorsConfig {
             allowed_origins: AllowedOrigins::Any,
             allowed_methods: AllowedMethods::Any,
             allowed_headers: AllowedHeaders::Any,
+            allow_credentials: false,
+            max_age: None,
+            exposed_headers: Vec::new(),
         }
     }
 
@@ -52,8 +64,60 @@ impl CorsConfig {
             ]),
             allowed_origins: AllowedOrigins::Exact(origins),
             allowed_headers: AllowedHeaders::List(vec![header::ACCEPT, header::AUTHORIZATION]),
+            allow_credentials: false,
+            max_age: None,
+            exposed_headers: Vec::new(),
         }
     }
+
+    /// Lets browsers send cookies and credentials, such as a session cookie
+    /// from a single-page app on another origin.
+    ///
+    /// With credentials allowed, `*` is never sent: any methods or headers
+    /// are answered by echoing what the preflight asked for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config allows any origin. Browsers refuse credentials
+    /// for `*`, so list the origins with [`with_origins`](Self::with_origins).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rapina::middleware::CorsConfig;
+    ///
+    /// let cors = CorsConfig::with_origins(vec!["https://app.example.com".to_string()])
+    ///     .allow_credentials(true)
+    ///     .max_age(Duration::from_secs(600));
+    /// ```
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self.assert_valid();
+        self
+    }
+
+    /// Lets browsers cache preflight responses for `max_age`, sent as
+    /// `Access-Control-Max-Age`.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Lets scripts read these response headers, sent as
+    /// `Access-Control-Expose-Headers`.
+    pub fn exposed_headers(mut self, headers: Vec<header::HeaderName>) -> Self {
+        self.exposed_headers = headers;
+        self
+    }
+
+    /// Panics if credentials are allowed for any origin.
+    pub(crate) fn assert_valid(&self) {
+        assert!(
+            !(self.allow_credentials && matches!(self.allowed_origins, AllowedOrigins::Any)),
+            "CORS credentials can't be allowed for any origin; list the origins with CorsConfig::with_origins"
+        );
+    }
 }
 
 /// Specifies which headers are allowed in CORS requests.
@@ -84,38 +148,86 @@ pub enum AllowedOrigins {
 }
 
 /// Middleware that handles CORS headers and preflight requests.
+///
+/// Routes can carry their own [`CorsConfig`] via [`Router::cors`](crate::router::Router::cors).
+/// The route's config wins over the one given here. Preflight requests are
+/// resolved against the route their `Access-Control-Request-Method` and path
+/// would reach, so `/admin/*` preflights answer with the admin policy.
 #[derive(Debug, Clone)]
 pub struct CorsMiddleware {
-    config: CorsConfig,
+    config: Option<CorsConfig>,
 }
 
 impl CorsMiddleware {
     /// Creates a new CORS middleware with the given configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config allows credentials for any origin.
     pub fn new(config: CorsConfig) -> Self {
-        Self { config }
+        config.assert_valid();
+        Self {
+            config: Some(config),
+        }
+    }
+
+    /// Middleware that only serves routes with their own [`CorsConfig`].
+    ///
+    /// Added automatically when routes use [`Router::cors`](crate::router::Router::cors)
+    /// but the app has no global policy.
+    pub(crate) fn route_scoped() -> Self {
+        Self { config: None }
+    }
+
+    /// The config that applies to `method` requests for `path`.
+    fn resolve<'a>(
+        &'a self,
+        router: &'a Router,
+        method: &Method,
+        path: &str,
+    ) -> Option<&'a CorsConfig> {
+        router.cors_for(method, path).or(self.config.as_ref())
+    }
+}
+
+impl CorsConfig {
+    fn allow_origin(&self, origin: &Option<HeaderValue>) -> Option<HeaderValue> {
+        match &self.allowed_origins {
+            // Browsers reject `*` on credentialed requests, and reflecting the
+            // origin would trust every site; `assert_valid` forbids this
+            // combination, so fail closed if it is ever reached.
+            AllowedOrigins::Any if self.allow_credentials => None,
+            AllowedOrigins::Any => Some(HeaderValue::from_static("*")),
+            AllowedOrigins::Exact(origins) => origin
+                .as_ref()
+                .filter(|o| {
+                    let origin_str = o.to_str().unwrap_or("");
+                    origins.iter().any(|allowed| allowed == origin_str)
+                })
+                .cloned(),
+        }
     }
 
-    fn preflight_response(&self, origin: &Option<HeaderValue>) -> Response<BoxBody> {
+    fn preflight_response(
+        &self,
+        origin: &Option<HeaderValue>,
+        requested_method: &Method,
+        requested_headers: Option<&HeaderValue>,
+    ) -> Response<BoxBody> {
         let mut builder = Response::builder().status(StatusCode::NO_CONTENT);
 
         // Set Access-Control-Allow-Origin
-        match &self.config.allowed_origins {
-            AllowedOrigins::Any => {
-                builder = builder.header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
-            }
-            AllowedOrigins::Exact(origins) => {
-                if let Some(req_origin) = origin {
-                    let origin_str = req_origin.to_str().unwrap_or("");
-                    if origins.iter().any(|o| o == origin_str) {
-                        builder =
-                            builder.header(header::ACCESS_CONTROL_ALLOW_ORIGIN, req_origin.clone());
-                    }
-                }
+        if let Some(allowed) = self.allow_origin(origin) {
+            builder = builder.header(header::ACCESS_CONTROL_ALLOW_ORIGIN, allowed);
+            if self.allow_credentials {
+                builder = builder.header(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
             }
         }
 
-        // Set Access-Control-Allow-Methods
-        let methods_value = match &self.config.allowed_methods {
+        // Set Access-Control-Allow-Methods. `*` is taken literally on
+        // credentialed requests, so echo the requested method instead
+        let methods_value = match &self.allowed_methods {
+            AllowedMethods::Any if self.allow_credentials => requested_method.to_string(),
             AllowedMethods::Any => "*".to_string(),
             AllowedMethods::List(methods) => methods
                 .iter()
@@ -125,16 +237,30 @@ impl CorsMiddleware {
         };
         builder = builder.header(header::ACCESS_CONTROL_ALLOW_METHODS, methods_value);
 
-        // Set Access-Control-Allow-Headers
-        let headers_value = match &self.config.allowed_headers {
-            AllowedHeaders::Any => "*".to_string(),
-            AllowedHeaders::List(headers) => headers
-                .iter()
-                .map(|h| h.as_str())
-                .collect::<Vec<_>>()
-                .join(", "),
-        };
-        builder = builder.header(header::ACCESS_CONTROL_ALLOW_HEADERS, headers_value);
+        // Set Access-Control-Allow-Headers, echoing the requested ones for
+        // the same reason
+        match &self.allowed_headers {
+            AllowedHeaders::Any if self.allow_credentials => {
+                if let Some(requested) = requested_headers {
+                    builder = builder.header(header::ACCESS_CONTROL_ALLOW_HEADERS, requested);
+                }
+            }
+            AllowedHeaders::Any => {
+                builder = builder.header(header::ACCESS_CONTROL_ALLOW_HEADERS, "*");
+            }
+            AllowedHeaders::List(headers) => {
+                let headers_value = headers
+                    .iter()
+                    .map(|h| h.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                builder = builder.header(header::ACCESS_CONTROL_ALLOW_HEADERS, headers_value);
+            }
+        }
+
+        if let Some(max_age) = self.max_age {
+            builder = builder.header(header::ACCESS_CONTROL_MAX_AGE, max_age.as_secs());
+        }
 
         builder = builder.header(header::VARY, "Origin");
 
@@ -145,25 +271,29 @@ impl CorsMiddleware {
         let headers = response.headers_mut();
 
         // Set Access-Control-Allow-Origin
-        match &self.config.allowed_origins {
-            AllowedOrigins::Any => {
+        if let Some(allowed) = self.allow_origin(origin) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allowed);
+            if self.allow_credentials {
                 headers.insert(
-                    header::ACCESS_CONTROL_ALLOW_ORIGIN,
-                    HeaderValue::from_static("*"),
+                    header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                    HeaderValue::from_static("true"),
                 );
             }
-            AllowedOrigins::Exact(origins) => {
-                if let Some(req_origin) = origin {
-                    let origin_str = req_origin.to_str().unwrap_or("");
-                    if origins.iter().any(|o| o == origin_str) {
-                        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, req_origin.clone());
-                    }
+            if !self.exposed_headers.is_empty() {
+                let exposed = self
+                    .exposed_headers
+                    .iter()
+                    .map(|h| h.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if let Ok(value) = HeaderValue::from_str(&exposed) {
+                    headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, value);
                 }
             }
         }
 
-        // Vary header
-        headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+        // Vary header, kept alongside e.g. compression's Accept-Encoding
+        add_vary(headers, "Origin");
     }
 }
 
@@ -176,15 +306,153 @@ impl Middleware for CorsMiddleware {
     ) -> BoxFuture<'a, Response<BoxBody>> {
         Box::pin(async move {
             let origin = req.headers().get(header::ORIGIN).cloned();
+            let path = req.uri().path().to_string();
 
             // if it's OPTIONS (preflight), return early with 204 + CORS headers
             if req.method() == Method::OPTIONS {
-                return self.preflight_response(&origin);
+                let requested_method = req
+                    .headers()
+                    .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+                    .and_then(|v| Method::from_bytes(v.as_bytes()).ok())
+                    .unwrap_or(Method::OPTIONS);
+                if let Some(config) = self.resolve(next.router(), &requested_method, &path) {
+                    let requested_headers =
+                        req.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS);
+                    return config.preflight_response(
+                        &origin,
+                        &requested_method,
+                        requested_headers,
+                    );
+                }
+                return next.run(req).await;
             }
 
+            let method = req.method().clone();
+            let router = next.router();
             let mut response = next.run(req).await;
-            self.add_cors_headers(&mut response, &origin);
+            if let Some(config) = self.resolve(router, &method, &path) {
+                config.add_cors_headers(&mut response, &origin);
+            }
             response
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(value: &'static str) -> Option<HeaderValue> {
+        Some(HeaderValue::from_static(value))
+    }
+
+    #[test]
+    fn test_allow_origin_any() {
+        let config = CorsConfig::permissive();
+        assert_eq!(config.allow_origin(&None).unwrap(), "*");
+    }
+
+    #[test]
+    fn test_allow_origin_exact() {
+        let config = CorsConfig::with_origins(vec!["https://app.example.com".to_string()]);
+        assert_eq!(
+            config
+                .allow_origin(&origin("https://app.example.com"))
+                .unwrap(),
+            "https://app.example.com"
+        );
+        assert!(config.allow_origin(&origin("https://evil.com")).is_none());
+        assert!(config.allow_origin(&None).is_none());
+    }
+
+    fn credentialed() -> CorsConfig {
+        CorsConfig::with_origins(vec!["https://app.example.com".to_string()])
+            .allow_credentials(true)
+    }
+
+    #[test]
+    fn test_allow_origin_fails_closed_for_any_with_credentials() {
+        let mut config = CorsConfig::permissive();
+        config.allow_credentials = true;
+        assert!(config.allow_origin(&origin("https://anyone.com")).is_none());
+        assert!(config.allow_origin(&None).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be allowed for any origin")]
+    fn test_credentials_rejected_for_any_origin() {
+        let _ = CorsConfig::permissive().allow_credentials(true);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be allowed for any origin")]
+    fn test_middleware_rejects_credentials_for_any_origin() {
+        let mut config = CorsConfig::permissive();
+        config.allow_credentials = true;
+        let _ = CorsMiddleware::new(config);
+    }
+
+    #[test]
+    fn test_preflight_echoes_any_with_credentials() {
+        let mut config = credentialed();
+        config.allowed_methods = AllowedMethods::Any;
+        config.allowed_headers = AllowedHeaders::Any;
+
+        let requested = HeaderValue::from_static("content-type, x-csrf-token");
+        let response = config.preflight_response(
+            &origin("https://app.example.com"),
+            &Method::PATCH,
+            Some(&requested),
+        );
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "PATCH");
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "content-type, x-csrf-token"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    }
+
+    #[test]
+    fn test_preflight_max_age() {
+        let config = CorsConfig::permissive().max_age(Duration::from_secs(600));
+        let response = config.preflight_response(&origin("https://a.com"), &Method::GET, None);
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_MAX_AGE], "600");
+        assert!(
+            !response
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        );
+    }
+
+    #[test]
+    fn test_route_config_wins_over_global() {
+        let admin = Router::new()
+            .route(Method::GET, "/users", |_, _, _| async { "ok" })
+            .cors(CorsConfig::with_origins(vec![
+                "https://dash.internal".to_string(),
+            ]));
+        let router =
+            Router::new()
+                .group("/admin", admin)
+                .route(Method::GET, "/public", |_, _, _| async { "ok" });
+        let middleware = CorsMiddleware::new(CorsConfig::permissive());
+
+        let admin = middleware
+            .resolve(&router, &Method::GET, "/admin/users")
+            .unwrap();
+        assert!(matches!(admin.allowed_origins, AllowedOrigins::Exact(_)));
+
+        let public = middleware
+            .resolve(&router, &Method::GET, "/public")
+            .unwrap();
+        assert!(matches!(public.allowed_origins, AllowedOrigins::Any));
+    }
+
+    #[test]
+    fn test_route_scoped_has_no_default() {
+        let router = Router::new().route(Method::GET, "/", |_, _, _| async { "ok" });
+        let middleware = CorsMiddleware::route_scoped();
+        assert!(middleware.resolve(&router, &Method::GET, "/").is_none());
+    }
+}
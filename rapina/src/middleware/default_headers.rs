@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use hyper::body::Incoming;
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::{Request, Response};
+
+use crate::context::RequestContext;
+use crate::response::BoxBody;
+use crate::state::AppState;
+
+use super::{BoxFuture, Middleware, Next};
+
+type LazyValue = Arc<dyn Fn(&AppState) -> Option<String> + Send + Sync>;
+
+/// Value of a default response header: fixed, or computed for each request.
+///
+/// Fixed values convert from `&str`, `String` and [`HeaderValue`]. Use
+/// [`lazy`](Self::lazy) for values read from application state.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::middleware::DefaultHeaderValue;
+///
+/// struct Build(String);
+///
+/// let fixed = DefaultHeaderValue::from("2024-06-01");
+/// let lazy = DefaultHeaderValue::lazy(|state| state.get::<Build>().map(|b| b.0.clone()));
+/// ```
+#[derive(Clone)]
+pub enum DefaultHeaderValue {
+    /// The same value on every response.
+    Static(HeaderValue),
+    /// Computed per request; `None` or an invalid value skips the header.
+    Lazy(LazyValue),
+}
+
+impl DefaultHeaderValue {
+    /// A value computed from application state for each response.
+    pub fn lazy<F>(f: F) -> Self
+    where
+        F: Fn(&AppState) -> Option<String> + Send + Sync + 'static,
+    {
+        DefaultHeaderValue::Lazy(Arc::new(f))
+    }
+
+    fn resolve(&self, name: &HeaderName, state: &AppState) -> Option<HeaderValue> {
+        match self {
+            DefaultHeaderValue::Static(value) => Some(value.clone()),
+            DefaultHeaderValue::Lazy(f) => {
+                let value = f(state)?;
+                match HeaderValue::from_str(&value) {
+                    Ok(value) => Some(value),
+                    Err(_) => {
+                        tracing::warn!(header = %name, value = %value, "invalid default header value, skipping");
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl From<HeaderValue> for DefaultHeaderValue {
+    fn from(value: HeaderValue) -> Self {
+        DefaultHeaderValue::Static(value)
+    }
+}
+
+impl From<&str> for DefaultHeaderValue {
+    fn from(value: &str) -> Self {
+        let value = HeaderValue::from_str(value)
+            .unwrap_or_else(|_| panic!("invalid default header value {:?}", value));
+        DefaultHeaderValue::Static(value)
+    }
+}
+
+impl From<String> for DefaultHeaderValue {
+    fn from(value: String) -> Self {
+        DefaultHeaderValue::from(value.as_str())
+    }
+}
+
+impl std::fmt::Debug for DefaultHeaderValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefaultHeaderValue::Static(value) => f.debug_tuple("Static").field(value).finish(),
+            DefaultHeaderValue::Lazy(_) => f.write_str("Lazy"),
+        }
+    }
+}
+
+/// Parses a default header name, panicking on invalid input since it is
+/// always a literal in the app's setup code.
+pub(crate) fn default_header_name(name: &str) -> HeaderName {
+    HeaderName::from_bytes(name.as_bytes())
+        .unwrap_or_else(|_| panic!("invalid default header name {:?}", name))
+}
+
+/// Adds headers to every response that doesn't already have them.
+///
+/// Registered by [`Rapina::default_header`](crate::app::Rapina::default_header)
+/// for the whole app and by [`Router::default_header`](crate::router::Router::default_header)
+/// for a group of routes. Headers set by the handler, or by route-level
+/// defaults, are never overwritten.
+#[derive(Debug, Clone, Default)]
+pub struct DefaultHeadersMiddleware {
+    headers: Vec<(HeaderName, DefaultHeaderValue)>,
+}
+
+impl DefaultHeadersMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name.
+    pub fn header(mut self, name: &str, value: impl Into<DefaultHeaderValue>) -> Self {
+        self.headers.push((default_header_name(name), value.into()));
+        self
+    }
+
+    pub(crate) fn push(&mut self, name: HeaderName, value: DefaultHeaderValue) {
+        self.headers.push((name, value));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+}
+
+impl Middleware for DefaultHeadersMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            let state = next.state().clone();
+            let mut response = next.run(req).await;
+
+            for (name, value) in &self.headers {
+                if response.headers().contains_key(name) {
+                    continue;
+                }
+                if let Some(value) = value.resolve(name, &state) {
+                    response.headers_mut().insert(name.clone(), value);
+                }
+            }
+
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Version(&'static str);
+
+    #[test]
+    fn test_static_value_resolves() {
+        let value = DefaultHeaderValue::from("v1");
+        let name = default_header_name("x-api-version");
+        assert_eq!(
+            value.resolve(&name, &AppState::new()).unwrap(),
+            HeaderValue::from_static("v1")
+        );
+    }
+
+    #[test]
+    fn test_lazy_value_reads_state() {
+        let value = DefaultHeaderValue::lazy(|state| state.get::<Version>().map(|v| v.0.into()));
+        let name = default_header_name("x-api-version");
+
+        assert!(value.resolve(&name, &AppState::new()).is_none());
+        let state = AppState::new().with(Version("2024-06-01"));
+        assert_eq!(
+            value.resolve(&name, &state).unwrap(),
+            HeaderValue::from_static("2024-06-01")
+        );
+    }
+
+    #[test]
+    fn test_invalid_lazy_value_is_skipped() {
+        let value = DefaultHeaderValue::lazy(|_| Some("bad\nvalue".to_string()));
+        let name = default_header_name("x-api-version");
+        assert!(value.resolve(&name, &AppState::new()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid default header name")]
+    fn test_invalid_header_name_panics() {
+        DefaultHeadersMiddleware::new().header("bad header", "v1");
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, Method, StatusCode};
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::{Request, Response};
+use sha2::{Digest, Sha256};
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::extract::{BodyDeadline, BodyLimit, read_body_limited};
+use crate::response::{BoxBody, IntoResponse};
+use crate::service::replay_body;
+
+use super::{BoxFuture, Middleware, Next};
+
+/// Header carrying the client's idempotency key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header added to responses replayed from the store.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+const MAX_KEY_LEN: usize = 255;
+
+/// A response recorded for an idempotency key.
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    /// SHA-256 of the request body that produced the response, hex-encoded.
+    pub request_hash: String,
+}
+
+/// Where [`IdempotencyMiddleware`] keeps recorded responses.
+///
+/// Implement this trait to share keys between instances, e.g. in Redis or
+/// the database. [`MemoryIdempotencyStore`] is used by default.
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Returns the response stored under `key`, if it hasn't expired.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<StoredResponse>>;
+
+    /// Stores `response` under `key` for `ttl`.
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> BoxFuture<'a, ()>;
+}
+
+/// In-process [`IdempotencyStore`]. Clones share the same entries.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryIdempotencyStore {
+    entries: Arc<Mutex<HashMap<String, (StoredResponse, Instant)>>>,
+}
+
+impl MemoryIdempotencyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of stored responses, including expired ones not yet evicted.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns `true` if nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<StoredResponse>> {
+        Box::pin(async move {
+            let mut entries = self.entries.lock().unwrap();
+            match entries.get(key) {
+                Some((response, expires_at)) if *expires_at > Instant::now() => {
+                    Some(response.clone())
+                }
+                Some(_) => {
+                    entries.remove(key);
+                    None
+                }
+                None => None,
+            }
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let mut entries = self.entries.lock().unwrap();
+            let now = Instant::now();
+            entries.retain(|_, (_, expires_at)| *expires_at > now);
+            entries.insert(key.to_string(), (response, now + ttl));
+        })
+    }
+}
+
+/// Makes `POST` and `PATCH` requests carrying an `Idempotency-Key` header
+/// safe to retry.
+///
+/// The first response for a key is recorded, keyed by the method, path and
+/// key, together with a hash of the request body. A retry with the same key
+/// and body gets the recorded response back, marked `Idempotent-Replayed:
+/// true`, without running the handler again. The same key with a different
+/// body is answered with 409 Conflict.
+///
+/// Requests with the same key that arrive while the first is still running
+/// wait for it and then get its response, so the handler runs once.
+///
+/// `5xx` responses and response bodies over the size cap are not recorded,
+/// so those requests run again when retried. Requests without the header, or
+/// with other methods, pass straight through.
+///
+/// # Example
+///
+/// ```ignore
+/// use rapina::middleware::IdempotencyMiddleware;
+///
+/// Rapina::new()
+///     .middleware(IdempotencyMiddleware::new())
+///     .router(router)
+/// ```
+#[derive(Clone)]
+pub struct IdempotencyMiddleware {
+    store: Arc<dyn IdempotencyStore>,
+    ttl: Duration,
+    max_body_size: usize,
+    in_flight: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl std::fmt::Debug for IdempotencyMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdempotencyMiddleware")
+            .field("ttl", &self.ttl)
+            .field("max_body_size", &self.max_body_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IdempotencyMiddleware {
+    /// Records responses in memory for 24 hours, with bodies up to 1MB.
+    pub fn new() -> Self {
+        Self {
+            store: Arc::new(MemoryIdempotencyStore::new()),
+            ttl: DEFAULT_TTL,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Records responses in `store` instead of in memory.
+    pub fn store(mut self, store: impl IdempotencyStore) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// Sets how long a recorded response is replayed for.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the largest response body that is recorded.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    fn lock_for(&self, key: &str) -> InFlight<'_> {
+        let lock = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        InFlight {
+            in_flight: &self.in_flight,
+            key: key.to_string(),
+            lock,
+        }
+    }
+
+    async fn run(
+        &self,
+        req: Request<Incoming>,
+        idempotency_key: String,
+        next: Next<'_>,
+    ) -> Result<Response<BoxBody>, Error> {
+        let key = format!("{} {} {}", req.method(), req.uri().path(), idempotency_key);
+
+        let (parts, body) = req.into_parts();
+        let mut buffered = Request::new(body);
+        if let Some(limit) = parts.extensions.get::<BodyLimit>() {
+            buffered.extensions_mut().insert(*limit);
+        }
+        if let Some(deadline) = parts.extensions.get::<BodyDeadline>() {
+            buffered.extensions_mut().insert(*deadline);
+        }
+        let body = read_body_limited(buffered).await?;
+        let request_hash = hex_digest(&body);
+
+        let in_flight = self.lock_for(&key);
+        let _guard = in_flight.lock.lock().await;
+        self.respond(&key, parts, body, request_hash, next).await
+    }
+
+    async fn respond(
+        &self,
+        key: &str,
+        parts: http::request::Parts,
+        body: Bytes,
+        request_hash: String,
+        next: Next<'_>,
+    ) -> Result<Response<BoxBody>, Error> {
+        if let Some(stored) = self.store.get(key).await {
+            if stored.request_hash != request_hash {
+                return Err(Error::conflict(
+                    "Idempotency-Key was already used with a different request body",
+                ));
+            }
+            let mut response = Response::new(BoxBody::new(stored.body));
+            *response.status_mut() = stored.status;
+            *response.headers_mut() = stored.headers;
+            response
+                .headers_mut()
+                .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+            return Ok(response);
+        }
+
+        let (incoming, _connection) = replay_body(body).await?;
+        let response = next.run(Request::from_parts(parts, incoming)).await;
+        // A streamed body may never end, so it can't be stored for replay
+        if response.status().is_server_error() || response.body().is_stream() {
+            return Ok(response);
+        }
+
+        let (parts, body) = response.into_parts();
+        let body = match body.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            // Nothing is stored, so a retry runs the handler again
+            Err(e) => {
+                tracing::error!("failed to read response body for idempotency key: {}", e);
+                return Ok(Error::internal("failed to read response body").into_response());
+            }
+        };
+        if body.len() <= self.max_body_size {
+            let stored = StoredResponse {
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+                request_hash,
+            };
+            self.store.put(key, stored, self.ttl).await;
+        }
+        Ok(Response::from_parts(parts, BoxBody::new(body)))
+    }
+}
+
+/// A request's share of the lock for its key. Dropping it, also when the
+/// request is cancelled, forgets the lock once no other request is waiting
+/// on it.
+struct InFlight<'a> {
+    in_flight: &'a Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    key: String,
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        // One reference in the map and one held here
+        if Arc::strong_count(&self.lock) <= 2 {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+impl Default for IdempotencyMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for IdempotencyMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            if req.method() != Method::POST && req.method() != Method::PATCH {
+                return next.run(req).await;
+            }
+            let Some(value) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+                return next.run(req).await;
+            };
+            let idempotency_key = match parse_key(value) {
+                Ok(key) => key,
+                Err(error) => return error.into_response(),
+            };
+
+            match self.run(req, idempotency_key, next).await {
+                Ok(response) => response,
+                Err(error) => error.into_response(),
+            }
+        })
+    }
+}
+
+fn parse_key(value: &HeaderValue) -> Result<String, Error> {
+    let key = value
+        .to_str()
+        .map_err(|_| Error::bad_request("Idempotency-Key must be visible ASCII"))?
+        .trim();
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(Error::bad_request(format!(
+            "Idempotency-Key must be between 1 and {} characters",
+            MAX_KEY_LEN
+        )));
+    }
+    Ok(key.to_string())
+}
+
+fn hex_digest(body: &[u8]) -> String {
+    Sha256::digest(body)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::MiddlewareStack;
+    use crate::router::Router;
+    use crate::state::AppState;
+
+    fn stored(body: &'static str) -> StoredResponse {
+        StoredResponse {
+            status: StatusCode::CREATED,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(body.as_bytes()),
+            request_hash: hex_digest(b"{}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_releases_its_key() {
+        let entered = Arc::new(tokio::sync::Notify::new());
+        let handler_entered = entered.clone();
+        let router = Router::new().route(Method::POST, "/orders", move |_, _, _| {
+            let entered = handler_entered.clone();
+            async move {
+                entered.notify_one();
+                std::future::pending::<&'static str>().await
+            }
+        });
+        let middleware = IdempotencyMiddleware::new();
+        let mut stack = MiddlewareStack::new();
+        stack.add(middleware.clone());
+        let state = Arc::new(AppState::new());
+        let ctx = RequestContext::new();
+
+        let (body, _connection) = replay_body(Bytes::from_static(b"{}")).await.unwrap();
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/orders")
+            .header(IDEMPOTENCY_KEY_HEADER, "order-1")
+            .body(body)
+            .unwrap();
+        tokio::select! {
+            _ = stack.execute(req, &router, &state, &ctx) => panic!("the handler never returns"),
+            _ = entered.notified() => {}
+        }
+
+        assert!(middleware.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_roundtrip() {
+        let store = MemoryIdempotencyStore::new();
+        assert!(store.get("POST /payments abc").await.is_none());
+
+        store
+            .put("POST /payments abc", stored("ok"), Duration::from_secs(60))
+            .await;
+        let response = store.get("POST /payments abc").await.unwrap();
+        assert_eq!(response.status, StatusCode::CREATED);
+        assert_eq!(response.body, "ok");
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_expires_entries() {
+        let store = MemoryIdempotencyStore::new();
+        store
+            .put("POST /payments abc", stored("ok"), Duration::ZERO)
+            .await;
+        assert!(store.get("POST /payments abc").await.is_none());
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(
+            parse_key(&HeaderValue::from_static(" order-1 ")).unwrap(),
+            "order-1"
+        );
+        assert_eq!(
+            parse_key(&HeaderValue::from_static("")).unwrap_err().status,
+            400
+        );
+        let long = HeaderValue::from_str(&"k".repeat(MAX_KEY_LEN + 1)).unwrap();
+        assert_eq!(parse_key(&long).unwrap_err().status, 400);
+    }
+
+    #[test]
+    fn test_hex_digest() {
+        assert_eq!(
+            hex_digest(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
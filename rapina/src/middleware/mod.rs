@@ -7,23 +7,55 @@
 //!
 //! - [`TimeoutMiddleware`] - Request timeout handling
 //! - [`BodyLimitMiddleware`] - Limit request body size
+//! - [`ContentTypeGuard`] - Reject bodies outside a `Content-Type` allow-list
 //! - [`TraceIdMiddleware`] - Add trace IDs to requests/responses
 //! - [`RequestLogMiddleware`] - Structured request logging
+//! - [`TraceMiddleware`] - Request spans with route, status and latency
+//! - [`CacheMiddleware`] - Cache successful `GET` responses
+//! - [`IdempotencyMiddleware`] - Replay responses for retried `Idempotency-Key` requests
+//! - [`DefaultHeadersMiddleware`] - Add headers to responses that lack them
+//! - `TowerLayerMiddleware` - Run `tower` layers (requires the `tower` feature)
 
 mod body_limit;
+mod cache;
 mod compression;
+mod concurrency;
+mod content_type;
 mod cors;
+mod default_headers;
+mod idempotency;
+mod phase;
 mod rate_limit;
 mod request_log;
 mod timeout;
+#[cfg(feature = "tower")]
+mod tower_compat;
+mod trace;
 mod trace_id;
 
 pub use body_limit::BodyLimitMiddleware;
-pub use compression::{CompressionConfig, CompressionMiddleware};
+pub(crate) use body_limit::RouteBodyLimit;
+pub use cache::{CacheConfig, CacheMiddleware};
+pub use compression::{CompressionAlgorithm, CompressionConfig, CompressionMiddleware};
+pub use concurrency::ConcurrencyLimit;
+pub(crate) use concurrency::ConcurrencyLimitMiddleware;
+pub use content_type::ContentTypeGuard;
+pub(crate) use content_type::{has_body, media_essence, media_matches};
 pub use cors::{AllowedHeaders, AllowedMethods, AllowedOrigins, CorsConfig, CorsMiddleware};
+pub(crate) use default_headers::default_header_name;
+pub use default_headers::{DefaultHeaderValue, DefaultHeadersMiddleware};
+pub use idempotency::{
+    IDEMPOTENCY_KEY_HEADER, IDEMPOTENT_REPLAYED_HEADER, IdempotencyMiddleware, IdempotencyStore,
+    MemoryIdempotencyStore, StoredResponse,
+};
+pub use phase::{MiddlewareInfo, Phase};
 pub use rate_limit::{KeyExtractor, RateLimitConfig, RateLimitMiddleware};
 pub use request_log::RequestLogMiddleware;
-pub use timeout::TimeoutMiddleware;
+pub(crate) use request_log::{SlowLogConfig, SlowRequestMiddleware};
+pub use timeout::{NoTimeout, TimeoutMiddleware};
+#[cfg(feature = "tower")]
+pub use tower_compat::{NextService, TowerLayerMiddleware};
+pub use trace::TraceMiddleware;
 pub use trace_id::{TRACE_ID_HEADER, TraceIdMiddleware};
 
 use std::future::Future;
@@ -34,8 +66,10 @@ use hyper::body::Incoming;
 use hyper::{Request, Response};
 
 use crate::context::RequestContext;
+use crate::extract::PathParams;
+use crate::json::{JsonOptions, ResponseLimits};
 use crate::response::BoxBody;
-use crate::router::Router;
+use crate::router::{Route, Router};
 use crate::state::AppState;
 
 /// A boxed future type used by middleware.
@@ -75,6 +109,12 @@ pub trait Middleware: Send + Sync + 'static {
         ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>>;
+
+    /// Name shown in the resolved middleware order, the type name by
+    /// default.
+    fn name(&self) -> &'static str {
+        phase::short_type_name(std::any::type_name::<Self>())
+    }
 }
 
 /// Represents the next middleware or handler in the chain.
@@ -83,6 +123,9 @@ pub struct Next<'a> {
     router: &'a Router,
     state: &'a Arc<AppState>,
     ctx: &'a RequestContext,
+    /// Set when the chain is a route's own middleware, which ends at that
+    /// route's handler instead of the router.
+    route: Option<(&'a Route, PathParams)>,
 }
 
 impl<'a> Next<'a> {
@@ -97,9 +140,37 @@ impl<'a> Next<'a> {
             router,
             state,
             ctx,
+            route: None,
+        }
+    }
+
+    /// A chain over `route`'s own middleware, already matched with `params`.
+    pub(crate) fn for_route(
+        router: &'a Router,
+        route: &'a Route,
+        params: PathParams,
+        state: &'a Arc<AppState>,
+        ctx: &'a RequestContext,
+    ) -> Self {
+        Self {
+            middlewares: &route.middlewares,
+            router,
+            state,
+            ctx,
+            route: Some((route, params)),
         }
     }
 
+    /// The router requests are dispatched to once the chain ends.
+    pub(crate) fn router(&self) -> &'a Router {
+        self.router
+    }
+
+    /// The application state handlers receive.
+    pub(crate) fn state(&self) -> &'a Arc<AppState> {
+        self.state
+    }
+
     /// Runs the next middleware or handler in the chain.
     pub async fn run(self, req: Request<Incoming>) -> Response<BoxBody> {
         if let Some((current, rest)) = self.middlewares.split_first() {
@@ -108,43 +179,101 @@ impl<'a> Next<'a> {
                 router: self.router,
                 state: self.state,
                 ctx: self.ctx,
+                route: self.route,
             };
             current.handle(req, self.ctx, next).await
+        } else if let Some((route, params)) = self.route {
+            route.call(req, params, self.state.clone()).await
         } else {
-            self.router.handle(req, self.state).await
+            // Router::handle runs route middleware through Next again
+            Box::pin(self.router.handle(req, self.state)).await
         }
     }
 }
 
 /// A stack of middleware to be executed in order.
+///
+/// Middleware are grouped by [`Phase`], and run in the order they were
+/// added within their phase.
 pub struct MiddlewareStack {
     middlewares: Vec<Arc<dyn Middleware>>,
+    phases: Vec<Phase>,
 }
 
 impl MiddlewareStack {
     pub fn new() -> Self {
         Self {
             middlewares: Vec::new(),
+            phases: Vec::new(),
         }
     }
 
+    /// Adds a middleware to the [`Normal`](Phase::Normal) phase.
     pub fn add<M: Middleware>(&mut self, middleware: M) {
-        self.middlewares.push(Arc::new(middleware));
+        self.add_in(Phase::Normal, middleware);
+    }
+
+    /// Adds a middleware to the end of `phase`.
+    pub fn add_in<M: Middleware>(&mut self, phase: Phase, middleware: M) {
+        self.push_in(phase, Arc::new(middleware));
     }
 
+    /// Adds a middleware to the [`Normal`](Phase::Normal) phase.
     pub fn push(&mut self, middleware: Arc<dyn Middleware>) {
-        self.middlewares.push(middleware);
+        self.push_in(Phase::Normal, middleware);
+    }
+
+    /// Adds a middleware to the end of `phase`.
+    pub fn push_in(&mut self, phase: Phase, middleware: Arc<dyn Middleware>) {
+        let at = self
+            .phases
+            .iter()
+            .take_while(|p| p.rank() <= phase.rank())
+            .count();
+        self.middlewares.insert(at, middleware);
+        self.phases.insert(at, phase);
+    }
+
+    /// Adds a middleware that runs before all others, so it sees every
+    /// response, including ones other middleware answer early.
+    pub(crate) fn push_front(&mut self, middleware: Arc<dyn Middleware>) {
+        self.middlewares.insert(0, middleware);
+        self.phases.insert(0, Phase::PostProcessing);
+    }
+
+    /// The middleware in the order they run, outermost first.
+    pub fn describe(&self) -> Vec<MiddlewareInfo> {
+        self.middlewares
+            .iter()
+            .zip(&self.phases)
+            .map(|(middleware, &phase)| MiddlewareInfo {
+                name: middleware.name().to_string(),
+                phase,
+            })
+            .collect()
+    }
+
+    /// Warnings for known bad orderings, such as CORS running after auth.
+    pub fn ordering_warnings(&self) -> Vec<String> {
+        phase::ordering_warnings(&self.describe())
     }
 
     pub async fn execute(
         &self,
-        req: Request<Incoming>,
+        mut req: Request<Incoming>,
         router: &Router,
         state: &Arc<AppState>,
         ctx: &RequestContext,
     ) -> Response<BoxBody> {
+        router.record_matched_route(&mut req, ctx);
         let next = Next::new(&self.middlewares, router, state, ctx);
-        next.run(req).await
+        let limits = state.get::<ResponseLimits>().copied().unwrap_or_default();
+        let run = limits.scope(next.run(req));
+        let response = match state.get::<JsonOptions>() {
+            Some(options) => options.scope(run).await,
+            None => run.await,
+        };
+        limits.check(response)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -183,6 +312,28 @@ mod tests {
         assert!(stack.is_empty());
     }
 
+    #[test]
+    fn test_middleware_stack_orders_by_phase() {
+        let mut stack = MiddlewareStack::new();
+        stack.add(TestMiddleware);
+        stack.add_in(Phase::Auth, TimeoutMiddleware::default());
+        stack.add_in(Phase::PostProcessing, TraceIdMiddleware::new());
+
+        let order: Vec<_> = stack
+            .describe()
+            .into_iter()
+            .map(|info| (info.name, info.phase))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("TraceIdMiddleware".to_string(), Phase::PostProcessing),
+                ("TimeoutMiddleware".to_string(), Phase::Auth),
+                ("TestMiddleware".to_string(), Phase::Normal),
+            ]
+        );
+    }
+
     #[test]
     fn test_middleware_stack_default() {
         let stack = MiddlewareStack::default();
@@ -224,6 +375,19 @@ mod tests {
     fn test_timeout_middleware_default() {
         let mw = TimeoutMiddleware::default();
         assert_eq!(mw.duration, Duration::from_secs(30));
+        assert_eq!(mw.status, http::StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[test]
+    fn test_timeout_middleware_status() {
+        let mw = TimeoutMiddleware::default().status(http::StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(mw.status, http::StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[test]
+    #[should_panic(expected = "timeout status must be 408, 503 or 504")]
+    fn test_timeout_middleware_rejects_other_status() {
+        let _ = TimeoutMiddleware::default().status(http::StatusCode::OK);
     }
 
     #[test]
@@ -236,6 +400,13 @@ mod tests {
     fn test_body_limit_middleware_default() {
         let mw = BodyLimitMiddleware::default();
         assert_eq!(mw.max_size, 1024 * 1024); // 1MB default
+        assert!(mw.read_timeout.is_none());
+    }
+
+    #[test]
+    fn test_body_limit_middleware_read_timeout() {
+        let mw = BodyLimitMiddleware::new(2048).read_timeout(Duration::from_secs(5));
+        assert_eq!(mw.read_timeout, Some(Duration::from_secs(5)));
     }
 
     #[test]
@@ -0,0 +1,86 @@
+//! Phases that fix where middleware runs relative to each other.
+
+use serde::{Deserialize, Serialize};
+
+/// Where in the stack a middleware runs.
+///
+/// Requests go through [`PreRouting`](Phase::PreRouting), then
+/// [`Auth`](Phase::Auth), then [`Normal`](Phase::Normal) middleware before
+/// reaching the handler. [`PostProcessing`](Phase::PostProcessing)
+/// middleware wrap all three, so they get the response last, after every
+/// other middleware is done with it. Within a phase, middleware run in the
+/// order they were added.
+///
+/// | Phase            | Built-in middleware                             |
+/// |------------------|-------------------------------------------------|
+/// | `PostProcessing` | compression, error translation                  |
+/// | `PreRouting`     | CORS, rate limiting, slow request logging       |
+/// | `Auth`           | JWT authentication                              |
+/// | `Normal`         | response caching, metrics, `Rapina::middleware` |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Before authentication: CORS preflights, rate limiting, request logging
+    PreRouting,
+    /// Authentication and anything that rejects unauthenticated requests
+    Auth,
+    /// Everything else; the phase `add` uses
+    Normal,
+    /// Rewrites final responses: compression, error translation
+    PostProcessing,
+}
+
+impl Phase {
+    /// Position in the stack, outermost first.
+    pub(crate) fn rank(self) -> u8 {
+        match self {
+            Phase::PostProcessing => 0,
+            Phase::PreRouting => 1,
+            Phase::Auth => 2,
+            Phase::Normal => 3,
+        }
+    }
+}
+
+/// A middleware's place in the resolved stack, as listed on
+/// `/__rapina/middleware`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiddlewareInfo {
+    /// Type name, without its module path
+    pub name: String,
+    pub phase: Phase,
+}
+
+/// Pairs of middleware that misbehave when the first runs inside the
+/// second: `(inner, outer, consequence)`.
+const BAD_ORDERINGS: &[(&str, &str, &str)] = &[
+    (
+        "CorsMiddleware",
+        "AuthMiddleware",
+        "CORS preflight requests are rejected by auth before they get an answer",
+    ),
+    (
+        "CompressionMiddleware",
+        "CacheMiddleware",
+        "cached responses keep the encoding of the request that filled the cache",
+    ),
+];
+
+/// Warnings for known bad orderings in a resolved stack, outermost first.
+pub(crate) fn ordering_warnings(stack: &[MiddlewareInfo]) -> Vec<String> {
+    let position = |name: &str| stack.iter().position(|info| info.name == name);
+    BAD_ORDERINGS
+        .iter()
+        .filter_map(|(inner, outer, consequence)| {
+            let (inner_at, outer_at) = (position(inner)?, position(outer)?);
+            (inner_at > outer_at).then(|| format!("{inner} runs after {outer}, so {consequence}"))
+        })
+        .collect()
+}
+
+/// `rapina::middleware::CorsMiddleware` to `CorsMiddleware`, generics
+/// dropped.
+pub(crate) fn short_type_name(name: &'static str) -> &'static str {
+    let base = name.split('<').next().unwrap_or(name);
+    base.rsplit("::").next().unwrap_or(base)
+}
@@ -1,6 +1,8 @@
+use std::time::Duration;
+
 use hyper::body::Incoming;
 use hyper::{Request, Response};
-use tracing::{Instrument, info, info_span};
+use tracing::{Instrument, info, info_span, warn};
 
 use crate::context::RequestContext;
 use crate::response::BoxBody;
@@ -59,6 +61,73 @@ impl Middleware for RequestLogMiddleware {
     }
 }
 
+/// Thresholds for slow request and slow query logging.
+///
+/// Stored in app state so the `Db` extractor knows to time queries.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SlowLogConfig {
+    pub(crate) request: Option<Duration>,
+    pub(crate) query: Option<Duration>,
+}
+
+impl SlowLogConfig {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.request.is_some() || self.query.is_some()
+    }
+}
+
+/// Logs a warning for requests slower than a threshold, listing the
+/// slowest database queries recorded on the request context.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SlowRequestMiddleware {
+    threshold: Duration,
+}
+
+impl SlowRequestMiddleware {
+    pub(crate) fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+}
+
+impl Middleware for SlowRequestMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+
+        Box::pin(async move {
+            let response = next.run(req).await;
+            let duration = ctx.elapsed();
+
+            if duration >= self.threshold {
+                let queries = ctx
+                    .slowest_queries()
+                    .iter()
+                    .map(|q| format!("{}ms {}", q.duration.as_millis(), q.sql))
+                    .collect::<Vec<_>>()
+                    .join(" | ");
+
+                warn!(
+                    method = %method,
+                    path = %path,
+                    status = response.status().as_u16(),
+                    duration_ms = duration.as_millis() as u64,
+                    threshold_ms = self.threshold.as_millis() as u64,
+                    trace_id = %ctx.trace_id,
+                    slowest_queries = %queries,
+                    "slow request"
+                );
+            }
+
+            response
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use http::StatusCode;
 use hyper::body::Incoming;
 use hyper::{Request, Response};
 
@@ -9,14 +10,62 @@ use crate::response::{BoxBody, IntoResponse};
 
 use super::{BoxFuture, Middleware, Next};
 
+/// Marks a request as exempt from [`TimeoutMiddleware`].
+///
+/// Inserted for routes registered with [`Router::no_timeout`](crate::router::Router::no_timeout),
+/// so streaming and SSE endpoints can stay open past the deadline. A
+/// middleware that runs before the timeout can also insert it itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoTimeout;
+
+/// Bounds how long a request may take, answering with an error once the
+/// deadline passes.
+///
+/// Timed out requests get a `408 Request Timeout` by default, or the status
+/// set with [`status`](Self::status), with the usual JSON error body.
 #[derive(Debug, Clone)]
 pub struct TimeoutMiddleware {
     pub(crate) duration: Duration,
+    pub(crate) status: StatusCode,
 }
 
 impl TimeoutMiddleware {
     pub fn new(duration: Duration) -> Self {
-        Self { duration }
+        Self {
+            duration,
+            status: StatusCode::REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Answers timed out requests with `status` instead of 408, e.g. 504
+    /// when the app sits behind a gateway.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` is not 408, 503 or 504.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        assert!(
+            matches!(
+                status,
+                StatusCode::REQUEST_TIMEOUT
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            "timeout status must be 408, 503 or 504, got {}",
+            status
+        );
+        self.status = status;
+        self
+    }
+
+    fn error(&self) -> Error {
+        match self.status {
+            StatusCode::SERVICE_UNAVAILABLE => {
+                Error::new(503, "SERVICE_UNAVAILABLE", "request timeout")
+            }
+            StatusCode::GATEWAY_TIMEOUT => Error::new(504, "GATEWAY_TIMEOUT", "request timeout"),
+            _ => Error::request_timeout("request timeout"),
+        }
     }
 }
 
@@ -30,13 +79,28 @@ impl Middleware for TimeoutMiddleware {
     fn handle<'a>(
         &'a self,
         req: Request<Incoming>,
-        _ctx: &'a RequestContext,
+        ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
         Box::pin(async move {
+            if req.extensions().get::<NoTimeout>().is_some() {
+                return next.run(req).await;
+            }
+
+            let method = req.method().clone();
+            let path = req.uri().path().to_string();
             match tokio::time::timeout(self.duration, next.run(req)).await {
                 Ok(response) => response,
-                Err(_) => Error::internal("request timeout").into_response(),
+                Err(_) => {
+                    tracing::warn!(
+                        method = %method,
+                        path = %path,
+                        elapsed_ms = ctx.elapsed().as_millis() as u64,
+                        timeout_ms = self.duration.as_millis() as u64,
+                        "request timed out"
+                    );
+                    self.error().with_trace_id(&ctx.trace_id).into_response()
+                }
             }
         })
     }
@@ -0,0 +1,196 @@
+use std::any::Any;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use bytes::Buf;
+use http_body_util::BodyExt;
+use hyper::body::{Body, Incoming};
+use hyper::{Request, Response};
+use tokio::sync::oneshot;
+use tower::{Layer, Service};
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::response::{BoxBody, IntoResponse};
+
+use super::{BoxFuture, Middleware, Next};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type Forwarded = (Request<Incoming>, oneshot::Sender<Response<BoxBody>>);
+
+/// Runs a `tower` layer as Rapina middleware.
+///
+/// The layer wraps a [`NextService`] that continues down the middleware
+/// chain, so layers from `tower-http` (tracing, compression, timeouts, ...)
+/// behave as they would in any other tower stack. Layers may short-circuit
+/// by answering without calling the inner service.
+///
+/// The layered service is built once and cloned for each request, so
+/// state shared between clones, like the permits of a concurrency limit,
+/// spans all requests.
+///
+/// The layer must leave the request body as `Incoming`. Response bodies
+/// are passed on as they are produced; one of another type than
+/// [`BoxBody`] becomes a streamed [`BoxBody`].
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::middleware::TowerLayerMiddleware;
+/// use tower_http::compression::CompressionLayer;
+/// use tower_http::trace::TraceLayer;
+///
+/// Rapina::new()
+///     .middleware(TowerLayerMiddleware::new(TraceLayer::new_for_http()))
+///     .middleware(TowerLayerMiddleware::new(CompressionLayer::new()))
+///     .router(router)
+///     .listen("127.0.0.1:3000")
+///     .await
+/// ```
+#[derive(Debug)]
+pub struct TowerLayerMiddleware<S> {
+    service: Mutex<S>,
+}
+
+impl<S> TowerLayerMiddleware<S> {
+    pub fn new<L>(layer: L) -> Self
+    where
+        L: Layer<NextService, Service = S>,
+    {
+        Self {
+            service: Mutex::new(layer.layer(NextService { _private: () })),
+        }
+    }
+}
+
+/// The inner service handed to a layer wrapped by [`TowerLayerMiddleware`].
+///
+/// Calling it passes the request on to the rest of the Rapina middleware
+/// chain and the router. It can be called once per request.
+#[derive(Debug, Clone)]
+pub struct NextService {
+    _private: (),
+}
+
+/// The way back into the middleware chain, carried in the request's
+/// extensions so the shared [`NextService`] knows where each request goes.
+#[derive(Clone)]
+struct Forward(Arc<Mutex<Option<oneshot::Sender<Forwarded>>>>);
+
+impl Service<Request<Incoming>> for NextService {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut req: Request<Incoming>) -> Self::Future {
+        let forward = req
+            .extensions_mut()
+            .remove::<Forward>()
+            .and_then(|forward| forward.0.lock().unwrap().take());
+
+        Box::pin(async move {
+            let Some(forward) = forward else {
+                return Ok(
+                    Error::internal("tower layer called the inner service twice").into_response(),
+                );
+            };
+
+            let (reply, response) = oneshot::channel();
+            if forward.send((req, reply)).is_err() {
+                return Ok(Error::internal("middleware chain is gone").into_response());
+            }
+
+            Ok(response
+                .await
+                .unwrap_or_else(|_| Error::internal("middleware chain is gone").into_response()))
+        })
+    }
+}
+
+impl<S, B> Middleware for TowerLayerMiddleware<S>
+where
+    S: Service<Request<Incoming>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    B: Body + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    fn handle<'a>(
+        &'a self,
+        mut req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            let (forward, forwarded) = oneshot::channel::<Forwarded>();
+            req.extensions_mut()
+                .insert(Forward(Arc::new(Mutex::new(Some(forward)))));
+            let mut service = self.service.lock().unwrap().clone();
+
+            let layered = async move {
+                std::future::poll_fn(|cx| service.poll_ready(cx)).await?;
+                service.call(req).await
+            };
+
+            // Runs the rest of the chain once the layer calls its inner
+            // service. It never finishes on its own, so the select below ends
+            // with the layer's response, even when the layer answers early.
+            let chain = async move {
+                if let Ok((req, reply)) = forwarded.await {
+                    let _ = reply.send(next.run(req).await);
+                }
+                std::future::pending::<()>().await
+            };
+
+            match tokio::select! {
+                result = layered => result,
+                _ = chain => unreachable!(),
+            } {
+                Ok(response) => response.map(into_box_body),
+                Err(e) => {
+                    let e: BoxError = e.into();
+                    tracing::error!("tower layer failed: {}", e);
+                    Error::internal("internal server error").into_response()
+                }
+            }
+        })
+    }
+}
+
+/// Hands a [`BoxBody`] back as it is and streams any other body.
+fn into_box_body<B>(body: B) -> BoxBody
+where
+    B: Body + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let mut body = Some(body);
+    if let Some(body) = (&mut body as &mut dyn Any).downcast_mut::<Option<BoxBody>>() {
+        return body.take().unwrap();
+    }
+    BoxBody::stream(
+        body.unwrap()
+            .map_frame(|frame| frame.map_data(|mut data| data.copy_to_bytes(data.remaining()))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::layer::util::Identity;
+
+    #[test]
+    fn test_tower_layer_middleware_new() {
+        let _mw = TowerLayerMiddleware::new(Identity::new());
+    }
+
+    #[test]
+    fn test_into_box_body_keeps_box_body() {
+        assert!(!into_box_body(BoxBody::new("full")).is_stream());
+        assert!(into_box_body(http_body_util::Full::new(bytes::Bytes::new())).is_stream());
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::sync::Arc;
+
+use hyper::body::Incoming;
+use hyper::{Request, Response};
+use tracing::field::{Empty, display};
+use tracing::{Instrument, Span, error, info, info_span, warn};
+
+use crate::context::RequestContext;
+use crate::response::BoxBody;
+
+use super::{BoxFuture, Middleware, Next};
+
+type MakeFields = Arc<dyn Fn(&Request<Incoming>) -> Span + Send + Sync>;
+
+/// Wraps each request in a `request` span and logs its outcome.
+///
+/// The span records the method, path, matched route pattern and request
+/// ID, then the status code and latency once the response is ready. The
+/// closing `request completed` event is logged at `INFO` for 1xx to 3xx
+/// responses, `WARN` for 4xx and `ERROR` for 5xx.
+///
+/// The request ID is the trace ID of the [`RequestContext`], so register
+/// [`TraceIdMiddleware`](super::TraceIdMiddleware) before this middleware
+/// to log the `x-trace-id` sent by upstream services.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::middleware::{Phase, TraceIdMiddleware, TraceMiddleware};
+/// use rapina::prelude::*;
+///
+/// let app = Rapina::new()
+///     .middleware_in(Phase::PreRouting, TraceIdMiddleware::new())
+///     .middleware_in(
+///         Phase::PreRouting,
+///         TraceMiddleware::new().with_fields(|req| {
+///             let tenant = req
+///                 .headers()
+///                 .get("x-tenant")
+///                 .and_then(|v| v.to_str().ok())
+///                 .unwrap_or("none");
+///             tracing::info_span!("fields", tenant = %tenant)
+///         }),
+///     );
+/// ```
+#[derive(Clone, Default)]
+pub struct TraceMiddleware {
+    make_fields: Option<MakeFields>,
+}
+
+impl TraceMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds fields of your own to every request.
+    ///
+    /// The closure is called with the request and returns a span, which is
+    /// entered inside the `request` span. Its fields are attached to
+    /// everything logged during the request, `request completed` included.
+    pub fn with_fields<F>(mut self, make_fields: F) -> Self
+    where
+        F: Fn(&Request<Incoming>) -> Span + Send + Sync + 'static,
+    {
+        self.make_fields = Some(Arc::new(make_fields));
+        self
+    }
+}
+
+impl fmt::Debug for TraceMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceMiddleware")
+            .field("with_fields", &self.make_fields.is_some())
+            .finish()
+    }
+}
+
+impl Middleware for TraceMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        // TraceIdMiddleware leaves the context holding an upstream trace ID
+        // on the request
+        let request_id = req
+            .extensions()
+            .get::<RequestContext>()
+            .unwrap_or(ctx)
+            .trace_id
+            .clone();
+        let route = ctx
+            .matched_route()
+            .map(|route| display(route.pattern.as_str()));
+
+        let span = info_span!(
+            "request",
+            method = %req.method(),
+            path = %req.uri().path(),
+            route = route,
+            request_id = %request_id,
+            status = Empty,
+            latency_ms = Empty,
+        );
+        let fields = match &self.make_fields {
+            Some(make_fields) => span.in_scope(|| make_fields(&req)),
+            None => Span::none(),
+        };
+
+        let request_span = span.clone();
+        Box::pin(
+            async move {
+                let response = next.run(req).await;
+                let status = response.status().as_u16();
+                let latency_ms = ctx.elapsed().as_millis() as u64;
+                request_span.record("status", status);
+                request_span.record("latency_ms", latency_ms);
+
+                match status {
+                    500.. => error!(status, latency_ms, "request completed"),
+                    400..=499 => warn!(status, latency_ms, "request completed"),
+                    _ => info!(status, latency_ms, "request completed"),
+                }
+
+                response
+            }
+            .instrument(fields)
+            .instrument(span),
+        )
+    }
+}
@@ -41,7 +41,7 @@ impl Middleware for TraceIdMiddleware {
 
             let trace_id = if let Some(id) = incoming_trace_id {
                 // Use the provided trace_id and update context in extensions
-                let new_ctx = RequestContext::with_trace_id(id.clone());
+                let new_ctx = ctx.with_replaced_trace_id(id.clone());
                 req.extensions_mut().insert(new_ctx);
                 id
             } else {
@@ -112,3 +112,66 @@ pub async fn rollback<M: MigratorTrait>(
 pub async fn status<M: MigratorTrait>(conn: &sea_orm::DatabaseConnection) -> Result<(), DbErr> {
     M::status(conn).await
 }
+
+/// Environment variable naming a migration command for
+/// [`Rapina::run_migrations`](crate::app::Rapina::run_migrations) to run
+/// instead of applying pending migrations: `down`, `down:<steps>` or
+/// `fresh`. The app exits once the command finishes.
+///
+/// `rapina migrate down` and `rapina migrate fresh` set it and start the app
+/// with `cargo run`, so they use the app's own `Migrator`.
+pub const MIGRATE_COMMAND_ENV: &str = "RAPINA_MIGRATE";
+
+/// A migration command read from [`MIGRATE_COMMAND_ENV`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrateCommand {
+    /// Roll back the last `steps` applied migrations.
+    Down { steps: u32 },
+    /// Drop every table and apply all migrations from scratch.
+    Fresh,
+}
+
+impl MigrateCommand {
+    /// Parses `down`, `down:<steps>` or `fresh`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "fresh" => Ok(Self::Fresh),
+            "down" => Ok(Self::Down { steps: 1 }),
+            other => other
+                .strip_prefix("down:")
+                .and_then(|steps| steps.parse().ok())
+                .filter(|steps| *steps > 0)
+                .map(|steps| Self::Down { steps })
+                .ok_or_else(|| {
+                    format!(
+                        "invalid {} value '{}'; expected down, down:<steps> or fresh",
+                        MIGRATE_COMMAND_ENV, value
+                    )
+                }),
+        }
+    }
+
+    /// Reads the command from [`MIGRATE_COMMAND_ENV`], if set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match std::env::var(MIGRATE_COMMAND_ENV) {
+            Ok(value) if !value.trim().is_empty() => Self::parse(&value).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Runs the command against `conn`.
+    pub async fn run<M: MigratorTrait>(
+        self,
+        conn: &sea_orm::DatabaseConnection,
+    ) -> Result<(), DbErr> {
+        match self {
+            Self::Down { steps } => rollback::<M>(conn, Some(steps)).await,
+            Self::Fresh => {
+                tracing::info!("Dropping all tables and reapplying migrations...");
+                M::fresh(conn).await?;
+                tracing::info!("All migrations applied successfully");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,453 @@
+//! Newline-delimited JSON request bodies.
+//!
+//! [`NdJson<T>`] reads the request body one line at a time and deserializes
+//! each line into `T` as the handler asks for it, so a bulk upload is never
+//! buffered in full: only the line being parsed is held in memory.
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::ndjson::{IngestSummary, NdJson};
+//!
+//! #[derive(Deserialize)]
+//! struct Event {
+//!     kind: String,
+//! }
+//!
+//! #[post("/events")]
+//! async fn ingest(mut events: NdJson<Event>) -> Result<IngestSummary> {
+//!     while let Some(event) = events.next().await {
+//!         store(event?).await?;
+//!     }
+//!     Ok(events.summary())
+//! }
+//! ```
+//!
+//! # Malformed Lines
+//!
+//! With [`LinePolicy::FailFast`], the default, the first line that isn't
+//! valid JSON for `T` is returned as a 400 error and the stream ends. With
+//! [`LinePolicy::CollectErrors`], bad lines are skipped and listed in the
+//! [`IngestSummary`] instead. Blank lines are ignored under both policies.
+//!
+//! # Limits
+//!
+//! Register [`NdJsonConfig`] via `.state()` to change the defaults, or
+//! override them per handler with [`NdJson::on_error`] and
+//! [`NdJson::max_lines`]. Going over either limit ends the stream with a
+//! 413 error. The body size limit and read timeout set by
+//! [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware) only
+//! apply to extractors that buffer the whole body, not to `NdJson`.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use http::Request;
+use http_body_util::BodyExt;
+use http_body_util::combinators::UnsyncBoxBody;
+use hyper::body::Incoming;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::error::Error;
+use crate::extract::{FromRequest, PathParams};
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+const DEFAULT_MAX_LINES: usize = 1_000_000;
+const DEFAULT_MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// How many rejected lines the summary lists; later ones are only counted.
+const MAX_REPORTED_ERRORS: usize = 100;
+
+type LineBody = UnsyncBoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+/// What happens when a line can't be deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinePolicy {
+    /// Return a 400 error for the first bad line and stop reading.
+    #[default]
+    FailFast,
+    /// Skip bad lines and report them in the [`IngestSummary`].
+    CollectErrors,
+}
+
+/// NDJSON defaults. Register via `.state(NdJsonConfig { .. })`.
+///
+/// When not registered, bad lines fail fast, and bodies are capped at
+/// 1,000,000 lines of at most 1 MiB each.
+#[derive(Debug, Clone)]
+pub struct NdJsonConfig {
+    /// What to do with lines that aren't valid JSON for the target type.
+    pub on_error: LinePolicy,
+    /// Maximum number of non-blank lines in a body (default: 1,000,000).
+    pub max_lines: usize,
+    /// Maximum length of a single line in bytes (default: 1 MiB).
+    pub max_line_bytes: usize,
+}
+
+impl Default for NdJsonConfig {
+    fn default() -> Self {
+        Self {
+            on_error: LinePolicy::FailFast,
+            max_lines: DEFAULT_MAX_LINES,
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+        }
+    }
+}
+
+/// A line that was rejected, by its 1-based line number in the body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct LineError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Accepted and rejected line counts for an NDJSON upload.
+///
+/// Returned from handlers as a `200 OK` JSON body. `errors` lists the first
+/// 100 rejected lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct IngestSummary {
+    pub accepted: u64,
+    pub rejected: u64,
+    pub errors: Vec<LineError>,
+}
+
+impl IngestSummary {
+    fn reject(&mut self, line: usize, message: String) {
+        self.rejected += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(LineError { line, message });
+        }
+    }
+}
+
+impl IntoResponse for IngestSummary {
+    fn into_response(self) -> http::Response<BoxBody> {
+        let body = serde_json::to_vec(&self).unwrap_or_default();
+        http::Response::builder()
+            .status(http::StatusCode::OK)
+            .header("content-type", "application/json")
+            .body(BoxBody::new(body))
+            .unwrap()
+    }
+}
+
+/// Extracts a newline-delimited JSON body as a stream of `T`.
+///
+/// Call [`next`](Self::next) until it returns `None`. See the
+/// [module docs](self) for error handling and limits.
+pub struct NdJson<T> {
+    body: LineBody,
+    buf: BytesMut,
+    /// How far `buf` has been searched for a newline.
+    searched: usize,
+    /// Number of the last line read, blank lines included.
+    line: usize,
+    records: usize,
+    config: NdJsonConfig,
+    summary: IngestSummary,
+    body_done: bool,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> NdJson<T> {
+    fn new<B>(body: B, config: NdJsonConfig) -> Self
+    where
+        B: hyper::body::Body<Data = Bytes> + Send + 'static,
+        B::Error: std::error::Error + Send + Sync + 'static,
+    {
+        Self {
+            body: body.map_err(Into::into).boxed_unsync(),
+            buf: BytesMut::new(),
+            searched: 0,
+            line: 0,
+            records: 0,
+            config,
+            summary: IngestSummary::default(),
+            body_done: false,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets what happens with lines that can't be deserialized.
+    pub fn on_error(mut self, policy: LinePolicy) -> Self {
+        self.config.on_error = policy;
+        self
+    }
+
+    /// Sets the maximum number of non-blank lines.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.config.max_lines = max_lines;
+        self
+    }
+
+    /// Reads and deserializes the next line.
+    ///
+    /// Returns `None` once the body is exhausted or after an error ended the
+    /// stream. Under [`LinePolicy::CollectErrors`], lines that fail to
+    /// deserialize are skipped rather than returned.
+    pub async fn next(&mut self) -> Option<Result<T, Error>> {
+        while !self.done {
+            let line = match self.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(e) => return Some(Err(self.fail(e))),
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            self.records += 1;
+            if self.records > self.config.max_lines {
+                let e = Error::payload_too_large(format!(
+                    "NDJSON body has more than {} lines",
+                    self.config.max_lines
+                ));
+                return Some(Err(self.fail(e)));
+            }
+
+            match serde_json::from_slice(&line) {
+                Ok(value) => {
+                    self.summary.accepted += 1;
+                    return Some(Ok(value));
+                }
+                Err(e) => {
+                    let message = format!("invalid JSON: {}", e);
+                    self.summary.reject(self.line, message.clone());
+                    if self.config.on_error == LinePolicy::FailFast {
+                        let e = Error::bad_request(format!("line {}: {}", self.line, message))
+                            .with_details(serde_json::json!({ "line": self.line }));
+                        return Some(Err(self.fail(e)));
+                    }
+                }
+            }
+        }
+
+        self.done = true;
+        None
+    }
+
+    /// The 1-based number of the line most recently read.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Moves the line most recently returned by [`next`](Self::next) from
+    /// accepted to rejected, for values that parsed but that the handler
+    /// refused.
+    pub fn reject(&mut self, message: impl Into<String>) {
+        self.summary.accepted = self.summary.accepted.saturating_sub(1);
+        self.summary.reject(self.line, message.into());
+    }
+
+    /// Counts of the lines read so far.
+    pub fn summary(&self) -> IngestSummary {
+        self.summary.clone()
+    }
+
+    fn fail(&mut self, e: Error) -> Error {
+        self.done = true;
+        e
+    }
+
+    fn line_too_long(&self) -> Error {
+        Error::payload_too_large(format!(
+            "line {} is longer than {} bytes",
+            self.line + 1,
+            self.config.max_line_bytes
+        ))
+    }
+
+    /// The next line without its terminator, or `None` at the end of the body.
+    async fn next_line(&mut self) -> Result<Option<Bytes>, Error> {
+        loop {
+            if let Some(pos) = self.buf[self.searched..].iter().position(|b| *b == b'\n') {
+                let end = self.searched + pos;
+                // A frame can complete a line that is already too long
+                if end > self.config.max_line_bytes {
+                    return Err(self.line_too_long());
+                }
+                let mut line = self.buf.split_to(end + 1);
+                line.truncate(end);
+                if line.last() == Some(&b'\r') {
+                    line.truncate(end - 1);
+                }
+                self.searched = 0;
+                self.line += 1;
+                return Ok(Some(line.freeze()));
+            }
+            self.searched = self.buf.len();
+
+            if self.buf.len() > self.config.max_line_bytes {
+                return Err(self.line_too_long());
+            }
+
+            if self.body_done {
+                if self.buf.is_empty() {
+                    return Ok(None);
+                }
+                self.searched = 0;
+                self.line += 1;
+                return Ok(Some(self.buf.split().freeze()));
+            }
+
+            match self.body.frame().await {
+                None => self.body_done = true,
+                Some(Err(_)) => return Err(Error::bad_request("Failed to read request body")),
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        self.buf.extend_from_slice(&data);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned + Send> FromRequest for NdJson<T> {
+    async fn from_request(
+        req: Request<Incoming>,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let config = state.get::<NdJsonConfig>().cloned().unwrap_or_default();
+        Ok(NdJson::new(req.into_body(), config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::convert::Infallible;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use hyper::body::{Body, Frame};
+    use serde::Deserialize;
+
+    /// A body that yields each chunk as a separate frame.
+    struct Chunked(VecDeque<Bytes>);
+
+    impl Body for Chunked {
+        type Data = Bytes;
+        type Error = Infallible;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+            Poll::Ready(self.0.pop_front().map(|chunk| Ok(Frame::data(chunk))))
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Event {
+        id: u32,
+    }
+
+    fn events(chunks: &[&'static str], config: NdJsonConfig) -> NdJson<Event> {
+        let chunks = chunks.iter().map(|c| Bytes::from_static(c.as_bytes()));
+        NdJson::new(Chunked(chunks.collect()), config)
+    }
+
+    // Line 3 is malformed and lines are split across chunks
+    const BODY: &[&str] = &[
+        "{\"id\": 1}\n{\"i",
+        "d\": 2}\r\n{\"id\": ",
+        "oops}\n\n{\"id\": 4}",
+    ];
+
+    async fn drain(events: &mut NdJson<Event>) -> Vec<Result<u32, u16>> {
+        let mut out = Vec::new();
+        while let Some(event) = events.next().await {
+            out.push(event.map(|e| e.id).map_err(|e| e.status));
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_stops_at_malformed_line() {
+        let mut events = events(BODY, NdJsonConfig::default());
+
+        assert_eq!(drain(&mut events).await, vec![Ok(1), Ok(2), Err(400)]);
+        assert!(events.next().await.is_none());
+
+        let summary = events.summary();
+        assert_eq!(summary.accepted, 2);
+        assert_eq!(summary.rejected, 1);
+        assert_eq!(summary.errors[0].line, 3);
+    }
+
+    #[tokio::test]
+    async fn test_collect_errors_skips_malformed_line() {
+        let mut events = events(BODY, NdJsonConfig::default()).on_error(LinePolicy::CollectErrors);
+
+        assert_eq!(drain(&mut events).await, vec![Ok(1), Ok(2), Ok(4)]);
+
+        let summary = events.summary();
+        assert_eq!(summary.accepted, 3);
+        assert_eq!(summary.rejected, 1);
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!(summary.errors[0].line, 3);
+        assert!(summary.errors[0].message.starts_with("invalid JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_max_lines() {
+        let mut events = events(BODY, NdJsonConfig::default())
+            .on_error(LinePolicy::CollectErrors)
+            .max_lines(2);
+
+        assert_eq!(drain(&mut events).await, vec![Ok(1), Ok(2), Err(413)]);
+    }
+
+    #[tokio::test]
+    async fn test_max_line_bytes() {
+        let config = NdJsonConfig {
+            max_line_bytes: 8,
+            ..NdJsonConfig::default()
+        };
+        let mut events = events(&["{\"id\":1}\n", "{\"id\":", " 22222222}\n"], config);
+
+        assert_eq!(drain(&mut events).await, vec![Ok(1), Err(413)]);
+    }
+
+    #[tokio::test]
+    async fn test_reject_moves_line_to_rejected() {
+        let mut events = events(&["{\"id\": 1}\n{\"id\": 2}\n"], NdJsonConfig::default());
+
+        while let Some(event) = events.next().await {
+            if event.unwrap().id == 2 {
+                events.reject("duplicate id");
+            }
+        }
+
+        let summary = events.summary();
+        assert_eq!(summary.accepted, 1);
+        assert_eq!(
+            summary.errors,
+            vec![LineError {
+                line: 2,
+                message: "duplicate id".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_summary_caps_reported_errors() {
+        let mut summary = IngestSummary::default();
+        for line in 0..MAX_REPORTED_ERRORS + 5 {
+            summary.reject(line, "bad".to_string());
+        }
+        assert_eq!(summary.rejected, MAX_REPORTED_ERRORS as u64 + 5);
+        assert_eq!(summary.errors.len(), MAX_REPORTED_ERRORS);
+    }
+}
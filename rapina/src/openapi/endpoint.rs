@@ -39,15 +39,13 @@ pub async fn openapi_spec(
             Response::builder()
                 .status(StatusCode::OK)
                 .header("content-type", "application/json")
-                .body(http_body_util::Full::new(bytes::Bytes::from(json)))
+                .body(BoxBody::new(json))
                 .unwrap()
         }
         None => Response::builder()
             .status(StatusCode::NOT_FOUND)
             .header("content-type", "application/json")
-            .body(http_body_util::Full::new(bytes::Bytes::from(
-                r#"{"error": "OpenAPI spec not configured"}"#,
-            )))
+            .body(BoxBody::new(r#"{"error": "OpenAPI spec not configured"}"#))
             .unwrap(),
     }
 }
@@ -87,6 +85,68 @@ mod tests {
         assert!(json.get("components").is_some());
     }
 
+    #[tokio::test]
+    async fn test_openapi_spec_includes_metadata() {
+        use crate::openapi::{Contact, Info, License};
+
+        let router = Router::new().route(Method::GET, "/hello", |_, _, _| async { "hello" });
+        let app = Rapina::new()
+            .router(router)
+            .openapi_info(
+                Info::new("openapi-test", "2.0.0")
+                    .description("Says hello")
+                    .contact(Contact {
+                        email: Some("api@example.com".to_string()),
+                        ..Default::default()
+                    })
+                    .license(License::new("Apache-2.0")),
+            )
+            .openapi_server("https://api.example.com", "Production")
+            .openapi_server("https://${CARGO_PKG_NAME}.example.com", "Staging")
+            .openapi_external_docs("https://docs.example.com", "Guides");
+        let client = TestClient::new(app).await;
+        let json = client
+            .get("/__rapina/openapi.json")
+            .send()
+            .await
+            .json::<Value>();
+
+        assert_eq!(
+            json["info"],
+            serde_json::json!({
+                "title": "openapi-test",
+                "version": "2.0.0",
+                "description": "Says hello",
+                "contact": {"email": "api@example.com"},
+                "license": {"name": "Apache-2.0"}
+            })
+        );
+        assert_eq!(
+            json["servers"],
+            serde_json::json!([
+                {"url": "https://api.example.com", "description": "Production"},
+                {
+                    "url": format!("https://{}.example.com", env!("CARGO_PKG_NAME")),
+                    "description": "Staging"
+                }
+            ])
+        );
+        assert_eq!(
+            json["externalDocs"],
+            serde_json::json!({"url": "https://docs.example.com", "description": "Guides"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_openapi_server_with_unset_variable_fails_startup() {
+        let app = Rapina::new()
+            .openapi("openapi-test", "1.0")
+            .openapi_server("https://${RAPINA_TEST_UNSET_OPENAPI_HOST}", "Staging");
+
+        let err = app.prepare().err().unwrap();
+        assert!(err.to_string().contains("RAPINA_TEST_UNSET_OPENAPI_HOST"));
+    }
+
     #[tokio::test]
     async fn test_openapi_spec_returns_404_and_empty_body_when_openapi_is_disabled() {
         let router = Router::new().route(Method::GET, "/hello", |_, _, _| async { "hello" });
@@ -0,0 +1,150 @@
+//! Response schemas for types that don't implement `JsonSchema`.
+//!
+//! The route macros document `Json<T>` responses through [`SchemaProbe`],
+//! which picks `schemars::schema_for!(T)` when `T: JsonSchema` and
+//! [`opaque_schema`] otherwise, so a missing derive (for example on a
+//! `schema!` entity marked `#[no_json_schema]`) degrades the OpenAPI spec
+//! instead of failing to compile.
+
+use std::marker::PhantomData;
+
+use schemars::JsonSchema;
+use serde_json::{Value, json};
+
+/// The schema documented for a response type without a `JsonSchema` impl:
+/// an object with no declared properties, titled after the type.
+///
+/// Logs a warning naming the type, since its spec entry tells clients
+/// nothing about the shape of the response.
+pub fn opaque_schema<T: ?Sized>() -> Value {
+    let type_name = std::any::type_name::<T>();
+    tracing::warn!(
+        type_name,
+        "Response type does not implement JsonSchema, documenting it as an opaque object"
+    );
+    json!({
+        "type": "object",
+        "title": short_type_name(type_name),
+    })
+}
+
+/// `my_app::entity::user::Model` -> `Model`, keeping generic arguments.
+fn short_type_name(type_name: &str) -> &str {
+    let end = type_name.find('<').unwrap_or(type_name.len());
+    let start = type_name[..end].rfind("::").map(|i| i + 2).unwrap_or(0);
+    &type_name[start..]
+}
+
+/// Picks a response schema for `T` by method resolution: called as
+/// `(&SchemaProbe::<T>::new()).response_schema()` with both probe traits in
+/// scope, [`JsonSchemaProbe`] wins when `T: JsonSchema` and
+/// [`OpaqueSchemaProbe`] applies to everything else.
+#[doc(hidden)]
+pub struct SchemaProbe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> SchemaProbe<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Default for SchemaProbe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub trait JsonSchemaProbe {
+    fn response_schema(&self) -> Value;
+
+    /// The schema of a path parameter of type `T`, without the `$schema`
+    /// key that only belongs at the root of a document.
+    fn param_schema(&self) -> Option<Value>;
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchemaProbe for SchemaProbe<T> {
+    fn response_schema(&self) -> Value {
+        serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default()
+    }
+
+    fn param_schema(&self) -> Option<Value> {
+        let mut schema = self.response_schema();
+        if let Some(object) = schema.as_object_mut() {
+            object.remove("$schema");
+        }
+        Some(schema)
+    }
+}
+
+#[doc(hidden)]
+pub trait OpaqueSchemaProbe {
+    fn response_schema(&self) -> Value;
+
+    /// Path parameters without a `JsonSchema` impl are left undocumented.
+    fn param_schema(&self) -> Option<Value>;
+}
+
+impl<T: ?Sized> OpaqueSchemaProbe for &SchemaProbe<T> {
+    fn response_schema(&self) -> Value {
+        opaque_schema::<T>()
+    }
+
+    fn param_schema(&self) -> Option<Value> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Documented {
+        id: i32,
+    }
+
+    #[allow(dead_code)]
+    struct Undocumented {
+        id: i32,
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn test_probe_uses_json_schema_when_implemented() {
+        let schema = (&SchemaProbe::<Documented>::new()).response_schema();
+        assert_eq!(schema["title"], "Documented");
+        assert_eq!(schema["properties"]["id"]["type"], "integer");
+    }
+
+    #[test]
+    fn test_probe_falls_back_to_opaque_object() {
+        let schema = (&SchemaProbe::<Undocumented>::new()).response_schema();
+        assert_eq!(schema, json!({ "type": "object", "title": "Undocumented" }));
+
+        let schema = (&SchemaProbe::<Vec<Undocumented>>::new()).response_schema();
+        assert_eq!(schema["type"], "object");
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn test_param_schema() {
+        let schema = (&SchemaProbe::<uuid::Uuid>::new()).param_schema().unwrap();
+        assert_eq!(schema["type"], "string");
+        assert_eq!(schema["format"], "uuid");
+        assert!(schema.get("$schema").is_none());
+
+        assert_eq!((&SchemaProbe::<Undocumented>::new()).param_schema(), None);
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name("app::entity::user::Model"), "Model");
+        assert_eq!(
+            short_type_name("alloc::vec::Vec<app::entity::user::Model>"),
+            "Vec<app::entity::user::Model>"
+        );
+        assert_eq!(short_type_name("i32"), "i32");
+    }
+}
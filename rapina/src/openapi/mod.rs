@@ -4,7 +4,9 @@
 // ! derived from your route definitions
 
 mod endpoint;
+mod fallback;
 mod spec;
 
 pub use endpoint::*;
+pub use fallback::*;
 pub use spec::*;
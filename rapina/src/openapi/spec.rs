@@ -3,26 +3,30 @@
 use serde::Serialize;
 use std::collections::BTreeMap;
 
+use crate::error::ApiErrorBody;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct OpenApiSpec {
     pub openapi: String,
     pub info: Info,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<Server>,
     pub paths: BTreeMap<String, PathItem>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Components>,
+    #[serde(rename = "externalDocs", skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
 }
 
 impl OpenApiSpec {
     pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
         Self {
             openapi: "3.0.3".to_string(),
-            info: Info {
-                title: title.into(),
-                version: version.into(),
-                description: None,
-            },
+            info: Info::new(title, version),
+            servers: Vec::new(),
             paths: BTreeMap::new(),
             components: None,
+            external_docs: None,
         }
     }
 }
@@ -34,6 +38,110 @@ pub struct Info {
     pub version: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<License>,
+}
+
+impl Info {
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            contact: None,
+            license: None,
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn contact(mut self, contact: Contact) -> Self {
+        self.contact = Some(contact);
+        self
+    }
+
+    pub fn license(mut self, license: License) -> Self {
+        self.license = Some(license);
+        self
+    }
+}
+
+/// Contact information for the API
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct Contact {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// License the API is offered under
+#[derive(Debug, Clone, Serialize)]
+pub struct License {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl License {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: None,
+        }
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+}
+
+/// A server the API is reachable at
+#[derive(Debug, Clone, Serialize)]
+pub struct Server {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Link to documentation hosted outside the spec
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalDocs {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Replaces `${VAR}` references in `value` with environment variables.
+///
+/// Fails with the variable name if one is unset.
+pub(crate) fn interpolate_env(value: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            result.push_str(&rest[start..]);
+            return Ok(result);
+        };
+        let name = &after[..end];
+        let value = std::env::var(name).map_err(|_| name.to_string())?;
+        result.push_str(&value);
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
 }
 
 /// Operations available on a single path
@@ -46,6 +154,8 @@ pub struct PathItem {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub put: Option<Operation>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delete: Option<Operation>,
 }
 
@@ -58,11 +168,77 @@ pub struct Operation {
     pub description: Option<String>,
     #[serde(rename = "operationId", skip_serializing_if = "Option::is_none")]
     pub operation_id: Option<String>,
+    /// `v2` and so on, for each `Accept` version the operation serves.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub parameters: Vec<Parameter>,
     #[serde(rename = "requestBody", skip_serializing_if = "Option::is_none")]
     pub request_body: Option<RequestBody>,
     pub responses: BTreeMap<String, Response>,
+    /// Skipped by generated contract tests.
+    #[serde(
+        rename = "x-rapina-untestable",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub untestable: bool,
+    /// Handlers sharing this method and path, picked by media type,
+    /// version or request headers.
+    #[serde(rename = "x-rapina-variants", skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<OperationVariant>,
+}
+
+/// One of several handlers behind an operation, with the media types,
+/// version and request headers it is picked for.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationVariant {
+    #[serde(rename = "operationId")]
+    pub operation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub produces: Option<String>,
+    /// `Accept` version, for handlers versioned by media type.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<String>,
+}
+
+impl Operation {
+    /// Folds in another handler for the same method and path, so the
+    /// operation documents the request and response types of both.
+    fn merge(&mut self, other: Operation) {
+        for param in other.parameters {
+            if !self.parameters.iter().any(|p| p.name == param.name) {
+                self.parameters.push(param);
+            }
+        }
+        match (&mut self.request_body, other.request_body) {
+            (Some(body), Some(other)) => body.content.extend(other.content),
+            (body @ None, Some(other)) => *body = Some(other),
+            (_, None) => {}
+        }
+        for (status, response) in other.responses {
+            match self.responses.get_mut(&status) {
+                Some(existing) => {
+                    if let Some(content) = response.content {
+                        existing.content.get_or_insert_default().extend(content);
+                    }
+                }
+                None => {
+                    self.responses.insert(status, response);
+                }
+            }
+        }
+        self.untestable |= other.untestable;
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self.variants.extend(other.variants);
+    }
 }
 
 impl Default for Operation {
@@ -79,9 +255,12 @@ impl Default for Operation {
             summary: None,
             description: None,
             operation_id: None,
+            tags: Vec::new(),
             parameters: Vec::new(),
             request_body: None,
             responses,
+            untestable: false,
+            variants: Vec::new(),
         }
     }
 }
@@ -148,37 +327,19 @@ pub struct Components {
     pub schemas: BTreeMap<String, serde_json::Value>,
 }
 
-/// Create the standard Rapina error response schema
-fn error_response_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "required": ["error", "trace_id"],
-        "properties": {
-        "error": {
-                "type": "object",
-                "required": ["code", "message"],
-                "properties": {
-                    "code": {"type": "string", "description": "Machine-readable error code"},
-                    "message": {"type": "string", "description": "Human-readable error message"},
-                    "details": {"type": "object", "description": "Optional additional details", "additionalProperties": true}
-                }
-            }
-    }
-    })
-}
-
-fn error_response_ref() -> Response {
+/// An error response whose body is an [`ApiErrorBody`].
+fn error_response(description: impl Into<String>) -> Response {
     let mut content = BTreeMap::new();
     content.insert(
         "application/json".to_string(),
         MediaType {
             schema: Schema::Ref {
-                reference: "#/components/schemas/ErrorResponse".to_string(),
+                reference: ApiErrorBody::schema_ref(),
             },
         },
     );
     Response {
-        description: "Error response".to_string(),
+        description: description.into(),
         content: Some(content),
     }
 }
@@ -213,7 +374,10 @@ pub fn build_openapi_spec(
     let mut spec = OpenApiSpec::new(title, version);
 
     let mut schemas = BTreeMap::new();
-    schemas.insert("ErrorResponse".to_string(), error_response_schema());
+    schemas.insert(
+        ApiErrorBody::SCHEMA_NAME.to_string(),
+        ApiErrorBody::schema(),
+    );
 
     spec.components = Some(Components { schemas });
 
@@ -223,19 +387,35 @@ pub fn build_openapi_spec(
             continue;
         }
         // Extract path parameters (e.g., :id -> id)
-        let params: Vec<Parameter> = route
+        let mut params: Vec<Parameter> = route
             .path
             .split('/')
             .filter(|s| s.starts_with(':'))
-            .map(|s| Parameter {
-                name: s.trim_start_matches(':').to_string(),
-                location: ParameterLocation::Path,
-                description: None,
-                required: true,
-                schema: None,
+            .map(|s| {
+                let name = s.trim_start_matches(':');
+                Parameter {
+                    name: name.to_string(),
+                    location: ParameterLocation::Path,
+                    description: None,
+                    required: true,
+                    schema: route.path_schemas.get(name).cloned().map(Schema::Inline),
+                }
             })
             .collect();
 
+        if let Some(fields) = &route.selectable_fields {
+            params.push(Parameter {
+                name: "fields".to_string(),
+                location: ParameterLocation::Query,
+                description: Some(format!(
+                    "Comma-separated fields to include in the response. One of: {}",
+                    fields.join(", ")
+                )),
+                required: false,
+                schema: Some(Schema::Inline(serde_json::json!({ "type": "string" }))),
+            });
+        }
+
         // Convert :param to {param} for OpenAPI format
         let openapi_path = route
             .path
@@ -253,7 +433,10 @@ pub fn build_openapi_spec(
         let success_response = if let Some(schema) = &route.response_schema {
             let mut content = BTreeMap::new();
             content.insert(
-                "application/json".to_string(),
+                route
+                    .produces
+                    .clone()
+                    .unwrap_or_else(|| "application/json".to_string()),
                 MediaType {
                     schema: Schema::Inline(schema.clone()),
                 },
@@ -262,6 +445,18 @@ pub fn build_openapi_spec(
                 description: "Success".to_string(),
                 content: Some(content),
             }
+        } else if let Some(produces) = &route.produces {
+            let mut content = BTreeMap::new();
+            content.insert(
+                produces.clone(),
+                MediaType {
+                    schema: Schema::Inline(serde_json::json!({})),
+                },
+            );
+            Response {
+                description: "Success".to_string(),
+                content: Some(content),
+            }
         } else {
             Response {
                 description: "Success".to_string(),
@@ -274,7 +469,12 @@ pub fn build_openapi_spec(
         let mut operation = Operation {
             summary: Some(summary),
             operation_id: Some(route.handler_name.clone()),
+            tags: route
+                .accept_version
+                .map(|version| vec![format!("v{}", version)])
+                .unwrap_or_default(),
             parameters: params,
+            untestable: route.untestable,
             ..Default::default()
         };
 
@@ -282,40 +482,81 @@ pub fn build_openapi_spec(
             .responses
             .insert("200".to_string(), success_response);
 
+        if route.consumes.is_some()
+            || route.produces.is_some()
+            || !route.header_conditions.is_empty()
+        {
+            operation.variants.push(OperationVariant {
+                operation_id: route.handler_name.clone(),
+                consumes: route.consumes.clone(),
+                produces: route.produces.clone(),
+                version: route.accept_version,
+                headers: route.header_conditions.clone(),
+            });
+        }
+        if route.produces.is_some() || !route.header_conditions.is_empty() {
+            operation
+                .responses
+                .entry("406".to_string())
+                .or_insert_with(|| error_response("Not acceptable"));
+        }
+
+        // Body content types follow the handler's `consumes`, or else the
+        // route's content-type guard
+        let method = route.method.to_uppercase();
+        let body_types = match &route.consumes {
+            Some(consumes) => std::slice::from_ref(consumes),
+            None => route.request_content_types.as_slice(),
+        };
+        if !body_types.is_empty() && matches!(method.as_str(), "POST" | "PUT" | "PATCH") {
+            operation.request_body = Some(RequestBody {
+                description: None,
+                required: false,
+                content: body_types
+                    .iter()
+                    .map(|ty| {
+                        (
+                            ty.clone(),
+                            MediaType {
+                                schema: Schema::Inline(serde_json::json!({})),
+                            },
+                        )
+                    })
+                    .collect(),
+            });
+            operation
+                .responses
+                .entry("415".to_string())
+                .or_insert_with(|| error_response("Unsupported content type"));
+        }
+
         // Add documented error responses
         for error in &route.error_responses {
-            let status_key = error.status.to_string();
-            let error_desc = error.description.to_string();
-            operation.responses.entry(status_key).or_insert_with(|| {
-                let mut content = BTreeMap::new();
-                content.insert(
-                    "application/json".to_string(),
-                    MediaType {
-                        schema: Schema::Ref {
-                            reference: "#/components/schemas/ErrorResponse".to_string(),
-                        },
-                    },
-                );
-                Response {
-                    description: error_desc,
-                    content: Some(content),
-                }
-            });
+            operation
+                .responses
+                .entry(error.status.to_string())
+                .or_insert_with(|| error_response(error.description));
         }
 
         // Add default error response for undocumented errors
         operation
             .responses
-            .insert("default".to_string(), error_response_ref());
+            .insert("default".to_string(), error_response("Error response"));
 
         let path_item = spec.paths.entry(openapi_path).or_default();
 
-        match route.method.to_uppercase().as_str() {
-            "GET" => path_item.get = Some(operation),
-            "POST" => path_item.post = Some(operation),
-            "PUT" => path_item.put = Some(operation),
-            "DELETE" => path_item.delete = Some(operation),
-            _ => {}
+        let slot = match method.as_str() {
+            "GET" => &mut path_item.get,
+            "POST" => &mut path_item.post,
+            "PUT" => &mut path_item.put,
+            "PATCH" => &mut path_item.patch,
+            "DELETE" => &mut path_item.delete,
+            _ => continue,
+        };
+        // Overloads picked by media type or headers share one operation
+        match slot {
+            Some(existing) if !operation.variants.is_empty() => existing.merge(operation),
+            _ => *slot = Some(operation),
         }
     }
 
@@ -385,6 +626,17 @@ mod tests {
             get_op.responses.get("409").unwrap().description,
             "Email already taken"
         );
+
+        // Every error response references the shared error body
+        let json = serde_json::to_value(&spec).unwrap();
+        let responses = &json["paths"]["/users/{id}"]["get"]["responses"];
+        for status in ["404", "409", "default"] {
+            assert_eq!(
+                responses[status]["content"]["application/json"]["schema"]["$ref"],
+                "#/components/schemas/ApiErrorBody"
+            );
+        }
+        assert!(json["components"]["schemas"]["ApiErrorBody"].is_object());
     }
 
     #[test]
@@ -398,4 +650,237 @@ mod tests {
         assert!(!spec.paths.contains_key("/__rapina/routes"));
         assert!(spec.paths.contains_key("/users"));
     }
+
+    #[test]
+    fn test_build_openapi_spec_marks_untestable_operations() {
+        let routes = vec![
+            RouteInfo::new("POST", "/payments", "charge", None, Vec::new()).untestable(),
+            RouteInfo::new("GET", "/payments", "list_payments", None, Vec::new()),
+        ];
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+        let json = serde_json::to_value(&spec).unwrap();
+
+        assert_eq!(
+            json["paths"]["/payments"]["post"]["x-rapina-untestable"],
+            true
+        );
+        assert!(
+            json["paths"]["/payments"]["get"]
+                .get("x-rapina-untestable")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_build_openapi_spec_request_content_types() {
+        let routes = vec![
+            RouteInfo::new("POST", "/avatars", "upload_avatar", None, Vec::new())
+                .with_request_content_types(vec![
+                    "image/png".to_string(),
+                    "image/jpeg".to_string(),
+                ]),
+            RouteInfo::new("GET", "/avatars", "list_avatars", None, Vec::new())
+                .with_request_content_types(vec!["application/json".to_string()]),
+        ];
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+        let json = serde_json::to_value(&spec).unwrap();
+
+        let post = &json["paths"]["/avatars"]["post"];
+        let content = post["requestBody"]["content"].as_object().unwrap();
+        let types: Vec<&String> = content.keys().collect();
+        assert_eq!(types, ["image/jpeg", "image/png"]);
+        assert_eq!(
+            post["responses"]["415"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ApiErrorBody"
+        );
+        assert!(
+            json["paths"]["/avatars"]["get"]
+                .get("requestBody")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_build_openapi_spec_merges_media_type_overloads() {
+        let routes = vec![
+            RouteInfo::new("POST", "/login", "login_json", None, Vec::new())
+                .with_consumes("application/json"),
+            RouteInfo::new("POST", "/login", "login_form", None, Vec::new())
+                .with_consumes("application/x-www-form-urlencoded")
+                .with_produces("text/html"),
+        ];
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+        let json = serde_json::to_value(&spec).unwrap();
+
+        let post = &json["paths"]["/login"]["post"];
+        assert_eq!(post["operationId"], "login_json");
+        let content = post["requestBody"]["content"].as_object().unwrap();
+        let types: Vec<&String> = content.keys().collect();
+        assert_eq!(
+            types,
+            ["application/json", "application/x-www-form-urlencoded"]
+        );
+        assert!(post["responses"]["200"]["content"]["text/html"].is_object());
+        assert!(post["responses"]["406"].is_object());
+        assert!(post["responses"]["415"].is_object());
+        assert_eq!(
+            post["x-rapina-variants"],
+            serde_json::json!([
+                { "operationId": "login_json", "consumes": "application/json" },
+                {
+                    "operationId": "login_form",
+                    "consumes": "application/x-www-form-urlencoded",
+                    "produces": "text/html"
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_build_openapi_spec_versioned_overloads() {
+        let routes = vec![
+            RouteInfo::new("GET", "/users", "list_users_v1", None, Vec::new())
+                .with_header_conditions(vec!["accept: version 1".to_string()])
+                .with_accept_version(1),
+            RouteInfo::new("GET", "/users", "list_users_v2", None, Vec::new())
+                .with_header_conditions(vec!["accept: version 2".to_string()])
+                .with_accept_version(2),
+        ];
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+        let json = serde_json::to_value(&spec).unwrap();
+
+        let get = &json["paths"]["/users"]["get"];
+        assert_eq!(get["tags"], serde_json::json!(["v1", "v2"]));
+        assert!(get["responses"]["406"].is_object());
+        assert_eq!(
+            get["x-rapina-variants"],
+            serde_json::json!([
+                {
+                    "operationId": "list_users_v1",
+                    "version": 1,
+                    "headers": ["accept: version 1"]
+                },
+                {
+                    "operationId": "list_users_v2",
+                    "version": 2,
+                    "headers": ["accept: version 2"]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_build_openapi_spec_path_parameter_schemas() {
+        let uuid = serde_json::json!({ "type": "string", "format": "uuid" });
+        let routes = vec![
+            RouteInfo::new("GET", "/users/:id", "get_user", None, Vec::new())
+                .with_path_schemas(BTreeMap::from([("id".to_string(), uuid.clone())])),
+            RouteInfo::new("DELETE", "/users/:id", "delete_user", None, Vec::new()),
+        ];
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+        let json = serde_json::to_value(&spec).unwrap();
+
+        let user = &json["paths"]["/users/{id}"];
+        assert_eq!(user["get"]["parameters"][0]["schema"], uuid);
+        assert!(user["delete"]["parameters"][0].get("schema").is_none());
+    }
+
+    #[test]
+    fn test_build_openapi_spec_fields_parameter() {
+        let routes = vec![
+            RouteInfo::new("GET", "/users/:id", "get_user", None, Vec::new())
+                .with_selectable_fields(vec!["id".to_string(), "name".to_string()]),
+            RouteInfo::new("DELETE", "/users/:id", "delete_user", None, Vec::new()),
+        ];
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+        let json = serde_json::to_value(&spec).unwrap();
+
+        let params = json["paths"]["/users/{id}"]["get"]["parameters"]
+            .as_array()
+            .unwrap();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[1]["name"], "fields");
+        assert_eq!(params[1]["in"], "query");
+        assert_eq!(params[1]["required"], false);
+        assert!(
+            params[1]["description"]
+                .as_str()
+                .unwrap()
+                .ends_with("One of: id, name")
+        );
+        let params = json["paths"]["/users/{id}"]["delete"]["parameters"]
+            .as_array()
+            .unwrap();
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn test_spec_serializes_metadata() {
+        let mut spec = OpenApiSpec::new("Test API", "1.0.0");
+        spec.info = Info::new("Test API", "1.0.0")
+            .description("Manages users")
+            .contact(Contact {
+                name: Some("API Team".to_string()),
+                email: Some("api@example.com".to_string()),
+                ..Default::default()
+            })
+            .license(License::new("MIT").url("https://opensource.org/licenses/MIT"));
+        spec.servers.push(Server {
+            url: "https://api.example.com".to_string(),
+            description: Some("Production".to_string()),
+        });
+        spec.external_docs = Some(ExternalDocs {
+            url: "https://docs.example.com".to_string(),
+            description: None,
+        });
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["info"],
+            serde_json::json!({
+                "title": "Test API",
+                "version": "1.0.0",
+                "description": "Manages users",
+                "contact": {"name": "API Team", "email": "api@example.com"},
+                "license": {"name": "MIT", "url": "https://opensource.org/licenses/MIT"}
+            })
+        );
+        assert_eq!(
+            json["servers"],
+            serde_json::json!([{"url": "https://api.example.com", "description": "Production"}])
+        );
+        assert_eq!(
+            json["externalDocs"],
+            serde_json::json!({"url": "https://docs.example.com"})
+        );
+    }
+
+    #[test]
+    fn test_spec_omits_empty_metadata() {
+        let json = serde_json::to_value(OpenApiSpec::new("Test API", "1.0.0")).unwrap();
+
+        assert_eq!(
+            json["info"],
+            serde_json::json!({"title": "Test API", "version": "1.0.0"})
+        );
+        assert!(json.get("servers").is_none());
+        assert!(json.get("externalDocs").is_none());
+    }
+
+    #[test]
+    fn test_interpolate_env() {
+        // Cargo sets CARGO_PKG_NAME for the test binary
+        assert_eq!(
+            interpolate_env("https://${CARGO_PKG_NAME}.example.com/v1").unwrap(),
+            format!("https://{}.example.com/v1", env!("CARGO_PKG_NAME"))
+        );
+        assert_eq!(
+            interpolate_env("http://localhost:3000").unwrap(),
+            "http://localhost:3000"
+        );
+        assert_eq!(
+            interpolate_env("https://${RAPINA_TEST_OPENAPI_UNSET}").unwrap_err(),
+            "RAPINA_TEST_OPENAPI_UNSET"
+        );
+    }
 }
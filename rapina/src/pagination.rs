@@ -36,15 +36,13 @@
 
 use std::sync::Arc;
 
-use bytes::Bytes;
-use http_body_util::Full;
 use schemars::JsonSchema;
 use sea_orm::{EntityTrait, PaginatorTrait, Select};
 use serde::{Deserialize, Serialize};
 
 use crate::database::DbError;
 use crate::error::Error;
-use crate::extract::{FromRequestParts, PathParams};
+use crate::extract::{FromRequestParts, Json, PathParams};
 use crate::response::{BoxBody, IntoResponse};
 use crate::state::AppState;
 
@@ -81,9 +79,10 @@ struct PaginateQuery {
 
 /// Pagination extractor. Reads `?page=&per_page=` from the query string.
 ///
-/// Returns 422 when values are invalid (page < 1, per_page < 1,
-/// per_page > max). Respects [`PaginationConfig`] from app state if present,
-/// otherwise uses hardcoded defaults.
+/// Returns 400 when page or per_page is 0, and 422 when either isn't a
+/// number. A per_page above the max is clamped to it. Respects
+/// [`PaginationConfig`] from app state if present, otherwise uses hardcoded
+/// defaults.
 #[derive(Debug, Clone, Copy)]
 pub struct Paginate {
     pub page: u64,
@@ -108,25 +107,24 @@ impl FromRequestParts for Paginate {
         let per_page = raw.per_page.unwrap_or(default_per_page);
 
         if page < 1 {
-            return Err(Error::validation("page must be >= 1"));
+            return Err(Error::bad_request("page must be >= 1"));
         }
         if per_page < 1 {
-            return Err(Error::validation("per_page must be >= 1"));
-        }
-        if per_page > max_per_page {
-            return Err(Error::validation(format!(
-                "per_page must be <= {}",
-                max_per_page
-            )));
+            return Err(Error::bad_request("per_page must be >= 1"));
         }
 
-        Ok(Paginate { page, per_page })
+        Ok(Paginate {
+            page,
+            per_page: per_page.min(max_per_page),
+        })
     }
 }
 
 impl Paginate {
     /// Runs a paginated query: fetches the requested page and counts total
     /// items concurrently via `tokio::join!`.
+    ///
+    /// A page past the last one comes back with no data and the real totals.
     pub async fn exec<E>(
         &self,
         select: Select<E>,
@@ -166,6 +164,9 @@ impl Paginate {
 
 /// Paginated response wrapper. Implements `IntoResponse` so it can be
 /// returned directly from handlers without `Json<>` wrapping.
+///
+/// Handlers returning `Paginated<T>` or `Result<Paginated<T>>` are documented
+/// in the OpenAPI spec with the envelope.
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct Paginated<T> {
     pub data: Vec<T>,
@@ -194,12 +195,7 @@ impl<T> Paginated<T> {
 
 impl<T: Serialize> IntoResponse for Paginated<T> {
     fn into_response(self) -> http::Response<BoxBody> {
-        let body = serde_json::to_vec(&self).unwrap_or_default();
-        http::Response::builder()
-            .status(http::StatusCode::OK)
-            .header("content-type", "application/json")
-            .body(Full::new(Bytes::from(body)))
-            .unwrap()
+        Json(self).into_response()
     }
 }
 
@@ -234,7 +230,7 @@ mod tests {
         let result = Paginate::from_request_parts(&parts, &empty_params(), &empty_state()).await;
 
         let err = result.unwrap_err();
-        assert_eq!(err.status, 422);
+        assert_eq!(err.status, 400);
         assert!(err.message.contains("page must be >= 1"));
     }
 
@@ -244,18 +240,18 @@ mod tests {
         let result = Paginate::from_request_parts(&parts, &empty_params(), &empty_state()).await;
 
         let err = result.unwrap_err();
-        assert_eq!(err.status, 422);
+        assert_eq!(err.status, 400);
         assert!(err.message.contains("per_page must be >= 1"));
     }
 
     #[tokio::test]
-    async fn test_per_page_exceeds_max_rejected() {
-        let (parts, _) = TestRequest::get("/users?per_page=101").into_parts();
+    async fn test_per_page_exceeds_max_clamped() {
+        let (parts, _) = TestRequest::get("/users?page=2&per_page=101").into_parts();
         let result = Paginate::from_request_parts(&parts, &empty_params(), &empty_state()).await;
 
-        let err = result.unwrap_err();
-        assert_eq!(err.status, 422);
-        assert!(err.message.contains("per_page must be <= 100"));
+        let p = result.unwrap();
+        assert_eq!(p.page, 2);
+        assert_eq!(p.per_page, 100);
     }
 
     #[tokio::test]
@@ -280,9 +276,8 @@ mod tests {
         let (parts, _) = TestRequest::get("/users?per_page=51").into_parts();
         let result = Paginate::from_request_parts(&parts, &empty_params(), &state).await;
 
-        let err = result.unwrap_err();
-        assert_eq!(err.status, 422);
-        assert!(err.message.contains("per_page must be <= 50"));
+        let p = result.unwrap();
+        assert_eq!(p.per_page, 50);
     }
 
     #[tokio::test]
@@ -0,0 +1,803 @@
+//! Partial updates with JSON Merge Patch and JSON Patch.
+//!
+//! [`MergePatch`] reads an `application/merge-patch+json` body
+//! ([RFC 7396](https://www.rfc-editor.org/rfc/rfc7396)): a document shaped
+//! like the resource, where present members replace the current values and
+//! `null` removes them. [`JsonPatch`] reads an `application/json-patch+json`
+//! body ([RFC 6902](https://www.rfc-editor.org/rfc/rfc6902)): a list of
+//! `add`, `remove`, `replace`, `move`, `copy` and `test` operations.
+//!
+//! Both are applied to the current value of the resource, usually a fetched
+//! `Model`, through its JSON representation, and the result is deserialized
+//! back into the same type.
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::patch::MergePatch;
+//!
+//! #[patch("/todos/:id", consumes = "application/merge-patch+json")]
+//! async fn update_todo(id: Path<i32>, patch: MergePatch<Todo>) -> Result<Json<Todo>> {
+//!     let todo = load_todo(id.into_inner()).await?;
+//!     let todo = patch.apply(&todo)?;
+//!     Ok(Json(save_todo(todo).await?))
+//! }
+//! ```
+//!
+//! Naming the media type in `consumes` documents it in the OpenAPI spec.
+//!
+//! # Removing Fields
+//!
+//! A `null` in a merge patch removes the member, so after the merge an
+//! `Option` field is missing and deserializes as `None`. Removing a field
+//! that isn't an `Option` fails with 422, since the patched document no
+//! longer describes a valid value. The same goes for JSON Patch `remove`.
+//! As a consequence, a merge patch can't store a JSON `null` inside a
+//! `serde_json::Value` field; use JSON Patch for that.
+//!
+//! # Errors
+//!
+//! | Problem | Status |
+//! |---------|--------|
+//! | Wrong `Content-Type` | 415 |
+//! | Body isn't JSON, or an operation is malformed | 400 |
+//! | An operation can't be applied, or a `test` fails | 409 |
+//! | The patched document doesn't deserialize | 422 |
+//!
+//! JSON Patch errors name the failing operation in `details`, by its index
+//! in the list, along with its `op` and `path`.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use http::Request;
+use hyper::body::Incoming;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::extract::{FromRequest, PathParams, read_body_limited};
+use crate::json::JsonOptions;
+use crate::middleware::media_essence;
+use crate::state::AppState;
+
+/// Media type of a JSON Merge Patch document.
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// Media type of a JSON Patch document.
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+/// Extracts an `application/merge-patch+json` body to apply to a `T`.
+///
+/// Any JSON value is a valid merge patch. An object merges member by
+/// member, anything else replaces the target.
+#[derive(Debug)]
+pub struct MergePatch<T> {
+    patch: Value,
+    target: PhantomData<fn() -> T>,
+}
+
+impl<T> MergePatch<T> {
+    /// Wraps a merge patch document.
+    pub fn new(patch: Value) -> Self {
+        Self {
+            patch,
+            target: PhantomData,
+        }
+    }
+
+    /// Returns the patch document.
+    pub fn as_value(&self) -> &Value {
+        &self.patch
+    }
+
+    /// Returns the patch document.
+    pub fn into_inner(self) -> Value {
+        self.patch
+    }
+
+    /// Merges the patch into `target` and returns the result.
+    ///
+    /// Fails with 422 when the merged document is no longer a valid `T`,
+    /// e.g. when it removed a field that isn't an `Option`.
+    pub fn apply(&self, target: &T) -> Result<T, Error>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let mut doc = to_document(target)?;
+        merge(&mut doc, &self.patch);
+        from_document(&doc)
+    }
+}
+
+/// Applies the merge patch `patch` to `target`, as RFC 7396 describes.
+pub fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("target was just made an object");
+    };
+    for (name, value) in patch {
+        if value.is_null() {
+            target.remove(name);
+        } else {
+            merge(target.entry(name.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// A single JSON Patch operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Adds `value` at `path`, inserting into arrays.
+    Add { path: String, value: Value },
+    /// Removes the value at `path`.
+    Remove { path: String },
+    /// Replaces the value at `path`, which must exist.
+    Replace { path: String, value: Value },
+    /// Removes the value at `from` and adds it at `path`.
+    Move { from: String, path: String },
+    /// Adds a copy of the value at `from` at `path`.
+    Copy { from: String, path: String },
+    /// Checks that the value at `path` equals `value`.
+    Test { path: String, value: Value },
+}
+
+impl PatchOperation {
+    /// The operation's name, as written in `op`.
+    pub fn op(&self) -> &'static str {
+        match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+            PatchOperation::Move { .. } => "move",
+            PatchOperation::Copy { .. } => "copy",
+            PatchOperation::Test { .. } => "test",
+        }
+    }
+
+    /// The JSON Pointer the operation targets.
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Add { path, .. }
+            | PatchOperation::Remove { path }
+            | PatchOperation::Replace { path, .. }
+            | PatchOperation::Move { path, .. }
+            | PatchOperation::Copy { path, .. }
+            | PatchOperation::Test { path, .. } => path,
+        }
+    }
+
+    fn from(&self) -> Option<&str> {
+        match self {
+            PatchOperation::Move { from, .. } | PatchOperation::Copy { from, .. } => Some(from),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, doc: &mut Value) -> Result<(), String> {
+        match self {
+            PatchOperation::Add { path, value } => add(doc, &pointer(path), value.clone()),
+            PatchOperation::Remove { path } => remove(doc, &pointer(path)).map(drop),
+            PatchOperation::Replace { path, value } => {
+                let target = get_mut(doc, &pointer(path))?;
+                *target = value.clone();
+                Ok(())
+            }
+            PatchOperation::Move { from, path } => {
+                let value = remove(doc, &pointer(from))?;
+                add(doc, &pointer(path), value)
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = get_mut(doc, &pointer(from))?.clone();
+                add(doc, &pointer(path), value)
+            }
+            PatchOperation::Test { path, value } => {
+                if json_eq(get_mut(doc, &pointer(path))?, value) {
+                    Ok(())
+                } else {
+                    Err("the value does not match".to_string())
+                }
+            }
+        }
+    }
+}
+
+/// Extracts an `application/json-patch+json` body: a list of operations.
+///
+/// Every operation is checked when the body is read, so a malformed one
+/// is rejected with 400 before anything is applied.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonPatch(pub Vec<PatchOperation>);
+
+impl JsonPatch {
+    /// Parses and checks a JSON Patch document.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        let doc: Value = serde_json::from_slice(bytes)
+            .map_err(|e| Error::bad_request(format!("Invalid JSON in request body: {}", e)))?;
+        let Value::Array(items) = doc else {
+            return Err(Error::bad_request(
+                "A JSON Patch document must be an array of operations",
+            ));
+        };
+
+        let mut operations = Vec::with_capacity(items.len());
+        for (index, item) in items.into_iter().enumerate() {
+            let operation: PatchOperation = serde_json::from_value(item)
+                .map_err(|e| invalid_operation(index, e.to_string()))?;
+            for pointer in std::iter::once(operation.path()).chain(operation.from()) {
+                if parse_pointer(pointer).is_none() {
+                    return Err(invalid_operation(
+                        index,
+                        format!("'{}' is not a valid JSON Pointer", pointer),
+                    ));
+                }
+            }
+            if let PatchOperation::Move { from, path } = &operation
+                && path.starts_with(from.as_str())
+                && path[from.len()..].starts_with('/')
+            {
+                return Err(invalid_operation(
+                    index,
+                    "a value can't be moved into one of its own children".to_string(),
+                ));
+            }
+            operations.push(operation);
+        }
+        Ok(JsonPatch(operations))
+    }
+
+    /// Returns the operations.
+    pub fn operations(&self) -> &[PatchOperation] {
+        &self.0
+    }
+
+    /// Applies every operation to `doc`, in order.
+    ///
+    /// Either all operations apply or, on the first failure, `doc` is left
+    /// as it was and a 409 names the failing operation.
+    pub fn apply_to(&self, doc: &mut Value) -> Result<(), Error> {
+        let mut patched = doc.clone();
+        for (index, operation) in self.0.iter().enumerate() {
+            operation.apply(&mut patched).map_err(|reason| {
+                Error::conflict(format!(
+                    "Operation {} ({} {}) failed: {}",
+                    index,
+                    operation.op(),
+                    operation.path(),
+                    reason
+                ))
+                .with_details(serde_json::json!({
+                    "operation": index,
+                    "op": operation.op(),
+                    "path": operation.path(),
+                }))
+            })?;
+        }
+        *doc = patched;
+        Ok(())
+    }
+
+    /// Applies the operations to `target` and returns the result.
+    ///
+    /// Fails with 422 when the patched document is no longer a valid `T`.
+    pub fn apply<T: Serialize + DeserializeOwned>(&self, target: &T) -> Result<T, Error> {
+        let mut doc = to_document(target)?;
+        self.apply_to(&mut doc)?;
+        from_document(&doc)
+    }
+}
+
+fn invalid_operation(index: usize, reason: String) -> Error {
+    Error::bad_request(format!(
+        "Invalid JSON Patch operation {}: {}",
+        index, reason
+    ))
+    .with_details(serde_json::json!({ "operation": index }))
+}
+
+/// The request's media type must be `expected`.
+fn require_content_type(req: &Request<Incoming>, expected: &str) -> Result<(), Error> {
+    let content_type = req
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    match content_type {
+        Some(ct) if media_essence(ct) == expected => Ok(()),
+        other => Err(Error::unsupported_media_type(format!(
+            "Expected Content-Type '{}', got '{}'",
+            expected,
+            other.unwrap_or("none")
+        ))),
+    }
+}
+
+impl<T> FromRequest for MergePatch<T> {
+    async fn from_request(
+        req: Request<Incoming>,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        require_content_type(&req, MERGE_PATCH_CONTENT_TYPE)?;
+        let bytes = read_body_limited(req).await?;
+        let patch = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::bad_request(format!("Invalid JSON in request body: {}", e)))?;
+        Ok(MergePatch::new(patch))
+    }
+}
+
+impl FromRequest for JsonPatch {
+    async fn from_request(
+        req: Request<Incoming>,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        require_content_type(&req, JSON_PATCH_CONTENT_TYPE)?;
+        let bytes = read_body_limited(req).await?;
+        JsonPatch::from_slice(&bytes)
+    }
+}
+
+/// `target` as JSON, written the way the app sends it.
+fn to_document<T: Serialize>(target: &T) -> Result<Value, Error> {
+    let bytes = JsonOptions::current().to_vec(target).map_err(|e| {
+        tracing::error!(error = %e, "Failed to serialize the patch target");
+        Error::internal("Failed to serialize the patch target")
+    })?;
+    serde_json::from_slice(&bytes).map_err(|_| Error::internal("Failed to read the patch target"))
+}
+
+/// Reads a patched document back, accepting the app's field names.
+fn from_document<T: DeserializeOwned>(doc: &Value) -> Result<T, Error> {
+    let bytes =
+        serde_json::to_vec(doc).map_err(|_| Error::internal("Failed to write the patch"))?;
+    JsonOptions::current()
+        .from_slice(&bytes)
+        .map_err(|e| Error::validation(format!("The patched document is invalid: {}", e)))
+}
+
+// ---------------------------------------------------------------------------
+// JSON Pointer (RFC 6901)
+// ---------------------------------------------------------------------------
+
+/// Splits a JSON Pointer into its unescaped reference tokens.
+fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|token| {
+            let mut out = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                if c != '~' {
+                    out.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('0') => out.push('~'),
+                    Some('1') => out.push('/'),
+                    _ => return None,
+                }
+            }
+            Some(out)
+        })
+        .collect()
+}
+
+/// A pointer already checked by [`JsonPatch::from_slice`].
+fn pointer(pointer: &str) -> Vec<String> {
+    parse_pointer(pointer).unwrap_or_default()
+}
+
+/// Parses an array index, which can't have leading zeros.
+fn array_index(token: &str) -> Result<usize, String> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    valid
+        .then(|| token.parse().ok())
+        .flatten()
+        .ok_or_else(|| format!("'{}' is not an array index", token))
+}
+
+fn get_mut<'a>(doc: &'a mut Value, tokens: &[String]) -> Result<&'a mut Value, String> {
+    let mut current = doc;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(items) => {
+                let index = array_index(token)?;
+                items.get_mut(index)
+            }
+            _ => None,
+        }
+        .ok_or_else(|| "the path does not exist".to_string())?;
+    }
+    Ok(current)
+}
+
+fn add(doc: &mut Value, tokens: &[String], value: Value) -> Result<(), String> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match get_mut(doc, parent)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let index = if last == "-" {
+                items.len()
+            } else {
+                array_index(last)?
+            };
+            if index > items.len() {
+                return Err(format!("index {} is past the end of the array", index));
+            }
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err("the parent is not an object or array".to_string()),
+    }
+}
+
+fn remove(doc: &mut Value, tokens: &[String]) -> Result<Value, String> {
+    let Some((last, parent)) = tokens.split_last() else {
+        return Err("the whole document can't be removed".to_string());
+    };
+    match get_mut(doc, parent)? {
+        Value::Object(map) => map
+            .remove(last)
+            .ok_or_else(|| "the path does not exist".to_string()),
+        Value::Array(items) => {
+            let index = array_index(last)?;
+            if index >= items.len() {
+                return Err("the path does not exist".to_string());
+            }
+            Ok(items.remove(index))
+        }
+        _ => Err("the path does not exist".to_string()),
+    }
+}
+
+/// JSON equality, where numbers compare by value (`1` equals `1.0`).
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => a.as_f64() == b.as_f64(),
+        },
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(name, a)| b.get(name).is_some_and(|b| json_eq(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(operations: Value) -> JsonPatch {
+        JsonPatch::from_slice(operations.to_string().as_bytes()).unwrap()
+    }
+
+    fn apply(doc: Value, operations: Value) -> Result<Value, Error> {
+        let mut doc = doc;
+        patch(operations).apply_to(&mut doc)?;
+        Ok(doc)
+    }
+
+    #[test]
+    fn test_merge_patch_rfc7396_examples() {
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (
+                json!({"a": "b", "b": "c"}),
+                json!({"a": null}),
+                json!({"b": "c"}),
+            ),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (
+                json!({"a": [{"b": "c"}]}),
+                json!({"a": [1]}),
+                json!({"a": [1]}),
+            ),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+            (json!({"a": "foo"}), json!(null), json!(null)),
+            (json!({"a": "foo"}), json!("bar"), json!("bar")),
+            (
+                json!({"e": null}),
+                json!({"a": 1}),
+                json!({"e": null, "a": 1}),
+            ),
+            (
+                json!([1, 2]),
+                json!({"a": "b", "c": null}),
+                json!({"a": "b"}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ];
+        for (target, patch, expected) in cases {
+            let mut doc = target.clone();
+            merge(&mut doc, &patch);
+            assert_eq!(doc, expected, "merging {} into {}", patch, target);
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Todo {
+        title: String,
+        notes: Option<String>,
+    }
+
+    #[test]
+    fn test_merge_patch_null_clears_option_field() {
+        let todo = Todo {
+            title: "Buy milk".into(),
+            notes: Some("oat".into()),
+        };
+
+        let patched = MergePatch::new(json!({"notes": null}))
+            .apply(&todo)
+            .unwrap();
+
+        assert_eq!(
+            patched,
+            Todo {
+                title: "Buy milk".into(),
+                notes: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_patch_null_on_required_field_is_invalid() {
+        let todo = Todo {
+            title: "Buy milk".into(),
+            notes: None,
+        };
+
+        let err = MergePatch::new(json!({"title": null}))
+            .apply(&todo)
+            .unwrap_err();
+
+        assert_eq!(err.status, 422);
+        assert!(err.message.contains("title"));
+    }
+
+    #[test]
+    fn test_json_patch_rfc6902_examples() {
+        let cases = [
+            // A.1 Adding an object member
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux"}]),
+                json!({"baz": "qux", "foo": "bar"}),
+            ),
+            // A.2 Adding an array element
+            (
+                json!({"foo": ["bar", "baz"]}),
+                json!([{"op": "add", "path": "/foo/1", "value": "qux"}]),
+                json!({"foo": ["bar", "qux", "baz"]}),
+            ),
+            // A.3 Removing an object member
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "remove", "path": "/baz"}]),
+                json!({"foo": "bar"}),
+            ),
+            // A.4 Removing an array element
+            (
+                json!({"foo": ["bar", "qux", "baz"]}),
+                json!([{"op": "remove", "path": "/foo/1"}]),
+                json!({"foo": ["bar", "baz"]}),
+            ),
+            // A.5 Replacing a value
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "replace", "path": "/baz", "value": "boo"}]),
+                json!({"baz": "boo", "foo": "bar"}),
+            ),
+            // A.6 Moving a value
+            (
+                json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+                json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]),
+                json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}),
+            ),
+            // A.7 Moving an array element
+            (
+                json!({"foo": ["all", "grass", "cows", "eat"]}),
+                json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}]),
+                json!({"foo": ["all", "cows", "eat", "grass"]}),
+            ),
+            // A.8 Testing a value: success
+            (
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+                json!([
+                    {"op": "test", "path": "/baz", "value": "qux"},
+                    {"op": "test", "path": "/foo/1", "value": 2}
+                ]),
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+            ),
+            // A.10 Adding a nested member object
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}]),
+                json!({"foo": "bar", "child": {"grandchild": {}}}),
+            ),
+            // A.11 Ignoring unrecognized elements
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]),
+                json!({"foo": "bar", "baz": "qux"}),
+            ),
+            // A.14 ~ escape ordering
+            (
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": 10}]),
+                json!({"/": 9, "~1": 10}),
+            ),
+            // A.16 Adding an array value
+            (
+                json!({"foo": ["bar"]}),
+                json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]),
+                json!({"foo": ["bar", ["abc", "def"]]}),
+            ),
+        ];
+        for (target, operations, expected) in cases {
+            assert_eq!(
+                apply(target, operations.clone()).unwrap(),
+                expected,
+                "applying {}",
+                operations
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_patch_rfc6902_error_examples() {
+        let cases = [
+            // A.9 Testing a value: error
+            (
+                json!({"baz": "qux"}),
+                json!([{"op": "test", "path": "/baz", "value": "bar"}]),
+            ),
+            // A.12 Adding to a nonexistent target
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz/bat", "value": "qux"}]),
+            ),
+            // A.15 Comparing strings and numbers
+            (
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": "10"}]),
+            ),
+        ];
+        for (target, operations) in cases {
+            let err = apply(target, operations.clone()).unwrap_err();
+            assert_eq!(err.status, 409, "applying {}", operations);
+        }
+    }
+
+    #[test]
+    fn test_json_patch_error_names_the_failing_operation() {
+        let mut doc = json!({"tags": ["a"]});
+        let err = patch(json!([
+            {"op": "add", "path": "/tags/-", "value": "b"},
+            {"op": "remove", "path": "/tags/5"}
+        ]))
+        .apply_to(&mut doc)
+        .unwrap_err();
+
+        assert_eq!(err.status, 409);
+        assert_eq!(
+            err.details,
+            Some(json!({"operation": 1, "op": "remove", "path": "/tags/5"}))
+        );
+        // Nothing applies when one operation fails
+        assert_eq!(doc, json!({"tags": ["a"]}));
+    }
+
+    #[test]
+    fn test_json_patch_numbers_compare_by_value() {
+        assert!(
+            apply(
+                json!({"n": 1}),
+                json!([{"op": "test", "path": "/n", "value": 1.0}])
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_json_patch_rejects_leading_zero_index() {
+        let err = apply(
+            json!({"foo": ["a", "b"]}),
+            json!([{"op": "remove", "path": "/foo/01"}]),
+        )
+        .unwrap_err();
+        assert_eq!(err.status, 409);
+    }
+
+    #[test]
+    fn test_invalid_json_patch_documents() {
+        let cases = [
+            (json!({"op": "add"}), None),
+            (json!([{"op": "frobnicate", "path": "/a"}]), Some(0)),
+            (
+                json!([{"op": "remove", "path": "/a"}, {"op": "add", "path": "/b"}]),
+                Some(1),
+            ),
+            (json!([{"op": "remove", "path": "a"}]), Some(0)),
+            (json!([{"op": "remove", "path": "/a~2"}]), Some(0)),
+            (
+                json!([{"op": "move", "from": "/a", "path": "/a/b"}]),
+                Some(0),
+            ),
+        ];
+        for (doc, operation) in cases {
+            let err = JsonPatch::from_slice(doc.to_string().as_bytes()).unwrap_err();
+            assert_eq!(err.status, 400, "parsing {}", doc);
+            assert_eq!(
+                err.details.as_ref().map(|d| d["operation"].clone()),
+                operation.map(|i| json!(i)),
+                "parsing {}",
+                doc
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_patch_applies_to_typed_value() {
+        let todo = Todo {
+            title: "Buy milk".into(),
+            notes: None,
+        };
+
+        let patched = patch(json!([
+            {"op": "replace", "path": "/title", "value": "Buy oat milk"},
+            {"op": "add", "path": "/notes", "value": "two cartons"}
+        ]))
+        .apply(&todo)
+        .unwrap();
+
+        assert_eq!(patched.title, "Buy oat milk");
+        assert_eq!(patched.notes.as_deref(), Some("two cartons"));
+    }
+}
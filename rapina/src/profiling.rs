@@ -0,0 +1,458 @@
+//! Heap and allocation profiling for chasing leaks in long-running services.
+//!
+//! Behind the `profiling` feature, [`Rapina::with_heap_profiling`] registers
+//! `GET /__rapina/heap`, which reports:
+//!
+//! - allocator counters, when [`TrackingAllocator`] is the global allocator
+//! - the process's resident set size, where the platform exposes it
+//! - live connections and in-flight requests
+//! - entries in the [`ResponseCache`] and in [`AppState`]
+//!
+//! The report is JSON by default; `?format=text` renders it for a terminal.
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::profiling::TrackingAllocator;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: TrackingAllocator = TrackingAllocator::system();
+//!
+//! Rapina::new()
+//!     .with_heap_profiling(true)
+//!     .router(router)
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+//!
+//! [`TrackingAllocator`] wraps any [`GlobalAlloc`], so an app already on
+//! jemalloc or mimalloc keeps it and gains the counters:
+//! `TrackingAllocator::new(tikv_jemallocator::Jemalloc)`.
+//!
+//! The endpoint is served in debug builds once enabled. Release builds also
+//! need [`HEAP_PROFILING_ENV`] set to `1`, so a stray `with_heap_profiling`
+//! call can't expose it in production on its own.
+//!
+//! [`Rapina::with_heap_profiling`]: crate::app::Rapina::with_heap_profiling
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use http::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use serde::Serialize;
+
+use crate::cache::ResponseCache;
+use crate::context::RequestContext;
+use crate::extract::PathParams;
+use crate::middleware::{BoxFuture, Middleware, Next};
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+/// Environment variable that lets release builds serve `/__rapina/heap`.
+pub const HEAP_PROFILING_ENV: &str = "RAPINA_HEAP_PROFILING";
+
+/// Whether `/__rapina/heap` is served, given
+/// [`with_heap_profiling`](crate::app::Rapina::with_heap_profiling) and the
+/// [`HEAP_PROFILING_ENV`] value.
+pub(crate) fn heap_profiling_enabled(requested: bool, env: Option<&str>) -> bool {
+    requested && (cfg!(debug_assertions) || matches!(env.map(str::trim), Some("1" | "true")))
+}
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// A global allocator that counts what passes through it.
+///
+/// Delegates to the wrapped allocator, [`System`] unless given another, and
+/// keeps process-wide counters that [`AllocatorStats::current`] reads. Only
+/// one global allocator exists per binary, so the counters are shared.
+#[derive(Debug, Default)]
+pub struct TrackingAllocator<A = System> {
+    inner: A,
+}
+
+impl TrackingAllocator<System> {
+    /// Tracks the system allocator.
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> TrackingAllocator<A> {
+    /// Tracks `inner`.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    fn record_alloc(size: usize) {
+        INSTALLED.store(true, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+// SAFETY: every call is forwarded unchanged to `inner`; the counters don't
+// touch the memory handed out.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Counters kept by [`TrackingAllocator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AllocatorStats {
+    /// Bytes currently allocated.
+    pub allocated_bytes: usize,
+    /// Most bytes allocated at once since the process started.
+    pub peak_bytes: usize,
+    /// Allocations made, reallocations included.
+    pub allocations: u64,
+    /// Deallocations made, reallocations included.
+    pub deallocations: u64,
+}
+
+impl AllocatorStats {
+    /// Reads the counters, or `None` if [`TrackingAllocator`] isn't the
+    /// global allocator.
+    pub fn current() -> Option<Self> {
+        if !INSTALLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(Self {
+            allocated_bytes: ALLOCATED.load(Ordering::Relaxed),
+            peak_bytes: PEAK.load(Ordering::Relaxed),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Allocations not yet freed.
+    pub fn live_allocations(&self) -> u64 {
+        self.allocations.saturating_sub(self.deallocations)
+    }
+}
+
+/// Resident set size of the process in bytes, from `/proc/self/status`.
+///
+/// `None` on platforms without procfs.
+pub fn process_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_rss(&status)
+}
+
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line
+        .trim_start_matches("VmRSS:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Live connection and in-flight request counts, shared through
+/// application state.
+///
+/// Registered by
+/// [`Rapina::with_heap_profiling`](crate::app::Rapina::with_heap_profiling).
+/// Clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct ServerStats {
+    connections: Arc<AtomicUsize>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl ServerStats {
+    /// Creates zeroed counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connections currently open.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    /// Requests currently being handled.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Counts a connection until the returned guard is dropped.
+    pub(crate) fn connection_opened(&self) -> Tracked {
+        Tracked::new(self.connections.clone())
+    }
+
+    fn request_started(&self) -> Tracked {
+        Tracked::new(self.in_flight.clone())
+    }
+}
+
+/// Decrements its counter when dropped.
+pub(crate) struct Tracked(Arc<AtomicUsize>);
+
+impl Tracked {
+    fn new(counter: Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts requests in [`ServerStats`] while they're handled.
+pub(crate) struct InFlightMiddleware {
+    stats: ServerStats,
+}
+
+impl InFlightMiddleware {
+    pub(crate) fn new(stats: ServerStats) -> Self {
+        Self { stats }
+    }
+}
+
+impl Middleware for InFlightMiddleware {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            let _request = self.stats.request_started();
+            next.run(req).await
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "in_flight"
+    }
+}
+
+/// Report served by `GET /__rapina/heap`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeapReport {
+    /// [`TrackingAllocator`] counters, `null` when it isn't installed.
+    pub allocator: Option<AllocatorStats>,
+    /// Resident set size in bytes, `null` where the platform doesn't say.
+    pub rss_bytes: Option<u64>,
+    /// Connections currently open.
+    pub connections: usize,
+    /// Requests being handled, the one asking for the report included.
+    pub in_flight_requests: usize,
+    /// Entries in the shared [`ResponseCache`].
+    pub cached_responses: usize,
+    /// Values registered in [`AppState`].
+    pub state_entries: usize,
+}
+
+impl HeapReport {
+    /// Collects the report for an app's state.
+    pub fn collect(state: &AppState) -> Self {
+        let stats = state.get::<ServerStats>();
+        Self {
+            allocator: AllocatorStats::current(),
+            rss_bytes: process_rss_bytes(),
+            connections: stats.map_or(0, ServerStats::connections),
+            in_flight_requests: stats.map_or(0, ServerStats::in_flight),
+            cached_responses: state.get::<ResponseCache>().map_or(0, ResponseCache::len),
+            state_entries: state.len(),
+        }
+    }
+
+    /// Renders the report as aligned `name: value` lines.
+    pub fn to_text(&self) -> String {
+        let unknown = || "unknown".to_string();
+        let mut rows: Vec<(&str, String)> = Vec::new();
+        match &self.allocator {
+            Some(allocator) => {
+                rows.push(("allocated", format_bytes(allocator.allocated_bytes as u64)));
+                rows.push(("peak", format_bytes(allocator.peak_bytes as u64)));
+                rows.push(("allocations", allocator.allocations.to_string()));
+                rows.push(("deallocations", allocator.deallocations.to_string()));
+                rows.push(("live allocations", allocator.live_allocations().to_string()));
+            }
+            None => rows.push(("allocator", "not tracked".to_string())),
+        }
+        rows.push((
+            "rss",
+            self.rss_bytes.map(format_bytes).unwrap_or_else(unknown),
+        ));
+        rows.push(("connections", self.connections.to_string()));
+        rows.push(("in-flight requests", self.in_flight_requests.to_string()));
+        rows.push(("cached responses", self.cached_responses.to_string()));
+        rows.push(("state entries", self.state_entries.to_string()));
+
+        let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        let mut text = String::new();
+        for (name, value) in rows {
+            let _ = writeln!(
+                text,
+                "{:<width$} {}",
+                format!("{}:", name),
+                value,
+                width = width + 1
+            );
+        }
+        text
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {} ({} bytes)", value, UNITS[unit], bytes)
+}
+
+/// Handler for `GET /__rapina/heap`.
+///
+/// Returns the [`HeapReport`] as JSON, or as text with `?format=text`.
+pub async fn heap_profile(
+    req: Request<Incoming>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    let report = HeapReport::collect(&state);
+    let text = req
+        .uri()
+        .query()
+        .is_some_and(|query| query.split('&').any(|pair| pair == "format=text"));
+
+    let (content_type, body) = if text {
+        ("text/plain; charset=utf-8", report.to_text().into_bytes())
+    } else {
+        match serde_json::to_vec(&report) {
+            Ok(json) => ("application/json", json),
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", content_type)
+        .header("cache-control", "no-store")
+        .body(BoxBody::new(body))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heap_profiling_needs_the_flag() {
+        assert!(!heap_profiling_enabled(false, Some("1")));
+        assert_eq!(heap_profiling_enabled(true, None), cfg!(debug_assertions));
+        assert!(heap_profiling_enabled(true, Some("1")));
+        assert!(heap_profiling_enabled(true, Some(" true ")));
+        assert_eq!(
+            heap_profiling_enabled(true, Some("0")),
+            cfg!(debug_assertions)
+        );
+    }
+
+    #[test]
+    fn test_parse_vm_rss() {
+        let status = "Name:\trapina\nVmPeak:\t  20000 kB\nVmRSS:\t   10240 kB\nThreads:\t4\n";
+        assert_eq!(parse_vm_rss(status), Some(10240 * 1024));
+        assert_eq!(parse_vm_rss("Name:\trapina\n"), None);
+    }
+
+    #[test]
+    fn test_tracked_counts_until_dropped() {
+        let stats = ServerStats::new();
+        let first = stats.connection_opened();
+        let second = stats.connection_opened();
+        assert_eq!(stats.connections(), 2);
+        drop(first);
+        assert_eq!(stats.connections(), 1);
+        drop(second);
+        assert_eq!(stats.connections(), 0);
+        assert_eq!(stats.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_text_report_lists_every_figure() {
+        let report = HeapReport {
+            allocator: Some(AllocatorStats {
+                allocated_bytes: 3 * 1024 * 1024,
+                peak_bytes: 4 * 1024 * 1024,
+                allocations: 10,
+                deallocations: 4,
+            }),
+            rss_bytes: None,
+            connections: 2,
+            in_flight_requests: 1,
+            cached_responses: 5,
+            state_entries: 7,
+        };
+        let text = report.to_text();
+
+        assert!(
+            text.contains("allocated:          3.0 MiB (3145728 bytes)"),
+            "{}",
+            text
+        );
+        assert!(text.contains("live allocations:   6"), "{}", text);
+        assert!(text.contains("rss:                unknown"), "{}", text);
+        assert!(text.contains("cached responses:   5"), "{}", text);
+        assert!(text.contains("state entries:      7"), "{}", text);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB (1536 bytes)");
+    }
+}
@@ -0,0 +1,268 @@
+//! Reloading configuration without restarting the server.
+//!
+//! Register callbacks with [`Rapina::on_reload`](crate::app::Rapina::on_reload)
+//! and they run every time the process receives `SIGHUP`. A callback usually
+//! re-reads a config file and stores the result in a [`Reloadable`] cell kept
+//! in application state. Handlers read the current value with the
+//! [`ReloadableState`] extractor:
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::reload::{Reloadable, ReloadableState};
+//!
+//! #[derive(Clone, Deserialize)]
+//! struct Limits {
+//!     max_upload_mb: u32,
+//! }
+//!
+//! #[get("/limits")]
+//! async fn limits(limits: ReloadableState<Limits>) -> String {
+//!     format!("{} MB", limits.max_upload_mb)
+//! }
+//!
+//! Rapina::new()
+//!     .state(Reloadable::new(load_limits()?))
+//!     .on_reload(|state| async move {
+//!         let limits = load_limits()?;
+//!         state.get::<Reloadable<Limits>>().unwrap().set(limits);
+//!         Ok::<_, std::io::Error>(())
+//!     })
+//!     .discover()
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+//!
+//! Reloads run in the background, so in-flight requests finish with the
+//! snapshot they started with. A callback that returns an error is logged and
+//! leaves its cell untouched, so the previous configuration stays in effect.
+//!
+//! `SIGHUP` is only handled on Unix, and only when at least one callback is
+//! registered; elsewhere, reload callbacks never run.
+
+use std::fmt;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, watch};
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams, missing_state};
+use crate::state::AppState;
+
+/// A reload callback: a closure that returns a boxed future.
+pub(crate) type ReloadHook = Box<
+    dyn Fn(Arc<AppState>) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync,
+>;
+
+/// A value that reload callbacks can replace while the server is running.
+///
+/// Clones share the same value. Register one with `.state()` and swap its
+/// contents with [`set`](Self::set); requests that already read the old value
+/// keep their snapshot.
+///
+/// # Example
+///
+/// ```
+/// use rapina::reload::Reloadable;
+///
+/// let level = Reloadable::new("info".to_string());
+/// let snapshot = level.get();
+///
+/// level.set("debug".to_string());
+/// assert_eq!(*level.get(), "debug");
+/// assert_eq!(*snapshot, "info");
+/// ```
+pub struct Reloadable<T> {
+    tx: Arc<watch::Sender<Arc<T>>>,
+}
+
+impl<T> Reloadable<T> {
+    /// Creates a cell holding `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            tx: Arc::new(watch::Sender::new(Arc::new(value))),
+        }
+    }
+
+    /// Returns the current value.
+    pub fn get(&self) -> Arc<T> {
+        self.tx.borrow().clone()
+    }
+
+    /// Replaces the value seen by subsequent reads.
+    pub fn set(&self, value: T) {
+        self.tx.send_replace(Arc::new(value));
+    }
+
+    /// Returns a receiver that is notified every time the value is replaced.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<T>> {
+        self.tx.subscribe()
+    }
+}
+
+impl<T> Clone for Reloadable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Reloadable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Reloadable").field(&self.get()).finish()
+    }
+}
+
+/// Extracts the current snapshot of a [`Reloadable<T>`] registered in state.
+///
+/// The snapshot doesn't change for the rest of the request, even if a reload
+/// replaces the value meanwhile.
+#[derive(Debug, Clone)]
+pub struct ReloadableState<T>(pub Arc<T>);
+
+impl<T> ReloadableState<T> {
+    /// Consumes the extractor and returns the snapshot.
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for ReloadableState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Send + Sync + 'static> FromRequestParts for ReloadableState<T> {
+    async fn from_request_parts(
+        _parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let cell = state
+            .get::<Reloadable<T>>()
+            .ok_or_else(|| missing_state(&[std::any::type_name::<Reloadable<T>>()]))?;
+        Ok(ReloadableState(cell.get()))
+    }
+}
+
+/// Runs reload callbacks, one reload at a time.
+pub(crate) struct Reloader {
+    hooks: Vec<ReloadHook>,
+    running: Mutex<()>,
+}
+
+impl Reloader {
+    pub(crate) fn new(hooks: Vec<ReloadHook>) -> Self {
+        Self {
+            hooks,
+            running: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Runs every callback in registration order, logging the ones that fail.
+    ///
+    /// Returns the number of callbacks that failed.
+    pub(crate) async fn reload(&self, state: &Arc<AppState>) -> usize {
+        let _running = self.running.lock().await;
+        let mut failed = 0;
+        for hook in &self.hooks {
+            if let Err(error) = hook(state.clone()).await {
+                tracing::error!(error = %error, "Reload failed, keeping the previous configuration");
+                failed += 1;
+            }
+        }
+        if failed == 0 {
+            tracing::info!("Configuration reloaded");
+        }
+        failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{TestRequest, empty_params, empty_state, state_with};
+
+    fn hook<F, Fut>(f: F) -> ReloadHook
+    where
+        F: Fn(Arc<AppState>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        Box::new(move |state| Box::pin(f(state)))
+    }
+
+    #[test]
+    fn test_reloadable_clones_share_value() {
+        let cell = Reloadable::new(1);
+        let other = cell.clone();
+        other.set(2);
+        assert_eq!(*cell.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reloadable_subscribe_sees_updates() {
+        let cell = Reloadable::new("a");
+        let mut rx = cell.subscribe();
+        cell.set("b");
+        rx.changed().await.unwrap();
+        assert_eq!(**rx.borrow(), "b");
+    }
+
+    #[tokio::test]
+    async fn test_reloadable_state_extracts_snapshot() {
+        let state = state_with(Reloadable::new("v1".to_string()));
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let snapshot =
+            ReloadableState::<String>::from_request_parts(&parts, &empty_params(), &state)
+                .await
+                .unwrap();
+        state
+            .get::<Reloadable<String>>()
+            .unwrap()
+            .set("v2".to_string());
+
+        assert_eq!(*snapshot, "v1");
+    }
+
+    #[tokio::test]
+    async fn test_reloadable_state_missing() {
+        let (parts, _) = TestRequest::get("/").into_parts();
+
+        let err =
+            ReloadableState::<String>::from_request_parts(&parts, &empty_params(), &empty_state())
+                .await
+                .unwrap_err();
+        assert_eq!(err.status, 500);
+        assert!(err.message.contains("Reloadable"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_keeps_previous_value() {
+        let state = state_with(Reloadable::new(1));
+        let reloader = Reloader::new(vec![
+            hook(|_| async { Err("config file is invalid".to_string()) }),
+            hook(|state| async move {
+                state.get::<Reloadable<i32>>().unwrap().set(2);
+                Ok(())
+            }),
+        ]);
+
+        assert_eq!(reloader.reload(&state).await, 1);
+        assert_eq!(*state.get::<Reloadable<i32>>().unwrap().get(), 2);
+
+        let reloader = Reloader::new(vec![hook(|_| async { Err("unreadable".to_string()) })]);
+        assert_eq!(reloader.reload(&state).await, 1);
+        assert_eq!(*state.get::<Reloadable<i32>>().unwrap().get(), 2);
+    }
+}
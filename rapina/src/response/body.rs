@@ -0,0 +1,279 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use http::Response;
+use http::header::{self, HeaderValue};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body, Frame, SizeHint};
+
+use super::IntoResponse;
+
+/// Error of a streamed response body.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+type DynBody = Pin<Box<dyn Body<Data = Bytes, Error = BoxError> + Send>>;
+
+/// The body type used for HTTP responses.
+///
+/// Most responses hold their whole body, built from a complete value. A
+/// streamed body, such as a [`StreamBody`] or an [`Sse`](super::Sse)
+/// response, is sent chunk by chunk as it is produced instead.
+pub struct BoxBody {
+    inner: Inner,
+}
+
+enum Inner {
+    Full(Full<Bytes>),
+    Stream(DynBody),
+}
+
+impl BoxBody {
+    /// A body holding all of `data`.
+    pub fn new(data: impl Into<Bytes>) -> Self {
+        Self {
+            inner: Inner::Full(Full::new(data.into())),
+        }
+    }
+
+    /// A body with no content.
+    pub fn empty() -> Self {
+        Self::new(Bytes::new())
+    }
+
+    /// A body sending each frame of `body` as it is produced.
+    pub fn stream<B>(body: B) -> Self
+    where
+        B: Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        Self {
+            inner: Inner::Stream(Box::pin(body.map_err(Into::into))),
+        }
+    }
+
+    /// Whether the body is streamed rather than held in full.
+    ///
+    /// Middleware that reads the whole response body, to compress or cache
+    /// it, should pass streamed responses through untouched: the stream
+    /// may never end.
+    pub fn is_stream(&self) -> bool {
+        matches!(self.inner, Inner::Stream(_))
+    }
+
+    /// Ends a streamed body once `signal` resolves, so an endless stream
+    /// doesn't hold its connection open through a graceful shutdown.
+    pub(crate) fn until<F>(self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match self.inner {
+            Inner::Full(_) => self,
+            Inner::Stream(body) => Self::stream(Until {
+                body,
+                signal: Box::pin(signal),
+            }),
+        }
+    }
+}
+
+impl Default for BoxBody {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl fmt::Debug for BoxBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.inner {
+            Inner::Full(body) => f.debug_tuple("BoxBody").field(body).finish(),
+            Inner::Stream(_) => f.write_str("BoxBody(<stream>)"),
+        }
+    }
+}
+
+impl From<Full<Bytes>> for BoxBody {
+    fn from(body: Full<Bytes>) -> Self {
+        Self {
+            inner: Inner::Full(body),
+        }
+    }
+}
+
+impl From<Bytes> for BoxBody {
+    fn from(data: Bytes) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<Vec<u8>> for BoxBody {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<String> for BoxBody {
+    fn from(data: String) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<&'static str> for BoxBody {
+    fn from(data: &'static str) -> Self {
+        Self::new(data)
+    }
+}
+
+impl Body for BoxBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        match &mut self.get_mut().inner {
+            Inner::Full(body) => Pin::new(body)
+                .poll_frame(cx)
+                .map(|frame| frame.map(|frame| frame.map_err(|never| match never {}))),
+            Inner::Stream(body) => body.as_mut().poll_frame(cx),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.inner {
+            Inner::Full(body) => body.is_end_stream(),
+            Inner::Stream(body) => body.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.inner {
+            Inner::Full(body) => body.size_hint(),
+            Inner::Stream(body) => body.size_hint(),
+        }
+    }
+}
+
+/// A streamed body cut short by a signal.
+struct Until {
+    body: DynBody,
+    signal: Pin<Box<dyn Future<Output = ()> + Send>>,
+}
+
+impl Body for Until {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        if self.signal.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        self.body.as_mut().poll_frame(cx)
+    }
+}
+
+/// A response body read from a stream of chunks, such as a file being
+/// downloaded.
+///
+/// Each chunk is sent as soon as the stream yields it, without buffering
+/// the whole body. If the stream fails, the connection is closed and the
+/// client sees a truncated response. The response is sent as
+/// `application/octet-stream` unless another `Content-Type` is set.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::bytes::Bytes;
+/// use rapina::http::header::{CONTENT_TYPE, HeaderValue};
+/// use rapina::prelude::*;
+/// use rapina::response::{StreamBody, WithHeaders};
+///
+/// #[get("/reports/users.csv")]
+/// async fn users_csv() -> WithHeaders<StreamBody> {
+///     let rows = ["id,name\n", "1,Ada\n", "2,Grace\n"];
+///     let chunks = futures_util::stream::iter(
+///         rows.map(|row| Ok::<_, std::io::Error>(Bytes::from_static(row.as_bytes()))),
+///     );
+///     StreamBody::new(chunks).insert_header(CONTENT_TYPE, HeaderValue::from_static("text/csv"))
+/// }
+/// ```
+pub struct StreamBody {
+    body: DynBody,
+}
+
+impl StreamBody {
+    /// Streams the chunks of `stream` as the response body.
+    pub fn new<S, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        Self {
+            body: Box::pin(Chunks {
+                stream: Box::pin(stream),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for StreamBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StreamBody(<stream>)")
+    }
+}
+
+impl Body for StreamBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        self.body.as_mut().poll_frame(cx)
+    }
+}
+
+impl IntoResponse for StreamBody {
+    fn into_response(self) -> Response<BoxBody> {
+        let mut response = Response::new(BoxBody {
+            inner: Inner::Stream(self.body),
+        });
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+        response
+    }
+}
+
+/// Adapts a stream of byte chunks to a body of data frames.
+struct Chunks<S> {
+    stream: Pin<Box<S>>,
+}
+
+impl<S, E> Body for Chunks<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        self.stream
+            .as_mut()
+            .poll_next(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(Frame::data).map_err(Into::into)))
+    }
+}
@@ -0,0 +1,393 @@
+//! Response types and conversion traits.
+//!
+//! This module defines the [`IntoResponse`] trait which allows various types
+//! to be converted into HTTP responses, and the streamed bodies: [`Sse`] for
+//! Server-Sent Events and [`StreamBody`] for arbitrary chunks.
+
+mod body;
+mod sse;
+
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::{Response, StatusCode};
+
+pub use body::{BoxBody, BoxError, StreamBody};
+pub use sse::{Event, Sse};
+
+/// Trait for types that can be converted into an HTTP response.
+///
+/// Implement this trait to allow your type to be returned from handlers.
+/// Rapina provides implementations for common types like strings,
+/// status codes, and JSON.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::response::{BoxBody, IntoResponse};
+/// use http::Response;
+///
+/// struct MyResponse {
+///     message: String,
+/// }
+///
+/// impl IntoResponse for MyResponse {
+///     fn into_response(self) -> Response<BoxBody> {
+///         self.message.into_response()
+///     }
+/// }
+/// ```
+pub trait IntoResponse {
+    /// Converts this type into an HTTP response.
+    fn into_response(self) -> Response<BoxBody>;
+
+    /// Sets a header on the response, replacing any value it already has.
+    ///
+    /// `Set-Cookie` is the exception: every cookie is its own header line,
+    /// so inserting one adds to the cookies already set instead of
+    /// dropping them. See [`insert_header`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::http::header::{CACHE_CONTROL, HeaderValue};
+    /// use rapina::response::IntoResponse;
+    ///
+    /// let response = "hello"
+    ///     .insert_header(CACHE_CONTROL, HeaderValue::from_static("no-store"))
+    ///     .into_response();
+    /// assert_eq!(response.headers()[CACHE_CONTROL], "no-store");
+    /// ```
+    fn insert_header(self, name: HeaderName, value: HeaderValue) -> WithHeaders<Self>
+    where
+        Self: Sized,
+    {
+        WithHeaders::new(self).insert_header(name, value)
+    }
+
+    /// Adds a header line to the response, keeping the values it already has.
+    ///
+    /// Use this for headers that may appear more than once, such as
+    /// `Set-Cookie`. Each call is sent as a separate header line; values are
+    /// never joined with commas.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::http::header::{HeaderValue, SET_COOKIE};
+    /// use rapina::response::IntoResponse;
+    ///
+    /// let response = "signed in"
+    ///     .append_header(SET_COOKIE, HeaderValue::from_static("session=abc; HttpOnly"))
+    ///     .append_header(SET_COOKIE, HeaderValue::from_static("theme=dark"))
+    ///     .into_response();
+    /// assert_eq!(response.headers().get_all(SET_COOKIE).iter().count(), 2);
+    /// ```
+    fn append_header(self, name: HeaderName, value: HeaderValue) -> WithHeaders<Self>
+    where
+        Self: Sized,
+    {
+        WithHeaders::new(self).append_header(name, value)
+    }
+}
+
+/// A response with extra headers, created by
+/// [`IntoResponse::insert_header`] and [`IntoResponse::append_header`].
+///
+/// Headers are applied in the order they were added, after the inner
+/// response is built, so they can override headers such as `Content-Type`.
+#[derive(Debug)]
+pub struct WithHeaders<T> {
+    inner: T,
+    headers: Vec<(HeaderName, HeaderValue, bool)>,
+}
+
+impl<T> WithHeaders<T> {
+    fn new(inner: T) -> Self {
+        Self {
+            inner,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Sets a header, replacing earlier values. See [`IntoResponse::insert_header`].
+    pub fn insert_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.push((name, value, false));
+        self
+    }
+
+    /// Adds a header line, keeping earlier values. See [`IntoResponse::append_header`].
+    pub fn append_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.push((name, value, true));
+        self
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for WithHeaders<T> {
+    fn into_response(self) -> Response<BoxBody> {
+        let mut response = self.inner.into_response();
+        let headers = response.headers_mut();
+        for (name, value, append) in self.headers {
+            if append {
+                headers.append(name, value);
+            } else {
+                insert_header(headers, name, value);
+            }
+        }
+        response
+    }
+}
+
+/// Sets `name` to `value`, replacing existing values, except for `Set-Cookie`.
+///
+/// `Set-Cookie` values are appended instead: each cookie must reach the
+/// client as its own header line, and replacing them would silently drop
+/// cookies set by a handler. Middleware that writes response headers should
+/// use this instead of [`HeaderMap::insert`].
+pub fn insert_header(headers: &mut HeaderMap, name: HeaderName, value: HeaderValue) {
+    if name == header::SET_COOKIE {
+        headers.append(name, value);
+    } else {
+        headers.insert(name, value);
+    }
+}
+
+/// Adds `value` to the `Vary` header unless it's already listed.
+///
+/// Several middleware vary the response on different request headers
+/// (CORS on `Origin`, compression on `Accept-Encoding`); each one adds its
+/// own entry instead of replacing the others.
+pub fn add_vary(headers: &mut HeaderMap, value: &'static str) {
+    let listed = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|v| {
+            let v = v.trim();
+            v == "*" || v.eq_ignore_ascii_case(value)
+        });
+    if !listed {
+        headers.append(header::VARY, HeaderValue::from_static(value));
+    }
+}
+
+impl IntoResponse for Response<BoxBody> {
+    fn into_response(self) -> Response<BoxBody> {
+        self
+    }
+}
+
+impl IntoResponse for &str {
+    fn into_response(self) -> Response<BoxBody> {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "text/plain; charset=utf-8")
+            .body(BoxBody::new(self.to_owned()))
+            .unwrap()
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Response<BoxBody> {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "text/plain; charset=utf-8")
+            .body(BoxBody::new(self.to_owned()))
+            .unwrap()
+    }
+}
+
+impl IntoResponse for StatusCode {
+    fn into_response(self) -> Response<BoxBody> {
+        Response::builder()
+            .status(self)
+            .body(BoxBody::empty())
+            .unwrap()
+    }
+}
+
+impl IntoResponse for (StatusCode, String) {
+    fn into_response(self) -> Response<BoxBody> {
+        Response::builder()
+            .status(self.0)
+            .header("content-type", "text/plain; charset=utf-8")
+            .body(BoxBody::new(self.1))
+            .unwrap()
+    }
+}
+
+impl<T: IntoResponse, E: IntoResponse> IntoResponse for std::result::Result<T, E> {
+    fn into_response(self) -> Response<BoxBody> {
+        match self {
+            Ok(v) => v.into_response(),
+            Err(e) => e.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn test_str_into_response() {
+        let response = "hello".into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/plain; charset=utf-8"
+        );
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_string_into_response() {
+        let response = "world".to_string().into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/plain; charset=utf-8"
+        );
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"world");
+    }
+
+    #[tokio::test]
+    async fn test_status_code_into_response() {
+        let response = StatusCode::NOT_FOUND.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_status_code_ok() {
+        let response = StatusCode::OK.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_status_code_created() {
+        let response = StatusCode::CREATED.into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_status_code_no_content() {
+        let response = StatusCode::NO_CONTENT.into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_tuple_into_response() {
+        let response = (StatusCode::CREATED, "created".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/plain; charset=utf-8"
+        );
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"created");
+    }
+
+    #[tokio::test]
+    async fn test_tuple_with_error_status() {
+        let response = (StatusCode::BAD_REQUEST, "bad request".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_response_into_response_identity() {
+        let original = Response::builder()
+            .status(StatusCode::ACCEPTED)
+            .body(BoxBody::new("test"))
+            .unwrap();
+
+        let response = original.into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_result_ok_into_response() {
+        let result: std::result::Result<&str, StatusCode> = Ok("success");
+        let response = result.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"success");
+    }
+
+    #[test]
+    fn test_append_header_keeps_each_cookie() {
+        let response = "ok"
+            .append_header(header::SET_COOKIE, HeaderValue::from_static("a=1"))
+            .append_header(header::SET_COOKIE, HeaderValue::from_static("b=2"))
+            .into_response();
+
+        let cookies: Vec<_> = response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .collect();
+        assert_eq!(cookies, ["a=1", "b=2"]);
+    }
+
+    #[test]
+    fn test_insert_header_replaces() {
+        let response = "ok"
+            .insert_header(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"))
+            .into_response();
+
+        let values: Vec<_> = response
+            .headers()
+            .get_all(header::CONTENT_TYPE)
+            .iter()
+            .collect();
+        assert_eq!(values, ["text/csv"]);
+    }
+
+    #[test]
+    fn test_insert_header_never_replaces_cookies() {
+        let mut headers = HeaderMap::new();
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("a=1"));
+        insert_header(
+            &mut headers,
+            header::SET_COOKIE,
+            HeaderValue::from_static("b=2"),
+        );
+
+        assert_eq!(headers.get_all(header::SET_COOKIE).iter().count(), 2);
+    }
+
+    #[test]
+    fn test_add_vary_merges() {
+        let mut headers = HeaderMap::new();
+        add_vary(&mut headers, "Accept-Encoding");
+        add_vary(&mut headers, "Origin");
+        add_vary(&mut headers, "origin");
+
+        let values: Vec<_> = headers.get_all(header::VARY).iter().collect();
+        assert_eq!(values, ["Accept-Encoding", "Origin"]);
+    }
+
+    #[test]
+    fn test_add_vary_respects_wildcard() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::VARY, HeaderValue::from_static("*"));
+        add_vary(&mut headers, "Origin");
+
+        assert_eq!(headers.get_all(header::VARY).iter().count(), 1);
+    }
+
+    #[test]
+    fn test_result_err_into_response() {
+        let result: std::result::Result<&str, StatusCode> = Err(StatusCode::INTERNAL_SERVER_ERROR);
+        let response = result.into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,238 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_core::Stream;
+use http::Response;
+use http::header::{self, HeaderValue};
+use hyper::body::{Body, Frame};
+use serde::Serialize;
+
+use super::{BoxBody, IntoResponse};
+
+/// A Server-Sent Events response, sending each [`Event`] of a stream as
+/// soon as it is produced.
+///
+/// The response is sent as `text/event-stream` with `Cache-Control:
+/// no-cache`, and ends when the stream does. An endless stream is cut
+/// short when the server shuts down, so it doesn't hold up the graceful
+/// shutdown.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use rapina::prelude::*;
+/// use rapina::response::{Event, Sse};
+///
+/// #[get("/jobs/:id/progress")]
+/// async fn job_progress(id: Path<u64>) -> Sse {
+///     let id = id.into_inner();
+///     let events = futures_util::stream::iter((1..=3).map(move |step| {
+///         Event::new(format!("{}/3", step))
+///             .event("progress")
+///             .id(format!("{}-{}", id, step))
+///             .retry(Duration::from_secs(5))
+///     }));
+///     Sse::new(events)
+/// }
+/// ```
+pub struct Sse {
+    events: Pin<Box<dyn Stream<Item = Event> + Send>>,
+}
+
+impl Sse {
+    /// Streams `events` to the client.
+    pub fn new<S>(events: S) -> Self
+    where
+        S: Stream<Item = Event> + Send + 'static,
+    {
+        Self {
+            events: Box::pin(events),
+        }
+    }
+}
+
+impl fmt::Debug for Sse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sse(<stream>)")
+    }
+}
+
+impl IntoResponse for Sse {
+    fn into_response(self) -> Response<BoxBody> {
+        let mut response = Response::new(BoxBody::stream(self));
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/event-stream"),
+        );
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        response
+    }
+}
+
+impl Body for Sse {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        self.events
+            .as_mut()
+            .poll_next(cx)
+            .map(|event| event.map(|event| Ok(Frame::data(event.to_bytes()))))
+    }
+}
+
+/// A single Server-Sent Event.
+///
+/// Only `data` is required. `event` names the event type clients listen
+/// for (`message` when unset), `id` is echoed back by a reconnecting client
+/// in `Last-Event-ID`, and `retry` tells the client how long to wait before
+/// reconnecting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    /// Payload of the event; each line is sent as its own `data:` field.
+    pub data: String,
+    /// Event type, sent as the `event:` field.
+    pub event: Option<String>,
+    /// Event ID, sent as the `id:` field.
+    pub id: Option<String>,
+    /// Reconnection delay, sent in milliseconds as the `retry:` field.
+    pub retry: Option<Duration>,
+}
+
+impl Event {
+    /// An event carrying `data`.
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Self::default()
+        }
+    }
+
+    /// An event carrying `value` serialized as JSON.
+    pub fn json<T: Serialize>(value: &T) -> Result<Self, serde_json::Error> {
+        serde_json::to_string(value).map(Self::new)
+    }
+
+    /// Sets the event type.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the event ID.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the reconnection delay.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Encodes the event in the `text/event-stream` format.
+    ///
+    /// A line break would end the `event:` or `id:` field early, so line
+    /// breaks in them are dropped.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut out = String::with_capacity(self.data.len() + 16);
+        if let Some(event) = &self.event {
+            push_field(&mut out, "event", &single_line(event));
+        }
+        if let Some(id) = &self.id {
+            push_field(&mut out, "id", &single_line(id));
+        }
+        if let Some(retry) = self.retry {
+            push_field(&mut out, "retry", &retry.as_millis().to_string());
+        }
+        for line in self.data.replace("\r\n", "\n").split(['\n', '\r']) {
+            push_field(&mut out, "data", line);
+        }
+        out.push('\n');
+        Bytes::from(out)
+    }
+}
+
+fn push_field(out: &mut String, name: &str, value: &str) {
+    out.push_str(name);
+    out.push_str(": ");
+    out.push_str(value);
+    out.push('\n');
+}
+
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[test]
+    fn test_event_data_only() {
+        assert_eq!(Event::new("hello").to_bytes(), "data: hello\n\n");
+    }
+
+    #[test]
+    fn test_event_all_fields() {
+        let event = Event::new("50%")
+            .event("progress")
+            .id("7")
+            .retry(Duration::from_secs(3));
+
+        assert_eq!(
+            event.to_bytes(),
+            "event: progress\nid: 7\nretry: 3000\ndata: 50%\n\n"
+        );
+    }
+
+    #[test]
+    fn test_event_multiline_data() {
+        assert_eq!(
+            Event::new("one\ntwo\r\nthree").to_bytes(),
+            "data: one\ndata: two\ndata: three\n\n"
+        );
+    }
+
+    #[test]
+    fn test_event_line_breaks_dropped_from_fields() {
+        let event = Event::new("x").event("up\ndate").id("1\r\n2");
+
+        assert_eq!(event.to_bytes(), "event: update\nid: 12\ndata: x\n\n");
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = Event::json(&serde_json::json!({"step": 1})).unwrap();
+
+        assert_eq!(event.data, r#"{"step":1}"#);
+    }
+
+    #[tokio::test]
+    async fn test_sse_into_response() {
+        let events = futures_util::stream::iter([Event::new("a"), Event::new("b").id("2")]);
+        let response = Sse::new(events).into_response();
+
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        assert!(response.body().is_stream());
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "data: a\n\nid: 2\ndata: b\n\n");
+    }
+}
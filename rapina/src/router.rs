@@ -3,17 +3,26 @@
 //! The [`Router`] type collects route definitions and matches incoming
 //! requests to the appropriate handlers.
 
+use std::any::{Any, TypeId};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
-use http::{Method, Request, Response, StatusCode};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode, header};
 use hyper::body::Incoming;
+use tracing::{Instrument, info_span};
 
-use crate::error::ErrorVariant;
-use crate::extract::{PathParams, extract_path_params};
+use crate::context::{MatchedRoute, RequestContext};
+use crate::discovery::normalize_path;
+use crate::error::{Error, ErrorVariant};
+use crate::extract::{BodyLimit, PathParams, match_path};
 use crate::handler::Handler;
 use crate::introspection::RouteInfo;
+use crate::middleware::{
+    BodyLimitMiddleware, ConcurrencyLimit, ConcurrencyLimitMiddleware, ContentTypeGuard,
+    CorsConfig, DefaultHeaderValue, DefaultHeadersMiddleware, Middleware, Next, NoTimeout,
+    RouteBodyLimit, has_body, media_essence, media_matches,
+};
 use crate::response::{BoxBody, IntoResponse};
 use crate::state::AppState;
 
@@ -26,14 +35,114 @@ pub(crate) struct Route {
     pub(crate) handler_name: String,
     pub(crate) response_schema: Option<serde_json::Value>,
     pub(crate) error_responses: Vec<ErrorVariant>,
+    /// Feature flag the handler checks before running, for introspection.
+    pub(crate) feature_flag: Option<&'static str>,
+    /// Skipped by generated contract tests.
+    pub(crate) untestable: bool,
+    /// Schemas of the path parameters, for OpenAPI.
+    pub(crate) path_schemas: Vec<(&'static str, serde_json::Value)>,
+    /// Fields selectable with `?fields=`, for OpenAPI.
+    pub(crate) selectable_fields: Option<Vec<String>>,
+    /// Body media type this handler is picked for among overloads.
+    pub(crate) consumes: Option<&'static str>,
+    /// Response media type this handler is picked for among overloads.
+    pub(crate) produces: Option<&'static str>,
+    /// Request headers this handler is picked for among overloads.
+    pub(crate) header_conditions: Vec<(HeaderName, HeaderMatcher)>,
+    /// State types the handler extracts, checked at startup.
+    pub(crate) required_state: Vec<(TypeId, &'static str)>,
+    pub(crate) cors: Option<Arc<CorsConfig>>,
+    /// Content types the request body may have, checked before any route middleware.
+    pub(crate) content_types: Option<Arc<ContentTypeGuard>>,
+    /// Exempt from `TimeoutMiddleware`.
+    pub(crate) no_timeout: bool,
+    /// Body size limit replacing the `BodyLimitMiddleware` one.
+    pub(crate) body_limit: Option<usize>,
+    /// Middleware that only runs for this route, after the global stack.
+    pub(crate) middlewares: Vec<Arc<dyn Middleware>>,
     handler: HandlerFn,
 }
 
+impl Route {
+    /// Runs the extractors and handler inside a `handler` span, so events
+    /// logged from the handler carry its name, method and route pattern.
+    ///
+    /// Span names are static in `tracing`, so the handler name is also
+    /// recorded as `otel.name`, which OpenTelemetry exporters use as the
+    /// exported span's name.
+    pub(crate) async fn call(
+        &self,
+        req: Request<Incoming>,
+        params: PathParams,
+        state: Arc<AppState>,
+    ) -> Response<BoxBody> {
+        let span = info_span!(
+            "handler",
+            handler = %self.handler_name,
+            method = %req.method(),
+            route = %self.pattern,
+            otel.name = %self.handler_name,
+        );
+        (self.handler)(req, params, state).instrument(span).await
+    }
+
+    /// Whether the route carries a selector for picking among handlers that
+    /// share its method and path.
+    fn is_overload(&self) -> bool {
+        self.consumes.is_some() || self.produces.is_some() || !self.header_conditions.is_empty()
+    }
+
+    /// Whether `headers` satisfy every header condition of the route.
+    fn matches_headers(&self, headers: &HeaderMap) -> bool {
+        self.header_conditions.iter().all(|(name, matcher)| {
+            headers
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| matcher.matches(value))
+        })
+    }
+
+    /// The header conditions, described as `name: matcher`.
+    fn describe_header_conditions(&self) -> Vec<String> {
+        self.header_conditions
+            .iter()
+            .map(|(name, matcher)| format!("{}: {}", name, matcher.description))
+            .collect()
+    }
+
+    /// The `Accept` version the route is picked for, if any.
+    fn accept_version(&self) -> Option<u32> {
+        self.header_conditions
+            .iter()
+            .find(|(name, _)| name == header::ACCEPT)
+            .and_then(|(_, matcher)| matcher.version)
+    }
+
+    fn matched(&self) -> MatchedRoute {
+        MatchedRoute {
+            pattern: self.pattern.clone(),
+            name: self.handler_name.clone(),
+        }
+    }
+}
+
 /// The HTTP router for matching requests to handlers.
 ///
 /// Routes are matched in the order they are added. Use path parameters
 /// with the `:param` syntax.
 ///
+/// Router settings — [`middleware`](Self::middleware), [`cors`](Self::cors),
+/// [`content_types`](Self::content_types), [`no_timeout`](Self::no_timeout),
+/// [`body_limit`](Self::body_limit) and
+/// [`default_header`](Self::default_header) — cover every route of the
+/// router, including routes added after them, and are applied when the
+/// router is merged into another or the app starts. A route keeps what a
+/// more deeply nested router or [`at`](Self::at) already set, so the
+/// innermost setting wins. [`when_header`](Self::when_header) and
+/// [`concurrency_limit`](Self::concurrency_limit) configure only the route
+/// added right before them.
+///
 /// # Examples
 ///
 /// ```
@@ -55,12 +164,207 @@ pub(crate) struct Route {
 /// ```
 pub struct Router {
     pub(crate) routes: Vec<(Method, Route)>,
+    /// Percent-decode path parameters, and how to treat `%2F`.
+    decode_params: Option<EncodedSlash>,
+    /// Compare static segments without regard to ASCII case.
+    case_insensitive: bool,
+    /// Middleware for every route of this router, put in front of each
+    /// route's own when the router is merged or the app starts.
+    middlewares: Vec<Arc<dyn Middleware>>,
+    /// Limit of a `BodyLimitMiddleware` among `middlewares`.
+    middleware_body_limit: Option<usize>,
+    /// CORS policy of routes without their own.
+    cors: Option<Arc<CorsConfig>>,
+    /// Content-type guard of routes without their own.
+    content_types: Option<Arc<ContentTypeGuard>>,
+    /// Exempt every route from `TimeoutMiddleware`.
+    no_timeout: bool,
+    /// Body size limit of routes without their own.
+    body_limit: Option<usize>,
+    /// Response header defaults for every route.
+    default_headers: Option<DefaultHeadersMiddleware>,
+}
+
+/// A condition on a request header, for picking among handlers that share
+/// a method and path. See [`Router::when_header`].
+///
+/// # Examples
+///
+/// ```
+/// use rapina::router::HeaderMatcher;
+///
+/// assert!(HeaderMatcher::version(2).matches("application/vnd.myapp.v2+json"));
+/// assert!(HeaderMatcher::version(2).matches("application/json; version=2"));
+/// assert!(!HeaderMatcher::version(2).matches("application/vnd.myapp.v1+json"));
+/// assert!(HeaderMatcher::exact("beta").matches("BETA"));
+/// ```
+#[derive(Clone)]
+pub struct HeaderMatcher {
+    description: String,
+    /// Set for [`HeaderMatcher::version`].
+    version: Option<u32>,
+    test: Arc<dyn Fn(&str) -> bool + Send + Sync>,
+}
+
+impl HeaderMatcher {
+    /// Matches a value equal to `value`, ignoring ASCII case and surrounding
+    /// whitespace.
+    pub fn exact(value: impl Into<String>) -> Self {
+        let value: String = value.into();
+        let expected = value.trim().to_string();
+        Self {
+            description: value,
+            version: None,
+            test: Arc::new(move |actual| actual.trim().eq_ignore_ascii_case(&expected)),
+        }
+    }
+
+    /// Matches a media type list, like `Accept`, that asks for `version`:
+    /// as a `.v2` suffix of a vendor subtype, such as
+    /// `application/vnd.myapp.v2+json`, or as a `version=2` parameter.
+    /// Ranges with `q=0` don't count.
+    pub fn version(version: u32) -> Self {
+        Self {
+            description: format!("version {}", version),
+            version: Some(version),
+            test: Arc::new(move |actual| requested_versions(actual).contains(&version)),
+        }
+    }
+
+    /// Matches values `test` accepts. `description` names the condition in
+    /// errors and introspection.
+    pub fn custom(
+        description: impl Into<String>,
+        test: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            description: description.into(),
+            version: None,
+            test: Arc::new(test),
+        }
+    }
+
+    /// Whether a header `value` satisfies the condition.
+    pub fn matches(&self, value: &str) -> bool {
+        (self.test)(value)
+    }
+}
+
+impl From<&str> for HeaderMatcher {
+    fn from(value: &str) -> Self {
+        Self::exact(value)
+    }
+}
+
+impl std::fmt::Debug for HeaderMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeaderMatcher")
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The versions a media type list asks for, from `.vN` subtype suffixes
+/// and `version=N` parameters.
+fn requested_versions(value: &str) -> Vec<u32> {
+    let mut versions = Vec::new();
+    for range in value.split(',') {
+        let mut parts = range.split(';');
+        let essence = parts.next().unwrap_or_default().trim();
+        let mut from_param: Option<u32> = None;
+        let mut refused = false;
+        for param in parts {
+            let Some((key, value)) = param.split_once('=') else {
+                continue;
+            };
+            match key.trim().to_ascii_lowercase().as_str() {
+                "version" | "v" => from_param = value.trim().trim_matches('"').parse().ok(),
+                "q" => refused = value.trim().parse::<f32>().is_ok_and(|q| q <= 0.0),
+                _ => {}
+            }
+        }
+        if refused {
+            continue;
+        }
+        let from_subtype = essence
+            .split_once('/')
+            .map(|(_, subtype)| subtype.split('+').next().unwrap_or_default())
+            .and_then(|subtype| subtype.rsplit('.').next())
+            .and_then(|last| last.strip_prefix(['v', 'V']))
+            .and_then(|number| number.parse().ok());
+        versions.extend(from_param.or(from_subtype));
+    }
+    versions
+}
+
+/// What percent-decoding does with an encoded slash (`%2F`) in a path
+/// parameter.
+///
+/// A parameter always matches a single path segment, so an encoded slash
+/// never changes which route is chosen; the policy only decides what the
+/// handler sees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncodedSlash {
+    /// Decode `%2F` to `/` like any other escape.
+    #[default]
+    Decode,
+    /// Leave `%2F` encoded and decode everything else.
+    Keep,
+    /// Reject the request with 400 Bad Request.
+    Reject,
 }
 
 impl Router {
     /// Creates a new empty router.
     pub fn new() -> Self {
-        Self { routes: Vec::new() }
+        Self {
+            routes: Vec::new(),
+            decode_params: None,
+            case_insensitive: false,
+            middlewares: Vec::new(),
+            middleware_body_limit: None,
+            cors: None,
+            content_types: None,
+            no_timeout: false,
+            body_limit: None,
+            default_headers: None,
+        }
+    }
+
+    /// Percent-decodes path parameters before they reach extractors, so
+    /// `/files/report%202024.pdf` gives `:name` the value `report 2024.pdf`.
+    ///
+    /// `slash` decides what happens to `%2F`. A malformed escape or one that
+    /// decodes to invalid UTF-8 is answered with 400 Bad Request. Without
+    /// this, parameters are passed through exactly as they appear in the
+    /// path.
+    ///
+    /// Like [`Router::case_insensitive`], this applies to the whole router
+    /// and is read from the router given to [`Rapina::router`]; settings on
+    /// routers merged with [`Router::group`] are ignored.
+    ///
+    /// [`Rapina::router`]: crate::app::Rapina::router
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::router::EncodedSlash;
+    ///
+    /// let router = Router::new()
+    ///     .decode_params(EncodedSlash::Reject)
+    ///     .get_named("/files/:name", "get_file", |_, _, _| async { "file" });
+    /// ```
+    pub fn decode_params(mut self, slash: EncodedSlash) -> Self {
+        self.decode_params = Some(slash);
+        self
+    }
+
+    /// Matches static path segments without regard to ASCII case, so
+    /// `/Users/5` reaches `/users/:id`. Parameter values keep their case.
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
     }
 
     /// Adds a route with the given HTTP method, pattern, and handler name.
@@ -95,6 +399,19 @@ impl Router {
             handler_name: handler_name.to_string(),
             response_schema,
             error_responses,
+            feature_flag: None,
+            untestable: false,
+            path_schemas: Vec::new(),
+            selectable_fields: None,
+            consumes: None,
+            produces: None,
+            header_conditions: Vec::new(),
+            required_state: Vec::new(),
+            cors: None,
+            content_types: None,
+            no_timeout: false,
+            body_limit: None,
+            middlewares: Vec::new(),
             handler,
         };
 
@@ -133,186 +450,1210 @@ impl Router {
     }
 
     /// Adds a POST route with a handler name.
-    pub fn post_named<F, Fut, Out>(self, pattern: &str, handler_name: &str, handler: F) -> Self
+    pub fn post_named<F, Fut, Out>(self, pattern: &str, handler_name: &str, handler: F) -> Self
+    where
+        F: Fn(Request<Incoming>, PathParams, Arc<AppState>) -> Fut + Send + Sync + Clone + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        self.route_named(
+            Method::POST,
+            pattern,
+            handler_name,
+            None,
+            Vec::new(),
+            handler,
+        )
+    }
+
+    /// Adds a GET route with a Handler.
+    pub fn get<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.route_named(
+            Method::GET,
+            pattern,
+            H::NAME,
+            H::response_schema(),
+            H::error_responses(),
+            move |req, params, state| {
+                let h = handler.clone();
+                async move { h.call(req, params, state).await }
+            },
+        )
+        .with_handler_meta::<H>()
+    }
+
+    /// Adds a POST route with a Handler.
+    pub fn post<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.route_named(
+            Method::POST,
+            pattern,
+            H::NAME,
+            H::response_schema(),
+            H::error_responses(),
+            move |req, params, state| {
+                let h = handler.clone();
+                async move { h.call(req, params, state).await }
+            },
+        )
+        .with_handler_meta::<H>()
+    }
+
+    /// Adds a PUT route with a Handler.
+    pub fn put<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.route_named(
+            Method::PUT,
+            pattern,
+            H::NAME,
+            H::response_schema(),
+            H::error_responses(),
+            move |req, params, state| {
+                let h = handler.clone();
+                async move { h.call(req, params, state).await }
+            },
+        )
+        .with_handler_meta::<H>()
+    }
+
+    /// Adds a PATCH route with a Handler.
+    pub fn patch<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.route_named(
+            Method::PATCH,
+            pattern,
+            H::NAME,
+            H::response_schema(),
+            H::error_responses(),
+            move |req, params, state| {
+                let h = handler.clone();
+                async move { h.call(req, params, state).await }
+            },
+        )
+        .with_handler_meta::<H>()
+    }
+
+    /// Adds a DELETE route with a Handler.
+    pub fn delete<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.route_named(
+            Method::DELETE,
+            pattern,
+            H::NAME,
+            H::response_schema(),
+            H::error_responses(),
+            move |req, params, state| {
+                let h = handler.clone();
+                async move { h.call(req, params, state).await }
+            },
+        )
+        .with_handler_meta::<H>()
+    }
+
+    /// Registers several methods for one path without repeating it.
+    ///
+    /// The closure receives a [`MethodRouter`] for `pattern`. Middleware
+    /// and CORS policies set on it apply only to the methods it registers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// #[get("/users")]
+    /// async fn list_users() -> &'static str { "users" }
+    ///
+    /// #[post("/users")]
+    /// async fn create_user() -> StatusCode { StatusCode::CREATED }
+    ///
+    /// #[get("/users/:id")]
+    /// async fn get_user() -> &'static str { "user" }
+    ///
+    /// #[delete("/users/:id")]
+    /// async fn delete_user() -> StatusCode { StatusCode::NO_CONTENT }
+    ///
+    /// let router = Router::new()
+    ///     .at("/users", |r| r.get(list_users).post(create_user))
+    ///     .at("/users/:id", |r| r.get(get_user).delete(delete_user));
+    ///
+    /// assert_eq!(router.routes().len(), 4);
+    /// ```
+    pub fn at<F>(mut self, pattern: &str, build: F) -> Self
+    where
+        F: FnOnce(MethodRouter) -> MethodRouter,
+    {
+        let methods = build(MethodRouter::new(pattern));
+        self.routes.extend(methods.into_routes());
+        self
+    }
+
+    /// Returns metadata about all registered routes.
+    ///
+    /// This is useful for introspection, documentation generation,
+    /// and AI-native tooling integration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let router = Router::new()
+    ///     .get_named("/users", "list_users", |_, _, _| async { "users" })
+    ///     .post_named("/users", "create_user", |_, _, _| async { StatusCode::CREATED });
+    ///
+    /// let routes = router.routes();
+    /// assert_eq!(routes.len(), 2);
+    /// assert_eq!(routes[0].method, "GET");
+    /// assert_eq!(routes[0].path, "/users");
+    /// assert_eq!(routes[0].handler_name, "list_users");
+    /// ```
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        self.routes
+            .iter()
+            .map(|(method, route)| {
+                let info = RouteInfo::new(
+                    method.as_str(),
+                    &route.pattern,
+                    &route.handler_name,
+                    route.response_schema.clone(),
+                    route.error_responses.clone(),
+                );
+                let info = match route.feature_flag {
+                    Some(flag) => info.with_feature_flag(flag),
+                    None => info,
+                };
+                // Settings of a router not merged yet still show
+                let info = match route.content_types.as_ref().or(self.content_types.as_ref()) {
+                    Some(guard) => info.with_request_content_types(guard.allowed().to_vec()),
+                    None => info,
+                };
+                let info = if route.path_schemas.is_empty() {
+                    info
+                } else {
+                    info.with_path_schemas(
+                        route
+                            .path_schemas
+                            .iter()
+                            .map(|(name, schema)| (name.to_string(), schema.clone()))
+                            .collect(),
+                    )
+                };
+                let info = match &route.selectable_fields {
+                    Some(fields) => info.with_selectable_fields(fields.clone()),
+                    None => info,
+                };
+                let info = match route.consumes {
+                    Some(media_type) => info.with_consumes(media_type),
+                    None => info,
+                };
+                let info = match route.produces {
+                    Some(media_type) => info.with_produces(media_type),
+                    None => info,
+                };
+                let info = if route.header_conditions.is_empty() {
+                    info
+                } else {
+                    info.with_header_conditions(route.describe_header_conditions())
+                };
+                let info = match route.accept_version() {
+                    Some(version) => info.with_accept_version(version),
+                    None => info,
+                };
+                let middleware: Vec<String> = self
+                    .middlewares
+                    .iter()
+                    .chain(&route.middlewares)
+                    .map(|middleware| middleware.name().to_string())
+                    .collect();
+                let info = if middleware.is_empty() {
+                    info
+                } else {
+                    info.with_middleware(middleware)
+                };
+                if route.untestable {
+                    info.untestable()
+                } else {
+                    info
+                }
+            })
+            .collect()
+    }
+
+    /// Adds all routes from another router with a path prefix to compose a group of endpoints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let users_router = Router::new();
+    ///
+    /// let invoices_router = Router::new();
+    ///
+    /// let router = Router::new()
+    ///     .group("/api/users", users_router)
+    ///     .group("/api/invoices", invoices_router);
+    /// ```
+    pub fn group(mut self, prefix_pattern: &str, router: Router) -> Self {
+        if !prefix_pattern.starts_with("/") {
+            panic!("A group's prefix pattern must start with /");
+        }
+
+        self.extend_prefixed(prefix_pattern, router);
+        self
+    }
+
+    /// Mounts another router's routes under `prefix`.
+    ///
+    /// Paths are joined segment by segment, so the prefix may hold
+    /// parameters like `/orgs/:org_id`, and a child route at `/` or `""`
+    /// lands on the prefix itself. Nesting under `/` or `""` leaves the
+    /// paths as they are. Handler names and per-route settings such as
+    /// middleware and CORS policies carry over, and the merged routes are
+    /// sorted by specificity along with the rest when the app starts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a non-empty `prefix` doesn't start with `/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let users = Router::new()
+    ///     .get_named("/", "list_users", |_, _, _| async { "users" })
+    ///     .get_named("/:id", "get_user", |_, _, _| async { "user" });
+    ///
+    /// let router = Router::new().nest("/api/v1/users", users);
+    ///
+    /// let routes = router.routes();
+    /// assert_eq!(routes[0].path, "/api/v1/users");
+    /// assert_eq!(routes[1].path, "/api/v1/users/:id");
+    /// assert_eq!(routes[1].handler_name, "get_user");
+    /// ```
+    pub fn nest(mut self, prefix: &str, router: Router) -> Self {
+        if !prefix.is_empty() && !prefix.starts_with('/') {
+            panic!("A nested router's prefix must start with /");
+        }
+
+        self.extend_prefixed(prefix, router);
+        self
+    }
+
+    /// Builds a router in a closure and [`nest`](Self::nest)s it under
+    /// `prefix`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let router = Router::new().scope("/api", |api| {
+    ///     api.get_named("/health", "health", |_, _, _| async { "ok" })
+    ///         .scope("/orgs/:org_id", |org| {
+    ///             org.get_named("/members", "list_members", |_, _, _| async { "members" })
+    ///         })
+    /// });
+    ///
+    /// let routes = router.routes();
+    /// assert_eq!(routes[0].path, "/api/health");
+    /// assert_eq!(routes[1].path, "/api/orgs/:org_id/members");
+    /// ```
+    pub fn scope<F>(self, prefix: &str, build: F) -> Self
+    where
+        F: FnOnce(Router) -> Router,
+    {
+        self.nest(prefix, build(Router::new()))
+    }
+
+    fn extend_prefixed(&mut self, prefix: &str, mut router: Router) {
+        router.apply_settings();
+        for (method, mut route) in router.routes {
+            route.pattern = Self::join_group_route_pattern(prefix, &route.pattern);
+            self.routes.push((method, route));
+        }
+    }
+
+    /// Makes the route added last require a request header matching
+    /// `matcher`.
+    ///
+    /// Handlers sharing a method and path are then picked by their header
+    /// conditions, before their `consumes` and `produces` media types: a
+    /// route whose conditions all hold wins over one without conditions,
+    /// and when none can take the request it is answered with 406 listing
+    /// the supported values. `#[get("/users", accept_version = 2)]` is a
+    /// shorthand for `when_header("accept", HeaderMatcher::version(2))`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::router::HeaderMatcher;
+    ///
+    /// let router = Router::new()
+    ///     .get_named("/users", "list_users_v1", |_, _, _| async { "v1" })
+    ///     .when_header("accept", HeaderMatcher::version(1))
+    ///     .get_named("/users", "list_users_v2", |_, _, _| async { "v2" })
+    ///     .when_header("accept", HeaderMatcher::version(2));
+    ///
+    /// assert_eq!(router.routes()[1].accept_version, Some(2));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name, or if no route was
+    /// added yet.
+    pub fn when_header(mut self, name: &str, matcher: impl Into<HeaderMatcher>) -> Self {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .unwrap_or_else(|_| panic!("invalid header name: {:?}", name));
+        let (_, route) = self
+            .routes
+            .last_mut()
+            .expect("when_header() needs a route added before it");
+        route.header_conditions.push((name, matcher.into()));
+        self
+    }
+
+    /// Runs `middleware` for every route of this router, including routes
+    /// added after this call.
+    ///
+    /// Router middleware runs after the app's global middleware and before
+    /// the middleware of the route itself, in the order it was added. When
+    /// routers are nested, the outer router's middleware runs first, so a
+    /// group can require authentication while sibling routes outside it
+    /// stay open. A [`BodyLimitMiddleware`] here also replaces the app-wide
+    /// limit for these routes, like [`body_limit`](Self::body_limit).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::middleware::TimeoutMiddleware;
+    ///
+    /// let router = Router::new()
+    ///     .get_named("/health", "health", |_, _, _| async { "ok" })
+    ///     .scope("/admin", |admin| {
+    ///         admin
+    ///             .middleware(TimeoutMiddleware::default())
+    ///             .get_named("/stats", "stats", |_, _, _| async { "stats" })
+    ///     });
+    ///
+    /// let routes = router.routes();
+    /// assert!(routes[0].middleware.is_empty());
+    /// assert_eq!(routes[1].middleware, ["TimeoutMiddleware"]);
+    /// ```
+    pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
+        if let Some(limit) = (&middleware as &dyn Any).downcast_ref::<BodyLimitMiddleware>() {
+            self.middleware_body_limit.get_or_insert(limit.max_size);
+        }
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Puts the router's middleware in front of each route's own, and its
+    /// settings on the routes that don't have their own yet.
+    pub(crate) fn apply_settings(&mut self) {
+        let middlewares = std::mem::take(&mut self.middlewares);
+        let default_headers = self
+            .default_headers
+            .take()
+            .map(|headers| Arc::new(headers) as Arc<dyn Middleware>);
+        let body_limit = self.body_limit.take().or(self.middleware_body_limit.take());
+        let cors = self.cors.take();
+        let content_types = self.content_types.take();
+        let no_timeout = std::mem::take(&mut self.no_timeout);
+        for (_, route) in &mut self.routes {
+            route.middlewares.splice(0..0, middlewares.iter().cloned());
+            // First in the chain, so it sees the response last and nested
+            // routers' defaults are already in place
+            if let Some(default_headers) = &default_headers {
+                route.middlewares.insert(0, default_headers.clone());
+            }
+            if route.cors.is_none() {
+                route.cors = cors.clone();
+            }
+            if route.content_types.is_none() {
+                route.content_types = content_types.clone();
+            }
+            if let Some(max_size) = body_limit {
+                route.body_limit.get_or_insert(max_size);
+            }
+            route.no_timeout |= no_timeout;
+        }
+    }
+
+    /// Applies a CORS policy to every route of this router.
+    ///
+    /// Routes that already have a policy keep it, so the innermost router
+    /// wins when groups are nested. Routes without one fall back to the
+    /// config passed to [`Rapina::with_cors`](crate::app::Rapina::with_cors).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config allows credentials for any origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::middleware::CorsConfig;
+    ///
+    /// let public = Router::new()
+    ///     .get_named("/posts", "list_posts", |_, _, _| async { "posts" })
+    ///     .get_named("/tags", "list_tags", |_, _, _| async { "tags" })
+    ///     .cors(CorsConfig::permissive());
+    ///
+    /// let admin = Router::new()
+    ///     .get_named("/users", "list_users", |_, _, _| async { "users" })
+    ///     .post_named("/users", "create_user", |_, _, _| async { "created" })
+    ///     .cors(CorsConfig::with_origins(vec!["https://dash.example.com".to_string()]));
+    ///
+    /// let router = Router::new()
+    ///     .group("/public", public)
+    ///     .group("/admin", admin);
+    /// ```
+    pub fn cors(mut self, config: CorsConfig) -> Self {
+        config.assert_valid();
+        self.cors = Some(Arc::new(config));
+        self
+    }
+
+    /// Restricts the request body content types of every route of this
+    /// router.
+    ///
+    /// Requests with a body of another type, or without a `Content-Type`,
+    /// are answered with 415 before route middleware or the handler run.
+    /// Routes that already have a guard keep it, so the innermost router
+    /// wins when groups are nested, and any guard replaces the app-wide one
+    /// from [`Rapina::require_content_types`](crate::app::Rapina::require_content_types).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::middleware::ContentTypeGuard;
+    ///
+    /// let api = Router::new()
+    ///     .post_named("/users", "create_user", |_, _, _| async { StatusCode::CREATED })
+    ///     .content_types(ContentTypeGuard::json());
+    /// ```
+    pub fn content_types(mut self, guard: ContentTypeGuard) -> Self {
+        self.content_types = Some(Arc::new(guard));
+        self
+    }
+
+    /// Exempts every route of this router from
+    /// [`TimeoutMiddleware`](crate::middleware::TimeoutMiddleware), for
+    /// streaming or SSE endpoints that are meant to stay open.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let events = Router::new()
+    ///     .get_named("/events", "stream_events", |_, _, _| async { "events" })
+    ///     .no_timeout();
+    ///
+    /// let router = Router::new().group("/live", events);
+    /// ```
+    pub fn no_timeout(mut self) -> Self {
+        self.no_timeout = true;
+        self
+    }
+
+    /// Limits how many requests the route added last handles at once.
+    ///
+    /// Routes added before or after it are not limited. The limit runs
+    /// after the route's other middleware, right before the handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::middleware::ConcurrencyLimit;
+    ///
+    /// let router = Router::new()
+    ///     .get_named("/report", "render_report", |_, _, _| async { "pdf" })
+    ///     .concurrency_limit(2)
+    ///     .get_named("/export", "export_all", |_, _, _| async { "csv" })
+    ///     .concurrency_limit(ConcurrencyLimit::new(1).queue(5))
+    ///     .get_named("/health", "health", |_, _, _| async { "ok" });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the limit is zero, or if no route was added yet.
+    pub fn concurrency_limit(mut self, limit: impl Into<ConcurrencyLimit>) -> Self {
+        let (_, route) = self
+            .routes
+            .last_mut()
+            .expect("concurrency_limit() needs a route added before it");
+        route
+            .middlewares
+            .push(Arc::new(ConcurrencyLimitMiddleware::new(limit.into())));
+        self
+    }
+
+    /// Sets the request body size limit of every route of this router.
+    ///
+    /// It replaces the app-wide limit of
+    /// [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware) for
+    /// these routes, so an upload endpoint can take more than the rest of
+    /// the API, and is enforced by the extractors even when that middleware
+    /// is turned off. Routes that already have a limit keep it, so the
+    /// innermost router wins when groups are nested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let uploads = Router::new()
+    ///     .post_named("/avatars", "upload_avatar", |_, _, _| async { "ok" })
+    ///     .body_limit(10 * 1024 * 1024);
+    ///
+    /// let router = Router::new().nest("/uploads", uploads);
+    /// ```
+    pub fn body_limit(mut self, max_size: usize) -> Self {
+        self.body_limit = Some(max_size);
+        self
+    }
+
+    /// Adds a header to responses of every route of this router, unless
+    /// the handler already set it.
+    ///
+    /// Router defaults are applied before the app-wide ones from
+    /// [`Rapina::default_header`](crate::app::Rapina::default_header), so a
+    /// group's value wins over the app's, and the innermost router wins
+    /// when groups are nested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let admin = Router::new()
+    ///     .get_named("/users", "list_users", |_, _, _| async { "users" })
+    ///     .get_named("/users/:id", "get_user", |_, _, _| async { "user" })
+    ///     .default_header("cache-control", "no-store");
+    ///
+    /// let router = Router::new().group("/admin", admin);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name, or if a fixed value is
+    /// not a valid header value.
+    pub fn default_header(mut self, name: &str, value: impl Into<DefaultHeaderValue>) -> Self {
+        self.default_headers = Some(
+            self.default_headers
+                .take()
+                .unwrap_or_default()
+                .header(name, value),
+        );
+        self
+    }
+
+    /// The CORS policy of the route a `method` request for `path` would reach.
+    pub(crate) fn cors_for(&self, method: &Method, path: &str) -> Option<&CorsConfig> {
+        self.routes
+            .iter()
+            .find(|(route_method, route)| {
+                route_method == method
+                    && match_path(&route.pattern, path, self.case_insensitive).is_some()
+            })
+            .and_then(|(_, route)| route.cors.as_deref())
+    }
+
+    /// Whether any route carries its own CORS policy.
+    pub(crate) fn has_route_cors(&self) -> bool {
+        self.routes.iter().any(|(_, route)| route.cors.is_some())
+    }
+
+    /// Methods with a route matching `path`, in registration order, for the
+    /// `Allow` header.
+    pub(crate) fn allowed_methods(&self, path: &str) -> Vec<&Method> {
+        let mut methods: Vec<&Method> = Vec::new();
+        for (method, route) in &self.routes {
+            if !methods.contains(&method)
+                && match_path(&route.pattern, path, self.case_insensitive).is_some()
+            {
+                methods.push(method);
+            }
+        }
+        methods
+    }
+
+    /// Answers a request no route takes: 405 with an `Allow` header when
+    /// the path exists under other methods, 204 with the same header for
+    /// `OPTIONS`, and 404 otherwise.
+    fn no_route<B>(&self, req: &Request<B>) -> Response<BoxBody> {
+        let allowed = self.allowed_methods(req.uri().path());
+        if allowed.is_empty() {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+
+        let status = if req.method() == Method::OPTIONS {
+            StatusCode::NO_CONTENT
+        } else {
+            StatusCode::METHOD_NOT_ALLOWED
+        };
+        let allow: Vec<&str> = allowed.iter().map(|method| method.as_str()).collect();
+        let mut response = status.into_response();
+        if let Ok(value) = HeaderValue::from_str(&allow.join(", ")) {
+            response.headers_mut().insert(header::ALLOW, value);
+        }
+        response
+    }
+
+    /// Finds the route a `method` request for `path` is dispatched to.
+    pub(crate) fn resolve(&self, method: &Method, path: &str) -> Option<(&Route, PathParams)> {
+        self.routes
+            .iter()
+            .filter(|(route_method, _)| route_method == method)
+            .find_map(|(_, route)| {
+                match_path(&route.pattern, path, self.case_insensitive)
+                    .map(|params| (route, params))
+            })
+    }
+
+    /// Finds the route a request is dispatched to, choosing between
+    /// handlers that share its method and path by their header conditions
+    /// and their `consumes` and `produces` selectors.
+    ///
+    /// Fails with 406 when no handler's header conditions hold, with 415
+    /// when no handler consumes the body's `Content-Type`, and with 406
+    /// when none of those produces a type `Accept` allows.
+    pub(crate) fn select<B>(
+        &self,
+        req: &Request<B>,
+    ) -> Result<Option<(&Route, PathParams)>, Error> {
+        let path = req.uri().path();
+        let Some((first, params)) = self.resolve(req.method(), path) else {
+            return Ok(None);
+        };
+        if !first.is_overload()
+            && !self
+                .routes
+                .iter()
+                .any(|(method, route)| method == req.method() && route.is_overload())
+        {
+            return Ok(Some((first, params)));
+        }
+
+        // Overloads share the first match's path, whatever their parameter names
+        let shape = normalize_path(&first.pattern);
+        let overloads: Vec<(&Route, PathParams)> = self
+            .routes
+            .iter()
+            .filter(|(method, route)| {
+                method == req.method() && normalize_path(&route.pattern) == shape
+            })
+            .filter_map(|(_, route)| {
+                match_path(&route.pattern, path, self.case_insensitive)
+                    .map(|params| (route, params))
+            })
+            .collect();
+        if overloads.iter().all(|(route, _)| !route.is_overload()) {
+            return Ok(Some((first, params)));
+        }
+
+        let routes: Vec<&Route> = overloads.iter().map(|(route, _)| *route).collect();
+        let chosen = negotiate(&routes, req)?;
+        Ok(overloads
+            .into_iter()
+            .find(|(route, _)| std::ptr::eq(*route, chosen)))
+    }
+
+    /// Records the route a request resolves to in its context, and marks the
+    /// request with the route's markers, so middleware that runs before
+    /// dispatch can read them.
+    pub(crate) fn record_matched_route(&self, req: &mut Request<Incoming>, ctx: &RequestContext) {
+        let selected = self
+            .select(req)
+            .unwrap_or_else(|_| self.resolve(req.method(), req.uri().path()));
+        let Some((route, _)) = selected else {
+            return;
+        };
+        ctx.set_matched_route(route.matched());
+        // Global middleware runs before the route is known, so route
+        // markers go on the request here
+        if route.no_timeout {
+            req.extensions_mut().insert(NoTimeout);
+        }
+        if let Some(max_size) = route.body_limit {
+            req.extensions_mut().insert(RouteBodyLimit(max_size));
+            req.extensions_mut().insert(BodyLimit(max_size));
+        }
+    }
+
+    /// Handles an incoming request by matching it to a route.
+    pub async fn handle(&self, req: Request<Incoming>, state: &Arc<AppState>) -> Response<BoxBody> {
+        let (route, mut params) = match self.select(&req) {
+            Ok(Some(found)) => found,
+            Ok(None) => return self.no_route(&req),
+            Err(error) => return error.into_response(),
+        };
+
+        if let Some(slash) = self.decode_params {
+            for value in params.values_mut() {
+                match percent_decode(value, slash) {
+                    Ok(decoded) => *value = decoded,
+                    Err(error) => return error.into_response(),
+                }
+            }
+        }
+
+        let ctx = req.extensions().get::<RequestContext>().cloned();
+        if let Some(ctx) = &ctx {
+            ctx.set_matched_route(route.matched());
+        }
+
+        if let Some(guard) = &route.content_types
+            && let Err(error) = guard.check(&req)
+        {
+            return error.into_response();
+        }
+
+        if route.middlewares.is_empty() {
+            return route.call(req, params, state.clone()).await;
+        }
+
+        let ctx = ctx.unwrap_or_default();
+        Next::for_route(self, route, params, state, &ctx)
+            .run(req)
+            .await
+    }
+
+    /// Records the feature flag, contract-test opt-out, path parameter
+    /// schemas, selectable fields, media type and version selectors and
+    /// required state of the route added last.
+    fn with_handler_meta<H: Handler>(mut self) -> Self {
+        if let Some((_, route)) = self.routes.last_mut() {
+            route.feature_flag = H::feature_flag();
+            route.untestable = H::untestable();
+            route.path_schemas = H::path_schemas();
+            route.selectable_fields = H::selectable_fields();
+            route.consumes = H::consumes();
+            route.produces = H::produces();
+            route.required_state = H::required_state();
+            if let Some(version) = H::accept_version() {
+                route
+                    .header_conditions
+                    .push((header::ACCEPT, HeaderMatcher::version(version)));
+            }
+        }
+        self
+    }
+
+    /// Describes every `State` type a handler extracts that `state` doesn't
+    /// have, which would fail each request to the route with a 500.
+    pub(crate) fn state_warnings(&self, state: &AppState) -> Vec<String> {
+        self.routes
+            .iter()
+            .flat_map(|(method, route)| {
+                route
+                    .required_state
+                    .iter()
+                    .filter(|(type_id, _)| !state.contains_type(*type_id))
+                    .map(move |(_, type_name)| {
+                        format!(
+                            "Handler '{}' ({} {}) extracts state of type '{}', which was never registered. Did you forget to call .state()?",
+                            route.handler_name, method, route.pattern, type_name
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Sorts routes so static segments come before parameterized ones.
+    ///
+    /// This ensures `/users/current` is matched before `/users/:id` regardless
+    /// of registration order. Uses a stable sort so routes with identical
+    /// specificity keep their original order.
+    pub(crate) fn sort_routes(&mut self) {
+        self.routes.sort_by(|(_, a), (_, b)| {
+            route_specificity(&a.pattern).cmp(&route_specificity(&b.pattern))
+        });
+    }
+
+    fn join_group_route_pattern(prefix: &str, route_path: &str) -> String {
+        let prefix = prefix.trim_end_matches('/');
+        let route_path = route_path.trim_start_matches('/');
+
+        if prefix.is_empty() {
+            format!("/{}", route_path)
+        } else if route_path.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{}/{}", prefix, route_path)
+        }
+    }
+}
+
+/// Picks among routes sharing a method and path: first by the body's
+/// `Content-Type`, then by the highest `Accept` quality. A route without a
+/// selector accepts anything but loses to one that matches explicitly, and
+/// ties go to the route registered first.
+fn negotiate<'r, B>(overloads: &[&'r Route], req: &Request<B>) -> Result<&'r Route, Error> {
+    let headers = req.headers();
+    let overloads = &match_header_conditions(overloads, headers)?;
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap_or_default());
+
+    let consuming: Vec<&Route> = if has_body(req) {
+        let essence = content_type.map(media_essence);
+        overloads
+            .iter()
+            .copied()
+            .filter(|route| match (route.consumes, &essence) {
+                (None, _) => true,
+                (Some(consumes), Some(essence)) => {
+                    media_matches(&consumes.to_ascii_lowercase(), essence)
+                }
+                (Some(_), None) => false,
+            })
+            .collect()
+    } else {
+        overloads.to_vec()
+    };
+
+    if consuming.is_empty() {
+        let allowed = distinct(overloads.iter().filter_map(|route| route.consumes));
+        let message = match content_type {
+            Some(content_type) => format!(
+                "unsupported content type '{}'; expected {}",
+                content_type,
+                allowed.join(" or ")
+            ),
+            None => format!("missing Content-Type; expected {}", allowed.join(" or ")),
+        };
+        return Err(Error::unsupported_media_type(message)
+            .with_details(serde_json::json!({ "allowed": allowed })));
+    }
+
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok());
+    let mut best: Option<(&Route, f32)> = None;
+    for route in &consuming {
+        let quality = match (route.produces, accept) {
+            (None, _) => 0.0,
+            (Some(_), None) => 1.0,
+            (Some(produces), Some(accept)) => {
+                match accept_quality(accept, &produces.to_ascii_lowercase()) {
+                    q if q > 0.0 => q,
+                    _ => continue,
+                }
+            }
+        };
+        if best.is_none_or(|(_, best_quality)| quality > best_quality) {
+            best = Some((route, quality));
+        }
+    }
+
+    best.map(|(route, _)| route).ok_or_else(|| {
+        let allowed = distinct(consuming.iter().filter_map(|route| route.produces));
+        Error::not_acceptable(format!(
+            "none of the accepted types are available; expected {}",
+            allowed.join(" or ")
+        ))
+        .with_details(serde_json::json!({ "allowed": allowed }))
+    })
+}
+
+/// Keeps the overloads whose header conditions hold, or the ones without
+/// conditions when no conditioned overload matches.
+fn match_header_conditions<'r>(
+    overloads: &[&'r Route],
+    headers: &HeaderMap,
+) -> Result<Vec<&'r Route>, Error> {
+    let (mut conditioned, plain): (Vec<&Route>, Vec<&Route>) = overloads
+        .iter()
+        .partition(|route| !route.header_conditions.is_empty());
+    if conditioned.is_empty() {
+        return Ok(plain);
+    }
+
+    let matching: Vec<&Route> = conditioned
+        .iter()
+        .copied()
+        .filter(|route| route.matches_headers(headers))
+        .collect();
+    if !matching.is_empty() {
+        return Ok(matching);
+    }
+    if !plain.is_empty() {
+        return Ok(plain);
+    }
+
+    // Discovered routes come in no particular order
+    conditioned.sort_by_key(|route| route.accept_version());
+    let mut versions: Vec<u32> = conditioned
+        .iter()
+        .filter_map(|route| route.accept_version())
+        .collect();
+    versions.dedup();
+    let described: Vec<String> = conditioned
+        .iter()
+        .map(|route| route.describe_header_conditions().join(", "))
+        .collect();
+    let supported = distinct(described.iter().map(String::as_str));
+    let mut details = serde_json::json!({ "supported": supported });
+    if !versions.is_empty() {
+        details["versions"] = serde_json::json!(versions);
+    }
+    Err(Error::not_acceptable(format!(
+        "no handler accepts the request headers; supported: {}",
+        supported.join(" or ")
+    ))
+    .with_details(details))
+}
+
+/// The quality an `Accept` header gives `media_type`, taken from the most
+/// specific range that covers it, or 0 when none does.
+fn accept_quality(accept: &str, media_type: &str) -> f32 {
+    let mut best: Option<(u8, f32)> = None;
+    for range in accept.split(',') {
+        let essence = media_essence(range);
+        if !media_matches(&essence, media_type) {
+            continue;
+        }
+        let specificity = if essence == "*/*" {
+            0
+        } else if essence.ends_with("/*") {
+            1
+        } else {
+            2
+        };
+        let quality = range
+            .split(';')
+            .skip(1)
+            .find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                (name.trim().eq_ignore_ascii_case("q")).then(|| value.trim().parse::<f32>().ok())?
+            })
+            .unwrap_or(1.0);
+        if best.is_none_or(|(best_specificity, _)| specificity > best_specificity) {
+            best = Some((specificity, quality));
+        }
+    }
+    best.map_or(0.0, |(_, quality)| quality)
+}
+
+/// The values of `iter`, without repeats, in order of first appearance.
+fn distinct<'a>(iter: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut values = Vec::new();
+    for value in iter {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+    values
+}
+
+/// The methods registered for a single path.
+///
+/// Created by [`Router::at`]. Each method shares the path, and any
+/// middleware or CORS policy added here applies to all of them, but not to
+/// other paths.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::prelude::*;
+/// use rapina::middleware::{CorsConfig, TimeoutMiddleware};
+///
+/// let router = Router::new().at("/reports", |r| {
+///     r.get_named("list_reports", |_, _, _| async { "reports" })
+///         .post_named("create_report", |_, _, _| async { StatusCode::CREATED })
+///         .middleware(TimeoutMiddleware::default())
+///         .cors(CorsConfig::permissive())
+/// });
+/// ```
+pub struct MethodRouter {
+    pattern: String,
+    router: Router,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    cors: Option<CorsConfig>,
+    content_types: Option<ContentTypeGuard>,
+    no_timeout: bool,
+    concurrency_limit: Option<ConcurrencyLimit>,
+    body_limit: Option<usize>,
+}
+
+impl MethodRouter {
+    fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            router: Router::new(),
+            middlewares: Vec::new(),
+            cors: None,
+            content_types: None,
+            no_timeout: false,
+            concurrency_limit: None,
+            body_limit: None,
+        }
+    }
+
+    /// Adds a route for `method` with a handler name.
+    pub fn route_named<F, Fut, Out>(
+        mut self,
+        method: Method,
+        handler_name: &str,
+        response_schema: Option<serde_json::Value>,
+        error_responses: Vec<ErrorVariant>,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Request<Incoming>, PathParams, Arc<AppState>) -> Fut + Send + Sync + Clone + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        self.router = self.router.route_named(
+            method,
+            &self.pattern,
+            handler_name,
+            response_schema,
+            error_responses,
+            handler,
+        );
+        self
+    }
+
+    /// Adds a route for `method`.
+    pub fn route<F, Fut, Out>(mut self, method: Method, handler: F) -> Self
+    where
+        F: Fn(Request<Incoming>, PathParams, Arc<AppState>) -> Fut + Send + Sync + Clone + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        self.router = self.router.route(method, &self.pattern, handler);
+        self
+    }
+
+    /// Adds a GET route with a handler name.
+    pub fn get_named<F, Fut, Out>(mut self, handler_name: &str, handler: F) -> Self
+    where
+        F: Fn(Request<Incoming>, PathParams, Arc<AppState>) -> Fut + Send + Sync + Clone + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        self.router = self.router.get_named(&self.pattern, handler_name, handler);
+        self
+    }
+
+    /// Adds a POST route with a handler name.
+    pub fn post_named<F, Fut, Out>(mut self, handler_name: &str, handler: F) -> Self
     where
         F: Fn(Request<Incoming>, PathParams, Arc<AppState>) -> Fut + Send + Sync + Clone + 'static,
         Fut: Future<Output = Out> + Send + 'static,
         Out: IntoResponse + 'static,
     {
-        self.route_named(
-            Method::POST,
-            pattern,
-            handler_name,
-            None,
-            Vec::new(),
-            handler,
-        )
+        self.router = self.router.post_named(&self.pattern, handler_name, handler);
+        self
     }
 
     /// Adds a GET route with a Handler.
-    pub fn get<H: Handler>(self, pattern: &str, handler: H) -> Self {
-        self.route_named(
-            Method::GET,
-            pattern,
-            H::NAME,
-            H::response_schema(),
-            H::error_responses(),
-            move |req, params, state| {
-                let h = handler.clone();
-                async move { h.call(req, params, state).await }
-            },
-        )
+    pub fn get<H: Handler>(mut self, handler: H) -> Self {
+        self.router = self.router.get(&self.pattern, handler);
+        self
     }
 
     /// Adds a POST route with a Handler.
-    pub fn post<H: Handler>(self, pattern: &str, handler: H) -> Self {
-        self.route_named(
-            Method::POST,
-            pattern,
-            H::NAME,
-            H::response_schema(),
-            H::error_responses(),
-            move |req, params, state| {
-                let h = handler.clone();
-                async move { h.call(req, params, state).await }
-            },
-        )
+    pub fn post<H: Handler>(mut self, handler: H) -> Self {
+        self.router = self.router.post(&self.pattern, handler);
+        self
     }
 
     /// Adds a PUT route with a Handler.
-    pub fn put<H: Handler>(self, pattern: &str, handler: H) -> Self {
-        self.route_named(
-            Method::PUT,
-            pattern,
-            H::NAME,
-            H::response_schema(),
-            H::error_responses(),
-            move |req, params, state| {
-                let h = handler.clone();
-                async move { h.call(req, params, state).await }
-            },
-        )
+    pub fn put<H: Handler>(mut self, handler: H) -> Self {
+        self.router = self.router.put(&self.pattern, handler);
+        self
+    }
+
+    /// Adds a PATCH route with a Handler.
+    pub fn patch<H: Handler>(mut self, handler: H) -> Self {
+        self.router = self.router.patch(&self.pattern, handler);
+        self
     }
 
     /// Adds a DELETE route with a Handler.
-    pub fn delete<H: Handler>(self, pattern: &str, handler: H) -> Self {
-        self.route_named(
-            Method::DELETE,
-            pattern,
-            H::NAME,
-            H::response_schema(),
-            H::error_responses(),
-            move |req, params, state| {
-                let h = handler.clone();
-                async move { h.call(req, params, state).await }
-            },
-        )
+    pub fn delete<H: Handler>(mut self, handler: H) -> Self {
+        self.router = self.router.delete(&self.pattern, handler);
+        self
     }
 
-    /// Returns metadata about all registered routes.
-    ///
-    /// This is useful for introspection, documentation generation,
-    /// and AI-native tooling integration.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use rapina::prelude::*;
-    ///
-    /// let router = Router::new()
-    ///     .get_named("/users", "list_users", |_, _, _| async { "users" })
-    ///     .post_named("/users", "create_user", |_, _, _| async { StatusCode::CREATED });
+    /// Runs `middleware` for every method on this path.
     ///
-    /// let routes = router.routes();
-    /// assert_eq!(routes.len(), 2);
-    /// assert_eq!(routes[0].method, "GET");
-    /// assert_eq!(routes[0].path, "/users");
-    /// assert_eq!(routes[0].handler_name, "list_users");
-    /// ```
-    pub fn routes(&self) -> Vec<RouteInfo> {
-        self.routes
-            .iter()
-            .map(|(method, route)| {
-                RouteInfo::new(
-                    method.as_str(),
-                    &route.pattern,
-                    &route.handler_name,
-                    route.response_schema.clone(),
-                    route.error_responses.clone(),
-                )
-            })
-            .collect()
+    /// Path middleware runs after the app's global middleware and the
+    /// middleware of the routers it is in, in the order it was added. A [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware)
+    /// here also replaces the app-wide limit, like [`body_limit`](Self::body_limit).
+    pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
+        if let Some(limit) = (&middleware as &dyn Any).downcast_ref::<BodyLimitMiddleware>() {
+            self.body_limit.get_or_insert(limit.max_size);
+        }
+        self.middlewares.push(Arc::new(middleware));
+        self
     }
 
-    /// Adds all routes from another router with a path prefix to compose a group of endpoints.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use rapina::prelude::*;
+    /// Applies a CORS policy to every method on this path.
     ///
-    /// let users_router = Router::new();
-    ///
-    /// let invoices_router = Router::new();
+    /// # Panics
     ///
-    /// let router = Router::new()
-    ///     .group("/api/users", users_router)
-    ///     .group("/api/invoices", invoices_router);
-    /// ```
-    pub fn group(mut self, prefix_pattern: &str, router: Router) -> Self {
-        if !prefix_pattern.starts_with("/") {
-            panic!("A group's prefix pattern must start with /");
-        }
-
-        for (method, mut route) in router.routes {
-            let joined_route_path = Self::join_group_route_pattern(prefix_pattern, &route.pattern);
-            route.pattern = joined_route_path;
-            self.routes.push((method, route));
-        }
-
+    /// Panics if the config allows credentials for any origin.
+    pub fn cors(mut self, config: CorsConfig) -> Self {
+        config.assert_valid();
+        self.cors = Some(config);
         self
     }
 
-    /// Handles an incoming request by matching it to a route.
-    pub async fn handle(&self, req: Request<Incoming>, state: &Arc<AppState>) -> Response<BoxBody> {
-        let method = req.method().clone();
-        let path = req.uri().path().to_string();
-
-        for (route_method, route) in &self.routes {
-            if *route_method != method {
-                continue;
-            }
-
-            if let Some(params) = extract_path_params(&route.pattern, &path) {
-                return (route.handler)(req, params, state.clone()).await;
-            }
-        }
+    /// Restricts the request body content types of every method on this path.
+    pub fn content_types(mut self, guard: ContentTypeGuard) -> Self {
+        self.content_types = Some(guard);
+        self
+    }
 
-        StatusCode::NOT_FOUND.into_response()
+    /// Exempts every method on this path from
+    /// [`TimeoutMiddleware`](crate::middleware::TimeoutMiddleware).
+    pub fn no_timeout(mut self) -> Self {
+        self.no_timeout = true;
+        self
     }
 
-    /// Sorts routes so static segments come before parameterized ones.
+    /// Limits how many requests each method on this path handles at once.
     ///
-    /// This ensures `/users/current` is matched before `/users/:id` regardless
-    /// of registration order. Uses a stable sort so routes with identical
-    /// specificity keep their original order.
-    pub(crate) fn sort_routes(&mut self) {
-        self.routes.sort_by(|(_, a), (_, b)| {
-            route_specificity(&a.pattern).cmp(&route_specificity(&b.pattern))
-        });
+    /// # Panics
+    ///
+    /// Panics if the limit is zero.
+    pub fn concurrency_limit(mut self, limit: impl Into<ConcurrencyLimit>) -> Self {
+        self.concurrency_limit = Some(limit.into());
+        self
     }
 
-    fn join_group_route_pattern(prefix: &str, route_path: &str) -> String {
-        let prefix = prefix.trim_end_matches('/');
-        let route_path = route_path.trim_start_matches('/');
+    /// Sets the request body size limit of every method on this path.
+    pub fn body_limit(mut self, max_size: usize) -> Self {
+        self.body_limit = Some(max_size);
+        self
+    }
 
-        if prefix.is_empty() {
-            format!("/{}", route_path)
-        } else if route_path.is_empty() {
-            prefix.to_string()
-        } else {
-            format!("{}/{}", prefix, route_path)
+    fn into_routes(self) -> Vec<(Method, Route)> {
+        let mut routes = self.router.routes;
+        let cors = self.cors.map(Arc::new);
+        let content_types = self.content_types.map(Arc::new);
+        for (_, route) in &mut routes {
+            route.cors = cors.clone();
+            route.content_types = content_types.clone();
+            route.no_timeout = self.no_timeout;
+            route.body_limit = self.body_limit;
+            route.middlewares.extend(self.middlewares.iter().cloned());
+            // Each method gets its own limit
+            if let Some(limit) = &self.concurrency_limit {
+                route
+                    .middlewares
+                    .push(Arc::new(ConcurrencyLimitMiddleware::new(limit.clone())));
+            }
         }
+        routes
     }
 }
 
@@ -328,6 +1669,55 @@ fn route_specificity(pattern: &str) -> Vec<u8> {
         .collect()
 }
 
+/// Percent-decodes a path parameter, applying `slash` to `%2F`.
+fn percent_decode(value: &str, slash: EncodedSlash) -> Result<String, Error> {
+    if !value.contains('%') {
+        return Ok(value.to_string());
+    }
+
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            decoded.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+
+        let escape = &bytes[i..(i + 3).min(bytes.len())];
+        let byte = bytes
+            .get(i + 1..i + 3)
+            // `from_str_radix` alone would also take a sign, as in `%+1`
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| {
+                Error::bad_request(format!(
+                    "malformed percent-encoding '{}' in path",
+                    String::from_utf8_lossy(escape)
+                ))
+            })?;
+        if byte == b'/' {
+            match slash {
+                EncodedSlash::Decode => decoded.push(b'/'),
+                EncodedSlash::Keep => decoded.extend_from_slice(escape),
+                EncodedSlash::Reject => {
+                    return Err(Error::bad_request(
+                        "encoded slash '%2F' is not allowed in path parameters",
+                    ));
+                }
+            }
+        } else {
+            decoded.push(byte);
+        }
+        i += 3;
+    }
+
+    String::from_utf8(decoded)
+        .map_err(|_| Error::bad_request("path parameter is not valid UTF-8 once decoded"))
+}
+
 impl Default for Router {
     fn default() -> Self {
         Self::new()
@@ -555,6 +1945,146 @@ mod tests {
         Router::new().group("api/users", Router::new());
     }
 
+    #[test]
+    fn test_router_body_limit_innermost_wins() {
+        let uploads = Router::new()
+            .post_named("/avatars", "upload_avatar", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .body_limit(4096);
+
+        let mut router = Router::new()
+            .nest("/uploads", uploads)
+            .post_named("/notes", "create_note", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .body_limit(64);
+        router.apply_settings();
+
+        let limits: Vec<_> = router
+            .routes
+            .iter()
+            .map(|(_, route)| route.body_limit)
+            .collect();
+        assert_eq!(limits, vec![Some(4096), Some(64)]);
+    }
+
+    #[test]
+    fn test_router_settings_cover_every_route() {
+        let uploads = Router::new()
+            .post_named("/avatars", "upload_avatar", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .cors(CorsConfig::permissive())
+            .content_types(ContentTypeGuard::new(["image/png"]))
+            .no_timeout()
+            .body_limit(4096)
+            .default_header("cache-control", "no-store")
+            // Added after the settings, and still covered by them
+            .post_named("/banners", "upload_banner", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let mut router = Router::new()
+            .post_named("/notes", "create_note", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .group("/uploads", uploads)
+            .cors(CorsConfig::with_origins(vec![
+                "https://app.example.com".to_string(),
+            ]))
+            .content_types(ContentTypeGuard::json());
+        router.apply_settings();
+
+        let (_, notes) = &router.routes[0];
+        assert!(notes.cors.is_some());
+        assert_eq!(
+            notes.content_types.as_ref().unwrap().allowed(),
+            ["application/json"]
+        );
+        assert!(!notes.no_timeout);
+        assert!(notes.body_limit.is_none());
+        assert!(notes.middlewares.is_empty());
+
+        for (_, upload) in &router.routes[1..] {
+            assert!(Arc::ptr_eq(
+                upload.cors.as_ref().unwrap(),
+                router.routes[1].1.cors.as_ref().unwrap()
+            ));
+            assert!(!Arc::ptr_eq(
+                upload.cors.as_ref().unwrap(),
+                notes.cors.as_ref().unwrap()
+            ));
+            assert_eq!(
+                upload.content_types.as_ref().unwrap().allowed(),
+                ["image/png"]
+            );
+            assert!(upload.no_timeout);
+            assert_eq!(upload.body_limit, Some(4096));
+            assert_eq!(upload.middlewares.len(), 1);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "when_header() needs a route added before it")]
+    fn test_when_header_without_route() {
+        let _ = Router::new().when_header("accept", HeaderMatcher::version(1));
+    }
+
+    #[test]
+    fn test_router_nest_rewrites_paths() {
+        let members = Router::new()
+            .get_named("", "list_members", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .get_named("/:id", "get_member", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let router = Router::new()
+            .nest("/orgs/:org_id/members/", members)
+            .scope("/api", |api| {
+                api.scope("/v1", |v1| {
+                    v1.get_named("/", "index", |_req, _params, _state| async {
+                        StatusCode::OK
+                    })
+                })
+            });
+
+        let routes = router.routes();
+        assert_eq!(routes[0].path, "/orgs/:org_id/members");
+        assert_eq!(routes[0].handler_name, "list_members");
+        assert_eq!(routes[1].path, "/orgs/:org_id/members/:id");
+        assert_eq!(routes[1].handler_name, "get_member");
+        assert_eq!(routes[2].path, "/api/v1");
+        assert_eq!(routes[2].handler_name, "index");
+    }
+
+    #[test]
+    fn test_router_nest_under_root_is_noop() {
+        let users = || {
+            Router::new()
+                .get_named("/users", "list_users", |_req, _params, _state| async {
+                    StatusCode::OK
+                })
+                .get_named("/", "index", |_req, _params, _state| async {
+                    StatusCode::OK
+                })
+        };
+
+        for prefix in ["/", ""] {
+            let routes = Router::new().nest(prefix, users()).routes();
+            assert_eq!(routes[0].path, "/users");
+            assert_eq!(routes[1].path, "/");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "A nested router's prefix must start with /")]
+    fn test_invalid_router_nest_prefix() {
+        Router::new().nest("api", Router::new());
+    }
+
     #[test]
     fn test_route_specificity() {
         assert_eq!(super::route_specificity("/users/current"), vec![0, 0, 0]);
@@ -569,6 +2099,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_percent_decode() {
+        let decode = |value| percent_decode(value, EncodedSlash::Decode);
+        assert_eq!(decode("plain").unwrap(), "plain");
+        assert_eq!(decode("a%20b%2fc").unwrap(), "a b/c");
+        assert_eq!(decode("caf%C3%A9").unwrap(), "café");
+        assert_eq!(
+            percent_decode("a%2Fb%20c", EncodedSlash::Keep).unwrap(),
+            "a%2Fb c"
+        );
+
+        let error = percent_decode("a%2Fb", EncodedSlash::Reject).unwrap_err();
+        assert_eq!(error.status, 400);
+        assert_eq!(decode("100%").unwrap_err().status, 400);
+        assert_eq!(decode("%zz").unwrap_err().status, 400);
+        assert_eq!(decode("%+1").unwrap_err().status, 400);
+        assert_eq!(decode("%C3").unwrap_err().status, 400);
+        assert_eq!(decode("%\u{e9}").unwrap_err().status, 400);
+    }
+
     #[test]
     fn test_sort_routes_static_before_param() {
         let mut router = Router::new()
@@ -659,4 +2209,72 @@ mod tests {
         assert_eq!(routes[5].path, "/api/invoices/:id");
         assert_eq!(routes[5].handler_name, "get_invoice");
     }
+
+    #[test]
+    fn test_router_at_registers_methods_for_path() {
+        let router = Router::new()
+            .at("/users", |r| {
+                r.get_named("list_users", |_req, _params, _state| async {
+                    StatusCode::OK
+                })
+                .post_named("create_user", |_req, _params, _state| async {
+                    StatusCode::CREATED
+                })
+            })
+            .at("/users/:id", |r| {
+                r.route_named(
+                    Method::PUT,
+                    "update_user",
+                    None,
+                    Vec::new(),
+                    |_req, _params, _state| async { StatusCode::OK },
+                )
+                .route(Method::DELETE, |_req, _params, _state| async {
+                    StatusCode::NO_CONTENT
+                })
+            });
+
+        let routes = router.routes();
+        assert_eq!(routes.len(), 4);
+        assert_eq!(routes[0].method, "GET");
+        assert_eq!(routes[0].path, "/users");
+        assert_eq!(routes[0].handler_name, "list_users");
+        assert_eq!(routes[1].method, "POST");
+        assert_eq!(routes[1].path, "/users");
+        assert_eq!(routes[1].handler_name, "create_user");
+        assert_eq!(routes[2].method, "PUT");
+        assert_eq!(routes[2].path, "/users/:id");
+        assert_eq!(routes[2].handler_name, "update_user");
+        assert_eq!(routes[3].method, "DELETE");
+        assert_eq!(routes[3].handler_name, "handler");
+    }
+
+    #[test]
+    fn test_router_at_scopes_middleware_and_cors() {
+        use crate::middleware::TraceIdMiddleware;
+
+        let router = Router::new()
+            .route(Method::GET, "/health", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .at("/admin", |r| {
+                r.route(Method::GET, |_req, _params, _state| async {
+                    StatusCode::OK
+                })
+                .route(Method::POST, |_req, _params, _state| async {
+                    StatusCode::OK
+                })
+                .middleware(TraceIdMiddleware::new())
+                .cors(CorsConfig::permissive())
+            });
+
+        let (_, health) = &router.routes[0];
+        assert!(health.middlewares.is_empty());
+        assert!(health.cors.is_none());
+
+        for (_, route) in &router.routes[1..] {
+            assert_eq!(route.middlewares.len(), 1);
+            assert!(route.cors.is_some());
+        }
+    }
 }
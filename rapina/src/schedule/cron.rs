@@ -0,0 +1,576 @@
+//! Cron expression parsing and next-occurrence calculation.
+
+use chrono::{
+    DateTime, Datelike, LocalResult, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike,
+};
+
+const MONTH_NAMES: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How many years ahead [`CronSchedule::next_after`] searches before giving up.
+///
+/// Reaching the next February 29th can take up to eight years when a
+/// century skips its leap year (e.g. 2096 -> 2104).
+const SEARCH_YEARS: i32 = 10;
+
+/// Errors produced when parsing a cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CronError {
+    /// The expression does not have 5 or 6 whitespace-separated fields.
+    FieldCount(usize),
+    /// A field contains a value that could not be parsed.
+    InvalidValue { field: &'static str, value: String },
+    /// A field contains a value outside of its allowed range.
+    OutOfRange {
+        field: &'static str,
+        value: u32,
+        min: u32,
+        max: u32,
+    },
+    /// A step (`/n`) of zero was used.
+    ZeroStep { field: &'static str },
+}
+
+impl std::fmt::Display for CronError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CronError::FieldCount(count) => write!(
+                f,
+                "Invalid cron expression: expected 5 or 6 fields, got {}",
+                count
+            ),
+            CronError::InvalidValue { field, value } => {
+                write!(f, "Invalid value '{}' in cron {} field", value, field)
+            }
+            CronError::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "Value {} in cron {} field is out of range ({}-{})",
+                value, field, min, max
+            ),
+            CronError::ZeroStep { field } => {
+                write!(f, "Step of zero in cron {} field", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CronError {}
+
+/// A parsed cron expression.
+///
+/// Supports the standard five-field syntax (`minute hour day-of-month month
+/// day-of-week`) and a six-field variant with a leading seconds field. Each
+/// field accepts `*`, single values, ranges (`1-5`), lists (`1,15`), and
+/// steps (`*/15`, `0-30/10`). Months and weekdays also accept three-letter
+/// names (`JAN`, `MON`), and `7` is accepted as Sunday.
+///
+/// The `@yearly`, `@annually`, `@monthly`, `@weekly`, `@daily`, `@midnight`
+/// and `@hourly` shorthands are supported as well.
+///
+/// As in standard (Vixie) cron, when both day-of-month and day-of-week are
+/// restricted, a day matches if *either* field matches. A field starting with
+/// `*` (such as `*/2`) does not count as restricted here, so `0 0 */2 * MON`
+/// fires on Mondays that fall on an odd day of the month.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::schedule::CronSchedule;
+///
+/// // Every day at 03:00
+/// let nightly = CronSchedule::parse("0 3 * * *").unwrap();
+///
+/// // Every 10 seconds
+/// let frequent = CronSchedule::parse("*/10 * * * * *").unwrap();
+/// # let _ = (nightly, frequent);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_any: bool,
+    day_of_week_any: bool,
+}
+
+impl CronSchedule {
+    /// Parses a cron expression.
+    pub fn parse(expression: &str) -> Result<Self, CronError> {
+        let trimmed = expression.trim();
+        let expanded = match trimmed {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let (seconds, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            n => return Err(CronError::FieldCount(n)),
+        };
+
+        let days_of_week = parse_field(rest[4], "day-of-week", 0, 7, WEEKDAY_NAMES, 0)?;
+        // 7 is an alias for Sunday
+        let days_of_week = if days_of_week & (1 << 7) != 0 {
+            (days_of_week & !(1 << 7)) | 1
+        } else {
+            days_of_week
+        };
+
+        Ok(Self {
+            expression: trimmed.to_string(),
+            seconds: parse_field(seconds, "second", 0, 59, &[], 0)?,
+            minutes: parse_field(rest[0], "minute", 0, 59, &[], 0)?,
+            hours: parse_field(rest[1], "hour", 0, 23, &[], 0)?,
+            days_of_month: parse_field(rest[2], "day-of-month", 1, 31, &[], 0)?,
+            months: parse_field(rest[3], "month", 1, 12, MONTH_NAMES, 1)?,
+            days_of_week,
+            day_of_month_any: is_star_field(rest[2]),
+            day_of_week_any: is_star_field(rest[4]),
+        })
+    }
+
+    /// Returns the original expression.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns the first occurrence strictly after `after`, evaluated in the
+    /// timezone of `after`.
+    ///
+    /// Returns `None` if the schedule can never fire (e.g. `0 0 30 2 *`).
+    /// Local times that fall into a DST gap are skipped; ambiguous local
+    /// times resolve to their earliest instant.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let local = after.naive_local();
+        let mut t = local.with_nanosecond(0)? + TimeDelta::seconds(1);
+        let last_year = local.year() + SEARCH_YEARS;
+
+        while t.year() <= last_year {
+            if !has_bit(self.months, t.month()) {
+                t = start_of_next_month(t)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !has_bit(self.hours, t.hour()) {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + TimeDelta::hours(1);
+                continue;
+            }
+            if !has_bit(self.minutes, t.minute()) {
+                t = t.date().and_hms_opt(t.hour(), t.minute(), 0)? + TimeDelta::minutes(1);
+                continue;
+            }
+            if !has_bit(self.seconds, t.second()) {
+                t += TimeDelta::seconds(1);
+                continue;
+            }
+
+            let candidate = match tz.from_local_datetime(&t) {
+                LocalResult::Single(dt) => Some(dt),
+                LocalResult::Ambiguous(earliest, _) => Some(earliest),
+                LocalResult::None => None,
+            };
+            match candidate {
+                Some(dt) if dt > *after => return Some(dt),
+                _ => t += TimeDelta::seconds(1),
+            }
+        }
+
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = has_bit(self.days_of_month, date.day());
+        let dow = has_bit(self.days_of_week, date.weekday().num_days_from_sunday());
+
+        if self.day_of_month_any || self.day_of_week_any {
+            dom && dow
+        } else {
+            dom || dow
+        }
+    }
+}
+
+impl std::str::FromStr for CronSchedule {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn is_wildcard(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+/// Whether a day field counts as unrestricted when combining day-of-month
+/// and day-of-week. Like Vixie cron, any field starting with `*` does.
+fn is_star_field(field: &str) -> bool {
+    field.starts_with('*') || field == "?"
+}
+
+fn has_bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn start_of_next_month(t: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (year, month) = if t.month() == 12 {
+        (t.year() + 1, 1)
+    } else {
+        (t.year(), t.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
+
+/// Parses a single cron field into a bitmask of allowed values.
+///
+/// `names` are matched case-insensitively, with `names[i]` mapping to
+/// `i + name_offset`.
+fn parse_field(
+    field: &str,
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    name_offset: u32,
+) -> Result<u64, CronError> {
+    let parse_value = |value: &str| -> Result<u32, CronError> {
+        let upper = value.to_ascii_uppercase();
+        if let Some(index) = names.iter().position(|n| *n == upper) {
+            return Ok(index as u32 + name_offset);
+        }
+        let parsed: u32 = value.parse().map_err(|_| CronError::InvalidValue {
+            field: name,
+            value: value.to_string(),
+        })?;
+        if !(min..=max).contains(&parsed) {
+            return Err(CronError::OutOfRange {
+                field: name,
+                value: parsed,
+                min,
+                max,
+            });
+        }
+        Ok(parsed)
+    };
+
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| CronError::InvalidValue {
+                    field: name,
+                    value: part.to_string(),
+                })?;
+                if step == 0 {
+                    return Err(CronError::ZeroStep { field: name });
+                }
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+
+        let (start, end) = if is_wildcard(range) {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start)?, parse_value(end)?)
+        } else {
+            let value = parse_value(range)?;
+            // `5/15` means "starting at 5, every 15"
+            (value, if step.is_some() { max } else { value })
+        };
+
+        if start > end {
+            return Err(CronError::InvalidValue {
+                field: name,
+                value: part.to_string(),
+            });
+        }
+
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> String {
+        CronSchedule::parse(expr)
+            .unwrap()
+            .next_after(&utc(after))
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn test_parse_five_fields() {
+        let schedule = CronSchedule::parse("0 3 * * *").unwrap();
+        assert_eq!(schedule.expression(), "0 3 * * *");
+        assert_eq!(schedule.seconds, 1);
+        assert_eq!(schedule.minutes, 1);
+        assert_eq!(schedule.hours, 1 << 3);
+    }
+
+    #[test]
+    fn test_parse_six_fields() {
+        let schedule = CronSchedule::parse("30 0 3 * * *").unwrap();
+        assert_eq!(schedule.seconds, 1 << 30);
+        assert_eq!(schedule.minutes, 1);
+        assert_eq!(schedule.hours, 1 << 3);
+    }
+
+    #[test]
+    fn test_parse_lists_ranges_and_steps() {
+        let schedule = CronSchedule::parse("0,15 1-3 */10 * *").unwrap();
+        assert_eq!(schedule.minutes, (1 << 0) | (1 << 15));
+        assert_eq!(schedule.hours, (1 << 1) | (1 << 2) | (1 << 3));
+        assert_eq!(
+            schedule.days_of_month,
+            (1 << 1) | (1 << 11) | (1 << 21) | (1 << 31)
+        );
+
+        let schedule = CronSchedule::parse("0-30/10 5/20 * * *").unwrap();
+        assert_eq!(
+            schedule.minutes,
+            (1 << 0) | (1 << 10) | (1 << 20) | (1 << 30)
+        );
+        assert_eq!(schedule.hours, 1 << 5);
+    }
+
+    #[test]
+    fn test_parse_names() {
+        let schedule = CronSchedule::parse("0 0 * jan,Dec mon-fri").unwrap();
+        assert_eq!(schedule.months, (1 << 1) | (1 << 12));
+        assert_eq!(schedule.days_of_week, 0b0111110);
+    }
+
+    #[test]
+    fn test_parse_sunday_as_seven() {
+        let schedule = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(schedule.days_of_week, 1);
+
+        let schedule = CronSchedule::parse("0 0 * * 5-7").unwrap();
+        assert_eq!(schedule.days_of_week, (1 << 0) | (1 << 5) | (1 << 6));
+    }
+
+    #[test]
+    fn test_parse_shorthands() {
+        let daily = CronSchedule::parse("@daily").unwrap();
+        assert_eq!(daily.hours, 1);
+        assert_eq!(daily.expression(), "@daily");
+        assert!(CronSchedule::parse("@hourly").is_ok());
+        assert!(CronSchedule::parse("@weekly").is_ok());
+        assert!(CronSchedule::parse("@monthly").is_ok());
+        assert!(CronSchedule::parse("@yearly").is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            CronSchedule::parse("* * *").unwrap_err(),
+            CronError::FieldCount(3)
+        );
+        assert_eq!(
+            CronSchedule::parse("60 * * * *").unwrap_err(),
+            CronError::OutOfRange {
+                field: "minute",
+                value: 60,
+                min: 0,
+                max: 59
+            }
+        );
+        assert_eq!(
+            CronSchedule::parse("*/0 * * * *").unwrap_err(),
+            CronError::ZeroStep { field: "minute" }
+        );
+        assert!(matches!(
+            CronSchedule::parse("abc * * * *").unwrap_err(),
+            CronError::InvalidValue {
+                field: "minute",
+                ..
+            }
+        ));
+        assert!(matches!(
+            CronSchedule::parse("0 0 0 * *").unwrap_err(),
+            CronError::OutOfRange {
+                field: "day-of-month",
+                ..
+            }
+        ));
+        assert!(matches!(
+            CronSchedule::parse("0 5-1 * * *").unwrap_err(),
+            CronError::InvalidValue { field: "hour", .. }
+        ));
+        assert!(CronSchedule::parse("0 0 * FOO *").is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let schedule: CronSchedule = "*/5 * * * *".parse().unwrap();
+        assert_eq!(schedule.expression(), "*/5 * * * *");
+    }
+
+    #[test]
+    fn test_error_display() {
+        let err = CronSchedule::parse("* *").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid cron expression: expected 5 or 6 fields, got 2"
+        );
+    }
+
+    #[test]
+    fn test_next_every_minute() {
+        assert_eq!(
+            next("* * * * *", "2024-01-01T10:15:30Z"),
+            "2024-01-01T10:16:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_is_strictly_after() {
+        assert_eq!(
+            next("0 3 * * *", "2024-01-01T03:00:00Z"),
+            "2024-01-02T03:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 3 * * *", "2024-01-01T02:59:59.900Z"),
+            "2024-01-01T03:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_daily_rolls_over_month_and_year() {
+        assert_eq!(
+            next("0 3 * * *", "2024-01-31T04:00:00Z"),
+            "2024-02-01T03:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 3 * * *", "2024-12-31T04:00:00Z"),
+            "2025-01-01T03:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_with_seconds() {
+        assert_eq!(
+            next("*/10 * * * * *", "2024-01-01T10:15:31Z"),
+            "2024-01-01T10:15:40+00:00"
+        );
+        assert_eq!(
+            next("*/10 * * * * *", "2024-01-01T10:15:55Z"),
+            "2024-01-01T10:16:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_weekday() {
+        // 2024-01-05 is a Friday
+        assert_eq!(
+            next("0 9 * * MON", "2024-01-05T12:00:00Z"),
+            "2024-01-08T09:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_day_of_month_or_day_of_week() {
+        // Either the 15th or any Monday; 2024-01-08 is a Monday
+        assert_eq!(
+            next("0 0 15 * 1", "2024-01-02T00:00:00Z"),
+            "2024-01-08T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 15 * 1", "2024-01-08T00:00:00Z"),
+            "2024-01-15T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_star_step_day_fields_are_combined_with_and() {
+        let schedule = CronSchedule::parse("0 0 */2 * MON").unwrap();
+        assert!(schedule.day_of_month_any);
+        assert!(!schedule.day_of_week_any);
+
+        // 2024-01-01 is a Monday on an odd day; 2024-01-08 is an even Monday
+        // and 2024-01-03 an odd Wednesday, neither of which match.
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        assert!(schedule.day_matches(date(1)));
+        assert!(!schedule.day_matches(date(8)));
+        assert!(!schedule.day_matches(date(3)));
+
+        assert_eq!(
+            next("0 0 */2 * MON", "2024-01-01T12:00:00Z"),
+            "2024-01-15T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_leap_day() {
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00:00Z"),
+            "2028-02-29T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2096-03-01T00:00:00Z"),
+            "2104-02-29T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_impossible_schedule() {
+        let schedule = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert!(schedule.next_after(&utc("2024-01-01T00:00:00Z")).is_none());
+    }
+
+    #[test]
+    fn test_next_in_fixed_offset_timezone() {
+        let schedule = CronSchedule::parse("0 3 * * *").unwrap();
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let after = utc("2024-01-01T00:00:00Z").with_timezone(&tz);
+
+        let next = schedule.next_after(&after).unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-01-01T03:00:00+02:00");
+        assert_eq!(
+            next.with_timezone(&Utc).to_rfc3339(),
+            "2024-01-01T01:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_hourly_range() {
+        assert_eq!(
+            next("30 9-17 * * MON-FRI", "2024-01-05T17:45:00Z"),
+            "2024-01-08T09:30:00+00:00"
+        );
+    }
+}
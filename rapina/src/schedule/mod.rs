@@ -0,0 +1,488 @@
+//! Cron-style scheduled jobs.
+//!
+//! Jobs are registered on the application builder and run in the background
+//! alongside the HTTP server. Each job receives the shared [`AppState`], so
+//! it can use the same database connections and configuration as handlers.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rapina::prelude::*;
+//!
+//! Rapina::new()
+//!     .schedule("0 3 * * *", "nightly-cleanup", |_state| async {
+//!         tracing::info!("cleaning up expired sessions");
+//!     })
+//!     .router(router)
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+//!
+//! When introspection is enabled, `GET /__rapina/jobs` reports the schedule,
+//! next run and last run of every job.
+
+mod cron;
+
+pub use cron::{CronError, CronSchedule};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use http::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::extract::PathParams;
+use crate::response::{BoxBody, IntoResponse};
+use crate::state::AppState;
+
+type JobFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type JobFn = Arc<dyn Fn(Arc<AppState>) -> JobFuture + Send + Sync>;
+
+/// What to do when one or more scheduled runs were missed.
+///
+/// Runs are missed when the scheduler could not wake up in time, for example
+/// because the host was suspended or the runtime was starved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedRunPolicy {
+    /// Drop the missed runs and wait for the next scheduled time.
+    #[default]
+    Skip,
+    /// Run once immediately to catch up, then resume the schedule.
+    Coalesce,
+}
+
+/// Runtime status of a scheduled job.
+#[derive(Debug, Default)]
+struct JobState {
+    next_run: Option<DateTime<Utc>>,
+    last_run: Option<DateTime<Utc>>,
+    running: usize,
+}
+
+/// A job that runs on a cron schedule.
+///
+/// Use [`Rapina::schedule`](crate::app::Rapina::schedule) for the common case,
+/// or build a `ScheduledJob` to configure the timezone, missed-run policy,
+/// or overlap behavior and register it with
+/// [`Rapina::schedule_job`](crate::app::Rapina::schedule_job).
+///
+/// # Example
+///
+/// ```
+/// use rapina::chrono_tz::Europe::Berlin;
+/// use rapina::schedule::{MissedRunPolicy, ScheduledJob};
+///
+/// let job = ScheduledJob::new("0 3 * * *", "nightly-report", |_state| async {})
+///     .unwrap()
+///     .timezone(Berlin)
+///     .missed_runs(MissedRunPolicy::Coalesce)
+///     .allow_overlap(false);
+/// # let _ = job;
+/// ```
+pub struct ScheduledJob {
+    name: String,
+    schedule: CronSchedule,
+    timezone: Tz,
+    missed_runs: MissedRunPolicy,
+    allow_overlap: bool,
+    task: JobFn,
+    state: Arc<Mutex<JobState>>,
+}
+
+impl ScheduledJob {
+    /// Creates a new job from a cron expression, a name, and an async closure.
+    ///
+    /// The closure receives the application state each time the job runs.
+    /// Times are evaluated in UTC unless [`timezone`](Self::timezone) is set.
+    pub fn new<F, Fut>(
+        expression: &str,
+        name: impl Into<String>,
+        task: F,
+    ) -> Result<Self, CronError>
+    where
+        F: Fn(Arc<AppState>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Ok(Self {
+            name: name.into(),
+            schedule: CronSchedule::parse(expression)?,
+            timezone: Tz::UTC,
+            missed_runs: MissedRunPolicy::default(),
+            allow_overlap: false,
+            task: Arc::new(move |state: Arc<AppState>| -> JobFuture { Box::pin(task(state)) }),
+            state: Arc::new(Mutex::new(JobState::default())),
+        })
+    }
+
+    /// Sets the timezone used to evaluate the cron expression.
+    ///
+    /// Named zones follow daylight saving time, so a daily job keeps its
+    /// local wall-clock time all year round.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Sets the policy for runs missed while the scheduler was not running.
+    pub fn missed_runs(mut self, policy: MissedRunPolicy) -> Self {
+        self.missed_runs = policy;
+        self
+    }
+
+    /// Allows a new run to start while the previous one is still running.
+    ///
+    /// Disabled by default: a tick that fires while the job is still running
+    /// is skipped.
+    pub fn allow_overlap(mut self, allow: bool) -> Self {
+        self.allow_overlap = allow;
+        self
+    }
+
+    /// Returns the job name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the parsed cron schedule.
+    pub fn schedule(&self) -> &CronSchedule {
+        &self.schedule
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule
+            .next_after(&after.with_timezone(&self.timezone))
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    async fn run_loop(self, app_state: Arc<AppState>) {
+        let mut next = self.next_after(Utc::now());
+
+        while let Some(scheduled) = next {
+            self.state.lock().unwrap().next_run = Some(scheduled);
+
+            let wait = (scheduled - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            let now = Utc::now();
+            let missed = self.next_after(scheduled).is_some_and(|after| after <= now);
+            if missed && self.missed_runs == MissedRunPolicy::Skip {
+                tracing::warn!(job = %self.name, "Skipping missed scheduled runs");
+            } else {
+                self.trigger(&app_state);
+            }
+
+            next = self.next_after(now.max(scheduled));
+        }
+
+        self.state.lock().unwrap().next_run = None;
+        tracing::warn!(job = %self.name, "Schedule has no future runs, stopping");
+    }
+
+    fn trigger(&self, app_state: &Arc<AppState>) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.running > 0 && !self.allow_overlap {
+                tracing::warn!(job = %self.name, "Previous run still in progress, skipping");
+                return;
+            }
+            state.running += 1;
+            state.last_run = Some(Utc::now());
+        }
+
+        let task = self.task.clone();
+        let state = self.state.clone();
+        let app_state = app_state.clone();
+        let name = self.name.clone();
+
+        tokio::spawn(async move {
+            tracing::debug!(job = %name, "Running scheduled job");
+            let result = tokio::spawn(task(app_state)).await;
+            if result.is_err() {
+                tracing::error!(job = %name, "Scheduled job panicked");
+            }
+            state.lock().unwrap().running -= 1;
+        });
+    }
+
+    fn info(&self) -> JobInfoSource {
+        JobInfoSource {
+            name: self.name.clone(),
+            schedule: self.schedule.expression().to_string(),
+            timezone: self.timezone.to_string(),
+            allow_overlap: self.allow_overlap,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Collection of scheduled jobs owned by the application.
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: Vec<ScheduledJob>,
+}
+
+impl Scheduler {
+    /// Creates an empty scheduler.
+    pub fn new() -> Self {
+        Self { jobs: Vec::new() }
+    }
+
+    /// Adds a job to the scheduler.
+    pub fn add(&mut self, job: ScheduledJob) {
+        self.jobs.push(job);
+    }
+
+    /// Returns true if no jobs have been registered.
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Returns a registry that reports the live status of every job.
+    pub fn registry(&self) -> JobRegistry {
+        JobRegistry {
+            jobs: self.jobs.iter().map(ScheduledJob::info).collect(),
+        }
+    }
+
+    /// Spawns one background task per job.
+    ///
+    /// The returned handle stops the schedules when shut down; runs that are
+    /// already in progress are allowed to finish.
+    pub fn start(self, state: Arc<AppState>) -> SchedulerHandle {
+        let tasks = self
+            .jobs
+            .into_iter()
+            .map(|job| {
+                tracing::info!(job = %job.name, schedule = %job.schedule.expression(), "Scheduled job registered");
+                tokio::spawn(job.run_loop(state.clone()))
+            })
+            .collect();
+
+        SchedulerHandle { tasks }
+    }
+}
+
+/// Handle to the background tasks of a started [`Scheduler`].
+pub struct SchedulerHandle {
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl SchedulerHandle {
+    /// Stops all schedules. No new runs are started after this call.
+    pub fn shutdown(self) {
+        for task in self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[derive(Clone)]
+struct JobInfoSource {
+    name: String,
+    schedule: String,
+    timezone: String,
+    allow_overlap: bool,
+    state: Arc<Mutex<JobState>>,
+}
+
+/// Snapshot of a scheduled job, as reported by the introspection endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    /// Job name
+    pub name: String,
+    /// Cron expression
+    pub schedule: String,
+    /// Timezone the expression is evaluated in (IANA name)
+    pub timezone: String,
+    /// Whether overlapping runs are allowed
+    pub allow_overlap: bool,
+    /// Next scheduled run (RFC 3339)
+    pub next_run: Option<String>,
+    /// Start time of the most recent run (RFC 3339)
+    pub last_run: Option<String>,
+    /// Whether the job is currently running
+    pub running: bool,
+}
+
+/// Registry of scheduled jobs stored in application state.
+///
+/// This is automatically populated when introspection is enabled and
+/// the application has scheduled jobs.
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    jobs: Vec<JobInfoSource>,
+}
+
+impl JobRegistry {
+    /// Returns a snapshot of every registered job.
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.jobs
+            .iter()
+            .map(|job| {
+                let state = job.state.lock().unwrap();
+                JobInfo {
+                    name: job.name.clone(),
+                    schedule: job.schedule.clone(),
+                    timezone: job.timezone.clone(),
+                    allow_overlap: job.allow_overlap,
+                    next_run: state.next_run.map(|dt| dt.to_rfc3339()),
+                    last_run: state.last_run.map(|dt| dt.to_rfc3339()),
+                    running: state.running > 0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Handler for the scheduled jobs introspection endpoint.
+///
+/// Returns all scheduled jobs as JSON.
+pub async fn list_jobs(
+    _req: Request<Incoming>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    match state.get::<JobRegistry>() {
+        Some(registry) => {
+            let json = serde_json::to_vec(&registry.jobs()).unwrap_or_default();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "application/json")
+                .body(BoxBody::new(json))
+                .unwrap()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn noop_job(expression: &str) -> ScheduledJob {
+        ScheduledJob::new(expression, "noop", |_| async {}).unwrap()
+    }
+
+    #[test]
+    fn test_scheduled_job_invalid_expression() {
+        let result = ScheduledJob::new("not a cron", "bad", |_| async {});
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scheduled_job_defaults() {
+        let job = noop_job("0 3 * * *");
+        assert_eq!(job.name(), "noop");
+        assert_eq!(job.schedule().expression(), "0 3 * * *");
+        assert_eq!(job.missed_runs, MissedRunPolicy::Skip);
+        assert!(!job.allow_overlap);
+    }
+
+    #[test]
+    fn test_scheduled_job_next_after_uses_timezone() {
+        let job = noop_job("0 3 * * *").timezone(Tz::Etc__GMTMinus2);
+        let after = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let next = job.next_after(after).unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-01-01T01:00:00+00:00");
+    }
+
+    #[test]
+    fn test_scheduled_job_keeps_local_time_across_dst() {
+        let job = noop_job("0 3 * * *").timezone(Tz::Europe__Berlin);
+        let utc = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // CET (+01:00) before the switch on 2024-03-31, CEST (+02:00) after
+        let before = job.next_after(utc("2024-03-29T12:00:00Z")).unwrap();
+        assert_eq!(before, utc("2024-03-30T02:00:00Z"));
+        let after = job.next_after(before).unwrap();
+        assert_eq!(after, utc("2024-03-31T01:00:00Z"));
+
+        // And back to CET on 2024-10-27
+        let autumn = job.next_after(utc("2024-10-26T12:00:00Z")).unwrap();
+        assert_eq!(autumn, utc("2024-10-27T02:00:00Z"));
+    }
+
+    #[test]
+    fn test_registry_reports_jobs() {
+        let mut scheduler = Scheduler::new();
+        assert!(scheduler.is_empty());
+        scheduler.add(noop_job("*/5 * * * *"));
+
+        let jobs = scheduler.registry().jobs();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].name, "noop");
+        assert_eq!(jobs[0].schedule, "*/5 * * * *");
+        assert_eq!(jobs[0].timezone, "UTC");
+        assert!(jobs[0].next_run.is_none());
+        assert!(jobs[0].last_run.is_none());
+        assert!(!jobs[0].running);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_prevents_overlap_by_default() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let job = ScheduledJob::new("* * * * * *", "slow", move |_| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+        })
+        .unwrap();
+
+        let state = Arc::new(AppState::new());
+        job.trigger(&state);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        job.trigger(&state);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(job.state.lock().unwrap().running > 0);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_allows_overlap_when_enabled() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let job = ScheduledJob::new("* * * * * *", "slow", move |_| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+        })
+        .unwrap()
+        .allow_overlap(true);
+
+        let state = Arc::new(AppState::new());
+        job.trigger(&state);
+        job.trigger(&state);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_records_last_run_and_finishes() {
+        let job = noop_job("* * * * * *");
+        let state = Arc::new(AppState::new());
+
+        job.trigger(&state);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let job_state = job.state.lock().unwrap();
+        assert!(job_state.last_run.is_some());
+        assert_eq!(job_state.running, 0);
+    }
+}
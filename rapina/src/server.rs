@@ -14,51 +14,206 @@ use tokio::net::TcpListener;
 use tokio::signal::unix::SignalKind;
 
 use crate::context::RequestContext;
-use crate::middleware::MiddlewareStack;
+use crate::drain::Drain;
+use crate::middleware::{Middleware, MiddlewareStack};
+use crate::reload::{ReloadHook, Reloader};
 use crate::router::Router;
 use crate::state::AppState;
 
 /// A shutdown hook: a closure that returns a boxed future.
 pub(crate) type ShutdownHook = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
 
-pub(crate) async fn serve(
-    router: Router,
+/// Waits for a Unix-only signal such as `SIGHUP` or `SIGUSR1`.
+struct OptionalSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl OptionalSignal {
+    /// Installs the handler only when `enabled`, so the default behavior is
+    /// kept for apps that don't use the signal.
+    fn new(kind: SignalKind, enabled: bool) -> Self {
+        #[cfg(unix)]
+        {
+            let signal = enabled.then(|| {
+                tokio::signal::unix::signal(kind).expect("failed to install signal handler")
+            });
+            Self { signal }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (kind, enabled);
+            Self {}
+        }
+    }
+
+    /// Resolves on the next signal; never resolves without a handler.
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal
+            && signal.recv().await.is_some()
+        {
+            return;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+/// An extra address the app listens on, with its own routes and middleware.
+///
+/// Register it with [`Rapina::listener`](crate::app::Rapina::listener). It
+/// shares the app's state and graceful shutdown, but serves only its own
+/// router and runs only its own middleware; the app's router and global
+/// middleware stay on the main address.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::prelude::*;
+/// use rapina::server::Listener;
+///
+/// let admin = Router::new().get("/jobs", list_jobs);
+///
+/// Rapina::new()
+///     .state(AppConfig::from_env()?)
+///     .router(public_router)
+///     .listener(Listener::new("127.0.0.1:9090", admin).middleware(AdminOnly))
+///     .listen("0.0.0.0:8080")
+///     .await
+/// ```
+pub struct Listener {
+    pub(crate) addr: String,
+    pub(crate) router: Router,
+    pub(crate) middlewares: MiddlewareStack,
+}
+
+impl Listener {
+    /// Serves `router` on `addr`, e.g. `"127.0.0.1:9090"`.
+    pub fn new(addr: impl Into<String>, router: Router) -> Self {
+        Self {
+            addr: addr.into(),
+            router,
+            middlewares: MiddlewareStack::new(),
+        }
+    }
+
+    /// Adds a middleware that only runs for requests on this listener.
+    pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
+        self.middlewares.add(middleware);
+        self
+    }
+}
+
+/// A bound address and what it serves.
+pub(crate) struct Binding {
+    pub(crate) addr: SocketAddr,
+    pub(crate) router: Router,
+    pub(crate) middlewares: MiddlewareStack,
+}
+
+/// Serves every binding until a shutdown signal, then drains all of their
+/// connections together and runs the shutdown hooks once.
+pub(crate) async fn serve_all(
+    bindings: Vec<Binding>,
     state: AppState,
-    middlewares: MiddlewareStack,
-    addr: SocketAddr,
     shutdown_timeout: Duration,
     shutdown_hooks: Vec<ShutdownHook>,
+    reload_hooks: Vec<ReloadHook>,
 ) -> std::io::Result<()> {
-    let router = Arc::new(router);
     let state = Arc::new(state);
-    let middlewares = Arc::new(middlewares);
-    let listener = TcpListener::bind(addr).await?;
+    let reloader = Arc::new(Reloader::new(reload_hooks));
+    let mut hangup = OptionalSignal::new(SignalKind::hangup(), !reloader.is_empty());
+    let drain = state.get::<Drain>().cloned();
+    let mut usr1 = OptionalSignal::new(SignalKind::user_defined1(), drain.is_some());
+    let mut drained = pin!(async {
+        match &drain {
+            Some(drain) => drain.elapsed().await,
+            None => std::future::pending().await,
+        }
+    });
+
+    // Bind everything before serving anything, so a taken port fails fast
+    let mut listeners = Vec::with_capacity(bindings.len());
+    for binding in &bindings {
+        listeners.push(TcpListener::bind(binding.addr).await?);
+    }
+    let services: Vec<(Arc<Router>, Arc<MiddlewareStack>)> = bindings
+        .into_iter()
+        .map(|binding| (Arc::new(binding.router), Arc::new(binding.middlewares)))
+        .collect();
+
+    // One accept task per listener feeds a single loop, so every
+    // connection is tracked by the same graceful shutdown
+    let (accepted, mut incoming) = tokio::sync::mpsc::channel(64);
+    let mut acceptors = Vec::with_capacity(listeners.len());
+    for (index, listener) in listeners.into_iter().enumerate() {
+        tracing::info!("Rapina listening on http://{}", listener.local_addr()?);
+        let accepted = accepted.clone();
+        acceptors.push(tokio::spawn(async move {
+            loop {
+                let result = listener.accept().await.map(|(stream, _)| (stream, index));
+                let failed = result.is_err();
+                if accepted.send(result).await.is_err() || failed {
+                    break;
+                }
+            }
+        }));
+    }
+    drop(accepted);
+
     let graceful = GracefulShutdown::new();
+    // Flipped once shutdown starts, ending streamed response bodies so an
+    // endless stream doesn't keep its connection from draining
+    let (stopping_tx, stopping) = tokio::sync::watch::channel(false);
     let mut ctrl_c = pin!(tokio::signal::ctrl_c());
     let mut sigterm = tokio::signal::unix::signal(SignalKind::terminate())
         .expect("failed to install SIGTERM handler");
 
-    tracing::info!("Rapina listening on http://{}", addr);
+    let stop_accepting = |acceptors: &[tokio::task::JoinHandle<()>]| {
+        // Aborting drops the listeners, so new connections are refused
+        for acceptor in acceptors {
+            acceptor.abort();
+        }
+        tracing::info!("Shutdown signal received, waiting for connections to drain...");
+    };
 
     loop {
         tokio::select! {
-            result = listener.accept() => {
-                let (stream, _) = result?;
+            Some(result) = incoming.recv() => {
+                let (stream, index) = match result {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        for acceptor in &acceptors {
+                            acceptor.abort();
+                        }
+                        return Err(e);
+                    }
+                };
                 let io = TokioIo::new(stream);
-                let router = router.clone();
+                let (router, middlewares) = services[index].clone();
                 let state = state.clone();
-                let middlewares = middlewares.clone();
+                let stopping = stopping.clone();
+                #[cfg(feature = "profiling")]
+                let connection = state
+                    .get::<crate::profiling::ServerStats>()
+                    .map(crate::profiling::ServerStats::connection_opened);
 
                 let service = service_fn(move |mut req: Request<Incoming>| {
                     let router = router.clone();
                     let state = state.clone();
                     let middlewares = middlewares.clone();
+                    let mut stopping = stopping.clone();
 
                     let ctx = RequestContext::new();
                     req.extensions_mut().insert(ctx.clone());
 
                     async move {
                         let response = middlewares.execute(req, &router, &state, &ctx).await;
+                        let response = response.map(|body| {
+                            body.until(async move {
+                                let _ = stopping.wait_for(|stopping| *stopping).await;
+                            })
+                        });
                         Ok::<_, std::convert::Infallible>(response)
                     }
                 });
@@ -67,24 +222,43 @@ pub(crate) async fn serve(
                 let conn = graceful.watch(conn);
 
                 tokio::spawn(async move {
+                    #[cfg(feature = "profiling")]
+                    let _connection = connection;
                     if let Err(e) = conn.await {
                         tracing::error!("connection error: {}", e);
                     }
                 });
             }
+            _ = hangup.recv() => {
+                tracing::info!("SIGHUP received, reloading configuration...");
+                let reloader = reloader.clone();
+                let state = state.clone();
+                tokio::spawn(async move {
+                    reloader.reload(&state).await;
+                });
+            }
+            _ = usr1.recv() => {
+                if let Some(drain) = &drain {
+                    drain.start();
+                }
+            }
+            _ = drained.as_mut() => {
+                tracing::info!("Drain grace period elapsed");
+                stop_accepting(&acceptors);
+                break;
+            }
             _ = ctrl_c.as_mut() => {
-                drop(listener);
-                tracing::info!("Shutdown signal received, waiting for connections to drain...");
+                stop_accepting(&acceptors);
                 break;
             }
             _ = sigterm.recv() => {
-                drop(listener);
-                tracing::info!("Shutdown signal received, waiting for connections to drain...");
+                stop_accepting(&acceptors);
                 break;
             }
         }
     }
 
+    stopping_tx.send_replace(true);
     tokio::select! {
         _ = graceful.shutdown() => {
             tracing::info!("All connections drained.");
@@ -113,6 +287,31 @@ mod tests {
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
     use tokio::net::TcpStream;
 
+    /// Serves a single router, like `Rapina::listen` without extra listeners.
+    async fn serve(
+        router: Router,
+        state: AppState,
+        middlewares: MiddlewareStack,
+        addr: SocketAddr,
+        shutdown_timeout: Duration,
+        shutdown_hooks: Vec<ShutdownHook>,
+        reload_hooks: Vec<ReloadHook>,
+    ) -> std::io::Result<()> {
+        let binding = Binding {
+            addr,
+            router,
+            middlewares,
+        };
+        serve_all(
+            vec![binding],
+            state,
+            shutdown_timeout,
+            shutdown_hooks,
+            reload_hooks,
+        )
+        .await
+    }
+
     async fn free_port() -> u16 {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         listener.local_addr().unwrap().port()
@@ -141,6 +340,14 @@ mod tests {
         kill(getpid(), Signal::SIGTERM).unwrap();
     }
 
+    fn send_sighup() {
+        kill(getpid(), Signal::SIGHUP).unwrap();
+    }
+
+    fn send_sigusr1() {
+        kill(getpid(), Signal::SIGUSR1).unwrap();
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_shutdown_hooks_execute_in_order() {
@@ -170,6 +377,7 @@ mod tests {
                     }) as Pin<Box<dyn Future<Output = ()> + Send>>
                 }),
             ],
+            vec![],
         ));
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -211,6 +419,7 @@ mod tests {
             format!("127.0.0.1:{}", port).parse().unwrap(),
             Duration::from_secs(5),
             vec![],
+            vec![],
         ));
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -250,6 +459,7 @@ mod tests {
             format!("127.0.0.1:{}", port).parse().unwrap(),
             Duration::from_secs(1),
             vec![],
+            vec![],
         ));
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -268,6 +478,75 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_event_stream_ends_on_shutdown() {
+        use crate::response::{Event, Sse};
+
+        let port = free_port().await;
+
+        let router = Router::new().route(http::Method::GET, "/events", |_, _, _| async {
+            let ticks = futures_util::stream::unfold(0u32, |n| async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Some((Event::new(format!("tick {}", n)).id(n.to_string()), n + 1))
+            });
+            Sse::new(ticks)
+        });
+
+        let handle = tokio::spawn(serve(
+            router,
+            AppState::new(),
+            MiddlewareStack::new(),
+            format!("127.0.0.1:{}", port).parse().unwrap(),
+            Duration::from_secs(30),
+            vec![],
+            vec![],
+        ));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut stream = TcpStream::connect(format!("127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        stream
+            .write_all(b"GET /events HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n")
+            .await
+            .unwrap();
+
+        // Read the stream as it comes, without waiting for it to end
+        let mut received = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !String::from_utf8_lossy(&received).contains("data: tick 1\n\n") {
+            let n = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf))
+                .await
+                .expect("events should arrive while the stream is open")
+                .unwrap();
+            assert!(n > 0, "stream closed early");
+            received.extend_from_slice(&buf[..n]);
+        }
+        let head = String::from_utf8_lossy(&received).to_string();
+        assert!(head.contains("content-type: text/event-stream"));
+        assert!(head.contains("id: 0\ndata: tick 0\n\n"));
+        assert!(head.contains("id: 1\ndata: tick 1\n\n"));
+
+        send_sigint();
+
+        tokio::time::timeout(Duration::from_secs(3), stream.read_to_end(&mut received))
+            .await
+            .expect("the stream should end when shutdown starts")
+            .unwrap();
+        assert!(
+            String::from_utf8_lossy(&received).ends_with("0\r\n\r\n"),
+            "the chunked body should be terminated, not cut off"
+        );
+
+        let result = tokio::time::timeout(Duration::from_secs(3), handle).await;
+        assert!(
+            result.is_ok(),
+            "server should drain the stream instead of waiting for the shutdown timeout"
+        );
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_sigterm_triggers_shutdown() {
@@ -282,6 +561,7 @@ mod tests {
             format!("127.0.0.1:{}", port).parse().unwrap(),
             Duration::from_secs(5),
             vec![],
+            vec![],
         ));
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -298,4 +578,225 @@ mod tests {
             "server should exit cleanly after SIGTERM"
         );
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_sighup_runs_reload_hooks() {
+        use crate::extract::FromRequestParts;
+        use crate::reload::{Reloadable, ReloadableState};
+
+        let port = free_port().await;
+        let attempts = Arc::new(Mutex::new(0));
+        let counter = attempts.clone();
+
+        let router = Router::new().route(
+            http::Method::GET,
+            "/level",
+            |req: Request<Incoming>, params, state| async move {
+                let (parts, _) = req.into_parts();
+                let level = ReloadableState::<String>::from_request_parts(&parts, &params, &state)
+                    .await
+                    .unwrap();
+                level.to_string()
+            },
+        );
+        let reload_hooks: Vec<ReloadHook> = vec![Box::new(move |state| {
+            let counter = counter.clone();
+            Box::pin(async move {
+                let attempt = {
+                    let mut attempts = counter.lock().unwrap();
+                    *attempts += 1;
+                    *attempts
+                };
+                if attempt > 1 {
+                    return Err("config file is invalid".to_string());
+                }
+                state
+                    .get::<Reloadable<String>>()
+                    .unwrap()
+                    .set("debug".to_string());
+                Ok(())
+            })
+        })];
+
+        let handle = tokio::spawn(serve(
+            router,
+            AppState::new().with(Reloadable::new("info".to_string())),
+            MiddlewareStack::new(),
+            format!("127.0.0.1:{}", port).parse().unwrap(),
+            Duration::from_secs(5),
+            vec![],
+            reload_hooks,
+        ));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(http_get(port, "/level").await.ends_with("info"));
+
+        send_sighup();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(http_get(port, "/level").await.ends_with("debug"));
+
+        // A failed reload keeps the previous value
+        send_sighup();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(*attempts.lock().unwrap(), 2);
+        assert!(http_get(port, "/level").await.ends_with("debug"));
+
+        send_sigint();
+        let result = tokio::time::timeout(Duration::from_secs(5), handle).await;
+        assert!(result.is_ok(), "server should shut down within timeout");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_listeners_share_state_but_not_routes() {
+        use crate::app::Rapina;
+        use crate::middleware::DefaultHeadersMiddleware;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let public_port = free_port().await;
+        let admin_port = free_port().await;
+        let shutdowns = Arc::new(AtomicUsize::new(0));
+        let shutdown_counter = shutdowns.clone();
+
+        let public = Router::new().route(
+            http::Method::POST,
+            "/hits",
+            |_, _, state: Arc<AppState>| async move {
+                let hits = state.get::<Arc<AtomicUsize>>().unwrap();
+                (hits.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+            },
+        );
+        let admin = Router::new().route(
+            http::Method::GET,
+            "/hits",
+            |_, _, state: Arc<AppState>| async move {
+                let hits = state.get::<Arc<AtomicUsize>>().unwrap();
+                hits.load(Ordering::SeqCst).to_string()
+            },
+        );
+
+        let app = Rapina::new()
+            .with_introspection(false)
+            .state(Arc::new(AtomicUsize::new(0)))
+            .router(public)
+            .listener(
+                Listener::new(format!("127.0.0.1:{}", admin_port), admin)
+                    .middleware(DefaultHeadersMiddleware::new().header("x-listener", "admin")),
+            )
+            .on_shutdown(move || async move {
+                shutdown_counter.fetch_add(1, Ordering::SeqCst);
+            });
+        let addr = format!("127.0.0.1:{}", public_port);
+        let handle = tokio::spawn(async move { app.listen(&addr).await });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let post = |port: u16| async move {
+            let mut stream = TcpStream::connect(format!("127.0.0.1:{}", port))
+                .await
+                .unwrap();
+            stream
+                .write_all(
+                    b"POST /hits HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            let mut buf = Vec::new();
+            stream.read_to_end(&mut buf).await.unwrap();
+            String::from_utf8_lossy(&buf).to_string()
+        };
+
+        // Each listener serves only its own routes
+        assert!(post(public_port).await.ends_with("1"));
+        assert!(post(public_port).await.ends_with("2"));
+        assert!(post(admin_port).await.starts_with("HTTP/1.1 405"));
+        assert!(
+            http_get(public_port, "/hits")
+                .await
+                .starts_with("HTTP/1.1 405")
+        );
+
+        // ...but share state, and middleware stays on its listener
+        let admin_response = http_get(admin_port, "/hits").await;
+        assert!(admin_response.ends_with("2"), "{}", admin_response);
+        assert!(admin_response.contains("x-listener: admin"));
+        assert!(!post(public_port).await.contains("x-listener"));
+
+        send_sigint();
+        let result = tokio::time::timeout(Duration::from_secs(5), handle).await;
+        assert!(result.is_ok(), "both listeners should shut down together");
+        assert!(result.unwrap().unwrap().is_ok());
+        assert_eq!(shutdowns.load(Ordering::SeqCst), 1);
+
+        assert!(
+            TcpStream::connect(format!("127.0.0.1:{}", public_port))
+                .await
+                .is_err()
+        );
+        assert!(
+            TcpStream::connect(format!("127.0.0.1:{}", admin_port))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_sigusr1_drains_then_shuts_down() {
+        use crate::app::Rapina;
+
+        let port = free_port().await;
+        let router = Router::new().route(http::Method::GET, "/", |_, _, _| async { "ok" });
+        let app = Rapina::new()
+            .with_introspection(false)
+            .with_draining(Duration::from_millis(500))
+            .router(router);
+        let addr = format!("127.0.0.1:{}", port);
+        let handle = tokio::spawn(async move { app.listen(&addr).await });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(
+            http_get(port, "/__rapina/ready")
+                .await
+                .starts_with("HTTP/1.1 200")
+        );
+
+        send_sigusr1();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Still accepting, but not ready and not keeping connections alive
+        assert!(
+            http_get(port, "/__rapina/ready")
+                .await
+                .starts_with("HTTP/1.1 503")
+        );
+        let mut stream = TcpStream::connect(format!("127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = Vec::new();
+        tokio::time::timeout(Duration::from_secs(1), stream.read_to_end(&mut buf))
+            .await
+            .expect("the server should close a keep-alive connection while draining")
+            .unwrap();
+        let response = String::from_utf8_lossy(&buf);
+        assert!(response.contains("connection: close"), "{}", response);
+        assert!(response.ends_with("ok"));
+
+        let result = tokio::time::timeout(Duration::from_secs(3), handle).await;
+        assert!(
+            result.is_ok(),
+            "server should shut down after the grace period"
+        );
+        assert!(result.unwrap().unwrap().is_ok());
+        assert!(
+            TcpStream::connect(format!("127.0.0.1:{}", port))
+                .await
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,342 @@
+//! Running a Rapina application without binding a listener.
+//!
+//! [`RapinaService`] dispatches requests through the router and middleware
+//! stack of a prepared app. It is what you reach for when something else owns
+//! the socket: another server, a test harness, or a serverless runtime. With
+//! the `tower` feature enabled it also implements `tower::Service`.
+
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body, Incoming};
+use hyper::client::conn::http1 as client_http1;
+use hyper::server::conn::http1 as server_http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, header};
+use hyper_util::rt::TokioIo;
+use tokio::sync::oneshot;
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::middleware::MiddlewareStack;
+use crate::response::{BoxBody, IntoResponse};
+use crate::router::Router;
+use crate::state::AppState;
+
+const PIPE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A prepared Rapina application that can be called like a function.
+///
+/// Created with [`Rapina::into_service`](crate::app::Rapina::into_service).
+/// Cloning is cheap; clones share the router, state, and middleware.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::prelude::*;
+///
+/// let service = Rapina::new()
+///     .router(Router::new().route(http::Method::GET, "/", |_, _, _| async { "ok" }))
+///     .into_service()?;
+///
+/// let response = service.handle(http::Request::new(String::new())).await;
+/// ```
+#[derive(Clone)]
+pub struct RapinaService {
+    router: Arc<Router>,
+    state: Arc<AppState>,
+    middlewares: Arc<MiddlewareStack>,
+}
+
+impl RapinaService {
+    pub(crate) fn new(router: Router, state: AppState, middlewares: MiddlewareStack) -> Self {
+        Self {
+            router: Arc::new(router),
+            state: Arc::new(state),
+            middlewares: Arc::new(middlewares),
+        }
+    }
+
+    /// Dispatches a request through the app and returns its response.
+    ///
+    /// Handlers and middleware read hyper's `Incoming` body, which only a
+    /// hyper connection can produce, so the request is sent over an
+    /// in-memory HTTP/1.1 connection. Its body is streamed rather than
+    /// buffered, so the app's body limit and read timeout apply to it as
+    /// they do on a listener. Streamed responses, such as
+    /// [`Sse`](crate::response::Sse), are streamed back the same way.
+    pub async fn handle<B>(&self, req: Request<B>) -> Response<BoxBody>
+    where
+        B: Body + Send + 'static,
+        B::Data: Send,
+        B::Error: std::fmt::Display,
+    {
+        let (mut parts, body) = req.into_parts();
+        let body = body.map_err(|e| std::io::Error::other(e.to_string()));
+
+        // The connection frames the body from its own size hint
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::TRANSFER_ENCODING);
+
+        match self.roundtrip(Request::from_parts(parts, body)).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::error!("in-memory dispatch failed: {}", e);
+                Error::internal("failed to dispatch request").into_response()
+            }
+        }
+    }
+
+    async fn roundtrip<B>(&self, req: Request<B>) -> Result<Response<BoxBody>, hyper::Error>
+    where
+        B: Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (client_io, server_io) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            let handler = service_fn(move |req: Request<Incoming>| {
+                let service = service.clone();
+                async move { Ok::<_, Infallible>(service.dispatch(req).await) }
+            });
+            let _ = server_http1::Builder::new()
+                .serve_connection(TokioIo::new(server_io), handler)
+                .await;
+        });
+
+        let (mut sender, conn) = client_http1::handshake(TokioIo::new(client_io)).await?;
+        tokio::spawn(async move {
+            let _ = conn.await;
+        });
+
+        let (mut parts, body) = sender.send_request(req).await?.into_parts();
+
+        // A full body is sent with a Content-Length and comes back in full;
+        // a streamed one is chunked and is passed through as it arrives,
+        // since it may never end
+        let body = if parts.headers.contains_key(header::TRANSFER_ENCODING) {
+            parts.headers.remove(header::TRANSFER_ENCODING);
+            BoxBody::stream(body)
+        } else {
+            BoxBody::new(body.collect().await?.to_bytes())
+        };
+
+        Ok(Response::from_parts(parts, body))
+    }
+
+    /// Runs a request that arrived on a hyper connection.
+    pub(crate) async fn dispatch(&self, mut req: Request<Incoming>) -> Response<BoxBody> {
+        let ctx = RequestContext::new();
+        req.extensions_mut().insert(ctx.clone());
+        self.middlewares
+            .execute(req, &self.router, &self.state, &ctx)
+            .await
+    }
+}
+
+/// Turns a buffered body back into hyper's `Incoming`, for middleware that
+/// has to read the body before passing the request on.
+///
+/// Like [`RapinaService::handle`], the bytes are sent over an in-memory
+/// HTTP/1.1 connection, since only a connection can produce `Incoming`. The
+/// connection stays open, so the body stays readable, until the returned
+/// sender is dropped.
+pub(crate) async fn replay_body(body: Bytes) -> Result<(Incoming, oneshot::Sender<()>), Error> {
+    let (client_io, server_io) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+    let (body_tx, body_rx) = oneshot::channel::<Incoming>();
+    let (done_tx, done_rx) = oneshot::channel::<()>();
+
+    let slot = Arc::new(Mutex::new(Some((body_tx, done_rx))));
+    tokio::spawn(async move {
+        let handler = service_fn(move |req: Request<Incoming>| {
+            let slot = slot.lock().unwrap().take();
+            async move {
+                if let Some((body_tx, done_rx)) = slot {
+                    let _ = body_tx.send(req.into_body());
+                    let _ = done_rx.await;
+                }
+                Ok::<_, Infallible>(Response::new(Full::new(Bytes::new())))
+            }
+        });
+        let _ = server_http1::Builder::new()
+            .serve_connection(TokioIo::new(server_io), handler)
+            .await;
+    });
+
+    let replay_failed = |e: hyper::Error| {
+        tracing::error!("in-memory body replay failed: {}", e);
+        Error::internal("failed to read request body")
+    };
+    let (mut sender, conn) = client_http1::handshake(TokioIo::new(client_io))
+        .await
+        .map_err(replay_failed)?;
+    tokio::spawn(async move {
+        let _ = conn.await;
+    });
+    let request = Request::post("/")
+        .body(Full::new(body))
+        .expect("static request is valid");
+    tokio::spawn(async move {
+        let _ = sender.send_request(request).await;
+    });
+
+    let body = body_rx
+        .await
+        .map_err(|_| Error::internal("failed to read request body"))?;
+    Ok((body, done_tx))
+}
+
+#[cfg(feature = "tower")]
+impl<B> tower::Service<Request<B>> for RapinaService
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: std::fmt::Display,
+{
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = crate::middleware::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move { Ok(service.handle(req).await) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::BodyLimitMiddleware;
+    use futures_util::StreamExt;
+    use http::{Method, StatusCode};
+    use http_body_util::StreamBody;
+    use hyper::body::Frame;
+
+    fn service() -> RapinaService {
+        let router = Router::new()
+            .route(Method::GET, "/hello", |_, _, _| async { "hello" })
+            .route(Method::POST, "/echo", |req, params, state| async move {
+                use crate::extract::FromRequest;
+                String::from_request(req, &params, &state).await
+            });
+        RapinaService::new(router, AppState::new(), MiddlewareStack::new())
+    }
+
+    #[tokio::test]
+    async fn test_handle_dispatches_to_router() {
+        let req = Request::get("/hello")
+            .body(Full::new(Bytes::new()))
+            .unwrap();
+        let response = service().handle(req).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_handle_replays_request_body() {
+        let req = Request::post("/echo")
+            .header(header::CONTENT_LENGTH, "999")
+            .body(Full::new(Bytes::from_static(b"ping")))
+            .unwrap();
+        let response = service().handle(req).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), b"ping");
+    }
+
+    fn limited_service(limit: BodyLimitMiddleware) -> RapinaService {
+        let router = Router::new().route(Method::POST, "/echo", |req, params, state| async move {
+            use crate::extract::FromRequest;
+            String::from_request(req, &params, &state).await
+        });
+        let mut middlewares = MiddlewareStack::new();
+        middlewares.add(limit);
+        RapinaService::new(router, AppState::new(), middlewares)
+    }
+
+    fn chunks(
+        chunks: Vec<&'static [u8]>,
+    ) -> impl futures_util::Stream<Item = Result<Frame<Bytes>, Infallible>> + Send + 'static {
+        futures_util::stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok(Frame::data(Bytes::from_static(chunk)))),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_handle_applies_body_limit_to_streamed_body() {
+        let body = StreamBody::new(chunks(vec![&[b'a'; 1024], &[b'b'; 1024], &[b'c'; 1024]]));
+        let req = Request::post("/echo").body(body).unwrap();
+        let response = limited_service(BodyLimitMiddleware::new(2048))
+            .handle(req)
+            .await;
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_handle_applies_read_timeout_to_endless_body() {
+        let endless = chunks(vec![b"a"]).chain(futures_util::stream::pending());
+        let req = Request::post("/echo")
+            .body(StreamBody::new(endless))
+            .unwrap();
+        let service = limited_service(
+            BodyLimitMiddleware::new(2048).read_timeout(std::time::Duration::from_millis(50)),
+        );
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(5), service.handle(req))
+            .await
+            .expect("the read timeout ends the request");
+
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_handle_streams_endless_response() {
+        use crate::response::{Event, Sse};
+
+        let router = Router::new().route(Method::GET, "/events", |_, _, _| async {
+            Sse::new(futures_util::stream::repeat_with(|| Event::new("tick")))
+        });
+        let service = RapinaService::new(router, AppState::new(), MiddlewareStack::new());
+        let req = Request::get("/events")
+            .body(Full::new(Bytes::new()))
+            .unwrap();
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            let response = service.handle(req).await;
+            assert!(response.body().is_stream());
+            let mut body = response.into_body();
+            body.frame().await.unwrap().unwrap().into_data().unwrap()
+        })
+        .await
+        .expect("the first event arrives while the stream goes on");
+
+        assert!(response.starts_with(b"data: tick\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_unknown_route() {
+        let req = Request::get("/missing")
+            .body(Full::new(Bytes::new()))
+            .unwrap();
+        let response = service().handle(req).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,380 @@
+//! Signed URLs for temporary access links.
+//!
+//! [`UrlSigner`] appends an expiry and an HMAC-SHA256 signature to a path, so a
+//! link such as `/files/123?exp=1767225600&sig=...` can be handed out without a
+//! session. Handlers that serve those links take a [`VerifiedSignature`], which
+//! rejects the request with 403 Forbidden if the signature doesn't match or the
+//! link has expired.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::signing::{UrlSigner, VerifiedSignature};
+//! use std::time::Duration;
+//!
+//! #[get("/files/:id/link")]
+//! async fn share(id: Path<u64>, signer: State<UrlSigner>) -> String {
+//!     signer.sign_path(&format!("/files/{}", id.into_inner()), Duration::from_secs(600))
+//! }
+//!
+//! #[public]
+//! #[get("/files/:id")]
+//! async fn download(id: Path<u64>, _sig: VerifiedSignature) -> String {
+//!     format!("contents of file {}", id.into_inner())
+//! }
+//!
+//! Rapina::new()
+//!     .state(UrlSigner::new(std::env::var("URL_SIGNING_SECRET").unwrap()))
+//!     .discover()
+//!     .listen("127.0.0.1:3000")
+//!     .await
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::state::AppState;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Query parameter holding the expiry as a Unix timestamp.
+const EXPIRES_PARAM: &str = "exp";
+/// Query parameter holding the hex-encoded signature.
+const SIGNATURE_PARAM: &str = "sig";
+
+/// Signs and verifies expiring URLs.
+///
+/// The signature covers the path and every query parameter except `sig`
+/// itself, so changing any part of the link invalidates it.
+///
+/// Register the signer as state to enable the [`VerifiedSignature`] extractor.
+#[derive(Clone)]
+pub struct UrlSigner {
+    secret: Vec<u8>,
+    previous_secrets: Vec<Vec<u8>>,
+    clock_skew: Duration,
+}
+
+impl UrlSigner {
+    /// Creates a signer with the given secret and no clock skew tolerance.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+            previous_secrets: Vec::new(),
+            clock_skew: Duration::ZERO,
+        }
+    }
+
+    /// Also accepts links signed with an older secret.
+    ///
+    /// New links are always signed with the secret passed to [`new`](Self::new).
+    /// Keep the old secret around until links signed with it have expired.
+    pub fn with_previous_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.previous_secrets.push(secret.as_ref().to_vec());
+        self
+    }
+
+    /// Accepts links up to `skew` after their expiry, to tolerate clock
+    /// differences between the server that signed the link and the one
+    /// verifying it.
+    pub fn with_clock_skew(mut self, skew: Duration) -> Self {
+        self.clock_skew = skew;
+        self
+    }
+
+    /// Returns the configured clock skew tolerance.
+    pub fn clock_skew(&self) -> Duration {
+        self.clock_skew
+    }
+
+    /// Signs `path` so it stays valid for `ttl`.
+    ///
+    /// `path` may already contain a query string; `exp` and `sig` are
+    /// appended to it.
+    pub fn sign_path(&self, path: &str, ttl: Duration) -> String {
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let expires_at = Utc::now()
+            .checked_add_signed(ttl)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        self.sign_path_until(path, expires_at)
+    }
+
+    /// Signs `path` so it stays valid until `expires_at`.
+    pub fn sign_path_until(&self, path: &str, expires_at: DateTime<Utc>) -> String {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let unsigned = format!(
+            "{}{}{}={}",
+            path,
+            separator,
+            EXPIRES_PARAM,
+            expires_at.timestamp()
+        );
+        let signature = encode_hex(&sign(&self.secret, &unsigned));
+        format!("{}&{}={}", unsigned, SIGNATURE_PARAM, signature)
+    }
+
+    /// Verifies a signed path and query, returning when it expires.
+    ///
+    /// Fails with 403 Forbidden if the signature is missing or doesn't match
+    /// any configured secret, or if the link has expired.
+    pub fn verify(&self, path_and_query: &str) -> Result<DateTime<Utc>, Error> {
+        let (path, query) = path_and_query
+            .split_once('?')
+            .ok_or_else(|| Error::forbidden("missing URL signature"))?;
+
+        let mut signature = None;
+        let mut expires = None;
+        let mut signed_params = Vec::new();
+        for param in query.split('&') {
+            match param.split_once('=') {
+                Some((SIGNATURE_PARAM, value)) => signature = Some(value),
+                Some((EXPIRES_PARAM, value)) => {
+                    expires = Some(value);
+                    signed_params.push(param);
+                }
+                _ => signed_params.push(param),
+            }
+        }
+
+        let signature = signature.ok_or_else(|| Error::forbidden("missing URL signature"))?;
+        let signature =
+            decode_hex(signature).ok_or_else(|| Error::forbidden("invalid URL signature"))?;
+        let expires_at = expires
+            .and_then(|value| value.parse::<i64>().ok())
+            .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
+            .ok_or_else(|| Error::forbidden("missing URL expiry"))?;
+
+        let unsigned = format!("{}?{}", path, signed_params.join("&"));
+        let valid = std::iter::once(&self.secret)
+            .chain(&self.previous_secrets)
+            .any(|secret| {
+                let mut mac = new_mac(secret);
+                mac.update(unsigned.as_bytes());
+                mac.verify_slice(&signature).is_ok()
+            });
+        if !valid {
+            return Err(Error::forbidden("invalid URL signature"));
+        }
+
+        let skew = chrono::Duration::from_std(self.clock_skew).unwrap_or(chrono::Duration::MAX);
+        let deadline = expires_at
+            .checked_add_signed(skew)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        if Utc::now() > deadline {
+            return Err(Error::forbidden("URL signature has expired"));
+        }
+
+        Ok(expires_at)
+    }
+}
+
+impl std::fmt::Debug for UrlSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UrlSigner")
+            .field("previous_secrets", &self.previous_secrets.len())
+            .field("clock_skew", &self.clock_skew)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Proof that the current request URL carries a valid, unexpired signature.
+///
+/// Verifies the request's path and query against the [`UrlSigner`] registered
+/// in application state. Rejects the request with 403 Forbidden otherwise.
+///
+/// Signed links are usually opened without a session, so routes using this
+/// extractor are typically marked `#[public]`.
+#[derive(Debug, Clone, Copy)]
+pub struct VerifiedSignature {
+    expires_at: DateTime<Utc>,
+}
+
+impl VerifiedSignature {
+    /// Returns when the signed link expires.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+}
+
+impl FromRequestParts for VerifiedSignature {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let signer = state.get::<UrlSigner>().ok_or_else(|| {
+            Error::internal("UrlSigner not registered. Did you forget to call .state()?")
+        })?;
+        let path_and_query = parts
+            .uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or_else(|| parts.uri.path());
+        let expires_at = signer.verify(path_and_query)?;
+        Ok(Self { expires_at })
+    }
+}
+
+fn new_mac(secret: &[u8]) -> HmacSha256 {
+    HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length")
+}
+
+fn sign(secret: &[u8], message: &str) -> Vec<u8> {
+    let mut mac = new_mac(secret);
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.is_ascii() {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            if pair.len() != 2 {
+                return None;
+            }
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{TestRequest, empty_params, empty_state, state_with};
+    use http::StatusCode;
+
+    fn signer() -> UrlSigner {
+        UrlSigner::new("secret")
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let url = signer().sign_path("/files/123", Duration::from_secs(60));
+        assert!(url.starts_with("/files/123?exp="));
+        assert!(url.contains("&sig="));
+        assert!(signer().verify(&url).is_ok());
+    }
+
+    #[test]
+    fn test_sign_path_with_existing_query() {
+        let url = signer().sign_path("/files/123?download=true", Duration::from_secs(60));
+        assert!(url.starts_with("/files/123?download=true&exp="));
+        assert!(signer().verify(&url).is_ok());
+    }
+
+    #[test]
+    fn test_expired_link_is_rejected() {
+        let url = signer().sign_path_until("/files/123", Utc::now() - chrono::Duration::minutes(1));
+        let err = signer().verify(&url).unwrap_err();
+        assert_eq!(err.status, 403);
+        assert_eq!(err.message, "URL signature has expired");
+    }
+
+    #[test]
+    fn test_clock_skew_tolerance() {
+        let url = signer().sign_path_until("/files/123", Utc::now() - chrono::Duration::minutes(1));
+        let lenient = signer().with_clock_skew(Duration::from_secs(120));
+        assert!(lenient.verify(&url).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_path_is_rejected() {
+        let url = signer().sign_path("/files/123", Duration::from_secs(60));
+        let tampered = url.replace("/files/123", "/files/124");
+        let err = signer().verify(&tampered).unwrap_err();
+        assert_eq!(err.status, 403);
+        assert_eq!(err.message, "invalid URL signature");
+    }
+
+    #[test]
+    fn test_tampered_expiry_is_rejected() {
+        let expires_at = Utc::now() + chrono::Duration::minutes(1);
+        let url = signer().sign_path_until("/files/123", expires_at);
+        let extended = url.replace(
+            &format!("exp={}", expires_at.timestamp()),
+            &format!("exp={}", expires_at.timestamp() + 3600),
+        );
+        assert!(signer().verify(&extended).is_err());
+    }
+
+    #[test]
+    fn test_missing_signature_is_rejected() {
+        assert!(signer().verify("/files/123").is_err());
+        assert!(signer().verify("/files/123?exp=99999999999").is_err());
+        assert!(
+            signer()
+                .verify("/files/123?exp=99999999999&sig=zz")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rotated_key() {
+        let old = UrlSigner::new("old-secret");
+        let url = old.sign_path("/files/123", Duration::from_secs(60));
+
+        let rotated = UrlSigner::new("new-secret").with_previous_secret("old-secret");
+        assert!(rotated.verify(&url).is_ok());
+        assert!(UrlSigner::new("new-secret").verify(&url).is_err());
+
+        let new_url = rotated.sign_path("/files/123", Duration::from_secs(60));
+        assert!(UrlSigner::new("new-secret").verify(&new_url).is_ok());
+        assert!(old.verify(&new_url).is_err());
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let bytes = [0x00, 0x7f, 0xab, 0xff];
+        assert_eq!(encode_hex(&bytes), "007fabff");
+        assert_eq!(decode_hex("007fabff"), Some(bytes.to_vec()));
+        assert_eq!(decode_hex("abc"), None);
+    }
+
+    #[tokio::test]
+    async fn test_extractor_verifies_request_uri() {
+        let url = signer().sign_path("/files/123", Duration::from_secs(60));
+        let state = state_with(signer());
+        let (parts, _) = TestRequest::get(&url).into_parts();
+
+        let verified = VerifiedSignature::from_request_parts(&parts, &empty_params(), &state)
+            .await
+            .unwrap();
+        assert!(verified.expires_at() > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_extractor_rejects_tampered_uri() {
+        let url = signer().sign_path("/files/123", Duration::from_secs(60));
+        let state = state_with(signer());
+        let (parts, _) = TestRequest::get(&url.replace("/files/123", "/files/999")).into_parts();
+
+        let err = VerifiedSignature::from_request_parts(&parts, &empty_params(), &state)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status, StatusCode::FORBIDDEN.as_u16());
+    }
+
+    #[tokio::test]
+    async fn test_extractor_without_signer() {
+        let (parts, _) = TestRequest::get("/files/123").into_parts();
+        let err = VerifiedSignature::from_request_parts(&parts, &empty_params(), &empty_state())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status, 500);
+    }
+}
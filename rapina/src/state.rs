@@ -4,6 +4,8 @@ use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::error::{Error, IntoApiError};
+
 type StateMap = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
 
 /// A type-safe container for sharing state across request handlers.
@@ -70,6 +72,72 @@ impl AppState {
             .get(&TypeId::of::<T>())
             .and_then(|arc| arc.downcast_ref::<T>())
     }
+
+    /// Retrieves a reference to a value of type `T`, or an error naming the
+    /// type if it was never registered.
+    ///
+    /// The error converts into a 500 response, so handlers can use `?`.
+    ///
+    /// ```
+    /// use rapina::state::AppState;
+    ///
+    /// struct Mailer;
+    ///
+    /// let err = AppState::new().try_get::<Mailer>().err().unwrap();
+    /// assert!(err.type_name().ends_with("Mailer"));
+    /// ```
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Result<&T, StateError> {
+        self.get::<T>().ok_or(StateError {
+            type_name: std::any::type_name::<T>(),
+        })
+    }
+
+    /// Returns true if a value of type `T` has been registered.
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.contains_type(TypeId::of::<T>())
+    }
+
+    /// Returns the number of registered values.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if no value has been registered.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub(crate) fn contains_type(&self, type_id: TypeId) -> bool {
+        self.inner.contains_key(&type_id)
+    }
+}
+
+/// Error returned by [`AppState::try_get`] when the type was never
+/// registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateError {
+    type_name: &'static str,
+}
+
+impl StateError {
+    /// Returns the full name of the missing type.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no state registered for type '{}'", self.type_name)
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl IntoApiError for StateError {
+    fn into_api_error(self) -> Error {
+        crate::extract::missing_state(&[self.type_name])
+    }
 }
 
 #[cfg(test)]
@@ -88,6 +156,14 @@ mod tests {
         assert!(state.inner.is_empty());
     }
 
+    #[test]
+    fn test_app_state_len() {
+        let state = AppState::new().with(1u32).with("name").with(2u32);
+        assert_eq!(state.len(), 2);
+        assert!(!state.is_empty());
+        assert!(AppState::new().is_empty());
+    }
+
     #[test]
     fn test_app_state_with_value() {
         #[derive(Debug, PartialEq)]
@@ -111,6 +187,33 @@ mod tests {
         assert!(state.get::<Missing>().is_none());
     }
 
+    #[test]
+    fn test_app_state_try_get() {
+        #[derive(Debug)]
+        struct Mailer;
+
+        let state = AppState::new().with(7u32);
+        assert_eq!(state.try_get::<u32>(), Ok(&7));
+
+        let err = state.try_get::<Mailer>().unwrap_err();
+        assert!(err.type_name().ends_with("::Mailer"));
+        assert!(
+            err.to_string()
+                .starts_with("no state registered for type '")
+        );
+
+        let api_error = Error::from(err);
+        assert_eq!(api_error.status, 500);
+        assert!(api_error.message.contains("Mailer"));
+    }
+
+    #[test]
+    fn test_app_state_contains() {
+        let state = AppState::new().with(7u32);
+        assert!(state.contains::<u32>());
+        assert!(!state.contains::<String>());
+    }
+
     #[test]
     fn test_app_state_multiple_types() {
         #[derive(Debug)]
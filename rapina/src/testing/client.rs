@@ -1,6 +1,7 @@
 //! Test client for integration testing Rapina applications.
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -21,6 +22,8 @@ use crate::middleware::MiddlewareStack;
 use crate::router::Router;
 use crate::state::AppState;
 
+use super::examples::{EXAMPLE_ID_HEADER, ExampleRecorder, ExchangeParts, default_examples_dir};
+
 /// A test client for making HTTP requests to a Rapina application.
 ///
 /// The test client spawns a lightweight HTTP server on a random port
@@ -47,15 +50,25 @@ use crate::state::AppState;
 pub struct TestClient {
     addr: SocketAddr,
     client: Client<hyper_util::client::legacy::connect::HttpConnector, Full<Bytes>>,
-    _shutdown: oneshot::Sender<()>,
+    /// Stops the spawned server; `None` when connected to an external one.
+    _shutdown: Option<oneshot::Sender<()>>,
+    /// Shared with the spawned server; `None` when connected to an external one.
+    examples: Option<Arc<ExampleRecorder>>,
 }
 
 impl TestClient {
     /// Creates a new test client from a Rapina application.
     ///
-    /// This spawns a background server on a random available port.
-    pub async fn new(app: crate::app::Rapina) -> Self {
-        let app = app.prepare();
+    /// This spawns a background server on a random available port. Routes
+    /// added with [`Rapina::test_routes`](crate::app::Rapina::test_routes)
+    /// are served.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the app fails to start, e.g. because discovered routes conflict.
+    pub async fn new(mut app: crate::app::Rapina) -> Self {
+        app.serve_test_routes = true;
+        let app = app.prepare().unwrap_or_else(|e| panic!("{}", e));
         Self::from_parts(app.router, app.state, app.middlewares).await
     }
 
@@ -64,6 +77,8 @@ impl TestClient {
         let router = Arc::new(router);
         let state = Arc::new(state);
         let middlewares = Arc::new(middlewares);
+        let examples = Arc::new(ExampleRecorder::default());
+        let recorder = examples.clone();
 
         // Bind to a random available port
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -83,18 +98,29 @@ impl TestClient {
                                 let router = router.clone();
                                 let state = state.clone();
                                 let middlewares = middlewares.clone();
+                                let recorder = recorder.clone();
 
                                 tokio::spawn(async move {
                                     let service = service_fn(move |mut req: Request<Incoming>| {
                                         let router = router.clone();
                                         let state = state.clone();
                                         let middlewares = middlewares.clone();
+                                        let recorder = recorder.clone();
 
                                         let ctx = RequestContext::new();
                                         req.extensions_mut().insert(ctx.clone());
+                                        let example_id = req
+                                            .headers_mut()
+                                            .remove(EXAMPLE_ID_HEADER)
+                                            .and_then(|v| v.to_str().ok()?.parse::<u64>().ok());
 
                                         async move {
                                             let response = middlewares.execute(req, &router, &state, &ctx).await;
+                                            if let Some(id) = example_id
+                                                && let Some(route) = ctx.matched_route()
+                                            {
+                                                recorder.matched(id, route.clone());
+                                            }
                                             Ok::<_, std::convert::Infallible>(response)
                                         }
                                     });
@@ -119,7 +145,50 @@ impl TestClient {
         Self {
             addr,
             client,
-            _shutdown: shutdown_tx,
+            _shutdown: Some(shutdown_tx),
+            examples: Some(examples),
+        }
+    }
+
+    /// Creates a test client for a server that is already running, such as
+    /// one started with `rapina dev`.
+    ///
+    /// Used by the contract tests `rapina generate contract-tests` writes,
+    /// since a binary crate's app can't be built from `tests/`.
+    pub fn connect(addr: SocketAddr) -> Self {
+        let client = Client::builder(hyper_util::rt::TokioExecutor::new()).build_http();
+
+        Self {
+            addr,
+            client,
+            _shutdown: None,
+            examples: None,
+        }
+    }
+
+    /// Records every request and response that reaches a route as an
+    /// example, for `rapina openapi enrich --examples`.
+    ///
+    /// Examples go to `target/rapina-examples`, or to the directory in the
+    /// `RAPINA_EXAMPLES_DIR` environment variable, one JSON file each,
+    /// tagged with the matched route pattern. Credential headers such as
+    /// `Authorization` and `Cookie` are left out, and bodies are cut at
+    /// [`MAX_EXAMPLE_BODY_BYTES`](super::MAX_EXAMPLE_BODY_BYTES).
+    ///
+    /// Does nothing for clients created with [`TestClient::connect`], since
+    /// the matched route is only known to an in-process server.
+    pub fn record_examples(&self, enabled: bool) {
+        self.record_examples_in(enabled.then(default_examples_dir));
+    }
+
+    /// Like [`TestClient::record_examples`], writing to `dir`.
+    pub fn record_examples_to(&self, dir: impl Into<PathBuf>) {
+        self.record_examples_in(Some(dir.into()));
+    }
+
+    fn record_examples_in(&self, dir: Option<PathBuf>) {
+        if let Some(examples) = &self.examples {
+            examples.set_dir(dir);
         }
     }
 
@@ -188,26 +257,54 @@ impl<'a> TestRequestBuilder<'a> {
         self
     }
 
+    /// Appends query parameters to the request path.
+    ///
+    /// Can be called more than once, and combined with a query string
+    /// already in the path.
+    ///
+    /// ```ignore
+    /// client.get("/users").query(&[("page", "2"), ("per_page", "10")]).send().await;
+    /// ```
+    pub fn query<T: Serialize + ?Sized>(mut self, params: &T) -> Self {
+        let query = serde_urlencoded::to_string(params).unwrap();
+        if !query.is_empty() {
+            if !self.path.contains('?') {
+                self.path.push('?');
+            } else if !self.path.ends_with(['?', '&']) {
+                self.path.push('&');
+            }
+            self.path.push_str(&query);
+        }
+        self
+    }
+
     /// Sets a JSON body on the request.
+    ///
+    /// Sets `content-type: application/json` unless a content type was
+    /// already given with [`header`](Self::header); a later `header` call
+    /// overrides it as well.
     pub fn json<T: Serialize>(mut self, body: &T) -> Self {
         self.body = Bytes::from(serde_json::to_vec(body).unwrap());
-        self.headers.insert(
-            http::header::CONTENT_TYPE,
-            HeaderValue::from_static("application/json"),
-        );
+        self.default_content_type("application/json");
         self
     }
 
     /// Sets a form body on the request.
+    ///
+    /// Sets `content-type: application/x-www-form-urlencoded` unless a
+    /// content type was already given.
     pub fn form<T: Serialize>(mut self, body: &T) -> Self {
         self.body = Bytes::from(serde_urlencoded::to_string(body).unwrap());
-        self.headers.insert(
-            http::header::CONTENT_TYPE,
-            HeaderValue::from_static("application/x-www-form-urlencoded"),
-        );
+        self.default_content_type("application/x-www-form-urlencoded");
         self
     }
 
+    fn default_content_type(&mut self, value: &'static str) {
+        self.headers
+            .entry(http::header::CONTENT_TYPE)
+            .or_insert(HeaderValue::from_static(value));
+    }
+
     /// Sets raw body bytes.
     pub fn body(mut self, body: impl Into<Bytes>) -> Self {
         self.body = body.into();
@@ -218,13 +315,19 @@ impl<'a> TestRequestBuilder<'a> {
     pub async fn send(self) -> TestResponse {
         let uri = format!("http://{}{}", self.client.addr, self.path);
 
-        let mut builder = Request::builder().method(self.method).uri(&uri);
+        let mut builder = Request::builder().method(self.method.clone()).uri(&uri);
 
         for (key, value) in self.headers.iter() {
             builder = builder.header(key, value);
         }
 
-        let request = builder.body(Full::new(self.body)).unwrap();
+        let examples = self.client.examples.as_deref();
+        let example_id = examples.and_then(ExampleRecorder::next_id);
+        if let Some(id) = example_id {
+            builder = builder.header(EXAMPLE_ID_HEADER, id);
+        }
+
+        let request = builder.body(Full::new(self.body.clone())).unwrap();
 
         let response = self.client.client.request(request).await.unwrap();
 
@@ -232,6 +335,26 @@ impl<'a> TestRequestBuilder<'a> {
         let headers = response.headers().clone();
         let body = response.into_body().collect().await.unwrap().to_bytes();
 
+        if let (Some(examples), Some(id)) = (examples, example_id) {
+            examples.record(
+                id,
+                ExchangeParts {
+                    method: &self.method,
+                    path: &self.path,
+                    status: None,
+                    headers: &self.headers,
+                    body: &self.body,
+                },
+                ExchangeParts {
+                    method: &self.method,
+                    path: &self.path,
+                    status: Some(status),
+                    headers: &headers,
+                    body: &body,
+                },
+            );
+        }
+
         TestResponse {
             status,
             headers,
@@ -367,9 +490,7 @@ mod tests {
                     http::Response::builder()
                         .status(StatusCode::OK)
                         .header("content-type", "application/json")
-                        .body(http_body_util::Full::new(bytes::Bytes::from(
-                            r#"{"id":1,"name":"test"}"#,
-                        )))
+                        .body(crate::response::BoxBody::new(r#"{"id":1,"name":"test"}"#))
                         .unwrap()
                 }),
             );
@@ -478,4 +599,18 @@ mod tests {
         assert!(addr.port() > 0);
         assert_eq!(addr.ip().to_string(), "127.0.0.1");
     }
+
+    #[tokio::test]
+    async fn test_client_connect_to_running_server() {
+        let app = Rapina::new()
+            .with_introspection(false)
+            .router(Router::new().route(http::Method::GET, "/", |_, _, _| async { "Hello!" }));
+
+        let server = TestClient::new(app).await;
+        let client = TestClient::connect(server.addr());
+        let response = client.get("/").send().await;
+
+        assert_eq!(client.addr(), server.addr());
+        assert_eq!(response.text(), "Hello!");
+    }
 }
@@ -0,0 +1,486 @@
+//! Checking responses against an OpenAPI document.
+
+use http::StatusCode;
+use serde_json::Value;
+
+use super::TestResponse;
+
+/// An OpenAPI document that responses are checked against.
+///
+/// The contract tests written by `rapina generate contract-tests` build one
+/// from the committed `openapi.json` and call [`assert_response`] after every
+/// request, so a handler that drifts from its documentation fails the build.
+///
+/// A response passes when its status is documented for the operation (the
+/// catch-all `default` response doesn't count) and, if that response has an
+/// `application/json` schema, the body matches it. Schema checks are
+/// structural: types, `required`, `properties`, `additionalProperties`,
+/// `items`, `enum`, `const`, `nullable`, `allOf`, `anyOf`, `oneOf` and
+/// `$ref`. Formats and numeric or length bounds aren't checked.
+///
+/// [`assert_response`]: Contract::assert_response
+///
+/// # Examples
+///
+/// ```ignore
+/// use std::net::SocketAddr;
+/// use rapina::testing::{Contract, TestClient};
+///
+/// #[tokio::test]
+/// async fn list_users() {
+///     let contract = Contract::from_json(include_str!("../openapi.json"));
+///     let client = TestClient::connect("127.0.0.1:3000".parse().unwrap());
+///
+///     let response = client.get("/users").send().await;
+///     contract.assert_response("GET", "/users", &response);
+/// }
+/// ```
+pub struct Contract {
+    spec: Value,
+}
+
+impl Contract {
+    /// Parses an OpenAPI document.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec` isn't valid JSON.
+    pub fn from_json(spec: &str) -> Self {
+        let spec = serde_json::from_str(spec)
+            .unwrap_or_else(|e| panic!("invalid OpenAPI document: {}", e));
+        Self { spec }
+    }
+
+    /// Checks a response to `method path`, where `path` is the documented
+    /// template (e.g. `/users/{id}`). Returns every mismatch found.
+    pub fn check(
+        &self,
+        method: &str,
+        path: &str,
+        response: &TestResponse,
+    ) -> Result<(), Vec<String>> {
+        self.check_response(method, path, response.status(), response.bytes())
+    }
+
+    /// Like [`check`](Contract::check), but panics listing the mismatches.
+    pub fn assert_response(&self, method: &str, path: &str, response: &TestResponse) {
+        if let Err(errors) = self.check(method, path, response) {
+            panic!(
+                "{} {} broke its contract (status {}):\n  - {}\nbody: {}",
+                method.to_uppercase(),
+                path,
+                response.status().as_u16(),
+                errors.join("\n  - "),
+                response.text()
+            );
+        }
+    }
+
+    fn check_response(
+        &self,
+        method: &str,
+        path: &str,
+        status: StatusCode,
+        body: &[u8],
+    ) -> Result<(), Vec<String>> {
+        let Some(responses) = self
+            .spec
+            .get("paths")
+            .and_then(|paths| paths.get(path))
+            .and_then(|item| item.get(method.to_lowercase()))
+            .and_then(|operation| operation.get("responses"))
+            .and_then(Value::as_object)
+        else {
+            return Err(vec![format!(
+                "{} {} is not in the OpenAPI document",
+                method.to_uppercase(),
+                path
+            )]);
+        };
+
+        let code = status.as_u16().to_string();
+        let range = format!("{}XX", &code[..1]);
+        let Some(documented) = responses.get(&code).or_else(|| {
+            responses
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&range))
+                .map(|(_, response)| response)
+        }) else {
+            let listed: Vec<&str> = responses
+                .keys()
+                .map(String::as_str)
+                .filter(|key| *key != "default")
+                .collect();
+            return Err(vec![format!(
+                "status {} is not documented (documented: {})",
+                code,
+                listed.join(", ")
+            )]);
+        };
+
+        let Some(schema) = documented.pointer("/content/application~1json/schema") else {
+            return Ok(());
+        };
+        let value: Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(_) if body.is_empty() => {
+                return Err(vec!["expected a JSON body, got an empty one".to_string()]);
+            }
+            Err(e) => return Err(vec![format!("body is not valid JSON: {}", e)]),
+        };
+
+        let mut validator = Validator::new(&self.spec, schema);
+        validator.validate(schema, &value, "$");
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.errors)
+        }
+    }
+}
+
+/// Validates values against a schema, resolving `$ref`s first against the
+/// schema itself (schemars puts `definitions` there) and then against the
+/// whole document (`#/components/schemas/...`).
+struct Validator<'a> {
+    spec: &'a Value,
+    root: &'a Value,
+    errors: Vec<String>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(spec: &'a Value, root: &'a Value) -> Self {
+        Self {
+            spec,
+            root,
+            errors: Vec::new(),
+        }
+    }
+
+    fn matches(&self, schema: &'a Value, value: &Value) -> bool {
+        let mut validator = Validator::new(self.spec, self.root);
+        validator.validate(schema, value, "$");
+        validator.errors.is_empty()
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root
+            .pointer(pointer)
+            .or_else(|| self.spec.pointer(pointer))
+    }
+
+    fn validate(&mut self, schema: &'a Value, value: &Value, at: &str) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.errors
+                    .push(format!("{}: no value is allowed here", at));
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match self.resolve(reference) {
+                Some(target) => self.validate(target, value, at),
+                None => self
+                    .errors
+                    .push(format!("{}: cannot resolve $ref {}", at, reference)),
+            }
+            return;
+        }
+
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if value.is_null()
+            && (schema.get("nullable") == Some(&Value::Bool(true)) || types.contains(&"null"))
+        {
+            return;
+        }
+        if !types.is_empty() && !types.iter().any(|ty| is_type(ty, value)) {
+            self.errors.push(format!(
+                "{}: expected {}, got {}",
+                at,
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.contains(value)
+        {
+            self.errors.push(format!(
+                "{}: {} is not one of {}",
+                at,
+                value,
+                Value::from(allowed.clone())
+            ));
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != value
+        {
+            self.errors
+                .push(format!("{}: expected {}, got {}", at, expected, value));
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.validate(sub, value, at);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array)
+            && !any.iter().any(|sub| self.matches(sub, value))
+        {
+            self.errors
+                .push(format!("{}: matches none of the anyOf schemas", at));
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matched = one.iter().filter(|sub| self.matches(sub, value)).count();
+            if matched != 1 {
+                self.errors.push(format!(
+                    "{}: matches {} of the oneOf schemas, expected exactly 1",
+                    at, matched
+                ));
+            }
+        }
+
+        if let Value::Object(object) = value {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !object.contains_key(name) {
+                    self.errors
+                        .push(format!("{}: missing required field \"{}\"", at, name));
+                }
+            }
+            for (name, field) in object {
+                let field_at = format!("{}.{}", at, name);
+                match properties.and_then(|p| p.get(name)) {
+                    Some(property) => self.validate(property, field, &field_at),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => self
+                            .errors
+                            .push(format!("{}: unexpected field \"{}\"", at, name)),
+                        Some(additional) => self.validate(additional, field, &field_at),
+                        None => {}
+                    },
+                }
+            }
+        }
+
+        if let Value::Array(items) = value
+            && let Some(item_schema) = schema.get("items").filter(|items| !items.is_array())
+        {
+            for (i, item) in items.iter().enumerate() {
+                self.validate(item_schema, item, &format!("{}[{}]", at, i));
+            }
+        }
+    }
+}
+
+fn is_type(ty: &str, value: &Value) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn contract() -> Contract {
+        Contract::from_json(
+            &json!({
+                "openapi": "3.0.3",
+                "paths": {
+                    "/users/{id}": {
+                        "get": {
+                            "responses": {
+                                "200": {
+                                    "description": "Success",
+                                    "content": {"application/json": {"schema": {
+                                        "type": "object",
+                                        "required": ["id", "name", "address"],
+                                        "properties": {
+                                            "id": {"type": "integer"},
+                                            "name": {"type": "string"},
+                                            "nickname": {"type": ["string", "null"]},
+                                            "role": {"enum": ["admin", "member"]},
+                                            "address": {"$ref": "#/definitions/Address"},
+                                            "tags": {"type": "array", "items": {"type": "string"}}
+                                        },
+                                        "definitions": {
+                                            "Address": {
+                                                "type": "object",
+                                                "required": ["city"],
+                                                "properties": {"city": {"type": "string"}},
+                                                "additionalProperties": false
+                                            }
+                                        }
+                                    }}}
+                                },
+                                "404": {
+                                    "description": "Not found",
+                                    "content": {"application/json": {"schema": {
+                                        "$ref": "#/components/schemas/ApiErrorBody"
+                                    }}}
+                                },
+                                "default": {"description": "Error response"}
+                            }
+                        },
+                        "delete": {
+                            "responses": {"2XX": {"description": "Deleted"}}
+                        }
+                    }
+                },
+                "components": {"schemas": {"ApiErrorBody": {
+                    "type": "object",
+                    "required": ["error"],
+                    "properties": {"error": {"type": "object"}}
+                }}}
+            })
+            .to_string(),
+        )
+    }
+
+    fn check(method: &str, status: u16, body: Value) -> Result<(), Vec<String>> {
+        contract().check_response(
+            method,
+            "/users/{id}",
+            StatusCode::from_u16(status).unwrap(),
+            body.to_string().as_bytes(),
+        )
+    }
+
+    #[test]
+    fn test_valid_response() {
+        let body = json!({
+            "id": 1,
+            "name": "Ada",
+            "nickname": null,
+            "role": "admin",
+            "address": {"city": "London"},
+            "tags": ["a", "b"]
+        });
+        assert_eq!(check("GET", 200, body), Ok(()));
+    }
+
+    #[test]
+    fn test_reports_every_mismatch() {
+        let body = json!({
+            "id": "1",
+            "role": "owner",
+            "address": {"city": 3, "zip": "N1"},
+            "tags": ["a", 2]
+        });
+        let mut errors = check("GET", 200, body).unwrap_err();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "$.address.city: expected string, got number",
+                "$.address: unexpected field \"zip\"",
+                "$.id: expected integer, got string",
+                "$.role: \"owner\" is not one of [\"admin\",\"member\"]",
+                "$.tags[1]: expected string, got number",
+                "$: missing required field \"name\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolves_component_refs() {
+        assert_eq!(
+            check("GET", 404, json!({"error": {"code": "NOT_FOUND"}})),
+            Ok(())
+        );
+        assert_eq!(
+            check("GET", 404, json!({"message": "gone"})),
+            Err(vec!["$: missing required field \"error\"".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_undocumented_status() {
+        let errors = check("GET", 500, json!({})).unwrap_err();
+        assert_eq!(
+            errors,
+            vec!["status 500 is not documented (documented: 200, 404)"]
+        );
+    }
+
+    #[test]
+    fn test_status_ranges_and_missing_content() {
+        let contract = contract();
+        assert_eq!(
+            contract.check_response("DELETE", "/users/{id}", StatusCode::NO_CONTENT, b""),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_unknown_operation() {
+        let errors = contract()
+            .check_response("PUT", "/users/{id}", StatusCode::OK, b"")
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec!["PUT /users/{id} is not in the OpenAPI document"]
+        );
+    }
+
+    #[test]
+    fn test_body_must_be_json() {
+        let errors = contract()
+            .check_response("GET", "/users/{id}", StatusCode::OK, b"not json")
+            .unwrap_err();
+        assert!(errors[0].starts_with("body is not valid JSON"));
+    }
+
+    #[test]
+    fn test_one_of_and_any_of() {
+        let spec = json!({});
+        let schema = json!({
+            "oneOf": [{"type": "integer"}, {"type": "number"}],
+            "anyOf": [{"type": "string"}, {"type": "integer"}]
+        });
+        let mut validator = Validator::new(&spec, &schema);
+        validator.validate(&schema, &json!(1), "$");
+        assert_eq!(
+            validator.errors,
+            vec!["$: matches 2 of the oneOf schemas, expected exactly 1"]
+        );
+    }
+}
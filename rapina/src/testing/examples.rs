@@ -0,0 +1,245 @@
+//! Request and response examples recorded from tests.
+//!
+//! [`TestClient::record_examples`](super::TestClient::record_examples) writes
+//! one JSON file per exchange, and `rapina openapi enrich --examples` merges
+//! them into an exported spec as named examples.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::context::MatchedRoute;
+
+/// Request header the test client tags recorded requests with, so the test
+/// server can report which route each one matched. Removed before dispatch.
+pub(crate) const EXAMPLE_ID_HEADER: &str = "x-rapina-example-id";
+
+/// Where examples go unless `RAPINA_EXAMPLES_DIR` says otherwise.
+pub const DEFAULT_EXAMPLES_DIR: &str = "target/rapina-examples";
+
+/// Bodies longer than this are cut and stored as text.
+pub const MAX_EXAMPLE_BODY_BYTES: usize = 4096;
+
+/// Headers never written to example files.
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// Headers that change from run to run, left out so recordings stay stable.
+const VOLATILE_HEADERS: &[&str] = &["date", "content-length", "x-trace-id", "traceparent"];
+
+/// Header name fragments that mark a header as a credential.
+const SENSITIVE_FRAGMENTS: &[&str] = &["token", "secret", "api-key", "password", "signature"];
+
+/// Records examples for a [`TestClient`](super::TestClient).
+#[derive(Default)]
+pub(crate) struct ExampleRecorder {
+    /// Directory to write to, or `None` while recording is off.
+    dir: Mutex<Option<PathBuf>>,
+    next_id: AtomicU64,
+    /// Routes matched by tagged requests, filled in by the test server.
+    routes: Mutex<HashMap<u64, MatchedRoute>>,
+}
+
+impl ExampleRecorder {
+    pub(crate) fn set_dir(&self, dir: Option<PathBuf>) {
+        *self.dir.lock().unwrap() = dir;
+    }
+
+    /// An id to tag the next request with, while recording is on.
+    pub(crate) fn next_id(&self) -> Option<u64> {
+        self.dir
+            .lock()
+            .unwrap()
+            .is_some()
+            .then(|| self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Called by the test server once a tagged request was handled.
+    pub(crate) fn matched(&self, id: u64, route: MatchedRoute) {
+        self.routes.lock().unwrap().insert(id, route);
+    }
+
+    /// Writes the example for a tagged request, if it matched a route.
+    pub(crate) fn record(&self, id: u64, request: ExchangeParts<'_>, response: ExchangeParts<'_>) {
+        let Some(route) = self.routes.lock().unwrap().remove(&id) else {
+            return;
+        };
+        let Some(dir) = self.dir.lock().unwrap().clone() else {
+            return;
+        };
+        let example = RecordedExample::new(route, request, response);
+        if let Err(e) = example.write(&dir) {
+            panic!("failed to record example in {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// The directory examples are written to by default.
+pub(crate) fn default_examples_dir() -> PathBuf {
+    std::env::var_os("RAPINA_EXAMPLES_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_EXAMPLES_DIR))
+}
+
+/// One side of a recorded exchange.
+pub(crate) struct ExchangeParts<'a> {
+    pub(crate) method: &'a Method,
+    pub(crate) path: &'a str,
+    pub(crate) status: Option<StatusCode>,
+    pub(crate) headers: &'a HeaderMap,
+    pub(crate) body: &'a Bytes,
+}
+
+#[derive(Serialize)]
+struct RecordedExample {
+    method: String,
+    route: String,
+    handler: String,
+    request: RecordedMessage,
+    response: RecordedMessage,
+}
+
+#[derive(Serialize)]
+struct RecordedMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<serde_json::Value>,
+}
+
+impl RecordedExample {
+    fn new(route: MatchedRoute, request: ExchangeParts<'_>, response: ExchangeParts<'_>) -> Self {
+        Self {
+            method: request.method.to_string(),
+            route: route.pattern,
+            handler: route.name,
+            request: RecordedMessage::new(&request, true),
+            response: RecordedMessage::new(&response, false),
+        }
+    }
+
+    /// Writes the example under a name derived from its content, so
+    /// identical exchanges share one file and parallel test binaries never
+    /// write to the same one.
+    fn write(&self, dir: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        let digest = Sha256::digest(&json);
+        let hash: String = digest[..6].iter().map(|b| format!("{:02x}", b)).collect();
+        let slug: String = self
+            .route
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let name = format!(
+            "{}{}-{}-{}.json",
+            self.method.to_ascii_lowercase(),
+            slug.trim_end_matches('_'),
+            self.response.status.unwrap_or_default(),
+            hash
+        );
+
+        std::fs::create_dir_all(dir)?;
+        let tmp = dir.join(format!(".{}.{}", name, std::process::id()));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, dir.join(name))
+    }
+}
+
+impl RecordedMessage {
+    fn new(parts: &ExchangeParts<'_>, is_request: bool) -> Self {
+        Self {
+            path: is_request.then(|| parts.path.to_string()),
+            status: parts.status.map(|s| s.as_u16()),
+            headers: parts
+                .headers
+                .iter()
+                .filter(|(name, _)| {
+                    !is_sensitive(name.as_str()) && !VOLATILE_HEADERS.contains(&name.as_str())
+                })
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: example_body(parts.body),
+        }
+    }
+}
+
+fn is_sensitive(header: &str) -> bool {
+    SENSITIVE_HEADERS.contains(&header)
+        || SENSITIVE_FRAGMENTS
+            .iter()
+            .any(|fragment| header.contains(fragment))
+}
+
+/// The body as JSON when it parses, as text otherwise. Bodies over
+/// [`MAX_EXAMPLE_BODY_BYTES`] are cut on a character boundary and kept as
+/// text, since a cut JSON document no longer parses.
+fn example_body(body: &Bytes) -> Option<serde_json::Value> {
+    if body.is_empty() {
+        return None;
+    }
+    if body.len() > MAX_EXAMPLE_BODY_BYTES {
+        let text = String::from_utf8_lossy(&body[..MAX_EXAMPLE_BODY_BYTES]);
+        let text = text.trim_end_matches('\u{FFFD}');
+        return Some(serde_json::Value::String(format!("{}...", text)));
+    }
+    Some(
+        serde_json::from_slice(body)
+            .unwrap_or_else(|_| String::from_utf8_lossy(body).into_owned().into()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitive_headers() {
+        assert!(is_sensitive("authorization"));
+        assert!(is_sensitive("cookie"));
+        assert!(is_sensitive("x-csrf-token"));
+        assert!(is_sensitive("x-api-key"));
+        assert!(!is_sensitive("content-type"));
+        assert!(!is_sensitive("accept"));
+    }
+
+    #[test]
+    fn test_example_body() {
+        assert_eq!(example_body(&Bytes::new()), None);
+        assert_eq!(
+            example_body(&Bytes::from(r#"{"id":1}"#)),
+            Some(serde_json::json!({ "id": 1 }))
+        );
+        assert_eq!(
+            example_body(&Bytes::from("hello")),
+            Some(serde_json::json!("hello"))
+        );
+
+        let long = Bytes::from(format!("[{}]", "1,".repeat(MAX_EXAMPLE_BODY_BYTES)));
+        let body = example_body(&long).unwrap();
+        let text = body.as_str().unwrap();
+        assert_eq!(text.len(), MAX_EXAMPLE_BODY_BYTES + 3);
+        assert!(text.ends_with("..."));
+    }
+
+    #[test]
+    fn test_cut_body_keeps_whole_characters() {
+        let long = Bytes::from(format!("x{}", "é".repeat(MAX_EXAMPLE_BODY_BYTES)));
+        let body = example_body(&long).unwrap();
+        assert!(!body.as_str().unwrap().contains('\u{FFFD}'));
+    }
+}
@@ -0,0 +1,432 @@
+//! Property-based request fuzzing.
+//!
+//! Generates requests with random methods, paths, query strings, headers
+//! and bodies, shaped after an app's own routes, and checks that every one
+//! gets an HTTP response. Client and server errors are fine; a handler or
+//! extractor that panics, or a request that never gets an answer, fails
+//! the run with the smallest request proptest could shrink it to.
+
+use std::time::Duration;
+
+use http::StatusCode;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::TestClient;
+use crate::app::Rapina;
+
+/// A request as raw parts, so it can carry bytes a well-formed
+/// [`http::Request`] can't, such as invalid UTF-8 in the path or null bytes
+/// in a header value.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FuzzRequest {
+    /// Request method token, not necessarily a standard one.
+    pub method: String,
+    /// Request target: path and optional query string.
+    pub target: Vec<u8>,
+    /// Header names and raw values.
+    pub headers: Vec<(String, Vec<u8>)>,
+    /// Request body, sent with a matching `Content-Length`.
+    pub body: Vec<u8>,
+}
+
+impl FuzzRequest {
+    /// Serializes the request as HTTP/1.1, closing the connection after the
+    /// response.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.target.len() + self.body.len() + 128);
+        bytes.extend_from_slice(self.method.as_bytes());
+        bytes.push(b' ');
+        bytes.extend_from_slice(&self.target);
+        bytes.extend_from_slice(b" HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n");
+        for (name, value) in &self.headers {
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(b": ");
+            bytes.extend_from_slice(value);
+            bytes.extend_from_slice(b"\r\n");
+        }
+        bytes.extend_from_slice(format!("content-length: {}\r\n\r\n", self.body.len()).as_bytes());
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+}
+
+// Lossy, so a shrunk failure reads as the request that caused it
+impl std::fmt::Debug for FuzzRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let headers: Vec<(&str, String)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), String::from_utf8_lossy(value).into_owned()))
+            .collect();
+        f.debug_struct("FuzzRequest")
+            .field("method", &self.method)
+            .field("target", &String::from_utf8_lossy(&self.target))
+            .field("headers", &headers)
+            .field("body", &String::from_utf8_lossy(&self.body))
+            .finish()
+    }
+}
+
+/// Sends `request` to the server behind `client` and returns the response
+/// status.
+///
+/// Fails when the connection closes without a response, which is what a
+/// panicking handler looks like from the outside, or when no response
+/// arrives within `timeout`.
+pub async fn fuzz_request(
+    client: &TestClient,
+    request: &FuzzRequest,
+    timeout: Duration,
+) -> Result<StatusCode, String> {
+    let exchange = async {
+        let mut stream = TcpStream::connect(client.addr())
+            .await
+            .map_err(|e| format!("failed to connect: {}", e))?;
+        // The server may answer and close before reading the whole body
+        let _ = stream.write_all(&request.to_bytes()).await;
+
+        let mut response = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            if let Some(end) = response.windows(2).position(|w| w == b"\r\n") {
+                return status_from_line(&response[..end]);
+            }
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => {
+                    return Err(
+                        "connection closed without a response; did a handler panic?".to_string()
+                    );
+                }
+                Ok(n) => response.extend_from_slice(&buf[..n]),
+            }
+        }
+    };
+
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .unwrap_or_else(|_| Err(format!("no response within {:?}", timeout)))
+}
+
+fn status_from_line(line: &[u8]) -> Result<StatusCode, String> {
+    let line = String::from_utf8_lossy(line);
+    line.split(' ')
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .ok_or_else(|| format!("malformed status line {:?}", line))
+}
+
+/// How long [`fuzz_app`] runs and how patient it is.
+#[derive(Debug, Clone)]
+pub struct FuzzConfig {
+    cases: u32,
+    timeout: Duration,
+}
+
+/// Overrides the default number of cases, for longer runs in CI.
+pub const FUZZ_CASES_ENV: &str = "RAPINA_FUZZ_CASES";
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        let cases = std::env::var(FUZZ_CASES_ENV)
+            .ok()
+            .and_then(|cases| cases.parse().ok())
+            .unwrap_or(256);
+        Self {
+            cases,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl FuzzConfig {
+    /// Number of random requests to send. Defaults to 256, or the value
+    /// of the `RAPINA_FUZZ_CASES` environment variable.
+    pub fn cases(mut self, cases: u32) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// How long to wait for each response. Defaults to 5 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Sends random requests shaped after `app`'s routes and panics with the
+/// smallest failing request if any of them goes unanswered.
+///
+/// Runs its own Tokio runtime, so call it from a plain `#[test]`, not from
+/// `#[tokio::test]`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rapina::prelude::*;
+/// use rapina::testing::{FuzzConfig, fuzz_app};
+///
+/// #[get("/users/:id")]
+/// async fn get_user(id: Path<u64>) -> String {
+///     format!("user {}", id.into_inner())
+/// }
+///
+/// #[test]
+/// fn handlers_never_panic() {
+///     let app = Rapina::new().router(Router::new().get("/users/:id", get_user));
+///     fuzz_app(app, FuzzConfig::default().cases(500));
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if the app fails to start, or with proptest's report of the
+/// minimal failing input when a request gets no response.
+pub fn fuzz_app(mut app: Rapina, config: FuzzConfig) {
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start the fuzzing runtime");
+
+    app.serve_test_routes = true;
+    let app = app.prepare().unwrap_or_else(|e| panic!("{}", e));
+    let patterns: Vec<String> = app.router.routes().into_iter().map(|r| r.path).collect();
+    let client = runtime.block_on(TestClient::from_parts(
+        app.router,
+        app.state,
+        app.middlewares,
+    ));
+
+    let mut runner = TestRunner::new(Config {
+        cases: config.cases,
+        failure_persistence: None,
+        ..Config::default()
+    });
+    let result = runner.run(&fuzz_requests(&patterns), |request| {
+        runtime
+            .block_on(fuzz_request(&client, &request, config.timeout))
+            .map(|_| ())
+            .map_err(TestCaseError::fail)
+    });
+    if let Err(error) = result {
+        panic!("{}", error);
+    }
+}
+
+/// Random requests aimed at `patterns`, route paths like `/users/:id`.
+///
+/// Most requests follow one of the patterns with random parameter values,
+/// so they get past routing and into extractors; the rest use random paths.
+pub fn fuzz_requests(patterns: &[String]) -> BoxedStrategy<FuzzRequest> {
+    (method(), target(patterns), headers(), body())
+        .prop_map(|(method, target, headers, body)| FuzzRequest {
+            method,
+            target,
+            headers,
+            body,
+        })
+        .boxed()
+}
+
+fn method() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => prop::sample::select(vec!["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"])
+            .prop_map(str::to_string),
+        1 => "[A-Z]{1,12}",
+        1 => "[!-~]{1,12}",
+    ]
+}
+
+fn target(patterns: &[String]) -> impl Strategy<Value = Vec<u8>> + use<> {
+    let path = if patterns.is_empty() {
+        random_path().boxed()
+    } else {
+        let patterns = patterns.to_vec();
+        prop_oneof![
+            4 => (prop::sample::select(patterns), prop::collection::vec(segment(), 8))
+                .prop_map(|(pattern, values)| fill_pattern(&pattern, &values)),
+            1 => random_path(),
+        ]
+        .boxed()
+    };
+    (path, prop::option::of(query())).prop_map(|(mut path, query)| {
+        if let Some(query) = query {
+            path.push(b'?');
+            path.extend(query);
+        }
+        path
+    })
+}
+
+/// Replaces each `:param` segment of `pattern` with one of `values`.
+fn fill_pattern(pattern: &str, values: &[Vec<u8>]) -> Vec<u8> {
+    let mut values = values.iter().cycle();
+    let segments: Vec<Vec<u8>> = pattern
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with(':') {
+                values.next().cloned().unwrap_or_default()
+            } else {
+                segment.as_bytes().to_vec()
+            }
+        })
+        .collect();
+    segments.join(&b'/')
+}
+
+fn random_path() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(segment(), 0..6).prop_map(|segments| {
+        let mut path = Vec::new();
+        for segment in segments {
+            path.push(b'/');
+            path.extend(segment);
+        }
+        if path.is_empty() {
+            path.push(b'/');
+        }
+        path
+    })
+}
+
+/// A path segment or parameter value: plain words, numbers out of range,
+/// malformed and non-UTF-8 percent escapes, null bytes and overlong runs.
+fn segment() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        3 => "[a-zA-Z0-9_.~-]{0,16}".prop_map(String::into_bytes),
+        2 => "-?[0-9]{1,40}".prop_map(String::into_bytes),
+        2 => "(%[0-9A-Fa-f]{2}|%[0-9A-Za-z+]{0,2}|%[89A-Fa-f][0-9A-Fa-f]|[a-z])*"
+            .prop_map(String::into_bytes),
+        1 => "%00|%2[Ff]|%C0%AF|%FF%FE|\\.\\.".prop_map(String::into_bytes),
+        1 => (1usize..16_384).prop_map(|len| vec![b'a'; len]),
+        1 => raw_bytes(32),
+    ]
+}
+
+fn query() -> impl Strategy<Value = Vec<u8>> {
+    let pair = ("[a-z_\\[\\]]{0,8}", segment()).prop_map(|(key, value)| {
+        let mut pair = key.into_bytes();
+        pair.push(b'=');
+        pair.extend(value);
+        pair
+    });
+    prop_oneof![
+        3 => prop::collection::vec(pair, 0..6).prop_map(|pairs| pairs.join(&b'&')),
+        1 => raw_bytes(64),
+    ]
+}
+
+/// Bytes that can't end the request line or a header early.
+fn raw_bytes(max: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(
+        any::<u8>().prop_filter("line break", |b| !matches!(b, b'\r' | b'\n')),
+        0..max,
+    )
+}
+
+fn headers() -> impl Strategy<Value = Vec<(String, Vec<u8>)>> {
+    // Framing headers are left to `FuzzRequest::to_bytes`, so the server
+    // never waits for a body that isn't coming
+    let name = prop::sample::select(vec![
+        "content-type",
+        "accept",
+        "accept-encoding",
+        "accept-language",
+        "authorization",
+        "cookie",
+        "origin",
+        "if-none-match",
+        "idempotency-key",
+        "x-request-id",
+        "x-forwarded-for",
+        "x-fuzz",
+    ]);
+    let value = prop_oneof![
+        3 => prop::sample::select(vec![
+            "application/json",
+            "application/json; charset=utf-8",
+            "application/x-www-form-urlencoded",
+            "multipart/form-data; boundary=x",
+            "text/plain",
+            "*/*",
+            "gzip, br;q=0.5",
+            "Bearer ",
+            "Bearer a.b.c",
+            "session=abc; theme=dark",
+            "=; ;=;",
+        ])
+        .prop_map(|value| value.as_bytes().to_vec()),
+        1 => raw_bytes(64),
+        1 => (1usize..8_192).prop_map(|len| vec![b'x'; len]),
+    ];
+    prop::collection::vec((name.prop_map(str::to_string), value), 0..6)
+}
+
+/// JSON-ish and form-ish bodies, deep nesting, and arbitrary bytes.
+fn body() -> impl Strategy<Value = Vec<u8>> {
+    let scalar = prop_oneof![
+        Just("null".to_string()),
+        Just("true".to_string()),
+        "-?[0-9]{1,40}(\\.[0-9]{1,5})?(e-?[0-9]{1,4})?",
+        "\"([a-z ]|\\\\u[0-9a-fA-F]{4}|\\\\[\"\\\\nt]|\\\\u[dD][89abAB][0-9a-fA-F]{2}){0,16}\"",
+    ];
+    let json = scalar.prop_recursive(4, 32, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6)
+                .prop_map(|items| format!("[{}]", items.join(","))),
+            prop::collection::vec(("[a-z_]{1,8}", inner), 0..6).prop_map(|fields| {
+                let fields: Vec<String> = fields
+                    .into_iter()
+                    .map(|(key, value)| format!("\"{}\":{}", key, value))
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            }),
+        ]
+    });
+    prop_oneof![
+        2 => Just(Vec::new()),
+        4 => json.prop_map(String::into_bytes),
+        1 => (1usize..4_096).prop_map(|depth| "[".repeat(depth).into_bytes()),
+        1 => "([a-z]{0,6}=[a-z0-9%+]{0,8}&?){0,6}".prop_map(String::into_bytes),
+        2 => prop::collection::vec(any::<u8>(), 0..2_048),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_bytes_frames_body() {
+        let request = FuzzRequest {
+            method: "POST".into(),
+            target: b"/users/%FF?x=1".to_vec(),
+            headers: vec![("content-type".into(), b"application/json".to_vec())],
+            body: b"{}".to_vec(),
+        };
+        assert_eq!(
+            request.to_bytes(),
+            b"POST /users/%FF?x=1 HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\
+              content-type: application/json\r\ncontent-length: 2\r\n\r\n{}"
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_fill_pattern() {
+        let values = vec![b"7".to_vec(), b"%00".to_vec()];
+        assert_eq!(
+            fill_pattern("/users/:id/posts/:post_id", &values),
+            b"/users/7/posts/%00".to_vec()
+        );
+        assert_eq!(fill_pattern("/health", &values), b"/health".to_vec());
+    }
+
+    #[test]
+    fn test_status_from_line() {
+        assert_eq!(
+            status_from_line(b"HTTP/1.1 405 Method Not Allowed").unwrap(),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert!(status_from_line(b"garbage").is_err());
+    }
+}
@@ -4,5 +4,13 @@
 //! starting a full HTTP server.
 
 mod client;
+mod contract;
+mod examples;
+#[cfg(feature = "fuzz")]
+mod fuzz;
 
 pub use client::{TestClient, TestRequestBuilder, TestResponse};
+pub use contract::Contract;
+pub use examples::{DEFAULT_EXAMPLES_DIR, MAX_EXAMPLE_BODY_BYTES};
+#[cfg(feature = "fuzz")]
+pub use fuzz::{FUZZ_CASES_ENV, FuzzConfig, FuzzRequest, fuzz_app, fuzz_request, fuzz_requests};
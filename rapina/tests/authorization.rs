@@ -0,0 +1,225 @@
+//! Integration tests for authorization policies.
+
+use std::collections::HashSet;
+
+use http::StatusCode;
+use http::request::Parts;
+use rapina::auth::Claims;
+use rapina::prelude::*;
+use rapina::state::AppState;
+use rapina::testing::TestClient;
+use serde_json::Value;
+
+const SECRET: &str = "test-secret-policies";
+
+/// User ids allowed through `AdminOnly`.
+struct Admins(HashSet<String>);
+
+#[derive(Default)]
+struct AdminOnly;
+
+impl Policy for AdminOnly {
+    fn authorize(&self, claims: &Claims, _parts: &Parts, state: &AppState) -> Result<()> {
+        let admins = state
+            .get::<Admins>()
+            .ok_or_else(|| Error::internal("Admins is not registered"))?;
+        if admins.0.contains(&claims.sub) {
+            Ok(())
+        } else {
+            Err(Error::forbidden("admins only"))
+        }
+    }
+}
+
+/// Allows users acting on their own `/users/:id`.
+struct Owner;
+
+impl Policy for Owner {
+    fn authorize(&self, claims: &Claims, parts: &Parts, _state: &AppState) -> Result<()> {
+        let id = parts.uri.path().rsplit('/').next().unwrap_or_default();
+        if id == claims.sub {
+            Ok(())
+        } else {
+            Err(Error::forbidden("not the owner"))
+        }
+    }
+}
+
+/// Rejects users whose id starts with `banned-`.
+struct Banned;
+
+impl Policy for Banned {
+    fn authorize(&self, claims: &Claims, _parts: &Parts, _state: &AppState) -> Result<()> {
+        if claims.sub.starts_with("banned-") {
+            Ok(())
+        } else {
+            Err(Error::forbidden("not banned"))
+        }
+    }
+}
+
+#[get("/admin/stats")]
+#[authorize(AdminOnly)]
+async fn admin_stats() -> &'static str {
+    "stats"
+}
+
+#[delete("/users/:id")]
+#[authorize(any(AdminOnly, Owner))]
+async fn delete_user(id: Path<String>) -> String {
+    format!("deleted {}", id.into_inner())
+}
+
+#[authorize(all(Owner, not(Banned)))]
+#[put("/users/:id")]
+async fn update_user(id: Path<String>) -> String {
+    format!("updated {}", id.into_inner())
+}
+
+#[get("/admin/report")]
+async fn admin_report(_: Authorize<AdminOnly>) -> &'static str {
+    "report"
+}
+
+async fn client(public: bool) -> TestClient {
+    let mut app = Rapina::new()
+        .with_introspection(false)
+        .with_auth(AuthConfig::new(SECRET, 3600))
+        .state(Admins(HashSet::from(["alice".to_string()])));
+    if public {
+        app = app.public_route("GET", "/admin/stats");
+    }
+    let app = app.router(
+        Router::new()
+            .get("/admin/stats", admin_stats)
+            .get("/admin/report", admin_report)
+            .delete("/users/:id", delete_user)
+            .put("/users/:id", update_user),
+    );
+    TestClient::new(app).await
+}
+
+fn token(user: &str) -> String {
+    let token = AuthConfig::new(SECRET, 3600).create_token(user).unwrap();
+    format!("Bearer {}", token)
+}
+
+#[tokio::test]
+async fn test_policy_allows() {
+    let client = client(false).await;
+
+    let response = client
+        .get("/admin/stats")
+        .header("authorization", &token("alice"))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "stats");
+}
+
+#[tokio::test]
+async fn test_policy_denial_names_the_policy() {
+    let client = client(false).await;
+
+    let response = client
+        .get("/admin/stats")
+        .header("authorization", &token("bob"))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: Value = response.json();
+    assert_eq!(body["error"]["code"], "ADMIN_ONLY");
+    assert_eq!(body["error"]["message"], "admins only");
+}
+
+#[tokio::test]
+async fn test_policy_without_claims_is_unauthorized() {
+    let client = client(true).await;
+
+    let response = client.get("/admin/stats").send().await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_any_allows_when_one_policy_does() {
+    let client = client(false).await;
+
+    let admin = client
+        .delete("/users/bob")
+        .header("authorization", &token("alice"))
+        .send()
+        .await;
+    assert_eq!(admin.status(), StatusCode::OK);
+    assert_eq!(admin.text(), "deleted bob");
+
+    let owner = client
+        .delete("/users/bob")
+        .header("authorization", &token("bob"))
+        .send()
+        .await;
+    assert_eq!(owner.status(), StatusCode::OK);
+
+    let other = client
+        .delete("/users/bob")
+        .header("authorization", &token("carol"))
+        .send()
+        .await;
+    assert_eq!(other.status(), StatusCode::FORBIDDEN);
+    let body: Value = other.json();
+    assert_eq!(body["error"]["code"], "ADMIN_ONLY");
+}
+
+#[tokio::test]
+async fn test_all_and_not_compose() {
+    let client = client(false).await;
+
+    let owner = client
+        .put("/users/bob")
+        .header("authorization", &token("bob"))
+        .send()
+        .await;
+    assert_eq!(owner.status(), StatusCode::OK);
+    assert_eq!(owner.text(), "updated bob");
+
+    let banned = client
+        .put("/users/banned-eve")
+        .header("authorization", &token("banned-eve"))
+        .send()
+        .await;
+    assert_eq!(banned.status(), StatusCode::FORBIDDEN);
+    let body: Value = banned.json();
+    assert_eq!(body["error"]["code"], "NOT_BANNED");
+
+    let stranger = client
+        .put("/users/bob")
+        .header("authorization", &token("carol"))
+        .send()
+        .await;
+    let body: Value = stranger.json();
+    assert_eq!(body["error"]["code"], "OWNER");
+}
+
+#[tokio::test]
+async fn test_authorize_extractor() {
+    let client = client(false).await;
+
+    let allowed = client
+        .get("/admin/report")
+        .header("authorization", &token("alice"))
+        .send()
+        .await;
+    assert_eq!(allowed.status(), StatusCode::OK);
+    assert_eq!(allowed.text(), "report");
+
+    let denied = client
+        .get("/admin/report")
+        .header("authorization", &token("bob"))
+        .send()
+        .await;
+    assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+    let body: Value = denied.json();
+    assert_eq!(body["error"]["code"], "ADMIN_ONLY");
+}
@@ -0,0 +1,317 @@
+//! Integration tests for streaming body reads against a real listener.
+//!
+//! These drive the server with a raw TCP client so the body can be sent in
+//! pieces, without a Content-Length, or not at all.
+
+use http::StatusCode;
+use rapina::middleware::BodyLimitMiddleware;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+#[derive(Deserialize)]
+struct Payload {
+    name: String,
+}
+
+fn app(limit: BodyLimitMiddleware) -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .middleware(limit)
+        .router(payload_router())
+}
+
+fn payload_router() -> Router {
+    use rapina::extract::FromRequest;
+
+    Router::new()
+        .route(
+            http::Method::POST,
+            "/json",
+            |req, params, state| async move {
+                Json::<Payload>::from_request(req, &params, &state)
+                    .await
+                    .map(|body| body.into_inner().name)
+            },
+        )
+        .route(
+            http::Method::POST,
+            "/form",
+            |req, params, state| async move {
+                Form::<Payload>::from_request(req, &params, &state)
+                    .await
+                    .map(|body| body.into_inner().name)
+            },
+        )
+}
+
+/// Reads the status code of the first response on the connection.
+async fn read_status(stream: &mut TcpStream) -> u16 {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut chunk).await.expect("read response");
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    })
+    .await
+    .expect("server did not respond in time");
+
+    let head = String::from_utf8_lossy(&buf);
+    let status_line = head.lines().next().expect("empty response");
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or_else(|| panic!("malformed status line: {status_line}"))
+}
+
+async fn connect(client: &TestClient) -> TcpStream {
+    TcpStream::connect(client.addr()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_chunked_body_over_limit_is_rejected_before_it_ends() {
+    let client = TestClient::new(app(BodyLimitMiddleware::new(16))).await;
+    let mut stream = connect(&client).await;
+
+    // No Content-Length, and the terminating chunk is never sent: the server
+    // has to reject on the chunk that crosses the limit.
+    let body = format!(r#"{{"name": "{}"}}"#, "a".repeat(64));
+    let request = format!(
+        "POST /json HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    assert_eq!(read_status(&mut stream).await, 413);
+}
+
+#[tokio::test]
+async fn test_chunked_body_under_limit_is_accepted() {
+    let client = TestClient::new(app(BodyLimitMiddleware::new(64))).await;
+    let mut stream = connect(&client).await;
+
+    let request = "POST /json HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\n\r\n9\r\n{\"name\": \r\n5\r\n\"ok\"}\r\n0\r\n\r\n";
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    assert_eq!(read_status(&mut stream).await, 200);
+}
+
+#[tokio::test]
+async fn test_form_body_over_limit_is_rejected() {
+    let client = TestClient::new(app(BodyLimitMiddleware::new(16))).await;
+    let mut stream = connect(&client).await;
+
+    let body = format!("name={}", "a".repeat(64));
+    let request = format!(
+        "POST /form HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/x-www-form-urlencoded\r\ntransfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    assert_eq!(read_status(&mut stream).await, 413);
+}
+
+#[tokio::test]
+async fn test_content_length_over_limit_is_rejected_upfront() {
+    let client = TestClient::new(app(BodyLimitMiddleware::new(16))).await;
+
+    let response = client
+        .post("/json")
+        .header("content-type", "application/json")
+        .body(format!(r#"{{"name": "{}"}}"#, "a".repeat(64)))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "PAYLOAD_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn test_slow_body_times_out() {
+    let limit = BodyLimitMiddleware::new(1024).read_timeout(Duration::from_millis(200));
+    let client = TestClient::new(app(limit)).await;
+    let mut stream = connect(&client).await;
+
+    // Promise 20 bytes and only send a few of them.
+    let request = "POST /json HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/json\r\ncontent-length: 20\r\n\r\n{\"na";
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    assert_eq!(read_status(&mut stream).await, 408);
+}
+
+#[tokio::test]
+async fn test_body_arriving_within_deadline_is_accepted() {
+    let limit = BodyLimitMiddleware::new(1024).read_timeout(Duration::from_secs(2));
+    let client = TestClient::new(app(limit)).await;
+    let mut stream = connect(&client).await;
+
+    let head = "POST /json HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/json\r\ncontent-length: 14\r\n\r\n";
+    stream.write_all(head.as_bytes()).await.unwrap();
+    for piece in ["{\"name\"", ": \"ok\"}"] {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stream.write_all(piece.as_bytes()).await.unwrap();
+    }
+
+    assert_eq!(read_status(&mut stream).await, 200);
+}
+
+fn json_body(len: usize) -> String {
+    let padding = len - r#"{"name": ""}"#.len();
+    format!(r#"{{"name": "{}"}}"#, "a".repeat(padding))
+}
+
+#[tokio::test]
+async fn test_default_body_limit_applies() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(payload_router());
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/json")
+        .header("content-type", "application/json")
+        .body(json_body(1024 * 1024 + 1))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "PAYLOAD_TOO_LARGE");
+    assert!(json["trace_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_body_just_under_limit_is_accepted() {
+    let client = TestClient::new(app(BodyLimitMiddleware::new(64))).await;
+
+    let response = client
+        .post("/json")
+        .header("content-type", "application/json")
+        .body(json_body(64))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client
+        .post("/json")
+        .header("content-type", "application/json")
+        .body(json_body(65))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_without_body_limit() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .without_body_limit()
+        .router(payload_router());
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/json")
+        .header("content-type", "application/json")
+        .body(json_body(2 * 1024 * 1024))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_route_body_limit_overrides_app_limit() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_body_limit(BodyLimitMiddleware::new(64))
+        .router(
+            Router::new()
+                .nest("/small", payload_router().body_limit(16))
+                .nest("/large", payload_router().body_limit(256)),
+        );
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/large/json")
+        .header("content-type", "application/json")
+        .body(json_body(200))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client
+        .post("/small/json")
+        .header("content-type", "application/json")
+        .body(json_body(32))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // The route limit also caps bodies without a Content-Length
+    let mut stream = connect(&client).await;
+    let body = json_body(32);
+    let request = format!(
+        "POST /small/json HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    assert_eq!(read_status(&mut stream).await, 413);
+}
+
+#[tokio::test]
+async fn test_route_body_limit_without_app_limit() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .without_body_limit()
+        .router(payload_router().body_limit(16));
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/json")
+        .header("content-type", "application/json")
+        .body(json_body(32))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_path_body_limit_middleware_replaces_app_limit() {
+    use rapina::extract::FromRequest;
+
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_body_limit(BodyLimitMiddleware::new(16))
+        .router(Router::new().at("/upload", |r| {
+            r.route(http::Method::POST, |req, params, state| async move {
+                Json::<Payload>::from_request(req, &params, &state)
+                    .await
+                    .map(|body| body.into_inner().name)
+            })
+            .middleware(BodyLimitMiddleware::new(128))
+        }));
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/upload")
+        .header("content-type", "application/json")
+        .body(json_body(100))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
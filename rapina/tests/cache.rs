@@ -0,0 +1,152 @@
+//! Integration tests for response caching.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::events::{Event, Events};
+use rapina::middleware::CacheConfig;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+static STATS_CALLS: AtomicUsize = AtomicUsize::new(0);
+static QUERY_CALLS: AtomicUsize = AtomicUsize::new(0);
+static FEED_CALLS: AtomicUsize = AtomicUsize::new(0);
+static MISSING_CALLS: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_CALLS: AtomicUsize = AtomicUsize::new(0);
+static OVERRIDE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[get("/stats", cache = "60s")]
+async fn stats() -> String {
+    let calls = STATS_CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+    format!("calls: {}", calls)
+}
+
+#[get("/search", cache = "60s")]
+async fn search() -> String {
+    let calls = QUERY_CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+    format!("calls: {}", calls)
+}
+
+#[get("/feed", cache = "1h", invalidate_on = ["post"])]
+async fn feed() -> String {
+    let calls = FEED_CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+    format!("calls: {}", calls)
+}
+
+#[post("/posts")]
+async fn create_post(events: Events) -> StatusCode {
+    events.after_commit(Event::created("post", 1)).await;
+    StatusCode::CREATED
+}
+
+#[get("/missing", cache = "60s")]
+async fn missing() -> Result<String> {
+    MISSING_CALLS.fetch_add(1, Ordering::SeqCst);
+    Err(Error::not_found("nothing here"))
+}
+
+#[get("/global")]
+async fn global() -> String {
+    let calls = GLOBAL_CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+    format!("calls: {}", calls)
+}
+
+#[get("/override", cache = "5m")]
+async fn override_ttl() -> String {
+    let calls = OVERRIDE_CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+    format!("calls: {}", calls)
+}
+
+#[tokio::test]
+async fn test_cached_handler_runs_once() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/stats", stats));
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/stats").send().await;
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(first.headers()["cache-control"], "max-age=60");
+    assert_eq!(first.text(), "calls: 1");
+
+    let second = client.get("/stats").send().await;
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(second.text(), "calls: 1");
+    assert!(
+        second.headers()["cache-control"]
+            .to_str()
+            .unwrap()
+            .starts_with("max-age=")
+    );
+
+    assert_eq!(STATS_CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_query_string_is_part_of_the_key() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/search", search));
+    let client = TestClient::new(app).await;
+
+    assert_eq!(client.get("/search?q=a").send().await.text(), "calls: 1");
+    assert_eq!(client.get("/search?q=b").send().await.text(), "calls: 2");
+    assert_eq!(client.get("/search?q=a").send().await.text(), "calls: 1");
+    assert_eq!(QUERY_CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_events_invalidate_dependent_handlers() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/feed", feed).post("/posts", create_post));
+    let client = TestClient::new(app).await;
+
+    assert_eq!(client.get("/feed").send().await.text(), "calls: 1");
+    assert_eq!(client.get("/feed").send().await.text(), "calls: 1");
+
+    let created = client.post("/posts").send().await;
+    assert_eq!(created.status(), StatusCode::CREATED);
+
+    assert_eq!(client.get("/feed").send().await.text(), "calls: 2");
+    assert_eq!(FEED_CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_errors_are_not_cached() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/missing", missing));
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/missing").send().await;
+    assert_eq!(first.status(), StatusCode::NOT_FOUND);
+    assert!(first.headers().get("cache-control").is_none());
+
+    client.get("/missing").send().await;
+    assert_eq!(MISSING_CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_handler_ttl_wins_over_global_cache() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_cache(CacheConfig::new(Duration::from_secs(30)))
+        .router(
+            Router::new()
+                .get("/global", global)
+                .get("/override", override_ttl),
+        );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/global").send().await;
+    assert_eq!(response.headers()["cache-control"], "max-age=30");
+    assert_eq!(client.get("/global").send().await.text(), "calls: 1");
+    assert_eq!(GLOBAL_CALLS.load(Ordering::SeqCst), 1);
+
+    let response = client.get("/override").send().await;
+    assert_eq!(response.headers()["cache-control"], "max-age=300");
+    assert_eq!(client.get("/override").send().await.text(), "calls: 1");
+    assert_eq!(OVERRIDE_CALLS.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,130 @@
+//! Integration tests for `CachedQuery`.
+
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::database::{CachedQuery, Db, DbError};
+use rapina::events::{Event, Events};
+use rapina::prelude::*;
+use rapina::sea_orm::{
+    ActiveModelTrait, ConnectionTrait, Database, DatabaseConnection, EntityTrait, QueryOrder,
+    Schema, Set, TransactionTrait,
+};
+use rapina::testing::TestClient;
+
+schema! {
+    #[timestamps(none)]
+    Plan {
+        name: String,
+    }
+}
+
+use plan::{ActiveModel, Model};
+
+#[derive(Deserialize, JsonSchema)]
+struct CreatePlan {
+    name: String,
+}
+
+#[get("/plans")]
+async fn list_plans(db: Db, plans: State<CachedQuery<Vec<Model>>>) -> Result<Json<Vec<Model>>> {
+    Ok(Json(plans.into_inner().get(db.read()).await?))
+}
+
+#[post("/plans")]
+async fn create_plan(db: Db, events: Events, body: Json<CreatePlan>) -> Result<Json<Model>> {
+    let item = ActiveModel {
+        name: Set(body.into_inner().name),
+        ..Default::default()
+    };
+    let txn = db.conn().begin().await.map_err(DbError)?;
+    let result = item.insert(&txn).await.map_err(DbError)?;
+    let event = Event::created("plan", result.id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(result))
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let backend = conn.get_database_backend();
+    let schema = Schema::new(backend);
+    conn.execute(backend.build(&schema.create_table_from_entity(Plan)))
+        .await
+        .unwrap();
+    ActiveModel {
+        name: Set("free".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    conn
+}
+
+async fn client() -> (TestClient, CachedQuery<Vec<Model>>, Arc<AtomicUsize>) {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let plans = CachedQuery::new(
+        move |db| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move { Plan::find().order_by_asc(plan::Column::Id).all(&db).await }
+        },
+        Duration::from_secs(300),
+    )
+    .invalidate_on("plan");
+
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(setup_db().await)
+        .cached_query(plans.clone())
+        .router(
+            Router::new()
+                .get("/plans", list_plans)
+                .post("/plans", create_plan),
+        );
+    (TestClient::new(app).await, plans, loads)
+}
+
+#[tokio::test]
+async fn test_cached_query_hits_database_once() {
+    let (client, plans, loads) = client().await;
+
+    for _ in 0..25 {
+        let response = client.get("/plans").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let rows: Vec<serde_json::Value> = response.json();
+        assert_eq!(rows.len(), 1);
+    }
+
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    let stats = plans.stats();
+    assert_eq!(stats.loads, 1);
+    assert_eq!(stats.hits, 24);
+    assert!(!stats.stale);
+}
+
+#[tokio::test]
+async fn test_cached_query_invalidated_by_mutation() {
+    let (client, plans, loads) = client().await;
+
+    client.get("/plans").send().await;
+    let response = client
+        .post("/plans")
+        .json(&serde_json::json!({"name": "pro"}))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(plans.stats().stale);
+
+    let rows: Vec<serde_json::Value> = client.get("/plans").send().await.json();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1]["name"], "pro");
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+    assert_eq!(plans.stats().invalidations, 1);
+}
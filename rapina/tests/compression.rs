@@ -0,0 +1,150 @@
+//! Integration tests for response compression.
+
+use std::io::Read;
+
+use http::StatusCode;
+use rapina::middleware::{CompressionAlgorithm, CompressionConfig};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[post("/echo")]
+async fn echo(body: Json<serde_json::Value>) -> Json<serde_json::Value> {
+    body
+}
+
+fn app(config: CompressionConfig) -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .with_compression(config)
+        .router(Router::new().post("/echo", echo))
+}
+
+fn large_payload() -> serde_json::Value {
+    let items: Vec<_> = (0..200)
+        .map(|id| serde_json::json!({ "id": id, "name": format!("item {}", id), "active": true }))
+        .collect();
+    serde_json::json!({ "items": items })
+}
+
+fn gunzip(data: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    decoded
+}
+
+fn unbrotli(data: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    brotli::Decompressor::new(data, 4096)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    decoded
+}
+
+#[tokio::test]
+async fn test_large_json_is_gzipped_and_decodes() {
+    let client = TestClient::new(app(CompressionConfig::default())).await;
+    let payload = large_payload();
+    let original = serde_json::to_vec(&payload).unwrap();
+
+    let response = client
+        .post("/echo")
+        .header("accept-encoding", "gzip")
+        .json(&payload)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    assert_eq!(response.headers()["vary"], "Accept-Encoding");
+    assert!(response.bytes().len() < original.len());
+
+    let decoded: serde_json::Value = serde_json::from_slice(&gunzip(response.bytes())).unwrap();
+    assert_eq!(decoded, payload);
+}
+
+#[tokio::test]
+async fn test_large_json_is_brotli_compressed_and_decodes() {
+    let client = TestClient::new(app(CompressionConfig::default())).await;
+    let payload = large_payload();
+    let original = serde_json::to_vec(&payload).unwrap();
+
+    let response = client
+        .post("/echo")
+        .header("accept-encoding", "gzip, deflate, br")
+        .json(&payload)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "br");
+    assert!(response.bytes().len() < original.len());
+
+    let decoded: serde_json::Value = serde_json::from_slice(&unbrotli(response.bytes())).unwrap();
+    assert_eq!(decoded, payload);
+}
+
+#[tokio::test]
+async fn test_quality_values_steer_negotiation() {
+    let client = TestClient::new(app(CompressionConfig::default())).await;
+
+    let response = client
+        .post("/echo")
+        .header("accept-encoding", "br;q=0.2, gzip;q=0.8")
+        .json(&large_payload())
+        .send()
+        .await;
+
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+}
+
+#[tokio::test]
+async fn test_only_configured_algorithms_are_used() {
+    let config = CompressionConfig::default().algorithms([CompressionAlgorithm::Gzip]);
+    let client = TestClient::new(app(config)).await;
+
+    let response = client
+        .post("/echo")
+        .header("accept-encoding", "br")
+        .json(&large_payload())
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("content-encoding").is_none());
+    assert_eq!(response.json::<serde_json::Value>(), large_payload());
+}
+
+#[tokio::test]
+async fn test_content_type_outside_allowlist_is_not_compressed() {
+    let config = CompressionConfig::default().content_types(["text/*"]);
+    let client = TestClient::new(app(config)).await;
+
+    let response = client
+        .post("/echo")
+        .header("accept-encoding", "gzip")
+        .json(&large_payload())
+        .send()
+        .await;
+
+    assert!(response.headers().get("content-encoding").is_none());
+    assert_eq!(response.json::<serde_json::Value>(), large_payload());
+}
+
+#[tokio::test]
+async fn test_small_json_is_left_alone() {
+    let client = TestClient::new(app(CompressionConfig::default())).await;
+    let payload = serde_json::json!({ "ok": true });
+
+    let response = client
+        .post("/echo")
+        .header("accept-encoding", "gzip, br")
+        .json(&payload)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("content-encoding").is_none());
+    assert_eq!(response.json::<serde_json::Value>(), payload);
+}
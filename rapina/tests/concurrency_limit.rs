@@ -0,0 +1,213 @@
+//! Integration tests for per-route concurrency limits.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::middleware::ConcurrencyLimit;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+use tokio::sync::Semaphore;
+
+/// A limited handler that signals `entered` and then waits until `gate`
+/// opens, next to an unlimited health check.
+fn gated_router(
+    gate: Arc<Semaphore>,
+    entered: Arc<Semaphore>,
+    limit: impl Into<ConcurrencyLimit>,
+) -> Router {
+    Router::new()
+        .route(http::Method::GET, "/report", move |_, _, _| {
+            let gate = gate.clone();
+            let entered = entered.clone();
+            async move {
+                entered.add_permits(1);
+                gate.acquire().await.unwrap().forget();
+                "report"
+            }
+        })
+        .concurrency_limit(limit)
+        .route(http::Method::GET, "/health", |_, _, _| async { "ok" })
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_rejects_excess_requests() {
+    let gate = Arc::new(Semaphore::new(0));
+    let entered = Arc::new(Semaphore::new(0));
+    let app = Rapina::new().with_introspection(false).router(gated_router(
+        gate.clone(),
+        entered.clone(),
+        2,
+    ));
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/report").send();
+    let second = client.get("/report").send();
+    let third = async {
+        let _ = entered.acquire_many(2).await.unwrap();
+        let response = client.get("/report").send().await;
+        gate.add_permits(2);
+        response
+    };
+    let (first, second, third) = tokio::join!(first, second, third);
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(third.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(third.headers().get("retry-after").unwrap(), "1");
+    let body: serde_json::Value = third.json();
+    assert_eq!(body["error"]["code"], "SERVICE_UNAVAILABLE");
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_queues_excess_requests() {
+    let gate = Arc::new(Semaphore::new(0));
+    let entered = Arc::new(Semaphore::new(0));
+    let app = Rapina::new().with_introspection(false).router(gated_router(
+        gate.clone(),
+        entered.clone(),
+        ConcurrencyLimit::new(2).queue(1),
+    ));
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/report").send();
+    let second = client.get("/report").send();
+    let third = async {
+        let _ = entered.acquire_many(2).await.unwrap();
+        client.get("/report").send().await
+    };
+    let release = async {
+        let _ = entered.acquire_many(2).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        gate.add_permits(3);
+    };
+    let (first, second, third, _) = tokio::join!(first, second, third, release);
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(third.status(), StatusCode::OK);
+    assert_eq!(third.text(), "report");
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_custom_status_and_retry_after() {
+    let gate = Arc::new(Semaphore::new(0));
+    let entered = Arc::new(Semaphore::new(0));
+    let limit = ConcurrencyLimit::new(1)
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .retry_after(Duration::from_secs(10));
+    let app = Rapina::new().with_introspection(false).router(gated_router(
+        gate.clone(),
+        entered.clone(),
+        limit,
+    ));
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/report").send();
+    let second = async {
+        let _ = entered.acquire().await.unwrap();
+        let response = client.get("/report").send().await;
+        gate.add_permits(1);
+        response
+    };
+    let (first, second) = tokio::join!(first, second);
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(second.headers().get("retry-after").unwrap(), "10");
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_is_per_route() {
+    let gate = Arc::new(Semaphore::new(0));
+    let entered = Arc::new(Semaphore::new(0));
+    let app = Rapina::new().with_introspection(false).router(gated_router(
+        gate.clone(),
+        entered.clone(),
+        1,
+    ));
+    let client = TestClient::new(app).await;
+
+    let busy = client.get("/report").send();
+    let other = async {
+        let _ = entered.acquire().await.unwrap();
+        let response = client.get("/health").send().await;
+        gate.add_permits(1);
+        response
+    };
+    let (busy, other) = tokio::join!(busy, other);
+
+    assert_eq!(busy.status(), StatusCode::OK);
+    assert_eq!(other.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_skips_routes_added_before() {
+    let gate = Arc::new(Semaphore::new(0));
+    let entered = Arc::new(Semaphore::new(0));
+    let handler_gate = gate.clone();
+    let handler_entered = entered.clone();
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .route(http::Method::GET, "/export", move |_, _, _| {
+                let gate = handler_gate.clone();
+                let entered = handler_entered.clone();
+                async move {
+                    entered.add_permits(1);
+                    gate.acquire().await.unwrap().forget();
+                    "export"
+                }
+            })
+            .route(http::Method::GET, "/report", |_, _, _| async { "report" })
+            .concurrency_limit(1),
+    );
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/export").send();
+    let second = client.get("/export").send();
+    let release = async {
+        // A limited route would reject the second request instead of
+        // letting it in
+        let _ = tokio::time::timeout(Duration::from_secs(5), entered.acquire_many(2)).await;
+        gate.add_permits(2);
+    };
+    let (first, second, _) = tokio::join!(first, second, release);
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_method_router_concurrency_limit() {
+    let gate = Arc::new(Semaphore::new(0));
+    let entered = Arc::new(Semaphore::new(0));
+    let handler_gate = gate.clone();
+    let handler_entered = entered.clone();
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().at("/report", move |r| {
+            r.route(http::Method::GET, move |_, _, _| {
+                let gate = handler_gate.clone();
+                let entered = handler_entered.clone();
+                async move {
+                    entered.add_permits(1);
+                    gate.acquire().await.unwrap().forget();
+                    "report"
+                }
+            })
+            .concurrency_limit(1)
+        }));
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/report").send();
+    let second = async {
+        let _ = entered.acquire().await.unwrap();
+        let response = client.get("/report").send().await;
+        gate.add_permits(1);
+        response
+    };
+    let (first, second) = tokio::join!(first, second);
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
@@ -0,0 +1,132 @@
+//! Integration tests for app-wide and per-route content-type allow-lists.
+
+use http::StatusCode;
+use rapina::middleware::ContentTypeGuard;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+fn app() -> Rapina {
+    let uploads = Router::new()
+        .post_named("/avatars", "upload_avatar", |_, _, _| async {
+            StatusCode::CREATED
+        })
+        .content_types(ContentTypeGuard::new(["image/*"]));
+
+    Rapina::new()
+        .with_introspection(false)
+        .require_json()
+        .router(
+            Router::new()
+                .post_named("/notes", "create_note", |_, _, _| async { "created" })
+                .get_named("/notes", "list_notes", |_, _, _| async { "notes" })
+                .route(Method::DELETE, "/notes/:id", |_, _, _| async {
+                    StatusCode::NO_CONTENT
+                })
+                .group("/uploads", uploads),
+        )
+}
+
+#[tokio::test]
+async fn test_rejects_wrong_content_type() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .post("/notes")
+        .header("content-type", "text/plain")
+        .body("hello")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "UNSUPPORTED_MEDIA_TYPE");
+    assert_eq!(
+        json["error"]["message"],
+        "unsupported content type 'text/plain'; expected application/json"
+    );
+    assert_eq!(
+        json["error"]["details"]["allowed"],
+        serde_json::json!(["application/json"])
+    );
+}
+
+#[tokio::test]
+async fn test_rejects_body_without_content_type() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .post("/notes")
+        .body(r#"{"text":"hello"}"#)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(
+        json["error"]["message"],
+        "missing Content-Type; expected application/json"
+    );
+}
+
+#[tokio::test]
+async fn test_accepts_charset_parameter() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .post("/notes")
+        .header("content-type", "application/json; charset=utf-8")
+        .body(r#"{"text":"hello"}"#)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "created");
+}
+
+#[tokio::test]
+async fn test_bodiless_requests_pass() {
+    let client = TestClient::new(app()).await;
+
+    assert_eq!(client.get("/notes").send().await.status(), StatusCode::OK);
+    assert_eq!(
+        client.delete("/notes/1").send().await.status(),
+        StatusCode::NO_CONTENT
+    );
+}
+
+#[tokio::test]
+async fn test_route_guard_replaces_app_guard() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .post("/uploads/avatars")
+        .header("content-type", "image/png")
+        .body(vec![0u8; 4])
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = client
+        .post("/uploads/avatars")
+        .json(&serde_json::json!({}))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_openapi_request_body_reflects_guards() {
+    let client = TestClient::new(app().openapi("Notes", "1.0.0")).await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+
+    let notes = &spec["paths"]["/notes"]["post"];
+    assert!(notes["requestBody"]["content"]["application/json"].is_object());
+    assert!(notes["responses"]["415"].is_object());
+
+    let avatars = &spec["paths"]["/uploads/avatars"]["post"];
+    assert!(avatars["requestBody"]["content"]["image/*"].is_object());
+    assert!(avatars["requestBody"]["content"]["application/json"].is_null());
+
+    assert!(spec["paths"]["/notes"]["get"]["requestBody"].is_null());
+}
@@ -0,0 +1,157 @@
+//! Integration tests for app-wide and router-level default response headers.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use http::StatusCode;
+use rapina::middleware::DefaultHeaderValue;
+use rapina::prelude::*;
+use rapina::response::BoxBody;
+use rapina::testing::TestClient;
+
+struct ApiVersion(String);
+
+#[get("/plain")]
+async fn plain() -> &'static str {
+    "plain"
+}
+
+#[get("/custom")]
+async fn custom() -> http::Response<BoxBody> {
+    let mut response = "custom".into_response();
+    response
+        .headers_mut()
+        .insert("x-api-version", http::HeaderValue::from_static("handler"));
+    response
+}
+
+#[get("/users")]
+async fn admin_users() -> &'static str {
+    "users"
+}
+
+#[get("/reports")]
+async fn admin_reports() -> &'static str {
+    "reports"
+}
+
+fn app() -> Rapina {
+    let admin = Router::new()
+        .get("/users", admin_users)
+        .default_header("cache-control", "no-store")
+        .default_header("x-area", "admin")
+        .get("/reports", admin_reports);
+
+    Rapina::new()
+        .with_introspection(false)
+        .state(ApiVersion("2024-06-01".to_string()))
+        .default_header(
+            "x-api-version",
+            DefaultHeaderValue::lazy(|state| state.get::<ApiVersion>().map(|v| v.0.clone())),
+        )
+        .default_header("x-area", "public")
+        .router(
+            Router::new()
+                .get("/plain", plain)
+                .get("/custom", custom)
+                .group("/admin", admin),
+        )
+}
+
+#[tokio::test]
+async fn test_default_headers_on_every_response() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/plain").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-api-version"], "2024-06-01");
+    assert_eq!(response.headers()["x-area"], "public");
+    assert!(response.headers().get("cache-control").is_none());
+}
+
+#[tokio::test]
+async fn test_default_headers_on_unmatched_routes() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/missing").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["x-api-version"], "2024-06-01");
+}
+
+#[tokio::test]
+async fn test_handler_header_is_not_overwritten() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/custom").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-api-version"], "handler");
+    assert_eq!(
+        response.headers().get_all("x-api-version").iter().count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_router_headers_apply_to_its_routes_and_win() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/admin/users").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    assert_eq!(response.headers()["x-area"], "admin");
+    assert_eq!(response.headers()["x-api-version"], "2024-06-01");
+}
+
+#[tokio::test]
+async fn test_router_headers_cover_routes_added_after_them() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/admin/reports").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    assert_eq!(response.headers()["x-area"], "admin");
+}
+
+#[tokio::test]
+async fn test_innermost_router_headers_win() {
+    let billing = Router::new()
+        .get("/reports", admin_reports)
+        .default_header("x-area", "billing");
+    let admin = Router::new()
+        .get("/users", admin_users)
+        .group("/billing", billing)
+        .default_header("x-area", "admin");
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().group("/admin", admin));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/admin/billing/reports").send().await;
+    assert_eq!(response.headers()["x-area"], "billing");
+
+    let response = client.get("/admin/users").send().await;
+    assert_eq!(response.headers()["x-area"], "admin");
+}
+
+#[tokio::test]
+async fn test_lazy_value_is_evaluated_per_request() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let app = Rapina::new()
+        .with_introspection(false)
+        .default_header(
+            "x-request-number",
+            DefaultHeaderValue::lazy(move |_| {
+                Some((counter.fetch_add(1, Ordering::SeqCst) + 1).to_string())
+            }),
+        )
+        .router(Router::new().get("/plain", plain));
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/plain").send().await;
+    let second = client.get("/plain").send().await;
+
+    assert_eq!(first.headers()["x-request-number"], "1");
+    assert_eq!(second.headers()["x-request-number"], "2");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,184 @@
+//! Integration tests for `#[derive(FromRequestParts)]`.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+use serde_json::{Value, json};
+
+#[derive(Clone)]
+struct Settings {
+    region: String,
+}
+
+#[derive(FromRequestParts)]
+struct Caller {
+    #[from(header = "x-user-id", rejection = 401)]
+    user_id: u64,
+    #[from(header = "x-trace")]
+    trace: Option<String>,
+    #[from(query = "tenant")]
+    tenant: String,
+    #[from(query)]
+    limit: Option<u32>,
+    #[from(path = "id")]
+    id: i32,
+    #[from(state)]
+    settings: Settings,
+    ctx: Context,
+}
+
+#[get("/projects/:id")]
+async fn show(caller: Caller) -> Json<Value> {
+    Json(json!({
+        "user_id": caller.user_id,
+        "trace": caller.trace,
+        "tenant": caller.tenant,
+        "limit": caller.limit,
+        "id": caller.id,
+        "region": caller.settings.region,
+        "has_trace_id": !caller.ctx.trace_id().is_empty(),
+    }))
+}
+
+async fn client() -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(Settings {
+            region: "eu-west-1".to_string(),
+        })
+        .router(Router::new().get("/projects/:id", show));
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_fields_from_every_source() {
+    let client = client().await;
+
+    let response = client
+        .get("/projects/7?tenant=acme&limit=20")
+        .header("x-user-id", "42")
+        .header("x-trace", "abc")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(
+        body,
+        json!({
+            "user_id": 42,
+            "trace": "abc",
+            "tenant": "acme",
+            "limit": 20,
+            "id": 7,
+            "region": "eu-west-1",
+            "has_trace_id": true,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_optional_fields_absent() {
+    let client = client().await;
+
+    let response = client
+        .get("/projects/7?tenant=acme")
+        .header("x-user-id", "42")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["trace"], Value::Null);
+    assert_eq!(body["limit"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_missing_header_uses_field_rejection() {
+    let client = client().await;
+
+    let response = client.get("/projects/7?tenant=acme").send().await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = response.json();
+    assert_eq!(body["error"]["message"], "Missing header 'x-user-id'");
+}
+
+#[tokio::test]
+async fn test_invalid_header_uses_field_rejection() {
+    let client = client().await;
+
+    let response = client
+        .get("/projects/7?tenant=acme")
+        .header("x-user-id", "alice")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = response.json();
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(message.starts_with("Invalid header 'x-user-id': expected u64, got 'alice'"));
+}
+
+#[tokio::test]
+async fn test_missing_query_is_bad_request() {
+    let client = client().await;
+
+    let response = client
+        .get("/projects/7")
+        .header("x-user-id", "42")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["error"]["message"], "Missing query parameter 'tenant'");
+}
+
+#[tokio::test]
+async fn test_invalid_optional_query_is_rejected() {
+    let client = client().await;
+
+    let response = client
+        .get("/projects/7?tenant=acme&limit=lots")
+        .header("x-user-id", "42")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(message.starts_with("Invalid query parameter 'limit'"));
+}
+
+#[tokio::test]
+async fn test_invalid_path_is_bad_request() {
+    let client = client().await;
+
+    let response = client
+        .get("/projects/seven?tenant=acme")
+        .header("x-user-id", "42")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(message.starts_with("Invalid path parameter 'id': expected i32, got 'seven'"));
+}
+
+#[tokio::test]
+async fn test_missing_state_is_server_error() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/projects/:id", show));
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .get("/projects/7?tenant=acme")
+        .header("x-user-id", "42")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
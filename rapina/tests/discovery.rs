@@ -117,6 +117,22 @@ async fn test_discover_and_router_are_additive() {
     assert_eq!(resp.text(), "manual route");
 }
 
+#[tokio::test]
+async fn test_manual_and_discovered_handler_registered_once() {
+    let app = Rapina::new()
+        .router(Router::new().get("/disc-hello", disc_hello))
+        .discover();
+
+    let client = TestClient::new(app).await;
+
+    let resp = client.get("/disc-hello").send().await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let routes: Vec<serde_json::Value> = client.get("/__rapina/routes").send().await.json();
+    let hello_routes = routes.iter().filter(|r| r["path"] == "/disc-hello").count();
+    assert_eq!(hello_routes, 1);
+}
+
 #[tokio::test]
 async fn test_public_above_route_macro_bypasses_auth() {
     let auth_config = AuthConfig::new("test-secret-disc", 3600);
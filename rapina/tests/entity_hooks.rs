@@ -0,0 +1,125 @@
+//! Integration tests for `schema!` lifecycle hooks and timestamp maintenance.
+//!
+//! The create handler follows the same shape as the ones generated by
+//! `rapina add resource`.
+
+#![cfg(feature = "sqlite")]
+
+use std::sync::Mutex;
+
+use http::StatusCode;
+use rapina::database::{Db, DbError};
+use rapina::prelude::*;
+use rapina::sea_orm::{
+    ActiveModelTrait, ConnectionTrait, Database, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, Schema, Set, TransactionTrait,
+};
+use rapina::testing::TestClient;
+
+schema! {
+    #[before_save(normalize_email)]
+    #[after_save(record_save)]
+    #[after_delete(record_delete)]
+    Member {
+        email: String,
+        name: String,
+    }
+}
+
+use member::{ActiveModel, Model};
+
+static SAVES: Mutex<Vec<(String, bool)>> = Mutex::new(Vec::new());
+static DELETES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+async fn normalize_email(
+    mut member: ActiveModel,
+    _insert: bool,
+) -> std::result::Result<ActiveModel, DbErr> {
+    if let Some(email) = member.email.try_as_ref() {
+        member.email = Set(email.trim().to_lowercase());
+    }
+    Ok(member)
+}
+
+async fn record_save(member: Model, insert: bool) -> std::result::Result<Model, DbErr> {
+    SAVES.lock().unwrap().push((member.email.clone(), insert));
+    Ok(member)
+}
+
+async fn record_delete(member: ActiveModel) -> std::result::Result<ActiveModel, DbErr> {
+    if let Some(email) = member.email.try_as_ref() {
+        DELETES.lock().unwrap().push(email.clone());
+    }
+    Ok(member)
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateMember {
+    email: String,
+    name: String,
+}
+
+#[post("/members")]
+async fn create_member(db: Db, body: Json<CreateMember>) -> Result<Json<Model>> {
+    let input = body.into_inner();
+    let item = ActiveModel {
+        email: Set(input.email),
+        name: Set(input.name),
+        ..Default::default()
+    };
+    let txn = db.conn().begin().await.map_err(DbError)?;
+    let result = item.insert(&txn).await.map_err(DbError)?;
+    txn.commit().await.map_err(DbError)?;
+    Ok(Json(result))
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let backend = conn.get_database_backend();
+    let schema = Schema::new(backend);
+    conn.execute(backend.build(&schema.create_table_from_entity(Member)))
+        .await
+        .unwrap();
+    conn
+}
+
+#[tokio::test]
+async fn test_hooks_run_around_insert_update_and_delete() {
+    let conn = setup_db().await;
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(conn.clone())
+        .router(Router::new().post("/members", create_member));
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/members")
+        .json(&serde_json::json!({ "email": "  Ada@Example.COM ", "name": "Ada" }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["email"], "ada@example.com");
+    let id = json["id"].as_i64().unwrap() as i32;
+
+    let stored = Member::find_by_id(id).one(&conn).await.unwrap().unwrap();
+    assert_eq!(stored.email, "ada@example.com");
+    assert_eq!(stored.created_at, stored.updated_at);
+    assert_eq!(
+        SAVES.lock().unwrap().clone(),
+        vec![("ada@example.com".to_string(), true)]
+    );
+
+    let mut active = stored.clone().into_active_model();
+    active.name = Set("Ada Lovelace".to_string());
+    let updated = active.update(&conn).await.unwrap();
+    assert_eq!(updated.created_at, stored.created_at);
+    assert!(updated.updated_at > stored.updated_at);
+    assert_eq!(
+        SAVES.lock().unwrap().last().cloned(),
+        Some(("ada@example.com".to_string(), false))
+    );
+
+    updated.into_active_model().delete(&conn).await.unwrap();
+    assert_eq!(DELETES.lock().unwrap().clone(), vec!["ada@example.com"]);
+}
@@ -0,0 +1,206 @@
+//! Integration tests for entity mutation events.
+//!
+//! The handlers below follow the same shape as the ones generated by
+//! `rapina add resource`.
+
+#![cfg(feature = "sqlite")]
+
+use http::StatusCode;
+use rapina::database::{Db, DbError};
+use rapina::events::{BoxFuture, Event, EventSink, Events, InMemorySink};
+use rapina::prelude::*;
+use rapina::sea_orm::{
+    ActiveModelTrait, ConnectionTrait, Database, DatabaseConnection, EntityTrait, IntoActiveModel,
+    Schema, Set, TransactionTrait,
+};
+use rapina::testing::TestClient;
+
+schema! {
+    #[timestamps(none)]
+    Widget {
+        name: String,
+    }
+}
+
+use widget::{ActiveModel, Model};
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateWidget {
+    name: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct UpdateWidget {
+    name: Option<String>,
+}
+
+#[post("/widgets")]
+async fn create_widget(db: Db, events: Events, body: Json<CreateWidget>) -> Result<Json<Model>> {
+    let input = body.into_inner();
+    let item = ActiveModel {
+        name: Set(input.name),
+        ..Default::default()
+    };
+    let txn = db.conn().begin().await.map_err(DbError)?;
+    let result = item.insert(&txn).await.map_err(DbError)?;
+    let event = Event::created("widget", result.id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(result))
+}
+
+#[put("/widgets/:id")]
+async fn update_widget(
+    db: Db,
+    events: Events,
+    id: Path<i32>,
+    body: Json<UpdateWidget>,
+) -> Result<Json<Model>> {
+    let id = id.into_inner();
+    let txn = db.conn().begin().await.map_err(DbError)?;
+    let item = Widget::find_by_id(id)
+        .one(&txn)
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Widget {} not found", id)))?;
+
+    let update = body.into_inner();
+    let mut active: ActiveModel = item.into_active_model();
+    if let Some(val) = update.name {
+        active.name = Set(val);
+    }
+
+    let result = active.update(&txn).await.map_err(DbError)?;
+    let event = Event::updated("widget", id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(result))
+}
+
+#[delete("/widgets/:id")]
+async fn delete_widget(db: Db, events: Events, id: Path<i32>) -> Result<Json<serde_json::Value>> {
+    let id = id.into_inner();
+    let txn = db.conn().begin().await.map_err(DbError)?;
+    let result = Widget::delete_by_id(id).exec(&txn).await.map_err(DbError)?;
+    if result.rows_affected == 0 {
+        return Err(Error::not_found(format!("Widget {} not found", id)));
+    }
+    let event = Event::deleted("widget", id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let backend = conn.get_database_backend();
+    let schema = Schema::new(backend);
+    conn.execute(backend.build(&schema.create_table_from_entity(Widget)))
+        .await
+        .unwrap();
+    conn
+}
+
+async fn app_with_sink(events: Events) -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .state(setup_db().await)
+        .state(events)
+        .router(
+            Router::new()
+                .post("/widgets", create_widget)
+                .put("/widgets/:id", update_widget)
+                .delete("/widgets/:id", delete_widget),
+        )
+}
+
+#[tokio::test]
+async fn test_crud_operations_publish_events() {
+    let sink = InMemorySink::new();
+    let client = TestClient::new(app_with_sink(Events::new(sink.clone())).await).await;
+
+    let response = client
+        .post("/widgets")
+        .json(&serde_json::json!({ "name": "gear" }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+    let response = client
+        .put(&format!("/widgets/{}", id))
+        .json(&serde_json::json!({ "name": "cog" }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client.delete(&format!("/widgets/{}", id)).send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let events = sink.events();
+    let names: Vec<String> = events.iter().map(Event::name).collect();
+    assert_eq!(
+        names,
+        vec!["widget.created", "widget.updated", "widget.deleted"]
+    );
+    assert!(events.iter().all(|e| e.id == id.to_string()));
+}
+
+#[tokio::test]
+async fn test_failed_mutation_publishes_nothing() {
+    let sink = InMemorySink::new();
+    let client = TestClient::new(app_with_sink(Events::new(sink.clone())).await).await;
+
+    let response = client.delete("/widgets/999").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .put("/widgets/999")
+        .json(&serde_json::json!({ "name": "cog" }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert!(sink.events().is_empty());
+}
+
+/// Records events only through the transactional path.
+struct OutboxSink(InMemorySink);
+
+impl EventSink for OutboxSink {
+    fn publish(&self, _event: Event) -> BoxFuture<'_, Result<()>> {
+        panic!("transactional sinks should not be published to after commit");
+    }
+
+    fn is_transactional(&self) -> bool {
+        true
+    }
+
+    fn publish_in_transaction<'a>(
+        &'a self,
+        _txn: &'a rapina::sea_orm::DatabaseTransaction,
+        event: Event,
+    ) -> BoxFuture<'a, Result<()>> {
+        self.0.publish(event)
+    }
+}
+
+#[tokio::test]
+async fn test_transactional_sink_publishes_inside_transaction() {
+    let sink = InMemorySink::new();
+    let client = TestClient::new(app_with_sink(Events::new(OutboxSink(sink.clone()))).await).await;
+
+    let response = client
+        .post("/widgets")
+        .json(&serde_json::json!({ "name": "gear" }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let events = sink.events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].name(), "widget.created");
+}
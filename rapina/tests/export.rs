@@ -0,0 +1,100 @@
+//! Integration tests for exporting query results with `stream_json` and
+//! `stream_ndjson`.
+
+#![cfg(feature = "sqlite")]
+
+use http::StatusCode;
+use rapina::database::{Db, DbError};
+use rapina::export::{stream_json, stream_ndjson};
+use rapina::prelude::*;
+use rapina::response::BoxBody;
+use rapina::sea_orm::{
+    ConnectionTrait, Database, DatabaseConnection, EntityTrait, QueryOrder, Schema, Set,
+};
+use rapina::testing::TestClient;
+
+schema! {
+    #[timestamps(none)]
+    Widget {
+        name: String,
+    }
+}
+
+const ROWS: usize = 1200;
+
+#[get("/widgets/export")]
+async fn export_widgets(db: Db) -> Result<http::Response<BoxBody>> {
+    stream_json(Widget::find().order_by_asc(widget::Column::Id), db.read()).await
+}
+
+#[get("/widgets/export.ndjson")]
+async fn export_widgets_ndjson(db: Db) -> Result<http::Response<BoxBody>> {
+    stream_ndjson(Widget::find().order_by_asc(widget::Column::Id), db.read()).await
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let backend = conn.get_database_backend();
+    let schema = Schema::new(backend);
+    conn.execute(backend.build(&schema.create_table_from_entity(Widget)))
+        .await
+        .unwrap();
+
+    for chunk in (1..=ROWS).collect::<Vec<_>>().chunks(100) {
+        let models = chunk.iter().map(|i| widget::ActiveModel {
+            name: Set(format!("widget-{}", i)),
+            ..Default::default()
+        });
+        Widget::insert_many(models)
+            .exec(&conn)
+            .await
+            .map_err(DbError)
+            .unwrap();
+    }
+    conn
+}
+
+async fn client() -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(setup_db().await)
+        .router(
+            Router::new()
+                .get("/widgets/export", export_widgets)
+                .get("/widgets/export.ndjson", export_widgets_ndjson),
+        );
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_stream_json_returns_every_row() {
+    let client = client().await;
+
+    let response = client.get("/widgets/export").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/json");
+
+    let rows: Vec<serde_json::Value> = response.json();
+    assert_eq!(rows.len(), ROWS);
+    assert_eq!(rows[0]["name"], "widget-1");
+    assert_eq!(rows[ROWS - 1]["name"], format!("widget-{}", ROWS));
+}
+
+#[tokio::test]
+async fn test_stream_ndjson_returns_one_row_per_line() {
+    let client = client().await;
+
+    let response = client.get("/widgets/export.ndjson").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+
+    let text = response.text();
+    assert!(text.ends_with('\n'));
+    let rows: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), ROWS);
+    assert_eq!(rows[1]["id"], 2);
+    assert!(rows.iter().all(|row| row.get("error").is_none()));
+}
@@ -664,3 +664,101 @@ async fn test_cookie_extraction_missing() {
 
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
+
+// Raw Body Extractor Tests
+
+fn raw_body_app() -> Rapina {
+    use rapina::extract::FromRequest;
+
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .route(http::Method::POST, "/bytes", |req, params, state| async move {
+                Bytes::from_request(req, &params, &state)
+                    .await
+                    .map(|body| format!("{} bytes", body.len()))
+            })
+            .route(http::Method::POST, "/string", |req, params, state| async move {
+                String::from_request(req, &params, &state).await
+            })
+            .route(http::Method::POST, "/raw-json", |req, params, state| async move {
+                RawJson::<User>::from_request(req, &params, &state)
+                    .await
+                    .map(|body| format!("{} from {} bytes", body.value().name, body.bytes().len()))
+            }),
+    )
+}
+
+#[tokio::test]
+async fn test_bytes_extraction_ignores_content_type() {
+    let client = TestClient::new(raw_body_app()).await;
+    let response = client
+        .post("/bytes")
+        .header("content-type", "application/octet-stream")
+        .body(vec![0u8, 159, 146, 150])
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "4 bytes");
+}
+
+#[tokio::test]
+async fn test_string_extraction() {
+    let client = TestClient::new(raw_body_app()).await;
+    let response = client.post("/string").body("plain text").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "plain text");
+}
+
+#[tokio::test]
+async fn test_string_extraction_invalid_utf8() {
+    let client = TestClient::new(raw_body_app()).await;
+    let response = client
+        .post("/string")
+        .body(vec![0u8, 159, 146, 150])
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_raw_json_keeps_original_bytes() {
+    let client = TestClient::new(raw_body_app()).await;
+    // Extra whitespace must survive, since signatures cover the exact bytes
+    let body = r#"{ "name": "Alice",  "email": "alice@example.com" }"#;
+    let response = client
+        .post("/raw-json")
+        .header("content-type", "application/json")
+        .body(body)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), format!("Alice from {} bytes", body.len()));
+}
+
+#[tokio::test]
+async fn test_raw_json_invalid_json() {
+    let client = TestClient::new(raw_body_app()).await;
+    let response = client.post("/raw-json").body("not json").send().await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_raw_body_extractors_respect_body_limit() {
+    let app = raw_body_app().middleware(rapina::middleware::BodyLimitMiddleware::new(16));
+    let client = TestClient::new(app).await;
+
+    let response = client.post("/bytes").body("small").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "5 bytes");
+
+    let response = client.post("/bytes").body(vec![b'a'; 32]).send().await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let response = client.post("/string").body(vec![b'a'; 32]).send().await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,101 @@
+//! Integration tests for flag-gated routes and the `Flags` extractor.
+
+use http::StatusCode;
+use rapina::flags::{Flags, StaticFlags};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+use serde_json::Value;
+
+#[get("/dashboard", flag = "new_dashboard")]
+async fn dashboard() -> &'static str {
+    "new dashboard"
+}
+
+#[get("/home")]
+async fn home(flags: Flags) -> Result<&'static str> {
+    flags.require("home_page").await?;
+    if flags.is_enabled("new_dashboard").await {
+        return Ok("home with dashboard");
+    }
+    Ok("home")
+}
+
+fn router() -> Router {
+    Router::new()
+        .get("/dashboard", dashboard)
+        .get("/home", home)
+}
+
+async fn client(flags: &StaticFlags) -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(true)
+        .state(Flags::new(flags.clone()))
+        .router(router());
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_flag_gated_route_follows_flag() {
+    let flags = StaticFlags::new().with("new_dashboard", false);
+    let client = client(&flags).await;
+
+    let response = client.get("/dashboard").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    flags.enable("new_dashboard");
+    let response = client.get("/dashboard").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "new dashboard");
+
+    flags.disable("new_dashboard");
+    let response = client.get("/dashboard").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_flag_gated_route_without_provider_is_not_found() {
+    let app = Rapina::new().with_introspection(false).router(router());
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/dashboard").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_extractor_branches_on_flags() {
+    let flags = StaticFlags::new().with("home_page", true);
+    let client = client(&flags).await;
+
+    assert_eq!(client.get("/home").send().await.text(), "home");
+
+    flags.enable("new_dashboard");
+    assert_eq!(
+        client.get("/home").send().await.text(),
+        "home with dashboard"
+    );
+
+    flags.disable("home_page");
+    let response = client.get("/home").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_introspection_shows_flag() {
+    let client = client(&StaticFlags::new()).await;
+
+    let response = client.get("/__rapina/routes").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let routes: Vec<Value> = response.json();
+
+    let dashboard_route = routes
+        .iter()
+        .find(|route| route["path"] == "/dashboard")
+        .unwrap();
+    assert_eq!(dashboard_route["feature_flag"], "new_dashboard");
+
+    let home_route = routes
+        .iter()
+        .find(|route| route["path"] == "/home")
+        .unwrap();
+    assert!(home_route.get("feature_flag").is_none());
+}
@@ -0,0 +1,132 @@
+//! Integration tests for `?fields=` partial responses.
+
+use http::StatusCode;
+use rapina::fields::{FieldSelect, PartialJson};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Serialize, JsonSchema)]
+struct User {
+    id: i32,
+    name: String,
+    email: String,
+    #[serde(skip_serializing)]
+    password_hash: String,
+}
+
+fn user(id: i32) -> User {
+    User {
+        id,
+        name: format!("user {}", id),
+        email: format!("user{}@example.com", id),
+        password_hash: "hunter2".to_string(),
+    }
+}
+
+#[get("/users")]
+async fn list_users(fields: FieldSelect<User>) -> PartialJson<Vec<User>> {
+    fields.apply(vec![user(1), user(2)])
+}
+
+#[get("/users/:id")]
+async fn get_user(id: Path<i32>, fields: FieldSelect<User>) -> Result<PartialJson<User>> {
+    let user = user(id.into_inner());
+    assert_eq!(user.password_hash, "hunter2");
+    Ok(fields.apply(user))
+}
+
+fn app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/users", list_users)
+            .get("/users/:id", get_user),
+    )
+}
+
+#[tokio::test]
+async fn test_selects_fields() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/users/5?fields=id,name").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json, serde_json::json!({ "id": 5, "name": "user 5" }));
+
+    let response = client.get("/users?fields=email").send().await;
+    let json: serde_json::Value = response.json();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            { "email": "user1@example.com" },
+            { "email": "user2@example.com" },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_without_fields_returns_everything_but_hidden() {
+    let client = TestClient::new(app()).await;
+
+    let json: serde_json::Value = client.get("/users/5").send().await.json();
+    assert_eq!(
+        json,
+        serde_json::json!({ "id": 5, "name": "user 5", "email": "user5@example.com" })
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_field_lists_valid_ones() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/users/5?fields=id,nickname").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "BAD_REQUEST");
+    assert_eq!(
+        json["error"]["details"]["unknown"],
+        serde_json::json!(["nickname"])
+    );
+    let mut valid: Vec<String> =
+        serde_json::from_value(json["error"]["details"]["valid"].clone()).unwrap();
+    valid.sort();
+    assert_eq!(valid, ["email", "id", "name"]);
+}
+
+#[tokio::test]
+async fn test_hidden_field_is_never_selectable() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/users/5?fields=password_hash").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json();
+    assert_eq!(
+        json["error"]["details"]["unknown"],
+        serde_json::json!(["password_hash"])
+    );
+}
+
+#[tokio::test]
+async fn test_openapi_documents_fields_parameter() {
+    let client = TestClient::new(app().openapi("Users", "1.0.0")).await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+
+    let params = spec["paths"]["/users/{id}"]["get"]["parameters"]
+        .as_array()
+        .unwrap();
+    let fields = params.iter().find(|p| p["name"] == "fields").unwrap();
+    assert_eq!(fields["in"], "query");
+    assert_eq!(fields["required"], false);
+    assert!(fields["description"].as_str().unwrap().contains("email"));
+    assert!(
+        !fields["description"]
+            .as_str()
+            .unwrap()
+            .contains("password_hash")
+    );
+
+    let params = spec["paths"]["/users"]["get"]["parameters"]
+        .as_array()
+        .unwrap();
+    assert_eq!(params[0]["name"], "fields");
+}
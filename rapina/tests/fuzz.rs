@@ -0,0 +1,149 @@
+//! Fuzz tests: random requests against a router that uses every built-in
+//! extractor must always get a response.
+
+#![cfg(feature = "fuzz")]
+
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::middleware::{CompressionConfig, CorsConfig};
+use rapina::prelude::*;
+use rapina::router::EncodedSlash;
+use rapina::testing::{FuzzConfig, FuzzRequest, TestClient, fuzz_app, fuzz_request};
+
+#[derive(Debug, Deserialize, Validate)]
+struct CreateUser {
+    #[validate(length(min = 1, max = 50))]
+    name: String,
+    #[validate(email)]
+    email: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListParams {
+    q: Option<String>,
+    limit: Option<u32>,
+    active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Session {
+    session: Option<String>,
+}
+
+#[get("/users/:id")]
+async fn get_user(id: Path<u64>) -> String {
+    format!("user {}", id.into_inner())
+}
+
+#[get("/files/:name")]
+async fn get_file(name: Path<String>) -> String {
+    name.into_inner()
+}
+
+#[get("/users")]
+async fn list_users(params: Query<ListParams>) -> String {
+    let params = params.into_inner();
+    format!("{:?} {:?} {:?}", params.q, params.limit, params.active)
+}
+
+#[post("/users")]
+async fn create_user(body: Validated<Json<CreateUser>>) -> StatusCode {
+    let _ = body.into_inner();
+    StatusCode::CREATED
+}
+
+#[put("/users/:id")]
+async fn update_user(id: Path<i32>, body: Json<serde_json::Value>) -> String {
+    format!("{} {}", id.into_inner(), body.into_inner())
+}
+
+#[post("/login")]
+async fn login(form: Form<std::collections::HashMap<String, String>>) -> String {
+    format!("{}", form.into_inner().len())
+}
+
+#[get("/me")]
+async fn me(session: Cookie<Session>, headers: Headers) -> String {
+    format!("{:?} {}", session.into_inner().session, headers.0.len())
+}
+
+#[post("/upload")]
+async fn upload(body: Bytes) -> String {
+    body.len().to_string()
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .with_cors(CorsConfig::permissive())
+        .with_compression(CompressionConfig::default())
+        .router(
+            Router::new()
+                .decode_params(EncodedSlash::Decode)
+                .get("/users/:id", get_user)
+                .get("/files/:name", get_file)
+                .get("/users", list_users)
+                .post("/users", create_user)
+                .put("/users/:id", update_user)
+                .post("/login", login)
+                .get("/me", me)
+                .post("/upload", upload)
+                .route(
+                    Method::GET,
+                    "/orgs/:org/repos/:repo",
+                    |_, params, _| async move { format!("{:?}", params.get("repo")) },
+                ),
+        )
+}
+
+#[test]
+fn test_extractors_answer_random_requests() {
+    fuzz_app(app(), FuzzConfig::default());
+}
+
+#[test]
+#[should_panic(expected = "minimal failing input")]
+fn test_panicking_handler_is_reported() {
+    let app =
+        Rapina::new().router(
+            Router::new().route(Method::GET, "/boom", |_, _, _| async move {
+                panic!("boom");
+                #[allow(unreachable_code)]
+                ""
+            }),
+        );
+    fuzz_app(
+        app,
+        FuzzConfig::default()
+            .cases(1_000)
+            .timeout(Duration::from_secs(1)),
+    );
+}
+
+#[tokio::test]
+async fn test_fuzz_request_sends_raw_bytes() {
+    let client = TestClient::new(app()).await;
+    let request = |target: &[u8], body: &[u8]| FuzzRequest {
+        method: "POST".into(),
+        target: target.to_vec(),
+        headers: vec![("content-type".into(), b"application/json".to_vec())],
+        body: body.to_vec(),
+    };
+    let timeout = Duration::from_secs(5);
+
+    let status = fuzz_request(&client, &request(b"/users", b"{\"name\":"), timeout)
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let status = fuzz_request(&client, &request(b"/users/\xff\x00", b""), timeout)
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let nested = "[".repeat(100_000);
+    let status = fuzz_request(&client, &request(b"/users", nested.as_bytes()), timeout)
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
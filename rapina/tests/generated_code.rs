@@ -0,0 +1,95 @@
+//! Compiles the files `rapina add resource` generates inside an app that
+//! glob-imports its own `Error`, `Result` and `Json`, including the handlers
+//! written with `--with-links`.
+//!
+//! The files live in `rapina-cli/tests/fixtures/shadowed_error`, where a CLI
+//! test keeps them in sync with the templates.
+
+#![cfg(feature = "database")]
+
+#[allow(dead_code)]
+#[path = "../../rapina-cli/tests/fixtures/shadowed_error/src/prelude.rs"]
+mod prelude;
+
+mod entity {
+    use rapina::schema;
+
+    schema! {
+        Post {
+            title: String,
+            published: bool,
+            metadata: Json,
+        }
+    }
+}
+
+mod posts {
+    #[allow(unused_imports)]
+    pub mod dto {
+        use crate::prelude::*;
+
+        include!("../../rapina-cli/tests/fixtures/shadowed_error/src/posts/dto.rs");
+    }
+
+    #[allow(unused_imports)]
+    pub mod error {
+        use crate::prelude::*;
+
+        include!("../../rapina-cli/tests/fixtures/shadowed_error/src/posts/error.rs");
+    }
+
+    #[allow(unused_imports)]
+    pub mod handlers {
+        use crate::prelude::*;
+
+        include!("../../rapina-cli/tests/fixtures/shadowed_error/src/posts/handlers.rs");
+    }
+
+    #[allow(unused_imports)]
+    pub mod linked_handlers {
+        use crate::prelude::*;
+
+        include!("../../rapina-cli/tests/fixtures/shadowed_error/src/posts/linked_handlers.rs");
+    }
+}
+
+#[test]
+fn test_generated_handlers_register() {
+    use posts::handlers::*;
+    use rapina::router::Router;
+
+    let routes = Router::new()
+        .get("/posts", list_posts)
+        .get("/posts/:id", get_post)
+        .post("/posts", create_post)
+        .put("/posts/:id", update_post)
+        .delete("/posts/:id", delete_post)
+        .routes();
+
+    let names: Vec<&str> = routes.iter().map(|r| r.handler_name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "list_posts",
+            "get_post",
+            "create_post",
+            "update_post",
+            "delete_post"
+        ]
+    );
+    assert!(routes[0].error_responses.iter().any(|e| e.status == 404));
+}
+
+#[test]
+fn test_generated_linked_handlers_register() {
+    use posts::linked_handlers::*;
+    use rapina::router::Router;
+
+    let routes = Router::new()
+        .get("/posts", list_posts)
+        .get("/posts/:id", get_post)
+        .routes();
+
+    let names: Vec<&str> = routes.iter().map(|r| r.handler_name.as_str()).collect();
+    assert_eq!(names, ["list_posts", "get_post"]);
+}
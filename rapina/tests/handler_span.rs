@@ -0,0 +1,61 @@
+//! Integration tests for the tracing span wrapped around each handler.
+
+mod common;
+
+use http::StatusCode;
+use rapina::middleware::RequestLogMiddleware;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+use common::Logs;
+
+#[get("/users/:id")]
+async fn show_user(id: Path<u64>) -> String {
+    let id = id.into_inner();
+    tokio::task::yield_now().await;
+    tracing::info!(id, "loading user");
+    format!("user {}", id)
+}
+
+#[tokio::test]
+async fn test_handler_events_are_wrapped_in_handler_span() {
+    let (logs, _guard) = Logs::capture(tracing::Level::INFO);
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/users/:id", show_user));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/users/7").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let line = logs.line_containing("loading user");
+    assert!(
+        line.contains(
+            "handler{handler=show_user method=GET route=/users/:id otel.name=show_user}:"
+        ),
+        "{}",
+        line
+    );
+    assert!(line.contains("id=7"), "{}", line);
+}
+
+#[tokio::test]
+async fn test_handler_span_nests_inside_request_span() {
+    let (logs, _guard) = Logs::capture(tracing::Level::INFO);
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(RequestLogMiddleware::new())
+        .router(Router::new().get("/users/:id", show_user));
+    let client = TestClient::new(app).await;
+
+    client.get("/users/7").send().await;
+
+    let line = logs.line_containing("loading user");
+    let request = line.find("request{").expect(&line);
+    let handler = line.find("handler{").expect(&line);
+    assert!(request < handler, "{}", line);
+    assert_eq!(line.matches("handler{").count(), 1, "{}", line);
+
+    let completed = logs.line_containing("request completed");
+    assert!(!completed.contains("handler{"), "{}", completed);
+}
@@ -0,0 +1,156 @@
+//! Integration tests for handlers sharing a method and path, picked by
+//! request headers such as a versioned `Accept`.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::router::HeaderMatcher;
+use rapina::testing::TestClient;
+
+#[get("/users", accept_version = 1)]
+async fn list_users_v1() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "version": 1, "users": ["ada"] }))
+}
+
+#[get("/users", accept_version = 2)]
+async fn list_users_v2() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "version": 2, "data": [{ "name": "ada" }] }))
+}
+
+async fn client() -> TestClient {
+    TestClient::new(
+        Rapina::new()
+            .with_introspection(false)
+            .openapi("Versioned", "1.0.0")
+            .discover(),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_accept_version_picks_the_handler() {
+    let client = client().await;
+
+    let response = client
+        .get("/users")
+        .header("accept", "application/vnd.myapp.v1+json")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json::<serde_json::Value>()["version"], 1);
+
+    let response = client
+        .get("/users")
+        .header("accept", "application/vnd.myapp.v2+json")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json::<serde_json::Value>()["version"], 2);
+
+    let response = client
+        .get("/users")
+        .header("accept", "application/json; version=2")
+        .send()
+        .await;
+    assert_eq!(response.json::<serde_json::Value>()["version"], 2);
+}
+
+#[tokio::test]
+async fn test_unsupported_version_lists_supported_versions() {
+    let client = client().await;
+
+    for accept in ["application/vnd.myapp.v3+json", "application/json"] {
+        let response = client.get("/users").header("accept", accept).send().await;
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE, "{}", accept);
+        let json: serde_json::Value = response.json();
+        assert_eq!(json["error"]["code"], "NOT_ACCEPTABLE");
+        assert_eq!(
+            json["error"]["details"]["versions"],
+            serde_json::json!([1, 2])
+        );
+        assert_eq!(
+            json["error"]["details"]["supported"],
+            serde_json::json!(["accept: version 1", "accept: version 2"])
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_openapi_tags_operations_by_version() {
+    let client = client().await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+    let get = &spec["paths"]["/users"]["get"];
+    let mut tags: Vec<&str> = get["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tag| tag.as_str().unwrap())
+        .collect();
+    tags.sort();
+    assert_eq!(tags, ["v1", "v2"]);
+    let mut variants: Vec<(&str, u64)> = get["x-rapina-variants"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|variant| {
+            (
+                variant["operationId"].as_str().unwrap(),
+                variant["version"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    variants.sort();
+    assert_eq!(variants, [("list_users_v1", 1), ("list_users_v2", 2)]);
+}
+
+#[tokio::test]
+async fn test_when_header_falls_back_to_the_plain_route() {
+    let router = Router::new()
+        .get_named("/feed", "feed", |_, _, _| async { "stable" })
+        .get_named("/feed", "beta_feed", |_, _, _| async { "beta" })
+        .when_header("x-channel", "beta");
+    let client = TestClient::new(Rapina::new().with_introspection(false).router(router)).await;
+
+    let response = client.get("/feed").header("x-channel", "Beta").send().await;
+    assert_eq!(response.text(), "beta");
+
+    let response = client.get("/feed").send().await;
+    assert_eq!(response.text(), "stable");
+
+    let response = client
+        .get("/feed")
+        .header("x-channel", "nightly")
+        .send()
+        .await;
+    assert_eq!(response.text(), "stable");
+}
+
+#[tokio::test]
+async fn test_custom_header_matcher() {
+    let router = Router::new()
+        .get_named("/reports", "reports", |_, _, _| async { "reports" })
+        .when_header(
+            "x-tenant",
+            HeaderMatcher::custom("an acme tenant", |value| value.starts_with("acme-")),
+        );
+    let client = TestClient::new(Rapina::new().with_introspection(false).router(router)).await;
+
+    let response = client
+        .get("/reports")
+        .header("x-tenant", "acme-eu")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client
+        .get("/reports")
+        .header("x-tenant", "globex")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(
+        json["error"]["details"]["supported"],
+        serde_json::json!(["x-tenant: an acme tenant"])
+    );
+}
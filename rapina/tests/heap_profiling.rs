@@ -0,0 +1,112 @@
+//! Integration tests for the `/__rapina/heap` profiling endpoint.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: rapina::profiling::TrackingAllocator =
+    rapina::profiling::TrackingAllocator::system();
+
+fn router() -> Router {
+    Router::new().get_named("/hello", "hello", |_, _, _| async { "hello" })
+}
+
+#[tokio::test]
+async fn test_heap_endpoint_is_absent_by_default() {
+    let client = TestClient::new(Rapina::new().with_introspection(true).router(router())).await;
+
+    let response = client.get("/__rapina/heap").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[cfg(feature = "profiling")]
+#[tokio::test]
+async fn test_heap_endpoint_reports_json() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_heap_profiling(true)
+        .router(router());
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/__rapina/heap").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    let json: serde_json::Value = response.json();
+    for key in [
+        "allocator",
+        "rss_bytes",
+        "connections",
+        "in_flight_requests",
+        "cached_responses",
+        "state_entries",
+    ] {
+        assert!(json.get(key).is_some(), "missing {}: {}", key, json);
+    }
+    for key in [
+        "allocated_bytes",
+        "peak_bytes",
+        "allocations",
+        "deallocations",
+    ] {
+        assert!(
+            json["allocator"][key].as_u64().is_some(),
+            "missing allocator.{}: {}",
+            key,
+            json
+        );
+    }
+    assert!(
+        json["allocator"]["peak_bytes"].as_u64() >= json["allocator"]["allocated_bytes"].as_u64()
+    );
+    assert_eq!(json["in_flight_requests"], 1);
+    assert!(json["state_entries"].as_u64().unwrap() > 0);
+}
+
+#[cfg(feature = "profiling")]
+#[tokio::test]
+async fn test_heap_endpoint_renders_text() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_heap_profiling(true)
+        .router(router());
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/__rapina/heap?format=text").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let text = response.text();
+    for label in [
+        "allocated:",
+        "peak:",
+        "rss:",
+        "in-flight requests:",
+        "state entries:",
+    ] {
+        assert!(text.contains(label), "missing {}: {}", label, text);
+    }
+}
+
+#[cfg(feature = "profiling")]
+#[tokio::test]
+async fn test_heap_endpoint_needs_the_introspection_token() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_heap_profiling(true)
+        .introspection_token("heap-s3cret")
+        .router(router());
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/__rapina/heap").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .get("/__rapina/heap")
+        .header("x-rapina-token", "heap-s3cret")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
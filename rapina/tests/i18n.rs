@@ -0,0 +1,149 @@
+//! Integration tests for language negotiation.
+
+use http::StatusCode;
+use rapina::i18n::{AcceptLanguage, Locale, LocaleConfig};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[get("/locale")]
+async fn current_locale(locale: Locale) -> String {
+    locale.into_inner()
+}
+
+#[get("/languages")]
+async fn languages(accept: AcceptLanguage) -> String {
+    accept
+        .iter()
+        .map(|range| format!("{}={}", range.tag, range.quality))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[get("/missing")]
+async fn missing() -> Result<String> {
+    Err(Error::not_found("user not found"))
+}
+
+#[get("/conflict")]
+async fn conflict() -> Result<String> {
+    Err(Error::conflict("email taken"))
+}
+
+fn translate(code: &str, locale: &str) -> Option<String> {
+    match (code, locale) {
+        ("NOT_FOUND", "pt-BR") => Some("Recurso não encontrado".to_string()),
+        ("NOT_FOUND", "es") => Some("Recurso no encontrado".to_string()),
+        _ => None,
+    }
+}
+
+fn router() -> Router {
+    Router::new()
+        .get("/locale", current_locale)
+        .get("/languages", languages)
+        .get("/missing", missing)
+        .get("/conflict", conflict)
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .with_locales(LocaleConfig::new(["en", "pt-BR", "es"]).translate(translate))
+        .router(router())
+}
+
+#[tokio::test]
+async fn test_locale_negotiation_precedence() {
+    let client = TestClient::new(app()).await;
+
+    let cases = [
+        ("es, pt-BR;q=0.9", "es"),
+        ("es;q=0.4, pt-BR;q=0.9", "pt-BR"),
+        ("fr, pt;q=0.8, es;q=0.5", "pt-BR"),
+        ("es-AR", "es"),
+        ("de, fr", "en"),
+        ("*", "en"),
+    ];
+    for (header, expected) in cases {
+        let response = client
+            .get("/locale")
+            .header("accept-language", header)
+            .send()
+            .await;
+        assert_eq!(response.text(), expected, "Accept-Language: {}", header);
+    }
+
+    let response = client.get("/locale").send().await;
+    assert_eq!(response.text(), "en");
+}
+
+#[tokio::test]
+async fn test_locale_without_config_uses_client_preference() {
+    let app = Rapina::new().with_introspection(false).router(router());
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .get("/locale")
+        .header("accept-language", "de-CH;q=0.9, fr")
+        .send()
+        .await;
+    assert_eq!(response.text(), "fr");
+
+    let response = client.get("/locale").send().await;
+    assert_eq!(response.text(), "en");
+}
+
+#[tokio::test]
+async fn test_accept_language_extractor_tolerates_malformed_values() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .get("/languages")
+        .header("accept-language", "en;q=0.5, pt;q=bogus, es;q=0.8, ;q=1")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "es=0.8,en=0.5");
+}
+
+#[tokio::test]
+async fn test_error_messages_are_translated() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .get("/missing")
+        .header("accept-language", "pt-BR")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers().get("content-language").unwrap(), "pt-BR");
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+    assert_eq!(body["error"]["message"], "Recurso não encontrado");
+    assert!(body["trace_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_untranslated_errors_keep_their_message() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .get("/conflict")
+        .header("accept-language", "pt-BR")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert!(response.headers().get("content-language").is_none());
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["message"], "email taken");
+
+    let response = client
+        .get("/missing")
+        .header("accept-language", "de")
+        .send()
+        .await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["message"], "user not found");
+}
@@ -0,0 +1,180 @@
+//! Integration tests for `Idempotency-Key` handling.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::middleware::{IDEMPOTENT_REPLAYED_HEADER, IdempotencyMiddleware};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Clone, Default)]
+struct Charges(Arc<AtomicUsize>);
+
+impl Charges {
+    fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Charge {
+    amount: u64,
+}
+
+#[post("/payments")]
+async fn charge(
+    charges: State<Charges>,
+    body: Json<Charge>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let id = charges.into_inner().0.fetch_add(1, Ordering::SeqCst) + 1;
+    // Keep the first request running while a duplicate arrives
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    (
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "id": id, "amount": body.into_inner().amount })),
+    )
+}
+
+#[post("/failures")]
+async fn fail(charges: State<Charges>) -> StatusCode {
+    charges.into_inner().0.fetch_add(1, Ordering::SeqCst);
+    StatusCode::SERVICE_UNAVAILABLE
+}
+
+async fn client(charges: &Charges) -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(charges.clone())
+        .middleware(IdempotencyMiddleware::new())
+        .router(
+            Router::new()
+                .post("/payments", charge)
+                .post("/failures", fail),
+        );
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_retry_replays_first_response() {
+    let charges = Charges::default();
+    let client = client(&charges).await;
+
+    let first = client
+        .post("/payments")
+        .header("idempotency-key", "order-1")
+        .json(&Charge { amount: 500 })
+        .send()
+        .await;
+    assert_eq!(first.status(), StatusCode::CREATED);
+    assert!(first.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+
+    let retry = client
+        .post("/payments")
+        .header("idempotency-key", "order-1")
+        .json(&Charge { amount: 500 })
+        .send()
+        .await;
+    assert_eq!(retry.status(), StatusCode::CREATED);
+    assert_eq!(retry.text(), first.text());
+    assert_eq!(
+        retry.headers().get("content-type"),
+        first.headers().get("content-type")
+    );
+    assert_eq!(retry.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+    assert_eq!(charges.count(), 1);
+
+    // A new key runs the handler again
+    let other = client
+        .post("/payments")
+        .header("idempotency-key", "order-2")
+        .json(&Charge { amount: 500 })
+        .send()
+        .await;
+    let json: serde_json::Value = other.json();
+    assert_eq!(json["id"], 2);
+    assert_eq!(charges.count(), 2);
+}
+
+#[tokio::test]
+async fn test_same_key_with_different_body_conflicts() {
+    let charges = Charges::default();
+    let client = client(&charges).await;
+
+    client
+        .post("/payments")
+        .header("idempotency-key", "order-1")
+        .json(&Charge { amount: 500 })
+        .send()
+        .await;
+
+    let response = client
+        .post("/payments")
+        .header("idempotency-key", "order-1")
+        .json(&Charge { amount: 900 })
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "CONFLICT");
+    assert_eq!(charges.count(), 1);
+}
+
+#[tokio::test]
+async fn test_concurrent_duplicates_run_once() {
+    let charges = Charges::default();
+    let client = client(&charges).await;
+
+    let send = || {
+        client
+            .post("/payments")
+            .header("idempotency-key", "order-1")
+            .json(&Charge { amount: 500 })
+            .send()
+    };
+    let (a, b, c) = tokio::join!(send(), send(), send());
+
+    assert_eq!(charges.count(), 1);
+    for response in [&a, &b, &c] {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.text(), a.text());
+    }
+    let replayed = [&a, &b, &c]
+        .iter()
+        .filter(|r| r.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER))
+        .count();
+    assert_eq!(replayed, 2);
+}
+
+#[tokio::test]
+async fn test_requests_without_key_are_not_deduplicated() {
+    let charges = Charges::default();
+    let client = client(&charges).await;
+
+    for _ in 0..2 {
+        let response = client
+            .post("/payments")
+            .json(&Charge { amount: 500 })
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+    assert_eq!(charges.count(), 2);
+}
+
+#[tokio::test]
+async fn test_server_errors_are_not_recorded() {
+    let charges = Charges::default();
+    let client = client(&charges).await;
+
+    for _ in 0..2 {
+        let response = client
+            .post("/failures")
+            .header("idempotency-key", "retry-me")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+    assert_eq!(charges.count(), 2);
+}
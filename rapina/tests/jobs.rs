@@ -0,0 +1,264 @@
+//! Integration tests for the database-backed job queue.
+
+#![cfg(feature = "sqlite")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::jobs::entity::{self, FAILED};
+use rapina::jobs::{Job, JobContext, JobQueue, JobResult, Jobs};
+use rapina::migration::{MigrationTrait, SchemaManager};
+use rapina::prelude::*;
+use rapina::sea_orm::{Database, DatabaseConnection, EntityTrait};
+use rapina::state::AppState;
+use rapina::testing::TestClient;
+use tokio::sync::mpsc;
+
+#[derive(Serialize, Deserialize)]
+struct SendEmail {
+    to: String,
+}
+
+impl Job for SendEmail {
+    const NAME: &'static str = "send_email";
+
+    async fn run(self, ctx: JobContext) -> JobResult {
+        let sent = ctx.state().get::<mpsc::UnboundedSender<String>>().unwrap();
+        sent.send(self.to)?;
+        Ok(())
+    }
+}
+
+/// Fails until its third attempt.
+#[derive(Serialize, Deserialize)]
+struct Flaky;
+
+impl Job for Flaky {
+    const NAME: &'static str = "flaky";
+
+    async fn run(self, ctx: JobContext) -> JobResult {
+        let runs = ctx.state().get::<Arc<AtomicUsize>>().unwrap();
+        runs.fetch_add(1, Ordering::SeqCst);
+        if ctx.attempt() < 3 {
+            return Err("smtp server unavailable".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Doomed;
+
+impl Job for Doomed {
+    const NAME: &'static str = "doomed";
+    const MAX_ATTEMPTS: u32 = 2;
+
+    async fn run(self, _ctx: JobContext) -> JobResult {
+        Err("always fails".into())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Slow;
+
+impl Job for Slow {
+    const NAME: &'static str = "slow";
+
+    async fn run(self, ctx: JobContext) -> JobResult {
+        ctx.state()
+            .get::<mpsc::UnboundedSender<String>>()
+            .unwrap()
+            .send("started".into())?;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        ctx.state()
+            .get::<mpsc::UnboundedSender<String>>()
+            .unwrap()
+            .send("finished".into())?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct Signup {
+    email: String,
+}
+
+#[post("/signup")]
+async fn signup(jobs: Jobs, body: Json<Signup>) -> Result<StatusCode> {
+    jobs.enqueue(SendEmail {
+        to: body.into_inner().email,
+    })
+    .await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    rapina::jobs::migration::Migration
+        .up(&SchemaManager::new(&conn))
+        .await
+        .unwrap();
+    conn
+}
+
+fn queue() -> JobQueue {
+    JobQueue::new()
+        .register::<SendEmail>()
+        .register::<Flaky>()
+        .register::<Doomed>()
+        .register::<Slow>()
+        .poll_interval(Duration::from_millis(10))
+        .backoff(Duration::from_millis(10), Duration::from_millis(50))
+}
+
+async fn wait_for_empty_queue(conn: &DatabaseConnection) {
+    for _ in 0..200 {
+        if entity::Entity::find().all(conn).await.unwrap().is_empty() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("the queue was not drained");
+}
+
+#[tokio::test]
+async fn test_enqueued_job_runs() {
+    let conn = setup_db().await;
+    let (sent_tx, mut sent_rx) = mpsc::unbounded_channel::<String>();
+    let client = TestClient::new(
+        Rapina::new()
+            .with_introspection(false)
+            .state(conn.clone())
+            .router(Router::new().post("/signup", signup)),
+    )
+    .await;
+
+    let response = client
+        .post("/signup")
+        .json(&serde_json::json!({ "email": "ada@example.com" }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let state = AppState::new().with(conn.clone()).with(sent_tx);
+    let workers = queue().start(Arc::new(state)).unwrap();
+
+    let sent = tokio::time::timeout(Duration::from_secs(5), sent_rx.recv())
+        .await
+        .unwrap();
+    assert_eq!(sent.as_deref(), Some("ada@example.com"));
+    wait_for_empty_queue(&conn).await;
+
+    workers.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_failed_job_is_retried() {
+    let conn = setup_db().await;
+    let runs = Arc::new(AtomicUsize::new(0));
+    Jobs::new(conn.clone()).enqueue(Flaky).await.unwrap();
+
+    let state = AppState::new().with(conn.clone()).with(runs.clone());
+    let workers = queue().start(Arc::new(state)).unwrap();
+
+    wait_for_empty_queue(&conn).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+    workers.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_job_is_marked_failed_after_max_attempts() {
+    let conn = setup_db().await;
+    let id = Jobs::new(conn.clone()).enqueue(Doomed).await.unwrap();
+
+    let workers = queue()
+        .start(Arc::new(AppState::new().with(conn.clone())))
+        .unwrap();
+
+    let mut job = None;
+    for _ in 0..200 {
+        job = entity::Entity::find_by_id(id).one(&conn).await.unwrap();
+        if job.as_ref().is_some_and(|job| job.status == FAILED) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    workers.shutdown().await;
+
+    let job = job.unwrap();
+    assert_eq!(job.status, FAILED);
+    assert_eq!(job.attempts, 2);
+    assert_eq!(job.last_error.as_deref(), Some("always fails"));
+    assert!(job.locked_at.is_none());
+}
+
+#[tokio::test]
+async fn test_unknown_job_is_marked_failed() {
+    let conn = setup_db().await;
+    let id = Jobs::new(conn.clone()).enqueue(Doomed).await.unwrap();
+
+    let workers = JobQueue::new()
+        .register::<SendEmail>()
+        .poll_interval(Duration::from_millis(10))
+        .start(Arc::new(AppState::new().with(conn.clone())))
+        .unwrap();
+
+    let mut job = None;
+    for _ in 0..200 {
+        job = entity::Entity::find_by_id(id).one(&conn).await.unwrap();
+        if job.as_ref().is_some_and(|job| job.status == FAILED) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    workers.shutdown().await;
+
+    let job = job.unwrap();
+    assert_eq!(job.status, FAILED);
+    assert_eq!(
+        job.last_error.as_deref(),
+        Some("no job registered as 'doomed'")
+    );
+}
+
+#[tokio::test]
+async fn test_shutdown_finishes_running_job() {
+    let conn = setup_db().await;
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel::<String>();
+    Jobs::new(conn.clone()).enqueue(Slow).await.unwrap();
+
+    let state = AppState::new().with(conn.clone()).with(events_tx);
+    let workers = queue().start(Arc::new(state)).unwrap();
+
+    assert_eq!(events_rx.recv().await.as_deref(), Some("started"));
+    workers.shutdown().await;
+
+    assert_eq!(events_rx.try_recv().as_deref(), Ok("finished"));
+    assert!(entity::Entity::find().all(&conn).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_delayed_job_waits() {
+    let conn = setup_db().await;
+    let (sent_tx, mut sent_rx) = mpsc::unbounded_channel::<String>();
+    Jobs::new(conn.clone())
+        .enqueue_in(
+            SendEmail {
+                to: "later@example.com".into(),
+            },
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+    let state = AppState::new().with(conn.clone()).with(sent_tx);
+    let workers = queue().start(Arc::new(state)).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    workers.shutdown().await;
+
+    assert!(sent_rx.try_recv().is_err());
+    assert_eq!(entity::Entity::find().all(&conn).await.unwrap().len(), 1);
+}
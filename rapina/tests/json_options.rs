@@ -0,0 +1,153 @@
+//! Integration tests for app-wide JSON options.
+
+use http::StatusCode;
+use rapina::json::{JsonOptions, JsonWith, RenameRule};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct Profile {
+    user_id: i32,
+    display_name: String,
+    home_address: Option<Address>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct Address {
+    street_name: String,
+}
+
+#[post("/profiles")]
+async fn create_profile(body: Json<Profile>) -> (StatusCode, Json<Profile>) {
+    (StatusCode::CREATED, Json(body.into_inner()))
+}
+
+#[get("/profiles/:id")]
+async fn get_profile(id: Path<i32>) -> Json<Profile> {
+    Json(Profile {
+        user_id: id.into_inner(),
+        display_name: "Ada".to_string(),
+        home_address: Some(Address {
+            street_name: "Main St".to_string(),
+        }),
+    })
+}
+
+#[get("/legacy/profiles/:id")]
+async fn get_legacy_profile(id: Path<i32>) -> JsonWith<Profile> {
+    Json(Profile {
+        user_id: id.into_inner(),
+        display_name: "Ada".to_string(),
+        home_address: None,
+    })
+    .with_options(JsonOptions::default())
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .openapi("Profiles", "1.0.0")
+        .json_options(JsonOptions {
+            rename_all: RenameRule::CamelCase,
+            pretty: false,
+        })
+        .router(
+            Router::new()
+                .post("/profiles", create_profile)
+                .get("/profiles/:id", get_profile)
+                .get("/legacy/profiles/:id", get_legacy_profile),
+        )
+}
+
+#[tokio::test]
+async fn test_responses_use_camel_case() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/profiles/7").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.text(),
+        r#"{"userId":7,"displayName":"Ada","homeAddress":{"streetName":"Main St"}}"#
+    );
+}
+
+#[tokio::test]
+async fn test_requests_accept_both_cases() {
+    let client = TestClient::new(app()).await;
+
+    let camel = serde_json::json!({
+        "userId": 1,
+        "displayName": "Ada",
+        "homeAddress": { "streetName": "Main St" },
+    });
+    let snake = serde_json::json!({
+        "user_id": 1,
+        "display_name": "Ada",
+        "home_address": { "street_name": "Main St" },
+    });
+
+    for body in [camel.clone(), snake] {
+        let response = client.post("/profiles").json(&body).send().await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let json: serde_json::Value = response.json();
+        assert_eq!(json, camel);
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_body_is_bad_request() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .post("/profiles")
+        .json(&serde_json::json!({ "userId": 1 }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_with_options_overrides_app() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/legacy/profiles/7").send().await;
+    assert_eq!(
+        response.text(),
+        r#"{"user_id":7,"display_name":"Ada","home_address":null}"#
+    );
+}
+
+#[tokio::test]
+async fn test_pretty_output() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .json_options(JsonOptions {
+            rename_all: RenameRule::None,
+            pretty: true,
+        })
+        .router(Router::new().get("/profiles/:id", get_profile));
+    let client = TestClient::new(app).await;
+
+    let text = client.get("/profiles/7").send().await.text();
+    assert!(text.starts_with("{\n  \"user_id\": 7,\n  \"display_name\": \"Ada\","));
+}
+
+#[tokio::test]
+async fn test_openapi_matches_wire_format() {
+    let client = TestClient::new(app()).await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+
+    let schema = &spec["paths"]["/profiles/{id}"]["get"]["responses"]["200"]["content"]["application/json"]
+        ["schema"];
+    assert_eq!(
+        schema["required"],
+        serde_json::json!(["userId", "displayName"])
+    );
+    assert!(schema["properties"]["homeAddress"].is_object());
+    assert!(schema["properties"].get("user_id").is_none());
+    assert_eq!(
+        schema["$defs"]["Address"]["required"],
+        serde_json::json!(["streetName"])
+    );
+}
@@ -0,0 +1,162 @@
+//! Integration tests for the AWS Lambda adapter.
+//!
+//! Events under `tests/fixtures/lambda` are shaped after payloads recorded
+//! from API Gateway HTTP APIs and Application Load Balancers.
+
+#![cfg(feature = "lambda")]
+
+use http::StatusCode;
+use rapina::lambda::handle_event;
+use rapina::prelude::*;
+use rapina::response::BoxBody;
+use rapina::service::RapinaService;
+use serde_json::{Value, json};
+
+const PNG_HEADER: &[u8] = &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+fn fixture(name: &str) -> Value {
+    let path = format!(
+        "{}/tests/fixtures/lambda/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn service() -> RapinaService {
+    use rapina::extract::FromRequest;
+
+    let router = Router::new()
+        .route(
+            http::Method::GET,
+            "/users/:id",
+            |req, params, _| async move {
+                let cookies = req
+                    .headers()
+                    .get("cookie")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                let body = Json(json!({
+                    "id": params.get("id"),
+                    "query": req.uri().query(),
+                    "cookies": cookies,
+                }));
+
+                let mut response = body.into_response();
+                response.headers_mut().append(
+                    "set-cookie",
+                    http::HeaderValue::from_static("seen=1; Path=/"),
+                );
+                response.headers_mut().append(
+                    "set-cookie",
+                    http::HeaderValue::from_static("visits=2; Path=/"),
+                );
+                response
+            },
+        )
+        .route(
+            http::Method::POST,
+            "/users",
+            |req, params, state| async move {
+                Json::<Value>::from_request(req, &params, &state)
+                    .await
+                    .map(|body| (StatusCode::CREATED, Json(body.into_inner())))
+            },
+        )
+        .route(http::Method::GET, "/logo.png", |_, _, _| async {
+            http::Response::builder()
+                .header("content-type", "image/png")
+                .body(BoxBody::new(Bytes::from_static(PNG_HEADER)))
+                .unwrap()
+        })
+        .route(http::Method::GET, "/tags", |req, _, _| async move {
+            req.uri().query().unwrap_or_default().to_string()
+        });
+
+    Rapina::new()
+        .with_introspection(false)
+        .router(router)
+        .into_service()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_api_gateway_v2_get() {
+    let response = handle_event(&service(), fixture("apigw_v2_get.json"))
+        .await
+        .unwrap();
+
+    assert_eq!(response["statusCode"], 200);
+    assert_eq!(response["isBase64Encoded"], false);
+    assert_eq!(response["headers"]["content-type"], "application/json");
+    assert_eq!(
+        response["cookies"],
+        json!(["seen=1; Path=/", "visits=2; Path=/"])
+    );
+    assert!(response["headers"].get("set-cookie").is_none());
+
+    let body: Value = serde_json::from_str(response["body"].as_str().unwrap()).unwrap();
+    assert_eq!(body["id"], "42");
+    assert_eq!(body["query"], "include=posts&page=2");
+    assert_eq!(body["cookies"], "session=abc123; theme=dark");
+}
+
+#[tokio::test]
+async fn test_api_gateway_v2_base64_request_body() {
+    let response = handle_event(&service(), fixture("apigw_v2_post_base64.json"))
+        .await
+        .unwrap();
+
+    assert_eq!(response["statusCode"], 201);
+    assert_eq!(response["isBase64Encoded"], false);
+    let body: Value = serde_json::from_str(response["body"].as_str().unwrap()).unwrap();
+    assert_eq!(body, json!({"name": "Ada", "email": "ada@example.com"}));
+}
+
+#[tokio::test]
+async fn test_alb_binary_response_is_base64_encoded() {
+    let response = handle_event(&service(), fixture("alb_get.json"))
+        .await
+        .unwrap();
+
+    assert_eq!(response["statusCode"], 200);
+    assert_eq!(response["statusDescription"], "200 OK");
+    assert_eq!(response["headers"]["content-type"], "image/png");
+    assert!(response.get("multiValueHeaders").is_none());
+    assert_eq!(response["isBase64Encoded"], true);
+    assert_eq!(response["body"], "iVBORw0KGgo=");
+}
+
+#[tokio::test]
+async fn test_alb_multi_value_headers_and_query() {
+    let response = handle_event(&service(), fixture("alb_multi_value.json"))
+        .await
+        .unwrap();
+
+    assert_eq!(response["statusCode"], 200);
+    assert!(response.get("headers").is_none());
+    assert_eq!(
+        response["multiValueHeaders"]["content-type"],
+        json!(["text/plain; charset=utf-8"])
+    );
+    assert_eq!(response["body"], "tag=rust&tag=web");
+}
+
+#[tokio::test]
+async fn test_unknown_route_passes_through_status() {
+    let mut event = fixture("apigw_v2_get.json");
+    event["rawPath"] = json!("/missing");
+    event["requestContext"]["http"]["path"] = json!("/missing");
+
+    let response = handle_event(&service(), event).await.unwrap();
+
+    assert_eq!(response["statusCode"], 404);
+}
+
+#[tokio::test]
+async fn test_unsupported_event_is_an_error() {
+    let event = json!({"Records": [{"eventSource": "aws:sqs"}]});
+
+    assert!(handle_event(&service(), event).await.is_err());
+}
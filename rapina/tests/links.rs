@@ -0,0 +1,127 @@
+//! Integration tests for links built from route names.
+
+use http::StatusCode;
+use rapina::links::{Linked, UrlFor};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Serialize, JsonSchema)]
+struct Post {
+    id: i32,
+    title: String,
+}
+
+#[get("/posts")]
+async fn list_posts(urls: UrlFor) -> Result<Linked<Vec<Post>>> {
+    let links = urls.links().route("self", "list_posts", &[])?;
+    Ok(Linked::new(Vec::new(), links))
+}
+
+#[get("/posts/:id")]
+async fn get_post(id: Path<i32>, urls: UrlFor) -> Result<Linked<Post>> {
+    let id = id.into_inner();
+    let links = urls
+        .links()
+        .route("self", "get_post", &[("id", &id)])?
+        .route("comments", "list_comments", &[("post_id", &id)])?
+        .route("collection", "list_posts", &[])?;
+    let post = Post {
+        id,
+        title: "Hello".to_string(),
+    };
+    Ok(Linked::new(post, links))
+}
+
+#[get("/posts/:post_id/comments")]
+async fn list_comments(post_id: Path<i32>) -> String {
+    post_id.into_inner().to_string()
+}
+
+#[get("/broken")]
+async fn broken(urls: UrlFor) -> Result<Linked<Post>> {
+    let links = urls.links().route("self", "no_such_route", &[])?;
+    Ok(Linked::new(
+        Post {
+            id: 0,
+            title: String::new(),
+        },
+        links,
+    ))
+}
+
+fn routes() -> Router {
+    Router::new()
+        .get("/posts", list_posts)
+        .get("/posts/:id", get_post)
+        .get("/posts/:post_id/comments", list_comments)
+        .get("/broken", broken)
+}
+
+#[tokio::test]
+async fn test_links_from_route_names() {
+    let client = TestClient::new(Rapina::new().with_introspection(false).router(routes())).await;
+
+    let response = client.get("/posts/5").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "data": { "id": 5, "title": "Hello" },
+            "links": {
+                "self": "/posts/5",
+                "comments": "/posts/5/comments",
+                "collection": "/posts",
+            },
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_links_respect_group_prefix() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().group("/api/v1", routes()));
+    let client = TestClient::new(app).await;
+
+    let json: serde_json::Value = client.get("/api/v1/posts/5").send().await.json();
+    assert_eq!(json["links"]["self"], "/api/v1/posts/5");
+    assert_eq!(json["links"]["comments"], "/api/v1/posts/5/comments");
+
+    let json: serde_json::Value = client.get("/api/v1/posts").send().await.json();
+    assert_eq!(
+        json,
+        serde_json::json!({ "data": [], "links": { "self": "/api/v1/posts" } })
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_route_is_internal_error() {
+    let client = TestClient::new(Rapina::new().with_introspection(false).router(routes())).await;
+
+    let response = client.get("/broken").send().await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_openapi_documents_envelope() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .openapi("Posts", "1.0.0")
+        .router(routes());
+    let client = TestClient::new(app).await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+
+    let schema = &spec["paths"]["/posts/{id}"]["get"]["responses"]["200"]["content"]["application/json"]
+        ["schema"];
+    assert_eq!(schema["required"], serde_json::json!(["data", "links"]));
+    assert_eq!(
+        schema["$defs"]["Post"]["properties"]["title"]["type"],
+        "string"
+    );
+    assert_eq!(
+        schema["$defs"]["Links"]["additionalProperties"]["type"],
+        "string"
+    );
+}
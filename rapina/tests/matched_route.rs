@@ -0,0 +1,81 @@
+//! Integration tests for the matched route pattern.
+
+use http::{HeaderValue, StatusCode};
+use hyper::body::Incoming;
+use rapina::middleware::BoxFuture;
+use rapina::prelude::*;
+use rapina::response::BoxBody;
+use rapina::testing::TestClient;
+
+/// Echoes the pattern it sees before dispatch in an `x-route` header.
+struct RouteHeader;
+
+impl Middleware for RouteHeader {
+    fn handle<'a>(
+        &'a self,
+        req: http::Request<Incoming>,
+        ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, http::Response<BoxBody>> {
+        let route = ctx.matched_route().map(|route| route.pattern.clone());
+        Box::pin(async move {
+            let mut response = next.run(req).await;
+            if let Some(route) = route {
+                response
+                    .headers_mut()
+                    .insert("x-route", HeaderValue::from_str(&route).unwrap());
+            }
+            response
+        })
+    }
+}
+
+#[get("/users/current")]
+async fn current_user(route: MatchedRoute) -> String {
+    format!("{} {}", route.name, route.pattern)
+}
+
+#[get("/users/:id")]
+async fn get_user(route: MatchedRoute) -> String {
+    format!("{} {}", route.name, route.pattern)
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .middleware(RouteHeader)
+        .router(
+            Router::new()
+                .get("/users/current", current_user)
+                .get("/users/:id", get_user),
+        )
+}
+
+#[tokio::test]
+async fn test_matched_route_for_static_route() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/users/current").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-route"], "/users/current");
+    assert_eq!(response.text(), "current_user /users/current");
+}
+
+#[tokio::test]
+async fn test_matched_route_for_param_route() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/users/42").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-route"], "/users/:id");
+    assert_eq!(response.text(), "get_user /users/:id");
+}
+
+#[tokio::test]
+async fn test_no_matched_route_for_unknown_path() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/nope").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.headers().get("x-route").is_none());
+}
@@ -159,3 +159,25 @@ fn test_metrics_registry_encode_returns_text() {
     assert!(!out.is_empty());
     assert!(out.contains("# TYPE"));
 }
+
+// ── route concurrency limits ─────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_metrics_route_concurrency() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_metrics(true)
+        .router(
+            Router::new()
+                .route(http::Method::GET, "/report", |_, _, _| async { "report" })
+                .concurrency_limit(2),
+        );
+    let client = TestClient::new(app).await;
+
+    client.get("/report").send().await;
+
+    let body = client.get("/metrics").send().await.text();
+    assert!(body.contains(r#"route_concurrency_limit{path="/report"} 2"#));
+    assert!(body.contains(r#"route_concurrency_in_flight{path="/report"} 0"#));
+    assert!(body.contains(r#"route_concurrency_queued{path="/report"} 0"#));
+}
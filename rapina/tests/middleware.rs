@@ -91,6 +91,90 @@ async fn test_timeout_middleware_passes_fast_request() {
     assert_eq!(response.text(), "fast response");
 }
 
+#[tokio::test]
+async fn test_timeout_middleware_cancels_slow_request() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TimeoutMiddleware::new(Duration::from_millis(50)))
+        .router(
+            Router::new().route(http::Method::GET, "/hang", |_, _, _| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                "never"
+            }),
+        );
+
+    let client = TestClient::new(app).await;
+    let response = client.get("/hang").send().await;
+
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "REQUEST_TIMEOUT");
+    assert!(body["trace_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_timeout_middleware_custom_status() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(
+            TimeoutMiddleware::new(Duration::from_millis(50)).status(StatusCode::GATEWAY_TIMEOUT),
+        )
+        .router(
+            Router::new().route(http::Method::GET, "/hang", |_, _, _| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                "never"
+            }),
+        );
+
+    let client = TestClient::new(app).await;
+    let response = client.get("/hang").send().await;
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "GATEWAY_TIMEOUT");
+}
+
+#[tokio::test]
+async fn test_timeout_middleware_skips_no_timeout_routes() {
+    let slow = Router::new()
+        .route(http::Method::GET, "/stream", |_, _, _| async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            "done"
+        })
+        .no_timeout();
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TimeoutMiddleware::new(Duration::from_millis(50)))
+        .router(
+            Router::new()
+                .nest("", slow)
+                .route(http::Method::GET, "/hang", |_, _, _| async {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    "never"
+                })
+                .at("/report", |r| {
+                    r.route(http::Method::GET, |_, _, _| async {
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        "report"
+                    })
+                    .no_timeout()
+                }),
+        );
+
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/stream").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "done");
+
+    let response = client.get("/report").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "report");
+
+    let response = client.get("/hang").send().await;
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+}
+
 #[tokio::test]
 async fn test_body_limit_middleware_allows_small_body() {
     let app = Rapina::new()
@@ -354,6 +438,328 @@ async fn test_cors_permissive_returns_wildcard() {
     assert_eq!(origin_header.unwrap().to_str().unwrap(), "*");
 }
 
+fn cors_app(config: CorsConfig) -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .with_cors(config)
+        .router(Router::new().route(http::Method::GET, "/", |_, _, _| async { "ok" }))
+}
+
+fn spa_cors() -> CorsConfig {
+    CorsConfig::with_origins(vec!["https://app.example.com".to_string()])
+        .max_age(Duration::from_secs(600))
+        .exposed_headers(vec![
+            http::HeaderName::from_static("x-request-id"),
+            http::HeaderName::from_static("x-total-count"),
+        ])
+}
+
+#[tokio::test]
+async fn test_cors_credentials_preflight_and_simple_requests() {
+    // (config, origin sent, expected allow-origin, expected allow-credentials)
+    let cases = [
+        (
+            CorsConfig::permissive(),
+            "https://anyone.com",
+            Some("*"),
+            None,
+        ),
+        (
+            spa_cors(),
+            "https://app.example.com",
+            Some("https://app.example.com"),
+            None,
+        ),
+        (
+            spa_cors().allow_credentials(true),
+            "https://app.example.com",
+            Some("https://app.example.com"),
+            Some("true"),
+        ),
+        (
+            spa_cors().allow_credentials(true),
+            "https://evil.com",
+            None,
+            None,
+        ),
+    ];
+
+    for (config, origin, allow_origin, allow_credentials) in cases {
+        let client = TestClient::new(cors_app(config)).await;
+        let preflight = client
+            .request(http::Method::OPTIONS, "/")
+            .header("Origin", origin)
+            .header("Access-Control-Request-Method", "GET")
+            .send()
+            .await;
+        let simple = client.get("/").header("Origin", origin).send().await;
+
+        for response in [&preflight, &simple] {
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .map(|v| v.to_str().unwrap().to_string())
+            };
+            assert_eq!(
+                header("access-control-allow-origin").as_deref(),
+                allow_origin,
+                "{}",
+                origin
+            );
+            assert_eq!(
+                header("access-control-allow-credentials").as_deref(),
+                allow_credentials,
+                "{}",
+                origin
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cors_max_age_on_preflight_only() {
+    let client = TestClient::new(cors_app(spa_cors())).await;
+
+    let response = client
+        .request(http::Method::OPTIONS, "/")
+        .header("Origin", "https://app.example.com")
+        .header("Access-Control-Request-Method", "GET")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()["access-control-max-age"], "600");
+
+    let response = client
+        .get("/")
+        .header("Origin", "https://app.example.com")
+        .send()
+        .await;
+    assert!(response.headers().get("access-control-max-age").is_none());
+}
+
+#[tokio::test]
+async fn test_cors_exposed_headers_on_simple_requests() {
+    let client = TestClient::new(cors_app(spa_cors().allow_credentials(true))).await;
+
+    let response = client
+        .get("/")
+        .header("Origin", "https://app.example.com")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["access-control-expose-headers"],
+        "x-request-id, x-total-count"
+    );
+
+    // Nothing is exposed to origins that aren't allowed
+    let response = client
+        .get("/")
+        .header("Origin", "https://evil.com")
+        .send()
+        .await;
+    assert!(
+        response
+            .headers()
+            .get("access-control-expose-headers")
+            .is_none()
+    );
+}
+
+#[test]
+#[should_panic(expected = "can't be allowed for any origin")]
+fn test_cors_credentials_with_any_origin_rejected() {
+    let _ = cors_app(CorsConfig::permissive().allow_credentials(true));
+}
+
+fn scoped_cors_app() -> Rapina {
+    let public = Router::new()
+        .route(http::Method::GET, "/posts", |_, _, _| async { "posts" })
+        .cors(CorsConfig::permissive())
+        .route(http::Method::GET, "/tags", |_, _, _| async { "tags" });
+    let admin = Router::new()
+        .route(http::Method::DELETE, "/users/:id", |_, _, _| async {
+            "deleted"
+        })
+        .route(http::Method::GET, "/users", |_, _, _| async { "users" })
+        .cors(CorsConfig::with_origins(vec![
+            "https://dash.internal".to_string(),
+        ]));
+
+    Rapina::new()
+        .with_introspection(false)
+        .with_cors(CorsConfig::with_origins(vec![
+            "https://app.example.com".to_string(),
+        ]))
+        .router(
+            Router::new()
+                .group("/public", public)
+                .group("/admin", admin)
+                .route(http::Method::GET, "/", |_, _, _| async { "ok" }),
+        )
+}
+
+#[tokio::test]
+async fn test_cors_route_scoped_preflight() {
+    let client = TestClient::new(scoped_cors_app()).await;
+
+    let response = client
+        .request(http::Method::OPTIONS, "/public/posts")
+        .header("Origin", "https://anyone.com")
+        .header("Access-Control-Request-Method", "GET")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+    let response = client
+        .request(http::Method::OPTIONS, "/admin/users/1")
+        .header("Origin", "https://dash.internal")
+        .header("Access-Control-Request-Method", "DELETE")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://dash.internal"
+    );
+
+    // The global origin is not allowed on the admin group
+    let response = client
+        .request(http::Method::OPTIONS, "/admin/users/1")
+        .header("Origin", "https://app.example.com")
+        .header("Access-Control-Request-Method", "DELETE")
+        .send()
+        .await;
+    assert!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_cors_route_scoped_actual_request() {
+    let client = TestClient::new(scoped_cors_app()).await;
+
+    let response = client
+        .get("/public/posts")
+        .header("Origin", "https://anyone.com")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+    let response = client
+        .delete("/admin/users/1")
+        .header("Origin", "https://anyone.com")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none()
+    );
+
+    // Routes outside both groups use the global policy
+    let response = client
+        .get("/")
+        .header("Origin", "https://app.example.com")
+        .send()
+        .await;
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://app.example.com"
+    );
+}
+
+#[tokio::test]
+async fn test_cors_group_policy_covers_every_route() {
+    let client = TestClient::new(scoped_cors_app()).await;
+
+    // /tags is added after the public group's cors() call
+    let response = client
+        .request(http::Method::OPTIONS, "/public/tags")
+        .header("Origin", "https://anyone.com")
+        .header("Access-Control-Request-Method", "GET")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+    let response = client
+        .get("/public/tags")
+        .header("Origin", "https://anyone.com")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+    // /users is not the admin group's last route before cors()
+    let response = client
+        .request(http::Method::OPTIONS, "/admin/users")
+        .header("Origin", "https://dash.internal")
+        .header("Access-Control-Request-Method", "GET")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://dash.internal"
+    );
+
+    let response = client
+        .get("/admin/users")
+        .header("Origin", "https://app.example.com")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_cors_route_scoped_without_global_policy() {
+    let public = Router::new()
+        .route(http::Method::GET, "/posts", |_, _, _| async { "posts" })
+        .cors(CorsConfig::permissive());
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new().group("/public", public).route(
+            http::Method::GET,
+            "/private",
+            |_, _, _| async { "ok" },
+        ),
+    );
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .get("/public/posts")
+        .header("Origin", "https://anyone.com")
+        .send()
+        .await;
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+    let response = client
+        .get("/private")
+        .header("Origin", "https://anyone.com")
+        .send()
+        .await;
+    assert!(
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none()
+    );
+}
+
 #[tokio::test]
 async fn test_rate_limit_allows_under_limit() {
     let app = Rapina::new()
@@ -0,0 +1,153 @@
+//! Integration tests for middleware phases.
+
+use std::sync::{Arc, Mutex};
+
+use http::StatusCode;
+use hyper::body::Incoming;
+use rapina::middleware::{BoxFuture, CorsConfig, Middleware, MiddlewareStack, Next, Phase};
+use rapina::prelude::*;
+use rapina::response::BoxBody;
+use rapina::testing::TestClient;
+use serde_json::{Value, json};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Logs its label on the way in and on the way out.
+struct Recorder {
+    label: &'static str,
+    log: Log,
+}
+
+impl Middleware for Recorder {
+    fn handle<'a>(
+        &'a self,
+        req: hyper::Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, hyper::Response<BoxBody>> {
+        Box::pin(async move {
+            self.log.lock().unwrap().push(format!("> {}", self.label));
+            let response = next.run(req).await;
+            self.log.lock().unwrap().push(format!("< {}", self.label));
+            response
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        self.label
+    }
+}
+
+fn recorder(label: &'static str, log: &Log) -> Recorder {
+    Recorder {
+        label,
+        log: log.clone(),
+    }
+}
+
+#[tokio::test]
+async fn test_phases_run_in_order_regardless_of_registration() {
+    let log = Log::default();
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware_in(Phase::PostProcessing, recorder("post", &log))
+        .middleware(recorder("normal-1", &log))
+        .middleware_in(Phase::Auth, recorder("auth", &log))
+        .middleware(recorder("normal-2", &log))
+        .middleware_in(Phase::PreRouting, recorder("pre", &log))
+        .router(Router::new().route(http::Method::GET, "/", |_, _, _| async { "ok" }));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "> post",
+            "> pre",
+            "> auth",
+            "> normal-1",
+            "> normal-2",
+            "< normal-2",
+            "< normal-1",
+            "< auth",
+            "< pre",
+            "< post",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_cors_preflight_answered_before_auth() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_auth(AuthConfig::new("test-secret-phases", 3600))
+        .with_cors(CorsConfig::with_origins(vec![
+            "https://app.example".to_string(),
+        ]))
+        .router(Router::new().route(http::Method::POST, "/orders", |_, _, _| async { "ok" }));
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .request(http::Method::OPTIONS, "/orders")
+        .header("Origin", "https://app.example")
+        .header("Access-Control-Request-Method", "POST")
+        .send()
+        .await;
+
+    assert_ne!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://app.example"
+    );
+}
+
+#[tokio::test]
+async fn test_middleware_introspection_lists_resolved_order() {
+    let log = Log::default();
+    let app = Rapina::new()
+        .with_introspection(true)
+        .without_body_limit()
+        .middleware(recorder("Audit", &log))
+        .with_auth(AuthConfig::new("test-secret-phases", 3600))
+        .with_cors(CorsConfig::permissive())
+        .public_route("GET", "/__rapina/middleware")
+        .router(Router::new());
+    let client = TestClient::new(app).await;
+
+    let body: Value = client.get("/__rapina/middleware").send().await.json();
+
+    assert_eq!(
+        body,
+        json!([
+            {"name": "CorsMiddleware", "phase": "pre_routing"},
+            {"name": "AuthMiddleware", "phase": "auth"},
+            {"name": "Audit", "phase": "normal"},
+        ])
+    );
+}
+
+#[test]
+fn test_ordering_warnings() {
+    use rapina::middleware::CorsMiddleware;
+
+    let log = Log::default();
+    let mut stack = MiddlewareStack::new();
+    stack.add_in(
+        Phase::PreRouting,
+        CorsMiddleware::new(CorsConfig::permissive()),
+    );
+    stack.add_in(Phase::Auth, recorder("AuthMiddleware", &log));
+    assert!(stack.ordering_warnings().is_empty());
+
+    let mut stack = MiddlewareStack::new();
+    stack.add_in(Phase::PreRouting, recorder("AuthMiddleware", &log));
+    stack.add(CorsMiddleware::new(CorsConfig::permissive()));
+    assert_eq!(
+        stack.ordering_warnings(),
+        [
+            "CorsMiddleware runs after AuthMiddleware, so CORS preflight requests are rejected by auth before they get an answer"
+        ]
+    );
+}
@@ -72,3 +72,54 @@ async fn test_migration_rollback() {
         .await
         .unwrap();
 }
+
+#[test]
+fn test_parse_migrate_command() {
+    use rapina::migration::MigrateCommand;
+
+    assert_eq!(
+        MigrateCommand::parse("down"),
+        Ok(MigrateCommand::Down { steps: 1 })
+    );
+    assert_eq!(
+        MigrateCommand::parse("down:3"),
+        Ok(MigrateCommand::Down { steps: 3 })
+    );
+    assert_eq!(MigrateCommand::parse("fresh"), Ok(MigrateCommand::Fresh));
+    assert!(MigrateCommand::parse("down:0").is_err());
+    assert!(MigrateCommand::parse("up").is_err());
+}
+
+#[tokio::test]
+async fn test_migrate_command_down_and_fresh() {
+    use rapina::migration::MigrateCommand;
+    use rapina::sea_orm::{ConnectionTrait, Statement};
+
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let applied = |conn: &rapina::sea_orm::DatabaseConnection| {
+        let conn = conn.clone();
+        async move {
+            let row = conn
+                .query_one(Statement::from_string(
+                    conn.get_database_backend(),
+                    "SELECT COUNT(*) AS n FROM seaql_migrations",
+                ))
+                .await
+                .unwrap()
+                .unwrap();
+            row.try_get::<i64>("", "n").unwrap()
+        }
+    };
+
+    rapina::migration::run_pending::<Migrator>(&conn)
+        .await
+        .unwrap();
+    MigrateCommand::Down { steps: 1 }
+        .run::<Migrator>(&conn)
+        .await
+        .unwrap();
+    assert_eq!(applied(&conn).await, 0);
+
+    MigrateCommand::Fresh.run::<Migrator>(&conn).await.unwrap();
+    assert_eq!(applied(&conn).await, 1);
+}
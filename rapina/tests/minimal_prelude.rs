@@ -0,0 +1,51 @@
+//! Compiles an app against `rapina::prelude::minimal`, which leaves `Error`
+//! and `Result` free for the application's own types.
+
+use rapina::prelude::minimal::*;
+use rapina::testing::TestClient;
+
+/// Application error type that would collide with the full prelude.
+#[derive(Debug)]
+enum Error {
+    NotFound(u64),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+impl From<Error> for rapina::error::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::NotFound(id) => {
+                rapina::error::Error::not_found(format!("item {} not found", id))
+            }
+        }
+    }
+}
+
+fn lookup(id: u64) -> Result<String> {
+    if id == 1 {
+        Ok("first".to_string())
+    } else {
+        Err(Error::NotFound(id))
+    }
+}
+
+#[get("/items/:id")]
+async fn get_item(id: Path<u64>) -> rapina::error::Result<String> {
+    Ok(lookup(id.into_inner())?)
+}
+
+#[tokio::test]
+async fn test_minimal_prelude_with_user_error_type() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/items/:id", get_item));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/items/1").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "first");
+
+    let response = client.get("/items/2").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
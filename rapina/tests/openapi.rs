@@ -0,0 +1,144 @@
+//! Integration tests for the generated OpenAPI spec.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+// Mirrors the error type `rapina add resource` scaffolds
+enum TodoError {
+    NotFound(i32),
+}
+
+impl IntoApiError for TodoError {
+    fn into_api_error(self) -> Error {
+        match self {
+            TodoError::NotFound(id) => Error::not_found(format!("Todo {} not found", id)),
+        }
+    }
+}
+
+impl DocumentedError for TodoError {
+    fn error_variants() -> Vec<ErrorVariant> {
+        vec![
+            ErrorVariant {
+                status: 404,
+                code: "NOT_FOUND",
+                description: "Todo not found",
+            },
+            ErrorVariant {
+                status: 500,
+                code: "DATABASE_ERROR",
+                description: "Database operation failed",
+            },
+        ]
+    }
+}
+
+#[get("/todos/:id")]
+#[errors(TodoError)]
+async fn get_todo(id: Path<i32>) -> Result<String> {
+    Err(TodoError::NotFound(id.into_inner()).into())
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Todo {
+    id: i32,
+    title: String,
+}
+
+#[get("/todos")]
+async fn list_todos() -> Json<Vec<Todo>> {
+    Json(Vec::new())
+}
+
+// Like a `schema!` entity marked #[no_json_schema]
+#[derive(Serialize)]
+struct Invoice {
+    total: String,
+}
+
+#[get("/invoices/:id")]
+async fn get_invoice(id: Path<i32>) -> Result<Json<Invoice>> {
+    Ok(Json(Invoice {
+        total: format!("{}.00", id.into_inner()),
+    }))
+}
+
+async fn spec() -> serde_json::Value {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .openapi("Todos", "1.0.0")
+        .router(
+            Router::new()
+                .get("/todos", list_todos)
+                .get("/todos/:id", get_todo)
+                .get("/invoices/:id", get_invoice),
+        );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/__rapina/openapi.json").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()
+}
+
+#[tokio::test]
+async fn test_documented_errors_reference_error_body() {
+    let spec = spec().await;
+
+    let responses = &spec["paths"]["/todos/{id}"]["get"]["responses"];
+    assert_eq!(responses["404"]["description"], "Todo not found");
+    for status in ["404", "500", "default"] {
+        assert_eq!(
+            responses[status]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ApiErrorBody",
+            "response {}",
+            status
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_error_body_published_under_components() {
+    let spec = spec().await;
+
+    let schema = &spec["components"]["schemas"]["ApiErrorBody"];
+    assert_eq!(schema["type"], "object");
+    assert!(schema["properties"]["trace_id"].is_object());
+    assert!(schema["properties"]["error"]["properties"]["code"].is_object());
+}
+
+#[tokio::test]
+async fn test_response_schema_from_json_schema() {
+    let spec = spec().await;
+
+    let schema = &spec["paths"]["/todos"]["get"]["responses"]["200"]["content"]["application/json"]
+        ["schema"];
+    assert_eq!(schema["type"], "array");
+    assert!(schema.to_string().contains("title"));
+}
+
+#[tokio::test]
+async fn test_response_without_json_schema_is_opaque_object() {
+    let spec = spec().await;
+
+    let schema = &spec["paths"]["/invoices/{id}"]["get"]["responses"]["200"]["content"]["application/json"]
+        ["schema"];
+    assert_eq!(
+        *schema,
+        serde_json::json!({ "type": "object", "title": "Invoice" })
+    );
+}
+
+#[tokio::test]
+async fn test_error_body_matches_response() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/todos/:id", get_todo));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/todos/7").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+    assert!(body["trace_id"].is_string());
+}
@@ -0,0 +1,147 @@
+//! Integration tests for paginating a query with `Paginate::exec`.
+
+#![cfg(feature = "sqlite")]
+
+use http::StatusCode;
+use rapina::database::{Db, DbError};
+use rapina::pagination::{Paginate, Paginated};
+use rapina::prelude::*;
+use rapina::sea_orm::{
+    ConnectionTrait, Database, DatabaseConnection, EntityTrait, QueryOrder, Schema, Set,
+};
+use rapina::testing::TestClient;
+
+schema! {
+    #[timestamps(none)]
+    Widget {
+        name: String,
+    }
+}
+
+const ROWS: usize = 25;
+
+#[get("/widgets")]
+async fn list_widgets(db: Db, page: Paginate) -> Result<Paginated<widget::Model>> {
+    page.exec(Widget::find().order_by_asc(widget::Column::Id), db.read())
+        .await
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let backend = conn.get_database_backend();
+    let schema = Schema::new(backend);
+    conn.execute(backend.build(&schema.create_table_from_entity(Widget)))
+        .await
+        .unwrap();
+
+    let models = (1..=ROWS).map(|i| widget::ActiveModel {
+        name: Set(format!("widget-{}", i)),
+        ..Default::default()
+    });
+    Widget::insert_many(models)
+        .exec(&conn)
+        .await
+        .map_err(DbError)
+        .unwrap();
+    conn
+}
+
+async fn client() -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .openapi("Widgets", "1.0.0")
+        .state(setup_db().await)
+        .router(Router::new().get("/widgets", list_widgets));
+    TestClient::new(app).await
+}
+
+fn names(json: &serde_json::Value) -> Vec<&str> {
+    json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| row["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pages_through_the_table() {
+    let client = client().await;
+
+    let json: serde_json::Value = client.get("/widgets?per_page=10").send().await.json();
+    assert_eq!(names(&json).len(), 10);
+    assert_eq!(names(&json)[0], "widget-1");
+    assert_eq!(json["total"], 25);
+    assert_eq!(json["total_pages"], 3);
+    assert_eq!(json["has_prev"], false);
+    assert_eq!(json["has_next"], true);
+
+    let json: serde_json::Value = client
+        .get("/widgets?page=3&per_page=10")
+        .send()
+        .await
+        .json();
+    assert_eq!(
+        names(&json),
+        [
+            "widget-21",
+            "widget-22",
+            "widget-23",
+            "widget-24",
+            "widget-25"
+        ]
+    );
+    assert_eq!(json["has_prev"], true);
+    assert_eq!(json["has_next"], false);
+}
+
+#[tokio::test]
+async fn test_page_past_the_end_is_empty_with_totals() {
+    let client = client().await;
+
+    let response = client.get("/widgets?page=4&per_page=10").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"], serde_json::json!([]));
+    assert_eq!(json["page"], 4);
+    assert_eq!(json["total"], 25);
+    assert_eq!(json["total_pages"], 3);
+    assert_eq!(json["has_prev"], true);
+    assert_eq!(json["has_next"], false);
+}
+
+#[tokio::test]
+async fn test_per_page_over_the_max_is_clamped() {
+    let client = client().await;
+
+    let json: serde_json::Value = client.get("/widgets?per_page=1000").send().await.json();
+    assert_eq!(json["per_page"], 100);
+    assert_eq!(names(&json).len(), ROWS);
+    assert_eq!(json["total_pages"], 1);
+}
+
+#[tokio::test]
+async fn test_page_zero_is_a_bad_request() {
+    let client = client().await;
+
+    let response = client.get("/widgets?page=0").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_openapi_documents_the_envelope() {
+    let client = client().await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+    let schema = &spec["paths"]["/widgets"]["get"]["responses"]["200"]["content"]["application/json"]
+        ["schema"];
+    for field in ["data", "page", "per_page", "total", "total_pages"] {
+        assert!(
+            schema["properties"].get(field).is_some(),
+            "missing {}: {}",
+            field,
+            schema
+        );
+    }
+    assert_eq!(schema["properties"]["data"]["type"], "array");
+}
@@ -67,7 +67,7 @@ async fn test_paginate_explicit_params_via_handler() {
 }
 
 #[tokio::test]
-async fn test_paginate_invalid_page_returns_422() {
+async fn test_paginate_page_zero_returns_400() {
     let app = Rapina::new()
         .with_introspection(false)
         .router(Router::new().route(
@@ -85,11 +85,11 @@ async fn test_paginate_invalid_page_returns_422() {
     let client = TestClient::new(app).await;
     let response = client.get("/items?page=0").send().await;
 
-    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
 
 #[tokio::test]
-async fn test_paginate_per_page_exceeds_max_returns_422() {
+async fn test_paginate_per_page_exceeds_max_clamps() {
     let app = Rapina::new()
         .with_introspection(false)
         .router(Router::new().route(
@@ -107,7 +107,9 @@ async fn test_paginate_per_page_exceeds_max_returns_422() {
     let client = TestClient::new(app).await;
     let response = client.get("/items?per_page=101").send().await;
 
-    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["per_page"], 100);
 }
 
 #[tokio::test]
@@ -139,9 +141,11 @@ async fn test_paginate_respects_custom_config() {
     let json: serde_json::Value = response.json();
     assert_eq!(json["per_page"], 25);
 
-    // Exceeding custom max returns 422
+    // Exceeding custom max clamps to it
     let response = client.get("/items?per_page=51").send().await;
-    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["per_page"], 50);
 
     // Within custom max is fine
     let response = client.get("/items?per_page=50").send().await;
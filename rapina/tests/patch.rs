@@ -0,0 +1,197 @@
+//! Integration tests for `PATCH` routes with merge patch and JSON Patch bodies.
+
+use std::sync::{Arc, Mutex};
+
+use http::StatusCode;
+use rapina::patch::{JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct Todo {
+    title: String,
+    done: bool,
+    notes: Option<String>,
+    tags: Vec<String>,
+}
+
+type Store = Arc<Mutex<Todo>>;
+
+#[patch("/todo", consumes = "application/merge-patch+json")]
+async fn merge_todo(store: State<Store>, patch: MergePatch<Todo>) -> Result<Json<Todo>> {
+    let store = store.into_inner();
+    let mut todo = store.lock().unwrap();
+    *todo = patch.apply(&*todo)?;
+    Ok(Json(todo.clone()))
+}
+
+#[patch("/todo", consumes = "application/json-patch+json")]
+async fn json_patch_todo(store: State<Store>, patch: JsonPatch) -> Result<Json<Todo>> {
+    let store = store.into_inner();
+    let mut todo = store.lock().unwrap();
+    *todo = patch.apply(&*todo)?;
+    Ok(Json(todo.clone()))
+}
+
+#[patch("/any")]
+async fn merge_only(_patch: MergePatch<Todo>) -> StatusCode {
+    StatusCode::NO_CONTENT
+}
+
+fn todo() -> Todo {
+    Todo {
+        title: "Buy milk".into(),
+        done: false,
+        notes: Some("oat".into()),
+        tags: vec!["errands".into()],
+    }
+}
+
+async fn client() -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .openapi("Todos", "1.0.0")
+        .state(Arc::new(Mutex::new(todo())))
+        .router(
+            Router::new()
+                .patch("/todo", merge_todo)
+                .patch("/todo", json_patch_todo)
+                .patch("/any", merge_only),
+        );
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_merge_patch_updates_and_removes_fields() {
+    let client = client().await;
+
+    let response = client
+        .patch("/todo")
+        .header("content-type", MERGE_PATCH_CONTENT_TYPE)
+        .body(serde_json::json!({ "done": true, "notes": null }).to_string())
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json::<Todo>(),
+        Todo {
+            done: true,
+            notes: None,
+            ..todo()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_merge_patch_removing_required_field_is_rejected() {
+    let client = client().await;
+
+    let response = client
+        .patch("/todo")
+        .header("content-type", MERGE_PATCH_CONTENT_TYPE)
+        .body(r#"{"title": null}"#)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+}
+
+#[tokio::test]
+async fn test_json_patch_applies_operations() {
+    let client = client().await;
+
+    let response = client
+        .patch("/todo")
+        .header("content-type", JSON_PATCH_CONTENT_TYPE)
+        .body(
+            serde_json::json!([
+                { "op": "test", "path": "/done", "value": false },
+                { "op": "replace", "path": "/done", "value": true },
+                { "op": "add", "path": "/tags/-", "value": "dairy" }
+            ])
+            .to_string(),
+        )
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let patched: Todo = response.json();
+    assert!(patched.done);
+    assert_eq!(patched.tags, ["errands", "dairy"]);
+}
+
+#[tokio::test]
+async fn test_failed_json_patch_reports_operation() {
+    let client = client().await;
+
+    let response = client
+        .patch("/todo")
+        .header("content-type", JSON_PATCH_CONTENT_TYPE)
+        .body(
+            serde_json::json!([
+                { "op": "replace", "path": "/done", "value": true },
+                { "op": "test", "path": "/title", "value": "Walk the dog" }
+            ])
+            .to_string(),
+        )
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["details"]["operation"], 1);
+    assert_eq!(body["error"]["details"]["op"], "test");
+    assert_eq!(body["error"]["details"]["path"], "/title");
+
+    // The first operation was not kept
+    let response = client
+        .patch("/todo")
+        .header("content-type", MERGE_PATCH_CONTENT_TYPE)
+        .body("{}")
+        .send()
+        .await;
+    assert!(!response.json::<Todo>().done);
+}
+
+#[tokio::test]
+async fn test_malformed_json_patch_is_bad_request() {
+    let client = client().await;
+
+    let response = client
+        .patch("/todo")
+        .header("content-type", JSON_PATCH_CONTENT_TYPE)
+        .body(r#"[{ "op": "replace", "path": "/done" }]"#)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["details"]["operation"], 0);
+}
+
+#[tokio::test]
+async fn test_merge_patch_requires_its_content_type() {
+    let client = client().await;
+
+    let response = client
+        .patch("/any")
+        .json(&serde_json::json!({ "done": true }))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_openapi_documents_patch_content_types() {
+    let client = client().await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+    let content = &spec["paths"]["/todo"]["patch"]["requestBody"]["content"];
+
+    assert!(content.get(MERGE_PATCH_CONTENT_TYPE).is_some());
+    assert!(content.get(JSON_PATCH_CONTENT_TYPE).is_some());
+}
@@ -0,0 +1,108 @@
+//! Integration tests for percent-decoded path parameters and
+//! case-insensitive route matching.
+
+use http::{Method, StatusCode};
+use rapina::extract::PathParams;
+use rapina::prelude::*;
+use rapina::router::EncodedSlash;
+use rapina::testing::TestClient;
+
+fn files(router: Router) -> Router {
+    router.route(
+        Method::GET,
+        "/files/:name",
+        |_, params: PathParams, _| async move { params["name"].clone() },
+    )
+}
+
+async fn client(router: Router) -> TestClient {
+    TestClient::new(Rapina::new().with_introspection(false).router(router)).await
+}
+
+#[tokio::test]
+async fn test_params_are_raw_by_default() {
+    let client = client(files(Router::new())).await;
+
+    let response = client.get("/files/report%202024.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "report%202024.pdf");
+}
+
+#[tokio::test]
+async fn test_decodes_encoded_spaces() {
+    let client = client(files(Router::new().decode_params(EncodedSlash::Decode))).await;
+
+    let response = client.get("/files/report%202024.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "report 2024.pdf");
+
+    let response = client.get("/files/caf%C3%A9.txt").send().await;
+    assert_eq!(response.text(), "café.txt");
+}
+
+#[tokio::test]
+async fn test_encoded_slash_policies() {
+    let decode = client(files(Router::new().decode_params(EncodedSlash::Decode))).await;
+    let response = decode.get("/files/2024%2Freport.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "2024/report.pdf");
+
+    let keep = client(files(Router::new().decode_params(EncodedSlash::Keep))).await;
+    let response = keep.get("/files/2024%2freport%20final.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "2024%2freport final.pdf");
+
+    let reject = client(files(Router::new().decode_params(EncodedSlash::Reject))).await;
+    let response = reject.get("/files/2024%2Freport.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "BAD_REQUEST");
+
+    // An encoded slash never makes a parameter span two segments
+    let response = decode.get("/files/2024/report.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invalid_encoding_is_bad_request() {
+    let client = client(files(Router::new().decode_params(EncodedSlash::Decode))).await;
+
+    let response = client.get("/files/report%2.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json();
+    assert_eq!(
+        json["error"]["message"],
+        "malformed percent-encoding '%2.' in path"
+    );
+
+    let response = client.get("/files/%FF.pdf").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_case_insensitive_static_segments() {
+    let router = |case_insensitive| {
+        Router::new()
+            .case_insensitive(case_insensitive)
+            .route(
+                Method::GET,
+                "/users/:id",
+                |_, params: PathParams, _| async move { params["id"].clone() },
+            )
+            .route(Method::GET, "/users/current", |_, _, _| async { "current" })
+    };
+
+    let strict = client(router(false)).await;
+    assert_eq!(
+        strict.get("/Users/5").send().await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let relaxed = client(router(true)).await;
+    let response = relaxed.get("/Users/AbC").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "AbC");
+
+    let response = relaxed.get("/USERS/Current").send().await;
+    assert_eq!(response.text(), "current");
+}
@@ -0,0 +1,127 @@
+//! Integration tests for the `Parts` and `RawRequest` extractors.
+
+use http::StatusCode;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use rapina::extract::{Parts, RawRequest};
+use rapina::middleware::BoxFuture;
+use rapina::prelude::*;
+use rapina::response::BoxBody;
+use rapina::testing::TestClient;
+use serde_json::{Value, json};
+
+#[derive(Clone)]
+struct Tenant(&'static str);
+
+/// Puts a `Tenant` in the request extensions, like an auth middleware would.
+struct TenantMiddleware;
+
+impl Middleware for TenantMiddleware {
+    fn handle<'a>(
+        &'a self,
+        mut req: hyper::Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, hyper::Response<BoxBody>> {
+        Box::pin(async move {
+            req.extensions_mut().insert(Tenant("acme"));
+            next.run(req).await
+        })
+    }
+}
+
+#[get("/inspect")]
+async fn inspect(parts: Parts) -> Json<Value> {
+    Json(json!({
+        "method": parts.method.as_str(),
+        "uri": parts.uri.to_string(),
+        "agent": parts.headers.get("user-agent").and_then(|v| v.to_str().ok()),
+        "tenant": parts.extensions.get::<Tenant>().map(|t| t.0),
+        "has_context": parts.extensions.get::<RequestContext>().is_some(),
+    }))
+}
+
+#[post("/echo")]
+async fn echo(parts: Parts, body: Json<Value>) -> Json<Value> {
+    Json(json!({
+        "uri": parts.into_inner().uri.to_string(),
+        "body": body.into_inner(),
+    }))
+}
+
+#[post("/raw")]
+async fn raw(ctx: Context, req: RawRequest) -> Json<Value> {
+    let req = req.into_inner();
+    let tenant = req.extensions().get::<Tenant>().map(|t| t.0);
+    let same_trace = req
+        .extensions()
+        .get::<RequestContext>()
+        .is_some_and(|inner| inner.trace_id == ctx.trace_id());
+    let body = req.into_body().collect().await.unwrap().to_bytes();
+    Json(json!({
+        "tenant": tenant,
+        "same_trace": same_trace,
+        "body": String::from_utf8_lossy(&body),
+    }))
+}
+
+async fn client() -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TenantMiddleware)
+        .router(
+            Router::new()
+                .get("/inspect", inspect)
+                .post("/echo", echo)
+                .post("/raw", raw),
+        );
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_parts_sees_head_and_middleware_extensions() {
+    let client = client().await;
+
+    let response = client
+        .get("/inspect?page=2")
+        .header("user-agent", "rapina-test")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["method"], "GET");
+    assert_eq!(body["uri"], "/inspect?page=2");
+    assert_eq!(body["agent"], "rapina-test");
+    assert_eq!(body["tenant"], "acme");
+    assert_eq!(body["has_context"], true);
+}
+
+#[tokio::test]
+async fn test_parts_combines_with_body_extractor() {
+    let client = client().await;
+
+    let response = client
+        .post("/echo?v=1")
+        .json(&json!({"name": "Alice"}))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["uri"], "/echo?v=1");
+    assert_eq!(body["body"], json!({"name": "Alice"}));
+}
+
+#[tokio::test]
+async fn test_raw_request_gets_body_and_extensions() {
+    let client = client().await;
+
+    let response = client.post("/raw").body("plain bytes").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["tenant"], "acme");
+    assert_eq!(body["same_trace"], true);
+    assert_eq!(body["body"], "plain bytes");
+}
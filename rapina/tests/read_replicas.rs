@@ -0,0 +1,178 @@
+//! Integration tests for read/write splitting across a primary and replicas.
+//!
+//! The primary and replicas are separate SQLite files holding different
+//! rows, so the rows a query returns show which database served it.
+
+#![cfg(feature = "sqlite")]
+
+use std::path::PathBuf;
+
+use http::StatusCode;
+use rapina::database::{DatabaseConfig, Db, DbError};
+use rapina::prelude::*;
+use rapina::sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+use rapina::testing::TestClient;
+
+struct TempDb {
+    path: PathBuf,
+}
+
+impl TempDb {
+    fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("rapina-{}.db", rapina::uuid::Uuid::new_v4()));
+        Self { path }
+    }
+
+    fn url(&self) -> String {
+        format!("sqlite://{}?mode=rwc", self.path.display())
+    }
+
+    async fn seed(&self, source: &str) {
+        let conn = DatabaseConfig::new(self.url()).connect().await.unwrap();
+        conn.execute_unprepared("CREATE TABLE items (source TEXT NOT NULL)")
+            .await
+            .unwrap();
+        conn.execute_unprepared(&format!("INSERT INTO items (source) VALUES ('{}')", source))
+            .await
+            .unwrap();
+        conn.close().await.unwrap();
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn sources(conn: &DatabaseConnection) -> std::result::Result<Vec<String>, DbError> {
+    let rows = conn
+        .query_all(Statement::from_string(
+            conn.get_database_backend(),
+            "SELECT source FROM items ORDER BY rowid",
+        ))
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| row.try_get::<String>("", "source").unwrap())
+        .collect())
+}
+
+#[get("/read")]
+async fn read(db: Db) -> Result<Json<Vec<String>>> {
+    Ok(Json(sources(db.read()).await?))
+}
+
+#[post("/write-then-read")]
+async fn write_then_read(db: Db) -> Result<Json<Vec<String>>> {
+    db.write()
+        .execute_unprepared("INSERT INTO items (source) VALUES ('written')")
+        .await
+        .map_err(DbError)?;
+    Ok(Json(sources(db.read()).await?))
+}
+
+fn router() -> Router {
+    Router::new()
+        .get("/read", read)
+        .post("/write-then-read", write_then_read)
+}
+
+async fn read_sources(client: &TestClient) -> Vec<String> {
+    let response = client.get("/read").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()
+}
+
+#[tokio::test]
+async fn test_reads_round_robin_across_replicas() {
+    let (primary, replica1, replica2) = (TempDb::new(), TempDb::new(), TempDb::new());
+    primary.seed("primary").await;
+    replica1.seed("replica1").await;
+    replica2.seed("replica2").await;
+
+    let config = DatabaseConfig::new(primary.url())
+        .replica(replica1.url())
+        .replica(replica2.url());
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_database(config)
+        .await
+        .unwrap()
+        .router(router());
+    let client = TestClient::new(app).await;
+
+    let mut seen = Vec::new();
+    for _ in 0..4 {
+        seen.extend(read_sources(&client).await);
+    }
+    seen.sort();
+    assert_eq!(seen, vec!["replica1", "replica1", "replica2", "replica2"]);
+}
+
+#[tokio::test]
+async fn test_write_pins_request_reads_to_primary() {
+    let (primary, replica) = (TempDb::new(), TempDb::new());
+    primary.seed("primary").await;
+    replica.seed("replica").await;
+
+    let config = DatabaseConfig::new(primary.url()).replica(replica.url());
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_database(config)
+        .await
+        .unwrap()
+        .router(router());
+    let client = TestClient::new(app).await;
+
+    let response = client.post("/write-then-read").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let rows: Vec<String> = response.json();
+    assert_eq!(rows, vec!["primary", "written"]);
+
+    // The pin only lasts for the request that wrote
+    assert_eq!(read_sources(&client).await, vec!["replica"]);
+}
+
+#[tokio::test]
+async fn test_reads_use_primary_without_replicas() {
+    let primary = TempDb::new();
+    primary.seed("primary").await;
+
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_database(DatabaseConfig::new(primary.url()))
+        .await
+        .unwrap()
+        .router(router());
+    let client = TestClient::new(app).await;
+
+    assert_eq!(read_sources(&client).await, vec!["primary"]);
+}
+
+#[tokio::test]
+async fn test_unhealthy_replicas_fall_back() {
+    let (primary, replica1, replica2) = (TempDb::new(), TempDb::new(), TempDb::new());
+    primary.seed("primary").await;
+    replica1.seed("replica1").await;
+    replica2.seed("replica2").await;
+
+    let config = DatabaseConfig::new(primary.url())
+        .replica(replica1.url())
+        .replica(replica2.url());
+    let db = Db::new(config.connect().await.unwrap());
+    let replicas = config.connect_replicas().await.unwrap();
+    let db = db.with_replicas(replicas.clone());
+
+    replicas.set_healthy(0, false);
+    for _ in 0..3 {
+        assert_eq!(sources(db.read()).await.unwrap(), vec!["replica2"]);
+    }
+
+    replicas.set_healthy(1, false);
+    assert_eq!(replicas.healthy(), 0);
+    assert_eq!(sources(db.read()).await.unwrap(), vec!["primary"]);
+
+    replicas.check_health().await;
+    assert_eq!(replicas.healthy(), 2);
+}
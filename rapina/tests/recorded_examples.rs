@@ -0,0 +1,140 @@
+//! Integration tests for recording request/response examples from tests.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::{MAX_EXAMPLE_BODY_BYTES, TestClient};
+
+#[derive(Deserialize, Serialize)]
+struct NewUser {
+    name: String,
+}
+
+async fn client() -> TestClient {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .post_named("/users", "create_user", |_, _, _| async {
+                (StatusCode::CREATED, Json(serde_json::json!({ "id": 1 })))
+            })
+            .get_named("/users/:id", "get_user", |_, _, _| async {
+                Json(serde_json::json!({ "id": 1, "name": "Ada" }))
+            })
+            .get_named("/export", "export_users", |_, _, _| async {
+                "x".repeat(MAX_EXAMPLE_BODY_BYTES * 2)
+            }),
+    );
+    TestClient::new(app).await
+}
+
+fn read_examples(dir: &std::path::Path) -> Vec<serde_json::Value> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    files
+        .iter()
+        .map(|path| serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap())
+        .collect()
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("rapina-examples-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn test_records_exchange_with_route_pattern() {
+    let dir = temp_dir("record");
+    let client = client().await;
+    client.record_examples_to(&dir);
+
+    client
+        .post("/users")
+        .header("authorization", "Bearer secret")
+        .header("x-csrf-token", "abc")
+        .json(&NewUser {
+            name: "Ada".to_string(),
+        })
+        .send()
+        .await;
+    client.get("/users/1").send().await;
+    // Unmatched requests are not examples
+    client.get("/missing").send().await;
+
+    let examples = read_examples(&dir);
+    assert_eq!(examples.len(), 2);
+
+    let get = examples.iter().find(|e| e["method"] == "GET").unwrap();
+    assert_eq!(get["route"], "/users/:id");
+    assert_eq!(get["handler"], "get_user");
+    assert_eq!(get["request"]["path"], "/users/1");
+    assert_eq!(get["response"]["status"], 200);
+    assert_eq!(get["response"]["body"]["name"], "Ada");
+
+    let post = examples.iter().find(|e| e["method"] == "POST").unwrap();
+    assert_eq!(post["route"], "/users");
+    assert_eq!(
+        post["request"]["body"],
+        serde_json::json!({ "name": "Ada" })
+    );
+    assert_eq!(
+        post["request"]["headers"]["content-type"],
+        "application/json"
+    );
+    assert!(post["request"]["headers"].get("authorization").is_none());
+    assert!(post["request"]["headers"].get("x-csrf-token").is_none());
+    assert_eq!(post["response"]["status"], 201);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_long_bodies_are_truncated() {
+    let dir = temp_dir("truncate");
+    let client = client().await;
+    client.record_examples_to(&dir);
+
+    let response = client.get("/export").send().await;
+    // The test itself still sees the whole body
+    assert_eq!(response.text().len(), MAX_EXAMPLE_BODY_BYTES * 2);
+
+    let examples = read_examples(&dir);
+    let body = examples[0]["response"]["body"].as_str().unwrap();
+    assert_eq!(body.len(), MAX_EXAMPLE_BODY_BYTES + 3);
+    assert!(body.ends_with("..."));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_recording_is_off_by_default() {
+    let dir = temp_dir("off");
+    let client = client().await;
+    client.get("/users/1").send().await;
+
+    client.record_examples_to(&dir);
+    client.record_examples(false);
+    client.get("/users/1").send().await;
+
+    assert!(!dir.exists());
+}
+
+#[tokio::test]
+async fn test_identical_exchanges_share_a_file() {
+    let dir = temp_dir("dedupe");
+    let client = client().await;
+    client.record_examples_to(&dir);
+
+    client.get("/users/1").send().await;
+    client.get("/users/1").send().await;
+
+    let examples = read_examples(&dir);
+    assert_eq!(examples.len(), 1);
+    // Headers that change between runs would defeat deduplication
+    let headers = &examples[0]["response"]["headers"];
+    assert!(headers.get("date").is_none());
+    assert!(headers.get("content-length").is_none());
+    assert_eq!(headers["content-type"], "application/json");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,111 @@
+//! Integration tests for `TraceMiddleware` request spans.
+
+mod common;
+
+use http::StatusCode;
+use rapina::middleware::{Phase, TraceIdMiddleware, TraceMiddleware};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+use common::Logs;
+
+#[get("/users/:id")]
+async fn show_user(id: Path<u64>) -> String {
+    format!("user {}", id.into_inner())
+}
+
+#[get("/boom")]
+async fn boom() -> Result<String> {
+    Err(Error::internal("boom"))
+}
+
+fn app(trace: TraceMiddleware) -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .middleware_in(Phase::PreRouting, TraceIdMiddleware::new())
+        .middleware_in(Phase::PreRouting, trace)
+        .router(
+            Router::new()
+                .get("/users/:id", show_user)
+                .get("/boom", boom),
+        )
+}
+
+#[tokio::test]
+async fn test_success_is_logged_at_info_with_route_and_request_id() {
+    let (logs, _guard) = Logs::capture(tracing::Level::INFO);
+    let client = TestClient::new(app(TraceMiddleware::new())).await;
+
+    let response = client
+        .get("/users/7")
+        .header("x-trace-id", "req-123")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let line = logs.line_containing("request completed");
+    assert!(line.contains(" INFO "), "{}", line);
+    assert!(
+        line.contains("request{method=GET path=/users/7 route=/users/:id request_id=req-123"),
+        "{}",
+        line
+    );
+    assert!(line.contains("status=200"), "{}", line);
+    assert!(line.contains("latency_ms="), "{}", line);
+}
+
+#[tokio::test]
+async fn test_not_found_is_logged_at_warn_without_route() {
+    let (logs, _guard) = Logs::capture(tracing::Level::INFO);
+    let client = TestClient::new(app(TraceMiddleware::new())).await;
+
+    let response = client.get("/missing").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let line = logs.line_containing("request completed");
+    assert!(line.contains(" WARN "), "{}", line);
+    assert!(
+        line.contains("request{method=GET path=/missing request_id="),
+        "{}",
+        line
+    );
+    assert!(!line.contains("route="), "{}", line);
+    assert!(line.contains("status=404"), "{}", line);
+}
+
+#[tokio::test]
+async fn test_server_error_is_logged_at_error() {
+    let (logs, _guard) = Logs::capture(tracing::Level::INFO);
+    let client = TestClient::new(app(TraceMiddleware::new())).await;
+
+    let response = client.get("/boom").send().await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let line = logs.line_containing("request completed");
+    assert!(line.contains("ERROR "), "{}", line);
+    assert!(line.contains("status=500"), "{}", line);
+}
+
+#[tokio::test]
+async fn test_custom_fields() {
+    let (logs, _guard) = Logs::capture(tracing::Level::INFO);
+    let trace = TraceMiddleware::new().with_fields(|req| {
+        let tenant = req
+            .headers()
+            .get("x-tenant")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("none")
+            .to_string();
+        tracing::info_span!("fields", tenant = %tenant)
+    });
+    let client = TestClient::new(app(trace)).await;
+
+    client
+        .get("/users/7")
+        .header("x-tenant", "acme")
+        .send()
+        .await;
+
+    let line = logs.line_containing("request completed");
+    assert!(line.contains(":fields{tenant=acme}:"), "{}", line);
+}
@@ -0,0 +1,109 @@
+//! Integration tests for repeatable response headers.
+
+use std::time::Duration;
+
+use http::StatusCode;
+use http::header::{HeaderValue, SET_COOKIE, VARY};
+use rapina::middleware::{CompressionConfig, CorsConfig};
+use rapina::prelude::*;
+use rapina::response::WithHeaders;
+use rapina::testing::TestClient;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[get("/login")]
+async fn login() -> WithHeaders<String> {
+    "x".repeat(2048)
+        .append_header(
+            SET_COOKIE,
+            HeaderValue::from_static("session=abc; HttpOnly"),
+        )
+        .append_header(SET_COOKIE, HeaderValue::from_static("theme=dark; Path=/"))
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .with_cors(CorsConfig::with_origins(vec![
+            "https://app.example".to_string(),
+        ]))
+        .with_compression(CompressionConfig::default())
+        .default_header("set-cookie", "tracking=1")
+        .default_header("x-frame-options", "DENY")
+        .router(Router::new().get("/login", login))
+}
+
+#[tokio::test]
+async fn test_cookies_arrive_as_separate_headers() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .get("/login")
+        .header("Origin", "https://app.example")
+        .header("Accept-Encoding", "gzip")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookies: Vec<_> = response.headers().get_all(SET_COOKIE).iter().collect();
+    assert_eq!(cookies, ["session=abc; HttpOnly", "theme=dark; Path=/"]);
+    assert_eq!(response.headers()["x-frame-options"], "DENY");
+}
+
+#[tokio::test]
+async fn test_middleware_vary_values_are_merged() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .get("/login")
+        .header("Origin", "https://app.example")
+        .header("Accept-Encoding", "gzip")
+        .send()
+        .await;
+
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    let vary: Vec<_> = response.headers().get_all(VARY).iter().collect();
+    // Compression wraps CORS, so it appends its value last
+    assert_eq!(vary, ["Origin", "Accept-Encoding"]);
+}
+
+#[tokio::test]
+async fn test_cookies_on_the_wire() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+
+    let server_addr = addr.clone();
+    tokio::spawn(async move { app().listen(&server_addr).await });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        match tokio::net::TcpStream::connect(&addr).await {
+            Ok(s) => {
+                stream = Some(s);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let mut stream = stream.expect("server did not start");
+
+    stream
+        .write_all(b"GET /login HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await.unwrap();
+    let raw = String::from_utf8_lossy(&raw);
+    let (head, _) = raw.split_once("\r\n\r\n").unwrap();
+
+    let cookies: Vec<_> = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+        .map(|(_, value)| value.trim())
+        .collect();
+    assert_eq!(cookies, ["session=abc; HttpOnly", "theme=dark; Path=/"]);
+}
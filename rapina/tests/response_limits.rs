@@ -0,0 +1,152 @@
+//! Integration tests for serialization failures and the response size cap.
+
+mod common;
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+use common::Logs;
+
+struct Unserializable;
+
+impl serde::Serialize for Unserializable {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        _serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("the ledger is locked"))
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Row {
+    id: u64,
+    name: String,
+}
+
+#[get("/broken")]
+async fn broken() -> Json<Vec<Unserializable>> {
+    Json(vec![Unserializable])
+}
+
+#[get("/table")]
+async fn table() -> Json<Vec<Row>> {
+    Json(
+        (0..1_000_000)
+            .map(|id| Row {
+                id,
+                name: format!("row {}", id),
+            })
+            .collect(),
+    )
+}
+
+#[get("/small")]
+async fn small() -> Json<Vec<Row>> {
+    Json(vec![Row {
+        id: 1,
+        name: "row 1".into(),
+    }])
+}
+
+#[get("/text")]
+async fn text() -> String {
+    "x".repeat(100_000)
+}
+
+fn app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/broken", broken)
+            .get("/table", table)
+            .get("/small", small)
+            .get("/text", text),
+    )
+}
+
+#[tokio::test]
+async fn test_serialization_error_returns_500_and_logs() {
+    let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/broken").send().await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "SERIALIZATION_ERROR");
+    assert!(body["error"].get("details").is_none());
+    assert!(!response.text().contains("ledger"));
+
+    let logs = logs.contents();
+    assert!(logs.contains("Failed to serialize JSON response"));
+    assert!(logs.contains("Unserializable"));
+    assert!(logs.contains("the ledger is locked"));
+}
+
+#[tokio::test]
+async fn test_serialization_error_details_can_be_exposed() {
+    let client = TestClient::new(app().expose_serialization_errors(true)).await;
+
+    let response = client.get("/broken").send().await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "SERIALIZATION_ERROR");
+    assert_eq!(body["error"]["details"]["error"], "the ledger is locked");
+    assert!(
+        body["error"]["details"]["type"]
+            .as_str()
+            .unwrap()
+            .contains("Unserializable")
+    );
+}
+
+#[tokio::test]
+async fn test_oversized_json_is_aborted() {
+    let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+    let client = TestClient::new(app().max_response_size(64 * 1024)).await;
+
+    let response = client.get("/table").send().await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "RESPONSE_TOO_LARGE");
+    assert!(response.bytes().len() < 1024);
+
+    let logs = logs.contents();
+    assert!(logs.contains("JSON response exceeds the maximum response size"));
+    assert!(logs.contains("Row"));
+}
+
+#[tokio::test]
+async fn test_response_under_the_limit_is_sent() {
+    let client = TestClient::new(app().max_response_size(64 * 1024)).await;
+
+    let response = client.get("/small").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body[0]["name"], "row 1");
+}
+
+#[tokio::test]
+async fn test_oversized_buffered_body_is_replaced() {
+    let client = TestClient::new(app().max_response_size(64 * 1024)).await;
+
+    let response = client.get("/text").send().await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "RESPONSE_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn test_responses_are_unlimited_by_default() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/text").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.bytes().len(), 100_000);
+}
@@ -0,0 +1,58 @@
+//! Integration tests for route conflict detection during auto-discovery.
+//!
+//! `inventory` collects handlers from the whole test binary, so every
+//! `.discover()` call in this file sees the conflicting pair below.
+
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+mod users {
+    use super::*;
+
+    #[get("/conflict/users/:id")]
+    pub async fn get_user() -> &'static str {
+        "users::get_user"
+    }
+}
+
+mod admin {
+    use super::*;
+
+    #[get("/conflict/users/:user_id")]
+    pub async fn show_user() -> &'static str {
+        "admin::show_user"
+    }
+}
+
+#[tokio::test]
+async fn test_listen_fails_on_conflicting_routes() {
+    let err = Rapina::new()
+        .with_introspection(false)
+        .discover()
+        .listen("127.0.0.1:0")
+        .await
+        .unwrap_err();
+
+    let message = err.to_string();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(message.contains("GET /conflict/users/"));
+    assert!(message.contains("`get_user`"));
+    assert!(message.contains("`show_user`"));
+}
+
+#[tokio::test]
+#[should_panic(expected = "is handled by more than one handler")]
+async fn test_client_panics_on_conflicting_routes() {
+    TestClient::new(Rapina::new().with_introspection(false).discover()).await;
+}
+
+#[tokio::test]
+async fn test_explicit_router_is_not_checked_without_discovery() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/conflict/users/:id", users::get_user));
+
+    let client = TestClient::new(app).await;
+    let resp = client.get("/conflict/users/1").send().await;
+    assert_eq!(resp.text(), "users::get_user");
+}
@@ -0,0 +1,151 @@
+//! Integration tests for middleware attached to routers and routes.
+
+use std::sync::{Arc, Mutex};
+
+use http::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use rapina::auth::AuthMiddleware;
+use rapina::context::RequestContext;
+use rapina::middleware::{BoxFuture, Middleware, Next};
+use rapina::prelude::*;
+use rapina::response::BoxBody;
+use rapina::testing::TestClient;
+
+const SECRET: &str = "route-middleware-secret";
+
+/// Records its label when it runs.
+struct Record {
+    label: &'static str,
+    log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Middleware for Record {
+    fn handle<'a>(
+        &'a self,
+        req: Request<Incoming>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        Box::pin(async move {
+            self.log.lock().unwrap().push(self.label);
+            next.run(req).await
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        self.label
+    }
+}
+
+fn record(label: &'static str, log: &Arc<Mutex<Vec<&'static str>>>) -> Record {
+    Record {
+        label,
+        log: log.clone(),
+    }
+}
+
+fn admin_app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get_named("/health", "health", |_, _, _| async { "ok" })
+            .scope("/admin", |admin| {
+                admin
+                    .middleware(AuthMiddleware::new(AuthConfig::new(SECRET, 3600)))
+                    .get_named("/stats", "stats", |_, _, _| async { "stats" })
+            }),
+    )
+}
+
+#[tokio::test]
+async fn test_protected_group_rejects_unauthenticated_requests() {
+    let client = TestClient::new(admin_app()).await;
+
+    let response = client.get("/admin/stats").send().await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let token = AuthConfig::new(SECRET, 3600).create_token("admin").unwrap();
+    let response = client
+        .get("/admin/stats")
+        .header("authorization", &format!("Bearer {}", token))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "stats");
+}
+
+#[tokio::test]
+async fn test_sibling_route_outside_group_is_unaffected() {
+    let client = TestClient::new(admin_app()).await;
+
+    let response = client.get("/health").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "ok");
+}
+
+#[tokio::test]
+async fn test_global_then_group_then_route_order() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(record("global", &log))
+        .router(
+            Router::new()
+                .middleware(record("app", &log))
+                .scope("/api", |api| {
+                    api.scope("/admin", |admin| {
+                        admin
+                            .at("/stats", |r| {
+                                r.get_named("stats", |_, _, _| async { "stats" })
+                                    .middleware(record("route", &log))
+                            })
+                            .middleware(record("inner", &log))
+                    })
+                    .middleware(record("outer", &log))
+                })
+                .get_named("/health", "health", |_, _, _| async { "ok" }),
+        );
+    let client = TestClient::new(app).await;
+
+    client.get("/api/admin/stats").send().await;
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["global", "app", "outer", "inner", "route"]
+    );
+
+    log.lock().unwrap().clear();
+    client.get("/health").send().await;
+    assert_eq!(*log.lock().unwrap(), ["global", "app"]);
+}
+
+#[tokio::test]
+async fn test_introspection_lists_route_middleware() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let app = Rapina::new().with_introspection(true).router(
+        Router::new()
+            .get_named("/health", "health", |_, _, _| async { "ok" })
+            .scope("/admin", |admin| {
+                admin.middleware(record("audit", &log)).at("/stats", |r| {
+                    r.get_named("stats", |_, _, _| async { "stats" })
+                        .middleware(record("cache", &log))
+                })
+            }),
+    );
+    let client = TestClient::new(app).await;
+
+    let routes: serde_json::Value = client.get("/__rapina/routes").send().await.json();
+    let middleware_of = |path: &str| {
+        routes
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|route| route["path"] == path)
+            .unwrap()
+            .get("middleware")
+            .cloned()
+    };
+    assert_eq!(
+        middleware_of("/admin/stats"),
+        Some(serde_json::json!(["audit", "cache"]))
+    );
+    assert_eq!(middleware_of("/health"), None);
+}
@@ -0,0 +1,143 @@
+//! Integration tests for handlers sharing a method and path, picked by
+//! `consumes` and `produces`.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Deserialize, Serialize)]
+struct Credentials {
+    username: String,
+    password: String,
+}
+
+#[post("/login", consumes = "application/json")]
+async fn login_json(body: Json<Credentials>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "user": body.into_inner().username }))
+}
+
+#[post("/login", consumes = "application/x-www-form-urlencoded")]
+async fn login_form(body: Form<Credentials>) -> String {
+    format!("<p>Welcome, {}</p>", body.into_inner().username)
+}
+
+#[get("/report", produces = "application/json")]
+async fn report_json() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "total": 3 }))
+}
+
+#[get("/report", produces = "text/csv")]
+async fn report_csv() -> &'static str {
+    "total\n3\n"
+}
+
+fn credentials() -> Credentials {
+    Credentials {
+        username: "ada".to_string(),
+        password: "secret".to_string(),
+    }
+}
+
+async fn client() -> TestClient {
+    TestClient::new(Rapina::new().with_introspection(false).discover()).await
+}
+
+#[tokio::test]
+async fn test_same_path_dispatches_by_content_type() {
+    let client = client().await;
+
+    let response = client.post("/login").json(&credentials()).send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["user"], "ada");
+
+    let response = client.post("/login").form(&credentials()).send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "<p>Welcome, ada</p>");
+}
+
+#[tokio::test]
+async fn test_unsupported_content_type_lists_supported_types() {
+    let client = client().await;
+
+    let response = client
+        .post("/login")
+        .header("content-type", "text/plain")
+        .body("ada:secret")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "UNSUPPORTED_MEDIA_TYPE");
+    let allowed = json["error"]["details"]["allowed"].as_array().unwrap();
+    assert_eq!(allowed.len(), 2);
+    assert!(allowed.contains(&serde_json::json!("application/json")));
+    assert!(allowed.contains(&serde_json::json!("application/x-www-form-urlencoded")));
+}
+
+#[tokio::test]
+async fn test_same_path_dispatches_by_accept() {
+    let client = client().await;
+
+    let response = client
+        .get("/report")
+        .header("accept", "text/csv")
+        .send()
+        .await;
+    assert_eq!(response.text(), "total\n3\n");
+
+    let response = client
+        .get("/report")
+        .header("accept", "text/csv;q=0.5, application/json")
+        .send()
+        .await;
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["total"], 3);
+
+    // Without an Accept header either handler may answer
+    let response = client.get("/report").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_unacceptable_accept_lists_available_types() {
+    let client = client().await;
+
+    let response = client
+        .get("/report")
+        .header("accept", "application/xml")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "NOT_ACCEPTABLE");
+    let allowed = json["error"]["details"]["allowed"].as_array().unwrap();
+    assert_eq!(allowed.len(), 2);
+    assert!(allowed.contains(&serde_json::json!("text/csv")));
+    assert!(allowed.contains(&serde_json::json!("application/json")));
+}
+
+#[tokio::test]
+async fn test_introspection_and_openapi_show_both_variants() {
+    let app = Rapina::new().openapi("Overloads", "1.0.0").discover();
+    let client = TestClient::new(app).await;
+
+    let routes: Vec<serde_json::Value> = client.get("/__rapina/routes").send().await.json();
+    let login: Vec<&serde_json::Value> = routes.iter().filter(|r| r["path"] == "/login").collect();
+    assert_eq!(login.len(), 2);
+    assert!(login.iter().any(|r| r["consumes"] == "application/json"));
+    assert!(
+        login
+            .iter()
+            .any(|r| r["consumes"] == "application/x-www-form-urlencoded")
+    );
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+    let post = &spec["paths"]["/login"]["post"];
+    let content = post["requestBody"]["content"].as_object().unwrap();
+    assert!(content.contains_key("application/json"));
+    assert!(content.contains_key("application/x-www-form-urlencoded"));
+    assert_eq!(post["x-rapina-variants"].as_array().unwrap().len(), 2);
+}
@@ -93,8 +93,62 @@ async fn test_method_not_matching() {
     let response = client.get("/resource").send().await;
     assert_eq!(response.status(), StatusCode::OK);
 
-    // POST should return 404 (method doesn't match)
+    // POST should return 405 (method doesn't match)
     let response = client.post("/resource").send().await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET");
+}
+
+#[tokio::test]
+async fn test_method_not_allowed_lists_methods() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .route(Method::GET, "/users/:id", |_, _, _| async { "user" })
+            .route(Method::PUT, "/users/:id", |_, _, _| async { "updated" })
+            .route(Method::GET, "/users/current", |_, _, _| async { "me" })
+            .route(Method::DELETE, "/users/current", |_, _, _| async {
+                StatusCode::NO_CONTENT
+            })
+            .route(Method::POST, "/users", |_, _, _| async {
+                StatusCode::CREATED
+            }),
+    );
+
+    let client = TestClient::new(app).await;
+
+    let response = client.delete("/users/42").send().await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, PUT");
+
+    // Both patterns answer /users/current, so both contribute their methods
+    let response = client.post("/users/current").send().await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, DELETE, PUT");
+
+    let response = client.get("/users").send().await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "POST");
+
+    let response = client.get("/posts").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.headers().get("allow").is_none());
+}
+
+#[tokio::test]
+async fn test_options_lists_methods() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .route(Method::GET, "/items/:id", |_, _, _| async { "item" })
+            .route(Method::PATCH, "/items/:id", |_, _, _| async { "patched" }),
+    );
+
+    let client = TestClient::new(app).await;
+
+    let response = client.request(Method::OPTIONS, "/items/7").send().await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, PATCH");
+
+    let response = client.request(Method::OPTIONS, "/other").send().await;
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
@@ -334,3 +388,144 @@ async fn test_param_at_different_positions() {
     let response = client.get("/api/v2/users").send().await;
     assert_eq!(response.text(), "version param");
 }
+
+#[tokio::test]
+async fn test_nested_routers_sort_by_specificity() {
+    // Same patterns as above, split across nested routers
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .scope("/api", |api| {
+                api.route(http::Method::GET, "/:version/users", |_, _, _| async {
+                    "version param"
+                })
+            })
+            .nest(
+                "/api/v1",
+                Router::new().route(http::Method::GET, "/:resource", |_, _, _| async {
+                    "resource param"
+                }),
+            ),
+    );
+
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/api/v1/users").send().await;
+    assert_eq!(response.text(), "resource param");
+
+    let response = client.get("/api/v2/users").send().await;
+    assert_eq!(response.text(), "version param");
+}
+
+#[tokio::test]
+async fn test_nested_router_params_and_introspection() {
+    let members = Router::new().get_named("/:id", "get_member", |_, params, _| async move {
+        format!(
+            "org {} member {}",
+            params.get("org_id").unwrap(),
+            params.get("id").unwrap()
+        )
+    });
+    let app = Rapina::new()
+        .with_introspection(true)
+        .router(Router::new().scope("/api/v1", |v1| v1.nest("/orgs/:org_id/members", members)));
+
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/api/v1/orgs/acme/members/7").send().await;
+    assert_eq!(response.text(), "org acme member 7");
+
+    let routes: serde_json::Value = client.get("/__rapina/routes").send().await.json();
+    let route = routes
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|route| route["handler_name"] == "get_member")
+        .unwrap();
+    assert_eq!(route["path"], "/api/v1/orgs/:org_id/members/:id");
+}
+
+#[tokio::test]
+async fn test_methods_grouped_by_path() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .at("/users", |r| {
+                r.route(Method::GET, |_, _, _| async { "list" })
+                    .route(Method::POST, |_, _, _| async { StatusCode::CREATED })
+            })
+            .at("/users/:id", |r| {
+                r.route(Method::GET, |_, params, _| async move {
+                    format!("user {}", params.get("id").unwrap())
+                })
+                .route(Method::DELETE, |_, _, _| async { StatusCode::NO_CONTENT })
+            }),
+    );
+
+    let client = TestClient::new(app).await;
+
+    assert_eq!(client.get("/users").send().await.text(), "list");
+    assert_eq!(
+        client.post("/users").send().await.status(),
+        StatusCode::CREATED
+    );
+    assert_eq!(client.get("/users/7").send().await.text(), "user 7");
+    assert_eq!(
+        client.delete("/users/7").send().await.status(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        client.put("/users/7").send().await.status(),
+        StatusCode::METHOD_NOT_ALLOWED
+    );
+}
+
+#[tokio::test]
+async fn test_path_middleware_only_runs_for_its_path() {
+    use rapina::middleware::{TRACE_ID_HEADER, TraceIdMiddleware};
+
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .at("/traced", |r| {
+                r.route(Method::GET, |_, _, _| async { "get" })
+                    .route(Method::POST, |_, _, _| async { "post" })
+                    .middleware(TraceIdMiddleware::new())
+            })
+            .route(Method::GET, "/plain", |_, _, _| async { "plain" }),
+    );
+
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/traced").send().await;
+    assert_eq!(response.text(), "get");
+    assert!(response.headers().get(TRACE_ID_HEADER).is_some());
+
+    let response = client.post("/traced").send().await;
+    assert_eq!(response.text(), "post");
+    assert!(response.headers().get(TRACE_ID_HEADER).is_some());
+
+    let response = client.get("/plain").send().await;
+    assert_eq!(response.text(), "plain");
+    assert!(response.headers().get(TRACE_ID_HEADER).is_none());
+}
+
+#[tokio::test]
+async fn test_path_middleware_can_reject_requests() {
+    use rapina::middleware::BodyLimitMiddleware;
+
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .at("/small", |r| {
+                r.route(Method::POST, |_, _, _| async { "small" })
+                    .middleware(BodyLimitMiddleware::new(4))
+            })
+            .route(Method::POST, "/large", |_, _, _| async { "large" }),
+    );
+
+    let client = TestClient::new(app).await;
+
+    let response = client.post("/small").body("too long").send().await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let response = client.post("/large").body("too long").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "large");
+}
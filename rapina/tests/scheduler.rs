@@ -0,0 +1,75 @@
+//! Integration tests for cron-scheduled jobs.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::schedule::{ScheduledJob, Scheduler};
+use rapina::state::AppState;
+use rapina::testing::TestClient;
+
+#[derive(Clone)]
+struct Counter(Arc<AtomicUsize>);
+
+#[tokio::test]
+async fn test_scheduler_runs_job_on_tight_schedule() {
+    let counter = Counter(Arc::new(AtomicUsize::new(0)));
+    let state = Arc::new(AppState::new().with(counter.clone()));
+
+    let job = ScheduledJob::new("* * * * * *", "tick", |state: Arc<AppState>| async move {
+        state
+            .get::<Counter>()
+            .unwrap()
+            .0
+            .fetch_add(1, Ordering::SeqCst);
+    })
+    .unwrap();
+
+    let mut scheduler = Scheduler::new();
+    scheduler.add(job);
+    let registry = scheduler.registry();
+    let handle = scheduler.start(state);
+
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    handle.shutdown();
+
+    let runs = counter.0.load(Ordering::SeqCst);
+    assert!(runs >= 2, "expected at least 2 runs, got {}", runs);
+
+    let jobs = registry.jobs();
+    assert_eq!(jobs[0].name, "tick");
+    assert!(jobs[0].last_run.is_some());
+    assert!(jobs[0].next_run.is_some());
+
+    // No new runs after shutdown
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+    assert_eq!(counter.0.load(Ordering::SeqCst), runs);
+}
+
+#[tokio::test]
+async fn test_jobs_introspection_endpoint() {
+    let app = Rapina::new()
+        .with_introspection(true)
+        .schedule("0 3 * * *", "nightly", |_| async {});
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/__rapina/jobs").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json: serde_json::Value = response.json();
+    assert_eq!(json[0]["name"], "nightly");
+    assert_eq!(json[0]["schedule"], "0 3 * * *");
+    assert_eq!(json[0]["timezone"], "UTC");
+    assert_eq!(json[0]["running"], false);
+}
+
+#[tokio::test]
+async fn test_jobs_endpoint_absent_without_jobs() {
+    let app = Rapina::new().with_introspection(true);
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/__rapina/jobs").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
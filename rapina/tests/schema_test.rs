@@ -10,6 +10,7 @@ use rapina::sea_orm::entity::prelude::*;
 // Define a test schema with various relationship types
 schema! {
     TestUser {
+        #[unique]
         email: String,
         name: String,
         bio: Option<Text>,
@@ -32,6 +33,104 @@ schema! {
     }
 }
 
+// Keyword field names are emitted as raw identifiers
+schema! {
+    /// A customer order.
+    #[fixtures]
+    #[timestamps(none)]
+    TestOrder {
+        /// Fulfilment type, e.g. "pickup" or "delivery".
+        type: String,
+        #[index(name = "orders_ref_lookup")]
+        r#ref: Option<String>,
+    }
+}
+
+// Many-to-many through a join entity that belongs to both sides
+schema! {
+    #[timestamps(none)]
+    TestMember {
+        name: String,
+        #[through(TestMembership)]
+        groups: Vec<TestGroup>,
+    }
+
+    #[timestamps(none)]
+    TestGroup {
+        title: String,
+        #[through(TestMembership)]
+        members: Vec<TestMember>,
+    }
+
+    #[timestamps(none)]
+    TestMembership {
+        member: TestMember,
+        group: TestGroup,
+    }
+}
+
+// Column defaults
+schema! {
+    #[timestamps(none)]
+    TestArticle {
+        title: String,
+        #[default = "draft"]
+        status: String,
+        #[default = 0]
+        views: i32,
+        #[default = -1]
+        rank: i64,
+        #[default = 2.5]
+        score: f64,
+        #[default = false]
+        pinned: bool,
+        #[default = now()]
+        published_at: DateTime,
+    }
+}
+
+// Referential actions on belongs_to relations
+schema! {
+    #[timestamps(none)]
+    TestAuthor {
+        name: String,
+    }
+
+    #[timestamps(none)]
+    TestPublisher {
+        name: String,
+    }
+
+    #[timestamps(none)]
+    TestBook {
+        title: String,
+        #[on_delete(cascade)]
+        #[on_update(cascade)]
+        author: TestAuthor,
+        #[on_delete(set_null)]
+        publisher: Option<TestPublisher>,
+    }
+}
+
+// String-backed enum columns
+schema! {
+    /// Where a story is in its lifecycle.
+    enum TestStoryStatus {
+        Draft,
+        InReview,
+        Published,
+    }
+
+    #[fixtures]
+    #[timestamps(none)]
+    TestStory {
+        title: String,
+        #[default(Draft)]
+        status: TestStoryStatus,
+        previous_status: Option<TestStoryStatus>,
+    }
+}
+
 #[test]
 fn test_user_model_compiles() {
     use test_user::Model;
@@ -122,3 +221,222 @@ fn test_entity_traits_implemented() {
     let _ = test_post::Entity::table_name(&test_post::Entity);
     let _ = test_comment::Entity::table_name(&test_comment::Entity);
 }
+
+#[test]
+fn test_keyword_fields_use_raw_idents() {
+    use test_order::{Column, Model};
+
+    let order = Model {
+        id: 1,
+        r#type: "subscription".to_string(),
+        r#ref: None,
+    };
+
+    assert_eq!(order.r#type, "subscription");
+    assert_eq!(Column::Type.as_str(), "type");
+    assert_eq!(Column::Ref.as_str(), "ref");
+}
+
+#[test]
+fn test_indexes_have_stable_names() {
+    use rapina::migration::prelude::SqliteQueryBuilder;
+
+    let user_indexes = test_user::indexes();
+    assert_eq!(user_indexes.len(), 1);
+    assert!(
+        user_indexes[0]
+            .to_string(SqliteQueryBuilder)
+            .contains("\"uq_test_users_email\"")
+    );
+
+    let order_indexes = test_order::indexes();
+    assert_eq!(order_indexes.len(), 1);
+    assert!(
+        order_indexes[0]
+            .to_string(SqliteQueryBuilder)
+            .contains("\"orders_ref_lookup\"")
+    );
+
+    assert!(test_post::indexes().is_empty());
+}
+
+#[test]
+fn test_column_defaults_in_create_table() {
+    use rapina::migration::prelude::SqliteQueryBuilder;
+    use rapina::sea_orm::{DbBackend, Schema};
+
+    let sql = Schema::new(DbBackend::Sqlite)
+        .create_table_from_entity(test_article::Entity)
+        .to_string(SqliteQueryBuilder);
+
+    assert!(sql.contains(r#""title" varchar NOT NULL,"#), "{}", sql);
+    assert!(
+        sql.contains(r#""status" varchar NOT NULL DEFAULT 'draft'"#),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.contains(r#""views" integer NOT NULL DEFAULT 0"#),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.contains(r#""rank" bigint NOT NULL DEFAULT -1"#),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.contains(r#""score" double NOT NULL DEFAULT 2.5"#),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.contains(r#""pinned" boolean NOT NULL DEFAULT FALSE"#),
+        "{}",
+        sql
+    );
+    assert!(sql.contains("DEFAULT CURRENT_TIMESTAMP"), "{}", sql);
+}
+
+#[test]
+fn test_referential_actions_in_create_table() {
+    use rapina::migration::prelude::SqliteQueryBuilder;
+    use rapina::sea_orm::{DbBackend, Schema};
+
+    let sql = Schema::new(DbBackend::Sqlite)
+        .create_table_from_entity(test_book::Entity)
+        .to_string(SqliteQueryBuilder);
+
+    assert!(
+        sql.contains(
+            r#"FOREIGN KEY ("author_id") REFERENCES "test_authors" ("id") ON DELETE CASCADE ON UPDATE CASCADE"#
+        ),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.contains(
+            r#"FOREIGN KEY ("publisher_id") REFERENCES "test_publishers" ("id") ON DELETE SET NULL"#
+        ),
+        "{}",
+        sql
+    );
+}
+
+#[test]
+fn test_doc_comments_become_schema_descriptions() {
+    let schema = serde_json::to_value(rapina::schemars::schema_for!(test_order::Model)).unwrap();
+
+    assert_eq!(schema["description"], "A customer order.");
+    assert_eq!(
+        schema["properties"]["type"]["description"],
+        "Fulfilment type, e.g. \"pickup\" or \"delivery\"."
+    );
+}
+
+#[test]
+fn test_model_fixture_builder() {
+    let order = test_order::Model::fixture()
+        .r#type("delivery")
+        .r#ref(Some("A-1".to_string()))
+        .build();
+
+    assert_eq!(order.id, 0);
+    assert_eq!(order.r#type, "delivery");
+    assert_eq!(order.r#ref.as_deref(), Some("A-1"));
+
+    let defaults = test_order::Model::fixture().build();
+    assert_eq!(defaults.r#type, "");
+    assert_eq!(defaults.r#ref, None);
+}
+
+#[test]
+fn test_active_model_fixture_sets_every_field() {
+    use rapina::sea_orm::ActiveValue;
+
+    let order = test_order::ActiveModel::fixture()
+        .id(7)
+        .r#type("pickup")
+        .build();
+
+    assert_eq!(order.id, ActiveValue::Set(7));
+    assert_eq!(order.r#type, ActiveValue::Set("pickup".to_string()));
+    assert_eq!(order.r#ref, ActiveValue::Set(None));
+}
+
+#[test]
+fn test_many_to_many_find_related() {
+    use rapina::sea_orm::{DbBackend, QueryTrait};
+
+    let member = test_member::Model {
+        id: 7,
+        name: "Ada".to_string(),
+    };
+    let sql = member
+        .find_related(test_group::Entity)
+        .build(DbBackend::Postgres)
+        .to_string();
+    assert!(
+        sql.contains(
+            r#"INNER JOIN "test_memberships" ON "test_memberships"."group_id" = "test_groups"."id""#
+        ),
+        "{}",
+        sql
+    );
+    assert!(sql.contains(r#"WHERE "test_members"."id" = 7"#), "{}", sql);
+
+    let group = test_group::Model {
+        id: 3,
+        title: "admins".to_string(),
+    };
+    let sql = group
+        .find_related(test_member::Entity)
+        .build(DbBackend::Postgres)
+        .to_string();
+    assert!(sql.contains(r#"WHERE "test_groups"."id" = 3"#), "{}", sql);
+}
+
+#[test]
+fn test_enum_columns() {
+    use rapina::migration::prelude::SqliteQueryBuilder;
+    use rapina::sea_orm::{ActiveEnum, DbBackend, Schema};
+
+    let story = test_story::Model {
+        id: 1,
+        title: "Launch".to_string(),
+        status: TestStoryStatus::InReview,
+        previous_status: Some(TestStoryStatus::Draft),
+    };
+    assert_eq!(story.status.to_value(), "in_review");
+
+    let json = serde_json::to_value(&story).unwrap();
+    assert_eq!(json["status"], "in_review");
+    assert_eq!(json["previous_status"], "draft");
+    let back: test_story::Model = serde_json::from_value(json).unwrap();
+    assert_eq!(back, story);
+
+    let sql = Schema::new(DbBackend::Sqlite)
+        .create_table_from_entity(test_story::Entity)
+        .to_string(SqliteQueryBuilder);
+    assert!(
+        sql.contains(r#""status" varchar NOT NULL DEFAULT 'draft'"#),
+        "{}",
+        sql
+    );
+    assert!(sql.contains(r#""previous_status" varchar )"#), "{}", sql);
+
+    let defaults = test_story::Model::fixture().build();
+    assert_eq!(defaults.status, TestStoryStatus::Draft);
+    assert_eq!(defaults.previous_status, None);
+}
+
+#[test]
+fn test_enum_json_schema_lists_variants() {
+    let schema = serde_json::to_value(rapina::schemars::schema_for!(TestStoryStatus)).unwrap();
+
+    assert_eq!(schema["description"], "Where a story is in its lifecycle.");
+    assert_eq!(
+        schema["enum"],
+        serde_json::json!(["draft", "in_review", "published"])
+    );
+}
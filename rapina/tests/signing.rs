@@ -0,0 +1,69 @@
+//! Integration tests for signed URLs.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::signing::{UrlSigner, VerifiedSignature};
+use rapina::testing::TestClient;
+use std::time::Duration;
+
+#[get("/files/:id")]
+async fn download(id: Path<u64>, _sig: VerifiedSignature) -> String {
+    format!("file {}", id.into_inner())
+}
+
+async fn client_with(signer: UrlSigner) -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(signer)
+        .router(Router::new().get("/files/:id", download));
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_valid_signature() {
+    let signer = UrlSigner::new("secret");
+    let url = signer.sign_path("/files/123", Duration::from_secs(60));
+    let client = client_with(signer).await;
+
+    let response = client.get(&url).send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "file 123");
+}
+
+#[tokio::test]
+async fn test_expired_signature() {
+    let signer = UrlSigner::new("secret");
+    let url = signer.sign_path_until(
+        "/files/123",
+        rapina::chrono::Utc::now() - rapina::chrono::Duration::minutes(5),
+    );
+    let client = client_with(signer).await;
+
+    let response = client.get(&url).send().await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_tampered_path() {
+    let signer = UrlSigner::new("secret");
+    let url = signer.sign_path("/files/123", Duration::from_secs(60));
+    let client = client_with(signer).await;
+
+    let response = client
+        .get(&url.replace("/files/123", "/files/456"))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = client.get("/files/123").send().await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_rotated_key() {
+    let url = UrlSigner::new("old-secret").sign_path("/files/123", Duration::from_secs(60));
+    let client = client_with(UrlSigner::new("new-secret").with_previous_secret("old-secret")).await;
+
+    let response = client.get(&url).send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
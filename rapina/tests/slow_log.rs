@@ -0,0 +1,141 @@
+//! Integration tests for slow request and slow query logging.
+
+mod common;
+
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+use common::Logs;
+
+#[get("/slow")]
+async fn slow() -> &'static str {
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    "done"
+}
+
+#[get("/fast")]
+async fn fast() -> &'static str {
+    "done"
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .slow_request_threshold(Duration::from_millis(25))
+        .router(Router::new().get("/slow", slow).get("/fast", fast))
+}
+
+#[tokio::test]
+async fn test_slow_request_logs_warning() {
+    let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/slow").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let output = logs.contents();
+    assert!(output.contains("WARN"), "{}", output);
+    assert!(output.contains("slow request"), "{}", output);
+    assert!(output.contains("path=/slow"), "{}", output);
+    assert!(output.contains("status=200"), "{}", output);
+    assert!(output.contains("threshold_ms=25"), "{}", output);
+}
+
+#[tokio::test]
+async fn test_fast_request_is_not_logged() {
+    let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/fast").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert!(!logs.contents().contains("slow request"));
+}
+
+#[cfg(feature = "sqlite")]
+mod queries {
+    use super::*;
+    use rapina::database::{DatabaseConfig, Db, DbError};
+    use rapina::sea_orm::{ConnectionTrait, Statement};
+
+    const SLOW_QUERY: &str = "WITH RECURSIVE counter(n) AS (
+        SELECT 1 UNION ALL SELECT n + 1 FROM counter WHERE n < 300000
+    ) SELECT count(*) AS total FROM counter";
+
+    #[get("/report")]
+    async fn report(db: Db) -> Result<String> {
+        let conn = db.read();
+        conn.query_one(Statement::from_string(
+            conn.get_database_backend(),
+            "SELECT 1",
+        ))
+        .await
+        .map_err(DbError)?;
+        let row = conn
+            .query_one(Statement::from_string(
+                conn.get_database_backend(),
+                SLOW_QUERY,
+            ))
+            .await
+            .map_err(DbError)?
+            .unwrap();
+        let total: i64 = row.try_get("", "total").map_err(DbError)?;
+        Ok(total.to_string())
+    }
+
+    async fn client(query_threshold: Duration) -> TestClient {
+        let app = Rapina::new()
+            .with_introspection(false)
+            .slow_request_threshold(Duration::ZERO)
+            .slow_query_threshold(query_threshold)
+            .with_database(DatabaseConfig::new("sqlite::memory:"))
+            .await
+            .unwrap()
+            .router(Router::new().get("/report", report));
+        TestClient::new(app).await
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_lists_slowest_queries() {
+        let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+        let client = client(Duration::from_secs(60)).await;
+
+        let response = client.get("/report").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text(), "300000");
+
+        let output = logs.contents();
+        let line = output
+            .lines()
+            .find(|line| line.contains("slow request"))
+            .unwrap_or_else(|| panic!("no slow request log in {}", output));
+
+        // Slowest first, whitespace collapsed, one entry per query
+        let recursive = line
+            .find("WITH RECURSIVE counter(n) AS ( SELECT 1")
+            .unwrap();
+        let select_one = line.find("ms SELECT 1").unwrap();
+        assert!(recursive < select_one, "{}", line);
+        assert!(!output.contains("slow query"), "{}", output);
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_logs_warning() {
+        let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+        let client = client(Duration::from_millis(1)).await;
+
+        let response = client.get("/report").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let output = logs.contents();
+        let line = output
+            .lines()
+            .find(|line| line.contains("slow query") && line.contains("WITH RECURSIVE"))
+            .unwrap_or_else(|| panic!("no slow query log in {}", output));
+        assert!(line.contains("WITH RECURSIVE counter(n)"), "{}", line);
+        assert!(line.contains("threshold_ms=1"), "{}", line);
+    }
+}
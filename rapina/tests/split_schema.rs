@@ -0,0 +1,84 @@
+//! Integration tests for `schema!` blocks split across files, which
+//! reference each other's entities with `extern entity`.
+
+#![cfg(feature = "sqlite")]
+
+use rapina::sea_orm::{
+    ActiveModelTrait, ConnectionTrait, Database, DatabaseConnection, EntityTrait, ModelTrait,
+    Schema, Set,
+};
+
+#[path = "split_schema/entities/mod.rs"]
+mod entities;
+
+// Re-exports the blocks the way `src/entity.rs` does for a project using
+// `rapina add resource --split-entity`
+mod entity {
+    pub use crate::entities::author::*;
+    pub use crate::entities::book::*;
+}
+
+use entity::{Author, Book};
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let backend = conn.get_database_backend();
+    let schema = Schema::new(backend);
+    conn.execute(backend.build(&schema.create_table_from_entity(Author)))
+        .await
+        .unwrap();
+    conn.execute(backend.build(&schema.create_table_from_entity(Book)))
+        .await
+        .unwrap();
+    conn
+}
+
+#[test]
+fn test_belongs_to_extern_entity_uses_its_id_type() {
+    let author_id = rapina::uuid::Uuid::new_v4();
+    let book = entity::book::Model {
+        id: 1,
+        title: "Dune".to_string(),
+        author_id,
+        created_at: Default::default(),
+        updated_at: Default::default(),
+    };
+
+    assert_eq!(book.author_id, author_id);
+    let _ = entity::book::Relation::Author;
+    let _ = entity::author::Relation::Books;
+}
+
+#[tokio::test]
+async fn test_relations_resolve_across_files() {
+    let conn = setup_db().await;
+
+    let author = entity::author::ActiveModel {
+        name: Set("Frank Herbert".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    entity::book::ActiveModel {
+        title: Set("Dune".to_string()),
+        author_id: Set(author.id),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+
+    let books = author.find_related(Book).all(&conn).await.unwrap();
+    assert_eq!(books.len(), 1);
+    assert_eq!(books[0].title, "Dune");
+
+    let (book, written_by) = Book::find()
+        .find_also_related(Author)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(book.author_id, author.id);
+    assert_eq!(written_by.unwrap().name, "Frank Herbert");
+}
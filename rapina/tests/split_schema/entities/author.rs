@@ -0,0 +1,11 @@
+use rapina::prelude::*;
+
+schema! {
+    extern entity Book;
+
+    #[id(Uuid)]
+    Author {
+        name: String,
+        books: Vec<Book>,
+    }
+}
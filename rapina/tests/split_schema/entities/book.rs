@@ -0,0 +1,11 @@
+use rapina::prelude::*;
+
+schema! {
+    #[id(Uuid)]
+    extern entity Author;
+
+    Book {
+        title: String,
+        author: Author,
+    }
+}
@@ -0,0 +1,172 @@
+//! Integration tests for the `sql!` macro.
+
+#![cfg(feature = "sqlite")]
+
+use http::StatusCode;
+use rapina::database::{Db, sql};
+use rapina::prelude::*;
+use rapina::sea_orm::{Database, DatabaseConnection, DbBackend, FromQueryResult, Value};
+use rapina::testing::TestClient;
+
+#[derive(Debug, PartialEq, FromQueryResult, Serialize, Deserialize, JsonSchema)]
+struct AuthorStats {
+    author: String,
+    posts: i64,
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    sql!("CREATE TABLE posts (id INTEGER PRIMARY KEY, author TEXT NOT NULL, status TEXT NOT NULL)")
+        .execute(&conn)
+        .await
+        .unwrap();
+    for (author, status) in [
+        ("ada", "published"),
+        ("ada", "published"),
+        ("ada", "draft"),
+        ("grace", "published"),
+    ] {
+        sql!(
+            "INSERT INTO posts (author, status) VALUES (?, ?)",
+            author,
+            status
+        )
+        .execute(&conn)
+        .await
+        .unwrap();
+    }
+    conn
+}
+
+#[derive(Deserialize)]
+struct ReportParams {
+    status: String,
+}
+
+#[get("/reports/authors")]
+async fn author_report(db: Db, params: Query<ReportParams>) -> Result<Json<Vec<AuthorStats>>> {
+    let stats = sql!(
+        "SELECT author, count(*) AS posts FROM posts
+         WHERE status = :status GROUP BY author ORDER BY posts DESC, author",
+        status = params.into_inner().status,
+    )
+    .all(db.read())
+    .await?;
+    Ok(Json(stats))
+}
+
+#[tokio::test]
+async fn test_select_rows() {
+    let conn = setup_db().await;
+
+    let rows: Vec<AuthorStats> = sql!(
+        "SELECT author, count(*) AS posts FROM posts WHERE status = ? GROUP BY author ORDER BY author",
+        "published"
+    )
+    .all(&conn)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        rows,
+        [
+            AuthorStats {
+                author: "ada".to_string(),
+                posts: 2
+            },
+            AuthorStats {
+                author: "grace".to_string(),
+                posts: 1
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_named_parameters_can_repeat() {
+    let conn = setup_db().await;
+    let name = "grace".to_string();
+
+    let row: Option<AuthorStats> = sql!(
+        "SELECT author, count(*) AS posts FROM posts
+         WHERE author = :name OR (author = :name AND status = 'draft') GROUP BY author",
+        name = name.clone(),
+    )
+    .one(&conn)
+    .await
+    .unwrap();
+
+    assert_eq!(row.unwrap().posts, 1);
+}
+
+#[tokio::test]
+async fn test_values_are_bound_not_spliced() {
+    let conn = setup_db().await;
+    let hostile = "ada' OR '1'='1";
+
+    let rows: Vec<AuthorStats> = sql!(
+        "SELECT author, count(*) AS posts FROM posts WHERE author = ? GROUP BY author",
+        hostile
+    )
+    .all(&conn)
+    .await
+    .unwrap();
+
+    assert!(rows.is_empty());
+}
+
+#[tokio::test]
+async fn test_execute_returns_rows_affected() {
+    let conn = setup_db().await;
+
+    let result = sql!("DELETE FROM posts WHERE status = :status", status = "draft")
+        .execute(&conn)
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows_affected(), 1);
+}
+
+#[test]
+fn test_placeholders_follow_backend() {
+    let query = sql!(
+        "SELECT * FROM posts WHERE author = :name OR editor = :name AND id > :id",
+        name = "ada",
+        id = 3,
+    );
+
+    assert_eq!(
+        query.sql(DbBackend::Postgres),
+        "SELECT * FROM posts WHERE author = $1 OR editor = $1 AND id > $2"
+    );
+    assert_eq!(query.values(DbBackend::Postgres).len(), 2);
+
+    assert_eq!(
+        query.sql(DbBackend::Sqlite),
+        "SELECT * FROM posts WHERE author = ? OR editor = ? AND id > ?"
+    );
+    assert_eq!(
+        query.values(DbBackend::Sqlite),
+        [Value::from("ada"), Value::from("ada"), Value::from(3)]
+    );
+}
+
+#[tokio::test]
+async fn test_report_endpoint() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(setup_db().await)
+        .router(Router::new().get("/reports/authors", author_report));
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .get("/reports/authors")
+        .query(&[("status", "published")])
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let stats: Vec<AuthorStats> = response.json();
+    assert_eq!(stats[0].author, "ada");
+    assert_eq!(stats[0].posts, 2);
+}
@@ -0,0 +1,180 @@
+//! Integration tests for the `States` extractor and optional `State`.
+
+mod common;
+
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+use common::Logs;
+
+#[derive(Clone)]
+struct Payments(&'static str);
+
+#[derive(Clone)]
+struct Inventory(u32);
+
+#[derive(Clone)]
+struct Mailer;
+
+#[get("/checkout")]
+async fn checkout(states: States<(Payments, Inventory)>) -> String {
+    let (payments, inventory) = states.into_inner();
+    format!("{} {}", payments.0, inventory.0)
+}
+
+#[get("/notify")]
+async fn notify(states: States<(Payments, Mailer, Inventory)>) -> &'static str {
+    let _ = states;
+    "sent"
+}
+
+#[get("/receipt")]
+async fn receipt(mailer: State<Mailer>) -> &'static str {
+    let _ = mailer;
+    "sent"
+}
+
+#[get("/mail")]
+async fn mail(mailer: State<Option<Mailer>>, payments: State<Option<Payments>>) -> String {
+    format!(
+        "mailer={} payments={}",
+        mailer.into_inner().is_some(),
+        payments.into_inner().map(|p| p.0).unwrap_or("none")
+    )
+}
+
+async fn client() -> TestClient {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(Payments("stripe"))
+        .state(Inventory(3))
+        .router(
+            Router::new()
+                .get("/checkout", checkout)
+                .get("/notify", notify)
+                .get("/mail", mail),
+        );
+    TestClient::new(app).await
+}
+
+#[tokio::test]
+async fn test_states_resolves_every_type() {
+    let client = client().await;
+
+    let response = client.get("/checkout").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "stripe 3");
+}
+
+#[tokio::test]
+async fn test_states_reports_missing_type() {
+    let client = client().await;
+
+    let response = client.get("/notify").send().await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(
+        message.starts_with("State not registered for type '"),
+        "{}",
+        message
+    );
+    assert!(message.contains("Mailer"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_states_lists_every_missing_type() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(Inventory(3))
+        .router(Router::new().get("/notify", notify));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/notify").send().await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(
+        message.starts_with("State not registered for types '"),
+        "{}",
+        message
+    );
+    assert!(message.contains("Payments"), "{}", message);
+    assert!(message.contains("Mailer"), "{}", message);
+    assert!(!message.contains("Inventory"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_optional_state_is_none_when_missing() {
+    let client = client().await;
+
+    let response = client.get("/mail").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "mailer=false payments=stripe");
+}
+
+#[tokio::test]
+async fn test_missing_state_names_type_and_logs_error() {
+    let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/receipt", receipt));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/receipt").send().await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(message.contains("::Mailer'"), "{}", message);
+
+    let output = logs.contents();
+    assert!(output.contains("ERROR"), "{}", output);
+    assert!(output.contains("State not registered"), "{}", output);
+    assert!(output.contains("Mailer"), "{}", output);
+}
+
+#[tokio::test]
+async fn test_listen_warns_about_unregistered_state() {
+    let (logs, _guard) = Logs::capture(tracing::Level::WARN);
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(Payments("stripe"))
+        .state(Inventory(3))
+        .router(
+            Router::new()
+                .get("/checkout", checkout)
+                .get("/notify", notify)
+                .get("/receipt", receipt)
+                .get("/mail", mail),
+        );
+
+    let server = tokio::spawn(app.listen("127.0.0.1:0"));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.abort();
+
+    let output = logs.contents();
+    let warnings: Vec<&str> = output
+        .lines()
+        .filter(|line| line.contains("never registered"))
+        .collect();
+    assert_eq!(warnings.len(), 2, "{}", output);
+    assert!(warnings[0].contains("WARN"), "{}", output);
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("Handler 'notify' (GET /notify)") && w.contains("Mailer")),
+        "{}",
+        output
+    );
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("Handler 'receipt' (GET /receipt)") && w.contains("Mailer")),
+        "{}",
+        output
+    );
+    assert!(!output.contains("Payments'"), "{}", output);
+}
@@ -0,0 +1,136 @@
+//! Integration tests for streamed responses.
+
+use std::time::Duration;
+
+use http::StatusCode;
+use rapina::bytes::Bytes;
+use rapina::middleware::{CacheConfig, CompressionConfig};
+use rapina::prelude::*;
+use rapina::response::{Event, Sse, StreamBody};
+use rapina::testing::TestClient;
+
+#[get("/progress")]
+async fn progress() -> Sse {
+    let events = futures_util::stream::iter((1..=3).map(|step| {
+        Event::new(format!("{}/3", step))
+            .event("progress")
+            .id(step.to_string())
+    }));
+    Sse::new(events)
+}
+
+#[get("/download")]
+async fn download() -> StreamBody {
+    let chunks = futures_util::stream::iter(
+        ["first,", "second,", "third"].map(|chunk| Ok::<_, std::io::Error>(Bytes::from(chunk))),
+    );
+    StreamBody::new(chunks)
+}
+
+#[get("/broken")]
+async fn broken() -> StreamBody {
+    use futures_util::StreamExt;
+
+    let chunks = futures_util::stream::iter([
+        Ok(Bytes::from("partial")),
+        Err(std::io::Error::other("disk went away")),
+    ])
+    .then(|chunk| async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        chunk
+    });
+    StreamBody::new(chunks)
+}
+
+fn app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/progress", progress)
+            .get("/download", download)
+            .get("/broken", broken),
+    )
+}
+
+#[tokio::test]
+async fn test_sse_sends_events() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/progress").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    assert_eq!(response.headers()["cache-control"], "no-cache");
+    assert_eq!(
+        response.text(),
+        "event: progress\nid: 1\ndata: 1/3\n\n\
+         event: progress\nid: 2\ndata: 2/3\n\n\
+         event: progress\nid: 3\ndata: 3/3\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_stream_body_sends_chunks() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/download").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/octet-stream"
+    );
+    assert!(response.headers().get("content-length").is_none());
+    assert_eq!(response.text(), "first,second,third");
+}
+
+#[tokio::test]
+async fn test_stream_body_error_truncates_response() {
+    let client = TestClient::new(app()).await;
+
+    let raw = raw_get(client.addr(), "/broken").await;
+
+    assert!(raw.starts_with("HTTP/1.1 200 OK"));
+    assert!(raw.contains("partial"));
+    assert!(
+        !raw.ends_with("0\r\n\r\n"),
+        "a failed stream must not look complete"
+    );
+}
+
+#[tokio::test]
+async fn test_streams_skip_compression_and_cache() {
+    let app = app()
+        .with_compression(CompressionConfig::new(0, 6))
+        .with_cache(CacheConfig::new(Duration::from_secs(60)));
+    let client = TestClient::new(app).await;
+
+    for _ in 0..2 {
+        let response = client
+            .get("/progress")
+            .header("accept-encoding", "gzip")
+            .send()
+            .await;
+
+        assert!(response.headers().get("content-encoding").is_none());
+        assert!(response.text().starts_with("event: progress\nid: 1\n"));
+    }
+}
+
+/// Sends a GET over a fresh connection and reads until the server closes it.
+async fn raw_get(addr: std::net::SocketAddr, path: &str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut buf = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut buf))
+        .await
+        .expect("the server should close the connection")
+        .ok();
+    String::from_utf8_lossy(&buf).to_string()
+}
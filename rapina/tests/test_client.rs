@@ -0,0 +1,152 @@
+//! Integration tests for the `TestClient` request builder and response helpers.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CreateUser {
+    name: String,
+    email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+struct UserResponse {
+    id: i32,
+    name: String,
+    email: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListParams {
+    page: u32,
+    tag: Option<String>,
+}
+
+#[post("/users")]
+async fn create_user(body: Json<CreateUser>) -> (StatusCode, Json<UserResponse>) {
+    let body = body.into_inner();
+    (
+        StatusCode::CREATED,
+        Json(UserResponse {
+            id: 1,
+            name: body.name,
+            email: body.email,
+        }),
+    )
+}
+
+#[get("/users")]
+async fn list_users(params: Query<ListParams>) -> String {
+    let params = params.into_inner();
+    format!(
+        "page={} tag={}",
+        params.page,
+        params.tag.unwrap_or_default()
+    )
+}
+
+#[post("/echo")]
+async fn echo(headers: Headers, body: String) -> String {
+    let content_type = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("none");
+    format!("{} {}", content_type, body)
+}
+
+fn app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .post("/users", create_user)
+            .get("/users", list_users)
+            .post("/echo", echo),
+    )
+}
+
+#[tokio::test]
+async fn test_json_handler_end_to_end() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .post("/users")
+        .header("x-request-id", "abc")
+        .json(&serde_json::json!({ "name": "Ada", "email": "ada@example.com" }))
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()["content-type"], "application/json");
+    assert_eq!(
+        response.json::<UserResponse>(),
+        UserResponse {
+            id: 1,
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_json_body_is_bad_request() {
+    let client = TestClient::new(app()).await;
+
+    let response = client
+        .post("/users")
+        .header("content-type", "application/json")
+        .body("{\"name\": \"Ada\",")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post("/users")
+        .json(&serde_json::json!({ "name": "Ada" }))
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_json_content_type_can_be_overridden() {
+    let client = TestClient::new(app()).await;
+
+    let before = client
+        .post("/echo")
+        .header("content-type", "application/merge-patch+json")
+        .json(&serde_json::json!({ "a": 1 }))
+        .send()
+        .await;
+    assert_eq!(before.text(), r#"application/merge-patch+json {"a":1}"#);
+
+    let after = client
+        .post("/echo")
+        .json(&serde_json::json!({ "a": 1 }))
+        .header("content-type", "text/plain")
+        .send()
+        .await;
+    assert_eq!(after.text(), r#"text/plain {"a":1}"#);
+}
+
+#[tokio::test]
+async fn test_raw_body_bytes() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.post("/echo").body(vec![b'h', b'i']).send().await;
+    assert_eq!(response.bytes().as_ref(), b"none hi");
+}
+
+#[tokio::test]
+async fn test_query_params() {
+    let client = TestClient::new(app()).await;
+
+    let response = client.get("/users").query(&[("page", "2")]).send().await;
+    assert_eq!(response.text(), "page=2 tag=");
+
+    let response = client
+        .get("/users?page=3")
+        .query(&[("tag", "rust & web")])
+        .send()
+        .await;
+    assert_eq!(response.text(), "page=3 tag=rust & web");
+}
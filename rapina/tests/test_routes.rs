@@ -0,0 +1,90 @@
+//! Integration tests for test-only routes.
+
+use bytes::Bytes;
+use http::StatusCode;
+use http_body_util::Full;
+use rapina::middleware::{BodyLimitMiddleware, DefaultHeadersMiddleware};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[get("/health")]
+async fn health() -> &'static str {
+    "ok"
+}
+
+#[post("/__test/reset")]
+async fn reset() -> &'static str {
+    "reset"
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .router(Router::new().get("/health", health))
+        .test_routes(Router::new().post("/__test/reset", reset))
+}
+
+#[tokio::test]
+async fn test_routes_served_by_test_client() {
+    let client = TestClient::new(app().with_introspection(false)).await;
+
+    let response = client.post("/__test/reset").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "reset");
+}
+
+#[tokio::test]
+async fn test_routes_run_their_router_middleware() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/health", health))
+        .test_routes(
+            Router::new()
+                .middleware(DefaultHeadersMiddleware::new().header("x-test-route", "1"))
+                .middleware(BodyLimitMiddleware::new(8))
+                .post("/__test/reset", reset),
+        );
+    let client = TestClient::new(app).await;
+
+    let response = client.post("/__test/reset").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("x-test-route").unwrap(), "1");
+
+    let response = client
+        .post("/__test/reset")
+        .body("more than eight bytes")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let response = client.get("/health").send().await;
+    assert!(response.headers().get("x-test-route").is_none());
+}
+
+#[tokio::test]
+async fn test_routes_hidden_from_introspection() {
+    let client = TestClient::new(app().with_introspection(true)).await;
+
+    let response = client.get("/__rapina/routes").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let routes: Vec<serde_json::Value> = response.json();
+    assert!(routes.iter().any(|route| route["path"] == "/health"));
+    assert!(routes.iter().all(|route| route["path"] != "/__test/reset"));
+}
+
+#[tokio::test]
+async fn test_routes_not_found_without_flag() {
+    assert!(std::env::var(rapina::app::TEST_ROUTES_ENV).is_err());
+    let service = app().with_introspection(false).into_service().unwrap();
+
+    let request = http::Request::post("/__test/reset")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = service.handle(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let request = http::Request::get("/health")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = service.handle(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
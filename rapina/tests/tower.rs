@@ -0,0 +1,180 @@
+//! Integration tests for tower compatibility.
+
+#![cfg(feature = "tower")]
+
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use http::StatusCode;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use rapina::middleware::{TRACE_ID_HEADER, TowerLayerMiddleware, TraceIdMiddleware};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+use tokio::sync::Semaphore;
+use tower::Service;
+use tower::limit::ConcurrencyLimitLayer;
+use tower_http::compression::CompressionLayer;
+use tower_http::trace::TraceLayer;
+
+fn long_text() -> String {
+    "rapina ".repeat(200)
+}
+
+fn router() -> Router {
+    Router::new()
+        .route(http::Method::GET, "/text", |_, _, _| async { long_text() })
+        .route(
+            http::Method::POST,
+            "/echo",
+            |req, params, state| async move {
+                use rapina::extract::FromRequest;
+                String::from_request(req, &params, &state).await
+            },
+        )
+}
+
+#[tokio::test]
+async fn test_trace_layer_sees_every_request() {
+    let seen = Arc::new(AtomicUsize::new(0));
+    let counter = seen.clone();
+    let trace = TraceLayer::new_for_http().on_request(
+        move |_req: &http::Request<Incoming>, _span: &tracing::Span| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        },
+    );
+
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TraceIdMiddleware::new())
+        .middleware(TowerLayerMiddleware::new(trace))
+        .router(router());
+
+    let client = TestClient::new(app).await;
+    let response = client.post("/echo").body("hello").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "hello");
+    assert!(response.headers().get(TRACE_ID_HEADER).is_some());
+
+    client.get("/text").send().await;
+    assert_eq!(seen.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_compression_layer_compresses_response() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TowerLayerMiddleware::new(TraceLayer::new_for_http()))
+        .middleware(TowerLayerMiddleware::new(CompressionLayer::new()))
+        .router(router());
+
+    let client = TestClient::new(app).await;
+    let response = client
+        .get("/text")
+        .header("accept-encoding", "gzip")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-encoding").unwrap(), "gzip");
+
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(response.bytes().as_ref())
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, long_text());
+}
+
+#[tokio::test]
+async fn test_compression_layer_respects_accept_encoding() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TowerLayerMiddleware::new(CompressionLayer::new()))
+        .router(router());
+
+    let client = TestClient::new(app).await;
+    let response = client.get("/text").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("content-encoding").is_none());
+    assert_eq!(response.text(), long_text());
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_layer_queues_requests() {
+    let gate = Arc::new(Semaphore::new(0));
+    let entered = Arc::new(Semaphore::new(0));
+    let (handler_gate, handler_entered) = (gate.clone(), entered.clone());
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TowerLayerMiddleware::new(ConcurrencyLimitLayer::new(1)))
+        .router(
+            Router::new().route(http::Method::GET, "/slow", move |_, _, _| {
+                let gate = handler_gate.clone();
+                let entered = handler_entered.clone();
+                async move {
+                    entered.add_permits(1);
+                    gate.acquire().await.unwrap().forget();
+                    "done"
+                }
+            }),
+        );
+    let client = TestClient::new(app).await;
+
+    let first = client.get("/slow").send();
+    let second = client.get("/slow").send();
+    let release = async {
+        entered.acquire().await.unwrap().forget();
+        // The second request waits for the first one's permit
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(entered.available_permits(), 0);
+        gate.add_permits(2);
+    };
+    let (first, second, ()) = tokio::join!(first, second, release);
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(entered.available_permits(), 1);
+}
+
+#[tokio::test]
+async fn test_app_as_tower_service() {
+    let mut service = Rapina::new()
+        .with_introspection(false)
+        .middleware(TraceIdMiddleware::new())
+        .router(router())
+        .into_service()
+        .unwrap();
+
+    std::future::poll_fn(|cx| Service::<http::Request<Full<Bytes>>>::poll_ready(&mut service, cx))
+        .await
+        .unwrap();
+    let request = http::Request::post("/echo")
+        .body(Full::new(Bytes::from_static(b"from tower")))
+        .unwrap();
+    let response = service.call(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(TRACE_ID_HEADER).is_some());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body.as_ref(), b"from tower");
+}
+
+#[tokio::test]
+async fn test_app_as_tower_service_unknown_route() {
+    let mut service = Rapina::new()
+        .with_introspection(false)
+        .router(router())
+        .into_service()
+        .unwrap();
+
+    let request = http::Request::get("/missing")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = service.call(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,16 @@
+//! Compile-time checks of the macros, run as one trybuild build.
+//!
+//! Cases under `tests/ui/database` and `tests/ui/fixtures` need those
+//! features and only run when they are enabled.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    if cfg!(feature = "database") {
+        t.compile_fail("tests/ui/database/*.rs");
+    }
+    if cfg!(feature = "fixtures") {
+        t.pass("tests/ui/fixtures/*.rs");
+    }
+}
@@ -0,0 +1,16 @@
+use rapina::prelude::*;
+use rapina::sea_orm::DbErr;
+
+schema! {
+    #[timestamps(none)]
+    #[before_save(normalize_email)]
+    User {
+        email: String,
+    }
+}
+
+async fn normalize_email(user: user::ActiveModel) -> std::result::Result<user::ActiveModel, DbErr> {
+    Ok(user)
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use rapina::prelude::*;
+use rapina::sea_orm::DbErr;
+
+schema! {
+    #[timestamps(none)]
+    #[after_delete(forget)]
+    User {
+        email: String,
+    }
+}
+
+async fn forget(user: user::Model) -> std::result::Result<user::Model, DbErr> {
+    Ok(user)
+}
+
+fn main() {}
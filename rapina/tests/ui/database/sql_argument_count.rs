@@ -0,0 +1,6 @@
+use rapina::database::sql;
+
+fn main() {
+    let author = "ada";
+    let _ = sql!("SELECT * FROM posts WHERE author = ? AND status = ?", author);
+}
@@ -0,0 +1,5 @@
+use rapina::database::sql;
+
+fn main() {
+    let _ = sql!("SELECT count(* FROM posts WHERE author = 'ada'");
+}
@@ -0,0 +1,5 @@
+use rapina::database::sql;
+
+fn main() {
+    let _ = sql!("SELECT * FROM posts WHERE author = :author", author = "ada", status = "draft");
+}
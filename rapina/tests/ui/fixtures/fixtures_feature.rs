@@ -0,0 +1,21 @@
+use rapina::prelude::*;
+use rapina::sea_orm::ActiveValue;
+
+schema! {
+    #[fixtures]
+    #[timestamps(none)]
+    User {
+        email: String,
+        name: Option<String>,
+    }
+}
+
+// Built without `cfg(test)`, like the library an integration test uses
+fn main() {
+    let user = user::Model::fixture().email("ana@example.com").build();
+    assert_eq!(user.email, "ana@example.com");
+    assert_eq!(user.name, None);
+
+    let active = user::ActiveModel::fixture().id(7).build();
+    assert_eq!(active.id, ActiveValue::Set(7));
+}
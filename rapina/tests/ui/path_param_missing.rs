@@ -0,0 +1,8 @@
+use rapina::prelude::*;
+
+#[get("/users/:id")]
+async fn get_user(#[path_param("user_id")] id: Path<u64>) -> String {
+    id.into_inner().to_string()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use rapina::prelude::*;
+
+#[get("/users/:user_id/posts/:post_id")]
+async fn get_post(ids: Path<(u64, u64, u64)>) -> String {
+    format!("{:?}", ids.into_inner())
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use rapina::prelude::*;
+
+#[get("/users/:user_id/posts/:post_id")]
+async fn get_post(user_id: Path<u64>) -> String {
+    user_id.into_inner().to_string()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use rapina::prelude::*;
+
+#[post("/upload")]
+async fn upload(req: rapina::extract::RawRequest, body: Json<String>) -> StatusCode {
+    StatusCode::OK
+}
+
+fn main() {}
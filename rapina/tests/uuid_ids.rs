@@ -0,0 +1,220 @@
+//! Integration tests for `schema!` entities with `#[id(Uuid)]`.
+
+#![cfg(feature = "sqlite")]
+
+use http::StatusCode;
+use rapina::database::{Db, DbError};
+use rapina::events::{Event, Events};
+use rapina::prelude::*;
+use rapina::sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, Database, DatabaseConnection, EntityTrait,
+    QueryFilter, Schema, Set, TransactionTrait,
+};
+use rapina::testing::TestClient;
+use rapina::uuid::Uuid;
+
+schema! {
+    #[id(Uuid)]
+    Account {
+        email: String,
+    }
+
+    #[timestamps(none)]
+    Note {
+        owner: Account,
+        body: String,
+    }
+}
+
+// Item handlers as `rapina add resource` generates them for a Uuid key
+
+#[get("/accounts/:id")]
+async fn get_account(db: Db, id: Path<Uuid>) -> Result<Json<account::Model>> {
+    let id = id.into_inner();
+    let item = Account::find_by_id(id)
+        .one(db.read())
+        .await
+        .map_err(DbError)?
+        .ok_or_else(|| Error::not_found(format!("Account {} not found", id)))?;
+    Ok(Json(item))
+}
+
+#[delete("/accounts/:id")]
+async fn delete_account(db: Db, events: Events, id: Path<Uuid>) -> Result<Json<serde_json::Value>> {
+    let id = id.into_inner();
+    let txn = db.write().begin().await.map_err(DbError)?;
+    let result = Account::delete_by_id(id)
+        .exec(&txn)
+        .await
+        .map_err(DbError)?;
+    if result.rows_affected == 0 {
+        return Err(Error::not_found(format!("Account {} not found", id)));
+    }
+    let event = Event::deleted("account", id);
+    events.before_commit(&txn, &event).await?;
+    txn.commit().await.map_err(DbError)?;
+    events.after_commit(event).await;
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+async fn setup_db() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    let backend = conn.get_database_backend();
+    let schema = Schema::new(backend);
+    conn.execute(backend.build(&schema.create_table_from_entity(Account)))
+        .await
+        .unwrap();
+    conn.execute(backend.build(&schema.create_table_from_entity(Note)))
+        .await
+        .unwrap();
+    conn
+}
+
+#[tokio::test]
+async fn test_insert_generates_uuid_id() {
+    let conn = setup_db().await;
+
+    let first = account::ActiveModel {
+        email: Set("ada@example.com".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    let second = account::ActiveModel {
+        email: Set("grace@example.com".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+
+    assert!(!first.id.is_nil());
+    assert_eq!(first.id.get_version_num(), 4);
+    assert_ne!(first.id, second.id);
+
+    let stored = Account::find_by_id(first.id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored, first);
+}
+
+#[tokio::test]
+async fn test_explicit_uuid_id_is_kept() {
+    let conn = setup_db().await;
+    let id = Uuid::new_v4();
+
+    let account = account::ActiveModel {
+        id: Set(id),
+        email: Set("ada@example.com".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    assert_eq!(account.id, id);
+
+    // A nil id counts as unset
+    let account = account::ActiveModel {
+        id: Set(Uuid::nil()),
+        email: Set("grace@example.com".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    assert!(!account.id.is_nil());
+}
+
+#[tokio::test]
+async fn test_belongs_to_uuid_entity() {
+    let conn = setup_db().await;
+
+    let owner = account::ActiveModel {
+        email: Set("ada@example.com".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    let note = note::ActiveModel {
+        owner_id: Set(owner.id),
+        body: Set("hello".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+
+    let found = Note::find()
+        .filter(note::Column::OwnerId.eq(owner.id))
+        .find_also_related(Account)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.0, note);
+    assert_eq!(found.1, Some(owner));
+}
+
+async fn client_with_account() -> (TestClient, Uuid) {
+    let conn = setup_db().await;
+    let account = account::ActiveModel {
+        email: Set("ada@example.com".to_string()),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    let app = Rapina::new()
+        .with_introspection(false)
+        .state(conn)
+        .state(Events::default())
+        .openapi("Accounts", "1.0.0")
+        .router(
+            Router::new()
+                .get("/accounts/:id", get_account)
+                .delete("/accounts/:id", delete_account),
+        );
+    (TestClient::new(app).await, account.id)
+}
+
+#[tokio::test]
+async fn test_scaffolded_handlers_take_uuid_ids() {
+    let (client, id) = client_with_account().await;
+
+    let response = client.get(&format!("/accounts/{}", id)).send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["id"], id.to_string());
+
+    let response = client.delete(&format!("/accounts/{}", id)).send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["deleted"], id.to_string());
+
+    let response = client.get(&format!("/accounts/{}", id)).send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json();
+    assert_eq!(
+        body["error"]["message"],
+        format!("Account {} not found", id)
+    );
+
+    let response = client.get("/accounts/42").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_openapi_documents_uuid_path_parameter() {
+    let (client, _) = client_with_account().await;
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+
+    let param = &spec["paths"]["/accounts/{id}"]["get"]["parameters"][0];
+    assert_eq!(param["name"], "id");
+    assert_eq!(param["schema"]["type"], "string");
+    assert_eq!(param["schema"]["format"], "uuid");
+}